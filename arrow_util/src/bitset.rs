@@ -163,6 +163,15 @@ impl BitSet {
         self.buffer[byte_idx] |= 1 << bit_idx;
     }
 
+    /// Unsets a given bit
+    pub fn unset(&mut self, idx: usize) {
+        assert!(idx <= self.len);
+
+        let byte_idx = idx >> 3;
+        let bit_idx = idx & 7;
+        self.buffer[byte_idx] &= !(1 << bit_idx);
+    }
+
     /// Returns if the given index is set
     pub fn get(&self, idx: usize) -> bool {
         assert!(idx <= self.len);
@@ -513,6 +522,19 @@ mod tests {
         v.set(5);
     }
 
+    #[test]
+    fn test_bitset_unset() {
+        let mut v = BitSet::new();
+        v.append_set(10);
+
+        v.unset(1);
+        v.unset(9);
+        v.unset(9);
+
+        let set: Vec<_> = (0..10).filter(|i| v.get(*i)).collect();
+        assert_eq!(set, [0, 2, 3, 4, 5, 6, 7, 8]);
+    }
+
     #[test]
     fn test_all_set_unset() {
         for i in 1..100 {
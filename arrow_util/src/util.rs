@@ -1,10 +1,11 @@
 //! Utility functions for working with arrow
 
+use std::collections::HashSet;
 use std::iter::FromIterator;
 use std::sync::Arc;
 
 use arrow::{
-    array::{new_null_array, ArrayRef, StringArray},
+    array::{new_null_array, ArrayData, ArrayRef, StringArray},
     datatypes::SchemaRef,
     error::ArrowError,
     record_batch::RecordBatch,
@@ -55,3 +56,91 @@ pub fn ensure_schema(
 
     RecordBatch::try_new(Arc::clone(output_schema), batch_output_columns)
 }
+
+/// Returns the approximate memory size of the buffers referenced by
+/// `batches`, counting buffers that are shared between arrays only once.
+///
+/// Unlike [`Array::get_array_memory_size()`], which accounts for the full
+/// dictionary of every dictionary-encoded array, this reflects the memory
+/// actually retained when dictionary values are shared between arrays (for
+/// example, batches projected from the same snapshot).
+///
+/// [`Array::get_array_memory_size()`]: arrow::array::Array::get_array_memory_size
+pub fn record_batches_memory_size<'a, I>(batches: I) -> usize
+where
+    I: IntoIterator<Item = &'a RecordBatch>,
+{
+    let mut seen = HashSet::new();
+    batches
+        .into_iter()
+        .flat_map(|batch| batch.columns())
+        .map(|array| array_data_size(&array.to_data(), &mut seen))
+        .sum()
+}
+
+fn array_data_size(data: &ArrayData, seen: &mut HashSet<usize>) -> usize {
+    let buffers = data
+        .buffers()
+        .iter()
+        .chain(data.nulls().map(|v| v.buffer()))
+        .filter(|buffer| seen.insert(buffer.as_ptr() as usize))
+        .map(|buffer| buffer.capacity())
+        .sum::<usize>();
+
+    buffers
+        + data
+            .child_data()
+            .iter()
+            .map(|child| array_data_size(child, seen))
+            .sum::<usize>()
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow::{
+        array::{Array, DictionaryArray, Int32Array},
+        datatypes::Int32Type,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_memory_size_shared_dictionary() {
+        let values: ArrayRef = Arc::new(StringArray::from_iter_values(
+            (0..1000).map(|v| format!("value-{v}")),
+        ));
+
+        let a = DictionaryArray::<Int32Type>::try_new(
+            Int32Array::from(vec![0, 1, 2]),
+            Arc::clone(&values),
+        )
+        .unwrap();
+        let b = DictionaryArray::<Int32Type>::try_new(
+            Int32Array::from(vec![3, 4, 5]),
+            Arc::clone(&values),
+        )
+        .unwrap();
+
+        let batch_a = RecordBatch::try_from_iter([("t", Arc::new(a) as ArrayRef)]).unwrap();
+        let batch_b = RecordBatch::try_from_iter([("t", Arc::new(b) as ArrayRef)]).unwrap();
+
+        let naive =
+            batch_a.column(0).get_array_memory_size() + batch_b.column(0).get_array_memory_size();
+        let got = record_batches_memory_size([&batch_a, &batch_b]);
+
+        // The dictionary values are accounted for once.
+        let values_size =
+            record_batches_memory_size([
+                &RecordBatch::try_from_iter([("v", Arc::clone(&values))]).unwrap()
+            ]);
+        assert!(got < naive);
+        assert!(got >= values_size);
+        assert!(got < values_size * 2);
+
+        // Accounting a batch twice does not double count it.
+        assert_eq!(
+            record_batches_memory_size([&batch_a, &batch_a]),
+            record_batches_memory_size([&batch_a])
+        );
+    }
+}
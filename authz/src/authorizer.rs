@@ -3,7 +3,7 @@ use std::ops::ControlFlow;
 use async_trait::async_trait;
 use backoff::{Backoff, BackoffConfig};
 
-use super::{Error, Permission};
+use super::{Error, Permission, RowFilter};
 
 /// An authorizer is used to validate a request
 /// (+ associated permissions needed to fulfill the request)
@@ -35,6 +35,22 @@ pub trait Authorizer: std::fmt::Debug + Send + Sync {
         perms: &[Permission],
     ) -> Result<Vec<Permission>, Error>;
 
+    /// Determine the row filters restricting the rows of `namespace` the
+    /// request token may read and write.
+    ///
+    /// Row filters are additional to, and do not replace, the check of the
+    /// token's [permissions](Authorizer::permissions()). Tokens without any
+    /// filters may access all rows of the namespace, which is the case for
+    /// every token of authorizers that do not support row filters, as with the
+    /// default implementation.
+    async fn row_filters(
+        &self,
+        _token: Option<Vec<u8>>,
+        _namespace: &str,
+    ) -> Result<Vec<RowFilter>, Error> {
+        Ok(vec![])
+    }
+
     /// Make a test request that determines if end-to-end communication
     /// with the service is working.
     ///
@@ -74,6 +90,17 @@ impl<T: Authorizer> Authorizer for Option<T> {
             None => Ok(perms.to_vec()),
         }
     }
+
+    async fn row_filters(
+        &self,
+        token: Option<Vec<u8>>,
+        namespace: &str,
+    ) -> Result<Vec<RowFilter>, Error> {
+        match self {
+            Some(authz) => authz.row_filters(token, namespace).await,
+            None => Ok(vec![]),
+        }
+    }
 }
 
 #[async_trait]
@@ -85,4 +112,12 @@ impl<T: AsRef<dyn Authorizer> + std::fmt::Debug + Send + Sync> Authorizer for T
     ) -> Result<Vec<Permission>, Error> {
         self.as_ref().permissions(token, perms).await
     }
+
+    async fn row_filters(
+        &self,
+        token: Option<Vec<u8>>,
+        namespace: &str,
+    ) -> Result<Vec<RowFilter>, Error> {
+        self.as_ref().row_filters(token, namespace).await
+    }
 }
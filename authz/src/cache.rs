@@ -0,0 +1,280 @@
+use std::{collections::HashMap, time::Duration};
+
+use async_trait::async_trait;
+use iox_time::{SystemProvider, Time, TimeProvider};
+use metric::{Registry, U64Counter};
+use parking_lot::Mutex;
+
+use super::{hash_token, Authorizer, Error, Permission, RowFilter, TokenHash};
+
+const AUTHZ_CACHE_METRIC: &str = "authz_cache_requests";
+
+/// The maximum number of cached results before expired entries are evicted.
+const DEFAULT_MAX_ENTRIES: usize = 10_000;
+
+/// A definitive response from the inner [`Authorizer`] that is safe to cache.
+#[derive(Debug, Clone)]
+enum CachedResult {
+    Granted(Vec<Permission>),
+    Forbidden,
+    InvalidToken,
+}
+
+impl From<CachedResult> for Result<Vec<Permission>, Error> {
+    fn from(value: CachedResult) -> Self {
+        match value {
+            CachedResult::Granted(v) => Ok(v),
+            CachedResult::Forbidden => Err(Error::Forbidden),
+            CachedResult::InvalidToken => Err(Error::InvalidToken),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct CacheEntry {
+    result: CachedResult,
+    expires_at: Time,
+}
+
+/// A caching decorator over an [`Authorizer`] implementation.
+///
+/// Permission check results are cached for `ttl`, keyed by the SHA-256 hash of
+/// the request token (the token itself is never retained) and the requested
+/// permissions. Only definitive results (granted, forbidden and invalid token)
+/// are cached - verification errors are always returned from the inner
+/// [`Authorizer`] and retried on the next request.
+///
+/// Revoking a token therefore takes up to `ttl` to take effect. A zero `ttl`
+/// disables caching.
+#[derive(Debug)]
+pub struct CachingAuthorizer<T, P = SystemProvider> {
+    inner: T,
+    ttl: Duration,
+    max_entries: usize,
+    time_provider: P,
+
+    cache: Mutex<HashMap<(TokenHash, Vec<Permission>), CacheEntry>>,
+
+    hit: U64Counter,
+    miss: U64Counter,
+}
+
+impl<T> CachingAuthorizer<T> {
+    /// Cache the results of `inner` for `ttl`.
+    pub fn new(inner: T, ttl: Duration, registry: &Registry) -> Self {
+        Self::new_with_time_provider(inner, ttl, registry, SystemProvider::default())
+    }
+}
+
+impl<T, P> CachingAuthorizer<T, P> {
+    fn new_with_time_provider(
+        inner: T,
+        ttl: Duration,
+        registry: &Registry,
+        time_provider: P,
+    ) -> Self {
+        let metric = registry.register_metric::<U64Counter>(
+            AUTHZ_CACHE_METRIC,
+            "number of authz permission checks, by cache result",
+        );
+
+        Self {
+            inner,
+            ttl,
+            max_entries: DEFAULT_MAX_ENTRIES,
+            time_provider,
+            cache: Default::default(),
+            hit: metric.recorder(&[("result", "hit")]),
+            miss: metric.recorder(&[("result", "miss")]),
+        }
+    }
+}
+
+#[async_trait]
+impl<T, P> Authorizer for CachingAuthorizer<T, P>
+where
+    T: Authorizer,
+    P: TimeProvider,
+{
+    async fn permissions(
+        &self,
+        token: Option<Vec<u8>>,
+        perms: &[Permission],
+    ) -> Result<Vec<Permission>, Error> {
+        let hash = match &token {
+            Some(t) if !self.ttl.is_zero() => hash_token(t),
+            _ => return self.inner.permissions(token, perms).await,
+        };
+        let key = (hash, perms.to_vec());
+
+        let now = self.time_provider.now();
+        if let Some(entry) = self.cache.lock().get(&key) {
+            if entry.expires_at > now {
+                self.hit.inc(1);
+                return entry.result.clone().into();
+            }
+        }
+        self.miss.inc(1);
+
+        let res = self.inner.permissions(token, perms).await;
+
+        let result = match &res {
+            Ok(v) => CachedResult::Granted(v.clone()),
+            Err(Error::Forbidden) => CachedResult::Forbidden,
+            Err(Error::InvalidToken) => CachedResult::InvalidToken,
+            Err(Error::Verification { .. } | Error::NoToken) => return res,
+        };
+
+        let mut cache = self.cache.lock();
+        if cache.len() >= self.max_entries {
+            cache.retain(|_, v| v.expires_at > now);
+            if cache.len() >= self.max_entries {
+                cache.clear();
+            }
+        }
+        cache.insert(
+            key,
+            CacheEntry {
+                result,
+                expires_at: now + self.ttl,
+            },
+        );
+
+        res
+    }
+
+    /// Row filters are not cached, and are always returned from the inner
+    /// [`Authorizer`].
+    async fn row_filters(
+        &self,
+        token: Option<Vec<u8>>,
+        namespace: &str,
+    ) -> Result<Vec<RowFilter>, Error> {
+        self.inner.row_filters(token, namespace).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    use assert_matches::assert_matches;
+    use iox_time::MockProvider;
+
+    use super::*;
+    use crate::{Action, Resource};
+
+    const TTL: Duration = Duration::from_secs(10);
+
+    /// An authorizer granting all permissions to the "GOOD" token, rejecting
+    /// "BAD" and failing verification of all other tokens.
+    #[derive(Debug, Default)]
+    struct CountingAuthorizer {
+        calls: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl Authorizer for CountingAuthorizer {
+        async fn permissions(
+            &self,
+            token: Option<Vec<u8>>,
+            perms: &[Permission],
+        ) -> Result<Vec<Permission>, Error> {
+            self.calls.fetch_add(1, Ordering::Relaxed);
+            match token.as_deref() {
+                Some(b"GOOD") => Ok(perms.to_vec()),
+                Some(b"BAD") => Err(Error::Forbidden),
+                Some(_) => Err(Error::verification("test", "test error")),
+                None => Err(Error::NoToken),
+            }
+        }
+    }
+
+    fn perms(namespace: &str) -> Vec<Permission> {
+        vec![Permission::ResourceAction(
+            Resource::Database(namespace.to_string()),
+            Action::Write,
+        )]
+    }
+
+    #[tokio::test]
+    async fn test_cache() {
+        let registry = Registry::default();
+        let time_provider = Arc::new(MockProvider::new(Time::from_timestamp_nanos(0)));
+        let authz = CachingAuthorizer::new_with_time_provider(
+            CountingAuthorizer::default(),
+            TTL,
+            &registry,
+            Arc::clone(&time_provider),
+        );
+
+        let bananas = perms("bananas");
+
+        // The first request is a miss, the second a hit.
+        for _ in 0..2 {
+            let got = authz
+                .permissions(Some(b"GOOD".to_vec()), &bananas)
+                .await
+                .expect("authorised");
+            assert_eq!(got, bananas);
+        }
+        assert_eq!(authz.inner.calls.load(Ordering::Relaxed), 1);
+
+        // Different permissions are a different cache entry.
+        authz
+            .permissions(Some(b"GOOD".to_vec()), &perms("platanos"))
+            .await
+            .expect("authorised");
+        assert_eq!(authz.inner.calls.load(Ordering::Relaxed), 2);
+
+        // Forbidden responses are cached.
+        for _ in 0..2 {
+            assert_matches!(
+                authz.permissions(Some(b"BAD".to_vec()), &bananas).await,
+                Err(Error::Forbidden)
+            );
+        }
+        assert_eq!(authz.inner.calls.load(Ordering::Relaxed), 3);
+
+        // Verification errors and missing tokens are not.
+        for _ in 0..2 {
+            assert_matches!(
+                authz.permissions(Some(b"UGLY".to_vec()), &bananas).await,
+                Err(Error::Verification { .. })
+            );
+            assert_matches!(authz.permissions(None, &bananas).await, Err(Error::NoToken));
+        }
+        assert_eq!(authz.inner.calls.load(Ordering::Relaxed), 7);
+
+        // Once the TTL elapses, the inner authorizer is consulted again.
+        time_provider.inc(TTL);
+        authz
+            .permissions(Some(b"GOOD".to_vec()), &bananas)
+            .await
+            .expect("authorised");
+        assert_eq!(authz.inner.calls.load(Ordering::Relaxed), 8);
+
+        assert_eq!(authz.hit.fetch(), 2);
+        assert_eq!(authz.miss.fetch(), 6);
+    }
+
+    #[tokio::test]
+    async fn test_zero_ttl_disables_cache() {
+        let authz = CachingAuthorizer::new(
+            CountingAuthorizer::default(),
+            Duration::ZERO,
+            &Registry::default(),
+        );
+
+        for _ in 0..2 {
+            authz
+                .permissions(Some(b"GOOD".to_vec()), &perms("bananas"))
+                .await
+                .expect("authorised");
+        }
+        assert_eq!(authz.inner.calls.load(Ordering::Relaxed), 2);
+    }
+}
@@ -2,7 +2,7 @@ use async_trait::async_trait;
 use iox_time::{SystemProvider, TimeProvider};
 use metric::{DurationHistogram, Metric, Registry};
 
-use super::{Authorizer, Error, Permission};
+use super::{Authorizer, Error, Permission, RowFilter};
 
 const AUTHZ_DURATION_METRIC: &str = "authz_permission_check_duration";
 
@@ -74,6 +74,14 @@ where
 
         res
     }
+
+    async fn row_filters(
+        &self,
+        token: Option<Vec<u8>>,
+        namespace: &str,
+    ) -> Result<Vec<RowFilter>, Error> {
+        self.inner.row_filters(token, namespace).await
+    }
 }
 
 #[cfg(test)]
@@ -24,15 +24,22 @@ use workspace_hack as _;
 use base64::{prelude::BASE64_STANDARD, Engine};
 use generated_types::influxdata::iox::authz::v1::{self as proto};
 use observability_deps::tracing::warn;
+use sha2::{Digest, Sha256};
 
 mod authorizer;
 pub use authorizer::Authorizer;
+mod cache;
+pub use cache::CachingAuthorizer;
 mod iox_authorizer;
 pub use iox_authorizer::{Error, IoxAuthorizer};
 mod instrumentation;
 pub use instrumentation::AuthorizerInstrumentation;
 mod permission;
 pub use permission::{Action, Permission, Resource};
+mod row_filter;
+pub use row_filter::{RowFilter, RowFilterError};
+mod token_file;
+pub use token_file::{TokenFileAuthorizer, TokenFileError};
 
 #[cfg(feature = "http")]
 pub mod http;
@@ -57,6 +64,15 @@ pub fn extract_token<T: AsRef<[u8]> + ?Sized>(value: Option<&T>) -> Option<Vec<u
     }
 }
 
+/// The SHA-256 digest of a request token.
+type TokenHash = [u8; 32];
+
+/// Hash `token`, allowing it to be compared and retained without keeping the
+/// token itself in memory.
+fn hash_token(token: &[u8]) -> TokenHash {
+    Sha256::digest(token).into()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
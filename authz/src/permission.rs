@@ -2,7 +2,7 @@ use super::proto;
 use snafu::Snafu;
 
 /// Action is the type of operation being attempted on a resource.
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum Action {
     /// The create action is used when a new instance of the resource will
     /// be created.
@@ -61,7 +61,7 @@ pub struct IncompatiblePermissionError {}
 /// authorizer. Not all authorizers neccessarily support all forms of
 /// permission. If an authorizer doesn't support a permission then it
 /// is not an error, the permission will always be denied.
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub enum Permission {
     /// ResourceAction is a permission in the form of a reasource and an
     /// action.
@@ -113,7 +113,7 @@ impl TryFrom<Permission> for proto::Permission {
 }
 
 /// A resource is the object that a request is trying to access.
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub enum Resource {
     /// A database is a named IOx database.
     Database(String),
@@ -0,0 +1,128 @@
+use std::{fmt::Display, str::FromStr};
+
+use snafu::Snafu;
+
+/// Why a row filter expression might be invalid.
+#[derive(Debug, Clone, PartialEq, Eq, Snafu)]
+#[snafu(display("invalid row filter `{value}`: expected <tag> = '<value>'"))]
+pub struct RowFilterError {
+    value: String,
+}
+
+/// A predicate restricting the rows of a namespace a token may read and write
+/// to those with the tag `column` set to `value`, written as `tenant_id =
+/// 'x'`.
+///
+/// Row filters allow several tenants to share the tables of a namespace: the
+/// querier conjoins the filters of the request token to every table scan, and
+/// the router stamps them onto (or validates them against) every written line.
+///
+/// Single quotes within the value are escaped by doubling them, as in SQL.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RowFilter {
+    column: String,
+    value: String,
+}
+
+impl RowFilter {
+    /// Restrict rows to those with the tag `column` set to `value`.
+    pub fn new(column: impl Into<String>, value: impl Into<String>) -> Self {
+        Self {
+            column: column.into(),
+            value: value.into(),
+        }
+    }
+
+    /// The name of the filtered tag column.
+    pub fn column(&self) -> &str {
+        &self.column
+    }
+
+    /// The value the tag column must have.
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+}
+
+impl FromStr for RowFilter {
+    type Err = RowFilterError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || RowFilterError {
+            value: s.to_string(),
+        };
+
+        let (column, value) = s.split_once('=').ok_or_else(invalid)?;
+        let column = column.trim();
+        if column.is_empty() || column.contains(char::is_whitespace) {
+            return Err(invalid());
+        }
+
+        let value = value
+            .trim()
+            .strip_prefix('\'')
+            .and_then(|v| v.strip_suffix('\''))
+            .ok_or_else(invalid)?;
+        // Any quote within the value must be escaped by another.
+        if value.replace("''", "").contains('\'') {
+            return Err(invalid());
+        }
+
+        Ok(Self::new(column, value.replace("''", "'")))
+    }
+}
+
+impl Display for RowFilter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} = '{}'", self.column, self.value.replace('\'', "''"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        for (s, column, value) in [
+            ("tenant_id = 'x'", "tenant_id", "x"),
+            ("tenant_id='x'", "tenant_id", "x"),
+            ("  region =  'us west' ", "region", "us west"),
+            ("tenant_id = ''", "tenant_id", ""),
+            ("tenant_id = 'o''brien'", "tenant_id", "o'brien"),
+            ("tenant_id = 'a=b'", "tenant_id", "a=b"),
+        ] {
+            let got = s.parse::<RowFilter>().unwrap();
+            assert_eq!(got, RowFilter::new(column, value), "{s}");
+
+            // Display round-trips.
+            assert_eq!(got.to_string().parse::<RowFilter>().unwrap(), got);
+        }
+
+        for s in [
+            "",
+            "tenant_id",
+            "= 'x'",
+            "tenant id = 'x'",
+            "tenant_id = x",
+            "tenant_id = 'x",
+            "tenant_id = 'x'y'",
+        ] {
+            assert_eq!(
+                s.parse::<RowFilter>(),
+                Err(RowFilterError {
+                    value: s.to_string()
+                }),
+                "{s}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(
+            RowFilter::new("tenant_id", "o'brien").to_string(),
+            "tenant_id = 'o''brien'"
+        );
+    }
+}
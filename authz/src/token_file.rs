@@ -0,0 +1,497 @@
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, HashMap},
+    path::{Path, PathBuf},
+};
+
+use async_trait::async_trait;
+use metric::{Registry, U64Counter};
+use observability_deps::tracing::info;
+use serde::Deserialize;
+use snafu::{ResultExt, Snafu};
+
+use super::{
+    hash_token, Action, Authorizer, Error, Permission, Resource, RowFilter, RowFilterError,
+    TokenHash,
+};
+
+const TOKEN_REQUESTS_METRIC: &str = "authz_token_requests";
+
+/// Errors loading a token file.
+#[derive(Debug, Snafu)]
+pub enum TokenFileError {
+    /// The token file cannot be read.
+    #[snafu(display("failed to read token file {}: {source}", path.display()))]
+    Read {
+        /// The path of the token file.
+        path: PathBuf,
+        /// The underlying I/O error.
+        source: std::io::Error,
+    },
+
+    /// The token file is not a valid token definition document.
+    #[snafu(display("invalid token file {}: {source}", path.display()))]
+    Parse {
+        /// The path of the token file.
+        path: PathBuf,
+        /// The underlying decode error.
+        source: serde_json::Error,
+    },
+
+    /// A token hash is not a hex-encoded SHA-256 digest.
+    #[snafu(display("token {name} does not have a valid hex-encoded sha256 hash"))]
+    InvalidHash {
+        /// The name of the token.
+        name: String,
+    },
+
+    /// Two token definitions share the same hash.
+    #[snafu(display("tokens {a} and {b} have the same hash"))]
+    DuplicateToken {
+        /// The name of the first token.
+        a: String,
+        /// The name of the second token.
+        b: String,
+    },
+
+    /// A row filter of a token is not a valid row filter expression.
+    #[snafu(display("token {name} has an invalid row filter: {source}"))]
+    InvalidRowFilter {
+        /// The name of the token.
+        name: String,
+        /// The underlying parse error.
+        source: RowFilterError,
+    },
+}
+
+/// The on-disk token file format.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TokenFile {
+    tokens: Vec<TokenDefinition>,
+}
+
+/// A single token definition within a [`TokenFile`].
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TokenDefinition {
+    /// A human readable name for the token, used to attribute metrics.
+    name: String,
+
+    /// The hex-encoded SHA-256 hash of the token value - the token itself is
+    /// never stored.
+    sha256: String,
+
+    /// The set of namespaces this token grants access to, and the actions
+    /// allowed against each.
+    namespaces: BTreeMap<String, Vec<TokenAction>>,
+
+    /// The row filter expressions (such as `tenant_id = 'x'`) restricting the
+    /// rows of each namespace this token may read and write.
+    #[serde(default)]
+    row_filters: BTreeMap<String, Vec<String>>,
+}
+
+/// The actions a token may be granted on a namespace.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum TokenAction {
+    /// Grants [`Action::Read`] and [`Action::ReadSchema`].
+    Read,
+    /// Grants [`Action::Write`].
+    Write,
+}
+
+/// A loaded token definition.
+#[derive(Debug)]
+struct Token {
+    permissions: Vec<Permission>,
+    row_filters: HashMap<String, Vec<RowFilter>>,
+
+    authorised: U64Counter,
+    forbidden: U64Counter,
+}
+
+/// An [`Authorizer`] granting namespace-scoped permissions to a static set of
+/// tokens defined in a JSON file of the form:
+///
+/// ```json
+/// {
+///   "tokens": [
+///     {
+///       "name": "telegraf",
+///       "sha256": "<hex-encoded sha256 hash of the token>",
+///       "namespaces": { "company_sensors": ["read", "write"] },
+///       "row_filters": { "company_sensors": ["tenant_id = 'acme'"] }
+///     }
+///   ]
+/// }
+/// ```
+///
+/// Only the hash of each token is stored, both on disk and in memory.
+///
+/// The optional `row_filters` restrict the rows of a namespace a token may
+/// read and write, as described by [`RowFilter`].
+///
+/// Every permission check is counted in the `authz_token_requests` metric,
+/// faceted by token name and result, allowing per-token request rates to be
+/// observed.
+#[derive(Debug)]
+pub struct TokenFileAuthorizer {
+    tokens: HashMap<TokenHash, Token>,
+
+    /// Requests presenting a token that is not defined in the file.
+    invalid: U64Counter,
+}
+
+impl TokenFileAuthorizer {
+    /// Load the token definitions from the file at `path`.
+    pub fn load(path: impl AsRef<Path>, registry: &Registry) -> Result<Self, TokenFileError> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path).context(ReadSnafu { path })?;
+        Self::parse(&contents, path, registry)
+    }
+
+    fn parse(contents: &str, path: &Path, registry: &Registry) -> Result<Self, TokenFileError> {
+        let file: TokenFile = serde_json::from_str(contents).context(ParseSnafu { path })?;
+
+        let metric = registry.register_metric::<U64Counter>(
+            TOKEN_REQUESTS_METRIC,
+            "number of permission checks for each configured token",
+        );
+
+        let mut tokens = HashMap::with_capacity(file.tokens.len());
+        let mut names = HashMap::<TokenHash, String>::with_capacity(file.tokens.len());
+        for def in file.tokens {
+            let hash = decode_hash(&def.sha256).ok_or_else(|| TokenFileError::InvalidHash {
+                name: def.name.clone(),
+            })?;
+
+            if let Some(existing) = names.insert(hash, def.name.clone()) {
+                return Err(TokenFileError::DuplicateToken {
+                    a: existing,
+                    b: def.name,
+                });
+            }
+
+            let row_filters = def
+                .row_filters
+                .into_iter()
+                .map(|(namespace, filters)| {
+                    let filters = filters
+                        .iter()
+                        .map(|f| f.parse::<RowFilter>())
+                        .collect::<Result<Vec<_>, _>>()
+                        .context(InvalidRowFilterSnafu { name: &def.name })?;
+                    Ok((namespace, filters))
+                })
+                .collect::<Result<HashMap<_, _>, TokenFileError>>()?;
+
+            let permissions = def
+                .namespaces
+                .into_iter()
+                .flat_map(|(namespace, actions)| {
+                    actions
+                        .into_iter()
+                        .flat_map(|a| match a {
+                            TokenAction::Read => &[Action::Read, Action::ReadSchema][..],
+                            TokenAction::Write => &[Action::Write][..],
+                        })
+                        .map(move |a| {
+                            Permission::ResourceAction(Resource::Database(namespace.clone()), *a)
+                        })
+                        .collect::<Vec<_>>()
+                })
+                .collect();
+
+            tokens.insert(
+                hash,
+                Token {
+                    permissions,
+                    row_filters,
+                    authorised: metric.recorder([
+                        ("token", Cow::from(def.name.clone())),
+                        ("result", Cow::from("authorised")),
+                    ]),
+                    forbidden: metric.recorder([
+                        ("token", Cow::from(def.name)),
+                        ("result", Cow::from("forbidden")),
+                    ]),
+                },
+            );
+        }
+
+        info!(path=%path.display(), n_tokens=tokens.len(), "loaded authz token file");
+
+        Ok(Self {
+            tokens,
+            invalid: metric.recorder(&[("result", "invalid")]),
+        })
+    }
+}
+
+#[async_trait]
+impl Authorizer for TokenFileAuthorizer {
+    async fn permissions(
+        &self,
+        token: Option<Vec<u8>>,
+        perms: &[Permission],
+    ) -> Result<Vec<Permission>, Error> {
+        let hash = hash_token(&token.ok_or(Error::NoToken)?);
+
+        let Some(token) = self.tokens.get(&hash) else {
+            self.invalid.inc(1);
+            return Err(Error::InvalidToken);
+        };
+
+        let granted = perms
+            .iter()
+            .filter(|p| token.permissions.contains(p))
+            .cloned()
+            .collect::<Vec<_>>();
+
+        if granted.is_empty() {
+            token.forbidden.inc(1);
+            return Err(Error::Forbidden);
+        }
+
+        token.authorised.inc(1);
+        Ok(granted)
+    }
+
+    async fn row_filters(
+        &self,
+        token: Option<Vec<u8>>,
+        namespace: &str,
+    ) -> Result<Vec<RowFilter>, Error> {
+        let hash = hash_token(&token.ok_or(Error::NoToken)?);
+        let token = self.tokens.get(&hash).ok_or(Error::InvalidToken)?;
+
+        Ok(token
+            .row_filters
+            .get(namespace)
+            .cloned()
+            .unwrap_or_default())
+    }
+}
+
+/// Decode a hex-encoded SHA-256 digest.
+fn decode_hash(s: &str) -> Option<TokenHash> {
+    hex::decode(s).ok()?.try_into().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_matches::assert_matches;
+    use metric::{Attributes, Metric};
+
+    use super::*;
+
+    fn hex_hash(token: &str) -> String {
+        hex::encode(hash_token(token.as_bytes()))
+    }
+
+    fn authorizer(registry: &Registry) -> TokenFileAuthorizer {
+        let contents = format!(
+            r#"{{
+                "tokens": [
+                    {{
+                        "name": "writer",
+                        "sha256": "{}",
+                        "namespaces": {{ "bananas": ["write"] }}
+                    }},
+                    {{
+                        "name": "reader",
+                        "sha256": "{}",
+                        "namespaces": {{ "bananas": ["read"], "platanos": ["read", "write"] }}
+                    }},
+                    {{
+                        "name": "tenant",
+                        "sha256": "{}",
+                        "namespaces": {{ "bananas": ["read", "write"] }},
+                        "row_filters": {{ "bananas": ["tenant_id = 'x'", "region='eu'"] }}
+                    }}
+                ]
+            }}"#,
+            hex_hash("write-token"),
+            hex_hash("read-token"),
+            hex_hash("tenant-token"),
+        );
+
+        TokenFileAuthorizer::parse(&contents, Path::new("tokens.json"), registry)
+            .expect("valid token file")
+    }
+
+    fn perm(namespace: &str, action: Action) -> Permission {
+        Permission::ResourceAction(Resource::Database(namespace.to_string()), action)
+    }
+
+    fn count(registry: &Registry, attrs: impl Into<Attributes>) -> u64 {
+        registry
+            .get_instrument::<Metric<U64Counter>>(TOKEN_REQUESTS_METRIC)
+            .expect("metric registered")
+            .get_observer(&attrs.into())
+            .expect("observer exists")
+            .fetch()
+    }
+
+    #[tokio::test]
+    async fn test_permissions() {
+        let registry = Registry::default();
+        let authz = authorizer(&registry);
+
+        let write_bananas = [perm("bananas", Action::Write)];
+
+        let got = authz
+            .permissions(Some(b"write-token".to_vec()), &write_bananas)
+            .await
+            .expect("writer may write to bananas");
+        assert_eq!(got, write_bananas);
+
+        // The writer has no access to other namespaces.
+        assert_matches!(
+            authz
+                .permissions(
+                    Some(b"write-token".to_vec()),
+                    &[perm("platanos", Action::Write)]
+                )
+                .await,
+            Err(Error::Forbidden)
+        );
+
+        // The reader may not write to bananas, but may read it.
+        assert_matches!(
+            authz
+                .permissions(Some(b"read-token".to_vec()), &write_bananas)
+                .await,
+            Err(Error::Forbidden)
+        );
+        let got = authz
+            .permissions(
+                Some(b"read-token".to_vec()),
+                &[
+                    perm("bananas", Action::ReadSchema),
+                    perm("bananas", Action::Write),
+                ],
+            )
+            .await
+            .expect("reader may read bananas schema");
+        assert_eq!(got, [perm("bananas", Action::ReadSchema)]);
+
+        assert_matches!(
+            authz
+                .permissions(Some(b"bad-token".to_vec()), &write_bananas)
+                .await,
+            Err(Error::InvalidToken)
+        );
+        assert_matches!(
+            authz.permissions(None, &write_bananas).await,
+            Err(Error::NoToken)
+        );
+
+        assert_eq!(
+            count(&registry, &[("token", "writer"), ("result", "authorised")]),
+            1
+        );
+        assert_eq!(
+            count(&registry, &[("token", "writer"), ("result", "forbidden")]),
+            1
+        );
+        assert_eq!(
+            count(&registry, &[("token", "reader"), ("result", "authorised")]),
+            1
+        );
+        assert_eq!(
+            count(&registry, &[("token", "reader"), ("result", "forbidden")]),
+            1
+        );
+        assert_eq!(count(&registry, &[("result", "invalid")]), 1);
+    }
+
+    #[tokio::test]
+    async fn test_row_filters() {
+        let registry = Registry::default();
+        let authz = authorizer(&registry);
+
+        let got = authz
+            .row_filters(Some(b"tenant-token".to_vec()), "bananas")
+            .await
+            .expect("tenant token is valid");
+        assert_eq!(
+            got,
+            [
+                RowFilter::new("tenant_id", "x"),
+                RowFilter::new("region", "eu")
+            ]
+        );
+
+        // Tokens without row filters for a namespace may access all its rows.
+        for (token, namespace) in [("tenant-token", "platanos"), ("read-token", "bananas")] {
+            let got = authz
+                .row_filters(Some(token.as_bytes().to_vec()), namespace)
+                .await
+                .expect("token is valid");
+            assert!(got.is_empty(), "{token} {namespace}");
+        }
+
+        assert_matches!(
+            authz
+                .row_filters(Some(b"bad-token".to_vec()), "bananas")
+                .await,
+            Err(Error::InvalidToken)
+        );
+        assert_matches!(
+            authz.row_filters(None, "bananas").await,
+            Err(Error::NoToken)
+        );
+    }
+
+    #[test]
+    fn test_invalid_files() {
+        let registry = Registry::default();
+
+        let got = TokenFileAuthorizer::parse(
+            r#"{"tokens": [{"name": "a", "sha256": "bananas", "namespaces": {}}]}"#,
+            Path::new("tokens.json"),
+            &registry,
+        );
+        assert_matches!(got, Err(TokenFileError::InvalidHash { name }) if name == "a");
+
+        let hash = hex_hash("token");
+        let got = TokenFileAuthorizer::parse(
+            &format!(
+                r#"{{"tokens": [
+                    {{"name": "a", "sha256": "{hash}", "namespaces": {{}}}},
+                    {{"name": "b", "sha256": "{hash}", "namespaces": {{}}}}
+                ]}}"#
+            ),
+            Path::new("tokens.json"),
+            &registry,
+        );
+        assert_matches!(got, Err(TokenFileError::DuplicateToken { a, b }) => {
+            assert_eq!(a, "a");
+            assert_eq!(b, "b");
+        });
+
+        let got = TokenFileAuthorizer::parse(
+            r#"{"tokens": [{"name": "a", "sha256": "", "namespaces": {"ns": ["delete"]}}]}"#,
+            Path::new("tokens.json"),
+            &registry,
+        );
+        assert_matches!(got, Err(TokenFileError::Parse { .. }));
+
+        let got = TokenFileAuthorizer::parse(
+            &format!(
+                r#"{{"tokens": [{{
+                    "name": "a",
+                    "sha256": "{hash}",
+                    "namespaces": {{"ns": ["read"]}},
+                    "row_filters": {{"ns": ["tenant_id > 'x'"]}}
+                }}]}}"#
+            ),
+            Path::new("tokens.json"),
+            &registry,
+        );
+        assert_matches!(got, Err(TokenFileError::InvalidRowFilter { name, .. }) if name == "a");
+    }
+}
@@ -269,4 +269,47 @@ pub struct CompactorConfig {
         action
     )]
     pub max_partition_fetch_queries_per_second: Option<usize>,
+
+    /// A comma-separated list of tag columns for which bloom filters are
+    /// written into compacted parquet files.
+    ///
+    /// Queriers use the bloom filters to skip files that cannot contain the
+    /// value of an equality predicate on one of these tags. Best suited to
+    /// high cardinality tags, for which column statistics rarely prune files.
+    #[clap(
+        long = "compaction-bloom-filter-columns",
+        env = "INFLUXDB_IOX_COMPACTION_BLOOM_FILTER_COLUMNS",
+        value_delimiter = ','
+    )]
+    pub bloom_filter_columns: Vec<String>,
+
+    /// The false positive probability the bloom filters written for
+    /// `--compaction-bloom-filter-columns` are sized for.
+    #[clap(
+        long = "compaction-bloom-filter-fpp",
+        env = "INFLUXDB_IOX_COMPACTION_BLOOM_FILTER_FPP",
+        default_value = "0.01",
+        value_parser = parse_probability
+    )]
+    pub bloom_filter_fpp: f64,
+
+    /// The number of distinct values per row group the bloom filters written
+    /// for `--compaction-bloom-filter-columns` are sized for.
+    #[clap(
+        long = "compaction-bloom-filter-ndv",
+        env = "INFLUXDB_IOX_COMPACTION_BLOOM_FILTER_NDV",
+        default_value = "100000",
+        action
+    )]
+    pub bloom_filter_ndv: u64,
+}
+
+/// Parse a probability strictly between 0 and 1.
+fn parse_probability(input: &str) -> Result<f64, String> {
+    let v: f64 = input.parse().map_err(|e| format!("{e}"))?;
+    if v > 0.0 && v < 1.0 {
+        Ok(v)
+    } else {
+        Err(format!("{v} is not between 0 and 1 (exclusive)"))
+    }
 }
@@ -0,0 +1,20 @@
+//! CLI config for the handling of field values that cannot be stored as
+//! written.
+
+/// The handling of non-finite (NaN or infinite) float values, and of unsigned
+/// integer values exceeding [`i64::MAX`] written to integer columns.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum FieldValuePolicyConfig {
+    /// Store values as written.
+    #[default]
+    Disabled,
+
+    /// Reject writes containing such values.
+    Reject,
+
+    /// Replace values with the closest representable value, or NULL for NaN.
+    Clamp,
+
+    /// Replace values with NULL.
+    Null,
+}
@@ -82,4 +82,25 @@ pub struct GarbageCollectorConfig {
         env = "INFLUXDB_IOX_GC_RETENTION_SLEEP_INTERVAL_MINUTES"
     )]
     pub retention_sleep_interval_minutes: u64,
+
+    /// Query history entries recorded by the queriers that are older than this duration will be
+    /// deleted from the catalog.
+    /// Parsed with <https://docs.rs/humantime/latest/humantime/fn.parse_duration.html>
+    ///
+    /// If not specified, query history entries are not deleted.
+    #[clap(
+        long,
+        value_parser = parse_duration,
+        env = "INFLUXDB_IOX_GC_QUERY_HISTORY_CUTOFF"
+    )]
+    pub query_history_cutoff: Option<Duration>,
+
+    /// Number of minutes to sleep between iterations of the query history deletion loop.
+    /// Defaults to 30 minutes.
+    #[clap(
+        long,
+        default_value_t = 30,
+        env = "INFLUXDB_IOX_GC_QUERY_HISTORY_SLEEP_INTERVAL_MINUTES"
+    )]
+    pub query_history_sleep_interval_minutes: u64,
 }
@@ -1,8 +1,14 @@
 //! CLI config for the ingester using the RPC write path
 
-use std::{num::NonZeroUsize, path::PathBuf};
+use std::{
+    num::{NonZeroU64, NonZeroUsize},
+    path::PathBuf,
+};
 
-use crate::gossip::GossipConfig;
+use crate::{
+    field_value_policy::FieldValuePolicyConfig, gossip::GossipConfig,
+    persist_barrier::PersistBarrierGroupConfig, rollup::RollupConfig,
+};
 
 /// CLI config for the ingester using the RPC write path
 #[derive(Debug, Clone, clap::Parser)]
@@ -76,6 +82,21 @@ pub struct IngesterConfig {
     )]
     pub persist_hot_partition_cost: usize,
 
+    /// The number of executor slots shared between query requests and persist
+    /// compactions.
+    ///
+    /// When all slots are in use, query requests are prioritised over persist
+    /// compactions. Prioritisation only takes effect when this value is below
+    /// the sum of `--concurrent-query-limit` and `--persist-max-parallelism`
+    /// (25 by default), so that the two compete for slots.
+    #[clap(
+        long = "exec-priority-slots",
+        env = "INFLUXDB_IOX_EXEC_PRIORITY_SLOTS",
+        default_value = "16",
+        action
+    )]
+    pub exec_priority_slots: NonZeroUsize,
+
     /// Limit the number of partitions that may be buffered in a single
     /// namespace (across all tables) at any one time.
     ///
@@ -85,4 +106,333 @@ pub struct IngesterConfig {
         env = "INFLUXDB_IOX_MAX_PARTITIONS_PER_NAMESPACE"
     )]
     pub max_partitions_per_namespace: Option<NonZeroUsize>,
+
+    /// Reject writes to a namespace while it has at least this many bytes
+    /// buffered (and not yet persisting).
+    ///
+    /// The limit applies to each ingester independently: a namespace written
+    /// to N ingesters may buffer up to N times this many bytes in total.
+    ///
+    /// This limit is disabled by default.
+    #[clap(
+        long = "max-buffered-bytes-per-namespace",
+        env = "INFLUXDB_IOX_MAX_BUFFERED_BYTES_PER_NAMESPACE"
+    )]
+    pub max_buffered_bytes_per_namespace: Option<NonZeroUsize>,
+
+    /// Reject writes to a namespace that would take the number of rows
+    /// written to it within the current second above this limit.
+    ///
+    /// The limit applies to each ingester independently, counting only the
+    /// rows it has successfully buffered: a namespace written to N ingesters
+    /// may write up to N times this many rows per second in total.
+    ///
+    /// This limit is disabled by default.
+    #[clap(
+        long = "max-rows-per-second-per-namespace",
+        env = "INFLUXDB_IOX_MAX_ROWS_PER_SECOND_PER_NAMESPACE"
+    )]
+    pub max_rows_per_second_per_namespace: Option<NonZeroU64>,
+
+    /// Periodically validate the internal consistency of all buffered
+    /// partition data every specified number of seconds, logging and
+    /// counting any violations found.
+    ///
+    /// Intended for soak testing - disabled by default.
+    #[clap(
+        long = "buffer-invariant-check-interval-seconds",
+        env = "INFLUXDB_IOX_BUFFER_INVARIANT_CHECK_INTERVAL_SECONDS"
+    )]
+    pub buffer_invariant_check_interval_seconds: Option<u64>,
+
+    /// The interval, in seconds, between adding the rows and bytes accumulated
+    /// for each table to the hourly usage records in the catalog.
+    ///
+    /// Usage accumulated since the last flush is flushed during a graceful
+    /// shutdown, and lost if the ingester stops uncleanly.
+    #[clap(
+        long = "table-usage-flush-interval-seconds",
+        env = "INFLUXDB_IOX_TABLE_USAGE_FLUSH_INTERVAL_SECONDS",
+        default_value = "60",
+        value_parser = clap::value_parser!(u64).range(1..)
+    )]
+    pub table_usage_flush_interval_seconds: u64,
+
+    /// The maximum number of bytes of recently persisted (parquet encoded)
+    /// data to retain in memory after persistence, across all partitions.
+    ///
+    /// The most recently persisted data of each partition is retained up to
+    /// this limit and continues to be served to queriers, avoiding reading it
+    /// back from object storage. Setting this to 0 disables retention.
+    #[clap(
+        long = "recent-persisted-cache-bytes",
+        env = "INFLUXDB_IOX_RECENT_PERSISTED_CACHE_BYTES",
+        default_value = "0"
+    )]
+    pub recent_persisted_cache_bytes: usize,
+
+    /// The maximum number of bytes of memory the compaction of each persist
+    /// job may use, beyond which sorts spill to temporary files on disk.
+    ///
+    /// When unset, compactions share the memory pool of the persist executor
+    /// and fail if it is exhausted.
+    #[clap(
+        long = "persist-memory-limit-bytes",
+        env = "INFLUXDB_IOX_PERSIST_MEMORY_LIMIT_BYTES"
+    )]
+    pub persist_memory_limit_bytes: Option<usize>,
+
+    /// The number of consecutive failures to upload a persisted parquet file
+    /// or add it to the catalog after which persist attempts are paused.
+    ///
+    /// While paused, writes are rejected and a single persist attempt probes
+    /// for recovery at increasing intervals, resuming all persist jobs once
+    /// it succeeds.
+    #[clap(
+        long = "persist-circuit-breaker-threshold",
+        env = "INFLUXDB_IOX_PERSIST_CIRCUIT_BREAKER_THRESHOLD",
+        default_value = "10",
+        action
+    )]
+    pub persist_circuit_breaker_threshold: NonZeroUsize,
+
+    /// Skip unreadable entries in WAL segments during replay at startup.
+    ///
+    /// By default, the ingester refuses to start if a WAL segment (other than
+    /// the most recent, which may contain a truncated write) cannot be read in
+    /// full, reporting the segment and the last entry replayed from it.
+    /// Setting this flag accepts the loss of the unreadable entries and
+    /// continues replaying the remaining segments.
+    #[clap(
+        long = "wal-replay-accept-data-loss",
+        env = "INFLUXDB_IOX_WAL_REPLAY_ACCEPT_DATA_LOSS",
+        action
+    )]
+    pub wal_replay_accept_data_loss: bool,
+
+    /// Slow down WAL replay at startup while applying a replayed write takes
+    /// longer than this number of milliseconds.
+    ///
+    /// Applying a replayed write is slow when resolving its partitions in
+    /// the catalog is slow, and throttling the replay reduces the load the
+    /// replay of a large WAL places on the catalog. The delay between
+    /// replayed writes grows while writes are slow to apply, and shrinks
+    /// again once they are fast. Disabled by default.
+    #[clap(
+        long = "wal-replay-throttle-threshold-millis",
+        env = "INFLUXDB_IOX_WAL_REPLAY_THROTTLE_THRESHOLD_MILLIS",
+        action
+    )]
+    pub wal_replay_throttle_threshold_millis: Option<u64>,
+
+    /// Serve metadata-only query responses, omitting all buffered data, when
+    /// the buffer pressure reaches this percentage.
+    ///
+    /// Buffer pressure is the percentage of the persist queue occupied by
+    /// outstanding persist jobs, and is 100% whenever the ingester is not
+    /// accepting writes.
+    ///
+    /// Disabled by default.
+    #[clap(
+        long = "query-shed-degrade-percent",
+        env = "INFLUXDB_IOX_QUERY_SHED_DEGRADE_PERCENT",
+        value_parser = clap::value_parser!(u8).range(0..=100)
+    )]
+    pub query_shed_degrade_percent: Option<u8>,
+
+    /// Reject query requests when the buffer pressure reaches this
+    /// percentage.
+    ///
+    /// Disabled by default.
+    #[clap(
+        long = "query-shed-reject-percent",
+        env = "INFLUXDB_IOX_QUERY_SHED_REJECT_PERCENT",
+        value_parser = clap::value_parser!(u8).range(0..=100)
+    )]
+    pub query_shed_reject_percent: Option<u8>,
+
+    /// The number of seconds rejected query callers are advised to wait
+    /// before retrying.
+    #[clap(
+        long = "query-shed-retry-after-seconds",
+        env = "INFLUXDB_IOX_QUERY_SHED_RETRY_AFTER_SECONDS",
+        default_value = "5"
+    )]
+    pub query_shed_retry_after_seconds: u64,
+
+    /// The handling of non-finite (NaN or infinite) float field values in
+    /// writes.
+    ///
+    /// "reject" rejects writes containing such values, "clamp" replaces
+    /// infinite values with the closest representable value and NaN with
+    /// NULL, and "null" replaces them with NULL.
+    #[clap(
+        value_enum,
+        long = "field-value-policy",
+        env = "INFLUXDB_IOX_FIELD_VALUE_POLICY",
+        default_value = "disabled"
+    )]
+    pub field_value_policy: FieldValuePolicyConfig,
+
+    /// Seal partitions against further writes once their time range ended
+    /// more than this number of seconds ago.
+    ///
+    /// Writes to a sealed partition are rejected. Partitions whose key has no
+    /// time part are never sealed. Disabled by default.
+    #[clap(
+        long = "partition-seal-age-seconds",
+        env = "INFLUXDB_IOX_PARTITION_SEAL_AGE_SECONDS",
+        action
+    )]
+    pub partition_seal_age_seconds: Option<u64>,
+
+    /// Groups of tables whose partitions are persisted together, each
+    /// specified as `<namespace>:<table>,<table>[,...]`.
+    ///
+    /// Persisting a partition of a table in a group also persists the data
+    /// buffered in the partitions with the same partition key of the other
+    /// tables in the group, and their files become visible to queriers at the
+    /// same time. This is best-effort: data of a partition that is already
+    /// being persisted when the group is persisted becomes visible separately.
+    /// A group may not contain more tables than the `--persist-queue-depth`.
+    ///
+    /// Multiple groups are separated by `;` in the environment variable.
+    #[clap(
+        long = "persist-barrier-group",
+        env = "INFLUXDB_IOX_PERSIST_BARRIER_GROUPS",
+        value_delimiter = ';',
+        action = clap::ArgAction::Append
+    )]
+    pub persist_barrier_groups: Vec<PersistBarrierGroupConfig>,
+
+    /// Tables whose writes are rolled up into time buckets, each specified as
+    /// `<namespace>:<table>:<interval>` (such as `bananas:cpu:5m`).
+    ///
+    /// The count of the values of each field, and the sum, minimum and maximum
+    /// of the values of each numeric field, are maintained per tag set and
+    /// bucket in the `<table>_rollup_<interval>` table (such as
+    /// `cpu_rollup_5m`), created by the ingester. The interval must be a whole
+    /// number of seconds dividing a day.
+    ///
+    /// Each ingester rolls up the writes it receives on its own, and the
+    /// rollup rows of different ingesters overwrite each other. Only roll up
+    /// tables whose writes are all routed to this ingester, or replicated to
+    /// every ingester rolling them up.
+    ///
+    /// Multiple rollups are separated by `;` in the environment variable.
+    #[clap(
+        long = "rollup",
+        env = "INFLUXDB_IOX_ROLLUPS",
+        value_delimiter = ';',
+        action = clap::ArgAction::Append
+    )]
+    pub rollups: Vec<RollupConfig>,
+
+    /// The duration the rollup aggregates of a time bucket are kept in memory
+    /// for after the bucket ended, in seconds.
+    ///
+    /// Rows written to a bucket after its aggregates are evicted are not
+    /// rolled up.
+    #[clap(
+        long = "rollup-state-retention-seconds",
+        env = "INFLUXDB_IOX_ROLLUP_STATE_RETENTION_SECONDS",
+        default_value = "3600",
+        action
+    )]
+    pub rollup_state_retention_seconds: u64,
+
+    /// Persist the buffered data of a namespace once it has been neither
+    /// written to nor queried for this number of seconds.
+    ///
+    /// Releases the memory held by the data of idle namespaces sooner than
+    /// hot partition persistence or WAL rotation would. Disabled by default.
+    #[clap(
+        long = "idle-namespace-persist-seconds",
+        env = "INFLUXDB_IOX_IDLE_NAMESPACE_PERSIST_SECONDS",
+        value_parser = clap::value_parser!(u64).range(1..)
+    )]
+    pub idle_namespace_persist_seconds: Option<u64>,
+
+    /// Remove the columns containing only NULL values from persisted parquet
+    /// files.
+    ///
+    /// Tables with sparse schemas otherwise produce files with many empty
+    /// columns. The columns remain in the table schema, and are read as NULL
+    /// by queries.
+    #[clap(
+        long = "persist-drop-null-columns",
+        env = "INFLUXDB_IOX_PERSIST_DROP_NULL_COLUMNS",
+        action
+    )]
+    pub persist_drop_null_columns: bool,
+
+    /// Skip loading the namespaces and tables from the catalog at startup.
+    ///
+    /// The warm-up avoids a catalog query, and the associated latency, for
+    /// the first write to each namespace and table after a restart, at the
+    /// cost of a slower startup (bounded to 30 seconds). The most recently
+    /// created partitions are loaded either way.
+    #[clap(
+        long = "skip-catalog-warm-up",
+        env = "INFLUXDB_IOX_SKIP_CATALOG_WARM_UP",
+        action
+    )]
+    pub skip_catalog_warm_up: bool,
+
+    /// Evaluate the rate of rows written to each namespace every this number
+    /// of seconds, logging and counting abrupt drops and spikes relative to
+    /// its trailing baseline.
+    ///
+    /// A drop may indicate an outage of the agents writing to the namespace,
+    /// and a spike a runaway producer. Disabled by default.
+    #[clap(
+        long = "ingest-rate-check-interval-seconds",
+        env = "INFLUXDB_IOX_INGEST_RATE_CHECK_INTERVAL_SECONDS",
+        value_parser = clap::value_parser!(u64).range(1..)
+    )]
+    pub ingest_rate_check_interval_seconds: Option<u64>,
+
+    /// Raise an ingest rate anomaly when the rate of a namespace drops more
+    /// than this percentage below its trailing baseline.
+    #[clap(
+        long = "ingest-rate-drop-percent",
+        env = "INFLUXDB_IOX_INGEST_RATE_DROP_PERCENT",
+        default_value = "50",
+        value_parser = clap::value_parser!(u8).range(1..=100)
+    )]
+    pub ingest_rate_drop_percent: u8,
+
+    /// Raise an ingest rate anomaly when the rate of a namespace rises more
+    /// than this percentage above its trailing baseline.
+    #[clap(
+        long = "ingest-rate-spike-percent",
+        env = "INFLUXDB_IOX_INGEST_RATE_SPIKE_PERCENT",
+        default_value = "400",
+        action
+    )]
+    pub ingest_rate_spike_percent: u32,
+
+    /// Compress query responses with gzip when the querier accepts gzip
+    /// compressed responses, and accept gzip compressed query requests.
+    ///
+    /// This reduces the network bandwidth used to return large unpersisted
+    /// result sets to the queriers, at the cost of CPU time.
+    #[clap(
+        long = "query-response-gzip",
+        env = "INFLUXDB_IOX_QUERY_RESPONSE_GZIP",
+        action
+    )]
+    pub query_response_gzip: bool,
+
+    /// The maximum size of a query response message sent to the queriers, in
+    /// bytes.
+    ///
+    /// Record batches are split into messages of at most this size. This
+    /// MUST NOT exceed the maximum message size accepted by the queriers.
+    #[clap(
+        long = "query-max-response-message-bytes",
+        env = "INFLUXDB_IOX_QUERY_MAX_RESPONSE_MESSAGE_BYTES",
+        action
+    )]
+    pub query_max_response_message_bytes: Option<usize>,
 }
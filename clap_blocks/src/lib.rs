@@ -21,13 +21,16 @@ use workspace_hack as _;
 pub mod catalog_dsn;
 pub mod compactor;
 pub mod compactor_scheduler;
+pub mod field_value_policy;
 pub mod garbage_collector;
 pub mod gossip;
 pub mod ingester;
 pub mod ingester_address;
 pub mod memory_size;
 pub mod object_store;
+pub mod persist_barrier;
 pub mod querier;
+pub mod rollup;
 pub mod router;
 pub mod run_config;
 pub mod single_tenant;
@@ -6,7 +6,9 @@ use object_store::path::Path;
 use object_store::throttle::ThrottledStore;
 use object_store::{throttle::ThrottleConfig, DynObjectStore};
 use observability_deps::tracing::{info, warn};
+use serde::Deserialize;
 use snafu::{ResultExt, Snafu};
+use std::collections::BTreeMap;
 use std::sync::Arc;
 use std::{fs, num::NonZeroUsize, path::PathBuf, time::Duration};
 use uuid::Uuid;
@@ -47,6 +49,24 @@ pub enum ParseError {
 
     #[snafu(display("Error configuring Microsoft Azure: {}", source))]
     InvalidAzureConfig { source: object_store::Error },
+
+    #[snafu(display("Unable to read namespace object store config {:?}: {}", path, source))]
+    ReadNamespaceObjectStoreConfig {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+
+    #[snafu(display("Invalid namespace object store config {:?}: {}", path, source))]
+    ParseNamespaceObjectStoreConfig {
+        path: PathBuf,
+        source: serde_json::Error,
+    },
+
+    #[snafu(display("Error configuring namespace object store {}: {}", name, source))]
+    NamespaceObjectStore {
+        name: String,
+        source: Box<ParseError>,
+    },
 }
 
 /// The AWS region to use for Amazon S3 based object storage if none is
@@ -203,6 +223,26 @@ pub struct ObjectStoreConfig {
         action
     )]
     pub object_store_connection_limit: NonZeroUsize,
+
+    /// Path to a JSON file defining additional, named object stores that
+    /// namespaces can be configured to store their data in, instead of the
+    /// default object store.
+    ///
+    /// The file maps each object store name to its configuration, using the
+    /// same options as the default object store:
+    ///
+    /// {"tenant-a": {"object_store": "s3", "bucket": "tenant-a-data",
+    ///   "aws_access_key_id": "...", "aws_secret_access_key": "...",
+    ///   "aws_default_region": "eu-west-1"}}
+    ///
+    /// The same object stores must be configured on all ingesters, queriers,
+    /// compactors and garbage collectors.
+    #[clap(
+        long = "namespace-object-store-config",
+        env = "INFLUXDB_IOX_NAMESPACE_OBJECT_STORE_CONFIG",
+        action
+    )]
+    pub namespace_object_store_config: Option<PathBuf>,
 }
 
 impl ObjectStoreConfig {
@@ -229,12 +269,14 @@ impl ObjectStoreConfig {
             google_service_account: Default::default(),
             object_store,
             object_store_connection_limit: NonZeroUsize::new(16).unwrap(),
+            namespace_object_store_config: Default::default(),
         }
     }
 }
 
 /// Object-store type.
-#[derive(Debug, Copy, Clone, PartialEq, Eq, clap::ValueEnum)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, clap::ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum ObjectStoreType {
     /// In-memory.
     Memory,
@@ -409,6 +451,84 @@ pub fn make_object_store(config: &ObjectStoreConfig) -> Result<Arc<DynObjectStor
     }
 }
 
+/// The configuration of a single named object store within the
+/// `--namespace-object-store-config` file.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct NamespaceObjectStoreConfig {
+    object_store: ObjectStoreType,
+    bucket: Option<String>,
+    database_directory: Option<PathBuf>,
+    aws_access_key_id: Option<String>,
+    aws_secret_access_key: Option<String>,
+    aws_default_region: Option<String>,
+    aws_endpoint: Option<String>,
+    aws_session_token: Option<String>,
+    #[serde(default)]
+    aws_allow_http: bool,
+    google_service_account: Option<String>,
+    azure_storage_account: Option<String>,
+    azure_storage_access_key: Option<String>,
+    object_store_connection_limit: Option<NonZeroUsize>,
+}
+
+impl NamespaceObjectStoreConfig {
+    /// Convert into an [`ObjectStoreConfig`], inheriting the connection limit
+    /// of `parent` when unspecified.
+    fn into_object_store_config(self, parent: &ObjectStoreConfig) -> ObjectStoreConfig {
+        ObjectStoreConfig {
+            object_store: Some(self.object_store),
+            bucket: self.bucket,
+            database_directory: self.database_directory,
+            aws_access_key_id: self.aws_access_key_id,
+            aws_secret_access_key: self.aws_secret_access_key,
+            aws_default_region: self
+                .aws_default_region
+                .unwrap_or_else(|| FALLBACK_AWS_REGION.to_string()),
+            aws_endpoint: self.aws_endpoint,
+            aws_session_token: self.aws_session_token,
+            aws_allow_http: self.aws_allow_http,
+            google_service_account: self.google_service_account,
+            azure_storage_account: self.azure_storage_account,
+            azure_storage_access_key: self.azure_storage_access_key,
+            object_store_connection_limit: self
+                .object_store_connection_limit
+                .unwrap_or(parent.object_store_connection_limit),
+            namespace_object_store_config: None,
+        }
+    }
+}
+
+/// Create the named object stores defined in the
+/// `--namespace-object-store-config` file, if any, keyed by name.
+pub fn make_namespace_object_stores(
+    config: &ObjectStoreConfig,
+) -> Result<BTreeMap<String, Arc<DynObjectStore>>, ParseError> {
+    let Some(path) = &config.namespace_object_store_config else {
+        return Ok(Default::default());
+    };
+
+    let contents =
+        fs::read_to_string(path).context(ReadNamespaceObjectStoreConfigSnafu { path })?;
+    let stores: BTreeMap<String, NamespaceObjectStoreConfig> =
+        serde_json::from_str(&contents).context(ParseNamespaceObjectStoreConfigSnafu { path })?;
+
+    stores
+        .into_iter()
+        .map(|(name, store)| {
+            info!(%name, "configuring namespace object store");
+            let store =
+                make_object_store(&store.into_object_store_config(config)).map_err(|e| {
+                    ParseError::NamespaceObjectStore {
+                        name: name.clone(),
+                        source: Box::new(e),
+                    }
+                })?;
+            Ok((name, store))
+        })
+        .collect()
+}
+
 #[derive(Debug, Snafu)]
 #[allow(missing_docs)]
 pub enum CheckError {
@@ -597,6 +717,67 @@ mod tests {
         )
     }
 
+    #[test]
+    fn namespace_object_stores() {
+        let root = TempDir::new().unwrap();
+        let config_path = root.path().join("stores.json");
+        fs::write(
+            &config_path,
+            format!(
+                r#"{{
+                    "tenant-a": {{"object_store": "memory"}},
+                    "tenant-b": {{"object_store": "file", "database_directory": {:?}}}
+                }}"#,
+                root.path().join("tenant-b")
+            ),
+        )
+        .unwrap();
+
+        let config = ObjectStoreConfig::try_parse_from([
+            "server",
+            "--namespace-object-store-config",
+            config_path.to_str().unwrap(),
+        ])
+        .unwrap();
+
+        let stores = make_namespace_object_stores(&config).unwrap();
+        assert_eq!(
+            stores.keys().map(String::as_str).collect::<Vec<_>>(),
+            ["tenant-a", "tenant-b"]
+        );
+        assert_eq!(stores["tenant-a"].to_string(), "InMemory");
+        assert!(stores["tenant-b"]
+            .to_string()
+            .starts_with("LocalFileSystem"));
+
+        // No stores are configured by default.
+        let config = ObjectStoreConfig::try_parse_from(["server"]).unwrap();
+        assert!(make_namespace_object_stores(&config).unwrap().is_empty());
+    }
+
+    #[test]
+    fn namespace_object_stores_invalid() {
+        let root = TempDir::new().unwrap();
+        let config_path = root.path().join("stores.json");
+        fs::write(&config_path, r#"{"tenant-a": {"object_store": "file"}}"#).unwrap();
+
+        let config = ObjectStoreConfig::try_parse_from([
+            "server",
+            "--namespace-object-store-config",
+            config_path.to_str().unwrap(),
+        ])
+        .unwrap();
+
+        let err = make_namespace_object_stores(&config)
+            .unwrap_err()
+            .to_string();
+        assert_eq!(
+            err,
+            "Error configuring namespace object store tenant-a: Specified File for the object \
+            store, required configuration missing for data-dir"
+        );
+    }
+
     #[test]
     fn file_config_missing_params() {
         // this test tests for failure to configure the object store because of data-dir configuration missing
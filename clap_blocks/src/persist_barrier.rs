@@ -0,0 +1,89 @@
+//! CLI config for groups of tables whose partitions are persisted together.
+
+use std::str::FromStr;
+
+use snafu::Snafu;
+
+/// A set of tables in a namespace whose partitions are persisted together,
+/// specified as `<namespace>:<table>,<table>[,...]`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PersistBarrierGroupConfig {
+    /// The namespace containing the tables.
+    pub namespace: String,
+
+    /// The names of the tables in the group.
+    pub tables: Vec<String>,
+}
+
+/// Why a specified persist barrier group might be invalid
+#[allow(missing_docs)]
+#[derive(Snafu, Debug, PartialEq, Eq)]
+pub enum Error {
+    #[snafu(display("expected <namespace>:<table>,<table>[,...], got `{value}`"))]
+    Malformed { value: String },
+
+    #[snafu(display("a persist barrier group needs at least 2 tables, got `{value}`"))]
+    TooFewTables { value: String },
+}
+
+impl FromStr for PersistBarrierGroupConfig {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (namespace, tables) = s
+            .split_once(':')
+            .ok_or_else(|| MalformedSnafu { value: s }.build())?;
+
+        let namespace = namespace.trim();
+        let mut tables = tables
+            .split(',')
+            .map(|t| t.trim().to_string())
+            .collect::<Vec<_>>();
+        if namespace.is_empty() || tables.iter().any(|t| t.is_empty()) {
+            return MalformedSnafu { value: s }.fail();
+        }
+
+        tables.sort_unstable();
+        tables.dedup();
+        if tables.len() < 2 {
+            return TooFewTablesSnafu { value: s }.fail();
+        }
+
+        Ok(Self {
+            namespace: namespace.to_string(),
+            tables,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            "bananas:cpu, mem,cpu,disk".parse::<PersistBarrierGroupConfig>(),
+            Ok(PersistBarrierGroupConfig {
+                namespace: "bananas".to_string(),
+                tables: vec!["cpu".to_string(), "disk".to_string(), "mem".to_string()],
+            })
+        );
+
+        for value in ["bananas", "bananas:", ":cpu,mem", "bananas:cpu,,mem"] {
+            assert_eq!(
+                value.parse::<PersistBarrierGroupConfig>(),
+                Err(Error::Malformed {
+                    value: value.to_string()
+                })
+            );
+        }
+
+        assert_eq!(
+            "bananas:cpu,cpu".parse::<PersistBarrierGroupConfig>(),
+            Err(Error::TooFewTables {
+                value: "bananas:cpu,cpu".to_string()
+            })
+        );
+    }
+}
@@ -5,7 +5,11 @@ use crate::{
     memory_size::MemorySize,
     single_tenant::{CONFIG_AUTHZ_ENV_NAME, CONFIG_AUTHZ_FLAG},
 };
-use std::{collections::HashMap, num::NonZeroUsize};
+use std::{
+    collections::HashMap,
+    num::{NonZeroU32, NonZeroUsize},
+    path::PathBuf,
+};
 
 /// CLI config for querier configuration
 #[derive(Debug, Clone, PartialEq, Eq, clap::Parser)]
@@ -14,6 +18,18 @@ pub struct QuerierConfig {
     #[clap(long = CONFIG_AUTHZ_FLAG, env = CONFIG_AUTHZ_ENV_NAME)]
     pub authz_address: Option<String>,
 
+    /// Path to a JSON file defining namespace-scoped API tokens.
+    ///
+    /// When set, every query must present a token granting read access to the
+    /// target namespace, and only reads the rows matching the row filters of
+    /// the token (if any).
+    #[clap(
+        long = "authz-token-file",
+        env = "INFLUXDB_IOX_AUTHZ_TOKEN_FILE",
+        conflicts_with = "authz_address"
+    )]
+    pub authz_token_file: Option<PathBuf>,
+
     /// The number of threads to use for queries.
     ///
     /// If not specified, defaults to the number of cores on the system
@@ -111,6 +127,75 @@ pub struct QuerierConfig {
     )]
     pub ingester_circuit_breaker_threshold: u64,
 
+    /// Query only the ingesters that may own data for a table, as assigned by
+    /// routers configured with the same `--ingester-table-spread`.
+    ///
+    /// The ingester addresses MUST match those configured on the routers. If
+    /// not specified, all ingesters are queried for every table.
+    #[clap(
+        long = "ingester-table-spread",
+        env = "INFLUXDB_IOX_INGESTER_TABLE_SPREAD"
+    )]
+    pub ingester_table_spread: Option<NonZeroUsize>,
+
+    /// Accept gzip compressed query responses from the ingesters.
+    ///
+    /// The ingesters only compress responses if started with
+    /// `--query-response-gzip`.
+    #[clap(
+        long = "ingester-query-gzip",
+        env = "INFLUXDB_IOX_INGESTER_QUERY_GZIP",
+        action
+    )]
+    pub ingester_query_gzip: bool,
+
+    /// The maximum size of a query response message accepted from the
+    /// ingesters, in bytes.
+    ///
+    /// Defaults to 4 MiB. The ingesters MUST be configured with a
+    /// `--query-max-response-message-bytes` no larger than this value.
+    #[clap(
+        long = "ingester-query-max-message-bytes",
+        env = "INFLUXDB_IOX_INGESTER_QUERY_MAX_MESSAGE_BYTES",
+        action
+    )]
+    pub ingester_query_max_message_bytes: Option<usize>,
+
+    /// The maximum number of snapshots of a partition each ingester query
+    /// response returns.
+    ///
+    /// Partitions with more snapshots are fetched over several requests,
+    /// bounding the size of a single response. If not specified, all the data
+    /// of a table is fetched in one request.
+    #[clap(
+        long = "ingester-query-max-snapshots",
+        env = "INFLUXDB_IOX_INGESTER_QUERY_MAX_SNAPSHOTS",
+        action
+    )]
+    pub ingester_query_max_snapshots: Option<NonZeroU32>,
+
+    /// Rules rejecting queries, separated by `;`.
+    ///
+    /// A query reading a table is rejected if any rule matches. A rule is
+    /// one of:
+    ///
+    /// - `table=<regex>`: the table name matches the regex.
+    /// - `predicate=<regex>`: a filter of the query, in its displayed form
+    ///   (such as `host LIKE Utf8("%a")`), matches the regex.
+    /// - `no_time_bound`: the query has no filter on the time column.
+    /// - `no_time_bound=<regex>`: the query has no filter on the time column
+    ///   of a table with a name matching the regex.
+    ///
+    /// The rules can be replaced at runtime through the query admin API.
+    #[clap(
+        long = "query-denylist",
+        env = "INFLUXDB_IOX_QUERY_DENYLIST",
+        required = false,
+        num_args = 0..,
+        value_delimiter = ';'
+    )]
+    pub query_denylist: Vec<String>,
+
     /// DataFusion config.
     #[clap(
         long = "datafusion-config",
@@ -120,6 +205,53 @@ pub struct QuerierConfig {
         action
     )]
     pub datafusion_config: HashMap<String, String>,
+
+    /// The HTTP address of the router the results of tasks are written
+    /// through, such as `http://router:8080`.
+    ///
+    /// If not set, this querier does not run tasks.
+    #[clap(long = "task-router-address", env = "INFLUXDB_IOX_TASK_ROUTER_ADDRESS")]
+    pub task_router_address: Option<String>,
+
+    /// The token authorizing the writes of task results to the router, sent
+    /// in an `Authorization: Token <token>` header.
+    ///
+    /// Required if the router authorizes writes.
+    #[clap(long = "task-router-token", env = "INFLUXDB_IOX_TASK_ROUTER_TOKEN")]
+    pub task_router_token: Option<String>,
+
+    /// How often the catalog is polled for the tasks due, in seconds.
+    #[clap(
+        long = "task-poll-interval-seconds",
+        env = "INFLUXDB_IOX_TASK_POLL_INTERVAL_SECONDS",
+        default_value = "10",
+        action
+    )]
+    pub task_poll_interval_seconds: u64,
+
+    /// The duration a task run, including its retries, may take before it is
+    /// stopped and recorded as failed, in seconds.
+    ///
+    /// Runs left unfinished by a querier that stopped are recorded as failed
+    /// once they started twice this duration ago.
+    #[clap(
+        long = "task-run-timeout-seconds",
+        env = "INFLUXDB_IOX_TASK_RUN_TIMEOUT_SECONDS",
+        default_value = "300",
+        value_parser = clap::value_parser!(u64).range(1..),
+        action
+    )]
+    pub task_run_timeout_seconds: u64,
+
+    /// Record the completed queries, and the resources used to execute them,
+    /// into the query history of the catalog.
+    ///
+    /// The recorded queries are listed in the `system.query_history` table of
+    /// each namespace, and by the `query history` CLI command. The entries
+    /// are deleted by the garbage collector once older than its
+    /// `--query-history-cutoff`.
+    #[clap(long = "query-history", env = "INFLUXDB_IOX_QUERY_HISTORY", action)]
+    pub query_history: bool,
 }
 
 fn parse_datafusion_config(
@@ -166,6 +298,27 @@ mod tests {
         assert_eq!(actual.num_query_threads, None);
         assert!(actual.ingester_addresses.is_empty());
         assert!(actual.datafusion_config.is_empty());
+        assert_eq!(actual.ingester_table_spread, None);
+        assert!(actual.query_denylist.is_empty());
+        assert_eq!(actual.task_router_address, None);
+        assert_eq!(actual.task_poll_interval_seconds, 10);
+        assert_eq!(actual.task_router_token, None);
+        assert_eq!(actual.task_run_timeout_seconds, 300);
+    }
+
+    #[test]
+    fn test_query_denylist() {
+        let actual = QuerierConfig::try_parse_from([
+            "my_binary",
+            "--query-denylist",
+            "table=^(cpu|mem)$;no_time_bound",
+        ])
+        .unwrap();
+
+        assert_eq!(
+            actual.query_denylist,
+            ["table=^(cpu|mem)$", "no_time_bound"]
+        );
     }
 
     #[test]
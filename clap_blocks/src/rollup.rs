@@ -0,0 +1,119 @@
+//! CLI config for tables whose writes are rolled up by the ingester.
+
+use std::{str::FromStr, time::Duration};
+
+use snafu::{ResultExt, Snafu};
+
+/// A table in a namespace whose writes are rolled up into time buckets of an
+/// interval, specified as `<namespace>:<table>:<interval>` (such as
+/// `bananas:cpu:5m`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RollupConfig {
+    /// The namespace containing the table.
+    pub namespace: String,
+
+    /// The name of the rolled up table.
+    pub table: String,
+
+    /// The width of the time buckets.
+    pub interval: Duration,
+}
+
+/// Why a specified rollup might be invalid
+#[allow(missing_docs)]
+#[derive(Snafu, Debug)]
+pub enum Error {
+    #[snafu(display("expected <namespace>:<table>:<interval>, got `{value}`"))]
+    Malformed { value: String },
+
+    #[snafu(display("invalid rollup interval in `{value}`: {source}"))]
+    InvalidInterval {
+        value: String,
+        source: humantime::DurationError,
+    },
+
+    #[snafu(display(
+        "rollup interval in `{value}` must be a whole number of seconds dividing a day"
+    ))]
+    UnalignedInterval { value: String },
+}
+
+impl FromStr for RollupConfig {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split(':').map(str::trim);
+        let (namespace, table, interval) = match (parts.next(), parts.next(), parts.next()) {
+            (Some(n), Some(t), Some(i)) if !n.is_empty() && !t.is_empty() => (n, t, i),
+            _ => return MalformedSnafu { value: s }.fail(),
+        };
+        if parts.next().is_some() {
+            return MalformedSnafu { value: s }.fail();
+        }
+
+        let interval =
+            humantime::parse_duration(interval).context(InvalidIntervalSnafu { value: s })?;
+        if interval.subsec_nanos() != 0
+            || interval.as_secs() == 0
+            || 86_400 % interval.as_secs() != 0
+        {
+            return UnalignedIntervalSnafu { value: s }.fail();
+        }
+
+        Ok(Self {
+            namespace: namespace.to_string(),
+            table: table.to_string(),
+            interval,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            "bananas: cpu :5m".parse::<RollupConfig>().unwrap(),
+            RollupConfig {
+                namespace: "bananas".to_string(),
+                table: "cpu".to_string(),
+                interval: Duration::from_secs(300),
+            }
+        );
+
+        for value in [
+            "bananas",
+            "bananas:cpu",
+            ":cpu:5m",
+            "bananas::5m",
+            "a:b:5m:c",
+        ] {
+            assert!(
+                matches!(value.parse::<RollupConfig>(), Err(Error::Malformed { .. })),
+                "{value}"
+            );
+        }
+
+        assert!(matches!(
+            "bananas:cpu:platanos".parse::<RollupConfig>(),
+            Err(Error::InvalidInterval { .. })
+        ));
+
+        for value in [
+            "bananas:cpu:0s",
+            "bananas:cpu:500ms",
+            "bananas:cpu:7m",
+            "bananas:cpu:2d",
+        ] {
+            assert!(
+                matches!(
+                    value.parse::<RollupConfig>(),
+                    Err(Error::UnalignedInterval { .. })
+                ),
+                "{value}"
+            );
+        }
+    }
+}
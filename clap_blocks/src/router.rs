@@ -1,6 +1,7 @@
 //! CLI config for the router using the RPC write path
 
 use crate::{
+    field_value_policy::FieldValuePolicyConfig,
     gossip::GossipConfig,
     ingester_address::IngesterAddress,
     single_tenant::{
@@ -9,9 +10,52 @@ use crate::{
 };
 use std::{
     num::{NonZeroUsize, ParseIntError},
+    path::PathBuf,
     time::Duration,
 };
 
+/// The behaviour of the router when the queue of RPC write requests to an
+/// ingester is full.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum RpcWriteQueueOverflow {
+    /// Wait for a queued request to complete.
+    #[default]
+    Block,
+
+    /// Reject the request, retrying it against another ingester if possible.
+    Reject,
+}
+
+/// The enforcement of naming rules on the table, tag and field names of
+/// written line protocol.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum WriteNamingMode {
+    /// Accept all names.
+    #[default]
+    Disabled,
+
+    /// Reject lines containing a name that violates the naming rules.
+    Reject,
+
+    /// Rewrite names to conform to the naming rules.
+    Normalize,
+}
+
+/// The handling of written rows already carrying a default tag of their
+/// namespace.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum DefaultTagConflictConfig {
+    /// Keep the written value.
+    #[default]
+    Keep,
+
+    /// Replace the written value with the default value.
+    Overwrite,
+
+    /// Reject writes carrying a value other than the default value.
+    Reject,
+}
+
 /// CLI config for the router using the RPC write path
 #[derive(Debug, Clone, clap::Parser)]
 #[allow(missing_copy_implementations)]
@@ -40,6 +84,31 @@ pub struct RouterConfig {
     )]
     pub single_tenant_deployment: bool,
 
+    /// Path to a JSON file defining namespace-scoped API tokens.
+    ///
+    /// When set, every write request must present a token granting write
+    /// access to the target namespace. Only supported in multi-tenant
+    /// deployments - single tenant deployments use the authz service instead.
+    #[clap(
+        long = "authz-token-file",
+        env = "INFLUXDB_IOX_AUTHZ_TOKEN_FILE",
+        conflicts_with = "single_tenant_deployment"
+    )]
+    pub authz_token_file: Option<PathBuf>,
+
+    /// The duration in seconds the result of an authz service permission
+    /// check is cached for.
+    ///
+    /// This bounds how long a revoked token continues to be accepted. Set to
+    /// zero to disable caching.
+    #[clap(
+        long = "authz-cache-ttl-seconds",
+        env = "INFLUXDB_IOX_AUTHZ_CACHE_TTL_SECONDS",
+        default_value = "10",
+        value_parser = parse_duration
+    )]
+    pub authz_cache_ttl: Duration,
+
     /// The maximum number of simultaneous requests the HTTP server is
     /// configured to accept.
     ///
@@ -141,9 +210,200 @@ pub struct RouterConfig {
         default_value = "10"
     )]
     pub rpc_write_health_num_probes: u64,
+
+    /// The maximum number of RPC write requests queued for, or in-flight to,
+    /// a single ingester at any one time.
+    ///
+    /// Once reached, further requests to the ingester are handled according
+    /// to `--rpc-write-queue-overflow`. Unbounded if not specified.
+    #[clap(
+        long = "rpc-write-max-queued-requests",
+        env = "INFLUXDB_IOX_RPC_WRITE_MAX_QUEUED_REQUESTS"
+    )]
+    pub rpc_write_max_queued_requests: Option<NonZeroUsize>,
+
+    /// The behaviour when the queue of RPC write requests to an ingester is
+    /// full.
+    ///
+    /// "block" waits for a queue slot (bounded by the write timeout), while
+    /// "reject" immediately retries the request against another ingester.
+    #[clap(
+        value_enum,
+        long = "rpc-write-queue-overflow",
+        env = "INFLUXDB_IOX_RPC_WRITE_QUEUE_OVERFLOW",
+        default_value = "block"
+    )]
+    pub rpc_write_queue_overflow: RpcWriteQueueOverflow,
+
+    /// Route each write only to the ingesters owning the (table, partition
+    /// key) it targets, spreading the partitions of each table across this
+    /// many ingesters.
+    ///
+    /// Queriers configured with the same ingester addresses and
+    /// `--ingester-table-spread` query only the owning ingesters for a table.
+    ///
+    /// Must be at least `--rpc-write-replicas`, and at most the number of
+    /// ingester addresses. If not specified, writes are distributed across all
+    /// ingesters.
+    #[clap(
+        long = "ingester-table-spread",
+        env = "INFLUXDB_IOX_INGESTER_TABLE_SPREAD"
+    )]
+    pub ingester_table_spread: Option<NonZeroUsize>,
+
+    /// Coalesce the writes to the same namespace and partition received
+    /// within this many milliseconds of each other into a single RPC write to
+    /// the ingesters.
+    ///
+    /// Reduces the number of RPC writes sent for a high rate of small
+    /// writes, at the cost of adding up to this much latency to each write.
+    /// Each write is still acknowledged (or rejected) individually. Disabled
+    /// when set to 0.
+    #[clap(
+        long = "rpc-write-group-commit-window-millis",
+        env = "INFLUXDB_IOX_RPC_WRITE_GROUP_COMMIT_WINDOW_MILLIS",
+        default_value = "0",
+        value_parser = parse_duration_millis
+    )]
+    pub rpc_write_group_commit_window: Duration,
+
+    /// The duration in seconds a namespace's read-only flag is cached for
+    /// before being re-read from the catalog.
+    ///
+    /// This bounds how long a router continues to accept writes after a
+    /// namespace is marked read-only (and vice versa).
+    #[clap(
+        long = "namespace-read-only-cache-ttl-seconds",
+        env = "INFLUXDB_IOX_NAMESPACE_READ_ONLY_CACHE_TTL_SECONDS",
+        default_value = "10",
+        value_parser = parse_duration
+    )]
+    pub namespace_read_only_cache_ttl: Duration,
+
+    /// The duration in seconds the default tags of a namespace are cached
+    /// for before being re-read from the catalog.
+    #[clap(
+        long = "namespace-default-tags-cache-ttl-seconds",
+        env = "INFLUXDB_IOX_NAMESPACE_DEFAULT_TAGS_CACHE_TTL_SECONDS",
+        default_value = "10",
+        value_parser = parse_duration
+    )]
+    pub namespace_default_tags_cache_ttl: Duration,
+
+    /// The handling of written rows already carrying one of the default tags
+    /// configured in the catalog for their namespace.
+    ///
+    /// "keep" keeps the written value, "overwrite" replaces it with the
+    /// default value and "reject" rejects writes carrying a value other than
+    /// the default value. Rows without the tag are always given the default
+    /// value.
+    #[clap(
+        value_enum,
+        long = "namespace-default-tags-conflict",
+        env = "INFLUXDB_IOX_NAMESPACE_DEFAULT_TAGS_CONFLICT",
+        default_value = "keep"
+    )]
+    pub namespace_default_tags_conflict: DefaultTagConflictConfig,
+
+    /// The enforcement of the `--write-naming-*` rules on the table, tag and
+    /// field names of written line protocol.
+    ///
+    /// "reject" rejects lines containing a name that violates the rules,
+    /// while "normalize" lowercases names, replaces disallowed characters with
+    /// "_", removes reserved prefixes and truncates names to the maximum
+    /// length.
+    #[clap(
+        value_enum,
+        long = "write-naming-mode",
+        env = "INFLUXDB_IOX_WRITE_NAMING_MODE",
+        default_value = "disabled"
+    )]
+    pub write_naming_mode: WriteNamingMode,
+
+    /// The maximum length in bytes of written table, tag and field names.
+    #[clap(
+        long = "write-naming-max-length",
+        env = "INFLUXDB_IOX_WRITE_NAMING_MAX_LENGTH"
+    )]
+    pub write_naming_max_length: Option<usize>,
+
+    /// A comma-separated list of prefixes written table, tag and field names
+    /// must not start with.
+    #[clap(
+        long = "write-naming-reserved-prefixes",
+        env = "INFLUXDB_IOX_WRITE_NAMING_RESERVED_PREFIXES",
+        value_delimiter = ','
+    )]
+    pub write_naming_reserved_prefixes: Vec<String>,
+
+    /// Allow only ASCII letters, digits, "_", "." and "-" in written table,
+    /// tag and field names.
+    #[clap(
+        long = "write-naming-restrict-charset",
+        env = "INFLUXDB_IOX_WRITE_NAMING_RESTRICT_CHARSET",
+        default_value = "false"
+    )]
+    pub write_naming_restrict_charset: bool,
+
+    /// The handling of non-finite (NaN or infinite) float field values, and of
+    /// unsigned integer field values written to integer columns.
+    ///
+    /// Unsigned values up to the maximum integer value are always converted
+    /// when this is enabled. "reject" rejects writes containing values that
+    /// cannot be stored, "clamp" replaces them with the closest representable
+    /// value (NULL for NaN) and "null" replaces them with NULL.
+    #[clap(
+        value_enum,
+        long = "field-value-policy",
+        env = "INFLUXDB_IOX_FIELD_VALUE_POLICY",
+        default_value = "disabled"
+    )]
+    pub field_value_policy: FieldValuePolicyConfig,
+
+    /// Drop the rows of a write with a timestamp outside of the retention
+    /// period of the namespace, accepting the remaining rows, instead of
+    /// rejecting the whole write.
+    ///
+    /// The number of dropped rows is exposed in the
+    /// `router_retention_dropped_rows` metric.
+    #[clap(
+        long = "drop-rows-outside-retention",
+        env = "INFLUXDB_IOX_DROP_ROWS_OUTSIDE_RETENTION",
+        action
+    )]
+    pub drop_rows_outside_retention: bool,
+
+    /// The interval in seconds between validations of a random sample of the
+    /// namespace schema cache against the catalog.
+    ///
+    /// Cached schemas diverging from the catalog are logged, counted in the
+    /// `router_namespace_cache_divergences` metric, and repaired where
+    /// possible. Disabled if not specified.
+    #[clap(
+        long = "namespace-cache-validation-interval-seconds",
+        env = "INFLUXDB_IOX_NAMESPACE_CACHE_VALIDATION_INTERVAL_SECONDS",
+        value_parser = clap::value_parser!(u64).range(1..)
+    )]
+    pub namespace_cache_validation_interval_seconds: Option<u64>,
+
+    /// The number of cached namespaces validated against the catalog at each
+    /// `--namespace-cache-validation-interval-seconds`.
+    #[clap(
+        long = "namespace-cache-validation-sample-size",
+        env = "INFLUXDB_IOX_NAMESPACE_CACHE_VALIDATION_SAMPLE_SIZE",
+        default_value = "10",
+        action
+    )]
+    pub namespace_cache_validation_sample_size: usize,
 }
 
 /// Map a string containing an integer number of seconds into a [`Duration`].
 fn parse_duration(input: &str) -> Result<Duration, ParseIntError> {
     input.parse().map(Duration::from_secs)
 }
+
+/// Map a string containing an integer number of milliseconds into a
+/// [`Duration`].
+fn parse_duration_millis(input: &str) -> Result<Duration, ParseIntError> {
+    input.parse().map(Duration::from_millis)
+}
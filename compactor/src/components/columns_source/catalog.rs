@@ -2,7 +2,7 @@ use std::{fmt::Display, sync::Arc};
 
 use async_trait::async_trait;
 use backoff::{Backoff, BackoffConfig};
-use data_types::{Column, TableId};
+use data_types::{Column, ColumnRetention, TableId};
 use iox_catalog::interface::Catalog;
 
 use super::ColumnsSource;
@@ -43,4 +43,18 @@ impl ColumnsSource for CatalogColumnsSource {
             .await
             .expect("retry forever")
     }
+
+    async fn fetch_retention(&self, table: TableId) -> Vec<ColumnRetention> {
+        Backoff::new(&self.backoff_config)
+            .retry_all_errors("column_retention_of_given_table_id", || async {
+                self.catalog
+                    .repositories()
+                    .await
+                    .column_retention()
+                    .list_by_table_id(table)
+                    .await
+            })
+            .await
+            .expect("retry forever")
+    }
 }
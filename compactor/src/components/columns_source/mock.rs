@@ -1,7 +1,7 @@
 use std::{collections::HashMap, fmt::Display};
 
 use async_trait::async_trait;
-use data_types::{Column, TableId};
+use data_types::{Column, ColumnRetention, TableId};
 
 use super::ColumnsSource;
 
@@ -28,6 +28,10 @@ impl ColumnsSource for MockColumnsSource {
     async fn fetch(&self, table: TableId) -> Vec<Column> {
         self.tables.get(&table).cloned().unwrap_or_default()
     }
+
+    async fn fetch_retention(&self, _table: TableId) -> Vec<ColumnRetention> {
+        vec![]
+    }
 }
 
 #[cfg(test)]
@@ -1,7 +1,7 @@
 use std::fmt::{Debug, Display};
 
 use async_trait::async_trait;
-use data_types::{Column, TableId};
+use data_types::{Column, ColumnRetention, TableId};
 
 pub mod catalog;
 pub mod mock;
@@ -12,4 +12,10 @@ pub trait ColumnsSource: Debug + Display + Send + Sync {
     ///
     /// This method performs retries.
     async fn fetch(&self, table: TableId) -> Vec<Column>;
+
+    /// Get the retention periods of the columns of a given table that have
+    /// one
+    ///
+    /// This method performs retries.
+    async fn fetch_retention(&self, table: TableId) -> Vec<ColumnRetention>;
 }
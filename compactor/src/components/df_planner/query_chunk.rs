@@ -135,10 +135,13 @@ fn to_queryable_parquet_chunk(
     store: ParquetStorage,
 ) -> QueryableParquetChunk {
     let column_id_lookup = partition_info.table_schema.column_id_map();
+    // Columns past their retention period are not read, dropping them from the
+    // compacted output.
     let selection: Vec<_> = file
         .file
         .column_set
         .iter()
+        .filter(|id| !partition_info.is_column_expired(**id, file.file.max_time))
         .flat_map(|id| column_id_lookup.get(id).copied())
         .collect();
     let table_schema: Schema = partition_info
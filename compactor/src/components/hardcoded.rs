@@ -200,6 +200,7 @@ fn make_partition_info_source(config: &Config) -> Arc<dyn PartitionInfoSource> {
         )),
         CatalogTablesSource::new(config.backoff_config.clone(), Arc::clone(&config.catalog)),
         CatalogNamespacesSource::new(config.backoff_config.clone(), Arc::clone(&config.catalog)),
+        Arc::clone(&config.time_provider),
     ))
 }
 
@@ -190,6 +190,8 @@ mod tests {
                         retention_period_ns: None,
                         deleted_at: None,
                         partition_template: Default::default(),
+                        read_only: false,
+                        object_store_name: None,
                     },
                     schema: NamespaceSchema {
                         id,
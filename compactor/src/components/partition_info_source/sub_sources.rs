@@ -1,7 +1,8 @@
 use std::{fmt::Display, sync::Arc};
 
 use async_trait::async_trait;
-use data_types::PartitionId;
+use data_types::{ColumnType, PartitionId, Timestamp};
+use iox_time::TimeProvider;
 use schema::sort::SortKey;
 
 use crate::{
@@ -27,6 +28,7 @@ where
     partition_source: P,
     tables_source: T,
     namespaces_source: N,
+    time_provider: Arc<dyn TimeProvider>,
 }
 
 impl<C, P, T, N> SubSourcePartitionInfoSource<C, P, T, N>
@@ -41,12 +43,14 @@ where
         partition_source: P,
         tables_source: T,
         namespaces_source: N,
+        time_provider: Arc<dyn TimeProvider>,
     ) -> Self {
         Self {
             columns_source,
             partition_source,
             tables_source,
             namespaces_source,
+            time_provider,
         }
     }
 }
@@ -145,6 +149,28 @@ where
         // This wil be removed once sort_key is removed from partition
         assert_eq!(sort_key, p_sort_key);
 
+        // Only field columns can be dropped separately from the rows they
+        // belong to.
+        let now = Timestamp::from(self.time_provider.now());
+        let column_retention_cutoffs = self
+            .columns_source
+            .fetch_retention(table.id)
+            .await
+            .into_iter()
+            .filter(|r| {
+                columns.iter().any(|c| {
+                    c.id == r.column_id
+                        && !matches!(c.column_type, ColumnType::Tag | ColumnType::Time)
+                })
+            })
+            .map(|r| {
+                (
+                    r.column_id,
+                    Timestamp::new(now.get().saturating_sub(r.retention_period_ns)),
+                )
+            })
+            .collect();
+
         Ok(Arc::new(PartitionInfo {
             partition_id,
             partition_hash_id: partition.hash_id().cloned(),
@@ -154,6 +180,7 @@ where
             table_schema: Arc::new(table_schema.clone()),
             sort_key,
             partition_key: partition.partition_key,
+            column_retention_cutoffs,
         }))
     }
 }
@@ -1,10 +1,10 @@
 //! Information of a partition for compaction
 
-use std::sync::Arc;
+use std::{collections::HashMap, sync::Arc};
 
 use data_types::{
-    NamespaceId, PartitionHashId, PartitionId, PartitionKey, Table, TableSchema,
-    TransitionPartitionId,
+    ColumnId, NamespaceId, PartitionHashId, PartitionId, PartitionKey, Table, TableSchema,
+    Timestamp, TransitionPartitionId,
 };
 use schema::sort::SortKey;
 
@@ -34,6 +34,10 @@ pub struct PartitionInfo {
 
     /// partition_key
     pub partition_key: PartitionKey,
+
+    /// The field columns with a retention period override, mapped to the time
+    /// before which their values are past that retention period
+    pub column_retention_cutoffs: HashMap<ColumnId, Timestamp>,
 }
 
 impl PartitionInfo {
@@ -50,4 +54,35 @@ impl PartitionInfo {
     pub fn partition_id(&self) -> TransitionPartitionId {
         TransitionPartitionId::from((self.partition_id, self.partition_hash_id.as_ref()))
     }
+
+    /// Returns true if all the values of the column `id` in a file with the
+    /// given `max_time` are past the retention period of the column, and
+    /// should be dropped when the file is compacted.
+    pub fn is_column_expired(&self, id: ColumnId, max_time: Timestamp) -> bool {
+        self.column_retention_cutoffs
+            .get(&id)
+            .is_some_and(|cutoff| max_time < *cutoff)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::PartitionInfoBuilder;
+
+    use super::*;
+
+    #[test]
+    fn test_is_column_expired() {
+        let info = PartitionInfoBuilder::new()
+            .with_num_columns(3)
+            .with_column_retention_cutoff(ColumnId::new(1), Timestamp::new(100))
+            .build();
+
+        // Files straddling the cutoff keep the column.
+        assert!(info.is_column_expired(ColumnId::new(1), Timestamp::new(99)));
+        assert!(!info.is_column_expired(ColumnId::new(1), Timestamp::new(100)));
+
+        // Columns without a retention period never expire.
+        assert!(!info.is_column_expired(ColumnId::new(2), Timestamp::new(0)));
+    }
 }
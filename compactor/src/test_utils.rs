@@ -1,8 +1,8 @@
-use std::sync::Arc;
+use std::{collections::HashMap, sync::Arc};
 
 use data_types::{
     Column, ColumnId, ColumnType, ColumnsByName, NamespaceId, PartitionHashId, PartitionId,
-    PartitionKey, Table, TableId, TableSchema,
+    PartitionKey, Table, TableId, TableSchema, Timestamp,
 };
 
 use crate::PartitionInfo;
@@ -36,6 +36,7 @@ impl PartitionInfoBuilder {
                 table_schema,
                 sort_key: None,
                 partition_key,
+                column_retention_cutoffs: HashMap::new(),
             },
         }
     }
@@ -65,6 +66,11 @@ impl PartitionInfoBuilder {
         self
     }
 
+    pub fn with_column_retention_cutoff(mut self, id: ColumnId, cutoff: Timestamp) -> Self {
+        self.inner.column_retention_cutoffs.insert(id, cutoff);
+        self
+    }
+
     pub fn build(self) -> PartitionInfo {
         self.inner
     }
@@ -1,6 +1,6 @@
 use arrow_util::assert_batches_sorted_eq;
 use compactor_test_utils::{format_files, list_object_store, TestSetup};
-use data_types::{CompactionLevel, ParquetFile, PartitionId};
+use data_types::{ColumnRetention, CompactionLevel, ParquetFile, PartitionId};
 
 mod layouts;
 
@@ -181,6 +181,66 @@ async fn test_compact_target_level() {
     );
 }
 
+#[tokio::test]
+async fn test_compact_drops_expired_column() {
+    test_helpers::maybe_start_logging();
+
+    // Create a test setup with 6 files
+    let setup = TestSetup::builder()
+        .await
+        .with_files()
+        .await
+        // Ensure we have enough resource to compact the files
+        .with_max_num_files_per_plan(10)
+        .with_min_num_l1_files_to_compact(2)
+        .build()
+        .await;
+
+    // Expire the values of field_int in all the files
+    let mut repos = setup.catalog.catalog.repositories().await;
+    let field_int = repos
+        .columns()
+        .list_by_table_id(setup.table.table.id)
+        .await
+        .unwrap()
+        .into_iter()
+        .find(|c| c.name == "field_int")
+        .unwrap();
+    repos
+        .column_retention()
+        .upsert(&ColumnRetention {
+            table_id: setup.table.table.id,
+            column_id: field_int.id,
+            retention_period_ns: 1,
+        })
+        .await
+        .unwrap();
+    drop(repos);
+
+    let files = setup.list_by_table_not_to_delete().await;
+    assert_eq!(files.len(), 6);
+    assert!(files.iter().all(|f| f.column_set.contains(&field_int.id)));
+
+    // compact
+    setup.run_compact().await;
+
+    // verify the expired column is dropped from all the compacted files, and
+    // the rows are kept
+    let files = setup.list_by_table_not_to_delete().await;
+    assert_eq!(files.len(), 2);
+
+    let mut rows = 0;
+    for file in files {
+        assert!(!file.column_set.contains(&field_int.id));
+        let batches = setup.read_parquet_file(file).await;
+        for batch in batches {
+            assert!(batch.schema().column_with_name("field_int").is_none());
+            rows += batch.num_rows();
+        }
+    }
+    assert_eq!(rows, 10);
+}
+
 #[tokio::test]
 async fn test_compact_large_overlapes() {
     test_helpers::maybe_start_logging();
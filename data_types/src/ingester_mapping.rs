@@ -0,0 +1,261 @@
+//! A deterministic mapping of (table, partition key) pairs to the ingester
+//! replicas that own them.
+//!
+//! The router and querier both construct an [`IngesterMapping`] from the same
+//! set of ingester addresses, allowing the querier to direct a query for a
+//! table to only the ingesters the router may have written that table's data
+//! to.
+
+use std::num::NonZeroUsize;
+
+use sha2::{Digest, Sha256};
+
+use crate::{PartitionKey, TableId};
+
+/// Derive a stable 64-bit hash of `parts`.
+///
+/// Unlike [`std::collections::hash_map::DefaultHasher`], the output of this
+/// function is stable across processes, builds and releases - a requirement
+/// for the router and querier to independently agree on a mapping.
+fn stable_hash(parts: &[&[u8]]) -> u64 {
+    let mut h = Sha256::new();
+    for p in parts {
+        // Length-prefix each part so that ("ab", "c") and ("a", "bc") differ.
+        h.update((p.len() as u64).to_be_bytes());
+        h.update(p);
+    }
+    let digest = h.finalize();
+    u64::from_be_bytes(digest[..8].try_into().expect("sha256 digest is 32 bytes"))
+}
+
+/// A deterministic assignment of tables and partitions to a set of ingester
+/// nodes.
+///
+/// Each table is assigned a stable set of `table_spread` candidate nodes using
+/// rendezvous (highest random weight) hashing over the node names, and each
+/// partition of that table is assigned an ordered list of owners drawn from the
+/// table's candidates.
+///
+/// Rendezvous hashing minimises the reassignment of tables as nodes are added
+/// or removed - removing a node only moves the tables for which it was a
+/// candidate.
+///
+/// The ordering of the nodes passed to [`IngesterMapping::new()`] has no effect
+/// on the mapping, only their names.
+#[derive(Debug, Clone)]
+pub struct IngesterMapping<T> {
+    /// The nodes, and the hash of their names.
+    nodes: Vec<(u64, T)>,
+
+    /// The number of nodes a single table is spread across, never more than
+    /// the number of nodes.
+    table_spread: usize,
+}
+
+impl<T> IngesterMapping<T> {
+    /// Construct a mapping over `nodes`, each identified by a unique name
+    /// (typically the ingester address) that must be consistent across all
+    /// users of the mapping.
+    ///
+    /// Each table is spread over at most `table_spread` nodes.
+    pub fn new<N>(nodes: impl IntoIterator<Item = (N, T)>, table_spread: NonZeroUsize) -> Self
+    where
+        N: AsRef<str>,
+    {
+        let nodes: Vec<_> = nodes
+            .into_iter()
+            .map(|(name, node)| (stable_hash(&[name.as_ref().as_bytes()]), node))
+            .collect();
+
+        let table_spread = table_spread.get().min(nodes.len());
+
+        Self {
+            nodes,
+            table_spread,
+        }
+    }
+
+    /// Returns the number of nodes in this mapping.
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Returns true if this mapping contains no nodes.
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Return the set of nodes that may own partitions of `table_id`, in
+    /// descending order of preference.
+    ///
+    /// Queries for `table_id` need only be sent to these nodes.
+    pub fn table_candidates(&self, table_id: TableId) -> Vec<&T> {
+        let table = table_id.get().to_be_bytes();
+
+        let mut weighted = self
+            .nodes
+            .iter()
+            .map(|(node_hash, node)| (stable_hash(&[&node_hash.to_be_bytes(), &table]), node))
+            .collect::<Vec<_>>();
+
+        // Order by descending weight.
+        weighted.sort_unstable_by(|a, b| b.0.cmp(&a.0));
+
+        weighted
+            .into_iter()
+            .take(self.table_spread)
+            .map(|(_, node)| node)
+            .collect()
+    }
+
+    /// Return the ordered list of nodes that own the partition identified by
+    /// `partition_key` within `table_id`.
+    ///
+    /// A write replicated N times should be sent to the first N nodes. The
+    /// returned nodes are always a rotation of
+    /// [`IngesterMapping::table_candidates()`] for the same table.
+    pub fn partition_owners(&self, table_id: TableId, partition_key: &PartitionKey) -> Vec<&T> {
+        let mut candidates = self.table_candidates(table_id);
+        if candidates.is_empty() {
+            return candidates;
+        }
+
+        let offset = stable_hash(&[
+            &table_id.get().to_be_bytes(),
+            partition_key.inner().as_bytes(),
+        ]) % candidates.len() as u64;
+
+        candidates.rotate_left(offset as usize);
+        candidates
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{HashMap, HashSet};
+
+    use super::*;
+
+    fn mapping(n: usize, spread: usize) -> IngesterMapping<String> {
+        IngesterMapping::new(
+            (0..n).map(|i| {
+                let name = format!("http://ingester-{i}:8083");
+                (name.clone(), name)
+            }),
+            NonZeroUsize::new(spread).unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_stable_hash() {
+        // The hash must never change between releases, or routers and queriers
+        // running different versions will disagree.
+        assert_eq!(stable_hash(&[b"bananas"]), 0x7e39_5828_dc9f_a913);
+        assert_ne!(
+            stable_hash(&[b"ab", b"c"]),
+            stable_hash(&[b"a", b"bc"]),
+            "parts must be delimited"
+        );
+    }
+
+    #[test]
+    fn test_spread_clamped() {
+        let m = mapping(2, 5);
+        assert_eq!(m.table_candidates(TableId::new(1)).len(), 2);
+
+        let m = mapping(0, 3);
+        assert!(m.is_empty());
+        assert!(m.table_candidates(TableId::new(1)).is_empty());
+        assert!(m
+            .partition_owners(TableId::new(1), &PartitionKey::from("2023-01-01"))
+            .is_empty());
+    }
+
+    #[test]
+    fn test_deterministic() {
+        let a = mapping(10, 3);
+
+        // Construct the same mapping with the nodes in reverse order.
+        let b = IngesterMapping::new(
+            (0..10).rev().map(|i| {
+                let name = format!("http://ingester-{i}:8083");
+                (name.clone(), name)
+            }),
+            NonZeroUsize::new(3).unwrap(),
+        );
+
+        for t in 0..100 {
+            let table_id = TableId::new(t);
+            assert_eq!(a.table_candidates(table_id), b.table_candidates(table_id));
+
+            let key = PartitionKey::from(format!("2023-01-{:02}", t % 28 + 1));
+            assert_eq!(
+                a.partition_owners(table_id, &key),
+                b.partition_owners(table_id, &key)
+            );
+        }
+    }
+
+    #[test]
+    fn test_partition_owners_subset_of_candidates() {
+        let m = mapping(10, 3);
+        let table_id = TableId::new(42);
+        let candidates = m
+            .table_candidates(table_id)
+            .into_iter()
+            .collect::<HashSet<_>>();
+
+        let mut first_owners = HashSet::new();
+        for d in 1..=28 {
+            let key = PartitionKey::from(format!("2023-01-{d:02}"));
+            let owners = m.partition_owners(table_id, &key);
+            assert_eq!(owners.len(), 3);
+            assert_eq!(owners.iter().copied().collect::<HashSet<_>>(), candidates);
+            first_owners.insert(owners[0]);
+        }
+
+        // Partitions are spread across all candidates.
+        assert_eq!(first_owners, candidates);
+    }
+
+    #[test]
+    fn test_distribution() {
+        let m = mapping(5, 1);
+
+        let mut counts = HashMap::<&String, usize>::new();
+        for t in 0..1_000 {
+            *counts
+                .entry(m.table_candidates(TableId::new(t))[0])
+                .or_default() += 1;
+        }
+
+        assert_eq!(counts.len(), 5);
+        for (node, n) in counts {
+            assert!((120..=280).contains(&n), "{node} owns {n} tables");
+        }
+    }
+
+    #[test]
+    fn test_node_removal_stability() {
+        let before = mapping(6, 2);
+        // Remove ingester-5.
+        let after = mapping(5, 2);
+        let removed = "http://ingester-5:8083".to_string();
+
+        for t in 0..500 {
+            let table_id = TableId::new(t);
+            let old = before.table_candidates(table_id);
+            let new = after.table_candidates(table_id);
+
+            if !old.contains(&&removed) {
+                // Tables without the removed node as a candidate are unaffected.
+                assert_eq!(old, new);
+            } else {
+                // Otherwise the surviving candidate is retained.
+                for n in old.into_iter().filter(|v| **v != removed) {
+                    assert!(new.contains(&n));
+                }
+            }
+        }
+    }
+}
@@ -21,6 +21,7 @@ use workspace_hack as _;
 
 mod columns;
 pub use columns::*;
+pub mod ingester_mapping;
 mod namespace_name;
 pub use namespace_name::*;
 pub mod partition_template;
@@ -33,6 +34,7 @@ pub use service_limits::*;
 
 use observability_deps::tracing::warn;
 use schema::TIME_COLUMN_NAME;
+use sha2::{Digest, Sha256};
 use std::{
     borrow::Borrow,
     collections::{BTreeMap, BTreeSet, HashMap},
@@ -162,6 +164,28 @@ impl std::fmt::Display for TableId {
     }
 }
 
+/// Unique ID for a `Task`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, sqlx::Type)]
+#[sqlx(transparent)]
+pub struct TaskId(i64);
+
+#[allow(missing_docs)]
+impl TaskId {
+    pub const fn new(v: i64) -> Self {
+        Self(v)
+    }
+
+    pub fn get(&self) -> i64 {
+        self.0
+    }
+}
+
+impl std::fmt::Display for TaskId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
 /// A sequence number from an ingester
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct SequenceNumber(u64);
@@ -292,6 +316,11 @@ pub struct Namespace {
     /// The partition template to use for new tables in this namespace either created implicitly or
     /// created without specifying a partition template.
     pub partition_template: NamespacePartitionTemplateOverride,
+    /// When true, writes to this namespace are rejected while queries continue to be served.
+    pub read_only: bool,
+    /// The name of the object store this namespace's parquet files are stored in, or [`None`]
+    /// for the default object store.
+    pub object_store_name: Option<String>,
 }
 
 /// Schema collection for a namespace. This is an in-memory object useful for a schema
@@ -515,6 +544,219 @@ pub struct SkippedCompaction {
     pub limit_num_files_first_in_partition: i64,
 }
 
+/// The number of rows and bytes ingested into a table within a one hour
+/// window, as reported by the ingesters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, sqlx::FromRow)]
+pub struct TableUsage {
+    /// the namespace of the table
+    pub namespace_id: NamespaceId,
+    /// the table the usage was recorded for
+    pub table_id: TableId,
+    /// the start of the hour the usage was recorded in, always a multiple of
+    /// [`TableUsage::WINDOW`]
+    pub hour_start: Timestamp,
+    /// the number of rows buffered in the ingesters
+    pub rows_buffered: i64,
+    /// the (approximate, in-memory) byte size of the buffered rows
+    pub bytes_buffered: i64,
+    /// the number of rows persisted to parquet files
+    pub rows_persisted: i64,
+    /// the byte size of the persisted parquet files
+    pub bytes_persisted: i64,
+}
+
+impl TableUsage {
+    /// The duration of time usage is aggregated over.
+    pub const WINDOW: std::time::Duration = std::time::Duration::from_secs(60 * 60);
+
+    /// Return the start of the usage window containing `t`.
+    pub fn window_start(t: Timestamp) -> Timestamp {
+        let window = Self::WINDOW.as_nanos() as i64;
+        Timestamp::new(t.get() - t.get().rem_euclid(window))
+    }
+}
+
+/// A column of the view of a table, resolved by the querier at planning time.
+///
+/// A view column exposes an expression over the columns of the underlying
+/// table under a new name. This allows renaming, relabelling the type of (via
+/// `CAST`) and computing columns without rewriting persisted data.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, sqlx::FromRow)]
+pub struct TableViewColumn {
+    /// the table the view column belongs to
+    pub table_id: TableId,
+    /// the name of the column within the view
+    pub name: String,
+    /// the SQL expression over the columns of the table computing the value
+    /// of this column, for example `"temp"` or `"temp" * 1.8 + 32`
+    pub expression: String,
+    /// an optional annotation of the unit of the values of this column
+    pub unit: Option<String>,
+}
+
+impl TableViewColumn {
+    /// Estimated size in bytes of this column, including `self`.
+    pub fn size(&self) -> usize {
+        std::mem::size_of_val(self)
+            + self.name.capacity()
+            + self.expression.capacity()
+            + self.unit.as_ref().map(|u| u.capacity()).unwrap_or_default()
+    }
+}
+
+/// A retention period of a single field column, shorter than that of the rest
+/// of its table.
+///
+/// The values of the column in data older than the column retention period are
+/// dropped by the compactor, while the rest of the rows are kept for the
+/// retention period of the namespace.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, sqlx::FromRow)]
+pub struct ColumnRetention {
+    /// the table the column belongs to
+    pub table_id: TableId,
+    /// the column the retention period applies to
+    pub column_id: ColumnId,
+    /// the retention period of the values of the column, in nanoseconds
+    pub retention_period_ns: i64,
+}
+
+/// A tag added by the routers to every row written to a namespace.
+///
+/// Default tags label all the data of a namespace (for example with
+/// `env=prod`) without changing the writers.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, sqlx::FromRow)]
+pub struct NamespaceDefaultTag {
+    /// the namespace the tag is added to
+    pub namespace_id: NamespaceId,
+    /// the name of the tag column
+    pub name: String,
+    /// the value of the tag
+    pub value: String,
+}
+
+impl NamespaceDefaultTag {
+    /// Estimated size in bytes of this tag, including `self`.
+    pub fn size(&self) -> usize {
+        std::mem::size_of_val(self) + self.name.capacity() + self.value.capacity()
+    }
+}
+
+/// A completed query recorded in the query history of a namespace, and the
+/// resources used to execute it.
+///
+/// The text of the query is not stored, only a hash identifying repeated
+/// executions of the same query.
+#[derive(Debug, Clone, PartialEq, Eq, sqlx::FromRow)]
+pub struct QueryHistoryEntry {
+    /// the namespace the query was executed against
+    pub namespace_id: NamespaceId,
+    /// when the query was issued
+    pub issue_time: Timestamp,
+    /// the type of the query, for example `sql` or `influxql`
+    pub query_type: String,
+    /// the hash of the query text, see [`QueryHistoryEntry::hash_query_text()`]
+    pub query_text_hash: i64,
+    /// the time taken to execute the query and return its results
+    pub duration_ns: i64,
+    /// whether the query completed successfully
+    pub success: bool,
+    /// the number of partitions scanned, if recorded
+    pub partitions_scanned: Option<i64>,
+    /// the number of bytes read from object storage, if recorded
+    pub bytes_scanned: Option<i64>,
+    /// the number of rows returned to the client, if recorded
+    pub rows_returned: Option<i64>,
+    /// the peak memory used to execute the query, if recorded
+    pub peak_memory_bytes: Option<i64>,
+}
+
+impl QueryHistoryEntry {
+    /// Hash `query_text` into the stable identifier stored as
+    /// [`QueryHistoryEntry::query_text_hash`].
+    pub fn hash_query_text(query_text: &str) -> i64 {
+        let digest = Sha256::digest(query_text.as_bytes());
+        i64::from_be_bytes(digest[..8].try_into().expect("digest is 32 bytes"))
+    }
+}
+
+/// A task periodically executing a SQL query against a namespace, writing the
+/// results into a destination table of the same namespace.
+#[derive(Debug, Clone, PartialEq, Eq, sqlx::FromRow)]
+pub struct Task {
+    /// the id of the task
+    pub id: TaskId,
+    /// the namespace the query is executed against and the results written to
+    pub namespace_id: NamespaceId,
+    /// the name of the task, unique within the namespace
+    pub name: String,
+    /// the cron expression (`<minute> <hour> <day of month> <month> <day of
+    /// week>`, in UTC) of the times the task runs at
+    pub schedule: String,
+    /// the SQL query whose results are written to the destination table
+    pub query: String,
+    /// the name of the table the results are written to
+    pub destination_table: String,
+    /// the number of times a failed run is retried before it is recorded as
+    /// failed
+    pub max_retries: i32,
+    /// when the task was created
+    pub created_at: Timestamp,
+}
+
+/// The state of a run of a [`Task`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TaskRunStatus {
+    /// The run has started but not finished.
+    Running,
+    /// The results of the run were written to the destination table.
+    Succeeded,
+    /// All the attempts of the run failed.
+    Failed,
+}
+
+impl std::fmt::Display for TaskRunStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Running => write!(f, "running"),
+            Self::Succeeded => write!(f, "succeeded"),
+            Self::Failed => write!(f, "failed"),
+        }
+    }
+}
+
+/// A run of a [`Task`], for the time it was scheduled at.
+///
+/// Recording the start of the run for a scheduled time claims it, so that a
+/// scheduled run is executed once even if several queriers schedule it.
+#[derive(Debug, Clone, PartialEq, Eq, sqlx::FromRow)]
+pub struct TaskRun {
+    /// the task the run belongs to
+    pub task_id: TaskId,
+    /// the time the run was scheduled at, or requested at for manual runs
+    pub scheduled_at: Timestamp,
+    /// when the run started
+    pub started_at: Timestamp,
+    /// when the run finished, or NULL while it is running
+    pub finished_at: Option<Timestamp>,
+    /// the number of times the query was executed
+    pub attempts: i32,
+    /// the number of rows written to the destination table
+    pub rows_written: i64,
+    /// the error of the last attempt of a failed run
+    pub error: Option<String>,
+}
+
+impl TaskRun {
+    /// The state of this run.
+    pub fn status(&self) -> TaskRunStatus {
+        match (self.finished_at, &self.error) {
+            (None, _) => TaskRunStatus::Running,
+            (Some(_), None) => TaskRunStatus::Succeeded,
+            (Some(_), Some(_)) => TaskRunStatus::Failed,
+        }
+    }
+}
+
 use generated_types::influxdata::iox::compactor::v1 as compactor_proto;
 impl From<SkippedCompaction> for compactor_proto::SkippedCompaction {
     fn from(skipped_compaction: SkippedCompaction) -> Self {
@@ -763,6 +1005,8 @@ pub struct ParquetFileParams {
     pub column_set: ColumnSet,
     /// the max of created_at of all L0 files
     pub max_l0_created_at: Timestamp,
+    /// statistics of the string and tag columns in this file
+    pub column_statistics: Vec<ParquetFileColumnStatistics>,
 }
 
 impl From<ParquetFile> for ParquetFileParams {
@@ -780,8 +1024,106 @@ impl From<ParquetFile> for ParquetFileParams {
             created_at: value.created_at,
             column_set: value.column_set,
             max_l0_created_at: value.max_l0_created_at,
+            column_statistics: vec![],
+        }
+    }
+}
+
+/// The maximum number of characters of the min/max values of a
+/// [`ParquetFileColumnStatistics`].
+pub const MAX_COLUMN_STATISTICS_STRING_LENGTH: usize = 64;
+
+/// The min/max values and null count of a string or tag column of a parquet
+/// file, stored in the catalog to allow pruning files without reading their
+/// footers.
+///
+/// The min/max values are bounds: they are truncated to
+/// [`MAX_COLUMN_STATISTICS_STRING_LENGTH`] characters such that all values of
+/// the column still lie within them, and are `None` if unknown. They never
+/// contain NUL characters, which cannot be stored in a Postgres `TEXT`
+/// column - a value containing one is bounded by the text preceding it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParquetFileColumnStatistics {
+    /// the column these statistics apply to
+    pub column_id: ColumnId,
+    /// a lower bound of the non-null values of the column
+    pub min_value: Option<String>,
+    /// an upper bound of the non-null values of the column
+    pub max_value: Option<String>,
+    /// the number of null values of the column
+    pub null_count: i64,
+}
+
+impl ParquetFileColumnStatistics {
+    /// Create statistics for `column_id` from the exact `min` and `max`
+    /// values of the column, truncating them to
+    /// [`MAX_COLUMN_STATISTICS_STRING_LENGTH`] characters and before any NUL
+    /// character.
+    pub fn new(column_id: ColumnId, min: Option<&str>, max: Option<&str>, null_count: u64) -> Self {
+        Self {
+            column_id,
+            min_value: min.map(truncate_min),
+            max_value: max.and_then(truncate_max),
+            null_count: null_count as i64,
         }
     }
+
+    /// Estimate the memory consumption of this object and its contents
+    pub fn size(&self) -> usize {
+        std::mem::size_of_val(self)
+            + self
+                .min_value
+                .as_ref()
+                .map(|v| v.capacity())
+                .unwrap_or_default()
+            + self
+                .max_value
+                .as_ref()
+                .map(|v| v.capacity())
+                .unwrap_or_default()
+    }
+}
+
+/// A truncated prefix of `v` is smaller than or equal to `v`, and therefore
+/// remains a valid lower bound. The prefix ends before the first NUL.
+fn truncate_min(v: &str) -> String {
+    v.chars()
+        .take_while(|c| *c != '\0')
+        .take(MAX_COLUMN_STATISTICS_STRING_LENGTH)
+        .collect()
+}
+
+/// Replacing the last character of a truncated prefix of `v` with
+/// [`char::MAX`] forms a value greater than `v`, unless that character
+/// already is [`char::MAX`] - in which case no bound is returned.
+///
+/// A NUL within the truncated prefix is the smallest character, so replacing
+/// it with `\u{1}` and dropping the remainder also forms a greater value.
+fn truncate_max(v: &str) -> Option<String> {
+    let mut chars = v
+        .chars()
+        .take(MAX_COLUMN_STATISTICS_STRING_LENGTH + 1)
+        .collect::<Vec<_>>();
+    if let Some(nul) = chars
+        .iter()
+        .take(MAX_COLUMN_STATISTICS_STRING_LENGTH)
+        .position(|c| *c == '\0')
+    {
+        chars.truncate(nul);
+        chars.push('\u{1}');
+        return Some(chars.into_iter().collect());
+    }
+    if chars.len() <= MAX_COLUMN_STATISTICS_STRING_LENGTH {
+        return Some(v.to_string());
+    }
+
+    chars.truncate(MAX_COLUMN_STATISTICS_STRING_LENGTH);
+    let last = chars.last_mut()?;
+    if *last == char::MAX {
+        return None;
+    }
+    *last = char::MAX;
+    Some(chars.into_iter().collect())
 }
 
 /// ID of a chunk.
@@ -2735,6 +3077,67 @@ mod tests {
         assert_eq!(tr.end(), 1);
     }
 
+    #[test]
+    fn test_table_usage_window_start() {
+        const HOUR: i64 = 60 * 60 * 1_000_000_000;
+
+        assert_eq!(TableUsage::window_start(Timestamp::new(0)).get(), 0);
+        assert_eq!(TableUsage::window_start(Timestamp::new(HOUR - 1)).get(), 0);
+        assert_eq!(TableUsage::window_start(Timestamp::new(HOUR)).get(), HOUR);
+        assert_eq!(
+            TableUsage::window_start(Timestamp::new(3 * HOUR + 42)).get(),
+            3 * HOUR
+        );
+        // Timestamps before the epoch round down, not towards zero.
+        assert_eq!(TableUsage::window_start(Timestamp::new(-1)).get(), -HOUR);
+    }
+
+    #[test]
+    fn test_parquet_file_column_statistics_truncation() {
+        let id = ColumnId::new(1);
+
+        let stats = ParquetFileColumnStatistics::new(id, Some("a"), Some("b"), 2);
+        assert_eq!(stats.min_value.as_deref(), Some("a"));
+        assert_eq!(stats.max_value.as_deref(), Some("b"));
+        assert_eq!(stats.null_count, 2);
+
+        let long = "x".repeat(MAX_COLUMN_STATISTICS_STRING_LENGTH + 1);
+        let stats = ParquetFileColumnStatistics::new(id, Some(&long), Some(&long), 0);
+        let min = stats.min_value.unwrap();
+        let max = stats.max_value.unwrap();
+        assert_eq!(min.chars().count(), MAX_COLUMN_STATISTICS_STRING_LENGTH);
+        assert_eq!(max.chars().count(), MAX_COLUMN_STATISTICS_STRING_LENGTH);
+        assert!(min.as_str() <= long.as_str());
+        assert!(max.as_str() >= long.as_str());
+
+        // The truncated max cannot be bumped past a trailing char::MAX.
+        let long = char::MAX
+            .to_string()
+            .repeat(MAX_COLUMN_STATISTICS_STRING_LENGTH + 1);
+        let stats = ParquetFileColumnStatistics::new(id, Some(&long), Some(&long), 0);
+        assert!(stats.min_value.is_some());
+        assert_eq!(stats.max_value, None);
+
+        // Values are bounded before any NUL character.
+        let stats = ParquetFileColumnStatistics::new(id, Some("ab\0c"), Some("ab\0c"), 0);
+        assert_eq!(stats.min_value.as_deref(), Some("ab"));
+        assert_eq!(stats.max_value.as_deref(), Some("ab\u{1}"));
+        assert!(stats.max_value.unwrap().as_str() > "ab\0c");
+
+        let stats = ParquetFileColumnStatistics::new(id, Some("\0"), Some("\0"), 0);
+        assert_eq!(stats.min_value.as_deref(), Some(""));
+        assert_eq!(stats.max_value.as_deref(), Some("\u{1}"));
+
+        // A NUL beyond the truncated prefix does not affect the bounds.
+        let mut long = "x".repeat(MAX_COLUMN_STATISTICS_STRING_LENGTH);
+        long.push('\0');
+        let stats = ParquetFileColumnStatistics::new(id, Some(&long), Some(&long), 0);
+        assert_eq!(stats.min_value.as_deref(), Some(&long[..long.len() - 1]));
+        let max = stats.max_value.unwrap();
+        assert!(!max.contains('\0'));
+        assert!(max.as_str() > long.as_str());
+    }
+
     use crate::partition::tests::arbitrary_partition_id;
 
     prop_compose! {
@@ -100,6 +100,33 @@
 //! Together with the above value truncation, this bounds the maximum length of
 //! a partition key to 1,607 bytes (1.57 KiB).
 //!
+//! ## Bucketing
+//!
+//! A [`TemplatePart::Bucket`] part hashes the value of a tag and renders the
+//! bucket number it maps to, in the range `[0, num_buckets)` - for example,
+//! the template `[TimeFormat("%Y-%m-%d"), Bucket("host", 4)]` spreads each
+//! day of data across 4 partitions, `2023-01-01|0` to `2023-01-01|3`.
+//!
+//! This allows the data of a hot table, that would otherwise all land in the
+//! same (current) partition, to be buffered, persisted and compacted in
+//! parallel. The number of buckets is limited to
+//! [`MAXIMUM_NUMBER_OF_BUCKETS`].
+//!
+//! The bucket for a value is derived using [`bucket_for_tag_value()`], a
+//! stable hash that MUST NOT change, as the values of existing partitions were
+//! derived from it.
+//!
+//! Because the same tag value always maps to the same bucket, all rows of a
+//! series are always written to the same bucket of the (otherwise identical)
+//! partition key - the buckets of a time range contain disjoint sets of
+//! series, and can be queried as a single, logical partition without
+//! deduplicating rows across them. A bucket key part does not yield a column
+//! value from [`build_column_values()`], but the remaining parts of the key
+//! remain available for pruning.
+//!
+//! If the row does not contain the tag, a single `!` is inserted as for a
+//! missing [`TemplatePart::TagValue`].
+//!
 //! ### Reserved Characters
 //!
 //! Reserved characters that are percent encoded (in addition to non-ASCII
@@ -158,6 +185,7 @@ use generated_types::influxdata::iox::partition_template::v1 as proto;
 use once_cell::sync::Lazy;
 use percent_encoding::{percent_decode_str, AsciiSet, CONTROLS};
 use schema::TIME_COLUMN_NAME;
+use sha2::{Digest, Sha256};
 use std::{borrow::Cow, sync::Arc};
 use thiserror::Error;
 
@@ -193,6 +221,16 @@ pub enum ValidationError {
     /// [`TagValue`]: [`proto::template_part::Part::TagValue`]
     #[error("invalid tag value in partition template: {0}")]
     InvalidTagValue(String),
+
+    /// The partition template defines a [`Bucket`] part, but the number of
+    /// buckets is outside of the valid range.
+    ///
+    /// [`Bucket`]: [`proto::template_part::Part::Bucket`]
+    #[error(
+        "invalid number of buckets in partition template: {0} \
+        (must be between 1 and {MAXIMUM_NUMBER_OF_BUCKETS})"
+    )]
+    InvalidNumberOfBuckets(u32),
 }
 
 /// The maximum number of template parts a custom partition template may specify, to limit the
@@ -200,6 +238,10 @@ pub enum ValidationError {
 /// created with it.
 pub const MAXIMUM_NUMBER_OF_TEMPLATE_PARTS: usize = 8;
 
+/// The maximum number of buckets a [`TemplatePart::Bucket`] part may
+/// distribute tag values across.
+pub const MAXIMUM_NUMBER_OF_BUCKETS: u32 = 100_000;
+
 /// The sentinel character used to delimit partition key parts in the partition
 /// key string.
 pub const PARTITION_KEY_DELIMITER: char = '|';
@@ -253,6 +295,27 @@ pub const ENCODED_PARTITION_KEY_CHARS: AsciiSet = CONTROLS
 pub enum TemplatePart<'a> {
     TagValue(&'a str),
     TimeFormat(&'a str),
+    /// The tag name, and number of buckets its values are hashed into.
+    Bucket(&'a str, u32),
+}
+
+/// Return the bucket in the range `[0, num_buckets)` the tag `value` maps to
+/// for a [`TemplatePart::Bucket`] part.
+///
+/// The output of this function is stable across processes, builds and
+/// releases, and MUST NOT change - doing so would cause the rows of existing
+/// series to be written to a different partition.
+///
+/// # Panics
+///
+/// Panics if `num_buckets` is 0.
+pub fn bucket_for_tag_value(value: &str, num_buckets: u32) -> u32 {
+    assert_ne!(num_buckets, 0, "bucket count must be non-zero");
+
+    let digest = Sha256::digest(value.as_bytes());
+    let hash = u64::from_be_bytes(digest[..8].try_into().expect("sha256 digest is 32 bytes"));
+
+    (hash % num_buckets as u64) as u32
 }
 
 /// The default partitioning scheme is by each day according to the "time" column.
@@ -344,6 +407,9 @@ impl TablePartitionTemplateOverride {
             .map(|part| match part {
                 proto::template_part::Part::TagValue(value) => TemplatePart::TagValue(value),
                 proto::template_part::Part::TimeFormat(fmt) => TemplatePart::TimeFormat(fmt),
+                proto::template_part::Part::Bucket(b) => {
+                    TemplatePart::Bucket(&b.tag_name, b.num_buckets)
+                }
             })
     }
 
@@ -370,6 +436,9 @@ impl TablePartitionTemplateOverride {
                                     .map(|part| match part {
                                         proto::template_part::Part::TagValue(s) => s.capacity(),
                                         proto::template_part::Part::TimeFormat(s) => s.capacity(),
+                                        proto::template_part::Part::Bucket(b) => {
+                                            b.tag_name.capacity()
+                                        }
                                     })
                                     .unwrap_or_default()
                             })
@@ -389,7 +458,10 @@ impl TablePartitionTemplateOverride {
 /// `TablePartitionTemplateOverride` types. It's an internal implementation detail to minimize code
 /// duplication.
 mod serialization {
-    use super::{ValidationError, MAXIMUM_NUMBER_OF_TEMPLATE_PARTS, TAG_VALUE_KEY_TIME};
+    use super::{
+        ValidationError, MAXIMUM_NUMBER_OF_BUCKETS, MAXIMUM_NUMBER_OF_TEMPLATE_PARTS,
+        TAG_VALUE_KEY_TIME,
+    };
     use chrono::{format::StrftimeItems, Utc};
     use generated_types::influxdata::iox::partition_template::v1 as proto;
     use std::{fmt::Write, sync::Arc};
@@ -480,6 +552,25 @@ mod serialization {
                             )));
                         }
                     }
+                    Some(proto::template_part::Part::Bucket(bucket)) => {
+                        // The same restrictions as a tag value part apply to
+                        // the bucketed tag.
+                        if bucket.tag_name.is_empty() {
+                            return Err(ValidationError::InvalidTagValue(bucket.tag_name.clone()));
+                        }
+
+                        if bucket.tag_name.contains(TAG_VALUE_KEY_TIME) {
+                            return Err(ValidationError::InvalidTagValue(format!(
+                                "{TAG_VALUE_KEY_TIME} cannot be used"
+                            )));
+                        }
+
+                        if !(1..=MAXIMUM_NUMBER_OF_BUCKETS).contains(&bucket.num_buckets) {
+                            return Err(ValidationError::InvalidNumberOfBuckets(
+                                bucket.num_buckets,
+                            ));
+                        }
+                    }
                     None => {}
                 }
             }
@@ -634,6 +725,9 @@ pub fn build_column_values<'a>(
             TemplatePart::TimeFormat(format) => {
                 Some((TIME_COLUMN_NAME, parse_part_time_format(value, format)?))
             }
+            // The tag value cannot be recovered from the bucket it was hashed
+            // into.
+            TemplatePart::Bucket(..) => None,
         })
 }
 
@@ -705,6 +799,7 @@ fn parse_part_time_format(value: &str, format: &str) -> Option<ColumnValue<'stat
                     Numeric::Year => Some(begin + Months::new(12)),
                     Numeric::Month => Some(begin + Months::new(1)),
                     Numeric::Day => Some(begin + Days::new(1)),
+                    Numeric::Hour => Some(begin + chrono::Duration::hours(1)),
                     _ => {
                         // not supported
                         return None;
@@ -800,6 +895,12 @@ pub fn test_table_partition_override(
             let part = match part {
                 TemplatePart::TagValue(value) => proto::template_part::Part::TagValue(value.into()),
                 TemplatePart::TimeFormat(fmt) => proto::template_part::Part::TimeFormat(fmt.into()),
+                TemplatePart::Bucket(tag_name, num_buckets) => {
+                    proto::template_part::Part::Bucket(proto::template_part::Bucket {
+                        tag_name: tag_name.into(),
+                        num_buckets,
+                    })
+                }
             };
 
             proto::TemplatePart { part: Some(part) }
@@ -947,6 +1048,51 @@ mod tests {
         assert_error!(err, ValidationError::InvalidTagValue(ref value) if value.is_empty());
     }
 
+    #[test]
+    fn bucket_validation() {
+        let bucket = |tag_name: &str, num_buckets| proto::PartitionTemplate {
+            parts: vec![proto::TemplatePart {
+                part: Some(proto::template_part::Part::Bucket(
+                    proto::template_part::Bucket {
+                        tag_name: tag_name.into(),
+                        num_buckets,
+                    },
+                )),
+            }],
+        };
+
+        serialization::Wrapper::try_from(bucket("host", 1)).expect("valid template");
+        serialization::Wrapper::try_from(bucket("host", MAXIMUM_NUMBER_OF_BUCKETS))
+            .expect("valid template");
+
+        let err = serialization::Wrapper::try_from(bucket("host", 0));
+        assert_error!(err, ValidationError::InvalidNumberOfBuckets(0));
+
+        let err = serialization::Wrapper::try_from(bucket("host", MAXIMUM_NUMBER_OF_BUCKETS + 1));
+        assert_error!(err, ValidationError::InvalidNumberOfBuckets(_));
+
+        let err = serialization::Wrapper::try_from(bucket("", 4));
+        assert_error!(err, ValidationError::InvalidTagValue(_));
+
+        let err = serialization::Wrapper::try_from(bucket("time", 4));
+        assert_error!(err, ValidationError::InvalidTagValue(_));
+    }
+
+    #[test]
+    fn test_bucket_for_tag_value() {
+        // The bucket assignment must never change between releases.
+        assert_eq!(bucket_for_tag_value("bananas", 10), 2);
+        assert_eq!(bucket_for_tag_value("bananas", 1), 0);
+
+        // Values are distributed across all buckets.
+        let mut seen = [false; 8];
+        for i in 0..1_000 {
+            let b = bucket_for_tag_value(&format!("host-{i}"), 8);
+            seen[b as usize] = true;
+        }
+        assert!(seen.iter().all(|v| *v));
+    }
+
     fn identity(s: &str) -> ColumnValue<'_> {
         ColumnValue::Identity(s.into())
     }
@@ -995,6 +1141,17 @@ mod tests {
         };
     }
 
+    test_build_column_values!(
+        bucket,
+        template = [
+            TemplatePart::TimeFormat("%Y"),
+            TemplatePart::Bucket("a", 10),
+            TemplatePart::TagValue("b"),
+        ],
+        partition_key = "2023|5|bananas",
+        want = [(TIME_COLUMN_NAME, year(2023)), ("b", identity("bananas")),]
+    );
+
     test_build_column_values!(
         module_doc_example_1,
         template = [
@@ -1228,9 +1385,35 @@ mod tests {
     );
 
     test_build_column_values!(
-        datetime_range_unimplemented_y_m_d_h,
+        datetime_range_y_m_d_h,
         template = [TemplatePart::TimeFormat("%Y-%m-%dT%H"),],
-        partition_key = "2023-12-31T00",
+        partition_key = "2023-09-01T13",
+        want = [(
+            TIME_COLUMN_NAME,
+            ColumnValue::Datetime {
+                begin: Utc.with_ymd_and_hms(2023, 9, 1, 13, 0, 0).unwrap(),
+                end: Utc.with_ymd_and_hms(2023, 9, 1, 14, 0, 0).unwrap(),
+            },
+        )]
+    );
+
+    test_build_column_values!(
+        datetime_range_y_m_d_h_overflow_year,
+        template = [TemplatePart::TimeFormat("%Y-%m-%dT%H"),],
+        partition_key = "2023-12-31T23",
+        want = [(
+            TIME_COLUMN_NAME,
+            ColumnValue::Datetime {
+                begin: Utc.with_ymd_and_hms(2023, 12, 31, 23, 0, 0).unwrap(),
+                end: Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap(),
+            },
+        )]
+    );
+
+    test_build_column_values!(
+        datetime_not_compact_y_m_h,
+        template = [TemplatePart::TimeFormat("%Y-%m-%H"),],
+        partition_key = "2023-12-13",
         want = []
     );
 
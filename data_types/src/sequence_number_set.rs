@@ -55,6 +55,16 @@ impl SequenceNumberSet {
         self.0.is_empty()
     }
 
+    /// Returns the smallest [`SequenceNumber`] in this set, if any.
+    pub fn min(&self) -> Option<SequenceNumber> {
+        self.0.minimum().map(SequenceNumber::new)
+    }
+
+    /// Returns the largest [`SequenceNumber`] in this set, if any.
+    pub fn max(&self) -> Option<SequenceNumber> {
+        self.0.maximum().map(SequenceNumber::new)
+    }
+
     /// Return an iterator of all [`SequenceNumber`] in this set.
     pub fn iter(&self) -> impl Iterator<Item = SequenceNumber> + '_ {
         self.0.iter().map(|v| SequenceNumber::new(v as _))
@@ -125,6 +135,8 @@ mod tests {
         assert_eq!(a.len(), 2);
         assert!(a.contains(SequenceNumber::new(1)));
         assert!(a.contains(SequenceNumber::new(2)));
+        assert_eq!(a.max(), Some(SequenceNumber::new(2)));
+        assert_eq!(a.min(), Some(SequenceNumber::new(1)));
 
         // Removing the set should return it to the pre-merged state.
         a.remove_set(&b);
@@ -138,6 +150,8 @@ mod tests {
         // Removing the last element should result in an empty set.
         a.remove(SequenceNumber::new(1));
         assert_eq!(a.len(), 0);
+        assert_eq!(a.max(), None);
+        assert_eq!(a.min(), None);
     }
 
     #[test]
@@ -26,6 +26,7 @@ use workspace_hack as _;
 use crate::{
     objectstore::{checker as os_checker, deleter as os_deleter, lister as os_lister},
     parquetfile::deleter as pf_deleter,
+    queryhistory::deleter as qh_deleter,
     retention::flagger as retention_flagger,
 };
 
@@ -43,6 +44,8 @@ use tokio_util::sync::CancellationToken;
 mod objectstore;
 /// Logic for deleting parquet files from the catalog
 mod parquetfile;
+/// Logic for deleting query history entries from the catalog
+mod queryhistory;
 /// Logic for flagging parquet files for deletion based on retention settings
 mod retention;
 
@@ -60,6 +63,7 @@ pub struct GarbageCollector {
     os_checker: tokio::task::JoinHandle<Result<(), os_checker::Error>>,
     os_deleter: tokio::task::JoinHandle<Result<(), os_deleter::Error>>,
     pf_deleter: tokio::task::JoinHandle<Result<(), pf_deleter::Error>>,
+    qh_deleter: tokio::task::JoinHandle<Result<(), qh_deleter::Error>>,
     retention_flagger: tokio::task::JoinHandle<Result<(), retention_flagger::Error>>,
 }
 
@@ -85,6 +89,8 @@ impl GarbageCollector {
             objectstore_sleep_interval_minutes = %sub_config.objectstore_sleep_interval_minutes,
             parquetfile_sleep_interval_minutes = %sub_config.parquetfile_sleep_interval_minutes,
             retention_sleep_interval_minutes = %sub_config.retention_sleep_interval_minutes,
+            query_history_cutoff = ?sub_config.query_history_cutoff.map(|d| format_duration(d).to_string()),
+            query_history_sleep_interval_minutes = %sub_config.query_history_sleep_interval_minutes,
             "GarbageCollector starting"
         );
 
@@ -162,6 +168,15 @@ impl GarbageCollector {
             sub_config.parquetfile_sleep_interval_minutes,
         ));
 
+        // Initialise the query history deleter, which is just one thread that deletes the
+        // entries older than the configured cutoff (if any) from the catalog then sleeps.
+        let qh_deleter = tokio::spawn(qh_deleter::perform(
+            shutdown.clone(),
+            Arc::clone(&catalog),
+            sub_config.query_history_cutoff,
+            sub_config.query_history_sleep_interval_minutes,
+        ));
+
         // Initialise the retention code, which is just one thread that calls
         // flag_for_delete_by_retention() on the catalog then sleeps.
         let retention_flagger = tokio::spawn(retention_flagger::perform(
@@ -177,6 +192,7 @@ impl GarbageCollector {
             os_checker,
             os_deleter,
             pf_deleter,
+            qh_deleter,
             retention_flagger,
         })
     }
@@ -196,19 +212,22 @@ impl GarbageCollector {
             os_checker,
             os_deleter,
             pf_deleter,
+            qh_deleter,
             retention_flagger,
             shutdown: _,
         } = self;
 
-        let (os_lister, os_checker, os_deleter, pf_deleter, retention_flagger) = futures::join!(
+        let (os_lister, os_checker, os_deleter, pf_deleter, qh_deleter, retention_flagger) = futures::join!(
             os_lister,
             os_checker,
             os_deleter,
             pf_deleter,
+            qh_deleter,
             retention_flagger
         );
 
         retention_flagger.context(ParquetFileDeleterPanicSnafu)??;
+        qh_deleter.context(QueryHistoryDeleterPanicSnafu)??;
         pf_deleter.context(ParquetFileDeleterPanicSnafu)??;
         os_deleter.context(ObjectStoreDeleterPanicSnafu)??;
         os_checker.context(ObjectStoreCheckerPanicSnafu)??;
@@ -269,6 +288,12 @@ pub enum Error {
     #[snafu(display("The parquet file deleter task panicked"))]
     ParquetFileDeleterPanic { source: tokio::task::JoinError },
 
+    #[snafu(display("The query history deleter task failed"))]
+    #[snafu(context(false))]
+    QueryHistoryDeleter { source: qh_deleter::Error },
+    #[snafu(display("The query history deleter task panicked"))]
+    QueryHistoryDeleterPanic { source: tokio::task::JoinError },
+
     #[snafu(display("The parquet file retention flagger task failed"))]
     #[snafu(context(false))]
     ParquetFileRetentionFlagger { source: retention_flagger::Error },
@@ -228,8 +228,9 @@ mod tests {
     use super::*;
     use async_trait::async_trait;
     use data_types::{
-        ColumnId, ColumnSet, CompactionLevel, NamespaceId, ParquetFile, ParquetFileId,
-        ParquetFileParams, PartitionId, TableId, Timestamp, TransitionPartitionId,
+        ColumnId, ColumnSet, CompactionLevel, NamespaceId, ParquetFile,
+        ParquetFileColumnStatistics, ParquetFileId, ParquetFileParams, PartitionId, TableId,
+        Timestamp, TransitionPartitionId,
     };
     use iox_catalog::{
         interface::Catalog,
@@ -284,6 +285,7 @@ mod tests {
             created_at: Timestamp::new(1),
             column_set: ColumnSet::new([ColumnId::new(1), ColumnId::new(2)]),
             max_l0_created_at: Timestamp::new(1),
+            column_statistics: vec![],
         };
 
         let parquet_file = repos
@@ -581,5 +583,13 @@ mod tests {
             self.create_upgrade_delete(delete, upgrade, create, target_level)
                 .await
         }
+
+        async fn list_column_statistics_by_table(
+            &mut self,
+            table_id: TableId,
+        ) -> iox_catalog::interface::Result<Vec<(ParquetFileId, ParquetFileColumnStatistics)>>
+        {
+            self.inner.list_column_statistics_by_table(table_id).await
+        }
     }
 }
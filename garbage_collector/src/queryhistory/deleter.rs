@@ -0,0 +1,50 @@
+use data_types::Timestamp;
+use iox_catalog::interface::Catalog;
+use observability_deps::tracing::*;
+use snafu::prelude::*;
+use std::{sync::Arc, time::Duration};
+use tokio::{select, time::sleep};
+use tokio_util::sync::CancellationToken;
+
+pub(crate) async fn perform(
+    shutdown: CancellationToken,
+    catalog: Arc<dyn Catalog>,
+    cutoff: Option<Duration>,
+    sleep_interval_minutes: u64,
+) -> Result<()> {
+    // Query history entries are kept forever unless a cutoff is configured.
+    let Some(cutoff) = cutoff else {
+        return Ok(());
+    };
+
+    loop {
+        let older_than = Timestamp::from(catalog.time_provider().now() - cutoff);
+        let deleted = catalog
+            .repositories()
+            .await
+            .query_history()
+            .delete_older_than(older_than) // write
+            .await
+            .context(DeletingSnafu)?;
+        info!(delete_count = %deleted, "iox_catalog::query_history::delete_older_than()");
+
+        select! {
+            _ = shutdown.cancelled() => {
+                break
+            },
+            _ = sleep(Duration::from_secs(60 * sleep_interval_minutes)) => (),
+        }
+    }
+    Ok(())
+}
+
+#[derive(Debug, Snafu)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[snafu(display("Failed to delete old query history entries in catalog"))]
+    Deleting {
+        source: iox_catalog::interface::Error,
+    },
+}
+
+pub(crate) type Result<T, E = Error> = std::result::Result<T, E>;
@@ -0,0 +1,2 @@
+/// Logic for deleting query_history entries from the catalog.
+pub(crate) mod deleter;
@@ -19,6 +19,7 @@ fn main() -> Result<()> {
 /// Creates:
 ///
 /// - `influxdata.iox.authz.v1.rs`
+/// - `influxdata.iox.build_info.v1.rs`
 /// - `influxdata.iox.catalog.v1.rs`
 /// - `influxdata.iox.compactor.v1.rs`
 /// - `influxdata.iox.delete.v1.rs`
@@ -34,6 +35,7 @@ fn main() -> Result<()> {
 /// - `influxdata.platform.storage.rs`
 fn generate_grpc_types(root: &Path) -> Result<()> {
     let authz_path = root.join("influxdata/iox/authz/v1");
+    let build_info_path = root.join("influxdata/iox/build_info/v1");
     let catalog_path = root.join("influxdata/iox/catalog/v1");
     let compactor_path = root.join("influxdata/iox/compactor/v1");
     let delete_path = root.join("influxdata/iox/delete/v1");
@@ -52,6 +54,8 @@ fn generate_grpc_types(root: &Path) -> Result<()> {
 
     let proto_files = vec![
         authz_path.join("authz.proto"),
+        build_info_path.join("service.proto"),
+        catalog_path.join("introspection.proto"),
         catalog_path.join("parquet_file.proto"),
         catalog_path.join("partition_identifier.proto"),
         catalog_path.join("service.proto"),
@@ -61,14 +65,18 @@ fn generate_grpc_types(root: &Path) -> Result<()> {
         gossip_path.join("parquet_file.proto"),
         gossip_path.join("schema.proto"),
         gossip_path.join("schema_sync.proto"),
+        ingester_path.join("fault_injection.proto"),
         ingester_path.join("parquet_metadata.proto"),
         ingester_path.join("persist.proto"),
+        ingester_path.join("schema.proto"),
         ingester_path.join("write.proto"),
         namespace_path.join("service.proto"),
         object_store_path.join("service.proto"),
         partition_template_path.join("template.proto"),
         predicate_path.join("predicate.proto"),
         querier_path.join("flight.proto"),
+        querier_path.join("query_admin.proto"),
+        querier_path.join("task.proto"),
         root.join("google/longrunning/operations.proto"),
         root.join("google/rpc/error_details.proto"),
         root.join("google/rpc/status.proto"),
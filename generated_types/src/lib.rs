@@ -62,6 +62,94 @@ pub mod influxdata {
             }
         }
 
+        pub mod build_info {
+            pub mod v1 {
+                include!(concat!(env!("OUT_DIR"), "/influxdata.iox.build_info.v1.rs"));
+                include!(concat!(
+                    env!("OUT_DIR"),
+                    "/influxdata.iox.build_info.v1.serde.rs"
+                ));
+            }
+
+            /// The name of the protocol used by the queriers to query the
+            /// ingesters.
+            pub const INGESTER_QUERY_PROTOCOL: &str = "ingester_query";
+
+            /// The name of the protocol used by the routers to write to the
+            /// ingesters.
+            pub const RPC_WRITE_PROTOCOL: &str = "rpc_write";
+
+            /// The versions of the inter-service protocols spoken by this
+            /// build.
+            ///
+            /// NOTE: Bump the version of a protocol when a change to it cannot
+            /// be understood by a peer speaking the previous version.
+            pub const PROTOCOL_VERSIONS: &[(&str, u32)] =
+                &[(INGESTER_QUERY_PROTOCOL, 1), (RPC_WRITE_PROTOCOL, 1)];
+
+            /// The compatibility of a peer with this build for a protocol, as
+            /// returned by [`check_compatibility()`].
+            #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+            pub enum Compatibility {
+                /// The peer speaks the same version of the protocol.
+                Compatible,
+
+                /// The peer does not report a version of the protocol.
+                Unknown,
+
+                /// The peer speaks a different version of the protocol.
+                Incompatible {
+                    /// The version spoken by this build.
+                    local: u32,
+                    /// The version spoken by the peer.
+                    peer: u32,
+                },
+            }
+
+            /// Check the compatibility of the peer described by `info` with
+            /// this build for `protocol`.
+            ///
+            /// ```
+            /// use generated_types::influxdata::iox::build_info::{
+            ///     check_compatibility, v1::GetBuildInfoResponse, Compatibility,
+            ///     RPC_WRITE_PROTOCOL,
+            /// };
+            ///
+            /// let mut info = GetBuildInfoResponse::default();
+            /// assert_eq!(
+            ///     check_compatibility(&info, RPC_WRITE_PROTOCOL),
+            ///     Compatibility::Unknown
+            /// );
+            ///
+            /// info.protocol_versions.insert(RPC_WRITE_PROTOCOL.to_string(), 1);
+            /// assert_eq!(
+            ///     check_compatibility(&info, RPC_WRITE_PROTOCOL),
+            ///     Compatibility::Compatible
+            /// );
+            ///
+            /// info.protocol_versions.insert(RPC_WRITE_PROTOCOL.to_string(), 42);
+            /// assert_eq!(
+            ///     check_compatibility(&info, RPC_WRITE_PROTOCOL),
+            ///     Compatibility::Incompatible { local: 1, peer: 42 }
+            /// );
+            /// ```
+            pub fn check_compatibility(
+                info: &v1::GetBuildInfoResponse,
+                protocol: &str,
+            ) -> Compatibility {
+                let local = PROTOCOL_VERSIONS
+                    .iter()
+                    .find(|(name, _)| *name == protocol)
+                    .map(|(_, v)| *v);
+
+                match (local, info.protocol_versions.get(protocol)) {
+                    (Some(local), Some(&peer)) if local == peer => Compatibility::Compatible,
+                    (Some(local), Some(&peer)) => Compatibility::Incompatible { local, peer },
+                    _ => Compatibility::Unknown,
+                }
+            }
+        }
+
         pub mod catalog {
             pub mod v1 {
                 include!(concat!(env!("OUT_DIR"), "/influxdata.iox.catalog.v1.rs"));
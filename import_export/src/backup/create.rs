@@ -0,0 +1,201 @@
+//! Creation of backups.
+
+use std::collections::HashMap;
+
+use data_types::{ColumnId, Timestamp};
+use futures_util::{stream, StreamExt};
+use iox_catalog::interface::{Catalog, SoftDeletedRows};
+use object_store::{path::Path, ObjectStore};
+use observability_deps::tracing::{info, warn};
+use parquet_file::ParquetFilePath;
+
+use super::{
+    manifest_path, ColumnManifest, Error, Manifest, NamespaceManifest, ObjectStores,
+    ParquetFileManifest, PartitionManifest, Result, TableManifest, MANIFEST_VERSION,
+};
+
+/// The number of object store requests issued concurrently when verifying
+/// the existence of parquet files.
+pub(super) const VERIFY_CONCURRENCY: usize = 16;
+
+/// Snapshot the contents of `catalog` into a new backup `name`, writing its
+/// manifest to the default store of `object_stores`.
+///
+/// Fails without writing the manifest if a backup with the same name exists,
+/// if a namespace uses an object store that is not configured in
+/// `object_stores`, or if any parquet file referenced by the catalog is
+/// missing from the object store of its namespace.
+pub async fn create_backup(
+    catalog: &dyn Catalog,
+    object_stores: &ObjectStores,
+    name: &str,
+) -> Result<Manifest> {
+    let object_store = object_stores.default_store();
+    let path = manifest_path(name);
+    match object_store.head(&path).await {
+        Ok(_) => return Err(Error::BackupExists(name.to_string())),
+        Err(object_store::Error::NotFound { .. }) => {}
+        Err(e) => return Err(e.into()),
+    }
+
+    let created_at = Timestamp::from(catalog.time_provider().now()).get();
+    let mut repos = catalog.repositories().await;
+
+    let mut namespaces = vec![];
+    for namespace in repos
+        .namespaces()
+        .list(SoftDeletedRows::ExcludeDeleted)
+        .await?
+    {
+        let mut tables = vec![];
+        for table in repos.tables().list_by_namespace_id(namespace.id).await? {
+            let columns = repos.columns().list_by_table_id(table.id).await?;
+            let column_names = columns
+                .iter()
+                .map(|c| (c.id, c.name.clone()))
+                .collect::<HashMap<_, _>>();
+
+            let mut files_by_partition = HashMap::<_, Vec<_>>::new();
+            for file in repos
+                .parquet_files()
+                .list_by_table_not_to_delete(table.id)
+                .await?
+            {
+                files_by_partition
+                    .entry(file.partition_id.clone())
+                    .or_default()
+                    .push(ParquetFileManifest {
+                        object_store_id: file.object_store_id.to_string(),
+                        path: ParquetFilePath::from(&file).object_store_path().to_string(),
+                        min_time: file.min_time.get(),
+                        max_time: file.max_time.get(),
+                        file_size_bytes: file.file_size_bytes,
+                        row_count: file.row_count,
+                        compaction_level: file.compaction_level as i16,
+                        created_at: file.created_at.get(),
+                        max_l0_created_at: file.max_l0_created_at.get(),
+                        columns: names(&column_names, file.column_set.iter())?,
+                    });
+            }
+
+            let partitions = repos
+                .partitions()
+                .list_by_table_id(table.id)
+                .await?
+                .into_iter()
+                .map(|p| {
+                    Ok(PartitionManifest {
+                        sort_key: names(&column_names, p.sort_key_ids().iter())?,
+                        parquet_files: files_by_partition
+                            .remove(&p.transition_partition_id())
+                            .unwrap_or_default(),
+                        key: p.partition_key.inner().to_string(),
+                    })
+                })
+                .collect::<Result<Vec<_>>>()?;
+
+            tables.push(TableManifest {
+                name: table.name,
+                partition_template: table.partition_template.as_proto().cloned(),
+                columns: columns
+                    .into_iter()
+                    .map(|c| ColumnManifest {
+                        name: c.name,
+                        column_type: c.column_type as i16,
+                    })
+                    .collect(),
+                partitions,
+            });
+        }
+
+        namespaces.push(NamespaceManifest {
+            name: namespace.name,
+            retention_period_ns: namespace.retention_period_ns,
+            max_tables: namespace.max_tables.get(),
+            max_columns_per_table: namespace.max_columns_per_table.get(),
+            read_only: namespace.read_only,
+            object_store_name: namespace.object_store_name,
+            partition_template: namespace.partition_template.as_proto().cloned(),
+            tables,
+        });
+    }
+
+    let manifest = Manifest {
+        version: MANIFEST_VERSION,
+        created_at,
+        namespaces,
+    };
+
+    verify_objects(object_stores, &manifest).await?;
+
+    object_store
+        .put(&path, serde_json::to_vec_pretty(&manifest)?.into())
+        .await?;
+
+    info!(
+        %name,
+        namespaces = manifest.namespaces.len(),
+        parquet_files = manifest.parquet_file_count(),
+        "created backup"
+    );
+
+    Ok(manifest)
+}
+
+/// Return an error if any of the parquet files referenced by `manifest` does
+/// not exist in the object store of its namespace.
+pub(super) async fn verify_objects(
+    object_stores: &ObjectStores,
+    manifest: &Manifest,
+) -> Result<()> {
+    let mut objects = vec![];
+    for namespace in &manifest.namespaces {
+        let store =
+            object_stores.for_namespace(&namespace.name, namespace.object_store_name.as_deref())?;
+        objects.extend(
+            namespace
+                .parquet_files()
+                .map(|f| (store, Path::from(f.path.as_str()))),
+        );
+    }
+
+    let missing = stream::iter(objects)
+        .map(|(store, path)| async move {
+            match store.head(&path).await {
+                Ok(_) => Ok(None),
+                Err(object_store::Error::NotFound { .. }) => {
+                    warn!(%path, "parquet file missing from object store");
+                    Ok(Some(path))
+                }
+                Err(e) => Err(Error::ObjectStore(e)),
+            }
+        })
+        .buffer_unordered(VERIFY_CONCURRENCY)
+        .filter_map(|r| async move { r.transpose() })
+        .collect::<Vec<_>>()
+        .await
+        .into_iter()
+        .collect::<Result<Vec<_>>>()?;
+
+    match missing.first() {
+        Some(path) => Err(Error::MissingObjects {
+            count: missing.len(),
+            path: path.to_string(),
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Resolve the names of the columns `ids`.
+fn names<'a>(
+    column_names: &HashMap<ColumnId, String>,
+    ids: impl Iterator<Item = &'a ColumnId>,
+) -> Result<Vec<String>> {
+    ids.map(|id| {
+        column_names
+            .get(id)
+            .cloned()
+            .ok_or(Error::UnknownColumnId(id.get()))
+    })
+    .collect()
+}
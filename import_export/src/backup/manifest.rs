@@ -0,0 +1,106 @@
+//! The manifest describing the catalog contents captured by a backup.
+
+use generated_types::influxdata::iox::partition_template::v1::PartitionTemplate;
+use serde::{Deserialize, Serialize};
+
+/// The version of the manifest format written by this version of IOx.
+///
+/// Manifests with a different version are refused when restoring.
+pub const MANIFEST_VERSION: u32 = 1;
+
+/// A snapshot of the catalog contents of a backup.
+///
+/// Catalog IDs are not retained: restoring a backup creates new rows, and
+/// columns are referenced by name within their table.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Manifest {
+    /// The version of the manifest format, see [`MANIFEST_VERSION`].
+    pub version: u32,
+
+    /// The time the backup was created at, in nanoseconds since the epoch.
+    pub created_at: i64,
+
+    /// The namespaces that were not soft-deleted when the backup was
+    /// created.
+    pub namespaces: Vec<NamespaceManifest>,
+}
+
+/// A namespace of a [`Manifest`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NamespaceManifest {
+    pub name: String,
+    pub retention_period_ns: Option<i64>,
+    pub max_tables: i32,
+    pub max_columns_per_table: i32,
+    pub read_only: bool,
+    pub object_store_name: Option<String>,
+    pub partition_template: Option<PartitionTemplate>,
+    pub tables: Vec<TableManifest>,
+}
+
+/// A table of a [`NamespaceManifest`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TableManifest {
+    pub name: String,
+    pub partition_template: Option<PartitionTemplate>,
+    pub columns: Vec<ColumnManifest>,
+    pub partitions: Vec<PartitionManifest>,
+}
+
+/// A column of a [`TableManifest`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ColumnManifest {
+    pub name: String,
+    /// The [`ColumnType`](data_types::ColumnType) discriminant.
+    pub column_type: i16,
+}
+
+/// A partition of a [`TableManifest`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PartitionManifest {
+    pub key: String,
+    /// The names of the sort key columns, empty if the partition has none.
+    pub sort_key: Vec<String>,
+    pub parquet_files: Vec<ParquetFileManifest>,
+}
+
+/// A parquet file of a [`PartitionManifest`] that was not marked for
+/// deletion.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ParquetFileManifest {
+    pub object_store_id: String,
+    /// The object store path of the file when the backup was created.
+    pub path: String,
+    pub min_time: i64,
+    pub max_time: i64,
+    pub file_size_bytes: i64,
+    pub row_count: i64,
+    /// The [`CompactionLevel`](data_types::CompactionLevel) discriminant.
+    pub compaction_level: i16,
+    pub created_at: i64,
+    pub max_l0_created_at: i64,
+    /// The names of the columns contained in the file.
+    pub columns: Vec<String>,
+}
+
+impl Manifest {
+    /// The number of parquet files referenced by the manifest.
+    pub fn parquet_file_count(&self) -> usize {
+        self.parquet_files().count()
+    }
+
+    /// Iterate over the parquet files referenced by the manifest.
+    pub fn parquet_files(&self) -> impl Iterator<Item = &ParquetFileManifest> {
+        self.namespaces.iter().flat_map(|n| n.parquet_files())
+    }
+}
+
+impl NamespaceManifest {
+    /// Iterate over the parquet files of the namespace.
+    pub fn parquet_files(&self) -> impl Iterator<Item = &ParquetFileManifest> {
+        self.tables
+            .iter()
+            .flat_map(|t| &t.partitions)
+            .flat_map(|p| &p.parquet_files)
+    }
+}
@@ -0,0 +1,372 @@
+//! Backup and restore of the catalog contents referencing the parquet files
+//! in an object store.
+//!
+//! A backup is a versioned [`Manifest`] written to the object store at
+//! `backups/<name>/manifest.json`, capturing the namespaces, tables, columns,
+//! partitions and parquet file rows of the catalog. The parquet files
+//! themselves are not copied - they remain in the object store of their
+//! namespace (see [`ObjectStores`]), and their existence is verified both
+//! when creating and when restoring a backup.
+mod create;
+mod manifest;
+mod restore;
+
+pub use create::create_backup;
+pub use manifest::*;
+pub use restore::restore_backup;
+
+use std::{collections::BTreeMap, sync::Arc};
+
+use object_store::{path::Path, DynObjectStore};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("Catalog error: {0}")]
+    Catalog(#[from] iox_catalog::interface::Error),
+
+    #[error("Object store error: {0}")]
+    ObjectStore(#[from] object_store::Error),
+
+    #[error("JSON Serialization error: {0}")]
+    Serde(#[from] serde_json::Error),
+
+    #[error("Backup {0} already exists")]
+    BackupExists(String),
+
+    #[error("Backup {0} not found")]
+    BackupNotFound(String),
+
+    #[error("Unsupported backup manifest version {found}, expected {expected}")]
+    UnsupportedVersion { found: u32, expected: u32 },
+
+    #[error("Catalog references unknown column ID {0}")]
+    UnknownColumnId(i64),
+
+    #[error("Invalid backup manifest: {0}")]
+    InvalidManifest(String),
+
+    #[error("Partition {0} was modified concurrently with the restore")]
+    ConcurrentModification(String),
+
+    #[error("Namespace {0} already exists in the target catalog")]
+    NamespaceExists(String),
+
+    #[error("Namespace {namespace} uses object store {name} which is not configured")]
+    UnknownObjectStore { namespace: String, name: String },
+
+    #[error("{count} parquet files referenced by the backup are missing from the object store, including {path}")]
+    MissingObjects { count: usize, path: String },
+}
+
+type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// The object stores a backup is read from or written to.
+///
+/// Manifests are stored in the default store, while the parquet files of
+/// each namespace reside in the store named by its `object_store_name`, or
+/// the default store if it has none.
+#[derive(Debug, Clone)]
+pub struct ObjectStores {
+    default: Arc<DynObjectStore>,
+    named: BTreeMap<String, Arc<DynObjectStore>>,
+}
+
+impl ObjectStores {
+    /// Use `default` for manifests and namespaces without an object store
+    /// name, and `named` for the namespaces naming one of its stores.
+    pub fn new(
+        default: Arc<DynObjectStore>,
+        named: impl IntoIterator<Item = (String, Arc<DynObjectStore>)>,
+    ) -> Self {
+        Self {
+            default,
+            named: named.into_iter().collect(),
+        }
+    }
+
+    /// The store holding the backup manifests.
+    pub fn default_store(&self) -> &Arc<DynObjectStore> {
+        &self.default
+    }
+
+    /// Return the store holding the parquet files of the namespace `name`
+    /// that is configured to use `object_store_name`.
+    pub fn for_namespace(
+        &self,
+        name: &str,
+        object_store_name: Option<&str>,
+    ) -> Result<&Arc<DynObjectStore>> {
+        match object_store_name {
+            None => Ok(&self.default),
+            Some(store) => self
+                .named
+                .get(store)
+                .ok_or_else(|| Error::UnknownObjectStore {
+                    namespace: name.to_string(),
+                    name: store.to_string(),
+                }),
+        }
+    }
+}
+
+/// Return the object store path of the manifest of the backup `name`.
+pub fn manifest_path(name: &str) -> Path {
+    Path::from_iter(["backups", name, "manifest.json"])
+}
+
+#[cfg(test)]
+mod tests {
+    use data_types::{
+        ColumnSet, ColumnType, CompactionLevel, ParquetFileParams, SortedColumnSet, Timestamp,
+    };
+    use iox_catalog::{
+        interface::{Catalog, SoftDeletedRows},
+        mem::MemCatalog,
+        test_helpers::{arbitrary_namespace, arbitrary_table},
+    };
+    use object_store::{memory::InMemory, ObjectStore};
+    use parquet_file::ParquetFilePath;
+    use uuid::Uuid;
+
+    use super::*;
+
+    fn catalog() -> Arc<dyn Catalog> {
+        Arc::new(MemCatalog::new(Arc::new(metric::Registry::default())))
+    }
+
+    /// Create the namespace `name` using `object_store_name`, with a table, a
+    /// partition and a parquet file, returning the path and object store ID
+    /// of the file.
+    async fn populate(
+        catalog: &dyn Catalog,
+        name: &str,
+        object_store_name: Option<&str>,
+    ) -> (Path, Uuid) {
+        let mut repos = catalog.repositories().await;
+        let namespace = arbitrary_namespace(&mut *repos, name).await;
+        repos
+            .namespaces()
+            .update_object_store_name(name, object_store_name)
+            .await
+            .unwrap();
+        let table = arbitrary_table(&mut *repos, "platanos", &namespace).await;
+        let tag = repos
+            .columns()
+            .create_or_get("region", table.id, ColumnType::Tag)
+            .await
+            .unwrap();
+        let time = repos
+            .columns()
+            .create_or_get("time", table.id, ColumnType::Time)
+            .await
+            .unwrap();
+        let partition = repos
+            .partitions()
+            .create_or_get("2023-10-17".into(), table.id)
+            .await
+            .unwrap();
+        repos
+            .partitions()
+            .cas_sort_key(
+                &partition.transition_partition_id(),
+                None,
+                None,
+                &["region", "time"],
+                &SortedColumnSet::new([tag.id, time.id]),
+            )
+            .await
+            .unwrap();
+
+        let params = ParquetFileParams {
+            namespace_id: namespace.id,
+            table_id: table.id,
+            partition_id: partition.transition_partition_id(),
+            object_store_id: Uuid::new_v4(),
+            min_time: Timestamp::new(1),
+            max_time: Timestamp::new(2),
+            file_size_bytes: 3,
+            row_count: 4,
+            compaction_level: CompactionLevel::FileNonOverlapped,
+            created_at: Timestamp::new(5),
+            column_set: ColumnSet::new([tag.id, time.id]),
+            max_l0_created_at: Timestamp::new(6),
+            column_statistics: vec![],
+        };
+        let path = ParquetFilePath::from(&params).object_store_path();
+        repos.parquet_files().create(params.clone()).await.unwrap();
+        (path, params.object_store_id)
+    }
+
+    #[tokio::test]
+    async fn test_backup_restore() {
+        let source = catalog();
+        let object_store: Arc<DynObjectStore> = Arc::new(InMemory::new());
+        let stores = ObjectStores::new(Arc::clone(&object_store), []);
+
+        // A namespace with a table, a partition and a parquet file.
+        let (path, object_store_id) = populate(&*source, "bananas", None).await;
+
+        // Creating a backup fails if a parquet file is missing.
+        let err = create_backup(&*source, &stores, "b1").await.unwrap_err();
+        assert!(
+            matches!(err, Error::MissingObjects { count: 1, .. }),
+            "{err}"
+        );
+
+        object_store
+            .put(&path, "parquet".into())
+            .await
+            .expect("write object");
+        let manifest = create_backup(&*source, &stores, "b1").await.unwrap();
+        assert_eq!(manifest.version, MANIFEST_VERSION);
+        assert_eq!(manifest.parquet_file_count(), 1);
+
+        // Backups are never overwritten.
+        let err = create_backup(&*source, &stores, "b1").await.unwrap_err();
+        assert!(matches!(err, Error::BackupExists(_)), "{err}");
+
+        // Restore into a fresh catalog.
+        let target = catalog();
+        // Allocate some IDs so that the restored rows are assigned different
+        // IDs to the source rows.
+        arbitrary_namespace(&mut *target.repositories().await, "other").await;
+
+        let restored = restore_backup(&*target, &stores, "b1").await.unwrap();
+        assert_eq!(restored, manifest);
+
+        let mut repos = target.repositories().await;
+        let namespace = repos
+            .namespaces()
+            .get_by_name("bananas", SoftDeletedRows::ExcludeDeleted)
+            .await
+            .unwrap()
+            .unwrap();
+        let table = repos
+            .tables()
+            .get_by_namespace_and_name(namespace.id, "platanos")
+            .await
+            .unwrap()
+            .unwrap();
+        let partitions = repos.partitions().list_by_table_id(table.id).await.unwrap();
+        let [partition] = partitions.as_slice() else {
+            panic!("expected one partition, got {partitions:?}");
+        };
+        assert_eq!(
+            partition.sort_key,
+            Some(vec!["region".to_string(), "time".to_string()])
+        );
+
+        let file = repos
+            .parquet_files()
+            .get_by_object_store_id(object_store_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(file.table_id, table.id);
+        assert_eq!(file.compaction_level, CompactionLevel::FileNonOverlapped);
+        assert_eq!(file.row_count, 4);
+        assert_eq!(file.column_set.len(), 2);
+
+        // The parquet file is available at the path of the restored row.
+        object_store
+            .head(&ParquetFilePath::from(&file).object_store_path())
+            .await
+            .expect("restored parquet file exists");
+
+        // Restoring into a catalog containing the namespaces fails.
+        let err = restore_backup(&*target, &stores, "b1").await.unwrap_err();
+        assert!(matches!(err, Error::NamespaceExists(_)), "{err}");
+
+        let err = restore_backup(&*target, &stores, "b2").await.unwrap_err();
+        assert!(matches!(err, Error::BackupNotFound(_)), "{err}");
+    }
+
+    #[tokio::test]
+    async fn test_namespace_object_store() {
+        let source = catalog();
+        let default: Arc<DynObjectStore> = Arc::new(InMemory::new());
+        let tenant: Arc<DynObjectStore> = Arc::new(InMemory::new());
+
+        let (path, object_store_id) = populate(&*source, "bananas", Some("tenant")).await;
+        tenant
+            .put(&path, "parquet".into())
+            .await
+            .expect("write object");
+
+        // The store of the namespace must be configured.
+        let stores = ObjectStores::new(Arc::clone(&default), []);
+        let err = create_backup(&*source, &stores, "b1").await.unwrap_err();
+        assert!(matches!(err, Error::UnknownObjectStore { .. }), "{err}");
+
+        // The parquet file is found in the store of its namespace, while the
+        // manifest is written to the default store.
+        let stores = ObjectStores::new(
+            Arc::clone(&default),
+            [("tenant".to_string(), Arc::clone(&tenant))],
+        );
+        create_backup(&*source, &stores, "b1").await.unwrap();
+        default.head(&manifest_path("b1")).await.unwrap();
+
+        let target = catalog();
+        arbitrary_namespace(&mut *target.repositories().await, "other").await;
+        restore_backup(&*target, &stores, "b1").await.unwrap();
+
+        // The parquet file was copied within the store of the namespace.
+        let file = target
+            .repositories()
+            .await
+            .parquet_files()
+            .get_by_object_store_id(object_store_id)
+            .await
+            .unwrap()
+            .unwrap();
+        let restored_path = ParquetFilePath::from(&file).object_store_path();
+        assert_ne!(restored_path, path);
+        tenant
+            .head(&restored_path)
+            .await
+            .expect("restored parquet file exists");
+        assert!(matches!(
+            default.head(&restored_path).await,
+            Err(object_store::Error::NotFound { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_restore_validates_whole_manifest() {
+        let source = catalog();
+        let object_store: Arc<DynObjectStore> = Arc::new(InMemory::new());
+        let stores = ObjectStores::new(Arc::clone(&object_store), []);
+
+        for name in ["a", "b"] {
+            let (path, _) = populate(&*source, name, None).await;
+            object_store.put(&path, "parquet".into()).await.unwrap();
+        }
+        let mut manifest = create_backup(&*source, &stores, "b1").await.unwrap();
+
+        // Break the manifest of the last namespace only.
+        manifest.namespaces[1].tables[0].partitions[0].sort_key = vec!["bananas".to_string()];
+        object_store
+            .put(
+                &manifest_path("b2"),
+                serde_json::to_vec(&manifest).unwrap().into(),
+            )
+            .await
+            .unwrap();
+
+        let target = catalog();
+        let err = restore_backup(&*target, &stores, "b2").await.unwrap_err();
+        assert!(matches!(err, Error::InvalidManifest(_)), "{err}");
+
+        // Nothing was restored, including the valid namespace.
+        let namespaces = target
+            .repositories()
+            .await
+            .namespaces()
+            .list(SoftDeletedRows::AllRows)
+            .await
+            .unwrap();
+        assert!(namespaces.is_empty(), "{namespaces:?}");
+    }
+}
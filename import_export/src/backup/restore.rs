@@ -0,0 +1,374 @@
+//! Restoration of backups.
+
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
+
+use data_types::{
+    partition_template::{NamespacePartitionTemplateOverride, TablePartitionTemplateOverride},
+    ColumnId, ColumnSet, ColumnType, CompactionLevel, MaxColumnsPerTable, MaxTables, NamespaceName,
+    NamespaceServiceProtectionLimitsOverride, ParquetFileParams, SortedColumnSet, Timestamp,
+};
+use iox_catalog::interface::{CasFailure, Catalog, SoftDeletedRows};
+use object_store::{path::Path, DynObjectStore, ObjectStore};
+use observability_deps::tracing::{debug, info};
+use parquet_file::ParquetFilePath;
+use uuid::Uuid;
+
+use super::{
+    create::verify_objects, manifest_path, Error, Manifest, NamespaceManifest, ObjectStores,
+    ParquetFileManifest, PartitionManifest, Result, TableManifest, MANIFEST_VERSION,
+};
+
+/// Restore the backup `name` from `object_stores` into `catalog`, returning
+/// its manifest.
+///
+/// The whole manifest is validated before any change is made to `catalog`:
+/// it must be well-formed and within the limits of its namespaces, none of
+/// its namespaces or parquet files may exist in `catalog`, and all of its
+/// parquet files must exist in the object store of their namespace. The
+/// restored rows are assigned new IDs, and parquet files are copied within
+/// that store to the paths derived from them where these differ from the
+/// paths at the time of the backup.
+pub async fn restore_backup(
+    catalog: &dyn Catalog,
+    object_stores: &ObjectStores,
+    name: &str,
+) -> Result<Manifest> {
+    let manifest = read_manifest(object_stores.default_store().as_ref(), name).await?;
+    let plan = plan(&manifest, object_stores)?;
+
+    let mut repos = catalog.repositories().await;
+    for ns in &plan {
+        if repos
+            .namespaces()
+            .get_by_name(&ns.manifest.name, SoftDeletedRows::AllRows)
+            .await?
+            .is_some()
+        {
+            return Err(Error::NamespaceExists(ns.manifest.name.clone()));
+        }
+    }
+    for (f, object_store_id) in plan.iter().flat_map(|ns| ns.files()) {
+        if repos
+            .parquet_files()
+            .get_by_object_store_id(object_store_id)
+            .await?
+            .is_some()
+        {
+            return Err(Error::InvalidManifest(format!(
+                "parquet file {} already exists in the target catalog",
+                f.object_store_id
+            )));
+        }
+    }
+
+    verify_objects(object_stores, &manifest).await?;
+
+    for ns in &plan {
+        let mut namespace = repos
+            .namespaces()
+            .create(
+                &ns.name,
+                ns.partition_template.clone(),
+                ns.manifest.retention_period_ns,
+                Some(NamespaceServiceProtectionLimitsOverride {
+                    max_tables: Some(MaxTables::new(ns.manifest.max_tables)),
+                    max_columns_per_table: Some(MaxColumnsPerTable::new(
+                        ns.manifest.max_columns_per_table,
+                    )),
+                }),
+            )
+            .await?;
+        if ns.manifest.object_store_name.is_some() {
+            namespace = repos
+                .namespaces()
+                .update_object_store_name(
+                    &ns.manifest.name,
+                    ns.manifest.object_store_name.as_deref(),
+                )
+                .await?;
+        }
+        if ns.manifest.read_only {
+            namespace = repos
+                .namespaces()
+                .update_read_only(&ns.manifest.name, true)
+                .await?;
+        }
+        debug!(namespace = %ns.manifest.name, namespace_id = %namespace.id, "restored namespace");
+
+        for t in &ns.tables {
+            let table = repos
+                .tables()
+                .create(&t.manifest.name, t.partition_template.clone(), namespace.id)
+                .await?;
+
+            let mut column_ids = HashMap::new();
+            for (name, column_type) in &t.columns {
+                let column = repos
+                    .columns()
+                    .create_or_get(name, table.id, *column_type)
+                    .await?;
+                column_ids.insert(*name, column.id);
+            }
+
+            for p in &t.partitions {
+                let mut partition = repos
+                    .partitions()
+                    .create_or_get(p.manifest.key.as_str().into(), table.id)
+                    .await?;
+
+                if !p.manifest.sort_key.is_empty() {
+                    let sort_key = p
+                        .manifest
+                        .sort_key
+                        .iter()
+                        .map(|s| s.as_str())
+                        .collect::<Vec<_>>();
+                    let sort_key_ids = SortedColumnSet::new(ids(&column_ids, &p.manifest.sort_key));
+                    partition = repos
+                        .partitions()
+                        .cas_sort_key(
+                            &partition.transition_partition_id(),
+                            partition.sort_key.clone(),
+                            Some(partition.sort_key_ids.clone()),
+                            &sort_key,
+                            &sort_key_ids,
+                        )
+                        .await
+                        .map_err(|e| match e {
+                            CasFailure::ValueMismatch(_) => {
+                                Error::ConcurrentModification(p.manifest.key.clone())
+                            }
+                            CasFailure::QueryError(e) => Error::Catalog(e),
+                        })?;
+                }
+
+                for (f, object_store_id, compaction_level) in &p.files {
+                    let params = ParquetFileParams {
+                        namespace_id: namespace.id,
+                        table_id: table.id,
+                        partition_id: partition.transition_partition_id(),
+                        object_store_id: *object_store_id,
+                        min_time: Timestamp::new(f.min_time),
+                        max_time: Timestamp::new(f.max_time),
+                        file_size_bytes: f.file_size_bytes,
+                        row_count: f.row_count,
+                        compaction_level: *compaction_level,
+                        created_at: Timestamp::new(f.created_at),
+                        column_set: ColumnSet::new(ids(&column_ids, &f.columns)),
+                        max_l0_created_at: Timestamp::new(f.max_l0_created_at),
+                        column_statistics: vec![],
+                    };
+
+                    let from = Path::from(f.path.as_str());
+                    let to = ParquetFilePath::from(&params).object_store_path();
+                    if from != to {
+                        ns.object_store.copy(&from, &to).await?;
+                    }
+
+                    repos.parquet_files().create(params).await?;
+                }
+            }
+        }
+    }
+
+    info!(
+        %name,
+        namespaces = manifest.namespaces.len(),
+        parquet_files = manifest.parquet_file_count(),
+        "restored backup"
+    );
+
+    Ok(manifest)
+}
+
+/// A validated [`NamespaceManifest`], with its values parsed.
+struct NamespacePlan<'a> {
+    manifest: &'a NamespaceManifest,
+    name: NamespaceName<'a>,
+    partition_template: Option<NamespacePartitionTemplateOverride>,
+    object_store: &'a Arc<DynObjectStore>,
+    tables: Vec<TablePlan<'a>>,
+}
+
+impl<'a> NamespacePlan<'a> {
+    /// Iterate over the parquet files of the namespace and their object
+    /// store IDs.
+    fn files(&self) -> impl Iterator<Item = (&'a ParquetFileManifest, Uuid)> + '_ {
+        self.tables
+            .iter()
+            .flat_map(|t| &t.partitions)
+            .flat_map(|p| &p.files)
+            .map(|(f, id, _)| (*f, *id))
+    }
+}
+
+/// A validated [`TableManifest`].
+struct TablePlan<'a> {
+    manifest: &'a TableManifest,
+    partition_template: TablePartitionTemplateOverride,
+    columns: Vec<(&'a str, ColumnType)>,
+    partitions: Vec<PartitionPlan<'a>>,
+}
+
+/// A validated [`PartitionManifest`].
+struct PartitionPlan<'a> {
+    manifest: &'a PartitionManifest,
+    files: Vec<(&'a ParquetFileManifest, Uuid, CompactionLevel)>,
+}
+
+/// Validate the whole of `manifest` and parse its values, so that restoring
+/// it cannot fail part way through because of the manifest contents.
+fn plan<'a>(
+    manifest: &'a Manifest,
+    object_stores: &'a ObjectStores,
+) -> Result<Vec<NamespacePlan<'a>>> {
+    let invalid = |e: &dyn std::fmt::Display| Error::InvalidManifest(e.to_string());
+
+    let mut namespace_names = HashSet::new();
+    let mut object_store_ids = HashSet::new();
+    let mut namespaces = Vec::with_capacity(manifest.namespaces.len());
+    for ns in &manifest.namespaces {
+        let name = NamespaceName::try_from(ns.name.as_str()).map_err(|e| invalid(&e))?;
+        if !namespace_names.insert(ns.name.as_str()) {
+            return Err(invalid(&format!("duplicate namespace {}", ns.name)));
+        }
+        let object_store =
+            object_stores.for_namespace(&ns.name, ns.object_store_name.as_deref())?;
+        let partition_template = ns
+            .partition_template
+            .clone()
+            .map(NamespacePartitionTemplateOverride::try_from)
+            .transpose()
+            .map_err(|e| invalid(&e))?;
+        if ns.tables.len() > usize::try_from(ns.max_tables).unwrap_or_default() {
+            return Err(invalid(&format!(
+                "namespace {} has {} tables, exceeding its limit of {}",
+                ns.name,
+                ns.tables.len(),
+                ns.max_tables
+            )));
+        }
+
+        let mut table_names = HashSet::new();
+        let mut tables = Vec::with_capacity(ns.tables.len());
+        for t in &ns.tables {
+            if !table_names.insert(t.name.as_str()) {
+                return Err(invalid(&format!(
+                    "duplicate table {} in namespace {}",
+                    t.name, ns.name
+                )));
+            }
+            let table_template = TablePartitionTemplateOverride::try_new(
+                t.partition_template.clone(),
+                &partition_template.clone().unwrap_or_default(),
+            )
+            .map_err(|e| invalid(&e))?;
+            if t.columns.len() > usize::try_from(ns.max_columns_per_table).unwrap_or_default() {
+                return Err(invalid(&format!(
+                    "table {} has {} columns, exceeding its limit of {}",
+                    t.name,
+                    t.columns.len(),
+                    ns.max_columns_per_table
+                )));
+            }
+
+            let mut columns = Vec::with_capacity(t.columns.len());
+            for c in &t.columns {
+                if columns.iter().any(|(name, _)| *name == c.name) {
+                    return Err(invalid(&format!(
+                        "duplicate column {} in table {}",
+                        c.name, t.name
+                    )));
+                }
+                let column_type = ColumnType::try_from(c.column_type).map_err(|e| invalid(&e))?;
+                columns.push((c.name.as_str(), column_type));
+            }
+            let known_columns = |names: &[String]| match names
+                .iter()
+                .find(|name| !columns.iter().any(|(c, _)| *c == name.as_str()))
+            {
+                Some(name) => Err(invalid(&format!(
+                    "unknown column {name} referenced in table {}",
+                    t.name
+                ))),
+                None => Ok(()),
+            };
+
+            let mut partition_keys = HashSet::new();
+            let mut partitions = Vec::with_capacity(t.partitions.len());
+            for p in &t.partitions {
+                if !partition_keys.insert(p.key.as_str()) {
+                    return Err(invalid(&format!(
+                        "duplicate partition {} in table {}",
+                        p.key, t.name
+                    )));
+                }
+                known_columns(&p.sort_key)?;
+
+                let mut files = Vec::with_capacity(p.parquet_files.len());
+                for f in &p.parquet_files {
+                    let object_store_id =
+                        Uuid::parse_str(&f.object_store_id).map_err(|e| invalid(&e))?;
+                    if !object_store_ids.insert(object_store_id) {
+                        return Err(invalid(&format!(
+                            "duplicate parquet file {object_store_id}"
+                        )));
+                    }
+                    let compaction_level = CompactionLevel::try_from(i32::from(f.compaction_level))
+                        .map_err(|e| invalid(&e))?;
+                    known_columns(&f.columns)?;
+                    files.push((f, object_store_id, compaction_level));
+                }
+
+                partitions.push(PartitionPlan { manifest: p, files });
+            }
+
+            tables.push(TablePlan {
+                manifest: t,
+                partition_template: table_template,
+                columns,
+                partitions,
+            });
+        }
+
+        namespaces.push(NamespacePlan {
+            manifest: ns,
+            name,
+            partition_template,
+            object_store,
+            tables,
+        });
+    }
+
+    Ok(namespaces)
+}
+
+/// Read and validate the manifest of the backup `name`.
+async fn read_manifest(object_store: &dyn ObjectStore, name: &str) -> Result<Manifest> {
+    let bytes = match object_store.get(&manifest_path(name)).await {
+        Ok(v) => v.bytes().await?,
+        Err(object_store::Error::NotFound { .. }) => {
+            return Err(Error::BackupNotFound(name.to_string()))
+        }
+        Err(e) => return Err(e.into()),
+    };
+
+    let manifest: Manifest = serde_json::from_slice(&bytes)?;
+    if manifest.version != MANIFEST_VERSION {
+        return Err(Error::UnsupportedVersion {
+            found: manifest.version,
+            expected: MANIFEST_VERSION,
+        });
+    }
+
+    Ok(manifest)
+}
+
+/// Resolve the restored IDs of the columns `names`, all of which were
+/// validated to exist by [`plan()`].
+fn ids(column_ids: &HashMap<&str, ColumnId>, names: &[String]) -> Vec<ColumnId> {
+    names.iter().map(|name| column_ids[name.as_str()]).collect()
+}
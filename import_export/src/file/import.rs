@@ -647,6 +647,7 @@ impl RemoteImporter {
                 created_at: Timestamp::new(proto_parquet_file.created_at),
                 column_set,
                 max_l0_created_at: Timestamp::new(proto_parquet_file.max_l0_created_at),
+                column_statistics: vec![],
             }
         } else {
             warn!("Could not read parquet file metadata, reconstructing based on encoded metadata");
@@ -670,6 +671,7 @@ impl RemoteImporter {
                 created_at,
                 column_set,
                 max_l0_created_at: created_at,
+                column_statistics: vec![],
             }
         };
         debug!(?params, "Created ParquetFileParams");
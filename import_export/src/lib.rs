@@ -13,5 +13,8 @@
 // Workaround for "unused crate" lint false positives.
 use workspace_hack as _;
 
+/// Backup and restore the catalog
+pub mod backup;
+
 /// Import/Export data to files
 pub mod file;
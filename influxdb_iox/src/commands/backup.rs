@@ -0,0 +1,107 @@
+//! This module implements the `backup` CLI command
+
+use clap_blocks::{
+    catalog_dsn::CatalogDsnConfig,
+    object_store::{make_namespace_object_stores, make_object_store, ObjectStoreConfig},
+};
+use import_export::backup::{create_backup, restore_backup, Manifest, ObjectStores};
+use iox_time::Time;
+use thiserror::Error;
+
+use crate::process_info::setup_metric_registry;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("Catalog DSN error: {0}")]
+    CatalogDsn(#[from] clap_blocks::catalog_dsn::Error),
+
+    #[error("Object store error: {0}")]
+    ObjectStore(#[from] clap_blocks::object_store::ParseError),
+
+    #[error("Backup error: {0}")]
+    Backup(#[from] import_export::backup::Error),
+}
+
+/// Back up the catalog to, or restore it from, a manifest in object storage
+#[derive(Debug, clap::Parser)]
+pub struct Config {
+    #[clap(subcommand)]
+    command: Command,
+}
+
+/// The catalog and object store a backup is created from or restored to
+#[derive(Debug, clap::Parser)]
+struct BackupConfig {
+    #[clap(flatten)]
+    catalog_dsn: CatalogDsnConfig,
+
+    // The object store holding the manifest and the parquet files of the
+    // namespaces without an object store name. The stores of the other
+    // namespaces are defined by the namespace object store config.
+    #[clap(flatten)]
+    object_store: ObjectStoreConfig,
+
+    /// The name of the backup, stored at `backups/<name>/manifest.json` in
+    /// the object store
+    #[clap(long, action)]
+    name: String,
+}
+
+impl BackupConfig {
+    fn object_stores(&self) -> Result<ObjectStores, Error> {
+        Ok(ObjectStores::new(
+            make_object_store(&self.object_store)?,
+            make_namespace_object_stores(&self.object_store)?,
+        ))
+    }
+}
+
+/// All possible subcommands for backup
+#[derive(Debug, clap::Parser)]
+enum Command {
+    /// Snapshot the namespaces, tables, columns, partitions and parquet files
+    /// of the catalog into a new backup, verifying that all parquet files
+    /// exist in the object store of their namespace
+    Create(BackupConfig),
+
+    /// Restore a backup into a catalog containing none of its namespaces,
+    /// validating the whole backup and verifying that all parquet files exist
+    /// in the object store of their namespace before making any change
+    Restore(BackupConfig),
+}
+
+pub async fn command(config: Config) -> Result<(), Error> {
+    match config.command {
+        Command::Create(config) => {
+            let catalog = config
+                .catalog_dsn
+                .get_catalog("cli", setup_metric_registry())
+                .await?;
+            let object_stores = config.object_stores()?;
+
+            let manifest = create_backup(&*catalog, &object_stores, &config.name).await?;
+            println!("Created backup {}: {}", config.name, summary(&manifest));
+        }
+        Command::Restore(config) => {
+            let catalog = config
+                .catalog_dsn
+                .get_catalog("cli", setup_metric_registry())
+                .await?;
+            let object_stores = config.object_stores()?;
+
+            let manifest = restore_backup(&*catalog, &object_stores, &config.name).await?;
+            println!("Restored backup {}: {}", config.name, summary(&manifest));
+        }
+    }
+
+    Ok(())
+}
+
+fn summary(manifest: &Manifest) -> String {
+    format!(
+        "{} namespaces, {} parquet files, created at {}",
+        manifest.namespaces.len(),
+        manifest.parquet_file_count(),
+        Time::from_timestamp_nanos(manifest.created_at).to_rfc3339(),
+    )
+}
@@ -1,6 +1,20 @@
 //! This module implements the `catalog` CLI command
 
+use std::{collections::HashMap, time::Duration};
+
 use clap_blocks::catalog_dsn::CatalogDsnConfig;
+use comfy_table::{Cell, Table};
+use data_types::{
+    ColumnRetention, ColumnType, Namespace, NamespaceDefaultTag, Table as CatalogTable, TableId,
+    TableSchema, TableUsage, TableViewColumn, Timestamp,
+};
+use iox_catalog::interface::{RepoCollection, SoftDeletedRows};
+use iox_time::Time;
+use predicate::{
+    delete_estimate::{estimate_delete, DeleteEstimate},
+    delete_predicate::{parse_delete_predicate, validate_delete_predicate_schema},
+};
+use schema::TIME_COLUMN_NAME;
 use thiserror::Error;
 
 use crate::process_info::setup_metric_registry;
@@ -13,6 +27,33 @@ pub enum Error {
 
     #[error("Catalog DSN error: {0}")]
     CatalogDsn(#[from] clap_blocks::catalog_dsn::Error),
+
+    #[error("Namespace not found: {0}")]
+    NamespaceNotFound(String),
+
+    #[error("Table not found: {0}")]
+    TableNotFound(String),
+
+    #[error("Column not found: {0}")]
+    ColumnNotFound(String),
+
+    #[error("Column {0} is not a field column")]
+    NotAField(String),
+
+    #[error("Invalid default tag name {name:?}: {reason}")]
+    InvalidDefaultTag { name: String, reason: &'static str },
+
+    #[error("Column {column} of table {table} is not a tag column")]
+    NotATag { column: String, table: String },
+
+    #[error("Retention period too large: {0:?}")]
+    RetentionTooLarge(Duration),
+
+    #[error("Invalid delete predicate: {0}")]
+    DeletePredicate(#[from] predicate::delete_predicate::Error),
+
+    #[error("Delete predicate does not match the table schema: {0}")]
+    DeletePredicateSchema(String),
 }
 
 /// Various commands for catalog manipulation
@@ -29,11 +70,238 @@ struct Setup {
     catalog_dsn: CatalogDsnConfig,
 }
 
+/// Show the hourly rows and bytes ingested for each table in a namespace
+#[derive(Debug, clap::Parser)]
+struct Usage {
+    #[clap(flatten)]
+    catalog_dsn: CatalogDsnConfig,
+
+    /// The name of the namespace to report usage for
+    #[clap(long)]
+    namespace: String,
+
+    /// Only show usage recorded within the specified number of past hours
+    #[clap(long, default_value = "24")]
+    since_hours: u64,
+}
+
+/// Add or replace a column of the view of a table
+#[derive(Debug, clap::Parser)]
+struct SetViewColumn {
+    #[clap(flatten)]
+    catalog_dsn: CatalogDsnConfig,
+
+    /// The name of the namespace containing the table
+    #[clap(long)]
+    namespace: String,
+
+    /// The name of the table the view is defined on
+    #[clap(long)]
+    table: String,
+
+    /// The name of the column within the view
+    #[clap(long)]
+    name: String,
+
+    /// The SQL expression over the columns of the table computing the value
+    /// of the view column, for example `"temp" * 1.8 + 32`
+    #[clap(long)]
+    expression: String,
+
+    /// The unit of the values of the view column
+    #[clap(long)]
+    unit: Option<String>,
+}
+
+/// Remove a column from the view of a table
+#[derive(Debug, clap::Parser)]
+struct RemoveViewColumn {
+    #[clap(flatten)]
+    catalog_dsn: CatalogDsnConfig,
+
+    /// The name of the namespace containing the table
+    #[clap(long)]
+    namespace: String,
+
+    /// The name of the table the view is defined on
+    #[clap(long)]
+    table: String,
+
+    /// The name of the column within the view
+    #[clap(long)]
+    name: String,
+}
+
+/// Add or replace a default tag of a namespace
+#[derive(Debug, clap::Parser)]
+struct SetDefaultTag {
+    #[clap(flatten)]
+    catalog_dsn: CatalogDsnConfig,
+
+    /// The name of the namespace
+    #[clap(long)]
+    namespace: String,
+
+    /// The name of the tag added to every row written to the namespace
+    #[clap(long)]
+    name: String,
+
+    /// The value of the tag
+    #[clap(long)]
+    value: String,
+}
+
+/// Remove a default tag of a namespace
+#[derive(Debug, clap::Parser)]
+struct RemoveDefaultTag {
+    #[clap(flatten)]
+    catalog_dsn: CatalogDsnConfig,
+
+    /// The name of the namespace
+    #[clap(long)]
+    namespace: String,
+
+    /// The name of the tag
+    #[clap(long)]
+    name: String,
+}
+
+/// Set the retention period of a field column, shorter than that of the rest
+/// of its table
+#[derive(Debug, clap::Parser)]
+struct SetColumnRetention {
+    #[clap(flatten)]
+    catalog_dsn: CatalogDsnConfig,
+
+    /// The name of the namespace containing the table
+    #[clap(long)]
+    namespace: String,
+
+    /// The name of the table containing the column
+    #[clap(long)]
+    table: String,
+
+    /// The name of the field column
+    #[clap(long)]
+    column: String,
+
+    /// The retention period of the values of the column, for example `7d`
+    #[clap(long, value_parser = humantime::parse_duration)]
+    retention_period: Duration,
+}
+
+/// Remove the retention period of a column
+#[derive(Debug, clap::Parser)]
+struct RemoveColumnRetention {
+    #[clap(flatten)]
+    catalog_dsn: CatalogDsnConfig,
+
+    /// The name of the namespace containing the table
+    #[clap(long)]
+    namespace: String,
+
+    /// The name of the table containing the column
+    #[clap(long)]
+    table: String,
+
+    /// The name of the column
+    #[clap(long)]
+    column: String,
+}
+
+/// Soft-delete a namespace, rejecting further writes to it
+#[derive(Debug, clap::Parser)]
+struct DeleteNamespace {
+    #[clap(flatten)]
+    catalog_dsn: CatalogDsnConfig,
+
+    /// The name of the namespace
+    #[clap(long)]
+    namespace: String,
+}
+
+/// Restore a soft-deleted namespace
+#[derive(Debug, clap::Parser)]
+struct UndeleteNamespace {
+    #[clap(flatten)]
+    catalog_dsn: CatalogDsnConfig,
+
+    /// The name of the namespace
+    #[clap(long)]
+    namespace: String,
+}
+
+/// Estimate the persisted data a delete would affect, without deleting it
+#[derive(Debug, clap::Parser)]
+struct EstimateDelete {
+    #[clap(flatten)]
+    catalog_dsn: CatalogDsnConfig,
+
+    /// The name of the namespace containing the table
+    #[clap(long)]
+    namespace: String,
+
+    /// The name of the table to delete from
+    #[clap(long)]
+    table: String,
+
+    /// The inclusive start of the time range to delete, as an RFC3339
+    /// timestamp or nanoseconds since the epoch
+    #[clap(long)]
+    start: String,
+
+    /// The exclusive end of the time range to delete, as an RFC3339 timestamp
+    /// or nanoseconds since the epoch
+    #[clap(long)]
+    stop: String,
+
+    /// The conjunction of `column = value` and `column != value` expressions
+    /// the deleted rows must match, for example `city = Boston and
+    /// state != 'MA'`
+    #[clap(long, default_value = "")]
+    predicate: String,
+}
+
 /// All possible subcommands for catalog
 #[derive(Debug, clap::Parser)]
 enum Command {
     /// Run database migrations
     Setup(Setup),
+
+    /// Show per-table ingest usage
+    Usage(Usage),
+
+    /// Add or replace a column of the view of a table, queryable as
+    /// `view.<table>`
+    SetViewColumn(SetViewColumn),
+
+    /// Remove a column from the view of a table
+    RemoveViewColumn(RemoveViewColumn),
+
+    /// Add or replace a tag the routers add to every row written to a
+    /// namespace
+    SetDefaultTag(SetDefaultTag),
+
+    /// Remove a default tag of a namespace
+    RemoveDefaultTag(RemoveDefaultTag),
+
+    /// Set the retention period of a field column, after which its values are
+    /// hidden from queries and dropped by the compactor
+    SetColumnRetention(SetColumnRetention),
+
+    /// Remove the retention period of a column
+    RemoveColumnRetention(RemoveColumnRetention),
+
+    /// Soft-delete a namespace. Its data is retained until the namespace is
+    /// hard-deleted.
+    DeleteNamespace(DeleteNamespace),
+
+    /// Restore a soft-deleted namespace
+    UndeleteNamespace(UndeleteNamespace),
+
+    /// Estimate the parquet files, partitions and rows affected by a delete
+    /// from the catalog statistics, without executing it
+    EstimateDelete(EstimateDelete),
 }
 
 pub async fn command(config: Config) -> Result<(), Error> {
@@ -44,7 +312,338 @@ pub async fn command(config: Config) -> Result<(), Error> {
             catalog.setup().await?;
             println!("OK");
         }
+        Command::Usage(command) => {
+            let metrics = setup_metric_registry();
+            let catalog = command.catalog_dsn.get_catalog("cli", metrics).await?;
+            let mut repos = catalog.repositories().await;
+
+            let namespace = repos
+                .namespaces()
+                .get_by_name(&command.namespace, SoftDeletedRows::ExcludeDeleted)
+                .await?
+                .ok_or_else(|| Error::NamespaceNotFound(command.namespace.clone()))?;
+
+            let table_names = repos
+                .tables()
+                .list_by_namespace_id(namespace.id)
+                .await?
+                .into_iter()
+                .map(|t| (t.id, t.name))
+                .collect::<HashMap<_, _>>();
+
+            let since = catalog
+                .time_provider()
+                .now()
+                .checked_sub(Duration::from_secs(command.since_hours * 60 * 60))
+                .map(|t| TableUsage::window_start(Timestamp::from(t)))
+                .unwrap_or(Timestamp::new(0));
+
+            let usage = repos
+                .table_usage()
+                .list_by_namespace(namespace.id, since)
+                .await?;
+
+            println!("{}", create_usage_table(&usage, &table_names));
+        }
+        Command::SetViewColumn(command) => {
+            let metrics = setup_metric_registry();
+            let catalog = command.catalog_dsn.get_catalog("cli", metrics).await?;
+            let mut repos = catalog.repositories().await;
+
+            let table = get_table(repos.as_mut(), &command.namespace, &command.table).await?;
+
+            repos
+                .table_views()
+                .upsert(&TableViewColumn {
+                    table_id: table.id,
+                    name: command.name,
+                    expression: command.expression,
+                    unit: command.unit,
+                })
+                .await?;
+            println!("OK");
+        }
+        Command::RemoveViewColumn(command) => {
+            let metrics = setup_metric_registry();
+            let catalog = command.catalog_dsn.get_catalog("cli", metrics).await?;
+            let mut repos = catalog.repositories().await;
+
+            let table = get_table(repos.as_mut(), &command.namespace, &command.table).await?;
+
+            if repos.table_views().remove(table.id, &command.name).await? {
+                println!("OK");
+            } else {
+                println!("View column {} not found", command.name);
+            }
+        }
+        Command::SetDefaultTag(command) => {
+            let metrics = setup_metric_registry();
+            let catalog = command.catalog_dsn.get_catalog("cli", metrics).await?;
+            let mut repos = catalog.repositories().await;
+
+            let namespace = get_namespace(repos.as_mut(), &command.namespace).await?;
+            validate_default_tag_name(repos.as_mut(), &namespace, &command.name).await?;
+
+            repos
+                .namespace_default_tags()
+                .upsert(&NamespaceDefaultTag {
+                    namespace_id: namespace.id,
+                    name: command.name,
+                    value: command.value,
+                })
+                .await?;
+            println!("OK");
+        }
+        Command::RemoveDefaultTag(command) => {
+            let metrics = setup_metric_registry();
+            let catalog = command.catalog_dsn.get_catalog("cli", metrics).await?;
+            let mut repos = catalog.repositories().await;
+
+            let namespace = get_namespace(repos.as_mut(), &command.namespace).await?;
+
+            if repos
+                .namespace_default_tags()
+                .remove(namespace.id, &command.name)
+                .await?
+            {
+                println!("OK");
+            } else {
+                println!("Default tag {} not found", command.name);
+            }
+        }
+        Command::SetColumnRetention(command) => {
+            let metrics = setup_metric_registry();
+            let catalog = command.catalog_dsn.get_catalog("cli", metrics).await?;
+            let mut repos = catalog.repositories().await;
+
+            let table = get_table(repos.as_mut(), &command.namespace, &command.table).await?;
+            let column = repos
+                .columns()
+                .list_by_table_id(table.id)
+                .await?
+                .into_iter()
+                .find(|c| c.name == command.column)
+                .ok_or_else(|| Error::ColumnNotFound(command.column.clone()))?;
+
+            // Tags and the time column identify the rows of the table, so
+            // cannot be dropped separately from them.
+            if matches!(column.column_type, ColumnType::Tag | ColumnType::Time) {
+                return Err(Error::NotAField(command.column));
+            }
+
+            let retention_period_ns = i64::try_from(command.retention_period.as_nanos())
+                .map_err(|_| Error::RetentionTooLarge(command.retention_period))?;
+
+            repos
+                .column_retention()
+                .upsert(&ColumnRetention {
+                    table_id: table.id,
+                    column_id: column.id,
+                    retention_period_ns,
+                })
+                .await?;
+            println!("OK");
+        }
+        Command::RemoveColumnRetention(command) => {
+            let metrics = setup_metric_registry();
+            let catalog = command.catalog_dsn.get_catalog("cli", metrics).await?;
+            let mut repos = catalog.repositories().await;
+
+            let table = get_table(repos.as_mut(), &command.namespace, &command.table).await?;
+            let column = repos
+                .columns()
+                .list_by_table_id(table.id)
+                .await?
+                .into_iter()
+                .find(|c| c.name == command.column)
+                .ok_or_else(|| Error::ColumnNotFound(command.column.clone()))?;
+
+            if repos.column_retention().remove(column.id).await? {
+                println!("OK");
+            } else {
+                println!("Column {} has no retention period", command.column);
+            }
+        }
+        Command::DeleteNamespace(command) => {
+            let metrics = setup_metric_registry();
+            let catalog = command.catalog_dsn.get_catalog("cli", metrics).await?;
+            let mut repos = catalog.repositories().await;
+
+            let namespace = get_namespace(repos.as_mut(), &command.namespace).await?;
+            repos.namespaces().soft_delete(&namespace.name).await?;
+            println!("OK");
+        }
+        Command::UndeleteNamespace(command) => {
+            let metrics = setup_metric_registry();
+            let catalog = command.catalog_dsn.get_catalog("cli", metrics).await?;
+            let mut repos = catalog.repositories().await;
+
+            let namespace = repos
+                .namespaces()
+                .get_by_name(&command.namespace, SoftDeletedRows::OnlyDeleted)
+                .await?
+                .ok_or_else(|| Error::NamespaceNotFound(command.namespace.clone()))?;
+            repos.namespaces().undelete(&namespace.name).await?;
+            println!("OK");
+        }
+        Command::EstimateDelete(command) => {
+            let metrics = setup_metric_registry();
+            let catalog = command.catalog_dsn.get_catalog("cli", metrics).await?;
+            let mut repos = catalog.repositories().await;
+
+            let table = get_table(repos.as_mut(), &command.namespace, &command.table).await?;
+            let mut schema = TableSchema::new_empty_from(&table);
+            for c in repos.columns().list_by_table_id(table.id).await? {
+                schema.add_column(c);
+            }
+
+            let predicate =
+                parse_delete_predicate(&command.start, &command.stop, &command.predicate)?;
+            validate_delete_predicate_schema(&predicate, &schema).map_err(|violations| {
+                Error::DeletePredicateSchema(
+                    violations
+                        .iter()
+                        .map(ToString::to_string)
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                )
+            })?;
+
+            let files = repos
+                .parquet_files()
+                .list_by_table_not_to_delete(table.id)
+                .await?;
+            let column_statistics = repos
+                .parquet_files()
+                .list_column_statistics_by_table(table.id)
+                .await?;
+
+            let estimate = estimate_delete(&predicate, &schema, &files, &column_statistics);
+            println!("{}", create_delete_estimate_table(&estimate));
+        }
     }
 
     Ok(())
 }
+
+/// Look up the namespace `namespace_name`.
+async fn get_namespace(
+    repos: &mut dyn RepoCollection,
+    namespace_name: &str,
+) -> Result<Namespace, Error> {
+    repos
+        .namespaces()
+        .get_by_name(namespace_name, SoftDeletedRows::ExcludeDeleted)
+        .await?
+        .ok_or_else(|| Error::NamespaceNotFound(namespace_name.to_string()))
+}
+
+/// Check `name` can be added as a tag to every row written to `namespace`.
+///
+/// The name must not be empty or the time column, and must not be the name of
+/// an existing non-tag column of any table of the namespace.
+async fn validate_default_tag_name(
+    repos: &mut dyn RepoCollection,
+    namespace: &Namespace,
+    name: &str,
+) -> Result<(), Error> {
+    let invalid = |reason| Error::InvalidDefaultTag {
+        name: name.to_string(),
+        reason,
+    };
+    if name.is_empty() {
+        return Err(invalid("the name is empty"));
+    }
+    if name == TIME_COLUMN_NAME {
+        return Err(invalid("the name of the time column is reserved"));
+    }
+
+    let columns = repos.columns().list_by_namespace_id(namespace.id).await?;
+    let Some(column) = columns.into_iter().find(|c| c.name == name && !c.is_tag()) else {
+        return Ok(());
+    };
+
+    let table = repos
+        .tables()
+        .list_by_namespace_id(namespace.id)
+        .await?
+        .into_iter()
+        .find(|t| t.id == column.table_id)
+        .map(|t| t.name)
+        .unwrap_or_else(|| column.table_id.to_string());
+    Err(Error::NotATag {
+        column: column.name,
+        table,
+    })
+}
+
+/// Look up the table `table_name` within `namespace_name`.
+async fn get_table(
+    repos: &mut dyn RepoCollection,
+    namespace_name: &str,
+    table_name: &str,
+) -> Result<CatalogTable, Error> {
+    let namespace = get_namespace(repos, namespace_name).await?;
+
+    repos
+        .tables()
+        .get_by_namespace_and_name(namespace.id, table_name)
+        .await?
+        .ok_or_else(|| Error::TableNotFound(table_name.to_string()))
+}
+
+/// Turn a delete estimate into a table
+fn create_delete_estimate_table(estimate: &DeleteEstimate) -> Table {
+    let mut table = Table::new();
+    table.load_preset("||--+-++|    ++++++");
+    table.set_header(vec![Cell::new("estimate"), Cell::new("value")]);
+
+    for (name, value) in [
+        ("files", estimate.files.to_string()),
+        ("partitions", estimate.partitions.to_string()),
+        ("file_size_bytes", estimate.file_size_bytes.to_string()),
+        ("max_rows", estimate.max_rows.to_string()),
+        ("estimated_rows", format!("{:.0}", estimate.estimated_rows)),
+    ] {
+        table.add_row(vec![Cell::new(name), Cell::new(value)]);
+    }
+
+    table
+}
+
+/// Turn table usage records into a table
+fn create_usage_table(usage: &[TableUsage], table_names: &HashMap<TableId, String>) -> Table {
+    let mut table = Table::new();
+    table.load_preset("||--+-++|    ++++++");
+
+    let headers: Vec<_> = [
+        "hour_start",
+        "table",
+        "rows_buffered",
+        "bytes_buffered",
+        "rows_persisted",
+        "bytes_persisted",
+    ]
+    .into_iter()
+    .map(Cell::new)
+    .collect();
+    table.set_header(headers);
+
+    for u in usage {
+        let name = table_names
+            .get(&u.table_id)
+            .cloned()
+            .unwrap_or_else(|| u.table_id.to_string());
+
+        table.add_row(vec![
+            Cell::new(Time::from_timestamp_nanos(u.hour_start.get()).to_rfc3339()),
+            Cell::new(name),
+            Cell::new(u.rows_buffered.to_string()),
+            Cell::new(u.bytes_buffered.to_string()),
+            Cell::new(u.rows_persisted.to_string()),
+            Cell::new(u.bytes_persisted.to_string()),
+        ]);
+    }
+
+    table
+}
@@ -0,0 +1,390 @@
+//! This module implements the `debug dump-buffer` CLI command, which downloads
+//! the data buffered in an ingester for a table into a local directory.
+
+use std::{
+    fs::File,
+    path::{Path, PathBuf},
+};
+
+use arrow::{datatypes::SchemaRef, ipc::writer::FileWriter, record_batch::RecordBatch};
+use arrow_flight::{
+    decode::{DecodedFlightData, DecodedPayload},
+    Ticket,
+};
+use futures::{Stream, TryStreamExt};
+use influxdb_iox_client::connection::Connection;
+use ingester_query_grpc::influxdata::iox::ingester::v1::{
+    ingester_query_response_metadata::PartitionIdentifier, IngesterQueryRequest,
+    IngesterQueryResponseMetadata,
+};
+use prost::Message;
+use serde::Serialize;
+use thiserror::Error;
+
+/// The name of the file describing the dumped partitions.
+const METADATA_FILE: &str = "metadata.json";
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("Error querying ingester: {0}")]
+    Query(#[from] arrow_flight::error::FlightError),
+
+    #[error("Error decoding partition metadata: {0}")]
+    Metadata(#[from] prost::DecodeError),
+
+    #[error("Ingester response contained data before any partition metadata")]
+    MissingPartition,
+
+    #[error("Ingester response contained a record batch before its schema")]
+    MissingSchema,
+
+    #[error("Error writing {}: {source}", path.display())]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+
+    #[error("Error writing Arrow IPC file {}: {source}", path.display())]
+    Arrow {
+        path: PathBuf,
+        source: arrow::error::ArrowError,
+    },
+
+    #[error("Error encoding metadata: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// Download the data buffered by an ingester for a table (including data
+/// currently being persisted) into a local directory, for offline analysis.
+///
+/// The data of each partition is written as one or more Arrow IPC files
+/// (one per distinct schema), alongside a `metadata.json` file describing
+/// each partition and the ingester it was read from.
+#[derive(Debug, clap::Parser)]
+pub struct Config {
+    /// The ID of the namespace containing the table
+    #[clap(action)]
+    namespace_id: i64,
+
+    /// The ID of the table to dump
+    #[clap(action)]
+    table_id: i64,
+
+    /// The directory to write the dumped data to, created if it does not
+    /// exist
+    #[clap(long, short, action)]
+    output_dir: PathBuf,
+
+    /// Only dump the partition with this ID (either a catalog ID, or a
+    /// hex-encoded hash ID)
+    #[clap(long, action)]
+    partition_id: Option<String>,
+}
+
+/// The metadata of a dump, written to [`METADATA_FILE`].
+#[derive(Debug, Serialize)]
+struct DumpMetadata {
+    namespace_id: i64,
+    table_id: i64,
+    partitions: Vec<PartitionMetadata>,
+}
+
+/// The metadata of a single dumped partition.
+#[derive(Debug, Serialize)]
+struct PartitionMetadata {
+    partition_id: String,
+    ingester_uuid: String,
+    completed_persistence_count: u64,
+    persisting_object_store_ids: Vec<String>,
+    row_count: usize,
+    files: Vec<String>,
+}
+
+pub async fn command(connection: Connection, config: Config) -> Result<()> {
+    let client = influxdb_iox_client::flight::Client::new(connection);
+
+    // Request all the columns of the table, with no predicate.
+    let request = IngesterQueryRequest {
+        namespace_id: config.namespace_id,
+        table_id: config.table_id,
+        columns: vec![],
+        predicate: None,
+        max_snapshots: 0,
+        resume_after: vec![],
+    };
+    let ticket = Ticket {
+        ticket: request.encode_to_vec().into(),
+    };
+
+    let stream = client.into_inner().do_get(ticket).await?.into_inner();
+
+    std::fs::create_dir_all(&config.output_dir).map_err(|source| Error::Io {
+        path: config.output_dir.clone(),
+        source,
+    })?;
+
+    let metadata = dump(
+        stream,
+        &config.output_dir,
+        config.namespace_id,
+        config.table_id,
+        config.partition_id.as_deref(),
+    )
+    .await?;
+
+    println!(
+        "Dumped {} partitions ({} rows) to {}",
+        metadata.partitions.len(),
+        metadata
+            .partitions
+            .iter()
+            .map(|p| p.row_count)
+            .sum::<usize>(),
+        config.output_dir.display()
+    );
+
+    Ok(())
+}
+
+/// An open Arrow IPC file for the current schema of a partition.
+struct OpenFile {
+    path: PathBuf,
+    writer: FileWriter<File>,
+}
+
+impl OpenFile {
+    fn finish(mut self) -> Result<()> {
+        self.writer.finish().map_err(|source| Error::Arrow {
+            path: self.path,
+            source,
+        })
+    }
+}
+
+/// Write the partitions in `stream` to `dir`, returning the [`DumpMetadata`]
+/// that was written to [`METADATA_FILE`].
+///
+/// If `partition_filter` is [`Some`], partitions with a different ID are
+/// skipped.
+async fn dump<S>(
+    mut stream: S,
+    dir: &Path,
+    namespace_id: i64,
+    table_id: i64,
+    partition_filter: Option<&str>,
+) -> Result<DumpMetadata>
+where
+    S: Stream<Item = Result<DecodedFlightData, arrow_flight::error::FlightError>> + Unpin,
+{
+    let mut metadata = DumpMetadata {
+        namespace_id,
+        table_id,
+        partitions: vec![],
+    };
+
+    // The partition currently being read, and whether it is to be dumped.
+    let mut partition: Option<(PartitionMetadata, bool)> = None;
+    let mut file: Option<OpenFile> = None;
+
+    while let Some(data) = stream.try_next().await? {
+        match data.payload {
+            DecodedPayload::None => {
+                // The start of a new partition.
+                if let Some(f) = file.take() {
+                    f.finish()?;
+                }
+                if let Some((p, true)) = partition.take() {
+                    metadata.partitions.push(p);
+                }
+
+                let md = IngesterQueryResponseMetadata::decode(data.inner.app_metadata)?;
+                let partition_id = match md.partition_identifier {
+                    Some(PartitionIdentifier::CatalogId(id)) => id.to_string(),
+                    Some(PartitionIdentifier::HashId(bytes)) => {
+                        bytes.iter().map(|b| format!("{b:02x}")).collect()
+                    }
+                    None => "unknown".to_string(),
+                };
+                let wanted = partition_filter.map(|v| v == partition_id).unwrap_or(true);
+
+                partition = Some((
+                    PartitionMetadata {
+                        partition_id,
+                        ingester_uuid: md.ingester_uuid,
+                        completed_persistence_count: md.completed_persistence_count,
+                        persisting_object_store_ids: md.persisting_object_store_ids,
+                        row_count: 0,
+                        files: vec![],
+                    },
+                    wanted,
+                ));
+            }
+            DecodedPayload::Schema(schema) => {
+                let (p, wanted) = partition.as_mut().ok_or(Error::MissingPartition)?;
+                if let Some(f) = file.take() {
+                    f.finish()?;
+                }
+                if *wanted {
+                    file = Some(open_file(dir, p, &schema)?);
+                }
+            }
+            DecodedPayload::RecordBatch(batch) => {
+                let (p, wanted) = partition.as_mut().ok_or(Error::MissingPartition)?;
+                if !*wanted {
+                    continue;
+                }
+                let f = file.as_mut().ok_or(Error::MissingSchema)?;
+                write_batch(f, &batch)?;
+                p.row_count += batch.num_rows();
+            }
+        }
+    }
+
+    if let Some(f) = file.take() {
+        f.finish()?;
+    }
+    if let Some((p, true)) = partition.take() {
+        metadata.partitions.push(p);
+    }
+
+    let path = dir.join(METADATA_FILE);
+    let json = serde_json::to_vec_pretty(&metadata)?;
+    std::fs::write(&path, json).map_err(|source| Error::Io { path, source })?;
+
+    Ok(metadata)
+}
+
+/// Create a new Arrow IPC file in `dir` for the data of `partition` with
+/// `schema`, recording it in the partition metadata.
+fn open_file(
+    dir: &Path,
+    partition: &mut PartitionMetadata,
+    schema: &SchemaRef,
+) -> Result<OpenFile> {
+    let name = format!("{}-{}.arrow", partition.partition_id, partition.files.len());
+    let path = dir.join(&name);
+
+    let f = File::create(&path).map_err(|source| Error::Io {
+        path: path.clone(),
+        source,
+    })?;
+    let writer = FileWriter::try_new(f, schema).map_err(|source| Error::Arrow {
+        path: path.clone(),
+        source,
+    })?;
+
+    partition.files.push(name);
+    Ok(OpenFile { path, writer })
+}
+
+fn write_batch(f: &mut OpenFile, batch: &RecordBatch) -> Result<()> {
+    f.writer.write(batch).map_err(|source| Error::Arrow {
+        path: f.path.clone(),
+        source,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use arrow::{array::Int64Array, ipc::reader::FileReader};
+    use arrow_flight::FlightData;
+    use assert_matches::assert_matches;
+    use futures::StreamExt;
+
+    use super::*;
+
+    fn partition_header(id: PartitionIdentifier) -> DecodedFlightData {
+        let md = IngesterQueryResponseMetadata {
+            partition_identifier: Some(id),
+            ingester_uuid: "bananas".to_string(),
+            completed_persistence_count: 3,
+            persisting_object_store_ids: vec!["platanos".to_string()],
+            resume_after_sequence_number: None,
+            buffered_data_omitted: false,
+        };
+        DecodedFlightData::new_none(FlightData::new().with_app_metadata(md.encode_to_vec()))
+    }
+
+    fn batch(values: &[i64]) -> RecordBatch {
+        RecordBatch::try_from_iter([("v", Arc::new(Int64Array::from(values.to_vec())) as _)])
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_dump() {
+        let dir = test_helpers::tmp_dir().unwrap();
+
+        let b1 = batch(&[1, 2]);
+        let b2 = batch(&[3]);
+        let stream = futures::stream::iter([
+            partition_header(PartitionIdentifier::CatalogId(42)),
+            DecodedFlightData::new_schema(FlightData::new(), b1.schema()),
+            DecodedFlightData::new_record_batch(FlightData::new(), b1.clone()),
+            DecodedFlightData::new_record_batch(FlightData::new(), b2.clone()),
+            partition_header(PartitionIdentifier::HashId(vec![0xab, 0x01])),
+            DecodedFlightData::new_schema(FlightData::new(), b2.schema()),
+            DecodedFlightData::new_record_batch(FlightData::new(), b2.clone()),
+        ])
+        .map(Ok);
+
+        let got = dump(stream, dir.path(), 1, 2, None).await.unwrap();
+        assert_eq!(
+            got.partitions
+                .iter()
+                .map(|p| (p.partition_id.as_str(), p.row_count, p.files.clone()))
+                .collect::<Vec<_>>(),
+            [
+                ("42", 3, vec!["42-0.arrow".to_string()]),
+                ("ab01", 1, vec!["ab01-0.arrow".to_string()]),
+            ]
+        );
+        assert_eq!(got.partitions[0].ingester_uuid, "bananas");
+        assert_eq!(got.partitions[0].completed_persistence_count, 3);
+
+        // The IPC files contain the partition data.
+        let reader =
+            FileReader::try_new(File::open(dir.path().join("42-0.arrow")).unwrap(), None).unwrap();
+        let batches = reader.collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(batches, [b1, b2]);
+
+        assert!(dir.path().join(METADATA_FILE).exists());
+    }
+
+    #[tokio::test]
+    async fn test_dump_partition_filter() {
+        let dir = test_helpers::tmp_dir().unwrap();
+
+        let b = batch(&[1]);
+        let stream = futures::stream::iter([
+            partition_header(PartitionIdentifier::CatalogId(1)),
+            DecodedFlightData::new_schema(FlightData::new(), b.schema()),
+            DecodedFlightData::new_record_batch(FlightData::new(), b.clone()),
+            partition_header(PartitionIdentifier::CatalogId(2)),
+            DecodedFlightData::new_schema(FlightData::new(), b.schema()),
+            DecodedFlightData::new_record_batch(FlightData::new(), b.clone()),
+        ])
+        .map(Ok);
+
+        let got = dump(stream, dir.path(), 1, 2, Some("2")).await.unwrap();
+        assert_eq!(got.partitions.len(), 1);
+        assert_eq!(got.partitions[0].partition_id, "2");
+        assert!(!dir.path().join("1-0.arrow").exists());
+    }
+
+    #[tokio::test]
+    async fn test_dump_missing_partition() {
+        let dir = test_helpers::tmp_dir().unwrap();
+
+        let b = batch(&[1]);
+        let stream =
+            futures::stream::iter([DecodedFlightData::new_schema(FlightData::new(), b.schema())])
+                .map(Ok);
+
+        let got = dump(stream, dir.path(), 1, 2, None).await;
+        assert_matches!(got, Err(Error::MissingPartition));
+    }
+}
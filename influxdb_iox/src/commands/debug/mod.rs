@@ -3,11 +3,15 @@ use influxdb_iox_client::connection::Connection;
 use snafu::prelude::*;
 
 mod build_catalog;
+mod dump_buffer;
 mod parquet_to_lp;
+mod persist_jobs;
 mod print_cpu;
+mod profile;
 mod schema;
 mod skipped_compactions;
 mod wal;
+mod write_info;
 
 #[derive(Debug, Snafu)]
 pub enum Error {
@@ -19,10 +23,22 @@ pub enum Error {
     #[snafu(display("Error in build_catalog subcommand: {}", source))]
     BuildCatalog { source: build_catalog::Error },
 
+    #[snafu(context(false))]
+    #[snafu(display("Error in dump-buffer subcommand: {}", source))]
+    DumpBuffer { source: dump_buffer::Error },
+
     #[snafu(context(false))]
     #[snafu(display("Error in parquet_to_lp subcommand: {}", source))]
     ParquetToLp { source: parquet_to_lp::Error },
 
+    #[snafu(context(false))]
+    #[snafu(display("Error in persist-jobs subcommand: {}", source))]
+    PersistJobs { source: persist_jobs::Error },
+
+    #[snafu(context(false))]
+    #[snafu(display("Error in profile subcommand: {}", source))]
+    Profile { source: profile::Error },
+
     #[snafu(context(false))]
     #[snafu(display("Error in skipped-compactions subcommand: {}", source))]
     SkippedCompactions { source: skipped_compactions::Error },
@@ -30,6 +46,10 @@ pub enum Error {
     #[snafu(context(false))]
     #[snafu(display("Error in wal subcommand: {}", source))]
     Wal { source: wal::Error },
+
+    #[snafu(context(false))]
+    #[snafu(display("Error in write-info subcommand: {}", source))]
+    WriteInfo { source: write_info::Error },
 }
 
 pub type Result<T, E = Error> = std::result::Result<T, E>;
@@ -66,14 +86,29 @@ enum Command {
     #[clap(verbatim_doc_comment)]
     BuildCatalog(build_catalog::Config),
 
+    /// Download the data buffered in an ingester for a table into a local
+    /// directory as Arrow IPC files
+    DumpBuffer(dump_buffer::Config),
+
     /// Convert IOx Parquet files back into line protocol format
     ParquetToLp(parquet_to_lp::Config),
 
+    /// List the persist jobs executing in an ingester
+    PersistJobs(persist_jobs::Config),
+
+    /// Retrieve memory accounting, task dumps and heap profiles from a
+    /// running server
+    Profile(profile::Config),
+
     /// Interrogate skipped compactions
     SkippedCompactions(skipped_compactions::Config),
 
     /// Subcommands for debugging the WAL
     Wal(wal::Config),
+
+    /// Report the progress of the writes to each partition buffered in an
+    /// ingester
+    WriteInfo(write_info::Config),
 }
 
 pub async fn command<C, CFut>(connection: C, config: Config) -> Result<()>
@@ -88,12 +123,25 @@ where
             schema::command(connection, config).await?
         }
         Command::BuildCatalog(config) => build_catalog::command(config).await?,
+        Command::DumpBuffer(config) => {
+            let connection = connection().await;
+            dump_buffer::command(connection, config).await?
+        }
         Command::ParquetToLp(config) => parquet_to_lp::command(config).await?,
+        Command::PersistJobs(config) => {
+            let connection = connection().await;
+            persist_jobs::command(connection, config).await?
+        }
+        Command::Profile(config) => profile::command(config).await?,
         Command::SkippedCompactions(config) => {
             let connection = connection().await;
             skipped_compactions::command(connection, config).await?
         }
         Command::Wal(config) => wal::command(connection, config).await?,
+        Command::WriteInfo(config) => {
+            let connection = connection().await;
+            write_info::command(connection, config).await?
+        }
     }
 
     Ok(())
@@ -0,0 +1,99 @@
+//! This module implements the `persist-jobs` CLI command
+
+use std::time::Duration;
+
+use comfy_table::{Cell, Table};
+use data_types::TransitionPartitionId;
+use influxdb_iox_client::{
+    connection::Connection,
+    ingester::{
+        self,
+        generated_types::{PersistJob, PersistJobStage},
+    },
+};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("Client error: {0}")]
+    Client(#[from] influxdb_iox_client::error::Error),
+}
+
+/// List the persist jobs currently executing in the ingester, reporting the
+/// stage each job is in and for how long.
+///
+/// A job spending a long time in the "committing" stage is likely retrying a
+/// failing catalog operation.
+#[derive(Debug, clap::Parser)]
+pub struct Config {}
+
+pub async fn command(connection: Connection, _config: Config) -> Result<(), Error> {
+    let mut client = ingester::Client::new(connection);
+    let jobs = client.list_persist_jobs().await?;
+
+    println!("{}", create_table(&jobs));
+
+    Ok(())
+}
+
+/// Turn persist job records into a table
+fn create_table(jobs: &[PersistJob]) -> Table {
+    let mut table = Table::new();
+    table.load_preset("||--+-++|    ++++++");
+
+    let headers: Vec<_> = [
+        "namespace_id",
+        "table_id",
+        "partition_id",
+        "stage",
+        "active_for",
+        "in_stage_for",
+        "input_bytes",
+        "file_bytes",
+        "restarts",
+    ]
+    .into_iter()
+    .map(Cell::new)
+    .collect();
+    table.set_header(headers);
+
+    for job in jobs {
+        let partition_id = job
+            .partition_identifier
+            .clone()
+            .and_then(|id| TransitionPartitionId::try_from(id).ok())
+            .map(|id| id.to_string())
+            .unwrap_or_else(|| "unknown".to_string());
+
+        let stage = match job.stage() {
+            PersistJobStage::Compacting => "compacting",
+            PersistJobStage::Uploading => "uploading",
+            PersistJobStage::Committing => "committing",
+            PersistJobStage::Unspecified => "unknown",
+        };
+
+        table.add_row(vec![
+            Cell::new(job.namespace_id.to_string()),
+            Cell::new(job.table_id.to_string()),
+            Cell::new(partition_id),
+            Cell::new(stage),
+            Cell::new(format!(
+                "{:?}",
+                Duration::from_millis(job.active_duration_millis)
+            )),
+            Cell::new(format!(
+                "{:?}",
+                Duration::from_millis(job.stage_duration_millis)
+            )),
+            Cell::new(job.input_bytes.to_string()),
+            Cell::new(
+                job.file_bytes
+                    .map(|v| v.to_string())
+                    .unwrap_or_else(|| "-".to_string()),
+            ),
+            Cell::new(job.restarts.to_string()),
+        ]);
+    }
+
+    table
+}
@@ -0,0 +1,103 @@
+//! This module implements the `profile` CLI command
+
+use std::path::PathBuf;
+
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("HTTP request error: {0}")]
+    Request(#[from] reqwest::Error),
+
+    #[error("Server returned {status}: {body}")]
+    Server {
+        status: reqwest::StatusCode,
+        body: String,
+    },
+
+    #[error("Error writing profile to {path:?}: {source}")]
+    Write {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+}
+
+/// Retrieve runtime profiling data from the HTTP API of a running server,
+/// without restarting it.
+#[derive(Debug, clap::Parser)]
+pub struct Config {
+    /// The base URL of the HTTP API of the server to profile.
+    #[clap(
+        long = "http-host",
+        env = "INFLUXDB_IOX_HTTP_HOST",
+        default_value = "http://127.0.0.1:8080",
+        action
+    )]
+    http_host: String,
+
+    #[clap(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, clap::Parser)]
+enum Command {
+    /// Print the approximate memory held by each subsystem of the server, as
+    /// JSON.
+    Memory,
+
+    /// Print the stack trace of every task in the server's tokio runtime.
+    ///
+    /// Requires the server to be built with
+    /// `RUSTFLAGS="--cfg tokio_unstable --cfg tokio_taskdump"`.
+    Tasks,
+
+    /// Write a heap profile of the server to a file.
+    ///
+    /// Requires a server built with the `jemalloc_profiling` feature, and
+    /// started with `MALLOC_CONF=prof:true`. The profile is read with `jeprof`.
+    Heap {
+        /// The file to write the heap profile to.
+        #[clap(long, short, action)]
+        output: PathBuf,
+    },
+}
+
+pub async fn command(config: Config) -> Result<(), Error> {
+    let base = config.http_host.trim_end_matches('/');
+
+    match config.command {
+        Command::Memory => {
+            let body = get(&format!("{base}/debug/memory")).await?;
+            println!("{}", String::from_utf8_lossy(&body));
+        }
+        Command::Tasks => {
+            let body = get(&format!("{base}/debug/tasks")).await?;
+            println!("{}", String::from_utf8_lossy(&body));
+        }
+        Command::Heap { output } => {
+            let body = get(&format!("{base}/debug/pprof/heap")).await?;
+            std::fs::write(&output, &body).map_err(|source| Error::Write {
+                path: output.clone(),
+                source,
+            })?;
+            println!("wrote {} byte heap profile to {:?}", body.len(), output);
+        }
+    }
+
+    Ok(())
+}
+
+/// Fetch `url`, returning the response body.
+async fn get(url: &str) -> Result<Vec<u8>, Error> {
+    let response = reqwest::get(url).await?;
+
+    let status = response.status();
+    if !status.is_success() {
+        return Err(Error::Server {
+            status,
+            body: response.text().await?,
+        });
+    }
+
+    Ok(response.bytes().await?.to_vec())
+}
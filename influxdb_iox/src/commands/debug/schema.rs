@@ -28,11 +28,27 @@ struct Get {
     namespace: String,
 }
 
+/// Get the effective schema of a table, including columns only present in
+/// data buffered by the ingesters
+#[derive(Debug, clap::Parser)]
+struct Effective {
+    /// The name of the namespace containing the table
+    #[clap(action)]
+    namespace: String,
+
+    /// The name of the table for which you want to fetch the schema
+    #[clap(action)]
+    table: String,
+}
+
 /// All possible subcommands for catalog
 #[derive(Debug, clap::Parser)]
 enum Command {
     /// Fetch schema for a namespace
     Get(Get),
+
+    /// Fetch the effective schema of a table from a querier
+    Effective(Effective),
 }
 
 pub async fn command(connection: Connection, config: Config) -> Result<(), Error> {
@@ -41,6 +57,13 @@ pub async fn command(connection: Connection, config: Config) -> Result<(), Error
             let mut client = schema::Client::new(connection);
             let schema = client.get_schema(&command.namespace, None).await?;
             println!("{}", serde_json::to_string_pretty(&schema)?);
+        }
+        Command::Effective(command) => {
+            let mut client = schema::Client::new(connection);
+            let schema = client
+                .get_effective_table_schema(&command.namespace, &command.table)
+                .await?;
+            println!("{}", serde_json::to_string_pretty(&schema)?);
         } // Deliberately not adding _ => so the compiler will direct people here to impl new
           // commands
     }
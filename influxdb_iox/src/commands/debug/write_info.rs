@@ -0,0 +1,75 @@
+//! This module implements the `write-info` CLI command
+
+use comfy_table::{Cell, Table};
+use data_types::TransitionPartitionId;
+use influxdb_iox_client::{
+    connection::Connection,
+    ingester::{self, generated_types::PartitionWriteInfo},
+};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("Client error: {0}")]
+    Client(#[from] influxdb_iox_client::error::Error),
+}
+
+/// List the partitions with data buffered in the ingester, reporting the
+/// sequence numbers of the buffered writes, the highest sequence number
+/// persisted and the buffered size of each.
+///
+/// Sequence numbers are only comparable within a single run of the ingester.
+#[derive(Debug, clap::Parser)]
+pub struct Config {}
+
+pub async fn command(connection: Connection, _config: Config) -> Result<(), Error> {
+    let mut client = ingester::Client::new(connection);
+    let partitions = client.get_write_info().await?;
+
+    println!("{}", create_table(&partitions));
+
+    Ok(())
+}
+
+/// Turn partition write info records into a table
+fn create_table(partitions: &[PartitionWriteInfo]) -> Table {
+    let mut table = Table::new();
+    table.load_preset("||--+-++|    ++++++");
+
+    let headers: Vec<_> = [
+        "namespace_id",
+        "table_id",
+        "partition_id",
+        "min_buffered",
+        "max_buffered",
+        "max_persisted",
+        "buffered_bytes",
+    ]
+    .into_iter()
+    .map(Cell::new)
+    .collect();
+    table.set_header(headers);
+
+    let optional = |v: Option<u64>| v.map(|v| v.to_string()).unwrap_or_else(|| "-".to_string());
+
+    for p in partitions {
+        let partition_id = p
+            .partition_identifier
+            .clone()
+            .and_then(|id| TransitionPartitionId::try_from(id).ok())
+            .map(|id| id.to_string())
+            .unwrap_or_else(|| "unknown".to_string());
+
+        table.add_row(vec![
+            Cell::new(p.namespace_id.to_string()),
+            Cell::new(p.table_id.to_string()),
+            Cell::new(partition_id),
+            Cell::new(optional(p.min_buffered_sequence_number)),
+            Cell::new(optional(p.max_buffered_sequence_number)),
+            Cell::new(optional(p.max_persisted_sequence_number)),
+            Cell::new(p.buffered_bytes.to_string()),
+        ]);
+    }
+
+    table
+}
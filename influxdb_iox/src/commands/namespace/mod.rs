@@ -5,6 +5,9 @@ use thiserror::Error;
 
 mod create;
 mod delete;
+mod object_store;
+mod provision;
+mod read_only;
 mod retention;
 mod update_limit;
 
@@ -42,6 +45,15 @@ enum Command {
     /// Update one of the service protection limits for an existing namespace
     UpdateLimit(update_limit::Config),
 
+    /// Mark an existing namespace as read-only, or writable again
+    ReadOnly(read_only::Config),
+
+    /// Set the object store an existing namespace's data is stored in
+    ObjectStore(object_store::Config),
+
+    /// Compare the schema of an existing namespace to a template namespace
+    Provision(provision::Config),
+
     /// Delete a namespace
     Delete(delete::Config),
 }
@@ -62,6 +74,15 @@ pub async fn command(connection: Connection, config: Config) -> Result<()> {
         Command::UpdateLimit(config) => {
             update_limit::command(connection, config).await?;
         }
+        Command::ReadOnly(config) => {
+            read_only::command(connection, config).await?;
+        }
+        Command::ObjectStore(config) => {
+            object_store::command(connection, config).await?;
+        }
+        Command::Provision(config) => {
+            provision::command(connection, config).await?;
+        }
         Command::Delete(config) => {
             delete::command(connection, config).await?;
         } // Deliberately not adding _ => so the compiler will direct people here to impl new
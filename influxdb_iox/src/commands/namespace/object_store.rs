@@ -0,0 +1,44 @@
+use influxdb_iox_client::connection::Connection;
+
+use crate::commands::namespace::Result;
+
+/// Update the object store the specified namespace's parquet files are stored
+/// in.
+///
+/// The object store must be configured on all ingesters, queriers, compactors
+/// and garbage collectors. Existing parquet files are not moved, so the
+/// object store can only be changed while the namespace has no parquet files.
+/// Components pick up the change within a minute.
+#[derive(Debug, clap::Parser)]
+#[clap(group(
+    clap::ArgGroup::new("target")
+        .required(true)
+        .args(&["name", "clear"])
+))]
+pub struct Config {
+    /// The namespace to update the object store for
+    #[clap(action)]
+    namespace: String,
+
+    /// The name of the configured object store to use
+    #[clap(action)]
+    name: Option<String>,
+
+    /// Use the default object store
+    #[clap(action, long = "clear")]
+    clear: bool,
+}
+
+pub async fn command(connection: Connection, config: Config) -> Result<()> {
+    let Config {
+        namespace, name, ..
+    } = config;
+
+    let mut client = influxdb_iox_client::namespace::Client::new(connection);
+    let namespace = client
+        .update_namespace_object_store(&namespace, name.as_deref())
+        .await?;
+    println!("{}", serde_json::to_string_pretty(&namespace)?);
+
+    Ok(())
+}
@@ -0,0 +1,33 @@
+use influxdb_iox_client::connection::Connection;
+
+use crate::commands::namespace::Result;
+
+/// Compare the schema of a namespace to that of a template namespace.
+///
+/// Prints the tables and columns of the template missing from the namespace,
+/// and the columns whose types conflict.
+#[derive(Debug, clap::Parser)]
+pub struct Config {
+    /// The namespace whose schema is compared
+    #[clap(action)]
+    namespace: String,
+
+    /// The namespace whose schema is used as the template
+    #[clap(action, long = "template")]
+    template: String,
+}
+
+pub async fn command(connection: Connection, config: Config) -> Result<()> {
+    let Config {
+        namespace,
+        template,
+    } = config;
+
+    let mut client = influxdb_iox_client::schema::Client::new(connection);
+    let plan = client
+        .compare_namespace_schemas(&template, &namespace)
+        .await?;
+    println!("{}", serde_json::to_string_pretty(&plan)?);
+
+    Ok(())
+}
@@ -0,0 +1,30 @@
+use influxdb_iox_client::connection::Connection;
+
+use crate::commands::namespace::Result;
+
+/// Update the specified namespace's read-only flag.
+///
+/// Writes to a read-only namespace are rejected by the routers, while queries
+/// continue to be served.
+#[derive(Debug, clap::Parser)]
+pub struct Config {
+    /// The namespace to update the read-only flag for
+    #[clap(action)]
+    namespace: String,
+
+    /// Allow writes to the namespace again, clearing the read-only flag
+    #[clap(action, long = "clear")]
+    clear: bool,
+}
+
+pub async fn command(connection: Connection, config: Config) -> Result<()> {
+    let Config { namespace, clear } = config;
+
+    let mut client = influxdb_iox_client::namespace::Client::new(connection);
+    let namespace = client
+        .update_namespace_read_only(&namespace, !clear)
+        .await?;
+    println!("{}", serde_json::to_string_pretty(&namespace)?);
+
+    Ok(())
+}
@@ -23,6 +23,9 @@ pub struct PartitionTemplateConfig {
     ///    If they are not in the right format the server expcected, the server will return error.
     ///    Note that "time" is a reserved word and cannot be used in timeFormat.
     ///
+    ///  - A tag can be hashed into a fixed number of buckets to spread very high cardinality
+    ///    tags across partitions, e.g. {"bucket": {"tagName": "col1", "numBuckets": 16}}
+    ///
     ///  - The number of timeFormats and tagValues are not limited at parsing time. Server limits
     ///    the total number of them and will send back error if it exceeds the limit 8.
     #[clap(
@@ -0,0 +1,107 @@
+//! This module implements the `query-admin` CLI command
+
+use arrow::record_batch::RecordBatch;
+use futures::TryStreamExt;
+use influxdb_iox_client::{connection::Connection, flight, format::QueryOutputFormat, query_admin};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("Error formatting: {0}")]
+    Formatting(#[from] influxdb_iox_client::format::Error),
+
+    #[error("Error querying: {0}")]
+    Query(#[from] influxdb_iox_client::flight::Error),
+
+    #[error("JSON Serialization error: {0}")]
+    Serde(#[from] serde_json::Error),
+
+    #[error("Client error: {0}")]
+    Client(#[from] influxdb_iox_client::error::Error),
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// List and kill the queries running on a querier, or list the query history
+#[derive(Debug, clap::Parser)]
+pub struct Config {
+    #[clap(subcommand)]
+    command: Command,
+}
+
+/// All possible subcommands for query-admin
+#[derive(Debug, clap::Parser)]
+enum Command {
+    /// List the queries of a namespace currently running on the querier
+    List {
+        /// The IOx namespace to list the queries of
+        #[clap(action)]
+        namespace: String,
+    },
+
+    /// Kill a query running on the querier
+    Kill {
+        /// The IOx namespace the query is run against
+        #[clap(action)]
+        namespace: String,
+
+        /// The ID of the query to kill, as returned by `query-admin list`
+        #[clap(action)]
+        id: String,
+    },
+
+    /// List the most recent queries recorded in the query history of a
+    /// namespace, and the resources used to execute them
+    ///
+    /// The queriers only record queries if configured with a query history
+    /// retention period.
+    History {
+        /// The IOx namespace the queries were executed against
+        #[clap(action)]
+        namespace: String,
+
+        /// The maximum number of queries to list
+        #[clap(long, action, default_value_t = 100)]
+        limit: usize,
+    },
+}
+
+pub async fn command(connection: Connection, config: Config) -> Result<()> {
+    match config.command {
+        Command::List { namespace } => {
+            let mut client = query_admin::Client::new(connection);
+            let queries = client.list_queries(namespace).await?;
+            println!("{}", serde_json::to_string_pretty(&queries)?);
+        }
+        Command::Kill { namespace, id } => {
+            let mut client = query_admin::Client::new(connection);
+            let query = client.kill_query(namespace, id).await?;
+            println!("{}", serde_json::to_string_pretty(&query)?);
+        }
+        Command::History { namespace, limit } => {
+            // The query history is a debug system table.
+            let mut client = flight::Client::new(connection);
+            client.add_header("iox-debug", "true")?;
+
+            let query = format!(
+                "SELECT * FROM system.query_history ORDER BY issue_time DESC LIMIT {limit}"
+            );
+            let mut query_results = client.sql(namespace, query).await?;
+            let mut batches: Vec<_> = (&mut query_results).try_collect().await?;
+
+            // read schema AFTER collection, otherwise the stream does not have the schema data yet
+            let schema = query_results
+                .inner()
+                .schema()
+                .cloned()
+                .ok_or(influxdb_iox_client::flight::Error::NoSchema)?;
+
+            // preserve schema so we print table headers even for empty results
+            batches.push(RecordBatch::new_empty(schema));
+
+            println!("{}", QueryOutputFormat::Pretty.format(&batches)?);
+        }
+    }
+
+    Ok(())
+}
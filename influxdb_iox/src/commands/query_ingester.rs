@@ -74,6 +74,8 @@ pub async fn command(connection: Connection, config: Config) -> Result<()> {
         table_id,
         columns,
         predicate,
+        max_snapshots: 0,
+        resume_after: vec![],
         namespace_id,
     };
 
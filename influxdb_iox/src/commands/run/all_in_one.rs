@@ -486,11 +486,43 @@ impl Config {
             rpc_write_max_incoming_bytes: 1024 * 1024 * 1024, // 1GiB
             gossip_config: GossipConfig::disabled(),
             max_partitions_per_namespace: None,
+            max_buffered_bytes_per_namespace: None,
+            max_rows_per_second_per_namespace: None,
+            buffer_invariant_check_interval_seconds: None,
+            table_usage_flush_interval_seconds: 60,
+            recent_persisted_cache_bytes: 0,
+            persist_memory_limit_bytes: None,
+            persist_circuit_breaker_threshold: NonZeroUsize::new(10).unwrap(),
+            wal_replay_accept_data_loss: false,
+            wal_replay_throttle_threshold_millis: None,
+            query_shed_degrade_percent: None,
+            query_shed_reject_percent: None,
+            query_shed_retry_after_seconds: 5,
+            field_value_policy: Default::default(),
+            partition_seal_age_seconds: None,
+            persist_barrier_groups: vec![],
+            rollups: vec![],
+            rollup_state_retention_seconds: 3600,
+            idle_namespace_persist_seconds: None,
+            persist_drop_null_columns: false,
+            skip_catalog_warm_up: false,
+            ingest_rate_check_interval_seconds: None,
+            ingest_rate_drop_percent: 50,
+            ingest_rate_spike_percent: 400,
+            query_response_gzip: false,
+            query_max_response_message_bytes: None,
+            // Never exhaust the executor slots in all-in-one mode.
+            exec_priority_slots: NonZeroUsize::new(
+                concurrent_query_limit + persist_max_parallelism,
+            )
+            .unwrap_or_else(|| NonZeroUsize::new(1).unwrap()),
         };
 
         let router_config = RouterConfig {
             authz_address: authz_address.clone(),
             single_tenant_deployment,
+            authz_token_file: None,
+            authz_cache_ttl: Duration::from_secs(10),
             http_request_limit: 1_000,
             ingester_addresses: ingester_addresses.clone(),
             new_namespace_retention_hours: None, // infinite retention
@@ -499,6 +531,21 @@ impl Config {
             rpc_write_replicas: 1.try_into().unwrap(),
             rpc_write_max_outgoing_bytes: ingester_config.rpc_write_max_incoming_bytes,
             rpc_write_health_num_probes: 10,
+            rpc_write_max_queued_requests: None,
+            rpc_write_queue_overflow: Default::default(),
+            ingester_table_spread: None,
+            rpc_write_group_commit_window: Duration::ZERO,
+            namespace_read_only_cache_ttl: Duration::from_secs(10),
+            namespace_default_tags_cache_ttl: Duration::from_secs(10),
+            namespace_default_tags_conflict: Default::default(),
+            write_naming_mode: Default::default(),
+            write_naming_max_length: None,
+            write_naming_reserved_prefixes: vec![],
+            write_naming_restrict_charset: false,
+            field_value_policy: Default::default(),
+            drop_rows_outside_retention: false,
+            namespace_cache_validation_interval_seconds: None,
+            namespace_cache_validation_sample_size: 10,
             gossip_config: GossipConfig::disabled(),
         };
 
@@ -524,11 +571,15 @@ impl Config {
             max_num_columns_per_table: 200,
             max_num_files_per_plan: 200,
             max_partition_fetch_queries_per_second: Some(500),
+            bloom_filter_columns: vec![],
+            bloom_filter_fpp: 0.01,
+            bloom_filter_ndv: 100_000,
             gossip_config: GossipConfig::disabled(),
         };
 
         let querier_config = QuerierConfig {
             authz_address,
+            authz_token_file: None,
             num_query_threads: None, // will be ignored
             ingester_addresses,
             ram_pool_metadata_bytes: querier_ram_pool_metadata_bytes,
@@ -536,7 +587,17 @@ impl Config {
             max_concurrent_queries: querier_max_concurrent_queries,
             exec_mem_pool_bytes,
             ingester_circuit_breaker_threshold: u64::MAX, // never for all-in-one-mode
+            ingester_table_spread: None,
+            ingester_query_gzip: false,
+            ingester_query_max_message_bytes: None,
+            ingester_query_max_snapshots: None,
+            query_denylist: vec![],
             datafusion_config: Default::default(),
+            task_router_address: Some(format!("http://{router_http_bind_address}")),
+            task_router_token: None,
+            task_poll_interval_seconds: 10,
+            task_run_timeout_seconds: 300,
+            query_history: false,
         };
 
         SpecializedConfig {
@@ -3,7 +3,9 @@
 use super::main;
 use crate::process_info::setup_metric_registry;
 use clap_blocks::{
-    catalog_dsn::CatalogDsnConfig, compactor::CompactorConfig, object_store::make_object_store,
+    catalog_dsn::CatalogDsnConfig,
+    compactor::CompactorConfig,
+    object_store::{make_namespace_object_stores, make_object_store},
     run_config::RunConfig,
 };
 use compactor::object_store::metrics::MetricsStore;
@@ -17,7 +19,10 @@ use ioxd_compactor::{create_compactor_server_type, derive_mem_pool_size};
 use object_store::DynObjectStore;
 use object_store_metrics::ObjectStoreMetrics;
 use observability_deps::tracing::*;
-use parquet_file::storage::{ParquetStorage, StorageId};
+use parquet_file::{
+    namespace_store::NamespaceRoutedStore,
+    storage::{ParquetStorage, StorageId},
+};
 use std::num::NonZeroUsize;
 use std::sync::Arc;
 use thiserror::Error;
@@ -79,6 +84,14 @@ pub async fn command(config: Config) -> Result<(), Error> {
     let object_store = make_object_store(config.run_config.object_store_config())
         .map_err(Error::ObjectStoreParsing)?;
 
+    // Route the data of namespaces configured to use a named object store to it.
+    let object_store = NamespaceRoutedStore::wrap(
+        object_store,
+        make_namespace_object_stores(config.run_config.object_store_config())
+            .map_err(Error::ObjectStoreParsing)?,
+        Arc::clone(&catalog),
+    );
+
     // Decorate the object store with a metric recorder.
     let object_store: Arc<DynObjectStore> = Arc::new(ObjectStoreMetrics::new(
         object_store,
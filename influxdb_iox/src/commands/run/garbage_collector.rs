@@ -1,6 +1,8 @@
 use clap_blocks::{
-    catalog_dsn::CatalogDsnConfig, garbage_collector::GarbageCollectorConfig,
-    object_store::make_object_store, run_config::RunConfig,
+    catalog_dsn::CatalogDsnConfig,
+    garbage_collector::GarbageCollectorConfig,
+    object_store::{make_namespace_object_stores, make_object_store},
+    run_config::RunConfig,
 };
 use iox_time::SystemProvider;
 use ioxd_common::{
@@ -11,6 +13,7 @@ use ioxd_garbage_collector as gc;
 use object_store::DynObjectStore;
 use object_store_metrics::ObjectStoreMetrics;
 use observability_deps::tracing::*;
+use parquet_file::namespace_store::NamespaceRoutedStore;
 use snafu::prelude::*;
 use std::sync::Arc;
 
@@ -41,6 +44,13 @@ pub async fn command(config: Config) -> Result<()> {
 
     let object_store = make_object_store(config.run_config.object_store_config())?;
 
+    // Route the data of namespaces configured to use a named object store to it.
+    let object_store = NamespaceRoutedStore::wrap(
+        object_store,
+        make_namespace_object_stores(config.run_config.object_store_config())?,
+        Arc::clone(&catalog),
+    );
+
     // Decorate the object store with a metric recorder.
     let object_store: Arc<DynObjectStore> = Arc::new(ObjectStoreMetrics::new(
         object_store,
@@ -3,7 +3,9 @@
 use std::{num::NonZeroUsize, sync::Arc};
 
 use clap_blocks::{
-    catalog_dsn::CatalogDsnConfig, ingester::IngesterConfig, object_store::make_object_store,
+    catalog_dsn::CatalogDsnConfig,
+    ingester::IngesterConfig,
+    object_store::{make_namespace_object_stores, make_object_store},
     run_config::RunConfig,
 };
 use iox_query::exec::Executor;
@@ -17,7 +19,10 @@ use object_store::DynObjectStore;
 use object_store_metrics::ObjectStoreMetrics;
 use observability_deps::tracing::*;
 use panic_logging::make_panics_fatal;
-use parquet_file::storage::{ParquetStorage, StorageId};
+use parquet_file::{
+    namespace_store::NamespaceRoutedStore,
+    storage::{ParquetStorage, StorageId},
+};
 use thiserror::Error;
 
 use super::main;
@@ -121,6 +126,14 @@ pub async fn command(config: Config) -> Result<()> {
     let object_store = make_object_store(config.run_config.object_store_config())
         .map_err(Error::ObjectStoreParsing)?;
 
+    // Route the data of namespaces configured to use a named object store to it.
+    let object_store = NamespaceRoutedStore::wrap(
+        object_store,
+        make_namespace_object_stores(config.run_config.object_store_config())
+            .map_err(Error::ObjectStoreParsing)?,
+        Arc::clone(&catalog),
+    );
+
     // Decorate the object store with a metric recorder.
     let object_store: Arc<DynObjectStore> = Arc::new(ObjectStoreMetrics::new(
         object_store,
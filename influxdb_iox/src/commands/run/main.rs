@@ -1,5 +1,6 @@
 use std::sync::Arc;
 
+use ioxd_common::build_info::{set_build_info, BuildInfo};
 use ioxd_common::Service;
 use ioxd_common::{grpc_listener, http_listener, serve, server_type::CommonServerState};
 use observability_deps::tracing::{debug, error, info};
@@ -83,6 +84,15 @@ pub async fn main(
         "InfluxDB IOx server starting",
     );
 
+    set_build_info(BuildInfo {
+        version: *process_info::IOX_VERSION,
+        git_hash: process_info::IOX_GIT_HASH,
+        features: process_info::enabled_features(),
+    });
+
+    #[cfg(all(not(feature = "heappy"), feature = "jemalloc_profiling"))]
+    ioxd_common::heap_dump::set_heap_dump_fn(crate::jemalloc::dump_heap_profile);
+
     for service in &services {
         if let Some(http_bind_address) = &service.http_bind_address {
             if (&service.grpc_bind_address == http_bind_address)
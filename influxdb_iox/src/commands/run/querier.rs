@@ -4,7 +4,9 @@ use crate::process_info::setup_metric_registry;
 
 use super::main;
 use clap_blocks::{
-    catalog_dsn::CatalogDsnConfig, object_store::make_object_store, querier::QuerierConfig,
+    catalog_dsn::CatalogDsnConfig,
+    object_store::{make_namespace_object_stores, make_object_store},
+    querier::QuerierConfig,
     run_config::RunConfig,
 };
 use iox_query::exec::Executor;
@@ -17,6 +19,7 @@ use ioxd_querier::{create_querier_server_type, QuerierServerTypeArgs};
 use object_store::DynObjectStore;
 use object_store_metrics::ObjectStoreMetrics;
 use observability_deps::tracing::*;
+use parquet_file::namespace_store::NamespaceRoutedStore;
 use std::{num::NonZeroUsize, sync::Arc};
 use thiserror::Error;
 
@@ -83,6 +86,14 @@ pub async fn command(config: Config) -> Result<(), Error> {
 
     let object_store = make_object_store(config.run_config.object_store_config())
         .map_err(Error::ObjectStoreParsing)?;
+
+    // Route the data of namespaces configured to use a named object store to it.
+    let object_store = NamespaceRoutedStore::wrap(
+        object_store,
+        make_namespace_object_stores(config.run_config.object_store_config())
+            .map_err(Error::ObjectStoreParsing)?,
+        Arc::clone(&catalog),
+    );
     // Decorate the object store with a metric recorder.
     let object_store: Arc<DynObjectStore> = Arc::new(ObjectStoreMetrics::new(
         object_store,
@@ -4,6 +4,7 @@ use arrow::{
     array::{ArrayRef, Int64Array, StringArray},
     record_batch::RecordBatch,
 };
+use data_types::ColumnType;
 use futures::TryStreamExt;
 use observability_deps::tracing::{debug, info};
 use rustyline::{error::ReadlineError, hint::Hinter, history::FileHistory, Editor};
@@ -44,6 +45,9 @@ pub enum Error {
 
     #[snafu(display("Cannot create REPL: {}", source))]
     ReplCreation { source: ReadlineError },
+
+    #[snafu(display("Error waiting for Ctrl-C: {}", source))]
+    CtrlC { source: std::io::Error },
 }
 
 pub type Result<T, E = Error> = std::result::Result<T, E>;
@@ -76,7 +80,8 @@ impl rustyline::validate::Validator for RustylineHelper {
     ) -> rustyline::Result<rustyline::validate::ValidationResult> {
         let input = ctx.input();
 
-        if input.trim_end().ends_with(';') {
+        // Meta-commands are complete without a trailing semicolon
+        if input.trim_start().starts_with('\\') || input.trim_end().ends_with(';') {
             match ReplCommand::try_from(input) {
                 Ok(_) => Ok(rustyline::validate::ValidationResult::Valid(None)),
                 Err(err) => Ok(rustyline::validate::ValidationResult::Invalid(Some(err))),
@@ -164,6 +169,9 @@ pub struct Repl {
     /// Client for interacting with IOx namespace API
     namespace_client: influxdb_iox_client::namespace::Client,
 
+    /// Client for reading the catalog schema of a namespace
+    schema_client: influxdb_iox_client::schema::Client,
+
     /// Client for running sql
     flight_client: influxdb_iox_client::flight::Client,
 
@@ -172,6 +180,9 @@ pub struct Repl {
 
     /// Formatter to use to format query results
     output_format: QueryOutputFormat,
+
+    /// Print the number of rows returned and the query execution time
+    timing: bool,
 }
 
 impl Repl {
@@ -182,6 +193,7 @@ impl Repl {
     /// Create a new Repl instance, connected to the specified URL
     pub fn new(connection: Connection) -> Result<Self> {
         let namespace_client = influxdb_iox_client::namespace::Client::new(connection.clone());
+        let schema_client = influxdb_iox_client::schema::Client::new(connection.clone());
         let flight_client = influxdb_iox_client::flight::Client::new(connection);
 
         let mut rl = Editor::new().context(ReplCreationSnafu)?;
@@ -200,9 +212,11 @@ impl Repl {
             rl,
             prompt,
             namespace_client,
+            schema_client,
             flight_client,
             query_engine: None,
             output_format,
+            timing: true,
         })
     }
 
@@ -222,6 +236,20 @@ impl Repl {
                         .map_err(|e| println!("{e}"))
                         .ok();
                 }
+                ReplCommand::ListTables => {
+                    self.list_tables().await.map_err(|e| println!("{e}")).ok();
+                }
+                ReplCommand::DescribeTable { table_name } => {
+                    self.describe_table(table_name)
+                        .await
+                        .map_err(|e| println!("{e}"))
+                        .ok();
+                }
+                ReplCommand::ToggleTiming => {
+                    self.timing = !self.timing;
+                    let state = if self.timing { "on" } else { "off" };
+                    println!("Timing is {state}");
+                }
                 ReplCommand::UseNamespace { db_name } => {
                     self.use_namespace(db_name);
                 }
@@ -287,6 +315,97 @@ impl Repl {
         self.print_results(&[record_batch])
     }
 
+    // print all tables of the current namespace, as recorded in the catalog
+    async fn list_tables(&mut self) -> Result<()> {
+        let Some(db_name) = self.current_namespace() else {
+            return Ok(());
+        };
+
+        let schema = self
+            .schema_client
+            .get_schema(&db_name, None)
+            .await
+            .map_err(|e| Box::new(e) as _)
+            .context(LoadingRemoteStateSnafu)?;
+
+        // Proto maps are unordered, so sort by name
+        let mut tables = schema.tables.into_iter().collect::<Vec<_>>();
+        tables.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+
+        let table_id: Int64Array = tables.iter().map(|(_, t)| Some(t.id)).collect();
+        let name: StringArray = tables.iter().map(|(name, _)| Some(name)).collect();
+        let columns: Int64Array = tables
+            .iter()
+            .map(|(_, t)| Some(t.columns.len() as i64))
+            .collect();
+
+        let record_batch = RecordBatch::try_from_iter(vec![
+            ("table_id", Arc::new(table_id) as ArrayRef),
+            ("name", Arc::new(name) as ArrayRef),
+            ("columns", Arc::new(columns) as ArrayRef),
+        ])
+        .expect("creating record batch successfully");
+
+        self.print_results(&[record_batch])
+    }
+
+    // print the columns of a table in the current namespace, as recorded in the catalog
+    async fn describe_table(&mut self, table_name: String) -> Result<()> {
+        let Some(db_name) = self.current_namespace() else {
+            return Ok(());
+        };
+
+        let schema = self
+            .schema_client
+            .get_schema(&db_name, Some(&table_name))
+            .await
+            .map_err(|e| Box::new(e) as _)
+            .context(LoadingRemoteStateSnafu)?;
+
+        let Some(table) = schema.tables.get(&table_name) else {
+            println!("Error: table {table_name} not found in namespace {db_name}");
+            return Ok(());
+        };
+
+        let mut columns = table.columns.iter().collect::<Vec<_>>();
+        columns.sort_unstable_by(|a, b| a.0.cmp(b.0));
+
+        let column_id: Int64Array = columns.iter().map(|(_, c)| Some(c.id)).collect();
+        let name: StringArray = columns.iter().map(|(name, _)| Some(name)).collect();
+        let column_type: StringArray = columns
+            .iter()
+            .map(|(_, c)| {
+                Some(
+                    ColumnType::try_from(c.column_type())
+                        .map(|t| t.as_str())
+                        .unwrap_or("unknown"),
+                )
+            })
+            .collect();
+
+        let record_batch = RecordBatch::try_from_iter(vec![
+            ("column_id", Arc::new(column_id) as ArrayRef),
+            ("name", Arc::new(name) as ArrayRef),
+            ("type", Arc::new(column_type) as ArrayRef),
+        ])
+        .expect("creating record batch successfully");
+
+        self.print_results(&[record_batch])
+    }
+
+    /// Return the name of the currently selected namespace, or print a hint
+    /// and return [`None`] if no namespace is selected.
+    fn current_namespace(&self) -> Option<String> {
+        match &self.query_engine {
+            Some(QueryEngine::Remote(db_name)) => Some(db_name.clone()),
+            None => {
+                println!("Error: no namespace selected.");
+                println!("Hint: Run USE NAMESPACE <dbname> to select namespace");
+                None
+            }
+        }
+    }
+
     // Run a command against the currently selected remote namespace
     async fn run_sql(&mut self, sql: String) -> Result<()> {
         let start = Instant::now();
@@ -300,24 +419,38 @@ impl Repl {
             Some(QueryEngine::Remote(db_name)) => {
                 info!(%db_name, %sql, "Running sql on remote namespace");
 
-                self.flight_client
-                    .sql(db_name.to_string(), sql)
-                    .await
-                    .context(RunningRemoteQuerySnafu)?
-                    .try_collect()
-                    .await
-                    .context(RunningRemoteQuerySnafu)?
+                let client = &mut self.flight_client;
+                let db_name = db_name.to_string();
+                let query = async move {
+                    client
+                        .sql(db_name, sql)
+                        .await?
+                        .try_collect::<Vec<_>>()
+                        .await
+                };
+
+                // Dropping the query future on Ctrl-C cancels the request
+                tokio::select! {
+                    batches = query => batches.context(RunningRemoteQuerySnafu)?,
+                    res = tokio::signal::ctrl_c() => {
+                        res.context(CtrlCSnafu)?;
+                        println!("Query cancelled");
+                        return Ok(());
+                    }
+                }
             }
         };
 
         let end = Instant::now();
         self.print_results(&batches)?;
 
-        println!(
-            "Returned {} in {:?}",
-            Self::row_summary(&batches),
-            end - start
-        );
+        if self.timing {
+            println!(
+                "Returned {} in {:?}",
+                Self::row_summary(&batches),
+                end - start
+            );
+        }
         Ok(())
     }
 
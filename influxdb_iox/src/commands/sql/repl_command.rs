@@ -5,6 +5,9 @@ use observability_deps::tracing::{debug, warn};
 pub enum ReplCommand {
     Help,
     ShowNamespaces,
+    ListTables,
+    DescribeTable { table_name: String },
+    ToggleTiming,
     SetFormat { format: String },
     UseNamespace { db_name: String },
     SqlCommand { sql: String },
@@ -53,6 +56,32 @@ impl TryFrom<&str> for ReplCommand {
         // Get something we can more easily pattern match on
         let commands = commands.iter().map(|s| s.as_str()).collect::<Vec<_>>();
 
+        // Only a semicolon
+        let Some(first) = raw_commands.first() else {
+            return Err("No command specified".to_string());
+        };
+
+        // Backslash meta-commands
+        if first.starts_with('\\') {
+            return match commands.as_slice() {
+                ["\\?"] => Ok(Self::Help),
+                ["\\q"] => Ok(Self::Exit),
+                ["\\l"] => Ok(Self::ShowNamespaces),
+                ["\\d"] | ["\\dt"] => Ok(Self::ListTables),
+                ["\\d", _table_name] => Ok(Self::DescribeTable {
+                    table_name: raw_commands[1].to_string(),
+                }),
+                ["\\timing"] => Ok(Self::ToggleTiming),
+                ["\\format", _format] => Ok(Self::SetFormat {
+                    format: raw_commands[1].to_string(),
+                }),
+                _ => Err(format!(
+                    "invalid command '{}'. Try \\? for help",
+                    raw_commands.join(" ")
+                )),
+            };
+        }
+
         match commands.as_slice() {
             ["help"] => Ok(Self::Help),
             ["help", ..] => {
@@ -104,6 +133,17 @@ SET FORMAT <format>: Set the output format to Pretty, csv or json
 
 [EXIT | QUIT]: Quit this session and exit the program
 
+Meta-commands (no trailing semicolon required):
+\?               Show this help
+\l               List namespaces, same as SHOW NAMESPACES
+\d               List tables in the current namespace
+\d <table>       Describe the columns of a table in the current namespace
+\timing          Toggle display of query execution time
+\format <format> Set the output format, same as SET FORMAT
+\q               Quit this session
+
+Press Ctrl-C while a query is running to cancel it.
+
 # Examples: use remote namespace foo
 SHOW NAMESPACES;
 USE foo;
@@ -136,6 +176,8 @@ mod tests {
         assert_eq!("".try_into(), expected);
         assert_eq!("  ".try_into(), expected);
         assert_eq!(" \t".try_into(), expected);
+        assert_eq!(";".try_into(), expected);
+        assert_eq!(" ; ".try_into(), expected);
     }
 
     #[test]
@@ -244,4 +286,39 @@ mod tests {
         let expected = sql_cmd("quit dragging");
         assert_eq!("quit dragging".try_into(), expected);
     }
+
+    #[test]
+    fn meta_commands() {
+        assert_eq!("\\?".try_into(), Ok(ReplCommand::Help));
+        assert_eq!("\\q".try_into(), Ok(ReplCommand::Exit));
+        assert_eq!("\\l".try_into(), Ok(ReplCommand::ShowNamespaces));
+        assert_eq!("\\d".try_into(), Ok(ReplCommand::ListTables));
+        assert_eq!(" \\dt ".try_into(), Ok(ReplCommand::ListTables));
+        assert_eq!("\\d;".try_into(), Ok(ReplCommand::ListTables));
+        assert_eq!("\\timing".try_into(), Ok(ReplCommand::ToggleTiming));
+        assert_eq!("\\TIMING".try_into(), Ok(ReplCommand::ToggleTiming));
+
+        // ensure that table names are case sensitive
+        assert_eq!(
+            "\\d MyTable".try_into(),
+            Ok(ReplCommand::DescribeTable {
+                table_name: "MyTable".to_string()
+            })
+        );
+
+        assert_eq!(
+            "\\format CSV".try_into(),
+            Ok(ReplCommand::SetFormat {
+                format: "CSV".to_string()
+            })
+        );
+
+        let expected: Result<ReplCommand, String> =
+            Err("invalid command '\\x'. Try \\? for help".to_string());
+        assert_eq!("\\x".try_into(), expected);
+
+        let expected: Result<ReplCommand, String> =
+            Err("invalid command '\\d a b'. Try \\? for help".to_string());
+        assert_eq!("\\d a b".try_into(), expected);
+    }
 }
@@ -1,3 +1,4 @@
+pub(crate) mod backfill;
 pub(crate) mod request;
 pub(crate) mod response;
 
@@ -10,7 +11,7 @@ use influxrpc_parser::predicate;
 use iox_time;
 use observability_deps::tracing::info;
 use snafu::{ensure, OptionExt, ResultExt, Snafu};
-use std::{num::NonZeroU64, time::Duration};
+use std::{future::Future, num::NonZeroU64, time::Duration};
 use tonic::Status;
 
 #[derive(Debug, Snafu)]
@@ -47,6 +48,12 @@ pub enum ParseError {
         group
     ))]
     Group { group: String },
+
+    #[snafu(display("backfill error: {}", source))]
+    Backfill { source: backfill::Error },
+
+    #[snafu(display("backfill is not a storage read request"))]
+    NotARead,
 }
 
 pub type Result<T, E = ParseError> = std::result::Result<T, E>;
@@ -164,6 +171,7 @@ pub enum Format {
 /// All possible subcommands for storage
 #[derive(Debug, clap::Parser)]
 enum Command {
+    Backfill(backfill::Config),
     MeasurementFields(MeasurementFields),
     MeasurementTagKeys(MeasurementTagKeys),
     ReadFilter,
@@ -253,9 +261,35 @@ struct TagValues {
     tag_key: String,
 }
 
-/// Create and issue read request
-pub async fn command(connection: Connection, config: Config) -> Result<()> {
-    let mut client = influxdb_storage_client::Client::new(connection);
+impl Config {
+    /// Returns true if this command writes data through the router's HTTP
+    /// API, rather than making storage gRPC requests.
+    pub fn is_backfill(&self) -> bool {
+        matches!(self.command, Command::Backfill(_))
+    }
+}
+
+/// Create and issue read request, connecting with `connect`.
+///
+/// The backfill subcommand only connects when writing through the router.
+pub async fn command<C, F>(connect: C, config: Config) -> Result<()>
+where
+    C: FnOnce() -> F,
+    F: Future<Output = Connection>,
+{
+    if let Command::Backfill(backfill) = config.command {
+        return backfill::command(
+            connect,
+            config.db_name.db_name(),
+            config.start,
+            config.stop,
+            backfill,
+        )
+        .await
+        .context(BackfillSnafu);
+    }
+
+    let mut client = influxdb_storage_client::Client::new(connect().await);
 
     // convert predicate with no root node into None.
     let predicate = config.predicate.root.is_some().then_some(config.predicate);
@@ -379,6 +413,7 @@ pub async fn command(connection: Connection, config: Config) -> Result<()> {
                 Format::Quiet => {}
             }
         }
+        Command::Backfill(_) => return Err(ParseError::NotARead),
     };
     println!("Query execution: {:?}", now.elapsed());
     Ok(())
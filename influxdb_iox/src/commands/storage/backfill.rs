@@ -0,0 +1,890 @@
+//! This module implements the `storage backfill` CLI command, generating
+//! synthetic series across a historical time range.
+//!
+//! The generated data is either written through the router (exercising the
+//! full write path) or persisted directly to object storage as parquet files
+//! and registered in the catalog, skipping the ingesters entirely. The latter
+//! allows years of history to be loaded in minutes for capacity testing.
+
+use std::{
+    collections::HashMap, f64::consts::TAU, fmt::Write, future::Future, num::NonZeroUsize,
+    sync::Arc,
+};
+
+use clap_blocks::{
+    catalog_dsn::CatalogDsnConfig,
+    object_store::{make_object_store, ObjectStoreConfig},
+};
+use data_types::{
+    partition_template::TablePartitionTemplateOverride, ColumnType, ColumnsByName, CompactionLevel,
+    Namespace, NamespaceName, PartitionKey, Table,
+};
+use datafusion::{
+    arrow::record_batch::RecordBatch, execution::memory_pool::UnboundedMemoryPool,
+    physical_plan::stream::RecordBatchStreamAdapter,
+};
+use influxdb_storage_client::connection::Connection;
+use iox_catalog::interface::{CasFailure, Catalog, SoftDeletedRows};
+use iox_time::{SystemProvider, TimeProvider};
+use mutable_batch::{MutableBatch, PartitionWrite, WritePayload};
+use observability_deps::tracing::{debug, info};
+use parquet_file::{
+    metadata::IoxMetadata,
+    storage::{ParquetStorage, StorageId},
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use schema::{sort::SortKey, Projection, TIME_COLUMN_NAME};
+use snafu::{ensure, OptionExt, ResultExt, Snafu};
+use uuid::Uuid;
+
+use crate::process_info::setup_metric_registry;
+
+/// The number of nanoseconds in a (UTC) day.
+const NANOS_PER_DAY: i64 = 24 * 60 * 60 * 1_000_000_000;
+
+/// The initial value of every series field when generating a random walk.
+const RANDOM_WALK_START: f64 = 50.0;
+
+#[derive(Debug, Snafu)]
+pub enum Error {
+    #[snafu(display("start time {} must be before stop time {}", start, stop))]
+    TimeRange { start: i64, stop: i64 },
+
+    #[snafu(display("the point interval must be at least 1ns"))]
+    Interval,
+
+    #[snafu(display(
+        "{} tags with {} values each exceeds the maximum number of series",
+        tags,
+        tag_cardinality
+    ))]
+    SeriesOverflow { tags: usize, tag_cardinality: usize },
+
+    #[snafu(display(
+        "backfill would generate {} points, exceeding --max-points {} \
+        (set a narrower --start/--stop range or a larger --interval)",
+        points,
+        max_points
+    ))]
+    TooManyPoints { points: u128, max_points: u64 },
+
+    #[snafu(display("parquet mode requires a persistent --object-store to be configured"))]
+    ObjectStoreRequired,
+
+    #[snafu(display("catalog DSN error: {}", source))]
+    CatalogDsn {
+        source: clap_blocks::catalog_dsn::Error,
+    },
+
+    #[snafu(display("object store error: {}", source))]
+    ObjectStore {
+        source: clap_blocks::object_store::ParseError,
+    },
+
+    #[snafu(display("catalog error: {}", source))]
+    Catalog {
+        source: iox_catalog::interface::Error,
+    },
+
+    #[snafu(display("invalid namespace name: {}", source))]
+    NamespaceName {
+        source: data_types::NamespaceNameError,
+    },
+
+    #[snafu(display("invalid partition template: {}", source))]
+    PartitionTemplate {
+        source: data_types::partition_template::ValidationError,
+    },
+
+    #[snafu(display("error writing to the router: {}", source))]
+    RouterWrite {
+        source: influxdb_iox_client::error::Error,
+    },
+
+    #[snafu(display("error converting generated line protocol: {}", source))]
+    LineProtocol { source: mutable_batch_lp::Error },
+
+    #[snafu(display("error building record batch: {}", source))]
+    Batch { source: mutable_batch::Error },
+
+    #[snafu(display("error partitioning generated data: {}", source))]
+    Partition {
+        source: mutable_batch::PartitionKeyError,
+    },
+
+    #[snafu(display("error uploading parquet file: {}", source))]
+    Upload {
+        source: parquet_file::storage::UploadError,
+    },
+
+    #[snafu(display(
+        "partition {} has sort key {:?}, which differs from the generated data",
+        partition_key,
+        existing
+    ))]
+    MismatchedSortKey {
+        partition_key: PartitionKey,
+        existing: Vec<String>,
+    },
+
+    #[snafu(display("concurrent sort key update of partition {}", partition_key))]
+    SortKeyConflict { partition_key: PartitionKey },
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// How the generated data is written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Mode {
+    /// Write line protocol through the router's HTTP write API.
+    Router,
+
+    /// Write parquet files directly to object storage, and register them in
+    /// the catalog.
+    Parquet,
+}
+
+/// The distribution of the generated field values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Distribution {
+    /// All points have the value 1.
+    Constant,
+
+    /// Values are drawn uniformly from the range [0, 100).
+    Uniform,
+
+    /// Each point of a series differs from the previous point by a value
+    /// drawn uniformly from the range [-1, 1).
+    RandomWalk,
+
+    /// Values follow a sine wave with a period of one day, with a different
+    /// phase for each series.
+    Sine,
+}
+
+/// Generate synthetic series across the `--start` / `--stop` time range and
+/// write them to the namespace.
+#[derive(Debug, clap::Parser)]
+pub struct Config {
+    /// How the generated data is written.
+    #[clap(long, value_enum, default_value_t = Mode::Router)]
+    mode: Mode,
+
+    /// The number of tables to generate.
+    #[clap(long, default_value = "1")]
+    tables: NonZeroUsize,
+
+    /// The name prefix of the generated tables.
+    #[clap(long, default_value = "backfill")]
+    table_prefix: String,
+
+    /// The number of tag columns in each table.
+    #[clap(long, default_value = "2")]
+    tags: usize,
+
+    /// The number of distinct values of each tag.
+    ///
+    /// Each table contains one series for every combination of tag values,
+    /// or `tag-cardinality ^ tags` series.
+    #[clap(long, default_value = "10")]
+    tag_cardinality: NonZeroUsize,
+
+    /// The number of float fields in each table.
+    #[clap(long, default_value = "1")]
+    fields: NonZeroUsize,
+
+    /// The distribution of the generated field values.
+    #[clap(long, value_enum, default_value_t = Distribution::RandomWalk)]
+    distribution: Distribution,
+
+    /// The duration of time between two points of a series.
+    #[clap(long, default_value = "1m", value_parser = humantime::parse_duration)]
+    interval: std::time::Duration,
+
+    /// The seed of the random number generator - runs with the same seed and
+    /// configuration generate the same data.
+    #[clap(long, default_value = "0")]
+    seed: u64,
+
+    /// The maximum number of lines sent in a single write request (router
+    /// mode only).
+    #[clap(long, default_value = "10000")]
+    lines_per_request: NonZeroUsize,
+
+    /// Refuse to run if more than this many points would be generated,
+    /// guarding against accidentally backfilling the default (unbounded)
+    /// time range.
+    #[clap(long, default_value = "100000000")]
+    max_points: u64,
+
+    /// The catalog to register parquet files in (parquet mode only).
+    #[clap(flatten)]
+    catalog_dsn: CatalogDsnConfig,
+
+    /// The object store to write parquet files to (parquet mode only).
+    #[clap(flatten)]
+    object_store: ObjectStoreConfig,
+}
+
+/// Generate the configured series over `[start, stop)` and write them to
+/// `namespace`.
+///
+/// `connect` must connect to the router's HTTP API, and is only called in
+/// [`Mode::Router`].
+pub async fn command<C, F>(
+    connect: C,
+    namespace: &str,
+    start: i64,
+    stop: i64,
+    config: Config,
+) -> Result<()>
+where
+    C: FnOnce() -> F,
+    F: Future<Output = Connection>,
+{
+    ensure!(start < stop, TimeRangeSnafu { start, stop });
+
+    let interval = i64::try_from(config.interval.as_nanos())
+        .ok()
+        .filter(|v| *v > 0)
+        .context(IntervalSnafu)?;
+
+    let series = series_count(config.tags, config.tag_cardinality.get())?;
+
+    // Validate the total number of points before allocating anything.
+    let timestamps = (stop as i128 - start as i128 + interval as i128 - 1) / interval as i128;
+    let points = timestamps as u128 * series as u128 * config.tables.get() as u128;
+    ensure!(
+        points <= config.max_points as u128,
+        TooManyPointsSnafu {
+            points,
+            max_points: config.max_points
+        }
+    );
+
+    info!(
+        namespace,
+        tables = config.tables.get(),
+        series_per_table = series,
+        points,
+        mode = ?config.mode,
+        "starting backfill"
+    );
+
+    let mut generator = Generator::new(&config, series);
+    let windows = DayWindows::new(start, stop, interval);
+
+    let now = std::time::Instant::now();
+    let written = match config.mode {
+        Mode::Router => {
+            write_router(
+                connect().await,
+                namespace,
+                &mut generator,
+                windows,
+                config.lines_per_request.get(),
+            )
+            .await?
+        }
+        Mode::Parquet => {
+            ensure!(
+                config.object_store.object_store.is_some(),
+                ObjectStoreRequiredSnafu
+            );
+
+            let mut writer = ParquetWriter::new(
+                &config.catalog_dsn,
+                &config.object_store,
+                namespace,
+                &generator,
+            )
+            .await?;
+            writer.write(&mut generator, windows).await?
+        }
+    };
+
+    println!(
+        "Backfilled {written} points into {namespace} in {:?}",
+        now.elapsed()
+    );
+
+    Ok(())
+}
+
+/// Return the number of series in each table with `tags` tag columns, each
+/// with `tag_cardinality` distinct values.
+fn series_count(tags: usize, tag_cardinality: usize) -> Result<usize> {
+    u32::try_from(tags)
+        .ok()
+        .and_then(|tags| tag_cardinality.checked_pow(tags))
+        .context(SeriesOverflowSnafu {
+            tags,
+            tag_cardinality,
+        })
+}
+
+/// Write the generated data as line protocol, in requests of at most
+/// `lines_per_request` lines, returning the number of points written.
+async fn write_router(
+    connection: Connection,
+    namespace: &str,
+    generator: &mut Generator,
+    windows: DayWindows,
+    lines_per_request: usize,
+) -> Result<u64> {
+    let mut client = influxdb_iox_client::write::Client::new(connection);
+
+    let mut written = 0;
+    let mut buf = String::new();
+    let mut buf_lines = 0;
+
+    for window in windows {
+        for table in 0..generator.tables.len() {
+            for series in 0..generator.series {
+                for &t in &window {
+                    generator.write_line(table, series, t, &mut buf);
+                    buf_lines += 1;
+
+                    if buf_lines == lines_per_request {
+                        client
+                            .write_lp(namespace, std::mem::take(&mut buf))
+                            .await
+                            .context(RouterWriteSnafu)?;
+                        written += buf_lines as u64;
+                        buf_lines = 0;
+                    }
+                }
+            }
+        }
+
+        debug!(
+            window_start = window[0],
+            written, "generated backfill window"
+        );
+    }
+
+    if buf_lines > 0 {
+        client
+            .write_lp(namespace, buf)
+            .await
+            .context(RouterWriteSnafu)?;
+        written += buf_lines as u64;
+    }
+
+    Ok(written)
+}
+
+/// Writes generated data directly as parquet files, registering them in the
+/// catalog as L0 files.
+#[derive(Debug)]
+struct ParquetWriter {
+    catalog: Arc<dyn Catalog>,
+    store: ParquetStorage,
+    namespace: Namespace,
+
+    /// The catalog table and columns for each generated table, in generator
+    /// order.
+    tables: Vec<(Table, ColumnsByName)>,
+
+    /// The sort key of all generated tables, the tag columns followed by
+    /// the time column, matching the order the data is generated in.
+    sort_key: SortKey,
+}
+
+impl ParquetWriter {
+    /// Initialise the catalog, creating the namespace, tables and columns
+    /// written to by `generator` if they do not already exist.
+    async fn new(
+        catalog_dsn: &CatalogDsnConfig,
+        object_store: &ObjectStoreConfig,
+        namespace: &str,
+        generator: &Generator,
+    ) -> Result<Self> {
+        let catalog = catalog_dsn
+            .get_catalog("cli", setup_metric_registry())
+            .await
+            .context(CatalogDsnSnafu)?;
+        let store = ParquetStorage::new(
+            make_object_store(object_store).context(ObjectStoreSnafu)?,
+            StorageId::from("iox"),
+        );
+
+        let mut repos = catalog.repositories().await;
+
+        let namespace = match repos
+            .namespaces()
+            .get_by_name(namespace, SoftDeletedRows::ExcludeDeleted)
+            .await
+            .context(CatalogSnafu)?
+        {
+            Some(v) => v,
+            None => {
+                let name = NamespaceName::new(namespace.to_string()).context(NamespaceNameSnafu)?;
+                repos
+                    .namespaces()
+                    .create(&name, None, None, None)
+                    .await
+                    .context(CatalogSnafu)?
+            }
+        };
+
+        let mut columns = generator
+            .tag_keys
+            .iter()
+            .map(|v| (v.as_str(), ColumnType::Tag))
+            .chain(
+                generator
+                    .fields
+                    .iter()
+                    .map(|v| (v.as_str(), ColumnType::F64)),
+            )
+            .collect::<HashMap<_, _>>();
+        columns.insert(TIME_COLUMN_NAME, ColumnType::Time);
+
+        let mut tables = Vec::with_capacity(generator.tables.len());
+        for name in &generator.tables {
+            let table = match repos
+                .tables()
+                .get_by_namespace_and_name(namespace.id, name)
+                .await
+                .context(CatalogSnafu)?
+            {
+                Some(v) => v,
+                None => repos
+                    .tables()
+                    .create(
+                        name,
+                        TablePartitionTemplateOverride::try_new(
+                            None,
+                            &namespace.partition_template,
+                        )
+                        .context(PartitionTemplateSnafu)?,
+                        namespace.id,
+                    )
+                    .await
+                    .context(CatalogSnafu)?,
+            };
+
+            let table_columns = repos
+                .columns()
+                .create_or_get_many_unchecked(table.id, columns.clone())
+                .await
+                .context(CatalogSnafu)?;
+
+            tables.push((table, ColumnsByName::new(table_columns)));
+        }
+
+        drop(repos);
+
+        let sort_key = SortKey::from_columns(
+            generator
+                .tag_keys
+                .iter()
+                .map(String::as_str)
+                .chain([TIME_COLUMN_NAME]),
+        );
+
+        Ok(Self {
+            catalog,
+            store,
+            namespace,
+            tables,
+            sort_key,
+        })
+    }
+
+    /// Write one parquet file per table, per partition, per window of
+    /// generated data, returning the number of points written.
+    async fn write(&mut self, generator: &mut Generator, windows: DayWindows) -> Result<u64> {
+        let mut written = 0;
+        let mut lp = String::new();
+
+        for window in windows {
+            for table in 0..generator.tables.len() {
+                lp.clear();
+                for series in 0..generator.series {
+                    for &t in &window {
+                        generator.write_line(table, series, t, &mut lp);
+                    }
+                }
+
+                let batch = mutable_batch_lp::lines_to_batches(&lp, 0)
+                    .context(LineProtocolSnafu)?
+                    .remove(&generator.tables[table])
+                    .expect("generated line protocol contains the table");
+
+                written += batch.rows() as u64;
+
+                // The data is generated ordered by series, and then time, and
+                // therefore each partition is already sorted by the sort key.
+                let partitions =
+                    PartitionWrite::partition(&batch, &self.tables[table].0.partition_template)
+                        .context(PartitionSnafu)?;
+                for (partition_key, write) in partitions {
+                    let mut partition_batch = MutableBatch::new();
+                    write
+                        .write_to_batch(&mut partition_batch)
+                        .context(BatchSnafu)?;
+                    let record_batch = partition_batch
+                        .to_arrow(Projection::All)
+                        .context(BatchSnafu)?;
+
+                    self.write_file(table, partition_key, record_batch).await?;
+                }
+            }
+
+            debug!(
+                window_start = window[0],
+                written, "persisted backfill window"
+            );
+        }
+
+        Ok(written)
+    }
+
+    /// Upload `batch` as a single parquet file in `partition_key` of the
+    /// `table`-th generated table, and register it in the catalog.
+    async fn write_file(
+        &self,
+        table: usize,
+        partition_key: PartitionKey,
+        batch: RecordBatch,
+    ) -> Result<()> {
+        let (table, columns) = &self.tables[table];
+
+        let mut repos = self.catalog.repositories().await;
+
+        let partition = repos
+            .partitions()
+            .create_or_get(partition_key.clone(), table.id)
+            .await
+            .context(CatalogSnafu)?;
+
+        let sort_key = self.sort_key.to_columns().collect::<Vec<_>>();
+        if partition.sort_key_has_value() {
+            let existing = partition.sort_key.clone().unwrap_or_default();
+            ensure!(
+                existing == sort_key,
+                MismatchedSortKeySnafu {
+                    partition_key,
+                    existing
+                }
+            );
+        } else {
+            repos
+                .partitions()
+                .cas_sort_key(
+                    &partition.transition_partition_id(),
+                    partition.sort_key.clone(),
+                    Some(partition.sort_key_ids.clone()),
+                    &sort_key,
+                    &columns.ids_for_names(&sort_key),
+                )
+                .await
+                .map_err(|e| match e {
+                    CasFailure::ValueMismatch(_) => Error::SortKeyConflict {
+                        partition_key: partition_key.clone(),
+                    },
+                    CasFailure::QueryError(source) => Error::Catalog { source },
+                })?;
+        }
+
+        let now = SystemProvider::default().now();
+        let meta = IoxMetadata {
+            object_store_id: Uuid::new_v4(),
+            creation_timestamp: now,
+            namespace_id: self.namespace.id,
+            namespace_name: Arc::from(self.namespace.name.as_str()),
+            table_id: table.id,
+            table_name: Arc::from(table.name.as_str()),
+            partition_key,
+            compaction_level: CompactionLevel::Initial,
+            sort_key: Some(self.sort_key.clone()),
+            max_l0_created_at: now,
+        };
+
+        let stream = Box::pin(RecordBatchStreamAdapter::new(
+            batch.schema(),
+            futures::stream::iter([Ok(batch)]),
+        ));
+        let (md, file_size) = self
+            .store
+            .upload(
+                stream,
+                &partition.transition_partition_id(),
+                &meta,
+                Arc::new(UnboundedMemoryPool::default()),
+            )
+            .await
+            .context(UploadSnafu)?;
+
+        let params = meta.to_parquet_file(
+            partition.transition_partition_id(),
+            file_size,
+            &md,
+            |name| {
+                columns
+                    .get(name)
+                    .expect("generated column exists in catalog")
+                    .id
+            },
+        );
+
+        repos
+            .parquet_files()
+            .create(params)
+            .await
+            .context(CatalogSnafu)?;
+
+        Ok(())
+    }
+}
+
+/// Deterministically generates lines of synthetic series.
+///
+/// All names are zero-padded such that lexicographic and numeric order agree,
+/// so series in increasing index order are also in increasing (tag) sort
+/// order.
+#[derive(Debug)]
+struct Generator {
+    tables: Vec<String>,
+    tag_keys: Vec<String>,
+    fields: Vec<String>,
+    tag_cardinality: usize,
+
+    /// The number of series in each table.
+    series: usize,
+
+    distribution: Distribution,
+    rng: StdRng,
+
+    /// The last value of each field of each series of each table, used by
+    /// [`Distribution::RandomWalk`].
+    last: Vec<f64>,
+}
+
+impl Generator {
+    /// Initialise a generator of `series` series per table, as returned by
+    /// [`series_count()`] for `config`.
+    fn new(config: &Config, series: usize) -> Self {
+        let tables = padded_names(&config.table_prefix, config.tables.get());
+        let fields = padded_names("field", config.fields.get());
+
+        let last = match config.distribution {
+            Distribution::RandomWalk => {
+                vec![RANDOM_WALK_START; tables.len() * series * fields.len()]
+            }
+            _ => vec![],
+        };
+
+        Self {
+            tables,
+            tag_keys: padded_names("tag", config.tags),
+            fields,
+            tag_cardinality: config.tag_cardinality.get(),
+            series,
+            distribution: config.distribution,
+            rng: StdRng::seed_from_u64(config.seed),
+            last,
+        }
+    }
+
+    /// Append a line for the point at time `t` of the `series`-th series of
+    /// the `table`-th table to `out`.
+    fn write_line(&mut self, table: usize, series: usize, t: i64, out: &mut String) {
+        out.push_str(&self.tables[table]);
+
+        let width = digits(self.tag_cardinality);
+        let mut divisor = self.series;
+        for key in &self.tag_keys {
+            divisor /= self.tag_cardinality;
+            let value = (series / divisor) % self.tag_cardinality;
+            write!(out, ",{key}=value_{value:0width$}").unwrap();
+        }
+
+        for field in 0..self.fields.len() {
+            let value = self.value(table, series, field, t);
+            let sep = if field == 0 { ' ' } else { ',' };
+            write!(out, "{sep}{}={value}", self.fields[field]).unwrap();
+        }
+
+        writeln!(out, " {t}").unwrap();
+    }
+
+    /// Generate the next value of `field` for the specified series at `t`.
+    fn value(&mut self, table: usize, series: usize, field: usize, t: i64) -> f64 {
+        let idx = (table * self.series + series) * self.fields.len() + field;
+        match self.distribution {
+            Distribution::Constant => 1.0,
+            Distribution::Uniform => self.rng.gen_range(0.0..100.0),
+            Distribution::RandomWalk => {
+                self.last[idx] += self.rng.gen_range(-1.0..1.0);
+                self.last[idx]
+            }
+            Distribution::Sine => {
+                let day_fraction = t.rem_euclid(NANOS_PER_DAY) as f64 / NANOS_PER_DAY as f64;
+                50.0 + 50.0 * (TAU * day_fraction + idx as f64).sin()
+            }
+        }
+    }
+}
+
+/// Return `n` names of the form `{prefix}_{i}`, with `i` zero-padded to the
+/// same width.
+fn padded_names(prefix: &str, n: usize) -> Vec<String> {
+    let width = digits(n);
+    (0..n).map(|i| format!("{prefix}_{i:0width$}")).collect()
+}
+
+/// Return the number of decimal digits needed to print all values in `0..n`.
+fn digits(n: usize) -> usize {
+    n.saturating_sub(1).checked_ilog10().unwrap_or(0) as usize + 1
+}
+
+/// An iterator over the point timestamps `start + k * interval` in
+/// `[start, stop)`, yielded in windows that each cover at most one UTC day.
+#[derive(Debug)]
+struct DayWindows {
+    next: Option<i64>,
+    stop: i64,
+    interval: i64,
+}
+
+impl DayWindows {
+    fn new(start: i64, stop: i64, interval: i64) -> Self {
+        Self {
+            next: (start < stop).then_some(start),
+            stop,
+            interval,
+        }
+    }
+}
+
+impl Iterator for DayWindows {
+    type Item = Vec<i64>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut t = self.next?;
+
+        let day_end = (t.div_euclid(NANOS_PER_DAY) + 1)
+            .checked_mul(NANOS_PER_DAY)
+            .unwrap_or(i64::MAX)
+            .min(self.stop);
+
+        let mut window = vec![];
+        self.next = loop {
+            window.push(t);
+            match t.checked_add(self.interval) {
+                Some(v) if v < day_end => t = v,
+                Some(v) if v < self.stop => break Some(v),
+                _ => break None,
+            }
+        };
+
+        Some(window)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+
+    use super::*;
+
+    fn config(args: &[&str]) -> Config {
+        Config::try_parse_from(std::iter::once("backfill").chain(args.iter().copied()))
+            .expect("valid args")
+    }
+
+    #[test]
+    fn test_digits() {
+        assert_eq!(digits(0), 1);
+        assert_eq!(digits(1), 1);
+        assert_eq!(digits(10), 1);
+        assert_eq!(digits(11), 2);
+        assert_eq!(digits(100), 2);
+        assert_eq!(digits(101), 3);
+    }
+
+    #[test]
+    fn test_series_count() {
+        assert_eq!(series_count(0, 10).unwrap(), 1);
+        assert_eq!(series_count(3, 10).unwrap(), 1000);
+        assert!(matches!(
+            series_count(100, 10),
+            Err(Error::SeriesOverflow { .. })
+        ));
+    }
+
+    #[test]
+    fn test_day_windows() {
+        let hour = NANOS_PER_DAY / 24;
+
+        // A range spanning a day boundary, with an interval that does not
+        // divide the day.
+        let start = NANOS_PER_DAY - 2 * hour;
+        let windows =
+            DayWindows::new(start, NANOS_PER_DAY + 3 * hour, 90 * hour / 60).collect::<Vec<_>>();
+        assert_eq!(
+            windows,
+            [
+                vec![start, start + 90 * hour / 60],
+                vec![start + 3 * hour, start + 9 * hour / 2],
+            ]
+        );
+
+        assert_eq!(DayWindows::new(10, 10, 1).count(), 0);
+        assert_eq!(DayWindows::new(10, 11, 100).collect::<Vec<_>>(), [vec![10]]);
+    }
+
+    #[test]
+    fn test_generated_lines() {
+        let config = config(&[
+            "--tags",
+            "2",
+            "--tag-cardinality",
+            "2",
+            "--fields",
+            "2",
+            "--distribution",
+            "constant",
+        ]);
+        let series = series_count(config.tags, config.tag_cardinality.get()).unwrap();
+        let mut generator = Generator::new(&config, series);
+
+        let mut out = String::new();
+        for series in 0..series {
+            generator.write_line(0, series, 42, &mut out);
+        }
+
+        assert_eq!(
+            out,
+            "\
+            backfill_0,tag_0=value_0,tag_1=value_0 field_0=1,field_1=1 42\n\
+            backfill_0,tag_0=value_0,tag_1=value_1 field_0=1,field_1=1 42\n\
+            backfill_0,tag_0=value_1,tag_1=value_0 field_0=1,field_1=1 42\n\
+            backfill_0,tag_0=value_1,tag_1=value_1 field_0=1,field_1=1 42\n"
+        );
+
+        // The generated line protocol is valid.
+        let batches = mutable_batch_lp::lines_to_batches(&out, 0).unwrap();
+        assert_eq!(batches["backfill_0"].rows(), 4);
+    }
+
+    #[test]
+    fn test_generation_is_deterministic() {
+        let generate = || {
+            let config = config(&["--seed", "42", "--distribution", "random-walk"]);
+            let series = series_count(config.tags, config.tag_cardinality.get()).unwrap();
+            let mut generator = Generator::new(&config, series);
+
+            let mut out = String::new();
+            for t in 0..10 {
+                generator.write_line(0, 3, t, &mut out);
+            }
+            out
+        };
+
+        assert_eq!(generate(), generate());
+    }
+}
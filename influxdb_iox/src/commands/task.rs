@@ -0,0 +1,106 @@
+//! This module implements the `task` CLI command
+
+use influxdb_iox_client::{connection::Connection, task};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("JSON Serialization error: {0}")]
+    Serde(#[from] serde_json::Error),
+
+    #[error("Client error: {0}")]
+    Client(#[from] influxdb_iox_client::error::Error),
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// Manage the tasks periodically writing the results of SQL queries to tables
+#[derive(Debug, clap::Parser)]
+pub struct Config {
+    #[clap(subcommand)]
+    command: Command,
+}
+
+/// All possible subcommands for task
+#[derive(Debug, clap::Parser)]
+enum Command {
+    /// Create a task
+    Create {
+        /// The namespace the task queries and writes to
+        #[clap(action)]
+        namespace: String,
+
+        /// The name of the task, unique within the namespace
+        #[clap(action)]
+        name: String,
+
+        /// The cron expression (`<minute> <hour> <day of month> <month> <day
+        /// of week>`, in UTC) of the times the task runs at, such as
+        /// `*/5 * * * *`
+        #[clap(long, action)]
+        schedule: String,
+
+        /// The SQL query to run, whose results must have a `time` column
+        #[clap(long, action)]
+        query: String,
+
+        /// The table the results of the query are written to
+        #[clap(long, action)]
+        destination: String,
+
+        /// The number of times a failed run is retried
+        #[clap(long, action, default_value = "0")]
+        max_retries: i32,
+    },
+
+    /// List the tasks of a namespace, along with their most recent runs
+    List {
+        /// The namespace of the tasks
+        #[clap(action)]
+        namespace: String,
+
+        /// The number of most recent runs of each task to show
+        #[clap(long, action, default_value = "1")]
+        runs: i32,
+    },
+
+    /// Run a task now, outside of its schedule, waiting for the run to finish
+    Run {
+        /// The namespace of the task
+        #[clap(action)]
+        namespace: String,
+
+        /// The name of the task to run
+        #[clap(action)]
+        name: String,
+    },
+}
+
+pub async fn command(connection: Connection, config: Config) -> Result<()> {
+    let mut client = task::Client::new(connection);
+    match config.command {
+        Command::Create {
+            namespace,
+            name,
+            schedule,
+            query,
+            destination,
+            max_retries,
+        } => {
+            let task = client
+                .create_task(namespace, name, schedule, query, destination, max_retries)
+                .await?;
+            println!("{}", serde_json::to_string_pretty(&task)?);
+        }
+        Command::List { namespace, runs } => {
+            let tasks = client.list_tasks(namespace, runs).await?;
+            println!("{}", serde_json::to_string_pretty(&tasks)?);
+        }
+        Command::Run { namespace, name } => {
+            let run = client.run_task(namespace, name).await?;
+            println!("{}", serde_json::to_string_pretty(&run)?);
+        } // Deliberately not adding _ => so the compiler will direct people here to impl new
+          // commands
+    }
+    Ok(())
+}
@@ -77,3 +77,22 @@ impl metric::Instrument for JemallocMetrics {
         self
     }
 }
+
+/// Write a heap profile with jemalloc's `prof.dump` and return its contents,
+/// to be read with `jeprof`.
+///
+/// Profiling must be activated at startup with `MALLOC_CONF=prof:true`.
+#[cfg(feature = "jemalloc_profiling")]
+pub fn dump_heap_profile() -> Result<Vec<u8>, ioxd_common::heap_dump::HeapDumpError> {
+    use std::{ffi::CString, os::unix::ffi::OsStrExt};
+
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("heap.prof");
+    let c_path = CString::new(path.as_os_str().as_bytes())?;
+
+    // SAFETY: "prof.dump" takes a NUL terminated path, which outlives the
+    // call.
+    unsafe { tikv_jemalloc_ctl::raw::write(b"prof.dump\0", c_path.as_ptr()) }?;
+
+    Ok(std::fs::read(&path)?)
+}
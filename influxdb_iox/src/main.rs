@@ -37,17 +37,20 @@ use trace_exporters::{
 };
 
 mod commands {
+    pub mod backup;
     pub mod catalog;
     pub mod debug;
     pub mod namespace;
     pub mod partition_template;
     pub mod query;
+    pub mod query_admin;
     pub mod query_ingester;
     pub mod remote;
     pub mod run;
     pub mod sql;
     pub mod storage;
     pub mod table;
+    pub mod task;
     pub mod tracing;
     pub mod write;
 }
@@ -207,6 +210,9 @@ enum Command {
     /// Various commands for catalog manipulation
     Catalog(commands::catalog::Config),
 
+    /// Back up the catalog to, or restore it from, object storage
+    Backup(commands::backup::Config),
+
     /// Interrogate internal data
     Debug(commands::debug::Config),
 
@@ -219,6 +225,10 @@ enum Command {
     /// Query the data with SQL
     Query(commands::query::Config),
 
+    /// List and kill the queries running on a querier, or list the query
+    /// history
+    QueryAdmin(commands::query_admin::Config),
+
     /// Query the ingester only
     QueryIngester(commands::query_ingester::Config),
 
@@ -227,6 +237,10 @@ enum Command {
 
     /// Various commands for table manipulation
     Table(commands::table::Config),
+
+    /// Create, list and run the tasks periodically writing the results of
+    /// SQL queries to tables
+    Task(commands::task::Config),
 }
 
 fn main() -> Result<(), std::io::Error> {
@@ -331,8 +345,15 @@ fn main() -> Result<(), std::io::Error> {
             }
             Some(Command::Storage(config)) => {
                 let _tracing_guard = handle_init_logs(init_simple_logs(log_verbose_count));
-                let connection = connection(grpc_host).await;
-                if let Err(e) = commands::storage::command(connection, config).await {
+                // The backfill subcommand writes through the router's http
+                // endpoint, and only connects to it when writing in router
+                // mode.
+                let host = if config.is_backfill() {
+                    http_host
+                } else {
+                    grpc_host
+                };
+                if let Err(e) = commands::storage::command(|| connection(host), config).await {
                     eprintln!("{e}");
                     std::process::exit(ReturnCode::Failure as _)
                 }
@@ -344,6 +365,13 @@ fn main() -> Result<(), std::io::Error> {
                     std::process::exit(ReturnCode::Failure as _)
                 }
             }
+            Some(Command::Backup(config)) => {
+                let _tracing_guard = handle_init_logs(init_simple_logs(log_verbose_count));
+                if let Err(e) = commands::backup::command(config).await {
+                    eprintln!("{e}");
+                    std::process::exit(ReturnCode::Failure as _)
+                }
+            }
             Some(Command::Debug(config)) => {
                 let _tracing_guard = handle_init_logs(init_simple_logs(log_verbose_count));
                 if let Err(e) = commands::debug::command(|| connection(grpc_host), config).await {
@@ -367,6 +395,14 @@ fn main() -> Result<(), std::io::Error> {
                     std::process::exit(ReturnCode::Failure as _)
                 }
             }
+            Some(Command::QueryAdmin(config)) => {
+                let _tracing_guard = handle_init_logs(init_simple_logs(log_verbose_count));
+                let connection = connection(grpc_host).await;
+                if let Err(e) = commands::query_admin::command(connection, config).await {
+                    eprintln!("{e}");
+                    std::process::exit(ReturnCode::Failure as _)
+                }
+            }
             Some(Command::QueryIngester(config)) => {
                 let _tracing_guard = handle_init_logs(init_simple_logs(log_verbose_count));
                 let connection = connection(grpc_host).await;
@@ -391,6 +427,14 @@ fn main() -> Result<(), std::io::Error> {
                     std::process::exit(ReturnCode::Failure as _)
                 }
             }
+            Some(Command::Task(config)) => {
+                let _tracing_guard = handle_init_logs(init_simple_logs(log_verbose_count));
+                let connection = connection(grpc_host).await;
+                if let Err(e) = commands::task::command(connection, config).await {
+                    eprintln!("{e}");
+                    std::process::exit(ReturnCode::Failure as _)
+                }
+            }
         }
     });
 
@@ -24,6 +24,30 @@ pub static VERSION_STRING: Lazy<&'static str> = Lazy::new(|| {
     Box::leak(s)
 });
 
+/// Returns the optional cargo features enabled in this build.
+pub fn enabled_features() -> Vec<&'static str> {
+    [
+        ("aws", cfg!(feature = "aws")),
+        ("azure", cfg!(feature = "azure")),
+        ("gcp", cfg!(feature = "gcp")),
+        ("pprof", cfg!(feature = "pprof")),
+        ("heappy", cfg!(feature = "heappy")),
+        (
+            "jemalloc_replacing_malloc",
+            cfg!(feature = "jemalloc_replacing_malloc"),
+        ),
+        ("jemalloc_profiling", cfg!(feature = "jemalloc_profiling")),
+        (
+            "ingester_fault_injection",
+            cfg!(feature = "ingester_fault_injection"),
+        ),
+        ("tokio_console", cfg!(feature = "tokio_console")),
+    ]
+    .into_iter()
+    .filter_map(|(name, enabled)| enabled.then_some(name))
+    .collect()
+}
+
 /// A UUID that is unique for the process lifetime.
 pub static PROCESS_UUID: Lazy<&'static str> = Lazy::new(|| {
     let s = uuid::Uuid::new_v4().to_string();
@@ -507,28 +507,36 @@ async fn flightsql_get_tables() {
                     - "table_types:[]"
                     - "include_schema:false"
                     - "*********************"
-                    - +--------------+--------------------+-------------+------------+
-                    - "| catalog_name | db_schema_name     | table_name  | table_type |"
-                    - +--------------+--------------------+-------------+------------+
-                    - "| public       | information_schema | columns     | VIEW       |"
-                    - "| public       | information_schema | df_settings | VIEW       |"
-                    - "| public       | information_schema | tables      | VIEW       |"
-                    - "| public       | information_schema | views       | VIEW       |"
-                    - "| public       | iox                | the_table   | BASE TABLE |"
-                    - "| public       | system             | queries     | BASE TABLE |"
-                    - +--------------+--------------------+-------------+------------+
+                    - +--------------+--------------------+---------------------+------------+
+                    - "| catalog_name | db_schema_name     | table_name          | table_type |"
+                    - +--------------+--------------------+---------------------+------------+
+                    - "| public       | information_schema | columns             | VIEW       |"
+                    - "| public       | information_schema | df_settings         | VIEW       |"
+                    - "| public       | information_schema | tables              | VIEW       |"
+                    - "| public       | information_schema | views               | VIEW       |"
+                    - "| public       | iox                | the_table           | BASE TABLE |"
+                    - "| public       | system             | ingester_partitions | BASE TABLE |"
+                    - "| public       | system             | parquet_files       | BASE TABLE |"
+                    - "| public       | system             | partitions          | BASE TABLE |"
+                    - "| public       | system             | queries             | BASE TABLE |"
+                    - "| public       | system             | tables              | BASE TABLE |"
+                    - +--------------+--------------------+---------------------+------------+
                     - "catalog:None"
                     - "db_schema_filter_pattern:None"
                     - "table_name_filter_pattern:None"
                     - "table_types:[\"BASE TABLE\"]"
                     - "include_schema:false"
                     - "*********************"
-                    - +--------------+----------------+------------+------------+
-                    - "| catalog_name | db_schema_name | table_name | table_type |"
-                    - +--------------+----------------+------------+------------+
-                    - "| public       | iox            | the_table  | BASE TABLE |"
-                    - "| public       | system         | queries    | BASE TABLE |"
-                    - +--------------+----------------+------------+------------+
+                    - +--------------+----------------+---------------------+------------+
+                    - "| catalog_name | db_schema_name | table_name          | table_type |"
+                    - +--------------+----------------+---------------------+------------+
+                    - "| public       | iox            | the_table           | BASE TABLE |"
+                    - "| public       | system         | ingester_partitions | BASE TABLE |"
+                    - "| public       | system         | parquet_files       | BASE TABLE |"
+                    - "| public       | system         | partitions          | BASE TABLE |"
+                    - "| public       | system         | queries             | BASE TABLE |"
+                    - "| public       | system         | tables              | BASE TABLE |"
+                    - +--------------+----------------+---------------------+------------+
                     - "catalog:None"
                     - "db_schema_filter_pattern:None"
                     - "table_name_filter_pattern:None"
@@ -685,16 +693,20 @@ async fn flightsql_get_tables_matches_information_schema() {
                         get_tables_output,
                         @r###"
                     ---
-                    - +--------------+--------------------+-------------+------------+
-                    - "| catalog_name | db_schema_name     | table_name  | table_type |"
-                    - +--------------+--------------------+-------------+------------+
-                    - "| public       | information_schema | columns     | VIEW       |"
-                    - "| public       | information_schema | df_settings | VIEW       |"
-                    - "| public       | information_schema | tables      | VIEW       |"
-                    - "| public       | information_schema | views       | VIEW       |"
-                    - "| public       | iox                | the_table   | BASE TABLE |"
-                    - "| public       | system             | queries     | BASE TABLE |"
-                    - +--------------+--------------------+-------------+------------+
+                    - +--------------+--------------------+---------------------+------------+
+                    - "| catalog_name | db_schema_name     | table_name          | table_type |"
+                    - +--------------+--------------------+---------------------+------------+
+                    - "| public       | information_schema | columns             | VIEW       |"
+                    - "| public       | information_schema | df_settings         | VIEW       |"
+                    - "| public       | information_schema | tables              | VIEW       |"
+                    - "| public       | information_schema | views               | VIEW       |"
+                    - "| public       | iox                | the_table           | BASE TABLE |"
+                    - "| public       | system             | ingester_partitions | BASE TABLE |"
+                    - "| public       | system             | parquet_files       | BASE TABLE |"
+                    - "| public       | system             | partitions          | BASE TABLE |"
+                    - "| public       | system             | queries             | BASE TABLE |"
+                    - "| public       | system             | tables              | BASE TABLE |"
+                    - +--------------+--------------------+---------------------+------------+
                     "###
                     );
 
@@ -1457,7 +1469,11 @@ async fn jdbc_tests(jdbc_url: &str, table_name: &str) {
                                      public,  information_schema,  tables,  VIEW,  null,  null,  null,  null,  null,  null\n\
                                      public,  information_schema,  views,  VIEW,  null,  null,  null,  null,  null,  null\n\
                                      public,  iox,  the_table,  BASE TABLE,  null,  null,  null,  null,  null,  null\n\
-                                     public,  system,  queries,  BASE TABLE,  null,  null,  null,  null,  null,  null";
+                                     public,  system,  ingester_partitions,  BASE TABLE,  null,  null,  null,  null,  null,  null\n\
+                                     public,  system,  parquet_files,  BASE TABLE,  null,  null,  null,  null,  null,  null\n\
+                                     public,  system,  partitions,  BASE TABLE,  null,  null,  null,  null,  null,  null\n\
+                                     public,  system,  queries,  BASE TABLE,  null,  null,  null,  null,  null,  null\n\
+                                     public,  system,  tables,  BASE TABLE,  null,  null,  null,  null,  null,  null";
 
     // CommandGetTables output
     let expected_tables_with_filters = "**************\n\
@@ -1465,7 +1481,11 @@ async fn jdbc_tests(jdbc_url: &str, table_name: &str) {
                                         **************\n\
                                         TABLE_CAT,  TABLE_SCHEM,  TABLE_NAME,  TABLE_TYPE,  REMARKS,  TYPE_CAT,  TYPE_SCHEM,  TYPE_NAME,  SELF_REFERENCING_COL_NAME,  REF_GENERATION\n\
                                         ------------\n\
-                                        public,  system,  queries,  BASE TABLE,  null,  null,  null,  null,  null,  null";
+                                        public,  system,  ingester_partitions,  BASE TABLE,  null,  null,  null,  null,  null,  null\n\
+                                        public,  system,  parquet_files,  BASE TABLE,  null,  null,  null,  null,  null,  null\n\
+                                        public,  system,  partitions,  BASE TABLE,  null,  null,  null,  null,  null,  null\n\
+                                        public,  system,  queries,  BASE TABLE,  null,  null,  null,  null,  null,  null\n\
+                                        public,  system,  tables,  BASE TABLE,  null,  null,  null,  null,  null,  null";
 
     // CommandGetTableTypes output
     let expected_table_types = "**************\n\
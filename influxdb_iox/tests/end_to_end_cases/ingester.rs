@@ -8,7 +8,10 @@ use datafusion::{
 };
 use futures::FutureExt;
 use http::StatusCode;
-use influxdb_iox_client::table::generated_types::{Part, PartitionTemplate, TemplatePart};
+use influxdb_iox_client::{
+    build_info::generated_types::{check_compatibility, Compatibility, INGESTER_QUERY_PROTOCOL},
+    table::generated_types::{Part, PartitionTemplate, TemplatePart},
+};
 use ingester_query_grpc::{influxdata::iox::ingester::v1 as proto, IngesterQueryRequest};
 use prost::Message;
 use test_helpers_end_to_end::{maybe_skip_integration, MiniCluster, Step, StepTest, StepTestState};
@@ -405,3 +408,34 @@ async fn ingester_flight_api_table_not_found() {
         panic!("Wrong error variant: {err}")
     }
 }
+
+#[tokio::test]
+async fn get_build_info() {
+    test_helpers::maybe_start_logging();
+    let database_url = maybe_skip_integration!();
+
+    let mut cluster = MiniCluster::create_shared(database_url).await;
+
+    StepTest::new(
+        &mut cluster,
+        vec![Step::Custom(Box::new(move |state: &mut StepTestState| {
+            async move {
+                let mut client = influxdb_iox_client::build_info::Client::new(
+                    state.cluster().ingester().ingester_grpc_connection(),
+                );
+                let info = client.get_build_info().await.unwrap();
+
+                assert_eq!(info.server_type, "ingester");
+                assert!(!info.version.is_empty());
+                assert!(!info.git_hash.is_empty());
+                assert_eq!(
+                    check_compatibility(&info, INGESTER_QUERY_PROTOCOL),
+                    Compatibility::Compatible
+                );
+            }
+            .boxed()
+        }))],
+    )
+    .run()
+    .await
+}
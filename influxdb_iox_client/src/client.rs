@@ -1,3 +1,6 @@
+/// Client for the build information API
+pub mod build_info;
+
 /// Client for interacting with a remote catalog
 pub mod catalog;
 
@@ -27,6 +30,9 @@ pub mod ingester;
 /// Client for namespace API
 pub mod namespace;
 
+/// Client for the querier query administration API
+pub mod query_admin;
+
 /// Client for schema API
 pub mod schema;
 
@@ -36,6 +42,9 @@ pub mod store;
 /// Client for table API
 pub mod table;
 
+/// Client for the querier task API
+pub mod task;
+
 /// Client for testing purposes.
 pub mod test;
 
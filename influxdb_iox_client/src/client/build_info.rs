@@ -0,0 +1,34 @@
+use self::generated_types::{build_info_service_client::BuildInfoServiceClient, *};
+use crate::{connection::Connection, error::Error};
+use client_util::connection::GrpcConnection;
+
+/// Re-export generated_types
+pub mod generated_types {
+    pub use generated_types::influxdata::iox::build_info::v1::*;
+    pub use generated_types::influxdata::iox::build_info::{
+        check_compatibility, Compatibility, INGESTER_QUERY_PROTOCOL, PROTOCOL_VERSIONS,
+        RPC_WRITE_PROTOCOL,
+    };
+}
+
+/// A basic client for reading the build information of an IOx server.
+#[derive(Debug, Clone)]
+pub struct Client {
+    inner: BuildInfoServiceClient<GrpcConnection>,
+}
+
+impl Client {
+    /// Creates a new client with the provided connection
+    pub fn new(connection: Connection) -> Self {
+        Self {
+            inner: BuildInfoServiceClient::new(connection.into_grpc_connection()),
+        }
+    }
+
+    /// Get the build information of the server
+    pub async fn get_build_info(&mut self) -> Result<GetBuildInfoResponse, Error> {
+        let response = self.inner.get_build_info(GetBuildInfoRequest {}).await?;
+
+        Ok(response.into_inner())
+    }
+}
@@ -1,6 +1,9 @@
 use client_util::connection::GrpcConnection;
 
-use self::generated_types::{catalog_service_client::CatalogServiceClient, *};
+use self::generated_types::{
+    catalog_introspection_service_client::CatalogIntrospectionServiceClient,
+    catalog_service_client::CatalogServiceClient, *,
+};
 
 use crate::connection::Connection;
 use crate::error::Error;
@@ -14,13 +17,16 @@ pub mod generated_types {
 #[derive(Debug, Clone)]
 pub struct Client {
     inner: CatalogServiceClient<GrpcConnection>,
+    introspection: CatalogIntrospectionServiceClient<GrpcConnection>,
 }
 
 impl Client {
     /// Creates a new client with the provided connection
     pub fn new(connection: Connection) -> Self {
+        let connection = connection.into_grpc_connection();
         Self {
-            inner: CatalogServiceClient::new(connection.into_grpc_connection()),
+            inner: CatalogServiceClient::new(connection.clone()),
+            introspection: CatalogIntrospectionServiceClient::new(connection),
         }
     }
 
@@ -88,4 +94,45 @@ impl Client {
 
         Ok(response.into_inner().parquet_files)
     }
+
+    /// List a page of the namespaces in the catalog.
+    pub async fn list_namespaces(
+        &mut self,
+        request: ListNamespacesRequest,
+    ) -> Result<ListNamespacesResponse, Error> {
+        let response = self.introspection.list_namespaces(request).await?;
+
+        Ok(response.into_inner())
+    }
+
+    /// List a page of the tables in a namespace.
+    pub async fn list_tables(
+        &mut self,
+        request: ListTablesRequest,
+    ) -> Result<ListTablesResponse, Error> {
+        let response = self.introspection.list_tables(request).await?;
+
+        Ok(response.into_inner())
+    }
+
+    /// List a page of the columns of a table.
+    pub async fn list_columns(
+        &mut self,
+        request: ListColumnsRequest,
+    ) -> Result<ListColumnsResponse, Error> {
+        let response = self.introspection.list_columns(request).await?;
+
+        Ok(response.into_inner())
+    }
+
+    /// List a page of the partitions of a table, with a summary of their
+    /// parquet files.
+    pub async fn list_partitions(
+        &mut self,
+        request: ListPartitionsRequest,
+    ) -> Result<ListPartitionsResponse, Error> {
+        let response = self.introspection.list_partitions(request).await?;
+
+        Ok(response.into_inner())
+    }
 }
@@ -1,4 +1,7 @@
-use self::generated_types::{persist_service_client::PersistServiceClient, *};
+use self::generated_types::{
+    buffer_schema_service_client::BufferSchemaServiceClient,
+    persist_service_client::PersistServiceClient, *,
+};
 use crate::{connection::Connection, error::Error};
 use client_util::connection::GrpcConnection;
 
@@ -7,17 +10,21 @@ pub mod generated_types {
     pub use generated_types::influxdata::iox::ingester::v1::*;
 }
 
-/// A basic client for interacting with the ingester persist service.
+/// A basic client for interacting with the ingester persist and buffer schema
+/// services.
 #[derive(Debug, Clone)]
 pub struct Client {
     inner: PersistServiceClient<GrpcConnection>,
+    schema: BufferSchemaServiceClient<GrpcConnection>,
 }
 
 impl Client {
     /// Creates a new client with the provided connection
     pub fn new(connection: Connection) -> Self {
+        let connection = connection.into_grpc_connection();
         Self {
-            inner: PersistServiceClient::new(connection.into_grpc_connection()),
+            inner: PersistServiceClient::new(connection.clone()),
+            schema: BufferSchemaServiceClient::new(connection),
         }
     }
 
@@ -29,4 +36,41 @@ impl Client {
 
         Ok(())
     }
+
+    /// List the persist jobs currently executing in the ingester, oldest first, reporting the
+    /// stage and progress of each.
+    pub async fn list_persist_jobs(&mut self) -> Result<Vec<PersistJob>, Error> {
+        let response = self
+            .inner
+            .list_persist_jobs(ListPersistJobsRequest {})
+            .await?;
+
+        Ok(response.into_inner().jobs)
+    }
+
+    /// Return the buffered and persisted sequence numbers, and the buffered size, of each
+    /// partition with data buffered or persisting in the ingester.
+    pub async fn get_write_info(&mut self) -> Result<Vec<PartitionWriteInfo>, Error> {
+        let response = self.inner.get_write_info(GetWriteInfoRequest {}).await?;
+
+        Ok(response.into_inner().partitions)
+    }
+
+    /// Return the columns present in the data buffered by the ingester for the specified table,
+    /// including data that has not yet been persisted.
+    pub async fn buffer_schema(
+        &mut self,
+        namespace_id: i64,
+        table_id: i64,
+    ) -> Result<Vec<BufferedColumn>, Error> {
+        let response = self
+            .schema
+            .get_buffer_schema(GetBufferSchemaRequest {
+                namespace_id,
+                table_id,
+            })
+            .await?;
+
+        Ok(response.into_inner().columns)
+    }
 }
@@ -108,6 +108,43 @@ impl Client {
         Ok(response.into_inner().namespace.unwrap_field("namespace")?)
     }
 
+    /// Mark a namespace as read-only (rejecting all writes) or writable.
+    ///
+    /// Queries against a read-only namespace continue to be served.
+    pub async fn update_namespace_read_only(
+        &mut self,
+        namespace: &str,
+        read_only: bool,
+    ) -> Result<Namespace, Error> {
+        let response = self
+            .inner
+            .update_namespace_read_only(UpdateNamespaceReadOnlyRequest {
+                name: namespace.to_string(),
+                read_only,
+            })
+            .await?;
+
+        Ok(response.into_inner().namespace.unwrap_field("namespace")?)
+    }
+
+    /// Set the name of the object store the parquet files of a namespace are
+    /// stored in, or use the default object store when [`None`].
+    pub async fn update_namespace_object_store(
+        &mut self,
+        namespace: &str,
+        object_store_name: Option<&str>,
+    ) -> Result<Namespace, Error> {
+        let response = self
+            .inner
+            .update_namespace_object_store(UpdateNamespaceObjectStoreRequest {
+                name: namespace.to_string(),
+                object_store_name: object_store_name.map(ToString::to_string),
+            })
+            .await?;
+
+        Ok(response.into_inner().namespace.unwrap_field("namespace")?)
+    }
+
     /// Delete a namespace
     pub async fn delete_namespace(&mut self, namespace: &str) -> Result<(), Error> {
         self.inner
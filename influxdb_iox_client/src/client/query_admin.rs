@@ -0,0 +1,75 @@
+use self::generated_types::{query_admin_service_client::QueryAdminServiceClient, *};
+use crate::{connection::Connection, error::Error};
+use ::generated_types::google::OptionalField;
+use client_util::connection::GrpcConnection;
+
+/// Re-export generated_types
+pub mod generated_types {
+    pub use generated_types::influxdata::iox::querier::v1::*;
+}
+
+/// A basic client for administering the queries run by a querier.
+#[derive(Debug, Clone)]
+pub struct Client {
+    inner: QueryAdminServiceClient<GrpcConnection>,
+}
+
+impl Client {
+    /// Creates a new client with the provided connection
+    pub fn new(connection: Connection) -> Self {
+        Self {
+            inner: QueryAdminServiceClient::new(connection.into_grpc_connection()),
+        }
+    }
+
+    /// List the queries of `namespace_name` currently running on the querier
+    pub async fn list_queries(
+        &mut self,
+        namespace_name: impl Into<String> + Send,
+    ) -> Result<Vec<RunningQuery>, Error> {
+        let response = self
+            .inner
+            .list_queries(ListQueriesRequest {
+                namespace_name: namespace_name.into(),
+            })
+            .await?;
+
+        Ok(response.into_inner().queries)
+    }
+
+    /// Kill the query with the given ID running against `namespace_name`
+    pub async fn kill_query(
+        &mut self,
+        namespace_name: impl Into<String> + Send,
+        id: impl Into<String> + Send,
+    ) -> Result<RunningQuery, Error> {
+        let response = self
+            .inner
+            .kill_query(KillQueryRequest {
+                id: id.into(),
+                namespace_name: namespace_name.into(),
+            })
+            .await?;
+
+        Ok(response.into_inner().query.unwrap_field("query")?)
+    }
+
+    /// Get the rules of the query denylist
+    pub async fn get_query_denylist(&mut self) -> Result<Vec<String>, Error> {
+        let response = self
+            .inner
+            .get_query_denylist(GetQueryDenylistRequest {})
+            .await?;
+
+        Ok(response.into_inner().rules)
+    }
+
+    /// Replace the rules of the query denylist
+    pub async fn set_query_denylist(&mut self, rules: Vec<String>) -> Result<(), Error> {
+        self.inner
+            .set_query_denylist(SetQueryDenylistRequest { rules })
+            .await?;
+
+        Ok(())
+    }
+}
@@ -40,4 +40,40 @@ impl Client {
 
         Ok(response.into_inner().schema.unwrap_field("schema")?)
     }
+
+    /// Get the effective schema of a table: the catalog columns, merged with any columns present
+    /// only in data buffered by the ingesters.
+    pub async fn get_effective_table_schema(
+        &mut self,
+        namespace: &str,
+        table: &str,
+    ) -> Result<GetEffectiveTableSchemaResponse, Error> {
+        let response = self
+            .inner
+            .get_effective_table_schema(GetEffectiveTableSchemaRequest {
+                namespace: namespace.to_string(),
+                table: table.to_string(),
+            })
+            .await?;
+
+        Ok(response.into_inner())
+    }
+
+    /// Compare the schema of `target_namespace` to that of `template_namespace`, returning the
+    /// missing tables and columns and the conflicting column types.
+    pub async fn compare_namespace_schemas(
+        &mut self,
+        template_namespace: &str,
+        target_namespace: &str,
+    ) -> Result<CompareNamespaceSchemasResponse, Error> {
+        let response = self
+            .inner
+            .compare_namespace_schemas(CompareNamespaceSchemasRequest {
+                template_namespace: template_namespace.to_string(),
+                target_namespace: target_namespace.to_string(),
+            })
+            .await?;
+
+        Ok(response.into_inner())
+    }
 }
@@ -0,0 +1,85 @@
+use self::generated_types::{task_service_client::TaskServiceClient, *};
+use crate::{connection::Connection, error::Error};
+use ::generated_types::google::OptionalField;
+use client_util::connection::GrpcConnection;
+
+/// Re-export generated_types
+pub mod generated_types {
+    pub use generated_types::influxdata::iox::querier::v1::*;
+}
+
+/// A basic client for managing the tasks run by the queriers.
+#[derive(Debug, Clone)]
+pub struct Client {
+    inner: TaskServiceClient<GrpcConnection>,
+}
+
+impl Client {
+    /// Creates a new client with the provided connection
+    pub fn new(connection: Connection) -> Self {
+        Self {
+            inner: TaskServiceClient::new(connection.into_grpc_connection()),
+        }
+    }
+
+    /// Create a task periodically writing the results of `query` to
+    /// `destination_table`, on the cron `schedule`
+    pub async fn create_task(
+        &mut self,
+        namespace_name: impl Into<String> + Send,
+        name: impl Into<String> + Send,
+        schedule: impl Into<String> + Send,
+        query: impl Into<String> + Send,
+        destination_table: impl Into<String> + Send,
+        max_retries: i32,
+    ) -> Result<Task, Error> {
+        let response = self
+            .inner
+            .create_task(CreateTaskRequest {
+                namespace_name: namespace_name.into(),
+                name: name.into(),
+                schedule: schedule.into(),
+                query: query.into(),
+                destination_table: destination_table.into(),
+                max_retries,
+            })
+            .await?;
+
+        Ok(response.into_inner().task.unwrap_field("task")?)
+    }
+
+    /// List the tasks of a namespace, along with their `run_limit` most
+    /// recent runs
+    pub async fn list_tasks(
+        &mut self,
+        namespace_name: impl Into<String> + Send,
+        run_limit: i32,
+    ) -> Result<Vec<list_tasks_response::TaskWithRuns>, Error> {
+        let response = self
+            .inner
+            .list_tasks(ListTasksRequest {
+                namespace_name: namespace_name.into(),
+                run_limit,
+            })
+            .await?;
+
+        Ok(response.into_inner().tasks)
+    }
+
+    /// Run a task now, returning the finished run
+    pub async fn run_task(
+        &mut self,
+        namespace_name: impl Into<String> + Send,
+        name: impl Into<String> + Send,
+    ) -> Result<TaskRun, Error> {
+        let response = self
+            .inner
+            .run_task(RunTaskRequest {
+                namespace_name: namespace_name.into(),
+                name: name.into(),
+            })
+            .await?;
+
+        Ok(response.into_inner().run.unwrap_field("run")?)
+    }
+}
@@ -106,6 +106,8 @@ fn run_projection_bench(
                     table_id: table_id.get(),
                     columns: projection.clone(),
                     predicate: None,
+                    max_snapshots: 0,
+                    resume_after: vec![],
                 })
                 .await
                 .expect("query request failed");
@@ -162,6 +164,8 @@ fn run_concurrent_bench(
         table_id: table_id.get(),
         columns: vec![],
         predicate: None,
+        max_snapshots: 0,
+        resume_after: vec![],
     });
 
     let ctx = Arc::new(ctx);
@@ -261,6 +265,8 @@ fn run_partition_prune_bench(
                     table_id: table_id.get(),
                     columns: vec![],
                     predicate: predicate.clone(),
+                    max_snapshots: 0,
+                    resume_after: vec![],
                 })
                 .await
                 .expect("query request failed");
@@ -71,6 +71,8 @@ fn wal_replay_bench(c: &mut Criterion) {
                     &sink,
                     Arc::new(persist),
                     Arc::new(IngestState::default()),
+                    false,
+                    None,
                     &metric::Registry::default(),
                 )
                 .await
@@ -0,0 +1,138 @@
+//! A background task periodically validating the invariants of all buffered
+//! [`PartitionData`].
+//!
+//! [`PartitionData`]: super::partition::PartitionData
+
+use std::time::Duration;
+
+use metric::U64Counter;
+use observability_deps::tracing::*;
+use tokio::task::JoinHandle;
+
+use crate::partition_iter::PartitionIter;
+
+/// Spawn a task to validate the invariants of every partition yielded by
+/// `buffer`, every `period` duration of time.
+///
+/// Violations are logged at error level, and counted in the
+/// `ingester_buffer_invariant_violations` metric to allow alerting on them.
+/// The buffer is never modified, and no violation is fatal.
+pub(crate) fn spawn_invariant_check<T>(
+    buffer: T,
+    period: Duration,
+    metrics: &metric::Registry,
+) -> JoinHandle<()>
+where
+    T: PartitionIter + Sync + 'static,
+{
+    let violations = metrics
+        .register_metric::<U64Counter>(
+            "ingester_buffer_invariant_violations",
+            "number of partition buffer invariant violations observed by the \
+            periodic invariant checker",
+        )
+        .recorder(&[]);
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(period);
+        loop {
+            interval.tick().await;
+            check_partitions(&buffer, &violations);
+        }
+    })
+}
+
+/// Validate the invariants of every partition in `buffer`, returning the
+/// number of partitions checked.
+fn check_partitions<T>(buffer: &T, violations: &U64Counter) -> usize
+where
+    T: PartitionIter,
+{
+    let mut n_partitions = 0;
+    let mut n_violations = 0;
+
+    for p in buffer.partition_iter() {
+        // Only hold the partition lock for the duration of the check.
+        let (found, namespace_id, table_id, partition_id) = {
+            let p = p.lock();
+            (
+                p.check_invariants(),
+                p.namespace_id(),
+                p.table_id(),
+                p.partition_id().clone(),
+            )
+        };
+
+        for violation in &found {
+            error!(
+                %namespace_id,
+                %table_id,
+                %partition_id,
+                %violation,
+                "partition buffer invariant violated"
+            );
+        }
+
+        n_partitions += 1;
+        n_violations += found.len();
+    }
+
+    violations.inc(n_violations as u64);
+
+    debug!(
+        n_partitions,
+        n_violations, "checked partition buffer invariants"
+    );
+
+    n_partitions
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use data_types::SequenceNumber;
+    use mutable_batch_lp::test_helpers::lp_to_mutable_batch;
+    use parking_lot::Mutex;
+
+    use super::*;
+    use crate::test_util::PartitionDataBuilder;
+
+    #[test]
+    fn test_check_partitions() {
+        let metrics = metric::Registry::default();
+        let violations = metrics
+            .register_metric::<U64Counter>("test", "test")
+            .recorder(&[]);
+
+        let mut healthy = PartitionDataBuilder::new().build();
+        healthy
+            .buffer_write(
+                lp_to_mutable_batch(r#"bananas,city=London people=2 10"#).1,
+                SequenceNumber::new(1),
+            )
+            .expect("write should succeed");
+
+        // Apply the same sequence number to both the persisting batch and the
+        // buffer, violating the non-overlapping sequence set invariant.
+        let mut broken = PartitionDataBuilder::new().build();
+        broken
+            .buffer_write(
+                lp_to_mutable_batch(r#"bananas,city=London people=2 10"#).1,
+                SequenceNumber::new(2),
+            )
+            .expect("write should succeed");
+        let _persisting = broken.mark_persisting().expect("must contain data");
+        broken
+            .buffer_write_unchecked(
+                lp_to_mutable_batch(r#"bananas,city=Madrid people=4 20"#).1,
+                SequenceNumber::new(2),
+            )
+            .expect("write should succeed");
+
+        let buffer = vec![Arc::new(Mutex::new(healthy)), Arc::new(Mutex::new(broken))];
+
+        assert_eq!(check_partitions(&buffer, &violations), 2);
+        assert_eq!(violations.fetch(), 1);
+    }
+}
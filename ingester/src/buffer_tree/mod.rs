@@ -12,6 +12,8 @@ pub(crate) use root::*;
 
 pub(crate) mod post_write;
 
+pub(crate) mod invariant_check;
+
 /// This needs to be pub for the benchmarks but should not be used outside the crate.
 #[cfg(feature = "benches")]
 pub use partition::PartitionData;
@@ -1,5 +1,6 @@
 //! Namespace level data buffer structures.
 
+pub(crate) mod activity;
 pub(crate) mod name_resolver;
 
 use std::sync::Arc;
@@ -7,11 +8,14 @@ use std::sync::Arc;
 use async_trait::async_trait;
 use data_types::{NamespaceId, TableId};
 use metric::U64Counter;
+use observability_deps::tracing::warn;
+use parking_lot::Mutex;
 use predicate::Predicate;
 use trace::span::Span;
 
+use self::activity::NamespaceActivity;
 use super::{
-    partition::{counter::PartitionCounter, resolver::PartitionProvider},
+    partition::{counter::PartitionCounter, resolver::PartitionProvider, PartitionData},
     post_write::PostWriteObserver,
     table::{metadata_resolver::TableProvider, TableData},
     BufferWriteError,
@@ -22,8 +26,8 @@ use crate::{
     dml_payload::IngestOp,
     dml_sink::DmlSink,
     query::{
-        projection::OwnedProjection, response::QueryResponse, tracing::QueryExecTracing,
-        QueryError, QueryExec,
+        projection::OwnedProjection, response::QueryResponse, snapshot_page::SnapshotPage,
+        tracing::QueryExecTracing, QueryError, QueryExec,
     },
 };
 
@@ -75,6 +79,9 @@ pub(crate) struct NamespaceData<O> {
     /// The count of tables initialised in this Ingester so far, across all
     /// namespaces.
     table_count: U64Counter,
+    /// The count of writes dropped because their sequence number had already
+    /// been buffered, across all namespaces.
+    duplicate_writes: U64Counter,
 
     /// The resolver of `(table_id, partition_key)` to [`PartitionData`].
     ///
@@ -88,6 +95,9 @@ pub(crate) struct NamespaceData<O> {
     /// consistent when enforced.
     partition_count: Arc<PartitionCounter>,
 
+    /// The time of the most recent write to, and query of, this namespace.
+    activity: NamespaceActivity,
+
     post_write_observer: Arc<O>,
 }
 
@@ -108,6 +118,12 @@ impl<O> NamespaceData<O> {
                 "Number of tables known to the ingester",
             )
             .recorder(&[]);
+        let duplicate_writes = metrics
+            .register_metric::<U64Counter>(
+                "ingester_duplicate_writes_dropped",
+                "Number of writes dropped because their sequence number was already buffered",
+            )
+            .recorder(&[]);
 
         Self {
             namespace_id,
@@ -115,9 +131,11 @@ impl<O> NamespaceData<O> {
             tables: Default::default(),
             catalog_table_resolver,
             table_count,
+            duplicate_writes,
             partition_provider,
             post_write_observer,
             partition_count: Arc::new(partition_counter),
+            activity: Default::default(),
         }
     }
 
@@ -144,6 +162,21 @@ impl<O> NamespaceData<O> {
     pub(super) fn tables(&self) -> Vec<Arc<TableData<O>>> {
         self.tables.values()
     }
+
+    /// Obtain a snapshot of the partitions of the tables within this
+    /// [`NamespaceData`].
+    pub(crate) fn partitions(&self) -> impl Iterator<Item = Arc<Mutex<PartitionData>>> + Send
+    where
+        O: Send + Sync,
+    {
+        self.tables().into_iter().flat_map(|t| t.partitions())
+    }
+
+    /// Return the time of the most recent write to, and query of, this
+    /// namespace.
+    pub(crate) fn activity(&self) -> &NamespaceActivity {
+        &self.activity
+    }
 }
 
 #[async_trait]
@@ -156,6 +189,8 @@ where
     async fn apply(&self, op: IngestOp) -> Result<(), Self::Error> {
         match op {
             IngestOp::Write(write) => {
+                self.activity.record_write();
+
                 // Extract the partition key derived by the router.
                 let partition_key = write.partition_key().clone();
 
@@ -176,14 +211,32 @@ where
                     });
 
                     let partitioned_data = b.into_partitioned_data();
+                    let sequence_number = partitioned_data.sequence_number();
 
-                    table_data
+                    match table_data
                         .buffer_table_write(
-                            partitioned_data.sequence_number(),
+                            sequence_number,
                             partitioned_data.into_data(),
                             partition_key.clone(),
                         )
-                        .await?;
+                        .await
+                    {
+                        Ok(()) => {}
+                        // The write has already been applied to the partition
+                        // and dropping the duplicate makes the replay
+                        // idempotent.
+                        Err(BufferWriteError::DuplicateSequenceNumber { .. }) => {
+                            warn!(
+                                namespace_id = %self.namespace_id,
+                                %table_id,
+                                %partition_key,
+                                sequence_number = sequence_number.get(),
+                                "dropping write with duplicate sequence number"
+                            );
+                            self.duplicate_writes.inc(1);
+                        }
+                        Err(e) => return Err(e),
+                    }
                 }
             }
         }
@@ -206,12 +259,15 @@ where
         projection: OwnedProjection,
         span: Option<Span>,
         predicate: Option<Predicate>,
+        page: SnapshotPage,
     ) -> Result<Self::Response, QueryError> {
         assert_eq!(
             self.namespace_id, namespace_id,
             "buffer tree index inconsistency"
         );
 
+        self.activity.record_query();
+
         // Extract the table if it exists.
         let inner = self
             .table(table_id)
@@ -221,7 +277,7 @@ where
         // a tracing delegate to emit a child span.
         Ok(QueryResponse::new(
             QueryExecTracing::new(inner, "table")
-                .query_exec(namespace_id, table_id, projection, span, predicate)
+                .query_exec(namespace_id, table_id, projection, span, predicate, page)
                 .await?,
         ))
     }
@@ -313,4 +369,63 @@ mod tests {
             &***ARBITRARY_NAMESPACE_NAME
         );
     }
+
+    #[tokio::test]
+    async fn test_duplicate_write_dropped() {
+        let metrics = Arc::new(metric::Registry::default());
+
+        let partition_provider =
+            Arc::new(MockPartitionProvider::default().with_partition(PartitionDataBuilder::new()));
+
+        let ns = NamespaceData::new(
+            ARBITRARY_NAMESPACE_ID,
+            defer_namespace_name_1_ms(),
+            Arc::clone(&*ARBITRARY_TABLE_PROVIDER),
+            partition_provider,
+            PartitionCounter::new(NonZeroUsize::new(usize::MAX).unwrap()),
+            Arc::new(MockPostWriteObserver::default()),
+            &metrics,
+        );
+
+        // Apply the same write (and sequence number) twice, as would happen
+        // if the write was replayed.
+        for _ in 0..2 {
+            ns.apply(IngestOp::Write(make_write_op(
+                &ARBITRARY_PARTITION_KEY,
+                ARBITRARY_NAMESPACE_ID,
+                &ARBITRARY_TABLE_NAME,
+                ARBITRARY_TABLE_ID,
+                42,
+                &format!(
+                    r#"{},city=Medford day="sun",temp=55 22"#,
+                    &*ARBITRARY_TABLE_NAME
+                ),
+                None,
+            )))
+            .await
+            .expect("duplicate writes should not return an error");
+        }
+
+        let dropped = metrics
+            .get_instrument::<Metric<U64Counter>>("ingester_duplicate_writes_dropped")
+            .expect("failed to read metric")
+            .get_observer(&Attributes::from([]))
+            .expect("failed to get observer")
+            .fetch();
+        assert_eq!(dropped, 1);
+
+        // Only a single copy of the write is buffered.
+        let partition = ns
+            .table(ARBITRARY_TABLE_ID)
+            .expect("table must exist")
+            .partitions()
+            .pop()
+            .expect("partition must exist");
+        let rows = partition
+            .lock()
+            .get_query_data(&OwnedProjection::default())
+            .expect("must contain data")
+            .num_rows();
+        assert_eq!(rows, 1);
+    }
 }
@@ -0,0 +1,83 @@
+//! Tracking of the most recent writes to and queries of a namespace.
+
+use std::time::Duration;
+
+use parking_lot::Mutex;
+use tokio::time::Instant;
+
+/// The time of the most recent write to, and query of, a namespace buffered by
+/// this ingester.
+#[derive(Debug)]
+pub(crate) struct NamespaceActivity {
+    last_write: Mutex<Instant>,
+    last_query: Mutex<Option<Instant>>,
+}
+
+impl Default for NamespaceActivity {
+    /// Initialise the activity of a namespace created for a write applied
+    /// now.
+    fn default() -> Self {
+        Self {
+            last_write: Mutex::new(Instant::now()),
+            last_query: Default::default(),
+        }
+    }
+}
+
+impl NamespaceActivity {
+    /// Record a write to the namespace.
+    pub(crate) fn record_write(&self) {
+        *self.last_write.lock() = Instant::now();
+    }
+
+    /// Record a query of the namespace.
+    pub(crate) fn record_query(&self) {
+        *self.last_query.lock() = Some(Instant::now());
+    }
+
+    /// Return the time of the most recent write to the namespace.
+    pub(crate) fn last_write(&self) -> Instant {
+        *self.last_write.lock()
+    }
+
+    /// Return the time of the most recent query of the namespace, if it was
+    /// ever queried.
+    pub(crate) fn last_query(&self) -> Option<Instant> {
+        *self.last_query.lock()
+    }
+
+    /// Return the duration since the namespace was last written to or queried.
+    pub(crate) fn idle_for(&self) -> Duration {
+        let last_active = self
+            .last_query()
+            .map_or(self.last_write(), |q| q.max(self.last_write()));
+        Instant::now().saturating_duration_since(last_active)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(start_paused = true)]
+    async fn test_idle_for() {
+        let activity = NamespaceActivity::default();
+        assert_eq!(activity.idle_for(), Duration::ZERO);
+        assert!(activity.last_query().is_none());
+
+        tokio::time::advance(Duration::from_secs(10)).await;
+        assert_eq!(activity.idle_for(), Duration::from_secs(10));
+
+        // A query resets the idle duration.
+        activity.record_query();
+        assert_eq!(activity.idle_for(), Duration::ZERO);
+        assert!(activity.last_query().unwrap() > activity.last_write());
+
+        tokio::time::advance(Duration::from_secs(5)).await;
+        assert_eq!(activity.idle_for(), Duration::from_secs(5));
+
+        // And so does a write.
+        activity.record_write();
+        assert_eq!(activity.idle_for(), Duration::ZERO);
+    }
+}
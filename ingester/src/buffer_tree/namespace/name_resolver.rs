@@ -1,8 +1,9 @@
-use std::{sync::Arc, time::Duration};
+use std::{collections::HashMap, sync::Arc, time::Duration};
 
 use backoff::{Backoff, BackoffConfig};
 use data_types::NamespaceId;
 use iox_catalog::interface::{Catalog, SoftDeletedRows};
+use parking_lot::Mutex;
 
 use super::NamespaceName;
 use crate::deferred_load::DeferredLoad;
@@ -19,6 +20,10 @@ pub(crate) struct NamespaceNameResolver {
     catalog: Arc<dyn Catalog>,
     backoff_config: BackoffConfig,
     metrics: Arc<metric::Registry>,
+
+    /// Names loaded from the catalog at startup, each consumed by the first
+    /// [`DeferredLoad`] for its namespace instead of querying the catalog.
+    warm: Mutex<HashMap<NamespaceId, NamespaceName>>,
 }
 
 impl NamespaceNameResolver {
@@ -33,9 +38,16 @@ impl NamespaceNameResolver {
             catalog,
             backoff_config,
             metrics,
+            warm: Default::default(),
         }
     }
 
+    /// Resolve the namespaces in `names` without querying the catalog.
+    pub(crate) fn with_warm_names(self, names: HashMap<NamespaceId, NamespaceName>) -> Self {
+        *self.warm.lock() = names;
+        self
+    }
+
     /// Fetch the [`NamespaceName`] from the [`Catalog`] for specified
     /// `namespace_id`, retrying endlessly when errors occur.
     pub(crate) async fn fetch(
@@ -71,6 +83,10 @@ impl NamespaceNameResolver {
 
 impl NamespaceNameProvider for NamespaceNameResolver {
     fn for_namespace(&self, id: NamespaceId) -> DeferredLoad<NamespaceName> {
+        if let Some(name) = self.warm.lock().remove(&id) {
+            return DeferredLoad::new(self.max_smear, async { name }, &self.metrics);
+        }
+
         DeferredLoad::new(
             self.max_smear,
             Self::fetch(id, Arc::clone(&self.catalog), self.backoff_config.clone()),
@@ -142,4 +158,28 @@ mod tests {
             .await;
         assert_eq!(&**got, NAMESPACE_NAME);
     }
+
+    #[tokio::test]
+    async fn test_warm_names() {
+        let metrics = Arc::new(metric::Registry::default());
+        let catalog: Arc<dyn Catalog> =
+            Arc::new(iox_catalog::mem::MemCatalog::new(Arc::clone(&metrics)));
+
+        // The warm namespace does not exist in the catalog, so resolving it
+        // would panic if the catalog was queried.
+        let fetcher = NamespaceNameResolver::new(
+            Duration::from_secs(10),
+            Arc::clone(&catalog),
+            BackoffConfig::default(),
+            metrics,
+        )
+        .with_warm_names([(NamespaceId::new(42), NAMESPACE_NAME.into())].into());
+
+        let got = fetcher
+            .for_namespace(NamespaceId::new(42))
+            .get()
+            .with_timeout_panic(Duration::from_secs(5))
+            .await;
+        assert_eq!(&**got, NAMESPACE_NAME);
+    }
 }
@@ -1,30 +1,39 @@
 //! Partition level data buffer structures.
 
-use std::sync::Arc;
+use std::{collections::BTreeMap, sync::Arc};
 
+use bytes::Bytes;
 use data_types::{
     sequence_number_set::SequenceNumberSet, NamespaceId, PartitionKey, SequenceNumber,
     SortedColumnSet, TableId, TimestampMinMax, TransitionPartitionId,
 };
 use mutable_batch::MutableBatch;
 use observability_deps::tracing::*;
-use schema::{merge::SchemaMerger, sort::SortKey, Schema};
+use schema::{merge::SchemaMerger, sort::SortKey, InfluxColumnType, Schema};
+use uuid::Uuid;
 
 use self::{
     buffer::{traits::Queryable, DataBuffer},
     counter::PartitionCounter,
     persisting::{BatchIdent, PersistingData},
     persisting_list::PersistingList,
+    recent::{RecentFile, RecentPersistCache},
 };
 use super::{namespace::NamespaceName, table::metadata::TableMetadata, BufferWriteError};
 use crate::{
-    deferred_load::DeferredLoad, query::projection::OwnedProjection, query_adaptor::QueryAdaptor,
+    deferred_load::DeferredLoad,
+    query::{projection::OwnedProjection, snapshot_page::PartitionPage},
+    query_adaptor::QueryAdaptor,
 };
 
+#[cfg(feature = "ingest_audit")]
+mod audit;
 mod buffer;
 pub(crate) mod counter;
+pub(crate) mod invariants;
 pub(crate) mod persisting;
 mod persisting_list;
+pub(crate) mod recent;
 pub(crate) mod resolver;
 
 /// The load state of the [`SortKey`] for a given partition.
@@ -121,6 +130,10 @@ pub struct PartitionData {
     /// [`PartitionData`].
     completed_persistence_count: u64,
 
+    /// The largest [`SequenceNumber`] persisted over the lifetime of this
+    /// [`PartitionData`].
+    max_persisted_sequence_number: Option<SequenceNumber>,
+
     /// A counter tracking the number of non-empty partitions per namespace.
     ///
     /// This value is incremented when this [`PartitionData`] transitions from
@@ -129,6 +142,32 @@ pub struct PartitionData {
     /// data is dropped, transitioning the [`PartitionData`] from non-empty to
     /// empty.
     partition_counter: Arc<PartitionCounter>,
+
+    /// The columns present in the buffered (and persisting) data.
+    ///
+    /// Cleared when this partition transitions to empty, after which no
+    /// buffered data contains them.
+    buffered_columns: BTreeMap<String, BufferedColumn>,
+
+    /// The parquet encoding of the most recently persisted data, if retained
+    /// by the [`RecentPersistCache`].
+    ///
+    /// This data is not buffered, and does not contribute to the row count
+    /// or emptiness of this partition.
+    recent_persisted: Option<Arc<RecentFile>>,
+
+    /// An independent record of the writes buffered in this partition,
+    /// verified against each persisted batch.
+    #[cfg(feature = "ingest_audit")]
+    audit: audit::IngestAudit,
+}
+
+/// The type of a column in the data buffered in a [`PartitionData`], and the
+/// sequence number of the first write observed containing it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct BufferedColumn {
+    pub(crate) column_type: InfluxColumnType,
+    pub(crate) first_seen: SequenceNumber,
 }
 
 impl PartitionData {
@@ -156,16 +195,64 @@ impl PartitionData {
             persisting: PersistingList::default(),
             started_persistence_count: BatchIdent::default(),
             completed_persistence_count: 0,
+            max_persisted_sequence_number: None,
             partition_counter,
             is_empty: true,
+            buffered_columns: BTreeMap::new(),
+            recent_persisted: None,
+            #[cfg(feature = "ingest_audit")]
+            audit: Default::default(),
         }
     }
 
     /// Buffer the given [`MutableBatch`] in memory.
+    ///
+    /// Writes with a [`SequenceNumber`] that has already been applied to this
+    /// partition (and not yet persisted) are rejected with
+    /// [`BufferWriteError::DuplicateSequenceNumber`], ensuring a write replayed
+    /// more than once (such as during overlapping WAL replays) is buffered, and
+    /// therefore persisted & queried, exactly once.
     pub(crate) fn buffer_write(
         &mut self,
         mb: MutableBatch,
         sequence_number: SequenceNumber,
+    ) -> Result<(), BufferWriteError> {
+        if self.contains_sequence_number(sequence_number) {
+            return Err(BufferWriteError::DuplicateSequenceNumber { sequence_number });
+        }
+
+        self.apply_write(mb, sequence_number)
+    }
+
+    /// Buffer `mb` without checking for a duplicate `sequence_number`,
+    /// allowing tests to construct a partition violating the non-overlapping
+    /// sequence number invariant.
+    #[cfg(test)]
+    pub(crate) fn buffer_write_unchecked(
+        &mut self,
+        mb: MutableBatch,
+        sequence_number: SequenceNumber,
+    ) -> Result<(), BufferWriteError> {
+        self.apply_write(mb, sequence_number)
+    }
+
+    /// Returns true if `sequence_number` has been applied to the buffer, or
+    /// any of the persisting batches, of this partition.
+    ///
+    /// The sequence number sets are compressed bitmaps, making this check
+    /// cheap even for partitions that have buffered many writes.
+    fn contains_sequence_number(&self, sequence_number: SequenceNumber) -> bool {
+        self.buffer.sequence_number_set().contains(sequence_number)
+            || self
+                .persisting
+                .iter()
+                .any(|(_, b)| b.sequence_number_set().contains(sequence_number))
+    }
+
+    fn apply_write(
+        &mut self,
+        mb: MutableBatch,
+        sequence_number: SequenceNumber,
     ) -> Result<(), BufferWriteError> {
         if self.is_empty() {
             // This partition is transitioning from empty, to non-empty.
@@ -181,9 +268,33 @@ impl PartitionData {
         // point because this partition is non-empty.
         debug_assert_ne!(self.partition_counter.read(), 0);
 
+        // Identify any columns this write adds to the buffered data.
+        let new_columns = mb
+            .columns()
+            .filter(|(name, _)| !self.buffered_columns.contains_key(name.as_str()))
+            .map(|(name, col)| (name.clone(), col.influx_type()))
+            .collect::<Vec<_>>();
+
+        #[cfg(feature = "ingest_audit")]
+        let rows = mb.rows();
+
         // Buffer the write.
         self.buffer.buffer_write(mb, sequence_number)?;
 
+        #[cfg(feature = "ingest_audit")]
+        self.audit.record(sequence_number, rows);
+
+        self.buffered_columns
+            .extend(new_columns.into_iter().map(|(name, column_type)| {
+                (
+                    name,
+                    BufferedColumn {
+                        column_type,
+                        first_seen: sequence_number,
+                    },
+                )
+            }));
+
         // Invariant: if the partition contains a buffered write, it must report
         // non-empty.
         debug_assert!(!self.is_empty());
@@ -206,6 +317,12 @@ impl PartitionData {
         self.buffer.persist_cost_estimate()
     }
 
+    /// Return the approximate memory size of the data in this
+    /// [`PartitionData`] that is currently being persisted.
+    pub(crate) fn persisting_size(&self) -> usize {
+        self.persisting.size()
+    }
+
     /// Returns the number of rows currently buffered in this [`PartitionData`].
     ///
     /// The returned value will always match the row count of the data returned
@@ -359,6 +476,71 @@ impl PartitionData {
         Some(q)
     }
 
+    /// Return the data of the snapshots of this partition selected by `page`,
+    /// applying `projection`, along with the object store IDs of the
+    /// persisting batches among them.
+    ///
+    /// The persisting batches of the partition and its buffered data are each
+    /// a snapshot. If snapshots are left out because of the page limit, the
+    /// highest [`SequenceNumber`] of the returned data is returned to resume
+    /// from.
+    ///
+    /// Unlike [`Self::get_query_data()`], the returned data MAY contain fewer
+    /// rows than [`Self::rows()`].
+    pub(crate) fn get_query_data_page(
+        &mut self,
+        projection: &OwnedProjection,
+        page: PartitionPage,
+    ) -> (Option<QueryAdaptor>, Vec<Uuid>, Option<SequenceNumber>) {
+        if page.is_unbounded() {
+            let persisting_ids = self.persisting_object_store_ids();
+            return (self.get_query_data(projection), persisting_ids, None);
+        }
+        if page.is_metadata_only() {
+            return (None, vec![], None);
+        }
+
+        // The persisting batches are ordered before the buffered data, which
+        // is identified by the [`None`] snapshot.
+        let snapshots = self
+            .persisting
+            .snapshots()
+            .map(|(max, id, b)| (max, Some((id, b))))
+            .chain(
+                self.buffer
+                    .sequence_number_set()
+                    .max()
+                    .map(|max| (max, None)),
+            );
+        let (selected, resume) = page.select(snapshots);
+
+        let mut persisting_ids = Vec::new();
+        let mut data = Vec::new();
+        for snapshot in selected {
+            match snapshot {
+                Some((id, b)) => {
+                    persisting_ids.push(id);
+                    data.extend(b.get_query_data(projection));
+                }
+                None => data.extend(self.buffer.get_query_data(projection)),
+            }
+        }
+
+        trace!(
+            namespace_id = %self.namespace_id,
+            table_id = %self.table_id,
+            table = %self.table,
+            partition_id = %self.partition_id,
+            partition_key = %self.partition_key,
+            n_batches = data.len(),
+            ?resume,
+            "read partition data page"
+        );
+
+        let data = (!data.is_empty()).then(|| QueryAdaptor::new(self.partition_id.clone(), data));
+        (data, persisting_ids, resume)
+    }
+
     /// Snapshot and mark all buffered data as persisting.
     ///
     /// This method returns [`None`] if no data is buffered in [`Self`].
@@ -386,6 +568,12 @@ impl PartitionData {
         // mark_persisted() call and ensure monotonicity.
         let batch_ident = self.started_persistence_count.next();
 
+        // Allocate the ID of the parquet file this data will be persisted to.
+        //
+        // This ID is reported to queriers alongside the persisting data, so
+        // must be known before the data is made visible as persisting.
+        let object_store_id = Uuid::new_v4();
+
         debug!(
             namespace_id = %self.namespace_id,
             table_id = %self.table_id,
@@ -393,6 +581,7 @@ impl PartitionData {
             partition_id = %self.partition_id,
             partition_key = %self.partition_key,
             %batch_ident,
+            %object_store_id,
             "marking partition as persisting"
         );
 
@@ -403,11 +592,15 @@ impl PartitionData {
                 fsm.get_query_data(&OwnedProjection::default()),
             ),
             batch_ident,
+            object_store_id,
         );
 
         // Push the buffer into the persisting list (which maintains batch
         // order).
-        self.persisting.push(batch_ident, fsm);
+        self.persisting.push(batch_ident, object_store_id, fsm);
+
+        #[cfg(feature = "ingest_audit")]
+        self.audit.mark_persisting(batch_ident);
 
         // Invariant: the partition must not be marked as empty when there's an
         // entry in the persisting list.
@@ -462,16 +655,152 @@ impl PartitionData {
             // This partitioning from non-empty to empty.
             self.partition_counter.dec();
             self.is_empty = true;
+            self.buffered_columns.clear();
         }
 
         // Return the set of IDs this buffer contained.
-        fsm.into_sequence_number_set()
+        let sequence_numbers = fsm.into_sequence_number_set();
+
+        #[cfg(feature = "ingest_audit")]
+        if let Err(report) = self
+            .audit
+            .verify_persisted(batch.batch_ident(), &sequence_numbers)
+        {
+            error!(
+                namespace_id = %self.namespace_id,
+                table_id = %self.table_id,
+                table = %self.table,
+                partition_id = %self.partition_id,
+                partition_key = %self.partition_key,
+                %report,
+                "ingest audit detected persisted writes not matching the buffered writes"
+            );
+        }
+        self.max_persisted_sequence_number = self
+            .max_persisted_sequence_number
+            .max(sequence_numbers.max());
+        sequence_numbers
+    }
+
+    /// Return the smallest and largest [`SequenceNumber`] of the writes
+    /// buffered or persisting in this [`PartitionData`], if any.
+    pub(crate) fn buffered_sequence_number_range(
+        &self,
+    ) -> Option<(SequenceNumber, SequenceNumber)> {
+        let sets = || {
+            std::iter::once(self.buffer.sequence_number_set())
+                .chain(self.persisting.iter().map(|(_, b)| b.sequence_number_set()))
+        };
+
+        Some((
+            sets().filter_map(|v| v.min()).min()?,
+            sets().filter_map(|v| v.max()).max()?,
+        ))
+    }
+
+    /// Return the largest [`SequenceNumber`] persisted over the lifetime of
+    /// this [`PartitionData`], if any.
+    ///
+    /// Persist jobs may complete out of order, so writes with a lower
+    /// [`SequenceNumber`] may still be buffered or persisting.
+    pub(crate) fn max_persisted_sequence_number(&self) -> Option<SequenceNumber> {
+        self.max_persisted_sequence_number
     }
 
     pub(crate) fn partition_id(&self) -> &TransitionPartitionId {
         &self.partition_id
     }
 
+    /// Return the object store IDs of the parquet files currently being
+    /// persisted for this [`PartitionData`] instance.
+    ///
+    /// The data being persisted to these files is included in the output of
+    /// [`PartitionData::get_query_data()`] until [`Self::mark_persisted()`] is
+    /// called for it, which happens after the file has been added to the
+    /// catalog.
+    pub(crate) fn persisting_object_store_ids(&self) -> Vec<Uuid> {
+        self.persisting.object_store_ids().collect()
+    }
+
+    /// Allocate a new object store ID for the persisting `batch`, returning
+    /// it.
+    ///
+    /// Queriers are told about the new ID in subsequent query responses, as
+    /// the batch is persisted to a parquet file with the new ID.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if `batch` is not currently being persisted.
+    pub(crate) fn reassign_object_store_id(&mut self, batch: &mut PersistingData) -> Uuid {
+        let object_store_id = Uuid::new_v4();
+
+        debug!(
+            namespace_id = %self.namespace_id,
+            table_id = %self.table_id,
+            table = %self.table,
+            partition_id = %self.partition_id,
+            partition_key = %self.partition_key,
+            batch_ident = %batch.batch_ident(),
+            old_object_store_id = %batch.object_store_id(),
+            %object_store_id,
+            "reassigning persisting object store ID"
+        );
+
+        self.persisting
+            .set_object_store_id(batch.batch_ident(), object_store_id);
+        batch.set_object_store_id(object_store_id);
+
+        object_store_id
+    }
+
+    /// Retain `data`, the parquet encoding of the persisted `batch`, in
+    /// `cache`.
+    ///
+    /// Only the most recently persisted data is retained - if `batch` is older
+    /// than the currently retained data (because persist jobs completed out of
+    /// order) the currently retained data is kept.
+    pub(crate) fn retain_persisted(
+        &mut self,
+        cache: &RecentPersistCache,
+        batch: &PersistingData,
+        data: Bytes,
+    ) {
+        let batch_ident = batch.batch_ident();
+
+        let old = self.recent_persisted.take();
+        if let Some(old) = old.as_ref().filter(|v| v.batch_ident() > batch_ident) {
+            self.recent_persisted = Some(Arc::clone(old));
+            return;
+        }
+
+        self.recent_persisted = cache.insert(batch.object_store_id(), batch_ident, data, old);
+    }
+
+    /// Return the retained parquet encoding of the most recently persisted
+    /// data, if any.
+    ///
+    /// Queriers treat data returned by the ingester as newer than all
+    /// persisted files, so the retained data is only returned when it is
+    /// older than all data currently buffered or persisting in this partition,
+    /// and it is newer than all other persisted files (which holds while this
+    /// ingester is the only writer to this partition).
+    ///
+    /// The object store ID of the returned file MUST be reported alongside the
+    /// data to prevent the querier from reading it from object storage.
+    pub(crate) fn recent_persisted(&self) -> Option<Arc<RecentFile>> {
+        let file = self.recent_persisted.as_ref()?;
+        if !file.is_resident() || self.persisting.iter().any(|(v, _)| v < file.batch_ident()) {
+            return None;
+        }
+        Some(Arc::clone(file))
+    }
+
+    /// Return the columns present in the data buffered in this
+    /// [`PartitionData`], keyed by column name.
+    pub(crate) fn buffered_columns(&self) -> &BTreeMap<String, BufferedColumn> {
+        &self.buffered_columns
+    }
+
     /// Return the count of persisted Parquet files for this [`PartitionData`] instance.
     pub(crate) fn completed_persistence_count(&self) -> u64 {
         self.completed_persistence_count
@@ -530,7 +859,7 @@ impl PartitionData {
 
 #[cfg(test)]
 mod tests {
-    use std::time::Duration;
+    use std::{num::NonZeroUsize, time::Duration};
 
     use arrow::compute::SortOptions;
     use arrow_util::assert_batches_eq;
@@ -548,6 +877,7 @@ mod tests {
     use super::*;
     use crate::{
         buffer_tree::partition::resolver::SortKeyResolver,
+        query::snapshot_page::SnapshotPage,
         test_util::{populate_catalog, PartitionDataBuilder, ARBITRARY_TRANSITION_PARTITION_ID},
     };
 
@@ -875,7 +1205,7 @@ mod tests {
 
         // Buffer another write, and generate a snapshot by querying it.
         let mb = lp_to_mutable_batch(r#"bananas x=4 42"#).1;
-        p.buffer_write(mb, SequenceNumber::new(3))
+        p.buffer_write(mb, SequenceNumber::new(4))
             .expect("write should succeed");
 
         assert_eq!(
@@ -1231,6 +1561,294 @@ mod tests {
         );
     }
 
+    // Ensure the object store IDs of in-flight persist operations are reported
+    // until each is marked as persisted.
+    #[tokio::test]
+    async fn test_persisting_object_store_ids() {
+        let mut p = PartitionDataBuilder::new().build();
+        assert!(p.persisting_object_store_ids().is_empty());
+
+        let mb = lp_to_mutable_batch(r#"bananas,city=London people=2,pigeons="millions" 10"#).1;
+        p.buffer_write(mb, SequenceNumber::new(1))
+            .expect("write should succeed");
+        let first = p.mark_persisting().expect("must contain data");
+
+        let mb = lp_to_mutable_batch(r#"bananas,city=Madrid people=4,pigeons="none" 20"#).1;
+        p.buffer_write(mb, SequenceNumber::new(2))
+            .expect("write should succeed");
+        let second = p.mark_persisting().expect("must contain data");
+
+        assert_ne!(first.object_store_id(), second.object_store_id());
+        assert_eq!(
+            p.persisting_object_store_ids(),
+            [first.object_store_id(), second.object_store_id()]
+        );
+
+        let second_id = second.object_store_id();
+        let _ = p.mark_persisted(first);
+        assert_eq!(p.persisting_object_store_ids(), [second_id]);
+
+        let _ = p.mark_persisted(second);
+        assert!(p.persisting_object_store_ids().is_empty());
+    }
+
+    // Ensure a reassigned object store ID replaces the ID of the persisting
+    // batch, and is the ID reported to queriers.
+    #[tokio::test]
+    async fn test_reassign_object_store_id() {
+        let mut p = PartitionDataBuilder::new().build();
+
+        let mb = lp_to_mutable_batch(r#"bananas,city=London people=2,pigeons="millions" 10"#).1;
+        p.buffer_write(mb, SequenceNumber::new(1))
+            .expect("write should succeed");
+        let mut data = p.mark_persisting().expect("must contain data");
+        let old_id = data.object_store_id();
+
+        let new_id = p.reassign_object_store_id(&mut data);
+        assert_ne!(new_id, old_id);
+        assert_eq!(data.object_store_id(), new_id);
+        assert_eq!(p.persisting_object_store_ids(), [new_id]);
+
+        let _ = p.mark_persisted(data);
+        assert!(p.persisting_object_store_ids().is_empty());
+    }
+
+    // Ensure the buffered sequence number range spans the buffered and
+    // persisting writes, and the persisted sequence number only ever grows
+    // when persist jobs complete out of order.
+    #[tokio::test]
+    async fn test_write_progress() {
+        let mut p = PartitionDataBuilder::new().build();
+        assert_eq!(p.buffered_sequence_number_range(), None);
+        assert_eq!(p.max_persisted_sequence_number(), None);
+
+        let mb = lp_to_mutable_batch(r#"bananas,city=London people=2 10"#).1;
+        p.buffer_write(mb.clone(), SequenceNumber::new(1))
+            .expect("write should succeed");
+        p.buffer_write(mb.clone(), SequenceNumber::new(3))
+            .expect("write should succeed");
+        let first = p.mark_persisting().expect("must contain data");
+
+        p.buffer_write(mb.clone(), SequenceNumber::new(5))
+            .expect("write should succeed");
+        let second = p.mark_persisting().expect("must contain data");
+
+        p.buffer_write(mb, SequenceNumber::new(7))
+            .expect("write should succeed");
+        assert_eq!(
+            p.buffered_sequence_number_range(),
+            Some((SequenceNumber::new(1), SequenceNumber::new(7)))
+        );
+
+        let _ = p.mark_persisted(second);
+        assert_eq!(
+            p.buffered_sequence_number_range(),
+            Some((SequenceNumber::new(1), SequenceNumber::new(7)))
+        );
+        assert_eq!(
+            p.max_persisted_sequence_number(),
+            Some(SequenceNumber::new(5))
+        );
+
+        let _ = p.mark_persisted(first);
+        assert_eq!(
+            p.buffered_sequence_number_range(),
+            Some((SequenceNumber::new(7), SequenceNumber::new(7)))
+        );
+        assert_eq!(
+            p.max_persisted_sequence_number(),
+            Some(SequenceNumber::new(5))
+        );
+
+        let third = p.mark_persisting().expect("must contain data");
+        let _ = p.mark_persisted(third);
+        assert_eq!(p.buffered_sequence_number_range(), None);
+        assert_eq!(
+            p.max_persisted_sequence_number(),
+            Some(SequenceNumber::new(7))
+        );
+    }
+
+    // Ensure the snapshots of a partition can be paged through, resuming
+    // after the highest sequence number returned by the previous page.
+    #[tokio::test]
+    async fn test_get_query_data_page() {
+        let mut p = PartitionDataBuilder::new().build();
+
+        let mb = lp_to_mutable_batch(r#"bananas,city=London people=2,pigeons="millions" 10"#).1;
+        p.buffer_write(mb, SequenceNumber::new(1))
+            .expect("write should succeed");
+        let first = p.mark_persisting().expect("must contain data");
+
+        let mb = lp_to_mutable_batch(r#"bananas,city=Madrid people=4,pigeons="none" 20"#).1;
+        p.buffer_write(mb, SequenceNumber::new(2))
+            .expect("write should succeed");
+        let second = p.mark_persisting().expect("must contain data");
+
+        let mb = lp_to_mutable_batch(r#"bananas,city=Paris people=6,pigeons="few" 30"#).1;
+        p.buffer_write(mb, SequenceNumber::new(3))
+            .expect("write should succeed");
+
+        // A page of one snapshot, resuming after the given sequence number.
+        let id = p.partition_id().clone();
+        let page = |resume: Option<SequenceNumber>| {
+            SnapshotPage::new(
+                resume.map(|v| (id.clone(), v)).into_iter().collect(),
+                NonZeroUsize::new(1),
+            )
+            .partition(&id)
+            .expect("partition must be selected")
+        };
+
+        // The first page returns the oldest persisting batch.
+        let (data, ids, resume) = p.get_query_data_page(&OwnedProjection::default(), page(None));
+        assert_eq!(data.expect("should return data").num_rows(), 1);
+        assert_eq!(ids, [first.object_store_id()]);
+        assert_eq!(resume, Some(SequenceNumber::new(1)));
+
+        // The next page resumes from the second persisting batch.
+        let (data, ids, resume) = p.get_query_data_page(&OwnedProjection::default(), page(resume));
+        assert_eq!(data.expect("should return data").num_rows(), 1);
+        assert_eq!(ids, [second.object_store_id()]);
+        assert_eq!(resume, Some(SequenceNumber::new(2)));
+
+        // And the last page returns the buffered data.
+        let (data, ids, resume) = p.get_query_data_page(&OwnedProjection::default(), page(resume));
+        let data = data.expect("should return data");
+        let expected = [
+            "+-------+--------+---------+--------------------------------+",
+            "| city  | people | pigeons | time                           |",
+            "+-------+--------+---------+--------------------------------+",
+            "| Paris | 6.0    | few     | 1970-01-01T00:00:00.000000030Z |",
+            "+-------+--------+---------+--------------------------------+",
+        ];
+        assert_batches_eq!(expected, data.record_batches());
+        assert!(ids.is_empty());
+        assert_eq!(resume, None);
+
+        // An unbounded page returns everything.
+        let (data, ids, resume) =
+            p.get_query_data_page(&OwnedProjection::default(), PartitionPage::default());
+        assert_eq!(data.expect("should return data").num_rows(), 3);
+        assert_eq!(ids.len(), 2);
+        assert_eq!(resume, None);
+    }
+
+    // Ensure only the most recently persisted data is retained, and it is only
+    // returned once all older persisting data has been persisted.
+    #[tokio::test]
+    async fn test_recent_persisted() {
+        let cache = RecentPersistCache::new(1024);
+        let mut p = PartitionDataBuilder::new().build();
+        assert!(p.recent_persisted().is_none());
+
+        let mb = lp_to_mutable_batch(r#"bananas,city=London people=2,pigeons="millions" 10"#).1;
+        p.buffer_write(mb, SequenceNumber::new(1))
+            .expect("write should succeed");
+        let first = p.mark_persisting().expect("must contain data");
+
+        let mb = lp_to_mutable_batch(r#"bananas,city=Madrid people=4,pigeons="none" 20"#).1;
+        p.buffer_write(mb, SequenceNumber::new(2))
+            .expect("write should succeed");
+        let second = p.mark_persisting().expect("must contain data");
+
+        // Complete the persist jobs out of order.
+        p.retain_persisted(&cache, &second, Bytes::from_static(b"second"));
+        let second_id = second.object_store_id();
+        let _ = p.mark_persisted(second);
+
+        // The older data is still persisting, so the retained data must not
+        // be returned.
+        assert!(p.recent_persisted().is_none());
+
+        p.retain_persisted(&cache, &first, Bytes::from_static(b"first"));
+        let _ = p.mark_persisted(first);
+
+        // The newest persisted data is retained, and returned now all older
+        // data has been persisted.
+        let got = p.recent_persisted().expect("should retain persisted data");
+        assert_eq!(got.object_store_id(), second_id);
+
+        // Retaining the data does not count as buffered data.
+        assert!(p.is_empty());
+        assert_eq!(p.rows(), 0);
+        assert!(p.get_query_data(&OwnedProjection::default()).is_none());
+    }
+
+    // Ensure the columns of the buffered data are tracked along with the
+    // sequence number of the write that first contained them.
+    #[tokio::test]
+    async fn test_buffered_columns() {
+        let mut p = PartitionDataBuilder::new().build();
+        assert!(p.buffered_columns().is_empty());
+
+        let mb = lp_to_mutable_batch(r#"bananas,city=London people=2 10"#).1;
+        p.buffer_write(mb, SequenceNumber::new(1))
+            .expect("write should succeed");
+        let persisting = p.mark_persisting().expect("must contain data");
+
+        let mb = lp_to_mutable_batch(r#"bananas,city=Madrid people=4,pigeons="none" 20"#).1;
+        p.buffer_write(mb, SequenceNumber::new(2))
+            .expect("write should succeed");
+
+        let got = p
+            .buffered_columns()
+            .iter()
+            .map(|(name, col)| (name.as_str(), col.first_seen.get()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            got,
+            [("city", 1), ("people", 1), ("pigeons", 2), ("time", 1)]
+        );
+        assert_eq!(
+            p.buffered_columns()["city"].column_type,
+            InfluxColumnType::Tag
+        );
+
+        // The columns are retained while any buffered data remains.
+        let _ = p.mark_persisted(persisting);
+        assert_eq!(p.buffered_columns().len(), 4);
+
+        // And dropped once the partition is empty.
+        let persisting = p.mark_persisting().expect("must contain data");
+        let _ = p.mark_persisted(persisting);
+        assert!(p.buffered_columns().is_empty());
+    }
+
+    // Ensure writes with a sequence number already applied to the buffer, or a
+    // persisting batch, are rejected until the data is persisted.
+    #[tokio::test]
+    async fn test_duplicate_sequence_number() {
+        let mut p = PartitionDataBuilder::new().build();
+
+        let mb = lp_to_mutable_batch(r#"bananas,city=London people=2 10"#).1;
+        p.buffer_write(mb.clone(), SequenceNumber::new(1))
+            .expect("write should succeed");
+        assert_matches!(
+            p.buffer_write(mb.clone(), SequenceNumber::new(1)),
+            Err(BufferWriteError::DuplicateSequenceNumber { sequence_number }) => {
+                assert_eq!(sequence_number, SequenceNumber::new(1));
+            }
+        );
+
+        // The duplicate is still detected once the data is persisting.
+        let persisting = p.mark_persisting().expect("must contain data");
+        assert_matches!(
+            p.buffer_write(mb.clone(), SequenceNumber::new(1)),
+            Err(BufferWriteError::DuplicateSequenceNumber { .. })
+        );
+        p.buffer_write(mb.clone(), SequenceNumber::new(2))
+            .expect("write should succeed");
+
+        let data = p.get_query_data(&OwnedProjection::default()).unwrap();
+        assert_eq!(data.num_rows(), 2);
+
+        // Once persisted, the sequence number is no longer tracked.
+        let _ = p.mark_persisted(persisting);
+        p.buffer_write(mb, SequenceNumber::new(1))
+            .expect("write should succeed");
+    }
+
     #[tokio::test]
     async fn test_mark_persisting_no_data() {
         let mut p = PartitionDataBuilder::new().build();
@@ -0,0 +1,202 @@
+//! An audit log of the writes buffered in a [`PartitionData`], verified
+//! against the [`SequenceNumberSet`] of each persisted batch.
+//!
+//! Only compiled with the `ingest_audit` feature, to debug reports of data
+//! loss.
+//!
+//! [`PartitionData`]: super::PartitionData
+
+use std::{collections::VecDeque, fmt::Display};
+
+use data_types::{sequence_number_set::SequenceNumberSet, SequenceNumber};
+
+use super::persisting::BatchIdent;
+
+/// The number of most recently buffered writes retained for inclusion in a
+/// [`AuditMismatch`] report.
+const HISTORY_LEN: usize = 128;
+
+/// A single write buffered in a partition.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct AuditRecord {
+    pub(crate) sequence_number: SequenceNumber,
+    pub(crate) rows: usize,
+    /// The batch the write was persisted in, once marked as persisting.
+    pub(crate) batch: Option<BatchIdent>,
+}
+
+/// Records every write buffered in a partition, independently of the
+/// partition buffer, and verifies each persisted batch contains exactly the
+/// writes that were buffered before it was marked as persisting.
+#[derive(Debug, Default)]
+pub(crate) struct IngestAudit {
+    /// The writes buffered since the last call to [`Self::mark_persisting()`].
+    buffered: Vec<AuditRecord>,
+
+    /// The writes of each persisting batch, in the order they were marked.
+    persisting: Vec<(BatchIdent, Vec<AuditRecord>)>,
+
+    /// A ring buffer of the [`HISTORY_LEN`] most recently buffered writes.
+    history: VecDeque<AuditRecord>,
+}
+
+impl IngestAudit {
+    /// Record the write of `rows` with `sequence_number`, successfully
+    /// buffered.
+    pub(crate) fn record(&mut self, sequence_number: SequenceNumber, rows: usize) {
+        let record = AuditRecord {
+            sequence_number,
+            rows,
+            batch: None,
+        };
+
+        self.buffered.push(record);
+
+        if self.history.len() == HISTORY_LEN {
+            self.history.pop_front();
+        }
+        self.history.push_back(record);
+    }
+
+    /// Assign the writes buffered since the previous call to `batch`.
+    pub(crate) fn mark_persisting(&mut self, batch: BatchIdent) {
+        let mut records = std::mem::take(&mut self.buffered);
+        for r in &mut records {
+            r.batch = Some(batch);
+        }
+        for r in self.history.iter_mut().filter(|r| r.batch.is_none()) {
+            r.batch = Some(batch);
+        }
+        self.persisting.push((batch, records));
+    }
+
+    /// Verify `persisted` contains exactly the sequence numbers of the writes
+    /// assigned to `batch`, releasing them.
+    pub(crate) fn verify_persisted(
+        &mut self,
+        batch: BatchIdent,
+        persisted: &SequenceNumberSet,
+    ) -> Result<(), AuditMismatch> {
+        let records = self
+            .persisting
+            .iter()
+            .position(|(b, _)| *b == batch)
+            .map(|idx| self.persisting.remove(idx).1)
+            .unwrap_or_default();
+
+        let mut audited = SequenceNumberSet::default();
+        for r in &records {
+            audited.add(r.sequence_number);
+        }
+
+        let missing = difference(&audited, persisted);
+        let unexpected = difference(persisted, &audited);
+        if missing.is_empty() && unexpected.is_empty() {
+            return Ok(());
+        }
+
+        Err(AuditMismatch {
+            batch,
+            audited_rows: records.iter().map(|r| r.rows).sum(),
+            missing,
+            unexpected,
+            history: self.history.iter().copied().collect(),
+        })
+    }
+}
+
+/// Return the sequence numbers in `a` that are not in `b`, in ascending
+/// order.
+fn difference(a: &SequenceNumberSet, b: &SequenceNumberSet) -> Vec<SequenceNumber> {
+    let mut v = a.iter().filter(|n| !b.contains(*n)).collect::<Vec<_>>();
+    v.sort_unstable();
+    v
+}
+
+/// A diagnostic report of a persisted batch that does not contain exactly the
+/// writes buffered before it was marked as persisting.
+#[derive(Debug)]
+pub(crate) struct AuditMismatch {
+    batch: BatchIdent,
+    audited_rows: usize,
+    /// Buffered writes that were not persisted.
+    missing: Vec<SequenceNumber>,
+    /// Persisted writes that were never buffered in the batch.
+    unexpected: Vec<SequenceNumber>,
+    history: Vec<AuditRecord>,
+}
+
+impl Display for AuditMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "persisted batch {} ({} audited rows) does not match the buffered writes",
+            self.batch, self.audited_rows
+        )?;
+        writeln!(f, "buffered but not persisted: {:?}", self.missing)?;
+        writeln!(f, "persisted but not buffered: {:?}", self.unexpected)?;
+        writeln!(f, "most recently buffered writes (oldest first):")?;
+        for r in &self.history {
+            let batch = r
+                .batch
+                .map(|b| b.to_string())
+                .unwrap_or_else(|| "buffered".to_string());
+            writeln!(
+                f,
+                "  sequence_number={} rows={} batch={}",
+                r.sequence_number.get(),
+                r.rows,
+                batch
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set(v: impl IntoIterator<Item = u64>) -> SequenceNumberSet {
+        v.into_iter().map(SequenceNumber::new).collect()
+    }
+
+    #[test]
+    fn test_verify_persisted() {
+        let mut audit = IngestAudit::default();
+        let mut ident = BatchIdent::default();
+
+        audit.record(SequenceNumber::new(1), 10);
+        audit.record(SequenceNumber::new(2), 5);
+        let first = ident.next();
+        audit.mark_persisting(first);
+
+        audit.record(SequenceNumber::new(3), 7);
+        let second = ident.next();
+        audit.mark_persisting(second);
+
+        // Batches may complete out of order.
+        audit.verify_persisted(second, &set([3])).unwrap();
+
+        let err = audit.verify_persisted(first, &set([1, 4])).unwrap_err();
+        assert_eq!(err.missing, [SequenceNumber::new(2)]);
+        assert_eq!(err.unexpected, [SequenceNumber::new(4)]);
+        assert_eq!(err.audited_rows, 15);
+        assert_eq!(err.history.len(), 3);
+        assert!(err.to_string().contains("sequence_number=2 rows=5 batch=1"));
+    }
+
+    #[test]
+    fn test_history_bounded() {
+        let mut audit = IngestAudit::default();
+        for n in 0..(HISTORY_LEN as u64 * 2) {
+            audit.record(SequenceNumber::new(n), 1);
+        }
+
+        assert_eq!(audit.history.len(), HISTORY_LEN);
+        assert_eq!(
+            audit.history.front().unwrap().sequence_number,
+            SequenceNumber::new(HISTORY_LEN as u64)
+        );
+    }
+}
@@ -1,9 +1,11 @@
 use arrow::record_batch::RecordBatch;
-use data_types::{SequenceNumber, TimestampMinMax};
+use data_types::{sequence_number_set::SequenceNumberSet, SequenceNumber, TimestampMinMax};
 use mutable_batch::MutableBatch;
 
 mod always_some;
 mod mutable_buffer;
+mod projection_cache;
+mod snapshot_policy;
 mod state_machine;
 pub(crate) mod traits;
 
@@ -89,6 +91,13 @@ impl DataBuffer {
         }
     }
 
+    /// Returns the set of [`SequenceNumber`] applied to this buffer.
+    pub(crate) fn sequence_number_set(&self) -> &SequenceNumberSet {
+        match self.0.get() {
+            FsmState::Buffering(v) => v.sequence_number_set(),
+        }
+    }
+
     /// Returns the [`Schema`] for the buffered data.
     pub(crate) fn schema(&self) -> Option<Schema> {
         match self.0.get() {
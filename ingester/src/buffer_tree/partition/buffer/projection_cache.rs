@@ -0,0 +1,124 @@
+//! A cache of projected [`RecordBatch`] snapshots of a mutable buffer.
+
+use arrow::record_batch::RecordBatch;
+use parking_lot::Mutex;
+
+use crate::query::projection::OwnedProjection;
+
+/// The maximum number of distinct projections retained by a
+/// [`ProjectionCache`].
+const MAX_ENTRIES: usize = 4;
+
+/// A cache of the [`RecordBatch`] generated for each projection of the
+/// (unchanged) contents of a mutable buffer.
+///
+/// Converting buffered data into a [`RecordBatch`] copies the column data of
+/// the projection. Queries for the same partition that arrive between writes
+/// observe identical data, so the first query to project the buffer populates
+/// this cache, and subsequent queries with the same projection receive a
+/// cheap, ref-counted clone of the cached [`RecordBatch`] sharing its Arrow
+/// arrays.
+///
+/// The caller MUST call [`ProjectionCache::clear()`] whenever the buffered
+/// data changes. At most [`MAX_ENTRIES`] projections are retained, evicting
+/// the least recently used.
+#[derive(Debug, Default)]
+pub(super) struct ProjectionCache {
+    /// The cached projections, ordered from most to least recently used.
+    ///
+    /// A projection of all columns is keyed by [`None`].
+    entries: Mutex<Vec<(Option<Vec<String>>, RecordBatch)>>,
+}
+
+impl ProjectionCache {
+    /// Return the cached [`RecordBatch`] for `projection`, generating it with
+    /// `f` if it is not cached.
+    pub(super) fn get_or_insert_with<F>(&self, projection: &OwnedProjection, f: F) -> RecordBatch
+    where
+        F: FnOnce() -> RecordBatch,
+    {
+        let mut entries = self.entries.lock();
+
+        let key = projection.columns();
+        if let Some(idx) = entries.iter().position(|(k, _)| k.as_deref() == key) {
+            // Move the hit to the front of the LRU order.
+            let entry = entries.remove(idx);
+            let batch = entry.1.clone();
+            entries.insert(0, entry);
+            return batch;
+        }
+
+        let batch = f();
+        entries.truncate(MAX_ENTRIES - 1);
+        entries.insert(0, (key.map(<[String]>::to_vec), batch.clone()));
+
+        batch
+    }
+
+    /// Discard all cached projections.
+    pub(super) fn clear(&mut self) {
+        self.entries.get_mut().clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use arrow::array::{ArrayRef, Int64Array};
+
+    use super::*;
+
+    fn batch(v: i64) -> RecordBatch {
+        let array: ArrayRef = Arc::new(Int64Array::from(vec![v]));
+        RecordBatch::try_from_iter([("v", array)]).unwrap()
+    }
+
+    fn get(cache: &ProjectionCache, projection: &OwnedProjection, v: i64) -> RecordBatch {
+        cache.get_or_insert_with(projection, || batch(v))
+    }
+
+    #[test]
+    fn test_projection_cache() {
+        let mut cache = ProjectionCache::default();
+        let all = OwnedProjection::default();
+        let some = OwnedProjection::from(vec!["bananas"]);
+
+        // The first projection is generated and cached, and subsequent calls
+        // return the cached batch, sharing its arrays.
+        let a = get(&cache, &all, 1);
+        let b = get(&cache, &all, 2);
+        assert_eq!(a, batch(1));
+        assert!(Arc::ptr_eq(a.column(0), b.column(0)));
+
+        // Distinct projections are cached separately.
+        assert_eq!(get(&cache, &some, 3), batch(3));
+        assert_eq!(get(&cache, &some, 4), batch(3));
+        assert_eq!(get(&cache, &all, 5), batch(1));
+
+        // Clearing the cache discards all projections.
+        cache.clear();
+        assert_eq!(get(&cache, &all, 6), batch(6));
+        assert_eq!(get(&cache, &some, 7), batch(7));
+    }
+
+    #[test]
+    fn test_projection_cache_eviction() {
+        let cache = ProjectionCache::default();
+        let projections = (0..=MAX_ENTRIES)
+            .map(|i| OwnedProjection::from(vec![i.to_string()]))
+            .collect::<Vec<_>>();
+
+        for (i, p) in projections.iter().take(MAX_ENTRIES).enumerate() {
+            get(&cache, p, i as i64);
+        }
+
+        // Use the first projection, making the second least recently used.
+        assert_eq!(get(&cache, &projections[0], 42), batch(0));
+
+        // Adding another projection evicts the least recently used.
+        get(&cache, &projections[MAX_ENTRIES], 42);
+        assert_eq!(get(&cache, &projections[0], 42), batch(0));
+        assert_eq!(get(&cache, &projections[1], 42), batch(42));
+    }
+}
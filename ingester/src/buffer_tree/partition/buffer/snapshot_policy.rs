@@ -0,0 +1,134 @@
+//! The policy deciding when the mutable buffer of a partition is snapshot.
+
+use std::time::Duration;
+
+use tokio::time::Instant;
+
+/// The minimum number of rows in a mutable buffer before it is snapshot.
+///
+/// Snapshotting smaller buffers would fragment the buffered data into many
+/// small [`RecordBatch`] for little gain.
+///
+/// [`RecordBatch`]: arrow::record_batch::RecordBatch
+const MIN_SNAPSHOT_ROWS: usize = 10_000;
+
+/// The maximum number of rows in a mutable buffer before it is snapshot.
+const MAX_SNAPSHOT_ROWS: usize = 1_000_000;
+
+/// The size of the data in a mutable buffer above which it is snapshot,
+/// regardless of its row count.
+const MAX_SNAPSHOT_BYTES: usize = 64 * 1024 * 1024;
+
+/// The target duration between two snapshots of a buffer receiving writes at
+/// a steady rate.
+const TARGET_SNAPSHOT_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Decides when to snapshot the mutable buffer of a partition into an
+/// immutable [`RecordBatch`].
+///
+/// Every query of a partition copies the data in its mutable buffer, while a
+/// snapshot is converted once and then shared by all queries. Snapshotting
+/// the buffer once it is large bounds the data copied by queries, but each
+/// snapshot adds a [`RecordBatch`] to the partition, so snapshotting too
+/// often fragments the buffered data.
+///
+/// The buffer is snapshot once it holds [`MAX_SNAPSHOT_BYTES`] of data, or
+/// once it holds at least [`MIN_SNAPSHOT_ROWS`] and either its row threshold
+/// is reached or its first write is [`TARGET_SNAPSHOT_INTERVAL`] old. The row
+/// threshold adapts to the write rate observed by the previous snapshot to
+/// produce about one snapshot per [`TARGET_SNAPSHOT_INTERVAL`]: partitions
+/// with a high write rate are snapshot into fewer, larger batches.
+///
+/// [`RecordBatch`]: arrow::record_batch::RecordBatch
+#[derive(Debug)]
+pub(super) struct SnapshotPolicy {
+    /// The number of rows at which the buffer is snapshot.
+    row_threshold: usize,
+
+    /// The time of the first write to the current buffer, if any.
+    first_write: Option<Instant>,
+}
+
+impl Default for SnapshotPolicy {
+    fn default() -> Self {
+        Self {
+            row_threshold: MAX_SNAPSHOT_ROWS,
+            first_write: None,
+        }
+    }
+}
+
+impl SnapshotPolicy {
+    /// Record a write to the buffer.
+    pub(super) fn observe_write(&mut self) {
+        self.first_write.get_or_insert_with(Instant::now);
+    }
+
+    /// Returns true if a buffer containing `rows` rows and `bytes` bytes of
+    /// data should be snapshot.
+    pub(super) fn should_snapshot(&self, rows: usize, bytes: usize) -> bool {
+        if bytes >= MAX_SNAPSHOT_BYTES {
+            return true;
+        }
+        if rows < MIN_SNAPSHOT_ROWS {
+            return false;
+        }
+
+        rows >= self.row_threshold
+            || self
+                .first_write
+                .map(|t| t.elapsed() >= TARGET_SNAPSHOT_INTERVAL)
+                .unwrap_or_default()
+    }
+
+    /// Record the snapshot of a buffer containing `rows` rows, adapting the
+    /// row threshold to the write rate observed since its first write.
+    pub(super) fn observe_snapshot(&mut self, rows: usize) {
+        let Some(first_write) = self.first_write.take() else {
+            return;
+        };
+
+        let elapsed = first_write.elapsed().as_secs_f64();
+        self.row_threshold = if elapsed > 0.0 {
+            let rows_per_sec = rows as f64 / elapsed;
+            ((rows_per_sec * TARGET_SNAPSHOT_INTERVAL.as_secs_f64()) as usize)
+                .clamp(MIN_SNAPSHOT_ROWS, MAX_SNAPSHOT_ROWS)
+        } else {
+            MAX_SNAPSHOT_ROWS
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(start_paused = true)]
+    async fn test_snapshot_policy() {
+        let mut policy = SnapshotPolicy::default();
+
+        // Small buffers are never snapshot, large ones always are.
+        policy.observe_write();
+        assert!(!policy.should_snapshot(MIN_SNAPSHOT_ROWS - 1, 0));
+        assert!(policy.should_snapshot(1, MAX_SNAPSHOT_BYTES));
+
+        // Initially only the age of the buffer triggers a snapshot.
+        assert!(!policy.should_snapshot(MIN_SNAPSHOT_ROWS, 0));
+        tokio::time::advance(TARGET_SNAPSHOT_INTERVAL).await;
+        assert!(policy.should_snapshot(MIN_SNAPSHOT_ROWS, 0));
+
+        // A write rate of 1000 rows/s adapts the threshold to one interval of
+        // writes.
+        let rows = 1000 * TARGET_SNAPSHOT_INTERVAL.as_secs() as usize;
+        policy.observe_snapshot(rows);
+        policy.observe_write();
+        assert!(!policy.should_snapshot(rows - 1, 0));
+        assert!(policy.should_snapshot(rows, 0));
+
+        // A slower rate lowers the threshold down to the minimum.
+        tokio::time::advance(TARGET_SNAPSHOT_INTERVAL * 100).await;
+        policy.observe_snapshot(rows);
+        policy.observe_write();
+        assert!(policy.should_snapshot(MIN_SNAPSHOT_ROWS, 0));
+    }
+}
@@ -484,4 +484,62 @@ mod tests {
             })
         );
     }
+
+    /// Assert a large, old buffer is snapshot on write, and the snapshots are
+    /// queried and persisted ahead of the buffered data.
+    #[tokio::test(start_paused = true)]
+    async fn test_buffer_auto_snapshot() {
+        let mut buffer: BufferState<Buffering> = BufferState::new();
+
+        let lp = (0..10_000)
+            .map(|i| format!("bananas,tag=platanos v={i}i {i}"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        buffer
+            .write(lp_to_mutable_batch(&lp).1, SequenceNumber::new(0))
+            .unwrap();
+        assert_eq!(buffer.get_query_data(&OwnedProjection::default()).len(), 1);
+
+        // Once the buffer is old enough, the next write snapshots it.
+        tokio::time::advance(std::time::Duration::from_secs(60)).await;
+        buffer
+            .write(
+                lp_to_mutable_batch("bananas,tag=platanos v=-1i 20000").1,
+                SequenceNumber::new(1),
+            )
+            .unwrap();
+        let data = buffer.get_query_data(&OwnedProjection::default());
+        assert_eq!(data.len(), 1);
+        assert_eq!(data[0].num_rows(), 10_001);
+
+        // Subsequent writes are buffered after the snapshot.
+        buffer
+            .write(
+                lp_to_mutable_batch("bananas,tag=platanos,new=bananas v=-2i 20001").1,
+                SequenceNumber::new(2),
+            )
+            .unwrap();
+
+        let data = buffer.get_query_data(&OwnedProjection::default());
+        assert_eq!(data.len(), 2);
+        assert_eq!(data[0].num_rows(), 10_001);
+        assert_eq!(data[1].num_rows(), 1);
+        assert_eq!(buffer.rows(), 10_002);
+        assert_eq!(
+            buffer.timestamp_stats(),
+            Some(TimestampMinMax { min: 0, max: 20001 })
+        );
+        assert!(buffer.schema().unwrap().find_index_of("new").is_some());
+
+        // All the data is persisted, in order.
+        let buffer = assert_matches!(buffer.snapshot(), Transition::Ok(v) => v);
+        let buffer = buffer.into_persisting();
+        let persisting = buffer.get_query_data(&OwnedProjection::default());
+        assert_eq!(
+            persisting.iter().map(|b| b.num_rows()).collect::<Vec<_>>(),
+            [10_001, 1]
+        );
+        assert_eq!(persisting[0], data[0]);
+        assert_eq!(buffer.into_sequence_number_set().len(), 3);
+    }
 }
@@ -3,12 +3,14 @@
 use arrow::record_batch::RecordBatch;
 use data_types::{StatValues, TimestampMinMax};
 use mutable_batch::{column::ColumnData, MutableBatch};
-use schema::{Projection, TIME_COLUMN_NAME};
+use schema::{merge::SchemaMerger, Projection, TIME_COLUMN_NAME};
 
 use super::{snapshot::Snapshot, BufferState, Transition};
 use crate::{
     buffer_tree::partition::buffer::{
         mutable_buffer::Buffer,
+        projection_cache::ProjectionCache,
+        snapshot_policy::SnapshotPolicy,
         traits::{Queryable, Writeable},
     },
     query::projection::OwnedProjection,
@@ -22,10 +24,48 @@ pub(crate) struct Buffering {
     /// This buffer MAY be empty when no writes have occured since transitioning
     /// to this state.
     buffer: Buffer,
+
+    /// The projections of `buffer` generated for queries since the last
+    /// write.
+    projections: ProjectionCache,
+
+    /// The snapshots of previous `buffer` contents, ordered before the data
+    /// in `buffer`, taken once it grew large according to the `policy`.
+    snapshots: Option<Snapshot>,
+    policy: SnapshotPolicy,
 }
 
-/// Implement on-demand querying of the buffered contents without storing the
-/// generated snapshot.
+impl Buffering {
+    /// Snapshot the contents of `buffer` into `snapshots` if the
+    /// [`SnapshotPolicy`] decides so.
+    fn maybe_snapshot(&mut self) {
+        let Some(rows) = self
+            .buffer
+            .buffer()
+            .filter(|b| self.policy.should_snapshot(b.rows(), b.size_data()))
+            .map(|b| b.rows())
+        else {
+            return;
+        };
+
+        let snap = std::mem::take(&mut self.buffer)
+            .snapshot()
+            .expect("snapshot of non-empty buffer should succeed");
+        self.projections.clear();
+
+        match &mut self.snapshots {
+            Some(s) => s.push(snap),
+            None => self.snapshots = Some(Snapshot::new(vec![snap])),
+        }
+        self.policy.observe_snapshot(rows);
+    }
+}
+
+/// Implement on-demand querying of the buffered contents.
+///
+/// The generated snapshot of each projection is cached until the next write,
+/// so that queries observing the same buffered data share it rather than each
+/// copying the buffered data.
 ///
 /// In the future this [`Queryable`] should NOT be implemented for
 /// [`Buffering`], and instead snapshots should be incrementally generated and
@@ -38,18 +78,31 @@ pub(crate) struct Buffering {
 /// [`RecordBatch`] fails (a non-transient error).
 impl Queryable for Buffering {
     fn get_query_data(&self, projection: &OwnedProjection) -> Vec<RecordBatch> {
-        self.buffer
-            .buffer()
-            .map(|v| vec![projection.project_mutable_batches(v)])
-            .unwrap_or_default()
+        let mut data = self
+            .snapshots
+            .as_ref()
+            .map(|s| s.get_query_data(projection))
+            .unwrap_or_default();
+
+        data.extend(self.buffer.buffer().map(|v| {
+            self.projections
+                .get_or_insert_with(projection, || projection.project_mutable_batches(v))
+        }));
+
+        data
     }
 
     fn rows(&self) -> usize {
-        self.buffer.buffer().map(|v| v.rows()).unwrap_or_default()
+        self.snapshots
+            .as_ref()
+            .map(|s| s.rows())
+            .unwrap_or_default()
+            + self.buffer.buffer().map(|v| v.rows()).unwrap_or_default()
     }
 
     fn timestamp_stats(&self) -> Option<TimestampMinMax> {
-        self.buffer
+        let buffered = self
+            .buffer
             .buffer()
             .map(extract_timestamp_summary)
             // Safety: unwrapping the timestamp bounds is safe, as any non-empty
@@ -57,20 +110,48 @@ impl Queryable for Buffering {
             .map(|v| TimestampMinMax {
                 min: v.min.unwrap(),
                 max: v.max.unwrap(),
-            })
+            });
+
+        match (
+            self.snapshots.as_ref().and_then(|s| s.timestamp_stats()),
+            buffered,
+        ) {
+            (Some(a), Some(b)) => Some(TimestampMinMax {
+                min: a.min.min(b.min),
+                max: a.max.max(b.max),
+            }),
+            (a, b) => a.or(b),
+        }
     }
 
     fn schema(&self) -> Option<schema::Schema> {
-        self.buffer.buffer().map(|v| {
+        let buffered = self.buffer.buffer().map(|v| {
             v.schema(Projection::All)
                 .expect("failed to construct batch schema")
-        })
+        });
+
+        match (self.snapshots.as_ref().and_then(|s| s.schema()), buffered) {
+            (Some(a), Some(b)) => Some(
+                SchemaMerger::new()
+                    .merge(&a)
+                    .and_then(|m| m.merge(&b))
+                    .expect("Schemas compatible")
+                    .build(),
+            ),
+            (a, b) => a.or(b),
+        }
     }
 }
 
 impl Writeable for Buffering {
     fn write(&mut self, batch: MutableBatch) -> Result<(), mutable_batch::Error> {
-        self.buffer.buffer_write(batch)
+        self.projections.clear();
+        self.buffer.buffer_write(batch)?;
+
+        self.policy.observe_write();
+        self.maybe_snapshot();
+
+        Ok(())
     }
 }
 
@@ -79,24 +160,38 @@ impl BufferState<Buffering> {
     ///
     /// This returns [`Transition::Unchanged`] if this buffer contains no data.
     pub(crate) fn snapshot(self) -> Transition<Snapshot, Buffering> {
-        if self.state.buffer.is_empty() {
+        if self.state.buffer.is_empty() && self.state.snapshots.is_none() {
             // It is a logical error to snapshot an empty buffer.
             return Transition::unchanged(self);
         }
 
-        // Generate a snapshot from the buffer.
-        let snap = self
-            .state
-            .buffer
-            .snapshot()
-            .expect("snapshot of non-empty buffer should succeed");
+        // Generate a snapshot from the buffer, if it contains data, ordered
+        // after any previous snapshots.
+        let Buffering {
+            buffer, snapshots, ..
+        } = self.state;
+        let snapshot = match (snapshots, buffer.snapshot()) {
+            (Some(mut s), Some(snap)) => {
+                s.push(snap);
+                s
+            }
+            (Some(s), None) => s,
+            (None, Some(snap)) => Snapshot::new(vec![snap]),
+            (None, None) => unreachable!("non-empty buffer must snapshot"),
+        };
 
         // And transition to the WithSnapshot state.
-        Transition::ok(Snapshot::new(vec![snap]), self.sequence_numbers)
+        Transition::ok(snapshot, self.sequence_numbers)
     }
 
     pub(crate) fn persist_cost_estimate(&self) -> usize {
         self.state.buffer.persist_cost_estimate()
+            + self
+                .state
+                .snapshots
+                .as_ref()
+                .map(|s| s.size())
+                .unwrap_or_default()
     }
 }
 
@@ -76,4 +76,13 @@ impl BufferState<Persisting> {
     pub(crate) fn into_sequence_number_set(self) -> SequenceNumberSet {
         self.sequence_numbers
     }
+
+    /// Return the approximate memory size of the persisting snapshots.
+    pub(crate) fn size(&self) -> usize {
+        self.state
+            .snapshots
+            .iter()
+            .map(|b| b.get_array_memory_size())
+            .sum()
+    }
 }
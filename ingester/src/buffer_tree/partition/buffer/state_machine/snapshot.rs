@@ -3,7 +3,10 @@
 use arrow::record_batch::RecordBatch;
 use data_types::TimestampMinMax;
 use iox_query::util::compute_timenanosecond_min_max;
-use schema::{merge::merge_record_batch_schemas, Schema};
+use schema::{
+    merge::{merge_record_batch_schemas, SchemaMerger},
+    Schema,
+};
 
 use super::BufferState;
 use crate::{
@@ -43,6 +46,38 @@ impl Snapshot {
             schema,
         }
     }
+
+    /// Append `batch` to the snapshots, updating the summary statistics.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `batch` contains no timestamps, or its schema is
+    /// incompatible with the existing snapshots.
+    pub(super) fn push(&mut self, batch: RecordBatch) {
+        let ts = compute_timenanosecond_min_max(std::iter::once(&batch))
+            .expect("non-empty batch must contain timestamps");
+        let batch_schema = Schema::try_from(batch.schema()).expect("Schema conversion error");
+
+        self.row_count += batch.num_rows();
+        self.timestamp_stats = TimestampMinMax {
+            min: self.timestamp_stats.min.min(ts.min),
+            max: self.timestamp_stats.max.max(ts.max),
+        };
+        self.schema = SchemaMerger::new()
+            .merge(&self.schema)
+            .and_then(|m| m.merge(&batch_schema))
+            .expect("Schemas compatible")
+            .build();
+        self.snapshots.push(batch);
+    }
+
+    /// Return the approximate memory size of the snapshots.
+    pub(super) fn size(&self) -> usize {
+        self.snapshots
+            .iter()
+            .map(|b| b.get_array_memory_size())
+            .sum()
+    }
 }
 
 impl Queryable for Snapshot {
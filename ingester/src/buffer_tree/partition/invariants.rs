@@ -0,0 +1,310 @@
+//! Runtime validation of the [`PartitionData`] buffer invariants.
+//!
+//! Most invariants of the partition buffer are asserted inline with
+//! `debug_assert!()` at the point they are relied upon, and are therefore only
+//! validated in debug builds, and only when that code path executes. The
+//! checks in this module inspect the complete state of a [`PartitionData`] at
+//! an arbitrary point in time, allowing long-running (release build) soak
+//! tests to detect a corrupted buffer before it causes data loss or incorrect
+//! query results.
+
+use data_types::{sequence_number_set::SequenceNumberSet, TimestampMinMax};
+use thiserror::Error;
+
+use super::{buffer::traits::Queryable, persisting::BatchIdent, PartitionData};
+
+/// Identifies a set of buffered data within a [`PartitionData`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum BufferLocation {
+    /// The mutable, "hot" buffer accepting writes.
+    Buffer,
+    /// An immutable batch of data that is being persisted.
+    Persisting(BatchIdent),
+}
+
+impl std::fmt::Display for BufferLocation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Buffer => write!(f, "buffer"),
+            Self::Persisting(ident) => write!(f, "persisting batch {ident}"),
+        }
+    }
+}
+
+/// A violated [`PartitionData`] invariant.
+#[derive(Debug, Error, PartialEq, Eq)]
+pub(crate) enum InvariantViolation {
+    /// The optimised empty flag disagrees with the buffered data.
+    #[error(
+        "empty flag is {is_empty} but partition has {persisting_batches} \
+        persisting batches and {buffered_rows} buffered rows"
+    )]
+    EmptyFlag {
+        is_empty: bool,
+        persisting_batches: usize,
+        buffered_rows: usize,
+    },
+
+    /// The partition contains data, but the per-namespace partition counter
+    /// does not account for it.
+    #[error("partition is non-empty but the namespace partition counter is 0")]
+    PartitionCounter,
+
+    /// A set of buffered data has no associated sequence numbers, preventing
+    /// the WAL segments it was written to from being released once persisted.
+    #[error("{location} contains {rows} rows but no sequence numbers")]
+    MissingSequenceNumbers {
+        location: BufferLocation,
+        rows: usize,
+    },
+
+    /// The same sequence number was applied to more than one buffer, causing
+    /// the write to be persisted (and queried) more than once.
+    #[error("{a} and {b} share {overlap} sequence numbers")]
+    OverlappingSequenceNumbers {
+        a: BufferLocation,
+        b: BufferLocation,
+        overlap: u64,
+    },
+
+    /// The persisting batches are not ordered by their [`BatchIdent`], breaking
+    /// the write ordering of query results.
+    #[error("persisting batch {next} is ordered after persisting batch {previous}")]
+    PersistingOrder {
+        previous: BatchIdent,
+        next: BatchIdent,
+    },
+
+    /// The number of persisting batches does not match the number of persist
+    /// operations started but not yet completed.
+    #[error(
+        "partition has {persisting_batches} persisting batches, but \
+        {started} persist operations started and {completed} completed"
+    )]
+    PersistCount {
+        persisting_batches: usize,
+        started: u64,
+        completed: u64,
+    },
+
+    /// The cached persisting row count does not match the batches.
+    #[error("cached persisting row count is {cached}, but batches contain {actual} rows")]
+    CachedRows { cached: usize, actual: usize },
+
+    /// The cached persisting timestamp range does not match the batches.
+    #[error("cached persisting timestamp range is {cached:?}, but batches span {actual:?}")]
+    CachedTimestamps {
+        cached: Option<TimestampMinMax>,
+        actual: Option<TimestampMinMax>,
+    },
+}
+
+impl PartitionData {
+    /// Validate the internal consistency of this [`PartitionData`], returning
+    /// all the violated invariants (if any).
+    ///
+    /// This is an `O(n^2)` operation where `n` is the number of currently
+    /// persisting batches, plus 1 for the "hot" buffer - `n` is typically
+    /// very small.
+    pub(crate) fn check_invariants(&self) -> Vec<InvariantViolation> {
+        let mut violations = Vec::new();
+
+        let persisting_batches = self.persisting.iter().count();
+        let buffered_rows = self.buffer.rows();
+
+        if self.is_empty != (persisting_batches == 0 && buffered_rows == 0) {
+            violations.push(InvariantViolation::EmptyFlag {
+                is_empty: self.is_empty,
+                persisting_batches,
+                buffered_rows,
+            });
+        }
+
+        if !self.is_empty && self.partition_counter.read() == 0 {
+            violations.push(InvariantViolation::PartitionCounter);
+        }
+
+        // Gather the sequence number sets of all the buffered data, ordered
+        // oldest to newest.
+        let sets = self
+            .persisting
+            .iter()
+            .map(|(ident, b)| {
+                (
+                    BufferLocation::Persisting(ident),
+                    b.rows(),
+                    b.sequence_number_set(),
+                )
+            })
+            .chain(std::iter::once((
+                BufferLocation::Buffer,
+                buffered_rows,
+                self.buffer.sequence_number_set(),
+            )))
+            .collect::<Vec<_>>();
+
+        for (location, rows, set) in &sets {
+            if *rows > 0 && set.is_empty() {
+                violations.push(InvariantViolation::MissingSequenceNumbers {
+                    location: *location,
+                    rows: *rows,
+                });
+            }
+        }
+
+        for (i, (a, _, a_set)) in sets.iter().enumerate() {
+            for (b, _, b_set) in &sets[i + 1..] {
+                let overlap = overlap(a_set, b_set);
+                if overlap > 0 {
+                    violations.push(InvariantViolation::OverlappingSequenceNumbers {
+                        a: *a,
+                        b: *b,
+                        overlap,
+                    });
+                }
+            }
+        }
+
+        let mut idents = self.persisting.iter().map(|(ident, _)| ident);
+        if let Some(mut previous) = idents.next() {
+            for next in idents {
+                if next <= previous {
+                    violations.push(InvariantViolation::PersistingOrder { previous, next });
+                }
+                previous = next;
+            }
+        }
+
+        let started = self.started_persistence_count.get();
+        let completed = self.completed_persistence_count;
+        if started.checked_sub(completed) != Some(persisting_batches as u64) {
+            violations.push(InvariantViolation::PersistCount {
+                persisting_batches,
+                started,
+                completed,
+            });
+        }
+
+        let actual = self.persisting.iter().map(|(_, b)| b.rows()).sum();
+        let cached = self.persisting.rows();
+        if cached != actual {
+            violations.push(InvariantViolation::CachedRows { cached, actual });
+        }
+
+        let actual = self
+            .persisting
+            .iter()
+            .filter_map(|(_, b)| b.timestamp_stats())
+            .reduce(|acc, v| TimestampMinMax {
+                min: acc.min.min(v.min),
+                max: acc.max.max(v.max),
+            });
+        let cached = self.persisting.timestamp_stats();
+        if cached != actual {
+            violations.push(InvariantViolation::CachedTimestamps { cached, actual });
+        }
+
+        violations
+    }
+}
+
+/// Return the number of sequence numbers present in both `a` and `b`.
+fn overlap(a: &SequenceNumberSet, b: &SequenceNumberSet) -> u64 {
+    data_types::sequence_number_set::intersect(a, b).len()
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_matches::assert_matches;
+    use data_types::SequenceNumber;
+    use mutable_batch_lp::test_helpers::lp_to_mutable_batch;
+
+    use super::*;
+    use crate::test_util::PartitionDataBuilder;
+
+    #[test]
+    fn test_consistent_partition() {
+        let mut p = PartitionDataBuilder::new().build();
+        assert_eq!(p.check_invariants(), []);
+
+        let mb = lp_to_mutable_batch(r#"bananas,city=London people=2,pigeons="millions" 10"#).1;
+        p.buffer_write(mb, SequenceNumber::new(1))
+            .expect("write should succeed");
+        assert_eq!(p.check_invariants(), []);
+
+        let persisting = p.mark_persisting().expect("must contain data");
+        assert_eq!(p.check_invariants(), []);
+
+        let mb = lp_to_mutable_batch(r#"bananas,city=Madrid people=4,pigeons="none" 20"#).1;
+        p.buffer_write(mb, SequenceNumber::new(2))
+            .expect("write should succeed");
+        let second = p.mark_persisting().expect("must contain data");
+        assert_eq!(p.check_invariants(), []);
+
+        // Complete persistence out of order.
+        let _ = p.mark_persisted(second);
+        assert_eq!(p.check_invariants(), []);
+        let _ = p.mark_persisted(persisting);
+        assert_eq!(p.check_invariants(), []);
+    }
+
+    #[test]
+    fn test_overlapping_sequence_numbers() {
+        let mut p = PartitionDataBuilder::new().build();
+
+        let mb = lp_to_mutable_batch(r#"bananas,city=London people=2,pigeons="millions" 10"#).1;
+        p.buffer_write(mb, SequenceNumber::new(1))
+            .expect("write should succeed");
+        let persisting = p.mark_persisting().expect("must contain data");
+
+        // Buffer a write with the same sequence number as the persisting batch.
+        let mb = lp_to_mutable_batch(r#"bananas,city=Madrid people=4,pigeons="none" 20"#).1;
+        p.buffer_write_unchecked(mb, SequenceNumber::new(1))
+            .expect("write should succeed");
+
+        assert_matches!(
+            p.check_invariants().as_slice(),
+            [InvariantViolation::OverlappingSequenceNumbers {
+                a: BufferLocation::Persisting(ident),
+                b: BufferLocation::Buffer,
+                overlap: 1,
+            }] => {
+                assert_eq!(*ident, persisting.batch_ident());
+            }
+        );
+    }
+
+    #[test]
+    fn test_empty_flag_and_persist_count() {
+        let mut p = PartitionDataBuilder::new().build();
+
+        let mb = lp_to_mutable_batch(r#"bananas,city=London people=2,pigeons="millions" 10"#).1;
+        p.buffer_write(mb, SequenceNumber::new(1))
+            .expect("write should succeed");
+
+        // Corrupt the partition state.
+        p.is_empty = true;
+        p.completed_persistence_count = 1;
+
+        let violations = p.check_invariants();
+        assert_eq!(
+            violations,
+            [
+                InvariantViolation::EmptyFlag {
+                    is_empty: true,
+                    persisting_batches: 0,
+                    buffered_rows: 1,
+                },
+                InvariantViolation::PersistCount {
+                    persisting_batches: 0,
+                    started: 0,
+                    completed: 1,
+                },
+            ]
+        );
+        assert_eq!(
+            violations[0].to_string(),
+            "empty flag is true but partition has 0 persisting batches and 1 buffered rows"
+        );
+    }
+}
@@ -1,5 +1,7 @@
 use std::fmt::Display;
 
+use uuid::Uuid;
+
 use crate::query_adaptor::QueryAdaptor;
 
 /// An opaque, monotonic generational identifier of a buffer in a
@@ -19,9 +21,9 @@ impl BatchIdent {
         Self(self.0)
     }
 
-    /// Only for tests, this allows reading the opaque identifier to assert the
-    /// value changing between persist ops.
-    #[cfg(test)]
+    /// Read the opaque identifier, allowing tests to assert the value changing
+    /// between persist ops, and the invariant checker to derive the number of
+    /// started persist operations.
     pub(super) fn get(&self) -> u64 {
         self.0
     }
@@ -45,17 +47,36 @@ impl Display for BatchIdent {
 pub struct PersistingData {
     data: QueryAdaptor,
     batch_ident: BatchIdent,
+
+    /// The object store ID of the parquet file this data is persisted to.
+    ///
+    /// This ID is reported to queriers while the data is persisting, allowing
+    /// them to identify the parquet file containing the same rows once it
+    /// becomes visible in the catalog.
+    object_store_id: Uuid,
 }
 
 impl PersistingData {
-    pub(super) fn new(data: QueryAdaptor, batch_ident: BatchIdent) -> Self {
-        Self { data, batch_ident }
+    pub(super) fn new(data: QueryAdaptor, batch_ident: BatchIdent, object_store_id: Uuid) -> Self {
+        Self {
+            data,
+            batch_ident,
+            object_store_id,
+        }
     }
 
     pub(super) fn batch_ident(&self) -> BatchIdent {
         self.batch_ident
     }
 
+    pub(crate) fn object_store_id(&self) -> Uuid {
+        self.object_store_id
+    }
+
+    pub(super) fn set_object_store_id(&mut self, object_store_id: Uuid) {
+        self.object_store_id = object_store_id;
+    }
+
     pub(crate) fn query_adaptor(&self) -> QueryAdaptor {
         self.data.clone()
     }
@@ -1,8 +1,9 @@
 use std::collections::VecDeque;
 
 use arrow::record_batch::RecordBatch;
-use data_types::TimestampMinMax;
+use data_types::{SequenceNumber, TimestampMinMax};
 use schema::{merge::SchemaMerger, Schema};
+use uuid::Uuid;
 
 use crate::query::projection::OwnedProjection;
 
@@ -25,10 +26,11 @@ pub(crate) struct PersistingList {
     /// forward iteration order matches write order.
     ///
     /// The [`BatchIdent`] is a generational counter that is used to tag each
-    /// persisting with a unique, opaque, monotonic identifier.
+    /// persisting with a unique, opaque, monotonic identifier, and the [`Uuid`]
+    /// is the object store ID of the parquet file the batch is persisted to.
     ///
     /// [`DataBuffer`]: super::buffer::DataBuffer
-    persisting: VecDeque<(BatchIdent, BufferState<Persisting>)>,
+    persisting: VecDeque<(BatchIdent, Uuid, BufferState<Persisting>)>,
 
     cached: Option<CachedStats>,
 }
@@ -43,8 +45,8 @@ impl Default for PersistingList {
 }
 
 impl PersistingList {
-    /// Add this `buffer` which was assigned `ident` when marked as persisting
-    /// to the list.
+    /// Add this `buffer` which was assigned `ident` and `object_store_id` when
+    /// marked as persisting to the list.
     ///
     /// This call incrementally recomputes the cached data statistics.
     ///
@@ -56,7 +58,12 @@ impl PersistingList {
     ///
     /// The provided buffer MUST be non-empty (containing a timestamp column,
     /// and a schema)
-    pub(crate) fn push(&mut self, ident: BatchIdent, buffer: BufferState<Persisting>) {
+    pub(crate) fn push(
+        &mut self,
+        ident: BatchIdent,
+        object_store_id: Uuid,
+        buffer: BufferState<Persisting>,
+    ) {
         // Recompute the statistics.
         match &mut self.cached {
             Some(v) => v.push(&buffer),
@@ -81,10 +88,10 @@ impl PersistingList {
         assert!(self
             .persisting
             .back()
-            .map(|(last, _)| ident > *last)
+            .map(|(last, _, _)| ident > *last)
             .unwrap_or(true));
 
-        self.persisting.push_back((ident, buffer));
+        self.persisting.push_back((ident, object_store_id, buffer));
     }
 
     /// Remove the buffer identified by `ident` from the list.
@@ -100,14 +107,14 @@ impl PersistingList {
         let idx = self
             .persisting
             .iter()
-            .position(|(old, _)| *old == ident)
+            .position(|(old, _, _)| *old == ident)
             .expect("no currently persisting batch");
 
-        let (old_ident, fsm) = self.persisting.remove(idx).unwrap();
+        let (old_ident, _, fsm) = self.persisting.remove(idx).unwrap();
         assert_eq!(old_ident, ident);
 
         // Recompute the cache of all remaining persisting batch stats (if any)
-        self.cached = CachedStats::new(self.persisting.iter().map(|(_, v)| v));
+        self.cached = CachedStats::new(self.persisting.iter().map(|(_, _, v)| v));
 
         fsm
     }
@@ -123,6 +130,13 @@ impl PersistingList {
         self.cached.as_ref().map(|v| v.rows).unwrap_or_default()
     }
 
+    /// Returns the approximate memory size of all batches in this list.
+    ///
+    /// This is an `O(n)` operation.
+    pub(crate) fn size(&self) -> usize {
+        self.persisting.iter().map(|(_, _, b)| b.size()).sum()
+    }
+
     /// Returns the timestamp min/max values across all batches in this list.
     ///
     /// This is an `O(1)` operation.
@@ -137,6 +151,50 @@ impl PersistingList {
         self.cached.as_ref().map(|v| &v.schema)
     }
 
+    /// Returns an iterator over the batches in this list, in the order they
+    /// were pushed, along with the [`BatchIdent`] assigned to each.
+    pub(crate) fn iter(&self) -> impl Iterator<Item = (BatchIdent, &BufferState<Persisting>)> + '_ {
+        self.persisting.iter().map(|(ident, _, b)| (*ident, b))
+    }
+
+    /// Replace the object store ID of the batch identified by `ident` with
+    /// `object_store_id`.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if there is currently no batch identified by `ident`
+    /// in the list.
+    pub(crate) fn set_object_store_id(&mut self, ident: BatchIdent, object_store_id: Uuid) {
+        let (_, id, _) = self
+            .persisting
+            .iter_mut()
+            .find(|(old, _, _)| *old == ident)
+            .expect("no currently persisting batch");
+
+        *id = object_store_id;
+    }
+
+    /// Returns the object store IDs of the parquet files the batches in this
+    /// list are being persisted to.
+    pub(crate) fn object_store_ids(&self) -> impl Iterator<Item = Uuid> + '_ {
+        self.persisting.iter().map(|(_, id, _)| *id)
+    }
+
+    /// Returns the highest [`SequenceNumber`] and object store ID of each
+    /// batch in this list, along with the batch, in the order they were
+    /// pushed.
+    pub(crate) fn snapshots(
+        &self,
+    ) -> impl Iterator<Item = (SequenceNumber, Uuid, &BufferState<Persisting>)> + '_ {
+        self.persisting.iter().map(|(_, id, b)| {
+            let max = b
+                .sequence_number_set()
+                .max()
+                .expect("persisting batch must contain writes");
+            (max, *id, b)
+        })
+    }
+
     /// Returns the [`RecordBatch`] in this list, optionally applying the given
     /// projection.
     ///
@@ -147,7 +205,7 @@ impl PersistingList {
     ) -> impl Iterator<Item = RecordBatch> + 'a {
         self.persisting
             .iter()
-            .flat_map(move |(_, b)| b.get_query_data(projection))
+            .flat_map(move |(_, _, b)| b.get_query_data(projection))
     }
 }
 
@@ -262,7 +320,7 @@ mod tests {
         let buffer = buffer_with_lp(r#"bananas,tag=platanos great="yes" 42"#);
 
         // Add it to the list.
-        list.push(ident_oracle.next(), buffer);
+        list.push(ident_oracle.next(), Uuid::new_v4(), buffer);
 
         // The statistics must now match the expected values.
         assert!(!list.is_empty());
@@ -289,7 +347,7 @@ mod tests {
 
         // Push a new buffer updating the last row to check yielded row ordering.
         let buffer = buffer_with_lp(r#"bananas,tag=platanos great="definitely" 42"#);
-        list.push(ident_oracle.next(), buffer);
+        list.push(ident_oracle.next(), Uuid::new_v4(), buffer);
 
         // The statistics must now match the expected values.
         assert!(!list.is_empty());
@@ -328,6 +386,7 @@ mod tests {
         // Populate the list.
         list.push(
             ident_oracle.next(),
+            Uuid::new_v4(),
             buffer_with_lp(
                 "\
                 bananas,tag=platanos v=1 42\n\
@@ -338,6 +397,7 @@ mod tests {
 
         list.push(
             ident_oracle.next(),
+            Uuid::new_v4(),
             buffer_with_lp(
                 "\
                 bananas,tag=platanos v=3 424242\n\
@@ -375,6 +435,7 @@ mod tests {
         let first_batch = ident_oracle.next();
         list.push(
             first_batch,
+            Uuid::new_v4(),
             buffer_with_lp(r#"bananas,tag=platanos great="yes" 42"#),
         );
 
@@ -391,6 +452,7 @@ mod tests {
         let second_batch = ident_oracle.next();
         list.push(
             second_batch,
+            Uuid::new_v4(),
             buffer_with_lp(r#"bananas,another=yes great="definitely",incremental=true 4242"#),
         );
 
@@ -0,0 +1,252 @@
+//! A size-bounded, in-memory cache of the most recently persisted parquet file
+//! of each partition.
+//!
+//! Once buffered data has been persisted it is released from the ingester, and
+//! queries for the most recent data must read it back from object storage.
+//! Retaining the (already columnar-compressed) parquet encoding of the most
+//! recently persisted data of each partition allows the ingester to continue
+//! serving it from memory, avoiding a latency cliff for queries over the most
+//! recent window of data.
+
+use std::{collections::VecDeque, sync::Arc};
+
+use arrow::record_batch::RecordBatch;
+use bytes::Bytes;
+use datafusion::parquet::{
+    arrow::arrow_reader::ParquetRecordBatchReaderBuilder, errors::ParquetError,
+};
+use parking_lot::Mutex;
+use uuid::Uuid;
+
+use super::persisting::BatchIdent;
+
+/// The parquet encoding of the data persisted by a single persist job.
+///
+/// The data is released when it is evicted from the [`RecentPersistCache`],
+/// after which [`RecentFile::record_batches()`] returns [`None`].
+#[derive(Debug)]
+pub(crate) struct RecentFile {
+    object_store_id: Uuid,
+    batch_ident: BatchIdent,
+    data: Mutex<Option<Bytes>>,
+}
+
+impl RecentFile {
+    /// The object store ID of the parquet file this data was persisted to.
+    pub(crate) fn object_store_id(&self) -> Uuid {
+        self.object_store_id
+    }
+
+    /// The identifier of the persisting batch this file was generated from.
+    pub(crate) fn batch_ident(&self) -> BatchIdent {
+        self.batch_ident
+    }
+
+    /// Returns true if the data has not been evicted.
+    pub(crate) fn is_resident(&self) -> bool {
+        self.data.lock().is_some()
+    }
+
+    /// Decode the parquet data into [`RecordBatch`], or return [`None`] if the
+    /// data has been evicted.
+    pub(crate) fn record_batches(&self) -> Option<Result<Vec<RecordBatch>, ParquetError>> {
+        // Clone the (ref-counted) data to avoid holding the lock while
+        // decoding.
+        let data = self.data.lock().clone()?;
+
+        Some(
+            ParquetRecordBatchReaderBuilder::try_new(data).and_then(|builder| {
+                builder
+                    .build()?
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(ParquetError::from)
+            }),
+        )
+    }
+
+    /// Release the data, returning the number of bytes freed.
+    fn evict(&self) -> usize {
+        self.data.lock().take().map(|v| v.len()).unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Default)]
+struct State {
+    /// The number of bytes of data held by the files in `files`.
+    used_bytes: usize,
+
+    /// The resident files, in insertion order.
+    files: VecDeque<Arc<RecentFile>>,
+}
+
+/// A cache of [`RecentFile`] shared by all partitions, bounding the total
+/// number of bytes retained to `max_bytes`.
+///
+/// When the limit is exceeded, the least recently inserted files are evicted
+/// first.
+#[derive(Debug)]
+pub(crate) struct RecentPersistCache {
+    max_bytes: usize,
+    state: Mutex<State>,
+}
+
+impl RecentPersistCache {
+    /// Construct a cache retaining at most `max_bytes` of parquet data.
+    pub(crate) fn new(max_bytes: usize) -> Self {
+        Self {
+            max_bytes,
+            state: Mutex::default(),
+        }
+    }
+
+    /// Retain `data`, the parquet encoding of the persisting batch identified
+    /// by `batch_ident` that was uploaded as `object_store_id`, evicting older
+    /// files as necessary.
+    ///
+    /// If specified, the `replaces` file is evicted. Files larger than the
+    /// total capacity of the cache are not retained, and [`None`] is returned.
+    pub(crate) fn insert(
+        &self,
+        object_store_id: Uuid,
+        batch_ident: BatchIdent,
+        data: Bytes,
+        replaces: Option<Arc<RecentFile>>,
+    ) -> Option<Arc<RecentFile>> {
+        let mut state = self.state.lock();
+
+        if let Some(old) = replaces {
+            state.used_bytes -= old.evict();
+            state.files.retain(|v| !Arc::ptr_eq(v, &old));
+        }
+
+        if data.len() > self.max_bytes {
+            return None;
+        }
+
+        state.used_bytes += data.len();
+        let file = Arc::new(RecentFile {
+            object_store_id,
+            batch_ident,
+            data: Mutex::new(Some(data)),
+        });
+        state.files.push_back(Arc::clone(&file));
+
+        while state.used_bytes > self.max_bytes {
+            let evicted = state
+                .files
+                .pop_front()
+                .expect("cache files must account for used bytes");
+            state.used_bytes -= evicted.evict();
+        }
+
+        Some(file)
+    }
+
+    /// Return the number of bytes of parquet data currently retained.
+    pub(crate) fn used_bytes(&self) -> usize {
+        self.state.lock().used_bytes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow::array::Int64Array;
+    use datafusion::parquet::arrow::ArrowWriter;
+
+    use super::*;
+
+    fn parquet_bytes(values: &[i64]) -> (RecordBatch, Bytes) {
+        let batch =
+            RecordBatch::try_from_iter([("v", Arc::new(Int64Array::from(values.to_vec())) as _)])
+                .unwrap();
+
+        let mut buf = Vec::new();
+        let mut w = ArrowWriter::try_new(&mut buf, batch.schema(), None).unwrap();
+        w.write(&batch).unwrap();
+        w.close().unwrap();
+
+        (batch, Bytes::from(buf))
+    }
+
+    fn ident(v: u64) -> BatchIdent {
+        let mut ident = BatchIdent::default();
+        for _ in 0..v {
+            ident.next();
+        }
+        ident
+    }
+
+    #[test]
+    fn test_decode() {
+        let (batch, data) = parquet_bytes(&[1, 2, 3]);
+        let cache = RecentPersistCache::new(data.len());
+
+        let file = cache
+            .insert(Uuid::new_v4(), ident(1), data.clone(), None)
+            .expect("file fits in cache");
+
+        assert!(file.is_resident());
+        assert_eq!(file.batch_ident(), ident(1));
+        assert_eq!(cache.used_bytes(), data.len());
+
+        let got = file.record_batches().unwrap().unwrap();
+        assert_eq!(got, [batch]);
+    }
+
+    #[test]
+    fn test_eviction() {
+        let (_, data) = parquet_bytes(&[1, 2, 3]);
+        let cache = RecentPersistCache::new(data.len() * 2);
+
+        let a = cache
+            .insert(Uuid::new_v4(), ident(1), data.clone(), None)
+            .unwrap();
+        let b = cache
+            .insert(Uuid::new_v4(), ident(1), data.clone(), None)
+            .unwrap();
+        assert!(a.is_resident());
+        assert!(b.is_resident());
+
+        // Exceeding the capacity evicts the oldest file.
+        let c = cache
+            .insert(Uuid::new_v4(), ident(1), data.clone(), None)
+            .unwrap();
+        assert!(!a.is_resident());
+        assert!(a.record_batches().is_none());
+        assert!(b.is_resident());
+        assert!(c.is_resident());
+        assert_eq!(cache.used_bytes(), data.len() * 2);
+    }
+
+    #[test]
+    fn test_replace() {
+        let (_, data) = parquet_bytes(&[1, 2, 3]);
+        let cache = RecentPersistCache::new(data.len() * 2);
+
+        let a = cache
+            .insert(Uuid::new_v4(), ident(1), data.clone(), None)
+            .unwrap();
+        let other = cache
+            .insert(Uuid::new_v4(), ident(1), data.clone(), None)
+            .unwrap();
+
+        // Replacing a file releases it, making room for the new file without
+        // evicting the other partition's file.
+        let b = cache
+            .insert(Uuid::new_v4(), ident(2), data.clone(), Some(Arc::clone(&a)))
+            .unwrap();
+        assert!(!a.is_resident());
+        assert!(other.is_resident());
+        assert!(b.is_resident());
+        assert_eq!(cache.used_bytes(), data.len() * 2);
+    }
+
+    #[test]
+    fn test_too_large() {
+        let (_, data) = parquet_bytes(&[1, 2, 3]);
+        let cache = RecentPersistCache::new(data.len() - 1);
+
+        assert!(cache.insert(Uuid::new_v4(), ident(1), data, None).is_none());
+        assert_eq!(cache.used_bytes(), 0);
+    }
+}
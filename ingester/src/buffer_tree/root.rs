@@ -1,7 +1,7 @@
 use std::{fmt::Debug, num::NonZeroUsize, sync::Arc};
 
 use async_trait::async_trait;
-use data_types::{NamespaceId, TableId};
+use data_types::{NamespaceId, SequenceNumber, TableId};
 use metric::U64Counter;
 use parking_lot::Mutex;
 use predicate::Predicate;
@@ -20,8 +20,8 @@ use crate::{
     dml_sink::DmlSink,
     partition_iter::PartitionIter,
     query::{
-        projection::OwnedProjection, response::QueryResponse, tracing::QueryExecTracing,
-        QueryError, QueryExec,
+        projection::OwnedProjection, response::QueryResponse, snapshot_page::SnapshotPage,
+        tracing::QueryExecTracing, QueryError, QueryExec,
     },
 };
 
@@ -31,6 +31,9 @@ pub enum BufferWriteError {
     #[error("namespace reached buffered partition limit ({count} partitions at once)")]
     PartitionLimit { count: usize },
 
+    #[error("sequence number {} has already been buffered", sequence_number.get())]
+    DuplicateSequenceNumber { sequence_number: SequenceNumber },
+
     #[error(transparent)]
     Write(#[from] mutable_batch::Error),
 }
@@ -160,6 +163,11 @@ where
         self.namespaces.get(&namespace_id)
     }
 
+    /// Obtain a snapshot of the namespaces in the tree.
+    pub(crate) fn namespaces(&self) -> Vec<Arc<NamespaceData<O>>> {
+        self.namespaces.values()
+    }
+
     /// Iterate over a snapshot of [`PartitionData`] in the tree.
     ///
     /// This iterator will iterate over a consistent snapshot of namespaces
@@ -224,6 +232,7 @@ where
         projection: OwnedProjection,
         span: Option<Span>,
         predicate: Option<Predicate>,
+        page: SnapshotPage,
     ) -> Result<Self::Response, QueryError> {
         // Extract the namespace if it exists.
         let inner = self
@@ -233,7 +242,7 @@ where
         // Delegate query execution to the namespace, wrapping the execution in
         // a tracing delegate to emit a child span.
         QueryExecTracing::new(inner, "namespace")
-            .query_exec(namespace_id, table_id, projection, span, predicate)
+            .query_exec(namespace_id, table_id, projection, span, predicate, page)
             .await
     }
 }
@@ -249,9 +258,9 @@ where
 
 #[cfg(test)]
 mod tests {
-    use std::{sync::Arc, time::Duration};
+    use std::{collections::HashMap, sync::Arc, time::Duration};
 
-    use arrow::datatypes::DataType;
+    use arrow::datatypes::{DataType, Float64Type};
     use assert_matches::assert_matches;
     use data_types::{
         partition_template::{test_table_partition_override, TemplatePart},
@@ -442,7 +451,8 @@ mod tests {
                             ARBITRARY_TABLE_ID,
                             projection,
                             None,
-                            $predicate
+                            $predicate,
+                            SnapshotPage::default(),
                         )
                         .await
                         .expect("query should succeed")
@@ -1011,6 +1021,7 @@ mod tests {
                 OwnedProjection::default(),
                 None,
                 predicate,
+                SnapshotPage::default(),
             )
             .await
             .expect("query should succeed")
@@ -1234,6 +1245,7 @@ mod tests {
                 OwnedProjection::default(),
                 None,
                 None,
+                SnapshotPage::default(),
             )
             .await
             .expect_err("query should fail");
@@ -1265,6 +1277,7 @@ mod tests {
                 OwnedProjection::default(),
                 None,
                 None,
+                SnapshotPage::default(),
             )
             .await
             .expect_err("query should fail");
@@ -1280,6 +1293,7 @@ mod tests {
             OwnedProjection::default(),
             None,
             None,
+            SnapshotPage::default(),
         )
         .await
         .expect("namespace / table should exist");
@@ -1349,6 +1363,7 @@ mod tests {
                 OwnedProjection::default(),
                 None,
                 None,
+                SnapshotPage::default(),
             )
             .await
             .expect("query should succeed")
@@ -1461,6 +1476,7 @@ mod tests {
                 OwnedProjection::default(),
                 None,
                 None,
+                SnapshotPage::default(),
             )
             .await
             .expect("query should succeed")
@@ -1475,4 +1491,151 @@ mod tests {
             &TransitionPartitionId::Deprecated(ARBITRARY_CATALOG_PARTITION_ID),
         );
     }
+
+    /// Ensure a query truncated by its page limit resumes each partition from
+    /// its own sequence number, only returns the truncated partitions, and
+    /// never returns data twice when snapshots are persisted between pages.
+    #[tokio::test]
+    async fn test_query_page_resume() {
+        let partition_provider = Arc::new(
+            MockPartitionProvider::default()
+                .with_partition(
+                    PartitionDataBuilder::new().with_partition_key(ARBITRARY_PARTITION_KEY.clone()),
+                )
+                .with_partition(
+                    PartitionDataBuilder::new().with_partition_key(PARTITION2_KEY.clone()),
+                ),
+        );
+
+        let buf = BufferTree::new(
+            Arc::new(MockNamespaceNameProvider::new(&**ARBITRARY_NAMESPACE_NAME)),
+            Arc::clone(&*ARBITRARY_TABLE_PROVIDER),
+            partition_provider,
+            NonZeroUsize::new(usize::MAX).unwrap(),
+            Arc::new(MockPostWriteObserver::default()),
+            Arc::new(metric::Registry::default()),
+        );
+
+        let p1 = ARBITRARY_TRANSITION_PARTITION_ID.clone();
+        let p2 = ARBITRARY_TABLE_PARTITION2.clone();
+        let partition = |id: &TransitionPartitionId| {
+            buf.partitions()
+                .find(|p| p.lock().partition_id() == id)
+                .expect("partition must exist")
+        };
+
+        // Write a row to the partition with `key`, with a temp equal to its
+        // sequence number.
+        let write = |key: &PartitionKey, sequence_number: u64| {
+            buf.apply(IngestOp::Write(make_write_op(
+                key,
+                ARBITRARY_NAMESPACE_ID,
+                &ARBITRARY_TABLE_NAME,
+                ARBITRARY_TABLE_ID,
+                sequence_number,
+                &format!(
+                    r#"{},region=Madrid temp={sequence_number} 4242424242"#,
+                    &*ARBITRARY_TABLE_NAME
+                ),
+                None,
+            )))
+        };
+
+        // Partition 1 has three persisting snapshots and buffered data,
+        // partition 2 one persisting snapshot and buffered data.
+        write(&ARBITRARY_PARTITION_KEY, 1).await.unwrap();
+        partition(&p1).lock().mark_persisting().unwrap();
+        write(&ARBITRARY_PARTITION_KEY, 2).await.unwrap();
+        partition(&p1).lock().mark_persisting().unwrap();
+        write(&ARBITRARY_PARTITION_KEY, 3).await.unwrap();
+        let p1_persisting = partition(&p1).lock().mark_persisting().unwrap();
+        write(&PARTITION2_KEY, 4).await.unwrap();
+        let p2_persisting = partition(&p2).lock().mark_persisting().unwrap();
+        write(&PARTITION2_KEY, 5).await.unwrap();
+        write(&ARBITRARY_PARTITION_KEY, 6).await.unwrap();
+
+        // Query a page of one snapshot per partition, returning the temps,
+        // persistence count and resume point of each partition.
+        let query = |resume_after: HashMap<TransitionPartitionId, SequenceNumber>| {
+            let page = SnapshotPage::new(resume_after, NonZeroUsize::new(1));
+            let buf = &buf;
+            async move {
+                let stream = buf
+                    .query_exec(
+                        ARBITRARY_NAMESPACE_ID,
+                        ARBITRARY_TABLE_ID,
+                        OwnedProjection::default(),
+                        None,
+                        None,
+                        page,
+                    )
+                    .await
+                    .expect("query should succeed")
+                    .into_partition_stream();
+
+                let partitions: Vec<PartitionResponse> = stream.collect().await;
+                partitions
+                    .into_iter()
+                    .map(|p| {
+                        let id = p.id().clone();
+                        let count = p.completed_persistence_count();
+                        let resume = p.resume_after_sequence_number();
+                        let temps = p
+                            .into_record_batches()
+                            .iter()
+                            .flat_map(|b| {
+                                let col = b.column_by_name("temp").unwrap();
+                                arrow::array::as_primitive_array::<Float64Type>(col)
+                                    .values()
+                                    .to_vec()
+                            })
+                            .collect::<Vec<_>>();
+                        (id, (temps, count, resume))
+                    })
+                    .collect::<HashMap<_, _>>()
+            }
+        };
+
+        // The first page returns the oldest snapshot of each partition.
+        let page = query(HashMap::new()).await;
+        assert_eq!(
+            page,
+            [
+                (p1.clone(), (vec![1.0], 0, Some(SequenceNumber::new(1)))),
+                (p2.clone(), (vec![4.0], 0, Some(SequenceNumber::new(4)))),
+            ]
+            .into()
+        );
+
+        // Persist a returned snapshot of partition 2, and a snapshot of
+        // partition 1 not returned yet.
+        partition(&p1).lock().mark_persisted(p1_persisting);
+        partition(&p2).lock().mark_persisted(p2_persisting);
+
+        // Each partition resumes from its own sequence number. Partition 1
+        // does not skip the snapshot of sequence number 2, and the persisted
+        // snapshot of sequence number 3 is only available from its file, as
+        // indicated by the persistence count. The persisted data of
+        // partition 2 is not returned again.
+        let page = query(
+            [
+                (p1.clone(), SequenceNumber::new(1)),
+                (p2.clone(), SequenceNumber::new(4)),
+            ]
+            .into(),
+        )
+        .await;
+        assert_eq!(
+            page,
+            [
+                (p1.clone(), (vec![2.0], 1, Some(SequenceNumber::new(2)))),
+                (p2.clone(), (vec![5.0], 1, None)),
+            ]
+            .into()
+        );
+
+        // Only the truncated partition is returned by the last page.
+        let page = query([(p1.clone(), SequenceNumber::new(2))].into()).await;
+        assert_eq!(page, [(p1, (vec![6.0], 1, None))].into());
+    }
 }
@@ -8,7 +8,7 @@ use std::{collections::HashMap, fmt::Debug, sync::Arc};
 use async_trait::async_trait;
 use data_types::{
     partition_template::{build_column_values, ColumnValue, TablePartitionTemplateOverride},
-    NamespaceId, PartitionKey, SequenceNumber, TableId,
+    NamespaceId, PartitionKey, SequenceNumber, TableId, TransitionPartitionId,
 };
 use datafusion::{prelude::Expr, scalar::ScalarValue};
 use iox_query::{
@@ -17,15 +17,19 @@ use iox_query::{
     QueryChunk,
 };
 use mutable_batch::MutableBatch;
+use observability_deps::tracing::warn;
 use parking_lot::Mutex;
 use predicate::Predicate;
 use trace::span::{Span, SpanRecorder};
+use uuid::Uuid;
 
 use self::metadata::TableMetadata;
 
 use super::{
     namespace::NamespaceName,
-    partition::{counter::PartitionCounter, resolver::PartitionProvider, PartitionData},
+    partition::{
+        counter::PartitionCounter, recent::RecentFile, resolver::PartitionProvider, PartitionData,
+    },
     post_write::PostWriteObserver,
     BufferWriteError,
 };
@@ -34,7 +38,7 @@ use crate::{
     deferred_load::DeferredLoad,
     query::{
         partition_response::PartitionResponse, projection::OwnedProjection,
-        response::PartitionStream, QueryError, QueryExec,
+        response::PartitionStream, snapshot_page::SnapshotPage, QueryError, QueryExec,
     },
     query_adaptor::QueryAdaptor,
 };
@@ -198,6 +202,7 @@ where
         projection: OwnedProjection,
         span: Option<Span>,
         predicate: Option<Predicate>,
+        page: SnapshotPage,
     ) -> Result<Self::Response, QueryError> {
         assert_eq!(self.table_id, table_id, "buffer tree index inconsistency");
         assert_eq!(
@@ -215,16 +220,43 @@ where
         let partitions = self.partitions().into_iter().filter_map(move |p| {
             let mut span = span.child("partition read");
 
-            let (id, completed_persistence_count, data, partition_key) = {
+            let (
+                id,
+                page,
+                completed_persistence_count,
+                (data, persisting_ids, resume),
+                recent,
+                partition_key,
+            ) = {
                 let mut p = p.lock();
+                let id = p.partition_id().clone();
+
+                // Skip the partitions not resumed by a continuation page.
+                let page = page.partition(&id)?;
+
                 (
-                    p.partition_id().clone(),
+                    id,
+                    page,
                     p.completed_persistence_count(),
-                    p.get_query_data(&projection),
+                    // Read the persisting IDs under the same lock as the data
+                    // to ensure they accurately describe the persisting data
+                    // returned.
+                    p.get_query_data_page(&projection, page),
+                    p.recent_persisted(),
                     p.partition_key().clone(),
                 )
             };
 
+            // Decode the recently persisted data (if any) outside of the
+            // partition lock. It is older than all the snapshots, and so only
+            // returned in the first page of a query - and never in a
+            // metadata-only response.
+            let (data, persisting_ids) = if page.is_continuation() || page.is_metadata_only() {
+                (data, persisting_ids)
+            } else {
+                merge_recent_persisted(&id, recent, data, persisting_ids, &projection)
+            };
+
             let ret = match data {
                 Some(data) => {
                     assert_eq!(&id, data.partition_id());
@@ -261,6 +293,8 @@ where
                         id,
                         completed_persistence_count,
                     )
+                    .with_persisting_object_store_ids(persisting_ids)
+                    .with_resume_after_sequence_number(resume)
                 }
                 None => PartitionResponse::new(vec![], id, completed_persistence_count),
             };
@@ -273,6 +307,52 @@ where
     }
 }
 
+/// Prepend the retained data of the most recently persisted file in `recent`
+/// (if any) to the buffered and persisting `data` of the partition identified
+/// by `id`, applying `projection`.
+///
+/// The recently persisted data is older than all buffered and persisting data,
+/// and is ordered before it. Its object store ID is added to the returned
+/// `persisting_ids` so the querier does not also read the file from object
+/// storage.
+fn merge_recent_persisted(
+    id: &TransitionPartitionId,
+    recent: Option<Arc<RecentFile>>,
+    data: Option<QueryAdaptor>,
+    mut persisting_ids: Vec<Uuid>,
+    projection: &OwnedProjection,
+) -> (Option<QueryAdaptor>, Vec<Uuid>) {
+    let Some(file) = recent else {
+        return (data, persisting_ids);
+    };
+
+    let mut batches = match file.record_batches() {
+        Some(Ok(v)) => projection.project_record_batch(&v),
+        Some(Err(error)) => {
+            warn!(
+                %error,
+                partition_id = %id,
+                object_store_id = %file.object_store_id(),
+                "failed to decode recently persisted data"
+            );
+            return (data, persisting_ids);
+        }
+        // Evicted since it was read from the partition.
+        None => return (data, persisting_ids),
+    };
+
+    batches.extend(
+        data.map(QueryAdaptor::into_record_batches)
+            .unwrap_or_default(),
+    );
+    if !batches.iter().any(|b| b.num_rows() > 0) {
+        return (None, persisting_ids);
+    }
+
+    persisting_ids.push(file.object_store_id());
+    (Some(QueryAdaptor::new(id.clone(), batches)), persisting_ids)
+}
+
 /// Return true if `data` contains one or more rows matching `predicate`,
 /// pruning based on the `partition_key` and `template`.
 ///
@@ -382,18 +462,24 @@ mod tests {
     use std::{num::NonZeroUsize, sync::Arc};
 
     use assert_matches::assert_matches;
-    use mutable_batch_lp::lines_to_batches;
+    use bytes::Bytes;
+    use datafusion::parquet::arrow::ArrowWriter;
+    use mutable_batch_lp::{lines_to_batches, test_helpers::lp_to_mutable_batch};
+    use schema::Projection;
 
     use super::*;
     use crate::{
         buffer_tree::{
-            partition::resolver::mock::MockPartitionProvider,
+            partition::{
+                persisting::BatchIdent, recent::RecentPersistCache,
+                resolver::mock::MockPartitionProvider,
+            },
             post_write::mock::MockPostWriteObserver,
         },
         test_util::{
             defer_namespace_name_1_sec, defer_table_metadata_1_sec, PartitionDataBuilder,
             ARBITRARY_NAMESPACE_ID, ARBITRARY_PARTITION_KEY, ARBITRARY_TABLE_ID,
-            ARBITRARY_TABLE_NAME,
+            ARBITRARY_TABLE_NAME, ARBITRARY_TRANSITION_PARTITION_ID,
         },
     };
 
@@ -493,4 +579,66 @@ mod tests {
         // The partition counter should be unchanged
         assert_eq!(partition_counter.read(), N);
     }
+
+    // Ensure the retained data of the most recently persisted file is ordered
+    // before the buffered data, and its object store ID reported.
+    #[test]
+    fn test_merge_recent_persisted() {
+        let persisted = lp_to_mutable_batch(r#"bananas,city=London people=2 10"#)
+            .1
+            .to_arrow(Projection::All)
+            .unwrap();
+        let buffered = lp_to_mutable_batch(r#"bananas,city=Madrid people=4 20"#)
+            .1
+            .to_arrow(Projection::All)
+            .unwrap();
+
+        let mut buf = Vec::new();
+        let mut w = ArrowWriter::try_new(&mut buf, persisted.schema(), None).unwrap();
+        w.write(&persisted).unwrap();
+        w.close().unwrap();
+
+        let cache = RecentPersistCache::new(usize::MAX);
+        let object_store_id = Uuid::new_v4();
+        let file = cache
+            .insert(
+                object_store_id,
+                BatchIdent::default(),
+                Bytes::from(buf),
+                None,
+            )
+            .unwrap();
+
+        let persisting_id = Uuid::new_v4();
+        let (data, ids) = merge_recent_persisted(
+            &ARBITRARY_TRANSITION_PARTITION_ID,
+            Some(Arc::clone(&file)),
+            Some(QueryAdaptor::new(
+                ARBITRARY_TRANSITION_PARTITION_ID.clone(),
+                vec![buffered.clone()],
+            )),
+            vec![persisting_id],
+            &OwnedProjection::default(),
+        );
+
+        assert_eq!(ids, [persisting_id, object_store_id]);
+        assert_eq!(
+            data.expect("must contain data").into_record_batches(),
+            [persisted.clone(), buffered]
+        );
+
+        // The retained data is returned even if the partition is empty.
+        let (data, ids) = merge_recent_persisted(
+            &ARBITRARY_TRANSITION_PARTITION_ID,
+            Some(file),
+            None,
+            vec![],
+            &OwnedProjection::default(),
+        );
+        assert_eq!(ids, [object_store_id]);
+        assert_eq!(
+            data.expect("must contain data").into_record_batches(),
+            [persisted]
+        );
+    }
 }
@@ -1,8 +1,9 @@
-use std::{sync::Arc, time::Duration};
+use std::{collections::HashMap, sync::Arc, time::Duration};
 
 use backoff::{Backoff, BackoffConfig};
 use data_types::TableId;
 use iox_catalog::interface::Catalog;
+use parking_lot::Mutex;
 
 use crate::deferred_load::DeferredLoad;
 
@@ -20,6 +21,10 @@ pub(crate) struct TableResolver {
     catalog: Arc<dyn Catalog>,
     backoff_config: BackoffConfig,
     metrics: Arc<metric::Registry>,
+
+    /// Tables loaded from the catalog at startup, each consumed by the first
+    /// [`DeferredLoad`] for its table instead of querying the catalog.
+    warm: Mutex<HashMap<TableId, TableMetadata>>,
 }
 
 impl TableResolver {
@@ -34,9 +39,16 @@ impl TableResolver {
             catalog,
             backoff_config,
             metrics,
+            warm: Default::default(),
         }
     }
 
+    /// Resolve the tables in `tables` without querying the catalog.
+    pub(crate) fn with_warm_tables(self, tables: HashMap<TableId, TableMetadata>) -> Self {
+        *self.warm.lock() = tables;
+        self
+    }
+
     /// Fetch the [`TableMetadata`] from the [`Catalog`] for specified
     /// `table_id`, retrying endlessly when errors occur.
     pub(crate) async fn fetch(
@@ -66,6 +78,10 @@ impl TableResolver {
 
 impl TableProvider for TableResolver {
     fn for_table(&self, id: TableId) -> DeferredLoad<TableMetadata> {
+        if let Some(table) = self.warm.lock().remove(&id) {
+            return DeferredLoad::new(self.max_smear, async { table }, &self.metrics);
+        }
+
         DeferredLoad::new(
             self.max_smear,
             Self::fetch(id, Arc::clone(&self.catalog), self.backoff_config.clone()),
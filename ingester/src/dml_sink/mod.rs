@@ -4,6 +4,7 @@ mod r#trait;
 pub use r#trait::*;
 
 pub(crate) mod instrumentation;
+pub(crate) mod namespace_quota;
 pub(crate) mod tracing;
 
 #[cfg(test)]
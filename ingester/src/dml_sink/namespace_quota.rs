@@ -0,0 +1,432 @@
+//! Per-namespace limits on the data buffered by, and the rate of writes
+//! accepted by, this ingester.
+
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    fmt::{Debug, Display},
+    num::{NonZeroU64, NonZeroUsize},
+    sync::Arc,
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use data_types::NamespaceId;
+use iox_time::{Time, TimeProvider};
+use metric::{Metric, U64Counter, U64Gauge};
+use parking_lot::Mutex;
+
+use super::{DmlError, DmlSink};
+use crate::{
+    buffer_tree::{post_write::PostWriteObserver, BufferTree},
+    dml_payload::IngestOp,
+};
+
+/// The period for which the number of bytes buffered for a namespace, as read
+/// from the buffer, is reused before being read again.
+///
+/// Between reads, the bytes of the writes applied since the last read are
+/// added to it.
+const BUFFERED_BYTES_REFRESH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// The length of the window over which the rows written to a namespace are
+/// counted.
+const RATE_WINDOW: Duration = Duration::from_secs(1);
+
+/// A per-namespace ingest quota.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NamespaceQuota {
+    /// The number of bytes buffered (and not yet persisting) for the
+    /// namespace.
+    BufferedBytes,
+
+    /// The number of rows written to the namespace per second.
+    RowsPerSecond,
+}
+
+impl NamespaceQuota {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::BufferedBytes => "buffered_bytes",
+            Self::RowsPerSecond => "rows_per_second",
+        }
+    }
+}
+
+impl Display for NamespaceQuota {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A source of the number of bytes buffered for a namespace.
+pub(crate) trait NamespaceBufferedBytes: Debug + Send + Sync {
+    /// Return the number of bytes buffered for `namespace_id`, excluding the
+    /// data currently being persisted.
+    fn buffered_bytes(&self, namespace_id: NamespaceId) -> usize;
+}
+
+impl<O> NamespaceBufferedBytes for BufferTree<O>
+where
+    O: PostWriteObserver,
+{
+    fn buffered_bytes(&self, namespace_id: NamespaceId) -> usize {
+        self.namespace(namespace_id)
+            .map(|ns| {
+                ns.partitions()
+                    .map(|p| p.lock().persist_cost_estimate())
+                    .sum()
+            })
+            .unwrap_or_default()
+    }
+}
+
+/// The usage of a single namespace.
+#[derive(Debug, Default)]
+struct NamespaceUsage {
+    /// The bytes buffered for the namespace when last read at
+    /// `buffered_bytes_read_at`, plus the bytes of the writes applied since.
+    buffered_bytes: usize,
+    buffered_bytes_read_at: Option<Time>,
+
+    /// The start of the current rate window, and the number of rows accepted
+    /// within it.
+    window_start: Option<Time>,
+    window_rows: u64,
+}
+
+/// A [`DmlSink`] decorator rejecting writes to namespaces that exceed their
+/// ingest quotas with [`DmlError::NamespaceQuotaExceeded`], so that a single
+/// namespace cannot consume all the resources of the ingester.
+///
+/// Writes are rejected once the namespace has at least `max_buffered_bytes`
+/// buffered, or once the rows written within the current one second window
+/// would exceed `max_rows_per_second`. A single write containing more rows
+/// than `max_rows_per_second` is accepted if it is the first write in its
+/// window. Only the writes applied successfully count against the quotas, so
+/// concurrent writes are each admitted against the writes applied before them.
+///
+/// The quotas are enforced by each ingester independently, against the writes
+/// it receives.
+///
+/// The number of partitions buffered per namespace is limited by the
+/// [`BufferTree`] itself.
+///
+/// The current usage of each namespace is exposed as metrics, along with the
+/// number of rejected writes.
+#[derive(Debug)]
+pub(crate) struct NamespaceQuotaSink<T, B> {
+    inner: T,
+    buffer: Arc<B>,
+    time_provider: Arc<dyn TimeProvider>,
+
+    max_buffered_bytes: Option<NonZeroUsize>,
+    max_rows_per_second: Option<NonZeroU64>,
+
+    usage: Mutex<HashMap<NamespaceId, Arc<Mutex<NamespaceUsage>>>>,
+
+    buffered_bytes: Metric<U64Gauge>,
+    rows_per_second: Metric<U64Gauge>,
+    rejected_buffered_bytes: U64Counter,
+    rejected_rows_per_second: U64Counter,
+}
+
+impl<T, B> NamespaceQuotaSink<T, B>
+where
+    B: NamespaceBufferedBytes,
+{
+    pub(crate) fn new(
+        inner: T,
+        buffer: Arc<B>,
+        time_provider: Arc<dyn TimeProvider>,
+        max_buffered_bytes: Option<NonZeroUsize>,
+        max_rows_per_second: Option<NonZeroU64>,
+        metrics: &metric::Registry,
+    ) -> Self {
+        let buffered_bytes = metrics.register_metric::<U64Gauge>(
+            "ingester_namespace_quota_buffered_bytes",
+            "number of bytes buffered per namespace, as last read for quota enforcement",
+        );
+        let rows_per_second = metrics.register_metric::<U64Gauge>(
+            "ingester_namespace_quota_rows_per_second",
+            "number of rows written per namespace in the last complete one second window",
+        );
+        let rejected = metrics.register_metric::<U64Counter>(
+            "ingester_namespace_quota_rejected_writes",
+            "number of writes rejected because their namespace exceeded an ingest quota",
+        );
+
+        Self {
+            inner,
+            buffer,
+            time_provider,
+            max_buffered_bytes,
+            max_rows_per_second,
+            usage: Default::default(),
+            buffered_bytes,
+            rows_per_second,
+            rejected_buffered_bytes: rejected
+                .recorder(&[("quota", NamespaceQuota::BufferedBytes.as_str())]),
+            rejected_rows_per_second: rejected
+                .recorder(&[("quota", NamespaceQuota::RowsPerSecond.as_str())]),
+        }
+    }
+
+    fn namespace_usage(&self, namespace_id: NamespaceId) -> Arc<Mutex<NamespaceUsage>> {
+        Arc::clone(self.usage.lock().entry(namespace_id).or_default())
+    }
+
+    /// Check the write of `rows` to `namespace_id` is within its quotas.
+    ///
+    /// The rows are not counted against the current rate window until the
+    /// write has been applied, see [`Self::record_applied()`].
+    fn admit(
+        &self,
+        namespace_id: NamespaceId,
+        usage: &Mutex<NamespaceUsage>,
+        rows: u64,
+    ) -> Result<(), DmlError> {
+        let now = self.time_provider.now();
+        let elapsed = |since: Option<Time>, period: Duration| {
+            since
+                .and_then(|t| now.checked_duration_since(t))
+                .map_or(true, |d| d >= period)
+        };
+        let attr = [("namespace_id", Cow::from(namespace_id.to_string()))];
+
+        let mut usage = usage.lock();
+
+        if let Some(limit) = self.max_buffered_bytes {
+            if elapsed(
+                usage.buffered_bytes_read_at,
+                BUFFERED_BYTES_REFRESH_INTERVAL,
+            ) {
+                usage.buffered_bytes = self.buffer.buffered_bytes(namespace_id);
+                usage.buffered_bytes_read_at = Some(now);
+                self.buffered_bytes
+                    .recorder(attr.clone())
+                    .set(usage.buffered_bytes as u64);
+            }
+
+            if usage.buffered_bytes >= limit.get() {
+                self.rejected_buffered_bytes.inc(1);
+                return Err(DmlError::NamespaceQuotaExceeded {
+                    namespace_id,
+                    quota: NamespaceQuota::BufferedBytes,
+                    limit: limit.get() as u64,
+                });
+            }
+        }
+
+        if let Some(limit) = self.max_rows_per_second {
+            if elapsed(usage.window_start, RATE_WINDOW) {
+                self.rows_per_second.recorder(attr).set(usage.window_rows);
+                usage.window_start = Some(now);
+                usage.window_rows = 0;
+            }
+
+            if usage.window_rows > 0 && usage.window_rows + rows > limit.get() {
+                self.rejected_rows_per_second.inc(1);
+                return Err(DmlError::NamespaceQuotaExceeded {
+                    namespace_id,
+                    quota: NamespaceQuota::RowsPerSecond,
+                    limit: limit.get(),
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Account for a write of `rows` and `bytes` that has been applied, until
+    /// the buffered bytes are next read from the buffer and the current rate
+    /// window ends.
+    fn record_applied(&self, usage: &Mutex<NamespaceUsage>, rows: u64, bytes: usize) {
+        let mut usage = usage.lock();
+        usage.buffered_bytes += bytes;
+        if self.max_rows_per_second.is_some() {
+            usage.window_rows += rows;
+        }
+    }
+}
+
+#[async_trait]
+impl<T, B> DmlSink for NamespaceQuotaSink<T, B>
+where
+    T: DmlSink,
+    B: NamespaceBufferedBytes,
+{
+    type Error = DmlError;
+
+    async fn apply(&self, op: IngestOp) -> Result<(), Self::Error> {
+        if self.max_buffered_bytes.is_none() && self.max_rows_per_second.is_none() {
+            return self.inner.apply(op).await.map_err(Into::into);
+        }
+
+        let namespace_id = op.namespace();
+        let (rows, bytes) = match &op {
+            IngestOp::Write(w) => w.tables().fold((0, 0), |(rows, bytes), (_, t)| {
+                let data = t.partitioned_data().data();
+                (rows + data.rows(), bytes + data.size_data())
+            }),
+        };
+
+        let usage = self.namespace_usage(namespace_id);
+        self.admit(namespace_id, &usage, rows as u64)?;
+
+        self.inner.apply(op).await.map_err(Into::into)?;
+
+        // Writes that failed to apply never count against the quotas.
+        self.record_applied(&usage, rows as u64, bytes);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_matches::assert_matches;
+    use iox_time::MockProvider;
+
+    use super::*;
+    use crate::{
+        dml_sink::mock_sink::MockDmlSink,
+        test_util::{
+            make_write_op, ARBITRARY_NAMESPACE_ID, ARBITRARY_PARTITION_KEY, ARBITRARY_TABLE_ID,
+            ARBITRARY_TABLE_NAME,
+        },
+    };
+
+    #[derive(Debug, Default)]
+    struct MockBufferedBytes(Mutex<usize>);
+
+    impl NamespaceBufferedBytes for MockBufferedBytes {
+        fn buffered_bytes(&self, _namespace_id: NamespaceId) -> usize {
+            *self.0.lock()
+        }
+    }
+
+    /// A write of 2 rows.
+    fn op() -> IngestOp {
+        IngestOp::Write(make_write_op(
+            &ARBITRARY_PARTITION_KEY,
+            ARBITRARY_NAMESPACE_ID,
+            &ARBITRARY_TABLE_NAME,
+            ARBITRARY_TABLE_ID,
+            42,
+            &format!(
+                "{},tag=1 v=2 42424242\n{},tag=2 v=3 42424243",
+                &*ARBITRARY_TABLE_NAME, &*ARBITRARY_TABLE_NAME
+            ),
+            None,
+        ))
+    }
+
+    #[tokio::test]
+    async fn test_buffered_bytes_quota() {
+        let time_provider = Arc::new(MockProvider::new(Time::from_timestamp_nanos(0)));
+        let buffer = Arc::new(MockBufferedBytes::default());
+        let mock = Arc::new(MockDmlSink::default().with_apply_return([Ok(()), Ok(())]));
+        let sink = NamespaceQuotaSink::new(
+            Arc::clone(&mock),
+            Arc::clone(&buffer),
+            Arc::clone(&time_provider) as _,
+            Some(NonZeroUsize::new(100).unwrap()),
+            None,
+            &metric::Registry::default(),
+        );
+
+        sink.apply(op()).await.expect("write within quota");
+
+        // The buffered bytes are not read again within the refresh interval,
+        // so the limit is only observed once refreshed.
+        *buffer.0.lock() = 100;
+        sink.apply(op()).await.expect("write within quota");
+
+        time_provider.inc(BUFFERED_BYTES_REFRESH_INTERVAL);
+        assert_matches!(
+            sink.apply(op()).await,
+            Err(DmlError::NamespaceQuotaExceeded {
+                namespace_id,
+                quota: NamespaceQuota::BufferedBytes,
+                limit: 100,
+            }) => {
+                assert_eq!(namespace_id, ARBITRARY_NAMESPACE_ID);
+            }
+        );
+        assert_eq!(mock.get_calls().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_rows_per_second_quota() {
+        let time_provider = Arc::new(MockProvider::new(Time::from_timestamp_nanos(0)));
+        let mock = Arc::new(MockDmlSink::default().with_apply_return([Ok(()), Ok(())]));
+        let sink = NamespaceQuotaSink::new(
+            Arc::clone(&mock),
+            Arc::new(MockBufferedBytes::default()),
+            Arc::clone(&time_provider) as _,
+            None,
+            Some(NonZeroU64::new(3).unwrap()),
+            &metric::Registry::default(),
+        );
+
+        // 2 rows are within the limit of 3 rows per second, but 4 are not.
+        sink.apply(op()).await.expect("write within quota");
+        assert_matches!(
+            sink.apply(op()).await,
+            Err(DmlError::NamespaceQuotaExceeded {
+                quota: NamespaceQuota::RowsPerSecond,
+                limit: 3,
+                ..
+            })
+        );
+
+        // The quota is restored in the next window.
+        time_provider.inc(RATE_WINDOW);
+        sink.apply(op()).await.expect("write within quota");
+
+        assert_eq!(mock.get_calls().len(), 2);
+    }
+
+    // Writes that fail to apply do not count against the rate quota.
+    #[tokio::test]
+    async fn test_rows_per_second_quota_apply_error() {
+        let time_provider = Arc::new(MockProvider::new(Time::from_timestamp_nanos(0)));
+        let mock = Arc::new(
+            MockDmlSink::default()
+                .with_apply_return([Err(DmlError::Wal("broken".to_string())), Ok(())]),
+        );
+        let sink = NamespaceQuotaSink::new(
+            Arc::clone(&mock),
+            Arc::new(MockBufferedBytes::default()),
+            Arc::clone(&time_provider) as _,
+            None,
+            Some(NonZeroU64::new(3).unwrap()),
+            &metric::Registry::default(),
+        );
+
+        assert_matches!(sink.apply(op()).await, Err(DmlError::Wal(_)));
+        sink.apply(op()).await.expect("write within quota");
+
+        assert_eq!(mock.get_calls().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_no_quotas() {
+        let mock = Arc::new(
+            MockDmlSink::default()
+                .with_apply_return([Ok(()), Err(DmlError::Wal("broken".to_string()))]),
+        );
+        let sink = NamespaceQuotaSink::new(
+            Arc::clone(&mock),
+            Arc::new(MockBufferedBytes::default()),
+            Arc::new(MockProvider::new(Time::from_timestamp_nanos(0))),
+            None,
+            None,
+            &metric::Registry::default(),
+        );
+
+        sink.apply(op()).await.expect("write should succeed");
+        assert_matches!(sink.apply(op()).await, Err(DmlError::Wal(_)));
+    }
+}
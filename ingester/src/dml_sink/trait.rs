@@ -1,7 +1,9 @@
 use std::{error::Error, fmt::Debug, ops::Deref, sync::Arc};
 
+use super::namespace_quota::NamespaceQuota;
 use crate::{buffer_tree::BufferWriteError, dml_payload::IngestOp};
 use async_trait::async_trait;
+use data_types::NamespaceId;
 use thiserror::Error;
 
 /// Errors returned due from calls to [`DmlSink::apply()`].
@@ -21,6 +23,17 @@ pub enum DmlError {
     /// retrying indefinitely.
     #[error("buffer apply request timeout")]
     ApplyTimeout,
+
+    /// The namespace of the write has exceeded one of its ingest quotas.
+    #[error("namespace {namespace_id} exceeded its {quota} quota of {limit}")]
+    NamespaceQuotaExceeded {
+        /// The namespace the write was rejected for.
+        namespace_id: NamespaceId,
+        /// The quota exceeded.
+        quota: NamespaceQuota,
+        /// The configured limit of the quota.
+        limit: u64,
+    },
 }
 
 /// A [`DmlSink`] handles [`IngestOp`] instances in some abstract way.
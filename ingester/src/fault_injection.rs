@@ -0,0 +1,297 @@
+//! Test-only fault injection into the persist and catalog paths.
+//!
+//! When built with the `fault_injection` feature, a [`FaultInjector`] can be
+//! configured (via the `FaultInjectionService` gRPC service) to fail specific
+//! [`FaultPoint`] with a given probability, allowing the resilience of the
+//! retry logic and crash recovery to be exercised in end-to-end tests.
+//!
+//! Without the feature, [`FaultInjector`] is a zero-sized no-op.
+
+use std::fmt::Display;
+
+use thiserror::Error;
+
+/// A point in the ingester at which a fault can be injected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum FaultPoint {
+    /// The upload of a persisted parquet file to object storage.
+    ObjectStorePut,
+    /// The addition of a persisted parquet file to the catalog.
+    CatalogCommit,
+    /// The compaction of persisting data, prior to upload.
+    Compact,
+}
+
+impl Display for FaultPoint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::ObjectStorePut => write!(f, "object_store_put"),
+            Self::CatalogCommit => write!(f, "catalog_commit"),
+            Self::Compact => write!(f, "compact"),
+        }
+    }
+}
+
+/// An error returned when a fault is injected at a [`FaultPoint`].
+#[derive(Debug, Error)]
+#[error("injected fault at {0}")]
+#[cfg_attr(not(feature = "fault_injection"), allow(dead_code))]
+pub(crate) struct InjectedFault(FaultPoint);
+
+#[cfg(feature = "fault_injection")]
+pub(crate) use enabled::*;
+
+#[cfg(not(feature = "fault_injection"))]
+pub(crate) use disabled::*;
+
+#[cfg(not(feature = "fault_injection"))]
+mod disabled {
+    use super::*;
+
+    /// A no-op fault injector that never injects a fault.
+    #[derive(Debug, Default)]
+    pub(crate) struct FaultInjector;
+
+    impl FaultInjector {
+        /// Always returns [`Ok`].
+        #[inline(always)]
+        pub(crate) fn check(&self, _point: FaultPoint) -> Result<(), InjectedFault> {
+            Ok(())
+        }
+    }
+}
+
+#[cfg(feature = "fault_injection")]
+mod enabled {
+    use std::{collections::HashMap, ops::Range, sync::Arc};
+
+    use async_trait::async_trait;
+    use bytes::Bytes;
+    use futures::stream::BoxStream;
+    use object_store::{
+        path::Path, DynObjectStore, GetOptions, GetResult, ListResult, MultipartId, ObjectMeta,
+        ObjectStore,
+    };
+    use observability_deps::tracing::*;
+    use parking_lot::Mutex;
+    use parquet_file::storage::ParquetStorage;
+    use rand::Rng;
+    use tokio::io::AsyncWrite;
+
+    use super::*;
+
+    /// Injects faults at [`FaultPoint`] with a configurable probability.
+    #[derive(Debug, Default)]
+    pub(crate) struct FaultInjector {
+        probabilities: Mutex<HashMap<FaultPoint, f64>>,
+    }
+
+    impl FaultInjector {
+        /// Inject a fault with the given `probability` (between 0 and 1) each
+        /// time `point` is reached.
+        ///
+        /// # Panics
+        ///
+        /// Panics if `probability` is not within the range 0 to 1.
+        pub(crate) fn set(&self, point: FaultPoint, probability: f64) {
+            assert!((0.0..=1.0).contains(&probability));
+
+            let mut p = self.probabilities.lock();
+            if probability == 0.0 {
+                p.remove(&point);
+            } else {
+                p.insert(point, probability);
+            }
+
+            info!(%point, probability, "configured fault injection");
+        }
+
+        /// Remove all configured faults.
+        pub(crate) fn clear(&self) {
+            self.probabilities.lock().clear();
+            info!("cleared fault injection");
+        }
+
+        /// Return an [`InjectedFault`] error if a fault should be injected at
+        /// `point`.
+        pub(crate) fn check(&self, point: FaultPoint) -> Result<(), InjectedFault> {
+            let probability = match self.probabilities.lock().get(&point) {
+                Some(v) => *v,
+                None => return Ok(()),
+            };
+
+            if rand::thread_rng().gen_bool(probability) {
+                warn!(%point, "injecting fault");
+                return Err(InjectedFault(point));
+            }
+
+            Ok(())
+        }
+
+        /// Wrap the object store of `storage`, failing puts when a fault is
+        /// injected at [`FaultPoint::ObjectStorePut`].
+        pub(crate) fn wrap_storage(self: &Arc<Self>, storage: ParquetStorage) -> ParquetStorage {
+            let store = FaultInjectingStore {
+                inner: Arc::clone(storage.object_store()),
+                faults: Arc::clone(self),
+            };
+            ParquetStorage::new(Arc::new(store), storage.id())
+        }
+    }
+
+    /// An [`ObjectStore`] decorator that fails puts when a fault is injected
+    /// at [`FaultPoint::ObjectStorePut`].
+    #[derive(Debug)]
+    struct FaultInjectingStore {
+        inner: Arc<DynObjectStore>,
+        faults: Arc<FaultInjector>,
+    }
+
+    impl Display for FaultInjectingStore {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "fault_injection({})", self.inner)
+        }
+    }
+
+    #[async_trait]
+    impl ObjectStore for FaultInjectingStore {
+        async fn put(&self, location: &Path, bytes: Bytes) -> object_store::Result<()> {
+            self.faults.check(FaultPoint::ObjectStorePut).map_err(|e| {
+                object_store::Error::Generic {
+                    store: "fault_injection",
+                    source: Box::new(e),
+                }
+            })?;
+            self.inner.put(location, bytes).await
+        }
+
+        async fn put_multipart(
+            &self,
+            location: &Path,
+        ) -> object_store::Result<(MultipartId, Box<dyn AsyncWrite + Unpin + Send>)> {
+            self.inner.put_multipart(location).await
+        }
+
+        async fn abort_multipart(
+            &self,
+            location: &Path,
+            multipart_id: &MultipartId,
+        ) -> object_store::Result<()> {
+            self.inner.abort_multipart(location, multipart_id).await
+        }
+
+        async fn get_opts(
+            &self,
+            location: &Path,
+            options: GetOptions,
+        ) -> object_store::Result<GetResult> {
+            self.inner.get_opts(location, options).await
+        }
+
+        async fn get_range(
+            &self,
+            location: &Path,
+            range: Range<usize>,
+        ) -> object_store::Result<Bytes> {
+            self.inner.get_range(location, range).await
+        }
+
+        async fn get_ranges(
+            &self,
+            location: &Path,
+            ranges: &[Range<usize>],
+        ) -> object_store::Result<Vec<Bytes>> {
+            self.inner.get_ranges(location, ranges).await
+        }
+
+        async fn head(&self, location: &Path) -> object_store::Result<ObjectMeta> {
+            self.inner.head(location).await
+        }
+
+        async fn delete(&self, location: &Path) -> object_store::Result<()> {
+            self.inner.delete(location).await
+        }
+
+        async fn list(
+            &self,
+            prefix: Option<&Path>,
+        ) -> object_store::Result<BoxStream<'_, object_store::Result<ObjectMeta>>> {
+            self.inner.list(prefix).await
+        }
+
+        async fn list_with_delimiter(
+            &self,
+            prefix: Option<&Path>,
+        ) -> object_store::Result<ListResult> {
+            self.inner.list_with_delimiter(prefix).await
+        }
+
+        async fn copy(&self, from: &Path, to: &Path) -> object_store::Result<()> {
+            self.inner.copy(from, to).await
+        }
+
+        async fn copy_if_not_exists(&self, from: &Path, to: &Path) -> object_store::Result<()> {
+            self.inner.copy_if_not_exists(from, to).await
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use assert_matches::assert_matches;
+        use object_store::memory::InMemory;
+        use parquet_file::storage::StorageId;
+
+        use super::*;
+
+        #[test]
+        fn test_check() {
+            let faults = FaultInjector::default();
+            assert_matches!(faults.check(FaultPoint::Compact), Ok(()));
+
+            faults.set(FaultPoint::Compact, 1.0);
+            assert_matches!(
+                faults.check(FaultPoint::Compact),
+                Err(InjectedFault(FaultPoint::Compact))
+            );
+            assert_matches!(faults.check(FaultPoint::CatalogCommit), Ok(()));
+
+            // A zero probability disables the fault.
+            faults.set(FaultPoint::Compact, 0.0);
+            assert_matches!(faults.check(FaultPoint::Compact), Ok(()));
+
+            faults.set(FaultPoint::Compact, 1.0);
+            faults.set(FaultPoint::CatalogCommit, 1.0);
+            faults.clear();
+            assert_matches!(faults.check(FaultPoint::Compact), Ok(()));
+            assert_matches!(faults.check(FaultPoint::CatalogCommit), Ok(()));
+        }
+
+        #[tokio::test]
+        async fn test_wrap_storage() {
+            let faults = Arc::new(FaultInjector::default());
+            let storage = faults.wrap_storage(ParquetStorage::new(
+                Arc::new(InMemory::default()),
+                StorageId::from("iox"),
+            ));
+            let store = storage.object_store();
+            let path = Path::from("bananas");
+
+            faults.set(FaultPoint::ObjectStorePut, 1.0);
+            assert_matches!(
+                store.put(&path, Bytes::from_static(b"platanos")).await,
+                Err(object_store::Error::Generic { .. })
+            );
+            assert_matches!(
+                store.head(&path).await,
+                Err(object_store::Error::NotFound { .. })
+            );
+
+            faults.clear();
+            store
+                .put(&path, Bytes::from_static(b"platanos"))
+                .await
+                .expect("put should succeed");
+            store.head(&path).await.expect("object should exist");
+        }
+    }
+}
@@ -0,0 +1,21 @@
+use std::sync::Arc;
+
+use tokio::task::JoinHandle;
+
+use super::IngestRateDetector;
+
+/// Spawn a task evaluating the ingest rate of each namespace tracked by
+/// `detector` every check interval.
+pub(crate) fn spawn_ingest_rate_check(detector: Arc<IngestRateDetector>) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(detector.check_interval());
+
+        // The first tick completes immediately, before any rows were counted.
+        interval.tick().await;
+
+        loop {
+            interval.tick().await;
+            detector.evaluate();
+        }
+    })
+}
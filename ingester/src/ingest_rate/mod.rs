@@ -0,0 +1,506 @@
+//! Detection of abrupt changes in the rate of rows written to each namespace.
+//!
+//! The [`IngestRateSink`] decorator counts the rows written to each namespace
+//! in a shared [`IngestRateDetector`], which is evaluated every check interval
+//! by the task started with [`spawn_ingest_rate_check()`]. Each evaluation
+//! compares the rate of the last interval against a trailing baseline of the
+//! rate of the namespace, raising an [`IngestAnomaly`] when it dropped (such
+//! as during an outage of the agents writing to the namespace) or spiked (such
+//! as when a producer is stuck in a retry loop).
+//!
+//! The baseline keeps adapting while an anomaly is raised, so an anomaly
+//! clears once the new rate has persisted for long enough to become the
+//! baseline. The exception is a drop to no writes at all, which lasts until
+//! writes to the namespace resume.
+
+mod check;
+mod sink;
+
+pub(crate) use check::*;
+pub(crate) use sink::*;
+
+use std::{collections::HashMap, fmt::Display, sync::Arc, time::Duration};
+
+use data_types::NamespaceId;
+use iox_time::{Time, TimeProvider};
+use metric::{U64Counter, U64Gauge};
+use observability_deps::tracing::*;
+use parking_lot::Mutex;
+
+/// The number of check intervals the trailing baseline rate of a namespace is
+/// averaged over.
+const BASELINE_INTERVALS: usize = 30;
+
+/// The number of check intervals a namespace must be observed for before
+/// anomalies are raised for it.
+const WARM_UP_INTERVALS: usize = 10;
+
+/// The baseline rate, in rows per second, below which the rate of a namespace
+/// is too low for a drop or spike to be meaningful.
+const MIN_BASELINE_ROWS_PER_SECOND: f64 = 1.0;
+
+/// The configuration of the ingest rate anomaly detector.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IngestRateAnomalyConfig {
+    check_interval: Duration,
+    drop_percent: u8,
+    spike_percent: u32,
+}
+
+impl IngestRateAnomalyConfig {
+    /// Evaluate the ingest rate of each namespace every `check_interval`,
+    /// raising an anomaly when it is more than `drop_percent` below, or more
+    /// than `spike_percent` above, its trailing baseline.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `check_interval` is less than a second, or `drop_percent` is
+    /// not within 1 to 100.
+    pub fn new(check_interval: Duration, drop_percent: u8, spike_percent: u32) -> Self {
+        assert!(
+            check_interval.as_secs() > 0,
+            "ingest rate check interval must be at least a second"
+        );
+        assert!(
+            (1..=100).contains(&drop_percent),
+            "ingest rate drop percent must be within 1 to 100"
+        );
+
+        Self {
+            check_interval,
+            drop_percent,
+            spike_percent,
+        }
+    }
+}
+
+/// The direction of an abrupt change in the ingest rate of a namespace.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IngestAnomalyKind {
+    /// The rate dropped below the baseline by more than the configured
+    /// percentage.
+    Drop,
+
+    /// The rate rose above the baseline by more than the configured
+    /// percentage.
+    Spike,
+}
+
+impl IngestAnomalyKind {
+    /// The name of the kind, as used in logs and metric attributes.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Drop => "drop",
+            Self::Spike => "spike",
+        }
+    }
+}
+
+impl Display for IngestAnomalyKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// An ongoing abrupt change in the ingest rate of a namespace, as returned by
+/// [`IngesterGuard::ingest_anomalies()`].
+///
+/// [`IngesterGuard::ingest_anomalies()`]: crate::IngesterGuard::ingest_anomalies()
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct IngestAnomaly {
+    /// The namespace the anomaly was detected for.
+    pub namespace_id: NamespaceId,
+
+    /// Whether the rate dropped or spiked.
+    pub kind: IngestAnomalyKind,
+
+    /// The rate of rows written to the namespace in the most recent check
+    /// interval.
+    pub rows_per_second: f64,
+
+    /// The trailing baseline rate the most recent rate was compared against.
+    pub baseline_rows_per_second: f64,
+
+    /// The time the anomaly was first detected at.
+    pub since: Time,
+}
+
+/// The observed ingest rate of a single namespace.
+#[derive(Debug, Default)]
+struct NamespaceRate {
+    /// The rows written since the last evaluation.
+    rows: u64,
+
+    /// The trailing baseline rate, in rows per second, and the number of
+    /// intervals it was computed from (saturating at [`BASELINE_INTERVALS`]).
+    baseline: f64,
+    intervals: usize,
+
+    anomaly: Option<IngestAnomaly>,
+}
+
+#[derive(Debug)]
+struct State {
+    last_evaluated: Time,
+    namespaces: HashMap<NamespaceId, NamespaceRate>,
+}
+
+/// Tracks the rate of rows written to each namespace, detecting drops and
+/// spikes relative to the trailing baseline rate of each namespace.
+///
+/// Each anomaly is logged and counted in the `ingester_ingest_rate_anomalies`
+/// metric when first detected, and the number of namespaces with an ongoing
+/// anomaly is exposed in the `ingester_ingest_rate_anomalous_namespaces`
+/// metric.
+#[derive(Debug)]
+pub(crate) struct IngestRateDetector {
+    config: IngestRateAnomalyConfig,
+    time_provider: Arc<dyn TimeProvider>,
+
+    state: Mutex<State>,
+
+    raised_drop: U64Counter,
+    raised_spike: U64Counter,
+    anomalous_drop: U64Gauge,
+    anomalous_spike: U64Gauge,
+}
+
+impl IngestRateDetector {
+    pub(crate) fn new(
+        config: IngestRateAnomalyConfig,
+        time_provider: Arc<dyn TimeProvider>,
+        metrics: &metric::Registry,
+    ) -> Self {
+        let raised = metrics.register_metric::<U64Counter>(
+            "ingester_ingest_rate_anomalies",
+            "number of abrupt changes detected in the ingest rate of a namespace",
+        );
+        let anomalous = metrics.register_metric::<U64Gauge>(
+            "ingester_ingest_rate_anomalous_namespaces",
+            "number of namespaces with an ongoing abrupt change in their ingest rate",
+        );
+
+        let drop = [("kind", IngestAnomalyKind::Drop.as_str())];
+        let spike = [("kind", IngestAnomalyKind::Spike.as_str())];
+
+        Self {
+            config,
+            state: Mutex::new(State {
+                last_evaluated: time_provider.now(),
+                namespaces: Default::default(),
+            }),
+            time_provider,
+            raised_drop: raised.recorder(drop),
+            raised_spike: raised.recorder(spike),
+            anomalous_drop: anomalous.recorder(drop),
+            anomalous_spike: anomalous.recorder(spike),
+        }
+    }
+
+    /// The interval [`Self::evaluate()`] is expected to be called at.
+    pub(crate) fn check_interval(&self) -> Duration {
+        self.config.check_interval
+    }
+
+    /// Record the write of `rows` to `namespace_id`.
+    pub(crate) fn record(&self, namespace_id: NamespaceId, rows: u64) {
+        self.state
+            .lock()
+            .namespaces
+            .entry(namespace_id)
+            .or_default()
+            .rows += rows;
+    }
+
+    /// Compare the rate of each namespace since the previous evaluation to its
+    /// baseline, raising or clearing its anomaly, and fold the rate into the
+    /// baseline.
+    pub(crate) fn evaluate(&self) {
+        let now = self.time_provider.now();
+        let mut state = self.state.lock();
+
+        let elapsed = now
+            .checked_duration_since(state.last_evaluated)
+            .unwrap_or_default();
+        if elapsed.is_zero() {
+            return;
+        }
+        state.last_evaluated = now;
+
+        let secs = elapsed.as_secs_f64();
+        let drop_below = 1.0 - f64::from(self.config.drop_percent) / 100.0;
+        let spike_above = 1.0 + f64::from(self.config.spike_percent) / 100.0;
+        let (mut drops, mut spikes) = (0, 0);
+
+        state.namespaces.retain(|&namespace_id, ns| {
+            let rate = std::mem::take(&mut ns.rows) as f64 / secs;
+            let baseline = ns.baseline;
+
+            // A namespace that stopped being written to entirely remains in
+            // the dropped state until writes resume, however far its baseline
+            // has decayed since.
+            let stopped = rate == 0.0
+                && ns
+                    .anomaly
+                    .is_some_and(|a| a.kind == IngestAnomalyKind::Drop);
+
+            let kind = if stopped {
+                Some(IngestAnomalyKind::Drop)
+            } else if ns.intervals >= WARM_UP_INTERVALS && baseline >= MIN_BASELINE_ROWS_PER_SECOND
+            {
+                if rate < baseline * drop_below {
+                    Some(IngestAnomalyKind::Drop)
+                } else if rate > baseline * spike_above {
+                    Some(IngestAnomalyKind::Spike)
+                } else {
+                    None
+                }
+            } else {
+                None
+            };
+
+            match (ns.anomaly.map(|a| a.kind), kind) {
+                (prev, Some(kind)) if prev != Some(kind) => {
+                    warn!(
+                        %namespace_id,
+                        %kind,
+                        rows_per_second = rate,
+                        baseline_rows_per_second = baseline,
+                        "ingest rate anomaly detected"
+                    );
+                    match kind {
+                        IngestAnomalyKind::Drop => self.raised_drop.inc(1),
+                        IngestAnomalyKind::Spike => self.raised_spike.inc(1),
+                    }
+                    ns.anomaly = Some(IngestAnomaly {
+                        namespace_id,
+                        kind,
+                        rows_per_second: rate,
+                        baseline_rows_per_second: baseline,
+                        since: now,
+                    });
+                }
+                (Some(prev), None) => {
+                    info!(
+                        %namespace_id,
+                        kind = %prev,
+                        rows_per_second = rate,
+                        baseline_rows_per_second = baseline,
+                        "ingest rate anomaly cleared"
+                    );
+                    ns.anomaly = None;
+                }
+                _ => {}
+            }
+
+            if let Some(a) = &mut ns.anomaly {
+                a.rows_per_second = rate;
+                a.baseline_rows_per_second = baseline;
+                match a.kind {
+                    IngestAnomalyKind::Drop => drops += 1,
+                    IngestAnomalyKind::Spike => spikes += 1,
+                }
+            }
+
+            // Average the first intervals equally, and then exponentially
+            // weight the most recent BASELINE_INTERVALS.
+            ns.intervals = (ns.intervals + 1).min(BASELINE_INTERVALS);
+            ns.baseline += (rate - ns.baseline) / ns.intervals as f64;
+
+            // Forget namespaces that are no longer written to once their
+            // baseline has decayed, unless an anomaly is ongoing so that
+            // it is always cleared first.
+            ns.anomaly.is_some() || rate > 0.0 || ns.baseline >= MIN_BASELINE_ROWS_PER_SECOND
+        });
+
+        self.anomalous_drop.set(drops);
+        self.anomalous_spike.set(spikes);
+    }
+
+    /// Return the ongoing anomalies, ordered by namespace ID.
+    pub(crate) fn anomalies(&self) -> Vec<IngestAnomaly> {
+        let mut anomalies = self
+            .state
+            .lock()
+            .namespaces
+            .values()
+            .filter_map(|ns| ns.anomaly)
+            .collect::<Vec<_>>();
+        anomalies.sort_unstable_by_key(|a| a.namespace_id);
+        anomalies
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use iox_time::MockProvider;
+    use metric::{Attributes, Metric};
+
+    use super::*;
+
+    const INTERVAL: Duration = Duration::from_secs(10);
+    const NAMESPACE_ID: NamespaceId = NamespaceId::new(42);
+
+    fn detector(
+        time_provider: &Arc<MockProvider>,
+        metrics: &metric::Registry,
+    ) -> IngestRateDetector {
+        IngestRateDetector::new(
+            IngestRateAnomalyConfig::new(INTERVAL, 50, 400),
+            Arc::clone(time_provider) as _,
+            metrics,
+        )
+    }
+
+    /// Write `rows` to [`NAMESPACE_ID`] in each of `n` check intervals.
+    fn write_intervals(d: &IngestRateDetector, time_provider: &MockProvider, rows: u64, n: usize) {
+        for _ in 0..n {
+            if rows > 0 {
+                d.record(NAMESPACE_ID, rows);
+            }
+            time_provider.inc(INTERVAL);
+            d.evaluate();
+        }
+    }
+
+    /// The number of anomalies of `kind` raised.
+    fn raised(metrics: &metric::Registry, kind: IngestAnomalyKind) -> u64 {
+        metrics
+            .get_instrument::<Metric<U64Counter>>("ingester_ingest_rate_anomalies")
+            .expect("failed to read metric")
+            .get_observer(&Attributes::from(&[("kind", kind.as_str())]))
+            .expect("failed to get observer")
+            .fetch()
+    }
+
+    /// The number of namespaces with an ongoing anomaly of `kind`.
+    fn anomalous(metrics: &metric::Registry, kind: IngestAnomalyKind) -> u64 {
+        metrics
+            .get_instrument::<Metric<U64Gauge>>("ingester_ingest_rate_anomalous_namespaces")
+            .expect("failed to read metric")
+            .get_observer(&Attributes::from(&[("kind", kind.as_str())]))
+            .expect("failed to get observer")
+            .fetch()
+    }
+
+    #[test]
+    fn test_drop() {
+        let time_provider = Arc::new(MockProvider::new(Time::from_timestamp_nanos(0)));
+        let metrics = metric::Registry::default();
+        let d = detector(&time_provider, &metrics);
+
+        // 100 rows per second.
+        write_intervals(&d, &time_provider, 1_000, WARM_UP_INTERVALS);
+        assert!(d.anomalies().is_empty());
+
+        // A drop to 40 rows per second is more than 50% below the baseline.
+        write_intervals(&d, &time_provider, 400, 1);
+        let anomalies = d.anomalies();
+        assert_eq!(
+            anomalies,
+            [IngestAnomaly {
+                namespace_id: NAMESPACE_ID,
+                kind: IngestAnomalyKind::Drop,
+                rows_per_second: 40.0,
+                baseline_rows_per_second: 100.0,
+                since: time_provider.now(),
+            }]
+        );
+
+        // The anomaly is raised once, and remains while the rate is low.
+        write_intervals(&d, &time_provider, 0, 1);
+        let anomalies = d.anomalies();
+        assert_eq!(anomalies.len(), 1);
+        assert_eq!(anomalies[0].rows_per_second, 0.0);
+        assert_eq!(
+            anomalies[0].since,
+            Time::from_timestamp_nanos(0) + INTERVAL * 11
+        );
+        assert_eq!(raised(&metrics, IngestAnomalyKind::Drop), 1);
+        assert_eq!(anomalous(&metrics, IngestAnomalyKind::Drop), 1);
+
+        // The anomaly clears when the rate recovers.
+        write_intervals(&d, &time_provider, 1_000, 1);
+        assert!(d.anomalies().is_empty());
+        assert_eq!(anomalous(&metrics, IngestAnomalyKind::Drop), 0);
+    }
+
+    #[test]
+    fn test_spike() {
+        let time_provider = Arc::new(MockProvider::new(Time::from_timestamp_nanos(0)));
+        let metrics = metric::Registry::default();
+        let d = detector(&time_provider, &metrics);
+
+        write_intervals(&d, &time_provider, 1_000, WARM_UP_INTERVALS);
+
+        // 5x the baseline is within the 400% spike threshold, 6x is not.
+        write_intervals(&d, &time_provider, 5_000, 1);
+        assert!(d.anomalies().is_empty());
+
+        write_intervals(&d, &time_provider, 10_000, 1);
+        let anomalies = d.anomalies();
+        assert_eq!(anomalies.len(), 1);
+        assert_eq!(anomalies[0].kind, IngestAnomalyKind::Spike);
+        assert_eq!(raised(&metrics, IngestAnomalyKind::Spike), 1);
+
+        // A sustained rate becomes the baseline, clearing the anomaly.
+        write_intervals(&d, &time_provider, 10_000, BASELINE_INTERVALS);
+        assert!(d.anomalies().is_empty());
+    }
+
+    #[test]
+    fn test_warm_up_and_minimum_baseline() {
+        let time_provider = Arc::new(MockProvider::new(Time::from_timestamp_nanos(0)));
+        let d = detector(&time_provider, &metric::Registry::default());
+
+        // No anomaly is raised before the baseline is established.
+        write_intervals(&d, &time_provider, 1_000, WARM_UP_INTERVALS - 1);
+        write_intervals(&d, &time_provider, 0, 1);
+        assert!(d.anomalies().is_empty());
+
+        // Nor for namespaces written to at a negligible rate.
+        let d = detector(&time_provider, &metric::Registry::default());
+        write_intervals(&d, &time_provider, 5, WARM_UP_INTERVALS);
+        write_intervals(&d, &time_provider, 0, 1);
+        assert!(d.anomalies().is_empty());
+    }
+
+    #[test]
+    fn test_stopped_namespace() {
+        let time_provider = Arc::new(MockProvider::new(Time::from_timestamp_nanos(0)));
+        let metrics = metric::Registry::default();
+        let d = detector(&time_provider, &metrics);
+
+        write_intervals(&d, &time_provider, 1_000, WARM_UP_INTERVALS);
+        write_intervals(&d, &time_provider, 0, 1);
+        assert_eq!(d.anomalies().len(), 1);
+
+        // The drop lasts as long as the namespace is not written to, long
+        // after its baseline has decayed.
+        write_intervals(&d, &time_provider, 0, 10 * BASELINE_INTERVALS);
+        let anomalies = d.anomalies();
+        assert_eq!(anomalies.len(), 1);
+        assert_eq!(anomalies[0].kind, IngestAnomalyKind::Drop);
+        assert!(anomalies[0].baseline_rows_per_second < MIN_BASELINE_ROWS_PER_SECOND);
+        assert_eq!(raised(&metrics, IngestAnomalyKind::Drop), 1);
+        assert_eq!(anomalous(&metrics, IngestAnomalyKind::Drop), 1);
+
+        // And clears once writes resume.
+        write_intervals(&d, &time_provider, 1_000, 1);
+        assert!(d.anomalies().is_empty());
+        assert_eq!(anomalous(&metrics, IngestAnomalyKind::Drop), 0);
+    }
+
+    #[test]
+    fn test_forget_idle_namespace() {
+        let time_provider = Arc::new(MockProvider::new(Time::from_timestamp_nanos(0)));
+        let d = detector(&time_provider, &metric::Registry::default());
+
+        // A namespace written to at a negligible rate never raises an
+        // anomaly, and is forgotten once no longer written to.
+        write_intervals(&d, &time_provider, 5, WARM_UP_INTERVALS);
+        write_intervals(&d, &time_provider, 0, 1);
+        assert!(d.anomalies().is_empty());
+        assert!(d.state.lock().namespaces.is_empty());
+    }
+}
@@ -0,0 +1,105 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use super::IngestRateDetector;
+use crate::{dml_payload::IngestOp, dml_sink::DmlSink};
+
+/// A [`DmlSink`] decorator counting the rows of each successfully applied
+/// write in an [`IngestRateDetector`], if any.
+#[derive(Debug)]
+pub(crate) struct IngestRateSink<T> {
+    inner: T,
+    detector: Option<Arc<IngestRateDetector>>,
+}
+
+impl<T> IngestRateSink<T> {
+    pub(crate) fn new(inner: T, detector: Option<Arc<IngestRateDetector>>) -> Self {
+        Self { inner, detector }
+    }
+}
+
+#[async_trait]
+impl<T> DmlSink for IngestRateSink<T>
+where
+    T: DmlSink,
+{
+    type Error = T::Error;
+
+    async fn apply(&self, op: IngestOp) -> Result<(), Self::Error> {
+        let detector = match &self.detector {
+            Some(v) => v,
+            None => return self.inner.apply(op).await,
+        };
+
+        let namespace_id = op.namespace();
+        let rows = match &op {
+            IngestOp::Write(w) => w
+                .tables()
+                .map(|(_, t)| t.partitioned_data().data().rows())
+                .sum::<usize>(),
+        };
+
+        self.inner.apply(op).await?;
+
+        detector.record(namespace_id, rows as u64);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use assert_matches::assert_matches;
+    use iox_time::{MockProvider, Time};
+
+    use super::*;
+    use crate::{
+        dml_sink::{mock_sink::MockDmlSink, DmlError},
+        ingest_rate::IngestRateAnomalyConfig,
+        test_util::{
+            make_write_op, ARBITRARY_NAMESPACE_ID, ARBITRARY_PARTITION_KEY, ARBITRARY_TABLE_ID,
+            ARBITRARY_TABLE_NAME,
+        },
+    };
+
+    /// A write of 2 rows.
+    fn op() -> IngestOp {
+        IngestOp::Write(make_write_op(
+            &ARBITRARY_PARTITION_KEY,
+            ARBITRARY_NAMESPACE_ID,
+            &ARBITRARY_TABLE_NAME,
+            ARBITRARY_TABLE_ID,
+            42,
+            &format!(
+                "{},tag=1 v=2 42424242\n{},tag=2 v=3 42424243",
+                &*ARBITRARY_TABLE_NAME, &*ARBITRARY_TABLE_NAME
+            ),
+            None,
+        ))
+    }
+
+    #[tokio::test]
+    async fn test_ingest_rate_sink() {
+        let detector = Arc::new(IngestRateDetector::new(
+            IngestRateAnomalyConfig::new(Duration::from_secs(1), 50, 100),
+            Arc::new(MockProvider::new(Time::from_timestamp_nanos(0))),
+            &metric::Registry::default(),
+        ));
+        let mock = MockDmlSink::default()
+            .with_apply_return([Ok(()), Err(DmlError::Wal("broken".to_string()))]);
+        let sink = IngestRateSink::new(mock, Some(Arc::clone(&detector)));
+
+        sink.apply(op()).await.expect("write should succeed");
+
+        // Failed writes are not counted.
+        assert_matches!(sink.apply(op()).await, Err(DmlError::Wal(_)));
+
+        let state = detector.state.lock();
+        assert_matches!(state.namespaces.get(&ARBITRARY_NAMESPACE_ID), Some(ns) => {
+            assert_eq!(ns.rows, 2);
+        });
+    }
+}
@@ -28,6 +28,11 @@ pub enum IngestStateError {
     /// writes.
     #[error("ingester disk full - persisting write-ahead log")]
     DiskFull = 1 << 2,
+
+    /// Set while persist attempts are paused after repeated failures to
+    /// upload or commit persisted files.
+    #[error("ingester unavailable - persist failing")]
+    PersistFailing = 1 << 3,
 }
 
 impl IngestStateError {
@@ -142,7 +147,8 @@ impl IngestState {
     ///
     ///   1. [`IngestStateError::GracefulStop`]
     ///   2. [`IngestStateError::DiskFull`]
-    ///   3. [`IngestStateError::PersistSaturated`].
+    ///   3. [`IngestStateError::PersistFailing`]
+    ///   4. [`IngestStateError::PersistSaturated`].
     ///
     pub(crate) fn read(&self) -> Result<(), IngestStateError> {
         let current = self.state.load(Ordering::Relaxed);
@@ -197,6 +203,10 @@ fn as_err(state: usize) -> Result<(), IngestStateError> {
         return Err(IngestStateError::DiskFull);
     }
 
+    if state & IngestStateError::PersistFailing.as_bits() != 0 {
+        return Err(IngestStateError::PersistFailing);
+    }
+
     if state & IngestStateError::PersistSaturated.as_bits() != 0 {
         return Err(IngestStateError::PersistSaturated);
     }
@@ -279,8 +289,19 @@ mod tests {
             Err(IngestStateError::DiskFull)
         );
 
-        // Un-setting the disk full state then shows the persist saturated state.
+        // Un-setting the disk full state then shows the persist failing
+        // state, which takes precedence over the persist saturated state.
+        state.set(IngestStateError::PersistFailing);
         state.unset(IngestStateError::DiskFull);
+        assert_matches!(state.read(), Err(IngestStateError::PersistFailing));
+        assert_matches!(
+            state.read_with_exceptions([]),
+            Err(IngestStateError::PersistFailing)
+        );
+
+        // Un-setting the persist failing state then shows the persist
+        // saturated state.
+        state.unset(IngestStateError::PersistFailing);
         assert_matches!(state.read(), Err(IngestStateError::PersistSaturated));
         assert_matches!(
             state.read_with_exceptions([]),
@@ -294,7 +315,8 @@ mod tests {
         prop_oneof![
             Just(IngestStateError::PersistSaturated),
             Just(IngestStateError::GracefulStop),
-            Just(IngestStateError::DiskFull)
+            Just(IngestStateError::DiskFull),
+            Just(IngestStateError::PersistFailing)
         ]
     }
 
@@ -303,6 +325,7 @@ mod tests {
             IngestStateError::PersistSaturated,
             IngestStateError::GracefulStop,
             IngestStateError::DiskFull,
+            IngestStateError::PersistFailing,
         ]
         .into_iter()
         .filter(|v| !not.iter().any(|w| discriminant(v) == discriminant(w)))
@@ -318,6 +341,7 @@ mod tests {
                 IngestStateError::PersistSaturated => {}
                 IngestStateError::GracefulStop => {}
                 IngestStateError::DiskFull => {}
+                IngestStateError::PersistFailing => {}
             }
         }
 
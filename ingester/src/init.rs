@@ -6,19 +6,32 @@ use gossip_parquet_file::tx::ParquetFileTx;
 #[cfg(feature = "benches")]
 pub mod wal_replay;
 
+mod catalog_warm_up;
 mod graceful_shutdown;
+mod memory_usage;
 #[cfg(not(feature = "benches"))]
 mod wal_replay;
 
-use std::{net::SocketAddr, num::NonZeroUsize, path::PathBuf, sync::Arc, time::Duration};
+use std::{
+    net::SocketAddr,
+    num::{NonZeroU64, NonZeroUsize},
+    path::PathBuf,
+    sync::Arc,
+    time::Duration,
+};
 
 use arrow_flight::flight_service_server::FlightService;
 use backoff::BackoffConfig;
 use futures::{future::Shared, Future, FutureExt};
+#[cfg(feature = "fault_injection")]
+use generated_types::influxdata::iox::ingester::v1::fault_injection_service_server::FaultInjectionService;
 use generated_types::influxdata::iox::{
     catalog::v1::catalog_service_server::CatalogService,
     gossip::Topic,
-    ingester::v1::{persist_service_server::PersistService, write_service_server::WriteService},
+    ingester::v1::{
+        buffer_schema_service_server::BufferSchemaService, persist_service_server::PersistService,
+        write_service_server::WriteService,
+    },
 };
 use iox_catalog::interface::Catalog;
 use iox_query::exec::Executor;
@@ -30,8 +43,15 @@ use tokio_util::sync::CancellationToken;
 use tracker::DiskSpaceMetrics;
 use wal::Wal;
 
+pub use mutable_batch::value_policy::FieldValuePolicy;
+
+pub use crate::ingest_rate::{IngestAnomaly, IngestAnomalyKind, IngestRateAnomalyConfig};
+pub use crate::persist::barrier::PersistBarrierGroup;
+pub use crate::rollup::RollupRule;
+
 use crate::{
     buffer_tree::{
+        invariant_check::spawn_invariant_check,
         namespace::name_resolver::{NamespaceNameProvider, NamespaceNameResolver},
         partition::resolver::{
             CatalogPartitionResolver, CoalescePartitionResolver, OldPartitionBloomFilter,
@@ -40,21 +60,30 @@ use crate::{
         table::metadata_resolver::{TableProvider, TableResolver},
         BufferTree,
     },
-    dml_sink::{instrumentation::DmlSinkInstrumentation, tracing::DmlSinkTracing},
+    dml_sink::{
+        instrumentation::DmlSinkInstrumentation, namespace_quota::NamespaceQuotaSink,
+        tracing::DmlSinkTracing,
+    },
     gossip::persist_parquet::ParquetFileNotification,
+    ingest_rate::{spawn_ingest_rate_check, IngestRateDetector, IngestRateSink},
     ingest_state::IngestState,
     ingester_id::IngesterId,
+    partition_seal::PartitionSealer,
     persist::{
-        column_map_resolver::CatalogColumnMapResolver, completion_observer::MaybeLayer,
-        file_metrics::ParquetFileInstrumentation, handle::PersistHandle,
-        hot_partitions::HotPartitionPersister,
+        barrier::PersistBarrier, column_map_resolver::CatalogColumnMapResolver,
+        completion_observer::MaybeLayer, file_metrics::ParquetFileInstrumentation,
+        handle::PersistHandle, hot_partitions::HotPartitionPersister,
+        idle_namespace::spawn_idle_namespace_persist,
     },
+    priority_executor::PriorityExecutor,
     query::{
-        exec_instrumentation::QueryExecInstrumentation,
+        exec_instrumentation::QueryExecInstrumentation, priority::QueryExecPriority,
         result_instrumentation::QueryResultInstrumentation, tracing::QueryExecTracing,
     },
-    server::grpc::GrpcDelegate,
+    rollup::RollupSink,
+    server::grpc::{GrpcDelegate, LoadShedPolicy},
     timestamp_oracle::TimestampOracle,
+    usage::{spawn_usage_flush, UsageFlush, UsageObserver, UsageSink, UsageTracker},
     wal::{
         disk_full_protection::{self, guard_disk_capacity},
         reference_tracker::WalReferenceHandle,
@@ -63,7 +92,11 @@ use crate::{
     },
 };
 
-use self::graceful_shutdown::graceful_shutdown_handler;
+pub use self::memory_usage::MemoryUsage;
+use self::{
+    catalog_warm_up::CatalogWarmUp, graceful_shutdown::graceful_shutdown_handler,
+    memory_usage::MemoryAccountant,
+};
 
 /// Acquire opaque handles to the Ingester RPC service implementations.
 ///
@@ -83,6 +116,11 @@ pub trait IngesterRpcInterface: Send + Sync + std::fmt::Debug {
     type PersistHandler: PersistService;
     /// The type of the [`FlightService`] implementation.
     type FlightHandler: FlightService;
+    /// The type of the [`BufferSchemaService`] implementation.
+    type BufferSchemaHandler: BufferSchemaService;
+    /// The type of the test-only [`FaultInjectionService`] implementation.
+    #[cfg(feature = "fault_injection")]
+    type FaultInjectionHandler: FaultInjectionService;
 
     /// Acquire an opaque handle to the Ingester's [`CatalogService`] RPC
     /// handler implementation.
@@ -99,7 +137,24 @@ pub trait IngesterRpcInterface: Send + Sync + std::fmt::Debug {
     /// Acquire an opaque handle to the Ingester's Arrow Flight
     /// [`FlightService`] RPC handler implementation, allowing at most
     /// `max_simultaneous_requests` queries to be running at any one time.
-    fn query_service(&self, max_simultaneous_requests: usize) -> Self::FlightHandler;
+    ///
+    /// If `max_message_bytes` is specified, the response data is split into
+    /// messages of at most this size.
+    fn query_service(
+        &self,
+        max_simultaneous_requests: usize,
+        max_message_bytes: Option<usize>,
+    ) -> Self::FlightHandler;
+
+    /// Acquire an opaque handle to the Ingester's [`BufferSchemaService`] RPC
+    /// handler implementation.
+    fn buffer_schema_service(&self) -> Self::BufferSchemaHandler;
+
+    /// Acquire an opaque handle to the Ingester's test-only
+    /// `FaultInjectionService` RPC handler implementation, used to inject
+    /// faults into the persist and catalog paths.
+    #[cfg(feature = "fault_injection")]
+    fn fault_injection_service(&self) -> Self::FaultInjectionHandler;
 }
 
 /// A RAII guard to clean up `ingester` instance resources when dropped.
@@ -118,9 +173,36 @@ pub struct IngesterGuard<T> {
     /// Aborted on drop.
     disk_metric_task: tokio::task::JoinHandle<()>,
 
+    /// The handle of the periodic buffer invariant check task, if enabled.
+    ///
+    /// Aborted on drop.
+    invariant_check_task: Option<tokio::task::JoinHandle<()>>,
+
+    /// The handle of the periodic idle namespace persist task, if enabled.
+    ///
+    /// Aborted on drop.
+    idle_namespace_task: Option<tokio::task::JoinHandle<()>>,
+
+    /// The handle of the periodic table usage flush task.
+    ///
+    /// Aborted on drop.
+    usage_flush_task: tokio::task::JoinHandle<()>,
+
+    /// The handle of the periodic ingest rate check task, if enabled.
+    ///
+    /// Aborted on drop.
+    ingest_rate_task: Option<tokio::task::JoinHandle<()>>,
+
     /// The task handle executing the graceful shutdown once triggered.
     graceful_shutdown_handler: tokio::task::JoinHandle<()>,
     shutdown_complete: Shared<oneshot::Receiver<()>>,
+
+    /// Reports the memory held by the buffer and caches.
+    memory: MemoryAccountant,
+
+    /// Detects abrupt changes in the ingest rate of each namespace, if
+    /// enabled.
+    ingest_rate: Option<Arc<IngestRateDetector>>,
 }
 
 impl<T> IngesterGuard<T>
@@ -132,6 +214,23 @@ where
         &self.rpc
     }
 
+    /// Return the approximate memory held by the buffered data and caches of
+    /// the ingester.
+    pub fn memory_usage(&self) -> MemoryUsage {
+        self.memory.memory_usage()
+    }
+
+    /// Return the namespaces whose ingest rate recently dropped or spiked
+    /// relative to their trailing baseline, ordered by namespace ID.
+    ///
+    /// Always empty if ingest rate anomaly detection is disabled.
+    pub fn ingest_anomalies(&self) -> Vec<IngestAnomaly> {
+        self.ingest_rate
+            .as_ref()
+            .map(|d| d.anomalies())
+            .unwrap_or_default()
+    }
+
     /// Block and wait until the ingester has gracefully stopped.
     pub async fn join(&self) {
         self.shutdown_complete
@@ -145,6 +244,16 @@ impl<T> Drop for IngesterGuard<T> {
     fn drop(&mut self) {
         self.rotation_task.abort();
         self.disk_metric_task.abort();
+        if let Some(task) = &self.invariant_check_task {
+            task.abort();
+        }
+        if let Some(task) = &self.idle_namespace_task {
+            task.abort();
+        }
+        self.usage_flush_task.abort();
+        if let Some(task) = &self.ingest_rate_task {
+            task.abort();
+        }
         self.graceful_shutdown_handler.abort();
     }
 }
@@ -193,6 +302,23 @@ pub enum InitError {
     /// An error binding the UDP socket for gossip communication.
     #[error("failed to bind udp gossip socket: {0}")]
     GossipBind(std::io::Error),
+
+    /// An error initialising the disk manager compactions spill to.
+    #[error("failed to initialise persist compaction spilling: {0}")]
+    CompactionSpill(datafusion::error::DataFusionError),
+
+    /// A persist barrier group contains more tables than the persist queue
+    /// depth.
+    #[error(
+        "persist barrier group of {tables} tables exceeds the persist queue depth \
+        of {persist_queue_depth}"
+    )]
+    PersistBarrierGroupTooLarge {
+        /// The number of tables in the group.
+        tables: usize,
+        /// The configured persist queue depth.
+        persist_queue_depth: usize,
+    },
 }
 
 /// Initialise a new `ingester` instance, returning the gRPC service handler
@@ -266,6 +392,174 @@ pub enum InitError {
 /// Decreasing this value increases the frequency of persist operations, and
 /// usually decreases the size of the resulting parquet files.
 ///
+/// ## Executor Priority Lanes
+///
+/// Querier reads and persist compactions share the `persist_executor`, with
+/// at most `exec_priority_slots` units of work from either running at any one
+/// time. When the slots are exhausted, queries are prioritised over persist
+/// compactions, with starvation protection ensuring persist work continues to
+/// make progress.
+///
+/// Configuring this value to be greater than the sum of the query concurrency
+/// limit and `persist_workers` effectively disables prioritisation, as the
+/// slots are never exhausted.
+///
+/// ## Buffer Invariant Checking
+///
+/// When `buffer_invariant_check_interval` is set, every buffered partition is
+/// inspected at the given interval, and any inconsistency found in its
+/// internal state (e.g. overlapping sequence numbers between persisting
+/// batches, or stale cached statistics) is logged and counted in the
+/// `ingester_buffer_invariant_violations` metric. Each check briefly takes
+/// the lock of every partition, so this is intended for soak testing rather
+/// than production deployments.
+///
+/// ## Namespace Quotas
+///
+/// When `max_buffered_bytes_per_namespace` is set, writes to a namespace are
+/// rejected with a `RESOURCE_EXHAUSTED` status while it has at least that many
+/// bytes buffered (and not yet persisting). When
+/// `max_rows_per_second_per_namespace` is set, writes to a namespace are
+/// likewise rejected once the rows written to it within the current one second
+/// window would exceed the limit. The usage of each namespace, and the number
+/// of rejected writes, are exposed as `ingester_namespace_quota_*` metrics.
+///
+/// ## Table Usage
+///
+/// The number of rows and bytes buffered and persisted for each table is
+/// accumulated in memory, and added to the hourly `table_usage` records in the
+/// catalog every `table_usage_flush_interval`, which must be non-zero. The
+/// remaining usage is flushed once all data has been persisted during a
+/// graceful shutdown, and usage accumulated since the last flush is lost if the
+/// ingester stops uncleanly.
+///
+/// ## Recently Persisted Data
+///
+/// When `recent_persisted_bytes` is non-zero, up to `recent_persisted_bytes` of
+/// the (parquet encoded) most recently persisted data of each partition is
+/// retained in memory and continues to be served to queriers, avoiding a
+/// latency cliff for queries over the most recent data once it is persisted.
+///
+/// ## Persist Memory Limit
+///
+/// When `persist_memory_limit_bytes` is set, the compaction of each persist
+/// job executes within a dedicated memory pool of this size instead of the
+/// pool shared with queries. Sorts exceeding it spill to temporary files in
+/// the OS temporary directory, so that persisting a very large partition
+/// slows down rather than exhausting the memory of the ingester. Spills are
+/// recorded in the `ingester_persist_spill_count` and
+/// `ingester_persist_spilled_bytes` metrics.
+///
+/// ## Persist Circuit Breaker
+///
+/// After `persist_circuit_breaker_threshold` consecutive failures to upload a
+/// persisted parquet file or add it to the catalog, persist attempts are
+/// paused and writes are rejected, rather than every persist job retrying
+/// independently. A single attempt probes for recovery at exponentially
+/// increasing intervals, and its success resumes persistence and the
+/// acceptance of writes.
+///
+/// ## WAL Replay Data Loss
+///
+/// If a WAL segment other than the most recent cannot be read in full during
+/// replay, initialisation fails with an error identifying the segment and the
+/// last entry replayed from it. When `wal_replay_accept_data_loss` is true, the
+/// unreadable entries are instead skipped and replay continues.
+///
+/// ## WAL Replay Throttling
+///
+/// When `wal_replay_throttle_threshold` is set, WAL replay is paced while
+/// applying a replayed write takes longer than the threshold, which happens
+/// when resolving its partitions in the catalog is slow. The delay between
+/// replayed writes doubles after every slow write and halves after every fast
+/// one. The progress of the replay is reported by the
+/// `ingester_wal_replay_bytes_replayed` and `ingester_wal_replay_bytes_total`
+/// metrics.
+///
+/// ## Query Load Shedding
+///
+/// Buffer pressure is measured as the percentage of the persist queue occupied
+/// by outstanding persist jobs, and is 100% whenever the ingester is not
+/// accepting writes. When the pressure reaches `query_shed_degrade_percent`,
+/// the buffer is not read and query responses contain only partition metadata,
+/// flagged as omitting the buffered data. When it reaches
+/// `query_shed_reject_percent`, queries are rejected with an `UNAVAILABLE`
+/// status and a `retry-after` hint of `query_shed_retry_after`. Both are
+/// disabled when unset.
+///
+/// ## Field Value Policy
+///
+/// When `field_value_policy` is set, non-finite (NaN or infinite) float values
+/// in writes are rejected, clamped or replaced with NULL according to the
+/// policy before being buffered.
+///
+/// ## Partition Sealing
+///
+/// When `partition_seal_age` is set, writes to a partition whose time range
+/// (derived from the time part of its partition key) ended more than
+/// `partition_seal_age` ago are rejected with an `INVALID_ARGUMENT` status.
+/// Partitions with no time part in their key are never sealed.
+///
+/// ## Persist Barriers
+///
+/// The partitions of the tables in each of the `persist_barrier_groups` are
+/// persisted together: persisting a partition of a table in a group also
+/// persists the partitions with the same partition key of the other tables in
+/// the group, and their parquet files are added to the catalog in a single
+/// transaction. This reduces, but does not eliminate, the time queries joining
+/// the tables of a group observe the data of one table persisted while the data
+/// of another is still only buffered - data already persisting when a group is
+/// enqueued is committed separately. A group may not contain more tables than
+/// the `persist_queue_depth`.
+///
+/// ## Rollups
+///
+/// The writes to the tables of each of the `rollup_rules` are rolled up into
+/// time buckets of the rule's interval, maintaining the count of the values of
+/// each field and the sum, minimum and maximum of the values of each numeric
+/// field per tag set in a `<table>_rollup_<interval>` table created by the
+/// ingester. The aggregates of a bucket are kept in memory until it ended more
+/// than `rollup_state_retention` ago; rows written to a bucket after its state
+/// is evicted, or to a bucket that started before the ingester did, are not
+/// rolled up.
+///
+/// ## Idle Namespace Persistence
+///
+/// When `idle_namespace_persist_threshold` is set, the buffered data of a
+/// namespace is persisted once the namespace has been neither written to nor
+/// queried through this ingester for the threshold duration, releasing the
+/// memory it holds. The namespace remains writable and queryable. The
+/// threshold must be non-zero.
+///
+/// ## Null Column Pruning
+///
+/// When `persist_drop_null_columns` is true, the columns containing only NULL
+/// values in the data of a persist job are removed from its parquet file,
+/// reducing the size of the files of tables with sparse schemas. The columns
+/// remain in the table schema in the catalog, and queries read them as NULL.
+///
+/// ## Catalog Warm-Up
+///
+/// Before the WAL is replayed and writes are accepted, the names of all
+/// namespaces and the metadata of all tables are loaded from the catalog in
+/// bulk, so that the first writes to each of them after a restart do not each
+/// wait on a catalog query. The progress of the warm-up is logged. The
+/// warm-up is best effort and bounded in time - if it fails or times out, or
+/// when `skip_catalog_warm_up` is true, nothing is loaded and each is resolved
+/// from the catalog on first use. The most recently created partitions are
+/// always loaded.
+///
+/// ## Ingest Rate Anomalies
+///
+/// When `ingest_rate_anomaly` is set, the rate of rows written to each
+/// namespace is evaluated every check interval against a trailing baseline of
+/// its rate. A drop or spike beyond the configured percentages is logged and
+/// counted in the `ingester_ingest_rate_anomalies` metric when first detected,
+/// and the ongoing anomalies are returned by
+/// [`IngesterGuard::ingest_anomalies()`]. An anomaly clears once the rate
+/// returns to within the thresholds, or once a new rate has persisted long
+/// enough to become the baseline.
+///
 /// [`MutableBatch::size_data()`]: mutable_batch::MutableBatch::size_data
 #[allow(clippy::too_many_arguments)]
 pub async fn new<F>(
@@ -278,9 +572,31 @@ pub async fn new<F>(
     persist_workers: usize,
     persist_queue_depth: usize,
     persist_hot_partition_cost: usize,
+    exec_priority_slots: NonZeroUsize,
     object_store: ParquetStorage,
     gossip: GossipConfig,
     max_partitions_per_namespace: NonZeroUsize,
+    max_buffered_bytes_per_namespace: Option<NonZeroUsize>,
+    max_rows_per_second_per_namespace: Option<NonZeroU64>,
+    buffer_invariant_check_interval: Option<Duration>,
+    table_usage_flush_interval: Duration,
+    recent_persisted_bytes: usize,
+    persist_memory_limit_bytes: Option<usize>,
+    persist_circuit_breaker_threshold: NonZeroUsize,
+    wal_replay_accept_data_loss: bool,
+    wal_replay_throttle_threshold: Option<Duration>,
+    query_shed_degrade_percent: Option<u8>,
+    query_shed_reject_percent: Option<u8>,
+    query_shed_retry_after: Duration,
+    field_value_policy: Option<FieldValuePolicy>,
+    partition_seal_age: Option<Duration>,
+    persist_barrier_groups: Vec<PersistBarrierGroup>,
+    rollup_rules: Vec<RollupRule>,
+    rollup_state_retention: Duration,
+    idle_namespace_persist_threshold: Option<Duration>,
+    persist_drop_null_columns: bool,
+    skip_catalog_warm_up: bool,
+    ingest_rate_anomaly: Option<IngestRateAnomalyConfig>,
     shutdown: F,
 ) -> Result<IngesterGuard<impl IngesterRpcInterface>, InitError>
 where
@@ -289,22 +605,36 @@ where
     // Initialise a random ID for this ingester instance.
     let ingester_id = IngesterId::new();
 
+    // Load the catalog metadata resolved when buffering writes, unless
+    // disabled.
+    let warm = if skip_catalog_warm_up {
+        info!("skipping catalog metadata warm-up");
+        CatalogWarmUp::default()
+    } else {
+        catalog_warm_up::warm_up(&*catalog).await
+    };
+
     // Initialise the deferred namespace name resolver.
-    let namespace_name_provider: Arc<dyn NamespaceNameProvider> =
-        Arc::new(NamespaceNameResolver::new(
+    let namespace_name_provider: Arc<dyn NamespaceNameProvider> = Arc::new(
+        NamespaceNameResolver::new(
             persist_background_fetch_time,
             Arc::clone(&catalog),
             BackoffConfig::default(),
             Arc::clone(&metrics),
-        ));
+        )
+        .with_warm_names(warm.namespace_names),
+    );
 
     // Initialise the deferred table metadata resolver.
-    let table_provider: Arc<dyn TableProvider> = Arc::new(TableResolver::new(
-        persist_background_fetch_time,
-        Arc::clone(&catalog),
-        BackoffConfig::default(),
-        Arc::clone(&metrics),
-    ));
+    let table_provider: Arc<dyn TableProvider> = Arc::new(
+        TableResolver::new(
+            persist_background_fetch_time,
+            Arc::clone(&catalog),
+            BackoffConfig::default(),
+            Arc::clone(&metrics),
+        )
+        .with_warm_tables(warm.tables),
+    );
 
     // Read the most recently created partitions.
     //
@@ -400,19 +730,52 @@ where
         }
     };
 
+    // Account the rows and bytes persisted for each table.
+    let usage = Arc::new(UsageTracker::new(catalog.time_provider(), &metrics));
+    let persist_observer = UsageObserver::new(persist_observer, Arc::clone(&usage));
+
+    // Wrap the executor to prioritise query work over persist compactions.
+    let priority_exec = PriorityExecutor::new(persist_executor, exec_priority_slots, &metrics);
+
+    // Optionally limit the memory each persist compaction may use.
+    let compaction_memory_limit = persist_memory_limit_bytes
+        .map(CompactionMemoryLimit::new)
+        .transpose()
+        .map_err(InitError::CompactionSpill)?;
+
+    // Optionally persist the partitions of groups of tables together.
+    let persist_barrier = (!persist_barrier_groups.is_empty())
+        .then(|| Arc::new(PersistBarrier::new(persist_barrier_groups)));
+    if let Some(tables) = persist_barrier.as_ref().map(|b| b.max_group_len()) {
+        if tables > persist_queue_depth {
+            return Err(InitError::PersistBarrierGroupTooLarge {
+                tables,
+                persist_queue_depth,
+            });
+        }
+    }
+
     // Spawn the persist workers to compact partition data, convert it into
     // Parquet files, and upload them to object storage.
     let persist_handle = PersistHandle::new(
         persist_workers,
         persist_queue_depth,
         Arc::clone(&ingest_state),
-        persist_executor,
+        priority_exec.clone(),
         object_store,
         Arc::clone(&catalog),
         persist_observer,
         CatalogColumnMapResolver::new(Arc::clone(&catalog)),
+        recent_persisted_bytes,
+        compaction_memory_limit,
+        persist_circuit_breaker_threshold,
+        persist_drop_null_columns,
         &metrics,
     );
+    let persist_handle = match &persist_barrier {
+        Some(barrier) => persist_handle.with_persist_barrier(Arc::clone(barrier)),
+        None => persist_handle,
+    };
     let persist_handle = Arc::new(persist_handle);
 
     // Instantiate a post-write observer for hot partition persistence.
@@ -439,6 +802,11 @@ where
         Arc::clone(&metrics),
     ));
 
+    // Resolve the partitions persisted together from the buffer.
+    if let Some(barrier) = &persist_barrier {
+        barrier.set_partitions(Arc::clone(&buffer) as _);
+    }
+
     // Start the WAL reference actor and then replay the WAL log files, if any.
     // The tokio handle does not need retained here as the actor handle is
     // responsible for aborting the actor's run loop when dropped.
@@ -470,26 +838,68 @@ where
         &buffer,
         Arc::clone(&persist_handle),
         Arc::clone(&ingest_state),
+        wal_replay_accept_data_loss,
+        wal_replay_throttle_threshold,
         &metrics,
     )
     .await
     .map_err(|e| InitError::WalReplay(e.into()))?;
 
     // Build the chain of DmlSink that forms the write path.
+    //
+    // Usage is recorded only for writes applied through the write path, and
+    // not during the WAL replay above, as the usage of replayed writes was
+    // recorded when they were first applied.
+    //
+    // Rollup rows are added to writes before they are committed to the WAL,
+    // so they are replayed with the writes they were derived from.
+    //
+    // Namespace quotas are enforced before a write is rolled up or committed
+    // to the WAL, so rejected writes leave no trace.
+    //
+    // The ingest rate is measured in the rows written by clients, excluding
+    // the rollup rows derived from them.
+    let ingest_rate = ingest_rate_anomaly.map(|config| {
+        Arc::new(IngestRateDetector::new(
+            config,
+            catalog.time_provider(),
+            &metrics,
+        ))
+    });
     let write_path = DmlSinkInstrumentation::new(
         "write_apply",
         DmlSinkTracing::new(
-            DmlSinkTracing::new(
-                WalSink::new(
-                    DmlSinkInstrumentation::new(
-                        "buffer",
-                        DmlSinkTracing::new(Arc::clone(&buffer), "buffer"),
+            NamespaceQuotaSink::new(
+                IngestRateSink::new(
+                    RollupSink::new(
+                        DmlSinkTracing::new(
+                            WalSink::new(
+                                UsageSink::new(
+                                    DmlSinkInstrumentation::new(
+                                        "buffer",
+                                        DmlSinkTracing::new(Arc::clone(&buffer), "buffer"),
+                                        &metrics,
+                                    ),
+                                    Arc::clone(&usage),
+                                ),
+                                Arc::clone(&wal),
+                                wal_reference_handle.clone(),
+                            ),
+                            "wal",
+                        ),
+                        rollup_rules,
+                        Arc::clone(&catalog),
+                        catalog.time_provider(),
+                        rollup_state_retention,
                         &metrics,
                     ),
-                    Arc::clone(&wal),
-                    wal_reference_handle.clone(),
+                    ingest_rate.as_ref().map(Arc::clone),
                 ),
-                "wal",
+                Arc::clone(&buffer),
+                catalog.time_provider(),
+                max_buffered_bytes_per_namespace,
+                max_rows_per_second_per_namespace,
+                &metrics,
             ),
             "write_apply",
         ),
@@ -497,7 +907,8 @@ where
     );
 
     // And the chain of QueryExec that forms the read path.
-    let read_path = QueryResultInstrumentation::new(Arc::clone(&buffer), &metrics);
+    let read_path = QueryExecPriority::new(Arc::clone(&buffer), priority_exec);
+    let read_path = QueryResultInstrumentation::new(read_path, &metrics);
     let read_path = QueryExecInstrumentation::new(
         "buffer",
         QueryExecTracing::new(read_path, "buffer"),
@@ -513,6 +924,33 @@ where
         Arc::clone(&persist_handle),
     ));
 
+    // Optionally spawn a background task to periodically validate the buffer
+    // invariants.
+    let invariant_check_task = buffer_invariant_check_interval
+        .map(|period| spawn_invariant_check(Arc::clone(&buffer), period, &metrics));
+
+    // Optionally spawn a background task to periodically persist the data of
+    // idle namespaces.
+    let idle_namespace_task = idle_namespace_persist_threshold.map(|threshold| {
+        spawn_idle_namespace_persist(
+            Arc::clone(&buffer),
+            Arc::clone(&persist_handle),
+            threshold,
+            &metrics,
+        )
+    });
+
+    // Spawn a background task to periodically add the accumulated table usage
+    // to the catalog.
+    let usage_flush = Arc::new(UsageFlush::new(usage, Arc::clone(&catalog), &metrics));
+    let usage_flush_task = spawn_usage_flush(Arc::clone(&usage_flush), table_usage_flush_interval);
+
+    // Optionally spawn a background task to periodically evaluate the ingest
+    // rate of each namespace.
+    let ingest_rate_task = ingest_rate
+        .as_ref()
+        .map(|detector| spawn_ingest_rate_check(Arc::clone(detector)));
+
     // Restore the highest sequence number from the WAL files, and default to 0
     // if there were no files to replay.
     //
@@ -532,23 +970,73 @@ where
         Arc::clone(&persist_handle),
         Arc::clone(&wal),
         wal_reference_handle,
+        usage_flush,
     ));
 
+    // Account for the memory held by the buffer and the recently persisted
+    // data cache, served for debugging.
+    let memory = MemoryAccountant::new(
+        Arc::clone(&buffer) as _,
+        persist_handle.recent_persisted().map(Arc::clone),
+    );
+
+    let partition_sealer = partition_seal_age.map(|max_age| {
+        Arc::new(PartitionSealer::new(
+            max_age,
+            Arc::clone(&catalog),
+            catalog.time_provider(),
+            &metrics,
+        ))
+    });
+
+    let rpc = GrpcDelegate::new(
+        Arc::new(write_path),
+        Arc::new(read_path),
+        timestamp,
+        ingest_state,
+        ingester_id,
+        catalog,
+        metrics,
+        buffer,
+        Arc::clone(&persist_handle),
+    )
+    .with_persist_jobs(Arc::clone(persist_handle.jobs()));
+
+    // Shed query load under buffer pressure, if configured.
+    let rpc = match LoadShedPolicy::new(
+        query_shed_degrade_percent,
+        query_shed_reject_percent,
+        query_shed_retry_after,
+    ) {
+        Some(policy) => rpc.with_query_load_shedding(policy, persist_handle.queue_occupancy()),
+        None => rpc,
+    };
+
+    let rpc = match field_value_policy {
+        Some(policy) => rpc.with_field_value_policy(policy),
+        None => rpc,
+    };
+
+    let rpc = match partition_sealer {
+        Some(sealer) => rpc.with_partition_sealer(sealer),
+        None => rpc,
+    };
+
+    // Serve the persist fault injector over the test-only gRPC service.
+    #[cfg(feature = "fault_injection")]
+    let rpc = rpc.with_fault_injector(Arc::clone(persist_handle.faults()));
+
     Ok(IngesterGuard {
-        rpc: GrpcDelegate::new(
-            Arc::new(write_path),
-            Arc::new(read_path),
-            timestamp,
-            ingest_state,
-            ingester_id,
-            catalog,
-            metrics,
-            buffer,
-            persist_handle,
-        ),
+        rpc,
         rotation_task,
         disk_metric_task,
+        invariant_check_task,
+        idle_namespace_task,
+        usage_flush_task,
+        ingest_rate_task,
         graceful_shutdown_handler: shutdown_task,
         shutdown_complete: shutdown_rx.shared(),
+        memory,
+        ingest_rate,
     })
 }
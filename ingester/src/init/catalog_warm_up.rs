@@ -0,0 +1,91 @@
+//! Bulk loading of the catalog metadata resolved when buffering writes, before
+//! the ingester starts accepting them.
+
+use std::{collections::HashMap, time::Duration};
+
+use data_types::{NamespaceId, TableId};
+use iox_catalog::interface::{Catalog, SoftDeletedRows};
+use observability_deps::tracing::*;
+use tokio::time::Instant;
+
+use crate::buffer_tree::{namespace::NamespaceName, table::metadata::TableMetadata};
+
+/// The maximum duration the warm-up may delay the startup of the ingester.
+const WARM_UP_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// The catalog metadata loaded by [`warm_up()`].
+#[derive(Debug, Default)]
+pub(super) struct CatalogWarmUp {
+    pub(super) namespace_names: HashMap<NamespaceId, NamespaceName>,
+    pub(super) tables: HashMap<TableId, TableMetadata>,
+}
+
+/// Load the names of all namespaces and the metadata of all tables from
+/// `catalog`.
+///
+/// Without it, each namespace and table first written to (or replayed from
+/// the WAL) after a restart is resolved with its own catalog query, causing a
+/// burst of catalog load and slow first writes.
+///
+/// The warm-up is best effort: if it fails, or does not complete within
+/// [`WARM_UP_TIMEOUT`], nothing is loaded and each namespace and table is
+/// resolved from the catalog on first use.
+pub(super) async fn warm_up(catalog: &dyn Catalog) -> CatalogWarmUp {
+    let started_at = Instant::now();
+    info!("warming up catalog metadata");
+
+    match tokio::time::timeout(WARM_UP_TIMEOUT, load(catalog, started_at)).await {
+        Ok(Ok(v)) => v,
+        Ok(Err(error)) => {
+            warn!(%error, "failed to warm up catalog metadata, resolving lazily");
+            CatalogWarmUp::default()
+        }
+        Err(_) => {
+            warn!(
+                timeout = ?WARM_UP_TIMEOUT,
+                "catalog metadata warm-up timed out, resolving lazily"
+            );
+            CatalogWarmUp::default()
+        }
+    }
+}
+
+async fn load(
+    catalog: &dyn Catalog,
+    started_at: Instant,
+) -> Result<CatalogWarmUp, iox_catalog::interface::Error> {
+    let mut repos = catalog.repositories().await;
+
+    // Soft-deleted namespaces are included, as writes for them are still
+    // accepted.
+    let namespace_names = repos
+        .namespaces()
+        .list(SoftDeletedRows::AllRows)
+        .await?
+        .into_iter()
+        .map(|ns| (ns.id, NamespaceName::from(ns.name)))
+        .collect::<HashMap<_, _>>();
+    info!(
+        n_namespaces = namespace_names.len(),
+        elapsed = ?started_at.elapsed(),
+        "loaded namespace names"
+    );
+
+    let tables = repos
+        .tables()
+        .list()
+        .await?
+        .into_iter()
+        .map(|t| (t.id, TableMetadata::from(t)))
+        .collect::<HashMap<_, _>>();
+    info!(
+        n_tables = tables.len(),
+        elapsed = ?started_at.elapsed(),
+        "loaded table metadata"
+    );
+
+    Ok(CatalogWarmUp {
+        namespace_names,
+        tables,
+    })
+}
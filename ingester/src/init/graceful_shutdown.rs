@@ -10,6 +10,7 @@ use crate::{
     partition_iter::PartitionIter,
     persist::{drain_buffer::persist_partitions, queue::PersistQueue},
     query::projection::OwnedProjection,
+    usage::UsageFlush,
     wal::reference_tracker::WalReferenceHandle,
 };
 
@@ -31,7 +32,8 @@ const SHUTDOWN_POLL_INTERVAL: Duration = Duration::from_secs(1);
 /// [`PartitionData::mark_persisting()`] but not yet enqueued).
 ///
 /// Ingest is blocked by setting [`IngestStateError::GracefulStop`] in the
-/// [`IngestState`].
+/// [`IngestState`], and the table usage accumulated since the last periodic
+/// flush is written to the catalog once all data is persisted.
 ///
 /// [`PartitionData::mark_persisting()`]:
 ///     crate::buffer_tree::partition::PartitionData::mark_persisting()
@@ -43,6 +45,7 @@ pub(super) async fn graceful_shutdown_handler<F, T, P>(
     persist: P,
     wal: Arc<wal::Wal>,
     wal_reference_handle: WalReferenceHandle,
+    usage: Arc<UsageFlush>,
 ) where
     F: Future<Output = CancellationToken> + Send,
     T: PartitionIter + Sync,
@@ -130,6 +133,9 @@ pub(super) async fn graceful_shutdown_handler<F, T, P>(
     // to drop to empty.
     empty_waker.await;
 
+    // All writes and persists have been accounted for.
+    usage.flush().await;
+
     info!("persisted all data - stopping ingester");
 
     // Stop the RPC server (and therefore stop accepting new queries)
@@ -143,16 +149,19 @@ mod tests {
     use std::{future::ready, sync::Arc, task::Poll};
 
     use assert_matches::assert_matches;
-    use data_types::SequenceNumber;
+    use data_types::{NamespaceId, SequenceNumber, TableId};
     use futures::FutureExt;
     use mutable_batch_lp::test_helpers::lp_to_mutable_batch;
     use parking_lot::Mutex;
     use test_helpers::timeout::FutureTimeout;
 
+    use iox_catalog::{interface::Catalog, mem::MemCatalog};
+
     use crate::{
         buffer_tree::partition::PartitionData,
         persist::queue::mock::MockPersistQueue,
         test_util::{PartitionDataBuilder, ARBITRARY_TABLE_NAME},
+        usage::UsageTracker,
     };
 
     use super::*;
@@ -173,6 +182,16 @@ mod tests {
         Arc::new(Mutex::new(partition))
     }
 
+    // Initialise a table usage flush writing to an in-memory catalog.
+    fn new_usage_flush() -> (Arc<UsageTracker>, Arc<UsageFlush>) {
+        let metrics = metric::Registry::default();
+        let catalog: Arc<dyn Catalog> =
+            Arc::new(MemCatalog::new(Arc::new(metric::Registry::default())));
+        let tracker = Arc::new(UsageTracker::new(catalog.time_provider(), &metrics));
+        let flush = Arc::new(UsageFlush::new(Arc::clone(&tracker), catalog, &metrics));
+        (tracker, flush)
+    }
+
     // Initialise a WAL.
     async fn new_wal() -> (tempfile::TempDir, Arc<wal::Wal>) {
         let dir = tempfile::tempdir().expect("failed to get temporary WAL directory");
@@ -202,6 +221,10 @@ mod tests {
 
         let partition = new_partition();
 
+        // Usage recorded before the shutdown is flushed by it.
+        let (usage, usage_flush) = new_usage_flush();
+        usage.record_buffered(NamespaceId::new(1), TableId::new(1), 1, 1);
+
         let rpc_stop = CancellationToken::new();
         let (tx, rx) = oneshot::channel();
         graceful_shutdown_handler(
@@ -212,6 +235,7 @@ mod tests {
             Arc::clone(&persist),
             Arc::clone(&wal),
             wal_reference_handle,
+            usage_flush,
         )
         .await;
 
@@ -221,6 +245,7 @@ mod tests {
             .expect("shutdown task panicked");
 
         assert!(rpc_stop.is_cancelled());
+        assert!(usage.take().is_empty());
 
         // Assert the data was persisted
         let persist_calls = persist.calls();
@@ -269,6 +294,7 @@ mod tests {
             Arc::clone(&persist),
             Arc::clone(&wal),
             wal_reference_handle,
+            new_usage_flush().1,
         ));
 
         // Wait a small duration of time for the first buffer emptiness check to
@@ -381,6 +407,7 @@ mod tests {
             Arc::clone(&persist),
             Arc::clone(&wal),
             wal_reference_handle.clone(),
+            new_usage_flush().1,
         ));
 
         // Wait for the shutdown to complete.
@@ -0,0 +1,118 @@
+//! Accounting of the memory held by the data-carrying subsystems of the
+//! ingester, for diagnosing memory incidents in a running instance.
+
+use std::sync::Arc;
+
+use crate::{buffer_tree::partition::recent::RecentPersistCache, partition_iter::PartitionIter};
+
+/// The approximate memory held by each data-carrying subsystem of the
+/// ingester, as returned by [`IngesterGuard::memory_usage()`].
+///
+/// The values are estimates derived from the size of the buffered data, and
+/// exclude the overhead of the allocator and of the buffer tree itself.
+///
+/// [`IngesterGuard::memory_usage()`]: super::IngesterGuard::memory_usage()
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MemoryUsage {
+    /// The number of partitions in the buffer tree.
+    pub partitions: usize,
+
+    /// The estimated number of bytes of writes buffered and not yet
+    /// persisting, including the snapshots generated from them by queries.
+    pub buffered_bytes: usize,
+
+    /// The number of bytes of snapshots currently being persisted.
+    pub persisting_bytes: usize,
+
+    /// The number of bytes of parquet data retained by the cache of recently
+    /// persisted data.
+    pub recent_persisted_bytes: usize,
+}
+
+/// Computes the [`MemoryUsage`] of an ingester on demand.
+#[derive(Debug)]
+pub(super) struct MemoryAccountant {
+    partitions: Arc<dyn PartitionIter + Sync>,
+    recent_persisted: Option<Arc<RecentPersistCache>>,
+}
+
+impl MemoryAccountant {
+    pub(super) fn new(
+        partitions: Arc<dyn PartitionIter + Sync>,
+        recent_persisted: Option<Arc<RecentPersistCache>>,
+    ) -> Self {
+        Self {
+            partitions,
+            recent_persisted,
+        }
+    }
+
+    /// Sum the memory held by all partitions and caches.
+    ///
+    /// Each partition is locked in turn, so this is an `O(n)` operation where
+    /// `n` is the number of buffered partitions.
+    pub(super) fn memory_usage(&self) -> MemoryUsage {
+        let mut usage = MemoryUsage {
+            recent_persisted_bytes: self
+                .recent_persisted
+                .as_ref()
+                .map(|c| c.used_bytes())
+                .unwrap_or_default(),
+            ..Default::default()
+        };
+
+        for p in self.partitions.partition_iter() {
+            let p = p.lock();
+            usage.partitions += 1;
+            usage.buffered_bytes += p.persist_cost_estimate();
+            usage.persisting_bytes += p.persisting_size();
+        }
+
+        usage
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+    use data_types::SequenceNumber;
+    use mutable_batch_lp::test_helpers::lp_to_mutable_batch;
+    use parking_lot::Mutex;
+
+    use super::*;
+    use crate::test_util::PartitionDataBuilder;
+
+    #[test]
+    fn test_memory_usage() {
+        let cache = Arc::new(RecentPersistCache::new(1024));
+        let mut p = PartitionDataBuilder::new().build();
+
+        let mb = lp_to_mutable_batch(r#"bananas,city=London people=2,pigeons="millions" 10"#).1;
+        p.buffer_write(mb, SequenceNumber::new(1))
+            .expect("write should succeed");
+        let persisting = p.mark_persisting().expect("must contain data");
+        p.retain_persisted(&cache, &persisting, Bytes::from_static(b"parquet"));
+
+        let mb = lp_to_mutable_batch(r#"bananas,city=Madrid people=4,pigeons="none" 20"#).1;
+        p.buffer_write(mb, SequenceNumber::new(2))
+            .expect("write should succeed");
+
+        let buffered = p.persist_cost_estimate();
+        let persisting = p.persisting_size();
+        assert!(buffered > 0);
+        assert!(persisting > 0);
+
+        let partitions = vec![Arc::new(Mutex::new(p))];
+        let accountant = MemoryAccountant::new(Arc::new(partitions), Some(cache));
+
+        assert_eq!(
+            accountant.memory_usage(),
+            MemoryUsage {
+                partitions: 1,
+                buffered_bytes: buffered,
+                persisting_bytes: persisting,
+                recent_persisted_bytes: 7,
+            }
+        );
+    }
+}
@@ -1,3 +1,5 @@
+mod throttle;
+
 use std::fmt::Debug;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
@@ -5,7 +7,7 @@ use std::time::{Duration, Instant};
 use async_trait::async_trait;
 use data_types::{NamespaceId, PartitionKey, SequenceNumber, TableId};
 use generated_types::influxdata::iox::wal::v1::sequenced_wal_op::Op;
-use metric::U64Counter;
+use metric::{U64Counter, U64Gauge};
 use mutable_batch_pb::decode::decode_database_batch;
 use observability_deps::tracing::*;
 use thiserror::Error;
@@ -19,6 +21,7 @@ use crate::{
     partition_iter::PartitionIter,
     persist::{drain_buffer::persist_partitions, queue::PersistQueue},
 };
+use throttle::ReplayThrottle;
 
 /// This duration controls how long to wait between reads of the ingest state
 /// when WAL op replay is blocked on an unhealthy ingest state.
@@ -36,6 +39,29 @@ pub enum WalReplayError {
     #[error("failed to read wal entry: {0}")]
     ReadEntry(wal::Error, Option<SequenceNumber>),
 
+    /// A WAL segment that is not the most recent segment could not be read in
+    /// full, and replaying the remaining segments would lose the unreadable
+    /// entries.
+    ///
+    /// This error is returned instead of skipping the unreadable entries
+    /// unless data loss is explicitly accepted.
+    #[error(
+        "data loss: wal segment {segment_id} is unreadable after {} ({source}) - \
+        the remaining entries of this segment cannot be replayed; restart with \
+        --wal-replay-accept-data-loss to skip them and continue",
+        last_replayed.map(|v| format!("sequence number {}", v.get()))
+            .unwrap_or_else(|| "the start of the segment".to_string())
+    )]
+    DataLoss {
+        /// The segment that could not be read in full.
+        segment_id: SegmentId,
+        /// The highest sequence number successfully replayed from the segment,
+        /// if any.
+        last_replayed: Option<SequenceNumber>,
+        /// The underlying read error.
+        source: wal::Error,
+    },
+
     /// An error converting the WAL entry into a [`IngestOp`].
     #[error("failed converting wal entry to ingest operation: {0}")]
     MapToDml(#[from] mutable_batch_pb::decode::Error),
@@ -106,11 +132,23 @@ impl SegmentedWalOpBatchReader for wal::ClosedSegmentFileReader {
 
 /// Replay all the entries in `wal` to `sink`, returning the maximum observed
 /// [`SequenceNumber`].
+///
+/// If a segment other than the most recent cannot be read in full, a
+/// [`WalReplayError::DataLoss`] error is returned identifying the segment and
+/// the last entry replayed from it, unless `accept_data_loss` is true, in which
+/// case the unreadable entries are skipped and replay continues.
+///
+/// If `throttle_threshold` is set, replay is slowed down while applying an op
+/// to `sink` takes longer than the threshold, typically because resolving the
+/// partitions of the op in the catalog is slow.
+#[allow(clippy::too_many_arguments)]
 pub async fn replay<W, T, P>(
     wal: &W,
     sink: &T,
     persist: P,
     ingest_state: Arc<IngestState>,
+    accept_data_loss: bool,
+    throttle_threshold: Option<Duration>,
     metrics: &metric::Registry,
 ) -> Result<Option<SequenceNumber>, WalReplayError>
 where
@@ -149,6 +187,8 @@ where
     let file_count_success_metric = replayed_file_count_metric.recorder(&[("result", "success")]);
     let file_count_error_truncated_metric =
         replayed_file_count_metric.recorder(&[("result", "error"), ("reason", "truncated")]);
+    let file_count_error_data_loss_metric =
+        replayed_file_count_metric.recorder(&[("result", "error"), ("reason", "data_loss")]);
 
     let op_count_metric = metrics.register_metric::<U64Counter>(
         "ingester_wal_replay_ops",
@@ -157,6 +197,24 @@ where
     let ok_op_count_metric = op_count_metric.recorder(&[("outcome", "success")]);
     let empty_op_count_metric = op_count_metric.recorder(&[("outcome", "skipped_empty")]);
 
+    // The total size of the WAL files to replay, and the size of the files
+    // replayed so far, reporting the progress of the replay.
+    let bytes_total_metric = metrics
+        .register_metric::<U64Gauge>(
+            "ingester_wal_replay_bytes_total",
+            "Total size of the WAL files to be replayed",
+        )
+        .recorder(&[]);
+    let bytes_replayed_metric = metrics
+        .register_metric::<U64Gauge>(
+            "ingester_wal_replay_bytes_replayed",
+            "Size of the WAL files that have been replayed",
+        )
+        .recorder(&[]);
+    bytes_total_metric.set(files.iter().map(|(_, size)| size).sum());
+
+    let mut throttle = ReplayThrottle::new(throttle_threshold, metrics);
+
     let n_files = files.len();
     info!(n_files, "found wal files for replay");
 
@@ -194,6 +252,7 @@ where
             &ok_op_count_metric,
             &empty_op_count_metric,
             &ingest_state,
+            &mut throttle,
         )
         .await;
         if replay_result.is_ok() {
@@ -225,6 +284,7 @@ where
                     );
                 }
 
+                bytes_replayed_metric.inc(file_size);
                 continue;
             }
             // If the replay results in an underlying end of file error when
@@ -245,6 +305,26 @@ where
                 file_count_error_truncated_metric.inc(1);
                 warn!(%e, %file_id, "detected truncated WAL write, ending replay for file early");
             }
+            // Any other read error leaves entries in the segment that cannot
+            // be replayed.
+            Err(WalReplayError::ReadEntry(source, seq)) => {
+                if !accept_data_loss {
+                    return Err(WalReplayError::DataLoss {
+                        segment_id: file_id,
+                        last_replayed: seq,
+                        source,
+                    });
+                }
+
+                max_sequence = max_sequence.max(seq);
+                file_count_error_data_loss_metric.inc(1);
+                error!(
+                    error=%source,
+                    %file_id,
+                    last_replayed_sequence_number=?seq,
+                    "unreadable WAL segment entries, accepting data loss and skipping them"
+                );
+            }
             Err(e) => return Err(e),
         };
 
@@ -271,6 +351,7 @@ where
             size = file_size,
             "dropped persisted wal segment"
         );
+        bytes_replayed_metric.inc(file_size);
     }
 
     info!(
@@ -297,6 +378,7 @@ async fn replay_file<T, F>(
     ok_op_count_metric: &U64Counter,
     empty_op_count_metric: &U64Counter,
     ingest_state: &Arc<IngestState>,
+    throttle: &mut ReplayThrottle,
 ) -> Result<Option<SequenceNumber>, WalReplayError>
 where
     T: DmlSink,
@@ -385,10 +467,14 @@ where
                 "apply wal op"
             );
 
-            // Apply the operation to the provided DML sink
+            // Apply the operation to the provided DML sink, pacing the replay
+            // while applies are slow.
+            throttle.wait().await;
+            let apply_start = Instant::now();
             sink.apply(IngestOp::Write(op))
                 .await
                 .map_err(Into::<DmlError>::into)?;
+            throttle.observe(apply_start.elapsed());
 
             ok_op_count_metric.inc(1);
         }
@@ -408,7 +494,7 @@ mod tests {
     use async_trait::async_trait;
     use hashbrown::HashSet;
     use itertools::Itertools;
-    use metric::{assert_counter, Attributes};
+    use metric::{assert_counter, Attributes, Metric};
     use parking_lot::Mutex;
     use test_helpers::timeout::FutureTimeout;
     use wal::Wal;
@@ -608,6 +694,8 @@ mod tests {
             &mock_iter,
             Arc::clone(&persist),
             Arc::clone(&ingest_state),
+            false,
+            None,
             &metrics,
         )
         .with_timeout_panic(Duration::from_secs(2))
@@ -686,6 +774,21 @@ mod tests {
             labels = Attributes::from(&[("outcome", "skipped_empty")]),
             value = 1,
         );
+
+        // All the bytes of the replayed segments are reported as replayed.
+        let bytes = |name| {
+            metrics
+                .get_instrument::<Metric<U64Gauge>>(name)
+                .expect("failed to find metric")
+                .get_observer(&Attributes::from(&[]))
+                .expect("failed to find metric attributes")
+                .fetch()
+        };
+        assert!(bytes("ingester_wal_replay_bytes_total") > 0);
+        assert_eq!(
+            bytes("ingester_wal_replay_bytes_replayed"),
+            bytes("ingester_wal_replay_bytes_total")
+        );
     }
 
     #[derive(Debug)]
@@ -836,6 +939,8 @@ mod tests {
             &mock_iter,
             Arc::clone(&persist),
             Arc::new(IngestState::default()),
+            false,
+            None,
             &metrics,
         )
         .await
@@ -902,12 +1007,15 @@ mod tests {
             &mock_iter,
             Arc::clone(&persist),
             Arc::new(IngestState::default()),
+            false,
+            None,
             &metrics,
         )
         .await;
         assert_matches!(
             replay_result,
-            Err(WalReplayError::ReadEntry(_, Some(id))) => {
+            Err(WalReplayError::DataLoss { segment_id, last_replayed: Some(id), .. }) => {
+                assert_eq!(segment_id, SegmentId::new(2));
                 assert_eq!(id, SequenceNumber::new(2));
             }
         );
@@ -920,6 +1028,66 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_replay_accept_data_loss() {
+        let wal = MockWalReader::new(
+            [
+                MockSegmentedWalOpBatchReader::new(SegmentId::new(1)).with_entry_results([Ok(
+                    vec![arbitrary_sequenced_wal_op(SequenceNumber::new(1))],
+                )]),
+                MockSegmentedWalOpBatchReader::new(SegmentId::new(2)).with_entry_results([
+                    Ok(vec![arbitrary_sequenced_wal_op(SequenceNumber::new(2))]),
+                    Err(wal::Error::UnableToReadNextOps {
+                        source: wal::blocking::ReaderError::UnableToReadData {
+                            source: std::io::Error::new(
+                                std::io::ErrorKind::InvalidData,
+                                "gremlins in the drive",
+                            ),
+                        },
+                    }),
+                ]),
+                MockSegmentedWalOpBatchReader::new(SegmentId::new(3)).with_entry_results([Ok(
+                    vec![arbitrary_sequenced_wal_op(SequenceNumber::new(4))],
+                )]),
+            ],
+            [1, 2, 3],
+        );
+
+        let persist = Arc::new(MockPersistQueue::default());
+        let mock_sink = MockDmlSink::default().with_apply_return(vec![Ok(()), Ok(()), Ok(())]);
+        let mock_iter = MockIter {
+            sink: mock_sink,
+            partitions: vec![],
+        };
+        let metrics = metric::Registry::default();
+
+        let max_sequence_number = replay(
+            &wal,
+            &mock_iter,
+            Arc::clone(&persist),
+            Arc::new(IngestState::default()),
+            true,
+            None,
+            &metrics,
+        )
+        .await
+        .expect("data loss is accepted");
+
+        // The unreadable entries are skipped, and the remaining segments
+        // replayed.
+        assert_eq!(max_sequence_number, Some(SequenceNumber::new(4)));
+        assert_eq!(mock_iter.sink.get_calls().len(), 3);
+        assert!(wal.closed_segments().is_empty());
+
+        assert_counter!(
+            metrics,
+            U64Counter,
+            "ingester_wal_replay_files_finished",
+            labels = Attributes::from(&[("result", "error"), ("reason", "data_loss")]),
+            value = 1,
+        );
+    }
+
     #[tokio::test]
     async fn test_replay_respects_ingest_state() {
         let metrics = metric::Registry::default();
@@ -0,0 +1,124 @@
+//! Adaptive throttling of WAL replay to protect the catalog.
+//!
+//! Replaying a large WAL at startup applies writes as fast as they can be
+//! read, and each write to a partition not yet in the buffer resolves the
+//! partition in the catalog. A replay of many partitions therefore issues a
+//! burst of catalog requests, competing with (and slowing down) the catalog
+//! requests of the rest of the cluster.
+//!
+//! Applying a replayed op takes microseconds unless it waits on the catalog,
+//! so the [`ReplayThrottle`] treats an apply slower than the configured
+//! threshold as a signal of catalog latency, and paces the replay of
+//! subsequent ops until applies are fast again.
+
+use std::time::Duration;
+
+use metric::U64Gauge;
+
+/// The delay between ops introduced by the first slow apply.
+const MIN_DELAY: Duration = Duration::from_millis(10);
+
+/// The maximum delay between ops.
+const MAX_DELAY: Duration = Duration::from_secs(1);
+
+/// Paces WAL replay when applying ops becomes slow.
+///
+/// The delay between ops is doubled after every apply slower than the
+/// threshold (up to [`MAX_DELAY`]), and halved after every apply faster than
+/// it, dropping to zero once below [`MIN_DELAY`].
+#[derive(Debug)]
+pub(super) struct ReplayThrottle {
+    threshold: Option<Duration>,
+    delay: Duration,
+
+    delay_gauge: U64Gauge,
+}
+
+impl ReplayThrottle {
+    /// Construct a throttle pacing replay while applies take longer than
+    /// `threshold`, or never if `threshold` is [`None`].
+    pub(super) fn new(threshold: Option<Duration>, metrics: &metric::Registry) -> Self {
+        let delay_gauge = metrics
+            .register_metric::<U64Gauge>(
+                "ingester_wal_replay_throttle_delay_ms",
+                "delay between replayed wal ops introduced by slow applies",
+            )
+            .recorder(&[]);
+
+        Self {
+            threshold,
+            delay: Duration::ZERO,
+            delay_gauge,
+        }
+    }
+
+    /// Wait for the current delay before applying the next op.
+    pub(super) async fn wait(&self) {
+        if !self.delay.is_zero() {
+            tokio::time::sleep(self.delay).await;
+        }
+    }
+
+    /// Adjust the delay given the time taken to apply an op.
+    pub(super) fn observe(&mut self, apply_duration: Duration) {
+        let Some(threshold) = self.threshold else {
+            return;
+        };
+
+        self.delay = if apply_duration > threshold {
+            (self.delay * 2).clamp(MIN_DELAY, MAX_DELAY)
+        } else if self.delay / 2 < MIN_DELAY {
+            Duration::ZERO
+        } else {
+            self.delay / 2
+        };
+
+        self.delay_gauge.set(self.delay.as_millis() as u64);
+    }
+
+    /// Return the current delay between ops.
+    #[cfg(test)]
+    fn delay(&self) -> Duration {
+        self.delay
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const THRESHOLD: Duration = Duration::from_millis(100);
+    const SLOW: Duration = Duration::from_millis(101);
+    const FAST: Duration = Duration::from_millis(1);
+
+    #[test]
+    fn test_throttle() {
+        let mut throttle = ReplayThrottle::new(Some(THRESHOLD), &metric::Registry::default());
+        assert_eq!(throttle.delay(), Duration::ZERO);
+
+        // Slow applies increase the delay exponentially, up to the maximum.
+        throttle.observe(SLOW);
+        assert_eq!(throttle.delay(), MIN_DELAY);
+        throttle.observe(SLOW);
+        assert_eq!(throttle.delay(), MIN_DELAY * 2);
+        for _ in 0..20 {
+            throttle.observe(SLOW);
+        }
+        assert_eq!(throttle.delay(), MAX_DELAY);
+
+        // Fast applies decrease it until the throttle is released.
+        throttle.observe(FAST);
+        assert_eq!(throttle.delay(), MAX_DELAY / 2);
+        for _ in 0..20 {
+            throttle.observe(FAST);
+        }
+        assert_eq!(throttle.delay(), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_throttle_disabled() {
+        let mut throttle = ReplayThrottle::new(None, &metric::Registry::default());
+        throttle.observe(Duration::from_secs(42));
+        assert_eq!(throttle.delay(), Duration::ZERO);
+    }
+}
@@ -249,15 +249,21 @@ mod cancellation_safe;
 mod deferred_load;
 mod dml_payload;
 mod dml_sink;
+mod fault_injection;
 mod gossip;
+mod ingest_rate;
 mod ingest_state;
 mod ingester_id;
 mod partition_iter;
+mod partition_seal;
 mod persist;
+mod priority_executor;
 mod query;
 mod query_adaptor;
+mod rollup;
 pub(crate) mod server;
 mod timestamp_oracle;
+mod usage;
 mod wal;
 
 #[cfg(test)]
@@ -0,0 +1,197 @@
+//! Sealing of partitions whose time range ended too long ago to accept
+//! further writes.
+
+use std::{sync::Arc, time::Duration};
+
+use data_types::{
+    partition_template::{build_column_values, ColumnValue, TablePartitionTemplateOverride},
+    PartitionKey, TableId,
+};
+use hashbrown::HashMap;
+use iox_catalog::interface::Catalog;
+use iox_time::{Time, TimeProvider};
+use metric::U64Counter;
+use observability_deps::tracing::*;
+use parking_lot::Mutex;
+
+/// Rejects writes to partitions whose time range ended more than a configured
+/// maximum age ago.
+///
+/// The end of the time range of a partition is derived from the time part of
+/// its partition key and the partition template of its table, so a partition
+/// is sealed at the same time on every ingester without recording it
+/// anywhere. Partitions with no time part in their key are never sealed.
+///
+/// Only the partition templates of the tables written to are cached. If the
+/// template of a table cannot be read from the catalog, its writes are
+/// accepted.
+#[derive(Debug)]
+pub(crate) struct PartitionSealer {
+    max_age: Duration,
+    catalog: Arc<dyn Catalog>,
+    time_provider: Arc<dyn TimeProvider>,
+
+    /// The partition templates of the tables checked so far.
+    templates: Mutex<HashMap<TableId, Arc<TablePartitionTemplateOverride>>>,
+
+    rejected_count: U64Counter,
+}
+
+impl PartitionSealer {
+    /// Seal partitions whose time range ended more than `max_age` ago.
+    pub(crate) fn new(
+        max_age: Duration,
+        catalog: Arc<dyn Catalog>,
+        time_provider: Arc<dyn TimeProvider>,
+        metrics: &metric::Registry,
+    ) -> Self {
+        let rejected_count = metrics
+            .register_metric::<U64Counter>(
+                "ingester_partition_sealed_write_rejected",
+                "number of table writes rejected as their partition is sealed",
+            )
+            .recorder(&[]);
+
+        Self {
+            max_age,
+            catalog,
+            time_provider,
+            templates: Default::default(),
+            rejected_count,
+        }
+    }
+
+    /// Return the time the partition `partition_key` of `table_id` was sealed
+    /// at if it is too old to accept writes, or [`None`] if it accepts writes.
+    pub(crate) async fn check(
+        &self,
+        table_id: TableId,
+        partition_key: &PartitionKey,
+    ) -> Option<Time> {
+        let template = self.template(table_id).await?;
+
+        let range_end = build_column_values(&template, partition_key.inner()).find_map(
+            |(_, value)| match value {
+                ColumnValue::Datetime { end, .. } => Some(Time::from_date_time(end)),
+                _ => None,
+            },
+        )?;
+
+        let sealed_at = range_end + self.max_age;
+        if sealed_at > self.time_provider.now() {
+            return None;
+        }
+
+        self.rejected_count.inc(1);
+        Some(sealed_at)
+    }
+
+    /// Return the partition template of `table_id`, reading it from the
+    /// catalog once, or [`None`] if it cannot be read.
+    async fn template(&self, table_id: TableId) -> Option<Arc<TablePartitionTemplateOverride>> {
+        if let Some(template) = self.templates.lock().get(&table_id) {
+            return Some(Arc::clone(template));
+        }
+
+        let table = self
+            .catalog
+            .repositories()
+            .await
+            .tables()
+            .get_by_id(table_id)
+            .await;
+        let template = match table {
+            Ok(Some(table)) => Arc::new(table.partition_template),
+            Ok(None) => {
+                warn!(%table_id, "table to check for sealed partitions not found");
+                return None;
+            }
+            Err(error) => {
+                warn!(%error, %table_id, "failed to read table to check for sealed partitions");
+                return None;
+            }
+        };
+
+        self.templates
+            .lock()
+            .insert(table_id, Arc::clone(&template));
+        Some(template)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use iox_catalog::{
+        mem::MemCatalog,
+        test_helpers::{arbitrary_namespace, arbitrary_table},
+    };
+    use iox_time::MockProvider;
+    use metric::assert_counter;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_seal() {
+        let metrics = Arc::new(metric::Registry::default());
+        let catalog: Arc<dyn Catalog> = Arc::new(MemCatalog::new(Arc::clone(&metrics)));
+        let table_id = {
+            let mut repos = catalog.repositories().await;
+            let namespace = arbitrary_namespace(&mut *repos, "bananas").await;
+            arbitrary_table(&mut *repos, "platanos", &namespace)
+                .await
+                .id
+        };
+
+        let time_provider = Arc::new(MockProvider::new(
+            Time::from_rfc3339("2023-10-03T12:00:00Z").unwrap(),
+        ));
+        let sealer = PartitionSealer::new(
+            Duration::from_secs(24 * 60 * 60),
+            Arc::clone(&catalog),
+            Arc::clone(&time_provider) as _,
+            &metrics,
+        );
+
+        // The default template partitions by day: the range of 2023-10-02
+        // ended less than a day ago, and that of 2023-10-01 more than a day
+        // ago.
+        let recent = PartitionKey::from("2023-10-02");
+        let old = PartitionKey::from("2023-10-01");
+        assert_eq!(sealer.check(table_id, &recent).await, None);
+
+        // The range of 2023-10-01 ended at midnight on 2023-10-02, and the
+        // partition was sealed a day later.
+        let sealed_at = Time::from_rfc3339("2023-10-03T00:00:00Z").unwrap();
+        assert_eq!(sealer.check(table_id, &old).await, Some(sealed_at));
+
+        // The recent partition is sealed once a day passed since its range
+        // ended.
+        time_provider.set(Time::from_rfc3339("2023-10-04T00:00:00Z").unwrap());
+        assert_eq!(
+            sealer.check(table_id, &recent).await,
+            Some(time_provider.now())
+        );
+        assert_eq!(sealer.check(table_id, &old).await, Some(sealed_at));
+
+        // Keys with no time part are never sealed.
+        assert_eq!(
+            sealer
+                .check(table_id, &PartitionKey::from("not-a-date"))
+                .await,
+            None
+        );
+
+        // Writes to unknown tables are accepted.
+        assert_eq!(
+            sealer.check(TableId::new(table_id.get() + 1), &old).await,
+            None
+        );
+
+        assert_counter!(
+            metrics,
+            U64Counter,
+            "ingester_partition_sealed_write_rejected",
+            value = 3,
+        );
+    }
+}
@@ -144,6 +144,16 @@ impl PersistState {
 
         WaitGuard(Arc::clone(&s.waiting_to_enqueue))
     }
+
+    /// Return a [`PersistQueueOccupancy`] reporting the current utilisation of
+    /// the persist queue.
+    pub(super) fn occupancy(&self) -> PersistQueueOccupancy {
+        PersistQueueOccupancy {
+            ingest_state: Arc::clone(&self.ingest_state),
+            sem: Arc::clone(&self.sem),
+            persist_queue_depth: self.persist_queue_depth,
+        }
+    }
 }
 
 impl Drop for PersistState {
@@ -154,6 +164,53 @@ impl Drop for PersistState {
     }
 }
 
+/// A cheaply cloneable, read-only view of the utilisation of the persist
+/// queue, used as a measure of the buffer pressure within the ingester.
+#[derive(Debug, Clone)]
+pub(crate) struct PersistQueueOccupancy {
+    ingest_state: Arc<IngestState>,
+    sem: Arc<Semaphore>,
+    persist_queue_depth: usize,
+}
+
+impl PersistQueueOccupancy {
+    /// Return the percentage (0 to 100 inclusive) of the persist queue
+    /// occupied by outstanding persist jobs.
+    ///
+    /// If the ingester is not accepting writes (because the persist system is
+    /// saturated, the disk is full, or the ingester is stopping) the queue is
+    /// reported as fully occupied.
+    pub(crate) fn percent(&self) -> u8 {
+        if self.ingest_state.read().is_err() {
+            return 100;
+        }
+
+        let available = self.sem.available_permits().min(self.persist_queue_depth);
+        let outstanding = self.persist_queue_depth - available;
+
+        // The queue depth is always non-zero, as asserted by PersistState.
+        (outstanding * 100 / self.persist_queue_depth) as u8
+    }
+}
+
+#[cfg(test)]
+impl PersistQueueOccupancy {
+    /// Construct a [`PersistQueueOccupancy`] over the provided semaphore for
+    /// testing.
+    pub(crate) fn new_for_test(
+        ingest_state: Arc<IngestState>,
+        persist_queue_depth: usize,
+        sem: Arc<Semaphore>,
+    ) -> Self {
+        assert!(persist_queue_depth > 0);
+        Self {
+            ingest_state,
+            sem,
+            persist_queue_depth,
+        }
+    }
+}
+
 /// A guard that decrements the number of writers waiting to obtain a permit
 /// from the persistence semaphore.
 ///
@@ -311,6 +368,30 @@ mod tests {
         assert!(has_sufficient_capacity(3, 3));
     }
 
+    #[test]
+    fn test_occupancy() {
+        let metrics = metric::Registry::default();
+        let sem = Arc::new(Semaphore::new(4));
+        let ingest_state = Arc::new(IngestState::default());
+        let s = PersistState::new(Arc::clone(&ingest_state), 4, Arc::clone(&sem), &metrics);
+
+        let occupancy = s.occupancy();
+        assert_eq!(occupancy.percent(), 0);
+
+        let _p1 = sem.try_acquire().unwrap();
+        assert_eq!(occupancy.percent(), 25);
+
+        let _p2 = sem.try_acquire_many(2).unwrap();
+        assert_eq!(occupancy.percent(), 75);
+
+        // Any ingest error state reports the queue as full.
+        ingest_state.set(IngestStateError::DiskFull);
+        assert_eq!(occupancy.percent(), 100);
+
+        ingest_state.unset(IngestStateError::DiskFull);
+        assert_eq!(occupancy.percent(), 75);
+    }
+
     /// Ensure that the saturation evaluation checks for outstanding enqueue
     /// waiters (as tracked by the [`WaitGuard`]).
     #[tokio::test]
@@ -0,0 +1,414 @@
+//! Persist barriers, persisting the partitions of a group of tables together.
+//!
+//! Queries joining several tables (such as a dashboard showing related
+//! measurements) may otherwise observe the most recent partition of one table
+//! committed to the catalog, while the same partition of another table is
+//! still only buffered in the ingester.
+//!
+//! When a partition of a table in a [`PersistBarrierGroup`] is enqueued for
+//! persistence, the data buffered in the partitions with the same partition
+//! key of the other tables in the group is persisted with it. The parquet
+//! files of all these persist jobs are added to the catalog in a single
+//! transaction by the [`CommitBarrier`] shared between them.
+//!
+//! This narrows the window in which the tables of a group diverge, but is not
+//! a consistency guarantee. Persist jobs that started before the group was
+//! enqueued (for example, a sibling partition already persisting when the WAL
+//! is rotated, or during shutdown) commit their files independently, and
+//! writes buffered after the group was enqueued are persisted later.
+
+use std::{
+    collections::HashSet,
+    sync::{Arc, OnceLock},
+};
+
+use data_types::{CompactionLevel, ParquetFile, ParquetFileParams};
+use iox_catalog::interface::Catalog;
+use observability_deps::tracing::debug;
+use parking_lot::Mutex;
+use tokio::sync::{oneshot, MutexGuard};
+
+use crate::{
+    buffer_tree::partition::PartitionData,
+    fault_injection::{FaultInjector, FaultPoint},
+    partition_iter::PartitionIter,
+};
+
+use super::{circuit_breaker::PersistCircuitBreaker, worker::CatalogCommitError};
+
+/// A set of tables in a namespace whose partitions are persisted together.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PersistBarrierGroup {
+    namespace: String,
+    tables: HashSet<String>,
+}
+
+impl PersistBarrierGroup {
+    /// Construct a group persisting the partitions of `tables` in `namespace`
+    /// together.
+    pub fn new<T>(namespace: impl Into<String>, tables: impl IntoIterator<Item = T>) -> Self
+    where
+        T: Into<String>,
+    {
+        Self {
+            namespace: namespace.into(),
+            tables: tables.into_iter().map(Into::into).collect(),
+        }
+    }
+
+    /// Return the number of tables in this group.
+    pub fn len(&self) -> usize {
+        self.tables.len()
+    }
+
+    /// Returns true if this group contains no tables.
+    pub fn is_empty(&self) -> bool {
+        self.tables.is_empty()
+    }
+
+    fn contains(&self, namespace: &str, table: &str) -> bool {
+        self.namespace == namespace && self.tables.contains(table)
+    }
+}
+
+/// Resolves the partitions that must be persisted together with a partition
+/// of a table in one of the configured [`PersistBarrierGroup`].
+#[derive(Debug)]
+pub(crate) struct PersistBarrier {
+    groups: Vec<PersistBarrierGroup>,
+
+    /// The source of the buffered partitions, set once the buffer tree is
+    /// initialised (which itself depends on the persist system).
+    partitions: OnceLock<Arc<dyn PartitionIter + Sync>>,
+
+    /// Serialises enqueuing the persist jobs of a group.
+    ///
+    /// A persist job waits at the [`CommitBarrier`] for the other jobs of its
+    /// group while retaining its persist queue permit. If two groups were
+    /// enqueued concurrently, each could hold some of the permits needed by
+    /// the other, and neither could complete.
+    enqueue_lock: tokio::sync::Mutex<()>,
+}
+
+impl PersistBarrier {
+    pub(crate) fn new(groups: Vec<PersistBarrierGroup>) -> Self {
+        Self {
+            groups,
+            partitions: OnceLock::new(),
+            enqueue_lock: tokio::sync::Mutex::new(()),
+        }
+    }
+
+    /// Set the source of the partitions persisted together with an enqueued
+    /// partition.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called more than once.
+    pub(crate) fn set_partitions(&self, partitions: Arc<dyn PartitionIter + Sync>) {
+        self.partitions
+            .set(partitions)
+            .expect("persist barrier partitions set more than once");
+    }
+
+    /// Return the number of tables in the largest configured group.
+    pub(crate) fn max_group_len(&self) -> usize {
+        self.groups
+            .iter()
+            .map(|g| g.len())
+            .max()
+            .unwrap_or_default()
+    }
+
+    /// Wait for exclusive access to enqueue the persist jobs of a group.
+    pub(super) async fn lock_enqueue(&self) -> MutexGuard<'_, ()> {
+        self.enqueue_lock.lock().await
+    }
+
+    /// Return the partitions of the other tables in the group of the table of
+    /// `partition` that have the same partition key, or [`None`] if the table
+    /// is not part of a group.
+    pub(super) async fn siblings(
+        &self,
+        partition: &Arc<Mutex<PartitionData>>,
+    ) -> Option<Vec<Arc<Mutex<PartitionData>>>> {
+        let (namespace_id, partition_key, namespace_name, table) = {
+            let p = partition.lock();
+            (
+                p.namespace_id(),
+                p.partition_key().clone(),
+                Arc::clone(p.namespace_name()),
+                Arc::clone(p.table()),
+            )
+        };
+
+        let namespace_name = namespace_name.get().await;
+        let table_name = table.get().await.name().clone();
+        let group = self
+            .groups
+            .iter()
+            .find(|g| g.contains(&namespace_name, &table_name))?;
+
+        let candidates = self
+            .partitions
+            .get()?
+            .partition_iter()
+            .filter(|p| !Arc::ptr_eq(p, partition))
+            .filter_map(|p| {
+                let table = {
+                    let guard = p.lock();
+                    if guard.namespace_id() != namespace_id
+                        || guard.partition_key() != &partition_key
+                    {
+                        return None;
+                    }
+                    Arc::clone(guard.table())
+                };
+                Some((p, table))
+            })
+            .collect::<Vec<_>>();
+
+        let mut siblings = Vec::with_capacity(candidates.len());
+        for (p, table) in candidates {
+            if group.contains(&namespace_name, &table.get().await.name()) {
+                siblings.push(p);
+            }
+        }
+
+        debug!(
+            %namespace_name,
+            table = %table_name,
+            %partition_key,
+            n_siblings = siblings.len(),
+            "resolved persist barrier group partitions"
+        );
+
+        Some(siblings)
+    }
+}
+
+/// Adds the parquet files of a set of persist jobs to the catalog in a single
+/// transaction, once all of the jobs have uploaded their file.
+#[derive(Debug)]
+pub(super) struct CommitBarrier {
+    /// The number of persist jobs that share this barrier.
+    n_jobs: usize,
+
+    /// The files of the jobs waiting for the commit.
+    waiting: Mutex<Vec<(ParquetFileParams, oneshot::Sender<ParquetFile>)>>,
+}
+
+impl CommitBarrier {
+    pub(super) fn new(n_jobs: usize) -> Self {
+        assert!(n_jobs > 0);
+        Self {
+            n_jobs,
+            waiting: Mutex::new(Vec::with_capacity(n_jobs)),
+        }
+    }
+
+    /// Add the file described by `params` to the catalog together with the
+    /// files of the other jobs sharing this barrier, waiting for all of them
+    /// to arrive.
+    ///
+    /// The last job to arrive performs the commit, retrying until it succeeds.
+    pub(super) async fn commit(
+        &self,
+        params: ParquetFileParams,
+        catalog: &dyn Catalog,
+        circuit_breaker: &PersistCircuitBreaker,
+        faults: &FaultInjector,
+    ) -> ParquetFile {
+        let (tx, rx) = oneshot::channel();
+
+        let files = {
+            let mut waiting = self.waiting.lock();
+            waiting.push((params, tx));
+            if waiting.len() < self.n_jobs {
+                None
+            } else {
+                Some(std::mem::take(&mut *waiting))
+            }
+        };
+
+        if let Some(files) = files {
+            let (params, senders): (Vec<_>, Vec<_>) = files.into_iter().unzip();
+
+            let ids = circuit_breaker
+                .retry("add persist barrier parquet files to catalog", || async {
+                    faults.check(FaultPoint::CatalogCommit)?;
+
+                    let mut repos = catalog.repositories().await;
+                    let ids = repos
+                        .parquet_files()
+                        .create_upgrade_delete(&[], &[], &params, CompactionLevel::Initial)
+                        .await?;
+
+                    Ok(ids) as Result<_, CatalogCommitError>
+                })
+                .await;
+
+            debug!(
+                n_files = ids.len(),
+                "persist barrier files added to catalog"
+            );
+
+            assert_eq!(ids.len(), params.len());
+            for ((params, tx), id) in params.into_iter().zip(senders).zip(ids) {
+                // Each waiting job holds its receiver until it receives a file.
+                let _ = tx.send(ParquetFile::from_params(params, id));
+            }
+        }
+
+        rx.await.expect("persist barrier commit task stopped")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{num::NonZeroUsize, time::Duration};
+
+    use data_types::PartitionKey;
+    use futures::{future::join_all, FutureExt};
+    use iox_catalog::{
+        interface::SoftDeletedRows,
+        test_helpers::{arbitrary_namespace, arbitrary_parquet_file_params, arbitrary_table},
+    };
+    use test_helpers::timeout::FutureTimeout;
+
+    use super::*;
+    use crate::{
+        buffer_tree::table::metadata::TableMetadata,
+        deferred_load::DeferredLoad,
+        ingest_state::IngestState,
+        test_util::{defer_namespace_name_1_sec, PartitionDataBuilder, ARBITRARY_NAMESPACE_NAME},
+    };
+
+    fn partition(table: &str, key: &str) -> Arc<Mutex<PartitionData>> {
+        let table = TableMetadata::new_for_testing(table.into(), Default::default());
+        Arc::new(Mutex::new(
+            PartitionDataBuilder::new()
+                .with_namespace_loader(defer_namespace_name_1_sec())
+                .with_table_loader(Arc::new(DeferredLoad::new(
+                    Duration::from_secs(1),
+                    async move { table },
+                    &metric::Registry::default(),
+                )))
+                .with_partition_key(PartitionKey::from(key))
+                .build(),
+        ))
+    }
+
+    #[tokio::test]
+    async fn test_siblings() {
+        let a = partition("a", "2023-01-01");
+        let b = partition("b", "2023-01-01");
+        let b_other_key = partition("b", "2023-01-02");
+        let c = partition("c", "2023-01-01");
+
+        let barrier = PersistBarrier::new(vec![PersistBarrierGroup::new(
+            ARBITRARY_NAMESPACE_NAME.to_string(),
+            ["a", "b"],
+        )]);
+        assert_eq!(barrier.max_group_len(), 2);
+
+        barrier.set_partitions(Arc::new(vec![
+            Arc::clone(&a),
+            Arc::clone(&b),
+            Arc::clone(&b_other_key),
+            Arc::clone(&c),
+        ]));
+
+        let got = barrier.siblings(&a).await.expect("a is in a group");
+        assert_eq!(got.len(), 1);
+        assert!(Arc::ptr_eq(&got[0], &b));
+
+        let got = barrier
+            .siblings(&b_other_key)
+            .await
+            .expect("b is in a group");
+        assert!(got.is_empty());
+
+        assert!(barrier.siblings(&c).await.is_none());
+
+        // Tables in another namespace are not part of the group.
+        let barrier = PersistBarrier::new(vec![PersistBarrierGroup::new("platanos", ["a", "b"])]);
+        barrier.set_partitions(Arc::new(vec![Arc::clone(&a), Arc::clone(&b)]));
+        assert!(barrier.siblings(&a).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_commit_barrier() {
+        let catalog: Arc<dyn Catalog> = Arc::new(iox_catalog::mem::MemCatalog::new(Arc::new(
+            metric::Registry::default(),
+        )));
+        let faults = FaultInjector::default();
+        let circuit_breaker = PersistCircuitBreaker::new(
+            NonZeroUsize::new(10).unwrap(),
+            Arc::new(IngestState::default()),
+            &metric::Registry::default(),
+        );
+
+        let params = {
+            let mut repos = catalog.repositories().await;
+            let namespace = arbitrary_namespace(&mut *repos, "bananas").await;
+            let table = arbitrary_table(&mut *repos, "platanos", &namespace).await;
+            let partition = repos
+                .partitions()
+                .create_or_get("one".into(), table.id)
+                .await
+                .unwrap();
+            (0..3)
+                .map(|_| arbitrary_parquet_file_params(&namespace, &table, &partition))
+                .collect::<Vec<_>>()
+        };
+
+        let barrier = CommitBarrier::new(3);
+
+        // The first jobs to arrive wait for the last.
+        let mut first =
+            Box::pin(barrier.commit(params[0].clone(), &*catalog, &circuit_breaker, &faults));
+        assert!((&mut first).now_or_never().is_none());
+        {
+            let mut repos = catalog.repositories().await;
+            let files = repos
+                .parquet_files()
+                .list_by_namespace_not_to_delete(params[0].namespace_id)
+                .await
+                .unwrap();
+            assert!(files.is_empty());
+        }
+
+        let rest = join_all(
+            params[1..]
+                .iter()
+                .map(|p| barrier.commit(p.clone(), &*catalog, &circuit_breaker, &faults)),
+        );
+        let (first, rest) = futures::future::join(first, rest)
+            .with_timeout_panic(Duration::from_secs(5))
+            .await;
+
+        let mut got = std::iter::once(first)
+            .chain(rest)
+            .map(|f| f.object_store_id)
+            .collect::<Vec<_>>();
+        let mut want = params.iter().map(|p| p.object_store_id).collect::<Vec<_>>();
+        got.sort();
+        want.sort();
+        assert_eq!(got, want);
+
+        let mut repos = catalog.repositories().await;
+        let namespace = repos
+            .namespaces()
+            .get_by_name("bananas", SoftDeletedRows::ExcludeDeleted)
+            .await
+            .unwrap()
+            .unwrap();
+        let files = repos
+            .parquet_files()
+            .list_by_namespace_not_to_delete(namespace.id)
+            .await
+            .unwrap();
+        assert_eq!(files.len(), 3);
+    }
+}
@@ -0,0 +1,391 @@
+//! A circuit breaker pausing persist attempts after repeated failures.
+//!
+//! Persist jobs retry the upload of their parquet file to object storage and
+//! the addition of the file to the catalog until they succeed. When either
+//! fails persistently (for example, because the bucket is misconfigured) every
+//! persist worker spins in its own retry loop, and the buffered data, the
+//! outstanding requests and their connections pile up.
+//!
+//! The [`PersistCircuitBreaker`] is shared by all the persist workers of an
+//! ingester, which persist to a single object store and catalog. After a
+//! configurable number of consecutive failures the circuit "opens": writes are
+//! rejected with [`IngestStateError::PersistFailing`], and persist attempts
+//! are paused except for a single probe attempt at exponentially increasing
+//! intervals. The first successful attempt closes the circuit, resuming all
+//! paused jobs and clearing the error state.
+
+use std::{fmt::Display, future::Future, num::NonZeroUsize, sync::Arc, time::Duration};
+
+use backoff::{Backoff, BackoffConfig};
+use metric::{U64Counter, U64Gauge};
+use observability_deps::tracing::{info, warn};
+use parking_lot::Mutex;
+use tokio::{sync::Notify, time::Instant};
+
+use crate::ingest_state::{IngestState, IngestStateError};
+
+/// The interval between the probe attempts of an open circuit.
+const PROBE_BACKOFF: BackoffConfig = BackoffConfig {
+    init_backoff: Duration::from_secs(1),
+    max_backoff: Duration::from_secs(60),
+    base: 2.,
+    deadline: None,
+};
+
+/// The kind of attempt permitted by [`PersistCircuitBreaker::wait()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Attempt {
+    /// The circuit is closed.
+    Normal,
+    /// The circuit is open, and this attempt tests whether it can be closed.
+    Probe,
+}
+
+#[derive(Debug)]
+struct OpenState {
+    /// The earliest time at which the next probe attempt may start.
+    next_probe_at: Instant,
+
+    /// The intervals between probe attempts.
+    backoff: Backoff,
+
+    /// True while a probe attempt is in progress.
+    probing: bool,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    /// The number of attempts that failed since the last success.
+    consecutive_failures: usize,
+
+    /// Set while the circuit is open.
+    open: Option<OpenState>,
+}
+
+/// Pauses persist attempts after `threshold` consecutive failures, probing
+/// for recovery with backoff.
+///
+/// See the [module docs](self) for more information.
+#[derive(Debug)]
+pub(crate) struct PersistCircuitBreaker {
+    threshold: NonZeroUsize,
+    state: Mutex<State>,
+
+    /// Notified when a probe attempt completes.
+    probe_complete: Notify,
+
+    ingest_state: Arc<IngestState>,
+
+    open_gauge: U64Gauge,
+    trips: U64Counter,
+}
+
+impl PersistCircuitBreaker {
+    /// Construct a circuit breaker opening after `threshold` consecutive
+    /// failures, setting [`IngestStateError::PersistFailing`] in
+    /// `ingest_state` while open.
+    pub(crate) fn new(
+        threshold: NonZeroUsize,
+        ingest_state: Arc<IngestState>,
+        metrics: &metric::Registry,
+    ) -> Self {
+        let open_gauge = metrics
+            .register_metric::<U64Gauge>(
+                "ingester_persist_circuit_breaker_open",
+                "set to 1 while persist attempts are paused after repeated failures",
+            )
+            .recorder(&[]);
+        let trips = metrics
+            .register_metric::<U64Counter>(
+                "ingester_persist_circuit_breaker_trips",
+                "number of times persist attempts were paused after repeated failures",
+            )
+            .recorder(&[]);
+
+        Self {
+            threshold,
+            state: Default::default(),
+            probe_complete: Notify::new(),
+            ingest_state,
+            open_gauge,
+            trips,
+        }
+    }
+
+    /// Retry `op` until it succeeds, pausing between attempts while the
+    /// circuit is open.
+    ///
+    /// While the circuit is closed, failed attempts are retried with the
+    /// default [`BackoffConfig`].
+    pub(crate) async fn retry<F, Fut, T, E>(&self, task_name: &str, mut op: F) -> T
+    where
+        F: (FnMut() -> Fut) + Send,
+        Fut: Future<Output = Result<T, E>> + Send,
+        E: Display + Send,
+    {
+        let mut backoff = Backoff::new(&Default::default());
+        loop {
+            let attempt = self.wait().await;
+            match op().await {
+                Ok(v) => {
+                    self.observe_success();
+                    return v;
+                }
+                Err(error) => {
+                    warn!(
+                        %error,
+                        task_name,
+                        probe = attempt == Attempt::Probe,
+                        "persist attempt failed"
+                    );
+                    if self.observe_failure(attempt) {
+                        // The circuit is open - wait() paces the next attempt.
+                        continue;
+                    }
+                    let delay = backoff.next().expect("retry forever");
+                    tokio::time::sleep(delay).await;
+                }
+            }
+        }
+    }
+
+    /// Returns true while persist attempts are paused.
+    #[cfg(test)]
+    pub(crate) fn is_open(&self) -> bool {
+        self.state.lock().open.is_some()
+    }
+
+    /// Wait until an attempt may be made.
+    ///
+    /// Returns immediately while the circuit is closed. While open, at most
+    /// one caller at a time is permitted a probe attempt, once the probe
+    /// interval has elapsed.
+    async fn wait(&self) -> Attempt {
+        loop {
+            // Register interest in probe completion before inspecting the
+            // state, so that a completion in between is not missed.
+            let probe_complete = self.probe_complete.notified();
+
+            let wake_at = {
+                let mut state = self.state.lock();
+                match &mut state.open {
+                    None => return Attempt::Normal,
+                    Some(open) if open.probing => None,
+                    Some(open) if Instant::now() >= open.next_probe_at => {
+                        open.probing = true;
+                        return Attempt::Probe;
+                    }
+                    Some(open) => Some(open.next_probe_at),
+                }
+            };
+
+            match wake_at {
+                Some(t) => {
+                    tokio::select! {
+                        _ = tokio::time::sleep_until(t) => {},
+                        _ = probe_complete => {},
+                    }
+                }
+                None => probe_complete.await,
+            }
+        }
+    }
+
+    /// Close the circuit (if open) and reset the failure count.
+    fn observe_success(&self) {
+        let mut state = self.state.lock();
+        state.consecutive_failures = 0;
+        if state.open.take().is_none() {
+            return;
+        }
+        drop(state);
+
+        self.ingest_state.unset(IngestStateError::PersistFailing);
+        self.open_gauge.set(0);
+        self.probe_complete.notify_waiters();
+
+        info!("persist succeeded, resuming persist attempts");
+    }
+
+    /// Record a failed `attempt`, opening the circuit if the threshold is
+    /// reached.
+    ///
+    /// Returns true if the circuit is open.
+    fn observe_failure(&self, attempt: Attempt) -> bool {
+        let mut state = self.state.lock();
+        state.consecutive_failures = state.consecutive_failures.saturating_add(1);
+
+        if let Some(open) = &mut state.open {
+            // Only a failed probe reschedules the next probe - a failure of
+            // an attempt started before the circuit opened does not.
+            if attempt == Attempt::Probe {
+                open.probing = false;
+                open.next_probe_at = Instant::now() + open.backoff.next().expect("retry forever");
+                drop(state);
+                self.probe_complete.notify_waiters();
+            }
+            return true;
+        }
+
+        if state.consecutive_failures < self.threshold.get() {
+            return false;
+        }
+
+        let mut backoff = Backoff::new(&PROBE_BACKOFF);
+        let next_probe_in = backoff.next().expect("retry forever");
+        state.open = Some(OpenState {
+            next_probe_at: Instant::now() + next_probe_in,
+            backoff,
+            probing: false,
+        });
+        let consecutive_failures = state.consecutive_failures;
+        drop(state);
+
+        self.ingest_state.set(IngestStateError::PersistFailing);
+        self.open_gauge.set(1);
+        self.trips.inc(1);
+
+        warn!(
+            consecutive_failures,
+            next_probe_in_secs = next_probe_in.as_secs_f64(),
+            "persist failing repeatedly, pausing persist attempts"
+        );
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+    use assert_matches::assert_matches;
+    use futures::FutureExt;
+    use metric::{Attributes, Metric};
+    use test_helpers::timeout::FutureTimeout;
+
+    use super::*;
+
+    const THRESHOLD: usize = 3;
+
+    fn threshold() -> NonZeroUsize {
+        NonZeroUsize::new(THRESHOLD).unwrap()
+    }
+
+    fn gauge(metrics: &metric::Registry) -> u64 {
+        metrics
+            .get_instrument::<Metric<U64Gauge>>("ingester_persist_circuit_breaker_open")
+            .expect("gauge not registered")
+            .get_observer(&Attributes::from([]))
+            .expect("gauge not found")
+            .fetch()
+    }
+
+    #[tokio::test]
+    async fn test_circuit_breaker() {
+        tokio::time::pause();
+
+        let metrics = metric::Registry::default();
+        let ingest_state = Arc::new(IngestState::default());
+        let breaker = Arc::new(PersistCircuitBreaker::new(
+            threshold(),
+            Arc::clone(&ingest_state),
+            &metrics,
+        ));
+
+        // Fail every attempt until told otherwise.
+        let healthy = Arc::new(AtomicBool::new(false));
+        let attempts = Arc::new(AtomicUsize::new(0));
+        let op = {
+            let healthy = Arc::clone(&healthy);
+            let attempts = Arc::clone(&attempts);
+            move || {
+                attempts.fetch_add(1, Ordering::Relaxed);
+                let ok = healthy.load(Ordering::Relaxed);
+                async move {
+                    if ok {
+                        Ok(42)
+                    } else {
+                        Err("bananas")
+                    }
+                }
+            }
+        };
+
+        let task = tokio::spawn({
+            let breaker = Arc::clone(&breaker);
+            let op = op.clone();
+            async move { breaker.retry("test", op).await }
+        });
+
+        // The circuit opens after THRESHOLD failures, raising the ingest
+        // error state.
+        async {
+            while !breaker.is_open() {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        }
+        .with_timeout_panic(Duration::from_secs(600))
+        .await;
+        assert_eq!(attempts.load(Ordering::Relaxed), THRESHOLD);
+        assert_matches!(ingest_state.read(), Err(IngestStateError::PersistFailing));
+        assert_eq!(gauge(&metrics), 1);
+
+        // Further callers are paused rather than attempting.
+        let mut paused = Box::pin({
+            let breaker = Arc::clone(&breaker);
+            let op = op.clone();
+            async move { breaker.retry("test", op).await }
+        });
+        assert!((&mut paused).now_or_never().is_none());
+        assert_eq!(attempts.load(Ordering::Relaxed), THRESHOLD);
+
+        // A probe is attempted once the probe interval has elapsed, and fails.
+        tokio::time::sleep(PROBE_BACKOFF.max_backoff).await;
+        assert!(attempts.load(Ordering::Relaxed) > THRESHOLD);
+        assert!(breaker.is_open());
+
+        // Once healthy, the next probe closes the circuit and resumes all
+        // attempts.
+        healthy.store(true, Ordering::Relaxed);
+        let got = task
+            .with_timeout_panic(Duration::from_secs(600))
+            .await
+            .expect("task panicked");
+        assert_eq!(got, 42);
+        assert_eq!(paused.with_timeout_panic(Duration::from_secs(1)).await, 42);
+
+        assert!(!breaker.is_open());
+        assert_matches!(ingest_state.read(), Ok(()));
+        assert_eq!(gauge(&metrics), 0);
+
+        let trips = metrics
+            .get_instrument::<Metric<U64Counter>>("ingester_persist_circuit_breaker_trips")
+            .expect("counter not registered")
+            .get_observer(&Attributes::from([]))
+            .expect("counter not found")
+            .fetch();
+        assert_eq!(trips, 1);
+    }
+
+    #[tokio::test]
+    async fn test_success_resets_failures() {
+        let breaker = PersistCircuitBreaker::new(
+            threshold(),
+            Arc::new(IngestState::default()),
+            &metric::Registry::default(),
+        );
+
+        for _ in 0..THRESHOLD - 1 {
+            assert!(!breaker.observe_failure(Attempt::Normal));
+        }
+        breaker.observe_success();
+
+        // The failure count restarts after a success.
+        for _ in 0..THRESHOLD - 1 {
+            assert!(!breaker.observe_failure(Attempt::Normal));
+        }
+        assert!(breaker.observe_failure(Attempt::Normal));
+        assert!(breaker.is_open());
+    }
+}
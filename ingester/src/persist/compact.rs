@@ -1,21 +1,88 @@
-use std::sync::Arc;
-
-use datafusion::physical_plan::SendableRecordBatchStream;
+use std::{collections::HashSet, sync::Arc};
+
+use arrow::{array::TimestampNanosecondArray, record_batch::RecordBatch};
+use datafusion::{
+    error::DataFusionError,
+    execution::{
+        disk_manager::{DiskManager, DiskManagerConfig},
+        memory_pool::FairSpillPool,
+    },
+    physical_plan::{ExecutionPlan, SendableRecordBatchStream},
+};
 use iox_query::{
     exec::{Executor, ExecutorType},
     frontend::reorg::ReorgPlanner,
     QueryChunk,
 };
-use schema::sort::{adjust_sort_key_columns, compute_sort_key, SortKey};
+use schema::{
+    sort::{adjust_sort_key_columns, compute_sort_key, SortKey},
+    TIME_COLUMN_NAME,
+};
 
 use crate::{buffer_tree::table::metadata::TableName, query_adaptor::QueryAdaptor};
 
+/// A memory budget for executing each persist compaction.
+///
+/// Each compaction executes within its own memory pool of the configured size
+/// (instead of the pool shared with queries), and sorts that exceed it spill
+/// their input to temporary files (in the OS temporary directory) rather than
+/// failing.
+#[derive(Debug, Clone)]
+pub(crate) struct CompactionMemoryLimit {
+    bytes: usize,
+    disk_manager: Arc<DiskManager>,
+}
+
+impl CompactionMemoryLimit {
+    /// Limit each compaction to `bytes` of memory.
+    pub(crate) fn new(bytes: usize) -> Result<Self, DataFusionError> {
+        Ok(Self {
+            bytes,
+            disk_manager: DiskManager::try_new(DiskManagerConfig::NewOs)?,
+        })
+    }
+}
+
+/// The number of times, and bytes of data, the operators of a compaction plan
+/// spilled to disk.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub(super) struct SpillStats {
+    pub(super) spill_count: usize,
+    pub(super) spilled_bytes: usize,
+}
+
+impl SpillStats {
+    /// Sum the spill metrics of `plan` and its children.
+    ///
+    /// The metrics are only complete once the output stream of `plan` is
+    /// exhausted.
+    pub(super) fn from_plan(plan: &dyn ExecutionPlan) -> Self {
+        let mut stats = plan
+            .metrics()
+            .map(|m| Self {
+                spill_count: m.spill_count().unwrap_or_default(),
+                spilled_bytes: m.spilled_bytes().unwrap_or_default(),
+            })
+            .unwrap_or_default();
+        for child in plan.children() {
+            let child = Self::from_plan(child.as_ref());
+            stats.spill_count += child.spill_count;
+            stats.spilled_bytes += child.spilled_bytes;
+        }
+        stats
+    }
+}
+
 /// Result of calling [`compact_persisting_batch`]
 pub(super) struct CompactedStream {
     /// A stream of compacted, deduplicated
     /// [`RecordBatch`](arrow::record_batch::RecordBatch)es
     pub(super) stream: SendableRecordBatchStream,
 
+    /// The physical plan producing [`Self::stream`], from which the
+    /// [`SpillStats`] are read once it is exhausted.
+    pub(super) plan: Arc<dyn ExecutionPlan>,
+
     /// The sort key value the catalog should be updated to, if any.
     ///
     /// If returned, the compaction required extending the partition's
@@ -38,6 +105,7 @@ impl std::fmt::Debug for CompactedStream {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("CompactedStream")
             .field("stream", &"<SendableRecordBatchStream>")
+            .field("plan", &"<ExecutionPlan>")
             .field("data_sort_key", &self.data_sort_key)
             .field("catalog_sort_key_update", &self.catalog_sort_key_update)
             .finish()
@@ -46,8 +114,11 @@ impl std::fmt::Debug for CompactedStream {
 
 /// Compact a given batch into a [`CompactedStream`] or `None` if there is no
 /// data to compact, returning an updated sort key, if any.
+///
+/// If a `memory_limit` is provided, the compaction executes within it.
 pub(super) async fn compact_persisting_batch(
     executor: &Executor,
+    memory_limit: Option<&CompactionMemoryLimit>,
     sort_key: Option<&SortKey>,
     table_name: TableName,
     batch: QueryAdaptor,
@@ -77,7 +148,14 @@ pub(super) async fn compact_persisting_batch(
     let batch = Arc::new(batch);
 
     // Build logical plan for compaction
-    let ctx = executor.new_context(ExecutorType::Reorg);
+    let mut config = executor.new_execution_config(ExecutorType::Reorg);
+    if let Some(limit) = memory_limit {
+        config = config.with_memory_pool(
+            Arc::new(FairSpillPool::new(limit.bytes)),
+            Arc::clone(&limit.disk_manager),
+        );
+    }
+    let ctx = config.build();
     let logical_plan = ReorgPlanner::new()
         .compact_plan(
             table_name.into(),
@@ -91,18 +169,100 @@ pub(super) async fn compact_persisting_batch(
     let physical_plan = ctx.create_physical_plan(&logical_plan).await.unwrap();
 
     // Execute the plan and return the compacted stream
-    let output_stream = ctx.execute_stream(physical_plan).await.unwrap();
+    let output_stream = ctx
+        .execute_stream(Arc::clone(&physical_plan))
+        .await
+        .unwrap();
 
     CompactedStream {
         stream: output_stream,
+        plan: physical_plan,
         catalog_sort_key_update,
         data_sort_key,
     }
 }
 
+/// Remove the columns containing only NULL values from `batch`, returning it
+/// unchanged if there are none.
+///
+/// Sparse schemas produce many columns with no values in a partition, each
+/// adding an empty column chunk to every row group and to the footer of the
+/// persisted parquet file. Queriers read the columns missing from a file as
+/// NULL, so removing them does not change query results. The time column is
+/// never removed.
+pub(super) fn drop_null_columns(batch: QueryAdaptor) -> QueryAdaptor {
+    let null_columns = batch
+        .schema()
+        .as_arrow()
+        .fields()
+        .iter()
+        .map(|f| f.name())
+        .filter(|name| *name != TIME_COLUMN_NAME)
+        .filter(|name| {
+            batch.record_batches().iter().all(|b| {
+                b.column_by_name(name)
+                    .map_or(true, |c| c.null_count() == c.len())
+            })
+        })
+        .cloned()
+        .collect::<HashSet<_>>();
+
+    if null_columns.is_empty() {
+        return batch;
+    }
+
+    let batches = batch
+        .record_batches()
+        .iter()
+        .map(|b| {
+            let indices = b
+                .schema()
+                .fields()
+                .iter()
+                .enumerate()
+                .filter(|(_, f)| !null_columns.contains(f.name()))
+                .map(|(i, _)| i)
+                .collect::<Vec<_>>();
+            b.project(&indices).expect("projection of existing columns")
+        })
+        .collect();
+
+    QueryAdaptor::new(batch.partition_id().clone(), batches)
+}
+
+/// Returns true if the rows of `batches`, taken in order, are sorted by their
+/// timestamp.
+///
+/// Buffered data is ordered by arrival, and is only sorted by time if the
+/// writers of the partition sent their rows in time order.
+pub(super) fn is_sorted_by_time(batches: &[RecordBatch]) -> bool {
+    let mut last = i64::MIN;
+    for batch in batches {
+        let Some(times) = batch
+            .column_by_name(TIME_COLUMN_NAME)
+            .and_then(|c| c.as_any().downcast_ref::<TimestampNanosecondArray>())
+        else {
+            return false;
+        };
+
+        for t in times.values().iter() {
+            if *t < last {
+                return false;
+            }
+            last = *t;
+        }
+    }
+    true
+}
+
 #[cfg(test)]
 mod tests {
-    use arrow::record_batch::RecordBatch;
+    use std::collections::HashSet;
+
+    use arrow::{
+        array::{Array, DictionaryArray, StringArray},
+        datatypes::Int32Type,
+    };
     use arrow_util::assert_batches_eq;
     use iox_query::test::{raw_data, TestChunk};
     use mutable_batch_lp::lines_to_batches;
@@ -111,6 +271,68 @@ mod tests {
     use super::*;
     use crate::test_util::ARBITRARY_TRANSITION_PARTITION_ID;
 
+    fn lp_to_batch(lp: &str) -> RecordBatch {
+        lines_to_batches(lp, 0)
+            .unwrap()
+            .get("cpu")
+            .unwrap()
+            .to_arrow(Projection::All)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_drop_null_columns() {
+        let batch = QueryAdaptor::new(
+            ARBITRARY_TRANSITION_PARTITION_ID.clone(),
+            vec![
+                lp_to_batch("cpu,host=a bar=1 10\ncpu,host=b bar=2 20"),
+                lp_to_batch("cpu,host=a bar=3 30\ncpu,region=x baz=4 40"),
+            ],
+        );
+
+        // "region" and "baz" have values, so no column is removed.
+        let got = drop_null_columns(batch.clone());
+        assert_eq!(got.schema(), batch.schema());
+
+        // Projecting out the rows holding the values of "region" and "baz"
+        // leaves them entirely NULL.
+        let batch = QueryAdaptor::new(
+            ARBITRARY_TRANSITION_PARTITION_ID.clone(),
+            batch
+                .record_batches()
+                .iter()
+                .map(|b| b.slice(0, 1))
+                .collect(),
+        );
+        let got = drop_null_columns(batch);
+
+        let mut columns = got
+            .schema()
+            .as_arrow()
+            .fields()
+            .iter()
+            .map(|f| f.name().clone())
+            .collect::<Vec<_>>();
+        columns.sort();
+        assert_eq!(columns, ["bar", "host", "time"]);
+        assert_eq!(got.num_rows(), 2);
+    }
+
+    #[test]
+    fn test_is_sorted_by_time() {
+        let sorted = lp_to_batch("cpu bar=1 10\ncpu bar=2 20\ncpu bar=3 20");
+        let unsorted = lp_to_batch("cpu bar=1 20\ncpu bar=2 10");
+        let later = lp_to_batch("cpu bar=1 30\ncpu bar=2 40");
+
+        assert!(is_sorted_by_time(&[]));
+        assert!(is_sorted_by_time(&[sorted.clone()]));
+        assert!(is_sorted_by_time(&[sorted.clone(), later.clone()]));
+        assert!(!is_sorted_by_time(&[unsorted]));
+
+        // Batches that are each sorted, but overlap, are not sorted.
+        assert!(!is_sorted_by_time(&[later, sorted]));
+    }
+
     // this test was added to guard against https://github.com/influxdata/influxdb_iox/issues/3782
     // where if sending in a single row it would compact into an output of two batches, one of
     // which was empty, which would cause this to panic.
@@ -134,9 +356,14 @@ mod tests {
 
         // compact
         let exc = Executor::new_testing();
-        let CompactedStream { stream, .. } =
-            compact_persisting_batch(&exc, Some(&SortKey::empty()), "test_table".into(), batch)
-                .await;
+        let CompactedStream { stream, .. } = compact_persisting_batch(
+            &exc,
+            None,
+            Some(&SortKey::empty()),
+            "test_table".into(),
+            batch,
+        )
+        .await;
 
         let output_batches = datafusion::physical_plan::common::collect(stream)
             .await
@@ -174,8 +401,15 @@ mod tests {
             stream,
             data_sort_key,
             catalog_sort_key_update,
-        } = compact_persisting_batch(&exc, Some(&SortKey::empty()), "test_table".into(), batch)
-            .await;
+            ..
+        } = compact_persisting_batch(
+            &exc,
+            None,
+            Some(&SortKey::empty()),
+            "test_table".into(),
+            batch,
+        )
+        .await;
 
         let output_batches = datafusion::physical_plan::common::collect(stream)
             .await
@@ -223,8 +457,15 @@ mod tests {
             stream,
             data_sort_key,
             catalog_sort_key_update,
-        } = compact_persisting_batch(&exc, Some(&SortKey::empty()), "test_table".into(), batch)
-            .await;
+            ..
+        } = compact_persisting_batch(
+            &exc,
+            None,
+            Some(&SortKey::empty()),
+            "test_table".into(),
+            batch,
+        )
+        .await;
 
         let output_batches = datafusion::physical_plan::common::collect(stream)
             .await
@@ -277,8 +518,10 @@ mod tests {
             stream,
             data_sort_key,
             catalog_sort_key_update,
+            ..
         } = compact_persisting_batch(
             &exc,
+            None,
             Some(&SortKey::from_columns(["tag3", "tag1", "time"])),
             "test_table".into(),
             batch,
@@ -336,8 +579,10 @@ mod tests {
             stream,
             data_sort_key,
             catalog_sort_key_update,
+            ..
         } = compact_persisting_batch(
             &exc,
+            None,
             Some(&SortKey::from_columns(["tag3", "time"])),
             "test_table".into(),
             batch,
@@ -398,8 +643,10 @@ mod tests {
             stream,
             data_sort_key,
             catalog_sort_key_update,
+            ..
         } = compact_persisting_batch(
             &exc,
+            None,
             Some(&SortKey::from_columns(["tag3", "tag1", "tag4", "time"])),
             "test_table".into(),
             batch,
@@ -456,7 +703,7 @@ mod tests {
         // compact
         let exc = Executor::new_testing();
         let stream =
-            compact_persisting_batch(&exc, Some(&sort_key), "test_table".into(), batch).await;
+            compact_persisting_batch(&exc, None, Some(&sort_key), "test_table".into(), batch).await;
         let output_batches = datafusion::physical_plan::common::collect(stream.stream)
             .await
             .unwrap();
@@ -495,7 +742,7 @@ mod tests {
         // compact
         let exc = Executor::new_testing();
         let stream =
-            compact_persisting_batch(&exc, Some(&sort_key), "test_table".into(), batch).await;
+            compact_persisting_batch(&exc, None, Some(&sort_key), "test_table".into(), batch).await;
         let output_batches = datafusion::physical_plan::common::collect(stream.stream)
             .await
             .unwrap();
@@ -541,9 +788,10 @@ mod tests {
 
         // compact
         let exc = Executor::new_testing();
-        let stream = compact_persisting_batch(&exc, Some(&sort_key), "test_table".into(), batch)
-            .await
-            .stream;
+        let stream =
+            compact_persisting_batch(&exc, None, Some(&sort_key), "test_table".into(), batch)
+                .await
+                .stream;
         let output_batches = datafusion::physical_plan::common::collect(stream)
             .await
             .unwrap();
@@ -567,6 +815,54 @@ mod tests {
         assert_batches_eq!(&expected, &output_batches);
     }
 
+    #[tokio::test]
+    async fn test_compact_with_memory_limit() {
+        let batch = QueryAdaptor::new(
+            ARBITRARY_TRANSITION_PARTITION_ID.clone(),
+            create_batches_with_influxtype().await,
+        );
+        let sort_key = compute_sort_key(batch.schema(), batch.record_batches().iter());
+
+        let exc = Executor::new_testing();
+        let want = compact_persisting_batch(
+            &exc,
+            None,
+            Some(&sort_key),
+            "test_table".into(),
+            batch.clone(),
+        )
+        .await
+        .stream;
+        let want = datafusion::physical_plan::common::collect(want)
+            .await
+            .unwrap();
+
+        // Compacting within a dedicated memory pool produces the same output.
+        let limit = CompactionMemoryLimit::new(64 * 1024 * 1024).unwrap();
+        let CompactedStream { stream, plan, .. } = compact_persisting_batch(
+            &exc,
+            Some(&limit),
+            Some(&sort_key),
+            "test_table".into(),
+            batch,
+        )
+        .await;
+        let got = datafusion::physical_plan::common::collect(stream)
+            .await
+            .unwrap();
+        assert_eq!(
+            arrow::util::pretty::pretty_format_batches(&got)
+                .unwrap()
+                .to_string(),
+            arrow::util::pretty::pretty_format_batches(&want)
+                .unwrap()
+                .to_string(),
+        );
+
+        // The data fits within the limit, so nothing was spilled.
+        assert_eq!(SpillStats::from_plan(plan.as_ref()), SpillStats::default());
+    }
+
     #[tokio::test]
     async fn test_compact_many_batches_different_columns_with_duplicates() {
         // create many-batches input data
@@ -586,9 +882,10 @@ mod tests {
 
         // compact
         let exc = Executor::new_testing();
-        let stream = compact_persisting_batch(&exc, Some(&sort_key), "test_table".into(), batch)
-            .await
-            .stream;
+        let stream =
+            compact_persisting_batch(&exc, None, Some(&sort_key), "test_table".into(), batch)
+                .await
+                .stream;
         let output_batches = datafusion::physical_plan::common::collect(stream)
             .await
             .unwrap();
@@ -635,9 +932,10 @@ mod tests {
 
         // compact
         let exc = Executor::new_testing();
-        let stream = compact_persisting_batch(&exc, Some(&sort_key), "test_table".into(), batch)
-            .await
-            .stream;
+        let stream =
+            compact_persisting_batch(&exc, None, Some(&sort_key), "test_table".into(), batch)
+                .await
+                .stream;
         let output_batches = datafusion::physical_plan::common::collect(stream)
             .await
             .unwrap();
@@ -680,6 +978,62 @@ mod tests {
         batch.schema();
     }
 
+    #[tokio::test]
+    async fn test_compact_preserves_dictionary_encoding() {
+        // Two snapshots of the same partition, each with an independently
+        // encoded dictionary containing overlapping tag values.
+        let snapshots = ["cpu,tag1=A v=1 10\ncpu,tag1=B v=2 20", "cpu,tag1=A v=3 30"]
+            .into_iter()
+            .map(|lp| {
+                lines_to_batches(lp, 0)
+                    .unwrap()
+                    .get("cpu")
+                    .unwrap()
+                    .to_arrow(Projection::All)
+                    .unwrap()
+            })
+            .collect::<Vec<_>>();
+
+        let batch = QueryAdaptor::new(ARBITRARY_TRANSITION_PARTITION_ID.clone(), snapshots);
+
+        let exc = Executor::new_testing();
+        let stream = compact_persisting_batch(&exc, None, None, "test_table".into(), batch)
+            .await
+            .stream;
+        let output_batches = datafusion::physical_plan::common::collect(stream)
+            .await
+            .unwrap();
+
+        for batch in &output_batches {
+            let tags = batch
+                .column_by_name("tag1")
+                .unwrap()
+                .as_any()
+                .downcast_ref::<DictionaryArray<Int32Type>>()
+                .expect("tag column must remain dictionary encoded");
+
+            // The merged dictionary contains no duplicate values.
+            let values = tags
+                .values()
+                .as_any()
+                .downcast_ref::<StringArray>()
+                .unwrap();
+            let distinct = values.iter().collect::<HashSet<_>>();
+            assert_eq!(distinct.len(), values.len());
+        }
+
+        let expected = vec![
+            "+------+--------------------------------+-----+",
+            "| tag1 | time                           | v   |",
+            "+------+--------------------------------+-----+",
+            "| A    | 1970-01-01T00:00:00.000000010Z | 1.0 |",
+            "| A    | 1970-01-01T00:00:00.000000030Z | 3.0 |",
+            "| B    | 1970-01-01T00:00:00.000000020Z | 2.0 |",
+            "+------+--------------------------------+-----+",
+        ];
+        assert_batches_eq!(&expected, &output_batches);
+    }
+
     async fn create_one_row_record_batch_with_influxtype() -> Vec<RecordBatch> {
         let chunk1 = Arc::new(
             TestChunk::new("t")
@@ -1,5 +1,6 @@
 use std::sync::Arc;
 
+use bytes::Bytes;
 use data_types::{
     NamespaceId, ParquetFile, PartitionKey, SortedColumnSet, TableId, TransitionPartitionId,
 };
@@ -11,18 +12,22 @@ use tokio::{
     sync::{oneshot, OwnedSemaphorePermit},
     time::Instant,
 };
+use uuid::Uuid;
 
 use crate::{
     buffer_tree::{
         namespace::NamespaceName,
-        partition::{persisting::PersistingData, PartitionData, SortKeyState},
+        partition::{
+            persisting::PersistingData, recent::RecentPersistCache, PartitionData, SortKeyState,
+        },
         table::metadata::TableMetadata,
     },
     deferred_load::DeferredLoad,
+    fault_injection::InjectedFault,
     persist::completion_observer::CompletedPersist,
 };
 
-use super::completion_observer::PersistCompletionObserver;
+use super::{barrier::CommitBarrier, completion_observer::PersistCompletionObserver};
 
 /// Errors a persist can experience.
 #[derive(Debug, Error)]
@@ -31,6 +36,18 @@ pub(super) enum PersistError {
     /// aborted. The newly observed sort key is returned.
     #[error("detected concurrent sort key update")]
     ConcurrentSortKeyUpdate(Option<SortKey>, SortedColumnSet),
+
+    /// A test-only fault was injected, and the persist must be restarted.
+    #[error(transparent)]
+    InjectedFault(#[from] InjectedFault),
+
+    /// A different file exists in object storage under the object store ID of
+    /// the parquet file, and the persist must be restarted with a new ID.
+    #[error("parquet file {object_store_id} collides with an existing object store file")]
+    ObjectStoreIdCollision {
+        object_store_id: Uuid,
+        source: object_store::Error,
+    },
 }
 
 /// An internal type that contains all necessary information to run a persist
@@ -42,16 +59,21 @@ pub(super) struct PersistRequest {
     data: PersistingData,
     enqueued_at: Instant,
     permit: OwnedSemaphorePermit,
+    barrier: Option<Arc<CommitBarrier>>,
 }
 
 impl PersistRequest {
     /// Construct a [`PersistRequest`] for `data` from `partition`, recording
     /// the current timestamp as the "enqueued at" point.
+    ///
+    /// If `barrier` is provided, the persisted file is added to the catalog
+    /// together with the files of the other jobs sharing it.
     pub(super) fn new(
         partition: Arc<Mutex<PartitionData>>,
         data: PersistingData,
         permit: OwnedSemaphorePermit,
         enqueued_at: Instant,
+        barrier: Option<Arc<CommitBarrier>>,
     ) -> (Self, oneshot::Receiver<()>) {
         let (tx, rx) = oneshot::channel();
         (
@@ -61,6 +83,7 @@ impl PersistRequest {
                 data,
                 enqueued_at,
                 permit,
+                barrier,
             },
             rx,
         )
@@ -126,6 +149,18 @@ pub(super) struct Context {
     /// work, and MUST be released at the end of the persistence AFTER any
     /// references to the persisted data are released.
     permit: OwnedSemaphorePermit,
+
+    /// The barrier this job's file is committed to the catalog through, if
+    /// the partition is part of a persist barrier group.
+    barrier: Option<Arc<CommitBarrier>>,
+
+    /// True once a parquet file for this job has been uploaded to object
+    /// storage.
+    ///
+    /// A job restarted after uploading its file (such as after a concurrent
+    /// sort key update) regenerates the file under the same object store ID,
+    /// replacing the uncommitted file of its previous attempt.
+    uploaded: bool,
 }
 
 impl Context {
@@ -145,6 +180,7 @@ impl Context {
                 data,
                 enqueued_at,
                 permit,
+                barrier,
             } = req;
 
             let p = Arc::clone(&partition);
@@ -172,6 +208,8 @@ impl Context {
                 enqueued_at,
                 dequeued_at: Instant::now(),
                 permit,
+                barrier,
+                uploaded: false,
             }
         };
 
@@ -231,8 +269,16 @@ impl Context {
     // Call [`PartitionData::mark_complete`] to finalise the persistence job,
     // emit a log for the user, and notify the observer of this persistence
     // task, if any.
-    pub(super) async fn mark_complete<O>(self, metadata: ParquetFile, completion_observer: &O)
-    where
+    //
+    // If `recent_persisted` is provided, the uploaded parquet `data` is
+    // retained in it to continue serving queries for the persisted data.
+    pub(super) async fn mark_complete<O>(
+        self,
+        metadata: ParquetFile,
+        data: Bytes,
+        recent_persisted: Option<&RecentPersistCache>,
+        completion_observer: &O,
+    ) where
         O: PersistCompletionObserver,
     {
         let object_store_id = metadata.object_store_id;
@@ -244,7 +290,16 @@ impl Context {
         // This SHOULD cause the data to be dropped, but there MAY be ongoing
         // queries that currently hold a reference to the data. In either case,
         // the persisted data will be dropped "shortly".
-        let sequence_numbers = self.partition.lock().mark_persisted(self.data);
+        //
+        // Both the retained data and the release of the persisting data
+        // happen under the same lock, so queries observe them atomically.
+        let sequence_numbers = {
+            let mut p = self.partition.lock();
+            if let Some(cache) = recent_persisted {
+                p.retain_persisted(cache, &self.data, data);
+            }
+            p.mark_persisted(self.data)
+        };
         let n_writes = sequence_numbers.len();
 
         // Dispatch the completion notification into the observer chain before
@@ -314,4 +369,30 @@ impl Context {
     pub(super) fn table(&self) -> &DeferredLoad<TableMetadata> {
         self.table.as_ref()
     }
+
+    pub(super) fn barrier(&self) -> Option<&Arc<CommitBarrier>> {
+        self.barrier.as_ref()
+    }
+
+    /// Returns true if a previous attempt of this job uploaded its parquet
+    /// file.
+    pub(super) fn uploaded(&self) -> bool {
+        self.uploaded
+    }
+
+    /// Record the upload of the parquet file of this job.
+    pub(super) fn mark_uploaded(&mut self) {
+        self.uploaded = true;
+    }
+
+    /// Allocate a new object store ID for the persisting data, returning it.
+    ///
+    /// No parquet file has been uploaded under the new ID, so the next attempt
+    /// of this job must not replace an existing file.
+    pub(super) fn reassign_object_store_id(&mut self) -> Uuid {
+        self.uploaded = false;
+        self.partition
+            .lock()
+            .reassign_object_store_id(&mut self.data)
+    }
 }
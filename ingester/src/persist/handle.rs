@@ -1,8 +1,8 @@
-use std::{sync::Arc, time::Duration};
+use std::{num::NonZeroUsize, sync::Arc, time::Duration};
 
 use async_trait::async_trait;
 use iox_catalog::interface::Catalog;
-use iox_query::{exec::Executor, QueryChunk};
+use iox_query::QueryChunk;
 use metric::{DurationHistogram, DurationHistogramOptions, U64Counter, U64Gauge, DURATION_MAX};
 use observability_deps::tracing::*;
 use parking_lot::Mutex;
@@ -15,14 +15,26 @@ use tokio::{
 };
 
 use super::{
-    backpressure::PersistState, column_map_resolver::ColumnMapResolver,
-    completion_observer::PersistCompletionObserver, context::PersistRequest, queue::PersistQueue,
+    backpressure::{PersistQueueOccupancy, PersistState},
+    barrier::{CommitBarrier, PersistBarrier},
+    circuit_breaker::PersistCircuitBreaker,
+    column_map_resolver::ColumnMapResolver,
+    compact::CompactionMemoryLimit,
+    completion_observer::PersistCompletionObserver,
+    context::PersistRequest,
+    jobs::PersistJobs,
+    persist_metrics::PersistMetrics,
+    queue::PersistQueue,
     worker::SharedWorkerState,
 };
 use crate::{
-    buffer_tree::partition::{persisting::PersistingData, PartitionData, SortKeyState},
+    buffer_tree::partition::{
+        persisting::PersistingData, recent::RecentPersistCache, PartitionData, SortKeyState,
+    },
+    fault_injection::FaultInjector,
     ingest_state::IngestState,
     persist::worker,
+    priority_executor::PriorityExecutor,
 };
 
 /// A persistence task submission handle.
@@ -123,6 +135,31 @@ use crate::{
 /// For details of the exact saturation detection & recovery logic, see
 /// [`PersistState`].
 ///
+/// # Recently Persisted Data
+///
+/// When `recent_persisted_bytes` is non-zero, the parquet encoding of the most
+/// recently persisted data of each partition is retained in memory after the
+/// persist completes, up to a total of `recent_persisted_bytes` across all
+/// partitions. The retained data continues to be served to queries, avoiding
+/// reading freshly persisted data back from object storage. See
+/// [`RecentPersistCache`].
+///
+/// # Null Column Pruning
+///
+/// When `drop_null_columns` is true, the columns containing only NULL values
+/// in the persisted data are removed from the parquet file. They remain in
+/// the table schema, and are read as NULL by queriers.
+///
+/// # Persist Barriers
+///
+/// When configured with a [`PersistBarrier`] (see
+/// [`PersistHandle::with_persist_barrier()`]), enqueuing a partition of a table
+/// in a persist barrier group also persists the data buffered in the
+/// partitions with the same partition key of the other tables in the group,
+/// and the files of all these jobs are added to the catalog in a single
+/// transaction. Data already persisting when the group is enqueued is committed
+/// by its own job - see the [`barrier`](super::barrier) module.
+///
 /// [`IngestStateError::PersistSaturated`]:
 ///     crate::ingest_state::IngestStateError::PersistSaturated
 #[derive(Debug)]
@@ -165,6 +202,20 @@ pub(crate) struct PersistHandle {
 
     /// A counter tracking the number of enqueued into the persist system.
     enqueued_jobs: U64Counter,
+
+    /// The groups of tables whose partitions are persisted together, if any.
+    barrier: Option<Arc<PersistBarrier>>,
+
+    /// The persist jobs currently executing in the workers.
+    jobs: Arc<PersistJobs>,
+
+    /// The cache of recently persisted parquet data shared with the workers,
+    /// if enabled.
+    recent_persisted: Option<Arc<RecentPersistCache>>,
+
+    /// The test-only faults injected into persist jobs.
+    #[cfg(feature = "fault_injection")]
+    faults: Arc<FaultInjector>,
 }
 
 impl PersistHandle {
@@ -174,11 +225,15 @@ impl PersistHandle {
         n_workers: usize,
         persist_queue_depth: usize,
         ingest_state: Arc<IngestState>,
-        exec: Arc<Executor>,
+        exec: PriorityExecutor,
         store: ParquetStorage,
         catalog: Arc<dyn Catalog>,
         completion_observer: O,
         column_map_resolver: C,
+        recent_persisted_bytes: usize,
+        compaction_memory_limit: Option<CompactionMemoryLimit>,
+        circuit_breaker_threshold: NonZeroUsize,
+        drop_null_columns: bool,
         metrics: &metric::Registry,
     ) -> Self
     where
@@ -194,12 +249,32 @@ impl PersistHandle {
         // Log the important configuration parameters of the persist subsystem.
         info!(n_workers, persist_queue_depth, "initialised persist task");
 
+        let faults = Arc::new(FaultInjector::default());
+        let jobs = Arc::new(PersistJobs::default());
+        let recent_persisted = (recent_persisted_bytes > 0)
+            .then(|| Arc::new(RecentPersistCache::new(recent_persisted_bytes)));
+
+        // Fail object store puts when configured to.
+        #[cfg(feature = "fault_injection")]
+        let store = faults.wrap_storage(store);
+
         let worker_state = Arc::new(SharedWorkerState {
             exec,
             store,
             catalog,
             column_map_resolver,
             completion_observer,
+            persist_metrics: PersistMetrics::new(metrics),
+            recent_persisted: recent_persisted.as_ref().map(Arc::clone),
+            compaction_memory_limit,
+            circuit_breaker: PersistCircuitBreaker::new(
+                circuit_breaker_threshold,
+                Arc::clone(&ingest_state),
+                metrics,
+            ),
+            drop_null_columns,
+            jobs: Arc::clone(&jobs),
+            faults: Arc::clone(&faults),
         });
 
         // Initialise a histogram to capture persist job duration & time spent
@@ -311,9 +386,50 @@ impl PersistHandle {
             worker_tasks,
             persist_state,
             enqueued_jobs,
+            barrier: None,
+            jobs,
+            recent_persisted,
+            #[cfg(feature = "fault_injection")]
+            faults,
         }
     }
 
+    /// Persist the partitions of the tables in the groups of `barrier`
+    /// together.
+    ///
+    /// The largest group MUST NOT contain more tables than the
+    /// `persist_queue_depth`, as the jobs of a group wait for each other while
+    /// occupying the queue.
+    pub(crate) fn with_persist_barrier(mut self, barrier: Arc<PersistBarrier>) -> Self {
+        self.barrier = Some(barrier);
+        self
+    }
+
+    /// Return a [`PersistQueueOccupancy`] reporting the utilisation of the
+    /// persist queue.
+    pub(crate) fn queue_occupancy(&self) -> PersistQueueOccupancy {
+        self.persist_state.occupancy()
+    }
+
+    /// Return the [`PersistJobs`] registry of the persist jobs currently
+    /// executing.
+    pub(crate) fn jobs(&self) -> &Arc<PersistJobs> {
+        &self.jobs
+    }
+
+    /// Return the [`RecentPersistCache`] of recently persisted parquet data,
+    /// if enabled.
+    pub(crate) fn recent_persisted(&self) -> Option<&Arc<RecentPersistCache>> {
+        self.recent_persisted.as_ref()
+    }
+
+    /// Return the [`FaultInjector`] used to inject test-only faults into
+    /// persist jobs.
+    #[cfg(feature = "fault_injection")]
+    pub(crate) fn faults(&self) -> &Arc<FaultInjector> {
+        &self.faults
+    }
+
     fn assign_worker(&self, r: PersistRequest) {
         debug!(
             partition_id = %r.partition_id(),
@@ -327,39 +443,70 @@ impl PersistHandle {
             .send(r)
             .expect("persist worker stopped");
     }
-}
 
-#[async_trait]
-impl PersistQueue for PersistHandle {
-    /// Place `data` from `partition` into the persistence queue.
-    ///
-    /// This call (asynchronously) waits for space to become available in the
-    /// persistence queue.
-    ///
-    /// Once persistence is complete, the partition will be locked and the sort
-    /// key will be updated (if necessary), and
-    /// [`PartitionData::mark_persisted()`] is called with `data` to mark the
-    /// task as complete.
-    ///
-    /// Once all persistence related tasks for `data` are complete, the returned
-    /// channel publishes a notification.
-    ///
-    /// Persist tasks may be re-ordered w.r.t their submission order for
-    /// performance reasons.
-    ///
-    /// # Panics
+    /// Enqueue `data` from `partition` together with the data buffered in
+    /// `siblings`, committing the files of all the resulting persist jobs
+    /// through a shared [`CommitBarrier`].
     ///
-    /// Panics if the assigned persist worker task has stopped.
-    ///
-    /// Panics (asynchronously) if the [`PartitionData`]'s sort key is updated
-    /// between persistence starting and ending.
+    /// The returned channel publishes a notification once the persist job of
+    /// `data` is complete, at which point the files of all the jobs in the
+    /// group are visible in the catalog.
+    #[allow(clippy::async_yields_async)]
+    async fn enqueue_group(
+        &self,
+        barrier: &PersistBarrier,
+        partition: Arc<Mutex<PartitionData>>,
+        data: PersistingData,
+        siblings: Vec<Arc<Mutex<PartitionData>>>,
+    ) -> oneshot::Receiver<()> {
+        // Hold the enqueue lock until all jobs of the group have obtained a
+        // persist permit.
+        let _guard = barrier.lock_enqueue().await;
+
+        // Partitions without buffered data have nothing to contribute.
+        let siblings = siblings
+            .into_iter()
+            .filter_map(|p| {
+                let data = p.lock().mark_persisting()?;
+                Some((p, data))
+            })
+            .collect::<Vec<_>>();
+
+        if siblings.is_empty() {
+            return self.enqueue_request(partition, data, None).await;
+        }
+
+        debug!(
+            partition_id = %data.partition_id(),
+            n_siblings = siblings.len(),
+            "enqueuing persist barrier group"
+        );
+
+        let commit = Arc::new(CommitBarrier::new(siblings.len() + 1));
+        let notify = self
+            .enqueue_request(partition, data, Some(Arc::clone(&commit)))
+            .await;
+        for (p, data) in siblings {
+            // The files of the group are committed together, so only the
+            // notification for `data` is needed.
+            let _ = self
+                .enqueue_request(p, data, Some(Arc::clone(&commit)))
+                .await;
+        }
+
+        notify
+    }
+
+    /// Place `data` from `partition` into the persistence queue, committing
+    /// the resulting file through `barrier` if provided.
     ///
-    /// This will panic (asynchronously) if `data` was not from `partition`.
-    #[allow(clippy::async_yields_async)] // Callers may want to wait async
-    async fn enqueue(
+    /// See [`PersistQueue::enqueue()`].
+    #[allow(clippy::async_yields_async)]
+    async fn enqueue_request(
         &self,
         partition: Arc<Mutex<PartitionData>>,
         data: PersistingData,
+        barrier: Option<Arc<CommitBarrier>>,
     ) -> oneshot::Receiver<()> {
         let partition_id = data.partition_id().clone();
         debug!(%partition_id, "enqueuing persistence task");
@@ -434,7 +581,8 @@ impl PersistQueue for PersistHandle {
 
         // Build the persist task request.
         let schema = data.schema().clone();
-        let (r, notify) = PersistRequest::new(Arc::clone(&partition), data, permit, enqueued_at);
+        let (r, notify) =
+            PersistRequest::new(Arc::clone(&partition), data, permit, enqueued_at, barrier);
 
         match sort_key {
             Some(v) => {
@@ -471,6 +619,48 @@ impl PersistQueue for PersistHandle {
     }
 }
 
+#[async_trait]
+impl PersistQueue for PersistHandle {
+    /// Place `data` from `partition` into the persistence queue.
+    ///
+    /// This call (asynchronously) waits for space to become available in the
+    /// persistence queue.
+    ///
+    /// Once persistence is complete, the partition will be locked and the sort
+    /// key will be updated (if necessary), and
+    /// [`PartitionData::mark_persisted()`] is called with `data` to mark the
+    /// task as complete.
+    ///
+    /// Once all persistence related tasks for `data` are complete, the returned
+    /// channel publishes a notification.
+    ///
+    /// Persist tasks may be re-ordered w.r.t their submission order for
+    /// performance reasons.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the assigned persist worker task has stopped.
+    ///
+    /// Panics (asynchronously) if the [`PartitionData`]'s sort key is updated
+    /// between persistence starting and ending.
+    ///
+    /// This will panic (asynchronously) if `data` was not from `partition`.
+    #[allow(clippy::async_yields_async)] // Callers may want to wait async
+    async fn enqueue(
+        &self,
+        partition: Arc<Mutex<PartitionData>>,
+        data: PersistingData,
+    ) -> oneshot::Receiver<()> {
+        if let Some(barrier) = &self.barrier {
+            if let Some(siblings) = barrier.siblings(&partition).await {
+                return self.enqueue_group(barrier, partition, data, siblings).await;
+            }
+        }
+
+        self.enqueue_request(partition, data, None).await
+    }
+}
+
 #[derive(Debug)]
 struct AbortOnDrop<T>(tokio::task::JoinHandle<T>);
 
@@ -561,11 +751,15 @@ mod tests {
             1,
             2,
             Arc::new(IngestState::default()),
-            Arc::new(Executor::new_testing()),
+            PriorityExecutor::new_testing(),
             storage,
             Arc::clone(&catalog),
             Arc::new(MockCompletionObserver::default()),
             CatalogColumnMapResolver::new(catalog),
+            0,
+            None,
+            NonZeroUsize::new(10).unwrap(),
+            false,
             &metrics,
         );
 
@@ -638,11 +832,15 @@ mod tests {
             1,
             2,
             Arc::new(IngestState::default()),
-            Arc::new(Executor::new_testing()),
+            PriorityExecutor::new_testing(),
             storage,
             Arc::clone(&catalog),
             Arc::new(MockCompletionObserver::default()),
             CatalogColumnMapResolver::new(catalog),
+            0,
+            None,
+            NonZeroUsize::new(10).unwrap(),
+            false,
             &metrics,
         );
 
@@ -727,11 +925,15 @@ mod tests {
             1,
             2,
             Arc::new(IngestState::default()),
-            Arc::new(Executor::new_testing()),
+            PriorityExecutor::new_testing(),
             storage,
             Arc::clone(&catalog),
             Arc::new(MockCompletionObserver::default()),
             CatalogColumnMapResolver::new(catalog),
+            0,
+            None,
+            NonZeroUsize::new(10).unwrap(),
+            false,
             &metrics,
         );
 
@@ -820,11 +1022,15 @@ mod tests {
             1,
             2,
             Arc::new(IngestState::default()),
-            Arc::new(Executor::new_testing()),
+            PriorityExecutor::new_testing(),
             storage,
             Arc::clone(&catalog),
             Arc::new(MockCompletionObserver::default()),
             CatalogColumnMapResolver::new(catalog),
+            0,
+            None,
+            NonZeroUsize::new(10).unwrap(),
+            false,
             &metrics,
         );
 
@@ -907,11 +1113,15 @@ mod tests {
             1,
             1,
             Arc::clone(&ingest_state),
-            Arc::new(Executor::new_testing()),
+            PriorityExecutor::new_testing(),
             storage,
             Arc::clone(&catalog),
             NopObserver,
             CatalogColumnMapResolver::new(catalog),
+            0,
+            None,
+            NonZeroUsize::new(10).unwrap(),
+            false,
             &metrics,
         );
         assert!(ingest_state.read().is_ok());
@@ -988,11 +1198,15 @@ mod tests {
             5,
             42,
             Arc::clone(&ingest_state),
-            Arc::new(Executor::new_testing()),
+            PriorityExecutor::new_testing(),
             storage,
             Arc::clone(&catalog),
             NopObserver,
             CatalogColumnMapResolver::new(catalog),
+            0,
+            None,
+            NonZeroUsize::new(10).unwrap(),
+            false,
             &metrics,
         );
 
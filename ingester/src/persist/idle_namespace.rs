@@ -0,0 +1,176 @@
+//! A background task persisting the buffered data of idle namespaces.
+//!
+//! In a cluster serving many namespaces, most are written to only
+//! occasionally, yet the data of each is buffered until its partitions become
+//! hot or the WAL is rotated. Persisting the data of a namespace once it has
+//! neither been written to nor queried for a while releases the memory it
+//! holds sooner.
+
+use std::{fmt::Debug, sync::Arc, time::Duration};
+
+use metric::U64Counter;
+use observability_deps::tracing::*;
+use tokio::task::JoinHandle;
+
+use super::{drain_buffer::persist_partitions, queue::PersistQueue};
+use crate::buffer_tree::BufferTree;
+
+/// The maximum period between two checks for idle namespaces.
+const MAX_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Spawn a task persisting the buffered data of the namespaces in `buffer`
+/// that have been neither written to nor queried for `idle_threshold`.
+///
+/// Each namespace whose data is persisted is logged and counted in the
+/// `ingester_idle_namespace_persisted` metric.
+pub(crate) fn spawn_idle_namespace_persist<O, P>(
+    buffer: Arc<BufferTree<O>>,
+    persist: P,
+    idle_threshold: Duration,
+    metrics: &metric::Registry,
+) -> JoinHandle<()>
+where
+    O: Send + Sync + Debug + 'static,
+    P: PersistQueue + Clone + Sync + 'static,
+{
+    let persisted = metrics
+        .register_metric::<U64Counter>(
+            "ingester_idle_namespace_persisted",
+            "number of times the buffered data of an idle namespace was persisted",
+        )
+        .recorder(&[]);
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(idle_threshold.min(MAX_CHECK_INTERVAL));
+        loop {
+            interval.tick().await;
+            let n = persist_idle_namespaces(&buffer, &persist, idle_threshold).await;
+            persisted.inc(n as u64);
+        }
+    })
+}
+
+/// Persist the buffered data of the namespaces idle for at least
+/// `idle_threshold`, returning the number of namespaces with data persisted.
+async fn persist_idle_namespaces<O, P>(
+    buffer: &BufferTree<O>,
+    persist: &P,
+    idle_threshold: Duration,
+) -> usize
+where
+    O: Send + Sync + Debug,
+    P: PersistQueue + Clone,
+{
+    let mut n_namespaces = 0;
+
+    for namespace in buffer.namespaces() {
+        let idle_for = namespace.activity().idle_for();
+        if idle_for < idle_threshold {
+            continue;
+        }
+
+        // Namespaces with no buffered data persist no partitions.
+        let n_partitions = persist_partitions(namespace.partitions(), persist).await;
+        if n_partitions == 0 {
+            continue;
+        }
+
+        info!(
+            namespace_id = %namespace.namespace_id(),
+            ?idle_for,
+            n_partitions,
+            "persisted idle namespace"
+        );
+        n_namespaces += 1;
+    }
+
+    n_namespaces
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroUsize;
+
+    use super::*;
+    use crate::{
+        buffer_tree::{
+            namespace::name_resolver::mock::MockNamespaceNameProvider,
+            partition::resolver::mock::MockPartitionProvider,
+            post_write::mock::MockPostWriteObserver,
+        },
+        dml_payload::IngestOp,
+        dml_sink::DmlSink,
+        persist::queue::mock::MockPersistQueue,
+        test_util::{
+            make_write_op, PartitionDataBuilder, ARBITRARY_NAMESPACE_ID, ARBITRARY_NAMESPACE_NAME,
+            ARBITRARY_PARTITION_KEY, ARBITRARY_TABLE_ID, ARBITRARY_TABLE_NAME,
+            ARBITRARY_TABLE_PROVIDER,
+        },
+    };
+
+    const IDLE_THRESHOLD: Duration = Duration::from_secs(60);
+
+    #[tokio::test(start_paused = true)]
+    async fn test_persist_idle_namespaces() {
+        let buffer = BufferTree::new(
+            Arc::new(MockNamespaceNameProvider::new(&**ARBITRARY_NAMESPACE_NAME)),
+            Arc::clone(&*ARBITRARY_TABLE_PROVIDER),
+            Arc::new(MockPartitionProvider::default().with_partition(PartitionDataBuilder::new())),
+            NonZeroUsize::new(usize::MAX).unwrap(),
+            Arc::new(MockPostWriteObserver::default()),
+            Arc::new(metric::Registry::default()),
+        );
+        let persist = Arc::new(MockPersistQueue::default());
+
+        buffer
+            .apply(IngestOp::Write(make_write_op(
+                &ARBITRARY_PARTITION_KEY,
+                ARBITRARY_NAMESPACE_ID,
+                &ARBITRARY_TABLE_NAME,
+                ARBITRARY_TABLE_ID,
+                0,
+                &format!(
+                    r#"{},city=Madrid temp=35 4242424242"#,
+                    &*ARBITRARY_TABLE_NAME
+                ),
+                None,
+            )))
+            .await
+            .expect("write should succeed");
+
+        // The namespace was just written to.
+        assert_eq!(
+            persist_idle_namespaces(&buffer, &persist, IDLE_THRESHOLD).await,
+            0
+        );
+        assert!(persist.calls().is_empty());
+
+        // A query keeps the namespace active.
+        tokio::time::advance(IDLE_THRESHOLD / 2).await;
+        buffer
+            .namespace(ARBITRARY_NAMESPACE_ID)
+            .unwrap()
+            .activity()
+            .record_query();
+        tokio::time::advance(IDLE_THRESHOLD / 2).await;
+        assert_eq!(
+            persist_idle_namespaces(&buffer, &persist, IDLE_THRESHOLD).await,
+            0
+        );
+
+        // Once idle, its data is persisted.
+        tokio::time::advance(IDLE_THRESHOLD / 2).await;
+        assert_eq!(
+            persist_idle_namespaces(&buffer, &persist, IDLE_THRESHOLD).await,
+            1
+        );
+        assert_eq!(persist.calls().len(), 1);
+
+        // And not persisted again, as it buffers no data.
+        assert_eq!(
+            persist_idle_namespaces(&buffer, &persist, IDLE_THRESHOLD).await,
+            0
+        );
+        assert_eq!(persist.calls().len(), 1);
+    }
+}
@@ -0,0 +1,245 @@
+//! A registry of the persist jobs currently executing, reporting their
+//! progress.
+
+use std::{collections::BTreeMap, sync::Arc, time::Duration};
+
+use data_types::{NamespaceId, TableId, TransitionPartitionId};
+use parking_lot::Mutex;
+use tokio::time::Instant;
+
+/// The stage of an executing persist job.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum PersistStage {
+    /// Compacting the buffered data and encoding it as a parquet file.
+    Compacting,
+
+    /// Uploading the parquet file to object storage.
+    Uploading,
+
+    /// Updating the catalog with the partition sort key or the new parquet
+    /// file.
+    Committing,
+}
+
+/// A point-in-time view of the progress of an executing persist job.
+#[derive(Debug, Clone)]
+pub(crate) struct PersistJobStatus {
+    pub(crate) namespace_id: NamespaceId,
+    pub(crate) table_id: TableId,
+    pub(crate) partition_id: TransitionPartitionId,
+
+    /// The current stage of the job.
+    pub(crate) stage: PersistStage,
+
+    /// The time since the job started executing.
+    pub(crate) active_duration: Duration,
+
+    /// The time since the job entered its current stage.
+    pub(crate) stage_duration: Duration,
+
+    /// The in-memory size of the buffered data being persisted.
+    pub(crate) input_bytes: usize,
+
+    /// The size of the encoded parquet file, once compacted.
+    pub(crate) file_bytes: Option<usize>,
+
+    /// The number of times the job restarted compacting.
+    pub(crate) restarts: usize,
+}
+
+#[derive(Debug)]
+struct JobState {
+    namespace_id: NamespaceId,
+    table_id: TableId,
+    partition_id: TransitionPartitionId,
+    stage: PersistStage,
+    started_at: Instant,
+    stage_started_at: Instant,
+    input_bytes: usize,
+    file_bytes: Option<usize>,
+    restarts: usize,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    next_id: u64,
+    jobs: BTreeMap<u64, JobState>,
+}
+
+/// Tracks the persist jobs executing in the persist workers, allowing
+/// operators to tell whether a long-running persist is making progress or is
+/// stuck retrying a failing operation.
+///
+/// A job is listed from the time a worker starts executing it until the
+/// [`ActivePersistJob`] returned by [`PersistJobs::register()`] is dropped.
+#[derive(Debug, Default)]
+pub(crate) struct PersistJobs {
+    state: Mutex<State>,
+}
+
+impl PersistJobs {
+    /// Register a job persisting `input_bytes` of buffered data for the
+    /// specified partition, starting in the [`PersistStage::Compacting`]
+    /// stage.
+    pub(crate) fn register(
+        self: &Arc<Self>,
+        namespace_id: NamespaceId,
+        table_id: TableId,
+        partition_id: TransitionPartitionId,
+        input_bytes: usize,
+    ) -> ActivePersistJob {
+        let now = Instant::now();
+        let mut state = self.state.lock();
+
+        let id = state.next_id;
+        state.next_id += 1;
+        state.jobs.insert(
+            id,
+            JobState {
+                namespace_id,
+                table_id,
+                partition_id,
+                stage: PersistStage::Compacting,
+                started_at: now,
+                stage_started_at: now,
+                input_bytes,
+                file_bytes: None,
+                restarts: 0,
+            },
+        );
+
+        ActivePersistJob {
+            id,
+            jobs: Arc::clone(self),
+        }
+    }
+
+    /// Return the status of the executing jobs, oldest first.
+    pub(crate) fn list(&self) -> Vec<PersistJobStatus> {
+        let now = Instant::now();
+        self.state
+            .lock()
+            .jobs
+            .values()
+            .map(|job| PersistJobStatus {
+                namespace_id: job.namespace_id,
+                table_id: job.table_id,
+                partition_id: job.partition_id.clone(),
+                stage: job.stage,
+                active_duration: now.saturating_duration_since(job.started_at),
+                stage_duration: now.saturating_duration_since(job.stage_started_at),
+                input_bytes: job.input_bytes,
+                file_bytes: job.file_bytes,
+                restarts: job.restarts,
+            })
+            .collect()
+    }
+
+    fn update(&self, id: u64, f: impl FnOnce(&mut JobState)) {
+        if let Some(job) = self.state.lock().jobs.get_mut(&id) {
+            f(job);
+        }
+    }
+}
+
+/// A job registered in [`PersistJobs`], removed from the registry when
+/// dropped.
+#[derive(Debug)]
+pub(crate) struct ActivePersistJob {
+    id: u64,
+    jobs: Arc<PersistJobs>,
+}
+
+impl ActivePersistJob {
+    /// Record the job entering `stage`.
+    pub(crate) fn set_stage(&self, stage: PersistStage) {
+        self.jobs.update(self.id, |job| {
+            if job.stage != stage {
+                job.stage = stage;
+                job.stage_started_at = Instant::now();
+            }
+        });
+    }
+
+    /// Record the size of the encoded parquet file.
+    pub(crate) fn set_file_bytes(&self, file_bytes: usize) {
+        self.jobs
+            .update(self.id, |job| job.file_bytes = Some(file_bytes));
+    }
+
+    /// Record the job restarting from the [`PersistStage::Compacting`] stage.
+    pub(crate) fn record_restart(&self) {
+        self.jobs.update(self.id, |job| job.restarts += 1);
+        self.set_stage(PersistStage::Compacting);
+    }
+}
+
+impl Drop for ActivePersistJob {
+    fn drop(&mut self) {
+        self.jobs.state.lock().jobs.remove(&self.id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_matches::assert_matches;
+    use data_types::PartitionId;
+
+    use super::*;
+
+    #[tokio::test(start_paused = true)]
+    async fn test_persist_jobs() {
+        let jobs = Arc::new(PersistJobs::default());
+        assert!(jobs.list().is_empty());
+
+        let partition_id = TransitionPartitionId::Deprecated(PartitionId::new(3));
+        let a = jobs.register(
+            NamespaceId::new(1),
+            TableId::new(2),
+            partition_id.clone(),
+            42,
+        );
+
+        tokio::time::advance(Duration::from_secs(5)).await;
+        let b = jobs.register(
+            NamespaceId::new(1),
+            TableId::new(2),
+            partition_id.clone(),
+            24,
+        );
+
+        tokio::time::advance(Duration::from_secs(5)).await;
+        a.set_stage(PersistStage::Uploading);
+        a.set_file_bytes(10);
+
+        tokio::time::advance(Duration::from_secs(1)).await;
+        assert_matches!(jobs.list().as_slice(), [a, b] => {
+            assert_eq!(a.partition_id, partition_id);
+            assert_eq!(a.stage, PersistStage::Uploading);
+            assert_eq!(a.active_duration, Duration::from_secs(11));
+            assert_eq!(a.stage_duration, Duration::from_secs(1));
+            assert_eq!(a.input_bytes, 42);
+            assert_eq!(a.file_bytes, Some(10));
+
+            assert_eq!(b.stage, PersistStage::Compacting);
+            assert_eq!(b.active_duration, Duration::from_secs(6));
+            assert_eq!(b.file_bytes, None);
+        });
+
+        // A restart returns the job to compacting.
+        a.record_restart();
+        assert_matches!(jobs.list().as_slice(), [a, _] => {
+            assert_eq!(a.stage, PersistStage::Compacting);
+            assert_eq!(a.stage_duration, Duration::ZERO);
+            assert_eq!(a.restarts, 1);
+        });
+
+        // Completed jobs are removed.
+        drop(a);
+        assert_matches!(jobs.list().as_slice(), [b] => {
+            assert_eq!(b.input_bytes, 24);
+        });
+        drop(b);
+        assert!(jobs.list().is_empty());
+    }
+}
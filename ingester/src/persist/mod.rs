@@ -1,14 +1,19 @@
 //! The persistence subsystem; abstractions, types, and implementation.
 
 pub(crate) mod backpressure;
+pub(crate) mod barrier;
+pub(crate) mod circuit_breaker;
 pub(crate) mod column_map_resolver;
-pub(super) mod compact;
+pub(crate) mod compact;
 pub(crate) mod completion_observer;
 mod context;
 pub(crate) mod drain_buffer;
 pub(crate) mod file_metrics;
 pub(crate) mod handle;
 pub(crate) mod hot_partitions;
+pub(crate) mod idle_namespace;
+pub(crate) mod jobs;
+mod persist_metrics;
 pub mod queue;
 mod worker;
 
@@ -17,6 +22,7 @@ mod tests {
     use std::{num::NonZeroUsize, sync::Arc, time::Duration};
 
     use assert_matches::assert_matches;
+    use bytes::Bytes;
     use data_types::{CompactionLevel, ParquetFile, SortedColumnSet};
     use futures::TryStreamExt;
     use iox_catalog::{
@@ -49,6 +55,7 @@ mod tests {
             column_map_resolver::CatalogColumnMapResolver,
             completion_observer::mock::MockCompletionObserver, queue::PersistQueue,
         },
+        priority_executor::PriorityExecutor,
         test_util::{
             make_write_op, populate_catalog, ARBITRARY_NAMESPACE_NAME,
             ARBITRARY_NAMESPACE_NAME_PROVIDER, ARBITRARY_PARTITION_KEY, ARBITRARY_TABLE_NAME,
@@ -185,11 +192,15 @@ mod tests {
             1,
             2,
             Arc::clone(&ingest_state),
-            Arc::new(Executor::new_testing()),
+            PriorityExecutor::new_testing(),
             storage,
             Arc::clone(&catalog),
             Arc::clone(&completion_observer),
             column_map_resolver,
+            0,
+            None,
+            NonZeroUsize::new(10).unwrap(),
+            false,
             &metrics,
         );
         assert!(ingest_state.read().is_ok());
@@ -348,11 +359,15 @@ mod tests {
             1,
             2,
             Arc::clone(&ingest_state),
-            Arc::new(Executor::new_testing()),
+            PriorityExecutor::new_testing(),
             storage,
             Arc::clone(&catalog),
             Arc::clone(&completion_observer),
             column_map_resolver,
+            0,
+            None,
+            NonZeroUsize::new(10).unwrap(),
+            false,
             &metrics,
         );
         assert!(ingest_state.read().is_ok());
@@ -502,10 +517,10 @@ mod tests {
             .await
             .expect("failed to list object store files");
 
-        // Two files should have been uploaded - first the one that observed the
-        // concurrent sort key update, and then the resorted file using the
-        // observed sort key.
-        assert_eq!(files.len(), 2, "expected two uploaded files");
+        // A single file should exist - the file that observed the concurrent
+        // sort key update is replaced by the resorted file using the observed
+        // sort key, uploaded under the same object store ID.
+        assert_eq!(files.len(), 1, "expected one uploaded file");
 
         // Ensure the catalog record points at a valid file in object storage.
         let want_path =
@@ -518,4 +533,103 @@ mod tests {
 
         assert_eq!(file.size, *file_size_bytes as usize);
     }
+
+    /// An integration test covering an object store ID collision, where a
+    /// different file already exists under the object store ID of the
+    /// persisting data.
+    #[tokio::test]
+    async fn test_persist_integration_object_store_id_collision() {
+        maybe_start_logging();
+
+        let object_storage: Arc<dyn ObjectStore> = Arc::new(InMemory::default());
+        let storage = ParquetStorage::new(Arc::clone(&object_storage), StorageId::from("iox"));
+        let metrics = Arc::new(metric::Registry::default());
+        let catalog: Arc<dyn Catalog> = Arc::new(MemCatalog::new(Arc::clone(&metrics)));
+        let column_map_resolver = CatalogColumnMapResolver::new(Arc::clone(&catalog));
+        let ingest_state = Arc::new(IngestState::default());
+        let completion_observer = Arc::new(MockCompletionObserver::default());
+
+        // Initialise the persist system.
+        let handle = PersistHandle::new(
+            1,
+            2,
+            Arc::clone(&ingest_state),
+            PriorityExecutor::new_testing(),
+            storage,
+            Arc::clone(&catalog),
+            Arc::clone(&completion_observer),
+            column_map_resolver,
+            0,
+            None,
+            NonZeroUsize::new(10).unwrap(),
+            false,
+            &metrics,
+        );
+
+        // Generate a partition with data, and transition it to "persisting".
+        let partition = partition_with_write(Arc::clone(&catalog)).await;
+        let table_id = partition.lock().table_id();
+        let partition_id = partition.lock().partition_id().clone();
+        let namespace_id = partition.lock().namespace_id();
+        let data = partition
+            .lock()
+            .mark_persisting()
+            .expect("partition with write should transition to persisting");
+
+        // Upload a different file under the object store ID allocated to the
+        // persisting data.
+        let colliding_id = data.object_store_id();
+        let colliding_path =
+            ParquetFilePath::new(namespace_id, table_id, &partition_id, colliding_id)
+                .object_store_path();
+        object_storage
+            .put(&colliding_path, Bytes::from_static(b"bananas"))
+            .await
+            .expect("failed to upload colliding file");
+
+        // Enqueue the persist job, and wait for it to complete, rather than
+        // the worker panicking.
+        let notify = handle.enqueue(Arc::clone(&partition), data).await;
+        notify
+            .with_timeout(Duration::from_secs(10))
+            .await
+            .expect("timeout waiting for completion notification")
+            .expect("worker task failed");
+
+        assert_matches!(&completion_observer.calls().as_slice(), &[n] => {
+            assert_eq!(n.partition_id(), &partition_id);
+            assert_eq!(n.sequence_numbers().len(), 1);
+        });
+        assert_eq!(partition.lock().completed_persistence_count(), 1);
+        assert!(partition.lock().persisting_object_store_ids().is_empty());
+
+        // The file was persisted under a new object store ID.
+        let files = catalog
+            .repositories()
+            .await
+            .parquet_files()
+            .list_by_partition_not_to_delete(&partition_id)
+            .await
+            .expect("query for parquet files failed");
+        let object_store_id = assert_matches!(&*files, [f] => f.object_store_id);
+        assert_ne!(object_store_id, colliding_id);
+
+        // And the colliding file was left untouched.
+        let colliding = object_storage
+            .get(&colliding_path)
+            .await
+            .expect("colliding file must exist")
+            .bytes()
+            .await
+            .expect("failed to read colliding file");
+        assert_eq!(colliding, Bytes::from_static(b"bananas"));
+
+        let want_path =
+            ParquetFilePath::new(namespace_id, table_id, &partition_id, object_store_id)
+                .object_store_path();
+        object_storage
+            .head(&want_path)
+            .await
+            .expect("persisted file must exist");
+    }
 }
@@ -0,0 +1,403 @@
+//! Per-namespace instrumentation of the individual steps of a persist job.
+
+use std::{
+    pin::Pin,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
+
+use datafusion::physical_plan::{
+    stream::RecordBatchStreamAdapter, RecordBatchStream, SendableRecordBatchStream,
+};
+use futures::{Stream, StreamExt};
+use metric::{
+    DurationHistogram, DurationHistogramOptions, Metric, U64Counter, U64Histogram,
+    U64HistogramOptions, DURATION_MAX,
+};
+
+/// The duration histogram buckets used for the persist steps, increasing
+/// exponentially from 1ms to ~4 minutes.
+fn duration_buckets() -> DurationHistogramOptions {
+    DurationHistogramOptions::new(
+        (0..10)
+            .map(|i| Duration::from_millis(4_u64.pow(i)))
+            .chain([DURATION_MAX]),
+    )
+}
+
+/// The measurements of a single persist job, recorded into the
+/// [`PersistMetrics`] once the parquet file has been uploaded.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub(super) struct PersistObservation {
+    /// Time spent compacting (sorting & deduplicating) the persisting data.
+    pub(super) compact: Duration,
+    /// Time spent encoding the compacted data as parquet, excluding the
+    /// (lazily executed) compaction.
+    pub(super) encode: Duration,
+    /// Time spent uploading the parquet file to object storage.
+    pub(super) upload: Duration,
+
+    /// The number of rows in the persisting data, prior to deduplication.
+    pub(super) input_rows: usize,
+    /// The in-memory size of the persisting data.
+    pub(super) input_bytes: usize,
+    /// The size of the output parquet file.
+    pub(super) output_bytes: usize,
+
+    /// The number of times the compaction spilled data to disk.
+    pub(super) spill_count: usize,
+    /// The number of bytes the compaction spilled to disk.
+    pub(super) spilled_bytes: usize,
+
+    /// True if the persisting data was not sorted by time, as written.
+    pub(super) unsorted: bool,
+}
+
+/// Histograms of the duration and size of each step of a persist job, faceted
+/// by namespace name.
+#[derive(Debug)]
+pub(super) struct PersistMetrics {
+    compact_duration: Metric<DurationHistogram>,
+    encode_duration: Metric<DurationHistogram>,
+    upload_duration: Metric<DurationHistogram>,
+
+    output_bytes: Metric<U64Histogram>,
+    compacted_rows: Metric<U64Histogram>,
+    amplification_ratio: Metric<U64Histogram>,
+
+    spill_count: Metric<U64Counter>,
+    spilled_bytes: Metric<U64Counter>,
+
+    unsorted_jobs: Metric<U64Counter>,
+}
+
+impl PersistMetrics {
+    pub(super) fn new(metrics: &metric::Registry) -> Self {
+        let compact_duration = metrics.register_metric_with_options::<DurationHistogram, _>(
+            "ingester_persist_compact_duration",
+            "the distribution of time spent compacting the data of a persist job",
+            duration_buckets,
+        );
+        let encode_duration = metrics.register_metric_with_options::<DurationHistogram, _>(
+            "ingester_persist_encode_duration",
+            "the distribution of time spent encoding the compacted data of a \
+            persist job as parquet",
+            duration_buckets,
+        );
+        let upload_duration = metrics.register_metric_with_options::<DurationHistogram, _>(
+            "ingester_persist_upload_duration",
+            "the distribution of time spent uploading the parquet file of a \
+            persist job to object storage",
+            duration_buckets,
+        );
+
+        let output_bytes = metrics.register_metric_with_options::<U64Histogram, _>(
+            "ingester_persist_output_bytes",
+            "the distribution of parquet file sizes generated by persist jobs",
+            || {
+                U64HistogramOptions::new(
+                    (5..=16).map(|i| 4_u64.pow(i)).chain([u64::MAX]), // 1 KiB to 4 GiB
+                )
+            },
+        );
+        let compacted_rows = metrics.register_metric_with_options::<U64Histogram, _>(
+            "ingester_persist_compacted_rows",
+            "the distribution of the number of rows compacted by persist jobs",
+            || {
+                U64HistogramOptions::new(
+                    (3..=12).map(|i| 4_u64.pow(i)).chain([u64::MAX]), // 64 to ~16M
+                )
+            },
+        );
+        // Because a histogram records integer values, the ratio is rounded
+        // down - ratios below 1 (the parquet file is larger than the in-memory
+        // data) are recorded as 0.
+        let amplification_ratio = metrics.register_metric_with_options::<U64Histogram, _>(
+            "ingester_persist_amplification_ratio",
+            "the distribution of the ratio of in-memory bytes persisted to \
+            parquet file bytes generated by persist jobs",
+            || {
+                U64HistogramOptions::new(
+                    [0].into_iter()
+                        .chain((0..=7).map(|i| 2_u64.pow(i))) // 1 to 128
+                        .chain([u64::MAX]),
+                )
+            },
+        );
+
+        let spill_count = metrics.register_metric::<U64Counter>(
+            "ingester_persist_spill_count",
+            "the number of times persist compactions exceeded their memory \
+            limit and spilled data to disk",
+        );
+        let spilled_bytes = metrics.register_metric::<U64Counter>(
+            "ingester_persist_spilled_bytes",
+            "the number of bytes persist compactions spilled to disk",
+        );
+
+        let unsorted_jobs = metrics.register_metric::<U64Counter>(
+            "ingester_persist_unsorted_jobs",
+            "the number of persist jobs whose buffered data was not written \
+            in time order",
+        );
+
+        Self {
+            compact_duration,
+            encode_duration,
+            upload_duration,
+            output_bytes,
+            compacted_rows,
+            amplification_ratio,
+            spill_count,
+            spilled_bytes,
+            unsorted_jobs,
+        }
+    }
+
+    /// Record `obs` against the namespace named `namespace`.
+    pub(super) fn record(&self, namespace: &str, obs: PersistObservation) {
+        let attr = [("namespace", namespace.to_string().into())];
+
+        self.compact_duration
+            .recorder(attr.clone())
+            .record(obs.compact);
+        self.encode_duration
+            .recorder(attr.clone())
+            .record(obs.encode);
+        self.upload_duration
+            .recorder(attr.clone())
+            .record(obs.upload);
+
+        self.output_bytes
+            .recorder(attr.clone())
+            .record(obs.output_bytes as _);
+        self.compacted_rows
+            .recorder(attr.clone())
+            .record(obs.input_rows as _);
+
+        if let Some(ratio) = obs.input_bytes.checked_div(obs.output_bytes) {
+            self.amplification_ratio
+                .recorder(attr.clone())
+                .record(ratio as _);
+        }
+
+        if obs.spill_count > 0 {
+            self.spill_count
+                .recorder(attr.clone())
+                .inc(obs.spill_count as _);
+            self.spilled_bytes
+                .recorder(attr.clone())
+                .inc(obs.spilled_bytes as _);
+        }
+
+        if obs.unsorted {
+            self.unsorted_jobs.recorder(attr).inc(1);
+        }
+    }
+}
+
+/// A shared accumulator of the time spent waiting on a [`TimedStream`].
+#[derive(Debug, Default, Clone)]
+pub(super) struct WaitDuration(Arc<AtomicU64>);
+
+impl WaitDuration {
+    /// Return the total time spent waiting for the inner stream to yield.
+    pub(super) fn get(&self) -> Duration {
+        Duration::from_nanos(self.0.load(Ordering::Relaxed))
+    }
+
+    fn add(&self, d: Duration) {
+        self.0.fetch_add(d.as_nanos() as _, Ordering::Relaxed);
+    }
+}
+
+/// A [`Stream`] decorator accumulating the time between a caller first polling
+/// for the next item and the inner stream yielding it.
+///
+/// The compacted data of a persist job is lazily computed (potentially on
+/// another thread pool) as it is pulled by the parquet encoder - wrapping the
+/// compacted stream allows the time spent compacting to be separated from the
+/// time spent encoding.
+struct TimedStream {
+    inner: SendableRecordBatchStream,
+    waiting_since: Option<Instant>,
+    duration: WaitDuration,
+}
+
+impl Stream for TimedStream {
+    type Item = <SendableRecordBatchStream as Stream>::Item;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let started_at = *self.waiting_since.get_or_insert_with(Instant::now);
+
+        let res = self.inner.poll_next_unpin(cx);
+        if res.is_ready() {
+            self.waiting_since = None;
+            self.duration.add(started_at.elapsed());
+        }
+
+        res
+    }
+}
+
+/// Wrap `stream`, returning the wrapped stream and a [`WaitDuration`]
+/// reporting the time spent waiting for it to yield.
+pub(super) fn timed_stream(
+    stream: SendableRecordBatchStream,
+) -> (SendableRecordBatchStream, WaitDuration) {
+    let duration = WaitDuration::default();
+    let schema = stream.schema();
+    let stream = TimedStream {
+        inner: stream,
+        waiting_since: None,
+        duration: duration.clone(),
+    };
+
+    (
+        Box::pin(RecordBatchStreamAdapter::new(schema, stream)),
+        duration,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow::{array::Int64Array, record_batch::RecordBatch};
+    use metric::{assert_counter, assert_histogram, Attributes};
+
+    use super::*;
+
+    #[test]
+    fn test_record() {
+        let metrics = metric::Registry::default();
+        let m = PersistMetrics::new(&metrics);
+
+        m.record(
+            "bananas",
+            PersistObservation {
+                compact: Duration::from_millis(10),
+                encode: Duration::from_millis(20),
+                upload: Duration::from_millis(30),
+                input_rows: 42,
+                input_bytes: 1_000,
+                output_bytes: 100,
+                spill_count: 2,
+                spilled_bytes: 4_096,
+                unsorted: true,
+            },
+        );
+
+        // A persist job that generated a file larger than the input.
+        m.record(
+            "bananas",
+            PersistObservation {
+                output_bytes: 2_000,
+                input_bytes: 1_000,
+                ..Default::default()
+            },
+        );
+
+        let attr = Attributes::from(&[("namespace", "bananas")]);
+
+        assert_histogram!(
+            metrics,
+            DurationHistogram,
+            "ingester_persist_compact_duration",
+            labels = attr.clone(),
+            samples = 2,
+            sum = Duration::from_millis(10),
+        );
+        assert_histogram!(
+            metrics,
+            DurationHistogram,
+            "ingester_persist_encode_duration",
+            labels = attr.clone(),
+            samples = 2,
+            sum = Duration::from_millis(20),
+        );
+        assert_histogram!(
+            metrics,
+            DurationHistogram,
+            "ingester_persist_upload_duration",
+            labels = attr.clone(),
+            samples = 2,
+            sum = Duration::from_millis(30),
+        );
+        assert_histogram!(
+            metrics,
+            U64Histogram,
+            "ingester_persist_output_bytes",
+            labels = attr.clone(),
+            samples = 2,
+            sum = 2_100,
+        );
+        assert_histogram!(
+            metrics,
+            U64Histogram,
+            "ingester_persist_compacted_rows",
+            labels = attr.clone(),
+            samples = 2,
+            sum = 42,
+        );
+        assert_histogram!(
+            metrics,
+            U64Histogram,
+            "ingester_persist_amplification_ratio",
+            labels = attr.clone(),
+            samples = 2,
+            sum = 10,
+        );
+        assert_counter!(
+            metrics,
+            U64Counter,
+            "ingester_persist_spill_count",
+            labels = attr.clone(),
+            value = 2,
+        );
+        assert_counter!(
+            metrics,
+            U64Counter,
+            "ingester_persist_spilled_bytes",
+            labels = attr.clone(),
+            value = 4_096,
+        );
+        assert_counter!(
+            metrics,
+            U64Counter,
+            "ingester_persist_unsorted_jobs",
+            labels = attr,
+            value = 1,
+        );
+    }
+
+    #[tokio::test]
+    async fn test_timed_stream() {
+        let batch =
+            RecordBatch::try_from_iter([("v", Arc::new(Int64Array::from(vec![1, 2, 3])) as _)])
+                .unwrap();
+        let inner: SendableRecordBatchStream = Box::pin(RecordBatchStreamAdapter::new(
+            batch.schema(),
+            futures::stream::iter([Ok(batch.clone())]).map(|v| {
+                std::thread::sleep(Duration::from_millis(5));
+                v
+            }),
+        ));
+
+        let (stream, duration) = timed_stream(inner);
+        assert_eq!(stream.schema(), batch.schema());
+
+        let got = stream
+            .collect::<Vec<_>>()
+            .await
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(got, [batch]);
+
+        // The time spent producing the batch is captured.
+        assert!(duration.get() >= Duration::from_millis(5));
+    }
+}
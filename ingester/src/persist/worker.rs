@@ -1,35 +1,64 @@
-use std::{ops::ControlFlow, sync::Arc};
+use std::{ops::ControlFlow, sync::Arc, time::Duration};
 
+use arrow_util::util::record_batches_memory_size;
 use async_channel::RecvError;
 use backoff::Backoff;
+use bytes::Bytes;
 use data_types::{ColumnsByName, CompactionLevel, ParquetFile, ParquetFileParams, SortedColumnSet};
 use iox_catalog::interface::{CasFailure, Catalog};
-use iox_query::exec::Executor;
 use iox_time::{SystemProvider, TimeProvider};
 use metric::DurationHistogram;
 use observability_deps::tracing::{debug, info, warn};
 use parquet_file::{metadata::IoxMetadata, storage::ParquetStorage};
 use schema::sort::SortKey;
+use thiserror::Error;
 use tokio::{sync::mpsc, time::Instant};
 use uuid::Uuid;
 
-use crate::persist::compact::compact_persisting_batch;
+use crate::{
+    buffer_tree::partition::recent::RecentPersistCache,
+    fault_injection::{FaultInjector, FaultPoint, InjectedFault},
+    persist::compact::{compact_persisting_batch, drop_null_columns, is_sorted_by_time},
+    priority_executor::{Lane, PriorityExecutor},
+};
 
 use super::{
+    circuit_breaker::PersistCircuitBreaker,
     column_map_resolver::ColumnMapResolver,
-    compact::CompactedStream,
+    compact::{CompactedStream, CompactionMemoryLimit, SpillStats},
     completion_observer::PersistCompletionObserver,
     context::{Context, PersistError, PersistRequest},
+    jobs::{ActivePersistJob, PersistJobs, PersistStage},
+    persist_metrics::{timed_stream, PersistMetrics, PersistObservation},
 };
 
 /// State shared across workers.
 #[derive(Debug)]
 pub(super) struct SharedWorkerState<O, C> {
-    pub(super) exec: Arc<Executor>,
+    pub(super) exec: PriorityExecutor,
     pub(super) store: ParquetStorage,
     pub(super) catalog: Arc<dyn Catalog>,
     pub(super) completion_observer: O,
     pub(super) column_map_resolver: C,
+    pub(super) persist_metrics: PersistMetrics,
+
+    /// The cache of recently persisted parquet data, if enabled.
+    pub(super) recent_persisted: Option<Arc<RecentPersistCache>>,
+
+    /// The memory budget each compaction executes within, if limited.
+    pub(super) compaction_memory_limit: Option<CompactionMemoryLimit>,
+
+    /// Pauses uploads and catalog commits after repeated failures.
+    pub(super) circuit_breaker: PersistCircuitBreaker,
+
+    /// Remove the columns containing only NULL values from persisted files.
+    pub(super) drop_null_columns: bool,
+
+    /// The registry reporting the progress of executing jobs.
+    pub(super) jobs: Arc<PersistJobs>,
+
+    /// Test-only faults to inject into the persist steps.
+    pub(super) faults: Arc<FaultInjector>,
 }
 
 /// The worker routine that drives a [`PersistRequest`] to completion,
@@ -80,8 +109,8 @@ pub(super) async fn run_task<O, C>(
     queue_duration: DurationHistogram,
     persist_duration: DurationHistogram,
 ) where
-    O: PersistCompletionObserver,
-    C: ColumnMapResolver,
+    O: PersistCompletionObserver + 'static,
+    C: ColumnMapResolver + 'static,
 {
     loop {
         let req = tokio::select! {
@@ -122,6 +151,14 @@ pub(super) async fn run_task<O, C>(
         let started_at = Instant::now();
         queue_duration.record(started_at.duration_since(ctx.enqueued_at()));
 
+        // Report the progress of this job until it completes.
+        let job = worker_state.jobs.register(
+            ctx.namespace_id(),
+            ctx.table_id(),
+            ctx.partition_id().clone(),
+            record_batches_memory_size(ctx.data().record_batches()),
+        );
+
         // Compact the data, generate the parquet file from the result, and
         // upload it to object storage.
         //
@@ -130,20 +167,84 @@ pub(super) async fn run_task<O, C>(
         // operation; if this update fails due to a concurrent sort key update,
         // the compaction must be redone with the new sort key and uploaded
         // before continuing.
-        let parquet_table_data = loop {
-            match compact_and_upload(&mut ctx, &worker_state).await {
+        let (parquet_table_data, data) = loop {
+            match compact_and_upload(&mut ctx, &worker_state, &job).await {
                 Ok(v) => break v,
-                Err(PersistError::ConcurrentSortKeyUpdate(_sort_key, _sort_key_ids)) => continue,
+                Err(PersistError::ConcurrentSortKeyUpdate(_sort_key, _sort_key_ids)) => {
+                    job.record_restart();
+                    continue;
+                }
+                Err(PersistError::InjectedFault(error)) => {
+                    warn!(%error, partition_id = %ctx.partition_id(), "restarting persist");
+                    job.record_restart();
+                    continue;
+                }
+                Err(PersistError::ObjectStoreIdCollision {
+                    object_store_id,
+                    source,
+                }) => {
+                    let new_object_store_id = ctx.reassign_object_store_id();
+                    warn!(
+                        error = %source,
+                        partition_id = %ctx.partition_id(),
+                        %object_store_id,
+                        %new_object_store_id,
+                        "object store ID collision, restarting persist with a new ID"
+                    );
+                    job.record_restart();
+                    continue;
+                }
             };
         };
 
+        job.set_stage(PersistStage::Committing);
+
+        // Jobs sharing a persist barrier make their files visible to other
+        // nodes together, once every job of the group has uploaded its file.
+        //
+        // The wait happens in a separate task, freeing this worker to execute
+        // the other jobs of the group which may be queued behind this one.
+        if let Some(barrier) = ctx.barrier().cloned() {
+            let worker_state = Arc::clone(&worker_state);
+            let persist_duration = persist_duration.clone();
+            tokio::spawn(async move {
+                let parquet_file = barrier
+                    .commit(
+                        parquet_table_data,
+                        worker_state.catalog.as_ref(),
+                        &worker_state.circuit_breaker,
+                        &worker_state.faults,
+                    )
+                    .await;
+
+                ctx.mark_complete(
+                    parquet_file,
+                    data,
+                    worker_state.recent_persisted.as_deref(),
+                    &worker_state.completion_observer,
+                )
+                .await;
+
+                persist_duration.record(Instant::now().duration_since(started_at));
+
+                // The job is complete once its file is visible.
+                drop(job);
+            });
+            continue;
+        }
+
         // Make the newly uploaded parquet file visible to other nodes.
         let parquet_file = update_catalog_parquet(&ctx, &worker_state, &parquet_table_data).await;
 
         // And finally mark the persist job as complete and notify any
         // observers.
-        ctx.mark_complete(parquet_file, &worker_state.completion_observer)
-            .await;
+        ctx.mark_complete(
+            parquet_file,
+            data,
+            worker_state.recent_persisted.as_deref(),
+            &worker_state.completion_observer,
+        )
+        .await;
 
         // Capture the time spent actively persisting.
         let now = Instant::now();
@@ -152,7 +253,8 @@ pub(super) async fn run_task<O, C>(
 }
 
 /// Run a compaction on the [`PersistingData`], generate a parquet file and
-/// upload it to object storage.
+/// upload it to object storage, returning the catalog metadata and encoded
+/// parquet data of the file.
 ///
 /// This function composes functionality from the smaller [`compact()`],
 /// [`upload()`], and [`update_catalog_sort_key()`] functions.
@@ -168,7 +270,8 @@ pub(super) async fn run_task<O, C>(
 async fn compact_and_upload<O, C>(
     ctx: &mut Context,
     worker_state: &SharedWorkerState<O, C>,
-) -> Result<ParquetFileParams, PersistError>
+    job: &ActivePersistJob,
+) -> Result<(ParquetFileParams, Bytes), PersistError>
 where
     O: Send + Sync,
     C: ColumnMapResolver,
@@ -190,11 +293,30 @@ where
         .load_verified_column_map(ctx.table_id(), sort_key.as_ref())
         .await;
 
+    worker_state.faults.check(FaultPoint::Compact)?;
+
+    // Compaction and upload execute in the low priority executor lane,
+    // yielding to concurrent query work.
+    //
+    // The compacted stream is lazily executed as it is uploaded, so the permit
+    // must be held until the upload completes.
+    let permit = worker_state.exec.acquire(Lane::Low).await;
+    let started_at = Instant::now();
     let compacted = compact(ctx, worker_state, sort_key.as_ref()).await;
-    let (sort_key_update, parquet_table_data) =
-        upload(ctx, worker_state, compacted, &column_map).await;
+    let (sort_key_update, parquet_table_data, data) = upload(
+        ctx,
+        worker_state,
+        job,
+        compacted,
+        &column_map,
+        started_at.elapsed(),
+    )
+    .await?;
+    drop(permit);
+    ctx.mark_uploaded();
 
     if let Some(sort_key_update) = sort_key_update {
+        job.set_stage(PersistStage::Committing);
         update_catalog_sort_key(
             ctx,
             worker_state,
@@ -207,7 +329,7 @@ where
         .await?
     }
 
-    Ok(parquet_table_data)
+    Ok((parquet_table_data, data))
 }
 
 /// Compact the data in `ctx` using sorted by the sort key returned from
@@ -234,40 +356,62 @@ where
 
     assert!(!ctx.data().record_batches().is_empty());
 
+    let data = ctx.data().query_adaptor();
+    let data = if worker_state.drop_null_columns {
+        drop_null_columns(data)
+    } else {
+        data
+    };
+
     // Run a compaction sort the data and resolve any duplicate values.
     //
     // This demands the deferred load values and may have to wait for them
     // to be loaded before compaction starts.
     compact_persisting_batch(
         &worker_state.exec,
+        worker_state.compaction_memory_limit.as_ref(),
         sort_key,
         ctx.table().get().await.name().clone(),
-        ctx.data().query_adaptor(),
+        data,
     )
     .await
 }
 
-/// Upload the compacted data in `compacted`, returning the new sort key value
-/// and parquet metadata to be upserted into the catalog.
+/// Upload the compacted data in `compacted`, returning the new sort key value,
+/// the parquet metadata to be upserted into the catalog, and the uploaded
+/// parquet data.
+///
+/// The time spent planning the compaction is provided in `compact_duration`,
+/// to which the time spent executing the (lazy) compaction is added when
+/// recording the [`PersistMetrics`].
+///
+/// If a different file exists in object storage under the object store ID of
+/// the persisting data, [`PersistError::ObjectStoreIdCollision`] is returned
+/// and nothing is uploaded.
 async fn upload<O, C>(
     ctx: &Context,
     worker_state: &SharedWorkerState<O, C>,
+    job: &ActivePersistJob,
     compacted: CompactedStream,
     columns: &ColumnsByName,
-) -> (Option<SortKey>, ParquetFileParams)
+    compact_duration: Duration,
+) -> Result<(Option<SortKey>, ParquetFileParams, Bytes), PersistError>
 where
     O: Send + Sync,
     C: Send + Sync,
 {
     let CompactedStream {
         stream: record_stream,
+        plan,
         catalog_sort_key_update,
         data_sort_key,
     } = compacted;
 
-    // Generate a UUID to uniquely identify this parquet file in
-    // object storage.
-    let object_store_id = Uuid::new_v4();
+    // Use the UUID allocated when the data was marked as persisting to
+    // uniquely identify this parquet file in object storage - queriers use
+    // this ID to avoid reading the same rows from both the ingester and the
+    // persisted file.
+    let object_store_id = ctx.data().object_store_id();
 
     debug!(
         namespace_id = %ctx.namespace_id(),
@@ -296,15 +440,79 @@ where
         max_l0_created_at: time_now,
     };
 
-    // Save the compacted data to a parquet file in object storage.
-    //
-    // This call retries until it completes.
+    // Encode the compacted data as a parquet file, capturing the time spent
+    // waiting for the lazily executed compaction separately.
+    let (record_stream, compact_wait) = timed_stream(record_stream);
     let pool = worker_state.exec.pool();
-    let (md, file_size) = worker_state
+    let encode_started_at = Instant::now();
+    let (data, md) = worker_state
         .store
-        .upload(record_stream, ctx.partition_id(), &iox_metadata, pool)
+        .encode(record_stream, &iox_metadata, pool)
         .await
         .expect("unexpected fatal persist error");
+    let encode_duration = encode_started_at.elapsed();
+    let file_size = data.len();
+
+    // The compaction stream is exhausted, so its spill metrics are complete.
+    let spills = SpillStats::from_plan(plan.as_ref());
+
+    // Save the parquet file in object storage.
+    //
+    // This call retries until it completes, pausing while the circuit breaker
+    // is open. An attempt that succeeded but reported an error is not
+    // uploaded again, and an existing file with different content (an object
+    // store ID collision) is not overwritten - unless it is the uncommitted
+    // file uploaded by a previous attempt of this job, which is replaced.
+    //
+    // A collision is not retried, as the identical upload can never succeed.
+    // Instead the collision is returned to the caller, which restarts the job
+    // with a new object store ID - the partition reports the new ID to
+    // queriers, which use it to deduplicate the persisting data against the
+    // persisted file.
+    job.set_stage(PersistStage::Uploading);
+    job.set_file_bytes(file_size);
+    let upload_started_at = Instant::now();
+    let replace = ctx.uploaded();
+    let (store, partition_id, meta) = (&worker_state.store, ctx.partition_id(), &iox_metadata);
+    let uploaded = worker_state
+        .circuit_breaker
+        .retry("upload parquet file to object storage", || {
+            let data = data.clone();
+            async move {
+                let res = if replace {
+                    store.try_put(data, partition_id, meta).await
+                } else {
+                    store.try_put_if_not_exists(data, partition_id, meta).await
+                };
+                match res {
+                    Err(e @ object_store::Error::AlreadyExists { .. }) => Ok(Err(e)),
+                    res => res.map(Ok),
+                }
+            }
+        })
+        .await;
+    if let Err(source) = uploaded {
+        return Err(PersistError::ObjectStoreIdCollision {
+            object_store_id,
+            source,
+        });
+    }
+
+    let batches = ctx.data().record_batches();
+    worker_state.persist_metrics.record(
+        &ctx.namespace_name().get().await,
+        PersistObservation {
+            compact: compact_duration + compact_wait.get(),
+            encode: encode_duration.saturating_sub(compact_wait.get()),
+            upload: upload_started_at.elapsed(),
+            input_rows: batches.iter().map(|b| b.num_rows()).sum(),
+            input_bytes: record_batches_memory_size(batches),
+            output_bytes: file_size,
+            spill_count: spills.spill_count,
+            spilled_bytes: spills.spilled_bytes,
+            unsorted: !is_sorted_by_time(batches),
+        },
+    );
 
     debug!(
         namespace_id = %ctx.namespace_id(),
@@ -333,7 +541,17 @@ where
                 .id
         });
 
-    (catalog_sort_key_update, parquet_table_data)
+    Ok((catalog_sort_key_update, parquet_table_data, data))
+}
+
+/// The errors that may occur when adding a persisted file to the catalog.
+#[derive(Debug, Error)]
+pub(super) enum CatalogCommitError {
+    #[error(transparent)]
+    Catalog(#[from] iox_catalog::interface::Error),
+
+    #[error(transparent)]
+    InjectedFault(#[from] InjectedFault),
 }
 
 /// Update the sort key value stored in the catalog for this [`Context`].
@@ -516,6 +734,7 @@ where
                 new_sort_key_ids,
             ));
         }
+        Err(e) => return Err(e),
     }
 
     Ok(())
@@ -551,8 +770,11 @@ where
     //
     // This has the effect of allowing the queriers to "discover" the
     // parquet file by polling / querying the catalog.
-    let file = Backoff::new(&Default::default())
-        .retry_all_errors("add parquet file to catalog", || async {
+    let file = worker_state
+        .circuit_breaker
+        .retry("add parquet file to catalog", || async {
+            worker_state.faults.check(FaultPoint::CatalogCommit)?;
+
             let mut repos = worker_state.catalog.repositories().await;
             let parquet_file = repos
                 .parquet_files()
@@ -573,10 +795,9 @@ where
             );
 
             // compiler insisted on getting told the type of the error :shrug:
-            Ok(parquet_file) as Result<ParquetFile, iox_catalog::interface::Error>
+            Ok(parquet_file) as Result<ParquetFile, CatalogCommitError>
         })
-        .await
-        .expect("retry forever");
+        .await;
 
     // A newly created file should never be marked for deletion.
     assert!(file.to_delete.is_none());
@@ -0,0 +1,640 @@
+//! A priority-aware wrapper over the shared DataFusion [`Executor`].
+//!
+//! The ingester uses a single [`Executor`] for compacting persisting data, and
+//! query requests from the queriers compete with that compaction work for the
+//! same CPU resources.
+//!
+//! A [`PriorityExecutor`] splits the users of the [`Executor`] into two lanes:
+//!
+//!   * [`Lane::High`]: latency sensitive querier reads.
+//!   * [`Lane::Low`]: throughput orientated persist compaction / upload.
+//!
+//! Callers acquire a [`LanePermit`] for their lane before executing work, and
+//! hold it for the duration of that work. A fixed number of permits (slots)
+//! exist across both lanes - once they are all in use, further callers wait in
+//! a per-lane FIFO queue.
+//!
+//! When a slot becomes free and both lanes have waiters, the high lane is
+//! preferred for up to `high_lane_weight` consecutive grants before a single
+//! low lane waiter is granted the slot. Independently of this weighting, a low
+//! lane waiter that has waited longer than `max_low_lane_wait` is granted the
+//! next free slot, preventing persist starvation under sustained query load
+//! (which would otherwise cause the persist queue to saturate and block
+//! ingest).
+
+use std::{collections::VecDeque, fmt::Display, num::NonZeroUsize, ops::Deref, sync::Arc};
+
+use iox_query::exec::Executor;
+use metric::{DurationHistogram, Metric, U64Gauge};
+use parking_lot::Mutex;
+use tokio::{
+    sync::oneshot,
+    time::{Duration, Instant},
+};
+
+/// The default number of consecutive [`Lane::High`] grants made while
+/// [`Lane::Low`] waiters are queued, before a low lane waiter is served.
+pub(crate) const DEFAULT_HIGH_LANE_WEIGHT: usize = 4;
+
+/// The default maximum duration of time a [`Lane::Low`] waiter is queued
+/// before it is granted the next free slot, irrespective of the lane
+/// weighting.
+pub(crate) const DEFAULT_MAX_LOW_LANE_WAIT: Duration = Duration::from_secs(5);
+
+/// The scheduling lane a unit of work is executed in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Lane {
+    /// Latency sensitive work, such as querier reads.
+    High,
+    /// Background work, such as persist compaction.
+    Low,
+}
+
+impl Lane {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::High => "high",
+            Self::Low => "low",
+        }
+    }
+}
+
+impl Display for Lane {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A caller waiting for a slot to become available.
+#[derive(Debug)]
+struct Waiter {
+    /// An identifier unique amongst all waiters of the scheduler.
+    id: u64,
+    enqueued_at: Instant,
+    tx: oneshot::Sender<LanePermit>,
+}
+
+/// The queue of [`Waiter`] for a single [`Lane`], and the metrics describing
+/// it.
+#[derive(Debug)]
+struct LaneQueue {
+    waiters: VecDeque<Waiter>,
+
+    /// The number of waiters currently in `waiters`.
+    depth: U64Gauge,
+
+    /// The duration of time a waiter spent queued before being granted a
+    /// permit.
+    wait_duration: DurationHistogram,
+}
+
+impl LaneQueue {
+    fn new(lane: Lane, depth: &Metric<U64Gauge>, wait: &Metric<DurationHistogram>) -> Self {
+        let attr = &[("lane", lane.as_str())];
+        Self {
+            waiters: VecDeque::default(),
+            depth: depth.recorder(attr),
+            wait_duration: wait.recorder(attr),
+        }
+    }
+
+    fn push(&mut self, w: Waiter) {
+        self.depth.inc(1);
+        self.waiters.push_back(w);
+    }
+
+    fn pop(&mut self) -> Option<Waiter> {
+        let w = self.waiters.pop_front()?;
+        self.depth.dec(1);
+        Some(w)
+    }
+
+    /// Remove the waiter identified by `id`, if it is still queued.
+    fn remove(&mut self, id: u64) {
+        if let Some(idx) = self.waiters.iter().position(|w| w.id == id) {
+            self.waiters.remove(idx);
+            self.depth.dec(1);
+        }
+    }
+}
+
+/// Mutable scheduler state, protected by the [`Shared::state`] mutex.
+#[derive(Debug)]
+struct State {
+    /// The number of slots not currently held by a [`LanePermit`].
+    available: usize,
+
+    /// The number of consecutive high lane grants made while one or more low
+    /// lane waiters were queued.
+    high_streak: usize,
+
+    /// The identifier assigned to the next queued [`Waiter`].
+    next_waiter_id: u64,
+
+    high: LaneQueue,
+    low: LaneQueue,
+}
+
+impl State {
+    fn queue_mut(&mut self, lane: Lane) -> &mut LaneQueue {
+        match lane {
+            Lane::High => &mut self.high,
+            Lane::Low => &mut self.low,
+        }
+    }
+
+    /// Select the lane that should be granted the next free slot, if any
+    /// waiters are queued.
+    fn next_lane(&self, weight: usize, max_low_wait: Duration, now: Instant) -> Option<Lane> {
+        match (self.high.waiters.front(), self.low.waiters.front()) {
+            (None, None) => None,
+            (Some(_), None) => Some(Lane::High),
+            (None, Some(_)) => Some(Lane::Low),
+            (Some(_), Some(low)) => {
+                if self.high_streak >= weight || now.duration_since(low.enqueued_at) >= max_low_wait
+                {
+                    Some(Lane::Low)
+                } else {
+                    Some(Lane::High)
+                }
+            }
+        }
+    }
+}
+
+/// State shared between the [`PriorityExecutor`] and all outstanding
+/// [`LanePermit`].
+#[derive(Debug)]
+struct Shared {
+    state: Mutex<State>,
+    high_lane_weight: usize,
+    max_low_lane_wait: Duration,
+}
+
+impl Shared {
+    /// Return a slot to the pool, and hand it to the next waiter (if any).
+    fn release(self: &Arc<Self>) {
+        let mut state = self.state.lock();
+        state.available += 1;
+        self.dispatch(&mut state);
+    }
+
+    /// Grant free slots to queued waiters, respecting the lane weighting and
+    /// starvation protection.
+    fn dispatch(self: &Arc<Self>, state: &mut State) {
+        let now = Instant::now();
+
+        while state.available > 0 {
+            let lane = match state.next_lane(self.high_lane_weight, self.max_low_lane_wait, now) {
+                Some(v) => v,
+                None => return,
+            };
+
+            // Only count high lane grants that were made while there was
+            // contention from the low lane.
+            let contended = !state.low.waiters.is_empty();
+
+            let waiter = state
+                .queue_mut(lane)
+                .pop()
+                .expect("next_lane selected an empty lane");
+
+            let permit = LanePermit {
+                lane,
+                shared: Some(Arc::clone(self)),
+            };
+
+            match waiter.tx.send(permit) {
+                Ok(()) => {
+                    state.available -= 1;
+                    match lane {
+                        Lane::High if contended => state.high_streak += 1,
+                        Lane::High => {}
+                        Lane::Low => state.high_streak = 0,
+                    }
+                    state
+                        .queue_mut(lane)
+                        .wait_duration
+                        .record(now.duration_since(waiter.enqueued_at));
+                }
+                Err(mut permit) => {
+                    // The waiter stopped waiting before it was granted the
+                    // slot (waiters normally remove themselves from the queue
+                    // when they stop waiting) - disarm the permit so that
+                    // dropping it does not re-enter this (locked) scheduler,
+                    // and try the next waiter.
+                    permit.shared = None;
+                }
+            }
+        }
+    }
+}
+
+/// A [`Waiter`] queued by [`PriorityExecutor::acquire()`], removed from its
+/// lane queue when dropped.
+///
+/// If the waiter was granted a slot but stopped waiting before receiving it,
+/// the slot is returned to the pool when `rx` (holding the [`LanePermit`]) is
+/// dropped, after the queue lock is released.
+#[derive(Debug)]
+struct QueuedWaiter<'a> {
+    shared: &'a Shared,
+    lane: Lane,
+    id: u64,
+    rx: oneshot::Receiver<LanePermit>,
+}
+
+impl Drop for QueuedWaiter<'_> {
+    fn drop(&mut self) {
+        self.shared
+            .state
+            .lock()
+            .queue_mut(self.lane)
+            .remove(self.id);
+    }
+}
+
+/// A permit to execute work in the specified [`Lane`].
+///
+/// The slot held by this permit is returned to the pool when it is dropped.
+#[derive(Debug)]
+#[must_use = "the slot is released when the permit is dropped"]
+pub(crate) struct LanePermit {
+    lane: Lane,
+
+    /// The scheduler this permit returns its slot to, or [`None`] if this
+    /// permit has been disarmed.
+    shared: Option<Arc<Shared>>,
+}
+
+impl LanePermit {
+    /// The [`Lane`] this permit was granted for.
+    #[cfg(test)]
+    pub(crate) fn lane(&self) -> Lane {
+        self.lane
+    }
+}
+
+impl Drop for LanePermit {
+    fn drop(&mut self) {
+        if let Some(shared) = self.shared.take() {
+            shared.release();
+        }
+    }
+}
+
+/// A wrapper over an [`Executor`] that schedules access to it across
+/// prioritised [`Lane`]s.
+///
+/// See the [module docs](self) for details of the scheduling behaviour.
+///
+/// Cloning a [`PriorityExecutor`] is cheap, and all clones share the same
+/// scheduler state.
+#[derive(Debug, Clone)]
+pub(crate) struct PriorityExecutor {
+    exec: Arc<Executor>,
+    shared: Arc<Shared>,
+}
+
+impl PriorityExecutor {
+    /// Wrap `exec`, allowing at most `slots` units of work to hold a
+    /// [`LanePermit`] at any one time, using the default lane weighting
+    /// parameters.
+    pub(crate) fn new(
+        exec: Arc<Executor>,
+        slots: NonZeroUsize,
+        metrics: &metric::Registry,
+    ) -> Self {
+        Self::with_parameters(
+            exec,
+            slots,
+            DEFAULT_HIGH_LANE_WEIGHT,
+            DEFAULT_MAX_LOW_LANE_WAIT,
+            metrics,
+        )
+    }
+
+    /// Wrap `exec` with the specified lane weighting parameters.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `high_lane_weight` is 0.
+    pub(crate) fn with_parameters(
+        exec: Arc<Executor>,
+        slots: NonZeroUsize,
+        high_lane_weight: usize,
+        max_low_lane_wait: Duration,
+        metrics: &metric::Registry,
+    ) -> Self {
+        assert_ne!(high_lane_weight, 0, "high lane weight must be non-zero");
+
+        let depth = metrics.register_metric::<U64Gauge>(
+            "ingester_exec_lane_queue_depth",
+            "the number of tasks waiting for an executor slot, by priority lane",
+        );
+        let wait = metrics.register_metric::<DurationHistogram>(
+            "ingester_exec_lane_wait_duration",
+            "the distribution of time tasks spent waiting for an executor slot, \
+            by priority lane",
+        );
+        metrics
+            .register_metric::<U64Gauge>(
+                "ingester_exec_lane_slots",
+                "the number of executor slots shared across all priority lanes",
+            )
+            .recorder(&[])
+            .set(slots.get() as _);
+
+        let state = State {
+            available: slots.get(),
+            high_streak: 0,
+            next_waiter_id: 0,
+            high: LaneQueue::new(Lane::High, &depth, &wait),
+            low: LaneQueue::new(Lane::Low, &depth, &wait),
+        };
+
+        Self {
+            exec,
+            shared: Arc::new(Shared {
+                state: Mutex::new(state),
+                high_lane_weight,
+                max_low_lane_wait,
+            }),
+        }
+    }
+
+    /// Wait for a slot in the specified `lane` to become available.
+    ///
+    /// This call is cancellation safe - dropping the returned future before it
+    /// completes removes the caller from the queue (and the queue depth
+    /// metric) without consuming a slot. A slot granted to the caller but not
+    /// yet received is returned to the pool.
+    pub(crate) async fn acquire(&self, lane: Lane) -> LanePermit {
+        let mut waiter = {
+            let mut state = self.shared.state.lock();
+
+            // Fast path: a slot is free and nobody is queued ahead of this
+            // caller.
+            if state.available > 0 && state.high.waiters.is_empty() {
+                debug_assert!(state.low.waiters.is_empty());
+                state.available -= 1;
+                return LanePermit {
+                    lane,
+                    shared: Some(Arc::clone(&self.shared)),
+                };
+            }
+
+            let id = state.next_waiter_id;
+            state.next_waiter_id += 1;
+
+            let (tx, rx) = oneshot::channel();
+            state.queue_mut(lane).push(Waiter {
+                id,
+                enqueued_at: Instant::now(),
+                tx,
+            });
+
+            QueuedWaiter {
+                shared: &self.shared,
+                lane,
+                id,
+                rx,
+            }
+        };
+
+        // The sender half is only dropped without sending when the waiter is
+        // removed from the queue, which only happens when `waiter` is dropped.
+        (&mut waiter.rx)
+            .await
+            .expect("priority executor waiter removed from queue")
+    }
+
+    /// A [`PriorityExecutor`] wrapping [`Executor::new_testing()`] with ample
+    /// slots for tests that do not exercise the scheduling behaviour.
+    #[cfg(test)]
+    pub(crate) fn new_testing() -> Self {
+        Self::new(
+            Arc::new(Executor::new_testing()),
+            NonZeroUsize::new(100).unwrap(),
+            &metric::Registry::default(),
+        )
+    }
+}
+
+impl Deref for PriorityExecutor {
+    type Target = Executor;
+
+    fn deref(&self) -> &Self::Target {
+        &self.exec
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_matches::assert_matches;
+    use futures::{task::Context, Future, FutureExt};
+    use metric::Attributes;
+    use std::{pin::Pin, task::Poll};
+
+    use super::*;
+
+    fn new_exec(
+        slots: usize,
+        weight: usize,
+        max_wait: Duration,
+        metrics: &metric::Registry,
+    ) -> PriorityExecutor {
+        PriorityExecutor::with_parameters(
+            Arc::new(Executor::new_testing()),
+            NonZeroUsize::new(slots).unwrap(),
+            weight,
+            max_wait,
+            metrics,
+        )
+    }
+
+    fn queue_depth(metrics: &metric::Registry, lane: Lane) -> u64 {
+        metrics
+            .get_instrument::<Metric<U64Gauge>>("ingester_exec_lane_queue_depth")
+            .expect("failed to read metric")
+            .get_observer(&Attributes::from(&[("lane", lane.as_str())]))
+            .expect("failed to get observer")
+            .fetch()
+    }
+
+    /// Poll `fut` once, returning the output if it is ready.
+    fn poll_once<F>(fut: &mut Pin<Box<F>>) -> Option<F::Output>
+    where
+        F: Future,
+    {
+        let waker = futures::task::noop_waker();
+        let mut cx = Context::from_waker(&waker);
+        match fut.poll_unpin(&mut cx) {
+            Poll::Ready(v) => Some(v),
+            Poll::Pending => None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fast_path() {
+        let metrics = metric::Registry::default();
+        let exec = new_exec(2, 1, Duration::from_secs(100), &metrics);
+
+        let a = exec.acquire(Lane::Low).await;
+        let b = exec.acquire(Lane::High).await;
+        assert_eq!(a.lane(), Lane::Low);
+        assert_eq!(b.lane(), Lane::High);
+
+        // All slots are now held.
+        let mut c = Box::pin(exec.acquire(Lane::High));
+        assert!(poll_once(&mut c).is_none());
+        assert_eq!(queue_depth(&metrics, Lane::High), 1);
+
+        // Releasing a slot hands it to the waiter.
+        drop(a);
+        let c = poll_once(&mut c).expect("should be granted");
+        assert_eq!(c.lane(), Lane::High);
+        assert_eq!(queue_depth(&metrics, Lane::High), 0);
+    }
+
+    #[tokio::test]
+    async fn test_high_lane_preferred() {
+        let metrics = metric::Registry::default();
+        let exec = new_exec(1, 2, Duration::from_secs(100), &metrics);
+
+        let held = exec.acquire(Lane::Low).await;
+
+        // Queue a low lane waiter before a high lane waiter.
+        let mut low = Box::pin(exec.acquire(Lane::Low));
+        assert!(poll_once(&mut low).is_none());
+        let mut high = Box::pin(exec.acquire(Lane::High));
+        assert!(poll_once(&mut high).is_none());
+
+        assert_eq!(queue_depth(&metrics, Lane::Low), 1);
+        assert_eq!(queue_depth(&metrics, Lane::High), 1);
+
+        // The slot goes to the high lane despite the low waiter being queued
+        // first.
+        drop(held);
+        assert!(poll_once(&mut low).is_none());
+        let high = poll_once(&mut high).expect("high lane should be granted");
+
+        // And then to the low lane.
+        drop(high);
+        let low = poll_once(&mut low).expect("low lane should be granted");
+        assert_eq!(low.lane(), Lane::Low);
+    }
+
+    #[tokio::test]
+    async fn test_weighting() {
+        let metrics = metric::Registry::default();
+        let exec = new_exec(1, 2, Duration::from_secs(100), &metrics);
+
+        let mut held = exec.acquire(Lane::High).await;
+
+        let mut low = Box::pin(exec.acquire(Lane::Low));
+        assert!(poll_once(&mut low).is_none());
+
+        // Two high lane grants are made before the low lane is served, even
+        // though there is always a high lane waiter.
+        for _ in 0..2 {
+            let mut high = Box::pin(exec.acquire(Lane::High));
+            assert!(poll_once(&mut high).is_none());
+            drop(held);
+            held = poll_once(&mut high).expect("high lane should be granted");
+            assert!(poll_once(&mut low).is_none());
+        }
+
+        let mut high = Box::pin(exec.acquire(Lane::High));
+        assert!(poll_once(&mut high).is_none());
+        drop(held);
+
+        // The weight has been exhausted, so the low lane waiter is served.
+        let low = poll_once(&mut low).expect("low lane should be granted");
+        assert!(poll_once(&mut high).is_none());
+
+        drop(low);
+        assert_matches!(poll_once(&mut high), Some(p) => {
+            assert_eq!(p.lane(), Lane::High);
+        });
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_starvation_protection() {
+        let metrics = metric::Registry::default();
+        // A weight so large the low lane is never served by weighting alone.
+        let exec = new_exec(1, usize::MAX, Duration::from_secs(5), &metrics);
+
+        let held = exec.acquire(Lane::High).await;
+
+        let mut low = Box::pin(exec.acquire(Lane::Low));
+        assert!(poll_once(&mut low).is_none());
+        let mut high = Box::pin(exec.acquire(Lane::High));
+        assert!(poll_once(&mut high).is_none());
+
+        // Once the low lane waiter has been queued for longer than the
+        // maximum wait, it is served ahead of the high lane.
+        tokio::time::advance(Duration::from_secs(6)).await;
+
+        drop(held);
+        assert!(poll_once(&mut high).is_none());
+        assert_matches!(poll_once(&mut low), Some(p) => {
+            assert_eq!(p.lane(), Lane::Low);
+        });
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_waiter_does_not_consume_slot() {
+        let metrics = metric::Registry::default();
+        let exec = new_exec(1, 1, Duration::from_secs(100), &metrics);
+
+        let held = exec.acquire(Lane::Low).await;
+
+        // This waiter gives up before being granted the slot, and is removed
+        // from the queue immediately.
+        let mut cancelled = Box::pin(exec.acquire(Lane::High));
+        assert!(poll_once(&mut cancelled).is_none());
+        assert_eq!(queue_depth(&metrics, Lane::High), 1);
+        drop(cancelled);
+        assert_eq!(queue_depth(&metrics, Lane::High), 0);
+
+        let mut low = Box::pin(exec.acquire(Lane::Low));
+        assert!(poll_once(&mut low).is_none());
+
+        // The slot skips the cancelled waiter and is granted to the next.
+        drop(held);
+        let low = poll_once(&mut low).expect("low lane should be granted");
+        assert_eq!(queue_depth(&metrics, Lane::High), 0);
+        assert_eq!(queue_depth(&metrics, Lane::Low), 0);
+
+        // And once released, the slot is free to be acquired immediately.
+        drop(low);
+        let _p = exec
+            .acquire(Lane::High)
+            .now_or_never()
+            .expect("slot should be free");
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_granted_waiter_returns_slot() {
+        let metrics = metric::Registry::default();
+        let exec = new_exec(1, 1, Duration::from_secs(100), &metrics);
+
+        let held = exec.acquire(Lane::Low).await;
+
+        let mut cancelled = Box::pin(exec.acquire(Lane::High));
+        assert!(poll_once(&mut cancelled).is_none());
+
+        // The slot is granted to the waiter, which gives up before receiving
+        // it.
+        drop(held);
+        assert_eq!(queue_depth(&metrics, Lane::High), 0);
+        drop(cancelled);
+
+        // The slot is returned to the pool.
+        let _p = exec
+            .acquire(Lane::Low)
+            .now_or_never()
+            .expect("slot should be free");
+    }
+}
@@ -7,7 +7,7 @@ use metric::{DurationHistogram, Metric};
 use predicate::Predicate;
 use trace::span::Span;
 
-use super::{projection::OwnedProjection, QueryExec};
+use super::{projection::OwnedProjection, snapshot_page::SnapshotPage, QueryExec};
 use crate::query::QueryError;
 
 /// An instrumentation decorator over a [`QueryExec`] implementation.
@@ -66,12 +66,13 @@ where
         projection: OwnedProjection,
         span: Option<Span>,
         predicate: Option<Predicate>,
+        page: SnapshotPage,
     ) -> Result<Self::Response, QueryError> {
         let t = self.time_provider.now();
 
         let res = self
             .inner
-            .query_exec(namespace_id, table_id, projection, span, predicate)
+            .query_exec(namespace_id, table_id, projection, span, predicate, page)
             .await;
 
         if let Some(delta) = self.time_provider.now().checked_duration_since(t) {
@@ -115,7 +116,7 @@ mod tests {
 
                     // Call the decorator and assert the return value
                     let got = decorator
-                        .query_exec(NamespaceId::new(42), TableId::new(24),OwnedProjection::default(), None, None)
+                        .query_exec(NamespaceId::new(42), TableId::new(24),OwnedProjection::default(), None, None, SnapshotPage::default())
                         .await;
                     assert_matches!(got, $($want_ret)+);
 
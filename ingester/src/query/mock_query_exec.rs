@@ -4,11 +4,15 @@ use parking_lot::Mutex;
 use predicate::Predicate;
 use trace::span::Span;
 
-use super::{projection::OwnedProjection, response::QueryResponse, QueryError, QueryExec};
+use super::{
+    projection::OwnedProjection, response::QueryResponse, snapshot_page::SnapshotPage, QueryError,
+    QueryExec,
+};
 
 #[derive(Debug, Default)]
 pub(crate) struct MockQueryExec {
     response: Mutex<Option<Result<QueryResponse, QueryError>>>,
+    page: Mutex<Option<SnapshotPage>>,
 }
 
 impl MockQueryExec {
@@ -16,6 +20,11 @@ impl MockQueryExec {
         *self.response.lock() = Some(r);
         self
     }
+
+    /// Return the [`SnapshotPage`] of the last query, if any.
+    pub(crate) fn last_page(&self) -> Option<SnapshotPage> {
+        self.page.lock().clone()
+    }
 }
 
 #[async_trait]
@@ -29,7 +38,9 @@ impl QueryExec for MockQueryExec {
         _projection: OwnedProjection,
         _span: Option<Span>,
         _predicate: Option<Predicate>,
+        page: SnapshotPage,
     ) -> Result<Self::Response, QueryError> {
+        *self.page.lock() = Some(page);
         self.response
             .lock()
             .take()
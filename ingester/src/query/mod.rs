@@ -4,6 +4,7 @@ mod r#trait;
 pub(crate) use r#trait::*;
 
 pub(crate) mod projection;
+pub(crate) mod snapshot_page;
 
 // Response types
 pub(crate) mod partition_response;
@@ -14,5 +15,8 @@ pub(crate) mod exec_instrumentation;
 pub(crate) mod result_instrumentation;
 pub(crate) mod tracing;
 
+// Scheduling
+pub(crate) mod priority;
+
 #[cfg(test)]
 pub(crate) mod mock_query_exec;
@@ -3,7 +3,8 @@
 //! [`QueryResponse`]: super::response::QueryResponse
 
 use arrow::record_batch::RecordBatch;
-use data_types::TransitionPartitionId;
+use data_types::{SequenceNumber, TransitionPartitionId};
+use uuid::Uuid;
 
 /// Response data for a single partition.
 #[derive(Debug)]
@@ -16,6 +17,18 @@ pub(crate) struct PartitionResponse {
 
     /// Count of persisted Parquet files for this partition by this ingester instance.
     completed_persistence_count: u64,
+
+    /// Object store IDs of the parquet files currently being persisted for
+    /// this partition, the data of which is included in `batches`.
+    persisting_object_store_ids: Vec<Uuid>,
+
+    /// The highest sequence number of the data in `batches`, if snapshots of
+    /// this partition were left out of the response by the query page limit.
+    resume_after_sequence_number: Option<SequenceNumber>,
+
+    /// True if the buffered data of this partition was omitted from the
+    /// response to relieve buffer pressure.
+    data_omitted: bool,
 }
 
 impl PartitionResponse {
@@ -28,6 +41,38 @@ impl PartitionResponse {
             batches: data,
             id,
             completed_persistence_count,
+            persisting_object_store_ids: vec![],
+            resume_after_sequence_number: None,
+            data_omitted: false,
+        }
+    }
+
+    /// Set the object store IDs of the parquet files being persisted for this
+    /// partition at the time the data was read.
+    pub(crate) fn with_persisting_object_store_ids(mut self, ids: Vec<Uuid>) -> Self {
+        self.persisting_object_store_ids = ids;
+        self
+    }
+
+    /// Set the sequence number a query truncated by its page limit resumes
+    /// from.
+    pub(crate) fn with_resume_after_sequence_number(mut self, v: Option<SequenceNumber>) -> Self {
+        self.resume_after_sequence_number = v;
+        self
+    }
+
+    /// Discard the data of this partition, retaining only the partition
+    /// metadata, and mark the data as omitted in the response.
+    ///
+    /// The persisting object store IDs are also discarded, as the data of
+    /// those files is no longer included in the response.
+    pub(crate) fn into_metadata_only(self) -> Self {
+        Self {
+            batches: vec![],
+            persisting_object_store_ids: vec![],
+            resume_after_sequence_number: None,
+            data_omitted: true,
+            ..self
         }
     }
 
@@ -39,6 +84,18 @@ impl PartitionResponse {
         self.completed_persistence_count
     }
 
+    pub(crate) fn persisting_object_store_ids(&self) -> &[Uuid] {
+        &self.persisting_object_store_ids
+    }
+
+    pub(crate) fn resume_after_sequence_number(&self) -> Option<SequenceNumber> {
+        self.resume_after_sequence_number
+    }
+
+    pub(crate) fn data_omitted(&self) -> bool {
+        self.data_omitted
+    }
+
     pub(crate) fn into_record_batches(self) -> Vec<RecordBatch> {
         self.batches
     }
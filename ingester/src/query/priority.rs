@@ -0,0 +1,136 @@
+//! A [`QueryExec`] decorator scheduling queries in the high priority executor
+//! lane.
+
+use async_trait::async_trait;
+use data_types::{NamespaceId, TableId};
+use futures::StreamExt;
+use predicate::Predicate;
+use trace::span::Span;
+
+use super::{
+    projection::OwnedProjection,
+    response::{PartitionStream, QueryResponse},
+    snapshot_page::SnapshotPage,
+    QueryError, QueryExec,
+};
+use crate::priority_executor::{Lane, PriorityExecutor};
+
+/// A [`QueryExec`] decorator that acquires a [`Lane::High`] permit from a
+/// [`PriorityExecutor`] before executing the inner query.
+///
+/// The partitions of the inner [`QueryResponse`] are lazily read from the
+/// buffer as its stream is polled, so the stream is drained while the permit
+/// is held, snapshotting the buffered data of every partition. The permit is
+/// released before the response is returned, so a slow client reading the
+/// response does not hold an executor slot.
+#[derive(Debug)]
+pub(crate) struct QueryExecPriority<T> {
+    inner: T,
+    exec: PriorityExecutor,
+}
+
+impl<T> QueryExecPriority<T> {
+    pub(crate) fn new(inner: T, exec: PriorityExecutor) -> Self {
+        Self { inner, exec }
+    }
+}
+
+#[async_trait]
+impl<T> QueryExec for QueryExecPriority<T>
+where
+    T: QueryExec<Response = QueryResponse>,
+{
+    type Response = QueryResponse;
+
+    async fn query_exec(
+        &self,
+        namespace_id: NamespaceId,
+        table_id: TableId,
+        projection: OwnedProjection,
+        span: Option<Span>,
+        predicate: Option<Predicate>,
+        page: SnapshotPage,
+    ) -> Result<Self::Response, QueryError> {
+        let permit = self.exec.acquire(Lane::High).await;
+
+        let partitions = self
+            .inner
+            .query_exec(namespace_id, table_id, projection, span, predicate, page)
+            .await?
+            .into_partition_stream()
+            .collect::<Vec<_>>()
+            .await;
+
+        drop(permit);
+
+        Ok(QueryResponse::new(PartitionStream::new(
+            futures::stream::iter(partitions),
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{num::NonZeroUsize, sync::Arc};
+
+    use assert_matches::assert_matches;
+    use futures::FutureExt;
+    use iox_query::exec::Executor;
+
+    use super::*;
+    use crate::{
+        query::{mock_query_exec::MockQueryExec, partition_response::PartitionResponse},
+        test_util::{
+            ARBITRARY_NAMESPACE_ID, ARBITRARY_TABLE_ID, ARBITRARY_TRANSITION_PARTITION_ID,
+        },
+    };
+
+    #[tokio::test]
+    async fn test_permit_released_once_snapshot_taken() {
+        let exec = PriorityExecutor::new(
+            Arc::new(Executor::new_testing()),
+            NonZeroUsize::new(1).unwrap(),
+            &metric::Registry::default(),
+        );
+
+        // The single slot is held while the partitions are read from the
+        // inner response.
+        let stream = futures::stream::iter([PartitionResponse::new(
+            vec![],
+            ARBITRARY_TRANSITION_PARTITION_ID.clone(),
+            42,
+        )])
+        .map({
+            let exec = exec.clone();
+            move |v| {
+                assert!(exec.acquire(Lane::Low).now_or_never().is_none());
+                v
+            }
+        });
+
+        let mock = MockQueryExec::default()
+            .with_result(Ok(QueryResponse::new(PartitionStream::new(stream))));
+        let layer = QueryExecPriority::new(mock, exec.clone());
+
+        let resp = layer
+            .query_exec(
+                ARBITRARY_NAMESPACE_ID,
+                ARBITRARY_TABLE_ID,
+                OwnedProjection::default(),
+                None,
+                None,
+                SnapshotPage::default(),
+            )
+            .await
+            .expect("query should succeed");
+
+        // The slot is released before the response is read.
+        assert!(exec.acquire(Lane::Low).now_or_never().is_some());
+
+        let partitions = resp.into_partition_stream().collect::<Vec<_>>().await;
+        assert_matches!(partitions.as_slice(), [p] => {
+            assert_eq!(p.id(), &*ARBITRARY_TRANSITION_PARTITION_ID);
+            assert_eq!(p.completed_persistence_count(), 42);
+        });
+    }
+}
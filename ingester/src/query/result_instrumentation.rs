@@ -67,7 +67,7 @@ use crate::query::{
     QueryError, QueryExec,
 };
 
-use super::projection::OwnedProjection;
+use super::{projection::OwnedProjection, snapshot_page::SnapshotPage};
 
 /// A [`QueryExec`] decorator adding instrumentation to the [`QueryResponse`]
 /// returned by the inner implementation.
@@ -208,12 +208,13 @@ where
         projection: OwnedProjection,
         span: Option<Span>,
         predicate: Option<Predicate>,
+        page: SnapshotPage,
     ) -> Result<Self::Response, QueryError> {
         let started_at = self.time_provider.now();
 
         let stream = self
             .inner
-            .query_exec(namespace_id, table_id, projection, span, predicate)
+            .query_exec(namespace_id, table_id, projection, span, predicate, page)
             .await?;
 
         let stream = QueryMetricContext::new(
@@ -334,6 +335,8 @@ where
                 // Extract all the fields of the PartitionResponse
                 let id = p.id().clone();
                 let persist_count = p.completed_persistence_count();
+                let persisting_ids = p.persisting_object_store_ids().to_vec();
+                let resume = p.resume_after_sequence_number();
 
                 // And wrap the underlying stream of RecordBatch for this
                 // partition with a metric observer.
@@ -345,7 +348,11 @@ where
                 this.record_batch_count
                     .fetch_add(data.len(), Ordering::Relaxed);
 
-                Poll::Ready(Some(PartitionResponse::new(data, id, persist_count)))
+                Poll::Ready(Some(
+                    PartitionResponse::new(data, id, persist_count)
+                        .with_persisting_object_store_ids(persisting_ids)
+                        .with_resume_after_sequence_number(resume),
+                ))
             }
             Poll::Ready(None) => {
                 // Record the wall clock timestamp of the stream end.
@@ -465,6 +472,7 @@ mod tests {
                 OwnedProjection::default(),
                 None,
                 None,
+                SnapshotPage::default(),
             )
             .await
             .expect("query should succeed");
@@ -552,6 +560,7 @@ mod tests {
                 OwnedProjection::default(),
                 None,
                 None,
+                SnapshotPage::default(),
             )
             .await
             .expect("query should succeed");
@@ -638,6 +647,7 @@ mod tests {
                 OwnedProjection::default(),
                 None,
                 None,
+                SnapshotPage::default(),
             )
             .await
             .expect("query should succeed");
@@ -724,6 +734,7 @@ mod tests {
                 OwnedProjection::default(),
                 None,
                 None,
+                SnapshotPage::default(),
             )
             .await
             .expect("query should succeed");
@@ -0,0 +1,223 @@
+//! Paging through the snapshots of buffered partition data.
+
+use std::{collections::HashMap, num::NonZeroUsize, sync::Arc};
+
+use data_types::{SequenceNumber, TransitionPartitionId};
+
+/// Selects the snapshots of each partition returned by a query.
+///
+/// The data of a partition is returned as an ordered list of snapshots: the
+/// batches currently being persisted, oldest first, followed by the buffered
+/// data. For pathological partitions holding many snapshots, a query can cap
+/// the number of snapshots returned per partition. A truncated partition
+/// response carries the highest [`SequenceNumber`] of the data it returned.
+///
+/// A subsequent query resumes the truncated partitions from the next snapshot
+/// by passing this [`SequenceNumber`] for each of them. Sequence numbers are
+/// assigned across all partitions, so each partition resumes from its own -
+/// and the partitions returned in full are not returned again.
+///
+/// The default page is unbounded, selecting all snapshots. A
+/// [`SnapshotPage::metadata_only()`] page selects none, and is answered without
+/// snapshotting or reading any buffered data.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct SnapshotPage {
+    /// When resuming a previous query, the partitions to return and the
+    /// highest [`SequenceNumber`] already returned for each.
+    resume_after: Option<Arc<HashMap<TransitionPartitionId, SequenceNumber>>>,
+
+    /// The maximum number of snapshots selected per partition.
+    limit: Option<NonZeroUsize>,
+
+    /// Select no snapshots at all.
+    metadata_only: bool,
+}
+
+impl SnapshotPage {
+    /// Construct a page selecting at most `limit` snapshots per partition.
+    ///
+    /// If `resume_after` is not empty, only the partitions it contains are
+    /// selected, from the first snapshot containing writes with a sequence
+    /// number greater than the one given for the partition.
+    pub(crate) fn new(
+        resume_after: HashMap<TransitionPartitionId, SequenceNumber>,
+        limit: Option<NonZeroUsize>,
+    ) -> Self {
+        Self {
+            resume_after: (!resume_after.is_empty()).then(|| Arc::new(resume_after)),
+            limit,
+            metadata_only: false,
+        }
+    }
+
+    /// A page selecting no snapshots, returning only the partition metadata.
+    pub(crate) fn metadata_only() -> Self {
+        Self {
+            metadata_only: true,
+            ..Default::default()
+        }
+    }
+
+    /// Returns the page of the partition identified by `id`, or [`None`] if
+    /// the partition is not part of this page.
+    pub(crate) fn partition(&self, id: &TransitionPartitionId) -> Option<PartitionPage> {
+        let after = match &self.resume_after {
+            Some(resume_after) => Some(*resume_after.get(id)?),
+            None => None,
+        };
+
+        Some(PartitionPage {
+            after,
+            limit: self.limit,
+            metadata_only: self.metadata_only,
+        })
+    }
+}
+
+/// The snapshots of a single partition selected by a [`SnapshotPage`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct PartitionPage {
+    /// Only select snapshots containing writes with a sequence number greater
+    /// than this value.
+    after: Option<SequenceNumber>,
+
+    /// The maximum number of snapshots selected.
+    limit: Option<NonZeroUsize>,
+
+    /// Select no snapshots at all.
+    metadata_only: bool,
+}
+
+impl PartitionPage {
+    /// Returns true if this page selects all snapshots.
+    pub(crate) fn is_unbounded(&self) -> bool {
+        self.after.is_none() && self.limit.is_none() && !self.metadata_only
+    }
+
+    /// Returns true if this page selects no snapshots.
+    pub(crate) fn is_metadata_only(&self) -> bool {
+        self.metadata_only
+    }
+
+    /// Returns true if this page resumes a previously truncated query.
+    pub(crate) fn is_continuation(&self) -> bool {
+        self.after.is_some()
+    }
+
+    /// Select the snapshots of this page from `snapshots`, yielding each
+    /// snapshot of a partition in write order along with the highest
+    /// [`SequenceNumber`] of the writes it contains.
+    ///
+    /// Returns the selected snapshots, and the [`SequenceNumber`] to resume
+    /// from if snapshots were left out because of the page limit.
+    pub(crate) fn select<T, I>(&self, snapshots: I) -> (Vec<T>, Option<SequenceNumber>)
+    where
+        I: IntoIterator<Item = (SequenceNumber, T)>,
+    {
+        if self.metadata_only {
+            return (vec![], None);
+        }
+
+        let mut snapshots = snapshots
+            .into_iter()
+            .filter(|(max, _)| self.after.map_or(true, |after| *max > after))
+            .peekable();
+        let limit = self.limit.map_or(usize::MAX, NonZeroUsize::get);
+
+        let mut selected = Vec::new();
+        let mut last = None;
+        while selected.len() < limit {
+            let Some((max, v)) = snapshots.next() else {
+                break;
+            };
+            last = Some(max);
+            selected.push(v);
+        }
+
+        let resume = snapshots.peek().and(last);
+        (selected, resume)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use data_types::PartitionId;
+
+    use super::*;
+
+    fn partition_page(after: Option<u64>, limit: usize) -> PartitionPage {
+        let id = TransitionPartitionId::Deprecated(PartitionId::new(1));
+        let resume_after = after
+            .map(|v| (id.clone(), SequenceNumber::new(v)))
+            .into_iter()
+            .collect();
+        SnapshotPage::new(resume_after, NonZeroUsize::new(limit))
+            .partition(&id)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_select() {
+        let snapshots = || (1..=5).map(|v| (SequenceNumber::new(v * 10), v));
+
+        // An unbounded page selects everything.
+        let page = PartitionPage::default();
+        assert!(page.is_unbounded());
+        assert_eq!(page.select(snapshots()), (vec![1, 2, 3, 4, 5], None));
+
+        // A limited page is truncated, resuming after the last snapshot
+        // selected.
+        let page = partition_page(None, 2);
+        assert_eq!(
+            page.select(snapshots()),
+            (vec![1, 2], Some(SequenceNumber::new(20)))
+        );
+
+        let page = partition_page(Some(20), 2);
+        assert!(page.is_continuation());
+        assert_eq!(
+            page.select(snapshots()),
+            (vec![3, 4], Some(SequenceNumber::new(40)))
+        );
+
+        // The last page is not truncated.
+        let page = partition_page(Some(40), 2);
+        assert_eq!(page.select(snapshots()), (vec![5], None));
+
+        // Nor is a page selecting exactly the remaining snapshots.
+        let page = partition_page(Some(30), 2);
+        assert_eq!(page.select(snapshots()), (vec![4, 5], None));
+
+        // A metadata-only page selects nothing.
+        let page = SnapshotPage::metadata_only()
+            .partition(&TransitionPartitionId::Deprecated(PartitionId::new(1)))
+            .unwrap();
+        assert!(!page.is_unbounded());
+        assert!(page.is_metadata_only());
+        assert_eq!(page.select(snapshots()), (vec![], None));
+    }
+
+    #[test]
+    fn test_partition() {
+        let a = TransitionPartitionId::Deprecated(PartitionId::new(1));
+        let b = TransitionPartitionId::Deprecated(PartitionId::new(2));
+
+        // A first page selects every partition.
+        let page = SnapshotPage::new(HashMap::new(), NonZeroUsize::new(1));
+        assert!(!page.partition(&a).unwrap().is_continuation());
+        assert!(!page.partition(&b).unwrap().is_continuation());
+
+        // A continuation resumes each listed partition after its own sequence
+        // number, and skips the others.
+        let page = SnapshotPage::new(
+            [(a.clone(), SequenceNumber::new(10))].into(),
+            NonZeroUsize::new(1),
+        );
+        let snapshots = [(SequenceNumber::new(10), 1), (SequenceNumber::new(20), 2)];
+        assert_eq!(
+            page.partition(&a).unwrap().select(snapshots),
+            (vec![2], None)
+        );
+        assert_eq!(page.partition(&b), None);
+    }
+}
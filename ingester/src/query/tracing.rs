@@ -5,7 +5,7 @@ use data_types::{NamespaceId, TableId};
 use predicate::Predicate;
 use trace::span::{Span, SpanRecorder};
 
-use super::{projection::OwnedProjection, QueryExec};
+use super::{projection::OwnedProjection, snapshot_page::SnapshotPage, QueryExec};
 use crate::query::QueryError;
 
 /// An tracing decorator over a [`QueryExec`] implementation.
@@ -44,6 +44,7 @@ where
         projection: OwnedProjection,
         span: Option<Span>,
         predicate: Option<Predicate>,
+        page: SnapshotPage,
     ) -> Result<Self::Response, QueryError> {
         let mut recorder = SpanRecorder::new(span).child(self.name.clone());
 
@@ -55,6 +56,7 @@ where
                 projection,
                 recorder.span().cloned(),
                 predicate,
+                page,
             )
             .await
         {
@@ -120,6 +122,7 @@ mod tests {
                 OwnedProjection::default(),
                 Some(span.child("root span")),
                 None,
+                SnapshotPage::default(),
             )
             .await
             .expect("wrapper should not modify result");
@@ -144,6 +147,7 @@ mod tests {
                 OwnedProjection::default(),
                 Some(span.child("root span")),
                 None,
+                SnapshotPage::default(),
             )
             .await
             .expect_err("wrapper should not modify result");
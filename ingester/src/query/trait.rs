@@ -6,7 +6,7 @@ use predicate::Predicate;
 use thiserror::Error;
 use trace::span::Span;
 
-use super::projection::OwnedProjection;
+use super::{projection::OwnedProjection, snapshot_page::SnapshotPage};
 
 #[derive(Debug, Error)]
 #[allow(missing_copy_implementations)]
@@ -29,6 +29,7 @@ pub(crate) trait QueryExec: Send + Sync + Debug {
         projection: OwnedProjection,
         span: Option<Span>,
         predicate: Option<Predicate>,
+        page: SnapshotPage,
     ) -> Result<Self::Response, QueryError>;
 }
 
@@ -46,9 +47,10 @@ where
         projection: OwnedProjection,
         span: Option<Span>,
         predicate: Option<Predicate>,
+        page: SnapshotPage,
     ) -> Result<Self::Response, QueryError> {
         self.deref()
-            .query_exec(namespace_id, table_id, projection, span, predicate)
+            .query_exec(namespace_id, table_id, projection, span, predicate, page)
             .await
     }
 }
@@ -0,0 +1,271 @@
+//! Streaming rollups, maintaining time-bucketed aggregates of the rows written
+//! to configured tables in `<table>_rollup_<interval>` tables.
+//!
+//! For each [`RollupRule`], the [`RollupSink`] aggregates the rows written to
+//! the source table into buckets of the rule's interval, per tag set: the
+//! count of the values of each field, and the sum, minimum and maximum of the
+//! values of each numeric field. Writes to the source table are extended with
+//! a row per bucket they touch, containing the aggregates of the bucket so far,
+//! that is written to the rollup table in the same op (and the same WAL
+//! entry). As each row of a bucket supersedes the previous ones when
+//! deduplicated, the rollup table holds the aggregates of all the rows written
+//! to the bucket, ready to be queried over long time ranges without scanning
+//! the raw data.
+//!
+//! The rollup tables and their columns are created in the catalog by the
+//! ingester, with the partition template of the source table. The rollup rows
+//! are buffered in the partition of the write they were derived from, so the
+//! interval of a rule should not exceed the time granularity of the partition
+//! template.
+//!
+//! The aggregate state is only held in memory:
+//!
+//!   * The state of a bucket is evicted once the bucket ended more than the
+//!     state retention ago, after which rows written to it are counted as late
+//!     and not aggregated.
+//!   * Buckets that started before the ingester did are never aggregated, as
+//!     their state from before a restart is lost - aggregating the rows
+//!     written afterwards would overwrite the rolled up rows replayed from the
+//!     WAL with partial aggregates.
+//!
+//! The rollup columns are created one at a time and count towards the column
+//! limit of the namespace - a write whose rollup columns cannot be created is
+//! applied without its rollup rows.
+//!
+//! Each ingester rolls up the writes it receives independently, and the rows
+//! of a bucket written by different ingesters share the same primary key. If
+//! the writes to a rolled up table are spread across ingesters, the row of a
+//! bucket read back holds the partial aggregates of whichever ingester's row
+//! is deduplicated last, rather than the aggregates of all the rows. Rollups
+//! must therefore only be configured for tables whose writes are all routed
+//! to the same ingester, or replicated to every ingester rolling them up.
+
+mod sink;
+
+pub(crate) use sink::*;
+
+use std::{collections::BTreeMap, time::Duration};
+
+use data_types::{ColumnType, TableId};
+use mutable_batch::{column::ColumnData, writer::Writer, MutableBatch};
+use schema::{InfluxColumnType, InfluxFieldType, TIME_COLUMN_NAME};
+
+/// A table of a namespace whose writes are rolled up into buckets of an
+/// interval, in the `<table>_rollup_<interval>` table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RollupRule {
+    namespace: String,
+    table: String,
+    interval: Duration,
+}
+
+impl RollupRule {
+    /// Roll up the writes to `table` in `namespace` into buckets of
+    /// `interval`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `interval` is less than a second.
+    pub fn new(namespace: impl Into<String>, table: impl Into<String>, interval: Duration) -> Self {
+        assert!(
+            interval.as_secs() > 0,
+            "rollup interval must be at least a second"
+        );
+
+        Self {
+            namespace: namespace.into(),
+            table: table.into(),
+            interval,
+        }
+    }
+
+    /// The name of the table the writes are rolled up into, such as
+    /// `cpu_rollup_5m`.
+    pub(crate) fn rollup_table_name(&self) -> String {
+        format!("{}_rollup_{}", self.table, format_interval(self.interval))
+    }
+}
+
+/// Format `interval` in the largest of days, hours, minutes or seconds that
+/// represents it exactly.
+fn format_interval(interval: Duration) -> String {
+    let secs = interval.as_secs();
+    for (unit, unit_secs) in [("d", 86_400), ("h", 3_600), ("m", 60)] {
+        if secs % unit_secs == 0 {
+            return format!("{}{unit}", secs / unit_secs);
+        }
+    }
+    format!("{secs}s")
+}
+
+/// Identifies the aggregates of a tag set in a bucket of a rollup table.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+struct BucketKey {
+    table_id: TableId,
+    start: i64,
+    end: i64,
+    /// The `(tag, value)` pairs of the non-null tags, ordered by tag.
+    tags: Vec<(String, String)>,
+}
+
+/// The aggregates of the values of a field in a bucket.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct FieldAggregate {
+    count: u64,
+    /// The sum, minimum and maximum of the values of a numeric field.
+    numeric: Option<(f64, f64, f64)>,
+}
+
+impl FieldAggregate {
+    fn update(&mut self, value: Option<f64>) {
+        self.count += 1;
+        if let Some(v) = value {
+            self.numeric = Some(match self.numeric {
+                Some((sum, min, max)) => (sum + v, min.min(v), max.max(v)),
+                None => (v, v, v),
+            });
+        }
+    }
+}
+
+/// The aggregates of each field in a bucket, by field name.
+type BucketAggregates = BTreeMap<String, FieldAggregate>;
+
+/// Returns true if the values of `field_type` are aggregated numerically.
+fn is_numeric(field_type: InfluxFieldType) -> bool {
+    matches!(
+        field_type,
+        InfluxFieldType::Float | InfluxFieldType::Integer | InfluxFieldType::UInteger
+    )
+}
+
+/// Return the columns of the rollup table rows derived from `batch` are
+/// written to.
+fn rollup_columns(batch: &MutableBatch) -> Vec<(String, ColumnType)> {
+    let mut columns = Vec::new();
+    for (name, column) in batch.columns() {
+        match column.influx_type() {
+            InfluxColumnType::Tag => columns.push((name.clone(), ColumnType::Tag)),
+            InfluxColumnType::Timestamp => columns.push((name.clone(), ColumnType::Time)),
+            InfluxColumnType::Field(t) => {
+                columns.push((format!("{name}_count"), ColumnType::U64));
+                if is_numeric(t) {
+                    for agg in ["sum", "min", "max"] {
+                        columns.push((format!("{name}_{agg}"), ColumnType::F64));
+                    }
+                }
+            }
+        }
+    }
+    columns
+}
+
+/// Call `f` with the timestamp, ordered tags and field values of each row of
+/// `batch`.
+fn for_each_row<F>(batch: &MutableBatch, mut f: F)
+where
+    F: FnMut(i64, Vec<(String, String)>, Vec<(&str, Option<f64>)>),
+{
+    let mut columns = batch.columns().collect::<Vec<_>>();
+    columns.sort_unstable_by(|a, b| a.0.cmp(b.0));
+
+    let times = match batch.column(TIME_COLUMN_NAME).map(|c| c.data()) {
+        Ok(ColumnData::I64(times, _)) => times,
+        _ => return,
+    };
+
+    for (row, &time) in times.iter().enumerate() {
+        let mut tags = Vec::new();
+        let mut fields = Vec::new();
+        for (name, column) in &columns {
+            if !column.valid_mask().get(row) {
+                continue;
+            }
+            match column.data() {
+                ColumnData::Tag(ids, dictionary, _) => {
+                    if let Some(value) = dictionary.lookup_id(ids[row]) {
+                        tags.push((name.to_string(), value.to_string()));
+                    }
+                }
+                ColumnData::F64(values, _) => fields.push((name.as_str(), Some(values[row]))),
+                ColumnData::I64(values, _) if name.as_str() != TIME_COLUMN_NAME => {
+                    fields.push((name.as_str(), Some(values[row] as f64)))
+                }
+                ColumnData::U64(values, _) => {
+                    fields.push((name.as_str(), Some(values[row] as f64)))
+                }
+                ColumnData::String(..) | ColumnData::Bool(..) => fields.push((name.as_str(), None)),
+                ColumnData::I64(..) => {}
+            }
+        }
+        f(time, tags, fields);
+    }
+}
+
+/// Write a row with the aggregates of each bucket in `buckets` to `batch`.
+fn write_rollup_rows<'a, I>(
+    batch: &mut MutableBatch,
+    buckets: I,
+) -> mutable_batch::writer::Result<()>
+where
+    I: IntoIterator<Item = (&'a BucketKey, &'a BucketAggregates)>,
+{
+    for (key, aggregates) in buckets {
+        let mut writer = Writer::new(batch, 1);
+        for (tag, value) in &key.tags {
+            writer.write_tag(tag, None, std::iter::once(value.as_str()))?;
+        }
+        writer.write_time(TIME_COLUMN_NAME, std::iter::once(key.start))?;
+        for (field, agg) in aggregates {
+            writer.write_u64(&format!("{field}_count"), None, std::iter::once(agg.count))?;
+            if let Some((sum, min, max)) = agg.numeric {
+                writer.write_f64(&format!("{field}_sum"), None, std::iter::once(sum))?;
+                writer.write_f64(&format!("{field}_min"), None, std::iter::once(min))?;
+                writer.write_f64(&format!("{field}_max"), None, std::iter::once(max))?;
+            }
+        }
+        writer.commit();
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rollup_table_name() {
+        for (secs, want) in [
+            (1, "cpu_rollup_1s"),
+            (90, "cpu_rollup_90s"),
+            (300, "cpu_rollup_5m"),
+            (7_200, "cpu_rollup_2h"),
+            (86_400, "cpu_rollup_1d"),
+        ] {
+            let rule = RollupRule::new("ns", "cpu", Duration::from_secs(secs));
+            assert_eq!(rule.rollup_table_name(), want);
+        }
+    }
+
+    #[test]
+    fn test_field_aggregate() {
+        let mut agg = FieldAggregate {
+            count: 0,
+            numeric: None,
+        };
+        agg.update(Some(2.0));
+        agg.update(Some(-1.0));
+        agg.update(Some(5.0));
+        assert_eq!(agg.count, 3);
+        assert_eq!(agg.numeric, Some((6.0, -1.0, 5.0)));
+
+        let mut agg = FieldAggregate {
+            count: 0,
+            numeric: None,
+        };
+        agg.update(None);
+        agg.update(None);
+        assert_eq!(agg.count, 2);
+        assert_eq!(agg.numeric, None);
+    }
+}
@@ -0,0 +1,723 @@
+use std::{
+    collections::{btree_map::Entry, BTreeMap, HashMap, HashSet},
+    sync::Arc,
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use data_types::{NamespaceId, SequenceNumber, TableId};
+use iox_catalog::interface::{Catalog, Error as CatalogError, SoftDeletedRows};
+use iox_time::{Time, TimeProvider};
+use metric::U64Counter;
+use mutable_batch::MutableBatch;
+use observability_deps::tracing::warn;
+use parking_lot::Mutex;
+use thiserror::Error;
+
+use super::{
+    for_each_row, rollup_columns, write_rollup_rows, BucketAggregates, BucketKey, FieldAggregate,
+    RollupRule,
+};
+use crate::{
+    dml_payload::{
+        write::{PartitionedData, TableData, WriteOperation},
+        IngestOp,
+    },
+    dml_sink::DmlSink,
+};
+
+/// Errors rolling up a write, logged without failing the write.
+#[derive(Debug, Error)]
+enum RollupError {
+    #[error("catalog error: {0}")]
+    Catalog(#[from] CatalogError),
+
+    #[error("table {0} not found in catalog")]
+    TableNotFound(TableId),
+
+    #[error("rollup table {0} not found in catalog")]
+    RollupTableNotFound(String),
+
+    #[error("namespace {0} not found in catalog")]
+    NamespaceNotFound(NamespaceId),
+
+    #[error("failed to build rollup rows: {0}")]
+    Writer(#[from] mutable_batch::writer::Error),
+
+    #[error("write already contains rows for rollup table {0}")]
+    RollupTableWritten(TableId),
+}
+
+/// A rollup table the writes to a source table are rolled up into.
+#[derive(Debug)]
+struct RollupTarget {
+    table_id: TableId,
+    interval_nanos: i64,
+
+    /// The columns of the rollup table known to exist in the catalog.
+    columns: Mutex<HashSet<String>>,
+
+    /// The aggregates of each bucket of the rollup table, locked from the
+    /// time a write is rolled up until it is applied.
+    buckets: tokio::sync::Mutex<HashMap<BucketKey, BucketAggregates>>,
+}
+
+/// A [`DmlSink`] decorator extending the writes to the tables of the
+/// configured [`RollupRule`] with the rows of their rollup tables.
+///
+/// The writes rolled up into the same rollup table are applied to the inner
+/// sink one at a time, so that the rows of a bucket are buffered in the order
+/// they were aggregated in. The aggregates of each rollup table are locked
+/// independently, and the catalog is never accessed while they are locked.
+///
+/// Failing to roll up a write is logged, and the write applied without its
+/// rollup rows.
+#[derive(Debug)]
+pub(crate) struct RollupSink<T> {
+    inner: T,
+    rules: Vec<RollupRule>,
+    catalog: Arc<dyn Catalog>,
+    time_provider: Arc<dyn TimeProvider>,
+
+    /// Buckets starting before this time are never aggregated.
+    started_at: Time,
+    state_retention: Duration,
+
+    /// The rollup targets of each source table, resolved from the catalog.
+    targets: Mutex<HashMap<TableId, Arc<[Arc<RollupTarget>]>>>,
+
+    rows_aggregated: U64Counter,
+    rows_late: U64Counter,
+    errors: U64Counter,
+}
+
+impl<T> RollupSink<T> {
+    pub(crate) fn new(
+        inner: T,
+        rules: Vec<RollupRule>,
+        catalog: Arc<dyn Catalog>,
+        time_provider: Arc<dyn TimeProvider>,
+        state_retention: Duration,
+        metrics: &metric::Registry,
+    ) -> Self {
+        let rows = metrics.register_metric::<U64Counter>(
+            "ingester_rollup_rows",
+            "number of rows written to rolled up tables, by whether they were aggregated",
+        );
+        let errors = metrics
+            .register_metric::<U64Counter>(
+                "ingester_rollup_errors",
+                "number of writes applied without their rollup rows due to an error",
+            )
+            .recorder(&[]);
+
+        Self {
+            inner,
+            rules,
+            catalog,
+            started_at: time_provider.now(),
+            time_provider,
+            state_retention,
+            targets: Default::default(),
+            rows_aggregated: rows.recorder(&[("result", "aggregated")]),
+            rows_late: rows.recorder(&[("result", "late")]),
+            errors,
+        }
+    }
+
+    /// Return the rollup targets of `table_id`, resolving (and creating) them
+    /// in the catalog when first seen.
+    async fn targets(&self, table_id: TableId) -> Result<Arc<[Arc<RollupTarget>]>, RollupError> {
+        if let Some(targets) = self.targets.lock().get(&table_id) {
+            return Ok(Arc::clone(targets));
+        }
+
+        let targets: Arc<[_]> = self.resolve_targets(table_id).await?.into();
+        self.targets.lock().insert(table_id, Arc::clone(&targets));
+        Ok(targets)
+    }
+
+    async fn resolve_targets(
+        &self,
+        table_id: TableId,
+    ) -> Result<Vec<Arc<RollupTarget>>, RollupError> {
+        if self.rules.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let mut repos = self.catalog.repositories().await;
+        let table = repos
+            .tables()
+            .get_by_id(table_id)
+            .await?
+            .ok_or(RollupError::TableNotFound(table_id))?;
+
+        let rules = self
+            .rules
+            .iter()
+            .filter(|r| r.table == table.name)
+            .collect::<Vec<_>>();
+        if rules.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let namespace = repos
+            .namespaces()
+            .get_by_id(table.namespace_id, SoftDeletedRows::AllRows)
+            .await?
+            .ok_or(RollupError::NamespaceNotFound(table.namespace_id))?;
+
+        let mut targets = Vec::new();
+        for rule in rules.into_iter().filter(|r| r.namespace == namespace.name) {
+            let name = rule.rollup_table_name();
+            let rollup = match repos
+                .tables()
+                .get_by_namespace_and_name(namespace.id, &name)
+                .await?
+            {
+                Some(t) => t,
+                None => match repos
+                    .tables()
+                    .create(&name, table.partition_template.clone(), namespace.id)
+                    .await
+                {
+                    Ok(t) => t,
+                    // Created concurrently by another ingester.
+                    Err(CatalogError::TableNameExists { .. }) => repos
+                        .tables()
+                        .get_by_namespace_and_name(namespace.id, &name)
+                        .await?
+                        .ok_or(RollupError::RollupTableNotFound(name))?,
+                    Err(e) => return Err(e.into()),
+                },
+            };
+
+            let columns = repos
+                .columns()
+                .list_by_table_id(rollup.id)
+                .await?
+                .into_iter()
+                .map(|c| c.name)
+                .collect();
+
+            targets.push(Arc::new(RollupTarget {
+                table_id: rollup.id,
+                interval_nanos: rule.interval.as_nanos() as i64,
+                columns: Mutex::new(columns),
+                buckets: Default::default(),
+            }));
+        }
+
+        Ok(targets)
+    }
+
+    /// Create the columns of the rollup table of `target` the rows derived
+    /// from `batch` are written to, if not already known to exist.
+    ///
+    /// The columns are created one at a time, subject to the column limit of
+    /// the namespace.
+    async fn ensure_columns(
+        &self,
+        target: &RollupTarget,
+        batch: &MutableBatch,
+    ) -> Result<(), RollupError> {
+        let missing = {
+            let known = target.columns.lock();
+            rollup_columns(batch)
+                .into_iter()
+                .filter(|(name, _)| !known.contains(name))
+                .collect::<Vec<_>>()
+        };
+        if missing.is_empty() {
+            return Ok(());
+        }
+
+        let mut repos = self.catalog.repositories().await;
+        for (name, column_type) in missing {
+            repos
+                .columns()
+                .create_or_get(&name, target.table_id, column_type)
+                .await?;
+            target.columns.lock().insert(name);
+        }
+
+        Ok(())
+    }
+
+    /// Aggregate the rows of `batch` into the buckets of `target`, returning
+    /// the updated aggregates of the buckets touched and the number of late
+    /// rows.
+    fn aggregate(
+        &self,
+        state: &HashMap<BucketKey, BucketAggregates>,
+        target: &RollupTarget,
+        batch: &MutableBatch,
+        min_end: i64,
+    ) -> (BTreeMap<BucketKey, BucketAggregates>, u64) {
+        let started_at = self.started_at.timestamp_nanos();
+        let mut updates = BTreeMap::new();
+        let mut late = 0;
+
+        for_each_row(batch, |time, tags, fields| {
+            let start = time.div_euclid(target.interval_nanos) * target.interval_nanos;
+            let end = start.saturating_add(target.interval_nanos);
+            if start < started_at || end <= min_end {
+                late += 1;
+                return;
+            }
+
+            let key = BucketKey {
+                table_id: target.table_id,
+                start,
+                end,
+                tags,
+            };
+            let aggregates = match updates.entry(key) {
+                Entry::Occupied(e) => e.into_mut(),
+                Entry::Vacant(e) => {
+                    let current = state.get(e.key()).cloned().unwrap_or_default();
+                    e.insert(current)
+                }
+            };
+            for (field, value) in fields {
+                aggregates
+                    .entry(field.to_string())
+                    .or_insert(FieldAggregate {
+                        count: 0,
+                        numeric: None,
+                    })
+                    .update(value);
+            }
+        });
+
+        (updates, late)
+    }
+}
+
+#[async_trait]
+impl<T> DmlSink for RollupSink<T>
+where
+    T: DmlSink,
+{
+    type Error = T::Error;
+
+    async fn apply(&self, op: IngestOp) -> Result<(), Self::Error> {
+        let IngestOp::Write(write) = op;
+
+        // Resolve the rollup targets of the tables in the write, and create
+        // the columns their rows are written to, before any aggregates are
+        // locked.
+        let mut sources = Vec::new();
+        for (table_id, data) in write.tables() {
+            let targets = match self.targets(*table_id).await {
+                Ok(v) => v,
+                Err(error) => {
+                    warn!(%error, %table_id, "failed to resolve rollup tables");
+                    self.errors.inc(1);
+                    continue;
+                }
+            };
+            for target in targets.iter() {
+                let res = if write.tables().any(|(id, _)| *id == target.table_id) {
+                    Err(RollupError::RollupTableWritten(target.table_id))
+                } else {
+                    self.ensure_columns(target, data.partitioned_data().data())
+                        .await
+                };
+                match res {
+                    Ok(()) => sources.push((*table_id, Arc::clone(target))),
+                    Err(error) => {
+                        warn!(
+                            %error,
+                            %table_id,
+                            rollup_table_id=%target.table_id,
+                            "failed to roll up write"
+                        );
+                        self.errors.inc(1);
+                    }
+                }
+            }
+        }
+        if sources.is_empty() {
+            return self.inner.apply(IngestOp::Write(write)).await;
+        }
+
+        // Lock the aggregates of the rollup tables in a consistent order.
+        sources.sort_unstable_by_key(|(_, target)| target.table_id);
+        let mut states = Vec::with_capacity(sources.len());
+        for (_, target) in &sources {
+            states.push(target.buckets.lock().await);
+        }
+
+        // Evict the buckets that ended more than the state retention ago.
+        let min_end = self
+            .time_provider
+            .now()
+            .checked_sub(self.state_retention)
+            .map(|t| t.timestamp_nanos())
+            .unwrap_or(i64::MIN);
+
+        let mut rollups: Vec<(TableId, SequenceNumber, MutableBatch)> = Vec::new();
+        let mut updates = Vec::with_capacity(sources.len());
+        let mut aggregated = 0;
+        let mut late = 0;
+        for ((table_id, target), state) in sources.iter().zip(states.iter_mut()) {
+            state.retain(|k, _| k.end > min_end);
+
+            let data = write
+                .tables()
+                .find(|(id, _)| *id == table_id)
+                .map(|(_, t)| t.partitioned_data())
+                .expect("source table is in write");
+
+            let (target_updates, target_late) = self.aggregate(state, target, data.data(), min_end);
+
+            let mut batch = MutableBatch::new();
+            if let Err(error) = write_rollup_rows(&mut batch, &target_updates) {
+                warn!(
+                    %error,
+                    %table_id,
+                    rollup_table_id=%target.table_id,
+                    "failed to roll up write"
+                );
+                self.errors.inc(1);
+                updates.push(BTreeMap::new());
+                continue;
+            }
+
+            aggregated += data.data().rows() as u64 - target_late;
+            late += target_late;
+            if batch.rows() > 0 {
+                rollups.push((target.table_id, data.sequence_number(), batch));
+            }
+            updates.push(target_updates);
+        }
+
+        // Add the rollup rows to the write.
+        let namespace = write.namespace();
+        let partition_key = write.partition_key().clone();
+        let span_context = write.span_context().cloned();
+        let mut tables = write.into_tables().collect::<HashMap<_, _>>();
+        for (table_id, sequence_number, batch) in rollups {
+            tables.insert(
+                table_id,
+                TableData::new(table_id, PartitionedData::new(sequence_number, batch)),
+            );
+        }
+        let write = WriteOperation::new(namespace, tables, partition_key, span_context);
+
+        self.inner.apply(IngestOp::Write(write)).await?;
+
+        for (state, target_updates) in states.iter_mut().zip(updates) {
+            state.extend(target_updates);
+        }
+        self.rows_aggregated.inc(aggregated);
+        self.rows_late.inc(late);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow::record_batch::RecordBatch;
+    use arrow_util::assert_batches_eq;
+    use assert_matches::assert_matches;
+    use data_types::{MaxColumnsPerTable, PartitionKey};
+    use iox_catalog::mem::MemCatalog;
+    use iox_time::MockProvider;
+    use mutable_batch::Projection;
+
+    use super::*;
+    use crate::{
+        dml_sink::{mock_sink::MockDmlSink, DmlError},
+        test_util::{make_write_op, populate_catalog},
+    };
+
+    const NAMESPACE: &str = "bananas";
+    const TABLE: &str = "cpu";
+
+    /// The start of the day all the writes are made in.
+    const DAY: i64 = 1_700_006_400_000_000_000;
+    const MINUTE: i64 = 60_000_000_000;
+
+    fn write(table_id: TableId, sequence_number: u64, lines: &str) -> IngestOp {
+        IngestOp::Write(make_write_op(
+            &PartitionKey::from("2023-11-15"),
+            NamespaceId::new(1),
+            TABLE,
+            table_id,
+            sequence_number,
+            lines,
+            None,
+        ))
+    }
+
+    /// Return the number of buckets of the first rollup table of `table_id`
+    /// holding aggregates.
+    async fn buckets(sink: &RollupSink<Arc<MockDmlSink>>, table_id: TableId) -> usize {
+        let target = Arc::clone(&sink.targets.lock().get(&table_id).unwrap()[0]);
+        let n = target.buckets.lock().await.len();
+        n
+    }
+
+    /// Return the rows written to the rollup table in `op`.
+    fn rollup_rows(op: &IngestOp, rollup_table_id: TableId) -> RecordBatch {
+        let IngestOp::Write(w) = op;
+        w.tables()
+            .find(|(id, _)| **id == rollup_table_id)
+            .expect("no rollup rows")
+            .1
+            .partitioned_data()
+            .data()
+            .to_arrow(Projection::All)
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_rollup() {
+        let metrics = Arc::new(metric::Registry::default());
+        let catalog: Arc<dyn Catalog> = Arc::new(MemCatalog::new(Arc::clone(&metrics)));
+        let (namespace_id, table_id) = populate_catalog(&*catalog, NAMESPACE, TABLE).await;
+        let other_table_id = catalog
+            .repositories()
+            .await
+            .tables()
+            .create("mem", Default::default(), namespace_id)
+            .await
+            .unwrap()
+            .id;
+
+        // The ingester starts in the middle of the first minute.
+        let time_provider = Arc::new(MockProvider::new(Time::from_timestamp_nanos(
+            DAY + MINUTE / 2,
+        )));
+
+        let mock = Arc::new(MockDmlSink::default().with_apply_return([
+            Ok(()),
+            Ok(()),
+            Err(DmlError::Wal("broken".to_string())),
+            Ok(()),
+        ]));
+        let sink = RollupSink::new(
+            Arc::clone(&mock),
+            vec![RollupRule::new(NAMESPACE, TABLE, Duration::from_secs(60))],
+            Arc::clone(&catalog),
+            Arc::clone(&time_provider) as _,
+            Duration::from_secs(3_600),
+            &metrics,
+        );
+
+        // Writes to other tables are not modified.
+        let lines = format!("mem,host=a v=1 {DAY}");
+        let op = IngestOp::Write(make_write_op(
+            &PartitionKey::from("2023-11-15"),
+            NamespaceId::new(1),
+            "mem",
+            other_table_id,
+            1,
+            &lines,
+            None,
+        ));
+        sink.apply(op).await.unwrap();
+        assert_matches!(mock.get_calls().as_slice(), [IngestOp::Write(w)] => {
+            assert_eq!(w.tables().count(), 1);
+        });
+
+        // The first minute started before the ingester and is not rolled up.
+        let lines = format!(
+            "cpu,host=a v=1 {}\ncpu,host=a v=2 {}\ncpu,host=b v=4 {}\ncpu,host=a v=3 {}",
+            DAY + MINUTE + 1,
+            DAY + MINUTE + 2,
+            DAY + MINUTE + 3,
+            DAY + 1,
+        );
+        sink.apply(write(table_id, 2, &lines)).await.unwrap();
+
+        let rollup_table_id = catalog
+            .repositories()
+            .await
+            .tables()
+            .get_by_namespace_and_name(namespace_id, "cpu_rollup_1m")
+            .await
+            .unwrap()
+            .expect("rollup table not created")
+            .id;
+
+        let calls = mock.get_calls();
+        assert_batches_eq!(
+            [
+                "+------+----------------------+---------+-------+-------+-------+",
+                "| host | time                 | v_count | v_max | v_min | v_sum |",
+                "+------+----------------------+---------+-------+-------+-------+",
+                "| a    | 2023-11-15T00:01:00Z | 2       | 2.0   | 1.0   | 3.0   |",
+                "| b    | 2023-11-15T00:01:00Z | 1       | 4.0   | 4.0   | 4.0   |",
+                "+------+----------------------+---------+-------+-------+-------+",
+            ],
+            &[rollup_rows(&calls[1], rollup_table_id)]
+        );
+
+        // A failed write does not change the aggregates.
+        let lines = format!("cpu,host=a v=100 {}", DAY + MINUTE + 4);
+        assert_matches!(
+            sink.apply(write(table_id, 3, &lines)).await,
+            Err(DmlError::Wal(_))
+        );
+
+        // Subsequent rows of the bucket are aggregated with the previous ones.
+        let lines = format!("cpu,host=a v=5 {}", DAY + MINUTE + 5);
+        sink.apply(write(table_id, 4, &lines)).await.unwrap();
+
+        let calls = mock.get_calls();
+        assert_batches_eq!(
+            [
+                "+------+----------------------+---------+-------+-------+-------+",
+                "| host | time                 | v_count | v_max | v_min | v_sum |",
+                "+------+----------------------+---------+-------+-------+-------+",
+                "| a    | 2023-11-15T00:01:00Z | 3       | 5.0   | 1.0   | 8.0   |",
+                "+------+----------------------+---------+-------+-------+-------+",
+            ],
+            &[rollup_rows(&calls[3], rollup_table_id)]
+        );
+
+        // The columns of the rollup table are created in the catalog.
+        let mut columns = catalog
+            .repositories()
+            .await
+            .columns()
+            .list_by_table_id(rollup_table_id)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|c| c.name)
+            .collect::<Vec<_>>();
+        columns.sort_unstable();
+        assert_eq!(
+            columns,
+            ["host", "time", "v_count", "v_max", "v_min", "v_sum"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_rollup_state_eviction() {
+        let metrics = Arc::new(metric::Registry::default());
+        let catalog: Arc<dyn Catalog> = Arc::new(MemCatalog::new(Arc::clone(&metrics)));
+        let (_, table_id) = populate_catalog(&*catalog, NAMESPACE, TABLE).await;
+
+        let time_provider = Arc::new(MockProvider::new(Time::from_timestamp_nanos(DAY)));
+        let mock = Arc::new(MockDmlSink::default().with_apply_return([Ok(()), Ok(())]));
+        let sink = RollupSink::new(
+            Arc::clone(&mock),
+            vec![RollupRule::new(NAMESPACE, TABLE, Duration::from_secs(60))],
+            Arc::clone(&catalog),
+            Arc::clone(&time_provider) as _,
+            Duration::from_secs(60),
+            &metrics,
+        );
+
+        let lines = format!("cpu,host=a v=1 {}", DAY + 1);
+        sink.apply(write(table_id, 1, &lines)).await.unwrap();
+        assert_eq!(buckets(&sink, table_id).await, 1);
+
+        // Once the bucket ended more than the state retention ago, its state
+        // is evicted and rows written to it are late.
+        time_provider.inc(Duration::from_secs(121));
+        sink.apply(write(table_id, 2, &lines)).await.unwrap();
+        assert_eq!(buckets(&sink, table_id).await, 0);
+
+        let calls = mock.get_calls();
+        let IngestOp::Write(w) = &calls[1];
+        assert_eq!(w.tables().count(), 1);
+        assert_eq!(sink.rows_aggregated.fetch(), 1);
+        assert_eq!(sink.rows_late.fetch(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_rollup_column_limit() {
+        let metrics = Arc::new(metric::Registry::default());
+        let catalog: Arc<dyn Catalog> = Arc::new(MemCatalog::new(Arc::clone(&metrics)));
+        let (_, table_id) = populate_catalog(&*catalog, NAMESPACE, TABLE).await;
+        catalog
+            .repositories()
+            .await
+            .namespaces()
+            .update_column_limit(NAMESPACE, MaxColumnsPerTable::new(3))
+            .await
+            .unwrap();
+
+        let time_provider = Arc::new(MockProvider::new(Time::from_timestamp_nanos(DAY)));
+        let mock = Arc::new(MockDmlSink::default().with_apply_return([Ok(())]));
+        let sink = RollupSink::new(
+            Arc::clone(&mock),
+            vec![RollupRule::new(NAMESPACE, TABLE, Duration::from_secs(60))],
+            Arc::clone(&catalog),
+            Arc::clone(&time_provider) as _,
+            Duration::from_secs(3_600),
+            &metrics,
+        );
+
+        // The 6 columns of the rollup table exceed the column limit, so the
+        // write is applied without its rollup rows.
+        let lines = format!("cpu,host=a v=1 {}", DAY + 1);
+        sink.apply(write(table_id, 1, &lines)).await.unwrap();
+
+        assert_matches!(mock.get_calls().as_slice(), [IngestOp::Write(w)] => {
+            assert_eq!(w.tables().count(), 1);
+        });
+        assert_eq!(sink.errors.fetch(), 1);
+        assert_eq!(buckets(&sink, table_id).await, 0);
+    }
+
+    #[tokio::test]
+    async fn test_rollup_tables_locked_independently() {
+        let metrics = Arc::new(metric::Registry::default());
+        let catalog: Arc<dyn Catalog> = Arc::new(MemCatalog::new(Arc::clone(&metrics)));
+        let (namespace_id, table_id) = populate_catalog(&*catalog, NAMESPACE, TABLE).await;
+        let other_table_id = catalog
+            .repositories()
+            .await
+            .tables()
+            .create("mem", Default::default(), namespace_id)
+            .await
+            .unwrap()
+            .id;
+
+        let time_provider = Arc::new(MockProvider::new(Time::from_timestamp_nanos(DAY)));
+        let mock = Arc::new(MockDmlSink::default().with_apply_return([Ok(()), Ok(())]));
+        let sink = RollupSink::new(
+            Arc::clone(&mock),
+            vec![
+                RollupRule::new(NAMESPACE, TABLE, Duration::from_secs(60)),
+                RollupRule::new(NAMESPACE, "mem", Duration::from_secs(60)),
+            ],
+            Arc::clone(&catalog),
+            Arc::clone(&time_provider) as _,
+            Duration::from_secs(3_600),
+            &metrics,
+        );
+
+        let lines = format!("cpu,host=a v=1 {}", DAY + 1);
+        sink.apply(write(table_id, 1, &lines)).await.unwrap();
+
+        // Holding the aggregates of the cpu rollup table does not block the
+        // writes rolled up into the mem rollup table.
+        let target = Arc::clone(&sink.targets.lock().get(&table_id).unwrap()[0]);
+        let _guard = target.buckets.lock().await;
+
+        let lines = format!("mem,host=a v=1 {}", DAY + 1);
+        let op = IngestOp::Write(make_write_op(
+            &PartitionKey::from("2023-11-15"),
+            NamespaceId::new(1),
+            "mem",
+            other_table_id,
+            2,
+            &lines,
+            None,
+        ));
+        tokio::time::timeout(Duration::from_secs(5), sink.apply(op))
+            .await
+            .expect("write to other table blocked")
+            .unwrap();
+        assert_eq!(buckets(&sink, other_table_id).await, 1);
+    }
+}
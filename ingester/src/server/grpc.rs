@@ -1,12 +1,16 @@
 //! gRPC service implementations for `ingester`.
 
+#[cfg(feature = "fault_injection")]
+mod fault_injection;
 mod persist;
 mod query;
 mod rpc_write;
+mod schema;
 
 use std::{fmt::Debug, sync::Arc};
 
 use iox_catalog::interface::Catalog;
+use mutable_batch::value_policy::FieldValuePolicy;
 use service_grpc_catalog::CatalogService;
 
 use crate::{
@@ -15,12 +19,14 @@ use crate::{
     ingester_id::IngesterId,
     init::IngesterRpcInterface,
     partition_iter::PartitionIter,
-    persist::queue::PersistQueue,
+    partition_seal::PartitionSealer,
+    persist::{backpressure::PersistQueueOccupancy, jobs::PersistJobs, queue::PersistQueue},
     query::{response::QueryResponse, QueryExec},
     timestamp_oracle::TimestampOracle,
 };
 
-use self::{persist::PersistHandler, rpc_write::RpcWrite};
+pub(crate) use self::query::LoadShedPolicy;
+use self::{persist::PersistHandler, rpc_write::RpcWrite, schema::BufferSchemaHandler};
 
 /// This type is responsible for injecting internal dependencies that SHOULD NOT
 /// leak outside of the ingester crate into public gRPC handlers.
@@ -38,6 +44,12 @@ pub(crate) struct GrpcDelegate<D, Q, T, P> {
     metrics: Arc<metric::Registry>,
     buffer: Arc<T>,
     persist_handle: Arc<P>,
+    persist_jobs: Arc<PersistJobs>,
+    query_load_shed: Option<(LoadShedPolicy, PersistQueueOccupancy)>,
+    field_value_policy: Option<FieldValuePolicy>,
+    partition_sealer: Option<Arc<PartitionSealer>>,
+    #[cfg(feature = "fault_injection")]
+    faults: Arc<crate::fault_injection::FaultInjector>,
 }
 
 impl<D, Q, T, P> GrpcDelegate<D, Q, T, P>
@@ -70,8 +82,57 @@ where
             metrics,
             buffer,
             persist_handle,
+            persist_jobs: Default::default(),
+            query_load_shed: None,
+            field_value_policy: None,
+            partition_sealer: None,
+            #[cfg(feature = "fault_injection")]
+            faults: Default::default(),
         }
     }
+
+    /// Report the persist jobs executing in `jobs` through the persist gRPC
+    /// service.
+    pub(crate) fn with_persist_jobs(mut self, jobs: Arc<PersistJobs>) -> Self {
+        self.persist_jobs = jobs;
+        self
+    }
+
+    /// Degrade or reject query requests according to `policy` when the
+    /// `occupancy` of the persist queue is high.
+    pub(crate) fn with_query_load_shedding(
+        mut self,
+        policy: LoadShedPolicy,
+        occupancy: PersistQueueOccupancy,
+    ) -> Self {
+        self.query_load_shed = Some((policy, occupancy));
+        self
+    }
+
+    /// Enforce the field value `policy` on all writes.
+    pub(crate) fn with_field_value_policy(mut self, policy: FieldValuePolicy) -> Self {
+        self.field_value_policy = Some(policy);
+        self
+    }
+
+    /// Reject writes to the partitions sealed by `sealer`.
+    pub(crate) fn with_partition_sealer(mut self, sealer: Arc<PartitionSealer>) -> Self {
+        self.partition_sealer = Some(sealer);
+        self
+    }
+
+    /// Configure the [`FaultInjector`] served by the fault injection gRPC
+    /// service.
+    ///
+    /// [`FaultInjector`]: crate::fault_injection::FaultInjector
+    #[cfg(feature = "fault_injection")]
+    pub(crate) fn with_fault_injector(
+        mut self,
+        faults: Arc<crate::fault_injection::FaultInjector>,
+    ) -> Self {
+        self.faults = faults;
+        self
+    }
 }
 
 /// Implement the type-erasure trait to hide internal types from crate-external
@@ -87,6 +148,9 @@ where
     type WriteHandler = RpcWrite<Arc<D>>;
     type PersistHandler = PersistHandler<Arc<T>, Arc<P>>;
     type FlightHandler = query::FlightService<Arc<Q>>;
+    type BufferSchemaHandler = BufferSchemaHandler<Arc<T>>;
+    #[cfg(feature = "fault_injection")]
+    type FaultInjectionHandler = fault_injection::FaultInjectionHandler;
 
     /// Acquire a [`CatalogService`] gRPC service implementation.
     ///
@@ -99,11 +163,19 @@ where
     ///
     /// [`WriteService`]: generated_types::influxdata::iox::ingester::v1::write_service_server::WriteService.
     fn write_service(&self) -> Self::WriteHandler {
-        RpcWrite::new(
+        let handler = RpcWrite::new(
             Arc::clone(&self.dml_sink),
             Arc::clone(&self.timestamp),
             Arc::clone(&self.ingest_state),
-        )
+        );
+        let handler = match self.field_value_policy {
+            Some(policy) => handler.with_field_value_policy(policy, &self.metrics),
+            None => handler,
+        };
+        match &self.partition_sealer {
+            Some(sealer) => handler.with_partition_sealer(Arc::clone(sealer)),
+            None => handler,
+        }
     }
 
     /// Return a [`PersistService`] gRPC implementation.
@@ -114,18 +186,49 @@ where
             Arc::clone(&self.buffer),
             Arc::clone(&self.persist_handle),
             Arc::clone(&self.catalog),
+            Arc::clone(&self.persist_jobs),
         )
     }
 
     /// Return an Arrow [`FlightService`] gRPC implementation.
     ///
     /// [`FlightService`]: arrow_flight::flight_service_server::FlightService
-    fn query_service(&self, max_simultaneous_requests: usize) -> Self::FlightHandler {
-        query::FlightService::new(
+    fn query_service(
+        &self,
+        max_simultaneous_requests: usize,
+        max_message_bytes: Option<usize>,
+    ) -> Self::FlightHandler {
+        let service = query::FlightService::new(
             Arc::clone(&self.query_exec),
             self.ingester_id,
             max_simultaneous_requests,
             &self.metrics,
-        )
+        );
+        let service = match max_message_bytes {
+            Some(v) => service.with_max_message_bytes(v),
+            None => service,
+        };
+
+        match &self.query_load_shed {
+            Some((policy, occupancy)) => {
+                service.with_load_shedding(*policy, occupancy.clone(), &self.metrics)
+            }
+            None => service,
+        }
+    }
+
+    /// Return a [`BufferSchemaService`] gRPC implementation.
+    ///
+    /// [`BufferSchemaService`]: generated_types::influxdata::iox::ingester::v1::buffer_schema_service_server::BufferSchemaService.
+    fn buffer_schema_service(&self) -> Self::BufferSchemaHandler {
+        BufferSchemaHandler::new(Arc::clone(&self.buffer))
+    }
+
+    /// Return a [`FaultInjectionService`] gRPC implementation.
+    ///
+    /// [`FaultInjectionService`]: generated_types::influxdata::iox::ingester::v1::fault_injection_service_server::FaultInjectionService.
+    #[cfg(feature = "fault_injection")]
+    fn fault_injection_service(&self) -> Self::FaultInjectionHandler {
+        fault_injection::FaultInjectionHandler::new(Arc::clone(&self.faults))
     }
 }
@@ -0,0 +1,110 @@
+use std::sync::Arc;
+
+use generated_types::influxdata::iox::ingester::v1::{
+    self as proto, fault_injection_service_server::FaultInjectionService,
+};
+use tonic::{Request, Response};
+
+use crate::fault_injection::{FaultInjector, FaultPoint};
+
+/// A gRPC [`FaultInjectionService`] handler configuring a [`FaultInjector`].
+#[derive(Debug)]
+pub(crate) struct FaultInjectionHandler {
+    faults: Arc<FaultInjector>,
+}
+
+impl FaultInjectionHandler {
+    pub(crate) fn new(faults: Arc<FaultInjector>) -> Self {
+        Self { faults }
+    }
+}
+
+#[tonic::async_trait]
+impl FaultInjectionService for FaultInjectionHandler {
+    async fn set_fault(
+        &self,
+        request: Request<proto::SetFaultRequest>,
+    ) -> Result<Response<proto::SetFaultResponse>, tonic::Status> {
+        let request = request.into_inner();
+
+        let point = match request.point() {
+            proto::FaultPoint::ObjectStorePut => FaultPoint::ObjectStorePut,
+            proto::FaultPoint::CatalogCommit => FaultPoint::CatalogCommit,
+            proto::FaultPoint::Compact => FaultPoint::Compact,
+            proto::FaultPoint::Unspecified => {
+                return Err(tonic::Status::invalid_argument("fault point not specified"))
+            }
+        };
+
+        if !(0.0..=1.0).contains(&request.probability) {
+            return Err(tonic::Status::invalid_argument(format!(
+                "fault probability must be between 0 and 1, got {}",
+                request.probability
+            )));
+        }
+
+        self.faults.set(point, request.probability);
+
+        Ok(Response::new(proto::SetFaultResponse {}))
+    }
+
+    async fn clear_faults(
+        &self,
+        _request: Request<proto::ClearFaultsRequest>,
+    ) -> Result<Response<proto::ClearFaultsResponse>, tonic::Status> {
+        self.faults.clear();
+
+        Ok(Response::new(proto::ClearFaultsResponse {}))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_matches::assert_matches;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_set_fault() {
+        let faults = Arc::new(FaultInjector::default());
+        let handler = FaultInjectionHandler::new(Arc::clone(&faults));
+
+        handler
+            .set_fault(Request::new(proto::SetFaultRequest {
+                point: proto::FaultPoint::Compact.into(),
+                probability: 1.0,
+            }))
+            .await
+            .expect("request should succeed");
+        assert_matches!(faults.check(FaultPoint::Compact), Err(_));
+
+        handler
+            .clear_faults(Request::new(proto::ClearFaultsRequest {}))
+            .await
+            .expect("request should succeed");
+        assert_matches!(faults.check(FaultPoint::Compact), Ok(()));
+    }
+
+    #[tokio::test]
+    async fn test_set_fault_invalid() {
+        let handler = FaultInjectionHandler::new(Default::default());
+
+        let err = handler
+            .set_fault(Request::new(proto::SetFaultRequest {
+                point: proto::FaultPoint::Unspecified.into(),
+                probability: 1.0,
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), tonic::Code::InvalidArgument);
+
+        let err = handler
+            .set_fault(Request::new(proto::SetFaultRequest {
+                point: proto::FaultPoint::CatalogCommit.into(),
+                probability: 1.5,
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), tonic::Code::InvalidArgument);
+    }
+}
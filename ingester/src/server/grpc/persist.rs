@@ -1,6 +1,10 @@
 use crate::{
     partition_iter::PartitionIter,
-    persist::{drain_buffer::persist_partitions, queue::PersistQueue},
+    persist::{
+        drain_buffer::persist_partitions,
+        jobs::{PersistJobStatus, PersistJobs, PersistStage},
+        queue::PersistQueue,
+    },
 };
 use generated_types::influxdata::iox::ingester::v1::{
     self as proto, persist_service_server::PersistService,
@@ -14,6 +18,7 @@ pub(crate) struct PersistHandler<T, P> {
     buffer: T,
     persist_handle: P,
     catalog: Arc<dyn Catalog>,
+    jobs: Arc<PersistJobs>,
 }
 
 impl<T, P> PersistHandler<T, P>
@@ -21,11 +26,17 @@ where
     T: PartitionIter + Sync + 'static,
     P: PersistQueue + Clone + Sync + 'static,
 {
-    pub(crate) fn new(buffer: T, persist_handle: P, catalog: Arc<dyn Catalog>) -> Self {
+    pub(crate) fn new(
+        buffer: T,
+        persist_handle: P,
+        catalog: Arc<dyn Catalog>,
+        jobs: Arc<PersistJobs>,
+    ) -> Self {
         Self {
             buffer,
             persist_handle,
             catalog,
+            jobs,
         }
     }
 }
@@ -65,4 +76,67 @@ where
 
         Ok(Response::new(proto::PersistResponse {}))
     }
+
+    /// List the persist jobs currently executing, reporting their progress.
+    async fn list_persist_jobs(
+        &self,
+        _request: Request<proto::ListPersistJobsRequest>,
+    ) -> Result<Response<proto::ListPersistJobsResponse>, tonic::Status> {
+        let jobs = self.jobs.list().into_iter().map(encode_job).collect();
+
+        Ok(Response::new(proto::ListPersistJobsResponse { jobs }))
+    }
+
+    /// Report the buffered and persisted sequence numbers, and the buffered
+    /// size, of each partition with data buffered or persisting.
+    async fn get_write_info(
+        &self,
+        _request: Request<proto::GetWriteInfoRequest>,
+    ) -> Result<Response<proto::GetWriteInfoResponse>, tonic::Status> {
+        let partitions = self
+            .buffer
+            .partition_iter()
+            .filter_map(|p| {
+                let p = p.lock();
+                if p.is_empty() {
+                    return None;
+                }
+
+                let buffered = p.buffered_sequence_number_range();
+                Some(proto::PartitionWriteInfo {
+                    namespace_id: p.namespace_id().get(),
+                    table_id: p.table_id().get(),
+                    partition_identifier: Some(p.partition_id().clone().into()),
+                    min_buffered_sequence_number: buffered.map(|(min, _)| min.get()),
+                    max_buffered_sequence_number: buffered.map(|(_, max)| max.get()),
+                    max_persisted_sequence_number: p
+                        .max_persisted_sequence_number()
+                        .map(|v| v.get()),
+                    buffered_bytes: (p.persist_cost_estimate() + p.persisting_size()) as u64,
+                })
+            })
+            .collect();
+
+        Ok(Response::new(proto::GetWriteInfoResponse { partitions }))
+    }
+}
+
+fn encode_job(job: PersistJobStatus) -> proto::PersistJob {
+    let stage = match job.stage {
+        PersistStage::Compacting => proto::PersistJobStage::Compacting,
+        PersistStage::Uploading => proto::PersistJobStage::Uploading,
+        PersistStage::Committing => proto::PersistJobStage::Committing,
+    };
+
+    proto::PersistJob {
+        namespace_id: job.namespace_id.get(),
+        table_id: job.table_id.get(),
+        partition_identifier: Some(job.partition_id.into()),
+        stage: stage.into(),
+        active_duration_millis: job.active_duration.as_millis() as u64,
+        stage_duration_millis: job.stage_duration.as_millis() as u64,
+        input_bytes: job.input_bytes as u64,
+        file_bytes: job.file_bytes.map(|v| v as u64),
+        restarts: job.restarts as u64,
+    }
 }
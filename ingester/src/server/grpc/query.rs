@@ -1,4 +1,4 @@
-use std::{pin::Pin, sync::Arc};
+use std::{collections::HashMap, num::NonZeroUsize, pin::Pin, sync::Arc, time::Duration};
 
 use arrow_flight::{
     encode::FlightDataEncoderBuilder, error::FlightError,
@@ -6,10 +6,12 @@ use arrow_flight::{
     FlightData, FlightDescriptor, FlightInfo, HandshakeRequest, HandshakeResponse, PutResult,
     SchemaResult, Ticket,
 };
-use data_types::{NamespaceId, TableId, TransitionPartitionId};
+use data_types::{
+    NamespaceId, PartitionHashId, PartitionId, SequenceNumber, TableId, TransitionPartitionId,
+};
 use flatbuffers::FlatBufferBuilder;
 use futures::{Stream, StreamExt, TryStreamExt};
-use ingester_query_grpc::influxdata::iox::ingester::v1 as proto;
+use ingester_query_grpc::{influxdata::iox::ingester::v1 as proto, FieldViolation};
 use metric::{DurationHistogram, U64Counter};
 use observability_deps::tracing::*;
 use predicate::Predicate;
@@ -21,13 +23,25 @@ use trace::{
     ctx::SpanContext,
     span::{Span, SpanExt, SpanRecorder},
 };
+use uuid::Uuid;
 
 mod instrumentation;
 use instrumentation::FlightFrameEncodeInstrumentation;
 
+mod load_shed;
+pub(crate) use load_shed::LoadShedPolicy;
+use load_shed::{Decision, LoadShedder};
+
 use crate::{
     ingester_id::IngesterId,
-    query::{projection::OwnedProjection, response::QueryResponse, QueryError, QueryExec},
+    persist::backpressure::PersistQueueOccupancy,
+    query::{
+        partition_response::PartitionResponse,
+        projection::OwnedProjection,
+        response::{PartitionStream, QueryResponse},
+        snapshot_page::SnapshotPage,
+        QueryError, QueryExec,
+    },
 };
 
 /// Error states for the query RPC handler.
@@ -53,6 +67,10 @@ enum Error {
     /// The payload within the request has an invalid field value.
     #[error("field violation: {0}")]
     FieldViolation(#[from] ingester_query_grpc::FieldViolation),
+
+    /// The request was rejected to relieve buffer pressure.
+    #[error("ingester under buffer pressure, retry after {}s", .retry_after.as_secs())]
+    LoadShed { retry_after: Duration },
 }
 
 /// Map a query-execution error into a [`tonic::Status`].
@@ -73,6 +91,7 @@ impl From<Error> for tonic::Status {
     fn from(e: Error) -> Self {
         use tonic::Code;
 
+        let mut retry_after = None;
         let code = match e {
             Error::InvalidTicket(_) => {
                 debug!(error=%e, "invalid flight query ticket");
@@ -86,9 +105,21 @@ impl From<Error> for tonic::Status {
                 debug!(error=%e, "request contains field violation");
                 Code::InvalidArgument
             }
+            Error::LoadShed { retry_after: d } => {
+                warn!("buffer pressure exceeds query reject threshold");
+                retry_after = Some(d);
+                Code::Unavailable
+            }
         };
 
-        Self::new(code, e.to_string())
+        let mut status = Self::new(code, e.to_string());
+        if let Some(d) = retry_after {
+            // Advise the caller when to retry, in whole seconds.
+            status
+                .metadata_mut()
+                .insert("retry-after", d.as_secs().into());
+        }
+        status
     }
 }
 
@@ -112,9 +143,20 @@ pub(crate) struct FlightService<Q> {
     /// Duration per partition, per request.
     query_request_frame_encoding_duration: Arc<DurationHistogram>,
 
+    /// An optional policy to degrade or reject requests when the ingester is
+    /// under buffer pressure.
+    load_shed: Option<LoadShedder>,
+
+    /// The target size of the encoded response data messages, if bounded.
+    max_flight_data_size: Option<usize>,
+
     ingester_id: IngesterId,
 }
 
+/// An allowance for the gRPC and Arrow IPC framing of a response data message,
+/// not accounted for by the target size of the encoded data.
+const FLIGHT_DATA_FRAMING_OVERHEAD_BYTES: usize = 16 * 1024;
+
 impl<Q> FlightService<Q> {
     pub(super) fn new(
         query_handler: Q,
@@ -143,9 +185,38 @@ impl<Q> FlightService<Q> {
             request_sem: Semaphore::new(max_simultaneous_requests),
             query_request_limit_rejected,
             query_request_frame_encoding_duration,
+            load_shed: None,
+            max_flight_data_size: None,
             ingester_id,
         }
     }
+
+    /// Split the response data into messages of at most `max_message_bytes`,
+    /// so that they fit within the message size limit of the queriers.
+    ///
+    /// The data of a single row is never split across messages.
+    pub(super) fn with_max_message_bytes(mut self, max_message_bytes: usize) -> Self {
+        self.max_flight_data_size =
+            Some(max_message_bytes.saturating_sub(FLIGHT_DATA_FRAMING_OVERHEAD_BYTES));
+        self
+    }
+
+    /// Degrade or reject query requests according to `policy`, measuring the
+    /// buffer pressure as the `occupancy` of the persist queue.
+    ///
+    /// Degraded requests are answered with the metadata of each partition,
+    /// flagged as omitting its buffered data, without snapshotting or reading
+    /// the buffer - queriers receive only persisted data until the pressure
+    /// subsides.
+    pub(super) fn with_load_shedding(
+        mut self,
+        policy: LoadShedPolicy,
+        occupancy: PersistQueueOccupancy,
+        metrics: &metric::Registry,
+    ) -> Self {
+        self.load_shed = Some(LoadShedder::new(policy, occupancy, metrics));
+        self
+    }
 }
 
 type TonicStream<T> = Pin<Box<dyn Stream<Item = Result<T, tonic::Status>> + Send + 'static>>;
@@ -194,6 +265,16 @@ where
             Err(e) => panic!("request limiter error: {e}"),
         };
 
+        // Shed load to prioritise ingest if the ingester is under pressure.
+        let decision = self
+            .load_shed
+            .as_ref()
+            .map(LoadShedder::evaluate)
+            .unwrap_or(Decision::Accept);
+        if let Decision::Reject { retry_after } = decision {
+            return Err(Error::LoadShed { retry_after })?;
+        }
+
         let ticket = request.into_inner();
         let request = proto::IngesterQueryRequest::decode(&*ticket.ticket).map_err(Error::from)?;
 
@@ -208,6 +289,15 @@ where
         };
 
         let projection = OwnedProjection::from(request.columns);
+        let page = match decision {
+            // Avoid snapshotting and reading the buffered data of a degraded
+            // response.
+            Decision::Degrade => SnapshotPage::metadata_only(),
+            Decision::Accept | Decision::Reject { .. } => SnapshotPage::new(
+                decode_resume_after(request.resume_after).map_err(Error::from)?,
+                NonZeroUsize::new(request.max_snapshots as usize),
+            ),
+        };
 
         let response = match self
             .query_handler
@@ -217,6 +307,7 @@ where
                 projection,
                 query_recorder.child_span("query exec"),
                 predicate,
+                page,
             )
             .await
         {
@@ -233,11 +324,24 @@ where
             }
         };
 
+        let response = match decision {
+            Decision::Degrade => {
+                debug!(%namespace_id, %table_id, "serving metadata-only query response");
+                QueryResponse::new(PartitionStream::new(
+                    response
+                        .into_partition_stream()
+                        .map(PartitionResponse::into_metadata_only),
+                ))
+            }
+            Decision::Accept | Decision::Reject { .. } => response,
+        };
+
         let output = encode_response(
             response,
             self.ingester_id,
             query_recorder.child_span("serialise response"),
             Arc::clone(&self.query_request_frame_encoding_duration),
+            self.max_flight_data_size,
         )
         .map_err(tonic::Status::from);
 
@@ -301,6 +405,37 @@ where
     }
 }
 
+/// Decode the partitions, and the sequence number of each, a query resumes
+/// after.
+fn decode_resume_after(
+    resume_after: Vec<proto::PartitionSequenceNumber>,
+) -> Result<HashMap<TransitionPartitionId, SequenceNumber>, FieldViolation> {
+    use proto::partition_sequence_number::PartitionIdentifier;
+
+    resume_after
+        .into_iter()
+        .map(|v| {
+            let id = match v.partition_identifier {
+                Some(PartitionIdentifier::CatalogId(id)) => {
+                    TransitionPartitionId::Deprecated(PartitionId::new(id))
+                }
+                Some(PartitionIdentifier::HashId(id)) => TransitionPartitionId::Deterministic(
+                    PartitionHashId::try_from(id.as_slice()).map_err(|e| FieldViolation {
+                        field: "resume_after.hash_id".to_owned(),
+                        description: e.to_string(),
+                    })?,
+                ),
+                None => {
+                    return Err(FieldViolation::required(
+                        "resume_after.partition_identifier",
+                    ))
+                }
+            };
+            Ok((id, SequenceNumber::new(v.sequence_number)))
+        })
+        .collect()
+}
+
 /// Encode the partition information as a None flight data with meatadata
 fn encode_partition(
     // Partition identifier.
@@ -311,6 +446,14 @@ fn encode_partition(
     // [`PartitionData`]: crate::buffer_tree::partition::PartitionData
     // [`PartitionResponse`]: crate::query::partition_response::PartitionResponse
     completed_persistence_count: u64,
+    // Object store IDs of the Parquet files being persisted for this
+    // partition, the data of which is included in the response.
+    persisting_object_store_ids: &[Uuid],
+    // The sequence number to resume a query truncated by its page limit from.
+    resume_after_sequence_number: Option<SequenceNumber>,
+    // True if the buffered data of the partition was omitted to relieve
+    // buffer pressure.
+    buffered_data_omitted: bool,
     ingester_id: IngesterId,
 ) -> Result<FlightData, FlightError> {
     use proto::ingester_query_response_metadata::PartitionIdentifier;
@@ -329,6 +472,12 @@ fn encode_partition(
         partition_identifier: Some(partition_identifier),
         ingester_uuid: ingester_id.to_string(),
         completed_persistence_count,
+        persisting_object_store_ids: persisting_object_store_ids
+            .iter()
+            .map(ToString::to_string)
+            .collect(),
+        resume_after_sequence_number: resume_after_sequence_number.map(|v| v.get()),
+        buffered_data_omitted,
     };
     prost::Message::encode(&app_metadata, &mut bytes)
         .map_err(|e| FlightError::from_external_error(Box::new(e)))?;
@@ -358,16 +507,27 @@ fn encode_response(
     ingester_id: IngesterId,
     span: Option<Span>,
     frame_encoding_duration_metric: Arc<DurationHistogram>,
+    max_flight_data_size: Option<usize>,
 ) -> impl Stream<Item = Result<FlightData, FlightError>> {
     let span = SpanRecorder::new(span.clone()).span().cloned();
 
     response.into_partition_stream().flat_map(move |partition| {
         let partition_id = partition.id().clone();
         let completed_persistence_count = partition.completed_persistence_count();
+        let persisting_object_store_ids = partition.persisting_object_store_ids().to_vec();
+        let resume_after_sequence_number = partition.resume_after_sequence_number();
+        let buffered_data_omitted = partition.data_omitted();
 
         // prefix payload data w/ metadata for that particular partition
         let head = futures::stream::once(async move {
-            encode_partition(partition_id, completed_persistence_count, ingester_id)
+            encode_partition(
+                partition_id,
+                completed_persistence_count,
+                &persisting_object_store_ids,
+                resume_after_sequence_number,
+                buffered_data_omitted,
+                ingester_id,
+            )
         });
 
         // An output vector of FlightDataEncoder streams, each entry stream with
@@ -381,8 +541,12 @@ fn encode_response(
 
         // While there are more batches to process.
         while let Some(schema) = batch_iter.peek().map(|v| v.schema()) {
+            let encoder = match max_flight_data_size {
+                Some(v) => FlightDataEncoderBuilder::new().with_max_flight_data_size(v),
+                None => FlightDataEncoderBuilder::new(),
+            };
             output.push(FlightFrameEncodeInstrumentation::new(
-                FlightDataEncoderBuilder::new().build(futures::stream::iter(
+                encoder.build(futures::stream::iter(
                     // Take all the RecordBatch with a matching schema
                     std::iter::from_fn(|| batch_iter.next_if(|v| v.schema() == schema))
                         .map(Ok)
@@ -401,18 +565,17 @@ fn encode_response(
 mod tests {
     use super::*;
     use crate::{
+        ingest_state::{IngestState, IngestStateError},
         make_batch,
-        query::{
-            mock_query_exec::MockQueryExec, partition_response::PartitionResponse,
-            response::PartitionStream,
-        },
+        query::mock_query_exec::MockQueryExec,
         test_util::{ARBITRARY_PARTITION_HASH_ID, ARBITRARY_TRANSITION_PARTITION_ID},
     };
-    use arrow::array::{Float64Array, Int32Array};
+    use arrow::array::{Float64Array, Int32Array, Int64Array};
     use arrow_flight::decode::{DecodedPayload, FlightRecordBatchStream};
     use assert_matches::assert_matches;
     use bytes::Bytes;
     use data_types::PartitionId;
+    use metric::{Attributes, Metric};
     use proto::ingester_query_response_metadata::PartitionIdentifier;
     use tonic::Code;
     use trace::{ctx::SpanContext, RingBufferTraceCollector, TraceCollector};
@@ -457,10 +620,54 @@ mod tests {
             )),
             ingester_uuid: ingester_id.to_string(),
             completed_persistence_count: 42,
+            persisting_object_store_ids: vec![],
+            resume_after_sequence_number: None,
+            buffered_data_omitted: false,
         };
         assert_eq!(md_actual, md_expected);
     }
 
+    #[tokio::test]
+    async fn sends_persisting_object_store_ids() {
+        let ingester_id = IngesterId::new();
+        let ids = vec![Uuid::new_v4(), Uuid::new_v4()];
+
+        let flight = FlightService::new(
+            MockQueryExec::default().with_result(Ok(QueryResponse::new(PartitionStream::new(
+                futures::stream::iter([PartitionResponse::new(
+                    vec![],
+                    ARBITRARY_TRANSITION_PARTITION_ID.clone(),
+                    42,
+                )
+                .with_persisting_object_store_ids(ids.clone())]),
+            )))),
+            ingester_id,
+            100,
+            &metric::Registry::default(),
+        );
+
+        let req = tonic::Request::new(Ticket {
+            ticket: Bytes::new(),
+        });
+        let response_stream = flight
+            .do_get(req)
+            .await
+            .unwrap()
+            .into_inner()
+            .map_err(FlightError::Tonic);
+        let flight_decoder =
+            FlightRecordBatchStream::new_from_flight_data(response_stream).into_inner();
+        let flight_data = flight_decoder.try_collect::<Vec<_>>().await.unwrap();
+
+        assert_matches!(flight_data[0].payload, DecodedPayload::None);
+        let md_actual =
+            proto::IngesterQueryResponseMetadata::decode(flight_data[0].app_metadata()).unwrap();
+        assert_eq!(
+            md_actual.persisting_object_store_ids,
+            ids.iter().map(ToString::to_string).collect::<Vec<_>>()
+        );
+    }
+
     #[tokio::test]
     async fn doesnt_send_partition_hash_id_if_not_present() {
         let ingester_id = IngesterId::new();
@@ -498,6 +705,9 @@ mod tests {
             partition_identifier: Some(PartitionIdentifier::CatalogId(2)),
             ingester_uuid: ingester_id.to_string(),
             completed_persistence_count: 42,
+            persisting_object_store_ids: vec![],
+            resume_after_sequence_number: None,
+            buffered_data_omitted: false,
         };
         assert_eq!(md_actual, md_expected);
     }
@@ -534,6 +744,106 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_load_shedding() {
+        let ingester_id = IngesterId::new();
+        let (batch, _) = make_batch!(
+            Int32Array("int" => vec![1, 2, 3]),
+        );
+
+        let ingest_state = Arc::new(IngestState::default());
+        let sem = Arc::new(Semaphore::new(10));
+        let occupancy =
+            PersistQueueOccupancy::new_for_test(Arc::clone(&ingest_state), 10, Arc::clone(&sem));
+
+        let metrics = metric::Registry::default();
+        let flight = FlightService::new(
+            MockQueryExec::default().with_result(Ok(QueryResponse::new(PartitionStream::new(
+                futures::stream::iter([PartitionResponse::new(
+                    vec![batch],
+                    ARBITRARY_TRANSITION_PARTITION_ID.clone(),
+                    42,
+                )
+                .with_persisting_object_store_ids(vec![Uuid::new_v4()])]),
+            )))),
+            ingester_id,
+            100,
+            &metrics,
+        )
+        .with_load_shedding(
+            LoadShedPolicy::new(Some(50), Some(90), Duration::from_secs(7)).unwrap(),
+            occupancy,
+            &metrics,
+        );
+
+        // Occupy 50% of the persist queue, degrading the response to contain
+        // only the partition metadata.
+        let _permits = sem.try_acquire_many(5).unwrap();
+
+        let req = tonic::Request::new(Ticket {
+            ticket: Bytes::new(),
+        });
+        let response_stream = flight
+            .do_get(req)
+            .await
+            .unwrap()
+            .into_inner()
+            .map_err(FlightError::Tonic);
+        let flight_data = FlightRecordBatchStream::new_from_flight_data(response_stream)
+            .into_inner()
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+
+        assert_matches!(flight_data.as_slice(), [metadata] => {
+            assert_matches!(metadata.payload, DecodedPayload::None);
+            let md = proto::IngesterQueryResponseMetadata::decode(metadata.app_metadata())
+                .unwrap();
+            assert_eq!(md.completed_persistence_count, 42);
+            assert!(md.persisting_object_store_ids.is_empty());
+            assert!(md.buffered_data_omitted);
+        });
+
+        // The buffered data was not read.
+        assert_eq!(
+            flight.query_handler.last_page(),
+            Some(SnapshotPage::metadata_only())
+        );
+
+        // Stop accepting writes, marking the buffer pressure as 100% and
+        // rejecting the query with a retry hint.
+        ingest_state.set(IngestStateError::PersistSaturated);
+
+        let req = tonic::Request::new(Ticket {
+            ticket: Bytes::new(),
+        });
+        let status = flight.do_get(req).await.err().unwrap();
+        assert_eq!(status.code(), Code::Unavailable);
+        assert_eq!(
+            status
+                .metadata()
+                .get("retry-after")
+                .unwrap()
+                .to_str()
+                .unwrap(),
+            "7"
+        );
+
+        let metric = metrics
+            .get_instrument::<Metric<U64Counter>>("ingester_query_request_shed")
+            .unwrap();
+        for action in ["degraded", "rejected"] {
+            assert_eq!(
+                metric
+                    .get_observer(&Attributes::from(&[("action", action)]))
+                    .unwrap()
+                    .fetch(),
+                1,
+                "{action}"
+            );
+        }
+    }
+
     #[tokio::test]
     async fn test_encoded_spans_attached_to_collector() {
         let ingester_id = IngesterId::new();
@@ -560,7 +870,13 @@ mod tests {
         let query_span = span_ctx.child("query span");
 
         // test with encode_response
-        let call_chain = encode_response(query_response, ingester_id, Some(query_span), histogram);
+        let call_chain = encode_response(
+            query_response,
+            ingester_id,
+            Some(query_span),
+            histogram,
+            None,
+        );
         call_chain.collect::<Vec<_>>().await;
 
         let spans = trace_collector.spans();
@@ -572,6 +888,46 @@ mod tests {
         });
     }
 
+    #[tokio::test]
+    async fn test_encode_response_max_message_size() {
+        let (batch, _) = make_batch!(
+            Int64Array("int" => vec![42_i64; 100_000]),
+        );
+        let histogram = Arc::new(
+            metric::Registry::default()
+                .register_metric::<DurationHistogram>("test", "")
+                .recorder([]),
+        );
+
+        let encode = |max_flight_data_size| {
+            let query_response = QueryResponse::new(PartitionStream::new(futures::stream::iter([
+                PartitionResponse::new(
+                    vec![batch.clone()],
+                    ARBITRARY_TRANSITION_PARTITION_ID.clone(),
+                    42,
+                ),
+            ])));
+            encode_response(
+                query_response,
+                IngesterId::new(),
+                None,
+                Arc::clone(&histogram),
+                max_flight_data_size,
+            )
+            .map(|v| v.expect("encoding should succeed").data_body.len())
+            .collect::<Vec<_>>()
+        };
+
+        // Without a limit, the 800KB batch is sent as a single message.
+        let unbounded = encode(None).await;
+        assert_eq!(unbounded.iter().filter(|&&v| v > 0).count(), 1);
+
+        // With a limit, it is split into messages fitting within it.
+        let bounded = encode(Some(100 * 1024)).await;
+        assert!(bounded.iter().filter(|&&v| v > 0).count() > 1);
+        assert!(bounded.iter().all(|&v| v <= 100 * 1024));
+    }
+
     /// Regression test for https://github.com/influxdata/idpe/issues/17408
     #[tokio::test]
     async fn test_chunks_with_different_schemas() {
@@ -636,6 +992,9 @@ mod tests {
             )),
             ingester_uuid: ingester_id.to_string(),
             completed_persistence_count: 42,
+            persisting_object_store_ids: vec![],
+            resume_after_sequence_number: None,
+            buffered_data_omitted: false,
         };
         assert_eq!(md_actual, md_expected);
 
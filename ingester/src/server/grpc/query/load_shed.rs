@@ -0,0 +1,166 @@
+//! Shedding of query load when the ingester is under buffer pressure.
+
+use std::time::Duration;
+
+use metric::U64Counter;
+
+use crate::persist::backpressure::PersistQueueOccupancy;
+
+/// The buffer pressure thresholds at which query requests are degraded or
+/// rejected to prioritise ingest stability.
+///
+/// Buffer pressure is measured as the percentage of the persist queue occupied
+/// by outstanding persist jobs, and is always 100% when the ingester is not
+/// accepting writes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct LoadShedPolicy {
+    /// Serve metadata-only responses at or above this pressure.
+    degrade_percent: Option<u8>,
+
+    /// Reject requests at or above this pressure.
+    reject_percent: Option<u8>,
+
+    /// The duration of time rejected callers are advised to wait before
+    /// retrying.
+    retry_after: Duration,
+}
+
+impl LoadShedPolicy {
+    /// Initialise a [`LoadShedPolicy`], returning [`None`] if neither
+    /// threshold is set.
+    pub(crate) fn new(
+        degrade_percent: Option<u8>,
+        reject_percent: Option<u8>,
+        retry_after: Duration,
+    ) -> Option<Self> {
+        if degrade_percent.is_none() && reject_percent.is_none() {
+            return None;
+        }
+
+        Some(Self {
+            degrade_percent,
+            reject_percent,
+            retry_after,
+        })
+    }
+
+    fn decide(&self, pressure: u8) -> Decision {
+        if self.reject_percent.is_some_and(|v| pressure >= v) {
+            return Decision::Reject {
+                retry_after: self.retry_after,
+            };
+        }
+
+        if self.degrade_percent.is_some_and(|v| pressure >= v) {
+            return Decision::Degrade;
+        }
+
+        Decision::Accept
+    }
+}
+
+/// The action to take for a query request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Decision {
+    /// Execute the query as normal.
+    Accept,
+
+    /// Execute the query, but return only the partition metadata and none of
+    /// the buffered data.
+    Degrade,
+
+    /// Do not execute the query, advising the caller to retry after the
+    /// specified duration.
+    Reject { retry_after: Duration },
+}
+
+/// Evaluates the [`LoadShedPolicy`] against the current buffer pressure for
+/// each query request.
+#[derive(Debug)]
+pub(super) struct LoadShedder {
+    policy: LoadShedPolicy,
+    occupancy: PersistQueueOccupancy,
+
+    degraded: U64Counter,
+    rejected: U64Counter,
+}
+
+impl LoadShedder {
+    pub(super) fn new(
+        policy: LoadShedPolicy,
+        occupancy: PersistQueueOccupancy,
+        metrics: &metric::Registry,
+    ) -> Self {
+        let metric = metrics.register_metric::<U64Counter>(
+            "ingester_query_request_shed",
+            "number of query requests degraded or rejected due to buffer pressure",
+        );
+
+        Self {
+            policy,
+            occupancy,
+            degraded: metric.recorder(&[("action", "degraded")]),
+            rejected: metric.recorder(&[("action", "rejected")]),
+        }
+    }
+
+    /// Return the action to take for a query request received now.
+    pub(super) fn evaluate(&self) -> Decision {
+        let decision = self.policy.decide(self.occupancy.percent());
+        match decision {
+            Decision::Accept => {}
+            Decision::Degrade => self.degraded.inc(1),
+            Decision::Reject { .. } => self.rejected.inc(1),
+        }
+        decision
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RETRY_AFTER: Duration = Duration::from_secs(5);
+
+    #[test]
+    fn test_policy_disabled() {
+        assert_eq!(LoadShedPolicy::new(None, None, RETRY_AFTER), None);
+    }
+
+    #[test]
+    fn test_decide() {
+        let policy = LoadShedPolicy::new(Some(50), Some(90), RETRY_AFTER).unwrap();
+
+        assert_eq!(policy.decide(0), Decision::Accept);
+        assert_eq!(policy.decide(49), Decision::Accept);
+        assert_eq!(policy.decide(50), Decision::Degrade);
+        assert_eq!(policy.decide(89), Decision::Degrade);
+        assert_eq!(
+            policy.decide(90),
+            Decision::Reject {
+                retry_after: RETRY_AFTER
+            }
+        );
+        assert_eq!(
+            policy.decide(100),
+            Decision::Reject {
+                retry_after: RETRY_AFTER
+            }
+        );
+    }
+
+    #[test]
+    fn test_decide_single_threshold() {
+        let policy = LoadShedPolicy::new(Some(50), None, RETRY_AFTER).unwrap();
+        assert_eq!(policy.decide(100), Decision::Degrade);
+
+        let policy = LoadShedPolicy::new(None, Some(50), RETRY_AFTER).unwrap();
+        assert_eq!(policy.decide(49), Decision::Accept);
+        assert_eq!(
+            policy.decide(50),
+            Decision::Reject {
+                retry_after: RETRY_AFTER
+            }
+        );
+    }
+}
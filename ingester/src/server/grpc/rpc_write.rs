@@ -4,7 +4,12 @@ use data_types::{NamespaceId, PartitionKey, TableId};
 use generated_types::influxdata::iox::ingester::v1::{
     self as proto, write_service_server::WriteService,
 };
-use mutable_batch::writer;
+use iox_time::Time;
+use metric::U64Counter;
+use mutable_batch::{
+    value_policy::{FieldValueError, FieldValuePolicy},
+    writer,
+};
 use mutable_batch_pb::decode::decode_database_batch;
 use observability_deps::tracing::*;
 use thiserror::Error;
@@ -20,6 +25,7 @@ use crate::{
     dml_payload::IngestOp,
     dml_sink::{DmlError, DmlSink},
     ingest_state::{IngestState, IngestStateError},
+    partition_seal::PartitionSealer,
     timestamp_oracle::TimestampOracle,
 };
 
@@ -43,6 +49,25 @@ enum RpcError {
     #[error(transparent)]
     Decode(mutable_batch_pb::decode::Error),
 
+    /// A field value in the write payload was rejected by the configured
+    /// [`FieldValuePolicy`].
+    #[error("invalid field value in table {table_id}: {source}")]
+    FieldValue {
+        table_id: TableId,
+        source: FieldValueError,
+    },
+
+    /// The partition of the write was sealed against further writes by the
+    /// configured [`PartitionSealer`].
+    #[error(
+        "partition {partition_key} of table {table_id} was sealed against further writes at {sealed_at}"
+    )]
+    PartitionSealed {
+        table_id: TableId,
+        partition_key: PartitionKey,
+        sealed_at: Time,
+    },
+
     /// The ingester's [`IngestState`] returns [`IngestStateError`] instances if
     /// set by a subsystem. See [`IngestState`] for documentation.
     #[error(transparent)]
@@ -52,9 +77,14 @@ enum RpcError {
 impl From<RpcError> for tonic::Status {
     fn from(e: RpcError) -> Self {
         let code = match e {
-            RpcError::Decode(_) | RpcError::NoPayload | RpcError::NoTables => Code::InvalidArgument,
+            RpcError::Decode(_)
+            | RpcError::NoPayload
+            | RpcError::NoTables
+            | RpcError::FieldValue { .. }
+            | RpcError::PartitionSealed { .. } => Code::InvalidArgument,
             RpcError::SystemState(IngestStateError::PersistSaturated) => Code::ResourceExhausted,
             RpcError::SystemState(IngestStateError::DiskFull) => Code::ResourceExhausted,
+            RpcError::SystemState(IngestStateError::PersistFailing) => Code::Unavailable,
             RpcError::SystemState(IngestStateError::GracefulStop) => Code::FailedPrecondition,
         };
 
@@ -71,8 +101,10 @@ impl From<DmlError> for tonic::Status {
                 Self::resource_exhausted(e.to_string())
             }
             DmlError::Buffer(BufferWriteError::Write(e)) => map_write_error(e),
-            DmlError::Wal(_) => Self::internal(e.to_string()),
+            DmlError::Buffer(BufferWriteError::DuplicateSequenceNumber { .. })
+            | DmlError::Wal(_) => Self::internal(e.to_string()),
             DmlError::ApplyTimeout => Self::internal(e.to_string()),
+            DmlError::NamespaceQuotaExceeded { .. } => Self::resource_exhausted(e.to_string()),
         }
     }
 }
@@ -113,6 +145,19 @@ pub(crate) struct RpcWrite<T> {
     sink: T,
     timestamp: Arc<TimestampOracle>,
     ingest_state: Arc<IngestState>,
+    field_value_policy: Option<FieldValuePolicyEnforcer>,
+    partition_sealer: Option<Arc<PartitionSealer>>,
+}
+
+/// Enforces a [`FieldValuePolicy`] on the field values of writes, recording
+/// the actions taken.
+#[derive(Debug)]
+struct FieldValuePolicyEnforcer {
+    policy: FieldValuePolicy,
+
+    clamped: U64Counter,
+    nulled: U64Counter,
+    rejected: U64Counter,
 }
 
 impl<T> RpcWrite<T> {
@@ -127,8 +172,40 @@ impl<T> RpcWrite<T> {
             sink,
             timestamp,
             ingest_state,
+            field_value_policy: None,
+            partition_sealer: None,
         }
     }
+
+    /// Enforce `policy` on the non-finite float values of all writes before
+    /// they are buffered.
+    ///
+    /// Unsigned integer values are not converted, as the router converts them
+    /// against the namespace schema before the write reaches the ingester.
+    pub(crate) fn with_field_value_policy(
+        mut self,
+        policy: FieldValuePolicy,
+        metrics: &metric::Registry,
+    ) -> Self {
+        let actions = metrics.register_metric::<U64Counter>(
+            "ingester_write_field_value_policy",
+            "number of field values clamped or nulled, and writes rejected, by the field value policy",
+        );
+
+        self.field_value_policy = Some(FieldValuePolicyEnforcer {
+            policy,
+            clamped: actions.recorder(&[("action", "clamped")]),
+            nulled: actions.recorder(&[("action", "nulled")]),
+            rejected: actions.recorder(&[("action", "rejected")]),
+        });
+        self
+    }
+
+    /// Reject writes to the partitions sealed by `sealer`.
+    pub(crate) fn with_partition_sealer(mut self, sealer: Arc<PartitionSealer>) -> Self {
+        self.partition_sealer = Some(sealer);
+        self
+    }
 }
 
 #[tonic::async_trait]
@@ -173,7 +250,7 @@ where
             .unwrap_or_else(|| "<unknown>".to_string());
         let payload = request.into_inner().payload.ok_or(RpcError::NoPayload)?;
 
-        let batches = decode_database_batch(&payload).map_err(RpcError::Decode)?;
+        let mut batches = decode_database_batch(&payload).map_err(RpcError::Decode)?;
         let num_tables = batches.len();
         let namespace_id = NamespaceId::new(payload.database_id);
         let partition_key = PartitionKey::from(payload.partition_key);
@@ -192,6 +269,37 @@ where
             "received rpc write"
         );
 
+        if let Some(sealer) = &self.partition_sealer {
+            for table_id in batches.keys() {
+                let table_id = TableId::new(*table_id);
+                if let Some(sealed_at) = sealer.check(table_id, &partition_key).await {
+                    warn!(%namespace_id, %table_id, %partition_key, "write to sealed partition rejected");
+                    return Err(RpcError::PartitionSealed {
+                        table_id,
+                        partition_key,
+                        sealed_at,
+                    })?;
+                }
+            }
+        }
+
+        if let Some(enforcer) = &self.field_value_policy {
+            for (table_id, batch) in &mut batches {
+                let table_id = TableId::new(*table_id);
+                match batch.enforce_field_value_policy(enforcer.policy, |_| false) {
+                    Ok(actions) => {
+                        enforcer.clamped.inc(actions.clamped as u64);
+                        enforcer.nulled.inc(actions.nulled as u64);
+                    }
+                    Err(source) => {
+                        warn!(%namespace_id, %table_id, error=%source, "field value rejected");
+                        enforcer.rejected.inc(1);
+                        return Err(RpcError::FieldValue { table_id, source })?;
+                    }
+                }
+            }
+        }
+
         // Construct the corresponding ingester write operation for the RPC payload,
         // independently sequencing the data contained by the write per-partition
         let op = WriteOperation::new(
@@ -747,4 +855,134 @@ mod tests {
             assert_eq!(handler_span.ctx.parent_span_id, Some(external_span.ctx.span_id));
         })
     }
+
+    #[tokio::test]
+    async fn test_field_value_policy() {
+        let mut batch = mutable_batch::MutableBatch::new();
+        let mut writer = mutable_batch::writer::Writer::new(&mut batch, 2);
+        writer
+            .write_f64("f", None, [1.0, f64::INFINITY].into_iter())
+            .unwrap();
+        writer.write_time("time", [1, 2].into_iter()).unwrap();
+        writer.commit();
+
+        let request = || {
+            Request::new(proto::WriteRequest {
+                payload: Some(DatabaseBatch {
+                    database_id: ARBITRARY_NAMESPACE_ID.get(),
+                    partition_key: ARBITRARY_PARTITION_KEY.to_string(),
+                    table_batches: vec![mutable_batch_pb::encode::encode_batch(
+                        ARBITRARY_TABLE_ID.get(),
+                        &batch,
+                    )],
+                }),
+            })
+        };
+
+        let metrics = metric::Registry::default();
+
+        // Non-finite values are replaced with NULL.
+        let mock = Arc::new(MockDmlSink::default().with_apply_return(vec![Ok(())]));
+        let handler = RpcWrite::new(
+            Arc::clone(&mock),
+            Arc::new(TimestampOracle::new(0)),
+            Arc::new(IngestState::default()),
+        )
+        .with_field_value_policy(FieldValuePolicy::Null, &metrics);
+
+        handler
+            .write(request())
+            .await
+            .expect("write should succeed");
+        assert_matches!(mock.get_calls().as_slice(), [IngestOp::Write(w)] => {
+            let (_, table) = w.tables().next().unwrap();
+            let f = table.partitioned_data().data().column("f").unwrap();
+            assert!(f.valid_mask().get(0));
+            assert!(!f.valid_mask().get(1));
+        });
+        let enforcer = handler.field_value_policy.as_ref().unwrap();
+        assert_eq!(enforcer.nulled.fetch(), 1);
+
+        // Or the write is rejected.
+        let mock = Arc::new(MockDmlSink::default());
+        let handler = RpcWrite::new(
+            Arc::clone(&mock),
+            Arc::new(TimestampOracle::new(0)),
+            Arc::new(IngestState::default()),
+        )
+        .with_field_value_policy(FieldValuePolicy::Reject, &metrics);
+
+        let err = handler
+            .write(request())
+            .await
+            .expect_err("write should be rejected");
+        assert_eq!(err.code(), Code::InvalidArgument);
+        assert!(mock.get_calls().is_empty());
+        let enforcer = handler.field_value_policy.as_ref().unwrap();
+        assert_eq!(enforcer.rejected.fetch(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_partition_sealed() {
+        let metrics = Arc::new(metric::Registry::default());
+        let catalog: Arc<dyn iox_catalog::interface::Catalog> =
+            Arc::new(iox_catalog::mem::MemCatalog::new(Arc::clone(&metrics)));
+        let table_id = {
+            let mut repos = catalog.repositories().await;
+            let namespace =
+                iox_catalog::test_helpers::arbitrary_namespace(&mut *repos, "bananas").await;
+            iox_catalog::test_helpers::arbitrary_table(&mut *repos, "platanos", &namespace)
+                .await
+                .id
+        };
+
+        let mut batch = mutable_batch::MutableBatch::new();
+        let mut writer = mutable_batch::writer::Writer::new(&mut batch, 1);
+        writer.write_time("time", [1].into_iter()).unwrap();
+        writer.commit();
+
+        let request = |partition_key: &str| {
+            Request::new(proto::WriteRequest {
+                payload: Some(DatabaseBatch {
+                    database_id: ARBITRARY_NAMESPACE_ID.get(),
+                    partition_key: partition_key.to_string(),
+                    table_batches: vec![mutable_batch_pb::encode::encode_batch(
+                        table_id.get(),
+                        &batch,
+                    )],
+                }),
+            })
+        };
+
+        // Partitions are sealed a day after their time range ends.
+        let sealer = Arc::new(PartitionSealer::new(
+            std::time::Duration::from_secs(24 * 60 * 60),
+            catalog,
+            Arc::new(iox_time::MockProvider::new(
+                Time::from_rfc3339("2023-10-03T12:00:00Z").unwrap(),
+            )),
+            &metrics,
+        ));
+        let mock = Arc::new(MockDmlSink::default().with_apply_return(vec![Ok(())]));
+        let handler = RpcWrite::new(
+            Arc::clone(&mock),
+            Arc::new(TimestampOracle::new(0)),
+            Arc::new(IngestState::default()),
+        )
+        .with_partition_sealer(sealer);
+
+        handler
+            .write(request("2023-10-02"))
+            .await
+            .expect("write should succeed");
+        assert_matches!(mock.get_calls().as_slice(), [IngestOp::Write(_)]);
+
+        let err = handler
+            .write(request("2023-10-01"))
+            .await
+            .expect_err("write should be rejected");
+        assert_eq!(err.code(), Code::InvalidArgument);
+        assert!(err.message().contains("sealed"), "{err}");
+        assert_eq!(mock.get_calls().len(), 1);
+    }
 }
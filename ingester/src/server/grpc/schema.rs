@@ -0,0 +1,157 @@
+use std::collections::BTreeMap;
+
+use data_types::{ColumnType, NamespaceId, TableId};
+use generated_types::influxdata::iox::ingester::v1::{
+    self as proto, buffer_schema_service_server::BufferSchemaService,
+};
+use tonic::{Request, Response};
+
+use crate::{buffer_tree::partition::BufferedColumn, partition_iter::PartitionIter};
+
+/// A gRPC handler reporting the columns of the data buffered for a table.
+#[derive(Debug)]
+pub(crate) struct BufferSchemaHandler<T> {
+    buffer: T,
+}
+
+impl<T> BufferSchemaHandler<T>
+where
+    T: PartitionIter + Sync + 'static,
+{
+    pub(crate) fn new(buffer: T) -> Self {
+        Self { buffer }
+    }
+
+    /// Merge the columns buffered in all partitions of the specified table,
+    /// retaining the lowest first-seen sequence number of each column.
+    fn buffered_columns(
+        &self,
+        namespace_id: NamespaceId,
+        table_id: TableId,
+    ) -> BTreeMap<String, BufferedColumn> {
+        let mut columns = BTreeMap::<String, BufferedColumn>::new();
+
+        for p in self.buffer.partition_iter() {
+            let p = p.lock();
+            if p.namespace_id() != namespace_id || p.table_id() != table_id {
+                continue;
+            }
+
+            for (name, col) in p.buffered_columns() {
+                columns
+                    .entry(name.clone())
+                    .and_modify(|v| v.first_seen = v.first_seen.min(col.first_seen))
+                    .or_insert(*col);
+            }
+        }
+
+        columns
+    }
+}
+
+#[tonic::async_trait]
+impl<T> BufferSchemaService for BufferSchemaHandler<T>
+where
+    T: PartitionIter + Sync + 'static,
+{
+    async fn get_buffer_schema(
+        &self,
+        request: Request<proto::GetBufferSchemaRequest>,
+    ) -> Result<Response<proto::GetBufferSchemaResponse>, tonic::Status> {
+        let request = request.into_inner();
+
+        let columns = self
+            .buffered_columns(
+                NamespaceId::new(request.namespace_id),
+                TableId::new(request.table_id),
+            )
+            .into_iter()
+            .map(|(name, col)| proto::BufferedColumn {
+                name,
+                column_type: ColumnType::from(col.column_type) as i32,
+                first_seen_sequence_number: col.first_seen.get() as i64,
+            })
+            .collect();
+
+        Ok(Response::new(proto::GetBufferSchemaResponse { columns }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use data_types::SequenceNumber;
+    use generated_types::influxdata::iox::schema::v1::column_schema::ColumnType as ProtoColumnType;
+    use mutable_batch_lp::test_helpers::lp_to_mutable_batch;
+    use parking_lot::Mutex;
+
+    use super::*;
+    use crate::test_util::{PartitionDataBuilder, ARBITRARY_NAMESPACE_ID, ARBITRARY_TABLE_ID};
+
+    #[tokio::test]
+    async fn test_get_buffer_schema() {
+        let mut a = PartitionDataBuilder::new().build();
+        a.buffer_write(
+            lp_to_mutable_batch(r#"bananas,city=London people=2 10"#).1,
+            SequenceNumber::new(3),
+        )
+        .expect("write should succeed");
+
+        let mut b = PartitionDataBuilder::new()
+            .with_partition_key("platanos".into())
+            .build();
+        b.buffer_write(
+            lp_to_mutable_batch(r#"bananas,city=Madrid people=4,pigeons="none" 20"#).1,
+            SequenceNumber::new(2),
+        )
+        .expect("write should succeed");
+
+        // A partition of another table is not included.
+        let mut other = PartitionDataBuilder::new()
+            .with_table_id(TableId::new(ARBITRARY_TABLE_ID.get() + 1))
+            .build();
+        other
+            .buffer_write(
+                lp_to_mutable_batch(r#"bananas,country=UK people=4 20"#).1,
+                SequenceNumber::new(1),
+            )
+            .expect("write should succeed");
+
+        let handler = BufferSchemaHandler::new(vec![
+            Arc::new(Mutex::new(a)),
+            Arc::new(Mutex::new(b)),
+            Arc::new(Mutex::new(other)),
+        ]);
+
+        let got = handler
+            .get_buffer_schema(Request::new(proto::GetBufferSchemaRequest {
+                namespace_id: ARBITRARY_NAMESPACE_ID.get(),
+                table_id: ARBITRARY_TABLE_ID.get(),
+            }))
+            .await
+            .expect("request should succeed")
+            .into_inner();
+
+        let got = got
+            .columns
+            .iter()
+            .map(|c| {
+                (
+                    c.name.as_str(),
+                    ProtoColumnType::from_i32(c.column_type).unwrap(),
+                    c.first_seen_sequence_number,
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            got,
+            [
+                ("city", ProtoColumnType::Tag, 2),
+                ("people", ProtoColumnType::F64, 2),
+                ("pigeons", ProtoColumnType::String, 2),
+                ("time", ProtoColumnType::Time, 2),
+            ]
+        );
+    }
+}
@@ -0,0 +1,170 @@
+use std::{sync::Arc, time::Duration};
+
+use iox_catalog::interface::{Catalog, Error};
+use metric::U64Counter;
+use observability_deps::tracing::*;
+use tokio::task::JoinHandle;
+
+use super::UsageTracker;
+
+/// Adds the usage accumulated in a [`UsageTracker`] to the catalog
+/// [`TableUsage`] records.
+///
+/// Usage that cannot be written to the catalog is retained and retried in the
+/// next flush, unless the table no longer exists.
+///
+/// [`TableUsage`]: data_types::TableUsage
+#[derive(Debug)]
+pub(crate) struct UsageFlush {
+    tracker: Arc<UsageTracker>,
+    catalog: Arc<dyn Catalog>,
+
+    ok: U64Counter,
+    err: U64Counter,
+}
+
+impl UsageFlush {
+    pub(crate) fn new(
+        tracker: Arc<UsageTracker>,
+        catalog: Arc<dyn Catalog>,
+        metrics: &metric::Registry,
+    ) -> Self {
+        let flushes = metrics.register_metric::<U64Counter>(
+            "ingester_usage_flush_records",
+            "number of table usage records written to the catalog, by result",
+        );
+
+        Self {
+            tracker,
+            catalog,
+            ok: flushes.recorder(&[("result", "success")]),
+            err: flushes.recorder(&[("result", "error")]),
+        }
+    }
+
+    /// Write all the usage currently in the tracker to the catalog.
+    ///
+    /// Concurrent flushes each write a disjoint set of usage.
+    pub(crate) async fn flush(&self) {
+        flush(&self.tracker, &*self.catalog, &self.ok, &self.err).await
+    }
+}
+
+/// Spawn a task calling [`UsageFlush::flush()`] every `period`.
+///
+/// # Panics
+///
+/// Panics if `period` is zero.
+pub(crate) fn spawn_usage_flush(usage: Arc<UsageFlush>, period: Duration) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(period);
+        loop {
+            interval.tick().await;
+            usage.flush().await;
+        }
+    })
+}
+
+/// Write all the usage currently in `tracker` to `catalog`.
+async fn flush(tracker: &UsageTracker, catalog: &dyn Catalog, ok: &U64Counter, err: &U64Counter) {
+    let usage = tracker.take();
+    if usage.is_empty() {
+        return;
+    }
+
+    let mut repos = catalog.repositories().await;
+    let mut retry = Vec::new();
+
+    // Each record is written independently, so that a failure does not cause
+    // already-recorded usage to be retried (and counted twice).
+    for u in usage {
+        match repos.table_usage().record(&[u]).await {
+            Ok(()) => ok.inc(1),
+            Err(error @ (Error::ForeignKeyViolation { .. } | Error::TableNotFound { .. })) => {
+                err.inc(1);
+                warn!(
+                    %error,
+                    namespace_id=%u.namespace_id,
+                    table_id=%u.table_id,
+                    "dropping table usage for deleted table"
+                );
+            }
+            Err(error) => {
+                err.inc(1);
+                warn!(
+                    %error,
+                    namespace_id=%u.namespace_id,
+                    table_id=%u.table_id,
+                    "failed to record table usage"
+                );
+                retry.push(u);
+            }
+        }
+    }
+
+    debug!(n_retry = retry.len(), "flushed table usage");
+    tracker.restore(retry);
+}
+
+#[cfg(test)]
+mod tests {
+    use data_types::{TableId, TableUsage, Timestamp};
+    use iox_catalog::{
+        mem::MemCatalog,
+        test_helpers::{arbitrary_namespace, arbitrary_table},
+    };
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_flush() {
+        let metrics = Arc::new(metric::Registry::default());
+        let catalog: Arc<dyn Catalog> = Arc::new(MemCatalog::new(Arc::clone(&metrics)));
+
+        let (namespace, table) = {
+            let mut repos = catalog.repositories().await;
+            let namespace = arbitrary_namespace(&mut *repos, "bananas").await;
+            let table = arbitrary_table(&mut *repos, "platanos", &namespace).await;
+            (namespace, table)
+        };
+
+        let tracker = UsageTracker::new(catalog.time_provider(), &metrics);
+        let ok = U64Counter::default();
+        let err = U64Counter::default();
+
+        tracker.record_buffered(namespace.id, table.id, 10, 100);
+        tracker.record_persisted(namespace.id, table.id, 10, 42);
+        // Usage for a table that does not exist is dropped.
+        tracker.record_buffered(namespace.id, TableId::new(i64::MAX), 1, 1);
+
+        flush(&tracker, &*catalog, &ok, &err).await;
+        assert_eq!(ok.fetch(), 1);
+        assert_eq!(err.fetch(), 1);
+        assert!(tracker.take().is_empty());
+
+        // Flushing again adds to the existing record.
+        tracker.record_buffered(namespace.id, table.id, 5, 50);
+        flush(&tracker, &*catalog, &ok, &err).await;
+        assert_eq!(ok.fetch(), 2);
+
+        let got = catalog
+            .repositories()
+            .await
+            .table_usage()
+            .list_by_namespace(namespace.id, Timestamp::new(0))
+            .await
+            .unwrap();
+        assert_eq!(
+            got,
+            [TableUsage {
+                namespace_id: namespace.id,
+                table_id: table.id,
+                hour_start: TableUsage::window_start(catalog.time_provider().now().into()),
+                rows_buffered: 15,
+                bytes_buffered: 150,
+                rows_persisted: 10,
+                bytes_persisted: 42,
+            }]
+        );
+    }
+}
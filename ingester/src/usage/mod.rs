@@ -0,0 +1,245 @@
+//! Per-table accounting of the rows and bytes buffered and persisted by this
+//! ingester, periodically added to the hourly [`TableUsage`] records in the
+//! catalog to enable chargeback.
+//!
+//! The [`UsageSink`] and [`UsageObserver`] decorators record into a shared
+//! [`UsageTracker`], which is drained by the task started with
+//! [`spawn_usage_flush()`], and once more during a graceful shutdown.
+//!
+//! Usage is accumulated in memory between flushes - at most one flush period
+//! of usage is lost if the ingester stops uncleanly.
+
+mod flush;
+mod observer;
+mod sink;
+
+pub(crate) use flush::*;
+pub(crate) use observer::*;
+pub(crate) use sink::*;
+
+use std::{collections::HashMap, sync::Arc};
+
+use data_types::{NamespaceId, TableId, TableUsage, Timestamp};
+use iox_time::TimeProvider;
+use metric::U64Counter;
+use parking_lot::Mutex;
+
+/// The usage counts of a single table within a single window.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+struct Counts {
+    rows_buffered: i64,
+    bytes_buffered: i64,
+    rows_persisted: i64,
+    bytes_persisted: i64,
+}
+
+impl Counts {
+    fn add(&mut self, other: &Self) {
+        self.rows_buffered += other.rows_buffered;
+        self.bytes_buffered += other.bytes_buffered;
+        self.rows_persisted += other.rows_persisted;
+        self.bytes_persisted += other.bytes_persisted;
+    }
+}
+
+/// Accumulates the per-table usage counts of this ingester, bucketed into
+/// [`TableUsage::WINDOW`] length windows.
+#[derive(Debug)]
+pub(crate) struct UsageTracker {
+    time_provider: Arc<dyn TimeProvider>,
+
+    counts: Mutex<HashMap<(NamespaceId, TableId, Timestamp), Counts>>,
+
+    rows_buffered: U64Counter,
+    bytes_buffered: U64Counter,
+    rows_persisted: U64Counter,
+    bytes_persisted: U64Counter,
+}
+
+impl UsageTracker {
+    pub(crate) fn new(time_provider: Arc<dyn TimeProvider>, metrics: &metric::Registry) -> Self {
+        let rows = metrics.register_metric::<U64Counter>(
+            "ingester_usage_rows",
+            "number of rows buffered and persisted, as recorded for table usage accounting",
+        );
+        let bytes = metrics.register_metric::<U64Counter>(
+            "ingester_usage_bytes",
+            "number of bytes buffered and persisted, as recorded for table usage accounting",
+        );
+
+        Self {
+            time_provider,
+            counts: Default::default(),
+            rows_buffered: rows.recorder(&[("state", "buffered")]),
+            bytes_buffered: bytes.recorder(&[("state", "buffered")]),
+            rows_persisted: rows.recorder(&[("state", "persisted")]),
+            bytes_persisted: bytes.recorder(&[("state", "persisted")]),
+        }
+    }
+
+    /// Record `rows` totalling `bytes` were buffered for `table_id`.
+    pub(crate) fn record_buffered(
+        &self,
+        namespace_id: NamespaceId,
+        table_id: TableId,
+        rows: usize,
+        bytes: usize,
+    ) {
+        self.rows_buffered.inc(rows as _);
+        self.bytes_buffered.inc(bytes as _);
+        self.add(
+            namespace_id,
+            table_id,
+            Counts {
+                rows_buffered: rows as _,
+                bytes_buffered: bytes as _,
+                ..Default::default()
+            },
+        );
+    }
+
+    /// Record `rows` were persisted for `table_id` into a parquet file of
+    /// `bytes` in size.
+    pub(crate) fn record_persisted(
+        &self,
+        namespace_id: NamespaceId,
+        table_id: TableId,
+        rows: usize,
+        bytes: usize,
+    ) {
+        self.rows_persisted.inc(rows as _);
+        self.bytes_persisted.inc(bytes as _);
+        self.add(
+            namespace_id,
+            table_id,
+            Counts {
+                rows_persisted: rows as _,
+                bytes_persisted: bytes as _,
+                ..Default::default()
+            },
+        );
+    }
+
+    fn add(&self, namespace_id: NamespaceId, table_id: TableId, counts: Counts) {
+        let window = TableUsage::window_start(self.time_provider.now().into());
+        self.counts
+            .lock()
+            .entry((namespace_id, table_id, window))
+            .or_default()
+            .add(&counts);
+    }
+
+    /// Remove and return all the usage recorded since the last call.
+    pub(crate) fn take(&self) -> Vec<TableUsage> {
+        std::mem::take(&mut *self.counts.lock())
+            .into_iter()
+            .map(|((namespace_id, table_id, hour_start), c)| TableUsage {
+                namespace_id,
+                table_id,
+                hour_start,
+                rows_buffered: c.rows_buffered,
+                bytes_buffered: c.bytes_buffered,
+                rows_persisted: c.rows_persisted,
+                bytes_persisted: c.bytes_persisted,
+            })
+            .collect()
+    }
+
+    /// Merge `usage` previously returned by [`UsageTracker::take()`] back into
+    /// the tracked counts, retaining the original windows.
+    pub(crate) fn restore(&self, usage: impl IntoIterator<Item = TableUsage>) {
+        let mut counts = self.counts.lock();
+        for u in usage {
+            counts
+                .entry((u.namespace_id, u.table_id, u.hour_start))
+                .or_default()
+                .add(&Counts {
+                    rows_buffered: u.rows_buffered,
+                    bytes_buffered: u.bytes_buffered,
+                    rows_persisted: u.rows_persisted,
+                    bytes_persisted: u.bytes_persisted,
+                });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use iox_time::{MockProvider, Time};
+
+    use super::*;
+    use crate::test_util::{ARBITRARY_NAMESPACE_ID, ARBITRARY_TABLE_ID};
+
+    #[test]
+    fn test_tracker() {
+        let time_provider = Arc::new(MockProvider::new(Time::from_timestamp_nanos(0)));
+        let tracker = UsageTracker::new(
+            Arc::clone(&time_provider) as _,
+            &metric::Registry::default(),
+        );
+        let other_table = TableId::new(ARBITRARY_TABLE_ID.get() + 1);
+
+        tracker.record_buffered(ARBITRARY_NAMESPACE_ID, ARBITRARY_TABLE_ID, 10, 100);
+        tracker.record_buffered(ARBITRARY_NAMESPACE_ID, ARBITRARY_TABLE_ID, 5, 50);
+        tracker.record_persisted(ARBITRARY_NAMESPACE_ID, ARBITRARY_TABLE_ID, 15, 42);
+        tracker.record_buffered(ARBITRARY_NAMESPACE_ID, other_table, 1, 1);
+
+        // Usage in the next window is recorded separately.
+        time_provider.inc(TableUsage::WINDOW + Duration::from_secs(1));
+        tracker.record_buffered(ARBITRARY_NAMESPACE_ID, ARBITRARY_TABLE_ID, 2, 20);
+
+        let mut got = tracker.take();
+        got.sort_unstable_by_key(|v| (v.hour_start, v.table_id));
+
+        let window = TableUsage::WINDOW.as_nanos() as i64;
+        let want = [
+            TableUsage {
+                namespace_id: ARBITRARY_NAMESPACE_ID,
+                table_id: ARBITRARY_TABLE_ID,
+                hour_start: Timestamp::new(0),
+                rows_buffered: 15,
+                bytes_buffered: 150,
+                rows_persisted: 15,
+                bytes_persisted: 42,
+            },
+            TableUsage {
+                namespace_id: ARBITRARY_NAMESPACE_ID,
+                table_id: other_table,
+                hour_start: Timestamp::new(0),
+                rows_buffered: 1,
+                bytes_buffered: 1,
+                rows_persisted: 0,
+                bytes_persisted: 0,
+            },
+            TableUsage {
+                namespace_id: ARBITRARY_NAMESPACE_ID,
+                table_id: ARBITRARY_TABLE_ID,
+                hour_start: Timestamp::new(window),
+                rows_buffered: 2,
+                bytes_buffered: 20,
+                rows_persisted: 0,
+                bytes_persisted: 0,
+            },
+        ];
+        assert_eq!(got, want);
+
+        // The counts were drained.
+        assert!(tracker.take().is_empty());
+
+        // Restored usage is merged with newly recorded usage.
+        tracker.restore(want);
+        tracker.record_buffered(ARBITRARY_NAMESPACE_ID, ARBITRARY_TABLE_ID, 1, 1);
+        let got = tracker
+            .take()
+            .into_iter()
+            .find(|v| v.table_id == ARBITRARY_TABLE_ID && v.hour_start.get() == window)
+            .unwrap();
+        assert_eq!(got.rows_buffered, 3);
+        assert_eq!(got.bytes_buffered, 21);
+
+        assert_eq!(tracker.rows_buffered.fetch(), 19);
+        assert_eq!(tracker.rows_persisted.fetch(), 15);
+    }
+}
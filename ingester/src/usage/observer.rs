@@ -0,0 +1,98 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use super::UsageTracker;
+use crate::persist::completion_observer::{CompletedPersist, PersistCompletionObserver};
+
+/// A [`PersistCompletionObserver`] decorator recording the rows and file
+/// size of each persisted parquet file in a [`UsageTracker`].
+#[derive(Debug)]
+pub(crate) struct UsageObserver<T> {
+    inner: T,
+    tracker: Arc<UsageTracker>,
+}
+
+impl<T> UsageObserver<T> {
+    pub(crate) fn new(inner: T, tracker: Arc<UsageTracker>) -> Self {
+        Self { inner, tracker }
+    }
+}
+
+#[async_trait]
+impl<T> PersistCompletionObserver for UsageObserver<T>
+where
+    T: PersistCompletionObserver,
+{
+    async fn persist_complete(&self, note: Arc<CompletedPersist>) {
+        self.tracker.record_persisted(
+            note.namespace_id(),
+            note.table_id(),
+            note.row_count(),
+            note.parquet_file_bytes(),
+        );
+
+        self.inner.persist_complete(note).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use data_types::{
+        sequence_number_set::SequenceNumberSet, ColumnId, ColumnSet, CompactionLevel, ParquetFile,
+        ParquetFileId, Timestamp,
+    };
+    use iox_time::SystemProvider;
+
+    use super::*;
+    use crate::{
+        persist::completion_observer::mock::MockCompletionObserver,
+        test_util::{
+            ARBITRARY_NAMESPACE_ID, ARBITRARY_TABLE_ID, ARBITRARY_TRANSITION_PARTITION_ID,
+        },
+    };
+
+    #[tokio::test]
+    async fn test_usage_observer() {
+        let inner = Arc::new(MockCompletionObserver::default());
+        let tracker = Arc::new(UsageTracker::new(
+            Arc::new(SystemProvider::new()),
+            &metric::Registry::default(),
+        ));
+        let observer = UsageObserver::new(Arc::clone(&inner), Arc::clone(&tracker));
+
+        let meta = ParquetFile {
+            id: ParquetFileId::new(42),
+            to_delete: None,
+            namespace_id: ARBITRARY_NAMESPACE_ID,
+            table_id: ARBITRARY_TABLE_ID,
+            partition_id: ARBITRARY_TRANSITION_PARTITION_ID.clone(),
+            object_store_id: Default::default(),
+            min_time: Timestamp::new(1),
+            max_time: Timestamp::new(2),
+            file_size_bytes: 4242,
+            row_count: 24,
+            compaction_level: CompactionLevel::Initial,
+            created_at: Timestamp::new(1234),
+            column_set: ColumnSet::new([1, 2].into_iter().map(ColumnId::new)),
+            max_l0_created_at: Timestamp::new(42),
+        };
+
+        observer
+            .persist_complete(Arc::new(CompletedPersist::new(
+                meta,
+                SequenceNumberSet::default(),
+            )))
+            .await;
+
+        // The notification is passed through.
+        assert_eq!(inner.calls().len(), 1);
+
+        let got = tracker.take();
+        assert_eq!(got.len(), 1);
+        assert_eq!(got[0].table_id, ARBITRARY_TABLE_ID);
+        assert_eq!(got[0].rows_persisted, 24);
+        assert_eq!(got[0].bytes_persisted, 4242);
+        assert_eq!(got[0].rows_buffered, 0);
+    }
+}
@@ -0,0 +1,116 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use data_types::TableId;
+
+use super::UsageTracker;
+use crate::{dml_payload::IngestOp, dml_sink::DmlSink};
+
+/// A [`DmlSink`] decorator recording the rows and bytes of each successfully
+/// applied write in a [`UsageTracker`].
+#[derive(Debug)]
+pub(crate) struct UsageSink<T> {
+    inner: T,
+    tracker: Arc<UsageTracker>,
+}
+
+impl<T> UsageSink<T> {
+    pub(crate) fn new(inner: T, tracker: Arc<UsageTracker>) -> Self {
+        Self { inner, tracker }
+    }
+}
+
+#[async_trait]
+impl<T> DmlSink for UsageSink<T>
+where
+    T: DmlSink,
+{
+    type Error = T::Error;
+
+    async fn apply(&self, op: IngestOp) -> Result<(), Self::Error> {
+        let namespace_id = op.namespace();
+
+        // Capture the per-table sizes before ownership of the op is passed to
+        // the inner sink.
+        let usage = match &op {
+            IngestOp::Write(w) => w
+                .tables()
+                .map(|(table_id, t)| {
+                    let data = t.partitioned_data().data();
+                    (*table_id, data.rows(), data.size_data())
+                })
+                .collect::<Vec<(TableId, usize, usize)>>(),
+        };
+
+        self.inner.apply(op).await?;
+
+        for (table_id, rows, bytes) in usage {
+            self.tracker
+                .record_buffered(namespace_id, table_id, rows, bytes);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_matches::assert_matches;
+    use iox_time::SystemProvider;
+
+    use super::*;
+    use crate::{
+        dml_sink::{mock_sink::MockDmlSink, DmlError},
+        test_util::{
+            make_write_op, ARBITRARY_NAMESPACE_ID, ARBITRARY_PARTITION_KEY, ARBITRARY_TABLE_ID,
+            ARBITRARY_TABLE_NAME,
+        },
+    };
+
+    fn op() -> IngestOp {
+        IngestOp::Write(make_write_op(
+            &ARBITRARY_PARTITION_KEY,
+            ARBITRARY_NAMESPACE_ID,
+            &ARBITRARY_TABLE_NAME,
+            ARBITRARY_TABLE_ID,
+            42,
+            &format!(
+                "{},tag=1 v=2 42424242\n{},tag=2 v=3 42424243",
+                &*ARBITRARY_TABLE_NAME, &*ARBITRARY_TABLE_NAME
+            ),
+            None,
+        ))
+    }
+
+    #[tokio::test]
+    async fn test_usage_sink() {
+        let tracker = Arc::new(UsageTracker::new(
+            Arc::new(SystemProvider::new()),
+            &metric::Registry::default(),
+        ));
+        let mock = MockDmlSink::default()
+            .with_apply_return([Ok(()), Err(DmlError::Wal("broken".to_string()))]);
+        let sink = UsageSink::new(mock, Arc::clone(&tracker));
+
+        let want_bytes = match op() {
+            IngestOp::Write(w) => w
+                .tables()
+                .map(|(_, t)| t.partitioned_data().data().size_data())
+                .sum::<usize>(),
+        };
+
+        sink.apply(op()).await.expect("write should succeed");
+
+        // Failed writes are not recorded.
+        assert_matches!(sink.apply(op()).await, Err(DmlError::Wal(_)));
+
+        let got = tracker.take();
+        assert_matches!(got.as_slice(), [u] => {
+            assert_eq!(u.namespace_id, ARBITRARY_NAMESPACE_ID);
+            assert_eq!(u.table_id, ARBITRARY_TABLE_ID);
+            assert_eq!(u.rows_buffered, 2);
+            assert_eq!(u.bytes_buffered, want_bytes as i64);
+            assert_eq!(u.rows_persisted, 0);
+        });
+    }
+}
@@ -50,6 +50,8 @@ async fn write_query() {
             table_id: ctx.table_id(namespace_name, "bananas").await.get(),
             columns: vec![],
             predicate: None,
+            max_snapshots: 0,
+            resume_after: vec![],
         })
         .await
         .expect("query request failed");
@@ -124,6 +126,8 @@ async fn write_query_projection() {
             table_id: ctx.table_id(namespace_name, "bananas").await.get(),
             columns: vec![],
             predicate: None,
+            max_snapshots: 0,
+            resume_after: vec![],
         })
         .await
         .expect("query request failed");
@@ -151,6 +155,8 @@ async fn write_query_projection() {
                 "platanos".to_string(),
             ],
             predicate: None,
+            max_snapshots: 0,
+            resume_after: vec![],
         })
         .await
         .expect("query request failed");
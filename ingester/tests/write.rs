@@ -43,6 +43,8 @@ async fn write_persist() {
             table_id,
             columns: vec![],
             predicate: None,
+            max_snapshots: 0,
+            resume_after: vec![],
         })
         .await
         .expect("query request failed");
@@ -66,6 +68,8 @@ async fn write_persist() {
             table_id,
             columns: vec![],
             predicate: None,
+            max_snapshots: 0,
+            resume_after: vec![],
         })
         .await
         .expect("query request failed");
@@ -232,6 +236,8 @@ async fn wal_replay() {
                 table_id: ctx.table_id(namespace_name, "bananas").await.get(),
                 columns: vec![],
                 predicate: None,
+                max_snapshots: 0,
+                resume_after: vec![],
             })
             .await
             .expect("query request failed");
@@ -330,6 +336,8 @@ async fn graceful_shutdown() {
             table_id: ctx.table_id(namespace_name, "bananas").await.get(),
             columns: vec![],
             predicate: None,
+            max_snapshots: 0,
+            resume_after: vec![],
         })
         .await
         .expect("query request failed");
@@ -435,6 +443,8 @@ async fn wal_reference_dropping() {
             table_id: ctx.table_id(TEST_NAMESPACE_NAME, "bananas").await.get(),
             columns: vec![],
             predicate: None,
+            max_snapshots: 0,
+            resume_after: vec![],
         })
         .await
         .expect("query request failed");
@@ -521,6 +531,8 @@ async fn write_tracing() {
             table_id,
             columns: vec![],
             predicate: None,
+            max_snapshots: 0,
+            resume_after: vec![],
         })
         .await
         .expect("query request failed");
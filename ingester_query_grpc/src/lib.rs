@@ -22,13 +22,15 @@ use crate::influxdata::iox::ingester::v1 as proto;
 use crate::influxdata::iox::ingester::v2 as proto2;
 use base64::{prelude::BASE64_STANDARD, Engine};
 use data_types::{
-    NamespaceId, PartitionHashId, PartitionId, TableId, TimestampRange, TransitionPartitionId,
+    NamespaceId, PartitionHashId, PartitionId, SequenceNumber, TableId, TimestampRange,
+    TransitionPartitionId,
 };
 use datafusion::{common::DataFusionError, prelude::Expr};
 use datafusion_proto::bytes::Serializeable;
 use predicate::{Predicate, ValueExpr};
 use prost::Message;
 use snafu::{ResultExt, Snafu};
+use std::num::NonZeroU32;
 
 /// This module imports the generated protobuf code into a Rust module
 /// hierarchy that matches the namespace hierarchy of the protobuf
@@ -133,6 +135,14 @@ pub struct IngesterQueryRequest {
 
     /// Predicate for filtering
     pub predicate: Option<Predicate>,
+
+    /// The maximum number of snapshots returned per partition, or all of them
+    /// if [`None`].
+    pub max_snapshots: Option<NonZeroU32>,
+
+    /// The partitions of a truncated response to resume, and the sequence
+    /// number to resume each after. If empty, all partitions are returned.
+    pub resume_after: Vec<(TransitionPartitionId, SequenceNumber)>,
 }
 
 impl IngesterQueryRequest {
@@ -148,6 +158,8 @@ impl IngesterQueryRequest {
             table_id,
             columns,
             predicate,
+            max_snapshots: None,
+            resume_after: vec![],
         }
     }
 }
@@ -161,13 +173,46 @@ impl TryFrom<proto::IngesterQueryRequest> for IngesterQueryRequest {
             table_id,
             columns,
             predicate,
+            max_snapshots,
+            resume_after,
         } = proto;
 
         let namespace_id = NamespaceId::new(namespace_id);
         let table_id = TableId::new(table_id);
         let predicate = predicate.map(TryInto::try_into).transpose()?;
+        let resume_after = resume_after
+            .into_iter()
+            .map(|v| {
+                use proto::partition_sequence_number::PartitionIdentifier;
+
+                let id = match v.partition_identifier {
+                    Some(PartitionIdentifier::CatalogId(id)) => {
+                        TransitionPartitionId::Deprecated(PartitionId::new(id))
+                    }
+                    Some(PartitionIdentifier::HashId(id)) => TransitionPartitionId::Deterministic(
+                        PartitionHashId::try_from(id.as_slice()).map_err(|e| FieldViolation {
+                            field: "resume_after.hash_id".to_owned(),
+                            description: e.to_string(),
+                        })?,
+                    ),
+                    None => {
+                        return Err(FieldViolation::required(
+                            "resume_after.partition_identifier",
+                        ))
+                    }
+                };
+                Ok((id, SequenceNumber::new(v.sequence_number)))
+            })
+            .collect::<Result<_, _>>()?;
 
-        Ok(Self::new(namespace_id, table_id, columns, predicate))
+        Ok(Self {
+            namespace_id,
+            table_id,
+            columns,
+            predicate,
+            max_snapshots: NonZeroU32::new(max_snapshots),
+            resume_after,
+        })
     }
 }
 
@@ -180,13 +225,37 @@ impl TryFrom<IngesterQueryRequest> for proto::IngesterQueryRequest {
             table_id,
             columns,
             predicate,
+            max_snapshots,
+            resume_after,
         } = query;
 
+        let resume_after = resume_after
+            .into_iter()
+            .map(|(id, sequence_number)| {
+                use proto::partition_sequence_number::PartitionIdentifier;
+
+                let partition_identifier = match id {
+                    TransitionPartitionId::Deterministic(hash_id) => {
+                        PartitionIdentifier::HashId(hash_id.as_bytes().to_owned())
+                    }
+                    TransitionPartitionId::Deprecated(partition_id) => {
+                        PartitionIdentifier::CatalogId(partition_id.get())
+                    }
+                };
+                proto::PartitionSequenceNumber {
+                    partition_identifier: Some(partition_identifier),
+                    sequence_number: sequence_number.get(),
+                }
+            })
+            .collect();
+
         Ok(Self {
             namespace_id: namespace_id.get(),
             table_id: table_id.get(),
             columns,
             predicate: predicate.map(TryInto::try_into).transpose()?,
+            max_snapshots: max_snapshots.map(NonZeroU32::get).unwrap_or_default(),
+            resume_after,
         })
     }
 }
@@ -525,6 +594,31 @@ mod tests {
         assert_eq!(rust_query, rust_query_converted);
     }
 
+    #[test]
+    fn query_resume_round_trip() {
+        let rust_query = IngesterQueryRequest {
+            max_snapshots: NonZeroU32::new(2),
+            resume_after: vec![
+                (
+                    TransitionPartitionId::Deprecated(PartitionId::new(1)),
+                    SequenceNumber::new(3),
+                ),
+                (
+                    TransitionPartitionId::Deterministic(PartitionHashId::arbitrary_for_testing()),
+                    SequenceNumber::new(7),
+                ),
+            ],
+            ..IngesterQueryRequest::new(NamespaceId::new(42), TableId::new(1337), vec![], None)
+        };
+
+        let proto_query: proto::IngesterQueryRequest = rust_query.clone().try_into().unwrap();
+        assert_eq!(proto_query.max_snapshots, 2);
+
+        let rust_query_converted: IngesterQueryRequest = proto_query.try_into().unwrap();
+
+        assert_eq!(rust_query, rust_query_converted);
+    }
+
     #[test]
     fn query2_round_trip() {
         let rust_query = IngesterQueryRequest2::new(
@@ -167,9 +167,31 @@ impl TestContextBuilder {
             persist_workers,
             max_persist_queue_depth,
             persist_hot_partition_cost,
+            NonZeroUsize::new(persist_workers + 20).unwrap(),
             storage.clone(),
             GossipConfig::default(),
             NonZeroUsize::new(usize::MAX).unwrap(),
+            None,
+            None,
+            Some(Duration::from_secs(1)),
+            Duration::from_secs(1),
+            0,
+            None,
+            NonZeroUsize::new(10).unwrap(),
+            false,
+            None,
+            None,
+            None,
+            Duration::from_secs(5),
+            None,
+            None,
+            vec![],
+            vec![],
+            Duration::from_secs(3600),
+            None,
+            false,
+            false,
+            None,
             shutdown_rx.map(|v| v.expect("shutdown sender dropped without calling shutdown")),
         )
         .await
@@ -391,7 +413,7 @@ where
         let flight_data_stream = self
             .ingester
             .rpc()
-            .query_service(5)
+            .query_service(5, None)
             .do_get(tonic::Request::new(t))
             .await?
             .into_inner();
@@ -0,0 +1,8 @@
+-- Add a "read only" flag to the "namespace" table.
+--
+-- When set, writes to the namespace are rejected by the routers while queries
+-- continue to be served.
+ALTER TABLE
+    IF EXISTS namespace
+ADD
+    COLUMN IF NOT EXISTS read_only BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,19 @@
+-- Add a "table_usage" table recording the number of rows and bytes buffered
+-- and persisted by the ingesters for each table, aggregated per hour.
+--
+-- Each ingester periodically adds its counts to the record for the hour they
+-- were observed in, allowing per-namespace and per-table chargeback.
+CREATE TABLE IF NOT EXISTS table_usage (
+    namespace_id BIGINT NOT NULL,
+    table_id BIGINT NOT NULL,
+    hour_start BIGINT NOT NULL,
+    rows_buffered BIGINT NOT NULL DEFAULT 0,
+    bytes_buffered BIGINT NOT NULL DEFAULT 0,
+    rows_persisted BIGINT NOT NULL DEFAULT 0,
+    bytes_persisted BIGINT NOT NULL DEFAULT 0,
+    PRIMARY KEY (table_id, hour_start),
+    FOREIGN KEY (namespace_id) REFERENCES namespace (id) ON DELETE CASCADE,
+    FOREIGN KEY (table_id) REFERENCES table_name (id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS table_usage_namespace_hour_idx ON table_usage (namespace_id, hour_start);
@@ -0,0 +1,14 @@
+-- Add a "table_view_column" table holding the columns of the view of a table.
+--
+-- Each view column exposes a SQL expression over the columns of the table
+-- under a new name, optionally annotated with a unit. The querier resolves the
+-- view of a table at planning time, allowing columns to be renamed, relabelled
+-- and computed without rewriting persisted data.
+CREATE TABLE IF NOT EXISTS table_view_column (
+    table_id BIGINT NOT NULL,
+    name TEXT NOT NULL,
+    expression TEXT NOT NULL,
+    unit TEXT NULL,
+    PRIMARY KEY (table_id, name),
+    FOREIGN KEY (table_id) REFERENCES table_name (id) ON DELETE CASCADE
+);
@@ -0,0 +1,31 @@
+-- Add a "task" table holding the definitions of the tasks periodically
+-- executing a SQL query against a namespace, and a "task_run" table recording
+-- the history of their runs.
+--
+-- The queriers execute the tasks on their cron schedule, writing the results
+-- into the destination table through the router. Inserting the run of a task
+-- for a scheduled time claims it, so each scheduled run is executed once.
+CREATE TABLE IF NOT EXISTS task (
+    id BIGSERIAL NOT NULL PRIMARY KEY,
+    namespace_id BIGINT NOT NULL,
+    name TEXT NOT NULL,
+    schedule TEXT NOT NULL,
+    query TEXT NOT NULL,
+    destination_table TEXT NOT NULL,
+    max_retries INT NOT NULL DEFAULT 0,
+    created_at BIGINT NOT NULL,
+    UNIQUE (namespace_id, name),
+    FOREIGN KEY (namespace_id) REFERENCES namespace (id) ON DELETE CASCADE
+);
+
+CREATE TABLE IF NOT EXISTS task_run (
+    task_id BIGINT NOT NULL,
+    scheduled_at BIGINT NOT NULL,
+    started_at BIGINT NOT NULL,
+    finished_at BIGINT NULL,
+    attempts INT NOT NULL DEFAULT 0,
+    rows_written BIGINT NOT NULL DEFAULT 0,
+    error TEXT NULL,
+    PRIMARY KEY (task_id, scheduled_at),
+    FOREIGN KEY (task_id) REFERENCES task (id) ON DELETE CASCADE
+);
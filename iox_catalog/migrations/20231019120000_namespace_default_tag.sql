@@ -0,0 +1,12 @@
+-- Add a "namespace_default_tag" table holding the tags added by the routers to
+-- every row written to a namespace.
+--
+-- Default tags label all the data of a namespace (for example with "env=prod")
+-- without changing the writers.
+CREATE TABLE IF NOT EXISTS namespace_default_tag (
+    namespace_id BIGINT NOT NULL,
+    name TEXT NOT NULL,
+    value TEXT NOT NULL,
+    PRIMARY KEY (namespace_id, name),
+    FOREIGN KEY (namespace_id) REFERENCES namespace (id) ON DELETE CASCADE
+);
@@ -0,0 +1,24 @@
+-- Add a "query_history" table recording the queries completed against each
+-- namespace, and the resources used to execute them.
+--
+-- Only a hash of the query text is stored. Entries older than the retention
+-- period configured in the queriers are periodically deleted.
+CREATE TABLE IF NOT EXISTS query_history (
+    namespace_id BIGINT NOT NULL,
+    issue_time BIGINT NOT NULL,
+    query_type TEXT NOT NULL,
+    query_text_hash BIGINT NOT NULL,
+    duration_ns BIGINT NOT NULL,
+    success BOOLEAN NOT NULL,
+    partitions_scanned BIGINT NULL,
+    bytes_scanned BIGINT NULL,
+    rows_returned BIGINT NULL,
+    peak_memory_bytes BIGINT NULL,
+    FOREIGN KEY (namespace_id) REFERENCES namespace (id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS query_history_namespace_issue_time_idx
+    ON query_history (namespace_id, issue_time);
+
+CREATE INDEX IF NOT EXISTS query_history_issue_time_idx
+    ON query_history (issue_time);
@@ -0,0 +1,17 @@
+-- Add a "column_retention" table holding the retention period overrides of
+-- individual field columns.
+--
+-- The values of a column with a retention period override are dropped by the
+-- compactor from the data older than that period, while the rest of the rows
+-- are kept for the retention period of the namespace.
+CREATE TABLE IF NOT EXISTS column_retention (
+    table_id BIGINT NOT NULL,
+    column_id BIGINT NOT NULL,
+    retention_period_ns BIGINT NOT NULL,
+    PRIMARY KEY (column_id),
+    FOREIGN KEY (table_id) REFERENCES table_name (id) ON DELETE CASCADE,
+    FOREIGN KEY (column_id) REFERENCES column_name (id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS column_retention_table_idx
+    ON column_retention (table_id);
@@ -0,0 +1,22 @@
+-- Add a "table_usage" table recording the number of rows and bytes buffered
+-- and persisted by the ingesters for each table, aggregated per hour.
+--
+-- Each ingester periodically adds its counts to the record for the hour they
+-- were observed in, allowing per-namespace and per-table chargeback.
+CREATE TABLE IF NOT EXISTS table_usage
+(
+    namespace_id    INTEGER NOT NULL
+        REFERENCES namespace
+            ON DELETE CASCADE,
+    table_id        INTEGER NOT NULL
+        REFERENCES table_name
+            ON DELETE CASCADE,
+    hour_start      INTEGER NOT NULL,
+    rows_buffered   INTEGER NOT NULL DEFAULT 0,
+    bytes_buffered  INTEGER NOT NULL DEFAULT 0,
+    rows_persisted  INTEGER NOT NULL DEFAULT 0,
+    bytes_persisted INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY (table_id, hour_start)
+);
+
+CREATE INDEX IF NOT EXISTS table_usage_namespace_hour_idx ON table_usage (namespace_id, hour_start);
@@ -0,0 +1,10 @@
+-- Add the name of the object store a namespace's parquet files are stored in
+-- to the "namespace" table.
+--
+-- A NULL value means the namespace uses the default object store. The name
+-- refers to an object store configured on each server, so credentials are
+-- never stored in the catalog.
+ALTER TABLE
+    namespace
+ADD
+    COLUMN object_store_name TEXT NULL;
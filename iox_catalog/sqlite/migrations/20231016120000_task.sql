@@ -0,0 +1,37 @@
+-- Add a "task" table holding the definitions of the tasks periodically
+-- executing a SQL query against a namespace, and a "task_run" table recording
+-- the history of their runs.
+--
+-- The queriers execute the tasks on their cron schedule, writing the results
+-- into the destination table through the router. Inserting the run of a task
+-- for a scheduled time claims it, so each scheduled run is executed once.
+CREATE TABLE IF NOT EXISTS task
+(
+    id                INTEGER
+        constraint task_pkey
+            primary key autoincrement,
+    namespace_id      INTEGER NOT NULL
+        REFERENCES namespace
+            ON DELETE CASCADE,
+    name              TEXT    NOT NULL,
+    schedule          TEXT    NOT NULL,
+    query             TEXT    NOT NULL,
+    destination_table TEXT    NOT NULL,
+    max_retries       INTEGER NOT NULL DEFAULT 0,
+    created_at        INTEGER NOT NULL,
+    UNIQUE (namespace_id, name)
+);
+
+CREATE TABLE IF NOT EXISTS task_run
+(
+    task_id      INTEGER NOT NULL
+        REFERENCES task
+            ON DELETE CASCADE,
+    scheduled_at INTEGER NOT NULL,
+    started_at   INTEGER NOT NULL,
+    finished_at  INTEGER NULL,
+    attempts     INTEGER NOT NULL DEFAULT 0,
+    rows_written INTEGER NOT NULL DEFAULT 0,
+    error        TEXT    NULL,
+    PRIMARY KEY (task_id, scheduled_at)
+);
@@ -0,0 +1,18 @@
+-- Add a "parquet_file_column_statistics" table holding the statistics of the
+-- string and tag columns of each parquet file.
+--
+-- The statistics are recorded when a parquet file is created by the ingester
+-- or the compactor, allowing the querier to prune files on predicates over
+-- these columns without reading the parquet file footers. The min/max values
+-- are truncated bounds of the column values, NULL if unknown.
+CREATE TABLE IF NOT EXISTS parquet_file_column_statistics
+(
+    parquet_file_id INTEGER NOT NULL
+        REFERENCES parquet_file
+            ON DELETE CASCADE,
+    column_id       INTEGER NOT NULL,
+    min_value       TEXT    NULL,
+    max_value       TEXT    NULL,
+    null_count      INTEGER NOT NULL,
+    PRIMARY KEY (parquet_file_id, column_id)
+);
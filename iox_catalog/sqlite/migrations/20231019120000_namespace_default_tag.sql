@@ -0,0 +1,14 @@
+-- Add a "namespace_default_tag" table holding the tags added by the routers to
+-- every row written to a namespace.
+--
+-- Default tags label all the data of a namespace (for example with "env=prod")
+-- without changing the writers.
+CREATE TABLE IF NOT EXISTS namespace_default_tag
+(
+    namespace_id INTEGER NOT NULL
+        REFERENCES namespace
+            ON DELETE CASCADE,
+    name         TEXT    NOT NULL,
+    value        TEXT    NOT NULL,
+    PRIMARY KEY (namespace_id, name)
+);
@@ -0,0 +1,26 @@
+-- Add a "query_history" table recording the queries completed against each
+-- namespace, and the resources used to execute them.
+--
+-- Only a hash of the query text is stored. Entries older than the retention
+-- period configured in the queriers are periodically deleted.
+CREATE TABLE IF NOT EXISTS query_history
+(
+    namespace_id       INTEGER NOT NULL
+        REFERENCES namespace
+            ON DELETE CASCADE,
+    issue_time         INTEGER NOT NULL,
+    query_type         TEXT    NOT NULL,
+    query_text_hash    INTEGER NOT NULL,
+    duration_ns        INTEGER NOT NULL,
+    success            BOOLEAN NOT NULL,
+    partitions_scanned INTEGER NULL,
+    bytes_scanned      INTEGER NULL,
+    rows_returned      INTEGER NULL,
+    peak_memory_bytes  INTEGER NULL
+);
+
+CREATE INDEX IF NOT EXISTS query_history_namespace_issue_time_idx
+    ON query_history (namespace_id, issue_time);
+
+CREATE INDEX IF NOT EXISTS query_history_issue_time_idx
+    ON query_history (issue_time);
@@ -0,0 +1,20 @@
+-- Add a "column_retention" table holding the retention period overrides of
+-- individual field columns.
+--
+-- The values of a column with a retention period override are dropped by the
+-- compactor from the data older than that period, while the rest of the rows
+-- are kept for the retention period of the namespace.
+CREATE TABLE IF NOT EXISTS column_retention
+(
+    table_id            INTEGER NOT NULL
+        REFERENCES table_name
+            ON DELETE CASCADE,
+    column_id           INTEGER NOT NULL
+        REFERENCES column_name
+            ON DELETE CASCADE,
+    retention_period_ns INTEGER NOT NULL,
+    PRIMARY KEY (column_id)
+);
+
+CREATE INDEX IF NOT EXISTS column_retention_table_idx
+    ON column_retention (table_id);
@@ -3,10 +3,12 @@
 use async_trait::async_trait;
 use data_types::{
     partition_template::{NamespacePartitionTemplateOverride, TablePartitionTemplateOverride},
-    Column, ColumnType, ColumnsByName, CompactionLevel, MaxColumnsPerTable, MaxTables, Namespace,
-    NamespaceId, NamespaceName, NamespaceSchema, NamespaceServiceProtectionLimitsOverride,
-    ParquetFile, ParquetFileId, ParquetFileParams, Partition, PartitionHashId, PartitionId,
-    PartitionKey, SkippedCompaction, SortedColumnSet, Table, TableId, TableSchema, Timestamp,
+    Column, ColumnId, ColumnRetention, ColumnType, ColumnsByName, CompactionLevel,
+    MaxColumnsPerTable, MaxTables, Namespace, NamespaceDefaultTag, NamespaceId, NamespaceName,
+    NamespaceSchema, NamespaceServiceProtectionLimitsOverride, ParquetFile,
+    ParquetFileColumnStatistics, ParquetFileId, ParquetFileParams, Partition, PartitionHashId,
+    PartitionId, PartitionKey, QueryHistoryEntry, SkippedCompaction, SortedColumnSet, Table,
+    TableId, TableSchema, TableUsage, TableViewColumn, Task, TaskId, TaskRun, Timestamp,
     TransitionPartitionId,
 };
 use iox_time::TimeProvider;
@@ -83,6 +85,9 @@ pub enum Error {
     #[snafu(display("table {} not found", name))]
     TableNotFoundByName { name: String },
 
+    #[snafu(display("column {} not found", id))]
+    ColumnNotFound { id: ColumnId },
+
     #[snafu(display("partition {} not found", id))]
     PartitionNotFound { id: TransitionPartitionId },
 
@@ -152,6 +157,12 @@ pub enum Error {
 
     #[snafu(display("could not delete namespace: {source}"))]
     CouldNotDeleteNamespace { source: sqlx::Error },
+
+    #[snafu(display("run of task {task_id} scheduled at {scheduled_at:?} not found"))]
+    TaskRunNotFound {
+        task_id: TaskId,
+        scheduled_at: Timestamp,
+    },
 }
 
 /// A specialized `Error` for Catalog errors
@@ -244,11 +255,29 @@ pub trait RepoCollection: Send + Sync + Debug {
     /// Repository for [columns](data_types::Column).
     fn columns(&mut self) -> &mut dyn ColumnRepo;
 
+    /// Repository for [column retention periods](data_types::ColumnRetention).
+    fn column_retention(&mut self) -> &mut dyn ColumnRetentionRepo;
+
     /// Repository for [partitions](data_types::Partition).
     fn partitions(&mut self) -> &mut dyn PartitionRepo;
 
     /// Repository for [Parquet files](data_types::ParquetFile).
     fn parquet_files(&mut self) -> &mut dyn ParquetFileRepo;
+
+    /// Repository for [table usage](data_types::TableUsage).
+    fn table_usage(&mut self) -> &mut dyn TableUsageRepo;
+
+    /// Repository for [table view columns](data_types::TableViewColumn).
+    fn table_views(&mut self) -> &mut dyn TableViewRepo;
+
+    /// Repository for [tasks](data_types::Task) and their runs.
+    fn tasks(&mut self) -> &mut dyn TaskRepo;
+
+    /// Repository for [namespace default tags](data_types::NamespaceDefaultTag).
+    fn namespace_default_tags(&mut self) -> &mut dyn NamespaceDefaultTagRepo;
+
+    /// Repository for the [query history](data_types::QueryHistoryEntry).
+    fn query_history(&mut self) -> &mut dyn QueryHistoryRepo;
 }
 
 /// Functions for working with namespaces in the catalog
@@ -292,6 +321,11 @@ pub trait NamespaceRepo: Send + Sync {
     /// Soft-delete a namespace by name
     async fn soft_delete(&mut self, name: &str) -> Result<()>;
 
+    /// Restore a soft-deleted namespace by name, returning it.
+    ///
+    /// Restoring a namespace that is not deleted is a no-op.
+    async fn undelete(&mut self, name: &str) -> Result<Namespace>;
+
     /// Update the limit on the number of tables that can exist per namespace.
     async fn update_table_limit(&mut self, name: &str, new_max: MaxTables) -> Result<Namespace>;
 
@@ -301,6 +335,19 @@ pub trait NamespaceRepo: Send + Sync {
         name: &str,
         new_max: MaxColumnsPerTable,
     ) -> Result<Namespace>;
+
+    /// Mark a namespace as read-only (rejecting all writes), or writable.
+    async fn update_read_only(&mut self, name: &str, read_only: bool) -> Result<Namespace>;
+
+    /// Set the name of the object store the parquet files of a namespace are
+    /// stored in, or use the default object store when [`None`].
+    ///
+    /// Existing parquet files are not moved between object stores.
+    async fn update_object_store_name(
+        &mut self,
+        name: &str,
+        object_store_name: Option<&str>,
+    ) -> Result<Namespace>;
 }
 
 /// Functions for working with tables in the catalog
@@ -370,6 +417,29 @@ pub trait ColumnRepo: Send + Sync {
     async fn list(&mut self) -> Result<Vec<Column>>;
 }
 
+/// Functions for working with the retention period overrides of columns in the
+/// catalog
+#[async_trait]
+pub trait ColumnRetentionRepo: Send + Sync {
+    /// Set the retention period of the column of `retention`, replacing any
+    /// existing retention period of that column.
+    async fn upsert(&mut self, retention: &ColumnRetention) -> Result<()>;
+
+    /// Remove the retention period of `column_id`, returning true if it
+    /// existed.
+    async fn remove(&mut self, column_id: ColumnId) -> Result<bool>;
+
+    /// List the column retention periods of `table_id`, ordered by column ID.
+    async fn list_by_table_id(&mut self, table_id: TableId) -> Result<Vec<ColumnRetention>>;
+
+    /// List the column retention periods of all the tables in
+    /// `namespace_id`, ordered by table and column ID.
+    async fn list_by_namespace_id(
+        &mut self,
+        namespace_id: NamespaceId,
+    ) -> Result<Vec<ColumnRetention>>;
+}
+
 /// Functions for working with IOx partitions in the catalog. These are how IOx splits up
 /// data within a namespace.
 #[async_trait]
@@ -542,6 +612,150 @@ pub trait ParquetFileRepo: Send + Sync {
         create: &[ParquetFileParams],
         target_level: CompactionLevel,
     ) -> Result<Vec<ParquetFileId>>;
+
+    /// List the [column statistics](ParquetFileParams::column_statistics) recorded for the
+    /// parquet files within a given table that are NOT marked as
+    /// [`to_delete`](ParquetFile::to_delete).
+    async fn list_column_statistics_by_table(
+        &mut self,
+        table_id: TableId,
+    ) -> Result<Vec<(ParquetFileId, ParquetFileColumnStatistics)>>;
+}
+
+/// Functions for working with the hourly table usage records in the catalog
+#[async_trait]
+pub trait TableUsageRepo: Send + Sync {
+    /// Add the row and byte counts of each entry in `usage` to the existing
+    /// totals for the same table and hour, creating the record if necessary.
+    async fn record(&mut self, usage: &[TableUsage]) -> Result<()>;
+
+    /// List the usage records of all tables within `namespace_id` for the
+    /// hours starting at or after `since`, ordered by hour and table.
+    async fn list_by_namespace(
+        &mut self,
+        namespace_id: NamespaceId,
+        since: Timestamp,
+    ) -> Result<Vec<TableUsage>>;
+}
+
+/// Functions for working with the columns of table views in the catalog
+#[async_trait]
+pub trait TableViewRepo: Send + Sync {
+    /// Create the view column `column`, replacing the expression and unit of
+    /// any existing view column with the same name in the same table.
+    async fn upsert(&mut self, column: &TableViewColumn) -> Result<()>;
+
+    /// Remove the view column `name` of `table_id`, returning true if it
+    /// existed.
+    async fn remove(&mut self, table_id: TableId, name: &str) -> Result<bool>;
+
+    /// List the view columns of all tables within `namespace_id`, ordered by
+    /// table and name.
+    async fn list_by_namespace_id(
+        &mut self,
+        namespace_id: NamespaceId,
+    ) -> Result<Vec<TableViewColumn>>;
+}
+
+/// Functions for working with tasks and the history of their runs in the
+/// catalog
+#[async_trait]
+pub trait TaskRepo: Send + Sync {
+    /// Create a task in `namespace_id`. If a task with the same name already
+    /// exists in the namespace, an error is returned.
+    async fn create(
+        &mut self,
+        namespace_id: NamespaceId,
+        name: &str,
+        schedule: &str,
+        query: &str,
+        destination_table: &str,
+        max_retries: i32,
+    ) -> Result<Task>;
+
+    /// Get the task `name` of `namespace_id`.
+    async fn get_by_namespace_and_name(
+        &mut self,
+        namespace_id: NamespaceId,
+        name: &str,
+    ) -> Result<Option<Task>>;
+
+    /// List the tasks of `namespace_id`, ordered by name.
+    async fn list_by_namespace_id(&mut self, namespace_id: NamespaceId) -> Result<Vec<Task>>;
+
+    /// List the tasks of all namespaces.
+    async fn list(&mut self) -> Result<Vec<Task>>;
+
+    /// Record the start of the run of `task_id` scheduled at `scheduled_at`,
+    /// returning it.
+    ///
+    /// Returns [`None`] if a run of the task for `scheduled_at` was already
+    /// started, which must then not be executed again.
+    async fn start_run(
+        &mut self,
+        task_id: TaskId,
+        scheduled_at: Timestamp,
+    ) -> Result<Option<TaskRun>>;
+
+    /// Record the end of the run of `task_id` scheduled at `scheduled_at`,
+    /// failed with `error` if set.
+    async fn finish_run(
+        &mut self,
+        task_id: TaskId,
+        scheduled_at: Timestamp,
+        attempts: i32,
+        rows_written: i64,
+        error: Option<&str>,
+    ) -> Result<TaskRun>;
+
+    /// List the `limit` most recently scheduled runs of `task_id`, most
+    /// recent first.
+    async fn list_runs(&mut self, task_id: TaskId, limit: i64) -> Result<Vec<TaskRun>>;
+
+    /// Record the runs of any task started before `started_before` and not
+    /// yet finished as failed with `error`, returning them.
+    async fn fail_stale_runs(
+        &mut self,
+        started_before: Timestamp,
+        error: &str,
+    ) -> Result<Vec<TaskRun>>;
+}
+
+/// Functions for working with the default tags of namespaces in the catalog
+#[async_trait]
+pub trait NamespaceDefaultTagRepo: Send + Sync {
+    /// Create the default tag `tag`, replacing the value of any existing
+    /// default tag with the same name in the same namespace.
+    async fn upsert(&mut self, tag: &NamespaceDefaultTag) -> Result<()>;
+
+    /// Remove the default tag `name` of `namespace_id`, returning true if it
+    /// existed.
+    async fn remove(&mut self, namespace_id: NamespaceId, name: &str) -> Result<bool>;
+
+    /// List the default tags of `namespace_id`, ordered by name.
+    async fn list_by_namespace_id(
+        &mut self,
+        namespace_id: NamespaceId,
+    ) -> Result<Vec<NamespaceDefaultTag>>;
+}
+
+/// Functions for working with the query history in the catalog
+#[async_trait]
+pub trait QueryHistoryRepo: Send + Sync {
+    /// Record the completed query `entry`.
+    async fn record(&mut self, entry: &QueryHistoryEntry) -> Result<()>;
+
+    /// List the `limit` most recently issued queries of `namespace_id`, most
+    /// recent first.
+    async fn list_by_namespace_id(
+        &mut self,
+        namespace_id: NamespaceId,
+        limit: i64,
+    ) -> Result<Vec<QueryHistoryEntry>>;
+
+    /// Delete the queries of all namespaces issued before `older_than`,
+    /// returning the number of entries deleted.
+    async fn delete_older_than(&mut self, older_than: Timestamp) -> Result<u64>;
 }
 
 /// Gets the namespace schema including all tables and columns.
@@ -775,7 +989,7 @@ pub(crate) mod test_helpers {
     use super::*;
     use ::test_helpers::assert_error;
     use assert_matches::assert_matches;
-    use data_types::{ColumnId, CompactionLevel, MaxColumnsPerTable, MaxTables};
+    use data_types::{CompactionLevel, MaxColumnsPerTable, MaxTables, TaskRunStatus};
     use futures::Future;
     use generated_types::influxdata::iox::partition_template::v1 as proto;
     use metric::{Attributes, DurationHistogram, Metric};
@@ -793,11 +1007,18 @@ pub(crate) mod test_helpers {
         test_partition(clean_state().await).await;
         test_parquet_file(clean_state().await).await;
         test_parquet_file_delete_broken(clean_state().await).await;
+        test_parquet_file_column_statistics(clean_state().await).await;
         test_update_to_compaction_level_1(clean_state().await).await;
         test_list_by_partiton_not_to_delete(clean_state().await).await;
         test_list_schemas(clean_state().await).await;
         test_list_schemas_soft_deleted_rows(clean_state().await).await;
         test_delete_namespace(clean_state().await).await;
+        test_table_usage(clean_state().await).await;
+        test_table_views(clean_state().await).await;
+        test_tasks(clean_state().await).await;
+        test_namespace_default_tags(clean_state().await).await;
+        test_query_history(clean_state().await).await;
+        test_column_retention(clean_state().await).await;
 
         let catalog = clean_state().await;
         test_namespace(Arc::clone(&catalog)).await;
@@ -936,6 +1157,64 @@ pub(crate) mod test_helpers {
             .expect("namespace should be updateable");
         assert!(modified.retention_period_ns.is_none());
 
+        assert!(!namespace.read_only);
+        let modified = repos
+            .namespaces()
+            .update_read_only(namespace_name.as_str(), true)
+            .await
+            .expect("namespace should be updateable");
+        assert!(modified.read_only);
+        let found = repos
+            .namespaces()
+            .get_by_id(namespace.id, SoftDeletedRows::ExcludeDeleted)
+            .await
+            .unwrap()
+            .expect("namespace should be there");
+        assert!(found.read_only);
+
+        let modified = repos
+            .namespaces()
+            .update_read_only(namespace_name.as_str(), false)
+            .await
+            .expect("namespace should be updateable");
+        assert!(!modified.read_only);
+
+        let err = repos
+            .namespaces()
+            .update_read_only("does_not_exist", true)
+            .await
+            .expect_err("update of unknown namespace should fail");
+        assert!(matches!(err, Error::NamespaceNotFoundByName { .. }));
+
+        assert!(namespace.object_store_name.is_none());
+        let modified = repos
+            .namespaces()
+            .update_object_store_name(namespace_name.as_str(), Some("tenant-a"))
+            .await
+            .expect("namespace should be updateable");
+        assert_eq!(modified.object_store_name.as_deref(), Some("tenant-a"));
+        let found = repos
+            .namespaces()
+            .get_by_id(namespace.id, SoftDeletedRows::ExcludeDeleted)
+            .await
+            .unwrap()
+            .expect("namespace should be there");
+        assert_eq!(found.object_store_name.as_deref(), Some("tenant-a"));
+
+        let modified = repos
+            .namespaces()
+            .update_object_store_name(namespace_name.as_str(), None)
+            .await
+            .expect("namespace should be updateable");
+        assert!(modified.object_store_name.is_none());
+
+        let err = repos
+            .namespaces()
+            .update_object_store_name("does_not_exist", Some("tenant-a"))
+            .await
+            .expect_err("update of unknown namespace should fail");
+        assert!(matches!(err, Error::NamespaceNotFoundByName { .. }));
+
         // create namespace with retention period NULL (the default)
         let namespace3 = arbitrary_namespace(&mut *repos, "test_namespace3").await;
         assert!(namespace3.retention_period_ns.is_none());
@@ -1155,6 +1434,29 @@ pub(crate) mod test_helpers {
             .into_iter()
             .map(|v| v.name);
         assert_string_set_eq(got, ["active-ns"]);
+
+        // Restoring the deleted namespace makes it visible again, and is
+        // idempotent.
+        let got = repos.namespaces().undelete("deleted-ns").await.unwrap();
+        assert_eq!(got.id, deleted_ns.id);
+        assert!(got.deleted_at.is_none());
+        let got = repos.namespaces().undelete("deleted-ns").await.unwrap();
+        assert!(got.deleted_at.is_none());
+        let got = repos
+            .namespaces()
+            .list(SoftDeletedRows::ExcludeDeleted)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|v| v.name);
+        assert_string_set_eq(got, ["deleted-ns", "active-ns"]);
+
+        let err = repos
+            .namespaces()
+            .undelete("missing-ns")
+            .await
+            .expect_err("restoring an unknown namespace should fail");
+        assert_matches!(err, Error::NamespaceNotFoundByName { .. });
     }
 
     // Assert the set of strings "a" is equal to the set "b", tolerating
@@ -2630,6 +2932,148 @@ pub(crate) mod test_helpers {
         assert_eq!(ids, vec![parquet_file_2.id]);
     }
 
+    async fn test_parquet_file_column_statistics(catalog: Arc<dyn Catalog>) {
+        let mut repos = catalog.repositories().await;
+        let namespace = arbitrary_namespace(&mut *repos, "column_statistics").await;
+        let table = arbitrary_table(&mut *repos, "test_table", &namespace).await;
+        let other_table = arbitrary_table(&mut *repos, "other_table", &namespace).await;
+        let partition = repos
+            .partitions()
+            .create_or_get("one".into(), table.id)
+            .await
+            .unwrap();
+        let other_partition = repos
+            .partitions()
+            .create_or_get("one".into(), other_table.id)
+            .await
+            .unwrap();
+
+        let stats = |id: i64, min: &str, max: &str| ParquetFileColumnStatistics {
+            column_id: ColumnId::new(id),
+            min_value: Some(min.to_string()),
+            max_value: Some(max.to_string()),
+            null_count: id,
+        };
+
+        // No statistics are recorded for files created without them.
+        let plain = repos
+            .parquet_files()
+            .create(arbitrary_parquet_file_params(
+                &namespace, &table, &partition,
+            ))
+            .await
+            .unwrap();
+
+        let f1 = repos
+            .parquet_files()
+            .create(ParquetFileParams {
+                column_statistics: vec![
+                    stats(1, "a", "b"),
+                    ParquetFileColumnStatistics {
+                        column_id: ColumnId::new(2),
+                        min_value: None,
+                        max_value: None,
+                        null_count: 42,
+                    },
+                ],
+                ..arbitrary_parquet_file_params(&namespace, &table, &partition)
+            })
+            .await
+            .unwrap();
+        let ids = repos
+            .parquet_files()
+            .create_upgrade_delete(
+                &[],
+                &[],
+                &[ParquetFileParams {
+                    column_statistics: vec![stats(1, "c", "d")],
+                    ..arbitrary_parquet_file_params(&namespace, &table, &partition)
+                }],
+                CompactionLevel::Initial,
+            )
+            .await
+            .unwrap();
+        let [f2] = ids.as_slice() else {
+            panic!("expected one created file, got {ids:?}");
+        };
+        repos
+            .parquet_files()
+            .create(ParquetFileParams {
+                column_statistics: vec![stats(1, "x", "y")],
+                ..arbitrary_parquet_file_params(&namespace, &other_table, &other_partition)
+            })
+            .await
+            .unwrap();
+
+        // Tag values may contain NUL characters, which Postgres cannot store
+        // in a TEXT column.
+        let nul = ParquetFileColumnStatistics::new(ColumnId::new(2), Some("a\0b"), Some("a\0b"), 0);
+        let f3 = repos
+            .parquet_files()
+            .create(ParquetFileParams {
+                column_statistics: vec![nul.clone()],
+                ..arbitrary_parquet_file_params(&namespace, &other_table, &other_partition)
+            })
+            .await
+            .unwrap();
+        let got = repos
+            .parquet_files()
+            .list_column_statistics_by_table(other_table.id)
+            .await
+            .unwrap();
+        assert!(got.contains(&(f3.id, nul)), "{got:?}");
+
+        let mut got = repos
+            .parquet_files()
+            .list_column_statistics_by_table(table.id)
+            .await
+            .unwrap();
+        got.sort_by_key(|(id, s)| (*id, s.column_id));
+        assert_eq!(
+            got,
+            [
+                (f1.id, stats(1, "a", "b")),
+                (
+                    f1.id,
+                    ParquetFileColumnStatistics {
+                        column_id: ColumnId::new(2),
+                        min_value: None,
+                        max_value: None,
+                        null_count: 42,
+                    }
+                ),
+                (*f2, stats(1, "c", "d")),
+            ]
+        );
+
+        // Statistics of files marked for deletion are not listed, nor are
+        // those of files that were deleted.
+        repos
+            .parquet_files()
+            .create_upgrade_delete(&[f1.id, plain.id], &[], &[], CompactionLevel::Initial)
+            .await
+            .unwrap();
+        let got = repos
+            .parquet_files()
+            .list_column_statistics_by_table(table.id)
+            .await
+            .unwrap();
+        assert_eq!(got, [(*f2, stats(1, "c", "d"))]);
+
+        let deleted = repos
+            .parquet_files()
+            .delete_old_ids_only(Timestamp::new(i64::MAX))
+            .await
+            .unwrap();
+        assert_eq!(deleted.len(), 2);
+        let got = repos
+            .parquet_files()
+            .list_column_statistics_by_table(table.id)
+            .await
+            .unwrap();
+        assert_eq!(got, [(*f2, stats(1, "c", "d"))]);
+    }
+
     async fn test_partitions_new_file_between(catalog: Arc<dyn Catalog>) {
         let mut repos = catalog.repositories().await;
         let namespace = arbitrary_namespace(&mut *repos, "test_partitions_new_file_between").await;
@@ -3148,6 +3592,563 @@ pub(crate) mod test_helpers {
     /// being actively used by the system. This is done by waiting a long time
     /// before deleting records, and whilst isn't perfect, it is largely
     /// effective.
+    async fn test_table_usage(catalog: Arc<dyn Catalog>) {
+        let mut repos = catalog.repositories().await;
+        let namespace = arbitrary_namespace(&mut *repos, "namespace_table_usage").await;
+        let table_1 = arbitrary_table(&mut *repos, "table_usage_1", &namespace).await;
+        let table_2 = arbitrary_table(&mut *repos, "table_usage_2", &namespace).await;
+        let other_namespace = arbitrary_namespace(&mut *repos, "namespace_table_usage_2").await;
+        let other_table = arbitrary_table(&mut *repos, "table_usage_1", &other_namespace).await;
+
+        let hour = TableUsage::WINDOW.as_nanos() as i64;
+        let usage = |table: &Table, h: i64, rows: i64| TableUsage {
+            namespace_id: table.namespace_id,
+            table_id: table.id,
+            hour_start: Timestamp::new(h * hour),
+            rows_buffered: rows,
+            bytes_buffered: rows * 10,
+            rows_persisted: rows / 2,
+            bytes_persisted: rows * 3,
+        };
+
+        assert!(repos
+            .table_usage()
+            .list_by_namespace(namespace.id, Timestamp::new(0))
+            .await
+            .unwrap()
+            .is_empty());
+
+        repos
+            .table_usage()
+            .record(&[
+                usage(&table_1, 1, 10),
+                usage(&table_2, 1, 4),
+                usage(&other_table, 1, 42),
+            ])
+            .await
+            .unwrap();
+
+        // Recording the same table and hour again adds to the existing counts.
+        repos
+            .table_usage()
+            .record(&[usage(&table_1, 1, 2), usage(&table_1, 2, 6)])
+            .await
+            .unwrap();
+
+        let got = repos
+            .table_usage()
+            .list_by_namespace(namespace.id, Timestamp::new(0))
+            .await
+            .unwrap();
+        assert_eq!(
+            got,
+            [
+                usage(&table_1, 1, 12),
+                usage(&table_2, 1, 4),
+                usage(&table_1, 2, 6)
+            ]
+        );
+
+        // Only hours at or after "since" are returned.
+        let got = repos
+            .table_usage()
+            .list_by_namespace(namespace.id, Timestamp::new(2 * hour))
+            .await
+            .unwrap();
+        assert_eq!(got, [usage(&table_1, 2, 6)]);
+
+        let got = repos
+            .table_usage()
+            .list_by_namespace(other_namespace.id, Timestamp::new(0))
+            .await
+            .unwrap();
+        assert_eq!(got, [usage(&other_table, 1, 42)]);
+
+        // Recording usage for an unknown table fails.
+        let mut bad = usage(&table_1, 1, 1);
+        bad.table_id = TableId::new(i64::MAX);
+        assert!(repos.table_usage().record(&[bad]).await.is_err());
+    }
+
+    async fn test_table_views(catalog: Arc<dyn Catalog>) {
+        let mut repos = catalog.repositories().await;
+        let namespace = arbitrary_namespace(&mut *repos, "namespace_table_views").await;
+        let table_1 = arbitrary_table(&mut *repos, "table_views_1", &namespace).await;
+        let table_2 = arbitrary_table(&mut *repos, "table_views_2", &namespace).await;
+        let other_namespace = arbitrary_namespace(&mut *repos, "namespace_table_views_2").await;
+        let other_table = arbitrary_table(&mut *repos, "table_views_1", &other_namespace).await;
+
+        let column =
+            |table: &Table, name: &str, expression: &str, unit: Option<&str>| TableViewColumn {
+                table_id: table.id,
+                name: name.to_string(),
+                expression: expression.to_string(),
+                unit: unit.map(ToString::to_string),
+            };
+
+        assert!(repos
+            .table_views()
+            .list_by_namespace_id(namespace.id)
+            .await
+            .unwrap()
+            .is_empty());
+
+        let temp_c = column(&table_1, "temp_c", r#""temp""#, Some("celsius"));
+        let temp_f = column(&table_1, "temp_f", r#""temp" * 1.8 + 32"#, None);
+        let host = column(&table_2, "host", r#""hostname""#, None);
+        let other = column(&other_table, "temp_c", r#""t""#, None);
+        for c in [&temp_f, &temp_c, &host, &other] {
+            repos.table_views().upsert(c).await.unwrap();
+        }
+
+        let got = repos
+            .table_views()
+            .list_by_namespace_id(namespace.id)
+            .await
+            .unwrap();
+        assert_eq!(got, [temp_c.clone(), temp_f.clone(), host.clone()]);
+
+        // Upserting an existing column replaces its expression and unit.
+        let temp_f = column(
+            &table_1,
+            "temp_f",
+            r#""temp" * 9 / 5 + 32"#,
+            Some("fahrenheit"),
+        );
+        repos.table_views().upsert(&temp_f).await.unwrap();
+
+        assert!(repos
+            .table_views()
+            .remove(table_1.id, "temp_c")
+            .await
+            .unwrap());
+        assert!(!repos
+            .table_views()
+            .remove(table_1.id, "temp_c")
+            .await
+            .unwrap());
+
+        let got = repos
+            .table_views()
+            .list_by_namespace_id(namespace.id)
+            .await
+            .unwrap();
+        assert_eq!(got, [temp_f, host]);
+
+        let got = repos
+            .table_views()
+            .list_by_namespace_id(other_namespace.id)
+            .await
+            .unwrap();
+        assert_eq!(got, [other]);
+
+        // View columns of unknown tables are rejected.
+        let mut bad = column(&table_1, "bad", r#""temp""#, None);
+        bad.table_id = TableId::new(i64::MAX);
+        assert!(repos.table_views().upsert(&bad).await.is_err());
+    }
+
+    async fn test_namespace_default_tags(catalog: Arc<dyn Catalog>) {
+        let mut repos = catalog.repositories().await;
+        let namespace = arbitrary_namespace(&mut *repos, "namespace_default_tags").await;
+        let other_namespace = arbitrary_namespace(&mut *repos, "namespace_default_tags_2").await;
+
+        let tag = |namespace: &Namespace, name: &str, value: &str| NamespaceDefaultTag {
+            namespace_id: namespace.id,
+            name: name.to_string(),
+            value: value.to_string(),
+        };
+
+        assert!(repos
+            .namespace_default_tags()
+            .list_by_namespace_id(namespace.id)
+            .await
+            .unwrap()
+            .is_empty());
+
+        let region = tag(&namespace, "region", "eu");
+        let env = tag(&namespace, "env", "dev");
+        let other = tag(&other_namespace, "env", "prod");
+        for t in [&region, &env, &other] {
+            repos.namespace_default_tags().upsert(t).await.unwrap();
+        }
+
+        let got = repos
+            .namespace_default_tags()
+            .list_by_namespace_id(namespace.id)
+            .await
+            .unwrap();
+        assert_eq!(got, [env, region.clone()]);
+
+        // Upserting an existing tag replaces its value.
+        let env = tag(&namespace, "env", "prod");
+        repos.namespace_default_tags().upsert(&env).await.unwrap();
+
+        assert!(repos
+            .namespace_default_tags()
+            .remove(namespace.id, "region")
+            .await
+            .unwrap());
+        assert!(!repos
+            .namespace_default_tags()
+            .remove(namespace.id, "region")
+            .await
+            .unwrap());
+
+        let got = repos
+            .namespace_default_tags()
+            .list_by_namespace_id(namespace.id)
+            .await
+            .unwrap();
+        assert_eq!(got, [env]);
+
+        let got = repos
+            .namespace_default_tags()
+            .list_by_namespace_id(other_namespace.id)
+            .await
+            .unwrap();
+        assert_eq!(got, [other]);
+
+        // Default tags of unknown namespaces are rejected.
+        let mut bad = region;
+        bad.namespace_id = NamespaceId::new(i64::MAX);
+        assert!(repos.namespace_default_tags().upsert(&bad).await.is_err());
+    }
+
+    async fn test_query_history(catalog: Arc<dyn Catalog>) {
+        let mut repos = catalog.repositories().await;
+        let namespace = arbitrary_namespace(&mut *repos, "namespace_query_history").await;
+        let other_namespace = arbitrary_namespace(&mut *repos, "namespace_query_history_2").await;
+
+        let entry = |namespace: &Namespace, issue_time: i64| QueryHistoryEntry {
+            namespace_id: namespace.id,
+            issue_time: Timestamp::new(issue_time),
+            query_type: "sql".to_string(),
+            query_text_hash: QueryHistoryEntry::hash_query_text("SELECT 1"),
+            duration_ns: 42,
+            success: true,
+            partitions_scanned: Some(1),
+            bytes_scanned: Some(1024),
+            rows_returned: Some(1),
+            peak_memory_bytes: None,
+        };
+
+        assert!(repos
+            .query_history()
+            .list_by_namespace_id(namespace.id, 10)
+            .await
+            .unwrap()
+            .is_empty());
+
+        let a = entry(&namespace, 100);
+        let b = entry(&namespace, 200);
+        let c = entry(&namespace, 300);
+        let other = entry(&other_namespace, 100);
+        for e in [&a, &b, &c, &other] {
+            repos.query_history().record(e).await.unwrap();
+        }
+
+        // Most recent first, limited.
+        let got = repos
+            .query_history()
+            .list_by_namespace_id(namespace.id, 2)
+            .await
+            .unwrap();
+        assert_eq!(got, [c.clone(), b.clone()]);
+
+        // Entries issued before the cutoff are deleted from all namespaces.
+        let deleted = repos
+            .query_history()
+            .delete_older_than(Timestamp::new(200))
+            .await
+            .unwrap();
+        assert_eq!(deleted, 2);
+
+        let got = repos
+            .query_history()
+            .list_by_namespace_id(namespace.id, 10)
+            .await
+            .unwrap();
+        assert_eq!(got, [c, b]);
+        assert!(repos
+            .query_history()
+            .list_by_namespace_id(other_namespace.id, 10)
+            .await
+            .unwrap()
+            .is_empty());
+
+        // Entries of unknown namespaces are rejected.
+        let mut bad = a;
+        bad.namespace_id = NamespaceId::new(i64::MAX);
+        assert!(repos.query_history().record(&bad).await.is_err());
+    }
+
+    async fn test_column_retention(catalog: Arc<dyn Catalog>) {
+        let mut repos = catalog.repositories().await;
+        let namespace = arbitrary_namespace(&mut *repos, "namespace_column_retention").await;
+        let table = arbitrary_table(&mut *repos, "column_retention", &namespace).await;
+        let other_table = arbitrary_table(&mut *repos, "column_retention_2", &namespace).await;
+
+        let debug = repos
+            .columns()
+            .create_or_get("debug", table.id, ColumnType::String)
+            .await
+            .unwrap();
+        let trace = repos
+            .columns()
+            .create_or_get("trace", table.id, ColumnType::String)
+            .await
+            .unwrap();
+        let other = repos
+            .columns()
+            .create_or_get("debug", other_table.id, ColumnType::String)
+            .await
+            .unwrap();
+
+        let retention = |column: &Column, retention_period_ns: i64| ColumnRetention {
+            table_id: column.table_id,
+            column_id: column.id,
+            retention_period_ns,
+        };
+
+        assert!(repos
+            .column_retention()
+            .list_by_table_id(table.id)
+            .await
+            .unwrap()
+            .is_empty());
+
+        let debug_retention = retention(&debug, 3_600_000_000_000);
+        let trace_retention = retention(&trace, 60_000_000_000);
+        let other_retention = retention(&other, 60_000_000_000);
+        for r in [&trace_retention, &debug_retention, &other_retention] {
+            repos.column_retention().upsert(r).await.unwrap();
+        }
+
+        let got = repos
+            .column_retention()
+            .list_by_table_id(table.id)
+            .await
+            .unwrap();
+        assert_eq!(got, [debug_retention, trace_retention]);
+
+        // Upserting an existing column replaces its retention period.
+        let debug_retention = retention(&debug, 7_200_000_000_000);
+        repos
+            .column_retention()
+            .upsert(&debug_retention)
+            .await
+            .unwrap();
+
+        assert!(repos.column_retention().remove(trace.id).await.unwrap());
+        assert!(!repos.column_retention().remove(trace.id).await.unwrap());
+
+        let got = repos
+            .column_retention()
+            .list_by_table_id(table.id)
+            .await
+            .unwrap();
+        assert_eq!(got, [debug_retention]);
+
+        let got = repos
+            .column_retention()
+            .list_by_table_id(other_table.id)
+            .await
+            .unwrap();
+        assert_eq!(got, [other_retention]);
+
+        let got = repos
+            .column_retention()
+            .list_by_namespace_id(namespace.id)
+            .await
+            .unwrap();
+        assert_eq!(got, [debug_retention, other_retention]);
+
+        // Retention periods of unknown columns are rejected.
+        let mut bad = debug_retention;
+        bad.column_id = ColumnId::new(i64::MAX);
+        assert!(repos.column_retention().upsert(&bad).await.is_err());
+    }
+
+    async fn test_tasks(catalog: Arc<dyn Catalog>) {
+        let mut repos = catalog.repositories().await;
+        let namespace = arbitrary_namespace(&mut *repos, "namespace_tasks").await;
+        let other_namespace = arbitrary_namespace(&mut *repos, "namespace_tasks_2").await;
+
+        assert!(repos
+            .tasks()
+            .list_by_namespace_id(namespace.id)
+            .await
+            .unwrap()
+            .is_empty());
+
+        let hourly = repos
+            .tasks()
+            .create(
+                namespace.id,
+                "hourly",
+                "0 * * * *",
+                "SELECT 1",
+                "hourly_out",
+                2,
+            )
+            .await
+            .unwrap();
+        assert_eq!(hourly.namespace_id, namespace.id);
+        assert_eq!(hourly.name, "hourly");
+        assert_eq!(hourly.schedule, "0 * * * *");
+        assert_eq!(hourly.query, "SELECT 1");
+        assert_eq!(hourly.destination_table, "hourly_out");
+        assert_eq!(hourly.max_retries, 2);
+
+        let daily = repos
+            .tasks()
+            .create(
+                namespace.id,
+                "daily",
+                "0 0 * * *",
+                "SELECT 2",
+                "daily_out",
+                0,
+            )
+            .await
+            .unwrap();
+        let other = repos
+            .tasks()
+            .create(
+                other_namespace.id,
+                "hourly",
+                "0 * * * *",
+                "SELECT 3",
+                "out",
+                0,
+            )
+            .await
+            .unwrap();
+
+        // Task names are unique within a namespace.
+        let err = repos
+            .tasks()
+            .create(namespace.id, "hourly", "* * * * *", "SELECT 4", "out", 0)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::NameExists { .. }), "{err}");
+
+        let got = repos
+            .tasks()
+            .list_by_namespace_id(namespace.id)
+            .await
+            .unwrap();
+        assert_eq!(got, [daily.clone(), hourly.clone()]);
+
+        let mut got = repos.tasks().list().await.unwrap();
+        got.sort_unstable_by_key(|t| t.id);
+        assert_eq!(got, [hourly.clone(), daily.clone(), other.clone()]);
+
+        let got = repos
+            .tasks()
+            .get_by_namespace_and_name(other_namespace.id, "hourly")
+            .await
+            .unwrap();
+        assert_eq!(got, Some(other));
+        assert!(repos
+            .tasks()
+            .get_by_namespace_and_name(other_namespace.id, "daily")
+            .await
+            .unwrap()
+            .is_none());
+
+        // A scheduled run can only be started once.
+        let run = repos
+            .tasks()
+            .start_run(hourly.id, Timestamp::new(100))
+            .await
+            .unwrap()
+            .expect("run not started");
+        assert_eq!(run.task_id, hourly.id);
+        assert_eq!(run.scheduled_at, Timestamp::new(100));
+        assert_eq!(run.finished_at, None);
+        assert_eq!(run.status(), TaskRunStatus::Running);
+        assert!(repos
+            .tasks()
+            .start_run(hourly.id, Timestamp::new(100))
+            .await
+            .unwrap()
+            .is_none());
+
+        let run = repos
+            .tasks()
+            .finish_run(hourly.id, Timestamp::new(100), 1, 42, None)
+            .await
+            .unwrap();
+        assert_eq!(run.attempts, 1);
+        assert_eq!(run.rows_written, 42);
+        assert_eq!(run.status(), TaskRunStatus::Succeeded);
+
+        repos
+            .tasks()
+            .start_run(hourly.id, Timestamp::new(200))
+            .await
+            .unwrap()
+            .expect("run not started");
+        let failed = repos
+            .tasks()
+            .finish_run(hourly.id, Timestamp::new(200), 3, 0, Some("bananas"))
+            .await
+            .unwrap();
+        assert_eq!(failed.error.as_deref(), Some("bananas"));
+        assert_eq!(failed.status(), TaskRunStatus::Failed);
+
+        let got = repos.tasks().list_runs(hourly.id, 10).await.unwrap();
+        assert_eq!(got, [failed.clone(), run]);
+        let got = repos.tasks().list_runs(hourly.id, 1).await.unwrap();
+        assert_eq!(got, [failed]);
+        assert!(repos
+            .tasks()
+            .list_runs(daily.id, 10)
+            .await
+            .unwrap()
+            .is_empty());
+
+        // Finishing a run that was not started fails.
+        let err = repos
+            .tasks()
+            .finish_run(daily.id, Timestamp::new(100), 1, 0, None)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::TaskRunNotFound { .. }), "{err}");
+
+        // Only the unfinished runs started before the given time are failed.
+        let stuck = repos
+            .tasks()
+            .start_run(daily.id, Timestamp::new(300))
+            .await
+            .unwrap()
+            .expect("run not started");
+        assert!(repos
+            .tasks()
+            .fail_stale_runs(stuck.started_at, "stuck")
+            .await
+            .unwrap()
+            .is_empty());
+        let got = repos
+            .tasks()
+            .fail_stale_runs(Timestamp::new(stuck.started_at.get() + 1), "stuck")
+            .await
+            .unwrap();
+        assert_eq!(got.len(), 1);
+        assert_eq!(got[0].task_id, daily.id);
+        assert_eq!(got[0].scheduled_at, Timestamp::new(300));
+        assert_eq!(got[0].error.as_deref(), Some("stuck"));
+        assert_eq!(got[0].status(), TaskRunStatus::Failed);
+        assert!(repos
+            .tasks()
+            .fail_stale_runs(Timestamp::new(i64::MAX), "stuck")
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
     async fn test_delete_namespace(catalog: Arc<dyn Catalog>) {
         let mut repos = catalog.repositories().await;
         let namespace_1 =
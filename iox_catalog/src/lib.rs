@@ -423,6 +423,7 @@ pub mod test_helpers {
             created_at: Timestamp::new(1),
             column_set: ColumnSet::new([ColumnId::new(1), ColumnId::new(2)]),
             max_l0_created_at: Timestamp::new(1),
+            column_statistics: vec![],
         }
     }
 }
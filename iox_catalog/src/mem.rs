@@ -4,9 +4,10 @@
 use crate::interface::MAX_PARQUET_FILES_SELECTED_ONCE_FOR_DELETE;
 use crate::{
     interface::{
-        CasFailure, Catalog, ColumnRepo, ColumnTypeMismatchSnafu, Error, NamespaceRepo,
-        ParquetFileRepo, PartitionRepo, RepoCollection, Result, SoftDeletedRows, TableRepo,
-        MAX_PARQUET_FILES_SELECTED_ONCE_FOR_RETENTION,
+        CasFailure, Catalog, ColumnRepo, ColumnRetentionRepo, ColumnTypeMismatchSnafu, Error,
+        NamespaceDefaultTagRepo, NamespaceRepo, ParquetFileRepo, PartitionRepo, QueryHistoryRepo,
+        RepoCollection, Result, SoftDeletedRows, TableRepo, TableUsageRepo, TableViewRepo,
+        TaskRepo, MAX_PARQUET_FILES_SELECTED_ONCE_FOR_RETENTION,
     },
     metrics::MetricDecorator,
 };
@@ -16,10 +17,12 @@ use data_types::{
     partition_template::{
         NamespacePartitionTemplateOverride, TablePartitionTemplateOverride, TemplatePart,
     },
-    Column, ColumnId, ColumnType, CompactionLevel, MaxColumnsPerTable, MaxTables, Namespace,
-    NamespaceId, NamespaceName, NamespaceServiceProtectionLimitsOverride, ParquetFile,
-    ParquetFileId, ParquetFileParams, Partition, PartitionHashId, PartitionId, PartitionKey,
-    SkippedCompaction, Table, TableId, Timestamp, TransitionPartitionId,
+    Column, ColumnId, ColumnRetention, ColumnType, CompactionLevel, MaxColumnsPerTable, MaxTables,
+    Namespace, NamespaceDefaultTag, NamespaceId, NamespaceName,
+    NamespaceServiceProtectionLimitsOverride, ParquetFile, ParquetFileId, ParquetFileParams,
+    Partition, PartitionHashId, PartitionId, PartitionKey, QueryHistoryEntry, SkippedCompaction,
+    Table, TableId, TableUsage, TableViewColumn, Task, TaskId, TaskRun, Timestamp,
+    TransitionPartitionId,
 };
 use iox_time::{SystemProvider, TimeProvider};
 use snafu::ensure;
@@ -68,9 +71,17 @@ struct MemCollections {
     namespaces: Vec<Namespace>,
     tables: Vec<Table>,
     columns: Vec<Column>,
+    column_retention: Vec<ColumnRetention>,
     partitions: Vec<Partition>,
     skipped_compactions: Vec<SkippedCompaction>,
     parquet_files: Vec<ParquetFile>,
+    parquet_file_column_statistics: Vec<(ParquetFileId, ParquetFileColumnStatistics)>,
+    table_usage: Vec<TableUsage>,
+    table_view_columns: Vec<TableViewColumn>,
+    tasks: Vec<Task>,
+    task_runs: Vec<TaskRun>,
+    namespace_default_tags: Vec<NamespaceDefaultTag>,
+    query_history: Vec<QueryHistoryEntry>,
 }
 
 /// transaction bound to an in-memory catalog.
@@ -133,6 +144,10 @@ impl RepoCollection for MemTxn {
         self
     }
 
+    fn column_retention(&mut self) -> &mut dyn ColumnRetentionRepo {
+        self
+    }
+
     fn partitions(&mut self) -> &mut dyn PartitionRepo {
         self
     }
@@ -140,6 +155,26 @@ impl RepoCollection for MemTxn {
     fn parquet_files(&mut self) -> &mut dyn ParquetFileRepo {
         self
     }
+
+    fn table_usage(&mut self) -> &mut dyn TableUsageRepo {
+        self
+    }
+
+    fn table_views(&mut self) -> &mut dyn TableViewRepo {
+        self
+    }
+
+    fn tasks(&mut self) -> &mut dyn TaskRepo {
+        self
+    }
+
+    fn namespace_default_tags(&mut self) -> &mut dyn NamespaceDefaultTagRepo {
+        self
+    }
+
+    fn query_history(&mut self) -> &mut dyn QueryHistoryRepo {
+        self
+    }
 }
 
 #[async_trait]
@@ -174,6 +209,8 @@ impl NamespaceRepo for MemTxn {
             retention_period_ns,
             deleted_at: None,
             partition_template: partition_template.unwrap_or_default(),
+            read_only: false,
+            object_store_name: None,
         };
         stage.namespaces.push(namespace);
         Ok(stage.namespaces.last().unwrap().clone())
@@ -228,6 +265,19 @@ impl NamespaceRepo for MemTxn {
         }
     }
 
+    async fn undelete(&mut self, name: &str) -> Result<Namespace> {
+        let stage = self.stage();
+        match stage.namespaces.iter_mut().find(|n| n.name == name) {
+            Some(n) => {
+                n.deleted_at = None;
+                Ok(n.clone())
+            }
+            None => Err(Error::NamespaceNotFoundByName {
+                name: name.to_string(),
+            }),
+        }
+    }
+
     async fn update_table_limit(&mut self, name: &str, new_max: MaxTables) -> Result<Namespace> {
         let stage = self.stage();
         match stage.namespaces.iter_mut().find(|n| n.name == name) {
@@ -274,6 +324,36 @@ impl NamespaceRepo for MemTxn {
             }),
         }
     }
+
+    async fn update_read_only(&mut self, name: &str, read_only: bool) -> Result<Namespace> {
+        let stage = self.stage();
+        match stage.namespaces.iter_mut().find(|n| n.name == name) {
+            Some(n) => {
+                n.read_only = read_only;
+                Ok(n.clone())
+            }
+            None => Err(Error::NamespaceNotFoundByName {
+                name: name.to_string(),
+            }),
+        }
+    }
+
+    async fn update_object_store_name(
+        &mut self,
+        name: &str,
+        object_store_name: Option<&str>,
+    ) -> Result<Namespace> {
+        let stage = self.stage();
+        match stage.namespaces.iter_mut().find(|n| n.name == name) {
+            Some(n) => {
+                n.object_store_name = object_store_name.map(ToString::to_string);
+                Ok(n.clone())
+            }
+            None => Err(Error::NamespaceNotFoundByName {
+                name: name.to_string(),
+            }),
+        }
+    }
 }
 
 #[async_trait]
@@ -915,6 +995,9 @@ impl ParquetFileRepo for MemTxn {
         );
 
         stage.parquet_files = keep;
+        stage
+            .parquet_file_column_statistics
+            .retain(|(id, _)| !delete.iter().any(|f| f.id == *id));
 
         let delete = delete
             .into_iter()
@@ -1019,6 +1102,460 @@ impl ParquetFileRepo for MemTxn {
 
         Ok(ids)
     }
+
+    async fn list_column_statistics_by_table(
+        &mut self,
+        table_id: TableId,
+    ) -> Result<Vec<(ParquetFileId, ParquetFileColumnStatistics)>> {
+        let stage = self.stage();
+
+        Ok(stage
+            .parquet_file_column_statistics
+            .iter()
+            .filter(|(id, _)| {
+                stage
+                    .parquet_files
+                    .iter()
+                    .any(|f| f.id == *id && f.table_id == table_id && f.to_delete.is_none())
+            })
+            .cloned()
+            .collect())
+    }
+}
+
+#[async_trait]
+impl TableUsageRepo for MemTxn {
+    async fn record(&mut self, usage: &[TableUsage]) -> Result<()> {
+        let stage = self.stage();
+
+        for u in usage {
+            // Mirror the foreign key constraints of the SQL implementations.
+            if !stage.tables.iter().any(|t| t.id == u.table_id) {
+                return Err(Error::TableNotFound { id: u.table_id });
+            }
+
+            match stage
+                .table_usage
+                .iter_mut()
+                .find(|v| v.table_id == u.table_id && v.hour_start == u.hour_start)
+            {
+                Some(v) => {
+                    v.rows_buffered += u.rows_buffered;
+                    v.bytes_buffered += u.bytes_buffered;
+                    v.rows_persisted += u.rows_persisted;
+                    v.bytes_persisted += u.bytes_persisted;
+                }
+                None => stage.table_usage.push(*u),
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn list_by_namespace(
+        &mut self,
+        namespace_id: NamespaceId,
+        since: Timestamp,
+    ) -> Result<Vec<TableUsage>> {
+        let mut usage = self
+            .stage()
+            .table_usage
+            .iter()
+            .filter(|v| v.namespace_id == namespace_id && v.hour_start >= since)
+            .copied()
+            .collect::<Vec<_>>();
+
+        usage.sort_unstable_by_key(|v| (v.hour_start, v.table_id));
+        Ok(usage)
+    }
+}
+
+#[async_trait]
+impl ColumnRetentionRepo for MemTxn {
+    async fn upsert(&mut self, retention: &ColumnRetention) -> Result<()> {
+        let stage = self.stage();
+
+        // Mirror the foreign key constraints of the SQL implementations.
+        if !stage
+            .columns
+            .iter()
+            .any(|c| c.id == retention.column_id && c.table_id == retention.table_id)
+        {
+            return Err(Error::ColumnNotFound {
+                id: retention.column_id,
+            });
+        }
+
+        match stage
+            .column_retention
+            .iter_mut()
+            .find(|r| r.column_id == retention.column_id)
+        {
+            Some(r) => *r = *retention,
+            None => stage.column_retention.push(*retention),
+        }
+
+        Ok(())
+    }
+
+    async fn remove(&mut self, column_id: ColumnId) -> Result<bool> {
+        let stage = self.stage();
+        let len = stage.column_retention.len();
+        stage.column_retention.retain(|r| r.column_id != column_id);
+        Ok(stage.column_retention.len() != len)
+    }
+
+    async fn list_by_table_id(&mut self, table_id: TableId) -> Result<Vec<ColumnRetention>> {
+        let stage = self.stage();
+
+        let mut retention = stage
+            .column_retention
+            .iter()
+            .filter(|r| r.table_id == table_id)
+            .copied()
+            .collect::<Vec<_>>();
+
+        retention.sort_unstable_by_key(|r| r.column_id);
+        Ok(retention)
+    }
+
+    async fn list_by_namespace_id(
+        &mut self,
+        namespace_id: NamespaceId,
+    ) -> Result<Vec<ColumnRetention>> {
+        let stage = self.stage();
+
+        let table_ids = stage
+            .tables
+            .iter()
+            .filter(|t| t.namespace_id == namespace_id)
+            .map(|t| t.id)
+            .collect::<HashSet<_>>();
+
+        let mut retention = stage
+            .column_retention
+            .iter()
+            .filter(|r| table_ids.contains(&r.table_id))
+            .copied()
+            .collect::<Vec<_>>();
+
+        retention.sort_unstable_by_key(|r| (r.table_id, r.column_id));
+        Ok(retention)
+    }
+}
+
+#[async_trait]
+impl TableViewRepo for MemTxn {
+    async fn upsert(&mut self, column: &TableViewColumn) -> Result<()> {
+        let stage = self.stage();
+
+        // Mirror the foreign key constraints of the SQL implementations.
+        if !stage.tables.iter().any(|t| t.id == column.table_id) {
+            return Err(Error::TableNotFound {
+                id: column.table_id,
+            });
+        }
+
+        match stage
+            .table_view_columns
+            .iter_mut()
+            .find(|c| c.table_id == column.table_id && c.name == column.name)
+        {
+            Some(c) => *c = column.clone(),
+            None => stage.table_view_columns.push(column.clone()),
+        }
+
+        Ok(())
+    }
+
+    async fn remove(&mut self, table_id: TableId, name: &str) -> Result<bool> {
+        let stage = self.stage();
+        let len = stage.table_view_columns.len();
+        stage
+            .table_view_columns
+            .retain(|c| !(c.table_id == table_id && c.name == name));
+        Ok(stage.table_view_columns.len() != len)
+    }
+
+    async fn list_by_namespace_id(
+        &mut self,
+        namespace_id: NamespaceId,
+    ) -> Result<Vec<TableViewColumn>> {
+        let stage = self.stage();
+
+        let table_ids = stage
+            .tables
+            .iter()
+            .filter(|t| t.namespace_id == namespace_id)
+            .map(|t| t.id)
+            .collect::<HashSet<_>>();
+
+        let mut columns = stage
+            .table_view_columns
+            .iter()
+            .filter(|c| table_ids.contains(&c.table_id))
+            .cloned()
+            .collect::<Vec<_>>();
+
+        columns.sort_unstable_by(|a, b| (a.table_id, &a.name).cmp(&(b.table_id, &b.name)));
+        Ok(columns)
+    }
+}
+
+#[async_trait]
+impl TaskRepo for MemTxn {
+    async fn create(
+        &mut self,
+        namespace_id: NamespaceId,
+        name: &str,
+        schedule: &str,
+        query: &str,
+        destination_table: &str,
+        max_retries: i32,
+    ) -> Result<Task> {
+        let created_at = Timestamp::from(self.time_provider.now());
+        let stage = self.stage();
+
+        // Mirror the constraints of the SQL implementations.
+        if !stage.namespaces.iter().any(|n| n.id == namespace_id) {
+            return Err(Error::NamespaceNotFoundById { id: namespace_id });
+        }
+        if stage
+            .tasks
+            .iter()
+            .any(|t| t.namespace_id == namespace_id && t.name == name)
+        {
+            return Err(Error::NameExists {
+                name: name.to_string(),
+            });
+        }
+
+        let task = Task {
+            id: TaskId::new(stage.tasks.len() as i64 + 1),
+            namespace_id,
+            name: name.to_string(),
+            schedule: schedule.to_string(),
+            query: query.to_string(),
+            destination_table: destination_table.to_string(),
+            max_retries,
+            created_at,
+        };
+        stage.tasks.push(task.clone());
+
+        Ok(task)
+    }
+
+    async fn get_by_namespace_and_name(
+        &mut self,
+        namespace_id: NamespaceId,
+        name: &str,
+    ) -> Result<Option<Task>> {
+        Ok(self
+            .stage()
+            .tasks
+            .iter()
+            .find(|t| t.namespace_id == namespace_id && t.name == name)
+            .cloned())
+    }
+
+    async fn list_by_namespace_id(&mut self, namespace_id: NamespaceId) -> Result<Vec<Task>> {
+        let mut tasks = self
+            .stage()
+            .tasks
+            .iter()
+            .filter(|t| t.namespace_id == namespace_id)
+            .cloned()
+            .collect::<Vec<_>>();
+
+        tasks.sort_unstable_by(|a, b| a.name.cmp(&b.name));
+        Ok(tasks)
+    }
+
+    async fn list(&mut self) -> Result<Vec<Task>> {
+        Ok(self.stage().tasks.clone())
+    }
+
+    async fn start_run(
+        &mut self,
+        task_id: TaskId,
+        scheduled_at: Timestamp,
+    ) -> Result<Option<TaskRun>> {
+        let started_at = Timestamp::from(self.time_provider.now());
+        let stage = self.stage();
+
+        if stage
+            .task_runs
+            .iter()
+            .any(|r| r.task_id == task_id && r.scheduled_at == scheduled_at)
+        {
+            return Ok(None);
+        }
+
+        let run = TaskRun {
+            task_id,
+            scheduled_at,
+            started_at,
+            finished_at: None,
+            attempts: 0,
+            rows_written: 0,
+            error: None,
+        };
+        stage.task_runs.push(run.clone());
+
+        Ok(Some(run))
+    }
+
+    async fn finish_run(
+        &mut self,
+        task_id: TaskId,
+        scheduled_at: Timestamp,
+        attempts: i32,
+        rows_written: i64,
+        error: Option<&str>,
+    ) -> Result<TaskRun> {
+        let finished_at = Timestamp::from(self.time_provider.now());
+
+        let run = self
+            .stage()
+            .task_runs
+            .iter_mut()
+            .find(|r| r.task_id == task_id && r.scheduled_at == scheduled_at)
+            .ok_or(Error::TaskRunNotFound {
+                task_id,
+                scheduled_at,
+            })?;
+        run.finished_at = Some(finished_at);
+        run.attempts = attempts;
+        run.rows_written = rows_written;
+        run.error = error.map(ToString::to_string);
+
+        Ok(run.clone())
+    }
+
+    async fn list_runs(&mut self, task_id: TaskId, limit: i64) -> Result<Vec<TaskRun>> {
+        let mut runs = self
+            .stage()
+            .task_runs
+            .iter()
+            .filter(|r| r.task_id == task_id)
+            .cloned()
+            .collect::<Vec<_>>();
+
+        runs.sort_unstable_by_key(|r| std::cmp::Reverse(r.scheduled_at));
+        runs.truncate(limit.max(0) as usize);
+        Ok(runs)
+    }
+    async fn fail_stale_runs(
+        &mut self,
+        started_before: Timestamp,
+        error: &str,
+    ) -> Result<Vec<TaskRun>> {
+        let finished_at = Timestamp::from(self.time_provider.now());
+
+        Ok(self
+            .stage()
+            .task_runs
+            .iter_mut()
+            .filter(|r| r.finished_at.is_none() && r.started_at < started_before)
+            .map(|r| {
+                r.finished_at = Some(finished_at);
+                r.error = Some(error.to_string());
+                r.clone()
+            })
+            .collect())
+    }
+}
+
+#[async_trait]
+impl NamespaceDefaultTagRepo for MemTxn {
+    async fn upsert(&mut self, tag: &NamespaceDefaultTag) -> Result<()> {
+        let stage = self.stage();
+
+        // Mirror the foreign key constraints of the SQL implementations.
+        if !stage.namespaces.iter().any(|n| n.id == tag.namespace_id) {
+            return Err(Error::NamespaceNotFoundById {
+                id: tag.namespace_id,
+            });
+        }
+
+        match stage
+            .namespace_default_tags
+            .iter_mut()
+            .find(|t| t.namespace_id == tag.namespace_id && t.name == tag.name)
+        {
+            Some(t) => *t = tag.clone(),
+            None => stage.namespace_default_tags.push(tag.clone()),
+        }
+
+        Ok(())
+    }
+
+    async fn remove(&mut self, namespace_id: NamespaceId, name: &str) -> Result<bool> {
+        let stage = self.stage();
+        let len = stage.namespace_default_tags.len();
+        stage
+            .namespace_default_tags
+            .retain(|t| !(t.namespace_id == namespace_id && t.name == name));
+        Ok(stage.namespace_default_tags.len() != len)
+    }
+
+    async fn list_by_namespace_id(
+        &mut self,
+        namespace_id: NamespaceId,
+    ) -> Result<Vec<NamespaceDefaultTag>> {
+        let mut tags = self
+            .stage()
+            .namespace_default_tags
+            .iter()
+            .filter(|t| t.namespace_id == namespace_id)
+            .cloned()
+            .collect::<Vec<_>>();
+
+        tags.sort_unstable_by(|a, b| a.name.cmp(&b.name));
+        Ok(tags)
+    }
+}
+
+#[async_trait]
+impl QueryHistoryRepo for MemTxn {
+    async fn record(&mut self, entry: &QueryHistoryEntry) -> Result<()> {
+        let stage = self.stage();
+
+        // Mirror the foreign key constraints of the SQL implementations.
+        if !stage.namespaces.iter().any(|n| n.id == entry.namespace_id) {
+            return Err(Error::NamespaceNotFoundById {
+                id: entry.namespace_id,
+            });
+        }
+
+        stage.query_history.push(entry.clone());
+        Ok(())
+    }
+
+    async fn list_by_namespace_id(
+        &mut self,
+        namespace_id: NamespaceId,
+        limit: i64,
+    ) -> Result<Vec<QueryHistoryEntry>> {
+        let mut entries = self
+            .stage()
+            .query_history
+            .iter()
+            .filter(|e| e.namespace_id == namespace_id)
+            .cloned()
+            .collect::<Vec<_>>();
+
+        entries.sort_by(|a, b| b.issue_time.cmp(&a.issue_time));
+        entries.truncate(limit.max(0) as usize);
+        Ok(entries)
+    }
+
+    async fn delete_older_than(&mut self, older_than: Timestamp) -> Result<u64> {
+        let stage = self.stage();
+        let len = stage.query_history.len();
+        stage.query_history.retain(|e| e.issue_time >= older_than);
+        Ok((len - stage.query_history.len()) as u64)
+    }
 }
 
 fn filter_namespace_soft_delete<'a>(
@@ -1036,7 +1573,7 @@ fn filter_namespace_soft_delete<'a>(
 // They are also used by the respective create/flag_for_delete/update_compaction_level methods.
 async fn create_parquet_file(
     stage: &mut MemCollections,
-    parquet_file_params: ParquetFileParams,
+    mut parquet_file_params: ParquetFileParams,
 ) -> Result<ParquetFile> {
     if stage
         .parquet_files
@@ -1048,12 +1585,18 @@ async fn create_parquet_file(
         });
     }
 
+    let column_statistics = std::mem::take(&mut parquet_file_params.column_statistics);
     let parquet_file = ParquetFile::from_params(
         parquet_file_params,
         ParquetFileId::new(stage.parquet_files.len() as i64 + 1),
     );
     let created_at = parquet_file.created_at;
     let partition_id = parquet_file.partition_id.clone();
+    stage.parquet_file_column_statistics.extend(
+        column_statistics
+            .into_iter()
+            .map(|stats| (parquet_file.id, stats)),
+    );
     stage.parquet_files.push(parquet_file);
 
     // Update the new_file_at field its partition to the time of created_at
@@ -1,16 +1,19 @@
 //! Metric instrumentation for catalog implementations.
 
 use crate::interface::{
-    CasFailure, ColumnRepo, NamespaceRepo, ParquetFileRepo, PartitionRepo, RepoCollection, Result,
-    SoftDeletedRows, TableRepo,
+    CasFailure, ColumnRepo, ColumnRetentionRepo, NamespaceDefaultTagRepo, NamespaceRepo,
+    ParquetFileRepo, PartitionRepo, QueryHistoryRepo, RepoCollection, Result, SoftDeletedRows,
+    TableRepo, TableUsageRepo, TableViewRepo, TaskRepo,
 };
 use async_trait::async_trait;
 use data_types::{
     partition_template::{NamespacePartitionTemplateOverride, TablePartitionTemplateOverride},
-    Column, ColumnType, CompactionLevel, MaxColumnsPerTable, MaxTables, Namespace, NamespaceId,
-    NamespaceName, NamespaceServiceProtectionLimitsOverride, ParquetFile, ParquetFileId,
-    ParquetFileParams, Partition, PartitionHashId, PartitionId, PartitionKey, SkippedCompaction,
-    SortedColumnSet, Table, TableId, Timestamp, TransitionPartitionId,
+    Column, ColumnId, ColumnRetention, ColumnType, CompactionLevel, MaxColumnsPerTable, MaxTables,
+    Namespace, NamespaceDefaultTag, NamespaceId, NamespaceName,
+    NamespaceServiceProtectionLimitsOverride, ParquetFile, ParquetFileColumnStatistics,
+    ParquetFileId, ParquetFileParams, Partition, PartitionHashId, PartitionId, PartitionKey,
+    QueryHistoryEntry, SkippedCompaction, SortedColumnSet, Table, TableId, TableUsage,
+    TableViewColumn, Task, TaskId, TaskRun, Timestamp, TransitionPartitionId,
 };
 use iox_time::{SystemProvider, TimeProvider};
 use metric::{DurationHistogram, Metric};
@@ -43,7 +46,18 @@ impl<T> MetricDecorator<T> {
 
 impl<T, P> RepoCollection for MetricDecorator<T, P>
 where
-    T: NamespaceRepo + TableRepo + ColumnRepo + PartitionRepo + ParquetFileRepo + Debug,
+    T: NamespaceRepo
+        + TableRepo
+        + ColumnRepo
+        + ColumnRetentionRepo
+        + PartitionRepo
+        + ParquetFileRepo
+        + TableUsageRepo
+        + TableViewRepo
+        + TaskRepo
+        + NamespaceDefaultTagRepo
+        + QueryHistoryRepo
+        + Debug,
     P: TimeProvider,
 {
     fn namespaces(&mut self) -> &mut dyn NamespaceRepo {
@@ -58,6 +72,10 @@ where
         self
     }
 
+    fn column_retention(&mut self) -> &mut dyn ColumnRetentionRepo {
+        self
+    }
+
     fn partitions(&mut self) -> &mut dyn PartitionRepo {
         self
     }
@@ -65,6 +83,26 @@ where
     fn parquet_files(&mut self) -> &mut dyn ParquetFileRepo {
         self
     }
+
+    fn table_usage(&mut self) -> &mut dyn TableUsageRepo {
+        self
+    }
+
+    fn table_views(&mut self) -> &mut dyn TableViewRepo {
+        self
+    }
+
+    fn tasks(&mut self) -> &mut dyn TaskRepo {
+        self
+    }
+
+    fn namespace_default_tags(&mut self) -> &mut dyn NamespaceDefaultTagRepo {
+        self
+    }
+
+    fn query_history(&mut self) -> &mut dyn QueryHistoryRepo {
+        self
+    }
 }
 
 /// Emit a trait impl for `impl_trait` that delegates calls to the inner
@@ -139,8 +177,11 @@ decorate!(
         "namespace_get_by_id" = get_by_id(&mut self, id: NamespaceId, deleted: SoftDeletedRows) -> Result<Option<Namespace>>;
         "namespace_get_by_name" = get_by_name(&mut self, name: &str, deleted: SoftDeletedRows) -> Result<Option<Namespace>>;
         "namespace_soft_delete" = soft_delete(&mut self, name: &str) -> Result<()>;
+        "namespace_undelete" = undelete(&mut self, name: &str) -> Result<Namespace>;
         "namespace_update_table_limit" = update_table_limit(&mut self, name: &str, new_max: MaxTables) -> Result<Namespace>;
         "namespace_update_column_limit" = update_column_limit(&mut self, name: &str, new_max: MaxColumnsPerTable) -> Result<Namespace>;
+        "namespace_update_read_only" = update_read_only(&mut self, name: &str, read_only: bool) -> Result<Namespace>;
+        "namespace_update_object_store_name" = update_object_store_name(&mut self, name: &str, object_store_name: Option<&str>) -> Result<Namespace>;
     ]
 );
 
@@ -166,6 +207,16 @@ decorate!(
     ]
 );
 
+decorate!(
+    impl_trait = ColumnRetentionRepo,
+    methods = [
+        "column_retention_upsert" = upsert(&mut self, retention: &ColumnRetention) -> Result<()>;
+        "column_retention_remove" = remove(&mut self, column_id: ColumnId) -> Result<bool>;
+        "column_retention_list_by_table_id" = list_by_table_id(&mut self, table_id: TableId) -> Result<Vec<ColumnRetention>>;
+        "column_retention_list_by_namespace_id" = list_by_namespace_id(&mut self, namespace_id: NamespaceId) -> Result<Vec<ColumnRetention>>;
+    ]
+);
+
 decorate!(
     impl_trait = PartitionRepo,
     methods = [
@@ -200,5 +251,55 @@ decorate!(
         "parquet_get_by_object_store_id" = get_by_object_store_id(&mut self, object_store_id: Uuid) -> Result<Option<ParquetFile>>;
         "parquet_exists_by_object_store_id_batch" = exists_by_object_store_id_batch(&mut self, object_store_ids: Vec<Uuid>) -> Result<Vec<Uuid>>;
         "parquet_create_upgrade_delete" = create_upgrade_delete(&mut self, delete: &[ParquetFileId], upgrade: &[ParquetFileId], create: &[ParquetFileParams], target_level: CompactionLevel) -> Result<Vec<ParquetFileId>>;
+        "parquet_list_column_statistics_by_table" = list_column_statistics_by_table(&mut self, table_id: TableId) -> Result<Vec<(ParquetFileId, ParquetFileColumnStatistics)>>;
+    ]
+);
+
+decorate!(
+    impl_trait = TableUsageRepo,
+    methods = [
+        "table_usage_record" = record(&mut self, usage: &[TableUsage]) -> Result<()>;
+        "table_usage_list_by_namespace" = list_by_namespace(&mut self, namespace_id: NamespaceId, since: Timestamp) -> Result<Vec<TableUsage>>;
+    ]
+);
+
+decorate!(
+    impl_trait = TableViewRepo,
+    methods = [
+        "table_view_upsert" = upsert(&mut self, column: &TableViewColumn) -> Result<()>;
+        "table_view_remove" = remove(&mut self, table_id: TableId, name: &str) -> Result<bool>;
+        "table_view_list_by_namespace_id" = list_by_namespace_id(&mut self, namespace_id: NamespaceId) -> Result<Vec<TableViewColumn>>;
+    ]
+);
+
+decorate!(
+    impl_trait = TaskRepo,
+    methods = [
+        "task_create" = create(&mut self, namespace_id: NamespaceId, name: &str, schedule: &str, query: &str, destination_table: &str, max_retries: i32) -> Result<Task>;
+        "task_get_by_namespace_and_name" = get_by_namespace_and_name(&mut self, namespace_id: NamespaceId, name: &str) -> Result<Option<Task>>;
+        "task_list_by_namespace_id" = list_by_namespace_id(&mut self, namespace_id: NamespaceId) -> Result<Vec<Task>>;
+        "task_list" = list(&mut self) -> Result<Vec<Task>>;
+        "task_start_run" = start_run(&mut self, task_id: TaskId, scheduled_at: Timestamp) -> Result<Option<TaskRun>>;
+        "task_finish_run" = finish_run(&mut self, task_id: TaskId, scheduled_at: Timestamp, attempts: i32, rows_written: i64, error: Option<&str>) -> Result<TaskRun>;
+        "task_fail_stale_runs" = fail_stale_runs(&mut self, started_before: Timestamp, error: &str) -> Result<Vec<TaskRun>>;
+        "task_list_runs" = list_runs(&mut self, task_id: TaskId, limit: i64) -> Result<Vec<TaskRun>>;
+    ]
+);
+
+decorate!(
+    impl_trait = NamespaceDefaultTagRepo,
+    methods = [
+        "namespace_default_tag_upsert" = upsert(&mut self, tag: &NamespaceDefaultTag) -> Result<()>;
+        "namespace_default_tag_remove" = remove(&mut self, namespace_id: NamespaceId, name: &str) -> Result<bool>;
+        "namespace_default_tag_list_by_namespace_id" = list_by_namespace_id(&mut self, namespace_id: NamespaceId) -> Result<Vec<NamespaceDefaultTag>>;
+    ]
+);
+
+decorate!(
+    impl_trait = QueryHistoryRepo,
+    methods = [
+        "query_history_record" = record(&mut self, entry: &QueryHistoryEntry) -> Result<()>;
+        "query_history_list_by_namespace_id" = list_by_namespace_id(&mut self, namespace_id: NamespaceId, limit: i64) -> Result<Vec<QueryHistoryEntry>>;
+        "query_history_delete_older_than" = delete_older_than(&mut self, older_than: Timestamp) -> Result<u64>;
     ]
 );
@@ -2,9 +2,10 @@
 
 use crate::{
     interface::{
-        self, CasFailure, Catalog, ColumnRepo, ColumnTypeMismatchSnafu, Error, NamespaceRepo,
-        ParquetFileRepo, PartitionRepo, RepoCollection, Result, SoftDeletedRows, TableRepo,
-        MAX_PARQUET_FILES_SELECTED_ONCE_FOR_RETENTION,
+        self, CasFailure, Catalog, ColumnRepo, ColumnRetentionRepo, ColumnTypeMismatchSnafu, Error,
+        NamespaceDefaultTagRepo, NamespaceRepo, ParquetFileRepo, PartitionRepo, QueryHistoryRepo,
+        RepoCollection, Result, SoftDeletedRows, TableRepo, TableUsageRepo, TableViewRepo,
+        TaskRepo, MAX_PARQUET_FILES_SELECTED_ONCE_FOR_RETENTION,
     },
     kafkaless_transition::{
         SHARED_QUERY_POOL, SHARED_QUERY_POOL_ID, SHARED_TOPIC_ID, SHARED_TOPIC_NAME,
@@ -17,10 +18,12 @@ use data_types::{
     partition_template::{
         NamespacePartitionTemplateOverride, TablePartitionTemplateOverride, TemplatePart,
     },
-    Column, ColumnId, ColumnSet, ColumnType, CompactionLevel, MaxColumnsPerTable, MaxTables,
-    Namespace, NamespaceId, NamespaceName, NamespaceServiceProtectionLimitsOverride, ParquetFile,
+    Column, ColumnId, ColumnRetention, ColumnSet, ColumnType, CompactionLevel, MaxColumnsPerTable,
+    MaxTables, Namespace, NamespaceDefaultTag, NamespaceId, NamespaceName,
+    NamespaceServiceProtectionLimitsOverride, ParquetFile, ParquetFileColumnStatistics,
     ParquetFileId, ParquetFileParams, Partition, PartitionHashId, PartitionId, PartitionKey,
-    SkippedCompaction, SortedColumnSet, Table, TableId, Timestamp, TransitionPartitionId,
+    QueryHistoryEntry, SkippedCompaction, SortedColumnSet, Table, TableId, TableUsage,
+    TableViewColumn, Task, TaskId, TaskRun, Timestamp, TransitionPartitionId,
 };
 use serde::{Deserialize, Serialize};
 use std::{collections::HashMap, fmt::Display};
@@ -252,6 +255,10 @@ impl RepoCollection for SqliteTxn {
         self
     }
 
+    fn column_retention(&mut self) -> &mut dyn ColumnRetentionRepo {
+        self
+    }
+
     fn partitions(&mut self) -> &mut dyn PartitionRepo {
         self
     }
@@ -259,6 +266,25 @@ impl RepoCollection for SqliteTxn {
     fn parquet_files(&mut self) -> &mut dyn ParquetFileRepo {
         self
     }
+
+    fn table_usage(&mut self) -> &mut dyn TableUsageRepo {
+        self
+    }
+
+    fn table_views(&mut self) -> &mut dyn TableViewRepo {
+        self
+    }
+    fn tasks(&mut self) -> &mut dyn TaskRepo {
+        self
+    }
+
+    fn namespace_default_tags(&mut self) -> &mut dyn NamespaceDefaultTagRepo {
+        self
+    }
+
+    fn query_history(&mut self) -> &mut dyn QueryHistoryRepo {
+        self
+    }
 }
 
 #[async_trait]
@@ -282,7 +308,7 @@ impl NamespaceRepo for SqliteTxn {
 INSERT INTO namespace ( name, topic_id, query_pool_id, retention_period_ns, max_tables, max_columns_per_table, partition_template )
 VALUES ( $1, $2, $3, $4, $5, $6, $7 )
 RETURNING id, name, retention_period_ns, max_tables, max_columns_per_table, deleted_at,
-          partition_template;
+          partition_template, read_only, object_store_name;
             "#,
         )
         .bind(name.as_str()) // $1
@@ -313,7 +339,7 @@ RETURNING id, name, retention_period_ns, max_tables, max_columns_per_table, dele
             format!(
                 r#"
 SELECT id, name, retention_period_ns, max_tables, max_columns_per_table, deleted_at,
-       partition_template
+       partition_template, read_only, object_store_name
 FROM namespace
 WHERE {v};
                 "#,
@@ -337,7 +363,7 @@ WHERE {v};
             format!(
                 r#"
 SELECT id, name, retention_period_ns, max_tables, max_columns_per_table, deleted_at,
-       partition_template
+       partition_template, read_only, object_store_name
 FROM namespace
 WHERE id=$1 AND {v};
                 "#,
@@ -367,7 +393,7 @@ WHERE id=$1 AND {v};
             format!(
                 r#"
 SELECT id, name, retention_period_ns, max_tables, max_columns_per_table, deleted_at,
-       partition_template
+       partition_template, read_only, object_store_name
 FROM namespace
 WHERE name=$1 AND {v};
                 "#,
@@ -401,6 +427,30 @@ WHERE name=$1 AND {v};
             .map(|_| ())
     }
 
+    async fn undelete(&mut self, name: &str) -> Result<Namespace> {
+        let rec = sqlx::query_as::<_, Namespace>(
+            r#"
+UPDATE namespace
+SET deleted_at = NULL
+WHERE name = $1
+RETURNING id, name, retention_period_ns, max_tables, max_columns_per_table, deleted_at,
+          partition_template, read_only, object_store_name;
+        "#,
+        )
+        .bind(name) // $1
+        .fetch_one(self.inner.get_mut())
+        .await;
+
+        let namespace = rec.map_err(|e| match e {
+            sqlx::Error::RowNotFound => Error::NamespaceNotFoundByName {
+                name: name.to_string(),
+            },
+            _ => Error::SqlxError { source: e },
+        })?;
+
+        Ok(namespace)
+    }
+
     async fn update_table_limit(&mut self, name: &str, new_max: MaxTables) -> Result<Namespace> {
         let rec = sqlx::query_as::<_, Namespace>(
             r#"
@@ -408,7 +458,7 @@ UPDATE namespace
 SET max_tables = $1
 WHERE name = $2
 RETURNING id, name, retention_period_ns, max_tables, max_columns_per_table, deleted_at,
-          partition_template;
+          partition_template, read_only, object_store_name;
         "#,
         )
         .bind(new_max)
@@ -437,7 +487,7 @@ UPDATE namespace
 SET max_columns_per_table = $1
 WHERE name = $2
 RETURNING id, name, retention_period_ns, max_tables, max_columns_per_table, deleted_at,
-          partition_template;
+          partition_template, read_only, object_store_name;
         "#,
         )
         .bind(new_max)
@@ -466,7 +516,7 @@ UPDATE namespace
 SET retention_period_ns = $1
 WHERE name = $2
 RETURNING id, name, retention_period_ns, max_tables, max_columns_per_table, deleted_at,
-          partition_template;
+          partition_template, read_only, object_store_name;
             "#,
         )
         .bind(retention_period_ns) // $1
@@ -483,6 +533,60 @@ RETURNING id, name, retention_period_ns, max_tables, max_columns_per_table, dele
 
         Ok(namespace)
     }
+
+    async fn update_read_only(&mut self, name: &str, read_only: bool) -> Result<Namespace> {
+        let rec = sqlx::query_as::<_, Namespace>(
+            r#"
+UPDATE namespace
+SET read_only = $1
+WHERE name = $2
+RETURNING id, name, retention_period_ns, max_tables, max_columns_per_table, deleted_at,
+          partition_template, read_only, object_store_name;
+        "#,
+        )
+        .bind(read_only) // $1
+        .bind(name) // $2
+        .fetch_one(self.inner.get_mut())
+        .await;
+
+        let namespace = rec.map_err(|e| match e {
+            sqlx::Error::RowNotFound => Error::NamespaceNotFoundByName {
+                name: name.to_string(),
+            },
+            _ => Error::SqlxError { source: e },
+        })?;
+
+        Ok(namespace)
+    }
+
+    async fn update_object_store_name(
+        &mut self,
+        name: &str,
+        object_store_name: Option<&str>,
+    ) -> Result<Namespace> {
+        let rec = sqlx::query_as::<_, Namespace>(
+            r#"
+UPDATE namespace
+SET object_store_name = $1
+WHERE name = $2
+RETURNING id, name, retention_period_ns, max_tables, max_columns_per_table, deleted_at,
+          partition_template, read_only, object_store_name;
+        "#,
+        )
+        .bind(object_store_name) // $1
+        .bind(name) // $2
+        .fetch_one(self.inner.get_mut())
+        .await;
+
+        let namespace = rec.map_err(|e| match e {
+            sqlx::Error::RowNotFound => Error::NamespaceNotFoundByName {
+                name: name.to_string(),
+            },
+            _ => Error::SqlxError { source: e },
+        })?;
+
+        Ok(namespace)
+    }
 }
 
 /// [`TableRepo::create`] needs the ability to create some columns within the same transaction as
@@ -1306,8 +1410,28 @@ impl From<ParquetFilePod> for ParquetFile {
 #[async_trait]
 impl ParquetFileRepo for SqliteTxn {
     async fn create(&mut self, parquet_file_params: ParquetFileParams) -> Result<ParquetFile> {
-        let executor = self.inner.get_mut();
-        create_parquet_file(executor, parquet_file_params).await
+        if parquet_file_params.column_statistics.is_empty() {
+            let executor = self.inner.get_mut();
+            return create_parquet_file(executor, parquet_file_params).await;
+        }
+
+        // Record the file and its column statistics atomically.
+        let mut tx = self
+            .inner
+            .get_mut()
+            .pool
+            .begin()
+            .await
+            .map_err(|e| Error::StartTransaction { source: e })?;
+
+        let column_statistics = parquet_file_params.column_statistics.clone();
+        let parquet_file = create_parquet_file(&mut *tx, parquet_file_params).await?;
+        insert_column_statistics(&mut *tx, parquet_file.id, &column_statistics).await?;
+
+        tx.commit()
+            .await
+            .map_err(|e| Error::FailedToCommit { source: e })?;
+        Ok(parquet_file)
     }
 
     async fn list_all(&mut self) -> Result<Vec<ParquetFile>> {
@@ -1560,6 +1684,7 @@ WHERE object_store_id IN ({v});",
         let mut ids = Vec::with_capacity(create.len());
         for file in create {
             let res = create_parquet_file(&mut *tx, file.clone()).await?;
+            insert_column_statistics(&mut *tx, res.id, &file.column_statistics).await?;
             ids.push(res.id);
         }
         tx.commit()
@@ -1568,6 +1693,560 @@ WHERE object_store_id IN ({v});",
 
         Ok(ids)
     }
+
+    async fn list_column_statistics_by_table(
+        &mut self,
+        table_id: TableId,
+    ) -> Result<Vec<(ParquetFileId, ParquetFileColumnStatistics)>> {
+        let rows =
+            sqlx::query_as::<_, (ParquetFileId, ColumnId, Option<String>, Option<String>, i64)>(
+                r#"
+SELECT parquet_file_column_statistics.parquet_file_id, parquet_file_column_statistics.column_id,
+       parquet_file_column_statistics.min_value, parquet_file_column_statistics.max_value,
+       parquet_file_column_statistics.null_count
+FROM parquet_file_column_statistics
+INNER JOIN parquet_file ON parquet_file.id = parquet_file_column_statistics.parquet_file_id
+WHERE parquet_file.table_id = $1
+  AND parquet_file.to_delete IS NULL;
+             "#,
+            )
+            .bind(table_id) // $1
+            .fetch_all(self.inner.get_mut())
+            .await
+            .map_err(|e| Error::SqlxError { source: e })?;
+
+        Ok(rows
+            .into_iter()
+            .map(|(id, column_id, min_value, max_value, null_count)| {
+                (
+                    id,
+                    ParquetFileColumnStatistics {
+                        column_id,
+                        min_value,
+                        max_value,
+                        null_count,
+                    },
+                )
+            })
+            .collect())
+    }
+}
+
+#[async_trait]
+impl TableUsageRepo for SqliteTxn {
+    async fn record(&mut self, usage: &[TableUsage]) -> Result<()> {
+        for u in usage {
+            sqlx::query(
+                r#"
+INSERT INTO table_usage
+    ( namespace_id, table_id, hour_start, rows_buffered, bytes_buffered, rows_persisted, bytes_persisted )
+VALUES
+    ( $1, $2, $3, $4, $5, $6, $7 )
+ON CONFLICT ( table_id, hour_start )
+DO UPDATE
+SET
+rows_buffered = table_usage.rows_buffered + EXCLUDED.rows_buffered,
+bytes_buffered = table_usage.bytes_buffered + EXCLUDED.bytes_buffered,
+rows_persisted = table_usage.rows_persisted + EXCLUDED.rows_persisted,
+bytes_persisted = table_usage.bytes_persisted + EXCLUDED.bytes_persisted;
+        "#,
+            )
+            .bind(u.namespace_id) // $1
+            .bind(u.table_id) // $2
+            .bind(u.hour_start) // $3
+            .bind(u.rows_buffered) // $4
+            .bind(u.bytes_buffered) // $5
+            .bind(u.rows_persisted) // $6
+            .bind(u.bytes_persisted) // $7
+            .execute(self.inner.get_mut())
+            .await
+            .map_err(|e| {
+                if is_fk_violation(&e) {
+                    Error::ForeignKeyViolation { source: e }
+                } else {
+                    Error::SqlxError { source: e }
+                }
+            })?;
+        }
+
+        Ok(())
+    }
+
+    async fn list_by_namespace(
+        &mut self,
+        namespace_id: NamespaceId,
+        since: Timestamp,
+    ) -> Result<Vec<TableUsage>> {
+        sqlx::query_as::<_, TableUsage>(
+            r#"
+SELECT namespace_id, table_id, hour_start, rows_buffered, bytes_buffered, rows_persisted, bytes_persisted
+FROM table_usage
+WHERE namespace_id = $1 AND hour_start >= $2
+ORDER BY hour_start, table_id;
+        "#,
+        )
+        .bind(namespace_id) // $1
+        .bind(since) // $2
+        .fetch_all(self.inner.get_mut())
+        .await
+        .map_err(|e| Error::SqlxError { source: e })
+    }
+}
+
+#[async_trait]
+impl ColumnRetentionRepo for SqliteTxn {
+    async fn upsert(&mut self, retention: &ColumnRetention) -> Result<()> {
+        sqlx::query(
+            r#"
+INSERT INTO column_retention ( table_id, column_id, retention_period_ns )
+VALUES ( $1, $2, $3 )
+ON CONFLICT ( column_id )
+DO UPDATE SET retention_period_ns = EXCLUDED.retention_period_ns;
+        "#,
+        )
+        .bind(retention.table_id) // $1
+        .bind(retention.column_id) // $2
+        .bind(retention.retention_period_ns) // $3
+        .execute(self.inner.get_mut())
+        .await
+        .map_err(|e| {
+            if is_fk_violation(&e) {
+                Error::ForeignKeyViolation { source: e }
+            } else {
+                Error::SqlxError { source: e }
+            }
+        })?;
+
+        Ok(())
+    }
+
+    async fn remove(&mut self, column_id: ColumnId) -> Result<bool> {
+        let res = sqlx::query(
+            r#"
+DELETE FROM column_retention
+WHERE column_id = $1;
+        "#,
+        )
+        .bind(column_id) // $1
+        .execute(self.inner.get_mut())
+        .await
+        .map_err(|e| Error::SqlxError { source: e })?;
+
+        Ok(res.rows_affected() > 0)
+    }
+
+    async fn list_by_table_id(&mut self, table_id: TableId) -> Result<Vec<ColumnRetention>> {
+        sqlx::query_as::<_, ColumnRetention>(
+            r#"
+SELECT table_id, column_id, retention_period_ns FROM column_retention
+WHERE table_id = $1
+ORDER BY column_id;
+        "#,
+        )
+        .bind(table_id) // $1
+        .fetch_all(self.inner.get_mut())
+        .await
+        .map_err(|e| Error::SqlxError { source: e })
+    }
+
+    async fn list_by_namespace_id(
+        &mut self,
+        namespace_id: NamespaceId,
+    ) -> Result<Vec<ColumnRetention>> {
+        sqlx::query_as::<_, ColumnRetention>(
+            r#"
+SELECT column_retention.table_id, column_retention.column_id, column_retention.retention_period_ns
+FROM table_name
+INNER JOIN column_retention ON column_retention.table_id = table_name.id
+WHERE table_name.namespace_id = $1
+ORDER BY column_retention.table_id, column_retention.column_id;
+        "#,
+        )
+        .bind(namespace_id) // $1
+        .fetch_all(self.inner.get_mut())
+        .await
+        .map_err(|e| Error::SqlxError { source: e })
+    }
+}
+
+#[async_trait]
+impl TableViewRepo for SqliteTxn {
+    async fn upsert(&mut self, column: &TableViewColumn) -> Result<()> {
+        sqlx::query(
+            r#"
+INSERT INTO table_view_column ( table_id, name, expression, unit )
+VALUES ( $1, $2, $3, $4 )
+ON CONFLICT ( table_id, name )
+DO UPDATE SET expression = EXCLUDED.expression, unit = EXCLUDED.unit;
+        "#,
+        )
+        .bind(column.table_id) // $1
+        .bind(&column.name) // $2
+        .bind(&column.expression) // $3
+        .bind(&column.unit) // $4
+        .execute(self.inner.get_mut())
+        .await
+        .map_err(|e| {
+            if is_fk_violation(&e) {
+                Error::ForeignKeyViolation { source: e }
+            } else {
+                Error::SqlxError { source: e }
+            }
+        })?;
+
+        Ok(())
+    }
+
+    async fn remove(&mut self, table_id: TableId, name: &str) -> Result<bool> {
+        let res = sqlx::query(
+            r#"
+DELETE FROM table_view_column
+WHERE table_id = $1 AND name = $2;
+        "#,
+        )
+        .bind(table_id) // $1
+        .bind(name) // $2
+        .execute(self.inner.get_mut())
+        .await
+        .map_err(|e| Error::SqlxError { source: e })?;
+
+        Ok(res.rows_affected() > 0)
+    }
+
+    async fn list_by_namespace_id(
+        &mut self,
+        namespace_id: NamespaceId,
+    ) -> Result<Vec<TableViewColumn>> {
+        sqlx::query_as::<_, TableViewColumn>(
+            r#"
+SELECT table_view_column.* FROM table_name
+INNER JOIN table_view_column ON table_view_column.table_id = table_name.id
+WHERE table_name.namespace_id = $1
+ORDER BY table_view_column.table_id, table_view_column.name;
+        "#,
+        )
+        .bind(namespace_id) // $1
+        .fetch_all(self.inner.get_mut())
+        .await
+        .map_err(|e| Error::SqlxError { source: e })
+    }
+}
+
+#[async_trait]
+impl TaskRepo for SqliteTxn {
+    async fn create(
+        &mut self,
+        namespace_id: NamespaceId,
+        name: &str,
+        schedule: &str,
+        query: &str,
+        destination_table: &str,
+        max_retries: i32,
+    ) -> Result<Task> {
+        let created_at = Timestamp::from(self.time_provider.now());
+
+        sqlx::query_as::<_, Task>(
+            r#"
+INSERT INTO task ( namespace_id, name, schedule, query, destination_table, max_retries, created_at )
+VALUES ( $1, $2, $3, $4, $5, $6, $7 )
+RETURNING *;
+        "#,
+        )
+        .bind(namespace_id) // $1
+        .bind(name) // $2
+        .bind(schedule) // $3
+        .bind(query) // $4
+        .bind(destination_table) // $5
+        .bind(max_retries) // $6
+        .bind(created_at) // $7
+        .fetch_one(self.inner.get_mut())
+        .await
+        .map_err(|e| {
+            if is_unique_violation(&e) {
+                Error::NameExists {
+                    name: name.to_string(),
+                }
+            } else if is_fk_violation(&e) {
+                Error::ForeignKeyViolation { source: e }
+            } else {
+                Error::SqlxError { source: e }
+            }
+        })
+    }
+
+    async fn get_by_namespace_and_name(
+        &mut self,
+        namespace_id: NamespaceId,
+        name: &str,
+    ) -> Result<Option<Task>> {
+        sqlx::query_as::<_, Task>(
+            r#"
+SELECT * FROM task
+WHERE namespace_id = $1 AND name = $2;
+        "#,
+        )
+        .bind(namespace_id) // $1
+        .bind(name) // $2
+        .fetch_optional(self.inner.get_mut())
+        .await
+        .map_err(|e| Error::SqlxError { source: e })
+    }
+
+    async fn list_by_namespace_id(&mut self, namespace_id: NamespaceId) -> Result<Vec<Task>> {
+        sqlx::query_as::<_, Task>(
+            r#"
+SELECT * FROM task
+WHERE namespace_id = $1
+ORDER BY name;
+        "#,
+        )
+        .bind(namespace_id) // $1
+        .fetch_all(self.inner.get_mut())
+        .await
+        .map_err(|e| Error::SqlxError { source: e })
+    }
+
+    async fn list(&mut self) -> Result<Vec<Task>> {
+        sqlx::query_as::<_, Task>("SELECT * FROM task;")
+            .fetch_all(self.inner.get_mut())
+            .await
+            .map_err(|e| Error::SqlxError { source: e })
+    }
+
+    async fn start_run(
+        &mut self,
+        task_id: TaskId,
+        scheduled_at: Timestamp,
+    ) -> Result<Option<TaskRun>> {
+        let started_at = Timestamp::from(self.time_provider.now());
+
+        sqlx::query_as::<_, TaskRun>(
+            r#"
+INSERT INTO task_run ( task_id, scheduled_at, started_at )
+VALUES ( $1, $2, $3 )
+ON CONFLICT ( task_id, scheduled_at ) DO NOTHING
+RETURNING *;
+        "#,
+        )
+        .bind(task_id) // $1
+        .bind(scheduled_at) // $2
+        .bind(started_at) // $3
+        .fetch_optional(self.inner.get_mut())
+        .await
+        .map_err(|e| {
+            if is_fk_violation(&e) {
+                Error::ForeignKeyViolation { source: e }
+            } else {
+                Error::SqlxError { source: e }
+            }
+        })
+    }
+
+    async fn finish_run(
+        &mut self,
+        task_id: TaskId,
+        scheduled_at: Timestamp,
+        attempts: i32,
+        rows_written: i64,
+        error: Option<&str>,
+    ) -> Result<TaskRun> {
+        let finished_at = Timestamp::from(self.time_provider.now());
+
+        sqlx::query_as::<_, TaskRun>(
+            r#"
+UPDATE task_run
+SET finished_at = $3, attempts = $4, rows_written = $5, error = $6
+WHERE task_id = $1 AND scheduled_at = $2
+RETURNING *;
+        "#,
+        )
+        .bind(task_id) // $1
+        .bind(scheduled_at) // $2
+        .bind(finished_at) // $3
+        .bind(attempts) // $4
+        .bind(rows_written) // $5
+        .bind(error) // $6
+        .fetch_optional(self.inner.get_mut())
+        .await
+        .map_err(|e| Error::SqlxError { source: e })?
+        .ok_or(Error::TaskRunNotFound {
+            task_id,
+            scheduled_at,
+        })
+    }
+
+    async fn list_runs(&mut self, task_id: TaskId, limit: i64) -> Result<Vec<TaskRun>> {
+        sqlx::query_as::<_, TaskRun>(
+            r#"
+SELECT * FROM task_run
+WHERE task_id = $1
+ORDER BY scheduled_at DESC
+LIMIT $2;
+        "#,
+        )
+        .bind(task_id) // $1
+        .bind(limit) // $2
+        .fetch_all(self.inner.get_mut())
+        .await
+        .map_err(|e| Error::SqlxError { source: e })
+    }
+
+    async fn fail_stale_runs(
+        &mut self,
+        started_before: Timestamp,
+        error: &str,
+    ) -> Result<Vec<TaskRun>> {
+        let finished_at = Timestamp::from(self.time_provider.now());
+
+        sqlx::query_as::<_, TaskRun>(
+            r#"
+UPDATE task_run
+SET finished_at = $2, error = $3
+WHERE finished_at IS NULL AND started_at < $1
+RETURNING *;
+        "#,
+        )
+        .bind(started_before) // $1
+        .bind(finished_at) // $2
+        .bind(error) // $3
+        .fetch_all(self.inner.get_mut())
+        .await
+        .map_err(|e| Error::SqlxError { source: e })
+    }
+}
+
+#[async_trait]
+impl NamespaceDefaultTagRepo for SqliteTxn {
+    async fn upsert(&mut self, tag: &NamespaceDefaultTag) -> Result<()> {
+        sqlx::query(
+            r#"
+INSERT INTO namespace_default_tag ( namespace_id, name, value )
+VALUES ( $1, $2, $3 )
+ON CONFLICT ( namespace_id, name )
+DO UPDATE SET value = EXCLUDED.value;
+        "#,
+        )
+        .bind(tag.namespace_id) // $1
+        .bind(&tag.name) // $2
+        .bind(&tag.value) // $3
+        .execute(self.inner.get_mut())
+        .await
+        .map_err(|e| {
+            if is_fk_violation(&e) {
+                Error::ForeignKeyViolation { source: e }
+            } else {
+                Error::SqlxError { source: e }
+            }
+        })?;
+
+        Ok(())
+    }
+
+    async fn remove(&mut self, namespace_id: NamespaceId, name: &str) -> Result<bool> {
+        let res = sqlx::query(
+            r#"
+DELETE FROM namespace_default_tag
+WHERE namespace_id = $1 AND name = $2;
+        "#,
+        )
+        .bind(namespace_id) // $1
+        .bind(name) // $2
+        .execute(self.inner.get_mut())
+        .await
+        .map_err(|e| Error::SqlxError { source: e })?;
+
+        Ok(res.rows_affected() > 0)
+    }
+
+    async fn list_by_namespace_id(
+        &mut self,
+        namespace_id: NamespaceId,
+    ) -> Result<Vec<NamespaceDefaultTag>> {
+        sqlx::query_as::<_, NamespaceDefaultTag>(
+            r#"
+SELECT namespace_id, name, value FROM namespace_default_tag
+WHERE namespace_id = $1
+ORDER BY name;
+        "#,
+        )
+        .bind(namespace_id) // $1
+        .fetch_all(self.inner.get_mut())
+        .await
+        .map_err(|e| Error::SqlxError { source: e })
+    }
+}
+
+#[async_trait]
+impl QueryHistoryRepo for SqliteTxn {
+    async fn record(&mut self, entry: &QueryHistoryEntry) -> Result<()> {
+        sqlx::query(
+            r#"
+INSERT INTO query_history (
+    namespace_id, issue_time, query_type, query_text_hash, duration_ns, success,
+    partitions_scanned, bytes_scanned, rows_returned, peak_memory_bytes
+)
+VALUES ( $1, $2, $3, $4, $5, $6, $7, $8, $9, $10 );
+        "#,
+        )
+        .bind(entry.namespace_id) // $1
+        .bind(entry.issue_time) // $2
+        .bind(&entry.query_type) // $3
+        .bind(entry.query_text_hash) // $4
+        .bind(entry.duration_ns) // $5
+        .bind(entry.success) // $6
+        .bind(entry.partitions_scanned) // $7
+        .bind(entry.bytes_scanned) // $8
+        .bind(entry.rows_returned) // $9
+        .bind(entry.peak_memory_bytes) // $10
+        .execute(self.inner.get_mut())
+        .await
+        .map_err(|e| {
+            if is_fk_violation(&e) {
+                Error::ForeignKeyViolation { source: e }
+            } else {
+                Error::SqlxError { source: e }
+            }
+        })?;
+
+        Ok(())
+    }
+
+    async fn list_by_namespace_id(
+        &mut self,
+        namespace_id: NamespaceId,
+        limit: i64,
+    ) -> Result<Vec<QueryHistoryEntry>> {
+        sqlx::query_as::<_, QueryHistoryEntry>(
+            r#"
+SELECT namespace_id, issue_time, query_type, query_text_hash, duration_ns, success,
+       partitions_scanned, bytes_scanned, rows_returned, peak_memory_bytes
+FROM query_history
+WHERE namespace_id = $1
+ORDER BY issue_time DESC
+LIMIT $2;
+        "#,
+        )
+        .bind(namespace_id) // $1
+        .bind(limit) // $2
+        .fetch_all(self.inner.get_mut())
+        .await
+        .map_err(|e| Error::SqlxError { source: e })
+    }
+
+    async fn delete_older_than(&mut self, older_than: Timestamp) -> Result<u64> {
+        let res = sqlx::query(
+            r#"
+DELETE FROM query_history
+WHERE issue_time < $1;
+        "#,
+        )
+        .bind(older_than) // $1
+        .execute(self.inner.get_mut())
+        .await
+        .map_err(|e| Error::SqlxError { source: e })?;
+
+        Ok(res.rows_affected())
+    }
 }
 
 // The following three functions are helpers to the create_upgrade_delete method.
@@ -1592,6 +2271,8 @@ where
         created_at,
         column_set,
         max_l0_created_at,
+        // recorded by insert_column_statistics once the file ID is known
+        column_statistics: _,
     } = parquet_file_params;
 
     let (partition_id, partition_hash_id) = match partition_id {
@@ -1641,6 +2322,53 @@ RETURNING
     Ok(rec.into())
 }
 
+async fn insert_column_statistics<'q, E>(
+    executor: E,
+    parquet_file_id: ParquetFileId,
+    column_statistics: &[ParquetFileColumnStatistics],
+) -> Result<()>
+where
+    E: Executor<'q, Database = Sqlite>,
+{
+    if column_statistics.is_empty() {
+        return Ok(());
+    }
+
+    #[derive(Serialize)]
+    struct ColumnStatistics<'a> {
+        column_id: i64,
+        min_value: Option<&'a str>,
+        max_value: Option<&'a str>,
+        null_count: i64,
+    }
+    let stats = column_statistics
+        .iter()
+        .map(|s| ColumnStatistics {
+            column_id: s.column_id.get(),
+            min_value: s.min_value.as_deref(),
+            max_value: s.max_value.as_deref(),
+            null_count: s.null_count,
+        })
+        .collect::<Vec<_>>();
+
+    sqlx::query(
+        r#"
+INSERT INTO parquet_file_column_statistics
+    ( parquet_file_id, column_id, min_value, max_value, null_count )
+SELECT $1, a.value ->> 'column_id', a.value ->> 'min_value', a.value ->> 'max_value',
+       a.value ->> 'null_count'
+FROM json_each($2) as a;
+        "#,
+    )
+    .bind(parquet_file_id) // $1
+    .bind(&Json(stats)) // $2
+    .execute(executor)
+    .await
+    .map_err(|e| Error::SqlxError { source: e })?;
+
+    Ok(())
+}
+
 async fn flag_for_delete<'q, E>(executor: E, id: ParquetFileId, marked_at: Timestamp) -> Result<()>
 where
     E: Executor<'q, Database = Sqlite>,
@@ -2131,7 +2859,7 @@ INSERT INTO namespace (
 )
 VALUES ( $1, $2, $3, $4, NULL )
 RETURNING id, name, retention_period_ns, max_tables, max_columns_per_table, deleted_at,
-          partition_template;
+          partition_template, read_only, object_store_name;
             "#,
         )
         .bind(namespace_name) // $1
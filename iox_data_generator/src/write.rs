@@ -355,7 +355,7 @@ impl InnerPointsWriter {
                     let meta = IoxMetadata::external(crate::now_ns(), &*measurement);
                     let pool = unbounded_memory_pool();
                     let (data, _parquet_file_meta) =
-                        serialize::to_parquet_bytes(stream, &meta, pool)
+                        serialize::to_parquet_bytes(stream, &meta, &Default::default(), pool)
                             .await
                             .context(ParquetSerializationSnafu)?;
                     let data = Bytes::from(data);
@@ -43,6 +43,12 @@ extensions_options! {
 
         /// Cuttoff date for InfluxQL metadata queries.
         pub influxql_metadata_cutoff: MetadataCutoff, default = MetadataCutoff::Relative(Duration::from_secs(3600 * 24))
+
+        /// Include the number of partitions, parquet files and ingester partitions considered, pruned and scanned
+        /// for each table scan in the physical plan, and therefore in `EXPLAIN` and `EXPLAIN ANALYZE` output.
+        ///
+        /// This adds a pass-through operator above each table scan, which may prevent some plan optimisations.
+        pub explain_pruning_statistics: bool, default = false
     }
 }
 
@@ -32,7 +32,7 @@ use datafusion::{
     logical_expr::{Expr, LogicalPlan},
 };
 
-pub use context::{IOxSessionConfig, IOxSessionContext, SessionContextIOxExt};
+pub use context::{IOxSessionConfig, IOxSessionContext, RowFilters, SessionContextIOxExt};
 use schema_pivot::SchemaPivotNode;
 
 use crate::exec::metrics::DataFusionMemoryPoolMetricsBridge;
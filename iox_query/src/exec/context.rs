@@ -34,12 +34,14 @@ use arrow::record_batch::RecordBatch;
 use async_trait::async_trait;
 use datafusion::{
     catalog::CatalogProvider,
+    common::DFSchema,
     execution::{
         context::{QueryPlanner, SessionState, TaskContext},
+        disk_manager::{DiskManager, DiskManagerConfig},
         memory_pool::MemoryPool,
-        runtime_env::RuntimeEnv,
+        runtime_env::{RuntimeConfig, RuntimeEnv},
     },
-    logical_expr::{LogicalPlan, UserDefinedLogicalNode},
+    logical_expr::{LogicalPlan, SetVariable, Statement, UserDefinedLogicalNode},
     physical_plan::{
         coalesce_partitions::CoalescePartitionsExec, displayable, stream::RecordBatchStreamAdapter,
         EmptyRecordBatchStream, ExecutionPlan, RecordBatchStream, SendableRecordBatchStream,
@@ -176,6 +178,9 @@ pub struct IOxSessionConfig {
 
     /// Span context from which to create spans for this query
     span_ctx: Option<SpanContext>,
+
+    /// Tag values the rows read by this query are restricted to
+    row_filters: RowFilters,
 }
 
 impl fmt::Debug for IOxSessionConfig {
@@ -198,6 +203,7 @@ impl IOxSessionConfig {
             runtime,
             default_catalog: None,
             span_ctx: None,
+            row_filters: RowFilters::default(),
         }
     }
 
@@ -222,6 +228,33 @@ impl IOxSessionConfig {
         Self { span_ctx, ..self }
     }
 
+    /// Restrict the rows read by every table scan of this query to those
+    /// matching `row_filters`.
+    pub fn with_row_filters(self, row_filters: RowFilters) -> Self {
+        Self {
+            row_filters,
+            ..self
+        }
+    }
+
+    /// Execute plans within `memory_pool` instead of the memory pool shared by
+    /// all contexts of the [`Executor`], spilling to `disk_manager` (if
+    /// enabled) when operators that support it exceed the pool.
+    ///
+    /// [`Executor`]: super::Executor
+    pub fn with_memory_pool(
+        self,
+        memory_pool: Arc<dyn MemoryPool>,
+        disk_manager: Arc<DiskManager>,
+    ) -> Self {
+        let runtime_config = RuntimeConfig::new()
+            .with_memory_pool(memory_pool)
+            .with_disk_manager(DiskManagerConfig::Existing(disk_manager))
+            .with_object_store_registry(Arc::clone(&self.runtime.object_store_registry));
+        let runtime = Arc::new(RuntimeEnv::new(runtime_config).expect("creating runtime"));
+        Self { runtime, ..self }
+    }
+
     /// Set DataFusion [config option].
     ///
     /// May be used to set [IOx-specific] option as well.
@@ -250,7 +283,8 @@ impl IOxSessionConfig {
         // attach span to DataFusion session
         let session_config = self
             .session_config
-            .with_extension(Arc::new(recorder.span().cloned()));
+            .with_extension(Arc::new(recorder.span().cloned()))
+            .with_extension(Arc::new(self.row_filters));
 
         let state = SessionState::with_config_rt(session_config, self.runtime)
             .with_query_planner(Arc::new(IOxQueryPlanner {}));
@@ -336,6 +370,23 @@ impl IOxSessionContext {
         &self.inner
     }
 
+    /// Set the DataFusion [config option] `key` of this context to `value`,
+    /// as if by a SQL `SET` statement.
+    ///
+    /// Unlike [`IOxSessionConfig::with_config_option`], an invalid option is
+    /// returned as an error.
+    ///
+    /// [config option]: datafusion::common::config::ConfigOptions
+    pub async fn set_config_option(&self, key: &str, value: &str) -> Result<()> {
+        let plan = LogicalPlan::Statement(Statement::SetVariable(SetVariable {
+            variable: key.to_string(),
+            value: value.to_string(),
+            schema: Arc::new(DFSchema::empty()),
+        }));
+        self.inner.execute_logical_plan(plan).await?;
+        Ok(())
+    }
+
     /// Plan a SQL statement. This assumes that any tables referenced
     /// in the SQL have been registered with this context. Use
     /// `create_physical_plan` to actually execute the query.
@@ -702,6 +753,9 @@ pub trait SessionContextIOxExt {
 
     /// Get span context
     fn span_ctx(&self) -> Option<SpanContext>;
+
+    /// Get the tag values the rows read by table scans are restricted to.
+    fn row_filters(&self) -> Arc<RowFilters>;
 }
 
 impl SessionContextIOxExt for SessionState {
@@ -716,4 +770,35 @@ impl SessionContextIOxExt for SessionState {
             .get_extension::<Option<Span>>()
             .and_then(|span| span.as_ref().as_ref().map(|span| span.ctx.clone()))
     }
+
+    fn row_filters(&self) -> Arc<RowFilters> {
+        self.config()
+            .get_extension::<RowFilters>()
+            .unwrap_or_default()
+    }
+}
+
+/// Tag values the rows read by a query are restricted to, such as those the
+/// request token is limited to.
+///
+/// Table providers conjoin a `<column> = '<value>'` predicate to every scan of
+/// a table for each filter, reading no rows of tables without the tag column.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RowFilters(Vec<(String, String)>);
+
+impl RowFilters {
+    /// Restrict rows to those with every `(column, value)` tag pair.
+    pub fn new(filters: impl IntoIterator<Item = (String, String)>) -> Self {
+        Self(filters.into_iter().collect())
+    }
+
+    /// Returns true if rows are not restricted.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// The `(column, value)` tag pairs rows are restricted to.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.0.iter().map(|(c, v)| (c.as_str(), v.as_str()))
+    }
 }
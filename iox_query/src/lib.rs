@@ -30,11 +30,13 @@ use datafusion::{
     physical_plan::{SendableRecordBatchStream, Statistics},
     prelude::{Expr, SessionContext},
 };
-use exec::IOxSessionContext;
+use exec::{IOxSessionContext, RowFilters};
 use once_cell::sync::Lazy;
 use parquet_file::storage::ParquetExecInput;
 use schema::{sort::SortKey, Projection, Schema};
 use std::{any::Any, fmt::Debug, sync::Arc};
+use tokio_util::sync::CancellationToken;
+use usage::QueryUsage;
 
 pub mod chunk_statistics;
 pub mod config;
@@ -46,6 +48,7 @@ pub mod plan;
 pub mod provider;
 pub mod pruning;
 pub mod statistics;
+pub mod usage;
 pub mod util;
 
 pub use query_functions::group_by::{Aggregate, WindowDuration};
@@ -106,37 +109,72 @@ pub struct QueryCompletedToken {
     /// If this query completed successfully
     success: bool,
 
+    /// The resources used to execute the query, if recorded.
+    usage: Option<QueryUsage>,
+
     /// Function invoked when the token is dropped. It is passed the
-    /// vaue of `self.success`
-    f: Option<Box<dyn FnOnce(bool) + Send>>,
+    /// vaue of `self.success` and `self.usage`
+    f: Option<Box<dyn FnOnce(bool, Option<QueryUsage>) + Send>>,
+
+    /// Cancelled when the query is killed, if the query can be killed.
+    cancellation: Option<CancellationToken>,
 }
 
 impl Debug for QueryCompletedToken {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("QueryCompletedToken")
             .field("success", &self.success)
+            .field("usage", &self.usage)
+            .field("cancellation", &self.cancellation)
             .finish()
     }
 }
 
 impl QueryCompletedToken {
     pub fn new(f: impl FnOnce(bool) + Send + 'static) -> Self {
+        Self::new_with_usage(move |success, _usage| f(success))
+    }
+
+    /// Like [`QueryCompletedToken::new()`], passing `f` the resources used
+    /// by the query if they were recorded with
+    /// [`QueryCompletedToken::set_usage()`].
+    pub fn new_with_usage(f: impl FnOnce(bool, Option<QueryUsage>) + Send + 'static) -> Self {
         Self {
             success: false,
+            usage: None,
             f: Some(Box::new(f)),
+            cancellation: None,
         }
     }
 
+    /// Attach a [`CancellationToken`] that is cancelled when the query is
+    /// killed, so the execution of the query can be aborted.
+    pub fn with_cancellation(mut self, cancellation: CancellationToken) -> Self {
+        self.cancellation = Some(cancellation);
+        self
+    }
+
+    /// Return the [`CancellationToken`] cancelled when the query is killed,
+    /// if any.
+    pub fn cancellation(&self) -> Option<&CancellationToken> {
+        self.cancellation.as_ref()
+    }
+
     /// Record that this query completed successfully
     pub fn set_success(&mut self) {
         self.success = true;
     }
+
+    /// Record the resources used to execute this query
+    pub fn set_usage(&mut self, usage: QueryUsage) {
+        self.usage = Some(usage);
+    }
 }
 
 impl Drop for QueryCompletedToken {
     fn drop(&mut self) {
         if let Some(f) = self.f.take() {
-            (f)(self.success)
+            (f)(self.success, self.usage.take())
         }
     }
 }
@@ -189,7 +227,17 @@ pub trait QueryNamespace: Debug + Send + Sync {
     ) -> QueryCompletedToken;
 
     /// Returns a new execution context suitable for running queries
-    fn new_query_context(&self, span_ctx: Option<SpanContext>) -> IOxSessionContext;
+    fn new_query_context(&self, span_ctx: Option<SpanContext>) -> IOxSessionContext {
+        self.new_filtered_query_context(span_ctx, RowFilters::default())
+    }
+
+    /// Returns a new execution context suitable for running queries that may
+    /// only read rows matching `row_filters`.
+    fn new_filtered_query_context(
+        &self,
+        span_ctx: Option<SpanContext>,
+        row_filters: RowFilters,
+    ) -> IOxSessionContext;
 }
 
 /// Raw data of a [`QueryChunk`].
@@ -5,7 +5,7 @@
 use crate::{
     exec::{
         stringset::{StringSet, StringSetRef},
-        Executor, ExecutorType, IOxSessionContext,
+        Executor, ExecutorType, IOxSessionContext, RowFilters,
     },
     pruning::prune_chunks,
     QueryChunk, QueryChunkData, QueryCompletedToken, QueryNamespace, QueryText,
@@ -170,7 +170,11 @@ impl QueryNamespace for TestDatabase {
         QueryCompletedToken::new(|_| {})
     }
 
-    fn new_query_context(&self, span_ctx: Option<SpanContext>) -> IOxSessionContext {
+    fn new_filtered_query_context(
+        &self,
+        span_ctx: Option<SpanContext>,
+        row_filters: RowFilters,
+    ) -> IOxSessionContext {
         // Note: unlike Db this does not register a catalog provider
         self.executor
             .new_execution_config(ExecutorType::Query)
@@ -178,6 +182,7 @@ impl QueryNamespace for TestDatabase {
                 self,
             )))
             .with_span_context(span_ctx)
+            .with_row_filters(row_filters)
             .build()
     }
 }
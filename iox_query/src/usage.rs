@@ -0,0 +1,85 @@
+//! Accounting of the resources used to execute a query.
+
+use datafusion::physical_plan::ExecutionPlan;
+
+/// The resources used to execute a query, reported to the
+/// [`QueryCompletedToken`](crate::QueryCompletedToken) of the query.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct QueryUsage {
+    /// The number of partitions scanned, after pruning.
+    pub partitions_scanned: usize,
+
+    /// The number of bytes read from parquet files in object storage.
+    pub bytes_scanned: usize,
+
+    /// The number of rows returned to the client.
+    pub rows_returned: usize,
+
+    /// The memory used by the operators of the plan, as reported by their
+    /// `mem_used` metrics.
+    ///
+    /// As operators do not all release their memory at the same time, this
+    /// is an upper bound of the peak memory usage of the query.
+    pub peak_memory_bytes: usize,
+}
+
+impl QueryUsage {
+    /// Derive the resources used from the metrics of the executed
+    /// `physical_plan` and its inputs, recording `rows_returned` rows as
+    /// returned to the client.
+    ///
+    /// The partitions scanned are derived from the `partitions_considered`
+    /// and `partitions_pruned` metrics reported by the table scans of the
+    /// querier.
+    pub fn from_plan(physical_plan: &dyn ExecutionPlan, rows_returned: usize) -> Self {
+        let mut usage = Self {
+            rows_returned,
+            ..Default::default()
+        };
+        usage.add_plan(physical_plan);
+        usage
+    }
+
+    fn add_plan(&mut self, plan: &dyn ExecutionPlan) {
+        if let Some(metrics) = plan.metrics() {
+            let sum = |name: &str| {
+                metrics
+                    .sum_by_name(name)
+                    .map(|v| v.as_usize())
+                    .unwrap_or_default()
+            };
+
+            self.partitions_scanned +=
+                sum("partitions_considered").saturating_sub(sum("partitions_pruned"));
+            self.bytes_scanned += sum("bytes_scanned");
+            self.peak_memory_bytes += sum("mem_used");
+        }
+
+        for child in plan.children() {
+            self.add_plan(child.as_ref());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use arrow::datatypes::Schema;
+    use datafusion::physical_plan::empty::EmptyExec;
+
+    use super::*;
+
+    #[test]
+    fn test_from_plan_without_metrics() {
+        let plan = EmptyExec::new(false, Arc::new(Schema::empty()));
+        let usage = QueryUsage::from_plan(&plan, 42);
+        assert_eq!(
+            usage,
+            QueryUsage {
+                rows_returned: 42,
+                ..Default::default()
+            }
+        );
+    }
+}
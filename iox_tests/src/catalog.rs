@@ -632,6 +632,7 @@ impl TestPartition {
             compaction_level,
             column_set,
             max_l0_created_at: Timestamp::new(max_l0_created_at),
+            column_statistics: vec![],
         };
 
         let mut repos = self.catalog.catalog.repositories().await;
@@ -0,0 +1,85 @@
+//! The build information reported by all the server types, over gRPC and
+//! HTTP.
+
+use std::sync::OnceLock;
+
+use generated_types::influxdata::iox::build_info::{
+    v1::{
+        build_info_service_server::{BuildInfoService, BuildInfoServiceServer},
+        GetBuildInfoRequest, GetBuildInfoResponse,
+    },
+    PROTOCOL_VERSIONS,
+};
+use tonic::{Request, Response, Status};
+
+static BUILD_INFO: OnceLock<BuildInfo> = OnceLock::new();
+
+/// The description of the running build, set once at startup with
+/// [`set_build_info()`].
+#[derive(Debug, Clone)]
+pub struct BuildInfo {
+    /// The package version.
+    pub version: &'static str,
+
+    /// The git revision the build was made from.
+    pub git_hash: &'static str,
+
+    /// The optional cargo features enabled in the build.
+    pub features: Vec<&'static str>,
+}
+
+impl Default for BuildInfo {
+    fn default() -> Self {
+        Self {
+            version: "UNKNOWN",
+            git_hash: "UNKNOWN",
+            features: vec![],
+        }
+    }
+}
+
+/// Set the build information reported by all server types.
+///
+/// Only the first call has an effect.
+pub fn set_build_info(info: BuildInfo) {
+    let _ = BUILD_INFO.set(info);
+}
+
+/// Describe the running build, as served to the clients of `server_type`.
+pub fn build_info_response(server_type: &str) -> GetBuildInfoResponse {
+    let info = BUILD_INFO.get_or_init(Default::default);
+
+    GetBuildInfoResponse {
+        version: info.version.to_string(),
+        git_hash: info.git_hash.to_string(),
+        server_type: server_type.to_string(),
+        features: info.features.iter().map(ToString::to_string).collect(),
+        protocol_versions: PROTOCOL_VERSIONS
+            .iter()
+            .map(|(name, version)| (name.to_string(), *version))
+            .collect(),
+    }
+}
+
+/// Create the gRPC build information service of `server_type`.
+pub fn make_server(server_type: &str) -> BuildInfoServiceServer<BuildInfoServiceImpl> {
+    BuildInfoServiceServer::new(BuildInfoServiceImpl {
+        server_type: server_type.to_string(),
+    })
+}
+
+/// Implementation of the gRPC build information service.
+#[derive(Debug)]
+pub struct BuildInfoServiceImpl {
+    server_type: String,
+}
+
+#[tonic::async_trait]
+impl BuildInfoService for BuildInfoServiceImpl {
+    async fn get_build_info(
+        &self,
+        _request: Request<GetBuildInfoRequest>,
+    ) -> Result<Response<GetBuildInfoResponse>, Status> {
+        Ok(Response::new(build_info_response(&self.server_type)))
+    }
+}
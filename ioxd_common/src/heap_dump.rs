@@ -0,0 +1,31 @@
+//! Optional integration with the heap profiler of the global allocator,
+//! served over HTTP at `/debug/pprof/heap`.
+//!
+//! The allocator is selected by the binary, so the function producing the
+//! heap profile is registered once at startup with [`set_heap_dump_fn()`].
+
+use std::sync::OnceLock;
+
+/// The error returned by a [`HeapDumpFn`].
+pub type HeapDumpError = Box<dyn std::error::Error + Send + Sync>;
+
+/// A function producing a heap profile of the running process, in the format
+/// of the allocator's profiler.
+///
+/// It is called on a blocking thread, and may perform blocking I/O.
+pub type HeapDumpFn = fn() -> Result<Vec<u8>, HeapDumpError>;
+
+static HEAP_DUMP: OnceLock<HeapDumpFn> = OnceLock::new();
+
+/// Set the function producing the heap profile served by all server types.
+///
+/// Only the first call has an effect.
+pub fn set_heap_dump_fn(f: HeapDumpFn) {
+    let _ = HEAP_DUMP.set(f);
+}
+
+/// Produce a heap profile with the registered [`HeapDumpFn`], or return
+/// [`None`] if heap profiling is not enabled.
+pub(crate) fn dump_heap() -> Option<Result<Vec<u8>, HeapDumpError>> {
+    HEAP_DUMP.get().map(|f| f())
+}
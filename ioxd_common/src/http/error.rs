@@ -113,6 +113,24 @@ pub struct HttpApiError {
     /// Optional error line (for line protocol errors).
     #[serde(skip_serializing_if = "Option::is_none")]
     line: Option<usize>,
+
+    /// Optional per-table errors (for writes rejected by schema validation).
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    errors: Vec<HttpApiErrorDetail>,
+}
+
+/// The error of a single table of a write request, as reported in the
+/// `errors` list of a [`HttpApiError`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct HttpApiErrorDetail {
+    /// The name of the table the error applies to.
+    pub table: String,
+
+    /// Machine-readable error code.
+    pub code: String,
+
+    /// Human-readable message.
+    pub message: String,
 }
 
 impl HttpApiError {
@@ -122,6 +140,7 @@ impl HttpApiError {
             code: code.into(),
             msg: msg.into(),
             line: None,
+            errors: vec![],
         }
     }
 
@@ -130,6 +149,11 @@ impl HttpApiError {
         Self { line, ..self }
     }
 
+    /// Add per-table errors to error.
+    pub fn with_details(self, errors: Vec<HttpApiErrorDetail>) -> Self {
+        Self { errors, ..self }
+    }
+
     /// Generate response body for this error.
     fn body(&self) -> Body {
         Body::from(serde_json::to_string(&self).expect("must serialise to json"))
@@ -1,4 +1,4 @@
-use std::{convert::Infallible, num::NonZeroI32, sync::Arc};
+use std::{collections::BTreeMap, convert::Infallible, num::NonZeroI32, sync::Arc};
 
 use authz::http::AuthorizationHeaderExtension;
 use hyper::{
@@ -14,6 +14,8 @@ use tower::Layer;
 use trace_http::{ctx::TraceHeaderParser, tower::TraceLayer};
 
 use crate::{
+    build_info::build_info_response,
+    heap_dump::{dump_heap, HeapDumpError},
     http::error::{HttpApiError, HttpApiErrorExt, HttpApiErrorSource},
     server_type::ServerType,
 };
@@ -58,6 +60,9 @@ pub enum ApplicationError {
     #[snafu(display("Protobuf error: {}", source))]
     ProstIO { source: std::io::Error },
 
+    #[snafu(display("JSON error: {}", source))]
+    Json { source: serde_json::Error },
+
     #[snafu(display("Empty flamegraph"))]
     EmptyFlamegraph,
 
@@ -67,6 +72,15 @@ pub enum ApplicationError {
     #[snafu(display("pprof support is not compiled"))]
     PProfIsNotCompiled,
 
+    #[snafu(display("heap profiling is not enabled"))]
+    HeapDumpIsNotEnabled,
+
+    #[snafu(display("Heap dump error: {}", source))]
+    HeapDump { source: HeapDumpError },
+
+    #[snafu(display("task dump support is not compiled"))]
+    TaskDumpIsNotCompiled,
+
     #[snafu(display("Route error from run mode: {}", e))]
     RunModeRouteError { e: Box<dyn HttpApiErrorSource> },
 }
@@ -78,9 +92,13 @@ impl HttpApiErrorSource for ApplicationError {
             e @ Self::PProf { .. } => e.internal_error(),
             e @ Self::Prost { .. } => e.internal_error(),
             e @ Self::ProstIO { .. } => e.internal_error(),
+            e @ Self::Json { .. } => e.internal_error(),
             e @ Self::EmptyFlamegraph => e.empty_value(),
             e @ Self::HeappyIsNotCompiled => e.internal_error(),
             e @ Self::PProfIsNotCompiled => e.internal_error(),
+            e @ Self::HeapDumpIsNotEnabled => e.internal_error(),
+            e @ Self::HeapDump { .. } => e.internal_error(),
+            e @ Self::TaskDumpIsNotCompiled => e.internal_error(),
             #[cfg(feature = "heappy")]
             e @ Self::HeappyError { .. } => e.internal_error(),
             Self::RunModeRouteError { e } => e.to_http_api_error(),
@@ -138,9 +156,13 @@ async fn route_request(
     let response = match (method.clone(), uri.path()) {
         (Method::GET, "/health") => health(),
         (Method::GET, "/metrics") => handle_metrics(server_type.as_ref()),
+        (Method::GET, "/info") => handle_info(server_type.as_ref()),
         (Method::GET, "/debug/pprof") => pprof_home(req).await,
         (Method::GET, "/debug/pprof/profile") => pprof_profile(req).await,
         (Method::GET, "/debug/pprof/allocs") => pprof_heappy_profile(req).await,
+        (Method::GET, "/debug/pprof/heap") => heap_dump().await,
+        (Method::GET, "/debug/tasks") => task_dump().await,
+        (Method::GET, "/debug/memory") => handle_memory(server_type.as_ref()),
         _ => server_type
             .route_http_request(req)
             .await
@@ -178,6 +200,73 @@ fn handle_metrics(server_type: &dyn ServerType) -> Result<Response<Body>, Applic
     Ok(Response::new(Body::from(body)))
 }
 
+fn handle_info(server_type: &dyn ServerType) -> Result<Response<Body>, ApplicationError> {
+    use snafu::ResultExt;
+
+    let info = build_info_response(server_type.name());
+    let body = serde_json::to_vec(&info).context(JsonSnafu)?;
+
+    Ok(Response::builder()
+        .header(hyper::header::CONTENT_TYPE, "application/json")
+        .body(Body::from(body))
+        .expect("valid response"))
+}
+
+fn handle_memory(server_type: &dyn ServerType) -> Result<Response<Body>, ApplicationError> {
+    use snafu::ResultExt;
+
+    let usage = server_type
+        .memory_usage()
+        .into_iter()
+        .collect::<BTreeMap<_, _>>();
+    let body = serde_json::to_vec(&usage).context(JsonSnafu)?;
+
+    Ok(Response::builder()
+        .header(hyper::header::CONTENT_TYPE, "application/json")
+        .body(Body::from(body))
+        .expect("valid response"))
+}
+
+/// Serve the heap profile of the allocator, if heap profiling was enabled at
+/// startup.
+async fn heap_dump() -> Result<Response<Body>, ApplicationError> {
+    use snafu::{OptionExt, ResultExt};
+
+    // The allocator writes the heap profile to a temporary file that is then
+    // read back, so keep this blocking I/O off the runtime threads.
+    let body = tokio::task::spawn_blocking(dump_heap)
+        .await
+        .map_err(HeapDumpError::from)
+        .context(HeapDumpSnafu)?
+        .context(HeapDumpIsNotEnabledSnafu)?
+        .context(HeapDumpSnafu)?;
+
+    Ok(Response::new(Body::from(body)))
+}
+
+/// Serve the stack trace of every task in the tokio runtime.
+///
+/// Requires building with `RUSTFLAGS="--cfg tokio_unstable --cfg
+/// tokio_taskdump"` on a supported (Linux) target.
+#[cfg(all(tokio_unstable, tokio_taskdump))]
+async fn task_dump() -> Result<Response<Body>, ApplicationError> {
+    use std::fmt::Write;
+
+    let dump = tokio::runtime::Handle::current().dump().await;
+
+    let mut body = String::new();
+    for (i, task) in dump.tasks().iter().enumerate() {
+        writeln!(body, "TASK {i}:\n{}\n", task.trace()).expect("write to string");
+    }
+
+    Ok(Response::new(Body::from(body)))
+}
+
+#[cfg(not(all(tokio_unstable, tokio_taskdump)))]
+async fn task_dump() -> Result<Response<Body>, ApplicationError> {
+    TaskDumpIsNotCompiledSnafu {}.fail()
+}
+
 async fn pprof_home(req: Request<Body>) -> Result<Response<Body>, ApplicationError> {
     let default_host = HeaderValue::from_static("localhost");
     let host = req
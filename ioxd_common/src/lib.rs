@@ -14,6 +14,8 @@
 // Workaround for "unused crate" lint false positives.
 use workspace_hack as _;
 
+pub mod build_info;
+pub mod heap_dump;
 pub mod http;
 pub mod rpc;
 pub mod server_type;
@@ -117,6 +117,10 @@ macro_rules! setup_builder {
 
         add_service!(builder, health_service);
         add_service!(builder, reflection_service);
+        add_service!(
+            builder,
+            $crate::build_info::make_server($server_type.name())
+        );
         add_service!(
             builder,
             $crate::reexport::service_grpc_testing::make_server()
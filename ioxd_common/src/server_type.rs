@@ -55,6 +55,14 @@ pub trait ServerType: std::fmt::Debug + Send + Sync + 'static {
         req: Request<Body>,
     ) -> Result<Response<Body>, Box<dyn HttpApiErrorSource>>;
 
+    /// The approximate number of bytes held by each memory-intensive
+    /// subsystem of the server, served at `/debug/memory`.
+    ///
+    /// Defaults to reporting no subsystems.
+    fn memory_usage(&self) -> Vec<(&'static str, usize)> {
+        vec![]
+    }
+
     /// Construct and serve gRPC subsystem.
     async fn server_grpc(self: Arc<Self>, builder_input: RpcBuilderInput) -> Result<(), RpcError>;
 
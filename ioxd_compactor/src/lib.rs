@@ -33,7 +33,7 @@ use ioxd_common::{
 };
 use metric::Registry;
 use observability_deps::tracing::{info, warn};
-use parquet_file::storage::ParquetStorage;
+use parquet_file::{serialize::BloomFilterConfig, storage::ParquetStorage};
 use std::{
     fmt::{Debug, Display},
     fs,
@@ -202,6 +202,14 @@ pub async fn create_compactor_server_type(
 ) -> Arc<dyn ServerType> {
     let backoff_config = BackoffConfig::default();
 
+    // Compacted files are encoded into the scratchpad store before being
+    // copied to the real store, so configure both.
+    let bloom_filters = BloomFilterConfig::new(compactor_config.bloom_filter_columns.clone())
+        .with_fpp(compactor_config.bloom_filter_fpp)
+        .with_ndv(compactor_config.bloom_filter_ndv);
+    let parquet_store_real = parquet_store_real.with_bloom_filters(bloom_filters.clone());
+    let parquet_store_scratchpad = parquet_store_scratchpad.with_bloom_filters(bloom_filters);
+
     let compactor = Compactor::start(Config {
         metric_registry: Arc::clone(&metric_registry),
         trace_collector: common_state.trace_collector(),
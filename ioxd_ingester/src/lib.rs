@@ -16,16 +16,22 @@ use workspace_hack as _;
 
 use arrow_flight::flight_service_server::FlightServiceServer;
 use async_trait::async_trait;
-use clap_blocks::ingester::IngesterConfig;
+use clap_blocks::{field_value_policy::FieldValuePolicyConfig, ingester::IngesterConfig};
 use futures::FutureExt;
+#[cfg(feature = "fault_injection")]
+use generated_types::influxdata::iox::ingester::v1::fault_injection_service_server::FaultInjectionServiceServer;
 use generated_types::influxdata::iox::{
     catalog::v1::catalog_service_server::CatalogServiceServer,
     ingester::v1::{
+        buffer_schema_service_server::BufferSchemaServiceServer,
         persist_service_server::PersistServiceServer, write_service_server::WriteServiceServer,
     },
 };
-use hyper::{Body, Request, Response};
-use ingester::{GossipConfig, IngesterGuard, IngesterRpcInterface};
+use hyper::{Body, Method, Request, Response};
+use ingester::{
+    FieldValuePolicy, GossipConfig, IngestRateAnomalyConfig, IngesterGuard, IngesterRpcInterface,
+    PersistBarrierGroup, RollupRule,
+};
 use iox_catalog::interface::Catalog;
 use iox_query::exec::Executor;
 use ioxd_common::{
@@ -47,6 +53,7 @@ use std::{
 use thiserror::Error;
 use tokio::sync::oneshot;
 use tokio_util::sync::CancellationToken;
+use tonic::codec::CompressionEncoding;
 use trace::TraceCollector;
 
 /// Define a safe maximum ingester write response size.
@@ -69,15 +76,20 @@ struct IngesterServerType<I: IngesterRpcInterface> {
     trace_collector: Option<Arc<dyn TraceCollector>>,
     max_simultaneous_queries: usize,
     max_incoming_msg_bytes: usize,
+    query_response_gzip: bool,
+    query_max_response_message_bytes: Option<usize>,
 }
 
 impl<I: IngesterRpcInterface> IngesterServerType<I> {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         server: IngesterGuard<I>,
         metrics: Arc<Registry>,
         common_state: &CommonServerState,
         max_simultaneous_queries: usize,
         max_incoming_msg_bytes: usize,
+        query_response_gzip: bool,
+        query_max_response_message_bytes: Option<usize>,
         shutdown: oneshot::Sender<CancellationToken>,
     ) -> Self {
         Self {
@@ -87,10 +99,37 @@ impl<I: IngesterRpcInterface> IngesterServerType<I> {
             trace_collector: common_state.trace_collector(),
             max_simultaneous_queries,
             max_incoming_msg_bytes,
+            query_response_gzip,
+            query_max_response_message_bytes,
         }
     }
 }
 
+impl<I: IngesterRpcInterface + Send + Sync> IngesterServerType<I> {
+    /// Render the ongoing ingest rate anomalies as a JSON array.
+    fn ingest_anomalies(&self) -> Response<Body> {
+        let anomalies = self
+            .server
+            .ingest_anomalies()
+            .into_iter()
+            .map(|a| {
+                serde_json::json!({
+                    "namespace_id": a.namespace_id.get(),
+                    "kind": a.kind.as_str(),
+                    "rows_per_second": a.rows_per_second,
+                    "baseline_rows_per_second": a.baseline_rows_per_second,
+                    "since": a.since.to_rfc3339(),
+                })
+            })
+            .collect::<Vec<_>>();
+
+        Response::builder()
+            .header(hyper::header::CONTENT_TYPE, "application/json")
+            .body(Body::from(serde_json::Value::from(anomalies).to_string()))
+            .expect("valid response")
+    }
+}
+
 impl<I: IngesterRpcInterface> std::fmt::Debug for IngesterServerType<I> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "Ingester")
@@ -114,12 +153,27 @@ impl<I: IngesterRpcInterface + Sync + Send + Debug + 'static> ServerType for Ing
         self.trace_collector.as_ref().map(Arc::clone)
     }
 
-    /// Just return "not found".
+    /// Serve the ongoing ingest rate anomalies, and return "not found" for
+    /// everything else.
     async fn route_http_request(
         &self,
-        _req: Request<Body>,
+        req: Request<Body>,
     ) -> Result<Response<Body>, Box<dyn HttpApiErrorSource>> {
-        Err(Box::new(IoxHttpError::NotFound))
+        match (req.method(), req.uri().path()) {
+            (&Method::GET, "/debug/ingest_anomalies") => Ok(self.ingest_anomalies()),
+            _ => Err(Box::new(IoxHttpError::NotFound)),
+        }
+    }
+
+    /// Report the memory held by the buffered data and caches.
+    fn memory_usage(&self) -> Vec<(&'static str, usize)> {
+        let usage = self.server.memory_usage();
+        vec![
+            ("partitions", usage.partitions),
+            ("buffered_bytes", usage.buffered_bytes),
+            ("persisting_bytes", usage.persisting_bytes),
+            ("recent_persisted_bytes", usage.recent_persisted_bytes),
+        ]
     }
 
     /// Configure the gRPC services.
@@ -142,13 +196,32 @@ impl<I: IngesterRpcInterface + Sync + Send + Debug + 'static> ServerType for Ing
         );
         add_service!(
             builder,
-            FlightServiceServer::new(
-                self.server
-                    .rpc()
-                    .query_service(self.max_simultaneous_queries)
-            )
+            BufferSchemaServiceServer::new(self.server.rpc().buffer_schema_service())
+        );
+        #[cfg(feature = "fault_injection")]
+        add_service!(
+            builder,
+            FaultInjectionServiceServer::new(self.server.rpc().fault_injection_service())
         );
 
+        let query_service = FlightServiceServer::new(self.server.rpc().query_service(
+            self.max_simultaneous_queries,
+            self.query_max_response_message_bytes,
+        ));
+        let query_service = match self.query_max_response_message_bytes {
+            Some(v) => query_service.max_encoding_message_size(v),
+            None => query_service,
+        };
+        // Responses are only compressed for queriers advertising gzip support.
+        let query_service = if self.query_response_gzip {
+            query_service
+                .send_compressed(CompressionEncoding::Gzip)
+                .accept_compressed(CompressionEncoding::Gzip)
+        } else {
+            query_service
+        };
+        add_service!(builder, query_service);
+
         serve_builder!(builder);
 
         Ok(())
@@ -170,7 +243,7 @@ impl<I: IngesterRpcInterface + Sync + Send + Debug + 'static> ServerType for Ing
     }
 }
 
-/// Simple error struct, we're not really providing an HTTP interface for the ingester.
+/// Simple error struct, the ingester only serves debugging endpoints over HTTP.
 #[derive(Debug)]
 pub enum IoxHttpError {
     NotFound,
@@ -229,11 +302,62 @@ pub async fn create_ingester_server_type(
         ingester_config.persist_max_parallelism,
         ingester_config.persist_queue_depth,
         ingester_config.persist_hot_partition_cost,
+        ingester_config.exec_priority_slots,
         object_store,
         gossip,
         ingester_config
             .max_partitions_per_namespace
             .unwrap_or_else(|| NonZeroUsize::new(usize::MAX).unwrap()),
+        ingester_config.max_buffered_bytes_per_namespace,
+        ingester_config.max_rows_per_second_per_namespace,
+        ingester_config
+            .buffer_invariant_check_interval_seconds
+            .map(Duration::from_secs),
+        Duration::from_secs(ingester_config.table_usage_flush_interval_seconds),
+        ingester_config.recent_persisted_cache_bytes,
+        ingester_config.persist_memory_limit_bytes,
+        ingester_config.persist_circuit_breaker_threshold,
+        ingester_config.wal_replay_accept_data_loss,
+        ingester_config
+            .wal_replay_throttle_threshold_millis
+            .map(Duration::from_millis),
+        ingester_config.query_shed_degrade_percent,
+        ingester_config.query_shed_reject_percent,
+        Duration::from_secs(ingester_config.query_shed_retry_after_seconds),
+        match ingester_config.field_value_policy {
+            FieldValuePolicyConfig::Disabled => None,
+            FieldValuePolicyConfig::Reject => Some(FieldValuePolicy::Reject),
+            FieldValuePolicyConfig::Clamp => Some(FieldValuePolicy::Clamp),
+            FieldValuePolicyConfig::Null => Some(FieldValuePolicy::Null),
+        },
+        ingester_config
+            .partition_seal_age_seconds
+            .map(Duration::from_secs),
+        ingester_config
+            .persist_barrier_groups
+            .iter()
+            .map(|g| PersistBarrierGroup::new(&g.namespace, &g.tables))
+            .collect(),
+        ingester_config
+            .rollups
+            .iter()
+            .map(|r| RollupRule::new(&r.namespace, &r.table, r.interval))
+            .collect(),
+        Duration::from_secs(ingester_config.rollup_state_retention_seconds),
+        ingester_config
+            .idle_namespace_persist_seconds
+            .map(Duration::from_secs),
+        ingester_config.persist_drop_null_columns,
+        ingester_config.skip_catalog_warm_up,
+        ingester_config
+            .ingest_rate_check_interval_seconds
+            .map(|secs| {
+                IngestRateAnomalyConfig::new(
+                    Duration::from_secs(secs),
+                    ingester_config.ingest_rate_drop_percent,
+                    ingester_config.ingest_rate_spike_percent,
+                )
+            }),
         shutdown_rx.map(|v| v.expect("shutdown sender dropped without calling shutdown")),
     )
     .await?;
@@ -244,6 +368,8 @@ pub async fn create_ingester_server_type(
         common_state,
         ingester_config.concurrent_query_limit,
         ingester_config.rpc_write_max_incoming_bytes,
+        ingester_config.query_response_gzip,
+        ingester_config.query_max_response_message_bytes,
         shutdown_tx,
     )))
 }
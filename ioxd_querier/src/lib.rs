@@ -12,18 +12,21 @@
 )]
 
 use generated_types::influxdata::iox::{
-    catalog::v1::catalog_service_server::CatalogServiceServer,
+    catalog::v1::{
+        catalog_introspection_service_server::CatalogIntrospectionServiceServer,
+        catalog_service_server::CatalogServiceServer,
+    },
     object_store::v1::object_store_service_server::ObjectStoreServiceServer,
     schema::v1::schema_service_server::SchemaServiceServer,
 };
-use service_grpc_catalog::CatalogService;
+use service_grpc_catalog::{CatalogIntrospectionService, CatalogService};
 use service_grpc_object_store::ObjectStoreService;
 use service_grpc_schema::SchemaService;
 // Workaround for "unused crate" lint false positives.
 use workspace_hack as _;
 
 use async_trait::async_trait;
-use authz::{Authorizer, IoxAuthorizer};
+use authz::{Authorizer, IoxAuthorizer, TokenFileAuthorizer};
 use clap_blocks::querier::QuerierConfig;
 use datafusion_util::config::register_iox_object_store;
 use hyper::{Body, Request, Response};
@@ -40,10 +43,14 @@ use ioxd_common::{
 };
 use metric::Registry;
 use object_store::{DynObjectStore, ObjectStore};
-use querier::{create_ingester_connections, QuerierCatalogCache, QuerierDatabase, QuerierServer};
+use querier::{
+    create_ingester_connections, DenyRule, QuerierCatalogCache, QuerierDatabase, QuerierServer,
+    RouterTaskSink, TaskExecutor, TaskScheduler,
+};
 use std::{
     fmt::{Debug, Display},
     sync::Arc,
+    time::Duration,
 };
 use thiserror::Error;
 use tokio::runtime::Handle;
@@ -60,6 +67,9 @@ pub struct QuerierServerType {
     object_store: Arc<dyn ObjectStore>,
     trace_collector: Option<Arc<dyn TraceCollector>>,
     authz: Option<Arc<dyn Authorizer>>,
+    ingester_addresses: Vec<Arc<str>>,
+    task_executor: Option<Arc<TaskExecutor>>,
+    task_shutdown: CancellationToken,
 }
 
 impl std::fmt::Debug for QuerierServerType {
@@ -113,12 +123,36 @@ impl ServerType for QuerierServerType {
         );
         add_service!(
             builder,
-            SchemaServiceServer::new(SchemaService::new(Arc::clone(&self.catalog)))
+            rpc::query_admin::query_admin_service(
+                Arc::clone(&self.database),
+                self.authz.as_ref().map(Arc::clone)
+            )
+        );
+        add_service!(
+            builder,
+            rpc::task::task_service(
+                Arc::clone(&self.catalog),
+                self.task_executor.clone(),
+                self.authz.as_ref().map(Arc::clone)
+            )
         );
+        let schema_service = SchemaService::new(Arc::clone(&self.catalog))
+            .with_buffered_column_source(Arc::new(
+                rpc::buffered_columns::IngesterBufferedColumns::new(
+                    self.ingester_addresses.clone(),
+                ),
+            ));
+        add_service!(builder, SchemaServiceServer::new(schema_service));
         add_service!(
             builder,
             CatalogServiceServer::new(CatalogService::new(Arc::clone(&self.catalog)))
         );
+        add_service!(
+            builder,
+            CatalogIntrospectionServiceServer::new(CatalogIntrospectionService::new(Arc::clone(
+                &self.catalog
+            )))
+        );
         add_service!(
             builder,
             ObjectStoreServiceServer::new(ObjectStoreService::new(
@@ -138,6 +172,7 @@ impl ServerType for QuerierServerType {
 
     fn shutdown(&self, frontend: CancellationToken) {
         frontend.cancel();
+        self.task_shutdown.cancel();
         self.server.shutdown();
     }
 }
@@ -188,6 +223,12 @@ pub enum Error {
     #[error("querier error: {0}")]
     Querier(#[from] querier::QuerierDatabaseError),
 
+    #[error("invalid query denylist: {0}")]
+    QueryDenylist(#[from] querier::DenyRuleError),
+
+    #[error("authz token file error: {0}")]
+    AuthzTokenFile(#[from] authz::TokenFileError),
+
     #[error("authz configuration error for '{addr}': '{source}'")]
     AuthzConfig {
         source: Box<dyn std::error::Error>,
@@ -235,23 +276,41 @@ pub async fn create_querier_server_type(
 
             Some(authz)
         }
-        None => None,
+        None => match &args.querier_config.authz_token_file {
+            Some(path) => Some(
+                Arc::new(TokenFileAuthorizer::load(path, &args.metric_registry)?)
+                    as Arc<dyn Authorizer>,
+            ),
+            None => None,
+        },
     };
 
-    let ingester_connections = if args.querier_config.ingester_addresses.is_empty() {
+    let query_denylist = args
+        .querier_config
+        .query_denylist
+        .iter()
+        .map(|rule| rule.parse::<DenyRule>())
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let ingester_addresses: Vec<Arc<str>> = args
+        .querier_config
+        .ingester_addresses
+        .iter()
+        .map(|addr| addr.to_string().into())
+        .collect();
+
+    let ingester_connections = if ingester_addresses.is_empty() {
         None
     } else {
-        let ingester_addresses = args
-            .querier_config
-            .ingester_addresses
-            .iter()
-            .map(|addr| addr.to_string().into())
-            .collect();
         Some(create_ingester_connections(
-            ingester_addresses,
+            ingester_addresses.clone(),
             Arc::clone(&catalog_cache),
             args.querier_config.ingester_circuit_breaker_threshold,
+            args.querier_config.ingester_table_spread,
             &args.trace_context_header_name,
+            args.querier_config.ingester_query_gzip,
+            args.querier_config.ingester_query_max_message_bytes,
+            args.querier_config.ingester_query_max_snapshots,
         ))
     };
 
@@ -266,6 +325,31 @@ pub async fn create_querier_server_type(
         )
         .await?,
     );
+    database.query_denylist().set(query_denylist);
+    if args.querier_config.query_history {
+        database.query_history().enable();
+    }
+
+    // Tasks are only run if their results can be written.
+    let task_shutdown = CancellationToken::new();
+    let task_router_token = args.querier_config.task_router_token;
+    let task_run_timeout = Duration::from_secs(args.querier_config.task_run_timeout_seconds);
+    let task_executor = args.querier_config.task_router_address.map(|addr| {
+        let sink = match task_router_token {
+            Some(token) => RouterTaskSink::new(addr).with_token(token),
+            None => RouterTaskSink::new(addr),
+        };
+        let executor = Arc::new(
+            TaskExecutor::new(Arc::clone(&database), Arc::new(sink))
+                .with_run_timeout(task_run_timeout),
+        );
+        let scheduler = TaskScheduler::new(
+            Arc::clone(&executor),
+            Duration::from_secs(args.querier_config.task_poll_interval_seconds),
+        );
+        tokio::spawn(scheduler.run(task_shutdown.clone()));
+        executor
+    });
 
     let server = QuerierServer::new(Arc::clone(&database));
     Ok(Arc::new(QuerierServerType {
@@ -276,5 +360,8 @@ pub async fn create_querier_server_type(
         object_store: args.object_store,
         trace_collector: args.common_state.trace_collector(),
         authz,
+        ingester_addresses,
+        task_executor,
+        task_shutdown,
     }))
 }
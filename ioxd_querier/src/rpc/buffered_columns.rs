@@ -0,0 +1,69 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use data_types::{NamespaceId, TableId};
+use generated_types::influxdata::iox::ingester::v1::BufferedColumn;
+use influxdb_iox_client::connection;
+use observability_deps::tracing::warn;
+use service_grpc_schema::BufferedColumnSource;
+
+/// A [`BufferedColumnSource`] that fetches the buffered columns of a table
+/// from each of the configured ingesters.
+///
+/// A new connection is established to each ingester per request - this source
+/// serves schema introspection requests, and is not on the query path.
+#[derive(Debug)]
+pub(crate) struct IngesterBufferedColumns {
+    ingester_addresses: Vec<Arc<str>>,
+}
+
+impl IngesterBufferedColumns {
+    pub(crate) fn new(ingester_addresses: Vec<Arc<str>>) -> Self {
+        Self { ingester_addresses }
+    }
+}
+
+#[async_trait]
+impl BufferedColumnSource for IngesterBufferedColumns {
+    /// Return the columns reported by all the reachable ingesters.
+    ///
+    /// Ingesters that cannot be queried are logged and skipped.
+    async fn buffered_columns(
+        &self,
+        namespace_id: NamespaceId,
+        table_id: TableId,
+    ) -> Vec<BufferedColumn> {
+        let results =
+            futures::future::join_all(self.ingester_addresses.iter().map(|addr| async move {
+                let connection = connection::Builder::new()
+                    .build(addr.as_ref())
+                    .await
+                    .map_err(|e| e.to_string())?;
+
+                influxdb_iox_client::ingester::Client::new(connection)
+                    .buffer_schema(namespace_id.get(), table_id.get())
+                    .await
+                    .map_err(|e| e.to_string())
+            }))
+            .await;
+
+        self.ingester_addresses
+            .iter()
+            .zip(results)
+            .filter_map(|(ingester_address, result)| match result {
+                Ok(columns) => Some(columns),
+                Err(error) => {
+                    warn!(
+                        %error,
+                        %ingester_address,
+                        %namespace_id,
+                        %table_id,
+                        "failed to fetch buffered table schema from ingester"
+                    );
+                    None
+                }
+            })
+            .flatten()
+            .collect()
+    }
+}
@@ -1,2 +1,6 @@
+pub(crate) mod buffered_columns;
 pub(crate) mod namespace;
+pub(crate) mod permissions;
 pub(crate) mod query;
+pub(crate) mod query_admin;
+pub(crate) mod task;
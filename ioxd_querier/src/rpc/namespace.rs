@@ -39,6 +39,8 @@ fn namespace_to_proto(namespace: Namespace) -> proto::Namespace {
         max_tables: namespace.max_tables.get(),
         max_columns_per_table: namespace.max_columns_per_table.get(),
         partition_template: namespace.partition_template.as_proto().cloned(),
+        read_only: namespace.read_only,
+        object_store_name: namespace.object_store_name,
     }
 }
 
@@ -95,6 +97,24 @@ impl proto::namespace_service_server::NamespaceService for NamespaceServiceImpl
             "use router instances to manage namespaces",
         ))
     }
+
+    async fn update_namespace_read_only(
+        &self,
+        _request: tonic::Request<proto::UpdateNamespaceReadOnlyRequest>,
+    ) -> Result<tonic::Response<proto::UpdateNamespaceReadOnlyResponse>, tonic::Status> {
+        Err(tonic::Status::unimplemented(
+            "use router instances to manage namespaces",
+        ))
+    }
+
+    async fn update_namespace_object_store(
+        &self,
+        _request: tonic::Request<proto::UpdateNamespaceObjectStoreRequest>,
+    ) -> Result<tonic::Response<proto::UpdateNamespaceObjectStoreResponse>, tonic::Status> {
+        Err(tonic::Status::unimplemented(
+            "use router instances to manage namespaces",
+        ))
+    }
 }
 
 #[cfg(test)]
@@ -184,6 +204,8 @@ mod tests {
                         max_tables: MaxTables::default().get(),
                         max_columns_per_table: MaxColumnsPerTable::default().get(),
                         partition_template: None,
+                        read_only: false,
+                        object_store_name: None,
                     },
                     proto::Namespace {
                         id: 2,
@@ -192,6 +214,8 @@ mod tests {
                         max_tables: MaxTables::default().get(),
                         max_columns_per_table: MaxColumnsPerTable::default().get(),
                         partition_template: None,
+                        read_only: false,
+                        object_store_name: None,
                     },
                 ]
             }
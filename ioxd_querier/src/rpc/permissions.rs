@@ -0,0 +1,91 @@
+//! Authorization of the gRPC requests administering a namespace.
+
+use authz::{extract_token, Action, Authorizer, Permission, Resource};
+use std::sync::Arc;
+
+/// Check the token in the `authorization` header of `metadata` grants each of
+/// `actions` on `namespace_name`.
+///
+/// All requests are authorized if `authz` is not set.
+pub(crate) async fn authorize_namespace(
+    authz: Option<&Arc<dyn Authorizer>>,
+    metadata: &tonic::metadata::MetadataMap,
+    namespace_name: &str,
+    actions: &[Action],
+) -> Result<(), tonic::Status> {
+    if namespace_name.is_empty() {
+        return Err(tonic::Status::invalid_argument(
+            "namespace name must be set",
+        ));
+    }
+    let Some(authz) = authz else {
+        return Ok(());
+    };
+
+    let perms = actions
+        .iter()
+        .map(|action| {
+            Permission::ResourceAction(Resource::Database(namespace_name.to_string()), *action)
+        })
+        .collect::<Vec<_>>();
+    authz
+        .permissions(extract_token(metadata.get("authorization")), &perms)
+        .await
+        .map(|_| ())
+        .map_err(|e| match e {
+            authz::Error::Forbidden | authz::Error::InvalidToken => {
+                tonic::Status::permission_denied("Permission denied.")
+            }
+            authz::Error::NoToken => tonic::Status::unauthenticated("Unauthenticated."),
+            e => tonic::Status::internal(format!("Authorization error: {e}")),
+        })
+}
+
+#[cfg(test)]
+pub(crate) mod mock {
+    use super::*;
+
+    /// Grants all permissions on the "bananas" namespace to the "GOOD" token.
+    #[derive(Debug)]
+    pub(crate) struct MockAuthorizer;
+
+    #[async_trait::async_trait]
+    impl Authorizer for MockAuthorizer {
+        async fn permissions(
+            &self,
+            token: Option<Vec<u8>>,
+            perms: &[Permission],
+        ) -> Result<Vec<Permission>, authz::Error> {
+            match token.as_deref() {
+                Some(b"GOOD")
+                    if perms.iter().all(|p| {
+                        matches!(
+                            p,
+                            Permission::ResourceAction(Resource::Database(name), _)
+                                if name == "bananas"
+                        )
+                    }) =>
+                {
+                    Ok(perms.to_vec())
+                }
+                Some(_) => Err(authz::Error::Forbidden),
+                None => Err(authz::Error::NoToken),
+            }
+        }
+    }
+
+    /// Build a request for `message` with `token` in its `authorization`
+    /// header, if set.
+    pub(crate) fn request_with_token<T>(
+        message: T,
+        token: Option<&'static str>,
+    ) -> tonic::Request<T> {
+        let mut request = tonic::Request::new(message);
+        if let Some(token) = token {
+            request
+                .metadata_mut()
+                .insert("authorization", format!("Bearer {token}").parse().unwrap());
+        }
+        request
+    }
+}
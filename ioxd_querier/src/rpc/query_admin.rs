@@ -0,0 +1,380 @@
+//! QueryAdminService gRPC implementation, listing and killing running queries
+//! and managing the query denylist.
+
+use super::permissions::authorize_namespace;
+use authz::{Action, Authorizer};
+use generated_types::influxdata::iox::querier::v1 as proto;
+use querier::{DenyRule, QuerierDatabase, QueryRegistry, RunningQuery};
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Acquire a [`QueryAdminService`](proto::query_admin_service_server::QueryAdminService) gRPC service implementation.
+///
+/// If `authz` is set, queries can only be listed and killed with read and
+/// write permission on their namespace respectively, and the querier-wide
+/// query denylist is unavailable.
+pub fn query_admin_service(
+    server: Arc<QuerierDatabase>,
+    authz: Option<Arc<dyn Authorizer>>,
+) -> proto::query_admin_service_server::QueryAdminServiceServer<
+    impl proto::query_admin_service_server::QueryAdminService,
+> {
+    proto::query_admin_service_server::QueryAdminServiceServer::new(QueryAdminServiceImpl::new(
+        server, authz,
+    ))
+}
+
+#[derive(Debug)]
+struct QueryAdminServiceImpl {
+    server: Arc<QuerierDatabase>,
+    authz: Option<Arc<dyn Authorizer>>,
+}
+
+impl QueryAdminServiceImpl {
+    pub fn new(server: Arc<QuerierDatabase>, authz: Option<Arc<dyn Authorizer>>) -> Self {
+        Self { server, authz }
+    }
+
+    /// Reject changes to the querier-wide denylist if requests are authorized
+    /// per namespace.
+    fn authorize_denylist(&self) -> Result<(), tonic::Status> {
+        match self.authz {
+            Some(_) => Err(tonic::Status::permission_denied(
+                "the query denylist is unavailable when authorization is enabled",
+            )),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Translate a [`RunningQuery`] to its protobuf form.
+fn running_query_to_proto(registry: &QueryRegistry, query: &RunningQuery) -> proto::RunningQuery {
+    let entry = query.entry();
+    proto::RunningQuery {
+        id: query.id().to_string(),
+        namespace_name: query.namespace_name().to_string(),
+        query_type: entry.query_type.to_string(),
+        query_text: entry.query_text.to_string(),
+        issue_time_ns: entry.issue_time.timestamp_nanos(),
+        elapsed_ns: registry.elapsed(query).as_nanos() as i64,
+        trace_id: entry.trace_id.map(|id| format!("{:x}", id.0)),
+        killed: query.is_killed(),
+    }
+}
+
+#[tonic::async_trait]
+impl proto::query_admin_service_server::QueryAdminService for QueryAdminServiceImpl {
+    async fn list_queries(
+        &self,
+        request: tonic::Request<proto::ListQueriesRequest>,
+    ) -> Result<tonic::Response<proto::ListQueriesResponse>, tonic::Status> {
+        let namespace_name = &request.get_ref().namespace_name;
+        authorize_namespace(
+            self.authz.as_ref(),
+            request.metadata(),
+            namespace_name,
+            &[Action::Read],
+        )
+        .await?;
+
+        let registry = self.server.query_registry();
+        let queries = registry
+            .list()
+            .iter()
+            .filter(|q| q.namespace_name().as_ref() == namespace_name.as_str())
+            .map(|q| running_query_to_proto(registry, q))
+            .collect();
+
+        Ok(tonic::Response::new(proto::ListQueriesResponse { queries }))
+    }
+
+    async fn kill_query(
+        &self,
+        request: tonic::Request<proto::KillQueryRequest>,
+    ) -> Result<tonic::Response<proto::KillQueryResponse>, tonic::Status> {
+        authorize_namespace(
+            self.authz.as_ref(),
+            request.metadata(),
+            &request.get_ref().namespace_name,
+            &[Action::Write],
+        )
+        .await?;
+
+        let proto::KillQueryRequest { id, namespace_name } = request.into_inner();
+        let uuid = Uuid::parse_str(&id)
+            .map_err(|e| tonic::Status::invalid_argument(format!("invalid query ID: {e}")))?;
+
+        // Only kill queries of the authorized namespace.
+        let registry = self.server.query_registry();
+        let query = registry
+            .list()
+            .iter()
+            .any(|q| q.id() == uuid && q.namespace_name().as_ref() == namespace_name.as_str())
+            .then(|| registry.kill(uuid))
+            .flatten()
+            .ok_or_else(|| {
+                tonic::Status::not_found(format!(
+                    "no running query with ID {id} in namespace {namespace_name}"
+                ))
+            })?;
+
+        Ok(tonic::Response::new(proto::KillQueryResponse {
+            query: Some(running_query_to_proto(registry, &query)),
+        }))
+    }
+
+    async fn get_query_denylist(
+        &self,
+        _request: tonic::Request<proto::GetQueryDenylistRequest>,
+    ) -> Result<tonic::Response<proto::GetQueryDenylistResponse>, tonic::Status> {
+        self.authorize_denylist()?;
+
+        let rules = self
+            .server
+            .query_denylist()
+            .rules()
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+
+        Ok(tonic::Response::new(proto::GetQueryDenylistResponse {
+            rules,
+        }))
+    }
+
+    async fn set_query_denylist(
+        &self,
+        request: tonic::Request<proto::SetQueryDenylistRequest>,
+    ) -> Result<tonic::Response<proto::SetQueryDenylistResponse>, tonic::Status> {
+        self.authorize_denylist()?;
+
+        let rules = request
+            .into_inner()
+            .rules
+            .iter()
+            .map(|r| r.parse::<DenyRule>())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| tonic::Status::invalid_argument(e.to_string()))?;
+
+        self.server.query_denylist().set(rules);
+
+        Ok(tonic::Response::new(proto::SetQueryDenylistResponse {}))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::rpc::permissions::mock::{request_with_token, MockAuthorizer};
+    use generated_types::influxdata::iox::querier::v1::query_admin_service_server::QueryAdminService;
+    use iox_query::QueryNamespace;
+    use iox_tests::TestCatalog;
+    use querier::{create_ingester_connection_for_testing, QuerierCatalogCache};
+    use tokio::runtime::Handle;
+
+    async fn new_service(
+        catalog: &Arc<TestCatalog>,
+        authz: Option<Arc<dyn Authorizer>>,
+    ) -> QueryAdminServiceImpl {
+        let catalog_cache = Arc::new(QuerierCatalogCache::new_testing(
+            catalog.catalog(),
+            catalog.time_provider(),
+            catalog.metric_registry(),
+            catalog.object_store(),
+            &Handle::current(),
+        ));
+        let db = Arc::new(
+            QuerierDatabase::new(
+                catalog_cache,
+                catalog.metric_registry(),
+                catalog.exec(),
+                Some(create_ingester_connection_for_testing()),
+                QuerierDatabase::MAX_CONCURRENT_QUERIES_MAX,
+                Arc::new(HashMap::default()),
+            )
+            .await
+            .unwrap(),
+        );
+
+        QueryAdminServiceImpl::new(db, authz)
+    }
+
+    #[tokio::test]
+    async fn test_list_kill_queries() {
+        let catalog = TestCatalog::new();
+        catalog.create_namespace_1hr_retention("bananas").await;
+        catalog.create_namespace_1hr_retention("platanos").await;
+        let service = new_service(&catalog, None).await;
+
+        let ns = service
+            .server
+            .namespace("bananas", None, false)
+            .await
+            .unwrap();
+        let token = ns.record_query(None, "sql", Box::new("SELECT 1"));
+
+        let queries = service
+            .list_queries(tonic::Request::new(proto::ListQueriesRequest {
+                namespace_name: "platanos".to_string(),
+            }))
+            .await
+            .unwrap()
+            .into_inner()
+            .queries;
+        assert!(queries.is_empty());
+
+        let queries = service
+            .list_queries(tonic::Request::new(proto::ListQueriesRequest {
+                namespace_name: "bananas".to_string(),
+            }))
+            .await
+            .unwrap()
+            .into_inner()
+            .queries;
+        assert_eq!(queries.len(), 1);
+        assert_eq!(queries[0].namespace_name, "bananas");
+        assert_eq!(queries[0].query_type, "sql");
+        assert_eq!(queries[0].query_text, "SELECT 1");
+        assert!(!queries[0].killed);
+
+        // A query cannot be killed through another namespace.
+        let err = service
+            .kill_query(tonic::Request::new(proto::KillQueryRequest {
+                id: queries[0].id.clone(),
+                namespace_name: "platanos".to_string(),
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), tonic::Code::NotFound);
+        assert!(!token.cancellation().unwrap().is_cancelled());
+
+        let killed = service
+            .kill_query(tonic::Request::new(proto::KillQueryRequest {
+                id: queries[0].id.clone(),
+                namespace_name: "bananas".to_string(),
+            }))
+            .await
+            .unwrap()
+            .into_inner()
+            .query
+            .unwrap();
+        assert!(killed.killed);
+        assert!(token.cancellation().unwrap().is_cancelled());
+
+        drop(token);
+        let err = service
+            .kill_query(tonic::Request::new(proto::KillQueryRequest {
+                id: queries[0].id.clone(),
+                namespace_name: "bananas".to_string(),
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), tonic::Code::NotFound);
+
+        let err = service
+            .kill_query(tonic::Request::new(proto::KillQueryRequest {
+                id: "bananas".to_string(),
+                namespace_name: "bananas".to_string(),
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), tonic::Code::InvalidArgument);
+    }
+
+    #[tokio::test]
+    async fn test_query_denylist() {
+        let catalog = TestCatalog::new();
+        let service = new_service(&catalog, None).await;
+
+        let rules = vec!["table=^cpu$".to_string(), "no_time_bound".to_string()];
+        service
+            .set_query_denylist(tonic::Request::new(proto::SetQueryDenylistRequest {
+                rules: rules.clone(),
+            }))
+            .await
+            .unwrap();
+
+        // Invalid rules leave the denylist unchanged.
+        let err = service
+            .set_query_denylist(tonic::Request::new(proto::SetQueryDenylistRequest {
+                rules: vec!["table=(".to_string()],
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), tonic::Code::InvalidArgument);
+
+        let got = service
+            .get_query_denylist(tonic::Request::new(proto::GetQueryDenylistRequest {}))
+            .await
+            .unwrap()
+            .into_inner()
+            .rules;
+        assert_eq!(got, rules);
+    }
+
+    #[tokio::test]
+    async fn test_authz() {
+        let catalog = TestCatalog::new();
+        catalog.create_namespace_1hr_retention("bananas").await;
+        catalog.create_namespace_1hr_retention("platanos").await;
+        let service = new_service(&catalog, Some(Arc::new(MockAuthorizer))).await;
+
+        let list = |namespace_name: &str, token| {
+            service.list_queries(request_with_token(
+                proto::ListQueriesRequest {
+                    namespace_name: namespace_name.to_string(),
+                },
+                token,
+            ))
+        };
+        assert!(list("bananas", Some("GOOD")).await.is_ok());
+        assert_eq!(
+            list("bananas", None).await.unwrap_err().code(),
+            tonic::Code::Unauthenticated
+        );
+        assert_eq!(
+            list("bananas", Some("BAD")).await.unwrap_err().code(),
+            tonic::Code::PermissionDenied
+        );
+        assert_eq!(
+            list("platanos", Some("GOOD")).await.unwrap_err().code(),
+            tonic::Code::PermissionDenied
+        );
+        assert_eq!(
+            list("", Some("GOOD")).await.unwrap_err().code(),
+            tonic::Code::InvalidArgument
+        );
+
+        let err = service
+            .kill_query(request_with_token(
+                proto::KillQueryRequest {
+                    id: Uuid::new_v4().to_string(),
+                    namespace_name: "platanos".to_string(),
+                },
+                Some("GOOD"),
+            ))
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), tonic::Code::PermissionDenied);
+
+        // The denylist applies to all namespaces.
+        let err = service
+            .get_query_denylist(request_with_token(
+                proto::GetQueryDenylistRequest {},
+                Some("GOOD"),
+            ))
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), tonic::Code::PermissionDenied);
+        let err = service
+            .set_query_denylist(request_with_token(
+                proto::SetQueryDenylistRequest { rules: vec![] },
+                Some("GOOD"),
+            ))
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), tonic::Code::PermissionDenied);
+    }
+}
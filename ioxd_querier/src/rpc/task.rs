@@ -0,0 +1,398 @@
+//! TaskService gRPC implementation, managing the tasks periodically executing
+//! SQL queries and running them on demand.
+
+use super::permissions::authorize_namespace;
+use authz::{Action, Authorizer};
+use data_types::{Namespace, Task, TaskRun, TaskRunStatus};
+use generated_types::influxdata::iox::querier::v1 as proto;
+use iox_catalog::interface::{Catalog, Error as CatalogError, SoftDeletedRows};
+use querier::{Schedule, TaskExecutor};
+use std::sync::Arc;
+
+/// Acquire a [`TaskService`](proto::task_service_server::TaskService) gRPC service implementation.
+///
+/// Tasks can only be run on demand if `executor` is set.
+///
+/// If `authz` is set, each request requires read and write permission on the
+/// namespace of the tasks, as their runs read from and write to it.
+pub fn task_service(
+    catalog: Arc<dyn Catalog>,
+    executor: Option<Arc<TaskExecutor>>,
+    authz: Option<Arc<dyn Authorizer>>,
+) -> proto::task_service_server::TaskServiceServer<impl proto::task_service_server::TaskService> {
+    proto::task_service_server::TaskServiceServer::new(TaskServiceImpl::new(
+        catalog, executor, authz,
+    ))
+}
+
+#[derive(Debug)]
+struct TaskServiceImpl {
+    catalog: Arc<dyn Catalog>,
+    executor: Option<Arc<TaskExecutor>>,
+    authz: Option<Arc<dyn Authorizer>>,
+}
+
+impl TaskServiceImpl {
+    pub fn new(
+        catalog: Arc<dyn Catalog>,
+        executor: Option<Arc<TaskExecutor>>,
+        authz: Option<Arc<dyn Authorizer>>,
+    ) -> Self {
+        Self {
+            catalog,
+            executor,
+            authz,
+        }
+    }
+
+    /// Check the request grants read and write permission on
+    /// `namespace_name`.
+    async fn authorize(
+        &self,
+        metadata: &tonic::metadata::MetadataMap,
+        namespace_name: &str,
+    ) -> Result<(), tonic::Status> {
+        authorize_namespace(
+            self.authz.as_ref(),
+            metadata,
+            namespace_name,
+            &[Action::Read, Action::Write],
+        )
+        .await
+    }
+
+    async fn namespace(&self, name: &str) -> Result<Namespace, tonic::Status> {
+        self.catalog
+            .repositories()
+            .await
+            .namespaces()
+            .get_by_name(name, SoftDeletedRows::ExcludeDeleted)
+            .await
+            .map_err(catalog_error)?
+            .ok_or_else(|| tonic::Status::not_found(format!("namespace {name} not found")))
+    }
+
+    async fn task(&self, namespace: &Namespace, name: &str) -> Result<Task, tonic::Status> {
+        self.catalog
+            .repositories()
+            .await
+            .tasks()
+            .get_by_namespace_and_name(namespace.id, name)
+            .await
+            .map_err(catalog_error)?
+            .ok_or_else(|| {
+                tonic::Status::not_found(format!(
+                    "task {name} not found in namespace {}",
+                    namespace.name
+                ))
+            })
+    }
+}
+
+fn catalog_error(e: CatalogError) -> tonic::Status {
+    tonic::Status::internal(e.to_string())
+}
+
+/// Translate a [`Task`] of `namespace_name` to its protobuf form.
+fn task_to_proto(namespace_name: &str, task: Task) -> proto::Task {
+    proto::Task {
+        id: task.id.get(),
+        namespace_name: namespace_name.to_string(),
+        name: task.name,
+        schedule: task.schedule,
+        query: task.query,
+        destination_table: task.destination_table,
+        max_retries: task.max_retries,
+        created_at_ns: task.created_at.get(),
+    }
+}
+
+/// Translate a [`TaskRun`] to its protobuf form.
+fn run_to_proto(run: TaskRun) -> proto::TaskRun {
+    let status = match run.status() {
+        TaskRunStatus::Running => proto::task_run::Status::Running,
+        TaskRunStatus::Succeeded => proto::task_run::Status::Succeeded,
+        TaskRunStatus::Failed => proto::task_run::Status::Failed,
+    };
+    proto::TaskRun {
+        task_id: run.task_id.get(),
+        scheduled_at_ns: run.scheduled_at.get(),
+        started_at_ns: run.started_at.get(),
+        finished_at_ns: run.finished_at.map(|t| t.get()),
+        status: status.into(),
+        attempts: run.attempts,
+        rows_written: run.rows_written,
+        error: run.error,
+    }
+}
+
+#[tonic::async_trait]
+impl proto::task_service_server::TaskService for TaskServiceImpl {
+    async fn create_task(
+        &self,
+        request: tonic::Request<proto::CreateTaskRequest>,
+    ) -> Result<tonic::Response<proto::CreateTaskResponse>, tonic::Status> {
+        self.authorize(request.metadata(), &request.get_ref().namespace_name)
+            .await?;
+
+        let request = request.into_inner();
+
+        if request.name.is_empty() || request.destination_table.is_empty() {
+            return Err(tonic::Status::invalid_argument(
+                "task name and destination table must be set",
+            ));
+        }
+        if request.max_retries < 0 {
+            return Err(tonic::Status::invalid_argument(
+                "max retries must not be negative",
+            ));
+        }
+        request
+            .schedule
+            .parse::<Schedule>()
+            .map_err(|e| tonic::Status::invalid_argument(format!("invalid schedule: {e}")))?;
+
+        let namespace = self.namespace(&request.namespace_name).await?;
+        let task = self
+            .catalog
+            .repositories()
+            .await
+            .tasks()
+            .create(
+                namespace.id,
+                &request.name,
+                &request.schedule,
+                &request.query,
+                &request.destination_table,
+                request.max_retries,
+            )
+            .await
+            .map_err(|e| match e {
+                CatalogError::NameExists { name } => tonic::Status::already_exists(format!(
+                    "task {name} already exists in namespace {}",
+                    namespace.name
+                )),
+                e => catalog_error(e),
+            })?;
+
+        Ok(tonic::Response::new(proto::CreateTaskResponse {
+            task: Some(task_to_proto(&namespace.name, task)),
+        }))
+    }
+
+    async fn list_tasks(
+        &self,
+        request: tonic::Request<proto::ListTasksRequest>,
+    ) -> Result<tonic::Response<proto::ListTasksResponse>, tonic::Status> {
+        self.authorize(request.metadata(), &request.get_ref().namespace_name)
+            .await?;
+
+        let request = request.into_inner();
+        let run_limit = match request.run_limit {
+            n if n <= 0 => 1,
+            n => n.into(),
+        };
+
+        let namespace = self.namespace(&request.namespace_name).await?;
+        let mut repos = self.catalog.repositories().await;
+        let tasks = repos
+            .tasks()
+            .list_by_namespace_id(namespace.id)
+            .await
+            .map_err(catalog_error)?;
+
+        let mut out = Vec::with_capacity(tasks.len());
+        for task in tasks {
+            let runs = repos
+                .tasks()
+                .list_runs(task.id, run_limit)
+                .await
+                .map_err(catalog_error)?;
+            out.push(proto::list_tasks_response::TaskWithRuns {
+                task: Some(task_to_proto(&namespace.name, task)),
+                runs: runs.into_iter().map(run_to_proto).collect(),
+            });
+        }
+
+        Ok(tonic::Response::new(proto::ListTasksResponse {
+            tasks: out,
+        }))
+    }
+
+    async fn run_task(
+        &self,
+        request: tonic::Request<proto::RunTaskRequest>,
+    ) -> Result<tonic::Response<proto::RunTaskResponse>, tonic::Status> {
+        self.authorize(request.metadata(), &request.get_ref().namespace_name)
+            .await?;
+
+        let request = request.into_inner();
+        let executor = self
+            .executor
+            .as_ref()
+            .ok_or_else(|| tonic::Status::failed_precondition("this querier does not run tasks"))?;
+
+        let namespace = self.namespace(&request.namespace_name).await?;
+        let task = self.task(&namespace, &request.name).await?;
+        let run = executor
+            .run_now(&task)
+            .await
+            .map_err(|e| tonic::Status::internal(e.to_string()))?
+            .ok_or_else(|| tonic::Status::aborted("a run of the task was already started"))?;
+
+        Ok(tonic::Response::new(proto::RunTaskResponse {
+            run: Some(run_to_proto(run)),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rpc::permissions::mock::{request_with_token, MockAuthorizer};
+    use generated_types::influxdata::iox::querier::v1::task_service_server::TaskService;
+    use iox_tests::TestCatalog;
+
+    #[tokio::test]
+    async fn test_create_list_tasks() {
+        let catalog = TestCatalog::new();
+        catalog.create_namespace_1hr_retention("bananas").await;
+        let service = TaskServiceImpl::new(catalog.catalog(), None, None);
+
+        let request = proto::CreateTaskRequest {
+            namespace_name: "bananas".to_string(),
+            name: "downsample".to_string(),
+            schedule: "*/5 * * * *".to_string(),
+            query: "SELECT 1".to_string(),
+            destination_table: "out".to_string(),
+            max_retries: 2,
+        };
+        let task = service
+            .create_task(tonic::Request::new(request.clone()))
+            .await
+            .unwrap()
+            .into_inner()
+            .task
+            .unwrap();
+        assert_eq!(task.namespace_name, "bananas");
+        assert_eq!(task.name, "downsample");
+        assert_eq!(task.max_retries, 2);
+
+        let err = service
+            .create_task(tonic::Request::new(request.clone()))
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), tonic::Code::AlreadyExists);
+
+        let err = service
+            .create_task(tonic::Request::new(proto::CreateTaskRequest {
+                schedule: "every 5 minutes".to_string(),
+                ..request.clone()
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), tonic::Code::InvalidArgument);
+
+        let err = service
+            .create_task(tonic::Request::new(proto::CreateTaskRequest {
+                namespace_name: "platanos".to_string(),
+                ..request
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), tonic::Code::NotFound);
+
+        let tasks = service
+            .list_tasks(tonic::Request::new(proto::ListTasksRequest {
+                namespace_name: "bananas".to_string(),
+                run_limit: 0,
+            }))
+            .await
+            .unwrap()
+            .into_inner()
+            .tasks;
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(tasks[0].task.as_ref(), Some(&task));
+        assert!(tasks[0].runs.is_empty());
+
+        // Tasks can't be run without an executor.
+        let err = service
+            .run_task(tonic::Request::new(proto::RunTaskRequest {
+                namespace_name: "bananas".to_string(),
+                name: "downsample".to_string(),
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), tonic::Code::FailedPrecondition);
+    }
+
+    #[tokio::test]
+    async fn test_authz() {
+        let catalog = TestCatalog::new();
+        catalog.create_namespace_1hr_retention("bananas").await;
+        catalog.create_namespace_1hr_retention("platanos").await;
+        let service = TaskServiceImpl::new(catalog.catalog(), None, Some(Arc::new(MockAuthorizer)));
+
+        let create = |namespace_name: &str, token| {
+            service.create_task(request_with_token(
+                proto::CreateTaskRequest {
+                    namespace_name: namespace_name.to_string(),
+                    name: "downsample".to_string(),
+                    schedule: "*/5 * * * *".to_string(),
+                    query: "SELECT 1".to_string(),
+                    destination_table: "out".to_string(),
+                    max_retries: 0,
+                },
+                token,
+            ))
+        };
+        assert_eq!(
+            create("bananas", None).await.unwrap_err().code(),
+            tonic::Code::Unauthenticated
+        );
+        assert_eq!(
+            create("bananas", Some("BAD")).await.unwrap_err().code(),
+            tonic::Code::PermissionDenied
+        );
+        assert_eq!(
+            create("platanos", Some("GOOD")).await.unwrap_err().code(),
+            tonic::Code::PermissionDenied
+        );
+        create("bananas", Some("GOOD")).await.unwrap();
+
+        let list = |namespace_name: &str, token| {
+            service.list_tasks(request_with_token(
+                proto::ListTasksRequest {
+                    namespace_name: namespace_name.to_string(),
+                    run_limit: 0,
+                },
+                token,
+            ))
+        };
+        assert_eq!(
+            list("bananas", Some("GOOD"))
+                .await
+                .unwrap()
+                .into_inner()
+                .tasks
+                .len(),
+            1
+        );
+        assert_eq!(
+            list("platanos", Some("GOOD")).await.unwrap_err().code(),
+            tonic::Code::PermissionDenied
+        );
+
+        let err = service
+            .run_task(request_with_token(
+                proto::RunTaskRequest {
+                    namespace_name: "bananas".to_string(),
+                    name: "downsample".to_string(),
+                },
+                Some("BAD"),
+            ))
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), tonic::Code::PermissionDenied);
+    }
+}
@@ -22,21 +22,28 @@ use std::{
     fmt::{Debug, Display},
     net::SocketAddr,
     sync::Arc,
+    time::Duration,
 };
 
 use async_trait::async_trait;
-use authz::{Authorizer, AuthorizerInstrumentation, IoxAuthorizer};
-use clap_blocks::{gossip::GossipConfig, router::RouterConfig};
+use authz::{
+    Authorizer, AuthorizerInstrumentation, CachingAuthorizer, IoxAuthorizer, TokenFileAuthorizer,
+};
+use clap_blocks::{
+    field_value_policy::FieldValuePolicyConfig,
+    gossip::GossipConfig,
+    router::{DefaultTagConflictConfig, RouterConfig, RpcWriteQueueOverflow, WriteNamingMode},
+};
 use data_types::NamespaceName;
 use hashbrown::HashMap;
 use hyper::{Body, Request, Response};
 use iox_catalog::interface::Catalog;
 use ioxd_common::{
     add_service,
-    http::error::{HttpApiError, HttpApiErrorSource},
+    http::error::{HttpApiError, HttpApiErrorDetail, HttpApiErrorSource},
     reexport::{
         generated_types::influxdata::iox::{
-            catalog::v1::catalog_service_server,
+            catalog::v1::{catalog_introspection_service_server, catalog_service_server},
             gossip::{v1::anti_entropy_service_server, Topic},
             namespace::v1::namespace_service_server,
             object_store::v1::object_store_service_server,
@@ -51,12 +58,16 @@ use ioxd_common::{
     setup_builder,
 };
 use metric::Registry;
-use mutable_batch::MutableBatch;
+use mutable_batch::{value_policy::FieldValuePolicy, MutableBatch};
+use mutable_batch_lp::{NamingMode, NamingRules};
 use object_store::DynObjectStore;
 use router::{
     dml_handlers::{
-        lazy_connector::LazyConnector, DmlHandler, DmlHandlerChainExt, FanOutAdaptor,
-        InstrumentationDecorator, Partitioner, RetentionValidator, RpcWrite,
+        bounded_queue::{BoundedQueueClient, QueueOverflow},
+        lazy_connector::LazyConnector,
+        DefaultTagConflict, DefaultTagInjector, DmlHandler, DmlHandlerChainExt, FanOutAdaptor,
+        GroupCommit, InstrumentationDecorator, Partitioner, ReadOnlyValidator, RetentionValidator,
+        RpcWrite,
     },
     gossip::{
         anti_entropy::{
@@ -73,7 +84,7 @@ use router::{
     },
     namespace_cache::{
         metrics::InstrumentedCache, CacheMissErr, MaybeLayer, MemoryNamespaceCache, NamespaceCache,
-        ReadThroughCache, ShardedCache,
+        NamespaceCacheValidator, ReadThroughCache, ShardedCache,
     },
     namespace_resolver::{
         MissingNamespaceAction, NamespaceAutocreation, NamespaceResolver, NamespaceSchemaResolver,
@@ -109,6 +120,12 @@ pub enum Error {
         addr: String,
     },
 
+    #[error("authz token file error: {0}")]
+    AuthzTokenFile(#[from] authz::TokenFileError),
+
+    #[error("invalid ingester table spread: {0}")]
+    TableSpread(#[from] router::dml_handlers::TableSpreadError),
+
     /// An error binding the UDP socket for gossip communication.
     #[error("failed to bind udp gossip socket: {0}")]
     GossipBind(std::io::Error),
@@ -120,14 +137,22 @@ pub struct RpcWriteRouterServerType<D, N, T> {
     server: RpcWriteRouterServer<D, N, T>,
     shutdown: CancellationToken,
     trace_collector: Option<Arc<dyn TraceCollector>>,
+
+    /// The in-memory namespace schema cache, for memory accounting.
+    namespace_cache: Arc<ShardedCache<MemoryNamespaceCache>>,
 }
 
 impl<D, N, T> RpcWriteRouterServerType<D, N, T> {
-    pub fn new(server: RpcWriteRouterServer<D, N, T>, common_state: &CommonServerState) -> Self {
+    pub fn new(
+        server: RpcWriteRouterServer<D, N, T>,
+        namespace_cache: Arc<ShardedCache<MemoryNamespaceCache>>,
+        common_state: &CommonServerState,
+    ) -> Self {
         Self {
             server,
             shutdown: CancellationToken::new(),
             trace_collector: common_state.trace_collector(),
+            namespace_cache,
         }
     }
 }
@@ -174,6 +199,11 @@ where
             .map_err(|e| Box::new(e) as _)
     }
 
+    /// Report the memory held by the namespace schema cache.
+    fn memory_usage(&self) -> Vec<(&'static str, usize)> {
+        vec![("namespace_cache_bytes", self.namespace_cache.size())]
+    }
+
     /// Registers the services exposed by the router [`RpcWriteGrpcDelegate`] delegate.
     ///
     /// [`RpcWriteGrpcDelegate`]: router::server::grpc::RpcWriteGrpcDelegate
@@ -187,6 +217,12 @@ where
             builder,
             catalog_service_server::CatalogServiceServer::new(self.server.grpc().catalog_service())
         );
+        add_service!(
+            builder,
+            catalog_introspection_service_server::CatalogIntrospectionServiceServer::new(
+                self.server.grpc().catalog_introspection_service()
+            )
+        );
         add_service!(
             builder,
             object_store_service_server::ObjectStoreServiceServer::new(
@@ -242,9 +278,46 @@ impl HttpApiErrorSource for IoxHttpErrorAdaptor {
     fn to_http_api_error(&self) -> HttpApiError {
         HttpApiError::new(self.0.as_status_code(), self.to_string())
             .with_line(self.0.get_parse_error_line_index())
+            .with_details(
+                self.0
+                    .rejected_tables()
+                    .into_iter()
+                    .map(|r| HttpApiErrorDetail {
+                        table: r.table.to_string(),
+                        code: r.code.to_string(),
+                        message: r.message,
+                    })
+                    .collect(),
+            )
     }
 }
 
+/// Build the [`NamingRules`] enforced on written line protocol, if enabled.
+fn naming_rules(router_config: &RouterConfig) -> Option<NamingRules> {
+    let mode = match router_config.write_naming_mode {
+        WriteNamingMode::Disabled => return None,
+        WriteNamingMode::Reject => NamingMode::Reject,
+        WriteNamingMode::Normalize => NamingMode::Normalize,
+    };
+
+    let mut rules = NamingRules::new(mode);
+    if let Some(max) = router_config.write_naming_max_length {
+        rules = rules.with_max_length(max);
+    }
+    for prefix in router_config
+        .write_naming_reserved_prefixes
+        .iter()
+        .filter(|p| !p.is_empty())
+    {
+        rules = rules.with_reserved_prefix(prefix.as_str());
+    }
+    if router_config.write_naming_restrict_charset {
+        rules = rules.with_restricted_charset();
+    }
+
+    Some(rules)
+}
+
 /// Instantiate a router server that uses the RPC write path
 #[allow(clippy::too_many_arguments)]
 pub async fn create_router_server_type(
@@ -257,16 +330,27 @@ pub async fn create_router_server_type(
     trace_context_header_name: String,
     grpc_bind_port: u16,
 ) -> Result<Arc<dyn ServerType>> {
+    let queue_overflow = match router_config.rpc_write_queue_overflow {
+        RpcWriteQueueOverflow::Block => QueueOverflow::Block,
+        RpcWriteQueueOverflow::Reject => QueueOverflow::Reject,
+    };
     let ingester_connections = router_config.ingester_addresses.iter().map(|addr| {
         let addr = addr.to_string();
         let endpoint = Endpoint::from_shared(hyper::body::Bytes::from(addr.clone()))
             .expect("invalid ingester connection address");
+        let connector = LazyConnector::new(
+            endpoint,
+            router_config.rpc_write_timeout_seconds,
+            router_config.rpc_write_max_outgoing_bytes,
+            trace_context_header_name.clone(),
+        );
         (
-            LazyConnector::new(
-                endpoint,
-                router_config.rpc_write_timeout_seconds,
-                router_config.rpc_write_max_outgoing_bytes,
-                trace_context_header_name.clone(),
+            BoundedQueueClient::new(
+                connector,
+                addr.clone(),
+                router_config.rpc_write_max_queued_requests,
+                queue_overflow,
+                &metrics,
             ),
             addr,
         )
@@ -279,15 +363,30 @@ pub async fn create_router_server_type(
         &metrics,
         router_config.rpc_write_health_num_probes,
     );
+    let rpc_writer = match router_config.ingester_table_spread {
+        Some(spread) => rpc_writer.with_partition_affinity(spread)?,
+        None => rpc_writer,
+    };
     let rpc_writer = InstrumentationDecorator::new("rpc_writer", &metrics, rpc_writer);
 
+    // Coalesce small writes to the same partition into a single RPC write, if
+    // configured.
+    let rpc_writer = GroupCommit::new(
+        rpc_writer,
+        router_config.rpc_write_group_commit_window,
+        &metrics,
+    );
+
     // # Namespace cache
     //
     // Initialise an instrumented namespace cache to be shared with the schema
     // validator, and namespace auto-creator that reports cache hit/miss/update
     // metrics.
+    let memory_ns_cache = Arc::new(ShardedCache::new(
+        std::iter::repeat_with(MemoryNamespaceCache::default).take(10),
+    ));
     let ns_cache = Arc::new(InstrumentedCache::new(
-        ShardedCache::new(std::iter::repeat_with(MemoryNamespaceCache::default).take(10)),
+        Arc::clone(&memory_ns_cache),
         &metrics,
     ));
 
@@ -333,25 +432,84 @@ pub async fn create_router_server_type(
     let ns_cache = Arc::new(ns_cache);
     let sync_rpc_server = AntiEntropyService::new(mst, Arc::clone(&ns_cache));
 
+    // Optionally start the background validation of the cache content against
+    // the catalog, repairing divergent entries through the MST and gossip
+    // layers.
+    if let Some(secs) = router_config.namespace_cache_validation_interval_seconds {
+        let validator = NamespaceCacheValidator::new(
+            Arc::clone(&ns_cache),
+            Arc::clone(&memory_ns_cache),
+            Arc::clone(&catalog),
+            Duration::from_secs(secs),
+            router_config.namespace_cache_validation_sample_size,
+            &metrics,
+        );
+        tokio::spawn(validator.run());
+    }
+
     // Wrap the NamespaceCache in a read-through layer that queries the catalog
     // for cache misses, and populates the local cache with the result.
     let ns_cache = Arc::new(ReadThroughCache::new(ns_cache, Arc::clone(&catalog)));
 
     // # Schema validator
     //
-    // Initialise and instrument the schema validator
+    // Initialise and instrument the schema validator, enforcing the field
+    // value policy (if any) against the namespace schema
     let schema_validator =
         SchemaValidator::new(Arc::clone(&catalog), Arc::clone(&ns_cache), &metrics);
+    let schema_validator = match router_config.field_value_policy {
+        FieldValuePolicyConfig::Disabled => schema_validator,
+        FieldValuePolicyConfig::Reject => {
+            schema_validator.with_field_value_policy(FieldValuePolicy::Reject, &metrics)
+        }
+        FieldValuePolicyConfig::Clamp => {
+            schema_validator.with_field_value_policy(FieldValuePolicy::Clamp, &metrics)
+        }
+        FieldValuePolicyConfig::Null => {
+            schema_validator.with_field_value_policy(FieldValuePolicy::Null, &metrics)
+        }
+    };
     let schema_validator =
         InstrumentationDecorator::new("schema_validator", &metrics, schema_validator);
 
     // # Retention validator
     //
-    // Add a retention validator into handler stack to reject data outside the retention period
+    // Add a retention validator into handler stack to reject (or drop) data
+    // outside the retention period
     let retention_validator = RetentionValidator::new();
+    let retention_validator = if router_config.drop_rows_outside_retention {
+        retention_validator.with_drop_outside_retention(&metrics)
+    } else {
+        retention_validator
+    };
     let retention_validator =
         InstrumentationDecorator::new("retention_validator", &metrics, retention_validator);
 
+    // # Read-only validator
+    //
+    // Reject writes to namespaces marked as read-only in the catalog
+    let read_only_validator = ReadOnlyValidator::new(
+        Arc::clone(&catalog),
+        router_config.namespace_read_only_cache_ttl,
+    );
+    let read_only_validator =
+        InstrumentationDecorator::new("read_only_validator", &metrics, read_only_validator);
+
+    // # Default tags
+    //
+    // Add the default tags configured in the catalog for the namespace to
+    // every written row, before schema validation adds the tag columns
+    let default_tags = DefaultTagInjector::new(
+        Arc::clone(&catalog),
+        router_config.namespace_default_tags_cache_ttl,
+        match router_config.namespace_default_tags_conflict {
+            DefaultTagConflictConfig::Keep => DefaultTagConflict::Keep,
+            DefaultTagConflictConfig::Overwrite => DefaultTagConflict::Overwrite,
+            DefaultTagConflictConfig::Reject => DefaultTagConflict::Reject,
+        },
+    );
+    let default_tags = InstrumentationDecorator::new("default_tags", &metrics, default_tags);
+
     // # Write partitioner
     //
     // Add a write partitioner into the handler stack that splits by the date
@@ -389,7 +547,9 @@ pub async fn create_router_server_type(
     // # Handler stack
     //
     // Build the chain of DML handlers that forms the request processing pipeline
-    let handler_stack = retention_validator
+    let handler_stack = read_only_validator
+        .and_then(retention_validator)
+        .and_then(default_tags)
         .and_then(schema_validator)
         .and_then(partitioner)
         // Once writes have been partitioned, they are processed in parallel.
@@ -415,7 +575,11 @@ pub async fn create_router_server_type(
         (true, Some(addr)) => {
             let authz = IoxAuthorizer::connect_lazy(addr.clone())
                 .map(|c| {
-                    Arc::new(AuthorizerInstrumentation::new(&metrics, c)) as Arc<dyn Authorizer>
+                    Arc::new(CachingAuthorizer::new(
+                        AuthorizerInstrumentation::new(&metrics, c),
+                        router_config.authz_cache_ttl,
+                        &metrics,
+                    )) as Arc<dyn Authorizer>
                 })
                 .map_err(|source| Error::AuthzConfig {
                     source,
@@ -432,14 +596,22 @@ pub async fn create_router_server_type(
             // never reach here.
             unreachable!("INFLUXDB_IOX_SINGLE_TENANCY is set, but could not create an authz service. Check the INFLUXDB_IOX_AUTHZ_ADDR")
         }
-        (false, None) => Ok(Box::<MultiTenantRequestUnifier>::default()),
+        (false, None) => match &router_config.authz_token_file {
+            Some(path) => {
+                let authz = TokenFileAuthorizer::load(path, &metrics)?;
+                Ok(Box::new(MultiTenantRequestUnifier::with_authorizer(
+                    Arc::new(authz),
+                )))
+            }
+            None => Ok(Box::<MultiTenantRequestUnifier>::default()),
+        },
         (false, Some(_)) => {
             // As above, this combination should be prevented by the
             // router's clap flag parse configuration.
             unreachable!("INFLUXDB_IOX_AUTHZ_ADDR is set, but authz only exists for single_tenancy. Check the INFLUXDB_IOX_SINGLE_TENANCY")
         }
     };
-    let http = HttpDelegate::new(
+    let mut http = HttpDelegate::new(
         common_state.run_config().max_http_request_size,
         router_config.http_request_limit,
         namespace_resolver,
@@ -447,6 +619,9 @@ pub async fn create_router_server_type(
         &metrics,
         write_request_unifier?,
     );
+    if let Some(rules) = naming_rules(router_config) {
+        http = http.with_naming_rules(rules);
+    }
 
     // Initialize the gRPC API delegate that creates the services relevant to the RPC
     // write router path and use it to create the relevant `RpcWriteRouterServer` and
@@ -455,7 +630,11 @@ pub async fn create_router_server_type(
 
     let router_server =
         RpcWriteRouterServer::new(http, grpc, metrics, common_state.trace_collector());
-    let server_type = Arc::new(RpcWriteRouterServerType::new(router_server, common_state));
+    let server_type = Arc::new(RpcWriteRouterServerType::new(
+        router_server,
+        memory_ns_cache,
+        common_state,
+    ));
     Ok(server_type)
 }
 
@@ -22,18 +22,19 @@
 // Workaround for "unused crate" lint false positives.
 use workspace_hack as _;
 
-use crate::column::{Column, ColumnData};
+use crate::column::{Column, ColumnData, INVALID_DID};
 use arrow::record_batch::RecordBatch;
 use data_types::StatValues;
 use hashbrown::HashMap;
 use iox_time::Time;
 use schema::Projection;
-use schema::{builder::SchemaBuilder, Schema, TIME_COLUMN_NAME};
+use schema::{builder::SchemaBuilder, InfluxColumnType, Schema, TIME_COLUMN_NAME};
 use snafu::{OptionExt, ResultExt, Snafu};
-use std::{collections::BTreeSet, ops::Range};
+use std::{collections::BTreeSet, num::NonZeroU64, ops::Range};
 
 pub mod column;
 pub mod payload;
+pub mod value_policy;
 pub mod writer;
 
 pub use payload::*;
@@ -195,6 +196,63 @@ impl MutableBatch {
         Ok(())
     }
 
+    /// Set the tag column `name` to `value` in every row without a value for
+    /// it, or in every row if `overwrite` is true, adding the column if it
+    /// does not exist.
+    ///
+    /// Returns the number of rows whose value was set. Fails if `name` exists
+    /// and is not a tag column.
+    pub fn set_tag(&mut self, name: &str, value: &str, overwrite: bool) -> Result<usize> {
+        let rows = self.row_count;
+        let idx = match self.column_names.get(name) {
+            Some(idx) => *idx,
+            None => {
+                self.columns.push(Column::new(rows, InfluxColumnType::Tag));
+                self.column_names
+                    .insert(name.to_string(), self.columns.len() - 1);
+                self.columns.len() - 1
+            }
+        };
+
+        let col = &mut self.columns[idx];
+        if col.influx_type != InfluxColumnType::Tag {
+            return Err(writer::Error::TypeMismatch {
+                column: name.to_string(),
+                existing: col.influx_type,
+                inserted: InfluxColumnType::Tag,
+            }
+            .into());
+        }
+        if overwrite {
+            *col = Column::new(rows, InfluxColumnType::Tag);
+        }
+
+        let ColumnData::Tag(data, dict, stats) = &mut col.data else {
+            unreachable!("tag column with non-tag data");
+        };
+
+        let did = dict.lookup_value_or_insert(value);
+        let mut set = 0;
+        for (row, v) in data.iter_mut().enumerate() {
+            if *v == INVALID_DID {
+                *v = did;
+                col.valid.set(row);
+                set += 1;
+            }
+        }
+
+        // Every row now has a value, drawn from the values of the dictionary.
+        let mut new_stats = StatValues::new_empty();
+        for v in dict.values().iter() {
+            new_stats.update(v);
+        }
+        new_stats.total_count = rows as u64;
+        new_stats.distinct_count = NonZeroU64::new(dict.values().len() as u64);
+        *stats = new_stats;
+
+        Ok(set)
+    }
+
     /// Returns a reference to the specified column
     pub fn column(&self, column: &str) -> Result<&Column> {
         let idx = self
@@ -262,8 +320,11 @@ impl TimestampSummary {
 
 #[cfg(test)]
 mod tests {
+    use arrow_util::assert_batches_eq;
     use mutable_batch_lp::lines_to_batches;
 
+    use super::*;
+
     #[test]
     fn size_data_without_nulls() {
         let batches = lines_to_batches(
@@ -298,4 +359,52 @@ mod tests {
         assert_eq!(batch.size_data(), 124);
         assert_eq!(batch.columns().len(), 5);
     }
+
+    #[test]
+    fn set_tag() {
+        let mut batches =
+            lines_to_batches("cpu,env=dev f=1 1\ncpu f=2 2\ncpu,env=prod f=3 3", 0).unwrap();
+        let mut batch = batches.remove("cpu").unwrap();
+
+        // Only the rows without a value are set.
+        assert_eq!(batch.set_tag("env", "prod", false).unwrap(), 1);
+        assert_batches_eq!(
+            [
+                "+------+-----+--------------------------------+",
+                "| env  | f   | time                           |",
+                "+------+-----+--------------------------------+",
+                "| dev  | 1.0 | 1970-01-01T00:00:00.000000001Z |",
+                "| prod | 2.0 | 1970-01-01T00:00:00.000000002Z |",
+                "| prod | 3.0 | 1970-01-01T00:00:00.000000003Z |",
+                "+------+-----+--------------------------------+",
+            ],
+            &[batch.to_arrow(Projection::All).unwrap()]
+        );
+        let stats = batch.column("env").unwrap().stats();
+        assert_eq!(stats.null_count(), Some(0));
+        assert_eq!(stats.distinct_count(), NonZeroU64::new(2));
+
+        // Overwriting sets all rows, and adds missing columns.
+        assert_eq!(batch.set_tag("env", "test", true).unwrap(), 3);
+        assert_eq!(batch.set_tag("region", "eu", false).unwrap(), 3);
+        assert_batches_eq!(
+            [
+                "+------+-----+--------+--------------------------------+",
+                "| env  | f   | region | time                           |",
+                "+------+-----+--------+--------------------------------+",
+                "| test | 1.0 | eu     | 1970-01-01T00:00:00.000000001Z |",
+                "| test | 2.0 | eu     | 1970-01-01T00:00:00.000000002Z |",
+                "| test | 3.0 | eu     | 1970-01-01T00:00:00.000000003Z |",
+                "+------+-----+--------+--------------------------------+",
+            ],
+            &[batch.to_arrow(Projection::All).unwrap()]
+        );
+        assert_eq!(
+            batch.column("env").unwrap().stats().distinct_count(),
+            NonZeroU64::new(1)
+        );
+
+        // Fields cannot be set as tags.
+        assert!(batch.set_tag("f", "x", false).is_err());
+    }
 }
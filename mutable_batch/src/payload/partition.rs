@@ -10,9 +10,10 @@ mod strftime;
 use std::{borrow::Cow, ops::Range};
 
 use data_types::partition_template::{
-    TablePartitionTemplateOverride, TemplatePart, ENCODED_PARTITION_KEY_CHARS,
-    MAXIMUM_NUMBER_OF_TEMPLATE_PARTS, PARTITION_KEY_DELIMITER, PARTITION_KEY_MAX_PART_LEN,
-    PARTITION_KEY_PART_TRUNCATED, PARTITION_KEY_VALUE_EMPTY_STR, PARTITION_KEY_VALUE_NULL_STR,
+    bucket_for_tag_value, TablePartitionTemplateOverride, TemplatePart,
+    ENCODED_PARTITION_KEY_CHARS, MAXIMUM_NUMBER_OF_TEMPLATE_PARTS, PARTITION_KEY_DELIMITER,
+    PARTITION_KEY_MAX_PART_LEN, PARTITION_KEY_PART_TRUNCATED, PARTITION_KEY_VALUE_EMPTY_STR,
+    PARTITION_KEY_VALUE_NULL_STR,
 };
 use percent_encoding::utf8_percent_encode;
 use schema::{InfluxColumnType, TIME_COLUMN_NAME};
@@ -77,6 +78,9 @@ pub fn partition_batch<'a>(
 enum Template<'a> {
     TagValue(&'a Column, Option<i32>),
     TimeFormat(&'a [i64], StrftimeFormatter<'a>),
+    /// A tag column, the last dictionary key rendered, and the number of
+    /// buckets the tag values are hashed into.
+    Bucket(&'a Column, Option<i32>, u32),
 
     /// This batch is missing a partitioning tag column.
     MissingTag,
@@ -105,9 +109,20 @@ impl<'a> Template<'a> {
                 _ => return Err(PartitionKeyError::TagValueNotTag(col.influx_type())),
             },
             Template::TimeFormat(t, fmt) => fmt.render(t[idx], out)?,
+            Template::Bucket(col, last_key, num_buckets) if col.valid.get(idx) => match &col.data {
+                ColumnData::Tag(col_data, dictionary, _) => {
+                    let this_key = col_data[idx];
+                    *last_key = Some(this_key);
+
+                    let bucket =
+                        bucket_for_tag_value(dictionary.lookup_id(this_key).unwrap(), *num_buckets);
+                    write!(out, "{bucket}")?
+                }
+                _ => return Err(PartitionKeyError::TagValueNotTag(col.influx_type())),
+            },
             // Either a tag that has no value for this given row index, or the
             // batch does not contain this tag at all.
-            Template::TagValue(_, last_key) => {
+            Template::TagValue(_, last_key) | Template::Bucket(_, last_key, _) => {
                 // This row doesn't have a tag value, which should be carried
                 // forwards to be checked against the next row.
                 *last_key = None;
@@ -123,28 +138,33 @@ impl<'a> Template<'a> {
     /// identical to the last generated key.
     fn is_identical(&self, idx: usize) -> bool {
         match self {
-            Template::TagValue(col, last_key) if col.valid.get(idx) => match &col.data {
-                ColumnData::Tag(col_data, _, _) => {
-                    let this_key = col_data[idx];
-                    // Check if the dictionary key matches the last dictionary
-                    // key, indicating the same value is going to be rendered.
-                    last_key.map(|v| v == this_key).unwrap_or_default()
+            Template::TagValue(col, last_key) | Template::Bucket(col, last_key, _)
+                if col.valid.get(idx) =>
+            {
+                match &col.data {
+                    ColumnData::Tag(col_data, _, _) => {
+                        let this_key = col_data[idx];
+                        // Check if the dictionary key matches the last
+                        // dictionary key, indicating the same value is going
+                        // to be rendered.
+                        last_key.map(|v| v == this_key).unwrap_or_default()
+                    }
+                    // This is an error, but for the purposes of identical
+                    // checks, it is treated as not identical, causing the
+                    // error to be raised when formatting is attempted.
+                    _ => false,
                 }
-                // This is an error, but for the purposes of identical checks,
-                // it is treated as not identical, causing the error to be
-                // raised when formatting is attempted.
-                _ => false,
-            },
+            }
             Template::TimeFormat(t, fmt) => {
                 // Check if the last value matches the current value, after
                 // optionally applying the precision reduction optimisation.
                 fmt.equals_last(t[idx])
             }
             // The last row did not contain this key, and neither does this.
-            Template::TagValue(_, None) => true,
+            Template::TagValue(_, None) | Template::Bucket(_, None, _) => true,
             // The last row did contain a key, but this one does not (therefore
             // it differs).
-            Template::TagValue(_, Some(_)) => false,
+            Template::TagValue(_, Some(_)) | Template::Bucket(_, Some(_), _) => false,
 
             // The batch does not contain this tag at all - it always matches
             // with the previous row.
@@ -223,6 +243,10 @@ fn partition_keys<'a>(
             TemplatePart::TimeFormat(fmt) => {
                 Template::TimeFormat(time, StrftimeFormatter::new(fmt))
             }
+            TemplatePart::Bucket(col_name, num_buckets) => batch.column(col_name).map_or_else(
+                |_| Template::MissingTag,
+                |v| Template::Bucket(v, None, num_buckets),
+            ),
         })
         .collect::<Vec<_>>();
 
@@ -527,6 +551,54 @@ mod tests {
         )
     }
 
+    #[test]
+    fn test_partition_bucket() {
+        let mut batch = MutableBatch::new();
+        let mut writer = Writer::new(&mut batch, 5);
+
+        writer
+            .write_time("time", vec![1, 2, 3, 4, 5].into_iter())
+            .unwrap();
+
+        writer
+            .write_tag(
+                "host",
+                Some(&[0b00011011]),
+                vec!["a", "a", "b", "c"].into_iter(),
+            )
+            .unwrap();
+
+        let template_parts = [
+            TemplatePart::TimeFormat("%Y"),
+            TemplatePart::Bucket("host", 10),
+            TemplatePart::Bucket("bananas", 10), // column not present
+        ];
+
+        writer.commit();
+
+        let keys = generate_denormalised_keys(&batch, template_parts.into_iter()).unwrap();
+
+        let bucket = |v| bucket_for_tag_value(v, 10);
+        assert_eq!(
+            keys,
+            vec![
+                format!("1970|{}|!", bucket("a")),
+                format!("1970|{}|!", bucket("a")),
+                "1970|!|!".to_string(),
+                format!("1970|{}|!", bucket("b")),
+                format!("1970|{}|!", bucket("c")),
+            ]
+        );
+
+        // The bucket part is not reversible, but the remaining parts are.
+        let template = test_table_partition_override(template_parts.to_vec());
+        let got = build_column_values(&template, &keys[0]).collect::<Vec<_>>();
+        assert_matches!(
+            got.as_slice(),
+            [(TIME_COLUMN_NAME, ColumnValue::Datetime { .. })]
+        );
+    }
+
     #[test]
     fn test_sparse_representation() {
         let mut batch = MutableBatch::new();
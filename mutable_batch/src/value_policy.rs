@@ -0,0 +1,316 @@
+//! Enforcement of a [`FieldValuePolicy`] on the field values of a
+//! [`MutableBatch`].
+
+use arrow_util::bitset::BitSet;
+use data_types::{IsNan, StatValues};
+use schema::{InfluxColumnType, InfluxFieldType};
+use snafu::Snafu;
+
+use crate::{column::ColumnData, MutableBatch};
+
+/// The handling of field values that cannot be stored as written:
+///
+/// * Non-finite (NaN or infinite) float values.
+/// * Unsigned integer values written to a column that is a (signed) integer
+///   column in the table schema. Values up to [`i64::MAX`] are always
+///   converted, and the policy applies to the values exceeding it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldValuePolicy {
+    /// Reject the write.
+    Reject,
+
+    /// Replace the value with the closest representable value.
+    ///
+    /// Infinite floats are replaced with [`f64::MAX`] / [`f64::MIN`], and
+    /// unsigned integers with [`i64::MAX`]. NaN has no closest value, and is
+    /// replaced with NULL.
+    Clamp,
+
+    /// Replace the value with NULL.
+    Null,
+}
+
+/// A field value rejected by [`FieldValuePolicy::Reject`].
+#[derive(Debug, Snafu, PartialEq)]
+#[allow(missing_docs)]
+pub enum FieldValueError {
+    #[snafu(display("non-finite value {} for float field {} in row {}", value, column, row))]
+    NonFiniteFloat {
+        column: String,
+        row: usize,
+        value: f64,
+    },
+
+    #[snafu(display(
+        "value {} for integer field {} in row {} overflows i64",
+        value,
+        column,
+        row
+    ))]
+    UnsignedOverflow {
+        column: String,
+        row: usize,
+        value: u64,
+    },
+}
+
+/// The number of field values replaced when enforcing a [`FieldValuePolicy`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct FieldValueActions {
+    /// Values replaced with the closest representable value.
+    pub clamped: usize,
+
+    /// Values replaced with NULL.
+    pub nulled: usize,
+}
+
+impl MutableBatch {
+    /// Enforce `policy` on the field values of this batch, converting the
+    /// unsigned integer columns for which `is_integer_column` returns true
+    /// into integer columns.
+    ///
+    /// If an error is returned the batch may have been partially converted,
+    /// and should be discarded.
+    pub fn enforce_field_value_policy(
+        &mut self,
+        policy: FieldValuePolicy,
+        is_integer_column: impl Fn(&str) -> bool,
+    ) -> Result<FieldValueActions, FieldValueError> {
+        let mut actions = FieldValueActions::default();
+
+        for (name, idx) in &self.column_names {
+            let column = &mut self.columns[*idx];
+            let valid = &mut column.valid;
+
+            let converted = match &mut column.data {
+                ColumnData::F64(values, stats) => {
+                    let mut changed = false;
+                    for (row, value) in values.iter_mut().enumerate() {
+                        if value.is_finite() || !valid.get(row) {
+                            continue;
+                        }
+                        match policy {
+                            FieldValuePolicy::Reject => {
+                                return NonFiniteFloatSnafu {
+                                    column: name,
+                                    row,
+                                    value: *value,
+                                }
+                                .fail()
+                            }
+                            FieldValuePolicy::Clamp if value.is_infinite() => {
+                                *value = if value.is_sign_positive() {
+                                    f64::MAX
+                                } else {
+                                    f64::MIN
+                                };
+                                actions.clamped += 1;
+                            }
+                            FieldValuePolicy::Clamp | FieldValuePolicy::Null => {
+                                *value = 0.0;
+                                valid.unset(row);
+                                actions.nulled += 1;
+                            }
+                        }
+                        changed = true;
+                    }
+                    if changed {
+                        *stats = stats_for(values, valid);
+                    }
+                    None
+                }
+                ColumnData::U64(values, _) if is_integer_column(name) => {
+                    let mut converted = Vec::with_capacity(values.len());
+                    for (row, &value) in values.iter().enumerate() {
+                        let value = match i64::try_from(value) {
+                            Ok(v) => v,
+                            Err(_) if !valid.get(row) => 0,
+                            Err(_) => match policy {
+                                FieldValuePolicy::Reject => {
+                                    return UnsignedOverflowSnafu {
+                                        column: name,
+                                        row,
+                                        value,
+                                    }
+                                    .fail()
+                                }
+                                FieldValuePolicy::Clamp => {
+                                    actions.clamped += 1;
+                                    i64::MAX
+                                }
+                                FieldValuePolicy::Null => {
+                                    valid.unset(row);
+                                    actions.nulled += 1;
+                                    0
+                                }
+                            },
+                        };
+                        converted.push(value);
+                    }
+                    Some(converted)
+                }
+                _ => None,
+            };
+
+            if let Some(values) = converted {
+                let stats = stats_for(&values, &column.valid);
+                column.data = ColumnData::I64(values, stats);
+                column.influx_type = InfluxColumnType::Field(InfluxFieldType::Integer);
+            }
+        }
+
+        Ok(actions)
+    }
+}
+
+/// Compute the [`StatValues`] of `values`, ignoring those not set in `valid`.
+fn stats_for<T>(values: &[T], valid: &BitSet) -> StatValues<T>
+where
+    T: Clone + PartialOrd + IsNan,
+{
+    let mut stats = StatValues::new_empty();
+    for (row, value) in values.iter().enumerate() {
+        if valid.get(row) {
+            stats.update(value);
+        } else {
+            stats.update_for_nulls(1);
+        }
+    }
+    stats
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_matches::assert_matches;
+    use data_types::Statistics;
+
+    use super::*;
+    use crate::writer::Writer;
+
+    fn batch() -> MutableBatch {
+        let mut batch = MutableBatch::new();
+        let mut writer = Writer::new(&mut batch, 4);
+        writer
+            .write_f64(
+                "f",
+                None,
+                [1.5, f64::INFINITY, f64::NAN, f64::NEG_INFINITY].into_iter(),
+            )
+            .unwrap();
+        writer
+            .write_u64("u", None, [1, u64::MAX, 3, 4].into_iter())
+            .unwrap();
+        writer
+            .write_u64("other", None, [1, u64::MAX, 3, 4].into_iter())
+            .unwrap();
+        writer.write_time("time", [1, 2, 3, 4].into_iter()).unwrap();
+        writer.commit();
+        batch
+    }
+
+    #[test]
+    fn test_reject() {
+        let mut b = batch();
+        let err = b
+            .enforce_field_value_policy(FieldValuePolicy::Reject, |_| false)
+            .unwrap_err();
+        assert_matches!(err, FieldValueError::NonFiniteFloat { column, row: 1, .. } => {
+            assert_eq!(column, "f");
+        });
+
+        // Unsigned values are rejected only for integer columns.
+        let mut b = MutableBatch::new();
+        let mut writer = Writer::new(&mut b, 2);
+        writer
+            .write_u64("u", None, [1, u64::MAX].into_iter())
+            .unwrap();
+        writer.commit();
+        let mut ok = b.clone();
+        assert_eq!(
+            ok.enforce_field_value_policy(FieldValuePolicy::Reject, |_| false),
+            Ok(FieldValueActions::default())
+        );
+        assert_eq!(
+            b.enforce_field_value_policy(FieldValuePolicy::Reject, |c| c == "u"),
+            Err(FieldValueError::UnsignedOverflow {
+                column: "u".to_string(),
+                row: 1,
+                value: u64::MAX,
+            })
+        );
+    }
+
+    #[test]
+    fn test_clamp() {
+        let mut b = batch();
+        let actions = b
+            .enforce_field_value_policy(FieldValuePolicy::Clamp, |c| c == "u")
+            .unwrap();
+        assert_eq!(
+            actions,
+            FieldValueActions {
+                clamped: 3,
+                nulled: 1,
+            }
+        );
+
+        let f = b.column("f").unwrap();
+        assert_matches!(f.stats(), Statistics::F64(stats) => {
+            assert_eq!(stats.min, Some(f64::MIN));
+            assert_eq!(stats.max, Some(f64::MAX));
+            assert_eq!(stats.null_count, Some(1));
+        });
+        assert!(!f.valid_mask().get(2));
+
+        let u = b.column("u").unwrap();
+        assert_eq!(
+            u.influx_type(),
+            InfluxColumnType::Field(InfluxFieldType::Integer)
+        );
+        assert_matches!(u.data(), ColumnData::I64(values, _) => {
+            assert_eq!(values, &[1, i64::MAX, 3, 4]);
+        });
+
+        // Unsigned columns that are not integer columns are unchanged.
+        let other = b.column("other").unwrap();
+        assert_eq!(
+            other.influx_type(),
+            InfluxColumnType::Field(InfluxFieldType::UInteger)
+        );
+
+        b.to_arrow(schema::Projection::All).unwrap();
+    }
+
+    #[test]
+    fn test_null() {
+        let mut b = batch();
+        let actions = b
+            .enforce_field_value_policy(FieldValuePolicy::Null, |c| c == "u")
+            .unwrap();
+        assert_eq!(
+            actions,
+            FieldValueActions {
+                clamped: 0,
+                nulled: 4,
+            }
+        );
+
+        let f = b.column("f").unwrap();
+        assert_matches!(f.stats(), Statistics::F64(stats) => {
+            assert_eq!(stats.min, Some(1.5));
+            assert_eq!(stats.max, Some(1.5));
+            assert_eq!(stats.null_count, Some(3));
+            assert_eq!(stats.total_count, 4);
+        });
+
+        let u = b.column("u").unwrap();
+        assert_matches!(u.stats(), Statistics::I64(stats) => {
+            assert_eq!(stats.min, Some(1));
+            assert_eq!(stats.max, Some(4));
+            assert_eq!(stats.null_count, Some(1));
+        });
+        assert!(!u.valid_mask().get(1));
+
+        b.to_arrow(schema::Projection::All).unwrap();
+    }
+}
@@ -21,11 +21,14 @@ use criterion as _;
 use workspace_hack as _;
 
 use hashbrown::{hash_map::Entry, HashMap, HashSet};
-use influxdb_line_protocol::{parse_lines, FieldValue, ParsedLine};
+use influxdb_line_protocol::{parse_lines, EscapedStr, FieldValue, ParsedLine};
 use mutable_batch::writer::Writer;
 use mutable_batch::MutableBatch;
 use snafu::{ResultExt, Snafu};
 
+mod naming;
+pub use naming::{NameError, NameKind, NamingMode, NamingRules};
+
 const MAXIMUM_RETURNED_ERRORS: usize = 100;
 
 /// Error type for a conversion attempt on a set of line protocol lines
@@ -57,6 +60,23 @@ pub enum LineError {
 
     #[snafu(display("timestamp overflows i64 on line {} (1-based)", line))]
     TimestampOverflow { line: usize },
+
+    #[snafu(display("invalid name on line {} (1-based): {}", line, source))]
+    InvalidName { source: NameError, line: usize },
+
+    #[snafu(display(
+        "tag '{}' must be '{}' on line {} (1-based), got '{}'",
+        tag,
+        want,
+        line,
+        got
+    ))]
+    RequiredTag {
+        tag: String,
+        want: String,
+        got: String,
+        line: usize,
+    },
 }
 
 /// Result type for line protocol conversion
@@ -82,6 +102,10 @@ pub struct LinesConverter {
     stats: PayloadStatistics,
     /// The current batches
     batches: HashMap<String, MutableBatch>,
+    /// The rules table, tag and field names must satisfy, if any
+    naming_rules: Option<NamingRules>,
+    /// The tag values every line must have
+    required_tags: Vec<(String, String)>,
 }
 
 impl LinesConverter {
@@ -92,9 +116,23 @@ impl LinesConverter {
             timestamp_base: 1,
             stats: Default::default(),
             batches: Default::default(),
+            naming_rules: None,
+            required_tags: Default::default(),
         }
     }
 
+    /// Validate (and optionally normalize) table, tag and field names against
+    /// `rules`, rejecting lines with names that violate them.
+    pub fn set_naming_rules(&mut self, rules: NamingRules) {
+        self.naming_rules = Some(rules)
+    }
+
+    /// Require every line to have each `(tag, value)` pair of `tags`, adding
+    /// the tag to lines without it and rejecting lines with a different value.
+    pub fn set_required_tags(&mut self, tags: Vec<(String, String)>) {
+        self.required_tags = tags
+    }
+
     /// Sets a multiplier to convert line protocol timestamps to nanoseconds
     pub fn set_timestamp_base(&mut self, timestamp_base: i64) {
         self.timestamp_base = timestamp_base
@@ -126,6 +164,8 @@ impl LinesConverter {
                 maybe_line
                     .context(LineProtocolSnafu { line: line_idx + 1 })
                     .and_then(|line| self.rebase_timestamp(line, line_idx))
+                    .and_then(|line| self.apply_naming_rules(line, line_idx))
+                    .and_then(|line| self.apply_required_tags(line, line_idx))
                     .and_then(|line| self.add_line_to_batch(line, line_idx))
                     .err()
             })
@@ -153,6 +193,56 @@ impl LinesConverter {
         Ok(line)
     }
 
+    fn apply_naming_rules<'a>(
+        &self,
+        mut line: ParsedLine<'a>,
+        line_idx: usize,
+    ) -> Result<ParsedLine<'a>, LineError> {
+        if let Some(rules) = &self.naming_rules {
+            rules
+                .apply_line(&mut line)
+                .context(InvalidNameSnafu { line: line_idx + 1 })?;
+        }
+        Ok(line)
+    }
+
+    fn apply_required_tags<'a>(
+        &self,
+        mut line: ParsedLine<'a>,
+        line_idx: usize,
+    ) -> Result<ParsedLine<'a>, LineError> {
+        for (tag, want) in &self.required_tags {
+            let got = line
+                .series
+                .tag_set
+                .iter()
+                .flatten()
+                .find(|(k, _v)| k.as_str() == tag.as_str())
+                .map(|(_k, v)| v.as_str());
+
+            match got {
+                Some(got) if got == want.as_str() => {}
+                Some(got) => {
+                    return Err(LineError::RequiredTag {
+                        tag: tag.clone(),
+                        want: want.clone(),
+                        got: got.to_string(),
+                        line: line_idx + 1,
+                    })
+                }
+                None => line
+                    .series
+                    .tag_set
+                    .get_or_insert_with(Default::default)
+                    .push((
+                        EscapedStr::CopiedValue(tag.clone()),
+                        EscapedStr::CopiedValue(want.clone()),
+                    )),
+            }
+        }
+        Ok(line)
+    }
+
     fn add_line_to_batch(
         &mut self,
         line: ParsedLine<'_>,
@@ -472,6 +562,101 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_naming_rules_reject() {
+        let lp =
+            "cpu,host=a val=1i 0\n_cpu,host=a val=1i 0\ncpu,_host=a val=1i 0\ncpu,host=a _val=1i 0";
+
+        let mut converter = LinesConverter::new(5);
+        converter.set_naming_rules(NamingRules::new(NamingMode::Reject).with_reserved_prefix("_"));
+        let err = converter.write_lp(lp).unwrap_err();
+        assert_matches!(
+            &err,
+            Error::PerLine { lines } if matches!(
+                &lines[..],
+                [
+                    LineError::InvalidName { source: NameError::ReservedPrefix { kind: NameKind::Table, .. }, line: 2 },
+                    LineError::InvalidName { source: NameError::ReservedPrefix { kind: NameKind::Tag, .. }, line: 3 },
+                    LineError::InvalidName { source: NameError::ReservedPrefix { kind: NameKind::Field, .. }, line: 4 },
+                ]
+            )
+        );
+        assert!(err.to_string().contains(
+            r#"invalid name on line 3 (1-based): tag name "_host" uses the reserved prefix "_""#
+        ));
+
+        // The valid line is still written.
+        let (batches, _) = converter.finish().unwrap();
+        assert_eq!(batches.len(), 1);
+    }
+
+    #[test]
+    fn test_naming_rules_normalize() {
+        let lp = "CPU,Host\\ Name=a Usage\\ Idle=1i 0\ncpu,host_name=b usage_idle=2i 1";
+
+        let mut converter = LinesConverter::new(5);
+        converter
+            .set_naming_rules(NamingRules::new(NamingMode::Normalize).with_restricted_charset());
+        converter.write_lp(lp).unwrap();
+        let (batches, _) = converter.finish().unwrap();
+
+        assert_batches_eq!(
+            &[
+                "+-----------+--------------------------------+------------+",
+                "| host_name | time                           | usage_idle |",
+                "+-----------+--------------------------------+------------+",
+                "| a         | 1970-01-01T00:00:00Z           | 1          |",
+                "| b         | 1970-01-01T00:00:00.000000001Z | 2          |",
+                "+-----------+--------------------------------+------------+",
+            ],
+            &[batches["cpu"].to_arrow(Projection::All).unwrap()]
+        );
+    }
+
+    #[test]
+    fn test_required_tags() {
+        let lp = "cpu,tenant_id=x val=1i 1\ncpu val=2i 2\ncpu,tenant_id=y val=3i 3\nmem val=4i 4";
+
+        let mut converter = LinesConverter::new(5);
+        converter.set_required_tags(vec![("tenant_id".to_string(), "x".to_string())]);
+        let err = converter.write_lp(lp).unwrap_err();
+        assert_matches!(
+            &err,
+            Error::PerLine { lines } if matches!(
+                &lines[..],
+                [LineError::RequiredTag { tag, want, got, line: 3 }]
+                    if tag == "tenant_id" && want == "x" && got == "y"
+            )
+        );
+        assert!(err
+            .to_string()
+            .contains("tag 'tenant_id' must be 'x' on line 3 (1-based), got 'y'"));
+
+        // Lines without the tag have it added.
+        let (batches, _) = converter.finish().unwrap();
+        assert_batches_eq!(
+            &[
+                "+-----------+--------------------------------+-----+",
+                "| tenant_id | time                           | val |",
+                "+-----------+--------------------------------+-----+",
+                "| x         | 1970-01-01T00:00:00.000000001Z | 1   |",
+                "| x         | 1970-01-01T00:00:00.000000002Z | 2   |",
+                "+-----------+--------------------------------+-----+",
+            ],
+            &[batches["cpu"].to_arrow(Projection::All).unwrap()]
+        );
+        assert_batches_eq!(
+            &[
+                "+-----------+--------------------------------+-----+",
+                "| tenant_id | time                           | val |",
+                "+-----------+--------------------------------+-----+",
+                "| x         | 1970-01-01T00:00:00.000000004Z | 4   |",
+                "+-----------+--------------------------------+-----+",
+            ],
+            &[batches["mem"].to_arrow(Projection::All).unwrap()]
+        );
+    }
+
     #[test]
     fn test_nulls_string_and_float() {
         let lp = r#"m f0="cat" 1639612800000000000
@@ -0,0 +1,345 @@
+//! Validation and normalization of the table, tag and field names of written
+//! line protocol.
+
+use std::{borrow::Cow, fmt::Display};
+
+use influxdb_line_protocol::{EscapedStr, ParsedLine};
+use snafu::Snafu;
+
+/// The kind of identifier a name is validated as.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NameKind {
+    /// A table (measurement) name.
+    Table,
+    /// A tag key.
+    Tag,
+    /// A field key.
+    Field,
+}
+
+impl Display for NameKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Table => write!(f, "table"),
+            Self::Tag => write!(f, "tag"),
+            Self::Field => write!(f, "field"),
+        }
+    }
+}
+
+/// A name that violates the configured [`NamingRules`].
+#[derive(Debug, Snafu, PartialEq, Eq)]
+#[allow(missing_docs)]
+pub enum NameError {
+    #[snafu(display("{} name \"{}\" uses the reserved prefix \"{}\"", kind, name, prefix))]
+    ReservedPrefix {
+        kind: NameKind,
+        name: String,
+        prefix: String,
+    },
+
+    #[snafu(display(
+        "{} name \"{}\" is {} bytes long, exceeding the maximum of {}",
+        kind,
+        name,
+        len,
+        max
+    ))]
+    TooLong {
+        kind: NameKind,
+        name: String,
+        len: usize,
+        max: usize,
+    },
+
+    #[snafu(display(
+        "{} name \"{}\" contains the character {:?}, only [A-Za-z0-9_.-] are allowed",
+        kind,
+        name,
+        character
+    ))]
+    InvalidCharacter {
+        kind: NameKind,
+        name: String,
+        character: char,
+    },
+
+    #[snafu(display("{} name \"{}\" is empty once normalized", kind, name))]
+    EmptyAfterNormalization { kind: NameKind, name: String },
+}
+
+/// How names that violate the [`NamingRules`] are handled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NamingMode {
+    /// Reject lines containing a name that violates the rules.
+    Reject,
+
+    /// Rewrite names to conform to the rules.
+    ///
+    /// Names are lowercased, disallowed characters are replaced with `_`,
+    /// reserved prefixes are removed, and names are truncated to the maximum
+    /// length. Lines containing a name that is empty once normalized are
+    /// rejected.
+    Normalize,
+}
+
+/// Rules that the table, tag and field names of written line protocol must
+/// satisfy.
+///
+/// No rules are enforced by default.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NamingRules {
+    mode: NamingMode,
+    max_length: Option<usize>,
+    reserved_prefixes: Vec<String>,
+    restrict_charset: bool,
+}
+
+impl NamingRules {
+    /// Initialise a new set of [`NamingRules`] enforced according to `mode`.
+    pub fn new(mode: NamingMode) -> Self {
+        Self {
+            mode,
+            max_length: None,
+            reserved_prefixes: vec![],
+            restrict_charset: false,
+        }
+    }
+
+    /// Limit names to at most `max_length` bytes.
+    pub fn with_max_length(mut self, max_length: usize) -> Self {
+        self.max_length = Some(max_length);
+        self
+    }
+
+    /// Disallow names starting with `prefix`.
+    pub fn with_reserved_prefix(mut self, prefix: impl Into<String>) -> Self {
+        let prefix = prefix.into();
+        assert!(!prefix.is_empty(), "reserved prefix must not be empty");
+        self.reserved_prefixes.push(prefix);
+        self
+    }
+
+    /// Allow only ASCII letters, digits, `_`, `.` and `-` in names.
+    pub fn with_restricted_charset(mut self) -> Self {
+        self.restrict_charset = true;
+        self
+    }
+
+    /// Check `name` of the given `kind` against the rules, returning the name
+    /// to write.
+    pub fn apply<'a>(&self, kind: NameKind, name: &'a str) -> Result<Cow<'a, str>, NameError> {
+        match self.mode {
+            NamingMode::Reject => self.check(kind, name).map(|_| Cow::Borrowed(name)),
+            NamingMode::Normalize => self.normalize(kind, name),
+        }
+    }
+
+    fn check(&self, kind: NameKind, name: &str) -> Result<(), NameError> {
+        if let Some(prefix) = self.reserved_prefix_of(name) {
+            return Err(NameError::ReservedPrefix {
+                kind,
+                name: name.to_string(),
+                prefix: prefix.to_string(),
+            });
+        }
+
+        if let Some(max) = self.max_length {
+            if name.len() > max {
+                return Err(NameError::TooLong {
+                    kind,
+                    name: name.to_string(),
+                    len: name.len(),
+                    max,
+                });
+            }
+        }
+
+        if self.restrict_charset {
+            if let Some(character) = name.chars().find(|c| !is_allowed_char(*c)) {
+                return Err(NameError::InvalidCharacter {
+                    kind,
+                    name: name.to_string(),
+                    character,
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    fn normalize<'a>(&self, kind: NameKind, name: &'a str) -> Result<Cow<'a, str>, NameError> {
+        let mut normalized = Cow::Borrowed(name);
+
+        if normalized.chars().any(char::is_uppercase) {
+            normalized = Cow::Owned(normalized.to_lowercase());
+        }
+
+        if self.restrict_charset && !normalized.chars().all(is_allowed_char) {
+            normalized = Cow::Owned(
+                normalized
+                    .chars()
+                    .map(|c| if is_allowed_char(c) { c } else { '_' })
+                    .collect(),
+            );
+        }
+
+        while let Some(prefix) = self.reserved_prefix_of(&normalized) {
+            normalized = Cow::Owned(normalized[prefix.len()..].to_string());
+        }
+
+        if let Some(max) = self.max_length {
+            if normalized.len() > max {
+                // Truncate to the last character boundary within the limit.
+                let end = (0..=max)
+                    .rev()
+                    .find(|i| normalized.is_char_boundary(*i))
+                    .unwrap_or_default();
+                normalized = Cow::Owned(normalized[..end].to_string());
+            }
+        }
+
+        if normalized.is_empty() {
+            return Err(NameError::EmptyAfterNormalization {
+                kind,
+                name: name.to_string(),
+            });
+        }
+
+        Ok(normalized)
+    }
+
+    fn reserved_prefix_of(&self, name: &str) -> Option<&str> {
+        self.reserved_prefixes
+            .iter()
+            .find(|p| name.starts_with(p.as_str()))
+            .map(String::as_str)
+    }
+
+    /// Apply the rules to the table, tag and field names of `line`, rewriting
+    /// any normalized names in place.
+    pub(crate) fn apply_line(&self, line: &mut ParsedLine<'_>) -> Result<(), NameError> {
+        apply_escaped(self, NameKind::Table, &mut line.series.measurement)?;
+
+        if let Some(tags) = line.series.tag_set.as_mut() {
+            for (key, _value) in tags.iter_mut() {
+                apply_escaped(self, NameKind::Tag, key)?;
+            }
+        }
+
+        for (key, _value) in line.field_set.iter_mut() {
+            apply_escaped(self, NameKind::Field, key)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Apply `rules` to `name`, replacing it if it was normalized.
+fn apply_escaped(
+    rules: &NamingRules,
+    kind: NameKind,
+    name: &mut EscapedStr<'_>,
+) -> Result<(), NameError> {
+    let normalized = match rules.apply(kind, name.as_str())? {
+        Cow::Borrowed(_) => None,
+        Cow::Owned(v) => Some(v),
+    };
+    if let Some(v) = normalized {
+        *name = EscapedStr::CopiedValue(v);
+    }
+    Ok(())
+}
+
+fn is_allowed_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '-')
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_matches::assert_matches;
+
+    use super::*;
+
+    fn rules(mode: NamingMode) -> NamingRules {
+        NamingRules::new(mode)
+            .with_max_length(8)
+            .with_reserved_prefix("_")
+            .with_restricted_charset()
+    }
+
+    #[test]
+    fn test_no_rules() {
+        let rules = NamingRules::new(NamingMode::Reject);
+        assert_matches!(
+            rules.apply(NameKind::Tag, "_Any Näme"),
+            Ok(Cow::Borrowed("_Any Näme"))
+        );
+    }
+
+    #[test]
+    fn test_reject() {
+        let rules = rules(NamingMode::Reject);
+
+        assert_matches!(
+            rules.apply(NameKind::Table, "cpu"),
+            Ok(Cow::Borrowed("cpu"))
+        );
+        assert_matches!(
+            rules.apply(NameKind::Table, "Cpu.load-1"),
+            Err(NameError::TooLong {
+                len: 10,
+                max: 8,
+                ..
+            })
+        );
+        assert_matches!(
+            rules.apply(NameKind::Tag, "_host"),
+            Err(NameError::ReservedPrefix { kind: NameKind::Tag, prefix, .. }) => {
+                assert_eq!(prefix, "_");
+            }
+        );
+        assert_matches!(
+            rules.apply(NameKind::Field, "a b"),
+            Err(NameError::InvalidCharacter {
+                kind: NameKind::Field,
+                character: ' ',
+                ..
+            })
+        );
+
+        let err = rules.apply(NameKind::Field, "a b").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            r#"field name "a b" contains the character ' ', only [A-Za-z0-9_.-] are allowed"#
+        );
+    }
+
+    #[test]
+    fn test_normalize() {
+        let rules = rules(NamingMode::Normalize);
+
+        assert_matches!(
+            rules.apply(NameKind::Table, "cpu"),
+            Ok(Cow::Borrowed("cpu"))
+        );
+        assert_eq!(rules.apply(NameKind::Table, "CPU").unwrap(), "cpu");
+        assert_eq!(rules.apply(NameKind::Tag, "a b").unwrap(), "a_b");
+        assert_eq!(rules.apply(NameKind::Tag, "__host").unwrap(), "host");
+        assert_eq!(rules.apply(NameKind::Tag, " host").unwrap(), "host");
+        assert_eq!(
+            rules.apply(NameKind::Field, "Usage_Idle").unwrap(),
+            "usage_id"
+        );
+        assert_matches!(
+            rules.apply(NameKind::Field, "___"),
+            Err(NameError::EmptyAfterNormalization { .. })
+        );
+    }
+
+    #[test]
+    fn test_normalize_truncates_at_char_boundary() {
+        let rules = NamingRules::new(NamingMode::Normalize).with_max_length(4);
+        assert_eq!(rules.apply(NameKind::Tag, "aaéé").unwrap(), "aaé");
+    }
+}
@@ -22,6 +22,7 @@ use workspace_hack as _;
 
 pub mod chunk;
 pub mod metadata;
+pub mod namespace_store;
 pub mod serialize;
 pub mod storage;
 pub mod writer;
@@ -90,8 +90,8 @@ use base64::{prelude::BASE64_STANDARD, Engine};
 use bytes::Bytes;
 use data_types::{
     ColumnId, ColumnSet, ColumnSummary, CompactionLevel, InfluxDbType, NamespaceId,
-    ParquetFileParams, PartitionKey, StatValues, Statistics, TableId, Timestamp,
-    TransitionPartitionId,
+    ParquetFileColumnStatistics, ParquetFileParams, PartitionKey, StatValues, Statistics, TableId,
+    Timestamp, TransitionPartitionId,
 };
 use generated_types::influxdata::iox::ingester::v1 as proto;
 use iox_time::Time;
@@ -464,6 +464,22 @@ impl IoxMetadata {
             .read_statistics(&schema)
             .expect("invalid statistics");
         let columns: Vec<_> = stats.iter().map(|v| column_id_map(&v.name)).collect();
+
+        // Record the statistics of the string and tag columns, allowing files
+        // to be pruned on them without reading their footers.
+        let column_statistics = stats
+            .iter()
+            .filter_map(|v| match &v.stats {
+                Statistics::String(s) => Some(ParquetFileColumnStatistics::new(
+                    column_id_map(&v.name),
+                    s.min.as_deref(),
+                    s.max.as_deref(),
+                    s.null_count.unwrap_or_default(),
+                )),
+                _ => None,
+            })
+            .collect();
+
         let time_summary = stats
             .into_iter()
             .find(|v| v.name == TIME_COLUMN_NAME)
@@ -495,6 +511,7 @@ impl IoxMetadata {
             created_at: Timestamp::from(self.creation_timestamp),
             column_set: ColumnSet::new(columns),
             max_l0_created_at: Timestamp::from(self.max_l0_created_at),
+            column_statistics,
         }
     }
 
@@ -1048,10 +1065,14 @@ mod tests {
         let batch = RecordBatch::try_new(schema, vec![data, timestamps]).unwrap();
         let stream = Box::pin(MemoryStream::new(vec![batch.clone()]));
 
-        let (bytes, file_meta) =
-            crate::serialize::to_parquet_bytes(stream, &meta, unbounded_memory_pool())
-                .await
-                .expect("should serialize");
+        let (bytes, file_meta) = crate::serialize::to_parquet_bytes(
+            stream,
+            &meta,
+            &Default::default(),
+            unbounded_memory_pool(),
+        )
+        .await
+        .expect("should serialize");
 
         // Verify if the parquet file meta data has values
         assert!(!file_meta.row_groups.is_empty());
@@ -0,0 +1,515 @@
+//! An [`ObjectStore`] routing the parquet files of each namespace to the
+//! object store configured for it in the catalog.
+
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    fmt::Display,
+    ops::Range,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use data_types::NamespaceId;
+use futures::{stream::BoxStream, StreamExt};
+use iox_catalog::interface::{Catalog, SoftDeletedRows};
+use object_store::{
+    path::Path, DynObjectStore, GetOptions, GetResult, ListResult, MultipartId, ObjectMeta,
+    ObjectStore, Result,
+};
+use observability_deps::tracing::*;
+use parking_lot::Mutex;
+use tokio::io::AsyncWrite;
+
+const STORE_NAME: &str = "NamespaceRoutedStore";
+
+/// The duration the object store name of a namespace is cached for, before
+/// being re-read from the catalog.
+pub const DEFAULT_CACHE_TTL: Duration = Duration::from_secs(60);
+
+/// An [`ObjectStore`] that directs requests for the parquet files of a
+/// namespace to the object store named by that namespace's
+/// `object_store_name` in the catalog, or the default store if it has none.
+///
+/// The namespace of a request is derived from the first segment of its path,
+/// which for a [`ParquetFilePath`] is the namespace ID. Paths that do not
+/// start with a namespace ID are directed to the default store.
+///
+/// The object store name of each namespace is cached for [`DEFAULT_CACHE_TTL`]
+/// once resolved. Existing parquet files are never moved between stores, so
+/// the object store of a namespace can only be changed while it has no
+/// parquet files, and must be set before any data is written to it - data
+/// written within the cache TTL of the change may otherwise be placed in the
+/// previous store. Listing a prefix that does not identify a namespace lists
+/// all the stores.
+///
+/// A namespace naming an object store that is not configured in this
+/// instance causes all requests for its files to fail, rather than silently
+/// reading or writing the default store.
+///
+/// [`ParquetFilePath`]: crate::ParquetFilePath
+#[derive(Debug)]
+pub struct NamespaceRoutedStore {
+    default: Arc<DynObjectStore>,
+    stores: BTreeMap<String, Arc<DynObjectStore>>,
+
+    catalog: Arc<dyn Catalog>,
+    cache: Mutex<HashMap<NamespaceId, (Option<String>, Instant)>>,
+    cache_ttl: Duration,
+}
+
+impl NamespaceRoutedStore {
+    /// Route requests for the namespaces configured to use one of `stores`
+    /// (keyed by name) to that store, and all others to `default`.
+    pub fn new(
+        default: Arc<DynObjectStore>,
+        stores: impl IntoIterator<Item = (String, Arc<DynObjectStore>)>,
+        catalog: Arc<dyn Catalog>,
+    ) -> Self {
+        Self {
+            default,
+            stores: stores.into_iter().collect(),
+            catalog,
+            cache: Default::default(),
+            cache_ttl: DEFAULT_CACHE_TTL,
+        }
+    }
+
+    /// Cache the object store name of each namespace for `ttl`, instead of
+    /// [`DEFAULT_CACHE_TTL`].
+    pub fn with_cache_ttl(mut self, ttl: Duration) -> Self {
+        self.cache_ttl = ttl;
+        self
+    }
+
+    /// Wrap `default` in a [`NamespaceRoutedStore`] if any named `stores` are
+    /// configured, otherwise return `default` unchanged.
+    pub fn wrap(
+        default: Arc<DynObjectStore>,
+        stores: impl IntoIterator<Item = (String, Arc<DynObjectStore>)>,
+        catalog: Arc<dyn Catalog>,
+    ) -> Arc<DynObjectStore> {
+        let mut stores = stores.into_iter().peekable();
+        if stores.peek().is_none() {
+            return default;
+        }
+
+        let store = Self::new(default, stores, catalog);
+        info!(stores=?store.stores.keys().collect::<Vec<_>>(), "routing namespaces to object stores");
+        Arc::new(store)
+    }
+
+    /// Return the object store the namespace with `id` is configured to use.
+    async fn store_for_namespace(&self, id: NamespaceId) -> Result<&Arc<DynObjectStore>> {
+        let cached = self
+            .cache
+            .lock()
+            .get(&id)
+            .filter(|(_, resolved_at)| resolved_at.elapsed() < self.cache_ttl)
+            .map(|(name, _)| name.clone());
+        let name = match cached {
+            Some(v) => v,
+            None => {
+                let name = self
+                    .catalog
+                    .repositories()
+                    .await
+                    .namespaces()
+                    .get_by_id(id, SoftDeletedRows::AllRows)
+                    .await
+                    .map_err(|e| object_store::Error::Generic {
+                        store: STORE_NAME,
+                        source: Box::new(e),
+                    })?
+                    .and_then(|ns| ns.object_store_name);
+
+                debug!(namespace_id=%id, object_store_name=?name, "resolved namespace object store");
+                self.cache.lock().insert(id, (name.clone(), Instant::now()));
+                name
+            }
+        };
+
+        match name {
+            None => Ok(&self.default),
+            Some(name) => self
+                .stores
+                .get(&name)
+                .ok_or_else(|| object_store::Error::Generic {
+                    store: STORE_NAME,
+                    source: format!(
+                        "namespace {id} uses object store {name} which is not configured"
+                    )
+                    .into(),
+                }),
+        }
+    }
+
+    /// Return the object store `location` resides in.
+    async fn store_for_path(&self, location: &Path) -> Result<&Arc<DynObjectStore>> {
+        match namespace_id(location) {
+            Some(id) => self.store_for_namespace(id).await,
+            None => Ok(&self.default),
+        }
+    }
+
+    /// Return the store containing all objects under `prefix`, or [`None`]
+    /// if `prefix` spans all the stores.
+    async fn store_for_prefix(
+        &self,
+        prefix: Option<&Path>,
+    ) -> Result<Option<&Arc<DynObjectStore>>> {
+        match prefix.and_then(namespace_id) {
+            Some(id) => self.store_for_namespace(id).await.map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn all_stores(&self) -> impl Iterator<Item = &Arc<DynObjectStore>> {
+        std::iter::once(&self.default).chain(self.stores.values())
+    }
+}
+
+/// Parse the leading namespace ID segment of `path`, if any.
+fn namespace_id(path: &Path) -> Option<NamespaceId> {
+    path.parts()
+        .next()
+        .and_then(|v| v.as_ref().parse().ok())
+        .map(NamespaceId::new)
+}
+
+impl Display for NamespaceRoutedStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{STORE_NAME}(default={}", self.default)?;
+        for (name, store) in &self.stores {
+            write!(f, ", {name}={store}")?;
+        }
+        write!(f, ")")
+    }
+}
+
+#[async_trait]
+impl ObjectStore for NamespaceRoutedStore {
+    async fn put(&self, location: &Path, bytes: Bytes) -> Result<()> {
+        self.store_for_path(location)
+            .await?
+            .put(location, bytes)
+            .await
+    }
+
+    async fn put_multipart(
+        &self,
+        location: &Path,
+    ) -> Result<(MultipartId, Box<dyn AsyncWrite + Unpin + Send>)> {
+        self.store_for_path(location)
+            .await?
+            .put_multipart(location)
+            .await
+    }
+
+    async fn abort_multipart(&self, location: &Path, multipart_id: &MultipartId) -> Result<()> {
+        self.store_for_path(location)
+            .await?
+            .abort_multipart(location, multipart_id)
+            .await
+    }
+
+    async fn get_opts(&self, location: &Path, options: GetOptions) -> Result<GetResult> {
+        self.store_for_path(location)
+            .await?
+            .get_opts(location, options)
+            .await
+    }
+
+    async fn get_range(&self, location: &Path, range: Range<usize>) -> Result<Bytes> {
+        self.store_for_path(location)
+            .await?
+            .get_range(location, range)
+            .await
+    }
+
+    async fn get_ranges(&self, location: &Path, ranges: &[Range<usize>]) -> Result<Vec<Bytes>> {
+        self.store_for_path(location)
+            .await?
+            .get_ranges(location, ranges)
+            .await
+    }
+
+    async fn head(&self, location: &Path) -> Result<ObjectMeta> {
+        self.store_for_path(location).await?.head(location).await
+    }
+
+    async fn delete(&self, location: &Path) -> Result<()> {
+        self.store_for_path(location).await?.delete(location).await
+    }
+
+    async fn list(&self, prefix: Option<&Path>) -> Result<BoxStream<'_, Result<ObjectMeta>>> {
+        if let Some(store) = self.store_for_prefix(prefix).await? {
+            return store.list(prefix).await;
+        }
+
+        let mut streams = Vec::with_capacity(self.stores.len() + 1);
+        for store in self.all_stores() {
+            streams.push(store.list(prefix).await?);
+        }
+        Ok(futures::stream::iter(streams).flatten().boxed())
+    }
+
+    async fn list_with_delimiter(&self, prefix: Option<&Path>) -> Result<ListResult> {
+        if let Some(store) = self.store_for_prefix(prefix).await? {
+            return store.list_with_delimiter(prefix).await;
+        }
+
+        let mut common_prefixes = BTreeSet::new();
+        let mut objects = vec![];
+        for store in self.all_stores() {
+            let res = store.list_with_delimiter(prefix).await?;
+            common_prefixes.extend(res.common_prefixes);
+            objects.extend(res.objects);
+        }
+
+        Ok(ListResult {
+            common_prefixes: common_prefixes.into_iter().collect(),
+            objects,
+        })
+    }
+
+    async fn copy(&self, from: &Path, to: &Path) -> Result<()> {
+        let store = self.store_for_path(from).await?;
+        ensure_same_store(store, self.store_for_path(to).await?, from, to)?;
+        store.copy(from, to).await
+    }
+
+    async fn copy_if_not_exists(&self, from: &Path, to: &Path) -> Result<()> {
+        let store = self.store_for_path(from).await?;
+        ensure_same_store(store, self.store_for_path(to).await?, from, to)?;
+        store.copy_if_not_exists(from, to).await
+    }
+}
+
+/// Objects cannot be copied between object stores.
+fn ensure_same_store(
+    a: &Arc<DynObjectStore>,
+    b: &Arc<DynObjectStore>,
+    from: &Path,
+    to: &Path,
+) -> Result<()> {
+    if Arc::ptr_eq(a, b) {
+        return Ok(());
+    }
+    Err(object_store::Error::NotSupported {
+        source: format!("cannot copy {from} to {to} in a different object store").into(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_matches::assert_matches;
+    use futures::TryStreamExt;
+    use iox_catalog::{mem::MemCatalog, test_helpers::arbitrary_namespace};
+    use object_store::memory::InMemory;
+
+    use super::*;
+
+    struct TestStores {
+        default: Arc<DynObjectStore>,
+        tenant: Arc<DynObjectStore>,
+        catalog: Arc<dyn Catalog>,
+        store: NamespaceRoutedStore,
+    }
+
+    fn stores() -> TestStores {
+        let default: Arc<DynObjectStore> = Arc::new(InMemory::new());
+        let tenant: Arc<DynObjectStore> = Arc::new(InMemory::new());
+        let catalog: Arc<dyn Catalog> = Arc::new(MemCatalog::new(Default::default()));
+
+        let store = NamespaceRoutedStore::new(
+            Arc::clone(&default),
+            [
+                ("tenant-a".to_string(), Arc::clone(&tenant)),
+                (
+                    "tenant-b".to_string(),
+                    Arc::new(InMemory::new()) as Arc<DynObjectStore>,
+                ),
+            ],
+            Arc::clone(&catalog),
+        );
+
+        TestStores {
+            default,
+            tenant,
+            catalog,
+            store,
+        }
+    }
+
+    /// Create a namespace using the object store named `object_store_name`.
+    async fn namespace(
+        catalog: &Arc<dyn Catalog>,
+        name: &str,
+        object_store_name: Option<&str>,
+    ) -> NamespaceId {
+        let mut repos = catalog.repositories().await;
+        let ns = arbitrary_namespace(&mut *repos, name).await;
+        repos
+            .namespaces()
+            .update_object_store_name(name, object_store_name)
+            .await
+            .unwrap();
+        ns.id
+    }
+
+    fn path(id: NamespaceId) -> Path {
+        Path::from_iter([id.to_string().as_str(), "1", "2", "file.parquet"])
+    }
+
+    async fn list(store: &DynObjectStore, prefix: Option<&Path>) -> Vec<Path> {
+        let mut got = store
+            .list(prefix)
+            .await
+            .unwrap()
+            .map_ok(|v| v.location)
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        got.sort();
+        got
+    }
+
+    #[tokio::test]
+    async fn test_routing() {
+        let s = stores();
+        let routed = namespace(&s.catalog, "routed", Some("tenant-a")).await;
+        let unrouted = namespace(&s.catalog, "unrouted", None).await;
+
+        s.store.put(&path(routed), Bytes::from("a")).await.unwrap();
+        s.store
+            .put(&path(unrouted), Bytes::from("b"))
+            .await
+            .unwrap();
+        let other = Path::from("bananas");
+        s.store.put(&other, Bytes::from("c")).await.unwrap();
+
+        // Each file is placed in the configured store.
+        assert_eq!(list(&*s.tenant, None).await, [path(routed)]);
+        assert_eq!(
+            list(&*s.default, None).await,
+            [path(unrouted), other.clone()]
+        );
+
+        // And is read back from it.
+        let got = s.store.get(&path(routed)).await.unwrap().bytes().await;
+        assert_eq!(got.unwrap(), "a");
+        let got = s.store.get(&path(unrouted)).await.unwrap().bytes().await;
+        assert_eq!(got.unwrap(), "b");
+
+        // Listing a namespace only reads its store, while listing everything
+        // spans all stores.
+        let prefix = Path::from(routed.to_string());
+        assert_eq!(list(&s.store, Some(&prefix)).await, [path(routed)]);
+        assert_eq!(
+            list(&s.store, None).await,
+            [path(routed), path(unrouted), other]
+        );
+
+        let got = s.store.list_with_delimiter(None).await.unwrap();
+        assert_eq!(got.objects.len(), 1);
+        assert_eq!(
+            got.common_prefixes,
+            [
+                Path::from(routed.to_string()),
+                Path::from(unrouted.to_string())
+            ]
+        );
+
+        s.store.delete(&path(routed)).await.unwrap();
+        assert!(list(&*s.tenant, None).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_cache_ttl() {
+        let s = stores();
+        let id = namespace(&s.catalog, "bananas", None).await;
+        let expiring = NamespaceRoutedStore::new(
+            Arc::clone(&s.default),
+            [("tenant-a".to_string(), Arc::clone(&s.tenant))],
+            Arc::clone(&s.catalog),
+        )
+        .with_cache_ttl(Duration::ZERO);
+
+        // Resolve and cache the default store in both.
+        for store in [&s.store, &expiring] {
+            store.put(&path(id), Bytes::from("a")).await.unwrap();
+            assert_eq!(list(&*s.default, None).await, [path(id)]);
+            store.delete(&path(id)).await.unwrap();
+        }
+
+        s.catalog
+            .repositories()
+            .await
+            .namespaces()
+            .update_object_store_name("bananas", Some("tenant-a"))
+            .await
+            .unwrap();
+
+        // The cached store is used until the entry expires.
+        s.store.put(&path(id), Bytes::from("a")).await.unwrap();
+        assert_eq!(list(&*s.default, None).await, [path(id)]);
+        s.store.delete(&path(id)).await.unwrap();
+
+        expiring.put(&path(id), Bytes::from("a")).await.unwrap();
+        assert!(list(&*s.default, None).await.is_empty());
+        assert_eq!(list(&*s.tenant, None).await, [path(id)]);
+    }
+
+    #[tokio::test]
+    async fn test_unknown_store() {
+        let s = stores();
+        let id = namespace(&s.catalog, "bananas", Some("platanos")).await;
+
+        // Writes for a namespace using a store that is not configured must
+        // not fall back to the default store.
+        let err = s
+            .store
+            .put(&path(id), Bytes::from("a"))
+            .await
+            .expect_err("unknown store should fail");
+        assert_matches!(err, object_store::Error::Generic { .. });
+        assert!(list(&*s.default, None).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_copy_across_stores() {
+        let s = stores();
+        let a = namespace(&s.catalog, "a", Some("tenant-a")).await;
+        let b = namespace(&s.catalog, "b", Some("tenant-b")).await;
+
+        s.store.put(&path(a), Bytes::from("a")).await.unwrap();
+
+        let err = s
+            .store
+            .copy(&path(a), &path(b))
+            .await
+            .expect_err("cross-store copy should fail");
+        assert_matches!(err, object_store::Error::NotSupported { .. });
+    }
+
+    #[tokio::test]
+    async fn test_wrap() {
+        let default: Arc<DynObjectStore> = Arc::new(InMemory::new());
+        let catalog: Arc<dyn Catalog> = Arc::new(MemCatalog::new(Default::default()));
+
+        let got = NamespaceRoutedStore::wrap(Arc::clone(&default), [], Arc::clone(&catalog));
+        assert!(Arc::ptr_eq(&got, &default));
+
+        let got = NamespaceRoutedStore::wrap(
+            Arc::clone(&default),
+            [(
+                "tenant-a".to_string(),
+                Arc::new(InMemory::new()) as Arc<DynObjectStore>,
+            )],
+            catalog,
+        );
+        assert!(!Arc::ptr_eq(&got, &default));
+    }
+}
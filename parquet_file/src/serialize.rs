@@ -2,8 +2,9 @@
 //!
 //! [`RecordBatch`]: arrow::record_batch::RecordBatch
 
-use std::{io::Write, sync::Arc};
+use std::{collections::BTreeSet, io::Write, sync::Arc};
 
+use arrow::datatypes::Schema as ArrowSchema;
 use datafusion::{
     error::DataFusionError, execution::memory_pool::MemoryPool,
     physical_plan::SendableRecordBatchStream,
@@ -14,7 +15,11 @@ use observability_deps::tracing::{debug, trace, warn};
 use parquet::{
     basic::Compression,
     errors::ParquetError,
-    file::{metadata::KeyValue, properties::WriterProperties},
+    file::{
+        metadata::KeyValue,
+        properties::{WriterProperties, WriterPropertiesBuilder},
+    },
+    schema::types::ColumnPath,
 };
 use thiserror::Error;
 
@@ -31,6 +36,102 @@ pub const ROW_GROUP_WRITE_SIZE: usize = 1024 * 1024;
 #[allow(clippy::assertions_on_constants)]
 const _: () = assert!(ROW_GROUP_WRITE_SIZE % BATCH_SIZE == 0);
 
+/// The default false positive probability of the bloom filters written for
+/// [`BloomFilterConfig`] columns.
+pub const DEFAULT_BLOOM_FILTER_FPP: f64 = 0.01;
+
+/// The default number of distinct values per row group the bloom filters
+/// written for [`BloomFilterConfig`] columns are sized for.
+pub const DEFAULT_BLOOM_FILTER_NDV: u64 = 100_000;
+
+/// The tag columns for which bloom filters are written into each row group of
+/// a parquet file.
+///
+/// The bloom filters are stored in the parquet file and referenced from its
+/// footer metadata, allowing readers to skip row groups (and therefore whole
+/// files) that cannot contain the value of an equality predicate on one of the
+/// columns, without reading any data pages. They are most effective for high
+/// cardinality tags, for which column statistics rarely prune anything.
+///
+/// Columns that are not tags in the schema of the encoded data are ignored. No
+/// bloom filters are written by default.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BloomFilterConfig {
+    columns: BTreeSet<String>,
+    fpp: f64,
+    ndv: u64,
+}
+
+impl Default for BloomFilterConfig {
+    fn default() -> Self {
+        Self {
+            columns: Default::default(),
+            fpp: DEFAULT_BLOOM_FILTER_FPP,
+            ndv: DEFAULT_BLOOM_FILTER_NDV,
+        }
+    }
+}
+
+impl BloomFilterConfig {
+    /// Write bloom filters for the tag `columns`.
+    pub fn new(columns: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        Self {
+            columns: columns.into_iter().map(Into::into).collect(),
+            ..Default::default()
+        }
+    }
+
+    /// Size the bloom filters for a false positive probability of `fpp`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `fpp` is not between 0 and 1 (exclusive).
+    pub fn with_fpp(mut self, fpp: f64) -> Self {
+        assert!(
+            fpp > 0.0 && fpp < 1.0,
+            "bloom filter fpp must be between 0 and 1"
+        );
+        self.fpp = fpp;
+        self
+    }
+
+    /// Size the bloom filters for `ndv` distinct values per row group.
+    pub fn with_ndv(mut self, ndv: u64) -> Self {
+        self.ndv = ndv;
+        self
+    }
+
+    /// Enable bloom filters in `builder` for the configured columns that are
+    /// tags in `schema`.
+    fn apply(
+        &self,
+        mut builder: WriterPropertiesBuilder,
+        schema: &Arc<ArrowSchema>,
+    ) -> WriterPropertiesBuilder {
+        if self.columns.is_empty() {
+            return builder;
+        }
+
+        // Data without an IOx schema has no tags.
+        let Ok(schema) = schema::Schema::try_from(Arc::clone(schema)) else {
+            return builder;
+        };
+
+        for tag in schema
+            .tags_iter()
+            .filter(|f| self.columns.contains(f.name()))
+        {
+            let path = ColumnPath::from(tag.name().as_str());
+            builder = builder
+                .set_column_bloom_filter_enabled(path.clone(), true)
+                .set_column_bloom_filter_fpp(path.clone(), self.fpp)
+                .set_column_bloom_filter_ndv(path, self.ndv);
+        }
+
+        builder
+    }
+}
+
 /// [`RecordBatch`] to Parquet serialisation errors.
 ///
 /// [`RecordBatch`]: arrow::record_batch::RecordBatch
@@ -102,6 +203,8 @@ impl From<crate::writer::Error> for CodecError {
 /// [`METADATA_KEY`], with a base64-wrapped, protobuf serialized
 /// [`proto::IoxMetadata`] structure.
 ///
+/// Bloom filters are written for the tag columns selected by `bloom_filters`.
+///
 /// Returns the serialized [`FileMetaData`] for the encoded parquet file, from
 /// which an [`IoxParquetMetaData`] can be derived.
 ///
@@ -121,6 +224,7 @@ impl From<crate::writer::Error> for CodecError {
 pub async fn to_parquet<W>(
     batches: SendableRecordBatchStream,
     meta: &IoxMetadata,
+    bloom_filters: &BloomFilterConfig,
     pool: Arc<dyn MemoryPool>,
     sink: W,
 ) -> Result<parquet::format::FileMetaData, CodecError>
@@ -135,7 +239,7 @@ where
     pin_mut!(stream);
 
     // Serialize the IoxMetadata to the protobuf bytes.
-    let props = writer_props(meta)?;
+    let props = writer_props(meta, &schema, bloom_filters)?;
     let write_batch_size = props.write_batch_size();
     let max_row_group_size = props.max_row_group_size();
 
@@ -171,6 +275,7 @@ where
 pub async fn to_parquet_bytes(
     batches: SendableRecordBatchStream,
     meta: &IoxMetadata,
+    bloom_filters: &BloomFilterConfig,
     pool: Arc<dyn MemoryPool>,
 ) -> Result<(Vec<u8>, parquet::format::FileMetaData), CodecError> {
     let mut bytes = vec![];
@@ -181,7 +286,7 @@ pub async fn to_parquet_bytes(
     );
 
     // Serialize the record batches into the in-memory buffer
-    let meta = to_parquet(batches, meta, pool, &mut bytes).await?;
+    let meta = to_parquet(batches, meta, bloom_filters, pool, &mut bytes).await?;
     bytes.shrink_to_fit();
 
     trace!(?meta, "generated parquet file metadata");
@@ -191,8 +296,12 @@ pub async fn to_parquet_bytes(
 
 /// Helper to construct [`WriterProperties`] , serialising the given
 /// [`IoxMetadata`] and embedding it as a key=value property keyed by
-/// [`METADATA_KEY`].
-fn writer_props(meta: &IoxMetadata) -> Result<WriterProperties, prost::EncodeError> {
+/// [`METADATA_KEY`], and enabling the configured bloom filters.
+fn writer_props(
+    meta: &IoxMetadata,
+    schema: &Arc<ArrowSchema>,
+    bloom_filters: &BloomFilterConfig,
+) -> Result<WriterProperties, prost::EncodeError> {
     let builder = WriterProperties::builder()
         .set_key_value_metadata(Some(vec![KeyValue {
             key: METADATA_KEY.to_string(),
@@ -201,7 +310,7 @@ fn writer_props(meta: &IoxMetadata) -> Result<WriterProperties, prost::EncodeErr
         .set_compression(Compression::ZSTD(Default::default()))
         .set_max_row_group_size(ROW_GROUP_WRITE_SIZE);
 
-    Ok(builder.build())
+    Ok(bloom_filters.apply(builder, schema).build())
 }
 
 #[cfg(test)]
@@ -209,7 +318,8 @@ mod tests {
     use super::*;
     use crate::metadata::IoxParquetMetaData;
     use arrow::{
-        array::{ArrayRef, StringArray},
+        array::{ArrayRef, DictionaryArray, StringArray, TimestampNanosecondArray},
+        datatypes::Int32Type,
         record_batch::RecordBatch,
     };
     use bytes::Bytes;
@@ -217,6 +327,15 @@ mod tests {
     use datafusion::parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
     use datafusion_util::{unbounded_memory_pool, MemoryStream};
     use iox_time::Time;
+    use parquet::{
+        data_type::ByteArray,
+        file::{
+            properties::ReaderProperties,
+            reader::{FileReader, RowGroupReader},
+            serialized_reader::{ReadOptionsBuilder, SerializedFileReader},
+        },
+    };
+    use schema::builder::SchemaBuilder;
     use std::sync::Arc;
 
     #[tokio::test]
@@ -237,9 +356,14 @@ mod tests {
         let batch = RecordBatch::try_from_iter([("a", to_string_array(&["value"]))]).unwrap();
         let stream = Box::pin(MemoryStream::new(vec![batch.clone()]));
 
-        let (bytes, _file_meta) = to_parquet_bytes(stream, &meta, unbounded_memory_pool())
-            .await
-            .expect("should serialize");
+        let (bytes, _file_meta) = to_parquet_bytes(
+            stream,
+            &meta,
+            &BloomFilterConfig::default(),
+            unbounded_memory_pool(),
+        )
+        .await
+        .expect("should serialize");
 
         let bytes = Bytes::from(bytes);
         // Read the metadata from the file bytes.
@@ -270,6 +394,113 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_encode_stream_preserves_dictionary() {
+        let meta = IoxMetadata {
+            object_store_id: Default::default(),
+            creation_timestamp: Time::from_timestamp_nanos(42),
+            namespace_id: NamespaceId::new(1),
+            namespace_name: "bananas".into(),
+            table_id: TableId::new(3),
+            table_name: "platanos".into(),
+            partition_key: "potato".into(),
+            compaction_level: CompactionLevel::Initial,
+            sort_key: None,
+            max_l0_created_at: Time::from_timestamp_nanos(42),
+        };
+
+        let tags: DictionaryArray<Int32Type> = vec!["a", "b", "a", "a"].into_iter().collect();
+        let batch = RecordBatch::try_from_iter([("t", Arc::new(tags) as ArrayRef)]).unwrap();
+        let stream = Box::pin(MemoryStream::new(vec![batch.clone()]));
+
+        let (bytes, _file_meta) = to_parquet_bytes(
+            stream,
+            &meta,
+            &BloomFilterConfig::default(),
+            unbounded_memory_pool(),
+        )
+        .await
+        .expect("should serialize");
+
+        let mut record_batches = ParquetRecordBatchReaderBuilder::try_new(Bytes::from(bytes))
+            .expect("should init builder")
+            .build()
+            .expect("should create reader")
+            .collect::<Result<Vec<_>, _>>()
+            .expect("should read batches");
+
+        // The dictionary encoding is restored when the file is read back.
+        assert_eq!(record_batches.len(), 1);
+        let got = record_batches.pop().unwrap();
+        assert_eq!(
+            got.schema().field(0).data_type(),
+            batch.schema().field(0).data_type()
+        );
+        assert_eq!(got, batch);
+    }
+
+    #[tokio::test]
+    async fn test_encode_stream_bloom_filters() {
+        let meta = IoxMetadata {
+            object_store_id: Default::default(),
+            creation_timestamp: Time::from_timestamp_nanos(42),
+            namespace_id: NamespaceId::new(1),
+            namespace_name: "bananas".into(),
+            table_id: TableId::new(3),
+            table_name: "platanos".into(),
+            partition_key: "potato".into(),
+            compaction_level: CompactionLevel::Final,
+            sort_key: None,
+            max_l0_created_at: Time::from_timestamp_nanos(42),
+        };
+
+        let schema = SchemaBuilder::new()
+            .tag("host")
+            .tag("region")
+            .timestamp()
+            .build()
+            .unwrap();
+        let hosts: DictionaryArray<Int32Type> = vec!["host-1", "host-2"].into_iter().collect();
+        let regions: DictionaryArray<Int32Type> = vec!["west", "west"].into_iter().collect();
+        let batch = RecordBatch::try_new(
+            schema.as_arrow(),
+            vec![
+                Arc::new(hosts),
+                Arc::new(regions),
+                Arc::new(TimestampNanosecondArray::from(vec![1, 2])),
+            ],
+        )
+        .unwrap();
+        let stream = Box::pin(MemoryStream::new(vec![batch]));
+
+        // Only "host" is a selected tag column, "time" is not a tag.
+        let bloom_filters = BloomFilterConfig::new(["host", "time", "missing"]);
+        let (bytes, _file_meta) =
+            to_parquet_bytes(stream, &meta, &bloom_filters, unbounded_memory_pool())
+                .await
+                .expect("should serialize");
+
+        let options = ReadOptionsBuilder::new()
+            .with_reader_properties(
+                ReaderProperties::builder()
+                    .set_read_bloom_filter(true)
+                    .build(),
+            )
+            .build();
+        let reader = SerializedFileReader::new_with_options(Bytes::from(bytes), options)
+            .expect("should read file");
+        let row_group = reader.get_row_group(0).expect("should read row group");
+
+        let filter = row_group
+            .get_column_bloom_filter(0)
+            .expect("host should have a bloom filter");
+        assert!(filter.check(&ByteArray::from("host-1")));
+        assert!(filter.check(&ByteArray::from("host-2")));
+
+        assert!(row_group.get_column_bloom_filter(1).is_none());
+        assert!(row_group.get_column_bloom_filter(2).is_none());
+    }
+
     fn to_string_array(strs: &[&str]) -> ArrayRef {
         let array: StringArray = strs.iter().map(|s| Some(*s)).collect();
         Arc::new(array)
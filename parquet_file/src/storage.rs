@@ -3,7 +3,7 @@
 
 use crate::{
     metadata::{IoxMetadata, IoxParquetMetaData},
-    serialize::{self, CodecError},
+    serialize::{self, BloomFilterConfig, CodecError},
     ParquetFilePath,
 };
 use arrow::{
@@ -171,6 +171,9 @@ pub struct ParquetStorage {
 
     /// Storage ID to hook it into DataFusion.
     id: StorageId,
+
+    /// The bloom filters written into uploaded files.
+    bloom_filters: BloomFilterConfig,
 }
 
 impl Display for ParquetStorage {
@@ -187,7 +190,18 @@ impl ParquetStorage {
     /// Initialise a new [`ParquetStorage`] using `object_store` as the
     /// persistence layer.
     pub fn new(object_store: Arc<DynObjectStore>, id: StorageId) -> Self {
-        Self { object_store, id }
+        Self {
+            object_store,
+            id,
+            bloom_filters: Default::default(),
+        }
+    }
+
+    /// Write the bloom filters described by `bloom_filters` into all files
+    /// encoded by this [`ParquetStorage`].
+    pub fn with_bloom_filters(mut self, bloom_filters: BloomFilterConfig) -> Self {
+        self.bloom_filters = bloom_filters;
+        self
     }
 
     /// Get underlying object store.
@@ -227,6 +241,31 @@ impl ParquetStorage {
         meta: &IoxMetadata,
         pool: Arc<dyn MemoryPool>,
     ) -> Result<(IoxParquetMetaData, usize), UploadError> {
+        let (data, parquet_meta) = self.encode(batches, meta, pool).await?;
+        let file_size = data.len();
+
+        self.put(data, partition_id, meta).await;
+
+        Ok((parquet_meta, file_size))
+    }
+
+    /// Encode `batches`, a stream of [`RecordBatch`] instances, into an
+    /// in-memory parquet file, returning the encoded bytes and the IOx
+    /// metadata describing them.
+    ///
+    /// This is the first half of [`ParquetStorage::upload()`], allowing callers
+    /// to observe the encoding and uploading of a file independently. The
+    /// returned bytes should be uploaded with [`ParquetStorage::put()`].
+    ///
+    /// Any buffering needed is registered with the pool.
+    ///
+    /// [`RecordBatch`]: arrow::record_batch::RecordBatch
+    pub async fn encode(
+        &self,
+        batches: SendableRecordBatchStream,
+        meta: &IoxMetadata,
+        pool: Arc<dyn MemoryPool>,
+    ) -> Result<(Bytes, IoxParquetMetaData), UploadError> {
         let start = Instant::now();
 
         // Stream the record batches into a parquet file.
@@ -237,7 +276,8 @@ impl ParquetStorage {
         //
         // This is not a huge concern, as the resulting parquet files are
         // currently smallish on average.
-        let (data, parquet_file_meta) = serialize::to_parquet_bytes(batches, meta, pool).await?;
+        let (data, parquet_file_meta) =
+            serialize::to_parquet_bytes(batches, meta, &self.bloom_filters, pool).await?;
 
         // Read the IOx-specific parquet metadata from the file metadata
         let parquet_meta =
@@ -247,27 +287,32 @@ impl ParquetStorage {
             "IoxParquetMetaData coverted from Row Group Metadata (aka FileMetaData)"
         );
 
-        // Derive the correct object store path from the metadata.
-        let path = ParquetFilePath::from((partition_id, meta)).object_store_path();
-
-        let file_size = data.len();
-        let data = Bytes::from(data);
-
         debug!(
-            file_size,
+            file_size = data.len(),
             object_store_id=?meta.object_store_id,
             // includes the time to run the datafusion plan (that is the batches)
             total_time_to_create_parquet_bytes=?(Instant::now() - start),
-            "Uploading parquet to object store"
+            "Encoded parquet file"
         );
 
+        Ok((Bytes::from(data), parquet_meta))
+    }
+
+    /// Upload the parquet file `data` (as produced by
+    /// [`ParquetStorage::encode()`]) to the object store path derived from
+    /// `partition_id` and `meta`.
+    ///
+    /// # Retries
+    ///
+    /// This method retries forever in the presence of object store errors.
+    pub async fn put(&self, data: Bytes, partition_id: &TransitionPartitionId, meta: &IoxMetadata) {
         // Retry uploading the file endlessly.
         //
-        // This is abort-able by the user by dropping the upload() future.
+        // This is abort-able by the user by dropping the put() future.
         //
         // Cloning `data` is a ref count inc, rather than a data copy.
         let mut retried = false;
-        while let Err(e) = self.object_store.put(&path, data.clone()).await {
+        while let Err(e) = self.try_put(data.clone(), partition_id, meta).await {
             warn!(error=%e, ?meta, "failed to upload parquet file to object storage, retrying");
             tokio::time::sleep(Duration::from_secs(1)).await;
             retried = true;
@@ -279,8 +324,84 @@ impl ParquetStorage {
                 "Succeeded uploading files to object storage on retry"
             );
         }
+    }
 
-        Ok((parquet_meta, file_size))
+    /// Make a single attempt to upload the encoded parquet file `data` for
+    /// `meta` to object storage, returning the error if it fails.
+    ///
+    /// Unlike [`Self::put()`], this allows the caller to decide how failed
+    /// uploads are retried.
+    pub async fn try_put(
+        &self,
+        data: Bytes,
+        partition_id: &TransitionPartitionId,
+        meta: &IoxMetadata,
+    ) -> Result<(), object_store::Error> {
+        // Derive the correct object store path from the metadata.
+        let path = ParquetFilePath::from((partition_id, meta)).object_store_path();
+
+        debug!(
+            file_size = data.len(),
+            object_store_id=?meta.object_store_id,
+            "Uploading parquet to object store"
+        );
+
+        self.object_store.put(&path, data).await
+    }
+
+    /// Make a single attempt to upload the encoded parquet file `data` for
+    /// `meta` to object storage, unless a different object already exists.
+    ///
+    /// If an object already exists at the path derived from `meta` (such as
+    /// when a previous attempt succeeded, but reported an error to the
+    /// caller), its content is compared to `data`: an identical object is
+    /// left untouched and the upload reported as successful, making retried
+    /// uploads idempotent. If the existing object differs (a collision of
+    /// object store IDs), nothing is uploaded and
+    /// [`object_store::Error::AlreadyExists`] is returned.
+    ///
+    /// The existence check, the comparison and the upload are separate
+    /// requests, as the object store API provides no conditional put. An
+    /// object written concurrently after the existence check is overwritten,
+    /// so this only detects collisions with objects that already existed.
+    pub async fn try_put_if_not_exists(
+        &self,
+        data: Bytes,
+        partition_id: &TransitionPartitionId,
+        meta: &IoxMetadata,
+    ) -> Result<(), object_store::Error> {
+        let path = ParquetFilePath::from((partition_id, meta)).object_store_path();
+
+        match self.object_store.head(&path).await {
+            Ok(existing) => {
+                // Only download the existing object if it may be identical.
+                if existing.size == data.len()
+                    && self.object_store.get(&path).await?.bytes().await? == data
+                {
+                    debug!(
+                        file_size = data.len(),
+                        object_store_id=?meta.object_store_id,
+                        "parquet file already uploaded to object store"
+                    );
+                    return Ok(());
+                }
+
+                error!(
+                    file_size = data.len(),
+                    existing_file_size = existing.size,
+                    object_store_id=?meta.object_store_id,
+                    "refusing to overwrite different parquet file in object store"
+                );
+                Err(object_store::Error::AlreadyExists {
+                    path: path.to_string(),
+                    source: "object exists with different content".into(),
+                })
+            }
+            Err(object_store::Error::NotFound { .. }) => {
+                self.try_put(data, partition_id, meta).await
+            }
+            Err(e) => Err(e),
+        }
     }
 
     /// Inputs for [`ParquetExec`].
@@ -358,6 +479,42 @@ mod tests {
         assert_eq!(got_iox_meta, meta);
     }
 
+    #[tokio::test]
+    async fn test_try_put_if_not_exists() {
+        let object_store: Arc<DynObjectStore> = Arc::new(object_store::memory::InMemory::default());
+        let store = ParquetStorage::new(Arc::clone(&object_store), StorageId::from("iox"));
+
+        let (partition_id, meta) = meta();
+        let path = ParquetFilePath::from((&partition_id, &meta)).object_store_path();
+        let data = Bytes::from_static(b"bananas");
+
+        // The first upload writes the object, and retrying it is a no-op.
+        store
+            .try_put_if_not_exists(data.clone(), &partition_id, &meta)
+            .await
+            .expect("upload should succeed");
+        store
+            .try_put_if_not_exists(data.clone(), &partition_id, &meta)
+            .await
+            .expect("retried upload should succeed");
+
+        // Different data is never written over the existing object.
+        let err = store
+            .try_put_if_not_exists(Bytes::from_static(b"platanos"), &partition_id, &meta)
+            .await
+            .expect_err("conflicting upload should fail");
+        assert!(matches!(err, object_store::Error::AlreadyExists { .. }));
+
+        let got = object_store
+            .get(&path)
+            .await
+            .unwrap()
+            .bytes()
+            .await
+            .unwrap();
+        assert_eq!(got, data);
+    }
+
     #[tokio::test]
     async fn test_simple_roundtrip() {
         let batch = RecordBatch::try_from_iter([("a", to_string_array(&["value"]))]).unwrap();
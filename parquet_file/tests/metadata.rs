@@ -5,8 +5,8 @@ use arrow::{
     record_batch::RecordBatch,
 };
 use data_types::{
-    ColumnId, CompactionLevel, NamespaceId, PartitionId, PartitionKey, TableId, Timestamp,
-    TransitionPartitionId,
+    ColumnId, CompactionLevel, NamespaceId, ParquetFileColumnStatistics, PartitionId, PartitionKey,
+    TableId, Timestamp, TransitionPartitionId,
 };
 use datafusion_util::{unbounded_memory_pool, MemoryStream};
 use iox_time::Time;
@@ -431,6 +431,15 @@ async fn test_derive_parquet_file_params() {
     assert_eq!(catalog_data.min_time, Timestamp::new(1646917692000000000));
     assert_eq!(catalog_data.max_time, Timestamp::new(1653311292000000000));
     assert_eq!(catalog_data.max_l0_created_at, Timestamp::new(1234));
+    assert_eq!(
+        catalog_data.column_statistics,
+        [ParquetFileColumnStatistics {
+            column_id: ColumnId::new(1),
+            min_value: Some("bananas".to_string()),
+            max_value: Some("platanos".to_string()),
+            null_count: 0,
+        }]
+    );
 }
 
 fn to_string_array(strs: &[&str]) -> ArrayRef {
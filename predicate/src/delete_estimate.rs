@@ -0,0 +1,316 @@
+//! Estimation of the persisted data affected by a [`DeletePredicate`], from
+//! the catalog metadata of the parquet files of a table, without reading the
+//! files.
+
+use std::collections::{HashMap, HashSet};
+
+use data_types::{
+    ColumnId, DeleteExpr, DeletePredicate, Op, ParquetFile, ParquetFileColumnStatistics,
+    ParquetFileId, Scalar, TableSchema,
+};
+
+/// The estimated impact of a [`DeletePredicate`] on the persisted data of a
+/// table, as returned by [`estimate_delete()`].
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct DeleteEstimate {
+    /// The number of parquet files that may contain affected rows.
+    pub files: usize,
+
+    /// The number of partitions containing the affected files.
+    pub partitions: usize,
+
+    /// The total size of the affected files, in bytes.
+    pub file_size_bytes: i64,
+
+    /// The total number of rows in the affected files - the upper bound of
+    /// the number of deleted rows.
+    pub max_rows: i64,
+
+    /// The approximate number of rows within the time range of the delete,
+    /// assuming the rows of each file are evenly distributed over its time
+    /// range.
+    ///
+    /// The selectivity of the expressions of the predicate is unknown, so
+    /// they are assumed to match every row in the time range.
+    pub estimated_rows: f64,
+}
+
+/// Estimate the files, partitions and rows of the table with `schema`
+/// affected by `predicate`, given the (not soft-deleted) parquet `files` of
+/// the table and their catalog `column_statistics`.
+///
+/// A file is affected if its time range overlaps the time range of the
+/// predicate, and every expression of the predicate may match a row of it:
+///
+/// * A column absent from a file, or NULL for all its rows, never matches -
+///   a comparison with NULL is never true.
+/// * A string or tag column compared for equality to a value outside of its
+///   min/max statistics never matches.
+/// * A string or tag column compared for inequality never matches if its
+///   min and max statistics are both equal to the value.
+///
+/// Nothing is affected if an expression references a column not in `schema`.
+pub fn estimate_delete(
+    predicate: &DeletePredicate,
+    schema: &TableSchema,
+    files: &[ParquetFile],
+    column_statistics: &[(ParquetFileId, ParquetFileColumnStatistics)],
+) -> DeleteEstimate {
+    let mut estimate = DeleteEstimate::default();
+
+    let Some(exprs) = predicate
+        .exprs
+        .iter()
+        .map(|e| schema.columns.get(&e.column).map(|c| (c.id, e)))
+        .collect::<Option<Vec<_>>>()
+    else {
+        return estimate;
+    };
+
+    let stats = column_statistics
+        .iter()
+        .map(|(file_id, s)| ((*file_id, s.column_id), s))
+        .collect::<HashMap<_, _>>();
+
+    let start = predicate.range.start();
+    let end = predicate.range.end();
+    let mut partitions = HashSet::new();
+
+    for f in files.iter().filter(|f| f.to_delete.is_none()) {
+        // The file time range is inclusive, the predicate's is not.
+        let (min, max) = (f.min_time.get(), f.max_time.get());
+        if min >= end || max < start {
+            continue;
+        }
+        if !exprs
+            .iter()
+            .all(|(column_id, expr)| may_match(f, *column_id, expr, &stats))
+        {
+            continue;
+        }
+
+        let overlap = (max.min(end - 1) as f64 - min.max(start) as f64) + 1.0;
+        let duration = (max as f64 - min as f64) + 1.0;
+
+        estimate.files += 1;
+        estimate.file_size_bytes += f.file_size_bytes;
+        estimate.max_rows += f.row_count;
+        estimate.estimated_rows += f.row_count as f64 * (overlap / duration);
+        partitions.insert(&f.partition_id);
+    }
+
+    estimate.partitions = partitions.len();
+    estimate
+}
+
+/// Return false if `expr`, comparing the column with `column_id`, cannot
+/// match any row of `file`.
+fn may_match(
+    file: &ParquetFile,
+    column_id: ColumnId,
+    expr: &DeleteExpr,
+    stats: &HashMap<(ParquetFileId, ColumnId), &ParquetFileColumnStatistics>,
+) -> bool {
+    if !file.column_set.contains(&column_id) {
+        return false;
+    }
+
+    let Some(s) = stats.get(&(file.id, column_id)) else {
+        return true;
+    };
+    if s.null_count >= file.row_count {
+        return false;
+    }
+
+    let Scalar::String(v) = &expr.scalar else {
+        return true;
+    };
+    let (min, max) = (s.min_value.as_deref(), s.max_value.as_deref());
+    match expr.op {
+        Op::Eq => {
+            min.map_or(true, |min| v.as_str() >= min) && max.map_or(true, |max| v.as_str() <= max)
+        }
+        // The bounds are only equal if neither was truncated.
+        Op::Ne => !(min == Some(v.as_str()) && max == Some(v.as_str())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use data_types::{
+        Column, ColumnSet, ColumnType, ColumnsByName, CompactionLevel, NamespaceId,
+        ParquetFileParams, PartitionKey, TableId, Timestamp, TimestampRange, TransitionPartitionId,
+    };
+
+    use super::*;
+
+    const TABLE_ID: TableId = TableId::new(1);
+
+    fn schema() -> TableSchema {
+        let columns = [
+            ("city", ColumnType::Tag),
+            ("temp", ColumnType::F64),
+            ("time", ColumnType::Time),
+        ];
+        TableSchema {
+            id: TABLE_ID,
+            partition_template: Default::default(),
+            columns: ColumnsByName::new(columns.into_iter().enumerate().map(
+                |(id, (name, column_type))| Column {
+                    id: ColumnId::new(id as _),
+                    table_id: TABLE_ID,
+                    name: name.to_string(),
+                    column_type,
+                },
+            )),
+        }
+    }
+
+    fn file(
+        id: i64,
+        partition_key: &str,
+        min_time: i64,
+        max_time: i64,
+        row_count: i64,
+        columns: impl IntoIterator<Item = i64>,
+    ) -> ParquetFile {
+        ParquetFile::from_params(
+            ParquetFileParams {
+                namespace_id: NamespaceId::new(1),
+                table_id: TABLE_ID,
+                partition_id: TransitionPartitionId::new(
+                    TABLE_ID,
+                    &PartitionKey::from(partition_key),
+                ),
+                object_store_id: uuid::Uuid::new_v4(),
+                min_time: Timestamp::new(min_time),
+                max_time: Timestamp::new(max_time),
+                file_size_bytes: 1_000,
+                row_count,
+                compaction_level: CompactionLevel::Initial,
+                created_at: Timestamp::new(1),
+                column_set: ColumnSet::new(columns.into_iter().map(ColumnId::new)),
+                max_l0_created_at: Timestamp::new(1),
+                column_statistics: vec![],
+            },
+            ParquetFileId::new(id),
+        )
+    }
+
+    #[test]
+    fn test_estimate_delete() {
+        let files = [
+            // Entirely within the time range.
+            file(1, "a", 10, 19, 100, [0, 1, 2]),
+            // The second half overlaps the time range.
+            file(2, "a", 0, 19, 100, [0, 1, 2]),
+            // Outside of the time range.
+            file(3, "b", 50, 59, 100, [0, 1, 2]),
+            // Does not contain the city column.
+            file(4, "b", 10, 19, 100, [1, 2]),
+            // The end of the time range is exclusive.
+            file(5, "c", 30, 39, 100, [0, 1, 2]),
+            // Second partition, the first quarter overlaps the time range.
+            file(6, "d", 20, 59, 100, [0, 1, 2]),
+        ];
+        let mut files = files.to_vec();
+        // Soft-deleted files are ignored.
+        let mut deleted = file(7, "e", 10, 19, 100, [0, 1, 2]);
+        deleted.to_delete = Some(Timestamp::new(1));
+        files.push(deleted);
+
+        let pred = DeletePredicate {
+            range: TimestampRange::new(10, 30),
+            exprs: vec![DeleteExpr::new(
+                "city".to_string(),
+                Op::Eq,
+                Scalar::String("Boston".into()),
+            )],
+        };
+
+        let got = estimate_delete(&pred, &schema(), &files, &[]);
+        assert_eq!(
+            got,
+            DeleteEstimate {
+                files: 3,
+                partitions: 2,
+                file_size_bytes: 3_000,
+                max_rows: 300,
+                estimated_rows: 100.0 + 50.0 + 25.0,
+            }
+        );
+
+        // Without expressions, every file overlapping the time range is
+        // affected.
+        let pred = DeletePredicate {
+            range: TimestampRange::new(10, 30),
+            exprs: vec![],
+        };
+        assert_eq!(estimate_delete(&pred, &schema(), &files, &[]).files, 4);
+
+        // Nothing is affected by a delete comparing a column the table does
+        // not have.
+        let pred = DeletePredicate {
+            range: TimestampRange::new(10, 30),
+            exprs: vec![DeleteExpr::new(
+                "country".to_string(),
+                Op::Ne,
+                Scalar::String("US".into()),
+            )],
+        };
+        assert_eq!(
+            estimate_delete(&pred, &schema(), &files, &[]),
+            DeleteEstimate::default()
+        );
+    }
+
+    #[test]
+    fn test_estimate_delete_column_statistics() {
+        let files = [
+            file(1, "a", 0, 9, 100, [0, 1, 2]),
+            file(2, "a", 0, 9, 100, [0, 1, 2]),
+            file(3, "a", 0, 9, 100, [0, 1, 2]),
+            file(4, "a", 0, 9, 100, [0, 1, 2]),
+        ];
+        let city = ColumnId::new(0);
+        let stats = [
+            (
+                ParquetFileId::new(1),
+                ParquetFileColumnStatistics::new(city, Some("Berlin"), Some("Madrid"), 0),
+            ),
+            (
+                ParquetFileId::new(2),
+                ParquetFileColumnStatistics::new(city, Some("Boston"), Some("Boston"), 10),
+            ),
+            (
+                ParquetFileId::new(3),
+                ParquetFileColumnStatistics::new(city, None, None, 100),
+            ),
+            // File 4 has no statistics, and may always match.
+        ];
+        let pred = |op, city: &str| DeletePredicate {
+            range: TimestampRange::new(0, 10),
+            exprs: vec![DeleteExpr::new(
+                "city".to_string(),
+                op,
+                Scalar::String(city.into()),
+            )],
+        };
+        let affected = |pred: DeletePredicate| {
+            let mut ids = files
+                .iter()
+                .filter(|f| {
+                    estimate_delete(&pred, &schema(), std::slice::from_ref(f), &stats).files > 0
+                })
+                .map(|f| f.id.get())
+                .collect::<Vec<_>>();
+            ids.sort_unstable();
+            ids
+        };
+
+        assert_eq!(affected(pred(Op::Eq, "Boston")), [1, 2, 4]);
+        assert_eq!(affected(pred(Op::Eq, "London")), [1, 4]);
+        assert_eq!(affected(pred(Op::Eq, "Paris")), [4]);
+        assert_eq!(affected(pred(Op::Ne, "Boston")), [1, 4]);
+    }
+}
@@ -1,6 +1,6 @@
 use crate::delete_expr::{df_to_expr, expr_to_df};
 use chrono::DateTime;
-use data_types::{DeleteExpr, DeletePredicate, TimestampRange};
+use data_types::{ColumnType, DeleteExpr, DeletePredicate, Scalar, TableSchema, TimestampRange};
 use datafusion::{
     logical_expr::Operator,
     prelude::{binary_expr, lit, Column, Expr},
@@ -37,6 +37,97 @@ pub enum Error {
     NotSupportPredicate { value: String },
 }
 
+/// An expression of a [`DeletePredicate`] that is invalid for the schema of
+/// the table it is applied to.
+#[derive(Debug, Snafu, PartialEq, Eq)]
+#[allow(missing_docs)]
+pub enum SchemaViolation {
+    #[snafu(display("column {} does not exist", column))]
+    ColumnNotFound { column: String },
+
+    #[snafu(display(
+        "column {} is of type {}, and cannot be compared to the {} value {}",
+        column,
+        column_type,
+        value_type,
+        value
+    ))]
+    TypeMismatch {
+        column: String,
+        column_type: ColumnType,
+        value_type: &'static str,
+        value: String,
+    },
+
+    #[snafu(display(
+        "column {} is the time column, which is restricted by the time range instead",
+        column
+    ))]
+    TimeColumn { column: String },
+}
+
+impl SchemaViolation {
+    /// The column of the invalid expression.
+    pub fn column(&self) -> &str {
+        match self {
+            Self::ColumnNotFound { column }
+            | Self::TypeMismatch { column, .. }
+            | Self::TimeColumn { column } => column,
+        }
+    }
+}
+
+/// Validate the expressions of `predicate` against the schema of the table
+/// they are applied to, returning a [`SchemaViolation`] for each expression
+/// referencing a column that does not exist or comparing it to a value of an
+/// incompatible type.
+///
+/// Integer values are accepted for float columns, and for unsigned integer
+/// columns when non-negative.
+pub fn validate_delete_predicate_schema(
+    predicate: &DeletePredicate,
+    schema: &TableSchema,
+) -> Result<(), Vec<SchemaViolation>> {
+    let violations = predicate
+        .exprs
+        .iter()
+        .filter_map(|expr| {
+            let column = expr.column.clone();
+            let Some(col) = schema.columns.get(&expr.column) else {
+                return Some(SchemaViolation::ColumnNotFound { column });
+            };
+
+            let compatible = match (col.column_type, &expr.scalar) {
+                (ColumnType::Time, _) => return Some(SchemaViolation::TimeColumn { column }),
+                (ColumnType::Bool, Scalar::Bool(_)) => true,
+                (ColumnType::I64 | ColumnType::F64, Scalar::I64(_)) => true,
+                (ColumnType::U64, Scalar::I64(v)) => *v >= 0,
+                (ColumnType::F64, Scalar::F64(_)) => true,
+                (ColumnType::String | ColumnType::Tag, Scalar::String(_)) => true,
+                _ => false,
+            };
+
+            (!compatible).then(|| SchemaViolation::TypeMismatch {
+                column,
+                column_type: col.column_type,
+                value_type: match expr.scalar {
+                    Scalar::Bool(_) => "boolean",
+                    Scalar::I64(_) => "integer",
+                    Scalar::F64(_) => "float",
+                    Scalar::String(_) => "string",
+                },
+                value: expr.scalar.to_string(),
+            })
+        })
+        .collect::<Vec<_>>();
+
+    if violations.is_empty() {
+        Ok(())
+    } else {
+        Err(violations)
+    }
+}
+
 /// Result type for Parser Cient
 pub type Result<T, E = Error> = std::result::Result<T, E>;
 
@@ -243,7 +334,7 @@ fn parse_time_range(start: &str, stop: &str) -> Result<(i64, i64)> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use data_types::{Op, Scalar};
+    use data_types::{Column, ColumnId, ColumnsByName, Op, Scalar, TableId};
 
     #[test]
     fn test_time_range_valid() {
@@ -439,4 +530,59 @@ mod tests {
         let result = parse_delete_predicate(start, stop, pred);
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_validate_delete_predicate_schema() {
+        let columns = [
+            ("city", ColumnType::Tag),
+            ("cost", ColumnType::U64),
+            ("temp", ColumnType::F64),
+            ("ok", ColumnType::Bool),
+            ("time", ColumnType::Time),
+        ];
+        let schema = TableSchema {
+            id: TableId::new(1),
+            partition_template: Default::default(),
+            columns: ColumnsByName::new(columns.into_iter().enumerate().map(
+                |(id, (name, column_type))| Column {
+                    id: ColumnId::new(id as _),
+                    table_id: TableId::new(1),
+                    name: name.to_string(),
+                    column_type,
+                },
+            )),
+        };
+
+        let pred =
+            parse_delete_predicate("0", "200", "city = Boston and cost != 100 and temp = 87")
+                .unwrap();
+        validate_delete_predicate_schema(&pred, &schema).unwrap();
+
+        let pred = DeletePredicate {
+            range: TimestampRange::new(0, 200),
+            exprs: vec![
+                DeleteExpr::new("city".to_string(), Op::Eq, Scalar::String("Boston".into())),
+                DeleteExpr::new("country".to_string(), Op::Eq, Scalar::String("US".into())),
+                DeleteExpr::new("cost".to_string(), Op::Eq, Scalar::I64(-1)),
+                DeleteExpr::new("ok".to_string(), Op::Eq, Scalar::I64(42)),
+                DeleteExpr::new("time".to_string(), Op::Eq, Scalar::I64(1)),
+            ],
+        };
+        let violations = validate_delete_predicate_schema(&pred, &schema).unwrap_err();
+        assert_eq!(
+            violations.iter().map(|v| v.column()).collect::<Vec<_>>(),
+            ["country", "cost", "ok", "time"]
+        );
+        assert_eq!(
+            violations[0],
+            SchemaViolation::ColumnNotFound {
+                column: "country".to_string()
+            }
+        );
+        assert_eq!(
+            violations[2].to_string(),
+            "column ok is of type bool, and cannot be compared to the integer value 42"
+        );
+        assert!(matches!(violations[3], SchemaViolation::TimeColumn { .. }));
+    }
 }
@@ -15,6 +15,7 @@
 // Workaround for "unused crate" lint false positives.
 use workspace_hack as _;
 
+pub mod delete_estimate;
 pub mod delete_expr;
 pub mod delete_predicate;
 pub mod rpc_predicate;
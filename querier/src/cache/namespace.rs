@@ -14,11 +14,11 @@ use cache_system::{
     resource_consumption::FunctionEstimator,
 };
 use data_types::{
-    partition_template::TablePartitionTemplateOverride, Column, ColumnId, Namespace, NamespaceId,
-    Table, TableId,
+    partition_template::TablePartitionTemplateOverride, Column, ColumnId, ColumnRetention,
+    ColumnType, Namespace, NamespaceId, Table, TableId, TableViewColumn, Timestamp,
 };
 use iox_catalog::interface::{Catalog, SoftDeletedRows};
-use iox_time::TimeProvider;
+use iox_time::{Time, TimeProvider};
 use schema::{InfluxColumnType, Schema, SchemaBuilder};
 use std::{
     collections::{HashMap, HashSet},
@@ -127,7 +127,37 @@ impl NamespaceCache {
                     .await
                     .expect("retry forever");
 
-                Some(Arc::new(CachedNamespace::new(namespace, tables, columns)))
+                let column_retention = Backoff::new(&backoff_config)
+                    .retry_all_errors("get namespace column retention", || async {
+                        catalog
+                            .repositories()
+                            .await
+                            .column_retention()
+                            .list_by_namespace_id(namespace.id)
+                            .await
+                    })
+                    .await
+                    .expect("retry forever");
+
+                let view_columns = Backoff::new(&backoff_config)
+                    .retry_all_errors("get namespace view columns", || async {
+                        catalog
+                            .repositories()
+                            .await
+                            .table_views()
+                            .list_by_namespace_id(namespace.id)
+                            .await
+                    })
+                    .await
+                    .expect("retry forever");
+
+                Some(Arc::new(CachedNamespace::new(
+                    namespace,
+                    tables,
+                    columns,
+                    column_retention,
+                    view_columns,
+                )))
             }
         });
         let loader = Arc::new(MetricsLoader::new(
@@ -236,10 +266,32 @@ pub struct CachedTable {
     pub column_id_map_rev: HashMap<Arc<str>, ColumnId>,
     pub primary_key_column_ids: Box<[ColumnId]>,
     pub partition_template: TablePartitionTemplateOverride,
+    /// The retention periods of the field columns with a retention period
+    /// override.
+    pub column_retention_periods: HashMap<ColumnId, Duration>,
 }
 
 impl CachedTable {
-    fn new(table: Table, mut columns: Vec<Column>) -> Self {
+    fn new(table: Table, mut columns: Vec<Column>, column_retention: Vec<ColumnRetention>) -> Self {
+        // Only field columns can be dropped separately from the rows they
+        // belong to.
+        let mut column_retention_periods: HashMap<ColumnId, Duration> = column_retention
+            .into_iter()
+            .filter(|r| {
+                columns.iter().any(|c| {
+                    c.id == r.column_id
+                        && !matches!(c.column_type, ColumnType::Tag | ColumnType::Time)
+                })
+            })
+            .map(|r| {
+                (
+                    r.column_id,
+                    Duration::from_nanos(r.retention_period_ns as u64),
+                )
+            })
+            .collect();
+        column_retention_periods.shrink_to_fit();
+
         // sort columns by name so that schema is normalized
         // Note: `sort_by_key` doesn't work if we don't wanna clone the strings every time
         columns.sort_by(|x, y| x.name.cmp(&y.name));
@@ -280,9 +332,26 @@ impl CachedTable {
             column_id_map_rev,
             primary_key_column_ids,
             partition_template: table.partition_template,
+            column_retention_periods,
         }
     }
 
+    /// Returns true if all the values of the column `id` in a file with the
+    /// given `max_time` are past the retention period of the column at `now`.
+    ///
+    /// The compactor drops these columns when the file is next compacted,
+    /// files straddling the cutoff keep the column.
+    pub fn is_column_expired(&self, id: ColumnId, max_time: Timestamp, now: Time) -> bool {
+        self.column_retention_periods
+            .get(&id)
+            .is_some_and(|period| {
+                let cutoff = now
+                    .timestamp_nanos()
+                    .saturating_sub(period.as_nanos() as i64);
+                max_time.get() < cutoff
+            })
+    }
+
     /// RAM-bytes EXCLUDING `self`.
     fn size(&self) -> usize {
         self.schema.estimate_size()
@@ -300,6 +369,7 @@ impl CachedTable {
                 .sum::<usize>()
             + (self.primary_key_column_ids.len() * size_of::<ColumnId>())
             + (self.partition_template.size() - size_of::<TablePartitionTemplateOverride>())
+            + (self.column_retention_periods.capacity() * size_of::<(ColumnId, Duration)>())
     }
 }
 
@@ -308,25 +378,51 @@ pub struct CachedNamespace {
     pub id: NamespaceId,
     pub retention_period: Option<Duration>,
     pub tables: HashMap<Arc<str>, Arc<CachedTable>>,
+    /// The view columns of the tables that have a view, keyed by table name.
+    pub views: HashMap<Arc<str>, Arc<[TableViewColumn]>>,
 }
 
 impl CachedNamespace {
-    pub fn new(namespace: Namespace, tables: Vec<Table>, columns: Vec<Column>) -> Self {
+    pub fn new(
+        namespace: Namespace,
+        tables: Vec<Table>,
+        columns: Vec<Column>,
+        column_retention: Vec<ColumnRetention>,
+        view_columns: Vec<TableViewColumn>,
+    ) -> Self {
         let mut tables_by_id = tables
             .into_iter()
-            .map(|t| (t.id, (t, vec![])))
+            .map(|t| (t.id, (t, vec![], vec![])))
             .collect::<HashMap<_, _>>();
         for col in columns {
-            if let Some((_t, tcols)) = tables_by_id.get_mut(&col.table_id) {
+            if let Some((_t, tcols, _tretention)) = tables_by_id.get_mut(&col.table_id) {
                 tcols.push(col);
             }
         }
+        for retention in column_retention {
+            if let Some((_t, _tcols, tretention)) = tables_by_id.get_mut(&retention.table_id) {
+                tretention.push(retention);
+            }
+        }
+
+        let mut views_by_id: HashMap<TableId, Vec<TableViewColumn>> = HashMap::new();
+        for col in view_columns {
+            views_by_id.entry(col.table_id).or_default().push(col);
+        }
+        let mut views: HashMap<Arc<str>, Arc<[TableViewColumn]>> = views_by_id
+            .into_iter()
+            .filter_map(|(tid, cols)| {
+                let (t, _tcols, _tretention) = tables_by_id.get(&tid)?;
+                Some((Arc::from(t.name.clone()), Arc::from(cols)))
+            })
+            .collect();
+        views.shrink_to_fit();
 
         let mut tables: HashMap<Arc<str>, Arc<CachedTable>> = tables_by_id
             .into_iter()
-            .map(|(_tid, (t, tcols))| {
+            .map(|(_tid, (t, tcols, tretention))| {
                 let name = Arc::from(t.name.clone());
-                let table = Arc::new(CachedTable::new(t, tcols));
+                let table = Arc::new(CachedTable::new(t, tcols, tretention));
                 (name, table)
             })
             .collect();
@@ -340,6 +436,7 @@ impl CachedNamespace {
             id: namespace.id,
             retention_period,
             tables,
+            views,
         }
     }
 
@@ -351,6 +448,12 @@ impl CachedNamespace {
                 .iter()
                 .map(|(name, table)| name.len() + table.size())
                 .sum::<usize>()
+            + self.views.capacity() * size_of::<(Arc<str>, Arc<[TableViewColumn]>)>()
+            + self
+                .views
+                .iter()
+                .map(|(name, cols)| name.len() + cols.iter().map(|c| c.size()).sum::<usize>())
+                .sum::<usize>()
     }
 }
 
@@ -442,6 +545,7 @@ mod tests {
                         ]),
                         primary_key_column_ids: [col112.column.id, col113.column.id].into(),
                         partition_template: table11.table.partition_template.clone(),
+                        column_retention_periods: Default::default(),
                     }),
                 ),
                 (
@@ -464,9 +568,11 @@ mod tests {
                         ]),
                         primary_key_column_ids: [col122.column.id].into(),
                         partition_template: TablePartitionTemplateOverride::default(),
+                        column_retention_periods: Default::default(),
                     }),
                 ),
             ]),
+            views: HashMap::new(),
         };
         assert_eq!(actual_ns_1_a.as_ref(), &expected_ns_1);
         assert_catalog_access_metric_count(&catalog.metric_registry, "namespace_get_by_name", 1);
@@ -497,8 +603,10 @@ mod tests {
                     )]),
                     primary_key_column_ids: [col211.column.id].into(),
                     partition_template: TablePartitionTemplateOverride::default(),
+                    column_retention_periods: Default::default(),
                 }),
             )]),
+            views: HashMap::new(),
         };
         assert_eq!(actual_ns_2.as_ref(), &expected_ns_2);
         assert_catalog_access_metric_count(&catalog.metric_registry, "namespace_get_by_name", 2);
@@ -511,6 +619,106 @@ mod tests {
         assert_catalog_access_metric_count(&catalog.metric_registry, "namespace_get_by_name", 2);
     }
 
+    #[tokio::test]
+    async fn test_views() {
+        let catalog = TestCatalog::new();
+
+        let ns = catalog.create_namespace_1hr_retention("ns").await;
+        let table1 = ns.create_table("table1").await;
+        ns.create_table("table2").await;
+        table1.create_column("temp", ColumnType::F64).await;
+        table1.create_column("time", ColumnType::Time).await;
+
+        let temp_c = TableViewColumn {
+            table_id: table1.table.id,
+            name: String::from("temp_c"),
+            expression: String::from(r#""temp""#),
+            unit: Some(String::from("celsius")),
+        };
+        catalog
+            .catalog
+            .repositories()
+            .await
+            .table_views()
+            .upsert(&temp_c)
+            .await
+            .unwrap();
+
+        let cache = NamespaceCache::new(
+            catalog.catalog(),
+            BackoffConfig::default(),
+            catalog.time_provider(),
+            &catalog.metric_registry(),
+            test_ram_pool(),
+            &Handle::current(),
+            true,
+        );
+
+        let cached = cache.get(Arc::from("ns"), &[], None).await.unwrap();
+        assert_eq!(cached.views.len(), 1);
+        assert_eq!(cached.views.get("table1").unwrap().as_ref(), &[temp_c]);
+        assert_catalog_access_metric_count(
+            &catalog.metric_registry,
+            "table_view_list_by_namespace_id",
+            1,
+        );
+    }
+
+    #[tokio::test]
+    async fn test_column_retention() {
+        let catalog = TestCatalog::new();
+
+        let ns = catalog.create_namespace_1hr_retention("ns").await;
+        let table = ns.create_table("table").await;
+        let tag = table.create_column("tag", ColumnType::Tag).await;
+        let debug = table.create_column("debug", ColumnType::String).await;
+        table.create_column("time", ColumnType::Time).await;
+
+        let mut repos = catalog.catalog.repositories().await;
+        for c in [&tag, &debug] {
+            repos
+                .column_retention()
+                .upsert(&ColumnRetention {
+                    table_id: table.table.id,
+                    column_id: c.column.id,
+                    retention_period_ns: 100,
+                })
+                .await
+                .unwrap();
+        }
+        drop(repos);
+
+        let cache = NamespaceCache::new(
+            catalog.catalog(),
+            BackoffConfig::default(),
+            catalog.time_provider(),
+            &catalog.metric_registry(),
+            test_ram_pool(),
+            &Handle::current(),
+            true,
+        );
+
+        let cached = cache.get(Arc::from("ns"), &[], None).await.unwrap();
+        assert_catalog_access_metric_count(
+            &catalog.metric_registry,
+            "column_retention_list_by_namespace_id",
+            1,
+        );
+
+        // Tags identify rows and never expire on their own.
+        let cached_table = cached.tables.get("table").unwrap();
+        assert_eq!(
+            cached_table.column_retention_periods,
+            HashMap::from([(debug.column.id, Duration::from_nanos(100))]),
+        );
+
+        // Files straddling the cutoff keep the column.
+        let now = Time::from_timestamp_nanos(1_000);
+        assert!(cached_table.is_column_expired(debug.column.id, Timestamp::new(899), now));
+        assert!(!cached_table.is_column_expired(debug.column.id, Timestamp::new(900), now));
+        assert!(!cached_table.is_column_expired(tag.column.id, Timestamp::new(0), now));
+    }
+
     #[tokio::test]
     async fn test_schema_non_existing() {
         let catalog = TestCatalog::new();
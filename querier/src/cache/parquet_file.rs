@@ -12,7 +12,7 @@ use cache_system::{
     loader::{metrics::MetricsLoader, FunctionLoader},
     resource_consumption::FunctionEstimator,
 };
-use data_types::{ParquetFile, TableId};
+use data_types::{ParquetFile, ParquetFileColumnStatistics, ParquetFileId, TableId};
 use iox_catalog::interface::Catalog;
 use iox_time::TimeProvider;
 use snafu::{ResultExt, Snafu};
@@ -46,6 +46,11 @@ pub struct CachedParquetFiles {
     /// Parquet catalog information
     pub files: Arc<[Arc<ParquetFile>]>,
 
+    /// Statistics of the string and tag columns of the files, by file ID.
+    ///
+    /// Files without recorded statistics have no entry.
+    column_statistics: HashMap<ParquetFileId, Arc<[ParquetFileColumnStatistics]>>,
+
     /// Number of persisted Parquet files per table ID per ingester UUID that ingesters have told
     /// us about. When a call to `get` includes a number of persisted Parquet files for this table
     /// and a particular ingester UUID that doesn't match what we've previously seen, the cache
@@ -58,16 +63,37 @@ pub struct CachedParquetFiles {
 impl CachedParquetFiles {
     fn new(
         parquet_files: Vec<ParquetFile>,
+        column_statistics: Vec<(ParquetFileId, ParquetFileColumnStatistics)>,
         persisted_file_counts_from_ingesters: IngesterCounts,
     ) -> Self {
         let files = parquet_files.into_iter().map(Arc::new).collect();
 
+        let mut by_file = HashMap::<_, Vec<_>>::new();
+        for (id, stats) in column_statistics {
+            by_file.entry(id).or_default().push(stats);
+        }
+        let mut column_statistics: HashMap<_, Arc<[_]>> = by_file
+            .into_iter()
+            .map(|(id, stats)| (id, stats.into()))
+            .collect();
+        column_statistics.shrink_to_fit();
+
         Self {
             files,
+            column_statistics,
             persisted_file_counts_from_ingesters,
         }
     }
 
+    /// Return the statistics of the string and tag columns of the file `id`,
+    /// empty if none were recorded.
+    pub fn column_statistics(&self, id: ParquetFileId) -> Arc<[ParquetFileColumnStatistics]> {
+        self.column_statistics
+            .get(&id)
+            .map(Arc::clone)
+            .unwrap_or_else(|| Arc::from([]))
+    }
+
     /// return the underlying files as a new Vec
     #[cfg(test)]
     fn vec(&self) -> Vec<Arc<ParquetFile>> {
@@ -88,6 +114,14 @@ impl CachedParquetFiles {
             mem::size_of_val(self.files.as_ref()) +
         // size of the underlying parquet files
             self.files.iter().map(|f| f.size()).sum::<usize>() +
+        // column statistics
+            self.column_statistics.capacity()
+                * mem::size_of::<(ParquetFileId, Arc<[ParquetFileColumnStatistics]>)>() +
+            self.column_statistics
+                .values()
+                .flat_map(|stats| stats.iter())
+                .map(|s| s.size())
+                .sum::<usize>() +
         // hashmap data
             self.persisted_file_counts_from_ingesters
                 .as_ref()
@@ -149,16 +183,23 @@ impl ParquetFileCache {
                             // 2. track time ranges needed for queries and
                             // limit files fetched to what is actually
                             // needed
-                            let parquet_files: Vec<_> = catalog
-                                .repositories()
-                                .await
+                            let mut repos = catalog.repositories().await;
+                            let parquet_files: Vec<_> = repos
                                 .parquet_files()
                                 .list_by_table_not_to_delete(table_id)
                                 .await
                                 .context(CatalogSnafu)?;
+                            let column_statistics = repos
+                                .parquet_files()
+                                .list_column_statistics_by_table(table_id)
+                                .await
+                                .context(CatalogSnafu)?;
 
-                            Ok(Arc::new(CachedParquetFiles::new(parquet_files, extra)))
-                                as std::result::Result<_, Error>
+                            Ok(Arc::new(CachedParquetFiles::new(
+                                parquet_files,
+                                column_statistics,
+                                extra,
+                            ))) as std::result::Result<_, Error>
                         }
                     })
                     .await
@@ -361,8 +402,8 @@ mod tests {
         partition.create_parquet_file(builder).await;
         let table_id = table.table.id;
 
-        let single_file_size = 256;
-        let two_file_size = 480;
+        let single_file_size = 304;
+        let two_file_size = 528;
         assert!(single_file_size < two_file_size);
 
         let cache = make_cache(&catalog);
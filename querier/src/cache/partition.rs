@@ -504,6 +504,7 @@ mod tests {
             ]),
             primary_key_column_ids: [c1.column.id, c2.column.id].into(),
             partition_template: TablePartitionTemplateOverride::default(),
+            column_retention_periods: Default::default(),
         });
 
         let cache = PartitionCache::new(
@@ -663,6 +664,7 @@ mod tests {
             ]),
             primary_key_column_ids: [c1.column.id, c2.column.id, c3.column.id, c4.column.id].into(),
             partition_template: t.table.partition_template.clone(),
+            column_retention_periods: Default::default(),
         });
 
         let cache = PartitionCache::new(
@@ -914,6 +916,7 @@ mod tests {
             column_id_map_rev: HashMap::from([(Arc::from(c.column.name.clone()), c.column.id)]),
             primary_key_column_ids: [c.column.id].into(),
             partition_template: t.table.partition_template.clone(),
+            column_retention_periods: Default::default(),
         });
 
         let cache = PartitionCache::new(
@@ -979,6 +982,7 @@ mod tests {
             column_id_map_rev: HashMap::from([(Arc::from(c.column.name.clone()), c.column.id)]),
             primary_key_column_ids: [c.column.id].into(),
             partition_template: t.table.partition_template.clone(),
+            column_retention_periods: Default::default(),
         });
 
         let cache = PartitionCache::new(
@@ -1080,6 +1084,7 @@ mod tests {
             ]),
             primary_key_column_ids: [c1.column.id, c2.column.id].into(),
             partition_template: TablePartitionTemplateOverride::default(),
+            column_retention_periods: Default::default(),
         });
 
         let cache = PartitionCache::new(
@@ -1228,6 +1233,7 @@ mod tests {
             column_id_map_rev: HashMap::default(),
             primary_key_column_ids: [].into(),
             partition_template: TablePartitionTemplateOverride::default(),
+            column_retention_periods: Default::default(),
         });
 
         let cache = PartitionCache::new(
@@ -1459,6 +1465,7 @@ mod tests {
                 column_id_map_rev: HashMap::from([(Arc::from(c.column.name.clone()), c.column.id)]),
                 primary_key_column_ids: [c.column.id].into(),
                 partition_template: TablePartitionTemplateOverride::default(),
+                column_retention_periods: Default::default(),
             });
             const N_PARTITIONS: usize = 20;
             let c_id = c.column.id.get();
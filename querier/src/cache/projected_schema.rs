@@ -214,6 +214,7 @@ mod tests {
             ]
             .into(),
             partition_template: TablePartitionTemplateOverride::default(),
+            column_retention_periods: Default::default(),
         });
         let table_1b = Arc::new(CachedTable {
             id: table_id_1,
@@ -228,6 +229,7 @@ mod tests {
             ]
             .into(),
             partition_template: TablePartitionTemplateOverride::default(),
+            column_retention_periods: Default::default(),
         });
         let table_2a = Arc::new(CachedTable {
             id: table_id_2,
@@ -243,6 +245,7 @@ mod tests {
             ]
             .into(),
             partition_template: TablePartitionTemplateOverride::default(),
+            column_retention_periods: Default::default(),
         });
 
         // initial request
@@ -335,6 +338,7 @@ mod tests {
             column_id_map_rev: HashMap::default(),
             primary_key_column_ids: [].into(),
             partition_template: TablePartitionTemplateOverride::default(),
+            column_retention_periods: Default::default(),
         });
 
         // different column order
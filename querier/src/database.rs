@@ -5,16 +5,24 @@ use crate::{
     ingester::IngesterConnection,
     namespace::{QuerierNamespace, QuerierNamespaceArgs},
     parquet::ChunkAdapter,
+    query_denylist::QueryDenylist,
+    query_history::QueryHistory,
     query_log::QueryLog,
+    query_registry::QueryRegistry,
     table::PruneMetrics,
     QueryLogEntry,
 };
+use arrow::record_batch::RecordBatch;
 use async_trait::async_trait;
 use backoff::{Backoff, BackoffConfig};
-use data_types::Namespace;
-use iox_catalog::interface::SoftDeletedRows;
+use data_types::{Namespace, NamespaceName};
+use iox_catalog::interface::{Catalog, Error as CatalogError, SoftDeletedRows};
 use iox_query::exec::Executor;
-use service_common::QueryNamespaceProvider;
+use iox_time::TimeProvider;
+use service_common::{
+    ddl::{namespace_batch, DdlError, DdlStatement},
+    QueryNamespaceProvider,
+};
 use snafu::Snafu;
 use std::{
     collections::{HashMap, VecDeque},
@@ -62,6 +70,15 @@ pub struct QuerierDatabase {
     /// Query log.
     query_log: Arc<QueryLog>,
 
+    /// Registry of running queries.
+    query_registry: Arc<QueryRegistry>,
+
+    /// Rules rejecting queries.
+    query_denylist: Arc<QueryDenylist>,
+
+    /// History of completed queries.
+    query_history: Arc<QueryHistory>,
+
     /// Semaphore that limits the number of namespaces in used at the time by the query subsystem.
     ///
     /// This should be a 1-to-1 relation to the number of active queries.
@@ -95,6 +112,21 @@ impl QueryNamespaceProvider for QuerierDatabase {
             .await
             .expect("Semaphore should not be closed by anyone")
     }
+
+    async fn execute_ddl(
+        &self,
+        _namespace_name: &str,
+        statement: DdlStatement,
+        span: Option<Span>,
+    ) -> Result<RecordBatch, DdlError> {
+        let mut span_recorder = SpanRecorder::new(span);
+        let res = self.execute_ddl_inner(statement).await;
+        match &res {
+            Ok(_) => span_recorder.ok("executed DDL"),
+            Err(e) => span_recorder.error(e.to_string()),
+        }
+        res
+    }
 }
 
 impl QuerierDatabase {
@@ -129,6 +161,8 @@ impl QuerierDatabase {
             Arc::clone(&prune_metrics),
         ));
         let query_log = Arc::new(QueryLog::new(QUERY_LOG_SIZE, catalog_cache.time_provider()));
+        let query_registry = Arc::new(QueryRegistry::new(catalog_cache.time_provider()));
+        let query_history = Arc::new(QueryHistory::new(catalog_cache.catalog(), &metric_registry));
         let semaphore_metrics = Arc::new(AsyncSemaphoreMetrics::new(
             &metric_registry,
             &[("semaphore", "query_execution")],
@@ -143,6 +177,9 @@ impl QuerierDatabase {
             exec,
             ingester_connection,
             query_log,
+            query_registry,
+            query_denylist: Default::default(),
+            query_history,
             query_execution_semaphore,
             prune_metrics,
             datafusion_config,
@@ -178,6 +215,9 @@ impl QuerierDatabase {
             exec: Arc::clone(&self.exec),
             ingester_connection: self.ingester_connection.clone(),
             query_log: Arc::clone(&self.query_log),
+            query_registry: Arc::clone(&self.query_registry),
+            query_denylist: Arc::clone(&self.query_denylist),
+            query_history: Arc::clone(&self.query_history),
             prune_metrics: Arc::clone(&self.prune_metrics),
             datafusion_config: Arc::clone(&self.datafusion_config),
             include_debug_info_tables,
@@ -200,6 +240,67 @@ impl QuerierDatabase {
             .expect("retry forever")
     }
 
+    /// Apply `statement` to the catalog.
+    ///
+    /// Catalog requests are not retried, as the statements are not idempotent
+    /// and any failure is reported to the caller.
+    async fn execute_ddl_inner(&self, statement: DdlStatement) -> Result<RecordBatch, DdlError> {
+        let catalog = self.catalog_cache.catalog();
+        let mut repos = catalog.repositories().await;
+
+        let ns = match statement {
+            DdlStatement::CreateNamespace {
+                name,
+                retention_period_ns,
+            } => {
+                let name = NamespaceName::new(name).map_err(|e| DdlError::InvalidName {
+                    reason: e.to_string(),
+                })?;
+                repos
+                    .namespaces()
+                    .create(&name, None, retention_period_ns, None)
+                    .await
+                    .map_err(|e| match e {
+                        CatalogError::NameExists { name } => DdlError::AlreadyExists { name },
+                        e => DdlError::Catalog { source: e.into() },
+                    })?
+            }
+            DdlStatement::AlterNamespaceRetention {
+                name,
+                retention_period_ns,
+            } => repos
+                .namespaces()
+                .update_retention_period(&name, retention_period_ns)
+                .await
+                .map_err(|e| match e {
+                    CatalogError::NamespaceNotFoundByName { name } => DdlError::NotFound { name },
+                    e => DdlError::Catalog { source: e.into() },
+                })?,
+            stmt @ DdlStatement::DropTable { .. } => {
+                return Err(DdlError::Unsupported {
+                    statement: stmt.kind(),
+                    reason: "the catalog does not support deleting tables".to_string(),
+                });
+            }
+        };
+
+        Ok(namespace_batch(
+            ns.id.get(),
+            &ns.name,
+            ns.retention_period_ns,
+        ))
+    }
+
+    /// Catalog.
+    pub(crate) fn catalog(&self) -> Arc<dyn Catalog> {
+        self.catalog_cache.catalog()
+    }
+
+    /// Time provider.
+    pub(crate) fn time_provider(&self) -> Arc<dyn TimeProvider> {
+        self.catalog_cache.time_provider()
+    }
+
     /// Executor
     pub(crate) fn exec(&self) -> &Executor {
         &self.exec
@@ -209,12 +310,29 @@ impl QuerierDatabase {
     pub fn query_log(&self) -> VecDeque<Arc<QueryLogEntry>> {
         self.query_log.entries()
     }
+
+    /// Registry of the queries currently running.
+    pub fn query_registry(&self) -> &QueryRegistry {
+        &self.query_registry
+    }
+
+    /// Rules rejecting queries.
+    pub fn query_denylist(&self) -> &QueryDenylist {
+        &self.query_denylist
+    }
+
+    /// History of completed queries.
+    pub fn query_history(&self) -> &Arc<QueryHistory> {
+        &self.query_history
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::create_ingester_connection_for_testing;
+    use assert_matches::assert_matches;
+    use iox_query::QueryNamespace;
     use iox_tests::TestCatalog;
     use tokio::runtime::Handle;
 
@@ -270,6 +388,77 @@ mod tests {
         assert_eq!(namespaces[1].name, "ns2");
     }
 
+    #[tokio::test]
+    async fn test_query_registry() {
+        let catalog = TestCatalog::new();
+        let db = new_db(&catalog).await;
+
+        catalog.create_namespace_1hr_retention("ns1").await;
+        let ns = db.namespace("ns1", None, true).await.unwrap();
+
+        let token = ns.record_query(None, "sql", Box::new("SELECT 1"));
+        let queries = db.query_registry().list();
+        assert_eq!(queries.len(), 1);
+        assert_eq!(queries[0].namespace_name().as_ref(), "ns1");
+        assert_eq!(queries[0].entry().query_text.to_string(), "SELECT 1");
+
+        db.query_registry().kill(queries[0].id()).unwrap();
+        assert!(token.cancellation().unwrap().is_cancelled());
+
+        // Completing the query removes it from the registry.
+        drop(token);
+        assert!(db.query_registry().list().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_execute_ddl() {
+        let catalog = TestCatalog::new();
+        let db = new_db(&catalog).await;
+
+        let parse = |sql: &str| DdlStatement::parse(sql).unwrap().unwrap();
+
+        let batch = db
+            .execute_ddl("", parse("CREATE NAMESPACE ns1 RETENTION '1h'"), None)
+            .await
+            .unwrap();
+        assert_eq!(batch.num_rows(), 1);
+
+        let err = db
+            .execute_ddl("", parse("CREATE NAMESPACE ns1"), None)
+            .await
+            .unwrap_err();
+        assert_matches!(err, DdlError::AlreadyExists { name } if name == "ns1");
+
+        let err = db
+            .execute_ddl("", parse(r#"CREATE NAMESPACE "bad name""#), None)
+            .await
+            .unwrap_err();
+        assert_matches!(err, DdlError::InvalidName { .. });
+
+        db.execute_ddl(
+            "",
+            parse("ALTER NAMESPACE ns1 SET RETENTION INFINITE"),
+            None,
+        )
+        .await
+        .unwrap();
+        let namespaces = db.namespaces().await;
+        assert_eq!(namespaces.len(), 1);
+        assert_eq!(namespaces[0].retention_period_ns, None);
+
+        let err = db
+            .execute_ddl("", parse("ALTER NAMESPACE ns2 SET RETENTION '1d'"), None)
+            .await
+            .unwrap_err();
+        assert_matches!(err, DdlError::NotFound { name } if name == "ns2");
+
+        let err = db
+            .execute_ddl("ns1", parse("DROP TABLE cpu"), None)
+            .await
+            .unwrap_err();
+        assert_matches!(err, DdlError::Unsupported { .. });
+    }
+
     async fn new_db(catalog: &Arc<TestCatalog>) -> QuerierDatabase {
         let catalog_cache = Arc::new(CatalogCache::new_testing(
             catalog.catalog(),
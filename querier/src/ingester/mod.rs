@@ -1,6 +1,7 @@
 use self::test_util::MockIngesterConnection;
 use crate::cache::{namespace::CachedTable, CatalogCache};
 use arrow::record_batch::RecordBatch;
+use arrow_util::util::record_batches_memory_size;
 use async_trait::async_trait;
 use data_types::{ChunkId, ChunkOrder, NamespaceId, TransitionPartitionId};
 use datafusion::{physical_plan::Statistics, prelude::Expr};
@@ -11,7 +12,11 @@ use iox_query::{
 };
 use observability_deps::tracing::trace;
 use schema::{sort::SortKey, Schema};
-use std::{any::Any, sync::Arc};
+use std::{
+    any::Any,
+    num::{NonZeroU32, NonZeroUsize},
+    sync::Arc,
+};
 use trace::span::Span;
 use uuid::Uuid;
 
@@ -19,17 +24,26 @@ pub(crate) mod test_util;
 mod v1;
 
 /// Create a new set of connections given ingester configurations
+#[allow(clippy::too_many_arguments)]
 pub fn create_ingester_connections(
     ingester_addresses: Vec<Arc<str>>,
     catalog_cache: Arc<CatalogCache>,
     open_circuit_after_n_errors: u64,
+    table_spread: Option<NonZeroUsize>,
     trace_context_header_name: &str,
+    accept_gzip: bool,
+    max_message_bytes: Option<usize>,
+    max_snapshots: Option<NonZeroU32>,
 ) -> Arc<dyn IngesterConnection> {
     v1::create_ingester_connections(
         ingester_addresses,
         catalog_cache,
         open_circuit_after_n_errors,
+        table_spread,
         trace_context_header_name,
+        accept_gzip,
+        max_message_bytes,
+        max_snapshots,
     )
 }
 
@@ -83,6 +97,13 @@ pub struct IngesterPartition {
     /// The number of Parquet files this ingester UUID has persisted for this partition.
     completed_persistence_count: u64,
 
+    /// The object store IDs of the Parquet files this ingester is currently persisting for this
+    /// partition.
+    ///
+    /// The data in these files is included in `chunks` - any of these files visible in the
+    /// catalog must not be queried alongside this partition.
+    persisting_object_store_ids: Vec<Uuid>,
+
     chunks: Vec<IngesterChunk>,
 }
 
@@ -98,10 +119,18 @@ impl IngesterPartition {
             ingester_uuid,
             partition_id,
             completed_persistence_count,
+            persisting_object_store_ids: vec![],
             chunks: vec![],
         }
     }
 
+    /// Set the object store IDs of the Parquet files the ingester is persisting for this
+    /// partition.
+    pub(crate) fn with_persisting_object_store_ids(mut self, ids: Vec<Uuid>) -> Self {
+        self.persisting_object_store_ids = ids;
+        self
+    }
+
     pub(crate) fn push_chunk(
         mut self,
         chunk_id: ChunkId,
@@ -121,6 +150,25 @@ impl IngesterPartition {
         self
     }
 
+    /// Append the data of `page`, the continuation of a response truncated
+    /// after the data of this partition.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `page` is the data of another partition.
+    pub(crate) fn extend(&mut self, page: Self) {
+        assert_eq!(self.partition_id, page.partition_id);
+
+        // Files persisted between the pages are only accounted for by the
+        // count of the later page.
+        self.completed_persistence_count = self
+            .completed_persistence_count
+            .max(page.completed_persistence_count);
+        self.persisting_object_store_ids
+            .extend(page.persisting_object_store_ids);
+        self.chunks.extend(page.chunks);
+    }
+
     pub(crate) fn set_partition_column_ranges(&mut self, partition_column_ranges: &ColumnRanges) {
         for chunk in &mut self.chunks {
             // TODO: may want to ask the Ingester to send this value instead of computing it here.
@@ -154,6 +202,10 @@ impl IngesterPartition {
         self.completed_persistence_count
     }
 
+    pub(crate) fn persisting_object_store_ids(&self) -> &[Uuid] {
+        &self.persisting_object_store_ids
+    }
+
     pub(crate) fn chunks(&self) -> &[IngesterChunk] {
         &self.chunks
     }
@@ -182,16 +234,8 @@ pub struct IngesterChunk {
 
 impl IngesterChunk {
     pub(crate) fn estimate_size(&self) -> usize {
-        self.batches
-            .iter()
-            .map(|batch| {
-                batch
-                    .columns()
-                    .iter()
-                    .map(|array| array.get_array_memory_size())
-                    .sum::<usize>()
-            })
-            .sum::<usize>()
+        // Dictionaries shared between batches are only accounted for once.
+        record_batches_memory_size(&self.batches)
     }
 
     pub(crate) fn rows(&self) -> usize {
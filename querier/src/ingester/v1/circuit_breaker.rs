@@ -1263,6 +1263,8 @@ mod tests {
             table_id: TableId::new(0),
             columns: vec![],
             predicate: None,
+            max_snapshots: None,
+            resume_after: vec![],
         }
     }
 
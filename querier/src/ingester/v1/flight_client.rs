@@ -1,20 +1,38 @@
 use arrow_flight::{
     decode::{DecodedFlightData, DecodedPayload, FlightDataDecoder},
-    Ticket,
+    FlightClient, Ticket,
 };
 use async_trait::async_trait;
 use client_util::connection::{self, Connection};
 use futures::StreamExt;
+use influxdb_iox_client::build_info::{
+    self,
+    generated_types::{Compatibility, INGESTER_QUERY_PROTOCOL},
+};
 use ingester_query_grpc::{influxdata::iox::ingester::v1 as proto, IngesterQueryRequest};
 use observability_deps::tracing::{debug, warn};
 use prost::Message;
 use snafu::{ResultExt, Snafu};
-use std::{collections::HashMap, fmt::Debug, ops::DerefMut, sync::Arc};
+use std::{
+    collections::HashMap,
+    fmt::Debug,
+    ops::DerefMut,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tonic::codec::CompressionEncoding;
 use trace::{ctx::SpanContext, span::SpanRecorder};
 use trace_http::ctx::format_jaeger_trace_context;
 
 pub use influxdb_iox_client::flight::Error as FlightError;
 
+/// How often the protocol compatibility of a cached ingester connection is
+/// checked again.
+///
+/// The connection transparently reconnects, possibly to an ingester running a
+/// different build behind the same address.
+const COMPATIBILITY_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
 #[derive(Debug, Snafu)]
 #[allow(missing_copy_implementations, missing_docs)]
 pub enum Error {
@@ -30,6 +48,20 @@ pub enum Error {
         source: FlightError,
     },
 
+    #[snafu(display(
+        "Ingester '{}' (version {}) speaks query protocol version {}, but this querier speaks version {}",
+        ingester_address,
+        version,
+        peer,
+        local
+    ))]
+    Incompatible {
+        ingester_address: String,
+        version: String,
+        local: u32,
+        peer: u32,
+    },
+
     #[snafu(display("Internal error creating flight request : {}", source))]
     CreatingRequest {
         source: ingester_query_grpc::FieldViolation,
@@ -57,7 +89,10 @@ impl Error {
                 e.code(),
                 tonic::Code::NotFound | tonic::Code::ResourceExhausted
             ),
-            Self::Connecting { .. } | Self::Handshake { .. } | Self::Flight { .. } => true,
+            Self::Connecting { .. }
+            | Self::Handshake { .. }
+            | Self::Incompatible { .. }
+            | Self::Flight { .. } => true,
             // do NOT break circuit for client-side errors
             Self::CreatingRequest { .. } => false,
             // circuit broken, not an upstream error
@@ -99,6 +134,12 @@ pub struct FlightClientImpl {
 
     /// Name of the http header that will contain the tracing context value.
     trace_context_header_name: String,
+
+    /// Accept gzip compressed responses.
+    accept_gzip: bool,
+
+    /// The maximum size of a response message, if not the tonic default.
+    max_message_bytes: Option<usize>,
 }
 
 impl FlightClientImpl {
@@ -110,6 +151,35 @@ impl FlightClientImpl {
         }
     }
 
+    /// Advertise support for, and accept, gzip compressed responses.
+    pub fn with_gzip_responses(mut self) -> Self {
+        self.accept_gzip = true;
+        self
+    }
+
+    /// Accept response messages of up to `max_message_bytes`.
+    pub fn with_max_message_bytes(mut self, max_message_bytes: usize) -> Self {
+        self.max_message_bytes = Some(max_message_bytes);
+        self
+    }
+
+    /// Apply the compression and message size settings to `client`.
+    fn configure(&self, client: FlightClient) -> FlightClient {
+        let metadata = client.metadata().clone();
+
+        let mut inner = client.into_inner();
+        if self.accept_gzip {
+            inner = inner.accept_compressed(CompressionEncoding::Gzip);
+        }
+        if let Some(v) = self.max_message_bytes {
+            inner = inner.max_decoding_message_size(v);
+        }
+
+        let mut client = FlightClient::new_from_inner(inner);
+        *client.metadata_mut() = metadata;
+        client
+    }
+
     /// Establish connection to given addr and perform handshake.
     async fn connect(&self, ingester_address: Arc<str>) -> Result<Connection, Error> {
         let cached_connection = {
@@ -163,9 +233,11 @@ impl IngesterFlightClient for FlightClientImpl {
             }
         };
 
-        let mut client = influxdb_iox_client::flight::Client::new(connection)
-            // use lower level client to send a custom message type
-            .into_inner();
+        let mut client = self.configure(
+            influxdb_iox_client::flight::Client::new(connection)
+                // use lower level client to send a custom message type
+                .into_inner(),
+        );
 
         // Add the span context header, if any
         let span_recorder_comm = span_recorder.child("comm");
@@ -361,7 +433,7 @@ where
 struct CachedConnection {
     ingester_address: Arc<str>,
     /// Real async mutex to
-    maybe_connection: Arc<tokio::sync::Mutex<Option<Connection>>>,
+    maybe_connection: Arc<tokio::sync::Mutex<Option<(Connection, Instant)>>>,
 }
 
 impl CachedConnection {
@@ -378,9 +450,17 @@ impl CachedConnection {
 
         let ingester_address = self.ingester_address.as_ref();
 
-        if let Some(connection) = maybe_connection.as_ref() {
+        if let Some((connection, checked_at)) = maybe_connection.as_mut() {
             debug!(%ingester_address, "Reusing connection to ingester");
 
+            if checked_at.elapsed() >= COMPATIBILITY_CHECK_INTERVAL {
+                if let Err(e) = check_compatibility(connection, ingester_address).await {
+                    maybe_connection.take();
+                    return Err(e);
+                }
+                *checked_at = Instant::now();
+            }
+
             Ok(connection.clone())
         } else {
             debug!(%ingester_address, "Connecting to ingester");
@@ -399,7 +479,9 @@ impl CachedConnection {
                 .await
                 .context(HandshakeSnafu { ingester_address })?;
 
-            *maybe_connection = Some(connection.clone());
+            check_compatibility(&connection, ingester_address).await?;
+
+            *maybe_connection = Some((connection.clone(), Instant::now()));
             Ok(connection)
         }
     }
@@ -413,6 +495,45 @@ impl CachedConnection {
     }
 }
 
+/// Refuse to query an ingester speaking a different version of the query
+/// protocol than this querier.
+///
+/// Ingesters that do not report their build information (i.e. those older
+/// than the build information service) are logged and accepted.
+async fn check_compatibility(connection: &Connection, ingester_address: &str) -> Result<(), Error> {
+    let mut client = build_info::Client::new(connection.clone());
+    let info = match client.get_build_info().await {
+        Ok(v) => v,
+        Err(error) => {
+            warn!(
+                %ingester_address,
+                %error,
+                "cannot read ingester build information, skipping compatibility check"
+            );
+            return Ok(());
+        }
+    };
+
+    match build_info::generated_types::check_compatibility(&info, INGESTER_QUERY_PROTOCOL) {
+        Compatibility::Compatible => Ok(()),
+        Compatibility::Unknown => {
+            warn!(
+                %ingester_address,
+                version = %info.version,
+                "ingester does not report its query protocol version"
+            );
+            Ok(())
+        }
+        Compatibility::Incompatible { local, peer } => IncompatibleSnafu {
+            ingester_address,
+            version: info.version,
+            local,
+            peer,
+        }
+        .fail(),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use data_types::{NamespaceId, TableId};
@@ -512,6 +633,8 @@ mod tests {
             table_id: TableId::new(1337),
             columns: vec![String::from("col1"), String::from("col2")],
             predicate: Some(predicate),
+            max_snapshots: None,
+            resume_after: vec![],
         };
 
         let proto = serialize_ingester_query_request(request.clone()).expect("serialization");
@@ -15,7 +15,10 @@ use arrow_flight::decode::DecodedPayload;
 use async_trait::async_trait;
 use backoff::{Backoff, BackoffConfig, BackoffError};
 use client_util::connection;
-use data_types::{ChunkId, NamespaceId, PartitionHashId, PartitionId, TransitionPartitionId};
+use data_types::{
+    ingester_mapping::IngesterMapping, ChunkId, NamespaceId, PartitionHashId, PartitionId,
+    SequenceNumber, TableId, TransitionPartitionId,
+};
 use datafusion::prelude::Expr;
 use futures::{stream::FuturesUnordered, TryStreamExt};
 use ingester_query_grpc::{
@@ -33,7 +36,8 @@ use schema::Schema;
 use snafu::{ensure, OptionExt, ResultExt, Snafu};
 use std::{
     any::Any,
-    collections::{HashMap, HashSet},
+    collections::{hash_map::Entry, HashMap, HashSet},
+    num::{NonZeroU32, NonZeroUsize},
     sync::Arc,
     time::Duration,
 };
@@ -120,12 +124,28 @@ pub enum Error {
         ingester_address: String,
     },
 
+    #[snafu(display(
+        "Ingester restarted while paging partition {partition_id}, ingester: {ingester_address}"
+    ))]
+    IngesterRestartedWhilePaging {
+        partition_id: TransitionPartitionId,
+        ingester_address: String,
+    },
+
     #[snafu(display("Could not parse `{ingester_uuid}` as a UUID: {source}"))]
     IngesterUuid {
         ingester_uuid: String,
         source: uuid::Error,
     },
 
+    #[snafu(display(
+        "Could not parse persisting object store ID `{object_store_id}` as a UUID: {source}"
+    ))]
+    PersistingObjectStoreId {
+        object_store_id: String,
+        source: uuid::Error,
+    },
+
     #[snafu(display("Could not parse bytes as a `PartitionHashId`: {source}"))]
     PartitionHashId {
         source: data_types::PartitionHashIdError,
@@ -140,12 +160,22 @@ pub enum Error {
 
 pub type Result<T, E = Error> = std::result::Result<T, E>;
 
-/// Create a new set of connections given ingester configurations
+/// Create a new set of connections given ingester configurations.
+///
+/// If `table_spread` is provided, queries for a table are sent only to the
+/// ingesters assigned to it by an [`IngesterMapping`] over
+/// `ingester_addresses`. If `max_snapshots` is provided, the data of a
+/// partition is fetched in pages of at most that many snapshots.
+#[allow(clippy::too_many_arguments)]
 pub fn create_ingester_connections(
     ingester_addresses: Vec<Arc<str>>,
     catalog_cache: Arc<CatalogCache>,
     open_circuit_after_n_errors: u64,
+    table_spread: Option<NonZeroUsize>,
     trace_context_header_name: &str,
+    accept_gzip: bool,
+    max_message_bytes: Option<usize>,
+    max_snapshots: Option<NonZeroU32>,
 ) -> Arc<dyn IngesterConnection> {
     // This backoff config is used to retry requests for a specific table-scoped query.
     let retry_backoff_config = BackoffConfig {
@@ -164,14 +194,22 @@ pub fn create_ingester_connections(
         deadline: None,
     };
 
-    Arc::new(IngesterConnectionImpl::by_addrs(
+    let conn = IngesterConnectionImpl::by_addrs(
         ingester_addresses,
         catalog_cache,
         retry_backoff_config,
         circuit_breaker_backoff_config,
         open_circuit_after_n_errors,
         trace_context_header_name,
-    ))
+        accept_gzip,
+        max_message_bytes,
+    )
+    .with_max_snapshots(max_snapshots);
+
+    Arc::new(match table_spread {
+        Some(spread) => conn.with_table_spread(spread),
+        None => conn,
+    })
 }
 
 /// Structure that holds metrics for ingester connections.
@@ -305,14 +343,20 @@ impl<'a> Drop for ObserveIngesterRequest<'a> {
 #[derive(Debug)]
 struct IngesterConnectionImpl {
     unique_ingester_addresses: HashSet<Arc<str>>,
+    /// If set, the subset of `unique_ingester_addresses` that may hold data
+    /// for a given table.
+    ingester_mapping: Option<IngesterMapping<Arc<str>>>,
     flight_client: Arc<dyn IngesterFlightClient>,
     time_provider: Arc<dyn TimeProvider>,
     metrics: Arc<IngesterConnectionMetrics>,
     backoff_config: BackoffConfig,
+    /// The maximum number of snapshots of a partition fetched per request.
+    max_snapshots: Option<NonZeroU32>,
 }
 
 impl IngesterConnectionImpl {
     /// Create a new set of connections given a list of ingester addresses.
+    #[allow(clippy::too_many_arguments)]
     fn by_addrs(
         ingester_addresses: Vec<Arc<str>>,
         catalog_cache: Arc<CatalogCache>,
@@ -320,8 +364,19 @@ impl IngesterConnectionImpl {
         circuit_breaker_backoff_config: BackoffConfig,
         open_circuit_after_n_errors: u64,
         trace_context_header_name: &str,
+        accept_gzip: bool,
+        max_message_bytes: Option<usize>,
     ) -> Self {
-        let flight_client = Arc::new(FlightClientImpl::new(trace_context_header_name));
+        let flight_client = FlightClientImpl::new(trace_context_header_name);
+        let flight_client = if accept_gzip {
+            flight_client.with_gzip_responses()
+        } else {
+            flight_client
+        };
+        let flight_client = Arc::new(match max_message_bytes {
+            Some(v) => flight_client.with_max_message_bytes(v),
+            None => flight_client,
+        });
         let flight_client = Arc::new(InvalidateOnErrorFlightClient::new(flight_client));
         let flight_client = Arc::new(CircuitBreakerFlightClient::new(
             flight_client,
@@ -354,10 +409,44 @@ impl IngesterConnectionImpl {
 
         Self {
             unique_ingester_addresses: ingester_addresses.into_iter().collect(),
+            ingester_mapping: None,
             flight_client,
             time_provider: catalog_cache.time_provider(),
             metrics,
             backoff_config,
+            max_snapshots: None,
+        }
+    }
+
+    /// Fetch the data of a partition in pages of at most `max_snapshots`
+    /// snapshots, following the continuation of each truncated response.
+    fn with_max_snapshots(mut self, max_snapshots: Option<NonZeroU32>) -> Self {
+        self.max_snapshots = max_snapshots;
+        self
+    }
+
+    /// Query only the ingesters that own partitions of the requested table,
+    /// as assigned by a router configured with the same ingester addresses
+    /// and `table_spread`.
+    fn with_table_spread(mut self, table_spread: NonZeroUsize) -> Self {
+        self.ingester_mapping = Some(IngesterMapping::new(
+            self.unique_ingester_addresses
+                .iter()
+                .map(|addr| (Arc::clone(addr), Arc::clone(addr))),
+            table_spread,
+        ));
+        self
+    }
+
+    /// Return the addresses of the ingesters to query for `table_id`.
+    fn ingesters_for_table(&self, table_id: TableId) -> Vec<Arc<str>> {
+        match &self.ingester_mapping {
+            Some(m) => m
+                .table_candidates(table_id)
+                .into_iter()
+                .map(Arc::clone)
+                .collect(),
+            None => self.unique_ingester_addresses.iter().cloned().collect(),
         }
     }
 }
@@ -372,9 +461,14 @@ struct GetPartitionForIngester<'a> {
     columns: Vec<String>,
     filters: &'a [Expr],
     cached_table: Arc<CachedTable>,
+    max_snapshots: Option<NonZeroU32>,
 }
 
 /// Fetches the partitions for a single ingester
+///
+/// A response truncated by `max_snapshots` is resumed until all the data of
+/// its partitions is fetched, appending the data of each page to the
+/// partitions of the previous ones.
 async fn execute(
     request: GetPartitionForIngester<'_>,
     span_recorder: &SpanRecorder,
@@ -387,97 +481,154 @@ async fn execute(
         columns,
         filters,
         cached_table,
+        max_snapshots,
     } = request;
 
-    let ingester_query_request = IngesterQueryRequest {
-        namespace_id,
-        table_id: cached_table.id,
-        columns: columns.clone(),
-        predicate: Some(Predicate::default().with_exprs(filters.iter().cloned())),
-    };
+    let mut partitions: HashMap<TransitionPartitionId, IngesterPartition> = HashMap::new();
+    let mut resume_after = vec![];
+    loop {
+        let is_continuation = !resume_after.is_empty();
+        let ingester_query_request = IngesterQueryRequest {
+            namespace_id,
+            table_id: cached_table.id,
+            columns: columns.clone(),
+            predicate: Some(Predicate::default().with_exprs(filters.iter().cloned())),
+            max_snapshots,
+            resume_after: std::mem::take(&mut resume_after),
+        };
 
-    let query_res = {
-        let span_recorder = span_recorder.child("flight client");
-        flight_client
-            .query(
-                Arc::clone(&ingester_address),
-                ingester_query_request,
-                span_recorder.span().map(|span| span.ctx.clone()),
-            )
+        let query_res = {
+            let span_recorder = span_recorder.child("flight client");
+            flight_client
+                .query(
+                    Arc::clone(&ingester_address),
+                    ingester_query_request,
+                    span_recorder.span().map(|span| span.ctx.clone()),
+                )
+                .await
+        };
+
+        match &query_res {
+            // A continuation failing fails the whole request, rather than
+            // returning the partitions of the previous pages partially.
+            Err(FlightClientError::CircuitBroken { .. }) if !is_continuation => {
+                warn!(
+                    ingester_address = ingester_address.as_ref(),
+                    namespace_id = namespace_id.get(),
+                    table_id = cached_table.id.get(),
+                    "Could not connect to ingester, circuit broken",
+                );
+                return Ok(vec![]);
+            }
+            Err(FlightClientError::Flight {
+                source:
+                    FlightError::ArrowFlightError(arrow_flight::error::FlightError::Tonic(status)),
+            }) if status.code() == tonic::Code::NotFound && !is_continuation => {
+                debug!(
+                    ingester_address = ingester_address.as_ref(),
+                    namespace_id = namespace_id.get(),
+                    table_id = cached_table.id.get(),
+                    "Ingester does not know namespace or table, skipping",
+                );
+                return Ok(vec![]);
+            }
+            _ => {}
+        }
+
+        let mut perform_query = query_res
+            .context(RemoteQuerySnafu {
+                ingester_address: ingester_address.as_ref(),
+            })
+            .map_err(|e| {
+                // generate a warning that is sufficient to replicate the request using CLI tooling
+                warn!(
+                    e=%e,
+                    ingester_address=ingester_address.as_ref(),
+                    namespace_id=namespace_id.get(),
+                    table_id=cached_table.id.get(),
+                    columns=columns.join(",").as_str(),
+                    filters_str=?filters,
+                    filters_binary=encode_filters_as_base64(filters).as_str(),
+                    "Failed to perform ingester query",
+                );
+
+                //  need to return error until https://github.com/rust-lang/rust/issues/91345 is stable
+                e
+            })?;
+
+        // collect data from IO stream
+        // Drain data from ingester so we don't block the ingester while performing catalog IO or CPU
+        // computations
+        let mut messages = vec![];
+        while let Some(data) = perform_query
+            .next_message()
             .await
-    };
+            .map_err(|source| FlightClientError::Flight { source })
+            .context(RemoteQuerySnafu {
+                ingester_address: ingester_address.as_ref(),
+            })?
+        {
+            messages.push(data);
+        }
 
-    match &query_res {
-        Err(FlightClientError::CircuitBroken { .. }) => {
+        // The ingester may omit its buffered data to relieve buffer pressure, in
+        // which case only the persisted data of those partitions is queried.
+        let n_omitted = messages
+            .iter()
+            .filter(|(_, md)| md.buffered_data_omitted)
+            .count();
+        if n_omitted > 0 {
             warn!(
                 ingester_address = ingester_address.as_ref(),
                 namespace_id = namespace_id.get(),
                 table_id = cached_table.id.get(),
-                "Could not connect to ingester, circuit broken",
+                n_partitions = n_omitted,
+                "ingester omitted buffered data under buffer pressure",
             );
-            return Ok(vec![]);
         }
-        Err(FlightClientError::Flight {
-            source: FlightError::ArrowFlightError(arrow_flight::error::FlightError::Tonic(status)),
-        }) if status.code() == tonic::Code::NotFound => {
-            debug!(
-                ingester_address = ingester_address.as_ref(),
-                namespace_id = namespace_id.get(),
-                table_id = cached_table.id.get(),
-                "Ingester does not know namespace or table, skipping",
-            );
-            return Ok(vec![]);
-        }
-        _ => {}
-    }
 
-    let mut perform_query = query_res
-        .context(RemoteQuerySnafu {
-            ingester_address: ingester_address.as_ref(),
-        })
-        .map_err(|e| {
-            // generate a warning that is sufficient to replicate the request using CLI tooling
-            warn!(
-                e=%e,
-                ingester_address=ingester_address.as_ref(),
-                namespace_id=namespace_id.get(),
-                table_id=cached_table.id.get(),
-                columns=columns.join(",").as_str(),
-                filters_str=?filters,
-                filters_binary=encode_filters_as_base64(filters).as_str(),
-                "Failed to perform ingester query",
-            );
+        // reconstruct partitions
+        let mut decoder = IngesterStreamDecoder::new(
+            Arc::clone(&ingester_address),
+            Arc::clone(&cached_table),
+            span_recorder.child_span("IngesterStreamDecoder"),
+        );
+        for (msg, md) in messages {
+            decoder.register(msg, md)?;
+        }
 
-            //  need to return error until https://github.com/rust-lang/rust/issues/91345 is stable
-            e
-        })?;
+        let (page, next) = decoder.finalize()?;
+        for partition in page {
+            match partitions.entry(partition.partition_id()) {
+                Entry::Occupied(mut e) => {
+                    // The position a continuation resumes from is only valid
+                    // for the ingester process that returned it.
+                    ensure!(
+                        e.get().ingester_uuid() == partition.ingester_uuid(),
+                        IngesterRestartedWhilePagingSnafu {
+                            partition_id: partition.partition_id(),
+                            ingester_address: ingester_address.as_ref(),
+                        }
+                    );
+                    e.get_mut().extend(partition);
+                }
+                Entry::Vacant(e) => {
+                    e.insert(partition);
+                }
+            }
+        }
 
-    // collect data from IO stream
-    // Drain data from ingester so we don't block the ingester while performing catalog IO or CPU
-    // computations
-    let mut messages = vec![];
-    while let Some(data) = perform_query
-        .next_message()
-        .await
-        .map_err(|source| FlightClientError::Flight { source })
-        .context(RemoteQuerySnafu {
-            ingester_address: ingester_address.as_ref(),
-        })?
-    {
-        messages.push(data);
+        if next.is_empty() {
+            break;
+        }
+        resume_after = next;
     }
 
-    // reconstruct partitions
-    let mut decoder = IngesterStreamDecoder::new(
-        ingester_address,
-        cached_table,
-        span_recorder.child_span("IngesterStreamDecoder"),
-    );
-    for (msg, md) in messages {
-        decoder.register(msg, md)?;
-    }
+    let mut partitions = partitions.into_values().collect::<Vec<_>>();
 
-    decoder.finalize()
+    // deterministic order
+    partitions.sort_by(|a, b| a.partition_id.cmp(&b.partition_id));
+    Ok(partitions)
 }
 
 /// Helper to disassemble the data from the ingester Apache Flight arrow stream.
@@ -488,6 +639,8 @@ struct IngesterStreamDecoder {
     finished_partitions: HashMap<TransitionPartitionId, IngesterPartition>,
     current_partition: Option<IngesterPartition>,
     current_chunk: Option<(Schema, Vec<RecordBatch>)>,
+    /// The truncated partitions, and the sequence number to resume each after.
+    resume_after: Vec<(TransitionPartitionId, SequenceNumber)>,
     ingester_address: Arc<str>,
     cached_table: Arc<CachedTable>,
     span_recorder: SpanRecorder,
@@ -500,6 +653,7 @@ impl IngesterStreamDecoder {
             finished_partitions: HashMap::new(),
             current_partition: None,
             current_chunk: None,
+            resume_after: vec![],
             ingester_address,
             cached_table,
             span_recorder: SpanRecorder::new(span),
@@ -574,11 +728,27 @@ impl IngesterStreamDecoder {
                         ingester_uuid: md.ingester_uuid,
                     })?;
 
+                let persisting_object_store_ids = md
+                    .persisting_object_store_ids
+                    .iter()
+                    .map(|id| {
+                        Uuid::parse_str(id).context(PersistingObjectStoreIdSnafu {
+                            object_store_id: id,
+                        })
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                if let Some(sequence_number) = md.resume_after_sequence_number {
+                    self.resume_after
+                        .push((partition_id.clone(), SequenceNumber::new(sequence_number)));
+                }
+
                 let partition = IngesterPartition::new(
                     ingester_uuid,
                     partition_id,
                     md.completed_persistence_count,
-                );
+                )
+                .with_persisting_object_store_ids(persisting_object_store_ids);
                 self.current_partition = Some(partition);
             }
             DecodedPayload::Schema(schema) => {
@@ -616,16 +786,20 @@ impl IngesterStreamDecoder {
         Ok(())
     }
 
-    /// Flush internal state and return sorted set of partitions.
-    fn finalize(mut self) -> Result<Vec<IngesterPartition>> {
+    /// Flush internal state and return the set of partitions, and the
+    /// truncated partitions to resume.
+    fn finalize(
+        mut self,
+    ) -> Result<(
+        Vec<IngesterPartition>,
+        Vec<(TransitionPartitionId, SequenceNumber)>,
+    )> {
         self.flush_partition()?;
 
-        let mut partitions = self.finished_partitions.into_values().collect::<Vec<_>>();
+        let partitions = self.finished_partitions.into_values().collect::<Vec<_>>();
 
-        // deterministic order
-        partitions.sort_by(|a, b| a.partition_id.cmp(&b.partition_id));
         self.span_recorder.ok("finished");
-        Ok(partitions)
+        Ok((partitions, self.resume_after))
     }
 }
 
@@ -671,6 +845,7 @@ impl IngesterConnection for IngesterConnectionImpl {
                 cached_table: Arc::clone(&cached_table),
                 columns: columns.clone(),
                 filters,
+                max_snapshots: self.max_snapshots,
             };
 
             let backoff_config = self.backoff_config.clone();
@@ -715,9 +890,8 @@ impl IngesterConnection for IngesterConnectionImpl {
         };
 
         let mut ingester_partitions: Vec<IngesterPartition> = self
-            .unique_ingester_addresses
-            .iter()
-            .cloned()
+            .ingesters_for_table(cached_table.id)
+            .into_iter()
             .map(move |ingester_address| measured_ingester_request(ingester_address))
             .collect::<FuturesUnordered<_>>()
             .try_collect::<Vec<_>>()
@@ -968,6 +1142,168 @@ mod tests {
         assert_eq!(p.completed_persistence_count, 5);
     }
 
+    #[tokio::test]
+    async fn test_flight_persisting_object_store_ids() {
+        let ingester_uuid = Uuid::new_v4();
+        let object_store_id = Uuid::new_v4();
+
+        let mock_flight_client = Arc::new(
+            MockFlightClient::new([(
+                "addr1",
+                Ok(MockQueryData {
+                    results: vec![Ok((
+                        DecodedPayload::None,
+                        IngesterQueryResponseMetadata {
+                            partition_identifier: Some(PartitionIdentifier::HashId(
+                                partition_hash_id(1).as_bytes().to_owned(),
+                            )),
+                            ingester_uuid: ingester_uuid.to_string(),
+                            completed_persistence_count: 5,
+                            persisting_object_store_ids: vec![object_store_id.to_string()],
+                            resume_after_sequence_number: None,
+                            buffered_data_omitted: false,
+                        },
+                    ))],
+                }),
+            )])
+            .await,
+        );
+        let ingester_conn = mock_flight_client.ingester_conn().await;
+
+        let partitions = get_partitions(&ingester_conn).await.unwrap();
+        assert_eq!(partitions.len(), 1);
+        assert_eq!(
+            partitions[0].persisting_object_store_ids(),
+            [object_store_id]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_flight_paging() {
+        let ingester_uuid = Uuid::new_v4();
+        let object_store_id = Uuid::new_v4();
+
+        let record_batch_1_1 = lp_to_record_batch("table foo=1 1");
+        let record_batch_1_2 = lp_to_record_batch("table foo=2 2");
+        let record_batch_2_1 = lp_to_record_batch("table foo=3 3");
+
+        let mock_flight_client = Arc::new(
+            MockFlightClient::new([(
+                "addr1",
+                Ok(MockQueryData {
+                    results: vec![
+                        Ok((
+                            DecodedPayload::None,
+                            IngesterQueryResponseMetadata {
+                                partition_identifier: Some(PartitionIdentifier::HashId(
+                                    partition_hash_id(1).as_bytes().to_owned(),
+                                )),
+                                ingester_uuid: ingester_uuid.to_string(),
+                                completed_persistence_count: 3,
+                                persisting_object_store_ids: vec![object_store_id.to_string()],
+                                resume_after_sequence_number: Some(5),
+                                buffered_data_omitted: false,
+                            },
+                        )),
+                        Ok((
+                            DecodedPayload::Schema(record_batch_1_1.schema()),
+                            IngesterQueryResponseMetadata::default(),
+                        )),
+                        Ok((
+                            DecodedPayload::RecordBatch(record_batch_1_1),
+                            IngesterQueryResponseMetadata::default(),
+                        )),
+                        metadata(2, ingester_uuid.to_string(), 1),
+                        Ok((
+                            DecodedPayload::Schema(record_batch_2_1.schema()),
+                            IngesterQueryResponseMetadata::default(),
+                        )),
+                        Ok((
+                            DecodedPayload::RecordBatch(record_batch_2_1),
+                            IngesterQueryResponseMetadata::default(),
+                        )),
+                    ],
+                }),
+            )])
+            .await
+            .with_continuation(Ok(MockQueryData {
+                results: vec![
+                    // A persist completed between the pages.
+                    metadata(1, ingester_uuid.to_string(), 4),
+                    Ok((
+                        DecodedPayload::Schema(record_batch_1_2.schema()),
+                        IngesterQueryResponseMetadata::default(),
+                    )),
+                    Ok((
+                        DecodedPayload::RecordBatch(record_batch_1_2),
+                        IngesterQueryResponseMetadata::default(),
+                    )),
+                ],
+            })),
+        );
+        let mut ingester_conn = mock_flight_client.ingester_conn().await;
+        ingester_conn.max_snapshots = NonZeroU32::new(1);
+
+        let partitions = get_partitions(&ingester_conn).await.unwrap();
+        assert_eq!(partitions.len(), 2);
+
+        let p1 = &partitions[0];
+        assert_eq!(p1.partition_id, partition_id(1));
+        assert_eq!(p1.chunks.len(), 2);
+        assert_eq!(p1.completed_persistence_count, 4);
+        assert_eq!(p1.persisting_object_store_ids(), [object_store_id]);
+
+        let p2 = &partitions[1];
+        assert_eq!(p2.partition_id, partition_id(2));
+        assert_eq!(p2.chunks.len(), 1);
+        assert_eq!(p2.completed_persistence_count, 1);
+
+        // Only the truncated partition is resumed.
+        let requests = mock_flight_client.requests.lock().await;
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0].max_snapshots, NonZeroU32::new(1));
+        assert!(requests[0].resume_after.is_empty());
+        assert_eq!(requests[1].max_snapshots, NonZeroU32::new(1));
+        assert_eq!(
+            requests[1].resume_after,
+            [(partition_id(1), SequenceNumber::new(5))]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_flight_err_ingester_restarted_while_paging() {
+        let mock_flight_client = Arc::new(
+            MockFlightClient::new([(
+                "addr1",
+                Ok(MockQueryData {
+                    results: vec![Ok((
+                        DecodedPayload::None,
+                        IngesterQueryResponseMetadata {
+                            partition_identifier: Some(PartitionIdentifier::HashId(
+                                partition_hash_id(1).as_bytes().to_owned(),
+                            )),
+                            ingester_uuid: Uuid::new_v4().to_string(),
+                            completed_persistence_count: 0,
+                            persisting_object_store_ids: vec![],
+                            resume_after_sequence_number: Some(5),
+                            buffered_data_omitted: false,
+                        },
+                    ))],
+                }),
+            )])
+            .await
+            .with_continuation(Ok(MockQueryData {
+                results: vec![metadata(1, Uuid::new_v4().to_string(), 0)],
+            })),
+        );
+        let mut ingester_conn = mock_flight_client.ingester_conn().await;
+        ingester_conn.max_snapshots = NonZeroU32::new(1);
+
+        let err = get_partitions(&ingester_conn).await.unwrap_err();
+        let err = err.downcast_ref::<Error>().unwrap();
+        assert_matches!(err, Error::IngesterRestartedWhilePaging { .. });
+    }
+
     #[tokio::test]
     async fn test_flight_no_partition_hash_id() {
         let ingester_uuid = Uuid::new_v4();
@@ -982,6 +1318,9 @@ mod tests {
                             partition_identifier: Some(PartitionIdentifier::CatalogId(1)),
                             ingester_uuid: ingester_uuid.to_string(),
                             completed_persistence_count: 5,
+                            persisting_object_store_ids: vec![],
+                            resume_after_sequence_number: None,
+                            buffered_data_omitted: false,
                         },
                     ))],
                 }),
@@ -1215,6 +1554,9 @@ mod tests {
                             )),
                             ingester_uuid: ingester_uuid.to_string(),
                             completed_persistence_count: 5,
+                            persisting_object_store_ids: vec![],
+                            resume_after_sequence_number: None,
+                            buffered_data_omitted: false,
                         },
                     ))],
                 }),
@@ -1383,6 +1725,41 @@ mod tests {
         get_partitions_with_span(ingester_conn, None).await
     }
 
+    #[tokio::test]
+    async fn test_table_spread() {
+        let mock_flight_client = Arc::new(
+            MockFlightClient::new(
+                ["addr1", "addr2", "addr3", "addr4", "addr5"]
+                    .map(|addr| (addr, Ok(MockQueryData { results: vec![] }))),
+            )
+            .await,
+        );
+        let ingester_conn = mock_flight_client
+            .ingester_conn()
+            .await
+            .with_table_spread(NonZeroUsize::new(2).unwrap());
+
+        let want = ingester_conn.ingesters_for_table(cached_table().id);
+        assert_eq!(want.len(), 2);
+
+        let partitions = get_partitions_with_span(&ingester_conn, None)
+            .await
+            .unwrap();
+        assert!(partitions.is_empty());
+
+        // Only the ingesters assigned to the table were queried, consuming
+        // their mocked responses.
+        let remaining = mock_flight_client
+            .responses
+            .lock()
+            .await
+            .keys()
+            .map(|addr| Arc::from(addr.as_str()))
+            .collect::<HashSet<Arc<str>>>();
+        assert_eq!(remaining.len(), 3);
+        assert!(want.iter().all(|addr| !remaining.contains(addr)));
+    }
+
     async fn get_partitions_with_span(
         ingester_conn: &IngesterConnectionImpl,
         span: Option<Span>,
@@ -1422,6 +1799,9 @@ mod tests {
                 )),
                 ingester_uuid: ingester_uuid.into(),
                 completed_persistence_count,
+                persisting_object_store_ids: vec![],
+                resume_after_sequence_number: None,
+                buffered_data_omitted: false,
             },
         ))
     }
@@ -1457,6 +1837,9 @@ mod tests {
     struct MockFlightClient {
         catalog: Arc<TestCatalog>,
         responses: Mutex<HashMap<String, Result<MockQueryData, FlightClientError>>>,
+        /// Responses to the requests resuming a truncated response, in order.
+        continuations: Mutex<Vec<Result<MockQueryData, FlightClientError>>>,
+        requests: Mutex<Vec<IngesterQueryRequest>>,
     }
 
     impl MockFlightClient {
@@ -1479,9 +1862,16 @@ mod tests {
                         .map(|(k, v)| (String::from(k), v))
                         .collect(),
                 ),
+                continuations: Default::default(),
+                requests: Default::default(),
             }
         }
 
+        fn with_continuation(mut self, response: Result<MockQueryData, FlightClientError>) -> Self {
+            self.continuations.get_mut().push(response);
+            self
+        }
+
         async fn ingester_conn(self: &Arc<Self>) -> IngesterConnectionImpl {
             let ingester_addresses: BTreeSet<_> =
                 self.responses.lock().await.keys().cloned().collect();
@@ -1514,15 +1904,23 @@ mod tests {
         async fn query(
             &self,
             ingester_address: Arc<str>,
-            _request: IngesterQueryRequest,
+            request: IngesterQueryRequest,
             _span_context: Option<SpanContext>,
         ) -> Result<Box<dyn QueryData>, FlightClientError> {
-            self.responses
-                .lock()
-                .await
-                .remove(ingester_address.as_ref())
-                .expect("Response not mocked")
-                .map(|query_data| Box::new(query_data) as _)
+            let response = if request.resume_after.is_empty() {
+                self.responses
+                    .lock()
+                    .await
+                    .remove(ingester_address.as_ref())
+                    .expect("Response not mocked")
+            } else {
+                let mut continuations = self.continuations.lock().await;
+                assert!(!continuations.is_empty(), "Continuation not mocked");
+                continuations.remove(0)
+            };
+            self.requests.lock().await.push(request);
+
+            response.map(|query_data| Box::new(query_data) as _)
         }
     }
 
@@ -1738,6 +2136,7 @@ mod tests {
             column_id_map_rev: Default::default(),
             primary_key_column_ids: Default::default(),
             partition_template: Default::default(),
+            column_retention_periods: Default::default(),
         })
     }
 }
@@ -21,10 +21,14 @@ mod database;
 mod ingester;
 mod namespace;
 mod parquet;
+mod query_denylist;
+mod query_history;
 mod query_log;
+mod query_registry;
 mod server;
 mod system_tables;
 mod table;
+mod task;
 
 /// Number of concurrent chunk creation jobs.
 ///
@@ -35,5 +39,11 @@ pub use cache::CatalogCache as QuerierCatalogCache;
 pub use database::{Error as QuerierDatabaseError, QuerierDatabase};
 pub use ingester::{create_ingester_connection_for_testing, create_ingester_connections};
 pub use namespace::QuerierNamespace;
+pub use query_denylist::{DenyRule, DenyRuleError, QueryDenylist};
+pub use query_history::QueryHistory;
 pub use query_log::QueryLogEntry;
+pub use query_registry::{QueryRegistry, RunningQuery};
 pub use server::QuerierServer;
+pub use task::{
+    RouterTaskSink, Schedule, ScheduleError, TaskExecutor, TaskScheduler, TaskSink, TaskSinkError,
+};
@@ -4,14 +4,18 @@ use crate::{
     cache::{namespace::CachedNamespace, CatalogCache},
     ingester::IngesterConnection,
     parquet::ChunkAdapter,
+    query_denylist::QueryDenylist,
+    query_history::QueryHistory,
     query_log::QueryLog,
+    query_registry::QueryRegistry,
     table::{PruneMetrics, QuerierTable, QuerierTableArgs},
 };
-use data_types::NamespaceId;
+use data_types::{NamespaceId, TableViewColumn};
 use iox_query::exec::Executor;
 use std::{collections::HashMap, sync::Arc, time::Duration};
 
 mod query_access;
+mod view;
 
 #[cfg(test)]
 mod test_util;
@@ -25,6 +29,9 @@ pub struct QuerierNamespaceArgs {
     pub exec: Arc<Executor>,
     pub ingester_connection: Option<Arc<dyn IngesterConnection>>,
     pub query_log: Arc<QueryLog>,
+    pub query_registry: Arc<QueryRegistry>,
+    pub query_denylist: Arc<QueryDenylist>,
+    pub query_history: Arc<QueryHistory>,
     pub prune_metrics: Arc<PruneMetrics>,
     pub datafusion_config: Arc<HashMap<String, String>>,
     pub include_debug_info_tables: bool,
@@ -50,6 +57,9 @@ pub struct QuerierNamespace {
     /// Tables in this namespace.
     tables: Arc<HashMap<Arc<str>, Arc<QuerierTable>>>,
 
+    /// View columns of the tables in this namespace that have a view.
+    views: Arc<HashMap<Arc<str>, Arc<[TableViewColumn]>>>,
+
     /// Executor for queries.
     exec: Arc<Executor>,
 
@@ -59,6 +69,12 @@ pub struct QuerierNamespace {
     /// Query log.
     query_log: Arc<QueryLog>,
 
+    /// Registry of running queries.
+    query_registry: Arc<QueryRegistry>,
+
+    /// History of completed queries.
+    query_history: Arc<QueryHistory>,
+
     /// DataFusion config.
     datafusion_config: Arc<HashMap<String, String>>,
 
@@ -79,6 +95,9 @@ impl QuerierNamespace {
             exec,
             ingester_connection,
             query_log,
+            query_registry,
+            query_denylist,
+            query_history,
             prune_metrics,
             datafusion_config,
             include_debug_info_tables,
@@ -98,6 +117,7 @@ impl QuerierNamespace {
                     ingester_connection: ingester_connection.clone(),
                     chunk_adapter: Arc::clone(&chunk_adapter),
                     prune_metrics: Arc::clone(&prune_metrics),
+                    query_denylist: Arc::clone(&query_denylist),
                 }));
 
                 (Arc::clone(table_name), table)
@@ -110,9 +130,12 @@ impl QuerierNamespace {
             id,
             name,
             tables: Arc::new(tables),
+            views: Arc::new(ns.views.clone()),
             exec,
             catalog_cache: Arc::clone(chunk_adapter.catalog_cache()),
             query_log,
+            query_registry,
+            query_history,
             datafusion_config,
             include_debug_info_tables,
             retention_period: ns.retention_period,
@@ -129,9 +152,11 @@ impl QuerierNamespace {
         ingester_connection: Option<Arc<dyn IngesterConnection>>,
     ) -> Self {
         let time_provider = catalog_cache.time_provider();
+        let query_history = Arc::new(QueryHistory::new(catalog_cache.catalog(), &metric_registry));
         let prune_metrics = Arc::new(PruneMetrics::new(&metric_registry));
         let chunk_adapter = Arc::new(ChunkAdapter::new(catalog_cache, Arc::clone(&prune_metrics)));
-        let query_log = Arc::new(QueryLog::new(10, time_provider));
+        let query_log = Arc::new(QueryLog::new(10, Arc::clone(&time_provider)));
+        let query_registry = Arc::new(QueryRegistry::new(time_provider));
 
         Self::new(QuerierNamespaceArgs {
             chunk_adapter,
@@ -140,6 +165,9 @@ impl QuerierNamespace {
             exec,
             ingester_connection,
             query_log,
+            query_registry,
+            query_denylist: Default::default(),
+            query_history,
             prune_metrics,
             datafusion_config: Default::default(),
             include_debug_info_tables: true,
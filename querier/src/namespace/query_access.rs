@@ -1,13 +1,17 @@
 //! This module contains implementations of [`iox_query`] interfaces for [QuerierNamespace].
 
 use crate::{
-    namespace::QuerierNamespace,
+    namespace::{
+        view::{ViewSchemaProvider, VIEW_SCHEMA},
+        QuerierNamespace,
+    },
+    query_history::QueryHistory,
     query_log::QueryLog,
     system_tables::{SystemSchemaProvider, SYSTEM_SCHEMA},
     table::QuerierTable,
 };
 use async_trait::async_trait;
-use data_types::NamespaceId;
+use data_types::{NamespaceId, TableViewColumn};
 use datafusion::{
     catalog::{schema::SchemaProvider, CatalogProvider},
     datasource::TableProvider,
@@ -15,8 +19,9 @@ use datafusion::{
     prelude::Expr,
 };
 use datafusion_util::config::DEFAULT_SCHEMA;
+use iox_catalog::interface::Catalog;
 use iox_query::{
-    exec::{ExecutorType, IOxSessionContext},
+    exec::{ExecutorType, IOxSessionContext, RowFilters},
     QueryChunk, QueryCompletedToken, QueryNamespace, QueryText,
 };
 use observability_deps::tracing::{debug, trace};
@@ -43,6 +48,8 @@ impl QueryNamespace for QuerierNamespace {
             }
         };
 
+        table.check_denylist(filters)?;
+
         let chunks = table
             .chunks(
                 filters,
@@ -71,15 +78,34 @@ impl QueryNamespace for QuerierNamespace {
         let query_log = Arc::clone(&self.query_log);
         let trace_id = span_ctx.map(|ctx| ctx.trace_id);
         let entry = query_log.push(self.id, query_type, query_text, trace_id);
-        QueryCompletedToken::new(move |success| query_log.set_completed(entry, success))
+
+        // The query is registered as running until the token is dropped.
+        let query_registry = Arc::clone(&self.query_registry);
+        let running = query_registry.register(Arc::clone(&self.name), Arc::clone(&entry));
+        let cancellation = running.cancellation().clone();
+
+        // The completed query is recorded in the query history, if enabled.
+        let query_history = Arc::clone(&self.query_history);
+
+        QueryCompletedToken::new_with_usage(move |success, usage| {
+            query_registry.deregister(running.id());
+            query_log.set_completed(Arc::clone(&entry), success);
+            query_history.record(&entry, usage);
+        })
+        .with_cancellation(cancellation)
     }
 
-    fn new_query_context(&self, span_ctx: Option<SpanContext>) -> IOxSessionContext {
+    fn new_filtered_query_context(
+        &self,
+        span_ctx: Option<SpanContext>,
+        row_filters: RowFilters,
+    ) -> IOxSessionContext {
         let mut cfg = self
             .exec
             .new_execution_config(ExecutorType::Query)
             .with_default_catalog(Arc::new(QuerierCatalogProvider::from_namespace(self)) as _)
-            .with_span_context(span_ctx);
+            .with_span_context(span_ctx)
+            .with_row_filters(row_filters);
 
         for (k, v) in self.datafusion_config.as_ref() {
             cfg = cfg.with_config_option(k, v);
@@ -96,11 +122,20 @@ pub struct QuerierCatalogProvider {
     /// A snapshot of all tables.
     tables: Arc<HashMap<Arc<str>, Arc<QuerierTable>>>,
 
+    /// A snapshot of all table views.
+    views: Arc<HashMap<Arc<str>, Arc<[TableViewColumn]>>>,
+
     /// Query log.
     query_log: Arc<QueryLog>,
 
+    /// History of completed queries.
+    query_history: Arc<QueryHistory>,
+
     /// Include debug info tables.
     include_debug_info_tables: bool,
+
+    /// Catalog, read by the debug info tables.
+    catalog: Arc<dyn Catalog>,
 }
 
 impl QuerierCatalogProvider {
@@ -108,8 +143,11 @@ impl QuerierCatalogProvider {
         Self {
             namespace_id: namespace.id,
             tables: Arc::clone(&namespace.tables),
+            views: Arc::clone(&namespace.views),
             query_log: Arc::clone(&namespace.query_log),
+            query_history: Arc::clone(&namespace.query_history),
             include_debug_info_tables: namespace.include_debug_info_tables,
+            catalog: namespace.catalog_cache.catalog(),
         }
     }
 }
@@ -120,7 +158,11 @@ impl CatalogProvider for QuerierCatalogProvider {
     }
 
     fn schema_names(&self) -> Vec<String> {
-        vec![DEFAULT_SCHEMA.to_string(), SYSTEM_SCHEMA.to_string()]
+        let mut names = vec![DEFAULT_SCHEMA.to_string(), SYSTEM_SCHEMA.to_string()];
+        if !self.views.is_empty() {
+            names.push(VIEW_SCHEMA.to_string());
+        }
+        names
     }
 
     fn schema(&self, name: &str) -> Option<Arc<dyn SchemaProvider>> {
@@ -130,8 +172,16 @@ impl CatalogProvider for QuerierCatalogProvider {
             })),
             SYSTEM_SCHEMA => Some(Arc::new(SystemSchemaProvider::new(
                 Arc::clone(&self.query_log),
+                Arc::clone(&self.query_history),
                 self.namespace_id,
+                Arc::clone(&self.tables),
+                Arc::clone(&self.views),
                 self.include_debug_info_tables,
+                Arc::clone(&self.catalog),
+            ))),
+            VIEW_SCHEMA => Some(Arc::new(ViewSchemaProvider::new(
+                Arc::clone(&self.tables),
+                Arc::clone(&self.views),
             ))),
             _ => None,
         }
@@ -171,7 +221,7 @@ mod tests {
     use crate::namespace::test_util::{clear_parquet_cache, querier_namespace};
     use arrow::record_batch::RecordBatch;
     use arrow_util::test_util::{batches_to_sorted_lines, Normalizer};
-    use data_types::ColumnType;
+    use data_types::{ColumnType, TableViewColumn};
     use datafusion::common::DataFusionError;
     use iox_query::frontend::sql::SqlQueryPlanner;
     use iox_tests::{TestCatalog, TestParquetFileBuilder};
@@ -529,6 +579,189 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_query_view() {
+        test_helpers::maybe_start_logging();
+
+        let catalog = TestCatalog::new();
+        let ns = catalog.create_namespace_with_retention("ns", None).await;
+        let table = ns.create_table("weather").await;
+        table.create_column("city", ColumnType::Tag).await;
+        table.create_column("temp", ColumnType::F64).await;
+        table.create_column("time", ColumnType::Time).await;
+
+        let partition = table.create_partition("a").await;
+        let builder = TestParquetFileBuilder::default()
+            .with_line_protocol("weather,city=x temp=10 11\nweather,city=y temp=20 22")
+            .with_min_time(11)
+            .with_max_time(22);
+        partition.create_parquet_file(builder).await;
+
+        {
+            let mut repos = catalog.catalog.repositories().await;
+            for (name, expression, unit) in [
+                ("location", "city", None),
+                ("temp_c", r#""temp""#, Some("celsius")),
+                ("temp_f", r#""temp" * 1.8 + 32"#, Some("fahrenheit")),
+            ] {
+                repos
+                    .table_views()
+                    .upsert(&TableViewColumn {
+                        table_id: table.table.id,
+                        name: name.to_string(),
+                        expression: expression.to_string(),
+                        unit: unit.map(ToString::to_string),
+                    })
+                    .await
+                    .unwrap();
+            }
+        }
+
+        let querier_namespace = Arc::new(querier_namespace(&ns).await);
+
+        insta::assert_yaml_snapshot!(
+            format_query(
+                &querier_namespace,
+                "SELECT * FROM view.weather WHERE temp_f > 60",
+            ).await,
+            @r###"
+        ---
+        - +----------+--------+--------+--------------------------------+
+        - "| location | temp_c | temp_f | time                           |"
+        - +----------+--------+--------+--------------------------------+
+        - "| y        | 20.0   | 68.0   | 1970-01-01T00:00:00.000000022Z |"
+        - +----------+--------+--------+--------------------------------+
+        "###
+        );
+
+        insta::assert_yaml_snapshot!(
+            format_query(
+                &querier_namespace,
+                "SELECT table_name, column_name, unit FROM system.view_columns",
+            ).await,
+            @r###"
+        ---
+        - +------------+-------------+------------+
+        - "| table_name | column_name | unit       |"
+        - +------------+-------------+------------+
+        - "| weather    | location    |            |"
+        - "| weather    | temp_c      | celsius    |"
+        - "| weather    | temp_f      | fahrenheit |"
+        - +------------+-------------+------------+
+        "###
+        );
+
+        // The underlying table is unchanged.
+        insta::assert_yaml_snapshot!(
+            format_query(&querier_namespace, "SELECT * FROM weather").await,
+            @r###"
+        ---
+        - +------+------+--------------------------------+
+        - "| city | temp | time                           |"
+        - +------+------+--------------------------------+
+        - "| x    | 10.0 | 1970-01-01T00:00:00.000000011Z |"
+        - "| y    | 20.0 | 1970-01-01T00:00:00.000000022Z |"
+        - +------+------+--------------------------------+
+        "###
+        );
+    }
+
+    #[tokio::test]
+    async fn test_query_row_filters() {
+        test_helpers::maybe_start_logging();
+
+        let catalog = TestCatalog::new();
+        let ns = catalog.create_namespace_with_retention("ns", None).await;
+        let table_cpu = ns.create_table("cpu").await;
+        table_cpu.create_column("tenant_id", ColumnType::Tag).await;
+        table_cpu.create_column("host", ColumnType::Tag).await;
+        table_cpu.create_column("load", ColumnType::F64).await;
+        table_cpu.create_column("time", ColumnType::Time).await;
+        let table_mem = ns.create_table("mem").await;
+        table_mem.create_column("host", ColumnType::Tag).await;
+        table_mem.create_column("perc", ColumnType::F64).await;
+        table_mem.create_column("time", ColumnType::Time).await;
+
+        let lp = [
+            "cpu,tenant_id=x,host=a load=1 11",
+            "cpu,tenant_id=y,host=a load=2 22",
+            "cpu,host=b load=3 33",
+        ]
+        .join("\n");
+        let builder = TestParquetFileBuilder::default()
+            .with_line_protocol(&lp)
+            .with_min_time(11)
+            .with_max_time(33);
+        table_cpu
+            .create_partition("a")
+            .await
+            .create_parquet_file(builder)
+            .await;
+
+        let builder = TestParquetFileBuilder::default()
+            .with_line_protocol("mem,host=a perc=50 11")
+            .with_min_time(11)
+            .with_max_time(11);
+        table_mem
+            .create_partition("a")
+            .await
+            .create_parquet_file(builder)
+            .await;
+
+        let querier_namespace = Arc::new(querier_namespace(&ns).await);
+        let row_filters = RowFilters::new([("tenant_id".to_string(), "x".to_string())]);
+
+        let query = |sql: &'static str| {
+            let querier_namespace = Arc::clone(&querier_namespace);
+            let row_filters = row_filters.clone();
+            async move {
+                let ctx = querier_namespace.new_filtered_query_context(None, row_filters);
+                let physical_plan = SqlQueryPlanner::default()
+                    .query(sql, &ctx)
+                    .await
+                    .expect("build plan");
+                batches_to_sorted_lines(&ctx.collect(physical_plan).await.expect("run plan"))
+            }
+        };
+
+        // Only the rows of the tenant are visible, including to aggregates.
+        insta::assert_yaml_snapshot!(
+            query("SELECT * FROM cpu").await,
+            @r###"
+        ---
+        - +------+------+-----------+--------------------------------+
+        - "| host | load | tenant_id | time                           |"
+        - +------+------+-----------+--------------------------------+
+        - "| a    | 1.0  | x         | 1970-01-01T00:00:00.000000011Z |"
+        - +------+------+-----------+--------------------------------+
+        "###
+        );
+        insta::assert_yaml_snapshot!(
+            query("SELECT count(*) FROM cpu WHERE tenant_id = 'y'").await,
+            @r###"
+        ---
+        - +----------+
+        - "| COUNT(*) |"
+        - +----------+
+        - "| 0        |"
+        - +----------+
+        "###
+        );
+
+        // No rows are visible in tables without the filtered tag.
+        insta::assert_yaml_snapshot!(
+            query("SELECT count(*) FROM mem").await,
+            @r###"
+        ---
+        - +----------+
+        - "| COUNT(*) |"
+        - +----------+
+        - "| 0        |"
+        - +----------+
+        "###
+        );
+    }
+
     async fn format_query(querier_namespace: &Arc<QuerierNamespace>, sql: &str) -> Vec<String> {
         format_query_with_span_ctx(querier_namespace, sql, None).await
     }
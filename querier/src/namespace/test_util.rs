@@ -22,7 +22,23 @@ pub async fn querier_namespace(ns: &Arc<TestNamespace>) -> QuerierNamespace {
         .list_by_namespace_id(ns.namespace.id)
         .await
         .unwrap();
-    let cached_ns = Arc::new(CachedNamespace::new(ns.namespace.clone(), tables, columns));
+    let column_retention = repos
+        .column_retention()
+        .list_by_namespace_id(ns.namespace.id)
+        .await
+        .unwrap();
+    let view_columns = repos
+        .table_views()
+        .list_by_namespace_id(ns.namespace.id)
+        .await
+        .unwrap();
+    let cached_ns = Arc::new(CachedNamespace::new(
+        ns.namespace.clone(),
+        tables,
+        columns,
+        column_retention,
+        view_columns,
+    ));
 
     let catalog_cache = Arc::new(QuerierCatalogCache::new_testing(
         ns.catalog.catalog(),
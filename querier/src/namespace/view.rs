@@ -0,0 +1,205 @@
+//! Resolution of the table views stored in the catalog.
+//!
+//! The view of a table is defined by a set of [`TableViewColumn`]s, each of
+//! which exposes a SQL expression over the columns of the table under a new
+//! name. Views are queryable as `view.<table name>`, and expose exactly the
+//! view columns, plus the `time` column of the table unless a view column of
+//! the same name is defined.
+//!
+//! A view is planned when it is first referenced by a query, and is inlined
+//! into the query plan so that predicates are pushed down to the underlying
+//! table.
+
+use std::{any::Any, collections::HashMap, fmt::Write, sync::Arc};
+
+use async_trait::async_trait;
+use data_types::TableViewColumn;
+use datafusion::{
+    catalog::schema::SchemaProvider,
+    common::TableReference,
+    datasource::{view::ViewTable, TableProvider},
+    error::DataFusionError,
+    prelude::SessionContext,
+    sql::sqlparser::{ast::Ident, dialect::GenericDialect, parser::Parser, tokenizer::Token},
+};
+use observability_deps::tracing::warn;
+use schema::TIME_COLUMN_NAME;
+
+use crate::table::QuerierTable;
+
+/// The name of the schema containing the table views.
+pub const VIEW_SCHEMA: &str = "view";
+
+/// Provider for the table views in [`VIEW_SCHEMA`].
+pub(crate) struct ViewSchemaProvider {
+    /// A snapshot of all tables.
+    tables: Arc<HashMap<Arc<str>, Arc<QuerierTable>>>,
+
+    /// The view columns of the tables that have a view.
+    views: Arc<HashMap<Arc<str>, Arc<[TableViewColumn]>>>,
+}
+
+impl ViewSchemaProvider {
+    pub(crate) fn new(
+        tables: Arc<HashMap<Arc<str>, Arc<QuerierTable>>>,
+        views: Arc<HashMap<Arc<str>, Arc<[TableViewColumn]>>>,
+    ) -> Self {
+        Self { tables, views }
+    }
+}
+
+#[async_trait]
+impl SchemaProvider for ViewSchemaProvider {
+    fn as_any(&self) -> &dyn Any {
+        self as &dyn Any
+    }
+
+    fn table_names(&self) -> Vec<String> {
+        let mut names: Vec<_> = self
+            .views
+            .keys()
+            .filter(|name| self.tables.contains_key(*name))
+            .map(|s| s.to_string())
+            .collect();
+        names.sort();
+        names
+    }
+
+    async fn table(&self, name: &str) -> Option<Arc<dyn TableProvider>> {
+        let table = self.tables.get(name)?;
+        let columns = self.views.get(name)?;
+
+        match plan_view(name, Arc::clone(table), columns).await {
+            Ok(view) => Some(Arc::new(view)),
+            Err(e) => {
+                warn!(table_name=%name, error=%e, "invalid table view");
+                None
+            }
+        }
+    }
+
+    fn table_exist(&self, name: &str) -> bool {
+        self.views.contains_key(name) && self.tables.contains_key(name)
+    }
+}
+
+/// Plan the view of `table_name` defined by `columns`.
+async fn plan_view(
+    table_name: &str,
+    table: Arc<QuerierTable>,
+    columns: &[TableViewColumn],
+) -> Result<ViewTable, DataFusionError> {
+    let sql = view_sql(table_name, columns)?;
+
+    // The view is planned against a context containing only the table it is
+    // defined on, so expressions cannot reference any other table.
+    let ctx = SessionContext::new();
+    ctx.register_table(TableReference::bare(table_name), table as _)?;
+    let plan = ctx.state().create_logical_plan(&sql).await?;
+
+    ViewTable::try_new(plan, Some(sql))
+}
+
+/// Build the `SELECT` statement defining the view of `table_name`.
+///
+/// Each expression is parsed on its own and re-rendered, rejecting anything
+/// that is not a single expression.
+fn view_sql(table_name: &str, columns: &[TableViewColumn]) -> Result<String, DataFusionError> {
+    let dialect = GenericDialect {};
+    let mut sql = String::from("SELECT ");
+
+    for (i, column) in columns.iter().enumerate() {
+        let mut parser = Parser::new(&dialect)
+            .try_with_sql(&column.expression)
+            .map_err(|e| invalid_expression(column, e))?;
+        let expr = parser
+            .parse_expr()
+            .map_err(|e| invalid_expression(column, e))?;
+        let next = parser.peek_token().token;
+        if next != Token::EOF {
+            return Err(invalid_expression(
+                column,
+                format!("unexpected token {next}"),
+            ));
+        }
+
+        if i > 0 {
+            sql.push_str(", ");
+        }
+        write!(sql, "{expr} AS {}", quote(&column.name)).expect("write to string");
+    }
+
+    if !columns.iter().any(|c| c.name == TIME_COLUMN_NAME) {
+        if !columns.is_empty() {
+            sql.push_str(", ");
+        }
+        sql.push_str(&quote(TIME_COLUMN_NAME));
+    }
+
+    write!(sql, " FROM {}", quote(table_name)).expect("write to string");
+    Ok(sql)
+}
+
+fn quote(ident: &str) -> String {
+    Ident::with_quote('"', ident).to_string()
+}
+
+fn invalid_expression(column: &TableViewColumn, e: impl std::fmt::Display) -> DataFusionError {
+    DataFusionError::Plan(format!(
+        "invalid expression for view column {}: {e}",
+        column.name
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use data_types::TableId;
+
+    use super::*;
+
+    fn column(name: &str, expression: &str) -> TableViewColumn {
+        TableViewColumn {
+            table_id: TableId::new(1),
+            name: name.to_string(),
+            expression: expression.to_string(),
+            unit: None,
+        }
+    }
+
+    #[test]
+    fn test_view_sql() {
+        let sql = view_sql(
+            "cpu",
+            &[
+                column("temp_c", r#""temp""#),
+                column("temp_f", r#""temp" * 1.8 + 32"#),
+                column("load", "CAST(load AS DOUBLE)"),
+            ],
+        )
+        .unwrap();
+        assert_eq!(
+            sql,
+            r#"SELECT "temp" AS "temp_c", "temp" * 1.8 + 32 AS "temp_f", CAST(load AS DOUBLE) AS "load", "time" FROM "cpu""#
+        );
+
+        // A view column named "time" replaces the time column.
+        let sql = view_sql("cpu", &[column("time", "time")]).unwrap();
+        assert_eq!(sql, r#"SELECT time AS "time" FROM "cpu""#);
+
+        // Identifiers are quoted.
+        let sql = view_sql(r#"my "table""#, &[column(r#"a"b"#, "1")]).unwrap();
+        assert_eq!(sql, r#"SELECT 1 AS "a""b", "time" FROM "my ""table""""#);
+    }
+
+    #[test]
+    fn test_view_sql_rejects_non_expressions() {
+        for expression in ["", "a FROM other", "a; DROP TABLE cpu", "a AS b"] {
+            let err = view_sql("cpu", &[column("x", expression)]).unwrap_err();
+            assert!(
+                err.to_string()
+                    .contains("invalid expression for view column x"),
+                "{expression}: {err}"
+            );
+        }
+    }
+}
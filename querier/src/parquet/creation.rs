@@ -1,11 +1,17 @@
 use std::{collections::HashMap, sync::Arc};
 
-use data_types::{ChunkId, ChunkOrder, ColumnId, ParquetFile, TimestampMinMax};
-use datafusion::{physical_plan::Statistics, prelude::Expr};
+use data_types::{
+    ChunkId, ChunkOrder, ColumnId, ParquetFile, ParquetFileColumnStatistics, TimestampMinMax,
+};
+use datafusion::{physical_plan::Statistics, prelude::Expr, scalar::ScalarValue};
 use futures::StreamExt;
 use hashbrown::HashSet;
 use iox_catalog::interface::Catalog;
-use iox_query::{chunk_statistics::create_chunk_statistics, pruning::prune_summaries};
+use iox_query::{
+    chunk_statistics::{create_chunk_statistics, ColumnRange, ColumnRanges},
+    pruning::prune_summaries,
+};
+use iox_time::Time;
 use parquet_file::chunk::ParquetChunk;
 use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
 use schema::{sort::SortKeyBuilder, Schema};
@@ -53,7 +59,13 @@ impl ChunkAdapter {
     pub(crate) async fn new_chunks(
         &self,
         cached_table: Arc<CachedTable>,
-        files: impl IntoIterator<Item = (Arc<ParquetFile>, Arc<CachedPartition>)> + Send,
+        files: impl IntoIterator<
+                Item = (
+                    Arc<ParquetFile>,
+                    Arc<CachedPartition>,
+                    Arc<[ParquetFileColumnStatistics]>,
+                ),
+            > + Send,
         filters: &[Expr],
         span: Option<Span>,
     ) -> Vec<QuerierParquetChunk> {
@@ -63,9 +75,10 @@ impl ChunkAdapter {
         let files = {
             let _span_recorder = span_recorder.child("prepare files");
 
+            let now = self.catalog_cache.time_provider().now();
             files
                 .into_iter()
-                .map(|(f, p)| PreparedParquetFile::new(f, &cached_table, p))
+                .map(|(f, p, s)| PreparedParquetFile::new(f, &cached_table, p, &s, now))
                 .collect::<Vec<_>>()
         };
 
@@ -272,6 +285,9 @@ struct PreparedParquetFile {
 
     /// The columns in this file as ordered in the schema.
     col_list: Box<[ColumnId]>,
+
+    /// Known min/max values of the columns in this file.
+    column_ranges: ColumnRanges,
 }
 
 impl PreparedParquetFile {
@@ -279,26 +295,79 @@ impl PreparedParquetFile {
         file: Arc<ParquetFile>,
         cached_table: &CachedTable,
         cached_partition: Arc<CachedPartition>,
+        column_statistics: &[ParquetFileColumnStatistics],
+        now: Time,
     ) -> Self {
         // be optimistic and assume the the cached table already knows about all columns. Otherwise
         // `.filter(...).collect()` is too pessimistic and resizes the HashSet too often.
+        //
+        // Columns past their retention period are not read, as if the compactor had already
+        // dropped them from the file.
         let mut col_set = HashSet::<ColumnId>::with_capacity(file.column_set.len());
         col_set.extend(
             file.column_set
                 .iter()
                 .filter(|id| cached_table.column_id_map.contains_key(*id))
+                .filter(|id| !cached_table.is_column_expired(**id, file.max_time, now))
                 .copied(),
         );
 
         let mut col_list = col_set.iter().copied().collect::<Box<[ColumnId]>>();
         col_list.sort();
 
+        let column_ranges = column_ranges(
+            &cached_partition.column_ranges,
+            cached_table,
+            column_statistics,
+        );
+
         Self {
             file,
             cached_partition,
             col_set,
             col_list,
+            column_ranges,
+        }
+    }
+}
+
+/// Extend the column ranges of the partition with the ranges recorded in the
+/// catalog for the columns of a file.
+///
+/// The partition ranges are derived from the partition key and are exact, so
+/// they take precedence. Columns of which only one bound is known are skipped.
+fn column_ranges(
+    partition_ranges: &ColumnRanges,
+    cached_table: &CachedTable,
+    column_statistics: &[ParquetFileColumnStatistics],
+) -> ColumnRanges {
+    let mut ranges = None;
+
+    for stats in column_statistics {
+        let (Some(min), Some(max)) = (&stats.min_value, &stats.max_value) else {
+            continue;
+        };
+        let Some(name) = cached_table.column_id_map.get(&stats.column_id) else {
+            continue;
+        };
+        if partition_ranges.contains_key(name) {
+            continue;
         }
+
+        ranges
+            .get_or_insert_with(|| partition_ranges.as_ref().clone())
+            .insert(
+                Arc::clone(name),
+                ColumnRange {
+                    min_value: Arc::new(ScalarValue::from(min.as_str())),
+                    max_value: Arc::new(ScalarValue::from(max.as_str())),
+                },
+            );
+    }
+
+    match ranges {
+        Some(ranges) => Arc::new(ranges),
+        None => Arc::clone(partition_ranges),
     }
 }
 
@@ -329,6 +398,7 @@ impl PreparedParquetFileWithStats {
             cached_partition,
             col_set,
             col_list,
+            column_ranges,
         } = file;
 
         // col_list was used to look up schemas, not needed for this transform
@@ -342,7 +412,7 @@ impl PreparedParquetFileWithStats {
             Some(file.row_count as usize),
             &schema,
             Some(ts_min_max),
-            Some(&cached_partition.column_ranges),
+            Some(&column_ranges),
         ));
 
         Self {
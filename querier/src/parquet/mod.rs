@@ -211,7 +211,8 @@ pub mod tests {
                 .list_by_namespace_id(ns.namespace.id)
                 .await
                 .unwrap();
-            let cached_namespace = CachedNamespace::new(ns.namespace.clone(), tables, columns);
+            let cached_namespace =
+                CachedNamespace::new(ns.namespace.clone(), tables, columns, vec![], vec![]);
             let cached_table =
                 Arc::clone(cached_namespace.tables.get("table").expect("table exists"));
 
@@ -243,7 +244,11 @@ pub mod tests {
             self.adapter
                 .new_chunks(
                     Arc::clone(&self.cached_table),
-                    [(Arc::clone(&self.parquet_file), cached_partition)],
+                    [(
+                        Arc::clone(&self.parquet_file),
+                        cached_partition,
+                        Arc::from([]),
+                    )],
                     &[],
                     None,
                 )
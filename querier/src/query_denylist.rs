@@ -0,0 +1,197 @@
+//! Rules rejecting queries matching configured patterns, such as reads of a
+//! table or reads without a time bound.
+
+use datafusion::{error::DataFusionError, prelude::Expr};
+use observability_deps::tracing::info;
+use parking_lot::RwLock;
+use regex::Regex;
+use schema::TIME_COLUMN_NAME;
+use snafu::{ResultExt, Snafu};
+use std::{fmt::Display, str::FromStr, sync::Arc};
+
+/// Errors parsing a [`DenyRule`].
+#[derive(Debug, Snafu)]
+#[allow(missing_docs)]
+pub enum DenyRuleError {
+    #[snafu(display("invalid regex in deny rule '{rule}': {source}"))]
+    InvalidRegex { rule: String, source: regex::Error },
+
+    #[snafu(display(
+        "invalid deny rule '{rule}', expected 'table=<regex>', 'predicate=<regex>', \
+         'no_time_bound' or 'no_time_bound=<regex>'"
+    ))]
+    UnknownRule { rule: String },
+}
+
+/// A rule rejecting reads of a table by queries matching it.
+#[derive(Debug, Clone)]
+pub enum DenyRule {
+    /// Reject reads of tables with a name matching the regex.
+    Table(Regex),
+
+    /// Reject reads with any filter matching the regex, in its displayed form
+    /// (such as `host LIKE Utf8("%a")`).
+    Predicate(Regex),
+
+    /// Reject reads without a filter on the time column, of any table or of
+    /// the tables with a name matching the regex.
+    NoTimeBound(Option<Regex>),
+}
+
+impl DenyRule {
+    /// Returns true if a read of `table_name` with `filters` matches this
+    /// rule.
+    fn matches(&self, table_name: &str, filters: &[Expr]) -> bool {
+        match self {
+            Self::Table(r) => r.is_match(table_name),
+            Self::Predicate(r) => filters.iter().any(|f| r.is_match(&f.to_string())),
+            Self::NoTimeBound(r) => {
+                r.as_ref().map_or(true, |r| r.is_match(table_name))
+                    && !filters.iter().any(|f| {
+                        f.to_columns()
+                            .map(|cols| cols.iter().any(|c| c.name == TIME_COLUMN_NAME))
+                            .unwrap_or_default()
+                    })
+            }
+        }
+    }
+}
+
+impl FromStr for DenyRule {
+    type Err = DenyRuleError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let rule = s.trim();
+        let regex = |r: &str| Regex::new(r).context(InvalidRegexSnafu { rule });
+
+        match rule.split_once('=') {
+            Some(("table", r)) => Ok(Self::Table(regex(r)?)),
+            Some(("predicate", r)) => Ok(Self::Predicate(regex(r)?)),
+            Some(("no_time_bound", r)) => Ok(Self::NoTimeBound(Some(regex(r)?))),
+            None if rule == "no_time_bound" => Ok(Self::NoTimeBound(None)),
+            _ => UnknownRuleSnafu { rule }.fail(),
+        }
+    }
+}
+
+impl Display for DenyRule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Table(r) => write!(f, "table={r}"),
+            Self::Predicate(r) => write!(f, "predicate={r}"),
+            Self::NoTimeBound(Some(r)) => write!(f, "no_time_bound={r}"),
+            Self::NoTimeBound(None) => write!(f, "no_time_bound"),
+        }
+    }
+}
+
+/// A set of [`DenyRule`] enforced when a query reads a table, which may be
+/// replaced at runtime.
+#[derive(Debug, Default)]
+pub struct QueryDenylist {
+    rules: RwLock<Arc<[DenyRule]>>,
+}
+
+impl QueryDenylist {
+    /// Replace the rules of this denylist.
+    pub fn set(&self, rules: Vec<DenyRule>) {
+        *self.rules.write() = rules.into();
+    }
+
+    /// Return the rules of this denylist.
+    pub fn rules(&self) -> Arc<[DenyRule]> {
+        Arc::clone(&self.rules.read())
+    }
+
+    /// Return an error if reading `table_name` with `filters` matches any
+    /// rule.
+    pub(crate) fn check(&self, table_name: &str, filters: &[Expr]) -> Result<(), DataFusionError> {
+        let rules = self.rules();
+        match rules.iter().find(|r| r.matches(table_name, filters)) {
+            Some(rule) => {
+                info!(%table_name, %rule, ?filters, "query rejected by denylist");
+                Err(DataFusionError::Plan(format!(
+                    "query reading table '{table_name}' rejected by denylist rule '{rule}'"
+                )))
+            }
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_matches::assert_matches;
+    use datafusion::prelude::{col, lit};
+
+    fn denylist(rules: &[&str]) -> QueryDenylist {
+        let list = QueryDenylist::default();
+        list.set(rules.iter().map(|r| r.parse().unwrap()).collect());
+        list
+    }
+
+    #[test]
+    fn test_parse_display() {
+        for rule in [
+            "table=^cpu$",
+            "predicate=LIKE",
+            "no_time_bound",
+            "no_time_bound=^big_",
+        ] {
+            assert_eq!(rule.parse::<DenyRule>().unwrap().to_string(), rule);
+        }
+
+        assert_matches!(
+            "bananas".parse::<DenyRule>(),
+            Err(DenyRuleError::UnknownRule { .. })
+        );
+        assert_matches!(
+            "table=(".parse::<DenyRule>(),
+            Err(DenyRuleError::InvalidRegex { .. })
+        );
+    }
+
+    #[test]
+    fn test_empty() {
+        denylist(&[]).check("cpu", &[]).unwrap();
+    }
+
+    #[test]
+    fn test_table() {
+        let list = denylist(&["table=^cpu$"]);
+        assert_matches!(list.check("cpu", &[]), Err(DataFusionError::Plan(_)));
+        list.check("cpu2", &[]).unwrap();
+    }
+
+    #[test]
+    fn test_predicate() {
+        let list = denylist(&["predicate=host LIKE"]);
+        let like = col("host").like(lit("%a"));
+        let eq = col("host").eq(lit("a"));
+        assert_matches!(list.check("cpu", &[like]), Err(DataFusionError::Plan(_)));
+        list.check("cpu", &[eq]).unwrap();
+    }
+
+    #[test]
+    fn test_no_time_bound() {
+        let time = col(TIME_COLUMN_NAME).gt(lit(42));
+        let host = col("host").eq(lit("a"));
+
+        let list = denylist(&["no_time_bound"]);
+        assert_matches!(list.check("cpu", &[]), Err(DataFusionError::Plan(_)));
+        assert_matches!(
+            list.check("cpu", &[host.clone()]),
+            Err(DataFusionError::Plan(_))
+        );
+        list.check("cpu", &[host.clone(), time.clone()]).unwrap();
+
+        let list = denylist(&["no_time_bound=^big_"]);
+        list.check("cpu", &[host.clone()]).unwrap();
+        assert_matches!(
+            list.check("big_cpu", &[host.clone()]),
+            Err(DataFusionError::Plan(_))
+        );
+        list.check("big_cpu", &[time]).unwrap();
+    }
+}
@@ -0,0 +1,253 @@
+//! Recording of completed queries, and the resources used to execute them,
+//! into the query history of the catalog.
+
+use crate::query_log::QueryLogEntry;
+use data_types::{NamespaceId, QueryHistoryEntry};
+use iox_catalog::interface::{Catalog, Result as CatalogResult};
+use iox_query::usage::QueryUsage;
+use metric::U64Counter;
+use observability_deps::tracing::{debug, info, warn};
+use std::sync::{Arc, OnceLock};
+use tokio::sync::mpsc::{self, error::TrySendError};
+
+/// The maximum number of entries waiting to be written to the catalog.
+const QUEUE_SIZE: usize = 1_000;
+
+/// The maximum number of queued entries written to the catalog together.
+const MAX_BATCH_SIZE: usize = 100;
+
+/// Records the completed queries of all namespaces into the query history of
+/// the catalog.
+///
+/// Recording is disabled until [`QueryHistory::enable()`] is called. Entries
+/// are queued and written by a background task in batches, so that recording
+/// never delays the response to a query. An entry is dropped if the queue is
+/// full, or if it cannot be written.
+///
+/// The entries older than the retention period are deleted by the garbage
+/// collector.
+#[derive(Debug)]
+pub struct QueryHistory {
+    catalog: Arc<dyn Catalog>,
+
+    /// The queue of entries to write, set once recording is enabled.
+    queue: OnceLock<mpsc::Sender<QueryHistoryEntry>>,
+
+    /// The number of entries dropped because the queue was full.
+    dropped: U64Counter,
+}
+
+impl QueryHistory {
+    /// Create a [`QueryHistory`] recording into `catalog`, initially
+    /// disabled.
+    pub fn new(catalog: Arc<dyn Catalog>, metrics: &metric::Registry) -> Self {
+        let dropped = metrics
+            .register_metric::<U64Counter>(
+                "query_history_dropped_entries",
+                "number of query history entries dropped because the write queue was full",
+            )
+            .recorder(&[]);
+
+        Self {
+            catalog,
+            queue: OnceLock::new(),
+            dropped,
+        }
+    }
+
+    /// Enable recording, starting the task writing the recorded entries.
+    ///
+    /// The task exits once this [`QueryHistory`] is dropped.
+    pub fn enable(&self) {
+        self.queue.get_or_init(|| {
+            info!("enabling query history");
+            let (tx, rx) = mpsc::channel(QUEUE_SIZE);
+            tokio::spawn(write_entries(Arc::clone(&self.catalog), rx));
+            tx
+        });
+    }
+
+    /// Returns true if completed queries are recorded.
+    pub fn is_enabled(&self) -> bool {
+        self.queue.get().is_some()
+    }
+
+    /// Record the completed query `entry`, executed with `usage`.
+    pub(crate) fn record(&self, entry: &QueryLogEntry, usage: Option<QueryUsage>) {
+        let Some(queue) = self.queue.get() else {
+            return;
+        };
+
+        let as_i64 = |v: usize| i64::try_from(v).unwrap_or(i64::MAX);
+        let history_entry = QueryHistoryEntry {
+            namespace_id: entry.namespace_id,
+            issue_time: entry.issue_time.into(),
+            query_type: entry.query_type.to_string(),
+            query_text_hash: QueryHistoryEntry::hash_query_text(&entry.query_text.to_string()),
+            duration_ns: entry
+                .query_completed_duration()
+                .map(|d| d.as_nanos() as i64)
+                .unwrap_or_default(),
+            success: entry.success(),
+            partitions_scanned: usage.map(|u| as_i64(u.partitions_scanned)),
+            bytes_scanned: usage.map(|u| as_i64(u.bytes_scanned)),
+            rows_returned: usage.map(|u| as_i64(u.rows_returned)),
+            peak_memory_bytes: usage.map(|u| as_i64(u.peak_memory_bytes)),
+        };
+
+        match queue.try_send(history_entry) {
+            Ok(()) => {}
+            Err(TrySendError::Full(entry)) => {
+                self.dropped.inc(1);
+                debug!(
+                    namespace_id = %entry.namespace_id,
+                    "query history queue full, dropping entry"
+                );
+            }
+            Err(TrySendError::Closed(_)) => {
+                warn!("query history writer stopped, dropping entry");
+            }
+        }
+    }
+
+    /// List the `limit` most recently issued queries of `namespace_id`, most
+    /// recent first.
+    pub(crate) async fn list(
+        &self,
+        namespace_id: NamespaceId,
+        limit: i64,
+    ) -> CatalogResult<Vec<QueryHistoryEntry>> {
+        self.catalog
+            .repositories()
+            .await
+            .query_history()
+            .list_by_namespace_id(namespace_id, limit)
+            .await
+    }
+}
+
+/// Write the entries received from `queue` to `catalog`, up to
+/// [`MAX_BATCH_SIZE`] entries at a time, until the queue is closed.
+async fn write_entries(catalog: Arc<dyn Catalog>, mut queue: mpsc::Receiver<QueryHistoryEntry>) {
+    let mut batch = Vec::with_capacity(MAX_BATCH_SIZE);
+    while let Some(entry) = queue.recv().await {
+        batch.push(entry);
+        while batch.len() < MAX_BATCH_SIZE {
+            match queue.try_recv() {
+                Ok(entry) => batch.push(entry),
+                Err(_) => break,
+            }
+        }
+
+        let mut repos = catalog.repositories().await;
+        for entry in batch.drain(..) {
+            if let Err(error) = repos.query_history().record(&entry).await {
+                warn!(
+                    %error,
+                    namespace_id = %entry.namespace_id,
+                    "failed to record query history entry"
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::query_log::QueryLog;
+    use iox_tests::TestCatalog;
+    use metric::{Attributes, Metric};
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_query_history() {
+        let catalog = TestCatalog::new();
+        let ns = catalog.create_namespace_1hr_retention("ns").await;
+        let time_provider = catalog.time_provider();
+        let metrics = metric::Registry::default();
+
+        let query_log = QueryLog::new(10, Arc::clone(&time_provider));
+        let history = QueryHistory::new(catalog.catalog(), &metrics);
+
+        let record = |history: &QueryHistory| {
+            let entry = query_log.push(ns.namespace.id, "sql", Box::new("SELECT 1"), None);
+            catalog.mock_time_provider().inc(Duration::from_secs(1));
+            query_log.set_completed(Arc::clone(&entry), true);
+            history.record(
+                &entry,
+                Some(QueryUsage {
+                    rows_returned: 1,
+                    ..Default::default()
+                }),
+            );
+        };
+
+        // Nothing is recorded while disabled.
+        record(&history);
+        tokio::task::yield_now().await;
+        assert!(history.list(ns.namespace.id, 10).await.unwrap().is_empty());
+
+        history.enable();
+        record(&history);
+
+        let got = wait_for_entries(&history, ns.namespace.id, 1).await;
+        assert_eq!(got[0].query_type, "sql");
+        assert_eq!(
+            got[0].query_text_hash,
+            QueryHistoryEntry::hash_query_text("SELECT 1")
+        );
+        assert_eq!(got[0].duration_ns, Duration::from_secs(1).as_nanos() as i64);
+        assert!(got[0].success);
+        assert_eq!(got[0].rows_returned, Some(1));
+    }
+
+    #[tokio::test]
+    async fn test_query_history_queue_full() {
+        let catalog = TestCatalog::new();
+        let ns = catalog.create_namespace_1hr_retention("ns").await;
+        let metrics = metric::Registry::default();
+
+        let query_log = QueryLog::new(10, catalog.time_provider());
+        let history = QueryHistory::new(catalog.catalog(), &metrics);
+        history.enable();
+
+        // The writer task does not run until this task yields, so the entries
+        // beyond the queue capacity are dropped.
+        let entry = query_log.push(ns.namespace.id, "sql", Box::new("SELECT 1"), None);
+        query_log.set_completed(Arc::clone(&entry), true);
+        for _ in 0..QUEUE_SIZE + 2 {
+            history.record(&entry, None);
+        }
+
+        let dropped = metrics
+            .get_instrument::<Metric<U64Counter>>("query_history_dropped_entries")
+            .expect("failed to read metric")
+            .get_observer(&Attributes::from([]))
+            .expect("failed to get observer")
+            .fetch();
+        assert_eq!(dropped, 2);
+
+        // The queued entries are written.
+        wait_for_entries(&history, ns.namespace.id, QUEUE_SIZE).await;
+    }
+
+    async fn wait_for_entries(
+        history: &QueryHistory,
+        namespace_id: NamespaceId,
+        n: usize,
+    ) -> Vec<QueryHistoryEntry> {
+        tokio::time::timeout(Duration::from_secs(10), async {
+            loop {
+                let limit = i64::try_from(n).unwrap() + 1;
+                let got = history.list(namespace_id, limit).await.unwrap();
+                if got.len() == n {
+                    return got;
+                }
+                tokio::time::sleep(Duration::from_millis(1)).await;
+            }
+        })
+        .await
+        .expect("timed out waiting for query history entries")
+    }
+}
@@ -0,0 +1,153 @@
+//! Registry of the queries currently running on the querier.
+
+use crate::query_log::QueryLogEntry;
+use iox_time::TimeProvider;
+use parking_lot::Mutex;
+use std::{collections::HashMap, sync::Arc, time::Duration};
+use tokio_util::sync::CancellationToken;
+use uuid::Uuid;
+
+/// A query registered in the [`QueryRegistry`] while it is running.
+#[derive(Debug)]
+pub struct RunningQuery {
+    id: Uuid,
+    namespace_name: Arc<str>,
+    entry: Arc<QueryLogEntry>,
+    cancellation: CancellationToken,
+}
+
+impl RunningQuery {
+    /// The ID of this query.
+    pub fn id(&self) -> Uuid {
+        self.id
+    }
+
+    /// The name of the namespace this query is run against.
+    pub fn namespace_name(&self) -> &Arc<str> {
+        &self.namespace_name
+    }
+
+    /// The query log entry describing this query.
+    pub fn entry(&self) -> &QueryLogEntry {
+        &self.entry
+    }
+
+    /// Returns true if this query was killed.
+    pub fn is_killed(&self) -> bool {
+        self.cancellation.is_cancelled()
+    }
+
+    /// The token cancelled when this query is killed.
+    pub(crate) fn cancellation(&self) -> &CancellationToken {
+        &self.cancellation
+    }
+}
+
+/// The set of queries currently running, allowing them to be listed and
+/// killed.
+///
+/// Queries are registered when they are recorded in the query log, and
+/// removed once complete.
+#[derive(Debug)]
+pub struct QueryRegistry {
+    running: Mutex<HashMap<Uuid, Arc<RunningQuery>>>,
+    time_provider: Arc<dyn TimeProvider>,
+}
+
+impl QueryRegistry {
+    /// Create an empty registry.
+    pub fn new(time_provider: Arc<dyn TimeProvider>) -> Self {
+        Self {
+            running: Default::default(),
+            time_provider,
+        }
+    }
+
+    /// Register the query described by `entry` as running.
+    pub(crate) fn register(
+        &self,
+        namespace_name: Arc<str>,
+        entry: Arc<QueryLogEntry>,
+    ) -> Arc<RunningQuery> {
+        let query = Arc::new(RunningQuery {
+            id: Uuid::new_v4(),
+            namespace_name,
+            entry,
+            cancellation: CancellationToken::new(),
+        });
+        self.running.lock().insert(query.id, Arc::clone(&query));
+        query
+    }
+
+    /// Remove the completed query `id` from the registry.
+    pub(crate) fn deregister(&self, id: Uuid) {
+        self.running.lock().remove(&id);
+    }
+
+    /// Return the running queries, ordered by their issue time.
+    pub fn list(&self) -> Vec<Arc<RunningQuery>> {
+        let mut queries = self.running.lock().values().cloned().collect::<Vec<_>>();
+        queries.sort_by_key(|q| (q.entry.issue_time, q.id));
+        queries
+    }
+
+    /// Return the duration `query` has been running for.
+    pub fn elapsed(&self, query: &RunningQuery) -> Duration {
+        self.time_provider
+            .now()
+            .checked_duration_since(query.entry.issue_time)
+            .unwrap_or_default()
+    }
+
+    /// Kill the running query `id`, returning it if found.
+    ///
+    /// The query is removed from the registry once its execution stops.
+    pub fn kill(&self, id: Uuid) -> Option<Arc<RunningQuery>> {
+        let query = self.running.lock().get(&id).cloned()?;
+        query.cancellation.cancel();
+        Some(query)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::query_log::QueryLog;
+    use data_types::NamespaceId;
+    use iox_time::{MockProvider, Time};
+
+    #[test]
+    fn test_register_kill() {
+        let time_provider = Arc::new(MockProvider::new(Time::from_timestamp_nanos(0)));
+        let log = QueryLog::new(10, Arc::clone(&time_provider) as _);
+        let registry = QueryRegistry::new(Arc::clone(&time_provider) as _);
+
+        let a = registry.register(
+            Arc::from("ns"),
+            log.push(NamespaceId::new(1), "sql", Box::new("SELECT 1"), None),
+        );
+        time_provider.inc(std::time::Duration::from_secs(1));
+        let b = registry.register(
+            Arc::from("ns"),
+            log.push(NamespaceId::new(1), "sql", Box::new("SELECT 2"), None),
+        );
+
+        let ids = registry.list().iter().map(|q| q.id()).collect::<Vec<_>>();
+        assert_eq!(ids, [a.id(), b.id()]);
+        assert_eq!(registry.elapsed(&a), std::time::Duration::from_secs(1));
+        assert_eq!(registry.elapsed(&b), std::time::Duration::ZERO);
+
+        assert!(registry.kill(Uuid::new_v4()).is_none());
+        assert!(!a.is_killed());
+        let killed = registry.kill(a.id()).unwrap();
+        assert!(killed.is_killed());
+        assert!(a.cancellation().is_cancelled());
+        assert!(!b.is_killed());
+
+        // Killed queries remain listed until they complete.
+        assert_eq!(registry.list().len(), 2);
+        registry.deregister(a.id());
+        let ids = registry.list().iter().map(|q| q.id()).collect::<Vec<_>>();
+        assert_eq!(ids, [b.id()]);
+    }
+}
@@ -0,0 +1,145 @@
+use crate::{ingester::IngesterPartition, table::QuerierTable};
+use arrow::{
+    array::{ArrayRef, StringArray, UInt64Array},
+    datatypes::{DataType, Field, Schema, SchemaRef},
+    record_batch::RecordBatch,
+};
+use async_trait::async_trait;
+use datafusion::{
+    datasource::TableProvider,
+    error::Result,
+    execution::context::SessionState,
+    logical_expr::TableType,
+    physical_plan::{memory::MemoryExec, ExecutionPlan},
+    prelude::Expr,
+};
+use iox_query::exec::SessionContextIOxExt;
+use std::{any::Any, collections::HashMap, sync::Arc};
+
+/// Implementation of system.ingester_partitions table
+///
+/// The partitions buffered by the ingesters are requested from the
+/// ingesters when the table is scanned, reading only the primary key columns
+/// of each table.
+#[derive(Debug)]
+pub(super) struct IngesterPartitionsTable {
+    schema: SchemaRef,
+    tables: Arc<HashMap<Arc<str>, Arc<QuerierTable>>>,
+}
+
+impl IngesterPartitionsTable {
+    pub(super) fn new(tables: Arc<HashMap<Arc<str>, Arc<QuerierTable>>>) -> Self {
+        Self {
+            schema: ingester_partitions_schema(),
+            tables,
+        }
+    }
+}
+
+#[async_trait]
+impl TableProvider for IngesterPartitionsTable {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        Arc::clone(&self.schema)
+    }
+
+    fn table_type(&self) -> TableType {
+        TableType::Base
+    }
+
+    async fn scan(
+        &self,
+        ctx: &SessionState,
+        projection: Option<&Vec<usize>>,
+        _filters: &[Expr],
+        limit: Option<usize>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        let limit = limit.unwrap_or(usize::MAX);
+
+        let mut tables = self.tables.values().collect::<Vec<_>>();
+        tables.sort_unstable_by(|a, b| a.table_name().cmp(b.table_name()));
+
+        let mut entries = vec![];
+        for table in tables {
+            if entries.len() >= limit {
+                break;
+            }
+            let partitions = table
+                .list_ingester_partitions(ctx.child_span("ingester partitions"))
+                .await?;
+            entries.extend(
+                partitions
+                    .into_iter()
+                    .map(|p| (Arc::clone(table.table_name()), p)),
+            );
+        }
+        entries.truncate(limit);
+
+        let batch = from_ingester_partitions(self.schema(), &entries)?;
+        Ok(Arc::new(MemoryExec::try_new(
+            &[vec![batch]],
+            self.schema(),
+            projection.cloned(),
+        )?))
+    }
+}
+
+fn ingester_partitions_schema() -> SchemaRef {
+    Arc::new(Schema::new(vec![
+        Field::new("table_name", DataType::Utf8, false),
+        Field::new("partition_id", DataType::Utf8, false),
+        Field::new("ingester_uuid", DataType::Utf8, false),
+        Field::new("completed_persistence_count", DataType::UInt64, false),
+        Field::new("persisting_files", DataType::UInt64, false),
+        Field::new("row_count", DataType::UInt64, false),
+    ]))
+}
+
+fn from_ingester_partitions(
+    schema: SchemaRef,
+    entries: &[(Arc<str>, IngesterPartition)],
+) -> Result<RecordBatch> {
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(
+            entries
+                .iter()
+                .map(|(table_name, _)| Some(table_name.as_ref()))
+                .collect::<StringArray>(),
+        ),
+        Arc::new(
+            entries
+                .iter()
+                .map(|(_, p)| Some(p.partition_id().to_string()))
+                .collect::<StringArray>(),
+        ),
+        Arc::new(
+            entries
+                .iter()
+                .map(|(_, p)| Some(p.ingester_uuid().to_string()))
+                .collect::<StringArray>(),
+        ),
+        Arc::new(
+            entries
+                .iter()
+                .map(|(_, p)| Some(p.completed_persistence_count()))
+                .collect::<UInt64Array>(),
+        ),
+        Arc::new(
+            entries
+                .iter()
+                .map(|(_, p)| Some(p.persisting_object_store_ids().len() as u64))
+                .collect::<UInt64Array>(),
+        ),
+        Arc::new(
+            entries
+                .iter()
+                .map(|(_, p)| Some(p.chunks().iter().map(|c| c.rows() as u64).sum::<u64>()))
+                .collect::<UInt64Array>(),
+        ),
+    ];
+
+    Ok(RecordBatch::try_new(schema, columns)?)
+}
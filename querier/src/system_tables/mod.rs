@@ -1,7 +1,7 @@
-use crate::query_log::QueryLog;
+use crate::{query_history::QueryHistory, query_log::QueryLog, table::QuerierTable};
 use arrow::{datatypes::SchemaRef, error::Result as ArrowResult, record_batch::RecordBatch};
 use async_trait::async_trait;
-use data_types::NamespaceId;
+use data_types::{NamespaceId, TableViewColumn};
 use datafusion::error::DataFusionError;
 use datafusion::physical_plan::{DisplayAs, DisplayFormatType};
 use datafusion::{
@@ -16,6 +16,7 @@ use datafusion::{
     },
     prelude::Expr,
 };
+use iox_catalog::interface::Catalog;
 use std::collections::HashMap;
 use std::{
     any::Any,
@@ -24,11 +25,23 @@ use std::{
     task::{Context, Poll},
 };
 
+mod ingester_partitions;
+mod parquet_files;
+mod partitions;
 mod queries;
+mod query_history;
+mod tables;
+mod view_columns;
 
 pub const SYSTEM_SCHEMA: &str = "system";
 
+const INGESTER_PARTITIONS_TABLE: &str = "ingester_partitions";
+const PARQUET_FILES_TABLE: &str = "parquet_files";
+const PARTITIONS_TABLE: &str = "partitions";
 const QUERIES_TABLE: &str = "queries";
+const QUERY_HISTORY_TABLE: &str = "query_history";
+const TABLES_TABLE: &str = "tables";
+const VIEW_COLUMNS_TABLE: &str = "view_columns";
 
 pub struct SystemSchemaProvider {
     tables: HashMap<&'static str, Arc<dyn TableProvider>>,
@@ -37,8 +50,12 @@ pub struct SystemSchemaProvider {
 impl SystemSchemaProvider {
     pub fn new(
         query_log: Arc<QueryLog>,
+        query_history: Arc<QueryHistory>,
         namespace_id: NamespaceId,
+        namespace_tables: Arc<HashMap<Arc<str>, Arc<QuerierTable>>>,
+        views: Arc<HashMap<Arc<str>, Arc<[TableViewColumn]>>>,
         include_debug_info: bool,
+        catalog: Arc<dyn Catalog>,
     ) -> Self {
         let mut tables: HashMap<&'static str, Arc<dyn TableProvider>> = HashMap::new();
 
@@ -47,6 +64,45 @@ impl SystemSchemaProvider {
                 table: Arc::new(queries::QueriesTable::new(query_log, Some(namespace_id))),
             });
             tables.insert(QUERIES_TABLE, queries);
+
+            // The query history is only recorded if enabled.
+            if query_history.is_enabled() {
+                let query_history = Arc::new(query_history::QueryHistoryTable::new(
+                    query_history,
+                    namespace_id,
+                ));
+                tables.insert(QUERY_HISTORY_TABLE, query_history);
+            }
+
+            let partitions = Arc::new(partitions::PartitionsTable::new(
+                Arc::clone(&catalog),
+                Arc::clone(&namespace_tables),
+            ));
+            tables.insert(PARTITIONS_TABLE, partitions);
+
+            let parquet_files = Arc::new(parquet_files::ParquetFilesTable::new(
+                catalog,
+                Arc::clone(&namespace_tables),
+            ));
+            tables.insert(PARQUET_FILES_TABLE, parquet_files);
+
+            let ingester_partitions = Arc::new(ingester_partitions::IngesterPartitionsTable::new(
+                Arc::clone(&namespace_tables),
+            ));
+            tables.insert(INGESTER_PARTITIONS_TABLE, ingester_partitions);
+
+            let namespace_tables = Arc::new(SystemTableProvider {
+                table: Arc::new(tables::TablesTable::new(namespace_tables)),
+            });
+            tables.insert(TABLES_TABLE, namespace_tables);
+        }
+
+        // Only namespaces with table views have a view columns table.
+        if !views.is_empty() {
+            let view_columns = Arc::new(SystemTableProvider {
+                table: Arc::new(view_columns::ViewColumnsTable::new(views)),
+            });
+            tables.insert(VIEW_COLUMNS_TABLE, view_columns);
         }
 
         Self { tables }
@@ -0,0 +1,228 @@
+use crate::table::QuerierTable;
+use arrow::{
+    array::{ArrayRef, Int64Array, StringArray, TimestampNanosecondArray},
+    datatypes::{DataType, Field, Schema, SchemaRef, TimeUnit},
+    record_batch::RecordBatch,
+};
+use async_trait::async_trait;
+use data_types::ParquetFile;
+use datafusion::{
+    datasource::TableProvider,
+    error::{DataFusionError, Result},
+    execution::context::SessionState,
+    logical_expr::TableType,
+    physical_plan::{memory::MemoryExec, ExecutionPlan},
+    prelude::Expr,
+};
+use iox_catalog::interface::Catalog;
+use std::{any::Any, collections::HashMap, sync::Arc};
+
+/// Implementation of system.parquet_files table
+///
+/// The parquet files of each table that are not marked for deletion are read
+/// from the catalog when the table is scanned.
+#[derive(Debug)]
+pub(super) struct ParquetFilesTable {
+    schema: SchemaRef,
+    catalog: Arc<dyn Catalog>,
+    tables: Arc<HashMap<Arc<str>, Arc<QuerierTable>>>,
+}
+
+impl ParquetFilesTable {
+    pub(super) fn new(
+        catalog: Arc<dyn Catalog>,
+        tables: Arc<HashMap<Arc<str>, Arc<QuerierTable>>>,
+    ) -> Self {
+        Self {
+            schema: parquet_files_schema(),
+            catalog,
+            tables,
+        }
+    }
+}
+
+#[async_trait]
+impl TableProvider for ParquetFilesTable {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        Arc::clone(&self.schema)
+    }
+
+    fn table_type(&self) -> TableType {
+        TableType::Base
+    }
+
+    async fn scan(
+        &self,
+        _ctx: &SessionState,
+        projection: Option<&Vec<usize>>,
+        _filters: &[Expr],
+        limit: Option<usize>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        let limit = limit.unwrap_or(usize::MAX);
+
+        let mut tables = self.tables.values().collect::<Vec<_>>();
+        tables.sort_unstable_by(|a, b| a.table_name().cmp(b.table_name()));
+
+        let mut entries = vec![];
+        let mut repos = self.catalog.repositories().await;
+        for table in tables {
+            if entries.len() >= limit {
+                break;
+            }
+            let mut files = repos
+                .parquet_files()
+                .list_by_table_not_to_delete(table.id())
+                .await
+                .map_err(|e| DataFusionError::External(Box::new(e)))?;
+            files.sort_unstable_by_key(|f| f.id);
+            entries.extend(
+                files
+                    .into_iter()
+                    .map(|f| (Arc::clone(table.table_name()), f)),
+            );
+        }
+        entries.truncate(limit);
+
+        let batch = from_parquet_files(self.schema(), &entries)?;
+        Ok(Arc::new(MemoryExec::try_new(
+            &[vec![batch]],
+            self.schema(),
+            projection.cloned(),
+        )?))
+    }
+}
+
+fn parquet_files_schema() -> SchemaRef {
+    let timestamp = DataType::Timestamp(TimeUnit::Nanosecond, None);
+    Arc::new(Schema::new(vec![
+        Field::new("table_name", DataType::Utf8, false),
+        Field::new("partition_id", DataType::Utf8, false),
+        Field::new("object_store_id", DataType::Utf8, false),
+        Field::new("min_time", timestamp.clone(), false),
+        Field::new("max_time", timestamp.clone(), false),
+        Field::new("file_size_bytes", DataType::Int64, false),
+        Field::new("row_count", DataType::Int64, false),
+        Field::new("compaction_level", DataType::Int64, false),
+        Field::new("created_at", timestamp.clone(), false),
+        Field::new("max_l0_created_at", timestamp, false),
+    ]))
+}
+
+fn from_parquet_files(
+    schema: SchemaRef,
+    entries: &[(Arc<str>, ParquetFile)],
+) -> Result<RecordBatch> {
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(
+            entries
+                .iter()
+                .map(|(table_name, _)| Some(table_name.as_ref()))
+                .collect::<StringArray>(),
+        ),
+        Arc::new(
+            entries
+                .iter()
+                .map(|(_, f)| Some(f.partition_id.to_string()))
+                .collect::<StringArray>(),
+        ),
+        Arc::new(
+            entries
+                .iter()
+                .map(|(_, f)| Some(f.object_store_id.to_string()))
+                .collect::<StringArray>(),
+        ),
+        Arc::new(
+            entries
+                .iter()
+                .map(|(_, f)| Some(f.min_time.get()))
+                .collect::<TimestampNanosecondArray>(),
+        ),
+        Arc::new(
+            entries
+                .iter()
+                .map(|(_, f)| Some(f.max_time.get()))
+                .collect::<TimestampNanosecondArray>(),
+        ),
+        Arc::new(
+            entries
+                .iter()
+                .map(|(_, f)| Some(f.file_size_bytes))
+                .collect::<Int64Array>(),
+        ),
+        Arc::new(
+            entries
+                .iter()
+                .map(|(_, f)| Some(f.row_count))
+                .collect::<Int64Array>(),
+        ),
+        Arc::new(
+            entries
+                .iter()
+                .map(|(_, f)| Some(f.compaction_level as i64))
+                .collect::<Int64Array>(),
+        ),
+        Arc::new(
+            entries
+                .iter()
+                .map(|(_, f)| Some(f.created_at.get()))
+                .collect::<TimestampNanosecondArray>(),
+        ),
+        Arc::new(
+            entries
+                .iter()
+                .map(|(_, f)| Some(f.max_l0_created_at.get()))
+                .collect::<TimestampNanosecondArray>(),
+        ),
+    ];
+
+    Ok(RecordBatch::try_new(schema, columns)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow_util::assert_batches_eq;
+    use data_types::{
+        ColumnSet, CompactionLevel, NamespaceId, ParquetFileId, ParquetFileParams, PartitionId,
+        TableId, Timestamp, TransitionPartitionId,
+    };
+    use uuid::Uuid;
+
+    #[test]
+    fn test_from_parquet_files() {
+        let file = ParquetFile::from_params(
+            ParquetFileParams {
+                namespace_id: NamespaceId::new(1),
+                table_id: TableId::new(2),
+                partition_id: TransitionPartitionId::Deprecated(PartitionId::new(3)),
+                object_store_id: Uuid::nil(),
+                min_time: Timestamp::new(1),
+                max_time: Timestamp::new(2),
+                file_size_bytes: 3,
+                row_count: 4,
+                compaction_level: CompactionLevel::FileNonOverlapped,
+                created_at: Timestamp::new(5),
+                column_set: ColumnSet::new([]),
+                max_l0_created_at: Timestamp::new(6),
+                column_statistics: vec![],
+            },
+            ParquetFileId::new(7),
+        );
+
+        let expected = vec![
+            "+------------+--------------+--------------------------------------+-------------------------------+-------------------------------+-----------------+-----------+------------------+-------------------------------+-------------------------------+",
+            "| table_name | partition_id | object_store_id                      | min_time                      | max_time                      | file_size_bytes | row_count | compaction_level | created_at                    | max_l0_created_at             |",
+            "+------------+--------------+--------------------------------------+-------------------------------+-------------------------------+-----------------+-----------+------------------+-------------------------------+-------------------------------+",
+            "| cpu        | 3            | 00000000-0000-0000-0000-000000000000 | 1970-01-01T00:00:00.000000001 | 1970-01-01T00:00:00.000000002 | 3               | 4         | 1                | 1970-01-01T00:00:00.000000005 | 1970-01-01T00:00:00.000000006 |",
+            "+------------+--------------+--------------------------------------+-------------------------------+-------------------------------+-----------------+-----------+------------------+-------------------------------+-------------------------------+",
+        ];
+
+        let batch =
+            from_parquet_files(parquet_files_schema(), &[(Arc::from("cpu"), file)]).unwrap();
+        assert_batches_eq!(&expected, &[batch]);
+    }
+}
@@ -0,0 +1,199 @@
+use crate::table::QuerierTable;
+use arrow::{
+    array::{ArrayRef, StringArray, TimestampNanosecondArray},
+    datatypes::{DataType, Field, Schema, SchemaRef, TimeUnit},
+    record_batch::RecordBatch,
+};
+use async_trait::async_trait;
+use data_types::Partition;
+use datafusion::{
+    datasource::TableProvider,
+    error::{DataFusionError, Result},
+    execution::context::SessionState,
+    logical_expr::TableType,
+    physical_plan::{memory::MemoryExec, ExecutionPlan},
+    prelude::Expr,
+};
+use iox_catalog::interface::Catalog;
+use std::{any::Any, collections::HashMap, sync::Arc};
+
+/// Implementation of system.partitions table
+///
+/// The partitions of each table are read from the catalog when the table is
+/// scanned.
+#[derive(Debug)]
+pub(super) struct PartitionsTable {
+    schema: SchemaRef,
+    catalog: Arc<dyn Catalog>,
+    tables: Arc<HashMap<Arc<str>, Arc<QuerierTable>>>,
+}
+
+impl PartitionsTable {
+    pub(super) fn new(
+        catalog: Arc<dyn Catalog>,
+        tables: Arc<HashMap<Arc<str>, Arc<QuerierTable>>>,
+    ) -> Self {
+        Self {
+            schema: partitions_schema(),
+            catalog,
+            tables,
+        }
+    }
+}
+
+#[async_trait]
+impl TableProvider for PartitionsTable {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        Arc::clone(&self.schema)
+    }
+
+    fn table_type(&self) -> TableType {
+        TableType::Base
+    }
+
+    async fn scan(
+        &self,
+        _ctx: &SessionState,
+        projection: Option<&Vec<usize>>,
+        _filters: &[Expr],
+        limit: Option<usize>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        let limit = limit.unwrap_or(usize::MAX);
+
+        let mut tables = self.tables.values().collect::<Vec<_>>();
+        tables.sort_unstable_by(|a, b| a.table_name().cmp(b.table_name()));
+
+        let mut entries = vec![];
+        let mut repos = self.catalog.repositories().await;
+        for table in tables {
+            if entries.len() >= limit {
+                break;
+            }
+            let mut partitions = repos
+                .partitions()
+                .list_by_table_id(table.id())
+                .await
+                .map_err(|e| DataFusionError::External(Box::new(e)))?;
+            partitions.sort_unstable_by(|a, b| a.partition_key.cmp(&b.partition_key));
+            entries.extend(
+                partitions
+                    .into_iter()
+                    .map(|p| (Arc::clone(table.table_name()), p)),
+            );
+        }
+        entries.truncate(limit);
+
+        let batch = from_partitions(self.schema(), &entries)?;
+        Ok(Arc::new(MemoryExec::try_new(
+            &[vec![batch]],
+            self.schema(),
+            projection.cloned(),
+        )?))
+    }
+}
+
+fn partitions_schema() -> SchemaRef {
+    Arc::new(Schema::new(vec![
+        Field::new("table_name", DataType::Utf8, false),
+        Field::new("partition_id", DataType::Utf8, false),
+        Field::new("partition_key", DataType::Utf8, false),
+        Field::new("sort_key", DataType::Utf8, true),
+        Field::new(
+            "new_file_at",
+            DataType::Timestamp(TimeUnit::Nanosecond, None),
+            true,
+        ),
+    ]))
+}
+
+fn from_partitions(schema: SchemaRef, entries: &[(Arc<str>, Partition)]) -> Result<RecordBatch> {
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(
+            entries
+                .iter()
+                .map(|(table_name, _)| Some(table_name.as_ref()))
+                .collect::<StringArray>(),
+        ),
+        Arc::new(
+            entries
+                .iter()
+                .map(|(_, p)| Some(p.transition_partition_id().to_string()))
+                .collect::<StringArray>(),
+        ),
+        Arc::new(
+            entries
+                .iter()
+                .map(|(_, p)| Some(p.partition_key.inner()))
+                .collect::<StringArray>(),
+        ),
+        Arc::new(
+            entries
+                .iter()
+                .map(|(_, p)| p.sort_key.as_ref().map(|k| k.join(",")))
+                .collect::<StringArray>(),
+        ),
+        Arc::new(
+            entries
+                .iter()
+                .map(|(_, p)| p.new_file_at.map(|t| t.get()))
+                .collect::<TimestampNanosecondArray>(),
+        ),
+    ];
+
+    Ok(RecordBatch::try_new(schema, columns)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::array::as_string_array;
+    use arrow_util::assert_batches_eq;
+    use data_types::{PartitionId, PartitionKey, SortedColumnSet, TableId, Timestamp};
+
+    #[test]
+    fn test_from_partitions() {
+        let partition = |id: i64, key: &str, sort_key: Option<&[&str]>, new_file_at| {
+            Partition::new_in_memory_only(
+                PartitionId::new(id),
+                TableId::new(1),
+                PartitionKey::from(key),
+                sort_key.map(|k| k.iter().map(|s| s.to_string()).collect()),
+                SortedColumnSet::new([]),
+                new_file_at,
+            )
+        };
+
+        let entries: [(Arc<str>, Partition); 2] = [
+            (
+                Arc::from("cpu"),
+                partition(1, "2023-10-17", Some(&["host", "time"]), None),
+            ),
+            (
+                Arc::from("cpu"),
+                partition(2, "2023-10-18", None, Some(Timestamp::new(1))),
+            ),
+        ];
+
+        let batch = from_partitions(partitions_schema(), &entries).unwrap();
+
+        // The partitions are identified as in system.parquet_files.
+        let ids = as_string_array(batch.column(1));
+        for (i, (_, p)) in entries.iter().enumerate() {
+            assert_eq!(ids.value(i), p.transition_partition_id().to_string());
+        }
+
+        let expected = vec![
+            "+------------+---------------+-----------+-------------------------------+",
+            "| table_name | partition_key | sort_key  | new_file_at                   |",
+            "+------------+---------------+-----------+-------------------------------+",
+            "| cpu        | 2023-10-17    | host,time |                               |",
+            "| cpu        | 2023-10-18    |           | 1970-01-01T00:00:00.000000001 |",
+            "+------------+---------------+-----------+-------------------------------+",
+        ];
+        assert_batches_eq!(&expected, &[batch.project(&[0, 2, 3, 4]).unwrap()]);
+    }
+}
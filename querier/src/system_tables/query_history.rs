@@ -0,0 +1,164 @@
+use crate::query_history::QueryHistory;
+use arrow::{
+    array::{
+        ArrayRef, BooleanArray, DurationNanosecondArray, Int64Array, StringArray,
+        TimestampNanosecondArray,
+    },
+    datatypes::{DataType, Field, Schema, SchemaRef, TimeUnit},
+    record_batch::RecordBatch,
+};
+use async_trait::async_trait;
+use data_types::{NamespaceId, QueryHistoryEntry};
+use datafusion::{
+    datasource::TableProvider,
+    error::{DataFusionError, Result},
+    execution::context::SessionState,
+    logical_expr::TableType,
+    physical_plan::{memory::MemoryExec, ExecutionPlan},
+    prelude::Expr,
+};
+use std::{any::Any, sync::Arc};
+
+/// The maximum number of entries read from the catalog by a scan of the
+/// system.query_history table.
+const MAX_ENTRIES: usize = 10_000;
+
+/// Implementation of system.query_history table
+///
+/// Unlike the other system tables, the entries are read from the catalog, so
+/// this table includes the queries executed by all the queriers.
+#[derive(Debug)]
+pub(super) struct QueryHistoryTable {
+    schema: SchemaRef,
+    query_history: Arc<QueryHistory>,
+    namespace_id: NamespaceId,
+}
+
+impl QueryHistoryTable {
+    pub(super) fn new(query_history: Arc<QueryHistory>, namespace_id: NamespaceId) -> Self {
+        Self {
+            schema: query_history_schema(),
+            query_history,
+            namespace_id,
+        }
+    }
+}
+
+#[async_trait]
+impl TableProvider for QueryHistoryTable {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        Arc::clone(&self.schema)
+    }
+
+    fn table_type(&self) -> TableType {
+        TableType::Base
+    }
+
+    async fn scan(
+        &self,
+        _ctx: &SessionState,
+        projection: Option<&Vec<usize>>,
+        _filters: &[Expr],
+        limit: Option<usize>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        let limit = limit.map_or(MAX_ENTRIES, |l| l.min(MAX_ENTRIES));
+        let entries = self
+            .query_history
+            .list(self.namespace_id, limit as i64)
+            .await
+            .map_err(|e| DataFusionError::External(Box::new(e)))?;
+
+        let batch = from_query_history_entries(self.schema(), &entries)?;
+        Ok(Arc::new(MemoryExec::try_new(
+            &[vec![batch]],
+            self.schema(),
+            projection.cloned(),
+        )?))
+    }
+}
+
+fn query_history_schema() -> SchemaRef {
+    Arc::new(Schema::new(vec![
+        Field::new(
+            "issue_time",
+            DataType::Timestamp(TimeUnit::Nanosecond, None),
+            false,
+        ),
+        Field::new("query_type", DataType::Utf8, false),
+        Field::new("query_text_hash", DataType::Int64, false),
+        Field::new("duration", DataType::Duration(TimeUnit::Nanosecond), false),
+        Field::new("success", DataType::Boolean, false),
+        Field::new("partitions_scanned", DataType::Int64, true),
+        Field::new("bytes_scanned", DataType::Int64, true),
+        Field::new("rows_returned", DataType::Int64, true),
+        Field::new("peak_memory_bytes", DataType::Int64, true),
+    ]))
+}
+
+fn from_query_history_entries(
+    schema: SchemaRef,
+    entries: &[QueryHistoryEntry],
+) -> Result<RecordBatch> {
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(
+            entries
+                .iter()
+                .map(|e| Some(e.issue_time.get()))
+                .collect::<TimestampNanosecondArray>(),
+        ),
+        Arc::new(
+            entries
+                .iter()
+                .map(|e| Some(&e.query_type))
+                .collect::<StringArray>(),
+        ),
+        Arc::new(
+            entries
+                .iter()
+                .map(|e| Some(e.query_text_hash))
+                .collect::<Int64Array>(),
+        ),
+        Arc::new(
+            entries
+                .iter()
+                .map(|e| Some(e.duration_ns))
+                .collect::<DurationNanosecondArray>(),
+        ),
+        Arc::new(
+            entries
+                .iter()
+                .map(|e| Some(e.success))
+                .collect::<BooleanArray>(),
+        ),
+        Arc::new(
+            entries
+                .iter()
+                .map(|e| e.partitions_scanned)
+                .collect::<Int64Array>(),
+        ),
+        Arc::new(
+            entries
+                .iter()
+                .map(|e| e.bytes_scanned)
+                .collect::<Int64Array>(),
+        ),
+        Arc::new(
+            entries
+                .iter()
+                .map(|e| e.rows_returned)
+                .collect::<Int64Array>(),
+        ),
+        Arc::new(
+            entries
+                .iter()
+                .map(|e| e.peak_memory_bytes)
+                .collect::<Int64Array>(),
+        ),
+    ];
+
+    Ok(RecordBatch::try_new(schema, columns)?)
+}
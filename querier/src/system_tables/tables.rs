@@ -0,0 +1,148 @@
+use crate::{
+    system_tables::{BatchIterator, IoxSystemTable},
+    table::QuerierTable,
+};
+use arrow::{
+    array::{ArrayRef, Int64Array, StringArray, UInt64Array},
+    datatypes::{DataType, Field, Schema, SchemaRef},
+    error::Result,
+    record_batch::RecordBatch,
+};
+use data_types::TableId;
+use std::{collections::HashMap, sync::Arc};
+
+/// Implementation of system.tables table
+#[derive(Debug)]
+pub(super) struct TablesTable {
+    schema: SchemaRef,
+    tables: Arc<HashMap<Arc<str>, Arc<QuerierTable>>>,
+}
+
+impl TablesTable {
+    pub(super) fn new(tables: Arc<HashMap<Arc<str>, Arc<QuerierTable>>>) -> Self {
+        Self {
+            schema: tables_schema(),
+            tables,
+        }
+    }
+}
+
+impl IoxSystemTable for TablesTable {
+    fn schema(&self) -> SchemaRef {
+        Arc::clone(&self.schema)
+    }
+
+    fn scan(&self, batch_size: usize) -> Result<BatchIterator> {
+        let schema = self.schema();
+
+        let mut entries = self
+            .tables
+            .values()
+            .map(|t| TableEntry::from(t.as_ref()))
+            .collect::<Vec<_>>();
+        entries.sort_unstable_by(|a, b| a.name.cmp(&b.name));
+
+        let mut offset = 0;
+        Ok(Box::new(std::iter::from_fn(move || {
+            if offset >= entries.len() {
+                return None;
+            }
+
+            let len = batch_size.min(entries.len() - offset);
+            let batch = from_table_entries(Arc::clone(&schema), &entries[offset..offset + len]);
+            offset += len;
+            Some(batch)
+        })))
+    }
+}
+
+/// A row of the system.tables table.
+#[derive(Debug)]
+struct TableEntry {
+    name: Arc<str>,
+    id: TableId,
+    tag_count: usize,
+    field_count: usize,
+}
+
+impl From<&QuerierTable> for TableEntry {
+    fn from(t: &QuerierTable) -> Self {
+        let schema = t.schema();
+        Self {
+            name: Arc::clone(t.table_name()),
+            id: t.id(),
+            tag_count: schema.tags_iter().count(),
+            field_count: schema.fields_iter().count(),
+        }
+    }
+}
+
+fn tables_schema() -> SchemaRef {
+    Arc::new(Schema::new(vec![
+        Field::new("table_name", DataType::Utf8, false),
+        Field::new("table_id", DataType::Int64, false),
+        Field::new("tag_count", DataType::UInt64, false),
+        Field::new("field_count", DataType::UInt64, false),
+    ]))
+}
+
+fn from_table_entries(schema: SchemaRef, entries: &[TableEntry]) -> Result<RecordBatch> {
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(
+            entries
+                .iter()
+                .map(|e| Some(e.name.as_ref()))
+                .collect::<StringArray>(),
+        ),
+        Arc::new(
+            entries
+                .iter()
+                .map(|e| Some(e.id.get()))
+                .collect::<Int64Array>(),
+        ),
+        Arc::new(
+            entries
+                .iter()
+                .map(|e| Some(e.tag_count as u64))
+                .collect::<UInt64Array>(),
+        ),
+        Arc::new(
+            entries
+                .iter()
+                .map(|e| Some(e.field_count as u64))
+                .collect::<UInt64Array>(),
+        ),
+    ];
+
+    RecordBatch::try_new(schema, columns)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow_util::assert_batches_eq;
+
+    #[test]
+    fn test_from_table_entries() {
+        let entry = |name: &str, id: i64, tag_count: usize, field_count: usize| TableEntry {
+            name: Arc::from(name),
+            id: TableId::new(id),
+            tag_count,
+            field_count,
+        };
+
+        let entries = [entry("cpu", 1, 2, 3), entry("mem", 2, 0, 1)];
+
+        let expected = vec![
+            "+------------+----------+-----------+-------------+",
+            "| table_name | table_id | tag_count | field_count |",
+            "+------------+----------+-----------+-------------+",
+            "| cpu        | 1        | 2         | 3           |",
+            "| mem        | 2        | 0         | 1           |",
+            "+------------+----------+-----------+-------------+",
+        ];
+
+        let batch = from_table_entries(tables_schema(), &entries).unwrap();
+        assert_batches_eq!(&expected, &[batch]);
+    }
+}
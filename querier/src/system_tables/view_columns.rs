@@ -0,0 +1,149 @@
+use crate::system_tables::{BatchIterator, IoxSystemTable};
+use arrow::{
+    array::{ArrayRef, StringArray},
+    datatypes::{DataType, Field, Schema, SchemaRef},
+    error::Result,
+    record_batch::RecordBatch,
+};
+use data_types::TableViewColumn;
+use std::{collections::HashMap, sync::Arc};
+
+/// Implementation of system.view_columns table
+#[derive(Debug)]
+pub(super) struct ViewColumnsTable {
+    schema: SchemaRef,
+    views: Arc<HashMap<Arc<str>, Arc<[TableViewColumn]>>>,
+}
+
+impl ViewColumnsTable {
+    pub(super) fn new(views: Arc<HashMap<Arc<str>, Arc<[TableViewColumn]>>>) -> Self {
+        Self {
+            schema: view_columns_schema(),
+            views,
+        }
+    }
+}
+
+impl IoxSystemTable for ViewColumnsTable {
+    fn schema(&self) -> SchemaRef {
+        Arc::clone(&self.schema)
+    }
+
+    fn scan(&self, batch_size: usize) -> Result<BatchIterator> {
+        let schema = self.schema();
+
+        let mut entries = self
+            .views
+            .iter()
+            .flat_map(|(table_name, columns)| {
+                columns
+                    .iter()
+                    .map(move |c| (Arc::clone(table_name), c.clone()))
+            })
+            .collect::<Vec<_>>();
+        entries.sort_unstable_by(|a, b| (&a.0, &a.1.name).cmp(&(&b.0, &b.1.name)));
+
+        let mut offset = 0;
+        Ok(Box::new(std::iter::from_fn(move || {
+            if offset >= entries.len() {
+                return None;
+            }
+
+            let len = batch_size.min(entries.len() - offset);
+            let batch = from_view_columns(Arc::clone(&schema), &entries[offset..offset + len]);
+            offset += len;
+            Some(batch)
+        })))
+    }
+}
+
+fn view_columns_schema() -> SchemaRef {
+    Arc::new(Schema::new(vec![
+        Field::new("table_name", DataType::Utf8, false),
+        Field::new("column_name", DataType::Utf8, false),
+        Field::new("expression", DataType::Utf8, false),
+        Field::new("unit", DataType::Utf8, true),
+    ]))
+}
+
+fn from_view_columns(
+    schema: SchemaRef,
+    entries: &[(Arc<str>, TableViewColumn)],
+) -> Result<RecordBatch> {
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(
+            entries
+                .iter()
+                .map(|(t, _)| Some(t.as_ref()))
+                .collect::<StringArray>(),
+        ),
+        Arc::new(
+            entries
+                .iter()
+                .map(|(_, c)| Some(c.name.as_str()))
+                .collect::<StringArray>(),
+        ),
+        Arc::new(
+            entries
+                .iter()
+                .map(|(_, c)| Some(c.expression.as_str()))
+                .collect::<StringArray>(),
+        ),
+        Arc::new(
+            entries
+                .iter()
+                .map(|(_, c)| c.unit.as_deref())
+                .collect::<StringArray>(),
+        ),
+    ];
+
+    RecordBatch::try_new(schema, columns)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow_util::assert_batches_eq;
+    use data_types::TableId;
+
+    #[test]
+    fn test_from_views() {
+        let column =
+            |table_id: i64, name: &str, expression: &str, unit: Option<&str>| TableViewColumn {
+                table_id: TableId::new(table_id),
+                name: name.to_string(),
+                expression: expression.to_string(),
+                unit: unit.map(ToString::to_string),
+            };
+
+        let views: HashMap<Arc<str>, Arc<[TableViewColumn]>> = HashMap::from([
+            (
+                Arc::from("weather"),
+                Arc::from(vec![
+                    column(1, "temp_f", r#""temp" * 1.8 + 32"#, Some("fahrenheit")),
+                    column(1, "temp_c", r#""temp""#, Some("celsius")),
+                ]),
+            ),
+            (
+                Arc::from("cpu"),
+                Arc::from(vec![column(2, "host", "hostname", None)]),
+            ),
+        ]);
+
+        let table = ViewColumnsTable::new(Arc::new(views));
+
+        let expected = vec![
+            "+------------+-------------+-------------------+------------+",
+            "| table_name | column_name | expression        | unit       |",
+            "+------------+-------------+-------------------+------------+",
+            "| cpu        | host        | hostname          |            |",
+            "| weather    | temp_c      | \"temp\"            | celsius    |",
+            "| weather    | temp_f      | \"temp\" * 1.8 + 32 | fahrenheit |",
+            "+------------+-------------+-------------------+------------+",
+        ];
+
+        let entries = table.scan(2).unwrap().collect::<Result<Vec<_>>>().unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_batches_eq!(&expected, &entries);
+    }
+}
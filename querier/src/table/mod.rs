@@ -5,6 +5,7 @@ use crate::{
     },
     ingester::{self, IngesterConnection, IngesterPartition},
     parquet::ChunkAdapter,
+    query_denylist::QueryDenylist,
     CONCURRENT_CHUNK_CREATION_JOBS,
 };
 use data_types::{
@@ -30,8 +31,10 @@ use trace::span::{Span, SpanRecorder};
 use uuid::Uuid;
 
 pub use self::metrics::PruneMetrics;
+pub use self::pruning_stats::PruningStatistics;
 
 mod metrics;
+mod pruning_stats;
 mod query_access;
 
 #[cfg(test)]
@@ -66,6 +69,7 @@ pub struct QuerierTableArgs {
     pub ingester_connection: Option<Arc<dyn IngesterConnection>>,
     pub chunk_adapter: Arc<ChunkAdapter>,
     pub prune_metrics: Arc<PruneMetrics>,
+    pub query_denylist: Arc<QueryDenylist>,
 }
 
 /// Table representation for the querier.
@@ -97,6 +101,9 @@ pub struct QuerierTable {
 
     /// Metrics for chunk pruning.
     prune_metrics: Arc<PruneMetrics>,
+
+    /// Rules rejecting reads of this table.
+    query_denylist: Arc<QueryDenylist>,
 }
 
 impl QuerierTable {
@@ -112,6 +119,7 @@ impl QuerierTable {
             ingester_connection,
             chunk_adapter,
             prune_metrics,
+            query_denylist,
         } = args;
 
         Self {
@@ -124,9 +132,16 @@ impl QuerierTable {
             ingester_connection,
             chunk_adapter,
             prune_metrics,
+            query_denylist,
         }
     }
 
+    /// Return an error if a read of this table with `filters` is rejected by
+    /// the query denylist.
+    pub(crate) fn check_denylist(&self, filters: &[Expr]) -> Result<(), DataFusionError> {
+        self.query_denylist.check(self.table_name(), filters)
+    }
+
     /// Table name.
     pub fn table_name(&self) -> &Arc<str> {
         &self.table_name
@@ -149,11 +164,24 @@ impl QuerierTable {
         span: Option<Span>,
         projection: Option<&Vec<usize>>,
     ) -> Result<Vec<Arc<dyn QueryChunk>>> {
+        self.chunks_with_statistics(filters, span, projection)
+            .await
+            .map(|(chunks, _stats)| chunks)
+    }
+
+    /// Query all chunks within this table, returning the
+    /// [`PruningStatistics`] of the chunk selection alongside them.
+    pub async fn chunks_with_statistics(
+        &self,
+        filters: &[Expr],
+        span: Option<Span>,
+        projection: Option<&Vec<usize>>,
+    ) -> Result<(Vec<Arc<dyn QueryChunk>>, PruningStatistics)> {
         let mut span_recorder = SpanRecorder::new(span);
         match self.chunks_inner(filters, &span_recorder, projection).await {
-            Ok(chunks) => {
+            Ok(v) => {
                 span_recorder.ok("got chunks");
-                Ok(chunks)
+                Ok(v)
             }
             Err(e) => {
                 span_recorder.error("failed to get chunks");
@@ -167,7 +195,7 @@ impl QuerierTable {
         filters: &[Expr],
         span_recorder: &SpanRecorder,
         projection: Option<&Vec<usize>>,
-    ) -> Result<Vec<Arc<dyn QueryChunk>>> {
+    ) -> Result<(Vec<Arc<dyn QueryChunk>>, PruningStatistics)> {
         debug!(
             ?filters,
             namespace=%self.namespace_name,
@@ -210,6 +238,10 @@ impl QuerierTable {
 
         // handle errors / cache refresh
         let partitions = partitions?;
+        let mut stats = PruningStatistics {
+            ingester_partitions: partitions.len(),
+            ..Default::default()
+        };
 
         // Determine number of persisted parquet files per ingester UUID seen in the ingester query
         // responses for cache invalidation. If `persisted_file_counts_by_ingester_uuid` is empty,
@@ -240,6 +272,7 @@ impl QuerierTable {
             )
             .await;
         let num_initial_parquet_file_chunks = parquet_files.files.len();
+        stats.parquet_files_considered = num_initial_parquet_file_chunks;
         debug!(
             num_chunks = num_initial_parquet_file_chunks,
             "Fetched Parquet file chunks"
@@ -264,7 +297,7 @@ impl QuerierTable {
             .as_ref()
             .and_then(|ns| ns.tables.get(self.table_name.as_ref()))
         else {
-            return Ok(vec![]);
+            return Ok((vec![], stats));
         };
         let cached_partitions = self
             .fetch_cached_partitions(
@@ -276,6 +309,7 @@ impl QuerierTable {
             .await;
 
         // prune partitons
+        stats.partitions_considered = cached_partitions.len();
         let cached_partitions = self
             .prune_partitions(
                 cached_partitions,
@@ -284,14 +318,35 @@ impl QuerierTable {
                 span_recorder.child_span("prune partitions"),
             )
             .await;
+        stats.partitions_pruned = stats.partitions_considered - cached_partitions.len();
+        // Parquet files that are still being persisted by an ingester contain
+        // the same rows as the ingester returned for that partition - use the
+        // ingester copy, and skip the file to avoid returning duplicate rows.
+        let persisting_object_store_ids: HashSet<Uuid> = partitions
+            .iter()
+            .flat_map(|p| p.persisting_object_store_ids().iter().copied())
+            .collect();
+
         let mut num_intermediate_parquet_files = 0;
+        let mut num_persisting_parquet_files = 0;
+        let mut num_pruned_early_parquet_files = 0;
         let parquet_files = parquet_files.files.iter().filter_map(|f| {
+            if persisting_object_store_ids.contains(&f.object_store_id) {
+                num_persisting_parquet_files += 1;
+                return None;
+            }
+
             match cached_partitions.get(&f.partition_id) {
                 Some(cached_partition) => {
                     num_intermediate_parquet_files += 1;
-                    Some((Arc::clone(f), Arc::clone(cached_partition)))
+                    Some((
+                        Arc::clone(f),
+                        Arc::clone(cached_partition),
+                        parquet_files.column_statistics(f.id),
+                    ))
                 }
                 None => {
+                    num_pruned_early_parquet_files += 1;
                     self.prune_metrics
                         .was_pruned_early(f.row_count as u64, f.file_size_bytes as u64);
                     None
@@ -310,6 +365,20 @@ impl QuerierTable {
             )
             .await;
         let num_final_parquet_file_chunks = parquet_files.len();
+        stats.parquet_files_persisting = num_persisting_parquet_files;
+        stats.parquet_files_pruned_early = num_pruned_early_parquet_files;
+        stats.parquet_files_pruned_late =
+            num_intermediate_parquet_files - num_final_parquet_file_chunks;
+        stats.parquet_files_scanned = num_final_parquet_file_chunks;
+
+        if num_persisting_parquet_files > 0 {
+            debug!(
+                namespace=%self.namespace_name,
+                table_name=%self.table_name(),
+                num_persisting_parquet_files,
+                "skipped parquet files still persisting in ingesters"
+            );
+        }
 
         // build final chunk list from ingester chunks + pruned parquet file chunks
         let chunks: Vec<_> = partitions
@@ -343,7 +412,9 @@ impl QuerierTable {
             num_ingester_chunks = chunks.len() - num_final_parquet_file_chunks,
             "pruned with pushed down predicates"
         );
-        Ok(chunks)
+        stats.ingester_chunks_scanned = chunks.len() - num_final_parquet_file_chunks;
+
+        Ok((chunks, stats))
     }
 
     async fn fetch_cached_partitions(
@@ -496,6 +567,15 @@ impl QuerierTable {
         }
     }
 
+    /// Get all partitions of this table from the ingesters, with the data of
+    /// the primary key columns only.
+    pub(crate) async fn list_ingester_partitions(
+        &self,
+        span: Option<Span>,
+    ) -> Result<Vec<IngesterPartition>> {
+        self.ingester_partitions(&[], span, Some(&vec![])).await
+    }
+
     /// Get partitions from ingesters.
     async fn ingester_partitions(
         &self,
@@ -881,6 +961,54 @@ mod tests {
         assert_eq!(chunks.len(), 3);
     }
 
+    #[tokio::test]
+    async fn test_persisting_parquet_file_excluded() {
+        maybe_start_logging();
+        let catalog = TestCatalog::new();
+        let ns = catalog.create_namespace_1hr_retention("ns").await;
+        let table = ns.create_table("table1").await;
+        let partition = table.create_partition("k").await;
+        let schema = make_schema(&table).await;
+
+        // A file containing data the ingester is still persisting, and a file
+        // that has finished persisting.
+        let persisting = partition
+            .create_parquet_file(
+                TestParquetFileBuilder::default().with_line_protocol("table1 foo=1 11"),
+            )
+            .await;
+        partition
+            .create_parquet_file(
+                TestParquetFileBuilder::default().with_line_protocol("table1 foo=2 22"),
+            )
+            .await;
+
+        let ingester_partition = IngesterPartitionBuilder::new(schema, &partition)
+            .with_lp(["table foo=1 11"])
+            .build()
+            .with_persisting_object_store_ids(vec![persisting.parquet_file.object_store_id]);
+
+        let querier_table = TestQuerierTable::new(&catalog, &table)
+            .await
+            .with_ingester_partition(ingester_partition);
+
+        // Expect 2 chunks: one for the ingester, and one for the completed
+        // parquet file.
+        let chunks = querier_table.chunks().await.unwrap();
+        assert_eq!(chunks.len(), 2);
+
+        // Once the ingester completes persisting the file, it is queried.
+        let ingester_partition =
+            IngesterPartitionBuilder::new(make_schema(&table).await, &partition)
+                .with_lp(["table foo=3 33"])
+                .build();
+        let querier_table = querier_table
+            .clear_ingester_partitions()
+            .with_ingester_partition(ingester_partition);
+        let chunks = querier_table.chunks().await.unwrap();
+        assert_eq!(chunks.len(), 3);
+    }
+
     #[tokio::test]
     async fn test_custom_partitioning() {
         maybe_start_logging();
@@ -961,6 +1089,26 @@ mod tests {
                 .unwrap(),
             &Observation::U64Counter(1),
         );
+
+        // check pruning statistics
+        let (_chunks, stats) = querier_table
+            .chunks_with_statistics(&filters)
+            .await
+            .unwrap();
+        assert_eq!(
+            stats,
+            PruningStatistics {
+                partitions_considered: 2,
+                partitions_pruned: 1,
+                parquet_files_considered: 2,
+                parquet_files_persisting: 0,
+                parquet_files_pruned_early: 1,
+                parquet_files_pruned_late: 0,
+                parquet_files_scanned: 1,
+                ingester_partitions: 1,
+                ingester_chunks_scanned: 1,
+            }
+        );
     }
 
     #[tokio::test]
@@ -1245,5 +1393,23 @@ mod tests {
             let span = Some(Span::root("root", Arc::clone(&self.traces) as _));
             self.querier_table.chunks(filters, span, projection).await
         }
+
+        async fn chunks_with_statistics(
+            &self,
+            filters: &[Expr],
+        ) -> Result<(Vec<Arc<dyn QueryChunk>>, PruningStatistics)> {
+            self.querier_table
+                .ingester_connection
+                .as_ref()
+                .unwrap()
+                .as_any()
+                .downcast_ref::<MockIngesterConnection>()
+                .unwrap()
+                .next_response(Ok(self.ingester_partitions.clone()));
+
+            self.querier_table
+                .chunks_with_statistics(filters, None, None)
+                .await
+        }
     }
 }
@@ -0,0 +1,237 @@
+//! Reporting of the chunk selection of a [`QuerierTable`] scan in `EXPLAIN`
+//! output.
+//!
+//! [`QuerierTable`]: super::QuerierTable
+
+use std::{any::Any, fmt, sync::Arc};
+
+use arrow::datatypes::SchemaRef;
+use datafusion::{
+    common::Statistics,
+    error::Result,
+    execution::context::TaskContext,
+    physical_expr::{EquivalenceProperties, PhysicalSortExpr},
+    physical_plan::{
+        metrics::{ExecutionPlanMetricsSet, MetricBuilder, MetricsSet},
+        DisplayAs, DisplayFormatType, ExecutionPlan, Partitioning, SendableRecordBatchStream,
+    },
+};
+
+/// The number of partitions, parquet files and ingester partitions considered,
+/// pruned and scanned when selecting the chunks of a table scan.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct PruningStatistics {
+    /// Partitions with buffered data or parquet files.
+    pub partitions_considered: usize,
+
+    /// Partitions pruned using the partition column ranges.
+    pub partitions_pruned: usize,
+
+    /// Parquet files in the catalog for the table.
+    pub parquet_files_considered: usize,
+
+    /// Parquet files skipped because the ingester returned their data.
+    pub parquet_files_persisting: usize,
+
+    /// Parquet files pruned with their partition.
+    pub parquet_files_pruned_early: usize,
+
+    /// Parquet files pruned using the file statistics.
+    pub parquet_files_pruned_late: usize,
+
+    /// Parquet files scanned.
+    pub parquet_files_scanned: usize,
+
+    /// Partitions returned by the ingesters.
+    pub ingester_partitions: usize,
+
+    /// Chunks of ingester data scanned.
+    pub ingester_chunks_scanned: usize,
+}
+
+/// A pass-through operator reporting the [`PruningStatistics`] of the table
+/// scan beneath it.
+///
+/// The statistics are included in the plan description, and as metrics in
+/// `EXPLAIN ANALYZE` output.
+pub(crate) struct PruningStatisticsExec {
+    input: Arc<dyn ExecutionPlan>,
+    table_name: Arc<str>,
+    stats: PruningStatistics,
+    metrics: ExecutionPlanMetricsSet,
+}
+
+impl PruningStatisticsExec {
+    pub(crate) fn new(
+        input: Arc<dyn ExecutionPlan>,
+        table_name: Arc<str>,
+        stats: PruningStatistics,
+    ) -> Self {
+        let metrics = ExecutionPlanMetricsSet::new();
+        for (name, value) in [
+            ("partitions_considered", stats.partitions_considered),
+            ("partitions_pruned", stats.partitions_pruned),
+            ("parquet_files_considered", stats.parquet_files_considered),
+            ("parquet_files_persisting", stats.parquet_files_persisting),
+            (
+                "parquet_files_pruned_early",
+                stats.parquet_files_pruned_early,
+            ),
+            ("parquet_files_pruned_late", stats.parquet_files_pruned_late),
+            ("parquet_files_scanned", stats.parquet_files_scanned),
+            ("ingester_partitions", stats.ingester_partitions),
+            ("ingester_chunks_scanned", stats.ingester_chunks_scanned),
+        ] {
+            MetricBuilder::new(&metrics).global_counter(name).add(value);
+        }
+
+        Self {
+            input,
+            table_name,
+            stats,
+            metrics,
+        }
+    }
+}
+
+impl fmt::Debug for PruningStatisticsExec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_as(DisplayFormatType::Default, f)
+    }
+}
+
+impl ExecutionPlan for PruningStatisticsExec {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.input.schema()
+    }
+
+    fn output_partitioning(&self) -> Partitioning {
+        self.input.output_partitioning()
+    }
+
+    fn output_ordering(&self) -> Option<&[PhysicalSortExpr]> {
+        self.input.output_ordering()
+    }
+
+    fn equivalence_properties(&self) -> EquivalenceProperties {
+        self.input.equivalence_properties()
+    }
+
+    fn maintains_input_order(&self) -> Vec<bool> {
+        vec![true]
+    }
+
+    fn benefits_from_input_partitioning(&self) -> bool {
+        false
+    }
+
+    fn children(&self) -> Vec<Arc<dyn ExecutionPlan>> {
+        vec![Arc::clone(&self.input)]
+    }
+
+    fn with_new_children(
+        self: Arc<Self>,
+        children: Vec<Arc<dyn ExecutionPlan>>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        assert_eq!(children.len(), 1);
+        Ok(Arc::new(Self {
+            input: Arc::clone(&children[0]),
+            table_name: Arc::clone(&self.table_name),
+            stats: self.stats,
+            metrics: self.metrics.clone(),
+        }))
+    }
+
+    fn execute(
+        &self,
+        partition: usize,
+        context: Arc<TaskContext>,
+    ) -> Result<SendableRecordBatchStream> {
+        self.input.execute(partition, context)
+    }
+
+    fn metrics(&self) -> Option<MetricsSet> {
+        Some(self.metrics.clone_inner())
+    }
+
+    fn statistics(&self) -> Statistics {
+        self.input.statistics()
+    }
+}
+
+impl DisplayAs for PruningStatisticsExec {
+    fn fmt_as(&self, t: DisplayFormatType, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match t {
+            DisplayFormatType::Default | DisplayFormatType::Verbose => {
+                let s = &self.stats;
+                write!(
+                    f,
+                    "PruningStatisticsExec: table={}, \
+                    partitions=[considered={}, pruned={}], \
+                    parquet_files=[considered={}, persisting={}, pruned_early={}, pruned_late={}, scanned={}], \
+                    ingester=[partitions={}, chunks_scanned={}]",
+                    self.table_name,
+                    s.partitions_considered,
+                    s.partitions_pruned,
+                    s.parquet_files_considered,
+                    s.parquet_files_persisting,
+                    s.parquet_files_pruned_early,
+                    s.parquet_files_pruned_late,
+                    s.parquet_files_scanned,
+                    s.ingester_partitions,
+                    s.ingester_chunks_scanned,
+                )
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow::datatypes::Schema;
+    use datafusion::physical_plan::{displayable, empty::EmptyExec};
+
+    use super::*;
+
+    #[test]
+    fn test_display() {
+        let input = Arc::new(EmptyExec::new(false, Arc::new(Schema::empty())));
+        let exec = PruningStatisticsExec::new(
+            input,
+            Arc::from("cpu"),
+            PruningStatistics {
+                partitions_considered: 4,
+                partitions_pruned: 3,
+                parquet_files_considered: 10,
+                parquet_files_persisting: 1,
+                parquet_files_pruned_early: 6,
+                parquet_files_pruned_late: 2,
+                parquet_files_scanned: 1,
+                ingester_partitions: 2,
+                ingester_chunks_scanned: 2,
+            },
+        );
+
+        insta::assert_snapshot!(
+            displayable(&exec).one_line(),
+            @"PruningStatisticsExec: table=cpu, partitions=[considered=4, pruned=3], parquet_files=[considered=10, persisting=1, pruned_early=6, pruned_late=2, scanned=1], ingester=[partitions=2, chunks_scanned=2]"
+        );
+
+        let metrics = exec.metrics().unwrap();
+        assert_eq!(
+            metrics
+                .sum_by_name("parquet_files_pruned_late")
+                .unwrap()
+                .as_usize(),
+            2
+        );
+        assert_eq!(
+            metrics.sum_by_name("partitions_pruned").unwrap().as_usize(),
+            3
+        );
+    }
+}
@@ -3,16 +3,44 @@ use std::{any::Any, sync::Arc};
 use arrow::datatypes::SchemaRef;
 use async_trait::async_trait;
 use datafusion::{
+    common::Column,
     datasource::{TableProvider, TableType},
     error::DataFusionError,
     execution::context::SessionState,
     logical_expr::TableProviderFilterPushDown,
     physical_plan::ExecutionPlan,
-    prelude::Expr,
+    prelude::{lit, Expr},
 };
-use iox_query::{exec::SessionContextIOxExt, provider::ProviderBuilder, pruning::retention_expr};
+use datafusion_util::lit_dict;
+use iox_query::{
+    config::IoxConfigExt,
+    exec::{RowFilters, SessionContextIOxExt},
+    provider::ProviderBuilder,
+    pruning::retention_expr,
+};
+use schema::InfluxColumnType;
+
+use super::{pruning_stats::PruningStatisticsExec, QuerierTable};
 
-use super::QuerierTable;
+impl QuerierTable {
+    /// Return the predicates restricting the rows read from this table to
+    /// those matching `row_filters`.
+    ///
+    /// A filter on a column that is not a tag of this table matches no rows.
+    fn row_filter_exprs(&self, row_filters: &RowFilters) -> Vec<Expr> {
+        row_filters
+            .iter()
+            .map(
+                |(column, value)| match self.schema().field_by_name(column) {
+                    Some((InfluxColumnType::Tag, _)) => {
+                        Expr::Column(Column::from_name(column)).eq(lit_dict(value))
+                    }
+                    _ => lit(false),
+                },
+            )
+            .collect()
+    }
+}
 
 #[async_trait]
 impl TableProvider for QuerierTable {
@@ -35,6 +63,10 @@ impl TableProvider for QuerierTable {
         filters: &[Expr],
         limit: Option<usize>,
     ) -> Result<Arc<dyn ExecutionPlan>, DataFusionError> {
+        // reject the query before the retention filter is added, which would
+        // otherwise always bound the time range
+        self.check_denylist(filters)?;
+
         // build provider out of all chunks
         // TODO: push down some predicates to catalog
 
@@ -59,9 +91,13 @@ impl TableProvider for QuerierTable {
             }
             None => filters.to_vec(),
         };
+        let filters = filters
+            .into_iter()
+            .chain(self.row_filter_exprs(&ctx.row_filters()))
+            .collect::<Vec<_>>();
 
-        let chunks = self
-            .chunks(&filters, ctx.child_span("QuerierTable chunks"), projection)
+        let (chunks, stats) = self
+            .chunks_with_statistics(&filters, ctx.child_span("QuerierTable chunks"), projection)
             .await?;
 
         for chunk in chunks {
@@ -73,7 +109,23 @@ impl TableProvider for QuerierTable {
             Err(e) => panic!("unexpected error: {e:?}"),
         };
 
-        provider.scan(ctx, projection, &filters, limit).await
+        let plan = provider.scan(ctx, projection, &filters, limit).await?;
+
+        let explain_pruning_statistics = ctx
+            .config_options()
+            .extensions
+            .get::<IoxConfigExt>()
+            .map(|config| config.explain_pruning_statistics)
+            .unwrap_or_default();
+        if !explain_pruning_statistics {
+            return Ok(plan);
+        }
+
+        Ok(Arc::new(PruningStatisticsExec::new(
+            plan,
+            Arc::clone(self.table_name()),
+            stats,
+        )))
     }
 
     fn supports_filter_pushdown(
@@ -56,6 +56,7 @@ pub async fn querier_table(catalog: &Arc<TestCatalog>, table: &Arc<TestTable>) -
         ingester_connection: Some(create_ingester_connection_for_testing()),
         chunk_adapter,
         prune_metrics: Arc::new(PruneMetrics::new(&catalog.metric_registry())),
+        query_denylist: Default::default(),
     })
 }
 
@@ -0,0 +1,251 @@
+//! Conversion of the results of task queries to line protocol.
+
+use arrow::{
+    array::{
+        as_boolean_array, as_dictionary_array, as_primitive_array, as_string_array, Array,
+        ArrayAccessor, StringArray,
+    },
+    datatypes::{
+        DataType, Float64Type, Int32Type, Int64Type, TimeUnit, TimestampNanosecondType, UInt64Type,
+    },
+    record_batch::RecordBatch,
+};
+use influxdb_line_protocol::{
+    builder::FieldValue, FieldValue as LPFieldValue, LineProtocolBuilder,
+};
+use schema::TIME_COLUMN_NAME;
+use snafu::{OptionExt, Snafu};
+
+/// Why the results of a task query might not be written
+#[allow(missing_docs)]
+#[derive(Debug, Snafu)]
+pub enum Error {
+    #[snafu(display("query results have no `{TIME_COLUMN_NAME}` timestamp column"))]
+    MissingTime,
+
+    #[snafu(display("`{TIME_COLUMN_NAME}` is NULL in row {row}"))]
+    NullTime { row: usize },
+
+    #[snafu(display("column `{column}` has unsupported type {data_type}"))]
+    UnsupportedType { column: String, data_type: DataType },
+}
+
+/// How a column of the query results is written.
+#[derive(Debug, Clone, Copy)]
+enum ColumnKind {
+    Tag,
+    Field,
+}
+
+/// Convert the rows of `batches` to line protocol lines of `measurement`,
+/// returning them along with the number of lines.
+///
+/// The `time` column (a nanosecond timestamp) is the timestamp of the lines,
+/// dictionary-encoded string columns (such as selected tags) are written as
+/// tags, and float, integer, unsigned integer, string and boolean columns as
+/// fields. Rows with no non-NULL field are skipped.
+pub(crate) fn batches_to_lines(
+    measurement: &str,
+    batches: &[RecordBatch],
+) -> Result<(String, usize), Error> {
+    let mut builder = LineProtocolBuilder::new();
+    let mut lines = 0;
+
+    for batch in batches {
+        let schema = batch.schema();
+        let mut time = None;
+        let mut columns = Vec::new();
+        for (index, field) in schema.fields().iter().enumerate() {
+            let kind = match field.data_type() {
+                DataType::Timestamp(TimeUnit::Nanosecond, _)
+                    if field.name() == TIME_COLUMN_NAME =>
+                {
+                    time = Some(as_primitive_array::<TimestampNanosecondType>(
+                        batch.column(index),
+                    ));
+                    continue;
+                }
+                DataType::Dictionary(k, v)
+                    if k.as_ref() == &DataType::Int32 && v.as_ref() == &DataType::Utf8 =>
+                {
+                    ColumnKind::Tag
+                }
+                DataType::Float64
+                | DataType::Int64
+                | DataType::UInt64
+                | DataType::Utf8
+                | DataType::Boolean => ColumnKind::Field,
+                data_type => {
+                    return UnsupportedTypeSnafu {
+                        column: field.name(),
+                        data_type: data_type.clone(),
+                    }
+                    .fail()
+                }
+            };
+            columns.push((field.name().as_str(), kind, batch.column(index).as_ref()));
+        }
+        let time = time.context(MissingTimeSnafu)?;
+
+        for row in 0..batch.num_rows() {
+            if !time.is_valid(row) {
+                return NullTimeSnafu { row }.fail();
+            }
+
+            let mut tags = Vec::new();
+            let mut fields = Vec::new();
+            for (name, kind, array) in columns.iter().copied() {
+                if !array.is_valid(row) {
+                    continue;
+                }
+                match kind {
+                    ColumnKind::Tag => {
+                        let values = as_dictionary_array::<Int32Type>(array)
+                            .downcast_dict::<StringArray>()
+                            .expect("tag is a string dictionary");
+                        tags.push((name, values.value(row)));
+                    }
+                    ColumnKind::Field => fields.push(FieldColumn {
+                        name,
+                        value: field_value(array, row),
+                    }),
+                }
+            }
+
+            let mut fields = fields.into_iter();
+            let Some(first) = fields.next() else {
+                continue;
+            };
+
+            let line = tags
+                .into_iter()
+                .fold(builder.measurement(measurement), |line, (tag, value)| {
+                    line.tag(tag, value)
+                });
+            let line = fields.fold(line.field(first.name, first.clone()), |line, field| {
+                line.field(field.name, field.clone())
+            });
+            builder = line.timestamp(time.value(row)).close_line();
+            lines += 1;
+        }
+    }
+
+    let lp = String::from_utf8(builder.build()).expect("line protocol is valid UTF-8");
+    Ok((lp, lines))
+}
+
+/// Return the value of the field column `array` in `row`.
+fn field_value(array: &dyn Array, row: usize) -> LPFieldValue<'_> {
+    match array.data_type() {
+        DataType::Float64 => LPFieldValue::F64(as_primitive_array::<Float64Type>(array).value(row)),
+        DataType::Int64 => LPFieldValue::I64(as_primitive_array::<Int64Type>(array).value(row)),
+        DataType::UInt64 => LPFieldValue::U64(as_primitive_array::<UInt64Type>(array).value(row)),
+        DataType::Utf8 => LPFieldValue::String(as_string_array(array).value(row).into()),
+        DataType::Boolean => LPFieldValue::Boolean(as_boolean_array(array).value(row)),
+        t => unreachable!("unsupported field type {t}"),
+    }
+}
+
+/// A field value of a row, formatted by the line protocol builder.
+#[derive(Debug, Clone)]
+struct FieldColumn<'a> {
+    name: &'a str,
+    value: LPFieldValue<'a>,
+}
+
+impl<'a> FieldValue for FieldColumn<'a> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.value {
+            LPFieldValue::I64(v) => v.fmt(f),
+            LPFieldValue::U64(v) => v.fmt(f),
+            LPFieldValue::F64(v) => v.fmt(f),
+            LPFieldValue::String(v) => v.as_str().fmt(f),
+            LPFieldValue::Boolean(v) => v.fmt(f),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use arrow::{
+        array::{
+            ArrayRef, BooleanArray, DictionaryArray, Float64Array, Int64Array,
+            TimestampNanosecondArray, UInt64Array,
+        },
+        datatypes::Date32Type,
+    };
+
+    use super::*;
+
+    fn time_array(values: &[i64]) -> ArrayRef {
+        Arc::new(TimestampNanosecondArray::from(values.to_vec()))
+    }
+
+    #[test]
+    fn test_batches_to_lines() {
+        let host: DictionaryArray<Int32Type> =
+            vec![Some("a"), None, Some("c")].into_iter().collect();
+        let batch = RecordBatch::try_from_iter(vec![
+            ("host", Arc::new(host) as ArrayRef),
+            (
+                "avg",
+                Arc::new(Float64Array::from(vec![Some(1.5), Some(2.0), None])) as ArrayRef,
+            ),
+            (
+                "n",
+                Arc::new(Int64Array::from(vec![Some(3), None, None])) as ArrayRef,
+            ),
+            (
+                "u",
+                Arc::new(UInt64Array::from(vec![Some(4), None, None])) as ArrayRef,
+            ),
+            (
+                "s",
+                Arc::new(StringArray::from(vec![Some("x \"y\""), None, None])) as ArrayRef,
+            ),
+            (
+                "ok",
+                Arc::new(BooleanArray::from(vec![Some(true), None, None])) as ArrayRef,
+            ),
+            ("time", time_array(&[100, 200, 300])),
+        ])
+        .unwrap();
+
+        let (lp, lines) = batches_to_lines("cpu summary", &[batch]).unwrap();
+        // The last row has no field values and is skipped.
+        assert_eq!(lines, 2);
+        assert_eq!(
+            lp,
+            "cpu\\ summary,host=a avg=1.5,n=3i,u=4u,s=\"x \\\"y\\\"\",ok=true 100\n\
+             cpu\\ summary avg=2 200\n"
+        );
+    }
+
+    #[test]
+    fn test_batches_to_lines_errors() {
+        let batch = RecordBatch::try_from_iter(vec![(
+            "v",
+            Arc::new(Float64Array::from(vec![1.0])) as ArrayRef,
+        )])
+        .unwrap();
+        assert!(matches!(
+            batches_to_lines("m", &[batch]),
+            Err(Error::MissingTime)
+        ));
+
+        let batch = RecordBatch::try_from_iter(vec![
+            (
+                "v",
+                Arc::new(arrow::array::PrimitiveArray::<Date32Type>::from(vec![1])) as ArrayRef,
+            ),
+            ("time", time_array(&[1])),
+        ])
+        .unwrap();
+        assert!(matches!(
+            batches_to_lines("m", &[batch]),
+            Err(Error::UnsupportedType { column, .. }) if column == "v"
+        ));
+    }
+}
@@ -0,0 +1,697 @@
+//! Tasks periodically executing a SQL query against a namespace and writing
+//! the results to a table of the namespace, such as to downsample raw data.
+//!
+//! Tasks are defined in the catalog with a cron [`Schedule`]. Each querier
+//! running a [`TaskScheduler`] polls the catalog for tasks and executes those
+//! due since the previous poll, writing the query results through the router
+//! (see [`batches_to_lines`](line_protocol::batches_to_lines) for how they are
+//! converted to line protocol).
+//!
+//! Starting a run records it in the catalog for the time it was scheduled at,
+//! which fails if another querier already did: each scheduled run is executed
+//! once, even if several queriers schedule it. Failed attempts are retried up
+//! to the `max_retries` of the task, and the outcome of the run is recorded in
+//! the catalog.
+//!
+//! A run, including its retries, fails if it does not finish within the run
+//! timeout. Runs left unfinished by a querier that stopped while executing
+//! them are recorded as failed by the [`TaskScheduler`] once they started
+//! twice the run timeout ago.
+//!
+//! Runs scheduled while no querier was polling (such as during a restart) are
+//! not caught up on.
+
+mod line_protocol;
+mod schedule;
+
+pub use schedule::{Schedule, ScheduleError};
+
+use std::{fmt::Debug, sync::Arc, time::Duration};
+
+use async_trait::async_trait;
+use data_types::{NamespaceId, Task, TaskRun, Timestamp};
+use datafusion::error::DataFusionError;
+use influxdb_iox_client::connection::{Builder, Connection};
+use iox_catalog::interface::{Catalog, SoftDeletedRows};
+use iox_query::QueryNamespace;
+use iox_time::{Time, TimeProvider};
+use observability_deps::tracing::{info, warn};
+use service_common::QueryNamespaceProvider;
+use snafu::{OptionExt, ResultExt, Snafu};
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+
+use crate::QuerierDatabase;
+
+/// The delay before the first retry of a failed attempt, doubled for each
+/// subsequent retry.
+const DEFAULT_RETRY_DELAY: Duration = Duration::from_secs(1);
+
+/// The default duration a run, including its retries, may take.
+pub const DEFAULT_RUN_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Boxed error returned by a [`TaskSink`].
+pub type TaskSinkError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Errors recording a run of a task
+#[allow(missing_docs)]
+#[derive(Debug, Snafu)]
+pub enum Error {
+    #[snafu(display("catalog error: {source}"))]
+    Catalog {
+        source: iox_catalog::interface::Error,
+    },
+}
+
+/// Why an attempt of a run might fail
+#[derive(Debug, Snafu)]
+enum AttemptError {
+    #[snafu(display("catalog error: {source}"))]
+    AttemptCatalog {
+        source: iox_catalog::interface::Error,
+    },
+
+    #[snafu(display("namespace {id} not found"))]
+    NamespaceNotFound { id: NamespaceId },
+
+    #[snafu(display("query failed: {source}"))]
+    Query { source: DataFusionError },
+
+    #[snafu(display("cannot write query results: {source}"))]
+    Convert { source: line_protocol::Error },
+
+    #[snafu(display("write to `{table}` failed: {source}"))]
+    Write {
+        table: String,
+        source: TaskSinkError,
+    },
+
+    #[snafu(display("run did not finish within {timeout:?}"))]
+    Timeout { timeout: Duration },
+}
+
+/// The destination of the line protocol written by tasks.
+#[async_trait]
+pub trait TaskSink: Debug + Send + Sync {
+    /// Write `lp` to `namespace`.
+    async fn write_lp(&self, namespace: &str, lp: String) -> Result<(), TaskSinkError>;
+}
+
+/// A [`TaskSink`] writing through the HTTP write API of a router.
+#[derive(Debug)]
+pub struct RouterTaskSink {
+    address: String,
+    token: Option<String>,
+    connection: tokio::sync::Mutex<Option<Connection>>,
+}
+
+impl RouterTaskSink {
+    /// Write to the router at `address` (such as `http://router:8080`),
+    /// connecting on the first write.
+    pub fn new(address: impl Into<String>) -> Self {
+        Self {
+            address: address.into(),
+            token: None,
+            connection: Default::default(),
+        }
+    }
+
+    /// Authorize the writes with `token`, sent in an
+    /// `Authorization: Token <token>` header.
+    pub fn with_token(self, token: impl Into<String>) -> Self {
+        Self {
+            token: Some(token.into()),
+            ..self
+        }
+    }
+
+    async fn connect(&self) -> Result<Connection, TaskSinkError> {
+        let mut connection = self.connection.lock().await;
+        if let Some(c) = connection.as_ref() {
+            return Ok(c.clone());
+        }
+
+        let mut builder = Builder::new();
+        if let Some(token) = &self.token {
+            builder = builder.header(
+                http::header::AUTHORIZATION,
+                http::HeaderValue::from_str(&format!("Token {token}"))?,
+            );
+        }
+        let c = builder.build(self.address.as_str()).await?;
+        *connection = Some(c.clone());
+        Ok(c)
+    }
+}
+
+#[async_trait]
+impl TaskSink for RouterTaskSink {
+    async fn write_lp(&self, namespace: &str, lp: String) -> Result<(), TaskSinkError> {
+        let connection = self.connect().await?;
+        let res = influxdb_iox_client::write::Client::new(connection)
+            .write_lp(namespace, lp)
+            .await;
+        if res.is_err() {
+            // Reconnect on the next write.
+            *self.connection.lock().await = None;
+        }
+        res.map(|_| ()).map_err(Into::into)
+    }
+}
+
+/// Executes the runs of tasks, recording them in the catalog.
+#[derive(Debug)]
+pub struct TaskExecutor {
+    database: Arc<QuerierDatabase>,
+    catalog: Arc<dyn Catalog>,
+    time_provider: Arc<dyn TimeProvider>,
+    sink: Arc<dyn TaskSink>,
+    retry_delay: Duration,
+    run_timeout: Duration,
+}
+
+impl TaskExecutor {
+    /// Execute the queries of tasks against `database`, writing the results
+    /// to `sink`.
+    pub fn new(database: Arc<QuerierDatabase>, sink: Arc<dyn TaskSink>) -> Self {
+        Self {
+            catalog: database.catalog(),
+            time_provider: database.time_provider(),
+            database,
+            sink,
+            retry_delay: DEFAULT_RETRY_DELAY,
+            run_timeout: DEFAULT_RUN_TIMEOUT,
+        }
+    }
+
+    /// Set the delay before the first retry of a failed attempt.
+    pub fn with_retry_delay(self, retry_delay: Duration) -> Self {
+        Self {
+            retry_delay,
+            ..self
+        }
+    }
+
+    /// Set the duration a run, including its retries, may take before it is
+    /// stopped and recorded as failed.
+    pub fn with_run_timeout(self, run_timeout: Duration) -> Self {
+        Self {
+            run_timeout,
+            ..self
+        }
+    }
+
+    /// The duration a run may take.
+    pub fn run_timeout(&self) -> Duration {
+        self.run_timeout
+    }
+
+    /// Execute the run of `task` scheduled at `scheduled_at`, returning it
+    /// once finished.
+    ///
+    /// Returns [`None`] without executing the query if the run was already
+    /// started.
+    pub async fn run(&self, task: &Task, scheduled_at: Time) -> Result<Option<TaskRun>, Error> {
+        let scheduled_at = Timestamp::from(scheduled_at);
+        let started = self
+            .catalog
+            .repositories()
+            .await
+            .tasks()
+            .start_run(task.id, scheduled_at)
+            .await
+            .context(CatalogSnafu)?;
+        if started.is_none() {
+            return Ok(None);
+        }
+
+        let mut attempts = 0;
+        let mut retry_delay = self.retry_delay;
+        let run = async {
+            loop {
+                attempts += 1;
+                match self.attempt(task).await {
+                    Ok(rows) => break Ok(rows),
+                    Err(e) if attempts <= task.max_retries => {
+                        warn!(
+                            task_id=%task.id,
+                            task=%task.name,
+                            attempts,
+                            error=%e,
+                            "task run failed, retrying"
+                        );
+                        tokio::time::sleep(retry_delay).await;
+                        retry_delay *= 2;
+                    }
+                    Err(e) => break Err(e),
+                }
+            }
+        };
+        let res = match tokio::time::timeout(self.run_timeout, run).await {
+            Ok(res) => res,
+            Err(_) => TimeoutSnafu {
+                timeout: self.run_timeout,
+            }
+            .fail(),
+        };
+
+        let (rows_written, error) = match &res {
+            Ok(rows) => {
+                info!(task_id=%task.id, task=%task.name, attempts, rows, "task run succeeded");
+                (*rows as i64, None)
+            }
+            Err(e) => {
+                warn!(task_id=%task.id, task=%task.name, attempts, error=%e, "task run failed");
+                (0, Some(e.to_string()))
+            }
+        };
+
+        self.catalog
+            .repositories()
+            .await
+            .tasks()
+            .finish_run(
+                task.id,
+                scheduled_at,
+                attempts,
+                rows_written,
+                error.as_deref(),
+            )
+            .await
+            .map(Some)
+            .context(CatalogSnafu)
+    }
+
+    /// Execute `task` now, outside of its schedule.
+    pub async fn run_now(&self, task: &Task) -> Result<Option<TaskRun>, Error> {
+        self.run(task, self.time_provider.now()).await
+    }
+
+    /// Execute the query of `task` and write its results, returning the
+    /// number of rows written.
+    async fn attempt(&self, task: &Task) -> Result<usize, AttemptError> {
+        let namespace = self
+            .catalog
+            .repositories()
+            .await
+            .namespaces()
+            .get_by_id(task.namespace_id, SoftDeletedRows::ExcludeDeleted)
+            .await
+            .context(AttemptCatalogSnafu)?
+            .context(NamespaceNotFoundSnafu {
+                id: task.namespace_id,
+            })?;
+
+        let _permit = self.database.acquire_semaphore(None).await;
+        let ns = self
+            .database
+            .namespace(&namespace.name, None, false)
+            .await
+            .context(NamespaceNotFoundSnafu {
+                id: task.namespace_id,
+            })?;
+
+        let mut token = ns.record_query(None, "sql", Box::new(task.query.clone()));
+        let ctx = ns.new_query_context(None);
+        let plan = ctx
+            .sql_to_physical_plan(&task.query)
+            .await
+            .context(QuerySnafu)?;
+        let batches = ctx.collect(plan).await.context(QuerySnafu)?;
+        token.set_success();
+
+        let (lp, rows) = line_protocol::batches_to_lines(&task.destination_table, &batches)
+            .context(ConvertSnafu)?;
+        if rows > 0 {
+            self.sink
+                .write_lp(&namespace.name, lp)
+                .await
+                .context(WriteSnafu {
+                    table: &task.destination_table,
+                })?;
+        }
+
+        Ok(rows)
+    }
+}
+
+/// Polls the catalog for tasks, executing them on their schedule.
+#[derive(Debug)]
+pub struct TaskScheduler {
+    executor: Arc<TaskExecutor>,
+    poll_interval: Duration,
+}
+
+impl TaskScheduler {
+    /// Execute the tasks with `executor`, polling the catalog every
+    /// `poll_interval`.
+    pub fn new(executor: Arc<TaskExecutor>, poll_interval: Duration) -> Self {
+        Self {
+            executor,
+            poll_interval,
+        }
+    }
+
+    /// Schedule tasks until `shutdown` is cancelled.
+    pub async fn run(self, shutdown: CancellationToken) {
+        let mut last_poll = self.executor.time_provider.now();
+        loop {
+            tokio::select! {
+                _ = shutdown.cancelled() => return,
+                _ = tokio::time::sleep(self.poll_interval) => {}
+            }
+
+            let now = self.executor.time_provider.now();
+            // Runs are detached, so that slow tasks do not delay others.
+            match self.poll(last_poll, now).await {
+                Ok(_) => last_poll = now,
+                Err(e) => warn!(error=%e, "failed to list tasks"),
+            }
+        }
+    }
+
+    /// Record the runs started more than twice the run timeout before `end`
+    /// and not yet finished as failed, then start the runs of the tasks
+    /// scheduled in `(start, end]`, returning their handles.
+    async fn poll(&self, start: Time, end: Time) -> Result<Vec<JoinHandle<()>>, Error> {
+        let mut repos = self.executor.catalog.repositories().await;
+
+        // The querier executing these runs would have recorded them as
+        // failed itself if it were still running.
+        if let Some(started_before) = end.checked_sub(self.executor.run_timeout * 2) {
+            let stale = repos
+                .tasks()
+                .fail_stale_runs(
+                    started_before.into(),
+                    "run was abandoned by the querier executing it",
+                )
+                .await
+                .context(CatalogSnafu)?;
+            for run in stale {
+                warn!(
+                    task_id=%run.task_id,
+                    scheduled_at=%run.scheduled_at.get(),
+                    "abandoned task run failed"
+                );
+            }
+        }
+
+        let tasks = repos.tasks().list().await.context(CatalogSnafu)?;
+        drop(repos);
+
+        let mut handles = Vec::new();
+        for task in tasks {
+            let schedule = match task.schedule.parse::<Schedule>() {
+                Ok(s) => s,
+                Err(e) => {
+                    warn!(task_id=%task.id, task=%task.name, error=%e, "invalid task schedule");
+                    continue;
+                }
+            };
+
+            for scheduled_at in schedule.times_between(start, end) {
+                let executor = Arc::clone(&self.executor);
+                let task = task.clone();
+                handles.push(tokio::spawn(async move {
+                    if let Err(e) = executor.run(&task, scheduled_at).await {
+                        warn!(task_id=%task.id, task=%task.name, error=%e, "failed to record task run");
+                    }
+                }));
+            }
+        }
+
+        Ok(handles)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use data_types::{ColumnType, TaskRunStatus};
+    use iox_tests::{TestCatalog, TestParquetFileBuilder};
+    use parking_lot::Mutex;
+    use tokio::runtime::Handle;
+
+    use super::*;
+    use crate::{cache::CatalogCache, create_ingester_connection_for_testing};
+
+    /// A [`TaskSink`] recording the writes, failing the first `failures`.
+    #[derive(Debug, Default)]
+    struct MockSink {
+        failures: Mutex<usize>,
+        writes: Mutex<Vec<(String, String)>>,
+    }
+
+    #[async_trait]
+    impl TaskSink for MockSink {
+        async fn write_lp(&self, namespace: &str, lp: String) -> Result<(), TaskSinkError> {
+            let mut failures = self.failures.lock();
+            if *failures > 0 {
+                *failures -= 1;
+                return Err("bananas".into());
+            }
+            self.writes.lock().push((namespace.to_string(), lp));
+            Ok(())
+        }
+    }
+
+    /// A [`TaskSink`] whose writes never complete.
+    #[derive(Debug)]
+    struct StallSink;
+
+    #[async_trait]
+    impl TaskSink for StallSink {
+        async fn write_lp(&self, _namespace: &str, _lp: String) -> Result<(), TaskSinkError> {
+            std::future::pending().await
+        }
+    }
+
+    async fn setup(failures: usize) -> (Arc<TestCatalog>, Arc<MockSink>, TaskExecutor) {
+        let catalog = TestCatalog::new();
+
+        let ns = catalog.create_namespace_with_retention("ns", None).await;
+        let table = ns.create_table("cpu").await;
+        table.create_column("host", ColumnType::Tag).await;
+        table.create_column("time", ColumnType::Time).await;
+        table.create_column("load", ColumnType::F64).await;
+        let builder = TestParquetFileBuilder::default()
+            .with_line_protocol("cpu,host=a load=1 11")
+            .with_min_time(11)
+            .with_max_time(11);
+        table
+            .create_partition("a")
+            .await
+            .create_parquet_file(builder)
+            .await;
+
+        let catalog_cache = Arc::new(CatalogCache::new_testing(
+            catalog.catalog(),
+            catalog.time_provider(),
+            catalog.metric_registry(),
+            catalog.object_store(),
+            &Handle::current(),
+        ));
+        let database = Arc::new(
+            QuerierDatabase::new(
+                catalog_cache,
+                catalog.metric_registry(),
+                catalog.exec(),
+                Some(create_ingester_connection_for_testing()),
+                QuerierDatabase::MAX_CONCURRENT_QUERIES_MAX,
+                Arc::new(HashMap::default()),
+            )
+            .await
+            .unwrap(),
+        );
+
+        let sink = Arc::new(MockSink {
+            failures: Mutex::new(failures),
+            ..Default::default()
+        });
+        let executor =
+            TaskExecutor::new(database, Arc::clone(&sink) as _).with_retry_delay(Duration::ZERO);
+
+        (catalog, sink, executor)
+    }
+
+    async fn create_task(catalog: &TestCatalog, schedule: &str, max_retries: i32) -> Task {
+        let mut repos = catalog.catalog().repositories().await;
+        let ns = repos
+            .namespaces()
+            .get_by_name("ns", SoftDeletedRows::ExcludeDeleted)
+            .await
+            .unwrap()
+            .unwrap();
+        repos
+            .tasks()
+            .create(
+                ns.id,
+                "double",
+                schedule,
+                "SELECT host, load * 2 AS load2, time FROM cpu",
+                "cpu_double",
+                max_retries,
+            )
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_run() {
+        let (catalog, sink, executor) = setup(1).await;
+        let task = create_task(&catalog, "* * * * *", 1).await;
+
+        let run = executor
+            .run(&task, Time::from_timestamp_nanos(60_000_000_000))
+            .await
+            .unwrap()
+            .expect("run not started");
+        assert_eq!(run.status(), TaskRunStatus::Succeeded);
+        assert_eq!(run.attempts, 2);
+        assert_eq!(run.rows_written, 1);
+        assert_eq!(
+            *sink.writes.lock(),
+            [(
+                "ns".to_string(),
+                "cpu_double,host=a load2=2 11\n".to_string()
+            )]
+        );
+
+        // A run is only executed once.
+        assert!(executor
+            .run(&task, Time::from_timestamp_nanos(60_000_000_000))
+            .await
+            .unwrap()
+            .is_none());
+        assert_eq!(sink.writes.lock().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_run_failed() {
+        let (catalog, sink, executor) = setup(3).await;
+        let task = create_task(&catalog, "* * * * *", 1).await;
+
+        let run = executor.run_now(&task).await.unwrap().unwrap();
+        assert_eq!(run.status(), TaskRunStatus::Failed);
+        assert_eq!(run.attempts, 2);
+        assert_eq!(run.rows_written, 0);
+        assert!(run.error.unwrap().contains("bananas"));
+        assert!(sink.writes.lock().is_empty());
+
+        let runs = catalog
+            .catalog()
+            .repositories()
+            .await
+            .tasks()
+            .list_runs(task.id, 10)
+            .await
+            .unwrap();
+        assert_eq!(runs.len(), 1);
+        assert_eq!(runs[0].status(), TaskRunStatus::Failed);
+    }
+
+    #[tokio::test]
+    async fn test_run_timeout() {
+        let (catalog, _sink, executor) = setup(0).await;
+        let task = create_task(&catalog, "* * * * *", 3).await;
+        let executor = TaskExecutor::new(Arc::clone(&executor.database), Arc::new(StallSink))
+            .with_retry_delay(Duration::ZERO)
+            .with_run_timeout(Duration::from_millis(10));
+
+        let run = executor.run_now(&task).await.unwrap().unwrap();
+        assert_eq!(run.status(), TaskRunStatus::Failed);
+        assert_eq!(run.attempts, 1);
+        assert!(run.error.unwrap().contains("did not finish"));
+    }
+
+    #[tokio::test]
+    async fn test_scheduler_fails_abandoned_runs() {
+        let (catalog, sink, executor) = setup(0).await;
+        let task = create_task(&catalog, "0 0 1 1 *", 0).await;
+        let scheduler = TaskScheduler::new(
+            Arc::new(executor.with_run_timeout(Duration::from_secs(10))),
+            Duration::from_secs(1),
+        );
+
+        // A run started by a querier that stopped before finishing it.
+        let abandoned = catalog
+            .catalog()
+            .repositories()
+            .await
+            .tasks()
+            .start_run(task.id, Timestamp::new(42))
+            .await
+            .unwrap()
+            .unwrap();
+        let started_at = Time::from(abandoned.started_at);
+
+        // Not failed until it started twice the run timeout ago.
+        let end = started_at + Duration::from_secs(20);
+        assert!(scheduler.poll(end, end).await.unwrap().is_empty());
+        let runs = catalog
+            .catalog()
+            .repositories()
+            .await
+            .tasks()
+            .list_runs(task.id, 10)
+            .await
+            .unwrap();
+        assert_eq!(runs[0].status(), TaskRunStatus::Running);
+
+        let end = started_at + Duration::from_secs(21);
+        assert!(scheduler.poll(end, end).await.unwrap().is_empty());
+        let runs = catalog
+            .catalog()
+            .repositories()
+            .await
+            .tasks()
+            .list_runs(task.id, 10)
+            .await
+            .unwrap();
+        assert_eq!(runs[0].status(), TaskRunStatus::Failed);
+        assert!(sink.writes.lock().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_scheduler_poll() {
+        let (catalog, sink, executor) = setup(0).await;
+        let task = create_task(&catalog, "*/10 * * * *", 0).await;
+        let scheduler = TaskScheduler::new(Arc::new(executor), Duration::from_secs(1));
+
+        let t = |s| Time::from_rfc3339(s).unwrap();
+        let handles = scheduler
+            .poll(t("2023-10-16T10:05:00Z"), t("2023-10-16T10:25:00Z"))
+            .await
+            .unwrap();
+        assert_eq!(handles.len(), 2);
+        for handle in handles {
+            handle.await.unwrap();
+        }
+        assert_eq!(sink.writes.lock().len(), 2);
+
+        let runs = catalog
+            .catalog()
+            .repositories()
+            .await
+            .tasks()
+            .list_runs(task.id, 10)
+            .await
+            .unwrap();
+        let scheduled = runs
+            .iter()
+            .map(|r| Time::from_timestamp_nanos(r.scheduled_at.get()).to_rfc3339())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            scheduled,
+            ["2023-10-16T10:20:00+00:00", "2023-10-16T10:10:00+00:00"]
+        );
+
+        // Nothing is due between the polls.
+        let handles = scheduler
+            .poll(t("2023-10-16T10:25:00Z"), t("2023-10-16T10:29:00Z"))
+            .await
+            .unwrap();
+        assert!(handles.is_empty());
+    }
+}
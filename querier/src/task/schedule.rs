@@ -0,0 +1,396 @@
+//! Parsing and evaluation of the cron schedules of tasks.
+
+use std::{fmt::Display, str::FromStr};
+
+use chrono::{DateTime, Datelike, Duration, NaiveDate, TimeZone, Timelike, Utc};
+use iox_time::Time;
+use snafu::{OptionExt, Snafu};
+
+/// The number of steps after which the search for the next time a schedule
+/// fires is abandoned, such as for `0 0 30 2 *` that never fires.
+///
+/// As each step skips at least a minute not matching the schedule (and whole
+/// hours, days or months where possible), this covers several years.
+const MAX_SEARCH_STEPS: usize = 100_000;
+
+/// Why a cron expression might be invalid
+#[allow(missing_docs)]
+#[derive(Debug, Snafu, PartialEq, Eq)]
+pub enum ScheduleError {
+    #[snafu(display(
+        "expected 5 fields (<minute> <hour> <day of month> <month> <day of week>), got {count}"
+    ))]
+    FieldCount { count: usize },
+
+    #[snafu(display("invalid {field} `{value}`"))]
+    InvalidField { field: &'static str, value: String },
+
+    #[snafu(display("{field} `{value}` out of range {min}-{max}"))]
+    OutOfRange {
+        field: &'static str,
+        value: String,
+        min: u32,
+        max: u32,
+    },
+}
+
+/// The values of a field of a cron expression matching it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct FieldSet {
+    /// Bit `n` is set if `n` matches the field.
+    bits: u64,
+    /// True if the field is `*`, matching any value.
+    any: bool,
+}
+
+impl FieldSet {
+    /// Parse a field of comma-separated `*`, `<n>` or `<n>-<m>` items, each
+    /// optionally followed by a `/<step>`, whose values are within
+    /// `min..=max`.
+    fn parse(field: &'static str, value: &str, min: u32, max: u32) -> Result<Self, ScheduleError> {
+        let invalid = || InvalidFieldSnafu {
+            field,
+            value: value.to_string(),
+        };
+        let number = |s: &str| -> Result<u32, ScheduleError> {
+            let n = s.parse::<u32>().ok().with_context(invalid)?;
+            if n < min || n > max {
+                return OutOfRangeSnafu {
+                    field,
+                    value: value.to_string(),
+                    min,
+                    max,
+                }
+                .fail();
+            }
+            Ok(n)
+        };
+
+        let mut bits = 0;
+        for item in value.split(',') {
+            let (range, step) = match item.split_once('/') {
+                Some((range, step)) => {
+                    let step = step.parse::<u32>().ok().filter(|s| *s > 0);
+                    (range, step.with_context(invalid)?)
+                }
+                None => (item, 1),
+            };
+
+            let (start, end) = match range {
+                "*" => (min, max),
+                r => match r.split_once('-') {
+                    Some((start, end)) => (number(start)?, number(end)?),
+                    None => {
+                        let n = number(r)?;
+                        // `<n>/<step>` runs from n to the end of the range.
+                        (n, if step > 1 { max } else { n })
+                    }
+                },
+            };
+            if start > end {
+                return invalid().fail();
+            }
+
+            for n in (start..=end).step_by(step as usize) {
+                bits |= 1 << n;
+            }
+        }
+
+        Ok(Self {
+            bits,
+            any: value == "*",
+        })
+    }
+
+    fn contains(&self, n: u32) -> bool {
+        self.bits & (1 << n) != 0
+    }
+}
+
+/// A parsed cron expression (`<minute> <hour> <day of month> <month> <day of
+/// week>`), evaluated in UTC.
+///
+/// Each field is a comma-separated list of `*`, values or ranges of values
+/// (`<n>-<m>`), each optionally stepped (`*/15`, `0-30/10`). Days of the week
+/// are numbered from 0 (or 7) for Sunday. As with cron, if both the day of the
+/// month and the day of the week are restricted, a day matching either
+/// matches.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Schedule {
+    expression: String,
+    minutes: FieldSet,
+    hours: FieldSet,
+    days_of_month: FieldSet,
+    months: FieldSet,
+    days_of_week: FieldSet,
+}
+
+impl FromStr for Schedule {
+    type Err = ScheduleError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let fields = s.split_whitespace().collect::<Vec<_>>();
+        let [minutes, hours, days_of_month, months, days_of_week] = fields[..] else {
+            return FieldCountSnafu {
+                count: fields.len(),
+            }
+            .fail();
+        };
+
+        let mut days_of_week = FieldSet::parse("day of week", days_of_week, 0, 7)?;
+        // Sunday is both 0 and 7.
+        if days_of_week.contains(7) {
+            days_of_week.bits |= 1;
+        }
+
+        Ok(Self {
+            expression: fields.join(" "),
+            minutes: FieldSet::parse("minute", minutes, 0, 59)?,
+            hours: FieldSet::parse("hour", hours, 0, 23)?,
+            days_of_month: FieldSet::parse("day of month", days_of_month, 1, 31)?,
+            months: FieldSet::parse("month", months, 1, 12)?,
+            days_of_week,
+        })
+    }
+}
+
+impl Display for Schedule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.expression)
+    }
+}
+
+impl Schedule {
+    /// Return the first time after `t` (exclusive) the schedule fires at, or
+    /// [`None`] if it never does.
+    pub fn next_after(&self, t: Time) -> Option<Time> {
+        let t = t.date_time();
+        let mut dt = t
+            .with_second(0)
+            .and_then(|dt| dt.with_nanosecond(0))
+            .expect("valid time")
+            + Duration::minutes(1);
+
+        for _ in 0..MAX_SEARCH_STEPS {
+            if !self.months.contains(dt.month()) {
+                let (year, month) = match dt.month() {
+                    12 => (dt.year() + 1, 1),
+                    m => (dt.year(), m + 1),
+                };
+                dt = midnight(NaiveDate::from_ymd_opt(year, month, 1)?);
+                continue;
+            }
+            if !self.day_matches(&dt) {
+                dt = midnight(dt.date_naive().succ_opt()?);
+                continue;
+            }
+            if !self.hours.contains(dt.hour()) {
+                dt = dt.with_minute(0)? + Duration::hours(1);
+                continue;
+            }
+            if !self.minutes.contains(dt.minute()) {
+                dt += Duration::minutes(1);
+                continue;
+            }
+            return Some(Time::from_date_time(dt));
+        }
+
+        None
+    }
+
+    /// Return the times in `(start, end]` the schedule fires at.
+    pub fn times_between(&self, start: Time, end: Time) -> Vec<Time> {
+        let mut times = Vec::new();
+        let mut t = start;
+        while let Some(next) = self.next_after(t).filter(|next| *next <= end) {
+            times.push(next);
+            t = next;
+        }
+        times
+    }
+
+    fn day_matches(&self, dt: &DateTime<Utc>) -> bool {
+        let dom = self.days_of_month.contains(dt.day());
+        let dow = self
+            .days_of_week
+            .contains(dt.weekday().num_days_from_sunday());
+        match (self.days_of_month.any, self.days_of_week.any) {
+            (true, true) => true,
+            (true, false) => dow,
+            (false, true) => dom,
+            (false, false) => dom || dow,
+        }
+    }
+}
+
+fn midnight(date: NaiveDate) -> DateTime<Utc> {
+    Utc.from_utc_datetime(&date.and_hms_opt(0, 0, 0).expect("valid time"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn time(s: &str) -> Time {
+        Time::from_rfc3339(s).unwrap()
+    }
+
+    fn next(schedule: &str, after: &str) -> Option<String> {
+        schedule
+            .parse::<Schedule>()
+            .unwrap()
+            .next_after(time(after))
+            .map(|t| t.to_rfc3339())
+    }
+
+    #[test]
+    fn test_next_after() {
+        for (schedule, after, want) in [
+            (
+                "* * * * *",
+                "2023-10-16T10:00:00Z",
+                "2023-10-16T10:01:00+00:00",
+            ),
+            (
+                "* * * * *",
+                "2023-10-16T10:00:30Z",
+                "2023-10-16T10:01:00+00:00",
+            ),
+            (
+                "*/15 * * * *",
+                "2023-10-16T10:07:00Z",
+                "2023-10-16T10:15:00+00:00",
+            ),
+            (
+                "*/15 * * * *",
+                "2023-10-16T10:45:00Z",
+                "2023-10-16T11:00:00+00:00",
+            ),
+            (
+                "5,35 * * * *",
+                "2023-10-16T10:05:00Z",
+                "2023-10-16T10:35:00+00:00",
+            ),
+            (
+                "0 * * * *",
+                "2023-10-16T23:30:00Z",
+                "2023-10-17T00:00:00+00:00",
+            ),
+            (
+                "30 9-17/4 * * *",
+                "2023-10-16T13:31:00Z",
+                "2023-10-16T17:30:00+00:00",
+            ),
+            (
+                "0 0 1 * *",
+                "2023-12-15T00:00:00Z",
+                "2024-01-01T00:00:00+00:00",
+            ),
+            (
+                "0 0 29 2 *",
+                "2023-01-01T00:00:00Z",
+                "2024-02-29T00:00:00+00:00",
+            ),
+            // 2023-10-16 is a Monday.
+            (
+                "0 12 * * 0",
+                "2023-10-16T00:00:00Z",
+                "2023-10-22T12:00:00+00:00",
+            ),
+            (
+                "0 12 * * 7",
+                "2023-10-16T00:00:00Z",
+                "2023-10-22T12:00:00+00:00",
+            ),
+            (
+                "0 12 * * 1-5",
+                "2023-10-16T12:00:00Z",
+                "2023-10-17T12:00:00+00:00",
+            ),
+            // Either the day of the month or the day of the week matches.
+            (
+                "0 0 20 * 3",
+                "2023-10-16T00:00:00Z",
+                "2023-10-18T00:00:00+00:00",
+            ),
+        ] {
+            assert_eq!(
+                next(schedule, after).as_deref(),
+                Some(want),
+                "{schedule} after {after}"
+            );
+        }
+
+        assert_eq!(next("0 0 30 2 *", "2023-01-01T00:00:00Z"), None);
+    }
+
+    #[test]
+    fn test_times_between() {
+        let schedule = "*/20 * * * *".parse::<Schedule>().unwrap();
+        let got = schedule
+            .times_between(time("2023-10-16T10:00:00Z"), time("2023-10-16T11:00:00Z"))
+            .into_iter()
+            .map(|t| t.to_rfc3339())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            got,
+            [
+                "2023-10-16T10:20:00+00:00",
+                "2023-10-16T10:40:00+00:00",
+                "2023-10-16T11:00:00+00:00",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        for (schedule, want) in [
+            ("* * * *", ScheduleError::FieldCount { count: 4 }),
+            (
+                "60 * * * *",
+                ScheduleError::OutOfRange {
+                    field: "minute",
+                    value: "60".to_string(),
+                    min: 0,
+                    max: 59,
+                },
+            ),
+            (
+                "* * 0 * *",
+                ScheduleError::OutOfRange {
+                    field: "day of month",
+                    value: "0".to_string(),
+                    min: 1,
+                    max: 31,
+                },
+            ),
+            (
+                "*/0 * * * *",
+                ScheduleError::InvalidField {
+                    field: "minute",
+                    value: "*/0".to_string(),
+                },
+            ),
+            (
+                "* 5-2 * * *",
+                ScheduleError::InvalidField {
+                    field: "hour",
+                    value: "5-2".to_string(),
+                },
+            ),
+            (
+                "* * * bananas *",
+                ScheduleError::InvalidField {
+                    field: "month",
+                    value: "bananas".to_string(),
+                },
+            ),
+        ] {
+            assert_eq!(
+                schedule.parse::<Schedule>().unwrap_err(),
+                want,
+                "{schedule}"
+            );
+        }
+    }
+}
@@ -0,0 +1,579 @@
+use std::{sync::Arc, time::Duration};
+
+use async_trait::async_trait;
+use data_types::{NamespaceDefaultTag, NamespaceId, NamespaceName, NamespaceSchema, Statistics};
+use futures::{
+    future::{BoxFuture, Shared},
+    FutureExt,
+};
+use hashbrown::HashMap;
+use iox_catalog::interface::Catalog;
+use iox_time::{SystemProvider, Time, TimeProvider};
+use mutable_batch::MutableBatch;
+use observability_deps::tracing::*;
+use parking_lot::Mutex;
+use schema::InfluxColumnType;
+use thiserror::Error;
+use trace::ctx::SpanContext;
+
+use super::DmlHandler;
+
+/// The default duration the cached default tags of a namespace are
+/// considered valid for before being refreshed from the catalog.
+pub const DEFAULT_TAGS_CACHE_TTL: Duration = Duration::from_secs(10);
+
+/// The cached default tags of a namespace not refreshed for this many
+/// multiples of the cache TTL (that is, not written to for at least
+/// `IDLE_TTL_MULTIPLE - 1` TTLs) are evicted.
+const IDLE_TTL_MULTIPLE: u32 = 6;
+
+/// The handling of a written row already carrying a default tag of its
+/// namespace.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum DefaultTagConflict {
+    /// Keep the value of the write, adding the default value to the rows
+    /// without one.
+    #[default]
+    Keep,
+
+    /// Replace the value of the write with the default value.
+    Overwrite,
+
+    /// Reject writes carrying a value other than the default value.
+    Reject,
+}
+
+/// Errors emitted when adding the default tags of a namespace to a write.
+#[derive(Debug, Error)]
+pub enum DefaultTagError {
+    /// The write carries a different value for a default tag, and conflicts
+    /// are rejected.
+    #[error("table {table} sets tag {tag} to a value other than the namespace default {value}")]
+    Conflict {
+        /// The table written to.
+        table: String,
+        /// The name of the default tag.
+        tag: String,
+        /// The default value of the tag.
+        value: String,
+    },
+
+    /// The write contains a non-tag column with the name of a default tag.
+    #[error("table {table} contains a non-tag column named after the namespace default tag {tag}")]
+    NotATag {
+        /// The table written to.
+        table: String,
+        /// The name of the default tag.
+        tag: String,
+    },
+}
+
+/// The last observed default tags of a namespace.
+#[derive(Debug, Clone)]
+struct CachedTags {
+    tags: Arc<[NamespaceDefaultTag]>,
+    fetched_at: Time,
+}
+
+/// A catalog query refreshing the default tags of a namespace, shared by the
+/// concurrent writes waiting for it.
+type RefreshFuture = Shared<BoxFuture<'static, Arc<[NamespaceDefaultTag]>>>;
+
+/// The cached default tags of each namespace.
+#[derive(Debug, Default)]
+struct Cache {
+    entries: HashMap<NamespaceId, CachedTags>,
+
+    /// The last time idle namespaces were evicted.
+    swept_at: Option<Time>,
+}
+
+impl Cache {
+    /// Returns the tags of the namespace `id` if fetched less than `ttl`
+    /// before `now`.
+    fn get_fresh(
+        &self,
+        id: NamespaceId,
+        now: Time,
+        ttl: Duration,
+    ) -> Option<Arc<[NamespaceDefaultTag]>> {
+        let cached = self.entries.get(&id)?;
+        let age = now
+            .checked_duration_since(cached.fetched_at)
+            .unwrap_or_default();
+        (age < ttl).then(|| Arc::clone(&cached.tags))
+    }
+
+    /// Cache `tags` for the namespace `id`, evicting the namespaces not
+    /// refreshed for `idle` at most once per `idle` period.
+    fn insert(&mut self, id: NamespaceId, tags: CachedTags, idle: Duration) {
+        let now = tags.fetched_at;
+        let elapsed = |t: Time| now.checked_duration_since(t).unwrap_or_default();
+
+        if self.swept_at.map_or(true, |t| elapsed(t) >= idle) {
+            self.entries.retain(|_, v| elapsed(v.fetched_at) < idle);
+            self.swept_at = Some(now);
+        }
+
+        self.entries.insert(id, tags);
+    }
+}
+
+/// The state shared between a [`DefaultTagInjector`] and its in-flight
+/// refreshes.
+///
+/// When both locks are held, `refreshing` is acquired first.
+#[derive(Debug, Default)]
+struct State {
+    cache: Mutex<Cache>,
+    refreshing: Mutex<HashMap<NamespaceId, RefreshFuture>>,
+}
+
+/// A [`DmlHandler`] implementation adding the default tags configured in the
+/// catalog for a namespace to every row written to it.
+///
+/// Default tags label all the data of a namespace (for example with
+/// `env=prod`) without changing the writers. A row already carrying a default
+/// tag is handled according to the [`DefaultTagConflict`] policy.
+///
+/// The default tags of each namespace are cached for `ttl` before being
+/// re-read from the catalog. If the catalog cannot be queried, the last
+/// observed tags are used (or none if never observed). Concurrent writes to a
+/// namespace with expired tags share a single catalog query, and the tags of
+/// namespaces no longer written to are evicted.
+///
+/// This handler must run before the schema validation, so that the default
+/// tag columns are added to the table schemas.
+#[derive(Debug)]
+pub struct DefaultTagInjector<P = SystemProvider> {
+    catalog: Arc<dyn Catalog>,
+    ttl: Duration,
+    conflict: DefaultTagConflict,
+    time_provider: P,
+
+    state: Arc<State>,
+}
+
+impl DefaultTagInjector {
+    /// Initialise a new [`DefaultTagInjector`] that caches the default tags
+    /// of each namespace for `ttl`, handling conflicting tags according to
+    /// `conflict`.
+    pub fn new(catalog: Arc<dyn Catalog>, ttl: Duration, conflict: DefaultTagConflict) -> Self {
+        Self::new_with_time_provider(catalog, ttl, conflict, SystemProvider::default())
+    }
+}
+
+impl<P> DefaultTagInjector<P>
+where
+    P: TimeProvider,
+{
+    fn new_with_time_provider(
+        catalog: Arc<dyn Catalog>,
+        ttl: Duration,
+        conflict: DefaultTagConflict,
+        time_provider: P,
+    ) -> Self {
+        Self {
+            catalog,
+            ttl,
+            conflict,
+            time_provider,
+            state: Default::default(),
+        }
+    }
+
+    /// Return the default tags of the namespace identified by `id`,
+    /// consulting the catalog if the cached tags are missing or expired.
+    async fn default_tags(
+        &self,
+        id: NamespaceId,
+        namespace: &NamespaceName<'static>,
+    ) -> Arc<[NamespaceDefaultTag]> {
+        let now = self.time_provider.now();
+
+        if let Some(tags) = self.state.cache.lock().get_fresh(id, now, self.ttl) {
+            return tags;
+        }
+
+        let refresh = {
+            let mut refreshing = self.state.refreshing.lock();
+
+            // A refresh may have completed since the cache was checked.
+            if let Some(tags) = self.state.cache.lock().get_fresh(id, now, self.ttl) {
+                return tags;
+            }
+
+            refreshing
+                .entry(id)
+                .or_insert_with(|| {
+                    refresh(
+                        Arc::clone(&self.catalog),
+                        Arc::clone(&self.state),
+                        id,
+                        namespace.clone(),
+                        now,
+                        self.ttl * IDLE_TTL_MULTIPLE,
+                    )
+                    .boxed()
+                    .shared()
+                })
+                .clone()
+        };
+
+        refresh.await
+    }
+}
+
+/// Read the default tags of the namespace identified by `id` from the
+/// catalog, caching them as fetched at `now`.
+async fn refresh(
+    catalog: Arc<dyn Catalog>,
+    state: Arc<State>,
+    id: NamespaceId,
+    namespace: NamespaceName<'static>,
+    now: Time,
+    idle: Duration,
+) -> Arc<[NamespaceDefaultTag]> {
+    let res = catalog
+        .repositories()
+        .await
+        .namespace_default_tags()
+        .list_by_namespace_id(id)
+        .await;
+
+    if let Err(error) = &res {
+        warn!(
+            %error,
+            namespace_id = %id,
+            %namespace,
+            "failed to refresh namespace default tags, using last known value"
+        );
+    }
+
+    let mut refreshing = state.refreshing.lock();
+    let mut cache = state.cache.lock();
+
+    let tags = match res {
+        Ok(tags) => tags.into(),
+        Err(_) => cache
+            .entries
+            .get(&id)
+            .map(|v| Arc::clone(&v.tags))
+            .unwrap_or_else(|| Arc::new([])),
+    };
+    cache.insert(
+        id,
+        CachedTags {
+            tags: Arc::clone(&tags),
+            fetched_at: now,
+        },
+        idle,
+    );
+    refreshing.remove(&id);
+
+    tags
+}
+
+#[async_trait]
+impl<P> DmlHandler for DefaultTagInjector<P>
+where
+    P: TimeProvider,
+{
+    type WriteError = DefaultTagError;
+
+    type WriteInput = HashMap<String, MutableBatch>;
+    type WriteOutput = Self::WriteInput;
+
+    /// Add the default tags of the namespace to every row of `batch`.
+    async fn write(
+        &self,
+        namespace: &NamespaceName<'static>,
+        namespace_schema: Arc<NamespaceSchema>,
+        mut batch: Self::WriteInput,
+        _span_ctx: Option<SpanContext>,
+    ) -> Result<Self::WriteOutput, Self::WriteError> {
+        let tags = self.default_tags(namespace_schema.id, namespace).await;
+
+        for tag in tags.iter() {
+            for (table, data) in batch.iter_mut() {
+                if let Ok(col) = data.column(&tag.name) {
+                    if col.influx_type() != InfluxColumnType::Tag {
+                        return Err(DefaultTagError::NotATag {
+                            table: table.clone(),
+                            tag: tag.name.clone(),
+                        });
+                    }
+
+                    // The min/max values of the column are only both the
+                    // default value if every non-NULL row carries it.
+                    if self.conflict == DefaultTagConflict::Reject {
+                        if let Statistics::String(v) = col.stats() {
+                            if [v.min, v.max].iter().flatten().any(|v| *v != tag.value) {
+                                return Err(DefaultTagError::Conflict {
+                                    table: table.clone(),
+                                    tag: tag.name.clone(),
+                                    value: tag.value.clone(),
+                                });
+                            }
+                        }
+                    }
+                }
+
+                data.set_tag(
+                    &tag.name,
+                    &tag.value,
+                    self.conflict == DefaultTagConflict::Overwrite,
+                )
+                .expect("default tag column is a tag");
+            }
+        }
+
+        Ok(batch)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow_util::assert_batches_eq;
+    use assert_matches::assert_matches;
+    use iox_tests::TestCatalog;
+    use metric::{Attributes, DurationHistogram, Metric};
+    use once_cell::sync::Lazy;
+    use schema::Projection;
+
+    use super::*;
+
+    static NAMESPACE: Lazy<NamespaceName<'static>> = Lazy::new(|| "bananas".try_into().unwrap());
+
+    const TTL: Duration = Duration::from_secs(10);
+
+    // Parse `lp` into a table-keyed MutableBatch map.
+    fn lp_to_writes(lp: &str) -> HashMap<String, MutableBatch> {
+        let (writes, _) = mutable_batch_lp::lines_to_batches_stats(lp, 42)
+            .expect("failed to build test writes from LP");
+        writes
+    }
+
+    async fn set_default_tag(catalog: &TestCatalog, namespace_id: NamespaceId, name: &str) {
+        catalog
+            .catalog
+            .repositories()
+            .await
+            .namespace_default_tags()
+            .upsert(&NamespaceDefaultTag {
+                namespace_id,
+                name: name.to_string(),
+                value: "prod".to_string(),
+            })
+            .await
+            .expect("upsert default tag");
+    }
+
+    async fn handler(
+        conflict: DefaultTagConflict,
+    ) -> (
+        Arc<TestCatalog>,
+        Arc<NamespaceSchema>,
+        DefaultTagInjector<Arc<iox_time::MockProvider>>,
+    ) {
+        let catalog = TestCatalog::new();
+        let namespace = catalog.create_namespace_1hr_retention(&NAMESPACE).await;
+        let schema: Arc<NamespaceSchema> = namespace.schema().await.into();
+        set_default_tag(&catalog, schema.id, "env").await;
+
+        let handler = DefaultTagInjector::new_with_time_provider(
+            catalog.catalog(),
+            TTL,
+            conflict,
+            Arc::clone(&catalog.time_provider),
+        );
+
+        (catalog, schema, handler)
+    }
+
+    #[tokio::test]
+    async fn test_default_tags_added() {
+        let (catalog, schema, handler) = handler(DefaultTagConflict::Keep).await;
+
+        let got = handler
+            .write(
+                &NAMESPACE,
+                Arc::clone(&schema),
+                lp_to_writes("bananas,env=dev val=1i 1\nbananas val=2i 2"),
+                None,
+            )
+            .await
+            .expect("write should succeed");
+
+        assert_batches_eq!(
+            [
+                "+------+--------------------------------+-----+",
+                "| env  | time                           | val |",
+                "+------+--------------------------------+-----+",
+                "| dev  | 1970-01-01T00:00:00.000000001Z | 1   |",
+                "| prod | 1970-01-01T00:00:00.000000002Z | 2   |",
+                "+------+--------------------------------+-----+",
+            ],
+            &[got["bananas"].to_arrow(Projection::All).unwrap()]
+        );
+
+        // Tags added to the catalog are observed once the TTL has elapsed.
+        set_default_tag(&catalog, schema.id, "region").await;
+        let got = handler
+            .write(
+                &NAMESPACE,
+                Arc::clone(&schema),
+                lp_to_writes("bananas val=3i 3"),
+                None,
+            )
+            .await
+            .expect("write should succeed");
+        assert!(got["bananas"].column("region").is_err());
+
+        catalog.mock_time_provider().inc(TTL);
+        let got = handler
+            .write(&NAMESPACE, schema, lp_to_writes("bananas val=3i 3"), None)
+            .await
+            .expect("write should succeed");
+        assert!(got["bananas"].column("region").is_ok());
+    }
+
+    fn catalog_queries(catalog: &TestCatalog) -> u64 {
+        catalog
+            .metric_registry
+            .get_instrument::<Metric<DurationHistogram>>("catalog_op_duration")
+            .expect("failed to read metric")
+            .get_observer(&Attributes::from(&[
+                ("op", "namespace_default_tag_list_by_namespace_id"),
+                ("result", "success"),
+            ]))
+            .expect("failed to get observer")
+            .fetch()
+            .sample_count()
+    }
+
+    #[tokio::test]
+    async fn test_default_tags_single_flight() {
+        let (catalog, schema, handler) = handler(DefaultTagConflict::Keep).await;
+
+        let writes = (0..10).map(|i| {
+            handler.write(
+                &NAMESPACE,
+                Arc::clone(&schema),
+                lp_to_writes(&format!("bananas val={i}i {i}")),
+                None,
+            )
+        });
+        for got in futures::future::join_all(writes).await {
+            assert!(got.expect("write should succeed")["bananas"]
+                .column("env")
+                .is_ok());
+        }
+
+        // The concurrent writes shared a single catalog query.
+        assert_eq!(catalog_queries(&catalog), 1);
+    }
+
+    #[tokio::test]
+    async fn test_default_tags_idle_eviction() {
+        let (catalog, schema, handler) = handler(DefaultTagConflict::Keep).await;
+        let other = catalog.create_namespace_1hr_retention("platanos").await;
+        let other_schema: Arc<NamespaceSchema> = other.schema().await.into();
+        let other_name: NamespaceName<'static> = "platanos".try_into().unwrap();
+
+        handler
+            .write(&NAMESPACE, schema, lp_to_writes("bananas val=1i 1"), None)
+            .await
+            .expect("write should succeed");
+
+        // The tags of a namespace written to recently are kept.
+        catalog.mock_time_provider().inc(TTL);
+        handler
+            .write(
+                &other_name,
+                Arc::clone(&other_schema),
+                lp_to_writes("bananas val=1i 1"),
+                None,
+            )
+            .await
+            .expect("write should succeed");
+        assert_eq!(handler.state.cache.lock().entries.len(), 2);
+
+        // Those of a namespace no longer written to are evicted.
+        catalog
+            .mock_time_provider()
+            .inc(TTL * (IDLE_TTL_MULTIPLE - 1));
+        handler
+            .write(
+                &other_name,
+                other_schema,
+                lp_to_writes("bananas val=1i 1"),
+                None,
+            )
+            .await
+            .expect("write should succeed");
+        let cache = handler.state.cache.lock();
+        assert_eq!(cache.entries.len(), 1);
+        assert!(cache.entries.contains_key(&other.namespace.id));
+    }
+
+    #[tokio::test]
+    async fn test_default_tags_overwrite() {
+        let (_catalog, schema, handler) = handler(DefaultTagConflict::Overwrite).await;
+
+        let got = handler
+            .write(
+                &NAMESPACE,
+                schema,
+                lp_to_writes("bananas,env=dev val=1i 1"),
+                None,
+            )
+            .await
+            .expect("write should succeed");
+
+        assert_batches_eq!(
+            [
+                "+------+--------------------------------+-----+",
+                "| env  | time                           | val |",
+                "+------+--------------------------------+-----+",
+                "| prod | 1970-01-01T00:00:00.000000001Z | 1   |",
+                "+------+--------------------------------+-----+",
+            ],
+            &[got["bananas"].to_arrow(Projection::All).unwrap()]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_default_tags_reject() {
+        let (_catalog, schema, handler) = handler(DefaultTagConflict::Reject).await;
+
+        // Writes carrying the default value are accepted.
+        handler
+            .write(
+                &NAMESPACE,
+                Arc::clone(&schema),
+                lp_to_writes("bananas,env=prod val=1i 1\nbananas val=2i 2"),
+                None,
+            )
+            .await
+            .expect("write should succeed");
+
+        let got = handler
+            .write(
+                &NAMESPACE,
+                Arc::clone(&schema),
+                lp_to_writes("bananas,env=dev val=1i 1"),
+                None,
+            )
+            .await;
+        assert_matches!(got, Err(DefaultTagError::Conflict { tag, .. }) => {
+            assert_eq!(tag, "env");
+        });
+
+        // A field named after a default tag is always rejected.
+        let got = handler
+            .write(&NAMESPACE, schema, lp_to_writes("bananas env=1i 1"), None)
+            .await;
+        assert_matches!(got, Err(DefaultTagError::NotATag { .. }));
+    }
+}
@@ -0,0 +1,460 @@
+use std::{fmt::Debug, sync::Arc, time::Duration};
+
+use async_trait::async_trait;
+use data_types::{NamespaceName, NamespaceSchema, PartitionKey, TableId};
+use hashbrown::{hash_map::Entry, HashMap};
+use metric::U64Counter;
+use mutable_batch::MutableBatch;
+use observability_deps::tracing::*;
+use parking_lot::Mutex;
+use tokio::sync::oneshot;
+use tonic::Code;
+use trace::{
+    ctx::SpanContext,
+    span::{Span, SpanRecorder},
+};
+
+use super::{client::RpcWriteClientError, DmlError, DmlHandler, Partitioned, RpcWriteError};
+
+/// The partitioned write input coalesced by a [`GroupCommit`].
+type PartitionedWrite = Partitioned<HashMap<TableId, (String, MutableBatch)>>;
+
+/// A write waiting for its group to be flushed, and the channel its result is
+/// returned to the caller through.
+#[derive(Debug)]
+struct PendingWrite<O> {
+    namespace_schema: Arc<NamespaceSchema>,
+    input: PartitionedWrite,
+    span_ctx: Option<SpanContext>,
+    tx: oneshot::Sender<Result<O, DmlError>>,
+}
+
+/// The writes waiting to be flushed, grouped by namespace & partition key.
+type PendingGroups<O> =
+    Mutex<HashMap<(NamespaceName<'static>, PartitionKey), Vec<PendingWrite<O>>>>;
+
+/// A [`DmlHandler`] coalescing the partitioned writes to the same namespace
+/// and partition received within a short `window` into a single write to the
+/// inner handler.
+///
+/// Many tiny writes each sent as an individual RPC write to the ingesters
+/// spend more time in per-request overhead than in writing data; grouping
+/// them reduces the number of RPC writes at the cost of adding up to `window`
+/// of latency to each write.
+///
+/// The first write to a (namespace, partition key) opens a group, which is
+/// flushed once `window` has elapsed. Each write in the group is acknowledged
+/// with the output of the coalesced write.
+///
+/// If the coalesced write is rejected because of its data, the writes of the
+/// group are retried individually and in order, so that each caller observes
+/// the error (if any) caused by its own write. As retried writes may have
+/// been partially applied by the coalesced write, they rely on the ingester
+/// deduplicating rows with the same primary key. Any other error (such as an
+/// unavailable ingester) is returned to every write of the group as a
+/// [`DmlError::GroupCommit`].
+///
+/// The coalesced write is traced as a new trace, linked to the trace of each
+/// write it contains.
+///
+/// The group is flushed by a background task, so cancelling a request does
+/// not abort the writes of the other requests in its group.
+///
+/// A zero `window` disables coalescing, passing each write through to the
+/// inner handler.
+#[derive(Debug)]
+pub struct GroupCommit<T>
+where
+    T: DmlHandler,
+{
+    inner: Arc<T>,
+    window: Duration,
+    pending: Arc<PendingGroups<T::WriteOutput>>,
+
+    /// The number of writes added to a group, and the number of coalesced
+    /// writes flushed to the inner handler.
+    writes: U64Counter,
+    flushes: U64Counter,
+}
+
+impl<T> GroupCommit<T>
+where
+    T: DmlHandler,
+{
+    /// Coalesce the writes to `inner` received within `window` of each other.
+    pub fn new(inner: T, window: Duration, metrics: &metric::Registry) -> Self {
+        let metric = metrics.register_metric::<U64Counter>(
+            "dml_handler_group_commit",
+            "number of writes received and coalesced writes flushed by the group commit handler",
+        );
+
+        Self {
+            inner: Arc::new(inner),
+            window,
+            pending: Default::default(),
+            writes: metric.recorder(&[("op", "write")]),
+            flushes: metric.recorder(&[("op", "flush")]),
+        }
+    }
+}
+
+#[async_trait]
+impl<T> DmlHandler for GroupCommit<T>
+where
+    T: DmlHandler<WriteInput = PartitionedWrite> + 'static,
+    T::WriteOutput: Clone + 'static,
+{
+    type WriteInput = PartitionedWrite;
+    type WriteOutput = T::WriteOutput;
+    type WriteError = DmlError;
+
+    /// Add `input` to the group of writes to its partition, waiting for the
+    /// group to be flushed.
+    async fn write(
+        &self,
+        namespace: &NamespaceName<'static>,
+        namespace_schema: Arc<NamespaceSchema>,
+        input: Self::WriteInput,
+        span_ctx: Option<SpanContext>,
+    ) -> Result<Self::WriteOutput, Self::WriteError> {
+        if self.window.is_zero() {
+            return self
+                .inner
+                .write(namespace, namespace_schema, input, span_ctx)
+                .await
+                .map_err(Into::into);
+        }
+
+        let key = (namespace.clone(), input.key().clone());
+        let (tx, rx) = oneshot::channel();
+        let write = PendingWrite {
+            namespace_schema,
+            input,
+            span_ctx,
+            tx,
+        };
+        self.writes.inc(1);
+
+        // The first write to the partition opens the group, and spawns the
+        // task flushing it.
+        let opened = match self.pending.lock().entry(key.clone()) {
+            Entry::Occupied(mut v) => {
+                v.get_mut().push(write);
+                false
+            }
+            Entry::Vacant(v) => {
+                v.insert(vec![write]);
+                true
+            }
+        };
+
+        if opened {
+            let inner = Arc::clone(&self.inner);
+            let pending = Arc::clone(&self.pending);
+            let window = self.window;
+            let flushes = self.flushes.clone();
+            tokio::spawn(async move {
+                tokio::time::sleep(window).await;
+                let writes = pending
+                    .lock()
+                    .remove(&key)
+                    .expect("group removed by another task");
+                flushes.inc(1);
+
+                let (namespace, partition_key) = key;
+                flush(&*inner, &namespace, partition_key, writes).await;
+            });
+        }
+
+        rx.await.unwrap_or_else(|_| {
+            Err(DmlError::Internal(
+                "group commit flush task exited without a result".into(),
+            ))
+        })
+    }
+}
+
+/// Write the coalesced `writes` to `inner`, returning the result of each
+/// write to its caller.
+async fn flush<T>(
+    inner: &T,
+    namespace: &NamespaceName<'static>,
+    partition_key: PartitionKey,
+    mut writes: Vec<PendingWrite<T::WriteOutput>>,
+) where
+    T: DmlHandler<WriteInput = PartitionedWrite>,
+    T::WriteOutput: Clone,
+{
+    if writes.len() == 1 {
+        let w = writes.pop().unwrap();
+        write_individually(inner, namespace, w).await;
+        return;
+    }
+
+    match merge(&writes) {
+        Ok(merged) => {
+            // All the writes of the group are to the same namespace - use the
+            // most recently observed schema.
+            let namespace_schema = Arc::clone(&writes.last().unwrap().namespace_schema);
+
+            let mut span_recorder = SpanRecorder::new(coalesced_span(&writes));
+            span_recorder.set_metadata("n_writes", writes.len() as i64);
+            let span_ctx = span_recorder.span().map(|span| span.ctx.clone());
+
+            let res: Result<_, DmlError> = inner
+                .write(
+                    namespace,
+                    namespace_schema,
+                    Partitioned::new(partition_key, merged),
+                    span_ctx,
+                )
+                .await
+                .map_err(Into::into);
+            match res {
+                Ok(output) => {
+                    span_recorder.ok("success");
+                    for PendingWrite { tx, .. } in writes {
+                        let _ = tx.send(Ok(output.clone()));
+                    }
+                    return;
+                }
+                Err(error) if is_data_error(&error) => {
+                    span_recorder.error(error.to_string());
+                    debug!(
+                        %error,
+                        %namespace,
+                        n_writes = writes.len(),
+                        "coalesced write rejected, retrying writes individually"
+                    );
+                }
+                Err(error) => {
+                    span_recorder.error(error.to_string());
+                    let error = Arc::new(error);
+                    for PendingWrite { tx, .. } in writes {
+                        let _ = tx.send(Err(DmlError::GroupCommit(Arc::clone(&error))));
+                    }
+                    return;
+                }
+            }
+        }
+        Err(error) => {
+            warn!(
+                %error,
+                %namespace,
+                n_writes = writes.len(),
+                "failed to coalesce writes, writing individually"
+            );
+        }
+    }
+
+    // Retry the writes one at a time, in the order they were received.
+    for w in writes {
+        write_individually(inner, namespace, w).await;
+    }
+}
+
+/// Write `w` to `inner` on its own, returning the result to its caller.
+async fn write_individually<T>(
+    inner: &T,
+    namespace: &NamespaceName<'static>,
+    w: PendingWrite<T::WriteOutput>,
+) where
+    T: DmlHandler<WriteInput = PartitionedWrite>,
+{
+    let PendingWrite {
+        namespace_schema,
+        input,
+        span_ctx,
+        tx,
+    } = w;
+
+    let res = inner
+        .write(namespace, namespace_schema, input, span_ctx)
+        .await
+        .map_err(Into::into);
+    // The caller may have stopped waiting for the result.
+    let _ = tx.send(res);
+}
+
+/// Returns true if `error` may have been caused by the data of a single write
+/// in the coalesced write, rather than by the state of the system.
+fn is_data_error(error: &DmlError) -> bool {
+    match error {
+        DmlError::Schema(_)
+        | DmlError::Partition(_)
+        | DmlError::Retention(_)
+        | DmlError::DefaultTag(_) => true,
+        DmlError::RpcWrite(RpcWriteError::Client(RpcWriteClientError::Upstream(status))) => {
+            status.code() == Code::InvalidArgument
+        }
+        _ => false,
+    }
+}
+
+/// Returns the span of a coalesced write - the root of a new trace, linked to
+/// the trace of each of the `writes` it contains.
+///
+/// Returns [`None`] if none of the `writes` is traced.
+fn coalesced_span<O>(writes: &[PendingWrite<O>]) -> Option<Span> {
+    let collector = writes
+        .iter()
+        .find_map(|w| w.span_ctx.as_ref()?.collector.clone())?;
+
+    let mut span = Span::root("group commit", collector);
+    for ctx in writes.iter().filter_map(|w| w.span_ctx.as_ref()) {
+        span.link(ctx);
+    }
+    Some(span)
+}
+
+/// Merge the per-table batches of `writes` into a single set of per-table
+/// batches.
+fn merge<O>(
+    writes: &[PendingWrite<O>],
+) -> Result<HashMap<TableId, (String, MutableBatch)>, mutable_batch::Error> {
+    let mut merged = HashMap::<TableId, (String, MutableBatch)>::new();
+    for w in writes {
+        for (table_id, (table_name, batch)) in w.input.payload() {
+            match merged.entry(*table_id) {
+                Entry::Occupied(mut v) => v.get_mut().1.extend_from(batch)?,
+                Entry::Vacant(v) => {
+                    v.insert((table_name.clone(), batch.clone()));
+                }
+            }
+        }
+    }
+    Ok(merged)
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_matches::assert_matches;
+    use once_cell::sync::Lazy;
+
+    use super::*;
+    use crate::{
+        dml_handlers::mock::{MockDmlHandler, MockDmlHandlerCall},
+        test_helpers::new_empty_namespace_schema,
+    };
+
+    static NAMESPACE: Lazy<NamespaceName<'static>> = Lazy::new(|| "bananas".try_into().unwrap());
+
+    const WINDOW: Duration = Duration::from_millis(5);
+
+    fn write(lp: &str) -> PartitionedWrite {
+        let (writes, _) = mutable_batch_lp::lines_to_batches_stats(lp, 42).unwrap();
+        let writes = writes
+            .into_iter()
+            .enumerate()
+            .map(|(i, (name, batch))| (TableId::new(i as _), (name, batch)))
+            .collect();
+        Partitioned::new(PartitionKey::from("2023-01-01"), writes)
+    }
+
+    fn rows(call: &MockDmlHandlerCall<PartitionedWrite>) -> usize {
+        let MockDmlHandlerCall::Write { write_input, .. } = call;
+        write_input.payload().values().map(|(_, b)| b.rows()).sum()
+    }
+
+    #[tokio::test]
+    async fn test_coalesce() {
+        let inner = Arc::new(MockDmlHandler::default().with_write_return([Ok(())]));
+        let handler = GroupCommit::new(Arc::clone(&inner), WINDOW, &metric::Registry::default());
+        let schema = Arc::new(new_empty_namespace_schema(42));
+
+        let (a, b) = tokio::join!(
+            handler.write(&NAMESPACE, Arc::clone(&schema), write("cpu v=1 1"), None),
+            handler.write(&NAMESPACE, Arc::clone(&schema), write("cpu v=2 2"), None),
+        );
+        a.expect("write should succeed");
+        b.expect("write should succeed");
+
+        // Both writes were sent to the inner handler as one.
+        assert_matches!(inner.calls().as_slice(), [call] => {
+            assert_eq!(rows(call), 2);
+        });
+    }
+
+    fn rejected() -> DmlError {
+        DmlError::RpcWrite(RpcWriteError::Client(RpcWriteClientError::Upstream(
+            tonic::Status::invalid_argument("bananas"),
+        )))
+    }
+
+    #[tokio::test]
+    async fn test_coalesced_write_rejected_retries_individually() {
+        let inner = Arc::new(MockDmlHandler::default().with_write_return([
+            Err(rejected()),
+            Ok(()),
+            Err(rejected()),
+        ]));
+        let handler = GroupCommit::new(Arc::clone(&inner), WINDOW, &metric::Registry::default());
+        let schema = Arc::new(new_empty_namespace_schema(42));
+
+        let (a, b) = tokio::join!(
+            handler.write(&NAMESPACE, Arc::clone(&schema), write("cpu v=1 1"), None),
+            handler.write(
+                &NAMESPACE,
+                Arc::clone(&schema),
+                write("cpu v=2 2\ncpu v=3 3"),
+                None
+            ),
+        );
+
+        // After the coalesced write was rejected, each write observes the
+        // result of its own retry.
+        assert!(a.is_ok());
+        assert_matches!(b, Err(DmlError::RpcWrite(_)));
+
+        // The writes are retried in the order they were received.
+        assert_matches!(inner.calls().as_slice(), [merged, a, b] => {
+            assert_eq!(rows(merged), 3);
+            assert_eq!(rows(a), 1);
+            assert_eq!(rows(b), 2);
+        });
+    }
+
+    #[tokio::test]
+    async fn test_coalesced_write_error_returned_to_all() {
+        let inner = Arc::new(
+            MockDmlHandler::default()
+                .with_write_return([Err(DmlError::RpcWrite(RpcWriteError::NoHealthyUpstreams))]),
+        );
+        let handler = GroupCommit::new(Arc::clone(&inner), WINDOW, &metric::Registry::default());
+        let schema = Arc::new(new_empty_namespace_schema(42));
+
+        let (a, b) = tokio::join!(
+            handler.write(&NAMESPACE, Arc::clone(&schema), write("cpu v=1 1"), None),
+            handler.write(&NAMESPACE, Arc::clone(&schema), write("cpu v=2 2"), None),
+        );
+
+        // An error not caused by the data of the writes is not retried, and
+        // is returned to every write of the group.
+        for res in [a, b] {
+            assert_matches!(res, Err(DmlError::GroupCommit(e)) => {
+                assert_matches!(*e, DmlError::RpcWrite(RpcWriteError::NoHealthyUpstreams));
+            });
+        }
+        assert_eq!(inner.calls().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_zero_window_passthrough() {
+        let inner = Arc::new(MockDmlHandler::default().with_write_return([Ok(()), Ok(())]));
+        let handler = GroupCommit::new(
+            Arc::clone(&inner),
+            Duration::ZERO,
+            &metric::Registry::default(),
+        );
+        let schema = Arc::new(new_empty_namespace_schema(42));
+
+        let (a, b) = tokio::join!(
+            handler.write(&NAMESPACE, Arc::clone(&schema), write("cpu v=1 1"), None),
+            handler.write(&NAMESPACE, Arc::clone(&schema), write("cpu v=2 2"), None),
+        );
+        a.expect("write should succeed");
+        b.expect("write should succeed");
+
+        assert_eq!(inner.calls().len(), 2);
+    }
+}
@@ -53,6 +53,12 @@ pub mod nop;
 mod retention_validation;
 pub use retention_validation::*;
 
+mod read_only_validation;
+pub use read_only_validation::*;
+
+mod default_tags;
+pub use default_tags::*;
+
 mod partitioner;
 pub use partitioner::*;
 
@@ -65,6 +71,9 @@ pub use chain::*;
 mod fan_out;
 pub use fan_out::*;
 
+mod group_commit;
+pub use group_commit::*;
+
 mod rpc_write;
 pub use rpc_write::*;
 
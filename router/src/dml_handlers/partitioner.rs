@@ -37,6 +37,11 @@ impl<T> Partitioned<T> {
         Self { key, payload }
     }
 
+    /// Get a reference to the partition key.
+    pub fn key(&self) -> &PartitionKey {
+        &self.key
+    }
+
     /// Get a reference to the partition payload.
     pub fn payload(&self) -> &T {
         &self.payload
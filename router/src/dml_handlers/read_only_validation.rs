@@ -0,0 +1,329 @@
+use std::{sync::Arc, time::Duration};
+
+use async_trait::async_trait;
+use data_types::{NamespaceId, NamespaceName, NamespaceSchema};
+use hashbrown::HashMap;
+use iox_catalog::interface::{Catalog, SoftDeletedRows};
+use iox_time::{SystemProvider, Time, TimeProvider};
+use mutable_batch::MutableBatch;
+use observability_deps::tracing::*;
+use parking_lot::Mutex;
+use thiserror::Error;
+use trace::ctx::SpanContext;
+
+use super::DmlHandler;
+
+/// The default duration a cached namespace read-only flag (and deletion
+/// state) is considered valid for before being refreshed from the catalog.
+pub const DEFAULT_READ_ONLY_CACHE_TTL: Duration = Duration::from_secs(10);
+
+/// Errors emitted during read-only validation.
+#[derive(Debug, Error)]
+pub enum ReadOnlyError {
+    /// The namespace has been marked as read-only.
+    #[error("namespace {0} is read-only and is not accepting writes")]
+    ReadOnly(String),
+
+    /// The namespace has been soft-deleted.
+    #[error("namespace {0} has been deleted")]
+    Deleted(String),
+}
+
+/// Whether a namespace accepts writes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum WriteState {
+    #[default]
+    Writable,
+    ReadOnly,
+    Deleted,
+}
+
+/// The last observed [`WriteState`] of a namespace.
+#[derive(Debug, Clone, Copy)]
+struct CachedState {
+    state: WriteState,
+    fetched_at: Time,
+}
+
+/// A [`DmlHandler`] implementation that rejects writes to namespaces that have
+/// been marked as read-only, or soft-deleted, in the catalog.
+///
+/// The read-only flag and deletion state of each namespace is cached for `ttl`
+/// before being re-read from the catalog, bounding both the catalog load and
+/// the delay between an operator changing it and this router observing it.
+/// This covers namespaces deleted after being loaded into the namespace
+/// schema cache, which would otherwise continue to accept writes until the
+/// router restarts.
+///
+/// If the catalog cannot be queried, the last observed state is used (or the
+/// namespace is assumed writable if it has never been observed) - an
+/// unavailable catalog must not cause writes to be rejected.
+#[derive(Debug)]
+pub struct ReadOnlyValidator<P = SystemProvider> {
+    catalog: Arc<dyn Catalog>,
+    ttl: Duration,
+    time_provider: P,
+
+    cache: Mutex<HashMap<NamespaceId, CachedState>>,
+}
+
+impl ReadOnlyValidator {
+    /// Initialise a new [`ReadOnlyValidator`] that caches the read-only state
+    /// of each namespace for `ttl`.
+    pub fn new(catalog: Arc<dyn Catalog>, ttl: Duration) -> Self {
+        Self::new_with_time_provider(catalog, ttl, SystemProvider::default())
+    }
+}
+
+impl<P> ReadOnlyValidator<P>
+where
+    P: TimeProvider,
+{
+    fn new_with_time_provider(catalog: Arc<dyn Catalog>, ttl: Duration, time_provider: P) -> Self {
+        Self {
+            catalog,
+            ttl,
+            time_provider,
+            cache: Default::default(),
+        }
+    }
+
+    /// Return the [`WriteState`] of the namespace identified by `id`,
+    /// consulting the catalog if the cached state is missing or expired.
+    async fn write_state(&self, id: NamespaceId, namespace: &NamespaceName<'static>) -> WriteState {
+        let now = self.time_provider.now();
+
+        let cached = self.cache.lock().get(&id).copied();
+        if let Some(state) = cached {
+            if now
+                .checked_duration_since(state.fetched_at)
+                .unwrap_or_default()
+                < self.ttl
+            {
+                return state.state;
+            }
+        }
+
+        let state = match self
+            .catalog
+            .repositories()
+            .await
+            .namespaces()
+            .get_by_id(id, SoftDeletedRows::AllRows)
+            .await
+        {
+            Ok(Some(ns)) if ns.deleted_at.is_some() => WriteState::Deleted,
+            Ok(Some(ns)) if ns.read_only => WriteState::ReadOnly,
+            Ok(Some(_)) => WriteState::Writable,
+            // A namespace missing from the catalog is rejected by the
+            // namespace resolver.
+            Ok(None) => WriteState::Writable,
+            Err(error) => {
+                warn!(
+                    %error,
+                    namespace_id = %id,
+                    %namespace,
+                    "failed to refresh namespace read-only state, using last known value"
+                );
+                cached.map(|v| v.state).unwrap_or_default()
+            }
+        };
+
+        if cached.map_or(state, |v| v.state) != state {
+            info!(
+                namespace_id = %id,
+                %namespace,
+                ?state,
+                "observed namespace write state change"
+            );
+        }
+
+        self.cache.lock().insert(
+            id,
+            CachedState {
+                state,
+                fetched_at: now,
+            },
+        );
+
+        state
+    }
+}
+
+#[async_trait]
+impl<P> DmlHandler for ReadOnlyValidator<P>
+where
+    P: TimeProvider,
+{
+    type WriteError = ReadOnlyError;
+
+    type WriteInput = HashMap<String, MutableBatch>;
+    type WriteOutput = Self::WriteInput;
+
+    /// Reject the write if the namespace is read-only or deleted.
+    async fn write(
+        &self,
+        namespace: &NamespaceName<'static>,
+        namespace_schema: Arc<NamespaceSchema>,
+        batch: Self::WriteInput,
+        _span_ctx: Option<SpanContext>,
+    ) -> Result<Self::WriteOutput, Self::WriteError> {
+        match self.write_state(namespace_schema.id, namespace).await {
+            WriteState::Writable => Ok(batch),
+            WriteState::ReadOnly => Err(ReadOnlyError::ReadOnly(namespace.to_string())),
+            WriteState::Deleted => Err(ReadOnlyError::Deleted(namespace.to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_matches::assert_matches;
+    use iox_tests::TestCatalog;
+    use once_cell::sync::Lazy;
+
+    use super::*;
+
+    static NAMESPACE: Lazy<NamespaceName<'static>> = Lazy::new(|| "bananas".try_into().unwrap());
+
+    const TTL: Duration = Duration::from_secs(10);
+
+    // Parse `lp` into a table-keyed MutableBatch map.
+    fn lp_to_writes(lp: &str) -> HashMap<String, MutableBatch> {
+        let (writes, _) = mutable_batch_lp::lines_to_batches_stats(lp, 42)
+            .expect("failed to build test writes from LP");
+        writes
+    }
+
+    async fn set_read_only(catalog: &TestCatalog, read_only: bool) {
+        catalog
+            .catalog
+            .repositories()
+            .await
+            .namespaces()
+            .update_read_only(NAMESPACE.as_str(), read_only)
+            .await
+            .expect("update read-only flag");
+    }
+
+    #[tokio::test]
+    async fn test_read_only_toggle() {
+        let catalog = TestCatalog::new();
+        let namespace = catalog.create_namespace_1hr_retention(&NAMESPACE).await;
+        let schema: Arc<NamespaceSchema> = namespace.schema().await.into();
+
+        let handler = ReadOnlyValidator::new_with_time_provider(
+            catalog.catalog(),
+            TTL,
+            Arc::clone(&catalog.time_provider),
+        );
+
+        // A writable namespace accepts writes.
+        handler
+            .write(
+                &NAMESPACE,
+                Arc::clone(&schema),
+                lp_to_writes("bananas val=42i 1"),
+                None,
+            )
+            .await
+            .expect("writable namespace should accept writes");
+
+        set_read_only(&catalog, true).await;
+
+        // The cached state has not yet expired, so the write is accepted.
+        handler
+            .write(
+                &NAMESPACE,
+                Arc::clone(&schema),
+                lp_to_writes("bananas val=42i 2"),
+                None,
+            )
+            .await
+            .expect("cached writable state should accept writes");
+
+        // Once the TTL has elapsed, the read-only state is observed.
+        catalog.mock_time_provider().inc(TTL);
+        let result = handler
+            .write(
+                &NAMESPACE,
+                Arc::clone(&schema),
+                lp_to_writes("bananas val=42i 3"),
+                None,
+            )
+            .await;
+        assert_matches!(result, Err(e) => {
+            assert_eq!(
+                e.to_string(),
+                "namespace bananas is read-only and is not accepting writes"
+            );
+        });
+
+        // Clearing the flag allows writes again after the TTL.
+        set_read_only(&catalog, false).await;
+        catalog.mock_time_provider().inc(TTL);
+        handler
+            .write(&NAMESPACE, schema, lp_to_writes("bananas val=42i 4"), None)
+            .await
+            .expect("namespace should be writable again");
+    }
+
+    #[tokio::test]
+    async fn test_deleted() {
+        let catalog = TestCatalog::new();
+        let namespace = catalog.create_namespace_1hr_retention(&NAMESPACE).await;
+        let schema: Arc<NamespaceSchema> = namespace.schema().await.into();
+
+        let handler = ReadOnlyValidator::new_with_time_provider(
+            catalog.catalog(),
+            TTL,
+            Arc::clone(&catalog.time_provider),
+        );
+
+        handler
+            .write(
+                &NAMESPACE,
+                Arc::clone(&schema),
+                lp_to_writes("bananas val=42i 1"),
+                None,
+            )
+            .await
+            .expect("writable namespace should accept writes");
+
+        catalog
+            .catalog
+            .repositories()
+            .await
+            .namespaces()
+            .soft_delete(NAMESPACE.as_str())
+            .await
+            .expect("soft-delete namespace");
+
+        // Once the TTL has elapsed, the deletion is observed.
+        catalog.mock_time_provider().inc(TTL);
+        let result = handler
+            .write(
+                &NAMESPACE,
+                Arc::clone(&schema),
+                lp_to_writes("bananas val=42i 2"),
+                None,
+            )
+            .await;
+        assert_matches!(result, Err(ReadOnlyError::Deleted(_)));
+
+        // Restoring the namespace allows writes again after the TTL.
+        catalog
+            .catalog
+            .repositories()
+            .await
+            .namespaces()
+            .undelete(NAMESPACE.as_str())
+            .await
+            .expect("undelete namespace");
+        catalog.mock_time_provider().inc(TTL);
+        handler
+            .write(&NAMESPACE, schema, lp_to_writes("bananas val=42i 3"), None)
+            .await
+            .expect("namespace should be writable again");
+    }
+}
@@ -2,9 +2,11 @@ use async_trait::async_trait;
 use data_types::{NamespaceName, NamespaceSchema};
 use hashbrown::HashMap;
 use iox_time::{SystemProvider, TimeProvider};
-use mutable_batch::MutableBatch;
+use metric::U64Counter;
+use mutable_batch::{column::ColumnData, MutableBatch};
 use observability_deps::tracing::*;
-use std::sync::Arc;
+use schema::TIME_COLUMN_NAME;
+use std::{ops::Range, sync::Arc};
 use thiserror::Error;
 use trace::ctx::SpanContext;
 
@@ -36,9 +38,17 @@ pub enum RetentionError {
 /// Each row of data being wrote is inspected, and if any "time" column
 /// timestamp lays outside of the configured namespace retention period, the
 /// entire write is rejected.
+///
+/// If configured with [`RetentionValidator::with_drop_outside_retention()`],
+/// the rows outside of the retention period are instead removed from the
+/// write, and the remaining rows are accepted.
 #[derive(Debug, Default)]
 pub struct RetentionValidator<P = SystemProvider> {
     time_provider: P,
+
+    /// The number of rows dropped for being outside of the retention period,
+    /// if such rows are dropped instead of rejected.
+    dropped_rows: Option<U64Counter>,
 }
 
 impl RetentionValidator {
@@ -48,6 +58,27 @@ impl RetentionValidator {
     }
 }
 
+impl<P> RetentionValidator<P> {
+    /// Drop the rows outside of the retention period from writes, instead of
+    /// rejecting the write.
+    ///
+    /// The dropped rows are counted in the `router_retention_dropped_rows`
+    /// metric.
+    pub fn with_drop_outside_retention(self, metrics: &metric::Registry) -> Self {
+        let dropped_rows = metrics
+            .register_metric::<U64Counter>(
+                "router_retention_dropped_rows",
+                "number of written rows dropped for being outside of the namespace retention period",
+            )
+            .recorder(&[]);
+
+        Self {
+            dropped_rows: Some(dropped_rows),
+            ..self
+        }
+    }
+}
+
 #[async_trait]
 impl<P> DmlHandler for RetentionValidator<P>
 where
@@ -61,7 +92,7 @@ where
     /// Partition the per-table [`MutableBatch`].
     async fn write(
         &self,
-        _namespace: &NamespaceName<'static>,
+        namespace: &NamespaceName<'static>,
         namespace_schema: Arc<NamespaceSchema>,
         batch: Self::WriteInput,
         _span_ctx: Option<SpanContext>,
@@ -69,6 +100,26 @@ where
         // retention is not infinte, validate all lines of a write are within the retention period
         if let Some(retention_period_ns) = namespace_schema.retention_period_ns {
             let min_retention = self.time_provider.now().timestamp_nanos() - retention_period_ns;
+
+            if let Some(dropped_rows) = &self.dropped_rows {
+                return Ok(batch
+                    .into_iter()
+                    .filter_map(|(table_name, batch)| {
+                        let (batch, dropped) = retain_within_retention(batch, min_retention);
+                        if dropped > 0 {
+                            debug!(
+                                %namespace,
+                                %table_name,
+                                dropped,
+                                "dropped rows outside of retention period"
+                            );
+                            dropped_rows.inc(dropped as u64);
+                        }
+                        batch.map(|b| (table_name, b))
+                    })
+                    .collect());
+            }
+
             // batch is a HashMap<tring, MutableBatch>
             for (table_name, batch) in &batch {
                 if let Some(min) = batch.timestamp_summary().and_then(|v| v.stats.min) {
@@ -87,6 +138,47 @@ where
     }
 }
 
+/// Remove the rows of `batch` with a timestamp before `min_retention`,
+/// returning the remaining rows (if any) and the number of rows removed.
+fn retain_within_retention(
+    batch: MutableBatch,
+    min_retention: i64,
+) -> (Option<MutableBatch>, usize) {
+    match batch.timestamp_summary().and_then(|v| v.stats.min) {
+        Some(min) if min < min_retention => {}
+        _ => return (Some(batch), 0),
+    }
+
+    let times = match batch.column(TIME_COLUMN_NAME).map(|c| c.data()) {
+        Ok(ColumnData::I64(times, _)) => times,
+        _ => unreachable!("time column must be present and i64"),
+    };
+
+    // Compute the contiguous ranges of rows within the retention period.
+    let mut ranges: Vec<Range<usize>> = vec![];
+    for (idx, &t) in times.iter().enumerate() {
+        if t < min_retention {
+            continue;
+        }
+        match ranges.last_mut() {
+            Some(r) if r.end == idx => r.end += 1,
+            _ => ranges.push(idx..idx + 1),
+        }
+    }
+
+    let retained = ranges.iter().map(|r| r.len()).sum::<usize>();
+    let dropped = batch.rows() - retained;
+    if ranges.is_empty() {
+        return (None, dropped);
+    }
+
+    let mut out = MutableBatch::new();
+    out.extend_from_ranges(&batch, &ranges)
+        .expect("extending an empty batch from a single batch cannot fail");
+
+    (Some(out), dropped)
+}
+
 #[cfg(test)]
 mod tests {
     use std::sync::Arc;
@@ -137,6 +229,7 @@ mod tests {
         // Create the validator whse retention period is 1 hour
         let handler = RetentionValidator {
             time_provider: mock_time.clone(),
+            dropped_rows: None,
         };
 
         // Make time outside the retention period
@@ -172,6 +265,7 @@ mod tests {
         // Create the validator whse retention period is 1 hour
         let handler = RetentionValidator {
             time_provider: mock_time.clone(),
+            dropped_rows: None,
         };
 
         // Make time now to be inside the retention period
@@ -212,6 +306,7 @@ mod tests {
         // Create the validator whse retention period is 1 hour
         let handler = RetentionValidator {
             time_provider: mock_time.clone(),
+            dropped_rows: None,
         };
 
         // Make time now to be inside the retention period
@@ -238,6 +333,52 @@ mod tests {
         });
     }
 
+    #[tokio::test]
+    async fn test_drop_outside_retention_period() {
+        let namespace = test_setup().await;
+
+        let mock_now = iox_time::Time::from_rfc3339("2023-05-23T09:59:06+00:00").unwrap();
+        let metrics = metric::Registry::default();
+
+        // Create the validator whose retention period is 1 hour
+        let handler = RetentionValidator {
+            time_provider: MockProvider::new(mock_now),
+            dropped_rows: None,
+        }
+        .with_drop_outside_retention(&metrics);
+
+        let now = mock_now.timestamp_nanos();
+        let two_hours_ago = now - 2 * 3_600 * 1_000_000_000;
+        let lp = format!(
+            "bananas,tag1=A val=1i {now}\n\
+             bananas,tag1=B val=2i {two_hours_ago}\n\
+             bananas,tag1=C val=3i {now}\n\
+             apple,tag1=D val=4i {two_hours_ago}"
+        );
+
+        let got = handler
+            .write(
+                &NAMESPACE,
+                namespace.schema().await.into(),
+                lp_to_writes(&lp),
+                None,
+            )
+            .await
+            .expect("rows outside retention should be dropped");
+
+        // The apple table contained only rows outside of the retention period.
+        assert_eq!(got.len(), 1);
+        let bananas = got.get("bananas").expect("table must be retained");
+        assert_eq!(bananas.rows(), 2);
+        assert_matches!(
+            bananas.column(TIME_COLUMN_NAME).unwrap().data(),
+            ColumnData::I64(times, _) => {
+                assert_eq!(times, &[now, now]);
+            }
+        );
+        assert_eq!(handler.dropped_rows.as_ref().unwrap().fetch(), 2);
+    }
+
     // Parse `lp` into a table-keyed MutableBatch map.
     fn lp_to_writes(lp: &str) -> HashMap<String, MutableBatch> {
         let (writes, _) = mutable_batch_lp::lines_to_batches_stats(lp, 42)
@@ -1,4 +1,5 @@
 mod balancer;
+pub mod bounded_queue;
 mod circuit_breaker;
 mod circuit_breaking_client;
 pub mod client;
@@ -11,9 +12,12 @@ use std::sync::Arc;
 use std::time::Duration;
 
 use async_trait::async_trait;
-use data_types::{NamespaceName, NamespaceSchema, TableId};
+use data_types::{
+    ingester_mapping::IngesterMapping, NamespaceId, NamespaceName, NamespaceSchema, PartitionKey,
+    TableId,
+};
 use dml::{DmlMeta, DmlWrite};
-use futures::{stream::FuturesUnordered, StreamExt};
+use futures::{stream::FuturesUnordered, StreamExt, TryStreamExt};
 use generated_types::influxdata::iox::ingester::v1::WriteRequest;
 use hashbrown::HashMap;
 use mutable_batch::MutableBatch;
@@ -73,12 +77,44 @@ pub enum RpcWriteError {
     },
 }
 
+/// An invalid [`RpcWrite::with_partition_affinity()`] table spread.
+#[derive(Debug, Error)]
+#[error(
+    "table spread ({table_spread}) must be at least the number of write \
+    copies ({n_copies}) and at most the number of ingester endpoints \
+    ({endpoints})"
+)]
+pub struct TableSpreadError {
+    table_spread: usize,
+    n_copies: usize,
+    endpoints: usize,
+}
+
 /// An [`RpcWrite`] handler submits a write directly to an Ingester via the
 /// [gRPC write service].
 ///
 /// Requests are sent to an arbitrary downstream Ingester, and request load is
 /// distributed approximately uniformly across all downstream Ingesters. There
-/// is no effort made to enforce or attempt data locality.
+/// is no effort made to enforce or attempt data locality, unless partition
+/// affinity is enabled.
+///
+/// # Partition Affinity
+///
+/// When configured with [`RpcWrite::with_partition_affinity()`], each table
+/// within a write is sent only to the ingesters that own the (table, partition
+/// key) pair, as determined by an [`IngesterMapping`] over the endpoint names.
+/// A querier configured with the same ingester addresses and table spread can
+/// then limit queries for a table to the owning ingesters.
+///
+/// Writes are never redirected to a non-owning ingester - if too few owners
+/// are healthy, the write fails with [`RpcWriteError::NotEnoughReplicas`].
+///
+/// # Request Queueing
+///
+/// Endpoints wrapped in a [`BoundedQueueClient`] bound the number of requests
+/// outstanding against each upstream. A request rejected by a full queue is
+/// retried against the next upstream without affecting the health of the
+/// rejecting upstream.
 ///
 /// # Replication
 ///
@@ -96,6 +132,7 @@ pub enum RpcWriteError {
 /// error to the client.
 ///
 /// [gRPC write service]: client::WriteClient
+/// [`BoundedQueueClient`]: bounded_queue::BoundedQueueClient
 #[derive(Debug)]
 pub struct RpcWrite<T, C = CircuitBreaker> {
     endpoints: Balancer<T, C>,
@@ -112,6 +149,10 @@ pub struct RpcWrite<T, C = CircuitBreaker> {
     /// may NACK a write, having already buffered the data. When this request is
     /// retried, the data will be duplicated.
     n_copies: usize,
+
+    /// An optional mapping of (table, partition key) to the indexes of the
+    /// owning endpoints in `endpoints`.
+    mapping: Option<IngesterMapping<usize>>,
 }
 
 impl<T> RpcWrite<T> {
@@ -162,7 +203,44 @@ impl<T> RpcWrite<T> {
         Self {
             endpoints,
             n_copies,
+            mapping: None,
+        }
+    }
+}
+
+impl<T, C> RpcWrite<T, C> {
+    /// Route each write only to the ingesters owning the partition it
+    /// targets, spreading the partitions of each table over `table_spread`
+    /// ingesters.
+    ///
+    /// Returns an error if `table_spread` is less than the number of write
+    /// copies, or greater than the number of endpoints.
+    pub fn with_partition_affinity(
+        mut self,
+        table_spread: NonZeroUsize,
+    ) -> Result<Self, TableSpreadError>
+    where
+        T: Send + Sync + Debug + 'static,
+        C: CircuitBreakerState + 'static,
+    {
+        if !(self.n_copies..=self.endpoints.len()).contains(&table_spread.get()) {
+            return Err(TableSpreadError {
+                table_spread: table_spread.get(),
+                n_copies: self.n_copies,
+                endpoints: self.endpoints.len(),
+            });
         }
+
+        debug!(%table_spread, "enabled partition affinity");
+
+        self.mapping = Some(IngesterMapping::new(
+            self.endpoints
+                .endpoint_names()
+                .enumerate()
+                .map(|(i, name)| (name, i)),
+            table_spread,
+        ));
+        Ok(self)
     }
 }
 
@@ -189,11 +267,88 @@ where
         let (partition_key, writes) = writes.into_parts();
 
         // Drop the table names from the value tuple.
-        let writes = writes
+        let writes = writes.into_iter().map(|(id, (_name, data))| (id, data));
+
+        let Some(mapping) = &self.mapping else {
+            // Obtain a snapshot of currently-healthy upstreams (and potentially
+            // some that need probing).
+            let snap = self
+                .endpoints
+                .endpoints()
+                .ok_or(RpcWriteError::NoHealthyUpstreams)?;
+
+            let op = self
+                .replicate(
+                    snap,
+                    namespace,
+                    namespace_id,
+                    partition_key,
+                    writes.collect(),
+                    span_ctx,
+                )
+                .await?;
+
+            return Ok(vec![op.meta().clone()]);
+        };
+
+        // Group the tables by the set of ingesters that own their partition,
+        // sending each group only to its owners.
+        let mut groups = HashMap::<Vec<usize>, HashMap<TableId, MutableBatch>>::new();
+        for (table_id, data) in writes {
+            let owners = mapping
+                .partition_owners(table_id, &partition_key)
+                .into_iter()
+                .take(self.n_copies)
+                .copied()
+                .collect::<Vec<_>>();
+            groups.entry(owners).or_default().insert(table_id, data);
+        }
+
+        groups
             .into_iter()
-            .map(|(id, (_name, data))| (id, data))
-            .collect();
+            .map(|(owners, writes)| {
+                let partition_key = partition_key.clone();
+                let span_ctx = span_ctx.clone();
+                async move {
+                    let snap = self
+                        .endpoints
+                        .owned_endpoints(&owners)
+                        .ok_or(RpcWriteError::NotEnoughReplicas)?;
+
+                    self.replicate(
+                        snap,
+                        namespace,
+                        namespace_id,
+                        partition_key,
+                        writes,
+                        span_ctx,
+                    )
+                    .await
+                    .map(|op| op.meta().clone())
+                }
+            })
+            .collect::<FuturesUnordered<_>>()
+            .try_collect()
+            .await
+    }
+}
 
+impl<T, C> RpcWrite<T, C>
+where
+    T: WriteClient + 'static,
+    C: CircuitBreakerState + 'static,
+{
+    /// Write `writes` to [`Self::n_copies`] distinct upstreams from `snap`,
+    /// returning the [`DmlWrite`] that was sent.
+    async fn replicate(
+        &self,
+        snap: UpstreamSnapshot<Arc<CircuitBreakingClient<T, C>>>,
+        namespace: &NamespaceName<'static>,
+        namespace_id: NamespaceId,
+        partition_key: PartitionKey,
+        writes: HashMap<TableId, MutableBatch>,
+        span_ctx: Option<SpanContext>,
+    ) -> Result<DmlWrite, RpcWriteError> {
         // Build the DmlWrite
         let op = DmlWrite::new(
             namespace_id,
@@ -209,13 +364,6 @@ where
             payload: Some(encode_write(namespace_id.get(), &op)),
         };
 
-        // Obtain a snapshot of currently-healthy upstreams (and potentially
-        // some that need probing).
-        let snap = self
-            .endpoints
-            .endpoints()
-            .ok_or(RpcWriteError::NoHealthyUpstreams)?;
-
         // It's possible the set of endpoints may contain fewer upstreams than
         // necessary for the write request to succeed (N < replication factor).
         //
@@ -297,7 +445,7 @@ where
             "dispatched write to ingester"
         );
 
-        Ok(vec![op.meta().clone()])
+        Ok(op)
     }
 }
 
@@ -418,6 +566,7 @@ mod tests {
         let handler = RpcWrite {
             endpoints: Balancer::new(endpoints, None),
             n_copies,
+            mapping: None,
         };
 
         assert!(
@@ -509,6 +658,171 @@ mod tests {
         assert_eq!(got_tables, want_tables);
     }
 
+    /// With partition affinity enabled, each table is written only to the
+    /// ingester that owns its partition.
+    #[tokio::test]
+    async fn test_write_partition_affinity() {
+        let batches = lp_to_writes(
+            "\
+                bananas,tag1=A,tag2=B val=42i 1\n\
+                platanos,tag1=A,tag2=B value=42i 2\n\
+                another,tag1=A,tag2=B value=42i 3\n\
+                table,tag1=A,tag2=B val=42i 1\n\
+                more,tag1=A,tag2=B val=42i 1\n\
+                tables,tag1=A,tag2=B val=42i 1\n\
+            ",
+        );
+        let partition_key = PartitionKey::from("2022-01-01");
+        let input = Partitioned::new(partition_key.clone(), batches.clone());
+
+        let names = ["ingester-a", "ingester-b", "ingester-c"];
+        let clients = names.map(|_| Arc::new(MockWriteClient::default()));
+        let handler = RpcWrite::new(
+            clients.iter().map(Arc::clone).zip(names),
+            1.try_into().unwrap(),
+            &metric::Registry::default(),
+            ARBITRARY_TEST_NUM_PROBES,
+        )
+        .with_partition_affinity(1.try_into().unwrap())
+        .expect("valid table spread");
+
+        let got = handler
+            .write(
+                &NamespaceName::new(NAMESPACE_NAME).unwrap(),
+                Arc::new(new_empty_namespace_schema(NAMESPACE_ID.get())),
+                input,
+                None,
+            )
+            .await
+            .expect("write should succeed");
+
+        // The mapping is derived from the endpoint names alone.
+        let mapping = IngesterMapping::new(
+            names.iter().enumerate().map(|(i, name)| (name, i)),
+            1.try_into().unwrap(),
+        );
+        let mut want = vec![HashSet::new(); names.len()];
+        for table_id in batches.keys() {
+            let owner = *mapping.partition_owners(*table_id, &partition_key)[0];
+            want[owner].insert(table_id.get());
+        }
+        assert_eq!(got.len(), want.iter().filter(|v| !v.is_empty()).count());
+
+        for (client, want) in clients.iter().zip(want) {
+            let got = client
+                .calls()
+                .into_iter()
+                .flat_map(|c| c.payload.unwrap().table_batches)
+                .map(|t| t.table_id)
+                .collect::<HashSet<_>>();
+            assert_eq!(got, want);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_partition_affinity_invalid_spread() {
+        let names = ["ingester-a", "ingester-b"];
+        let new_handler = || {
+            RpcWrite::new(
+                names
+                    .map(|_| Arc::new(MockWriteClient::default()))
+                    .into_iter()
+                    .zip(names),
+                2.try_into().unwrap(),
+                &metric::Registry::default(),
+                ARBITRARY_TEST_NUM_PROBES,
+            )
+        };
+
+        // Fewer owners than write copies.
+        assert_matches!(
+            new_handler().with_partition_affinity(1.try_into().unwrap()),
+            Err(TableSpreadError { .. })
+        );
+        // More owners than endpoints.
+        assert_matches!(
+            new_handler().with_partition_affinity(3.try_into().unwrap()),
+            Err(TableSpreadError { .. })
+        );
+        assert_matches!(
+            new_handler().with_partition_affinity(2.try_into().unwrap()),
+            Ok(_)
+        );
+    }
+
+    /// With partition affinity enabled, writes are not redirected to
+    /// non-owning ingesters when the owner is unhealthy.
+    #[tokio::test]
+    async fn test_write_partition_affinity_unhealthy_owner() {
+        let circuits = (0..3)
+            .map(|_| Arc::new(MockCircuitBreaker::default()))
+            .collect::<Vec<_>>();
+        let clients = (0..3)
+            .map(|_| Arc::new(MockWriteClient::default()))
+            .collect::<Vec<_>>();
+
+        let handler = RpcWrite {
+            endpoints: Balancer::new(
+                clients
+                    .iter()
+                    .zip(&circuits)
+                    .enumerate()
+                    .map(|(i, (client, c))| {
+                        CircuitBreakingClient::new(
+                            Arc::clone(client),
+                            format!("ingester-{i}"),
+                            ARBITRARY_TEST_NUM_PROBES,
+                        )
+                        .with_circuit_breaker(Arc::clone(c))
+                    }),
+                None,
+            ),
+            n_copies: 1,
+            mapping: None,
+        }
+        .with_partition_affinity(1.try_into().unwrap())
+        .expect("valid table spread");
+
+        let partition_key = PartitionKey::from("2022-01-01");
+        let owner = *handler
+            .mapping
+            .as_ref()
+            .unwrap()
+            .partition_owners(TableId::new(0), &partition_key)[0];
+
+        for (i, c) in circuits.iter().enumerate() {
+            c.set_healthy(i != owner);
+            c.set_should_probe(false);
+        }
+
+        let got = handler
+            .write(
+                &NamespaceName::new(NAMESPACE_NAME).unwrap(),
+                Arc::new(new_empty_namespace_schema(NAMESPACE_ID.get())),
+                Partitioned::new(partition_key, lp_to_writes("bananas val=42i 1")),
+                None,
+            )
+            .await;
+        assert_matches!(got, Err(RpcWriteError::NotEnoughReplicas));
+        assert!(clients.iter().all(|c| c.calls().is_empty()));
+
+        // Once the owner becomes healthy, the write succeeds.
+        circuits[owner].set_healthy(true);
+        let got = handler
+            .write(
+                &NamespaceName::new(NAMESPACE_NAME).unwrap(),
+                Arc::new(new_empty_namespace_schema(NAMESPACE_ID.get())),
+                Partitioned::new(
+                    PartitionKey::from("2022-01-01"),
+                    lp_to_writes("bananas val=42i 1"),
+                ),
+                None,
+            )
+            .await;
+        assert_matches!(got, Ok(_));
+        assert_eq!(clients[owner].calls().len(), 1);
+    }
+
     /// Ensure all candidates returned by the balancer are tried, aborting after
     /// the first successful request.
     #[tokio::test]
@@ -82,47 +82,85 @@ where
             new_value
         });
 
-        // Build a set of only healthy nodes, and at most one node needing a
-        // health probe.
-        //
-        // By doing this evaluation before returning the iterator, the health is
-        // evaluated only once per request.
-        //
-        // At most one node needing a health probe is returned to avoid one
-        // request having to make multiple RPC calls that are likely to fail -
-        // this smooths out the P99. The probe node is always requested first to
-        // drive recovery.
-        let mut probe = None;
-        let mut healthy = Vec::with_capacity(self.endpoints.len());
-        for e in &*self.endpoints {
-            if e.is_healthy() {
-                healthy.push(Arc::clone(e));
-                continue;
-            }
+        snapshot(self.endpoints.iter(), counter)
+    }
 
-            // NOTE: if should_probe() returns true, the caller SHOULD issue a
-            // probe request - therefore it is added to the front of the
-            // iter/request queue.
-            if probe.is_none() && e.should_probe() {
-                probe = Some(Arc::clone(e));
-            }
-        }
+    /// Return the names of the configured upstream endpoints, in the order
+    /// they were provided to [`Balancer::new()`].
+    pub(super) fn endpoint_names(&self) -> impl Iterator<Item = Arc<str>> + '_ {
+        self.endpoints.iter().map(|e| e.endpoint_name())
+    }
 
-        // If there is a node to probe, ensure it is the first node to be tried
-        // (otherwise it might not get a request sent to it).
-        let idx = match probe.is_some() {
-            true => 0, // Run the probe first
-            false => {
-                // Reduce it to the range of [0, N) where N is the number of
-                // healthy clients in this balancer, ensuring not to calculate
-                // the remainder of a division by 0.
-                counter % max(healthy.len(), 1)
-            }
-        };
+    /// Return an (infinite) iterator over the healthy subset of the endpoints
+    /// at the indexes specified in `owners`, and at most one of them needing a
+    /// health probe.
+    ///
+    /// Unlike [`Balancer::endpoints()`], the healthy endpoints are yielded
+    /// starting with the first healthy entry in `owners`, rather than
+    /// distributing load across them.
+    ///
+    /// # Panics
+    ///
+    /// Panics if an index in `owners` is out of bounds.
+    pub(super) fn owned_endpoints(
+        &self,
+        owners: &[usize],
+    ) -> Option<UpstreamSnapshot<Arc<CircuitBreakingClient<T, C>>>> {
+        snapshot(owners.iter().map(|&i| &self.endpoints[i]), 0)
+    }
+}
 
-        let contains_probe = probe.is_some();
-        UpstreamSnapshot::new(probe.into_iter().chain(healthy), idx, contains_probe)
+/// Build an [`UpstreamSnapshot`] of the healthy `endpoints`, and at most one
+/// endpoint needing a health probe, starting at the healthy endpoint
+/// identified by `counter` modulo the number of healthy endpoints.
+fn snapshot<'a, T, C>(
+    endpoints: impl Iterator<Item = &'a Arc<CircuitBreakingClient<T, C>>>,
+    counter: usize,
+) -> Option<UpstreamSnapshot<Arc<CircuitBreakingClient<T, C>>>>
+where
+    T: Send + Sync + Debug + 'static,
+    C: CircuitBreakerState + 'static,
+{
+    // Build a set of only healthy nodes, and at most one node needing a
+    // health probe.
+    //
+    // By doing this evaluation before returning the iterator, the health is
+    // evaluated only once per request.
+    //
+    // At most one node needing a health probe is returned to avoid one
+    // request having to make multiple RPC calls that are likely to fail -
+    // this smooths out the P99. The probe node is always requested first to
+    // drive recovery.
+    let mut probe = None;
+    let mut healthy = Vec::new();
+    for e in endpoints {
+        if e.is_healthy() {
+            healthy.push(Arc::clone(e));
+            continue;
+        }
+
+        // NOTE: if should_probe() returns true, the caller SHOULD issue a
+        // probe request - therefore it is added to the front of the
+        // iter/request queue.
+        if probe.is_none() && e.should_probe() {
+            probe = Some(Arc::clone(e));
+        }
     }
+
+    // If there is a node to probe, ensure it is the first node to be tried
+    // (otherwise it might not get a request sent to it).
+    let idx = match probe.is_some() {
+        true => 0, // Run the probe first
+        false => {
+            // Reduce it to the range of [0, N) where N is the number of
+            // healthy clients in this snapshot, ensuring not to calculate
+            // the remainder of a division by 0.
+            counter % max(healthy.len(), 1)
+        }
+    };
+
+    let contains_probe = probe.is_some();
+    UpstreamSnapshot::new(probe.into_iter().chain(healthy), idx, contains_probe)
 }
 
 /// Initialise the health metric exported by the RPC balancer, and return the
@@ -628,4 +666,62 @@ mod tests {
         circuit_err.set_healthy(true);
         assert!(balancer.endpoints().is_some());
     }
+
+    /// Only the healthy owners are returned by
+    /// [`Balancer::owned_endpoints()`], starting with the first owner.
+    #[tokio::test]
+    async fn test_owned_endpoints() {
+        let circuits = (0..4)
+            .map(|_| {
+                let c = Arc::new(MockCircuitBreaker::default());
+                c.set_healthy(true);
+                c
+            })
+            .collect::<Vec<_>>();
+
+        // The owner at index 3 is unhealthy.
+        circuits[3].set_healthy(false);
+        circuits[3].set_should_probe(false);
+
+        let balancer = Balancer::new(
+            circuits.iter().enumerate().map(|(i, c)| {
+                CircuitBreakingClient::new(
+                    Arc::new(MockWriteClient::default()),
+                    format!("ingester-{i}"),
+                    ARBITRARY_TEST_NUM_PROBES,
+                )
+                .with_circuit_breaker(Arc::clone(c))
+            }),
+            None,
+        );
+
+        assert_eq!(
+            balancer.endpoint_names().collect::<Vec<_>>(),
+            ["ingester-0", "ingester-1", "ingester-2", "ingester-3"]
+                .map(Arc::<str>::from)
+                .to_vec()
+        );
+
+        let mut snap = balancer.owned_endpoints(&[2, 3, 0]).unwrap();
+        assert_eq!(snap.initial_len(), 2);
+        assert!(!snap.contains_probe());
+
+        let got = (0..4)
+            .map(|_| snap.next().unwrap().endpoint_name())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            got,
+            ["ingester-2", "ingester-0", "ingester-2", "ingester-0"]
+                .map(Arc::<str>::from)
+                .to_vec()
+        );
+
+        // No owners are healthy.
+        assert!(balancer.owned_endpoints(&[3]).is_none());
+
+        // Unless they need probing.
+        circuits[3].set_should_probe(true);
+        let snap = balancer.owned_endpoints(&[3]).unwrap();
+        assert!(snap.contains_probe());
+    }
 }
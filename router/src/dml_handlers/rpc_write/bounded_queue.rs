@@ -0,0 +1,292 @@
+//! A [`WriteClient`] decorator bounding the number of concurrent requests
+//! queued for a single upstream.
+
+use std::{borrow::Cow, num::NonZeroUsize, sync::Arc};
+
+use async_trait::async_trait;
+use generated_types::influxdata::iox::ingester::v1::WriteRequest;
+use iox_time::{SystemProvider, TimeProvider};
+use metric::{DurationHistogram, U64Counter, U64Gauge};
+use tokio::sync::{Semaphore, TryAcquireError};
+use trace::ctx::SpanContext;
+
+use super::client::{RpcWriteClientError, WriteClient};
+
+/// The behaviour of a [`BoundedQueueClient`] when a request is made while the
+/// queue for an upstream is full.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum QueueOverflow {
+    /// Wait for a queue slot to become available, bounded by the overall RPC
+    /// write timeout.
+    #[default]
+    Block,
+
+    /// Immediately fail the request with [`RpcWriteClientError::QueueFull`],
+    /// allowing the caller to retry the write against another upstream.
+    Reject,
+}
+
+/// Decrements the queue depth gauge when dropped.
+#[derive(Debug)]
+struct DepthGuard<'a>(&'a U64Gauge);
+
+impl<'a> DepthGuard<'a> {
+    fn new(gauge: &'a U64Gauge) -> Self {
+        gauge.inc(1);
+        Self(gauge)
+    }
+}
+
+impl<'a> Drop for DepthGuard<'a> {
+    fn drop(&mut self) {
+        self.0.dec(1);
+    }
+}
+
+/// A [`WriteClient`] decorator limiting the number of requests outstanding
+/// against the inner client to at most `max_queued` at any one time, applying
+/// the configured [`QueueOverflow`] behaviour to requests made once the limit
+/// is reached.
+///
+/// Bounding the queue prevents a slow upstream from accumulating an unbounded
+/// number of in-flight requests (and the memory their payloads occupy) in the
+/// router. Rejected requests do not count towards the health of the upstream.
+///
+/// The depth of the queue, the number of rejected requests, and the latency of
+/// requests made against the inner client are recorded per upstream.
+#[derive(Debug)]
+pub struct BoundedQueueClient<T, P = SystemProvider> {
+    inner: T,
+
+    /// The queue slots, or [`None`] if the queue is unbounded.
+    slots: Option<Semaphore>,
+    overflow: QueueOverflow,
+
+    time_provider: P,
+
+    /// The number of requests waiting for, or holding, a queue slot.
+    queue_depth: U64Gauge,
+    rejected: U64Counter,
+    write_success: DurationHistogram,
+    write_error: DurationHistogram,
+}
+
+impl<T> BoundedQueueClient<T> {
+    /// Wrap `inner`, queueing at most `max_queued` requests when [`Some`].
+    ///
+    /// Metrics are attributed to `endpoint_name`.
+    pub fn new(
+        inner: T,
+        endpoint_name: impl Into<Arc<str>>,
+        max_queued: Option<NonZeroUsize>,
+        overflow: QueueOverflow,
+        metrics: &metric::Registry,
+    ) -> Self {
+        let endpoint = Cow::from(endpoint_name.into().to_string());
+
+        let queue_depth = metrics
+            .register_metric::<U64Gauge>(
+                "rpc_write_queue_depth",
+                "number of rpc write requests queued for, or in-flight to, an upstream",
+            )
+            .recorder([("endpoint", endpoint.clone())]);
+        let rejected = metrics
+            .register_metric::<U64Counter>(
+                "rpc_write_queue_rejected",
+                "number of rpc write requests rejected due to a full upstream queue",
+            )
+            .recorder([("endpoint", endpoint.clone())]);
+
+        let duration = metrics.register_metric::<DurationHistogram>(
+            "rpc_write_request_duration",
+            "duration of rpc write requests made to an upstream, excluding queueing",
+        );
+        let write_success = duration.recorder([
+            ("endpoint", endpoint.clone()),
+            ("result", Cow::from("success")),
+        ]);
+        let write_error =
+            duration.recorder([("endpoint", endpoint), ("result", Cow::from("error"))]);
+
+        Self {
+            inner,
+            slots: max_queued.map(|v| Semaphore::new(v.get())),
+            overflow,
+            time_provider: Default::default(),
+            queue_depth,
+            rejected,
+            write_success,
+            write_error,
+        }
+    }
+}
+
+#[async_trait]
+impl<T, P> WriteClient for BoundedQueueClient<T, P>
+where
+    T: WriteClient,
+    P: TimeProvider,
+{
+    async fn write(
+        &self,
+        op: WriteRequest,
+        span_ctx: Option<SpanContext>,
+    ) -> Result<(), RpcWriteClientError> {
+        let _depth = DepthGuard::new(&self.queue_depth);
+
+        let _permit = match (&self.slots, self.overflow) {
+            (None, _) => None,
+            (Some(slots), QueueOverflow::Block) => {
+                Some(slots.acquire().await.expect("semaphore is never closed"))
+            }
+            (Some(slots), QueueOverflow::Reject) => match slots.try_acquire() {
+                Ok(permit) => Some(permit),
+                Err(TryAcquireError::NoPermits) => {
+                    self.rejected.inc(1);
+                    return Err(RpcWriteClientError::QueueFull);
+                }
+                Err(TryAcquireError::Closed) => unreachable!("semaphore is never closed"),
+            },
+        };
+
+        let t = self.time_provider.now();
+        let res = self.inner.write(op, span_ctx).await;
+
+        // Avoid exploding if time goes backwards - simply drop the measurement
+        // if it happens.
+        if let Some(delta) = self.time_provider.now().checked_duration_since(t) {
+            match &res {
+                Ok(_) => self.write_success.record(delta),
+                Err(_) => self.write_error.record(delta),
+            }
+        }
+
+        res
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use assert_matches::assert_matches;
+    use futures::FutureExt;
+    use metric::{Attributes, Metric};
+    use test_helpers::timeout::FutureTimeout;
+
+    use super::*;
+
+    const ENDPOINT: &str = "http://ingester-0:8083";
+
+    /// A [`WriteClient`] that does not return until a permit is added to
+    /// `unblock`.
+    #[derive(Debug)]
+    struct BlockingClient {
+        unblock: Semaphore,
+    }
+
+    impl Default for BlockingClient {
+        fn default() -> Self {
+            Self {
+                unblock: Semaphore::new(0),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl WriteClient for BlockingClient {
+        async fn write(
+            &self,
+            _op: WriteRequest,
+            _span_ctx: Option<SpanContext>,
+        ) -> Result<(), RpcWriteClientError> {
+            self.unblock.acquire().await.unwrap().forget();
+            Ok(())
+        }
+    }
+
+    fn queue_depth(metrics: &metric::Registry) -> u64 {
+        metrics
+            .get_instrument::<Metric<U64Gauge>>("rpc_write_queue_depth")
+            .expect("metric registered")
+            .get_observer(&Attributes::from(&[("endpoint", ENDPOINT)]))
+            .expect("observer exists")
+            .fetch()
+    }
+
+    #[tokio::test]
+    async fn test_reject() {
+        let metrics = metric::Registry::default();
+        let client = BoundedQueueClient::new(
+            BlockingClient::default(),
+            ENDPOINT,
+            NonZeroUsize::new(1),
+            QueueOverflow::Reject,
+            &metrics,
+        );
+
+        let mut first = client.write(WriteRequest::default(), None).boxed();
+        assert!((&mut first).now_or_never().is_none());
+        assert_eq!(queue_depth(&metrics), 1);
+
+        // The queue is full, so the second request is rejected.
+        assert_matches!(
+            client.write(WriteRequest::default(), None).await,
+            Err(RpcWriteClientError::QueueFull)
+        );
+        assert_eq!(client.rejected.fetch(), 1);
+        assert_eq!(queue_depth(&metrics), 1);
+
+        client.inner.unblock.add_permits(1);
+        first.await.expect("write succeeds");
+        assert_eq!(queue_depth(&metrics), 0);
+        assert_eq!(client.write_success.fetch().sample_count(), 1);
+
+        // Once the slot is released, requests are accepted.
+        let mut third = client.write(WriteRequest::default(), None).boxed();
+        assert!((&mut third).now_or_never().is_none());
+        client.inner.unblock.add_permits(1);
+        third.await.expect("write succeeds");
+    }
+
+    #[tokio::test]
+    async fn test_block() {
+        let metrics = metric::Registry::default();
+        let client = Arc::new(BoundedQueueClient::new(
+            BlockingClient::default(),
+            ENDPOINT,
+            NonZeroUsize::new(1),
+            QueueOverflow::Block,
+            &metrics,
+        ));
+
+        let first = tokio::spawn({
+            let client = Arc::clone(&client);
+            async move { client.write(WriteRequest::default(), None).await }
+        });
+        let second = tokio::spawn({
+            let client = Arc::clone(&client);
+            async move { client.write(WriteRequest::default(), None).await }
+        });
+
+        // Wait for both requests to be queued.
+        async {
+            while queue_depth(&metrics) != 2 {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        }
+        .with_timeout_panic(Duration::from_secs(5))
+        .await;
+
+        // Only one request holds the single slot, the other waits for it and
+        // completes once the first returns.
+        client.inner.unblock.add_permits(2);
+
+        first.await.unwrap().expect("write succeeds");
+        second.await.unwrap().expect("write succeeds");
+
+        assert_eq!(client.rejected.fetch(), 0);
+        assert_eq!(queue_depth(&metrics), 0);
+        assert_eq!(client.write_success.fetch().sample_count(), 2);
+    }
+}
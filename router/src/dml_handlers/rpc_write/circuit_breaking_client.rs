@@ -109,7 +109,12 @@ where
         span_ctx: Option<SpanContext>,
     ) -> Result<(), RpcWriteClientError> {
         let res = self.inner.write(op, span_ctx).await;
-        self.state.observe(&res);
+
+        // A full request queue indicates the upstream is busy, not unhealthy -
+        // the request was never sent.
+        if !matches!(res, Err(RpcWriteClientError::QueueFull)) {
+            self.state.observe(&res);
+        }
         res
     }
 }
@@ -213,6 +218,7 @@ mod tests {
                     Err(RpcWriteClientError::UpstreamNotConnected(
                         "bananas".to_string(),
                     )),
+                    Err(RpcWriteClientError::QueueFull),
                 ]
                 .into_iter(),
             )),
@@ -236,5 +242,13 @@ mod tests {
             .expect_err("wrapper should return Err mock value");
         assert_eq!(circuit_breaker.ok_count(), 1);
         assert_eq!(circuit_breaker.err_count(), 1);
+
+        // Requests rejected by a full queue are not observed.
+        wrapper
+            .write(WriteRequest::default(), None)
+            .await
+            .expect_err("wrapper should return Err mock value");
+        assert_eq!(circuit_breaker.ok_count(), 1);
+        assert_eq!(circuit_breaker.err_count(), 1);
     }
 }
@@ -18,6 +18,11 @@ pub enum RpcWriteClientError {
     #[error("upstream {0} is not connected")]
     UpstreamNotConnected(String),
 
+    /// The request was not attempted, as the queue of requests to the upstream
+    /// is full.
+    #[error("upstream request queue is full")]
+    QueueFull,
+
     /// The upstream ingester returned an error response.
     #[error("upstream ingester error: {0}")]
     Upstream(#[from] tonic::Status),
@@ -5,12 +5,17 @@ use std::{
         atomic::{AtomicUsize, Ordering},
         Arc,
     },
-    time::Duration,
+    time::{Duration, Instant},
 };
 
 use async_trait::async_trait;
-use generated_types::influxdata::iox::ingester::v1::{
-    write_service_client::WriteServiceClient, WriteRequest,
+use generated_types::influxdata::iox::{
+    build_info::{
+        check_compatibility,
+        v1::{build_info_service_client::BuildInfoServiceClient, GetBuildInfoRequest},
+        Compatibility, RPC_WRITE_PROTOCOL,
+    },
+    ingester::v1::{write_service_client::WriteServiceClient, WriteRequest},
 };
 use observability_deps::tracing::*;
 use parking_lot::Mutex;
@@ -27,6 +32,13 @@ use super::client::{RpcWriteClientError, TracePropagatingWriteClient, WriteClien
 const RETRY_INTERVAL: Duration = Duration::from_secs(1);
 const CONNECT_TIMEOUT: Duration = Duration::from_secs(1);
 
+/// How often the protocol compatibility of an established connection is
+/// checked again.
+///
+/// The [`Channel`] transparently reconnects, possibly to an ingester running a
+/// different build behind the same address.
+const COMPATIBILITY_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
 /// How many consecutive errors must be observed before opening a new connection
 /// (at most once per [`RETRY_INTERVAL]).
 const RECONNECT_ERROR_COUNT: usize = 10;
@@ -38,7 +50,9 @@ const MAX_INCOMING_MSG_BYTES: usize = 1024 * 1024; // 1 MiB
 ///
 /// Connections are attempted in a background thread every [`RETRY_INTERVAL`].
 /// once a connection has been established, the [`Channel`] internally handles
-/// reconnections as needed.
+/// reconnections as needed, and the protocol compatibility of the upstream is
+/// checked again every [`COMPATIBILITY_CHECK_INTERVAL`]. An incompatible
+/// upstream is disconnected until a compatible one is reachable.
 ///
 /// Returns [`RpcWriteClientError::UpstreamNotConnected`] when no connection is
 /// available.
@@ -95,7 +109,8 @@ impl LazyConnector {
     /// past.
     ///
     /// If true, the connection may be active and healthy, or currently
-    /// unusable.
+    /// unusable. A connection dropped because the upstream speaks an
+    /// incompatible protocol version is not counted.
     pub fn did_connect(&self) -> bool {
         self.connection.lock().is_some()
     }
@@ -160,6 +175,7 @@ fn is_envoy_unavailable_error(e: &RpcWriteClientError) -> bool {
         RpcWriteClientError::MisconfiguredMetadataKey(_) => false,
         RpcWriteClientError::MisconfiguredMetadataValue(_) => false,
         RpcWriteClientError::UpstreamNotConnected(_) => unreachable!(),
+        RpcWriteClientError::QueueFull => unreachable!(),
     }
 }
 
@@ -174,13 +190,20 @@ async fn try_connect(
     connection: Arc<Mutex<Option<Channel>>>,
     consecutive_errors: Arc<AtomicUsize>,
 ) {
+    let mut checked_at = Instant::now();
     loop {
         if consecutive_errors.load(Ordering::Relaxed) > RECONNECT_ERROR_COUNT {
             match addr.connect().await {
                 Ok(v) => {
-                    info!(endpoint = %addr.uri(), "connected to upstream ingester");
-                    *connection.lock() = Some(v);
-                    consecutive_errors.store(0, Ordering::Relaxed);
+                    checked_at = Instant::now();
+                    if is_compatible(&addr, v.clone()).await {
+                        info!(endpoint = %addr.uri(), "connected to upstream ingester");
+                        *connection.lock() = Some(v);
+                        consecutive_errors.store(0, Ordering::Relaxed);
+                    } else {
+                        // Stop writing over any previous connection to this upstream.
+                        *connection.lock() = None;
+                    }
                 }
                 Err(e) => warn!(
                     endpoint = %addr.uri(),
@@ -188,7 +211,62 @@ async fn try_connect(
                     "failed to connect to upstream ingester"
                 ),
             }
+        } else if checked_at.elapsed() >= COMPATIBILITY_CHECK_INTERVAL {
+            checked_at = Instant::now();
+            let conn = connection.lock().clone();
+            if let Some(v) = conn {
+                if !is_compatible(&addr, v).await {
+                    *connection.lock() = None;
+                    // Keep retrying until a compatible upstream is reachable,
+                    // as writes no longer observe errors to drive reconnects.
+                    consecutive_errors.store(RECONNECT_ERROR_COUNT + 1, Ordering::Relaxed);
+                }
+            }
         }
         tokio::time::sleep(RETRY_INTERVAL).await;
     }
 }
+
+/// Returns false if the ingester at `addr` speaks a different version of the
+/// RPC write protocol than this router.
+///
+/// Ingesters that do not report their build information (i.e. those older than
+/// the build information service) are logged and accepted.
+async fn is_compatible(addr: &Endpoint, channel: Channel) -> bool {
+    let info = match BuildInfoServiceClient::new(channel)
+        .get_build_info(GetBuildInfoRequest {})
+        .await
+    {
+        Ok(v) => v.into_inner(),
+        Err(e) => {
+            warn!(
+                endpoint = %addr.uri(),
+                error=%e,
+                "cannot read upstream ingester build information, skipping compatibility check"
+            );
+            return true;
+        }
+    };
+
+    match check_compatibility(&info, RPC_WRITE_PROTOCOL) {
+        Compatibility::Compatible => true,
+        Compatibility::Unknown => {
+            warn!(
+                endpoint = %addr.uri(),
+                version = %info.version,
+                "upstream ingester does not report its rpc write protocol version"
+            );
+            true
+        }
+        Compatibility::Incompatible { local, peer } => {
+            error!(
+                endpoint = %addr.uri(),
+                version = %info.version,
+                ingester_protocol_version = peer,
+                router_protocol_version = local,
+                "refusing to connect to upstream ingester speaking an incompatible rpc write protocol version"
+            );
+            false
+        }
+    }
+}
@@ -2,10 +2,11 @@ use std::{ops::DerefMut, sync::Arc};
 
 use async_trait::async_trait;
 use data_types::{
-    partition_template::TablePartitionTemplateOverride, NamespaceName, NamespaceSchema, TableId,
+    partition_template::TablePartitionTemplateOverride, ColumnType, NamespaceName, NamespaceSchema,
+    TableId,
 };
 use hashbrown::HashMap;
-use iox_catalog::{interface::Error as CatalogError, validate_or_insert_schema};
+use iox_catalog::{interface::Error as CatalogError, validate_or_insert_schema, TableScopedError};
 use mutable_batch::MutableBatch;
 use observability_deps::tracing::*;
 use trace::ctx::SpanContext;
@@ -38,17 +39,59 @@ where
     /// If the schema validation fails due to a service limit being reached,
     /// [`SchemaError::ServiceLimit`] is returned.
     ///
+    /// If a field value is rejected by the configured field value policy,
+    /// [`SchemaError::FieldValue`] is returned.
+    ///
+    /// Schema conflicts and field value rejections are checked for every table
+    /// in the request. If more than one table is rejected,
+    /// [`SchemaError::Tables`] is returned, listing the error of each.
+    ///
     /// A request that fails validation on one or more tables fails the request
     /// as a whole - calling this method has "all or nothing" semantics.
     async fn write(
         &self,
         namespace: &NamespaceName<'static>,
         namespace_schema: Arc<NamespaceSchema>,
-        batches: Self::WriteInput,
+        mut batches: Self::WriteInput,
         _span_ctx: Option<SpanContext>,
     ) -> Result<Self::WriteOutput, Self::WriteError> {
         let namespace_id = namespace_schema.id;
 
+        // The table-scoped errors of all the tables rejected by the request.
+        let mut rejected = Vec::new();
+
+        if let Some(enforcer) = &self.field_value_policy {
+            for (table_name, batch) in &mut batches {
+                let table = namespace_schema.tables.get(table_name);
+                let is_integer_column = |column: &str| {
+                    table
+                        .and_then(|t| t.columns.get(column))
+                        .is_some_and(|c| c.column_type == ColumnType::I64)
+                };
+
+                match batch.enforce_field_value_policy(enforcer.policy, is_integer_column) {
+                    Ok(actions) => {
+                        enforcer.clamped.inc(actions.clamped as u64);
+                        enforcer.nulled.inc(actions.nulled as u64);
+                    }
+                    Err(e) => {
+                        warn!(
+                            %namespace,
+                            %namespace_id,
+                            %table_name,
+                            error=%e,
+                            "field value rejected"
+                        );
+                        enforcer.rejected.inc(1);
+                        rejected.push(SchemaError::FieldValue {
+                            table: table_name.clone(),
+                            source: e,
+                        });
+                    }
+                }
+            }
+        }
+
         let column_names_by_table = batches
             .iter()
             .map(|(table_name, batch)| (table_name.as_str(), batch.column_names()));
@@ -57,65 +100,41 @@ where
 
         let mut repos = self.catalog.repositories().await;
 
-        let maybe_new_schema = validate_or_insert_schema(
-            batches.iter().map(|(k, v)| (k.as_str(), v)),
-            &namespace_schema,
-            repos.deref_mut(),
-        )
-        .await
-        .map_err(|e| {
-            match e.err() {
-                // Schema conflicts
-                CatalogError::ColumnTypeMismatch {
-                    ref name,
-                    ref existing,
-                    ref new,
-                } => {
-                    warn!(
-                        %namespace,
-                        %namespace_id,
-                        column_name=%name,
-                        existing_column_type=%existing,
-                        request_column_type=%new,
-                        table_name=%e.table(),
-                        "schema conflict"
-                    );
-                    self.schema_conflict.inc(1);
-                    SchemaError::Conflict(e)
-                }
-                // Service limits
-                CatalogError::ColumnCreateLimitError { table_id, .. } => {
-                    warn!(
-                        %namespace,
-                        %namespace_id,
-                        %table_id,
-                        error=%e,
-                        "service protection limit reached (columns)"
-                    );
-                    self.service_limit_hit_columns.inc(1);
-                    SchemaError::ServiceLimit(Box::new(e.into_err()))
-                }
-                CatalogError::TableCreateLimitError { .. } => {
-                    warn!(
-                        %namespace,
-                        %namespace_id,
-                        error=%e,
-                        "service protection limit reached (tables)"
-                    );
-                    self.service_limit_hit_tables.inc(1);
-                    SchemaError::ServiceLimit(Box::new(e.into_err()))
-                }
-                _ => {
-                    error!(
-                        %namespace,
-                        %namespace_id,
-                        error=%e,
-                        "schema validation failed"
-                    );
-                    SchemaError::UnexpectedCatalogError(e.into_err())
-                }
+        // Validate each table independently so that the conflicts of all
+        // tables are reported, threading any schema changes through to the
+        // validation of the next table.
+        let mut maybe_new_schema: Option<NamespaceSchema> = None;
+        for (table_name, batch) in &batches {
+            if rejected
+                .iter()
+                .any(|e| e.rejected_tables().iter().any(|r| r.table == table_name))
+            {
+                continue;
             }
-        })?;
+
+            let schema = maybe_new_schema.as_ref().unwrap_or(&namespace_schema);
+            match validate_or_insert_schema(
+                std::iter::once((table_name.as_str(), batch)),
+                schema,
+                repos.deref_mut(),
+            )
+            .await
+            {
+                Ok(Some(v)) => maybe_new_schema = Some(v),
+                Ok(None) => {}
+                Err(e) => match self.map_catalog_error(namespace, &namespace_schema, e) {
+                    e @ SchemaError::Conflict(_) => rejected.push(e),
+                    e => return Err(e),
+                },
+            }
+        }
+
+        if rejected.len() > 1 {
+            return Err(SchemaError::Tables(rejected));
+        }
+        if let Some(e) = rejected.pop() {
+            return Err(e);
+        }
 
         trace!(%namespace, "schema validation complete");
 
@@ -152,6 +171,70 @@ where
     }
 }
 
+impl<C> SchemaValidator<C> {
+    /// Map the catalog error `e` returned when validating a table of a write
+    /// into `namespace` to a [`SchemaError`], recording it.
+    fn map_catalog_error(
+        &self,
+        namespace: &NamespaceName<'static>,
+        namespace_schema: &NamespaceSchema,
+        e: TableScopedError,
+    ) -> SchemaError {
+        let namespace_id = namespace_schema.id;
+        match e.err() {
+            // Schema conflicts
+            CatalogError::ColumnTypeMismatch {
+                ref name,
+                ref existing,
+                ref new,
+            } => {
+                warn!(
+                    %namespace,
+                    %namespace_id,
+                    column_name=%name,
+                    existing_column_type=%existing,
+                    request_column_type=%new,
+                    table_name=%e.table(),
+                    "schema conflict"
+                );
+                self.schema_conflict.inc(1);
+                SchemaError::Conflict(e)
+            }
+            // Service limits
+            CatalogError::ColumnCreateLimitError { table_id, .. } => {
+                warn!(
+                    %namespace,
+                    %namespace_id,
+                    %table_id,
+                    error=%e,
+                    "service protection limit reached (columns)"
+                );
+                self.service_limit_hit_columns.inc(1);
+                SchemaError::ServiceLimit(Box::new(e.into_err()))
+            }
+            CatalogError::TableCreateLimitError { .. } => {
+                warn!(
+                    %namespace,
+                    %namespace_id,
+                    error=%e,
+                    "service protection limit reached (tables)"
+                );
+                self.service_limit_hit_tables.inc(1);
+                SchemaError::ServiceLimit(Box::new(e.into_err()))
+            }
+            _ => {
+                error!(
+                    %namespace,
+                    %namespace_id,
+                    error=%e,
+                    "schema validation failed"
+                );
+                SchemaError::UnexpectedCatalogError(e.into_err())
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::{collections::BTreeSet, sync::Arc};
@@ -161,6 +244,8 @@ mod tests {
     use iox_tests::{TestCatalog, TestNamespace};
     use once_cell::sync::Lazy;
 
+    use mutable_batch::value_policy::FieldValuePolicy;
+
     use super::*;
     use crate::namespace_cache::{MemoryNamespaceCache, ReadThroughCache};
 
@@ -369,6 +454,99 @@ mod tests {
         assert_eq!(1, handler.schema_conflict.fetch());
     }
 
+    #[tokio::test]
+    async fn test_write_validation_failure_many_tables() {
+        let (catalog, namespace) = test_setup().await;
+        let metrics = Arc::new(metric::Registry::default());
+        let handler = SchemaValidator::new(catalog.catalog(), setup_test_cache(&catalog), &metrics);
+
+        // First write sets the schema
+        let writes = lp_to_writes("bananas val=42i 123456\nplatanos val=42i 123456");
+        handler
+            .write(&NAMESPACE, namespace.schema().await.into(), writes, None)
+            .await
+            .expect("request should succeed");
+
+        // Both tables conflict, while a third table is valid.
+        let writes = lp_to_writes(
+            "bananas val=42.0 123456\nplatanos val=\"str\" 123456\nmangos val=42i 123456",
+        );
+        let err = handler
+            .write(&NAMESPACE, namespace.schema().await.into(), writes, None)
+            .await
+            .expect_err("request should fail");
+
+        let mut rejected = err
+            .rejected_tables()
+            .into_iter()
+            .map(|r| (r.table.to_string(), r.code))
+            .collect::<Vec<_>>();
+        rejected.sort_unstable();
+        assert_eq!(
+            rejected,
+            [
+                ("bananas".to_string(), "schema_conflict"),
+                ("platanos".to_string(), "schema_conflict"),
+            ]
+        );
+        assert_matches!(err, SchemaError::Tables(errors) => {
+            assert_eq!(errors.len(), 2);
+        });
+
+        assert_eq!(2, handler.schema_conflict.fetch());
+    }
+
+    #[tokio::test]
+    async fn test_write_field_value_policy() {
+        let (catalog, namespace) = test_setup().await;
+        let metrics = Arc::new(metric::Registry::default());
+        let handler = SchemaValidator::new(catalog.catalog(), setup_test_cache(&catalog), &metrics)
+            .with_field_value_policy(FieldValuePolicy::Clamp, &metrics);
+
+        // First write sets the schema
+        let writes = lp_to_writes("bananas val=42i,f=1.0 123456"); // val=i64
+        handler
+            .write(&NAMESPACE, namespace.schema().await.into(), writes, None)
+            .await
+            .expect("request should succeed");
+
+        // Unsigned values are converted for the integer column, clamping those
+        // that overflow.
+        let writes = lp_to_writes(
+            "bananas val=18446744073709551615u,f=2.0 123457\nbananas val=1u,f=3.0 123458",
+        );
+        let got = handler
+            .write(&NAMESPACE, namespace.schema().await.into(), writes, None)
+            .await
+            .expect("request should succeed");
+        let (_, (_, _, batch)) = got.into_iter().next().unwrap();
+        assert_matches!(
+            batch.column("val").unwrap().data(),
+            mutable_batch::column::ColumnData::I64(values, _) => {
+                assert_eq!(values, &[i64::MAX, 1]);
+            }
+        );
+        assert_cache(&handler, "bananas", "val", ColumnType::I64).await;
+
+        let enforcer = handler.field_value_policy.as_ref().unwrap();
+        assert_eq!(enforcer.clamped.fetch(), 1);
+        assert_eq!(enforcer.rejected.fetch(), 0);
+
+        // The same write is rejected when the policy is to reject.
+        let handler = SchemaValidator::new(catalog.catalog(), setup_test_cache(&catalog), &metrics)
+            .with_field_value_policy(FieldValuePolicy::Reject, &metrics);
+        let writes = lp_to_writes("bananas val=18446744073709551615u 123457");
+        let err = handler
+            .write(&NAMESPACE, namespace.schema().await.into(), writes, None)
+            .await
+            .expect_err("request should fail");
+        assert_matches!(err, SchemaError::FieldValue { table, .. } => {
+            assert_eq!(table, "bananas");
+        });
+        let enforcer = handler.field_value_policy.as_ref().unwrap();
+        assert_eq!(enforcer.rejected.fetch(), 1);
+    }
+
     #[tokio::test]
     async fn test_write_table_service_limit() {
         let (catalog, namespace) = test_setup().await;
@@ -1,4 +1,7 @@
-use super::{partitioner::PartitionError, retention_validation::RetentionError, RpcWriteError};
+use super::{
+    default_tags::DefaultTagError, partitioner::PartitionError,
+    read_only_validation::ReadOnlyError, retention_validation::RetentionError, RpcWriteError,
+};
 use crate::schema_validator::SchemaError;
 use async_trait::async_trait;
 use data_types::{NamespaceName, NamespaceSchema};
@@ -31,6 +34,18 @@ pub enum DmlError {
     #[error(transparent)]
     Retention(#[from] RetentionError),
 
+    /// The namespace is read-only and rejecting writes.
+    #[error(transparent)]
+    ReadOnly(#[from] ReadOnlyError),
+
+    /// An error adding the default tags of the namespace to the write.
+    #[error(transparent)]
+    DefaultTag(#[from] DefaultTagError),
+
+    /// The coalesced write of the group commit containing this write failed.
+    #[error("group commit failed: {0}")]
+    GroupCommit(Arc<DmlError>),
+
     /// An unknown error occured while processing the DML request.
     #[error("internal dml handler error: {0}")]
     Internal(Box<dyn Error + Send + Sync>),
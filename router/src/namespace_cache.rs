@@ -11,6 +11,9 @@ pub mod metrics;
 mod read_through_cache;
 pub use read_through_cache::*;
 
+mod validation;
+pub use validation::*;
+
 use std::{collections::BTreeMap, error::Error, fmt::Debug, sync::Arc};
 
 use async_trait::async_trait;
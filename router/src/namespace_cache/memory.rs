@@ -22,6 +22,23 @@ pub struct MemoryNamespaceCache {
     cache: RwLock<HashMap<NamespaceName<'static>, Arc<NamespaceSchema>>>,
 }
 
+impl MemoryNamespaceCache {
+    /// Return the estimated number of bytes of the [`NamespaceSchema`] held
+    /// in this cache.
+    pub fn size(&self) -> usize {
+        self.cache
+            .read()
+            .iter()
+            .map(|(k, v)| k.len() + v.size())
+            .sum()
+    }
+
+    /// Return the names of all namespaces held in this cache.
+    pub fn namespaces(&self) -> Vec<NamespaceName<'static>> {
+        self.cache.read().keys().cloned().collect()
+    }
+}
+
 #[async_trait]
 impl NamespaceCache for MemoryNamespaceCache {
     type ReadError = CacheMissErr;
@@ -205,6 +222,18 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_size() {
+        let ns = NamespaceName::new("test").expect("namespace name is valid");
+        let cache = MemoryNamespaceCache::default();
+        assert_eq!(cache.size(), 0);
+
+        let schema = schema1();
+        let want = ns.len() + schema.size();
+        cache.put_schema(ns, schema);
+        assert_eq!(cache.size(), want);
+    }
+
     #[tokio::test]
     async fn test_put_get() {
         let ns = NamespaceName::new("test").expect("namespace name is valid");
@@ -4,7 +4,7 @@ use async_trait::async_trait;
 use data_types::{NamespaceName, NamespaceSchema};
 use sharder::JumpHash;
 
-use super::{ChangeStats, NamespaceCache};
+use super::{ChangeStats, MemoryNamespaceCache, NamespaceCache};
 
 /// A decorator sharding the [`NamespaceCache`] keyspace into a set of `T`.
 #[derive(Debug)]
@@ -22,6 +22,23 @@ impl<T> ShardedCache<T> {
     }
 }
 
+impl ShardedCache<MemoryNamespaceCache> {
+    /// Return the estimated number of bytes of the [`NamespaceSchema`] held
+    /// across all shards.
+    pub fn size(&self) -> usize {
+        self.shards.shards().iter().map(|v| v.size()).sum()
+    }
+
+    /// Return the names of all namespaces held across all shards.
+    pub fn namespaces(&self) -> Vec<NamespaceName<'static>> {
+        self.shards
+            .shards()
+            .iter()
+            .flat_map(|v| v.namespaces())
+            .collect()
+    }
+}
+
 #[async_trait]
 impl<T> NamespaceCache for ShardedCache<T>
 where
@@ -54,7 +71,7 @@ mod tests {
     use rand::{distributions::Alphanumeric, thread_rng, Rng};
 
     use super::*;
-    use crate::{namespace_cache::MemoryNamespaceCache, test_helpers::new_empty_namespace_schema};
+    use crate::test_helpers::new_empty_namespace_schema;
 
     fn rand_namespace() -> NamespaceName<'static> {
         thread_rng()
@@ -0,0 +1,388 @@
+//! Background validation of the [`NamespaceSchema`] held in the namespace
+//! cache against the catalog (anti-entropy with the source of truth).
+
+use std::{ops::DerefMut, sync::Arc, time::Duration};
+
+use data_types::{NamespaceName, NamespaceSchema};
+use iox_catalog::interface::{get_schema_by_name, Catalog, SoftDeletedRows};
+use metric::{Metric, U64Counter};
+use observability_deps::tracing::*;
+use rand::seq::SliceRandom;
+
+use super::{MemoryNamespaceCache, NamespaceCache, ShardedCache};
+
+/// A kind of divergence between a cached [`NamespaceSchema`] and the schema
+/// in the catalog.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Divergence {
+    /// A table in the catalog is missing from the cache.
+    MissingTable,
+    /// A column in the catalog is missing from a cached table.
+    MissingColumn,
+    /// A cached column has a different type to the catalog column.
+    ColumnType,
+    /// The cached table/column limits or retention period are stale.
+    Limits,
+    /// A cached table or column does not exist in the catalog.
+    Unknown,
+    /// The cached namespace does not exist in the catalog, or is
+    /// soft-deleted.
+    Deleted,
+}
+
+impl Divergence {
+    const ALL: [Self; 6] = [
+        Self::MissingTable,
+        Self::MissingColumn,
+        Self::ColumnType,
+        Self::Limits,
+        Self::Unknown,
+        Self::Deleted,
+    ];
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::MissingTable => "missing_table",
+            Self::MissingColumn => "missing_column",
+            Self::ColumnType => "column_type",
+            Self::Limits => "limits",
+            Self::Unknown => "unknown",
+            Self::Deleted => "deleted",
+        }
+    }
+
+    /// Returns true if merging the catalog schema into the cache resolves
+    /// this divergence.
+    ///
+    /// The cache merge is additive, so entries missing from the catalog
+    /// cannot be removed from the cache.
+    fn is_repairable(&self) -> bool {
+        !matches!(self, Self::Unknown | Self::Deleted)
+    }
+}
+
+/// Periodically compares a random sample of the [`NamespaceSchema`] in the
+/// namespace cache to the catalog, logging and counting the divergences and
+/// repairing the cache entry where possible.
+///
+/// Repairs are made by placing the catalog schema into `cache`, which merges
+/// it with the cached entry - tables and columns missing from the cache are
+/// added, and the catalog limits and retention period replace the cached
+/// values. Tables, columns and namespaces cached but absent from the catalog
+/// are only reported.
+///
+/// The cached schema is read before the catalog, so a concurrent schema
+/// change may be reported as a (harmlessly repaired) missing table or
+/// column, but never as an unknown one.
+#[derive(Debug)]
+pub struct NamespaceCacheValidator<T> {
+    /// The cache to repair, including any layers observing cache changes.
+    cache: T,
+    /// The underlying memory cache, sampled for validation.
+    memory_cache: Arc<ShardedCache<MemoryNamespaceCache>>,
+    catalog: Arc<dyn Catalog>,
+
+    interval: Duration,
+    sample_size: usize,
+
+    divergences: [U64Counter; Divergence::ALL.len()],
+}
+
+impl<T> NamespaceCacheValidator<T>
+where
+    T: NamespaceCache,
+{
+    /// Validate `sample_size` random namespaces of `memory_cache` against
+    /// `catalog` every `interval`, repairing divergences through `cache`.
+    ///
+    /// The validator does not start until [`Self::run()`] is called.
+    pub fn new(
+        cache: T,
+        memory_cache: Arc<ShardedCache<MemoryNamespaceCache>>,
+        catalog: Arc<dyn Catalog>,
+        interval: Duration,
+        sample_size: usize,
+        metrics: &metric::Registry,
+    ) -> Self {
+        let metric: Metric<U64Counter> = metrics.register_metric(
+            "router_namespace_cache_divergences",
+            "number of divergences between cached namespace schemas and the catalog, by kind",
+        );
+        let divergences = Divergence::ALL.map(|d| metric.recorder(&[("kind", d.as_str())]));
+
+        Self {
+            cache,
+            memory_cache,
+            catalog,
+            interval,
+            sample_size,
+            divergences,
+        }
+    }
+
+    /// Block and validate a sample of the cache every configured interval.
+    pub async fn run(self) {
+        let mut ticker = tokio::time::interval(self.interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+        // The cache has just been pre-warmed from the catalog.
+        let _ = ticker.tick().await;
+
+        loop {
+            ticker.tick().await;
+            self.validate_sample().await;
+        }
+    }
+
+    /// Validate a random sample of the cached namespaces.
+    async fn validate_sample(&self) {
+        let names = self.memory_cache.namespaces();
+        for name in names.choose_multiple(&mut rand::thread_rng(), self.sample_size) {
+            self.validate(name).await;
+        }
+    }
+
+    /// Validate the cached schema of `name`, returning the divergences
+    /// found.
+    async fn validate(&self, name: &NamespaceName<'static>) -> Vec<Divergence> {
+        let Ok(cached) = self.memory_cache.get_schema(name).await else {
+            return vec![];
+        };
+
+        let mut repos = self.catalog.repositories().await;
+        let catalog = match get_schema_by_name(
+            name,
+            repos.deref_mut(),
+            SoftDeletedRows::ExcludeDeleted,
+        )
+        .await
+        {
+            Ok(v) => Some(v),
+            Err(iox_catalog::interface::Error::NamespaceNotFoundByName { .. }) => None,
+            Err(e) => {
+                warn!(error=%e, namespace=%name, "failed to validate cached namespace schema");
+                return vec![];
+            }
+        };
+
+        let found = match &catalog {
+            Some(catalog) => find_divergences(&cached, catalog),
+            None => vec![Divergence::Deleted],
+        };
+        if found.is_empty() {
+            return found;
+        }
+
+        for d in &found {
+            self.divergences[*d as usize].inc(1);
+        }
+
+        let repair = found.iter().any(|d| d.is_repairable());
+        warn!(
+            namespace=%name,
+            divergences=?found,
+            repair,
+            "cached namespace schema diverges from catalog"
+        );
+        if let (true, Some(catalog)) = (repair, catalog) {
+            self.cache.put_schema(name.clone(), catalog);
+        }
+
+        found
+    }
+}
+
+/// Return the kinds of divergence between the `cached` schema and the
+/// authoritative `catalog` schema, each at most once.
+fn find_divergences(cached: &NamespaceSchema, catalog: &NamespaceSchema) -> Vec<Divergence> {
+    let mut found = vec![];
+    let mut add = |d| {
+        if !found.contains(&d) {
+            found.push(d);
+        }
+    };
+
+    for (table_name, table) in &catalog.tables {
+        let Some(cached_table) = cached.tables.get(table_name) else {
+            add(Divergence::MissingTable);
+            continue;
+        };
+        for (column_name, column) in table.columns.iter() {
+            match cached_table.columns.get(column_name) {
+                None => add(Divergence::MissingColumn),
+                Some(c) if c.column_type != column.column_type => add(Divergence::ColumnType),
+                Some(_) => {}
+            }
+        }
+    }
+
+    for (table_name, cached_table) in &cached.tables {
+        match catalog.tables.get(table_name) {
+            None => add(Divergence::Unknown),
+            Some(table) => {
+                if cached_table
+                    .columns
+                    .iter()
+                    .any(|(name, _)| !table.contains_column_name(name))
+                {
+                    add(Divergence::Unknown)
+                }
+            }
+        }
+    }
+
+    if cached.max_tables != catalog.max_tables
+        || cached.max_columns_per_table != catalog.max_columns_per_table
+        || cached.retention_period_ns != catalog.retention_period_ns
+    {
+        add(Divergence::Limits);
+    }
+
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use std::iter;
+
+    use data_types::{ColumnType, MaxTables};
+    use iox_catalog::mem::MemCatalog;
+    use metric::{Attributes, Metric};
+
+    use super::*;
+
+    const NAMESPACE: &str = "bananas";
+
+    async fn setup() -> (
+        Arc<dyn Catalog>,
+        Arc<ShardedCache<MemoryNamespaceCache>>,
+        NamespaceCacheValidator<Arc<ShardedCache<MemoryNamespaceCache>>>,
+        Arc<metric::Registry>,
+    ) {
+        let metrics = Arc::new(metric::Registry::default());
+        let catalog: Arc<dyn Catalog> = Arc::new(MemCatalog::new(Arc::clone(&metrics)));
+        let cache = Arc::new(ShardedCache::new(
+            iter::repeat_with(MemoryNamespaceCache::default).take(2),
+        ));
+
+        let mut repos = catalog.repositories().await;
+        let ns = repos
+            .namespaces()
+            .create(
+                &NamespaceName::try_from(NAMESPACE).unwrap(),
+                None,
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+        let table = repos
+            .tables()
+            .create("platanos", Default::default(), ns.id)
+            .await
+            .unwrap();
+        repos
+            .columns()
+            .create_or_get("tag", table.id, ColumnType::Tag)
+            .await
+            .unwrap();
+
+        let validator = NamespaceCacheValidator::new(
+            Arc::clone(&cache),
+            Arc::clone(&cache),
+            Arc::clone(&catalog),
+            Duration::from_secs(1),
+            10,
+            &metrics,
+        );
+
+        (catalog, cache, validator, metrics)
+    }
+
+    async fn catalog_schema(catalog: &Arc<dyn Catalog>) -> NamespaceSchema {
+        let mut repos = catalog.repositories().await;
+        get_schema_by_name(
+            NAMESPACE,
+            repos.deref_mut(),
+            SoftDeletedRows::ExcludeDeleted,
+        )
+        .await
+        .unwrap()
+    }
+
+    fn divergence_count(metrics: &metric::Registry, kind: &'static str) -> u64 {
+        metrics
+            .get_instrument::<Metric<U64Counter>>("router_namespace_cache_divergences")
+            .expect("metric should exist")
+            .get_observer(&Attributes::from(&[("kind", kind)]))
+            .expect("observer should exist")
+            .fetch()
+    }
+
+    #[tokio::test]
+    async fn test_validate_consistent() {
+        let (catalog, cache, validator, metrics) = setup().await;
+        let name = NamespaceName::try_from(NAMESPACE).unwrap();
+
+        cache.put_schema(name.clone(), catalog_schema(&catalog).await);
+
+        assert_eq!(validator.validate(&name).await, []);
+        for d in Divergence::ALL {
+            assert_eq!(divergence_count(&metrics, d.as_str()), 0);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_validate_repairs_stale_entry() {
+        let (catalog, cache, validator, metrics) = setup().await;
+        let name = NamespaceName::try_from(NAMESPACE).unwrap();
+
+        // Cache a schema missing the table, with stale limits.
+        let mut stale = catalog_schema(&catalog).await;
+        stale.tables.clear();
+        stale.max_tables = MaxTables::new(1);
+        cache.put_schema(name.clone(), stale);
+
+        assert_eq!(
+            validator.validate(&name).await,
+            [Divergence::MissingTable, Divergence::Limits]
+        );
+        assert_eq!(divergence_count(&metrics, "missing_table"), 1);
+        assert_eq!(divergence_count(&metrics, "limits"), 1);
+
+        // The entry was repaired.
+        assert_eq!(
+            *cache.get_schema(&name).await.unwrap(),
+            catalog_schema(&catalog).await
+        );
+        assert_eq!(validator.validate(&name).await, []);
+    }
+
+    #[tokio::test]
+    async fn test_validate_unknown_and_deleted() {
+        let (catalog, cache, validator, metrics) = setup().await;
+        let name = NamespaceName::try_from(NAMESPACE).unwrap();
+
+        // Cache a table the catalog does not have.
+        let mut schema = catalog_schema(&catalog).await;
+        let mut table = schema.tables.get("platanos").unwrap().clone();
+        table.columns = Default::default();
+        schema.tables.insert("bananas".to_string(), table);
+        cache.put_schema(name.clone(), schema);
+
+        assert_eq!(validator.validate(&name).await, [Divergence::Unknown]);
+        assert_eq!(validator.validate(&name).await, [Divergence::Unknown]);
+        assert_eq!(divergence_count(&metrics, "unknown"), 2);
+
+        catalog
+            .repositories()
+            .await
+            .namespaces()
+            .soft_delete(NAMESPACE)
+            .await
+            .unwrap();
+
+        assert_eq!(validator.validate(&name).await, [Divergence::Deleted]);
+        assert_eq!(divergence_count(&metrics, "deleted"), 1);
+    }
+}
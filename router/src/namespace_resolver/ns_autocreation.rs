@@ -233,6 +233,8 @@ mod tests {
                 retention_period_ns: TEST_RETENTION_PERIOD_NS,
                 deleted_at: None,
                 partition_template: Default::default(),
+                read_only: false,
+                object_store_name: None,
             }
         );
     }
@@ -6,6 +6,7 @@ use std::{collections::BTreeSet, sync::Arc};
 use data_types::{NamespaceId, NamespaceName, NamespaceSchema};
 use iox_catalog::interface::Catalog;
 use metric::U64Counter;
+use mutable_batch::value_policy::{FieldValueError, FieldValuePolicy};
 use observability_deps::tracing::*;
 use thiserror::Error;
 
@@ -20,6 +21,16 @@ pub enum SchemaError {
     #[error("schema conflict: {0}")]
     Conflict(iox_catalog::TableScopedError),
 
+    /// A field value in the request was rejected by the configured
+    /// [`FieldValuePolicy`].
+    #[error("invalid field value in table {table}: {source}")]
+    FieldValue {
+        /// The table containing the rejected value.
+        table: String,
+        /// The rejected value.
+        source: FieldValueError,
+    },
+
     /// A catalog error during schema validation.
     ///
     /// NOTE: this may be due to transient I/O errors while interrogating the
@@ -27,6 +38,50 @@ pub enum SchemaError {
     /// the failure reason.
     #[error(transparent)]
     UnexpectedCatalogError(iox_catalog::interface::Error),
+
+    /// More than one table in the request was rejected, each with a
+    /// [`SchemaError::Conflict`] or [`SchemaError::FieldValue`] error.
+    #[error(
+        "{} tables rejected: {}",
+        .0.len(),
+        .0.iter().map(ToString::to_string).collect::<Vec<_>>().join("; ")
+    )]
+    Tables(Vec<SchemaError>),
+}
+
+/// A table of a write request rejected by schema validation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TableRejection<'a> {
+    /// The name of the rejected table.
+    pub table: &'a str,
+    /// A machine-readable code of the rejection reason.
+    pub code: &'static str,
+    /// A human-readable description of the rejection reason.
+    pub message: String,
+}
+
+impl SchemaError {
+    /// Return the tables rejected by this error, if it is caused by the
+    /// content of the rejected tables alone.
+    ///
+    /// Returns an empty list for errors that are not scoped to tables, such
+    /// as service limit or catalog errors.
+    pub fn rejected_tables(&self) -> Vec<TableRejection<'_>> {
+        match self {
+            Self::Conflict(e) => vec![TableRejection {
+                table: e.table(),
+                code: "schema_conflict",
+                message: e.err().to_string(),
+            }],
+            Self::FieldValue { table, source } => vec![TableRejection {
+                table,
+                code: "invalid_field_value",
+                message: source.to_string(),
+            }],
+            Self::Tables(errors) => errors.iter().flat_map(|e| e.rejected_tables()).collect(),
+            Self::ServiceLimit(_) | Self::UnexpectedCatalogError(_) => vec![],
+        }
+    }
 }
 
 /// A [`SchemaValidator`] checks the schema of incoming writes against a
@@ -98,6 +153,19 @@ pub struct SchemaValidator<C> {
     pub(crate) service_limit_hit_tables: U64Counter,
     pub(crate) service_limit_hit_columns: U64Counter,
     pub(crate) schema_conflict: U64Counter,
+
+    pub(crate) field_value_policy: Option<FieldValuePolicyEnforcer>,
+}
+
+/// Enforces a [`FieldValuePolicy`] on the field values of writes, recording
+/// the actions taken.
+#[derive(Debug)]
+pub(crate) struct FieldValuePolicyEnforcer {
+    pub(crate) policy: FieldValuePolicy,
+
+    pub(crate) clamped: U64Counter,
+    pub(crate) nulled: U64Counter,
+    pub(crate) rejected: U64Counter,
 }
 
 impl<C> SchemaValidator<C> {
@@ -124,9 +192,34 @@ impl<C> SchemaValidator<C> {
             service_limit_hit_tables,
             service_limit_hit_columns,
             schema_conflict,
+            field_value_policy: None,
         }
     }
 
+    /// Enforce `policy` on the field values of all writes before validating
+    /// their schema.
+    ///
+    /// Unsigned integer values are converted to integers for columns that are
+    /// integer columns in the namespace schema.
+    pub fn with_field_value_policy(
+        mut self,
+        policy: FieldValuePolicy,
+        metrics: &metric::Registry,
+    ) -> Self {
+        let actions = metrics.register_metric::<U64Counter>(
+            "schema_validation_field_value_policy",
+            "number of field values clamped or nulled, and requests rejected, by the field value policy",
+        );
+
+        self.field_value_policy = Some(FieldValuePolicyEnforcer {
+            policy,
+            clamped: actions.recorder(&[("action", "clamped")]),
+            nulled: actions.recorder(&[("action", "nulled")]),
+            rejected: actions.recorder(&[("action", "rejected")]),
+        });
+        self
+    }
+
     /// Validate the schema changes specified are within the system's service limits.
     ///
     /// # Errors
@@ -6,7 +6,7 @@ use generated_types::influxdata::iox::{
 };
 use iox_catalog::interface::Catalog;
 use object_store::DynObjectStore;
-use service_grpc_catalog::CatalogService;
+use service_grpc_catalog::{CatalogIntrospectionService, CatalogService};
 use service_grpc_namespace::NamespaceService;
 use service_grpc_object_store::ObjectStoreService;
 use service_grpc_schema::SchemaService;
@@ -54,6 +54,15 @@ impl<T> RpcWriteGrpcDelegate<T> {
         CatalogService::new(Arc::clone(&self.catalog))
     }
 
+    /// Acquire a [`CatalogIntrospectionService`] gRPC service implementation.
+    ///
+    /// [`CatalogIntrospectionService`]: generated_types::influxdata::iox::catalog::v1::catalog_introspection_service_server::CatalogIntrospectionService.
+    pub fn catalog_introspection_service(
+        &self,
+    ) -> impl catalog_introspection_service_server::CatalogIntrospectionService {
+        CatalogIntrospectionService::new(Arc::clone(&self.catalog))
+    }
+
     /// Acquire a [`ObjectStoreService`] gRPC service implementation.
     ///
     /// [`ObjectStoreService`]: generated_types::influxdata::iox::object_store::v1::object_store_service_server::ObjectStoreService.
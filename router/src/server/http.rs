@@ -2,34 +2,45 @@
 
 pub mod write;
 
-use std::{str::Utf8Error, time::Instant};
+use std::{str::Utf8Error, sync::Arc, time::Instant};
 
 use bytes::{Bytes, BytesMut};
 use futures::StreamExt;
 use hashbrown::HashMap;
-use hyper::{header::CONTENT_ENCODING, Body, Method, Request, Response, StatusCode};
+use hyper::{
+    header::{CONTENT_ENCODING, CONTENT_TYPE},
+    Body, Method, Request, Response, StatusCode,
+};
 use iox_time::{SystemProvider, TimeProvider};
 use metric::{DurationHistogram, U64Counter};
 use mutable_batch::MutableBatch;
-use mutable_batch_lp::{LineError, LinesConverter};
+use mutable_batch_lp::{LineError, LinesConverter, NamingRules};
 use observability_deps::tracing::*;
+use schema::InfluxColumnType;
 use thiserror::Error;
 use tokio::sync::{Semaphore, TryAcquireError};
 use trace::ctx::SpanContext;
 
 use self::write::{
-    multi_tenant::MultiTenantExtractError, single_tenant::SingleTenantExtractError, WriteParams,
-    WriteRequestUnifier,
+    multi_tenant::MultiTenantExtractError,
+    single_tenant::SingleTenantExtractError,
+    tabular::{self, ConvertOptions, PayloadFormat, TabularError, TabularParams},
+    WriteParams, WriteRequestUnifier,
 };
 use crate::{
     dml_handlers::{
-        client::RpcWriteClientError, DmlError, DmlHandler, PartitionError, RetentionError,
-        RpcWriteError,
+        client::RpcWriteClientError, DefaultTagError, DmlError, DmlHandler, PartitionError,
+        ReadOnlyError, RetentionError, RpcWriteError,
     },
     namespace_resolver::NamespaceResolver,
-    schema_validator::SchemaError,
+    schema_validator::{SchemaError, TableRejection},
 };
 
+/// The HTTP header a client sets to `true` to accept the write of the valid
+/// subset of the tables in a request, when others are rejected by schema
+/// validation.
+pub const ACCEPT_PARTIAL_WRITE_HEADER: &str = "iox-accept-partial-write";
+
 /// Errors returned by the `router` HTTP request handler.
 #[derive(Debug, Error)]
 pub enum Error {
@@ -77,10 +88,20 @@ pub enum Error {
     #[error("failed to parse line protocol: {0}")]
     ParseLineProtocol(mutable_batch_lp::Error),
 
+    /// Failure to decode the provided CSV or Arrow IPC payload.
+    #[error("failed to decode CSV or Arrow IPC payload: {0}")]
+    Tabular(TabularError),
+
     /// An error returned from the [`DmlHandler`].
     #[error("dml handler error: {0}")]
     DmlHandler(#[from] DmlError),
 
+    /// The tables of the request rejected by schema validation were dropped,
+    /// and the remaining tables written, as the client opted in with the
+    /// [`ACCEPT_PARTIAL_WRITE_HEADER`].
+    #[error("partial write, rejected tables were not written: {0}")]
+    PartialWrite(SchemaError),
+
     /// An error that occurs when attempting to map the user-provided namespace
     /// name into a [`NamespaceId`].
     ///
@@ -114,12 +135,14 @@ impl Error {
             Error::NonUtf8ContentHeader(_) => StatusCode::BAD_REQUEST,
             Error::NonUtf8Body(_) => StatusCode::BAD_REQUEST,
             Error::ParseLineProtocol(_) => StatusCode::BAD_REQUEST,
+            Error::Tabular(_) => StatusCode::BAD_REQUEST,
             Error::RequestSizeExceeded(_) => StatusCode::PAYLOAD_TOO_LARGE,
             Error::InvalidContentEncoding(_) => {
                 // https://www.rfc-editor.org/rfc/rfc7231#section-6.5.13
                 StatusCode::UNSUPPORTED_MEDIA_TYPE
             }
             Error::DmlHandler(err) => StatusCode::from(err),
+            Error::PartialWrite(_) => StatusCode::BAD_REQUEST,
             // Error from the namespace resolver is 4xx if autocreation is disabled, 5xx otherwise
             Error::NamespaceResolver(crate::namespace_resolver::Error::Create(
                 crate::namespace_resolver::ns_autocreation::NamespaceCreationError::Reject(_),
@@ -146,13 +169,24 @@ impl Error {
                 {
                     LineError::LineProtocol { source: _, line }
                     | LineError::TimestampOverflow { line }
-                    | LineError::Write { source: _, line } => *line,
+                    | LineError::Write { source: _, line }
+                    | LineError::InvalidName { source: _, line }
+                    | LineError::RequiredTag { line, .. } => *line,
                 };
                 Some(line)
             }
             _ => None,
         }
     }
+
+    /// Return the tables of the request rejected by schema validation, if
+    /// any.
+    pub fn rejected_tables(&self) -> Vec<TableRejection<'_>> {
+        match self {
+            Self::DmlHandler(DmlError::Schema(e)) | Self::PartialWrite(e) => e.rejected_tables(),
+            _ => vec![],
+        }
+    }
 }
 
 impl From<&DmlError> for StatusCode {
@@ -165,16 +199,23 @@ impl From<&DmlError> for StatusCode {
                 StatusCode::BAD_REQUEST
             }
             DmlError::Schema(SchemaError::Conflict(_)) => StatusCode::BAD_REQUEST,
+            DmlError::Schema(SchemaError::FieldValue { .. }) => StatusCode::BAD_REQUEST,
+            DmlError::Schema(SchemaError::Tables(_)) => StatusCode::BAD_REQUEST,
             DmlError::Schema(SchemaError::UnexpectedCatalogError(_)) => {
                 StatusCode::INTERNAL_SERVER_ERROR
             }
 
             DmlError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            DmlError::GroupCommit(e) => StatusCode::from(e.as_ref()),
             DmlError::Partition(PartitionError::BatchWrite(_)) => StatusCode::INTERNAL_SERVER_ERROR,
             DmlError::Partition(PartitionError::Partitioner(_)) => {
                 StatusCode::INTERNAL_SERVER_ERROR
             }
             DmlError::Retention(RetentionError::OutsideRetention { .. }) => StatusCode::FORBIDDEN,
+            DmlError::ReadOnly(ReadOnlyError::ReadOnly(_)) => StatusCode::FORBIDDEN,
+            DmlError::ReadOnly(ReadOnlyError::Deleted(_)) => StatusCode::NOT_FOUND,
+            DmlError::DefaultTag(DefaultTagError::Conflict { .. })
+            | DmlError::DefaultTag(DefaultTagError::NotATag { .. }) => StatusCode::BAD_REQUEST,
             DmlError::RpcWrite(RpcWriteError::Client(RpcWriteClientError::Upstream(_))) => {
                 StatusCode::INTERNAL_SERVER_ERROR
             }
@@ -185,7 +226,7 @@ impl From<&DmlError> for StatusCode {
                 RpcWriteClientError::MisconfiguredMetadataValue(_),
             )) => StatusCode::INTERNAL_SERVER_ERROR,
             DmlError::RpcWrite(RpcWriteError::Client(
-                RpcWriteClientError::UpstreamNotConnected(_),
+                RpcWriteClientError::UpstreamNotConnected(_) | RpcWriteClientError::QueueFull,
             )) => StatusCode::SERVICE_UNAVAILABLE,
             DmlError::RpcWrite(RpcWriteError::Timeout(_)) => StatusCode::GATEWAY_TIMEOUT,
             DmlError::RpcWrite(
@@ -211,6 +252,10 @@ pub struct HttpDelegate<D, N, T = SystemProvider> {
     dml_handler: D,
     write_request_mode_handler: Box<dyn WriteRequestUnifier>,
 
+    /// The rules table, tag and field names of written line protocol must
+    /// satisfy, if any.
+    naming_rules: Option<NamingRules>,
+
     // A request limiter to restrict the number of simultaneous requests this
     // router services.
     //
@@ -285,6 +330,7 @@ impl<D, N> HttpDelegate<D, N, SystemProvider> {
             namespace_resolver,
             write_request_mode_handler,
             dml_handler,
+            naming_rules: None,
             request_sem: Semaphore::new(max_requests),
             write_metric_lines,
             http_line_protocol_parse_duration,
@@ -296,6 +342,15 @@ impl<D, N> HttpDelegate<D, N, SystemProvider> {
     }
 }
 
+impl<D, N, T> HttpDelegate<D, N, T> {
+    /// Validate the table, tag and field names of written line protocol
+    /// against `rules`, rejecting (or normalizing) names that violate them.
+    pub fn with_naming_rules(mut self, rules: NamingRules) -> Self {
+        self.naming_rules = Some(rules);
+        self
+    }
+}
+
 impl<D, N, T> HttpDelegate<D, N, T>
 where
     D: DmlHandler<WriteInput = HashMap<String, MutableBatch>, WriteOutput = ()>,
@@ -355,24 +410,74 @@ where
             "processing write request"
         );
 
-        // Read the HTTP body and convert it to a str.
+        // Negotiate the format of the body, and the table its rows are written
+        // to if it is not line protocol.
+        let format = PayloadFormat::from_content_type(
+            req.headers()
+                .get(&CONTENT_TYPE)
+                .and_then(|v| v.to_str().ok()),
+        );
+        let tabular_params = match format {
+            PayloadFormat::LineProtocol => None,
+            PayloadFormat::Csv | PayloadFormat::ArrowIpc => {
+                Some(TabularParams::try_from(&req).map_err(Error::Tabular)?)
+            }
+        };
+
+        let accept_partial = req
+            .headers()
+            .get(ACCEPT_PARTIAL_WRITE_HEADER)
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|v| v.eq_ignore_ascii_case("true"));
+
         let body = self.read_body(req).await?;
-        let body = std::str::from_utf8(&body).map_err(Error::NonUtf8Body)?;
 
         // The time, in nanoseconds since the epoch, to assign to any points that don't
         // contain a timestamp
         let default_time = self.time_provider.now().timestamp_nanos();
         let start_instant = Instant::now();
 
-        let mut converter = LinesConverter::new(default_time);
-        converter.set_timestamp_base(write_info.precision.timestamp_base());
-        let (batches, stats) = match converter.write_lp(body).and_then(|_| converter.finish()) {
-            Ok(v) => v,
-            Err(mutable_batch_lp::Error::EmptyPayload) => {
-                debug!("nothing to write");
-                return Ok(());
+        let (batches, stats) = match tabular_params {
+            None => {
+                let body = std::str::from_utf8(&body).map_err(Error::NonUtf8Body)?;
+
+                let mut converter = LinesConverter::new(default_time);
+                converter.set_timestamp_base(write_info.precision.timestamp_base());
+                if let Some(rules) = &self.naming_rules {
+                    converter.set_naming_rules(rules.clone());
+                }
+                converter.set_required_tags(
+                    write_info
+                        .row_filters
+                        .iter()
+                        .map(|f| (f.column().to_string(), f.value().to_string()))
+                        .collect(),
+                );
+                match converter.write_lp(body).and_then(|_| converter.finish()) {
+                    Ok(v) => v,
+                    Err(mutable_batch_lp::Error::EmptyPayload) => {
+                        debug!("nothing to write");
+                        return Ok(());
+                    }
+                    Err(line_errors) => return Err(Error::ParseLineProtocol(line_errors)),
+                }
+            }
+            Some(params) => {
+                let options = ConvertOptions {
+                    timestamp_base: write_info.precision.timestamp_base(),
+                    default_time,
+                    naming_rules: self.naming_rules.as_ref(),
+                    row_filters: &write_info.row_filters,
+                };
+                match tabular::convert(format, &body, &params, &options) {
+                    Ok(v) => v,
+                    Err(TabularError::EmptyPayload) => {
+                        debug!("nothing to write");
+                        return Ok(());
+                    }
+                    Err(e) => return Err(Error::Tabular(e)),
+                }
             }
-            Err(line_errors) => return Err(Error::ParseLineProtocol(line_errors)),
         };
 
         let num_tables = batches.len();
@@ -383,6 +488,7 @@ where
             num_fields=stats.num_fields,
             num_tables,
             precision=?write_info.precision,
+            ?format,
             body_size=body.len(),
             namespace=%write_info.namespace,
             duration=?duration,
@@ -395,17 +501,67 @@ where
             .get_namespace_schema(&write_info.namespace)
             .await?;
 
+        // Retain a copy of the batches to retry the write of the valid tables
+        // with, if the client accepts partial writes. A write of a single
+        // table has no valid subset to retry once rejected.
+        let retry_batches = (accept_partial && num_tables > 1).then(|| batches.clone());
+
+        let err = match self
+            .dml_handler
+            .write(
+                &write_info.namespace,
+                Arc::clone(&namespace_schema),
+                batches,
+                span_ctx.clone(),
+            )
+            .await
+            .map_err(Into::into)
+        {
+            Ok(()) => {
+                self.write_metric_lines.inc(stats.num_lines as _);
+                self.write_metric_fields.inc(stats.num_fields as _);
+                self.write_metric_tables.inc(num_tables as _);
+                self.write_metric_body_size.inc(body.len() as _);
+                return Ok(());
+            }
+            Err(DmlError::Schema(e)) => e,
+            Err(e) => return Err(e.into()),
+        };
+
+        // Drop the rejected tables and write the remainder, if any.
+        let mut batches = match retry_batches {
+            Some(v) if !err.rejected_tables().is_empty() => v,
+            _ => return Err(DmlError::Schema(err).into()),
+        };
+        for rejection in err.rejected_tables() {
+            batches.remove(rejection.table);
+        }
+        if batches.is_empty() {
+            return Err(DmlError::Schema(err).into());
+        }
+
+        let num_tables = batches.len();
+        let (num_lines, num_fields) = count_lines_and_fields(&batches);
+        debug!(
+            namespace=%write_info.namespace,
+            error=%err,
+            num_lines,
+            num_fields,
+            num_tables,
+            "writing valid subset of partially rejected write",
+        );
+
         self.dml_handler
             .write(&write_info.namespace, namespace_schema, batches, span_ctx)
             .await
             .map_err(Into::into)?;
 
-        self.write_metric_lines.inc(stats.num_lines as _);
-        self.write_metric_fields.inc(stats.num_fields as _);
+        self.write_metric_lines.inc(num_lines as _);
+        self.write_metric_fields.inc(num_fields as _);
         self.write_metric_tables.inc(num_tables as _);
         self.write_metric_body_size.inc(body.len() as _);
 
-        Ok(())
+        Err(Error::PartialWrite(err))
     }
 
     /// Parse the request's body into raw bytes, applying the configured size
@@ -466,6 +622,21 @@ where
     }
 }
 
+/// Return the number of lines and field values written to `batches`.
+///
+/// Each line is a row of its table's batch, and each field value a non-NULL
+/// value of a field column.
+fn count_lines_and_fields(batches: &HashMap<String, MutableBatch>) -> (usize, usize) {
+    batches.values().fold((0, 0), |(lines, fields), batch| {
+        let batch_fields = batch
+            .columns()
+            .filter(|(_, col)| matches!(col.influx_type(), InfluxColumnType::Field(_)))
+            .map(|(_, col)| col.len() - col.stats().null_count().unwrap_or_default() as usize)
+            .sum::<usize>();
+        (lines + batch.rows(), fields + batch_fields)
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use std::{io::Write, iter, sync::Arc, time::Duration};
@@ -478,7 +649,7 @@ mod tests {
     use hyper::header::HeaderValue;
     use metric::{Attributes, Metric};
     use mutable_batch::column::ColumnData;
-    use mutable_batch_lp::LineWriteError;
+    use mutable_batch_lp::{LineWriteError, NamingMode};
     use test_helpers::timeout::FutureTimeout;
     use tokio_stream::wrappers::ReceiverStream;
 
@@ -1105,6 +1276,7 @@ mod tests {
                     Ok(WriteParams {
                         namespace: NamespaceName::new(NAMESPACE_NAME).unwrap(),
                         precision: Precision::default(),
+                        row_filters: vec![],
                     })
                 })),
             ),
@@ -1217,6 +1389,219 @@ mod tests {
         assert_metric_hit(&metrics, "http_request_limit_rejected", Some(1));
     }
 
+    /// Assert the configured naming rules are applied to written line
+    /// protocol.
+    #[tokio::test]
+    async fn test_naming_rules() {
+        let mock_namespace_resolver =
+            MockNamespaceResolver::default().with_mapping(NAMESPACE_NAME, NamespaceId::new(42));
+
+        let dml_handler = Arc::new(MockDmlHandler::default().with_write_return([Ok(())]));
+        let metrics = Arc::new(metric::Registry::default());
+        let delegate = HttpDelegate::new(
+            MAX_BYTES,
+            1,
+            mock_namespace_resolver,
+            Arc::clone(&dml_handler),
+            &metrics,
+            Box::new(
+                MockWriteRequestUnifier::default().with_ret(iter::repeat_with(|| {
+                    Ok(WriteParams {
+                        namespace: NamespaceName::new(NAMESPACE_NAME).unwrap(),
+                        precision: Precision::default(),
+                        row_filters: vec![],
+                    })
+                })),
+            ),
+        )
+        .with_naming_rules(
+            NamingRules::new(NamingMode::Normalize)
+                .with_reserved_prefix("_")
+                .with_restricted_charset(),
+        );
+
+        let request = Request::builder()
+            .uri("https://bananas.example/api/v2/write?org=bananas&bucket=test")
+            .method("POST")
+            .body(Body::from("Platanos,_Tag1=A val=42i 123456"))
+            .unwrap();
+        delegate.route(request).await.expect("write should succeed");
+
+        assert_matches!(
+            dml_handler.calls().as_slice(),
+            [MockDmlHandlerCall::Write { write_input, .. }] => {
+                let batch = write_input.get("platanos").expect("normalized table name");
+                assert!(batch.column("tag1").is_ok());
+            }
+        );
+
+        // A name that is empty once normalized is rejected, identifying the
+        // line.
+        let request = Request::builder()
+            .uri("https://bananas.example/api/v2/write?org=bananas&bucket=test")
+            .method("POST")
+            .body(Body::from(
+                "platanos val=42i 123456\nplatanos,__=A val=42i 123456",
+            ))
+            .unwrap();
+        let err = delegate.route(request).await.unwrap_err();
+        assert_eq!(err.get_parse_error_line_index(), Some(2));
+        assert_eq!(err.as_status_code(), StatusCode::BAD_REQUEST);
+    }
+
+    /// Assert CSV and Arrow IPC payloads are negotiated by their content type
+    /// and written to the table named in the query string.
+    #[tokio::test]
+    async fn test_tabular_writes() {
+        use arrow::{
+            array::{ArrayRef, Float64Array, Int64Array},
+            ipc::writer::StreamWriter,
+            record_batch::RecordBatch,
+        };
+
+        let mock_namespace_resolver =
+            MockNamespaceResolver::default().with_mapping(NAMESPACE_NAME, NamespaceId::new(42));
+
+        let dml_handler = Arc::new(MockDmlHandler::default().with_write_return([Ok(()), Ok(())]));
+        let metrics = Arc::new(metric::Registry::default());
+        let delegate = HttpDelegate::new(
+            MAX_BYTES,
+            1,
+            mock_namespace_resolver,
+            Arc::clone(&dml_handler),
+            &metrics,
+            Box::<MultiTenantRequestUnifier>::default(),
+        );
+
+        let request = Request::builder()
+            .uri("https://bananas.example/api/v2/write?org=bananas&bucket=test&table=platanos&tags=host")
+            .method("POST")
+            .header(CONTENT_TYPE, "text/csv; charset=utf-8")
+            .body(Body::from("host,val,time\na,42,1\nb,24,2\n"))
+            .unwrap();
+        delegate.route(request).await.expect("write should succeed");
+
+        let batch = RecordBatch::try_from_iter([
+            ("val", Arc::new(Float64Array::from(vec![4.2])) as ArrayRef),
+            ("time", Arc::new(Int64Array::from(vec![1])) as ArrayRef),
+        ])
+        .unwrap();
+        let mut body = Vec::new();
+        let mut writer = StreamWriter::try_new(&mut body, &batch.schema()).unwrap();
+        writer.write(&batch).unwrap();
+        writer.finish().unwrap();
+        drop(writer);
+
+        let request = Request::builder()
+            .uri("https://bananas.example/api/v2/write?org=bananas&bucket=test&table=bananas")
+            .method("POST")
+            .header(CONTENT_TYPE, "application/vnd.apache.arrow.stream")
+            .body(Body::from(body))
+            .unwrap();
+        delegate.route(request).await.expect("write should succeed");
+
+        assert_matches!(
+            dml_handler.calls().as_slice(),
+            [
+                MockDmlHandlerCall::Write { write_input: csv, .. },
+                MockDmlHandlerCall::Write { write_input: ipc, .. },
+            ] => {
+                let batch = csv.get("platanos").expect("table from query string");
+                assert_eq!(batch.rows(), 2);
+                assert!(batch.column("host").is_ok());
+                let batch = ipc.get("bananas").expect("table from query string");
+                assert_eq!(batch.rows(), 1);
+                assert!(batch.column("val").is_ok());
+            }
+        );
+        assert_metric_hit(&metrics, "http_write_lines", Some(3));
+
+        // The table must be specified.
+        let request = Request::builder()
+            .uri("https://bananas.example/api/v2/write?org=bananas&bucket=test")
+            .method("POST")
+            .header(CONTENT_TYPE, "text/csv")
+            .body(Body::from("val\n42\n"))
+            .unwrap();
+        let err = delegate.route(request).await.unwrap_err();
+        assert_matches!(err, Error::Tabular(TabularError::NoTable));
+        assert_eq!(err.as_status_code(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_partial_write() {
+        let mock_namespace_resolver =
+            MockNamespaceResolver::default().with_mapping(NAMESPACE_NAME, NamespaceId::new(42));
+
+        let field_value_error = || {
+            Err(DmlError::Schema(SchemaError::FieldValue {
+                table: "bananas".to_string(),
+                source: mutable_batch::value_policy::FieldValueError::UnsignedOverflow {
+                    column: "val".to_string(),
+                    row: 0,
+                    value: u64::MAX,
+                },
+            }))
+        };
+        let dml_handler = Arc::new(MockDmlHandler::default().with_write_return([
+            field_value_error(),
+            field_value_error(),
+            Ok(()),
+        ]));
+        let metrics = Arc::new(metric::Registry::default());
+        let delegate = HttpDelegate::new(
+            MAX_BYTES,
+            1,
+            mock_namespace_resolver,
+            Arc::clone(&dml_handler),
+            &metrics,
+            Box::<MultiTenantRequestUnifier>::default(),
+        );
+
+        let request = || {
+            Request::builder()
+                .uri("https://bananas.example/api/v2/write?org=bananas&bucket=test")
+                .method("POST")
+                .body(Body::from(
+                    "bananas val=18446744073709551615u 1\nplatanos val=42i 1",
+                ))
+                .unwrap()
+        };
+
+        // Without opting in, the whole write is rejected.
+        let err = delegate.route(request()).await.unwrap_err();
+        assert_matches!(err, Error::DmlHandler(DmlError::Schema(_)));
+        assert_eq!(dml_handler.calls().len(), 1);
+
+        // Otherwise the valid tables are written, and the rejected ones
+        // reported.
+        let mut request = request();
+        request.headers_mut().insert(
+            ACCEPT_PARTIAL_WRITE_HEADER,
+            HeaderValue::from_static("true"),
+        );
+        let err = delegate.route(request).await.unwrap_err();
+        assert_matches!(err, Error::PartialWrite(_));
+        assert_eq!(err.as_status_code(), StatusCode::BAD_REQUEST);
+        assert_matches!(err.rejected_tables().as_slice(), [rejection] => {
+            assert_eq!(rejection.table, "bananas");
+            assert_eq!(rejection.code, "invalid_field_value");
+        });
+
+        // Only the accepted lines and fields are counted.
+        assert_metric_hit(&metrics, "http_write_lines", Some(1));
+        assert_metric_hit(&metrics, "http_write_fields", Some(1));
+        assert_metric_hit(&metrics, "http_write_tables", Some(1));
+
+        assert_matches!(
+            dml_handler.calls().as_slice(),
+            [_, _, MockDmlHandlerCall::Write { write_input, .. }] => {
+                assert_eq!(write_input.len(), 1);
+                assert!(write_input.contains_key("platanos"));
+            }
+        );
+    }
+
     /// Assert the router rejects writes to the V1 endpoint when in
     /// "multi-tenant" mode.
     #[tokio::test]
@@ -1261,6 +1646,7 @@ mod tests {
                 Ok(WriteParams {
                     namespace: NamespaceName::new(NAMESPACE_NAME).unwrap(),
                     precision: Precision::default(),
+                    row_filters: vec![],
                 })
             }),
         ));
@@ -1458,6 +1844,18 @@ mod tests {
             "failed to parse line protocol: empty write payload",
         ),
 
+        (
+            Tabular(write::tabular::TabularError::NoTable),
+            "failed to decode CSV or Arrow IPC payload: \
+            no table provided, set the `table` query parameter",
+        ),
+
+        (
+            Tabular(write::tabular::TabularError::TimestampOverflow(42)),
+            "failed to decode CSV or Arrow IPC payload: \
+            timestamp overflows i64 in row 42 (1-based)",
+        ),
+
         (
             ParseLineProtocol(mutable_batch_lp::Error::PerLine {
                 lines: vec![mutable_batch_lp::LineError::TimestampOverflow { line: 42 }]
@@ -1467,6 +1865,22 @@ mod tests {
             \ntimestamp overflows i64 on line 42 (1-based)",
         ),
 
+        (
+            ParseLineProtocol(mutable_batch_lp::Error::PerLine {
+                lines: vec![mutable_batch_lp::LineError::InvalidName {
+                    source: mutable_batch_lp::NameError::ReservedPrefix {
+                        kind: mutable_batch_lp::NameKind::Tag,
+                        name: "_host".into(),
+                        prefix: "_".into(),
+                    },
+                    line: 42,
+                }]
+            }),
+            "failed to parse line protocol: \
+            errors encountered on line(s):\
+            \ninvalid name on line 42 (1-based): tag name \"_host\" uses the reserved prefix \"_\"",
+        ),
+
         (
             ParseLineProtocol(mutable_batch_lp::Error::PerLine {
                 lines: vec![
@@ -1495,6 +1909,25 @@ mod tests {
             "dml handler error: namespace [namespace name] does not exist",
         ),
 
+        (
+            DmlHandler(DmlError::ReadOnly(ReadOnlyError::ReadOnly("[namespace name]".into()))),
+            "dml handler error: namespace [namespace name] is read-only and is not accepting writes",
+        ),
+
+        (
+            DmlHandler(DmlError::ReadOnly(ReadOnlyError::Deleted("[namespace name]".into()))),
+            "dml handler error: namespace [namespace name] has been deleted",
+        ),
+
+        (
+            DmlHandler(DmlError::DefaultTag(DefaultTagError::Conflict {
+                table: "[table]".into(),
+                tag: "[tag]".into(),
+                value: "[value]".into(),
+            })),
+            "dml handler error: table [table] sets tag [tag] to a value other than the namespace default [value]",
+        ),
+
         (
             NamespaceResolver({
                 let e = iox_catalog::interface::Error::NameExists { name: "[name]".into() };
@@ -1564,6 +1997,19 @@ mod tests {
             "dml handler error: service limit reached: couldn't create table bananas; limit reached on namespace 42",
         ),
 
+        (
+            DmlHandler(DmlError::Schema(SchemaError::FieldValue {
+                table: "bananas".to_string(),
+                source: mutable_batch::value_policy::FieldValueError::UnsignedOverflow {
+                    column: "platanos".to_string(),
+                    row: 4,
+                    value: u64::MAX,
+                },
+            })),
+            "dml handler error: invalid field value in table bananas: value 18446744073709551615 for integer field \
+            platanos in row 4 overflows i64",
+        ),
+
         // A single-tenant namespace parsing error
         (
             SingleTenantError(SingleTenantExtractError::InvalidNamespace(NamespaceNameError::LengthConstraint{name: "bananas".to_string()})),
@@ -6,6 +6,7 @@ pub mod v2;
 
 pub mod multi_tenant;
 pub mod single_tenant;
+pub mod tabular;
 
 mod params;
 pub use params::*;
@@ -3,11 +3,15 @@
 //! [V2 Write API]:
 //!     https://docs.influxdata.com/influxdb/v2.6/api/#operation/PostWrite
 
+use std::sync::Arc;
+
 use async_trait::async_trait;
+use authz::{self, Authorizer};
 use data_types::{NamespaceName, OrgBucketMappingError};
 use hyper::{Body, Request};
 
 use super::{
+    single_tenant::auth::authorize,
     v2::{V2WriteParseError, WriteParamsV2},
     WriteParams, WriteRequestUnifier,
 };
@@ -23,6 +27,10 @@ pub enum MultiTenantExtractError {
     /// A [`WriteParamsV2`] failed to be parsed from the HTTP request.
     #[error(transparent)]
     ParseV2Request(#[from] V2WriteParseError),
+
+    /// An error occurred verifying the authorization token.
+    #[error(transparent)]
+    Authorizer(authz::Error),
 }
 
 /// Implement a by-ref conversion to avoid "moving" the inner errors when only
@@ -38,6 +46,11 @@ impl From<&MultiTenantExtractError> for hyper::StatusCode {
             MultiTenantExtractError::ParseV2Request(
                 V2WriteParseError::NoQueryParams | V2WriteParseError::DecodeFail(_),
             ) => Self::BAD_REQUEST,
+            MultiTenantExtractError::Authorizer(e) => match e {
+                authz::Error::Forbidden => Self::FORBIDDEN,
+                authz::Error::NoToken => Self::UNAUTHORIZED,
+                _ => Self::FORBIDDEN,
+            },
         }
     }
 }
@@ -47,10 +60,23 @@ impl From<&MultiTenantExtractError> for hyper::StatusCode {
 /// This handler respects the [V2 Write API] without modification, and rejects
 /// any V1 write requests.
 ///
+/// If configured with an [`Authorizer`], the request token must grant write
+/// access to the resolved namespace - otherwise no authorization is
+/// performed.
+///
 /// [V2 Write API]:
 ///     https://docs.influxdata.com/influxdb/v2.6/api/#operation/PostWrite
 #[derive(Debug, Default)]
-pub struct MultiTenantRequestUnifier;
+pub struct MultiTenantRequestUnifier {
+    authz: Option<Arc<dyn Authorizer>>,
+}
+
+impl MultiTenantRequestUnifier {
+    /// Require all write requests to be authorized by `authz`.
+    pub fn with_authorizer(authz: Arc<dyn Authorizer>) -> Self {
+        Self { authz: Some(authz) }
+    }
+}
 
 #[async_trait]
 impl WriteRequestUnifier for MultiTenantRequestUnifier {
@@ -59,33 +85,49 @@ impl WriteRequestUnifier for MultiTenantRequestUnifier {
     }
 
     async fn parse_v2(&self, req: &Request<Body>) -> Result<WriteParams, Error> {
-        Ok(parse_v2(req)?)
+        Ok(parse_v2(req, self.authz.as_ref()).await?)
     }
 }
 
 // Parse a V2 write request for multi tenant mode.
-fn parse_v2(req: &Request<Body>) -> Result<WriteParams, MultiTenantExtractError> {
+async fn parse_v2(
+    req: &Request<Body>,
+    authz: Option<&Arc<dyn Authorizer>>,
+) -> Result<WriteParams, MultiTenantExtractError> {
     let write_params = WriteParamsV2::try_from(req)?;
 
     let namespace = NamespaceName::from_org_and_bucket(write_params.org, write_params.bucket)?;
 
+    let row_filters = match authz {
+        Some(authz) => authorize(authz, req, &namespace, None)
+            .await
+            .map_err(MultiTenantExtractError::Authorizer)?,
+        None => vec![],
+    };
+
     Ok(WriteParams {
         namespace,
         precision: write_params.precision,
+        row_filters,
     })
 }
 
 #[cfg(test)]
 mod tests {
     use assert_matches::assert_matches;
+    use authz::http::AuthorizationHeaderExtension;
     use data_types::NamespaceNameError;
+    use hyper::header::HeaderValue;
 
     use super::*;
-    use crate::server::http::write::Precision;
+    use crate::server::http::write::{
+        single_tenant::auth::mock::{MockAuthorizer, *},
+        Precision,
+    };
 
     #[tokio::test]
     async fn test_parse_v1_always_errors() {
-        let unifier = MultiTenantRequestUnifier;
+        let unifier = MultiTenantRequestUnifier::default();
 
         let got = unifier.parse_v1(&Request::default()).await;
         assert_matches!(got, Err(Error::NoHandler));
@@ -100,7 +142,7 @@ mod tests {
             paste::paste! {
                 #[tokio::test]
                 async fn [<test_parse_v2_ $name>]() {
-                    let unifier = MultiTenantRequestUnifier;
+                    let unifier = MultiTenantRequestUnifier::default();
 
                     let query = $query_string;
                     let request = Request::builder()
@@ -225,10 +267,56 @@ mod tests {
         query_string = "?org=banana&bucket=cool&precision=ms",
         want = Ok(WriteParams {
             namespace,
-            precision
+            precision,
+            ..
         }) => {
             assert_eq!(namespace.as_str(), "banana_cool");
             assert_matches!(precision, Precision::Milliseconds);
         }
     );
+
+    #[tokio::test]
+    async fn test_authorizer() {
+        let unifier =
+            MultiTenantRequestUnifier::with_authorizer(Arc::new(MockAuthorizer::default()));
+
+        let request = |token: Option<&'static str>| {
+            let mut request = Request::builder()
+                .uri("https://itsallbroken.com/ignored?org=bananas&bucket=test")
+                .method("POST")
+                .body(Body::from(""))
+                .unwrap();
+            request
+                .extensions_mut()
+                .insert(AuthorizationHeaderExtension::new(
+                    token.map(|t| HeaderValue::from_str(&format!("Token {t}")).unwrap()),
+                ));
+            request
+        };
+
+        let got = unifier
+            .parse_v2(&request(Some(MOCK_AUTH_VALID_TOKEN)))
+            .await;
+        assert_matches!(got, Ok(WriteParams { namespace, .. }) => {
+            assert_eq!(namespace.as_str(), "bananas_test");
+        });
+
+        let got = unifier
+            .parse_v2(&request(Some(MOCK_AUTH_NO_PERMS_TOKEN)))
+            .await;
+        assert_matches!(
+            got,
+            Err(Error::MultiTenantError(
+                MultiTenantExtractError::Authorizer(authz::Error::Forbidden)
+            ))
+        );
+
+        let got = unifier.parse_v2(&request(None)).await;
+        assert_matches!(
+            got,
+            Err(Error::MultiTenantError(
+                MultiTenantExtractError::Authorizer(authz::Error::NoToken)
+            ))
+        );
+    }
 }
@@ -1,6 +1,7 @@
 use std::sync::Arc;
 
 use async_trait::async_trait;
+use authz::RowFilter;
 use data_types::NamespaceName;
 use hyper::{Body, Request};
 use serde::Deserialize;
@@ -42,6 +43,9 @@ impl Precision {
 pub struct WriteParams {
     pub(crate) namespace: NamespaceName<'static>,
     pub(crate) precision: Precision,
+    /// The tag values the request token restricts written rows to, added to
+    /// lines without the tag.
+    pub(crate) row_filters: Vec<RowFilter>,
 }
 
 /// A [`WriteRequestUnifier`] abstraction returns a unified [`WriteParams`]
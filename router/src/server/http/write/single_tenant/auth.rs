@@ -4,17 +4,19 @@ use std::sync::Arc;
 
 use authz::{
     self, extract_token, http::AuthorizationHeaderExtension, Action, Authorizer, Error, Permission,
-    Resource,
+    Resource, RowFilter,
 };
 use data_types::NamespaceName;
 use hyper::{Body, Request};
 
+/// Authorize the write `req` to `namespace`, returning the row filters the
+/// written rows must match.
 pub(crate) async fn authorize(
     authz: &Arc<dyn Authorizer>,
     req: &Request<Body>,
     namespace: &NamespaceName<'_>,
     query_param_token: Option<String>,
-) -> Result<(), Error> {
+) -> Result<Vec<RowFilter>, Error> {
     let token = extract_token(
         req.extensions()
             .get::<AuthorizationHeaderExtension>()
@@ -27,8 +29,8 @@ pub(crate) async fn authorize(
         Action::Write,
     )];
 
-    authz.permissions(token, &perms).await?;
-    Ok(())
+    authz.permissions(token.clone(), &perms).await?;
+    authz.row_filters(token, namespace.as_str()).await
 }
 
 #[cfg(test)]
@@ -40,6 +42,7 @@ pub mod mock {
     pub const MOCK_AUTH_VALID_TOKEN: &str = "GOOD";
     pub const MOCK_AUTH_INVALID_TOKEN: &str = "UGLY";
     pub const MOCK_AUTH_NO_PERMS_TOKEN: &str = "BAD";
+    pub const MOCK_AUTH_TENANT_TOKEN: &str = "TENANT";
 
     #[derive(Debug, Default)]
     pub struct MockAuthorizer {}
@@ -53,7 +56,7 @@ pub mod mock {
         ) -> Result<Vec<Permission>, authz::Error> {
             match token {
                 Some(token) => match (&token as &dyn AsRef<[u8]>).as_ref() {
-                    b"GOOD" => Ok(perms.to_vec()),
+                    b"GOOD" | b"TENANT" => Ok(perms.to_vec()),
                     b"BAD" => Err(authz::Error::Forbidden),
                     b"UGLY" => Err(authz::Error::verification("test", "test error")),
                     _ => panic!("unexpected token"),
@@ -61,6 +64,17 @@ pub mod mock {
                 None => Err(authz::Error::NoToken),
             }
         }
+
+        async fn row_filters(
+            &self,
+            token: Option<Vec<u8>>,
+            _namespace: &str,
+        ) -> Result<Vec<RowFilter>, authz::Error> {
+            match token.as_deref() {
+                Some(b"TENANT") => Ok(vec![RowFilter::new("tenant_id", "x")]),
+                _ => Ok(vec![]),
+            }
+        }
     }
 }
 
@@ -171,6 +185,68 @@ mod tests {
         })
     }
 
+    #[tokio::test]
+    async fn test_authz_row_filters() {
+        static NAMESPACE_NAME: &str = "test";
+        let mock_namespace_resolver =
+            MockNamespaceResolver::default().with_mapping(NAMESPACE_NAME, NamespaceId::new(42));
+
+        let dml_handler = Arc::new(MockDmlHandler::default().with_write_return([Ok(())]));
+        let metrics = Arc::new(metric::Registry::default());
+        let authz = Arc::new(MockAuthorizer::default());
+        let delegate = HttpDelegate::new(
+            MAX_BYTES,
+            1,
+            mock_namespace_resolver,
+            Arc::clone(&dml_handler),
+            &metrics,
+            Box::new(SingleTenantRequestUnifier::new(authz)),
+        );
+
+        let write = |body: &'static str| {
+            Request::builder()
+                .uri("https://bananas.example/api/v2/write?org=bananas&bucket=test")
+                .method("POST")
+                .extension(AuthorizationHeaderExtension::new(Some(
+                    HeaderValue::from_str(format!("Token {MOCK_AUTH_TENANT_TOKEN}").as_str())
+                        .unwrap(),
+                )))
+                .body(Body::from(body))
+                .unwrap()
+        };
+
+        // Lines with the tag of another tenant are rejected.
+        let got = delegate
+            .route(write("platanos,tenant_id=y val=42i 123456"))
+            .await;
+        assert_matches!(
+            got,
+            Err(http::Error::ParseLineProtocol(mutable_batch_lp::Error::PerLine { lines })) => {
+                assert_matches!(
+                    lines.as_slice(),
+                    [mutable_batch_lp::LineError::RequiredTag { tag, want, got, line: 1 }] => {
+                        assert_eq!(tag, "tenant_id");
+                        assert_eq!(want, "x");
+                        assert_eq!(got, "y");
+                    }
+                );
+            }
+        );
+
+        // Lines without the tag have it stamped with the tenant's value.
+        let got = delegate.route(write("platanos val=42i 123456")).await;
+        assert_matches!(got, Ok(_));
+
+        let calls = dml_handler.calls();
+        assert_matches!(calls.as_slice(), [MockDmlHandlerCall::Write{write_input, ..}] => {
+            let table = write_input.get("platanos").expect("table not found");
+            assert_matches!(
+                table.column("tenant_id").expect("tenant_id not stamped").influx_type(),
+                schema::InfluxColumnType::Tag
+            );
+        })
+    }
+
     #[tokio::test]
     async fn test_authz_metric() {
         static NAMESPACE_NAME: &str = "test";
@@ -280,7 +356,7 @@ mod tests {
         token_header_ok,
         header_value = format!("Token {MOCK_AUTH_VALID_TOKEN}").as_str(),
         query_param_token = Some("ignore".to_string()),
-        want = Ok(())
+        want = Ok(filters) if filters.is_empty()
     );
 
     test_authorize!(
@@ -315,14 +391,14 @@ mod tests {
         token_header_missing_whitespace_match_next,
         header_value = "Token",
         query_param_token = Some(MOCK_AUTH_VALID_TOKEN.to_string()),
-        want = Ok(())
+        want = Ok(filters) if filters.is_empty()
     );
 
     test_authorize!(
         bearer_header_ok,
         header_value = format!("Bearer {MOCK_AUTH_VALID_TOKEN}").as_str(),
         query_param_token = Some("ignore".to_string()),
-        want = Ok(())
+        want = Ok(filters) if filters.is_empty()
     );
 
     test_authorize!(
@@ -336,7 +412,7 @@ mod tests {
         basic_header_ok,
         header_value = encode_basic_header(format!("ignore:{MOCK_AUTH_VALID_TOKEN}")).as_str(),
         query_param_token = Some("ignore".to_string()),
-        want = Ok(())
+        want = Ok(filters) if filters.is_empty()
     );
 
     test_authorize!(
@@ -371,7 +447,7 @@ mod tests {
         query_param_token_ok,
         header_value = "",
         query_param_token = Some(MOCK_AUTH_VALID_TOKEN.to_string()),
-        want = Ok(())
+        want = Ok(filters) if filters.is_empty()
     );
 
     test_authorize!(
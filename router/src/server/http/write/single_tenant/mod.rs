@@ -134,13 +134,14 @@ async fn parse_v1(
             )
         }
     })?;
-    authorize(authz, req, &namespace, write_params.password)
+    let row_filters = authorize(authz, req, &namespace, write_params.password)
         .await
         .map_err(SingleTenantExtractError::Authorizer)?;
 
     Ok(WriteParams {
         namespace,
         precision: write_params.precision,
+        row_filters,
     })
 }
 
@@ -161,13 +162,14 @@ async fn parse_v2(
         return Err(SingleTenantExtractError::NoBucketSpecified);
     }
     let namespace = NamespaceName::new(write_params.bucket)?;
-    authorize(authz, req, &namespace, None)
+    let row_filters = authorize(authz, req, &namespace, None)
         .await
         .map_err(SingleTenantExtractError::Authorizer)?;
 
     Ok(WriteParams {
         namespace,
         precision: write_params.precision,
+        row_filters,
     })
 }
 
@@ -304,7 +306,7 @@ mod tests {
     test_parse_v1!(
         no_rp,
         query_string = "?db=bananas",
-        want = Ok(WriteParams{ namespace, precision }) => {
+        want = Ok(WriteParams{ namespace, precision, .. }) => {
             assert_eq!(namespace.as_str(), "bananas");
             assert_matches!(precision, Precision::Nanoseconds);
         }
@@ -314,7 +316,7 @@ mod tests {
     test_parse_v1!(
         no_rp_db_with_rp_separator,
         query_string = "?db=bananas/are/great",
-        want = Ok(WriteParams{ namespace, precision }) => {
+        want = Ok(WriteParams{ namespace, precision, .. }) => {
             assert_eq!(namespace.as_str(), "bananas/are/great");
             assert_matches!(precision, Precision::Nanoseconds);
         }
@@ -324,7 +326,7 @@ mod tests {
     test_parse_v1!(
         rp_with_rp_separator,
         query_string = "?db=bananas&rp=are/great",
-        want = Ok(WriteParams{ namespace, precision }) => {
+        want = Ok(WriteParams{ namespace, precision, .. }) => {
             assert_eq!(namespace.as_str(), "bananas/are/great");
             assert_matches!(precision, Precision::Nanoseconds);
         }
@@ -334,7 +336,7 @@ mod tests {
     test_parse_v1!(
         db_with_rp_separator_and_rp,
         query_string = "?db=foo/bar&rp=my_rp",
-        want = Ok(WriteParams{ namespace, precision }) => {
+        want = Ok(WriteParams{ namespace, precision, .. }) => {
             assert_eq!(namespace.as_str(), "foo/bar/my_rp");
             assert_matches!(precision, Precision::Nanoseconds);
         }
@@ -345,7 +347,7 @@ mod tests {
     test_parse_v1!(
         db_with_rp_separator_and_duplicate_rp,
         query_string = "?db=foo/my_rp&rp=my_rp",
-        want = Ok(WriteParams{ namespace, precision }) => {
+        want = Ok(WriteParams{ namespace, precision, .. }) => {
             assert_eq!(namespace.as_str(), "foo/my_rp/my_rp");
             assert_matches!(precision, Precision::Nanoseconds);
         }
@@ -355,7 +357,7 @@ mod tests {
     test_parse_v1!(
         db_with_rp_separator_and_rp_autogen,
         query_string = "?db=foo/bar&rp=autogen",
-        want = Ok(WriteParams{ namespace, precision }) => {
+        want = Ok(WriteParams{ namespace, precision, .. }) => {
             assert_eq!(namespace.as_str(), "foo/bar");
             assert_matches!(precision, Precision::Nanoseconds);
         }
@@ -365,7 +367,7 @@ mod tests {
     test_parse_v1!(
         db_with_rp_separator_and_rp_default,
         query_string = "?db=foo/bar&rp=default",
-        want = Ok(WriteParams{ namespace, precision }) => {
+        want = Ok(WriteParams{ namespace, precision, .. }) => {
             assert_eq!(namespace.as_str(), "foo/bar");
             assert_matches!(precision, Precision::Nanoseconds);
         }
@@ -374,7 +376,7 @@ mod tests {
     test_parse_v1!(
         rp_empty,
         query_string = "?db=bananas&rp=",
-        want = Ok(WriteParams{ namespace, precision }) => {
+        want = Ok(WriteParams{ namespace, precision, .. }) => {
             assert_eq!(namespace.as_str(), "bananas");
             assert_matches!(precision, Precision::Nanoseconds);
         }
@@ -383,7 +385,7 @@ mod tests {
     test_parse_v1!(
         rp_empty_quotes,
         query_string = "?db=bananas&rp=''",
-        want = Ok(WriteParams{ namespace, precision }) => {
+        want = Ok(WriteParams{ namespace, precision, .. }) => {
             assert_eq!(namespace.as_str(), "bananas");
             assert_matches!(precision, Precision::Nanoseconds);
         }
@@ -392,7 +394,7 @@ mod tests {
     test_parse_v1!(
         rp_autogen,
         query_string = "?db=bananas&rp=autogen",
-        want = Ok(WriteParams{ namespace, precision }) => {
+        want = Ok(WriteParams{ namespace, precision, .. }) => {
             assert_eq!(namespace.as_str(), "bananas");
             assert_matches!(precision, Precision::Nanoseconds);
         }
@@ -401,7 +403,7 @@ mod tests {
     test_parse_v1!(
         rp_specified,
         query_string = "?db=bananas&rp=ageless",
-        want = Ok(WriteParams{ namespace, precision }) => {
+        want = Ok(WriteParams{ namespace, precision, .. }) => {
             assert_eq!(namespace.as_str(), "bananas/ageless");
             assert_matches!(precision, Precision::Nanoseconds);
         }
@@ -410,7 +412,7 @@ mod tests {
     test_parse_v1!(
         encoded_case_sensitive,
         query_string = "?db=BaNanas",
-        want = Ok(WriteParams{ namespace, precision: _ }) => {
+        want = Ok(WriteParams{ namespace, .. }) => {
             assert_eq!(namespace.as_str(), "BaNanas");
         }
     );
@@ -426,7 +428,7 @@ mod tests {
     test_parse_v1!(
         start_nonalphanumeric,
         query_string = "?db=_bananas",
-        want = Ok(WriteParams{ namespace, precision: _ }) => {
+        want = Ok(WriteParams{ namespace, .. }) => {
             assert_eq!(namespace.as_str(), "_bananas");
         }
     );
@@ -434,7 +436,7 @@ mod tests {
     test_parse_v1!(
         minimum_length_possible,
         query_string = "?db=d",
-        want = Ok(WriteParams{ namespace, precision: _ }) => {
+        want = Ok(WriteParams{ namespace, .. }) => {
             assert_eq!(namespace.as_str().len(), 1);
         }
     );
@@ -442,7 +444,7 @@ mod tests {
     test_parse_v1!(
         with_precision,
         query_string = "?db=bananas&rp=ageless&precision=ms",
-        want = Ok(WriteParams{ namespace, precision }) => {
+        want = Ok(WriteParams{ namespace, precision, .. }) => {
             assert_eq!(namespace.as_str(), "bananas/ageless");
             assert_matches!(precision, Precision::Milliseconds);
         }
@@ -489,7 +491,7 @@ mod tests {
     test_parse_v2!(
         bucket_only,
         query_string = "?bucket=bananas",
-        want = Ok(WriteParams{ namespace, precision }) => {
+        want = Ok(WriteParams{ namespace, precision, .. }) => {
             assert_eq!(namespace.as_str(), "bananas");
             assert_matches!(precision, Precision::Nanoseconds);
         }
@@ -543,7 +545,8 @@ mod tests {
         query_string = "?org=wat&bucket=bananas",
         want = Ok(WriteParams {
             namespace,
-            precision
+            precision,
+            ..
         }) => {
             assert_eq!(namespace.as_str(), "bananas");
             assert_matches!(precision, Precision::Nanoseconds);
@@ -596,7 +599,8 @@ mod tests {
         query_string = "?bucket=bananas&precision=ms",
         want = Ok(WriteParams {
             namespace,
-            precision
+            precision,
+            ..
         }) => {
             assert_eq!(namespace.as_str(), "bananas");
             assert_matches!(precision, Precision::Milliseconds);
@@ -0,0 +1,862 @@
+//! Conversion of CSV and [Arrow IPC stream] write request bodies to
+//! [`MutableBatch`], as an alternative to line protocol.
+//!
+//! The format of a request body is negotiated by its `Content-Type` header,
+//! and its rows are written to the single table named by the `table` query
+//! parameter. Columns are mapped to tags, fields and the timestamp as
+//! described by [`TabularParams`].
+//!
+//! [Arrow IPC stream]:
+//!     https://arrow.apache.org/docs/format/Columnar.html#ipc-streaming-format
+
+use std::{borrow::Cow, io::Cursor, str::FromStr, sync::Arc};
+
+use arrow::{
+    array::{as_boolean_array, as_primitive_array, as_string_array, Array, ArrayRef},
+    compute::cast,
+    csv::{reader::Format, ReaderBuilder},
+    datatypes::{
+        DataType, Field, Float64Type, Int64Type, Schema, TimeUnit, TimestampNanosecondType,
+        UInt64Type,
+    },
+    error::ArrowError,
+    ipc::reader::StreamReader,
+    record_batch::RecordBatch,
+};
+use authz::RowFilter;
+use hashbrown::HashMap;
+use hyper::Request;
+use mutable_batch::{writer::Writer, MutableBatch};
+use mutable_batch_lp::{NameError, NameKind, NamingRules, PayloadStatistics};
+use schema::TIME_COLUMN_NAME;
+use serde::Deserialize;
+use thiserror::Error;
+
+/// The `Content-Type` of Arrow IPC stream request bodies.
+pub const ARROW_STREAM_CONTENT_TYPE: &str = "application/vnd.apache.arrow.stream";
+
+/// The `Content-Type` of CSV request bodies.
+pub const CSV_CONTENT_TYPE: &str = "text/csv";
+
+/// Errors converting a CSV or Arrow IPC request body.
+#[derive(Debug, Error)]
+pub enum TabularError {
+    /// The request contains no destination table.
+    #[error("no table provided, set the `table` query parameter")]
+    NoTable,
+
+    /// The request contains invalid parameters.
+    #[error("failed to deserialize table/tags/time in request: {0}")]
+    DecodeFail(#[from] serde::de::value::Error),
+
+    /// The request body cannot be decoded.
+    #[error("invalid payload: {0}")]
+    Decode(#[from] ArrowError),
+
+    /// A CSV header has an unknown type annotation.
+    #[error("invalid type annotation '{annotation}' of column '{column}'")]
+    InvalidAnnotation {
+        /// The name of the column.
+        column: String,
+        /// The annotated type.
+        annotation: String,
+    },
+
+    /// A column listed in the `tags` query parameter is not in the payload.
+    #[error("tag column '{0}' not found in payload")]
+    MissingTag(String),
+
+    /// A column has a type that cannot be written.
+    #[error("column '{column}' has unsupported type {data_type}")]
+    UnsupportedType {
+        /// The name of the column.
+        column: String,
+        /// The type of the column.
+        data_type: DataType,
+    },
+
+    /// A timestamp overflows once converted to nanoseconds.
+    #[error("timestamp overflows i64 in row {0} (1-based)")]
+    TimestampOverflow(usize),
+
+    /// A row has a tag value other than the one the request is restricted to.
+    #[error("tag '{tag}' must be '{want}' in row {row} (1-based), got '{got}'")]
+    RequiredTag {
+        /// The name of the tag.
+        tag: String,
+        /// The value the tag must have.
+        want: String,
+        /// The value of the tag in the row.
+        got: String,
+        /// The row, counted from 1.
+        row: usize,
+    },
+
+    /// A table, tag or field name violates the naming rules.
+    #[error("invalid name: {0}")]
+    InvalidName(#[from] NameError),
+
+    /// The rows cannot be written to the table.
+    #[error(transparent)]
+    Write(#[from] mutable_batch::writer::Error),
+
+    /// The payload has no rows.
+    #[error("empty write payload")]
+    EmptyPayload,
+}
+
+/// The format of a write request body.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PayloadFormat {
+    /// Line protocol, used unless another format is negotiated.
+    LineProtocol,
+    /// CSV with a header row, see [`CSV_CONTENT_TYPE`].
+    Csv,
+    /// An Arrow IPC stream, see [`ARROW_STREAM_CONTENT_TYPE`].
+    ArrowIpc,
+}
+
+impl PayloadFormat {
+    /// Negotiate the format of a request body from its `Content-Type`.
+    ///
+    /// Any other (or no) content type is treated as line protocol, which is
+    /// commonly sent as `text/plain` or `application/x-www-form-urlencoded`.
+    pub fn from_content_type(content_type: Option<&str>) -> Self {
+        let mime = content_type
+            .and_then(|v| v.split(';').next())
+            .map(|v| v.trim().to_ascii_lowercase());
+        match mime.as_deref() {
+            Some(CSV_CONTENT_TYPE) => Self::Csv,
+            Some(ARROW_STREAM_CONTENT_TYPE) => Self::ArrowIpc,
+            _ => Self::LineProtocol,
+        }
+    }
+}
+
+/// Query parameters mapping the columns of CSV and Arrow IPC request bodies.
+#[derive(Debug, Deserialize)]
+pub struct TabularParams {
+    /// The table the rows are written to.
+    table: Option<String>,
+
+    /// A comma-separated list of the columns written as tags.
+    ///
+    /// Arrow dictionary-encoded string columns and CSV columns annotated as
+    /// `<name>:tag` are always written as tags.
+    #[serde(default)]
+    tags: String,
+
+    /// The name of the column holding the timestamp of each row, either as an
+    /// integer in the request precision or an (RFC 3339) timestamp. Rows
+    /// without a timestamp are written at the time of the request.
+    #[serde(default = "default_time_column", rename = "time")]
+    time_column: String,
+}
+
+fn default_time_column() -> String {
+    TIME_COLUMN_NAME.to_string()
+}
+
+impl TabularParams {
+    fn is_tag(&self, column: &str) -> bool {
+        self.tags().any(|t| t == column)
+    }
+
+    fn tags(&self) -> impl Iterator<Item = &str> {
+        self.tags
+            .split(',')
+            .map(str::trim)
+            .filter(|t| !t.is_empty())
+    }
+}
+
+impl<T> TryFrom<&Request<T>> for TabularParams {
+    type Error = TabularError;
+
+    fn try_from(req: &Request<T>) -> Result<Self, Self::Error> {
+        let query = req.uri().query().ok_or(TabularError::NoTable)?;
+        let params: Self = serde_urlencoded::from_str(query)?;
+        match params.table.as_deref() {
+            Some(t) if !t.is_empty() => Ok(params),
+            _ => Err(TabularError::NoTable),
+        }
+    }
+}
+
+/// The type of a CSV column, annotated in the header as `<name>:<type>`.
+///
+/// Unannotated columns have their type inferred from their values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CsvColumnType {
+    Tag,
+    Float,
+    Integer,
+    Unsigned,
+    String,
+    Boolean,
+    Time,
+}
+
+impl FromStr for CsvColumnType {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "tag" => Self::Tag,
+            "float" => Self::Float,
+            "integer" => Self::Integer,
+            "unsigned" => Self::Unsigned,
+            "string" => Self::String,
+            "boolean" => Self::Boolean,
+            "time" => Self::Time,
+            _ => return Err(()),
+        })
+    }
+}
+
+/// How a column of the payload is written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ColumnKind {
+    Tag,
+    Field,
+    Time,
+}
+
+/// Options applied when converting a request body.
+#[derive(Debug)]
+pub(crate) struct ConvertOptions<'a> {
+    /// The multiplier converting integer timestamps to nanoseconds.
+    pub(crate) timestamp_base: i64,
+    /// The timestamp of rows without one.
+    pub(crate) default_time: i64,
+    /// The rules table, tag and field names must satisfy, if any.
+    pub(crate) naming_rules: Option<&'a NamingRules>,
+    /// The tag values every row must have.
+    pub(crate) row_filters: &'a [RowFilter],
+}
+
+/// Convert the `format` request `body` to a batch of the table in `params`.
+pub(crate) fn convert(
+    format: PayloadFormat,
+    body: &[u8],
+    params: &TabularParams,
+    options: &ConvertOptions<'_>,
+) -> Result<(HashMap<String, MutableBatch>, PayloadStatistics), TabularError> {
+    let (batches, kinds) = match format {
+        PayloadFormat::Csv => read_csv(body, params)?,
+        PayloadFormat::ArrowIpc => read_arrow_ipc(body, params)?,
+        PayloadFormat::LineProtocol => unreachable!("line protocol is not tabular"),
+    };
+
+    let table = params.table.as_deref().ok_or(TabularError::NoTable)?;
+    let table = apply_naming_rules(options, NameKind::Table, table)?;
+
+    let mut batch = MutableBatch::new();
+    let mut stats = PayloadStatistics::default();
+    for record_batch in &batches {
+        stats.num_fields +=
+            write_record_batch(&mut batch, record_batch, &kinds, options, stats.num_lines)?;
+        stats.num_lines += record_batch.num_rows();
+    }
+
+    if stats.num_lines == 0 {
+        return Err(TabularError::EmptyPayload);
+    }
+
+    Ok((HashMap::from([(table.into_owned(), batch)]), stats))
+}
+
+/// Read the CSV `body`, returning its rows and how each column is written.
+fn read_csv(
+    body: &[u8],
+    params: &TabularParams,
+) -> Result<(Vec<RecordBatch>, Vec<ColumnKind>), TabularError> {
+    let format = Format::default().with_header(true);
+    let (inferred, _) = format.infer_schema(Cursor::new(body), None)?;
+
+    let mut fields = Vec::with_capacity(inferred.fields().len());
+    let mut kinds = Vec::with_capacity(inferred.fields().len());
+    for field in inferred.fields() {
+        let (name, annotation) = match field.name().rsplit_once(':') {
+            Some((name, annotation)) => {
+                let t = annotation.parse::<CsvColumnType>().map_err(|_| {
+                    TabularError::InvalidAnnotation {
+                        column: name.to_string(),
+                        annotation: annotation.to_string(),
+                    }
+                })?;
+                (name, Some(t))
+            }
+            None => (field.name().as_str(), None),
+        };
+
+        let inferred_type = field.data_type();
+        let (kind, data_type) = match annotation {
+            Some(CsvColumnType::Time) => (ColumnKind::Time, time_type(inferred_type)),
+            None if name == params.time_column => (ColumnKind::Time, time_type(inferred_type)),
+            Some(CsvColumnType::Tag) => (ColumnKind::Tag, DataType::Utf8),
+            _ if params.is_tag(name) => (ColumnKind::Tag, DataType::Utf8),
+            Some(CsvColumnType::Float) => (ColumnKind::Field, DataType::Float64),
+            Some(CsvColumnType::Integer) => (ColumnKind::Field, DataType::Int64),
+            Some(CsvColumnType::Unsigned) => (ColumnKind::Field, DataType::UInt64),
+            Some(CsvColumnType::String) => (ColumnKind::Field, DataType::Utf8),
+            Some(CsvColumnType::Boolean) => (ColumnKind::Field, DataType::Boolean),
+            None => match inferred_type {
+                DataType::Boolean | DataType::Int64 | DataType::Float64 => {
+                    (ColumnKind::Field, inferred_type.clone())
+                }
+                // Dates, empty columns and anything else are read as strings.
+                _ => (ColumnKind::Field, DataType::Utf8),
+            },
+        };
+
+        let name = match kind {
+            ColumnKind::Time => TIME_COLUMN_NAME,
+            _ => name,
+        };
+        fields.push(Field::new(name, data_type, true));
+        kinds.push(kind);
+    }
+
+    let schema = Arc::new(Schema::new(fields));
+    check_tags(&schema, params)?;
+
+    let batches = ReaderBuilder::new(schema)
+        .with_format(format)
+        .build(Cursor::new(body))?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok((batches, kinds))
+}
+
+/// The type a CSV time column is read as.
+fn time_type(inferred: &DataType) -> DataType {
+    match inferred {
+        DataType::Timestamp(_, _) => DataType::Timestamp(TimeUnit::Nanosecond, None),
+        _ => DataType::Int64,
+    }
+}
+
+/// Read the Arrow IPC stream `body`, returning its rows and how each column is
+/// written.
+fn read_arrow_ipc(
+    body: &[u8],
+    params: &TabularParams,
+) -> Result<(Vec<RecordBatch>, Vec<ColumnKind>), TabularError> {
+    let reader = StreamReader::try_new(Cursor::new(body), None)?;
+    let schema = reader.schema();
+    check_tags(&schema, params)?;
+
+    let kinds = schema
+        .fields()
+        .iter()
+        .map(|field| match field.data_type() {
+            _ if field.name() == &params.time_column => ColumnKind::Time,
+            DataType::Dictionary(_, v) if v.as_ref() == &DataType::Utf8 => ColumnKind::Tag,
+            _ if params.is_tag(field.name()) => ColumnKind::Tag,
+            _ => ColumnKind::Field,
+        })
+        .collect();
+
+    let batches = reader.collect::<Result<Vec<_>, _>>()?;
+    Ok((batches, kinds))
+}
+
+/// Ensure every column listed in the `tags` query parameter is in `schema`.
+fn check_tags(schema: &Schema, params: &TabularParams) -> Result<(), TabularError> {
+    match params.tags().find(|t| schema.column_with_name(t).is_none()) {
+        Some(t) => Err(TabularError::MissingTag(t.to_string())),
+        None => Ok(()),
+    }
+}
+
+/// Write the rows of `record_batch` to `batch`, returning the number of
+/// non-NULL field values written.
+///
+/// `first_row` is the number of rows of the payload written before, used to
+/// number rows in errors.
+fn write_record_batch(
+    batch: &mut MutableBatch,
+    record_batch: &RecordBatch,
+    kinds: &[ColumnKind],
+    options: &ConvertOptions<'_>,
+    first_row: usize,
+) -> Result<usize, TabularError> {
+    let rows = record_batch.num_rows();
+    if rows == 0 {
+        return Ok(0);
+    }
+
+    let schema = record_batch.schema();
+    let mut writer = Writer::new(batch, rows);
+    let mut num_fields = 0;
+    let mut has_time = false;
+    let mut written_tags = Vec::new();
+
+    for ((field, array), kind) in schema
+        .fields()
+        .iter()
+        .zip(record_batch.columns())
+        .zip(kinds.iter().copied())
+    {
+        match kind {
+            ColumnKind::Time => {
+                let times = timestamps(field.name(), array, options, first_row)?;
+                writer.write_time(TIME_COLUMN_NAME, times.into_iter())?;
+                has_time = true;
+            }
+            ColumnKind::Tag => {
+                let name = apply_naming_rules(options, NameKind::Tag, field.name())?;
+                let values = cast(array, &DataType::Utf8)?;
+                let values = as_string_array(&values);
+
+                match options.row_filters.iter().find(|f| f.column() == name) {
+                    Some(filter) => {
+                        // Rows must have the required value, which is
+                        // written to those without the tag.
+                        let want = filter.value();
+                        if let Some(row) =
+                            (0..rows).find(|i| values.is_valid(*i) && values.value(*i) != want)
+                        {
+                            return Err(TabularError::RequiredTag {
+                                tag: name.into_owned(),
+                                want: want.to_string(),
+                                got: values.value(row).to_string(),
+                                row: first_row + row + 1,
+                            });
+                        }
+                        writer.write_tag(&name, None, std::iter::repeat(want).take(rows))?;
+                    }
+                    None => writer.write_tag(
+                        &name,
+                        Some(&valid_mask(values)),
+                        valid_values(&values).map(|i| values.value(i)),
+                    )?,
+                }
+                written_tags.push(name.into_owned());
+            }
+            ColumnKind::Field => {
+                let name = apply_naming_rules(options, NameKind::Field, field.name())?;
+                num_fields += write_field(&mut writer, &name, array)?;
+            }
+        }
+    }
+
+    if !has_time {
+        writer.write_time(
+            TIME_COLUMN_NAME,
+            std::iter::repeat(options.default_time).take(rows),
+        )?;
+    }
+
+    for filter in options.row_filters {
+        if !written_tags.iter().any(|t| t == filter.column()) {
+            writer.write_tag(
+                filter.column(),
+                None,
+                std::iter::repeat(filter.value()).take(rows),
+            )?;
+        }
+    }
+
+    writer.commit();
+    Ok(num_fields)
+}
+
+/// Write the field column `array` named `name`, returning the number of
+/// non-NULL values written.
+fn write_field(
+    writer: &mut Writer<'_>,
+    name: &str,
+    array: &ArrayRef,
+) -> Result<usize, TabularError> {
+    let mask = valid_mask(array.as_ref());
+    match array.data_type() {
+        DataType::Float16 | DataType::Float32 | DataType::Float64 => {
+            let values = cast(array, &DataType::Float64)?;
+            let values = as_primitive_array::<Float64Type>(&values);
+            writer.write_f64(
+                name,
+                Some(&mask),
+                valid_values(values).map(|i| values.value(i)),
+            )?;
+        }
+        DataType::Int8 | DataType::Int16 | DataType::Int32 | DataType::Int64 => {
+            let values = cast(array, &DataType::Int64)?;
+            let values = as_primitive_array::<Int64Type>(&values);
+            writer.write_i64(
+                name,
+                Some(&mask),
+                valid_values(values).map(|i| values.value(i)),
+            )?;
+        }
+        DataType::UInt8 | DataType::UInt16 | DataType::UInt32 | DataType::UInt64 => {
+            let values = cast(array, &DataType::UInt64)?;
+            let values = as_primitive_array::<UInt64Type>(&values);
+            writer.write_u64(
+                name,
+                Some(&mask),
+                valid_values(values).map(|i| values.value(i)),
+            )?;
+        }
+        DataType::Boolean => {
+            let values = as_boolean_array(array);
+            writer.write_bool(
+                name,
+                Some(&mask),
+                valid_values(values).map(|i| values.value(i)),
+            )?;
+        }
+        DataType::Utf8 | DataType::LargeUtf8 => {
+            let values = cast(array, &DataType::Utf8)?;
+            let values = as_string_array(&values);
+            writer.write_string(
+                name,
+                Some(&mask),
+                valid_values(values).map(|i| values.value(i)),
+            )?;
+        }
+        data_type => {
+            return Err(TabularError::UnsupportedType {
+                column: name.to_string(),
+                data_type: data_type.clone(),
+            })
+        }
+    }
+    Ok(array.len() - array.null_count())
+}
+
+/// Return the timestamps in nanoseconds of the time column `array`, using the
+/// default time for NULL values.
+fn timestamps(
+    name: &str,
+    array: &ArrayRef,
+    options: &ConvertOptions<'_>,
+    first_row: usize,
+) -> Result<Vec<i64>, TabularError> {
+    match array.data_type() {
+        DataType::Timestamp(_, _) => {
+            let values = cast(array, &DataType::Timestamp(TimeUnit::Nanosecond, None))?;
+            let values = as_primitive_array::<TimestampNanosecondType>(&values);
+            Ok((0..values.len())
+                .map(|i| match values.is_valid(i) {
+                    true => values.value(i),
+                    false => options.default_time,
+                })
+                .collect())
+        }
+        DataType::Int8
+        | DataType::Int16
+        | DataType::Int32
+        | DataType::Int64
+        | DataType::UInt8
+        | DataType::UInt16
+        | DataType::UInt32 => {
+            let values = cast(array, &DataType::Int64)?;
+            let values = as_primitive_array::<Int64Type>(&values);
+            (0..values.len())
+                .map(|i| match values.is_valid(i) {
+                    true => values
+                        .value(i)
+                        .checked_mul(options.timestamp_base)
+                        .ok_or(TabularError::TimestampOverflow(first_row + i + 1)),
+                    false => Ok(options.default_time),
+                })
+                .collect()
+        }
+        data_type => Err(TabularError::UnsupportedType {
+            column: name.to_string(),
+            data_type: data_type.clone(),
+        }),
+    }
+}
+
+fn apply_naming_rules<'a>(
+    options: &ConvertOptions<'_>,
+    kind: NameKind,
+    name: &'a str,
+) -> Result<Cow<'a, str>, TabularError> {
+    match options.naming_rules {
+        Some(rules) => Ok(rules.apply(kind, name)?),
+        None => Ok(Cow::Borrowed(name)),
+    }
+}
+
+/// Return the indexes of the non-NULL values of `array`.
+fn valid_values(array: &dyn Array) -> impl Iterator<Item = usize> + '_ {
+    (0..array.len()).filter(|i| array.is_valid(*i))
+}
+
+/// Return a bitmask of the non-NULL values of `array`, as expected by
+/// [`Writer`].
+fn valid_mask(array: &dyn Array) -> Vec<u8> {
+    let mut mask = vec![0; (array.len() + 7) / 8];
+    for i in valid_values(array) {
+        mask[i / 8] |= 1 << (i % 8);
+    }
+    mask
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow::{
+        array::{DictionaryArray, Float64Array, StringArray, TimestampNanosecondArray},
+        datatypes::Int32Type,
+        ipc::writer::StreamWriter,
+    };
+    use arrow_util::assert_batches_eq;
+    use assert_matches::assert_matches;
+    use schema::Projection;
+
+    use super::*;
+
+    const DEFAULT_TIME: i64 = 42;
+
+    fn params(query: &str) -> TabularParams {
+        let req = Request::builder()
+            .uri(format!("https://bananas.example/api/v2/write?{query}"))
+            .body(())
+            .unwrap();
+        TabularParams::try_from(&req).expect("valid params")
+    }
+
+    fn options(row_filters: &[RowFilter]) -> ConvertOptions<'_> {
+        ConvertOptions {
+            timestamp_base: 1_000_000_000,
+            default_time: DEFAULT_TIME,
+            naming_rules: None,
+            row_filters,
+        }
+    }
+
+    fn arrow_ipc(batch: &RecordBatch) -> Vec<u8> {
+        let mut buf = Vec::new();
+        let mut writer = StreamWriter::try_new(&mut buf, &batch.schema()).unwrap();
+        writer.write(batch).unwrap();
+        writer.finish().unwrap();
+        drop(writer);
+        buf
+    }
+
+    #[test]
+    fn test_payload_format() {
+        for (content_type, want) in [
+            (None, PayloadFormat::LineProtocol),
+            (
+                Some("text/plain; charset=utf-8"),
+                PayloadFormat::LineProtocol,
+            ),
+            (
+                Some("application/x-www-form-urlencoded"),
+                PayloadFormat::LineProtocol,
+            ),
+            (Some("text/csv"), PayloadFormat::Csv),
+            (Some("Text/CSV; charset=utf-8"), PayloadFormat::Csv),
+            (
+                Some("application/vnd.apache.arrow.stream"),
+                PayloadFormat::ArrowIpc,
+            ),
+        ] {
+            assert_eq!(
+                PayloadFormat::from_content_type(content_type),
+                want,
+                "{content_type:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_params() {
+        let req = Request::builder()
+            .uri("https://bananas.example/api/v2/write?bucket=b")
+            .body(())
+            .unwrap();
+        assert_matches!(TabularParams::try_from(&req), Err(TabularError::NoTable));
+
+        let p = params("bucket=b&table=cpu&tags=host,%20region,&time=ts");
+        assert_eq!(p.tags().collect::<Vec<_>>(), ["host", "region"]);
+        assert_eq!(p.time_column, "ts");
+        assert_eq!(params("table=cpu").time_column, "time");
+    }
+
+    #[test]
+    fn test_csv() {
+        let csv = "\
+host:tag,region,usage,count:unsigned,ok,note:string,time
+a,eu,1.5,3,true,x,1
+b,,2,,false,,
+";
+        let (batches, stats) = convert(
+            PayloadFormat::Csv,
+            csv.as_bytes(),
+            &params("table=cpu&tags=region"),
+            &options(&[]),
+        )
+        .unwrap();
+
+        assert_eq!(stats.num_lines, 2);
+        assert_eq!(stats.num_fields, 6);
+        let got = batches["cpu"].to_arrow(Projection::All).unwrap();
+        assert_batches_eq!(
+            &[
+                "+-------+------+------+-------+--------+--------------------------------+-------+",
+                "| count | host | note | ok    | region | time                           | usage |",
+                "+-------+------+------+-------+--------+--------------------------------+-------+",
+                "| 3     | a    | x    | true  | eu     | 1970-01-01T00:00:01Z           | 1.5   |",
+                "|       | b    |      | false |        | 1970-01-01T00:00:00.000000042Z | 2.0   |",
+                "+-------+------+------+-------+--------+--------------------------------+-------+",
+            ],
+            &[got]
+        );
+    }
+
+    #[test]
+    fn test_csv_errors() {
+        assert_matches!(
+            convert(
+                PayloadFormat::Csv,
+                b"host:label,v\na,1\n",
+                &params("table=cpu"),
+                &options(&[]),
+            ),
+            Err(TabularError::InvalidAnnotation { column, annotation }) => {
+                assert_eq!(column, "host");
+                assert_eq!(annotation, "label");
+            }
+        );
+
+        assert_matches!(
+            convert(
+                PayloadFormat::Csv,
+                b"host,v\na,1\n",
+                &params("table=cpu&tags=region"),
+                &options(&[]),
+            ),
+            Err(TabularError::MissingTag(t)) if t == "region"
+        );
+
+        assert_matches!(
+            convert(
+                PayloadFormat::Csv,
+                b"host,v\n",
+                &params("table=cpu"),
+                &options(&[]),
+            ),
+            Err(TabularError::EmptyPayload)
+        );
+
+        assert_matches!(
+            convert(
+                PayloadFormat::Csv,
+                b"v,time\n1,9223372036854775807\n",
+                &params("table=cpu"),
+                &options(&[]),
+            ),
+            Err(TabularError::TimestampOverflow(1))
+        );
+    }
+
+    #[test]
+    fn test_arrow_ipc() {
+        let host: DictionaryArray<Int32Type> = vec![Some("a"), Some("b")].into_iter().collect();
+        let batch = RecordBatch::try_from_iter([
+            ("host", Arc::new(host) as ArrayRef),
+            (
+                "region",
+                Arc::new(StringArray::from(vec![Some("eu"), None])) as ArrayRef,
+            ),
+            (
+                "usage",
+                Arc::new(Float64Array::from(vec![Some(1.5), None])) as ArrayRef,
+            ),
+            (
+                "ts",
+                Arc::new(TimestampNanosecondArray::from(vec![Some(100), None])) as ArrayRef,
+            ),
+        ])
+        .unwrap();
+
+        let (batches, stats) = convert(
+            PayloadFormat::ArrowIpc,
+            &arrow_ipc(&batch),
+            &params("table=cpu&tags=region&time=ts"),
+            &options(&[]),
+        )
+        .unwrap();
+
+        assert_eq!(stats.num_lines, 2);
+        assert_eq!(stats.num_fields, 1);
+        let got = batches["cpu"].to_arrow(Projection::All).unwrap();
+        assert_batches_eq!(
+            &[
+                "+------+--------+--------------------------------+-------+",
+                "| host | region | time                           | usage |",
+                "+------+--------+--------------------------------+-------+",
+                "| a    | eu     | 1970-01-01T00:00:00.000000100Z | 1.5   |",
+                "| b    |        | 1970-01-01T00:00:00.000000042Z |       |",
+                "+------+--------+--------------------------------+-------+",
+            ],
+            &[got]
+        );
+
+        assert_matches!(
+            convert(
+                PayloadFormat::ArrowIpc,
+                b"bananas",
+                &params("table=cpu"),
+                &options(&[]),
+            ),
+            Err(TabularError::Decode(_))
+        );
+    }
+
+    #[test]
+    fn test_row_filters() {
+        let row_filters = [RowFilter::new("tenant_id", "x")];
+
+        // Rows without the tag have it added.
+        let (batches, _) = convert(
+            PayloadFormat::Csv,
+            b"tenant_id:tag,v\nx,1\n,2\n",
+            &params("table=cpu"),
+            &options(&row_filters),
+        )
+        .unwrap();
+        let got = batches["cpu"].to_arrow(Projection::All).unwrap();
+        assert_batches_eq!(
+            &[
+                "+-----------+--------------------------------+---+",
+                "| tenant_id | time                           | v |",
+                "+-----------+--------------------------------+---+",
+                "| x         | 1970-01-01T00:00:00.000000042Z | 1 |",
+                "| x         | 1970-01-01T00:00:00.000000042Z | 2 |",
+                "+-----------+--------------------------------+---+",
+            ],
+            &[got]
+        );
+
+        let (batches, _) = convert(
+            PayloadFormat::Csv,
+            b"v\n1\n",
+            &params("table=cpu"),
+            &options(&row_filters),
+        )
+        .unwrap();
+        assert!(batches["cpu"].column("tenant_id").is_ok());
+
+        // Rows with another value are rejected.
+        assert_matches!(
+            convert(
+                PayloadFormat::Csv,
+                b"tenant_id:tag,v\nx,1\ny,2\n",
+                &params("table=cpu"),
+                &options(&row_filters),
+            ),
+            Err(TabularError::RequiredTag { tag, want, got, row: 2 }) => {
+                assert_eq!(tag, "tenant_id");
+                assert_eq!(want, "x");
+                assert_eq!(got, "y");
+            }
+        );
+    }
+}
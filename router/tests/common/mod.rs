@@ -13,7 +13,8 @@ use object_store::memory::InMemory;
 use router::{
     dml_handlers::{
         client::mock::MockWriteClient, Chain, DmlHandlerChainExt, FanOutAdaptor,
-        InstrumentationDecorator, Partitioned, Partitioner, RetentionValidator, RpcWrite,
+        InstrumentationDecorator, Partitioned, Partitioner, ReadOnlyValidator, RetentionValidator,
+        RpcWrite, DEFAULT_READ_ONLY_CACHE_TTL,
     },
     gossip::anti_entropy::{mst::actor::AntiEntropyActor, sync::rpc_server::AntiEntropyService},
     namespace_cache::{MemoryNamespaceCache, ReadThroughCache, ShardedCache},
@@ -115,7 +116,10 @@ type HttpDelegateStack = HttpDelegate<
     InstrumentationDecorator<
         Chain<
             Chain<
-                Chain<RetentionValidator, SchemaValidator<Arc<ReadThroughCache<CacheImpl>>>>,
+                Chain<
+                    Chain<ReadOnlyValidator, RetentionValidator>,
+                    SchemaValidator<Arc<ReadThroughCache<CacheImpl>>>,
+                >,
                 Partitioner,
             >,
             FanOutAdaptor<
@@ -162,6 +166,9 @@ impl TestContext {
         let schema_validator =
             SchemaValidator::new(Arc::clone(&catalog), Arc::clone(&ns_cache), &metrics);
 
+        let read_only_validator =
+            ReadOnlyValidator::new(Arc::clone(&catalog), DEFAULT_READ_ONLY_CACHE_TTL);
+
         let retention_validator = RetentionValidator::new();
 
         let partitioner = Partitioner::default();
@@ -176,7 +183,8 @@ impl TestContext {
 
         let parallel_write = FanOutAdaptor::new(rpc_writer);
 
-        let handler_stack = retention_validator
+        let handler_stack = read_only_validator
+            .and_then(retention_validator)
             .and_then(schema_validator)
             .and_then(partitioner)
             .and_then(parallel_write);
@@ -0,0 +1,491 @@
+//! A small SQL DDL dialect for managing catalog metadata through the query
+//! interface.
+//!
+//! The supported statements are:
+//!
+//! ```text
+//! CREATE NAMESPACE <name> [RETENTION <retention>]
+//! ALTER NAMESPACE <name> SET RETENTION <retention>
+//! DROP TABLE <name>
+//! ```
+//!
+//! where `<retention>` is either `INFINITE` or a quoted duration such as
+//! `'30d'` or `'1w 2d'`. Names may be double quoted to preserve their case or
+//! to include characters otherwise not allowed in an identifier.
+//!
+//! Statements are executed by [`QueryNamespaceProvider::execute_ddl()`].
+//!
+//! [`QueryNamespaceProvider::execute_ddl()`]: crate::QueryNamespaceProvider::execute_ddl
+
+use std::{fmt::Display, sync::Arc};
+
+use datafusion::arrow::{
+    array::{ArrayRef, Int64Array, StringArray},
+    datatypes::{DataType, Field, Schema},
+    record_batch::RecordBatch,
+};
+use snafu::Snafu;
+
+/// A parsed DDL statement.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DdlStatement {
+    /// Create a namespace with an optional retention period (in nanoseconds),
+    /// retaining data forever if [`None`].
+    CreateNamespace {
+        /// The name of the namespace to create.
+        name: String,
+        /// The retention period of the namespace in nanoseconds.
+        retention_period_ns: Option<i64>,
+    },
+
+    /// Change the retention period (in nanoseconds) of a namespace, retaining
+    /// data forever if [`None`].
+    AlterNamespaceRetention {
+        /// The name of the namespace to alter.
+        name: String,
+        /// The new retention period of the namespace in nanoseconds.
+        retention_period_ns: Option<i64>,
+    },
+
+    /// Drop a table from the namespace the statement is executed against.
+    DropTable {
+        /// The name of the table to drop.
+        name: String,
+    },
+}
+
+/// Errors parsing or executing a [`DdlStatement`].
+#[derive(Debug, Snafu)]
+pub enum DdlError {
+    #[snafu(display("invalid DDL statement: {reason}"))]
+    Parse { reason: String },
+
+    #[snafu(display("invalid namespace name: {reason}"))]
+    InvalidName { reason: String },
+
+    #[snafu(display("namespace '{name}' already exists"))]
+    AlreadyExists { name: String },
+
+    #[snafu(display("'{name}' not found"))]
+    NotFound { name: String },
+
+    #[snafu(display("{statement} is not supported: {reason}"))]
+    Unsupported {
+        statement: &'static str,
+        reason: String,
+    },
+
+    #[snafu(display("catalog error: {source}"))]
+    Catalog {
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+}
+
+impl DdlError {
+    /// Return the [`tonic::Code`] describing this error.
+    pub fn tonic_code(&self) -> tonic::Code {
+        match self {
+            Self::Parse { .. } | Self::InvalidName { .. } => tonic::Code::InvalidArgument,
+            Self::AlreadyExists { .. } => tonic::Code::AlreadyExists,
+            Self::NotFound { .. } => tonic::Code::NotFound,
+            Self::Unsupported { .. } => tonic::Code::Unimplemented,
+            Self::Catalog { .. } => tonic::Code::Internal,
+        }
+    }
+}
+
+impl DdlStatement {
+    /// Parse `sql` as a DDL statement.
+    ///
+    /// Returns [`None`] if `sql` does not start with one of the supported
+    /// statement prefixes (and should be planned as a query), or an error if
+    /// it does but is otherwise invalid.
+    pub fn parse(sql: &str) -> Option<Result<Self, DdlError>> {
+        let tokens = match tokenize(sql) {
+            Ok(v) => v,
+            Err(e) => {
+                // Only report the tokenisation failure for DDL statements.
+                return sql_prefix_is_ddl(sql).then_some(Err(e));
+            }
+        };
+
+        let mut parser = Parser { tokens, pos: 0 };
+        let stmt = if parser.keywords(&["CREATE", "NAMESPACE"]) {
+            parser.create_namespace()
+        } else if parser.keywords(&["ALTER", "NAMESPACE"]) {
+            parser.alter_namespace()
+        } else if parser.keywords(&["DROP", "TABLE"]) {
+            parser.drop_table()
+        } else {
+            return None;
+        };
+
+        Some(stmt.and_then(|stmt| parser.end().map(|_| stmt)))
+    }
+
+    /// A short description of the kind of statement, such as `CREATE
+    /// NAMESPACE`.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::CreateNamespace { .. } => "CREATE NAMESPACE",
+            Self::AlterNamespaceRetention { .. } => "ALTER NAMESPACE",
+            Self::DropTable { .. } => "DROP TABLE",
+        }
+    }
+}
+
+impl Display for DdlStatement {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::CreateNamespace {
+                name,
+                retention_period_ns,
+            } => write!(
+                f,
+                "CREATE NAMESPACE \"{name}\" RETENTION {}",
+                DisplayRetention(*retention_period_ns)
+            ),
+            Self::AlterNamespaceRetention {
+                name,
+                retention_period_ns,
+            } => write!(
+                f,
+                "ALTER NAMESPACE \"{name}\" SET RETENTION {}",
+                DisplayRetention(*retention_period_ns)
+            ),
+            Self::DropTable { name } => write!(f, "DROP TABLE \"{name}\""),
+        }
+    }
+}
+
+struct DisplayRetention(Option<i64>);
+
+impl Display for DisplayRetention {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.0 {
+            Some(ns) => write!(
+                f,
+                "'{}'",
+                humantime::format_duration(std::time::Duration::from_nanos(ns as u64))
+            ),
+            None => write!(f, "INFINITE"),
+        }
+    }
+}
+
+/// Return a [`RecordBatch`] describing a namespace after a DDL statement was
+/// applied to it.
+pub fn namespace_batch(id: i64, name: &str, retention_period_ns: Option<i64>) -> RecordBatch {
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("namespace_id", DataType::Int64, false),
+        Field::new("namespace", DataType::Utf8, false),
+        Field::new("retention_period_ns", DataType::Int64, true),
+    ]));
+
+    RecordBatch::try_new(
+        schema,
+        vec![
+            Arc::new(Int64Array::from(vec![id])) as ArrayRef,
+            Arc::new(StringArray::from(vec![name])),
+            Arc::new(Int64Array::from(vec![retention_period_ns])),
+        ],
+    )
+    .expect("namespace batch columns match schema")
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    /// An unquoted word, either a keyword or an identifier.
+    Word(String),
+    /// A double quoted identifier.
+    Quoted(String),
+    /// A single quoted string literal.
+    Literal(String),
+}
+
+/// Returns true if the first two words of `sql` are a DDL statement prefix.
+fn sql_prefix_is_ddl(sql: &str) -> bool {
+    let mut words = sql.split_whitespace().map(|w| w.to_ascii_uppercase());
+    matches!(
+        (words.next().as_deref(), words.next().as_deref()),
+        (Some("CREATE"), Some("NAMESPACE"))
+            | (Some("ALTER"), Some("NAMESPACE"))
+            | (Some("DROP"), Some("TABLE"))
+    )
+}
+
+fn tokenize(sql: &str) -> Result<Vec<Token>, DdlError> {
+    let mut tokens = vec![];
+    let mut chars = sql.trim().trim_end_matches(';').chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => continue,
+            '"' | '\'' => {
+                let mut value = String::new();
+                loop {
+                    match chars.next() {
+                        // A doubled quote character is an escaped quote.
+                        Some(v) if v == c && chars.peek() == Some(&c) => {
+                            chars.next();
+                            value.push(c);
+                        }
+                        Some(v) if v == c => break,
+                        Some(v) => value.push(v),
+                        None => {
+                            return ParseSnafu {
+                                reason: format!("unterminated {c} quote"),
+                            }
+                            .fail()
+                        }
+                    }
+                }
+                tokens.push(match c {
+                    '"' => Token::Quoted(value),
+                    _ => Token::Literal(value),
+                });
+            }
+            c => {
+                let mut value = String::from(c);
+                while let Some(v) = chars.next_if(|v| !v.is_whitespace() && *v != '"' && *v != '\'')
+                {
+                    value.push(v);
+                }
+                tokens.push(Token::Word(value));
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    /// Consume the `keywords` if they are the next tokens, returning true if
+    /// they were.
+    fn keywords(&mut self, keywords: &[&str]) -> bool {
+        let matches = keywords.iter().enumerate().all(|(i, k)| {
+            matches!(self.tokens.get(self.pos + i), Some(Token::Word(w)) if w.eq_ignore_ascii_case(k))
+        });
+        if matches {
+            self.pos += keywords.len();
+        }
+        matches
+    }
+
+    fn expect_keywords(&mut self, keywords: &[&str]) -> Result<(), DdlError> {
+        if self.keywords(keywords) {
+            return Ok(());
+        }
+        ParseSnafu {
+            reason: format!("expected {}", keywords.join(" ")),
+        }
+        .fail()
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let t = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        t
+    }
+
+    /// Parse an identifier, which is case-folded to lower case unless quoted.
+    fn identifier(&mut self) -> Result<String, DdlError> {
+        match self.next() {
+            Some(Token::Word(w)) => Ok(w.to_lowercase()),
+            Some(Token::Quoted(v)) => Ok(v),
+            _ => ParseSnafu {
+                reason: "expected a name",
+            }
+            .fail(),
+        }
+    }
+
+    fn retention(&mut self) -> Result<Option<i64>, DdlError> {
+        if self.keywords(&["INFINITE"]) {
+            return Ok(None);
+        }
+
+        let Some(Token::Literal(v)) = self.next() else {
+            return ParseSnafu {
+                reason: "expected INFINITE or a quoted retention period such as '30d'",
+            }
+            .fail();
+        };
+
+        let duration = humantime::parse_duration(&v).map_err(|e| DdlError::Parse {
+            reason: format!("invalid retention period '{v}': {e}"),
+        })?;
+
+        match i64::try_from(duration.as_nanos()) {
+            Ok(0) => ParseSnafu {
+                reason: "retention period must be greater than zero",
+            }
+            .fail(),
+            Ok(ns) => Ok(Some(ns)),
+            Err(_) => ParseSnafu {
+                reason: format!("retention period '{v}' is too large"),
+            }
+            .fail(),
+        }
+    }
+
+    fn create_namespace(&mut self) -> Result<DdlStatement, DdlError> {
+        let name = self.identifier()?;
+        let retention_period_ns = if self.keywords(&["RETENTION"]) {
+            self.retention()?
+        } else {
+            None
+        };
+        Ok(DdlStatement::CreateNamespace {
+            name,
+            retention_period_ns,
+        })
+    }
+
+    fn alter_namespace(&mut self) -> Result<DdlStatement, DdlError> {
+        let name = self.identifier()?;
+        self.expect_keywords(&["SET", "RETENTION"])?;
+        let retention_period_ns = self.retention()?;
+        Ok(DdlStatement::AlterNamespaceRetention {
+            name,
+            retention_period_ns,
+        })
+    }
+
+    fn drop_table(&mut self) -> Result<DdlStatement, DdlError> {
+        let name = self.identifier()?;
+        Ok(DdlStatement::DropTable { name })
+    }
+
+    fn end(&self) -> Result<(), DdlError> {
+        match self.tokens.get(self.pos) {
+            None => Ok(()),
+            Some(t) => ParseSnafu {
+                reason: format!("unexpected {t:?} at end of statement"),
+            }
+            .fail(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_matches::assert_matches;
+
+    use super::*;
+
+    const DAY_NS: i64 = 24 * 60 * 60 * 1_000_000_000;
+
+    #[test]
+    fn test_not_ddl() {
+        for sql in [
+            "SELECT * FROM cpu",
+            "CREATE TABLE foo (a int)",
+            "create external table foo stored as parquet location 'bananas'",
+            "DROP VIEW bananas",
+            "",
+        ] {
+            assert!(DdlStatement::parse(sql).is_none(), "{sql}");
+        }
+    }
+
+    #[test]
+    fn test_create_namespace() {
+        assert_eq!(
+            DdlStatement::parse("CREATE NAMESPACE Bananas")
+                .unwrap()
+                .unwrap(),
+            DdlStatement::CreateNamespace {
+                name: "bananas".to_string(),
+                retention_period_ns: None,
+            }
+        );
+        assert_eq!(
+            DdlStatement::parse(r#"create namespace "My""Bananas" retention '30d';"#)
+                .unwrap()
+                .unwrap(),
+            DdlStatement::CreateNamespace {
+                name: r#"My"Bananas"#.to_string(),
+                retention_period_ns: Some(30 * DAY_NS),
+            }
+        );
+        assert_eq!(
+            DdlStatement::parse("CREATE NAMESPACE bananas RETENTION INFINITE")
+                .unwrap()
+                .unwrap(),
+            DdlStatement::CreateNamespace {
+                name: "bananas".to_string(),
+                retention_period_ns: None,
+            }
+        );
+    }
+
+    #[test]
+    fn test_alter_namespace() {
+        assert_eq!(
+            DdlStatement::parse("ALTER NAMESPACE bananas SET RETENTION '1w 1d'")
+                .unwrap()
+                .unwrap(),
+            DdlStatement::AlterNamespaceRetention {
+                name: "bananas".to_string(),
+                retention_period_ns: Some(8 * DAY_NS),
+            }
+        );
+        assert_eq!(
+            DdlStatement::parse("alter namespace bananas set retention infinite")
+                .unwrap()
+                .unwrap(),
+            DdlStatement::AlterNamespaceRetention {
+                name: "bananas".to_string(),
+                retention_period_ns: None,
+            }
+        );
+    }
+
+    #[test]
+    fn test_drop_table() {
+        let stmt = DdlStatement::parse("DROP TABLE cpu").unwrap().unwrap();
+        assert_eq!(
+            stmt,
+            DdlStatement::DropTable {
+                name: "cpu".to_string()
+            }
+        );
+        assert_eq!(stmt.kind(), "DROP TABLE");
+        assert_eq!(stmt.to_string(), r#"DROP TABLE "cpu""#);
+    }
+
+    #[test]
+    fn test_invalid() {
+        for sql in [
+            "CREATE NAMESPACE",
+            "CREATE NAMESPACE bananas RETENTION",
+            "CREATE NAMESPACE bananas RETENTION 30d",
+            "CREATE NAMESPACE bananas RETENTION 'bananas'",
+            "CREATE NAMESPACE bananas RETENTION '0s'",
+            "CREATE NAMESPACE bananas RETENTION '1000000y'",
+            "CREATE NAMESPACE 'bananas'",
+            "CREATE NAMESPACE \"bananas",
+            "ALTER NAMESPACE bananas RETENTION '1d'",
+            "DROP TABLE cpu mem",
+        ] {
+            assert_matches!(
+                DdlStatement::parse(sql),
+                Some(Err(DdlError::Parse { .. })),
+                "{sql}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_namespace_batch() {
+        let batch = namespace_batch(42, "bananas", Some(DAY_NS));
+        assert_eq!(batch.num_rows(), 1);
+        assert_eq!(batch.num_columns(), 3);
+    }
+}
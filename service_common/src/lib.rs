@@ -17,6 +17,7 @@
 // Workaround for "unused crate" lint false positives.
 use workspace_hack as _;
 
+pub mod ddl;
 mod error;
 pub mod planner;
 pub mod test_util;
@@ -24,6 +25,8 @@ pub mod test_util;
 use std::sync::Arc;
 
 use async_trait::async_trait;
+use datafusion::arrow::record_batch::RecordBatch;
+use ddl::{DdlError, DdlStatement};
 use iox_query::QueryNamespace;
 use trace::span::Span;
 use tracker::InstrumentedAsyncOwnedSemaphorePermit;
@@ -49,6 +52,24 @@ pub trait QueryNamespaceProvider: std::fmt::Debug + Send + Sync + 'static {
 
     /// Acquire concurrency-limiting sempahore
     async fn acquire_semaphore(&self, span: Option<Span>) -> InstrumentedAsyncOwnedSemaphorePermit;
+
+    /// Execute the DDL `statement` against the catalog, in the context of the
+    /// namespace `namespace_name`.
+    ///
+    /// Returns a [`RecordBatch`] describing the result. Implementations that
+    /// do not manage catalog metadata return [`DdlError::Unsupported`].
+    async fn execute_ddl(
+        &self,
+        namespace_name: &str,
+        statement: DdlStatement,
+        span: Option<Span>,
+    ) -> Result<RecordBatch, DdlError> {
+        let _ = (namespace_name, span);
+        Err(DdlError::Unsupported {
+            statement: statement.kind(),
+            reason: "DDL statements are not supported by this server".to_string(),
+        })
+    }
 }
 
 pub use error::datafusion_error_to_tonic_code;
@@ -0,0 +1,511 @@
+//! A read-only gRPC service exposing the catalog contents to external tooling.
+
+use std::{collections::HashMap, sync::Arc};
+
+use data_types::{Namespace, Table, TransitionPartitionId};
+use generated_types::influxdata::iox::catalog::v1::*;
+use iox_catalog::interface::{Catalog, RepoCollection, SoftDeletedRows};
+use observability_deps::tracing::*;
+use tonic::{Request, Response, Status};
+
+/// The number of results returned when the request does not specify a page
+/// size.
+const DEFAULT_PAGE_SIZE: usize = 100;
+
+/// The maximum number of results returned in a single page.
+const MAX_PAGE_SIZE: usize = 1000;
+
+/// Implementation of the catalog introspection gRPC service
+#[derive(Debug)]
+pub struct CatalogIntrospectionService {
+    /// Catalog.
+    catalog: Arc<dyn Catalog>,
+}
+
+impl CatalogIntrospectionService {
+    /// Create a new catalog introspection service with the given catalog
+    pub fn new(catalog: Arc<dyn Catalog>) -> Self {
+        Self { catalog }
+    }
+}
+
+#[tonic::async_trait]
+impl catalog_introspection_service_server::CatalogIntrospectionService
+    for CatalogIntrospectionService
+{
+    async fn list_namespaces(
+        &self,
+        request: Request<ListNamespacesRequest>,
+    ) -> Result<Response<ListNamespacesResponse>, Status> {
+        let mut repos = self.catalog.repositories().await;
+        let req = request.into_inner();
+        let page = Page::try_from(req.page)?;
+
+        let namespaces = repos
+            .namespaces()
+            .list(SoftDeletedRows::ExcludeDeleted)
+            .await
+            .map_err(|e| Status::unknown(e.to_string()))?
+            .into_iter()
+            .filter(|ns| ns.name.starts_with(&req.name_prefix))
+            .collect();
+
+        let (namespaces, next_page_token) = page.apply(namespaces, |ns| ns.id.get());
+
+        Ok(Response::new(ListNamespacesResponse {
+            namespaces: namespaces.into_iter().map(to_namespace_summary).collect(),
+            next_page_token,
+        }))
+    }
+
+    async fn list_tables(
+        &self,
+        request: Request<ListTablesRequest>,
+    ) -> Result<Response<ListTablesResponse>, Status> {
+        let mut repos = self.catalog.repositories().await;
+        let req = request.into_inner();
+        let page = Page::try_from(req.page)?;
+
+        let namespace = get_namespace(&mut *repos, &req.namespace_name).await?;
+        let tables = repos
+            .tables()
+            .list_by_namespace_id(namespace.id)
+            .await
+            .map_err(|e| Status::unknown(e.to_string()))?
+            .into_iter()
+            .filter(|t| t.name.starts_with(&req.name_prefix))
+            .collect();
+
+        let (tables, next_page_token) = page.apply(tables, |t| t.id.get());
+
+        Ok(Response::new(ListTablesResponse {
+            tables: tables
+                .into_iter()
+                .map(|t| TableSummary {
+                    id: t.id.get(),
+                    name: t.name,
+                })
+                .collect(),
+            next_page_token,
+        }))
+    }
+
+    async fn list_columns(
+        &self,
+        request: Request<ListColumnsRequest>,
+    ) -> Result<Response<ListColumnsResponse>, Status> {
+        let mut repos = self.catalog.repositories().await;
+        let req = request.into_inner();
+        let page = Page::try_from(req.page)?;
+
+        let table = get_table(&mut *repos, &req.namespace_name, &req.table_name).await?;
+        let columns = repos
+            .columns()
+            .list_by_table_id(table.id)
+            .await
+            .map_err(|e| Status::unknown(e.to_string()))?
+            .into_iter()
+            .filter(|c| {
+                req.column_type
+                    .map_or(true, |want| c.column_type as i32 == want)
+            })
+            .collect();
+
+        let (columns, next_page_token) = page.apply(columns, |c| c.id.get());
+
+        Ok(Response::new(ListColumnsResponse {
+            columns: columns
+                .into_iter()
+                .map(|c| ColumnSummary {
+                    id: c.id.get(),
+                    name: c.name,
+                    column_type: c.column_type as i32,
+                })
+                .collect(),
+            next_page_token,
+        }))
+    }
+
+    async fn list_partitions(
+        &self,
+        request: Request<ListPartitionsRequest>,
+    ) -> Result<Response<ListPartitionsResponse>, Status> {
+        let mut repos = self.catalog.repositories().await;
+        let req = request.into_inner();
+        let page = Page::try_from(req.page)?;
+
+        let table = get_table(&mut *repos, &req.namespace_name, &req.table_name).await?;
+        let partitions = repos
+            .partitions()
+            .list_by_table_id(table.id)
+            .await
+            .map_err(|e| Status::unknown(e.to_string()))?
+            .into_iter()
+            .filter(|p| p.partition_key.inner().starts_with(&req.key_prefix))
+            .collect();
+
+        let (partitions, next_page_token) = page.apply(partitions, |p| p.id.get());
+
+        let files = repos
+            .parquet_files()
+            .list_by_table_not_to_delete(table.id)
+            .await
+            .map_err(|e| {
+                warn!(
+                    error=%e,
+                    %req.namespace_name,
+                    %req.table_name,
+                    "failed to get parquet_files for table"
+                );
+                Status::unknown(e.to_string())
+            })?;
+
+        let mut file_summaries: HashMap<TransitionPartitionId, PartitionSummary> = HashMap::new();
+        for f in files {
+            let summary = file_summaries.entry(f.partition_id).or_default();
+            summary.file_count += 1;
+            summary.total_size_bytes += f.file_size_bytes;
+            summary.total_row_count += f.row_count;
+            summary.min_time = Some(
+                summary
+                    .min_time
+                    .map_or(f.min_time.get(), |v| v.min(f.min_time.get())),
+            );
+            summary.max_time = Some(
+                summary
+                    .max_time
+                    .map_or(f.max_time.get(), |v| v.max(f.max_time.get())),
+            );
+        }
+
+        let partitions = partitions
+            .into_iter()
+            .map(|p| {
+                let id = p.transition_partition_id();
+                let mut summary = file_summaries.remove(&id).unwrap_or_default();
+                summary.identifier = Some(PartitionIdentifier::from(id));
+                summary.key = p.partition_key.to_string();
+                summary.new_file_at = p.new_file_at.map(|t| t.get());
+                summary
+            })
+            .collect();
+
+        Ok(Response::new(ListPartitionsResponse {
+            partitions,
+            next_page_token,
+        }))
+    }
+}
+
+/// The page of results requested by a list request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Page {
+    size: usize,
+
+    /// The ID of the last result of the previous page.
+    after: Option<i64>,
+}
+
+impl TryFrom<Option<PageRequest>> for Page {
+    type Error = Status;
+
+    fn try_from(req: Option<PageRequest>) -> Result<Self, Self::Error> {
+        let req = req.unwrap_or_default();
+
+        let size = match req.page_size as usize {
+            0 => DEFAULT_PAGE_SIZE,
+            v => v.min(MAX_PAGE_SIZE),
+        };
+
+        let after = match req.page_token.as_str() {
+            "" => None,
+            v => Some(
+                v.parse::<i64>()
+                    .map_err(|_| Status::invalid_argument("invalid page token"))?,
+            ),
+        };
+
+        Ok(Self { size, after })
+    }
+}
+
+impl Page {
+    /// Return the results in this page, ordered by the ID returned by `id`,
+    /// and the token to fetch the next page (empty if there is none).
+    fn apply<T>(&self, mut items: Vec<T>, id: impl Fn(&T) -> i64) -> (Vec<T>, String) {
+        items.sort_unstable_by_key(|v| id(v));
+        if let Some(after) = self.after {
+            items.retain(|v| id(v) > after);
+        }
+
+        if items.len() <= self.size {
+            return (items, String::new());
+        }
+
+        items.truncate(self.size);
+        let next = items.last().map(|v| id(v).to_string()).unwrap_or_default();
+        (items, next)
+    }
+}
+
+async fn get_namespace(repos: &mut dyn RepoCollection, name: &str) -> Result<Namespace, Status> {
+    repos
+        .namespaces()
+        .get_by_name(name, SoftDeletedRows::ExcludeDeleted)
+        .await
+        .map_err(|e| Status::unknown(e.to_string()))?
+        .ok_or_else(|| Status::not_found(format!("Namespace {name} not found")))
+}
+
+async fn get_table(
+    repos: &mut dyn RepoCollection,
+    namespace_name: &str,
+    table_name: &str,
+) -> Result<Table, Status> {
+    let namespace = get_namespace(repos, namespace_name).await?;
+    repos
+        .tables()
+        .get_by_namespace_and_name(namespace.id, table_name)
+        .await
+        .map_err(|e| Status::unknown(e.to_string()))?
+        .ok_or_else(|| Status::not_found(format!("Table {table_name} not found")))
+}
+
+fn to_namespace_summary(ns: Namespace) -> NamespaceSummary {
+    NamespaceSummary {
+        id: ns.id.get(),
+        name: ns.name,
+        retention_period_ns: ns.retention_period_ns,
+        max_tables: ns.max_tables.get(),
+        max_columns_per_table: ns.max_columns_per_table.get(),
+        read_only: ns.read_only,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use data_types::{
+        ColumnId, ColumnSet, ColumnType, CompactionLevel, ParquetFileParams, Timestamp,
+    };
+    use generated_types::influxdata::iox::{
+        catalog::v1::catalog_introspection_service_server::CatalogIntrospectionService,
+        schema::v1::column_schema,
+    };
+    use iox_catalog::{
+        mem::MemCatalog,
+        test_helpers::{arbitrary_namespace, arbitrary_table},
+    };
+    use uuid::Uuid;
+
+    fn page(page_size: u32, page_token: &str) -> Option<PageRequest> {
+        Some(PageRequest {
+            page_size,
+            page_token: page_token.to_string(),
+        })
+    }
+
+    #[test]
+    fn test_page() {
+        let p = Page::try_from(None).unwrap();
+        assert_eq!(
+            p,
+            Page {
+                size: DEFAULT_PAGE_SIZE,
+                after: None
+            }
+        );
+        assert_eq!(
+            p.apply(vec![3, 1, 2], |v| *v),
+            (vec![1, 2, 3], String::new())
+        );
+
+        let p = Page::try_from(page(2, "")).unwrap();
+        assert_eq!(
+            p.apply(vec![3, 1, 2], |v| *v),
+            (vec![1, 2], "2".to_string())
+        );
+
+        let p = Page::try_from(page(2, "2")).unwrap();
+        assert_eq!(p.apply(vec![3, 1, 2], |v| *v), (vec![3], String::new()));
+
+        let p = Page::try_from(page(u32::MAX, "")).unwrap();
+        assert_eq!(p.size, MAX_PAGE_SIZE);
+
+        let err = Page::try_from(page(1, "bananas")).unwrap_err();
+        assert_eq!(err.code(), tonic::Code::InvalidArgument);
+    }
+
+    #[tokio::test]
+    async fn test_list() {
+        let metrics = Arc::new(metric::Registry::default());
+        let catalog: Arc<dyn Catalog> = Arc::new(MemCatalog::new(metrics));
+
+        let (table, p1, p2) = {
+            let mut repos = catalog.repositories().await;
+            let namespace = arbitrary_namespace(&mut *repos, "introspection_test").await;
+            arbitrary_namespace(&mut *repos, "other").await;
+            let table = arbitrary_table(&mut *repos, "cpu", &namespace).await;
+            arbitrary_table(&mut *repos, "mem", &namespace).await;
+            repos
+                .columns()
+                .create_or_get("host", table.id, ColumnType::Tag)
+                .await
+                .unwrap();
+            repos
+                .columns()
+                .create_or_get("usage", table.id, ColumnType::F64)
+                .await
+                .unwrap();
+
+            let p1 = repos
+                .partitions()
+                .create_or_get("2023-01-01".into(), table.id)
+                .await
+                .unwrap();
+            let p2 = repos
+                .partitions()
+                .create_or_get("2023-01-02".into(), table.id)
+                .await
+                .unwrap();
+
+            let params = ParquetFileParams {
+                namespace_id: namespace.id,
+                table_id: table.id,
+                partition_id: p1.transition_partition_id(),
+                object_store_id: Uuid::new_v4(),
+                min_time: Timestamp::new(10),
+                max_time: Timestamp::new(20),
+                file_size_bytes: 100,
+                row_count: 5,
+                compaction_level: CompactionLevel::Initial,
+                created_at: Timestamp::new(1),
+                column_set: ColumnSet::new([ColumnId::new(1), ColumnId::new(2)]),
+                max_l0_created_at: Timestamp::new(1),
+                column_statistics: vec![],
+            };
+            repos.parquet_files().create(params.clone()).await.unwrap();
+            repos
+                .parquet_files()
+                .create(ParquetFileParams {
+                    object_store_id: Uuid::new_v4(),
+                    min_time: Timestamp::new(5),
+                    max_time: Timestamp::new(15),
+                    ..params
+                })
+                .await
+                .unwrap();
+
+            (table, p1, p2)
+        };
+
+        let grpc = super::CatalogIntrospectionService::new(catalog);
+
+        let namespaces = grpc
+            .list_namespaces(Request::new(ListNamespacesRequest {
+                page: None,
+                name_prefix: "intro".to_string(),
+            }))
+            .await
+            .expect("rpc request should succeed")
+            .into_inner();
+        assert_eq!(namespaces.namespaces.len(), 1);
+        assert_eq!(namespaces.namespaces[0].name, "introspection_test");
+        assert!(namespaces.next_page_token.is_empty());
+
+        let tables = grpc
+            .list_tables(Request::new(ListTablesRequest {
+                page: page(1, ""),
+                namespace_name: "introspection_test".to_string(),
+                name_prefix: String::new(),
+            }))
+            .await
+            .expect("rpc request should succeed")
+            .into_inner();
+        assert_eq!(
+            tables.tables,
+            [TableSummary {
+                id: table.id.get(),
+                name: "cpu".to_string(),
+            }]
+        );
+        assert_eq!(tables.next_page_token, table.id.get().to_string());
+
+        let tables = grpc
+            .list_tables(Request::new(ListTablesRequest {
+                page: page(1, &tables.next_page_token),
+                namespace_name: "introspection_test".to_string(),
+                name_prefix: String::new(),
+            }))
+            .await
+            .expect("rpc request should succeed")
+            .into_inner();
+        assert_eq!(tables.tables.len(), 1);
+        assert_eq!(tables.tables[0].name, "mem");
+        assert!(tables.next_page_token.is_empty());
+
+        let columns = grpc
+            .list_columns(Request::new(ListColumnsRequest {
+                page: None,
+                namespace_name: "introspection_test".to_string(),
+                table_name: "cpu".to_string(),
+                column_type: Some(column_schema::ColumnType::Tag as i32),
+            }))
+            .await
+            .expect("rpc request should succeed")
+            .into_inner();
+        assert_eq!(columns.columns.len(), 1);
+        assert_eq!(columns.columns[0].name, "host");
+        assert_eq!(
+            columns.columns[0].column_type,
+            column_schema::ColumnType::Tag as i32
+        );
+
+        let partitions = grpc
+            .list_partitions(Request::new(ListPartitionsRequest {
+                page: None,
+                namespace_name: "introspection_test".to_string(),
+                table_name: "cpu".to_string(),
+                key_prefix: String::new(),
+            }))
+            .await
+            .expect("rpc request should succeed")
+            .into_inner();
+        assert_eq!(
+            partitions.partitions,
+            [
+                PartitionSummary {
+                    identifier: Some(p1.transition_partition_id().into()),
+                    key: "2023-01-01".to_string(),
+                    file_count: 2,
+                    total_size_bytes: 200,
+                    total_row_count: 10,
+                    min_time: Some(5),
+                    max_time: Some(20),
+                    new_file_at: Some(1),
+                },
+                PartitionSummary {
+                    identifier: Some(p2.transition_partition_id().into()),
+                    key: "2023-01-02".to_string(),
+                    file_count: 0,
+                    total_size_bytes: 0,
+                    total_row_count: 0,
+                    min_time: None,
+                    max_time: None,
+                    new_file_at: None,
+                },
+            ]
+        );
+
+        let err = grpc
+            .list_partitions(Request::new(ListPartitionsRequest {
+                page: None,
+                namespace_name: "introspection_test".to_string(),
+                table_name: "bananas".to_string(),
+                key_prefix: String::new(),
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), tonic::Code::NotFound);
+    }
+}
@@ -18,6 +18,9 @@
 // Workaround for "unused crate" lint false positives.
 use workspace_hack as _;
 
+mod introspection;
+pub use introspection::CatalogIntrospectionService;
+
 use data_types::{TableId, TransitionPartitionId};
 use generated_types::influxdata::iox::catalog::v1::*;
 use iox_catalog::interface::{Catalog, SoftDeletedRows};
@@ -236,6 +239,7 @@ mod tests {
                 created_at: Timestamp::new(2343),
                 column_set: ColumnSet::new([ColumnId::new(1), ColumnId::new(2)]),
                 max_l0_created_at: Timestamp::new(2343),
+                column_statistics: vec![],
             };
             let p2params = ParquetFileParams {
                 object_store_id: Uuid::new_v4(),
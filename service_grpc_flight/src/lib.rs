@@ -22,34 +22,49 @@ use workspace_hack as _;
 
 mod keep_alive;
 mod request;
+mod settings;
 
-use arrow::error::ArrowError;
+use arrow::{datatypes::SchemaRef, error::ArrowError};
 use arrow_flight::{
     encode::FlightDataEncoderBuilder,
     flight_descriptor::DescriptorType,
     flight_service_server::{FlightService as Flight, FlightServiceServer as FlightServer},
     Action, ActionType, Criteria, Empty, FlightData, FlightDescriptor, FlightEndpoint, FlightInfo,
-    HandshakeRequest, HandshakeResponse, PutResult, SchemaResult, Ticket,
+    HandshakeRequest, HandshakeResponse, PutResult, SchemaAsIpc, SchemaResult, Ticket,
 };
 use authz::{extract_token, Authorizer};
 use data_types::NamespaceNameError;
 use datafusion::{error::DataFusionError, physical_plan::ExecutionPlan};
 use flightsql::FlightSQLCommand;
-use futures::{ready, Stream, StreamExt, TryStreamExt};
+use futures::{ready, Future, Stream, StreamExt, TryStreamExt};
 use generated_types::influxdata::iox::querier::v1 as proto;
-use iox_query::{exec::IOxSessionContext, QueryCompletedToken, QueryNamespace};
+use iox_query::{
+    exec::{IOxSessionContext, RowFilters},
+    usage::QueryUsage,
+    QueryCompletedToken, QueryNamespace,
+};
 use observability_deps::tracing::{debug, info, warn};
 use prost::Message;
 use request::{IoxGetRequest, RunQuery};
-use service_common::{datafusion_error_to_tonic_code, planner::Planner, QueryNamespaceProvider};
+use service_common::{
+    datafusion_error_to_tonic_code,
+    ddl::{DdlError, DdlStatement},
+    planner::Planner,
+    QueryNamespaceProvider,
+};
+use settings::QuerySettings;
 use snafu::{OptionExt, ResultExt, Snafu};
 use std::{
     fmt::Debug,
     pin::Pin,
-    sync::Arc,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
     task::Poll,
     time::{Duration, Instant},
 };
+use tokio_util::sync::WaitForCancellationFutureOwned;
 use tonic::{
     metadata::{AsciiMetadataValue, MetadataMap},
     Request, Response, Streaming,
@@ -70,6 +85,18 @@ const IOX_FLIGHT_SQL_DATABASE_HEADERS: [&str; 4] = [
     "iox-namespace-name", // deprecated
 ];
 
+/// The FlightSQL actions supported by `DoAction`, and their descriptions.
+const FLIGHTSQL_ACTIONS: [(&str, &str); 2] = [
+    (
+        "CreatePreparedStatement",
+        "Creates a reusable prepared statement resource on the server.",
+    ),
+    (
+        "ClosePreparedStatement",
+        "Closes a reusable prepared statement resource on the server.",
+    ),
+];
+
 /// In which interval should the `DoGet` stream send empty messages as keep alive markers?
 const DO_GET_KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(5);
 
@@ -117,6 +144,9 @@ pub enum Error {
     #[snafu(display("Invalid database name: {}", source))]
     InvalidDatabaseName { source: NamespaceNameError },
 
+    #[snafu(display("Invalid query settings: {}", source))]
+    InvalidSettings { source: settings::Error },
+
     #[snafu(display("Failed to optimize record batch: {}", source))]
     Optimize { source: ArrowError },
 
@@ -130,6 +160,13 @@ pub enum Error {
         source: service_common::planner::Error,
     },
 
+    #[snafu(display("Error executing DDL statement: {}", source))]
+    Ddl {
+        namespace_name: String,
+        query: String,
+        source: DdlError,
+    },
+
     #[snafu(display("Error while planning Flight SQL : {}", source))]
     FlightSQL { source: flightsql::Error },
 
@@ -166,6 +203,7 @@ impl From<Error> for tonic::Status {
             | Error::Unauthenticated { .. }
             | Error::PermissionDenied { .. }
             | Error::InvalidDatabaseName { .. }
+            | Error::InvalidSettings { .. }
             | Error::Query { .. } => info!(e=%err, %namespace, %query, msg),
             Error::Optimize { .. }
             | Error::EncodeSchema { .. }
@@ -173,6 +211,7 @@ impl From<Error> for tonic::Status {
             | Error::NoFlightSQLDatabase
             | Error::InvalidDatabaseHeader { .. }
             | Error::Planning { .. }
+            | Error::Ddl { .. }
             | Error::Deserialization { .. }
             | Error::InternalCreatingTicket { .. }
             | Error::UnsupportedMessageType { .. }
@@ -199,10 +238,12 @@ impl Error {
             | Self::TooManyFlightSQLDatabases { .. }
             | Self::NoFlightSQLDatabase
             | Self::InvalidDatabaseHeader { .. }
-            | Self::InvalidDatabaseName { .. } => tonic::Code::InvalidArgument,
+            | Self::InvalidDatabaseName { .. }
+            | Self::InvalidSettings { .. } => tonic::Code::InvalidArgument,
             Self::Planning { source, .. } | Self::Query { source, .. } => {
                 datafusion_error_to_tonic_code(&source)
             }
+            Self::Ddl { source, .. } => source.tonic_code(),
             Self::UnsupportedMessageType { .. } => tonic::Code::Unimplemented,
             Self::FlightSQL { source } => match source {
                 flightsql::Error::InvalidHandle { .. }
@@ -238,6 +279,7 @@ impl Error {
             | Error::NoFlightSQLDatabase
             | Error::InvalidDatabaseHeader { .. }
             | Error::InvalidDatabaseName { .. }
+            | Error::InvalidSettings { .. }
             | Error::Optimize { .. }
             | Error::EncodeSchema { .. }
             | Error::FlightSQL { .. }
@@ -249,6 +291,7 @@ impl Error {
             Error::DatabaseNotFound { namespace_name } => namespace_name,
             Error::Query { namespace_name, .. } => namespace_name,
             Error::Planning { namespace_name, .. } => namespace_name,
+            Error::Ddl { namespace_name, .. } => namespace_name,
         }
     }
 
@@ -262,6 +305,7 @@ impl Error {
             | Error::NoFlightSQLDatabase
             | Error::InvalidDatabaseHeader { .. }
             | Error::InvalidDatabaseName { .. }
+            | Error::InvalidSettings { .. }
             | Error::Optimize { .. }
             | Error::EncodeSchema { .. }
             | Error::FlightSQL { .. }
@@ -273,6 +317,7 @@ impl Error {
             | Error::DatabaseNotFound { .. } => "NONE",
             Error::Query { query, .. } => query,
             Error::Planning { query, .. } => query,
+            Error::Ddl { query, .. } => query,
         }
     }
 
@@ -461,6 +506,13 @@ type TonicStream<T> = Pin<Box<dyn Stream<Item = Result<T, tonic::Status>> + Send
 ///     7 ┃◀ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ━ ┃
 /// ```
 ///
+/// ## FlightSQL Schema Discovery
+///
+/// Clients may call the `GetSchema` method with any FlightSQL command in a
+/// [`FlightDescriptor`] to obtain the schema of its result without executing
+/// it, and the `ListActions` method to discover the supported `DoAction`
+/// actions.
+///
 /// [Arrow Flight]: https://arrow.apache.org/docs/format/Flight.html
 /// [Arrow FlightSQL]: https://arrow.apache.org/docs/format/FlightSql.html
 #[derive(Debug)]
@@ -495,6 +547,8 @@ where
         query: RunQuery,
         namespace_name: String,
         is_debug: bool,
+        row_filters: RowFilters,
+        settings: QuerySettings,
     ) -> Result<Response<TonicStream<FlightData>>, tonic::Status> {
         let db = self
             .server
@@ -508,7 +562,12 @@ where
                 namespace_name: &namespace_name,
             })?;
 
-        let ctx = db.new_query_context(span_ctx);
+        let ctx = db.new_filtered_query_context(span_ctx, row_filters);
+        settings
+            .configure(&ctx)
+            .await
+            .context(InvalidSettingsSnafu)?;
+
         let (query_completed_token, physical_plan) = match &query {
             RunQuery::Sql(sql_query) => {
                 let token = db.record_query(
@@ -557,6 +616,15 @@ where
             }
         };
 
+        let physical_plan =
+            settings
+                .apply_to_plan(physical_plan, &ctx)
+                .await
+                .context(QuerySnafu {
+                    namespace_name: &namespace_name,
+                    query: query.to_string(),
+                })?;
+
         let output = GetStream::new(
             ctx,
             physical_plan,
@@ -564,6 +632,7 @@ where
             &query,
             query_completed_token,
             permit,
+            settings.timeout,
         )
         .await?;
 
@@ -584,6 +653,64 @@ where
 
         Ok(Response::new(Box::pin(output) as TonicStream<FlightData>))
     }
+
+    /// Execute the DDL `statement` received in a `DoGet` request, returning
+    /// the resulting record batch as the response stream.
+    async fn run_ddl(
+        &self,
+        span_ctx: Option<SpanContext>,
+        statement: DdlStatement,
+        namespace_name: String,
+        query: &RunQuery,
+    ) -> Result<Response<TonicStream<FlightData>>, tonic::Status> {
+        let batch = self
+            .server
+            .execute_ddl(
+                &namespace_name,
+                statement,
+                span_ctx.child_span("execute DDL"),
+            )
+            .await
+            .context(DdlSnafu {
+                namespace_name: &namespace_name,
+                query: query.to_string(),
+            })?;
+
+        let output = FlightDataEncoderBuilder::new()
+            .build(futures::stream::once(async move { Ok(batch) }))
+            .map_err(tonic::Status::from);
+
+        Ok(Response::new(Box::pin(output) as TonicStream<FlightData>))
+    }
+
+    /// Plan the schema of the response to the FlightSQL `cmd`, shared by the
+    /// `GetFlightInfo` and `GetSchema` methods.
+    async fn flightsql_schema(
+        &self,
+        span_ctx: Option<SpanContext>,
+        namespace_name: &str,
+        cmd: FlightSQLCommand,
+        is_debug: bool,
+    ) -> Result<SchemaRef> {
+        let db = self
+            .server
+            .db(
+                namespace_name,
+                span_ctx.child_span("get namespace"),
+                is_debug,
+            )
+            .await
+            .context(DatabaseNotFoundSnafu { namespace_name })?;
+
+        let ctx = db.new_query_context(span_ctx);
+        Planner::new(&ctx)
+            .flight_sql_get_flight_info_schema(namespace_name, cmd.clone())
+            .await
+            .context(PlanningSnafu {
+                namespace_name,
+                query: format!("{cmd:?}"),
+            })
+    }
 }
 
 #[tonic::async_trait]
@@ -599,13 +726,47 @@ where
     type ListActionsStream = TonicStream<ActionType>;
     type DoExchangeStream = TonicStream<FlightData>;
 
+    /// Handles `GetSchema` RPC requests, returning the schema of the response
+    /// to the FlightSQL command in the [`FlightDescriptor`] without planning a
+    /// ticket for it.
     async fn get_schema(
         &self,
-        _request: Request<FlightDescriptor>,
+        request: Request<FlightDescriptor>,
     ) -> Result<Response<SchemaResult>, tonic::Status> {
-        Err(tonic::Status::unimplemented(
-            "Not yet implemented: get_schema",
-        ))
+        let external_span_ctx: Option<RequestLogContext> = request.extensions().get().cloned();
+        let span_ctx: Option<SpanContext> = request.extensions().get().cloned();
+        let trace = external_span_ctx.format_jaeger();
+        let is_debug = has_debug_header(request.metadata());
+
+        let namespace_name = get_flightsql_namespace(request.metadata())?;
+        let authz_token = get_flight_authz(request.metadata());
+
+        // extract the FlightSQL message
+        let cmd = cmd_from_descriptor(request.into_inner())?;
+        info!(%namespace_name, %cmd, %trace, "GetSchema request");
+
+        let perms = flightsql_permissions(&namespace_name, &cmd);
+        self.authz
+            .permissions(authz_token, &perms)
+            .await
+            .map_err(Error::from)?;
+
+        let schema = self
+            .flightsql_schema(span_ctx, &namespace_name, cmd.clone(), is_debug)
+            .await;
+
+        if let Err(e) = &schema {
+            info!(%namespace_name, %cmd, %trace, %e, "Error running GetSchema");
+        } else {
+            debug!(%namespace_name, %cmd, %trace, "Completed GetSchema request");
+        };
+        let schema = schema?;
+
+        let schema: SchemaResult = SchemaAsIpc::new(schema.as_ref(), &Default::default())
+            .try_into()
+            .context(EncodeSchemaSnafu)?;
+
+        Ok(tonic::Response::new(schema))
     }
 
     async fn do_get(
@@ -616,6 +777,8 @@ where
         let span_ctx: Option<SpanContext> = request.extensions().get().cloned();
         let authz_token = get_flight_authz(request.metadata());
         let mut is_debug = has_debug_header(request.metadata());
+        let settings =
+            QuerySettings::from_metadata(request.metadata()).context(InvalidSettingsSnafu)?;
         let ticket = request.into_inner();
 
         // attempt to decode ticket
@@ -630,17 +793,55 @@ where
         let query = request.query();
         is_debug |= request.is_debug();
 
-        let perms = match query {
-            RunQuery::FlightSQL(cmd) => flightsql_permissions(namespace_name, cmd),
-            RunQuery::Sql(_) | RunQuery::InfluxQL(_) => vec![authz::Permission::ResourceAction(
-                authz::Resource::Database(namespace_name.to_string()),
-                authz::Action::Read,
-            )],
+        // SQL tickets may contain a DDL statement to be applied to the
+        // catalog rather than a query.
+        let ddl = match query {
+            RunQuery::Sql(sql) => DdlStatement::parse(sql).transpose().context(DdlSnafu {
+                namespace_name,
+                query: query.to_string(),
+            })?,
+            RunQuery::InfluxQL(_) | RunQuery::FlightSQL(_) => None,
+        };
+
+        let perms = match (query, &ddl) {
+            (_, Some(statement)) => ddl_permissions(namespace_name, statement),
+            (RunQuery::FlightSQL(cmd), None) => flightsql_permissions(namespace_name, cmd),
+            (RunQuery::Sql(_) | RunQuery::InfluxQL(_), None) => {
+                vec![authz::Permission::ResourceAction(
+                    authz::Resource::Database(namespace_name.to_string()),
+                    authz::Action::Read,
+                )]
+            }
         };
         self.authz
-            .permissions(authz_token, &perms)
+            .permissions(authz_token.clone(), &perms)
+            .await
+            .map_err(Error::from)?;
+
+        if let Some(statement) = ddl {
+            info!(
+                %namespace_name,
+                %statement,
+                trace=external_span_ctx.format_jaeger().as_str(),
+                "DoGet DDL request",
+            );
+            return self
+                .run_ddl(span_ctx, statement, namespace_name.to_string(), query)
+                .await;
+        }
+
+        // Restrict the rows the query may read to those the token is limited
+        // to, if any.
+        let row_filters = self
+            .authz
+            .row_filters(authz_token, namespace_name)
             .await
             .map_err(Error::from)?;
+        let row_filters = RowFilters::new(
+            row_filters
+                .into_iter()
+                .map(|f| (f.column().to_string(), f.value().to_string())),
+        );
 
         let permit = self
             .server
@@ -665,6 +866,8 @@ where
                 query.clone(),
                 namespace_name.to_string(),
                 is_debug,
+                row_filters,
+                settings,
             )
             .await;
 
@@ -766,26 +969,9 @@ where
             .await
             .map_err(Error::from)?;
 
-        let db = self
-            .server
-            .db(
-                &namespace_name,
-                span_ctx.child_span("get namespace"),
-                is_debug,
-            )
-            .await
-            .context(DatabaseNotFoundSnafu {
-                namespace_name: &namespace_name,
-            })?;
-
-        let ctx = db.new_query_context(span_ctx);
-        let schema = Planner::new(&ctx)
-            .flight_sql_get_flight_info_schema(&namespace_name, cmd.clone())
-            .await
-            .context(PlanningSnafu {
-                namespace_name: &namespace_name,
-                query: format!("{cmd:?}"),
-            });
+        let schema = self
+            .flightsql_schema(span_ctx, &namespace_name, cmd.clone(), is_debug)
+            .await;
 
         if let Err(e) = &schema {
             info!(%namespace_name, %cmd, %trace, %e, "Error running GetFlightInfo");
@@ -874,13 +1060,23 @@ where
         Ok(Response::new(stream.boxed()))
     }
 
+    /// Handles `ListActions` RPC requests, returning the FlightSQL actions
+    /// supported by [`Self::do_action()`].
     async fn list_actions(
         &self,
         _request: Request<Empty>,
     ) -> Result<Response<Self::ListActionsStream>, tonic::Status> {
-        Err(tonic::Status::unimplemented(
-            "Not yet implemented: list_actions",
-        ))
+        let actions = FLIGHTSQL_ACTIONS
+            .iter()
+            .map(|(r#type, description)| {
+                Ok(ActionType {
+                    r#type: r#type.to_string(),
+                    description: description.to_string(),
+                })
+            })
+            .collect::<Vec<_>>();
+
+        Ok(Response::new(futures::stream::iter(actions).boxed()))
     }
 
     async fn do_exchange(
@@ -972,6 +1168,23 @@ fn flightsql_permissions(namespace_name: &str, cmd: &FlightSQLCommand) -> Vec<au
     vec![authz::Permission::ResourceAction(resource, action)]
 }
 
+/// Return the permissions required to execute the DDL `statement` received
+/// for the namespace `namespace_name`.
+///
+/// Namespace statements require permission on the namespace they name, while
+/// table statements require permission on the namespace of the request.
+fn ddl_permissions(namespace_name: &str, statement: &DdlStatement) -> Vec<authz::Permission> {
+    let (resource, action) = match statement {
+        DdlStatement::CreateNamespace { name, .. } => (name.as_str(), authz::Action::Create),
+        DdlStatement::AlterNamespaceRetention { name, .. } => (name.as_str(), authz::Action::Write),
+        DdlStatement::DropTable { .. } => (namespace_name, authz::Action::Delete),
+    };
+    vec![authz::Permission::ResourceAction(
+        authz::Resource::Database(resource.to_string()),
+        action,
+    )]
+}
+
 /// Check if request has IOx debug header set.
 fn has_debug_header(metadata: &MetadataMap) -> bool {
     metadata
@@ -989,6 +1202,17 @@ struct GetStream {
     #[allow(dead_code)]
     permit: InstrumentedAsyncOwnedSemaphorePermit,
     query_completed_token: QueryCompletedToken,
+
+    /// The executing plan, and the number of rows it returned so far, from
+    /// which the resource usage of the query is recorded on completion.
+    physical_plan: Arc<dyn ExecutionPlan>,
+    rows_returned: Arc<AtomicUsize>,
+
+    /// Resolves when the query is killed.
+    killed: Option<Pin<Box<WaitForCancellationFutureOwned>>>,
+
+    /// Resolves when the query timeout set by the request elapses.
+    deadline: Option<Pin<Box<tokio::time::Sleep>>>,
     done: bool,
 }
 
@@ -1000,11 +1224,13 @@ impl GetStream {
         query: &RunQuery,
         query_completed_token: QueryCompletedToken,
         permit: InstrumentedAsyncOwnedSemaphorePermit,
+        timeout: Option<Duration>,
     ) -> Result<Self, tonic::Status> {
         let app_metadata = proto::AppMetadata {};
 
         let schema = physical_plan.schema();
 
+        let rows_returned = Arc::new(AtomicUsize::new(0));
         let query_results = ctx
             .execute_stream(Arc::clone(&physical_plan))
             .await
@@ -1012,6 +1238,12 @@ impl GetStream {
                 namespace_name: namespace_name.clone(),
                 query: query.to_string(),
             })?
+            .inspect_ok({
+                let rows_returned = Arc::clone(&rows_returned);
+                move |batch| {
+                    rows_returned.fetch_add(batch.num_rows(), Ordering::Relaxed);
+                }
+            })
             .map_err(|e| {
                 let code = datafusion_error_to_tonic_code(&e);
                 tonic::Status::new(code, e.to_string()).into()
@@ -1026,13 +1258,32 @@ impl GetStream {
         // add keep alive
         let inner = KeepAliveStream::new(inner, DO_GET_KEEP_ALIVE_INTERVAL);
 
+        let killed = query_completed_token
+            .cancellation()
+            .map(|token| Box::pin(token.clone().cancelled_owned()));
+        let deadline = timeout.map(|t| Box::pin(tokio::time::sleep(t)));
+
         Ok(Self {
             inner,
             permit,
             query_completed_token,
+            physical_plan,
+            rows_returned,
+            killed,
+            deadline,
             done: false,
         })
     }
+
+    /// Record the resources used by the query so far in the query completed
+    /// token.
+    fn record_usage(&mut self) {
+        let usage = QueryUsage::from_plan(
+            self.physical_plan.as_ref(),
+            self.rows_returned.load(Ordering::Relaxed),
+        );
+        self.query_completed_token.set_usage(usage);
+    }
 }
 
 impl Stream for GetStream {
@@ -1047,11 +1298,28 @@ impl Stream for GetStream {
                 return Poll::Ready(None);
             }
 
+            if let Some(killed) = self.killed.as_mut() {
+                if killed.as_mut().poll(cx).is_ready() {
+                    self.done = true;
+                    return Poll::Ready(Some(Err(tonic::Status::cancelled("query was killed"))));
+                }
+            }
+
+            if let Some(deadline) = self.deadline.as_mut() {
+                if deadline.as_mut().poll(cx).is_ready() {
+                    self.done = true;
+                    return Poll::Ready(Some(Err(tonic::Status::deadline_exceeded(
+                        "query timeout exceeded",
+                    ))));
+                }
+            }
+
             let res = ready!(self.inner.poll_next_unpin(cx));
             match res {
                 None => {
                     self.done = true;
                     // if we get here, all is good
+                    self.record_usage();
                     self.query_completed_token.set_success();
                 }
                 Some(Ok(data)) => {
@@ -1059,6 +1327,7 @@ impl Stream for GetStream {
                 }
                 Some(Err(e)) => {
                     self.done = true;
+                    self.record_usage();
                     return Poll::Ready(Some(Err(e.into())));
                 }
             }
@@ -1070,7 +1339,6 @@ mod tests {
     use arrow_flight::sql::ProstMessageExt;
     use async_trait::async_trait;
     use authz::Permission;
-    use futures::Future;
     use metric::{Attributes, Metric, U64Gauge};
     use service_common::test_util::TestDatabaseStore;
     use tokio::pin;
@@ -1366,6 +1634,69 @@ mod tests {
             flightsql_request("Bearer UGLY"),
         )
         .await;
+
+        fn ddl_request(
+            sql: &'static str,
+            authorization: &'static str,
+        ) -> tonic::Request<arrow_flight::Ticket> {
+            request(RunQuery::Sql(sql.to_string()), authorization)
+        }
+
+        // DDL statements are authorized before being executed, and the test
+        // store does not support them.
+        assert_code(
+            &svc,
+            tonic::Code::Unauthenticated,
+            ddl_request("CREATE NAMESPACE platanos", ""),
+        )
+        .await;
+        assert_code(
+            &svc,
+            tonic::Code::PermissionDenied,
+            ddl_request("CREATE NAMESPACE platanos", "Bearer BAD"),
+        )
+        .await;
+        assert_code(
+            &svc,
+            tonic::Code::Unimplemented,
+            ddl_request("CREATE NAMESPACE platanos", "Bearer GOOD"),
+        )
+        .await;
+
+        // Invalid DDL statements are rejected before authorization.
+        assert_code(
+            &svc,
+            tonic::Code::InvalidArgument,
+            ddl_request("CREATE NAMESPACE platanos RETENTION 1d", ""),
+        )
+        .await;
+    }
+
+    #[test]
+    fn test_ddl_permissions() {
+        let parse = |sql: &str| DdlStatement::parse(sql).unwrap().unwrap();
+        let perm = |name: &str, action| {
+            vec![Permission::ResourceAction(
+                authz::Resource::Database(name.to_string()),
+                action,
+            )]
+        };
+
+        assert_eq!(
+            ddl_permissions("bananas", &parse("CREATE NAMESPACE platanos")),
+            perm("platanos", authz::Action::Create)
+        );
+        assert_eq!(
+            ddl_permissions(
+                "bananas",
+                &parse("ALTER NAMESPACE platanos SET RETENTION '1d'")
+            ),
+            perm("platanos", authz::Action::Write)
+        );
+        assert_eq!(
+            ddl_permissions("bananas", &parse("DROP TABLE cpu")),
+            perm("bananas", authz::Action::Delete)
+        );
     }
 
     #[tokio::test]
@@ -1412,4 +1743,69 @@ mod tests {
         assert_code(&svc, tonic::Code::PermissionDenied, request("Bearer BAD")).await;
         assert_code(&svc, tonic::Code::Internal, request("Bearer UGLY")).await;
     }
+
+    #[tokio::test]
+    async fn get_schema() {
+        let test_storage = Arc::new(TestDatabaseStore::default());
+        test_storage.db_or_create("bananas").await;
+
+        let svc = FlightService {
+            server: Arc::clone(&test_storage),
+            authz: Some(Arc::new(MockAuthorizer {})),
+        };
+
+        fn request(authorization: &'static str) -> tonic::Request<FlightDescriptor> {
+            let cmd = arrow_flight::sql::CommandGetCatalogs {};
+            let mut req =
+                tonic::Request::new(FlightDescriptor::new_cmd(cmd.as_any().encode_to_vec()));
+            req.metadata_mut().insert(
+                MetadataKey::from_static("database"),
+                MetadataValue::from_static("bananas"),
+            );
+            req.metadata_mut().insert(
+                MetadataKey::from_static("authorization"),
+                MetadataValue::from_static(authorization),
+            );
+            req
+        }
+
+        let got = svc
+            .get_schema(request("Bearer GOOD"))
+            .await
+            .expect("get_schema succeeds")
+            .into_inner();
+        let got = arrow::datatypes::Schema::try_from(&got).expect("valid schema");
+        assert_eq!(
+            got,
+            *arrow_flight::sql::CommandGetCatalogs {}
+                .into_builder()
+                .schema()
+        );
+
+        let err = svc
+            .get_schema(request("Bearer BAD"))
+            .await
+            .expect_err("unauthorised");
+        assert_eq!(err.code(), tonic::Code::PermissionDenied);
+    }
+
+    #[tokio::test]
+    async fn list_actions() {
+        let svc = FlightService {
+            server: Arc::new(TestDatabaseStore::default()),
+            authz: Option::<Arc<dyn Authorizer>>::None,
+        };
+
+        let got = svc
+            .list_actions(tonic::Request::new(Empty {}))
+            .await
+            .expect("list_actions succeeds")
+            .into_inner()
+            .map_ok(|a| a.r#type)
+            .try_collect::<Vec<_>>()
+            .await
+            .expect("stream succeeds");
+
+        assert_eq!(got, ["CreatePreparedStatement", "ClosePreparedStatement"]);
+    }
 }
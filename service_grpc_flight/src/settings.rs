@@ -0,0 +1,311 @@
+//! Per-request query settings supplied as gRPC metadata.
+//!
+//! gRPC has no notion of a session, but clients may attach the same headers
+//! to every request sent over a channel (for example with the `--header`
+//! option of the `influxdb_iox` CLI), applying these settings to all the
+//! queries of a connection without repeating them in each query.
+
+use std::{fmt::Display, str::FromStr, sync::Arc, time::Duration};
+
+use arrow::{
+    array::{timezone::Tz, StringArray},
+    record_batch::RecordBatch,
+};
+use datafusion::{
+    error::DataFusionError,
+    physical_plan::{
+        coalesce_partitions::CoalescePartitionsExec, explain::ExplainExec, limit::GlobalLimitExec,
+        memory::MemoryExec, ExecutionPlan,
+    },
+};
+use iox_query::exec::IOxSessionContext;
+use snafu::{ResultExt, Snafu};
+use tonic::metadata::MetadataMap;
+
+/// The header setting the time zone of the query, as accepted by the
+/// `datafusion.execution.time_zone` option (for example `+02:00`).
+pub(crate) const TIME_ZONE_HEADER: &str = "iox-query-time-zone";
+
+/// The header limiting the number of rows returned by the query.
+pub(crate) const ROW_LIMIT_HEADER: &str = "iox-query-row-limit";
+
+/// The header setting the maximum duration of the query, in
+/// [humantime](https://docs.rs/humantime) format (for example `30s`).
+pub(crate) const TIMEOUT_HEADER: &str = "iox-query-timeout";
+
+/// The DataFusion config option set by [`TIME_ZONE_HEADER`].
+const TIME_ZONE_OPTION: &str = "datafusion.execution.time_zone";
+
+/// The `plan_type` of the row describing the settings in the output of an
+/// `EXPLAIN`.
+const EXPLAIN_PLAN_TYPE: &str = "query_settings";
+
+#[derive(Debug, Snafu)]
+#[snafu(visibility(pub(crate)))]
+#[allow(missing_docs)]
+pub enum Error {
+    #[snafu(display("Invalid '{}' header: {}", header, source))]
+    NotAscii {
+        header: &'static str,
+        source: tonic::metadata::errors::ToStrError,
+    },
+
+    #[snafu(display("Invalid '{}' header: {}", ROW_LIMIT_HEADER, source))]
+    InvalidRowLimit { source: std::num::ParseIntError },
+
+    #[snafu(display("Invalid '{}' header: {}", TIMEOUT_HEADER, source))]
+    InvalidTimeout { source: humantime::DurationError },
+
+    #[snafu(display("Invalid '{}' header: {}", TIME_ZONE_HEADER, source))]
+    InvalidTimeZone { source: arrow::error::ArrowError },
+
+    #[snafu(display("Failed to set query option '{}': {}", option, source))]
+    SetOption {
+        option: &'static str,
+        source: DataFusionError,
+    },
+}
+
+/// The query settings of a `DoGet` request.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub(crate) struct QuerySettings {
+    /// The time zone of the query.
+    pub(crate) time_zone: Option<String>,
+
+    /// The maximum number of rows returned.
+    pub(crate) row_limit: Option<usize>,
+
+    /// The maximum duration of the query execution.
+    pub(crate) timeout: Option<Duration>,
+}
+
+impl QuerySettings {
+    /// Read the settings from the request `metadata`.
+    pub(crate) fn from_metadata(metadata: &MetadataMap) -> Result<Self, Error> {
+        let time_zone = header(metadata, TIME_ZONE_HEADER)?
+            .map(|v| Tz::from_str(v).map(|_| v.to_string()))
+            .transpose()
+            .context(InvalidTimeZoneSnafu)?;
+
+        let row_limit = header(metadata, ROW_LIMIT_HEADER)?
+            .map(|v| v.parse())
+            .transpose()
+            .context(InvalidRowLimitSnafu)?;
+
+        let timeout = header(metadata, TIMEOUT_HEADER)?
+            .map(humantime::parse_duration)
+            .transpose()
+            .context(InvalidTimeoutSnafu)?;
+
+        Ok(Self {
+            time_zone,
+            row_limit,
+            timeout,
+        })
+    }
+
+    /// Return the config options to apply to the query context.
+    pub(crate) fn config_options(&self) -> impl Iterator<Item = (&'static str, &str)> {
+        self.time_zone
+            .as_deref()
+            .map(|tz| (TIME_ZONE_OPTION, tz))
+            .into_iter()
+    }
+
+    /// Apply the config options to `ctx`.
+    pub(crate) async fn configure(&self, ctx: &IOxSessionContext) -> Result<(), Error> {
+        for (option, value) in self.config_options() {
+            ctx.set_config_option(option, value)
+                .await
+                .context(SetOptionSnafu { option })?;
+        }
+        Ok(())
+    }
+
+    /// Apply the settings to the physical `plan` of a query.
+    ///
+    /// The output of an `EXPLAIN` is not limited. Instead, if any settings
+    /// are set, a `query_settings` row describing them is appended to it.
+    pub(crate) async fn apply_to_plan(
+        &self,
+        plan: Arc<dyn ExecutionPlan>,
+        ctx: &IOxSessionContext,
+    ) -> Result<Arc<dyn ExecutionPlan>, DataFusionError> {
+        if !plan.as_any().is::<ExplainExec>() {
+            return Ok(self.limit_plan(plan));
+        }
+        if self == &Self::default() {
+            return Ok(plan);
+        }
+
+        let schema = plan.schema();
+        let mut batches = ctx.collect(plan).await?;
+        batches.push(RecordBatch::try_new(
+            Arc::clone(&schema),
+            vec![
+                Arc::new(StringArray::from(vec![EXPLAIN_PLAN_TYPE])),
+                Arc::new(StringArray::from(vec![self.to_string()])),
+            ],
+        )?);
+        Ok(Arc::new(MemoryExec::try_new(&[batches], schema, None)?))
+    }
+
+    /// Limit the rows produced by `plan` to the configured row limit, if
+    /// any.
+    fn limit_plan(&self, plan: Arc<dyn ExecutionPlan>) -> Arc<dyn ExecutionPlan> {
+        let Some(limit) = self.row_limit else {
+            return plan;
+        };
+
+        let plan = match plan.output_partitioning().partition_count() {
+            1 => plan,
+            _ => Arc::new(CoalescePartitionsExec::new(plan)),
+        };
+        Arc::new(GlobalLimitExec::new(plan, 0, Some(limit)))
+    }
+}
+
+impl Display for QuerySettings {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut settings = vec![];
+        if let Some(v) = &self.time_zone {
+            settings.push(format!("time_zone={v}"));
+        }
+        if let Some(v) = self.row_limit {
+            settings.push(format!("row_limit={v}"));
+        }
+        if let Some(v) = self.timeout {
+            settings.push(format!("timeout={}", humantime::format_duration(v)));
+        }
+        write!(f, "{}", settings.join(", "))
+    }
+}
+
+/// Return the trimmed value of the header `name`, if present.
+fn header<'a>(metadata: &'a MetadataMap, name: &'static str) -> Result<Option<&'a str>, Error> {
+    metadata
+        .get(name)
+        .map(|v| v.to_str().map(str::trim))
+        .transpose()
+        .context(NotAsciiSnafu { header: name })
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow::{datatypes::Schema, util::pretty::pretty_format_batches};
+    use assert_matches::assert_matches;
+    use datafusion::{
+        logical_expr::{LogicalPlan, PlanType, StringifiedPlan},
+        physical_plan::{displayable, empty::EmptyExec},
+    };
+    use iox_query::exec::{Executor, ExecutorType};
+
+    use super::*;
+
+    fn metadata(headers: &[(&'static str, &str)]) -> MetadataMap {
+        let mut metadata = MetadataMap::new();
+        for (k, v) in headers {
+            metadata.insert(*k, v.parse().unwrap());
+        }
+        metadata
+    }
+
+    #[test]
+    fn test_from_metadata() {
+        assert_eq!(
+            QuerySettings::from_metadata(&metadata(&[])).unwrap(),
+            QuerySettings::default()
+        );
+
+        let settings = QuerySettings::from_metadata(&metadata(&[
+            (TIME_ZONE_HEADER, "+02:00"),
+            (ROW_LIMIT_HEADER, " 10 "),
+            (TIMEOUT_HEADER, "1m 30s"),
+        ]))
+        .unwrap();
+        assert_eq!(
+            settings,
+            QuerySettings {
+                time_zone: Some("+02:00".to_string()),
+                row_limit: Some(10),
+                timeout: Some(Duration::from_secs(90)),
+            }
+        );
+        assert_eq!(
+            settings.config_options().collect::<Vec<_>>(),
+            [(TIME_ZONE_OPTION, "+02:00")]
+        );
+
+        assert_matches!(
+            QuerySettings::from_metadata(&metadata(&[(ROW_LIMIT_HEADER, "-1")])),
+            Err(Error::InvalidRowLimit { .. })
+        );
+        assert_matches!(
+            QuerySettings::from_metadata(&metadata(&[(TIMEOUT_HEADER, "bananas")])),
+            Err(Error::InvalidTimeout { .. })
+        );
+        assert_matches!(
+            QuerySettings::from_metadata(&metadata(&[(TIME_ZONE_HEADER, "bananas")])),
+            Err(Error::InvalidTimeZone { .. })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_explain() {
+        let exec = Executor::new_testing();
+        let ctx = exec.new_context(ExecutorType::Query);
+        let schema = LogicalPlan::explain_schema();
+        let plan: Arc<dyn ExecutionPlan> = Arc::new(ExplainExec::new(
+            Arc::clone(&schema),
+            vec![StringifiedPlan::new(
+                PlanType::FinalPhysicalPlan,
+                "EmptyExec",
+            )],
+            false,
+        ));
+
+        // Without settings the EXPLAIN is unchanged.
+        let unchanged = QuerySettings::default()
+            .apply_to_plan(Arc::clone(&plan), &ctx)
+            .await
+            .unwrap();
+        assert!(Arc::ptr_eq(&unchanged, &plan));
+
+        // The settings are echoed, and the output is not limited by them.
+        let settings = QuerySettings {
+            time_zone: Some("+02:00".to_string()),
+            row_limit: Some(1),
+            timeout: Some(Duration::from_secs(90)),
+        };
+        let explained = settings.apply_to_plan(plan, &ctx).await.unwrap();
+        let batches = ctx.collect(explained).await.unwrap();
+        let output = pretty_format_batches(&batches).unwrap().to_string();
+        assert!(output.contains("| physical_plan  | EmptyExec"), "{output}");
+        assert!(
+            output.contains("| query_settings | time_zone=+02:00, row_limit=1, timeout=1m 30s |"),
+            "{output}"
+        );
+
+        exec.join().await;
+    }
+
+    #[test]
+    fn test_limit_plan() {
+        let plan: Arc<dyn ExecutionPlan> =
+            Arc::new(EmptyExec::new(false, Arc::new(Schema::empty())));
+
+        let unlimited = QuerySettings::default().limit_plan(Arc::clone(&plan));
+        assert!(Arc::ptr_eq(&unlimited, &plan));
+
+        let limited = QuerySettings {
+            row_limit: Some(3),
+            ..Default::default()
+        }
+        .limit_plan(plan);
+        let display = displayable(limited.as_ref()).one_line().to_string();
+        assert!(
+            display.starts_with("GlobalLimitExec: skip=0, fetch=3"),
+            "{display}"
+        );
+    }
+}
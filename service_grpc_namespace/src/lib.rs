@@ -242,6 +242,125 @@ impl namespace_service_server::NamespaceService for NamespaceService {
             },
         ))
     }
+
+    async fn update_namespace_read_only(
+        &self,
+        request: Request<UpdateNamespaceReadOnlyRequest>,
+    ) -> Result<Response<UpdateNamespaceReadOnlyResponse>, Status> {
+        let mut repos = self.catalog.repositories().await;
+
+        let UpdateNamespaceReadOnlyRequest {
+            name: namespace_name,
+            read_only,
+        } = request.into_inner();
+
+        debug!(%namespace_name, read_only, "updating namespace read-only flag");
+
+        let namespace = repos
+            .namespaces()
+            .update_read_only(&namespace_name, read_only)
+            .await
+            .map_err(|e| {
+                warn!(
+                    error = %e,
+                    %namespace_name,
+                    read_only,
+                    "failed to update namespace read-only flag",
+                );
+                status_from_catalog_namespace_error(e)
+            })?;
+
+        info!(
+            %namespace_name,
+            namespace_id = %namespace.id,
+            read_only = namespace.read_only,
+            "updated namespace read-only flag",
+        );
+
+        Ok(Response::new(UpdateNamespaceReadOnlyResponse {
+            namespace: Some(namespace_to_proto(&namespace)),
+        }))
+    }
+
+    async fn update_namespace_object_store(
+        &self,
+        request: Request<UpdateNamespaceObjectStoreRequest>,
+    ) -> Result<Response<UpdateNamespaceObjectStoreResponse>, Status> {
+        let mut repos = self.catalog.repositories().await;
+
+        let UpdateNamespaceObjectStoreRequest {
+            name: namespace_name,
+            object_store_name,
+        } = request.into_inner();
+
+        if object_store_name.as_deref() == Some("") {
+            return Err(Status::invalid_argument(
+                "object store name must not be empty",
+            ));
+        }
+
+        debug!(%namespace_name, ?object_store_name, "updating namespace object store");
+
+        // Existing parquet files are never moved between object stores, so
+        // the store of a namespace can only change while it has no files.
+        let current = repos
+            .namespaces()
+            .get_by_name(&namespace_name, SoftDeletedRows::ExcludeDeleted)
+            .await
+            .map_err(status_from_catalog_namespace_error)?
+            .ok_or_else(|| {
+                status_from_catalog_namespace_error(
+                    iox_catalog::interface::Error::NamespaceNotFoundByName {
+                        name: namespace_name.clone(),
+                    },
+                )
+            })?;
+        if current.object_store_name != object_store_name {
+            let files = repos
+                .parquet_files()
+                .list_by_namespace_not_to_delete(current.id)
+                .await
+                .map_err(|e| Status::internal(e.to_string()))?;
+            if !files.is_empty() {
+                warn!(
+                    %namespace_name,
+                    namespace_id = %current.id,
+                    n_files = files.len(),
+                    "refusing to change the object store of a namespace with parquet files",
+                );
+                return Err(Status::failed_precondition(format!(
+                    "cannot change the object store of namespace {namespace_name} \
+                    with {} existing parquet files",
+                    files.len()
+                )));
+            }
+        }
+
+        let namespace = repos
+            .namespaces()
+            .update_object_store_name(&namespace_name, object_store_name.as_deref())
+            .await
+            .map_err(|e| {
+                warn!(
+                    error = %e,
+                    %namespace_name,
+                    ?object_store_name,
+                    "failed to update namespace object store",
+                );
+                status_from_catalog_namespace_error(e)
+            })?;
+
+        info!(
+            %namespace_name,
+            namespace_id = %namespace.id,
+            object_store_name = ?namespace.object_store_name,
+            "updated namespace object store",
+        );
+
+        Ok(Response::new(UpdateNamespaceObjectStoreResponse {
+            namespace: Some(namespace_to_proto(&namespace)),
+        }))
+    }
 }
 
 /// Convert the namespace record from the catalog into its protobuf representation.
@@ -253,6 +372,8 @@ pub fn namespace_to_proto(namespace: &CatalogNamespace) -> Namespace {
         max_tables: namespace.max_tables.get(),
         max_columns_per_table: namespace.max_columns_per_table.get(),
         partition_template: namespace.partition_template.as_proto().cloned(),
+        read_only: namespace.read_only,
+        object_store_name: namespace.object_store_name.clone(),
     }
 }
 
@@ -295,7 +416,10 @@ mod tests {
         },
         partition_template::v1::PartitionTemplate,
     };
-    use iox_catalog::mem::MemCatalog;
+    use iox_catalog::{
+        mem::MemCatalog,
+        test_helpers::{arbitrary_namespace, arbitrary_parquet_file_params, arbitrary_table},
+    };
     use tonic::Code;
 
     use super::*;
@@ -462,6 +586,50 @@ mod tests {
         assert_eq!(updated_ns.max_tables, want_max_tables);
         assert_eq!(updated_ns.max_columns_per_table, want_max_columns_per_table);
 
+        // Mark the namespace as read-only, and then writable again
+        assert!(!created_ns.read_only);
+        for want in [true, false] {
+            let updated_ns = handler
+                .update_namespace_read_only(Request::new(UpdateNamespaceReadOnlyRequest {
+                    name: NS_NAME.to_string(),
+                    read_only: want,
+                }))
+                .await
+                .expect("failed to update namespace")
+                .into_inner()
+                .namespace
+                .expect("no namespace in response");
+            assert_eq!(updated_ns.id, created_ns.id);
+            assert_eq!(updated_ns.read_only, want);
+        }
+
+        // Route the namespace to a different object store, and then back to
+        // the default
+        assert!(created_ns.object_store_name.is_none());
+        for want in [Some("tenant-a".to_string()), None] {
+            let updated_ns = handler
+                .update_namespace_object_store(Request::new(UpdateNamespaceObjectStoreRequest {
+                    name: NS_NAME.to_string(),
+                    object_store_name: want.clone(),
+                }))
+                .await
+                .expect("failed to update namespace")
+                .into_inner()
+                .namespace
+                .expect("no namespace in response");
+            assert_eq!(updated_ns.id, created_ns.id);
+            assert_eq!(updated_ns.object_store_name, want);
+        }
+
+        let status = handler
+            .update_namespace_object_store(Request::new(UpdateNamespaceObjectStoreRequest {
+                name: NS_NAME.to_string(),
+                object_store_name: Some(String::new()),
+            }))
+            .await
+            .expect_err("empty object store name should be rejected");
+        assert_eq!(status.code(), Code::InvalidArgument);
+
         // Deleting the namespace should cause it to disappear
         handler
             .delete_namespace(Request::new(DeleteNamespaceRequest {
@@ -482,6 +650,63 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_update_object_store_with_files() {
+        let catalog: Arc<dyn Catalog> =
+            Arc::new(MemCatalog::new(Arc::new(metric::Registry::default())));
+        let handler = NamespaceService::new(Arc::clone(&catalog));
+
+        {
+            let mut repos = catalog.repositories().await;
+            let namespace = arbitrary_namespace(&mut *repos, NS_NAME).await;
+            let table = arbitrary_table(&mut *repos, "platanos", &namespace).await;
+            let partition = repos
+                .partitions()
+                .create_or_get("arán".into(), table.id)
+                .await
+                .unwrap();
+            repos
+                .parquet_files()
+                .create(arbitrary_parquet_file_params(
+                    &namespace, &table, &partition,
+                ))
+                .await
+                .unwrap();
+        }
+
+        // The files of the namespace would be stranded in the default store.
+        let status = handler
+            .update_namespace_object_store(Request::new(UpdateNamespaceObjectStoreRequest {
+                name: NS_NAME.to_string(),
+                object_store_name: Some("tenant-a".to_string()),
+            }))
+            .await
+            .expect_err("namespace with files should not change object store");
+        assert_eq!(status.code(), Code::FailedPrecondition);
+
+        // Setting the current object store is a no-op.
+        let ns = handler
+            .update_namespace_object_store(Request::new(UpdateNamespaceObjectStoreRequest {
+                name: NS_NAME.to_string(),
+                object_store_name: None,
+            }))
+            .await
+            .expect("unchanged object store should be accepted")
+            .into_inner()
+            .namespace
+            .expect("no namespace in response");
+        assert_eq!(ns.object_store_name, None);
+
+        let status = handler
+            .update_namespace_object_store(Request::new(UpdateNamespaceObjectStoreRequest {
+                name: "platanos".to_string(),
+                object_store_name: Some("tenant-a".to_string()),
+            }))
+            .await
+            .expect_err("unknown namespace should fail");
+        assert_eq!(status.code(), Code::NotFound);
+    }
+
     #[tokio::test]
     async fn creating_same_namespace_twice_fails() {
         let catalog: Arc<dyn Catalog> =
@@ -0,0 +1,218 @@
+//! Comparison of namespace schemas, used to check namespaces provisioned
+//! from a template namespace.
+
+use data_types::{ColumnType, NamespaceSchema};
+use generated_types::influxdata::iox::schema::v1::{
+    ColumnTypeConflict, CompareNamespaceSchemasResponse, MissingColumn,
+};
+
+/// A column of the template namespace missing from the target namespace.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct PlannedColumn {
+    pub(crate) table: String,
+    pub(crate) name: String,
+    pub(crate) column_type: ColumnType,
+}
+
+/// A column present in both namespaces with different types.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Conflict {
+    pub(crate) table: String,
+    pub(crate) name: String,
+    pub(crate) template_type: ColumnType,
+    pub(crate) target_type: ColumnType,
+}
+
+/// The changes required to make a target namespace schema compatible with a
+/// template namespace schema.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub(crate) struct MigrationPlan {
+    /// The tables missing from the target, ordered by name.
+    pub(crate) missing_tables: Vec<String>,
+
+    /// The columns missing from the target, including those of the missing
+    /// tables, ordered by table and column name.
+    pub(crate) missing_columns: Vec<PlannedColumn>,
+
+    /// The columns whose types conflict, ordered by table and column name.
+    pub(crate) conflicts: Vec<Conflict>,
+}
+
+impl MigrationPlan {
+    /// Compute the plan making `target` compatible with `template`.
+    ///
+    /// Tables and columns present only in `target` are ignored.
+    pub(crate) fn new(template: &NamespaceSchema, target: &NamespaceSchema) -> Self {
+        let mut plan = Self::default();
+
+        for (table_name, template_table) in &template.tables {
+            let target_table = target.tables.get(table_name);
+            if target_table.is_none() {
+                plan.missing_tables.push(table_name.clone());
+            }
+
+            for (column_name, template_column) in template_table.columns.iter() {
+                match target_table.and_then(|t| t.columns.get(column_name)) {
+                    None => plan.missing_columns.push(PlannedColumn {
+                        table: table_name.clone(),
+                        name: column_name.clone(),
+                        column_type: template_column.column_type,
+                    }),
+                    Some(target_column)
+                        if target_column.column_type != template_column.column_type =>
+                    {
+                        plan.conflicts.push(Conflict {
+                            table: table_name.clone(),
+                            name: column_name.clone(),
+                            template_type: template_column.column_type,
+                            target_type: target_column.column_type,
+                        })
+                    }
+                    Some(_) => {}
+                }
+            }
+        }
+
+        plan.missing_tables.sort();
+        plan.missing_columns
+            .sort_by(|a, b| (&a.table, &a.name).cmp(&(&b.table, &b.name)));
+        plan.conflicts
+            .sort_by(|a, b| (&a.table, &a.name).cmp(&(&b.table, &b.name)));
+
+        plan
+    }
+}
+
+impl From<MigrationPlan> for CompareNamespaceSchemasResponse {
+    fn from(plan: MigrationPlan) -> Self {
+        Self {
+            missing_tables: plan.missing_tables,
+            missing_columns: plan
+                .missing_columns
+                .into_iter()
+                .map(|c| MissingColumn {
+                    table: c.table,
+                    name: c.name,
+                    column_type: c.column_type as i32,
+                })
+                .collect(),
+            conflicts: plan
+                .conflicts
+                .into_iter()
+                .map(|c| ColumnTypeConflict {
+                    table: c.table,
+                    name: c.name,
+                    template_type: c.template_type as i32,
+                    target_type: c.target_type as i32,
+                })
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use data_types::{
+        Column, ColumnId, ColumnsByName, MaxColumnsPerTable, MaxTables, NamespaceId, TableId,
+        TableSchema,
+    };
+
+    use super::*;
+
+    fn schema(tables: &[(&str, &[(&str, ColumnType)])]) -> NamespaceSchema {
+        let mut next_id = 0;
+        NamespaceSchema {
+            id: NamespaceId::new(1),
+            tables: tables
+                .iter()
+                .map(|(name, columns)| {
+                    next_id += 1;
+                    let table_id = TableId::new(next_id);
+                    let columns = ColumnsByName::new(columns.iter().map(|(name, column_type)| {
+                        next_id += 1;
+                        Column {
+                            id: ColumnId::new(next_id),
+                            table_id,
+                            name: name.to_string(),
+                            column_type: *column_type,
+                        }
+                    }));
+                    (
+                        name.to_string(),
+                        TableSchema {
+                            id: table_id,
+                            partition_template: Default::default(),
+                            columns,
+                        },
+                    )
+                })
+                .collect(),
+            max_tables: MaxTables::default(),
+            max_columns_per_table: MaxColumnsPerTable::default(),
+            retention_period_ns: None,
+            partition_template: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_plan() {
+        let template = schema(&[
+            (
+                "cpu",
+                &[
+                    ("host", ColumnType::Tag),
+                    ("usage", ColumnType::F64),
+                    ("time", ColumnType::Time),
+                ],
+            ),
+            ("mem", &[("free", ColumnType::I64)]),
+        ]);
+        let target = schema(&[
+            (
+                "cpu",
+                &[
+                    ("host", ColumnType::Tag),
+                    ("usage", ColumnType::I64),
+                    ("extra", ColumnType::Bool),
+                ],
+            ),
+            ("disk", &[("used", ColumnType::I64)]),
+        ]);
+
+        let plan = MigrationPlan::new(&template, &target);
+        assert_eq!(
+            plan,
+            MigrationPlan {
+                missing_tables: vec!["mem".to_string()],
+                missing_columns: vec![
+                    PlannedColumn {
+                        table: "cpu".to_string(),
+                        name: "time".to_string(),
+                        column_type: ColumnType::Time,
+                    },
+                    PlannedColumn {
+                        table: "mem".to_string(),
+                        name: "free".to_string(),
+                        column_type: ColumnType::I64,
+                    },
+                ],
+                conflicts: vec![Conflict {
+                    table: "cpu".to_string(),
+                    name: "usage".to_string(),
+                    template_type: ColumnType::F64,
+                    target_type: ColumnType::I64,
+                }],
+            }
+        );
+
+        // A namespace is compatible with itself, and with any subset of it.
+        assert_eq!(
+            MigrationPlan::new(&template, &template),
+            MigrationPlan::default()
+        );
+        assert_eq!(
+            MigrationPlan::new(&schema(&[]), &template),
+            MigrationPlan::default()
+        );
+    }
+}
@@ -16,26 +16,126 @@
 // Workaround for "unused crate" lint false positives.
 use workspace_hack as _;
 
-use std::{ops::DerefMut, sync::Arc};
+mod compatibility;
 
-use generated_types::influxdata::iox::schema::v1::*;
+use std::{collections::BTreeMap, ops::DerefMut, sync::Arc};
+
+use data_types::{ColumnRetention, NamespaceId, TableId};
+use generated_types::influxdata::iox::{ingester::v1::BufferedColumn, schema::v1::*};
 use iox_catalog::interface::{
-    get_schema_by_name, get_schema_by_namespace_and_table, Catalog, SoftDeletedRows,
+    get_schema_by_name, get_schema_by_namespace_and_table, Catalog, RepoCollection, SoftDeletedRows,
 };
 use observability_deps::tracing::warn;
 use tonic::{Request, Response, Status};
 
+use crate::compatibility::MigrationPlan;
+
+/// A source of the columns present in data buffered by the ingesters, which
+/// may not yet be persisted.
+#[tonic::async_trait]
+pub trait BufferedColumnSource: std::fmt::Debug + Send + Sync {
+    /// Return the columns buffered for the specified table across all
+    /// ingesters.
+    ///
+    /// Implementations should return the columns of the ingesters that could
+    /// be reached, rather than failing the request.
+    async fn buffered_columns(
+        &self,
+        namespace_id: NamespaceId,
+        table_id: TableId,
+    ) -> Vec<BufferedColumn>;
+}
+
 /// Implementation of the gRPC schema service
 #[derive(Debug)]
 pub struct SchemaService {
     /// Catalog.
     catalog: Arc<dyn Catalog>,
+
+    /// An optional source of the columns buffered in the ingesters, merged
+    /// into the effective table schema.
+    buffered: Option<Arc<dyn BufferedColumnSource>>,
 }
 
 impl SchemaService {
     pub fn new(catalog: Arc<dyn Catalog>) -> Self {
-        Self { catalog }
+        Self {
+            catalog,
+            buffered: None,
+        }
+    }
+
+    /// Include the columns reported by `source` in effective table schema
+    /// responses.
+    pub fn with_buffered_column_source(mut self, source: Arc<dyn BufferedColumnSource>) -> Self {
+        self.buffered = Some(source);
+        self
+    }
+}
+
+/// Merge the catalog columns of `table` with the `buffered` columns, ordered
+/// by column name, annotating the catalog columns with their `retention`
+/// period.
+///
+/// The catalog is authoritative for the type of any column it contains.
+fn merge_columns(
+    table: &data_types::TableSchema,
+    retention: &[ColumnRetention],
+    buffered: Vec<BufferedColumn>,
+) -> Vec<EffectiveColumn> {
+    let mut columns = table
+        .columns
+        .iter()
+        .map(|(name, col)| {
+            (
+                name.clone(),
+                EffectiveColumn {
+                    name: name.clone(),
+                    column_type: col.column_type as i32,
+                    column_id: Some(col.id.get()),
+                    first_seen_sequence_number: None,
+                    retention_period_ns: retention
+                        .iter()
+                        .find(|r| r.column_id == col.id)
+                        .map(|r| r.retention_period_ns),
+                },
+            )
+        })
+        .collect::<BTreeMap<_, _>>();
+
+    for col in buffered {
+        let v = columns
+            .entry(col.name.clone())
+            .or_insert_with(|| EffectiveColumn {
+                name: col.name,
+                column_type: col.column_type,
+                column_id: None,
+                first_seen_sequence_number: None,
+                retention_period_ns: None,
+            });
+
+        v.first_seen_sequence_number = Some(
+            v.first_seen_sequence_number
+                .map_or(col.first_seen_sequence_number, |seq| {
+                    seq.min(col.first_seen_sequence_number)
+                }),
+        );
     }
+
+    columns.into_values().collect()
+}
+
+/// Fetch the schema of the namespace `name`.
+async fn namespace_schema(
+    name: &str,
+    repos: &mut dyn RepoCollection,
+) -> Result<data_types::NamespaceSchema, Status> {
+    get_schema_by_name(name, repos, SoftDeletedRows::ExcludeDeleted)
+        .await
+        .map_err(|e| {
+            warn!(error=%e, namespace=%name, "failed to retrieve namespace schema");
+            Status::not_found(e.to_string())
+        })
 }
 
 #[tonic::async_trait]
@@ -76,6 +176,64 @@ impl schema_service_server::SchemaService for SchemaService {
             schema: Some((&schema).into()),
         }))
     }
+
+    async fn get_effective_table_schema(
+        &self,
+        request: Request<GetEffectiveTableSchemaRequest>,
+    ) -> Result<Response<GetEffectiveTableSchemaResponse>, Status> {
+        let req = request.into_inner();
+
+        let mut repos = self.catalog.repositories().await;
+        let schema = get_schema_by_namespace_and_table(
+            &req.namespace,
+            &req.table,
+            repos.deref_mut(),
+            SoftDeletedRows::ExcludeDeleted,
+        )
+        .await
+        .map_err(|e| {
+            warn!(error=%e, %req.namespace, %req.table, "failed to retrieve table schema");
+            Status::not_found(e.to_string())
+        })?;
+
+        let table = schema
+            .tables
+            .get(&req.table)
+            .ok_or_else(|| Status::not_found(format!("table {} not found", req.table)))?;
+
+        let retention = repos
+            .column_retention()
+            .list_by_table_id(table.id)
+            .await
+            .map_err(|e| {
+                warn!(error=%e, %req.namespace, %req.table, "failed to retrieve column retention");
+                Status::internal(e.to_string())
+            })?;
+        drop(repos);
+
+        let buffered = match &self.buffered {
+            Some(source) => source.buffered_columns(schema.id, table.id).await,
+            None => vec![],
+        };
+
+        Ok(Response::new(GetEffectiveTableSchemaResponse {
+            table_id: table.id.get(),
+            columns: merge_columns(table, &retention, buffered),
+        }))
+    }
+
+    async fn compare_namespace_schemas(
+        &self,
+        request: Request<CompareNamespaceSchemasRequest>,
+    ) -> Result<Response<CompareNamespaceSchemasResponse>, Status> {
+        let req = request.into_inner();
+        let mut repos = self.catalog.repositories().await;
+
+        let template = namespace_schema(&req.template_namespace, repos.deref_mut()).await?;
+        let target = namespace_schema(&req.target_namespace, repos.deref_mut()).await?;
+
+        Ok(Response::new(MigrationPlan::new(&template, &target).into()))
+    }
 }
 
 #[cfg(test)]
@@ -158,6 +316,202 @@ mod tests {
         column_names
     }
 
+    #[derive(Debug)]
+    struct MockBufferedColumnSource(Vec<BufferedColumn>);
+
+    #[tonic::async_trait]
+    impl BufferedColumnSource for MockBufferedColumnSource {
+        async fn buffered_columns(
+            &self,
+            _namespace_id: NamespaceId,
+            _table_id: TableId,
+        ) -> Vec<BufferedColumn> {
+            self.0.clone()
+        }
+    }
+
+    #[tokio::test]
+    async fn get_effective_table_schema_works() {
+        let namespace = "namespace_effective_schema_test";
+        let table = "effective_schema_test_table";
+
+        let grpc = service_setup(|repos| {
+            async {
+                let namespace = arbitrary_namespace(&mut *repos, namespace).await;
+                let table = arbitrary_table(&mut *repos, table, &namespace).await;
+                repos
+                    .columns()
+                    .create_or_get("region", table.id, ColumnType::Tag)
+                    .await
+                    .unwrap();
+                let debug = repos
+                    .columns()
+                    .create_or_get("debug", table.id, ColumnType::String)
+                    .await
+                    .unwrap();
+                repos
+                    .column_retention()
+                    .upsert(&ColumnRetention {
+                        table_id: table.id,
+                        column_id: debug.id,
+                        retention_period_ns: 3_600_000_000_000,
+                    })
+                    .await
+                    .unwrap();
+            }
+            .boxed()
+        })
+        .await
+        .with_buffered_column_source(Arc::new(MockBufferedColumnSource(vec![
+            // Reported by two ingesters.
+            BufferedColumn {
+                name: "region".to_string(),
+                column_type: column_schema::ColumnType::Tag as i32,
+                first_seen_sequence_number: 4,
+            },
+            BufferedColumn {
+                name: "region".to_string(),
+                column_type: column_schema::ColumnType::Tag as i32,
+                first_seen_sequence_number: 2,
+            },
+            // Not yet in the catalog.
+            BufferedColumn {
+                name: "bananas".to_string(),
+                column_type: column_schema::ColumnType::F64 as i32,
+                first_seen_sequence_number: 7,
+            },
+        ])));
+
+        let response = grpc
+            .get_effective_table_schema(Request::new(GetEffectiveTableSchemaRequest {
+                namespace: namespace.to_string(),
+                table: table.to_string(),
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+
+        let got = response
+            .columns
+            .iter()
+            .map(|c| {
+                (
+                    c.name.as_str(),
+                    c.column_type,
+                    c.column_id.is_some(),
+                    c.first_seen_sequence_number,
+                    c.retention_period_ns,
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            got,
+            [
+                (
+                    "bananas",
+                    column_schema::ColumnType::F64 as i32,
+                    false,
+                    Some(7),
+                    None
+                ),
+                (
+                    "debug",
+                    column_schema::ColumnType::String as i32,
+                    true,
+                    None,
+                    Some(3_600_000_000_000)
+                ),
+                (
+                    "region",
+                    column_schema::ColumnType::Tag as i32,
+                    true,
+                    Some(2),
+                    None
+                ),
+            ]
+        );
+
+        // A nonexistent table fails
+        let status = grpc
+            .get_effective_table_schema(Request::new(GetEffectiveTableSchemaRequest {
+                namespace: namespace.to_string(),
+                table: "does_not_exist".to_string(),
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), Code::NotFound);
+    }
+
+    #[tokio::test]
+    async fn compare_namespace_schemas_works() {
+        let grpc = service_setup(|repos| {
+            async {
+                let template = arbitrary_namespace(&mut *repos, "template").await;
+                let cpu = arbitrary_table(&mut *repos, "cpu", &template).await;
+                repos
+                    .columns()
+                    .create_or_get("host", cpu.id, ColumnType::Tag)
+                    .await
+                    .unwrap();
+                repos
+                    .columns()
+                    .create_or_get("usage", cpu.id, ColumnType::F64)
+                    .await
+                    .unwrap();
+
+                let tenant = arbitrary_namespace(&mut *repos, "tenant").await;
+                let cpu = arbitrary_table(&mut *repos, "cpu", &tenant).await;
+                repos
+                    .columns()
+                    .create_or_get("host", cpu.id, ColumnType::Tag)
+                    .await
+                    .unwrap();
+
+                let conflicting = arbitrary_namespace(&mut *repos, "conflicting").await;
+                let cpu = arbitrary_table(&mut *repos, "cpu", &conflicting).await;
+                repos
+                    .columns()
+                    .create_or_get("usage", cpu.id, ColumnType::I64)
+                    .await
+                    .unwrap();
+            }
+            .boxed()
+        })
+        .await;
+
+        let compare = |target: &str| {
+            grpc.compare_namespace_schemas(Request::new(CompareNamespaceSchemasRequest {
+                template_namespace: "template".to_string(),
+                target_namespace: target.to_string(),
+            }))
+        };
+
+        let response = compare("tenant").await.unwrap().into_inner();
+        assert!(response.missing_tables.is_empty());
+        assert_eq!(
+            response
+                .missing_columns
+                .iter()
+                .map(|c| (c.table.as_str(), c.name.as_str()))
+                .collect::<Vec<_>>(),
+            [("cpu", "usage")]
+        );
+        assert!(response.conflicts.is_empty());
+
+        // The comparison does not modify the target namespace.
+        let schema = get_schema(&grpc, "tenant", None).await;
+        assert_eq!(sorted_column_names(&schema, "cpu"), ["host"]);
+
+        // Conflicting column types are reported.
+        let response = compare("conflicting").await.unwrap().into_inner();
+        assert_eq!(response.conflicts.len(), 1);
+        assert_eq!(response.conflicts[0].name, "usage");
+
+        // A missing namespace fails.
+        let status = compare("does_not_exist").await.unwrap_err();
+        assert_eq!(status.code(), Code::NotFound);
+    }
+
     #[tokio::test]
     async fn get_schema_works() {
         let namespace = "namespace_schema_test";